@@ -0,0 +1,68 @@
+/*
+ * genmcp: C interface to the genmcp client and proxy.
+ *
+ * Link against libgenmcp_ffi. Calls block until they finish; callbacks run
+ * on library threads and must not call back into the library. Strings
+ * returned by the library are freed with genmcp_string_free; strings passed
+ * to callbacks are only valid during the call. On failure a function
+ * returns GENMCP_ERROR or NULL and genmcp_last_error describes why.
+ */
+
+#ifndef GENMCP_H
+#define GENMCP_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define GENMCP_OK 0
+#define GENMCP_ERROR (-1)
+
+#define GENMCP_TO_BACKEND 0
+#define GENMCP_FROM_BACKEND 1
+
+typedef struct GenmcpClient GenmcpClient;
+typedef struct GenmcpProxy GenmcpProxy;
+
+typedef void (*GenmcpNotificationCallback)(const char *method, const char *params,
+                                           void *user_data);
+typedef void (*GenmcpMessageCallback)(const char *message, void *user_data);
+typedef void (*GenmcpCaptureCallback)(int direction, const char *message, void *user_data);
+
+/* Errors and memory */
+
+const char *genmcp_last_error(void);
+void genmcp_string_free(char *value);
+const char *genmcp_version(void);
+
+/* Client */
+
+GenmcpClient *genmcp_client_create(const char *config_json);
+GenmcpClient *genmcp_client_create_stdio(const char *command, const char *const *argv,
+                                         size_t argc);
+int genmcp_client_connect(GenmcpClient *client, const char *name, const char *version,
+                          char **server_info);
+char *genmcp_client_call(GenmcpClient *client, const char *method, const char *params_json);
+char *genmcp_client_call_tool(GenmcpClient *client, const char *name,
+                              const char *arguments_json);
+int genmcp_client_notify(GenmcpClient *client, const char *method, const char *params_json);
+int genmcp_client_on_notification(GenmcpClient *client, const char *method,
+                                  GenmcpNotificationCallback callback, void *user_data);
+int genmcp_client_disconnect(GenmcpClient *client);
+void genmcp_client_free(GenmcpClient *client);
+
+/* Proxy */
+
+GenmcpProxy *genmcp_proxy_start(const char *command, const char *const *argv, size_t argc,
+                                GenmcpMessageCallback on_message,
+                                GenmcpCaptureCallback on_capture, void *user_data);
+int genmcp_proxy_send(GenmcpProxy *proxy, const char *message);
+void genmcp_proxy_stop(GenmcpProxy *proxy);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GENMCP_H */
@@ -0,0 +1,379 @@
+//! Create, connect, call and disconnect a client.
+//!
+//! A [`GenmcpClient`] owns an [`McpClient`]. Calls on one handle may come
+//! from several threads; they are sent one at a time.
+
+use std::ffi::{c_char, c_int, c_void};
+
+use anyhow::{Context, Result};
+use mcp_core::client::{McpClient, McpClientBuilder};
+use mcp_core::messages::Implementation;
+use mcp_core::transport::{StdioConfig, TransportConfig};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::{
+    callback_string, guard, json_arg, opt_str_arg, owned_string, runtime, str_arg, str_array,
+    UserData, GENMCP_ERROR, GENMCP_OK,
+};
+
+/// Receives a notification: its method, its params as JSON (`"{}"` when it
+/// had none) and the `user_data` it was registered with.
+pub type GenmcpNotificationCallback =
+    extern "C" fn(method: *const c_char, params: *const c_char, user_data: *mut c_void);
+
+/// An MCP client, opaque to C
+pub struct GenmcpClient {
+    client: Mutex<McpClient>,
+}
+
+impl GenmcpClient {
+    fn build(config: TransportConfig) -> Result<*mut GenmcpClient> {
+        config.validate()?;
+        let client = runtime().block_on(McpClientBuilder::new().transport(config).build())?;
+        Ok(Box::into_raw(Box::new(GenmcpClient {
+            client: Mutex::new(client),
+        })))
+    }
+}
+
+/// The client behind a handle
+///
+/// # Safety
+///
+/// `client` must be null or a live handle from this module.
+unsafe fn handle<'a>(client: *mut GenmcpClient) -> Result<&'a GenmcpClient> {
+    client.as_ref().context("client must not be NULL")
+}
+
+/// Create a client from a transport configuration in JSON, as in a
+/// transport configuration file:
+/// `{"type": "http_stream", "base_url": "https://example.com/mcp", ...}`.
+///
+/// Returns `NULL` on failure. Nothing is started until
+/// [`genmcp_client_connect`].
+///
+/// # Safety
+///
+/// `config_json` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_create(config_json: *const c_char) -> *mut GenmcpClient {
+    guard(std::ptr::null_mut(), || {
+        let config: TransportConfig = serde_json::from_str(str_arg(config_json, "config_json")?)
+            .context("invalid transport configuration")?;
+        GenmcpClient::build(config)
+    })
+}
+
+/// Create a client that runs `command` with `argc` arguments and talks to
+/// it over stdio.
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `command` must be a NUL-terminated string and `argv` must point to `argc`
+/// of them (or be `NULL` with `argc` 0).
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_create_stdio(
+    command: *const c_char,
+    argv: *const *const c_char,
+    argc: usize,
+) -> *mut GenmcpClient {
+    guard(std::ptr::null_mut(), || {
+        let config =
+            StdioConfig::new(str_arg(command, "command")?).args(str_array(argv, argc, "argv")?);
+        GenmcpClient::build(TransportConfig::Stdio(config))
+    })
+}
+
+/// Connect and run the initialization handshake, introducing the host as
+/// `name` and `version`.
+///
+/// On success, stores the server's name, version, protocol version and
+/// capabilities as JSON in `*server_info` unless `server_info` is `NULL`;
+/// free it with [`genmcp_string_free`](crate::genmcp_string_free).
+///
+/// # Safety
+///
+/// `client` must be a live handle, `name` and `version` NUL-terminated
+/// strings, and `server_info` null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_connect(
+    client: *mut GenmcpClient,
+    name: *const c_char,
+    version: *const c_char,
+    server_info: *mut *mut c_char,
+) -> c_int {
+    guard(GENMCP_ERROR, || {
+        let client = handle(client)?;
+        let implementation =
+            Implementation::new(str_arg(name, "name")?, str_arg(version, "version")?);
+        let info = runtime()
+            .block_on(async { client.client.lock().await.connect(implementation).await })?;
+        if !server_info.is_null() {
+            let info = json!({
+                "name": info.implementation.name,
+                "version": info.implementation.version,
+                "protocol_version": info.protocol_version.to_string(),
+                "capabilities": info.capabilities,
+            });
+            *server_info = owned_string(info.to_string())?;
+        }
+        Ok(GENMCP_OK)
+    })
+}
+
+/// Send a request and wait for its result.
+///
+/// `params_json` may be `NULL` for no params. Returns the `result` as JSON,
+/// to be freed with [`genmcp_string_free`](crate::genmcp_string_free), or
+/// `NULL` on failure, including an error response from the server.
+///
+/// # Safety
+///
+/// `client` must be a live handle, `method` a NUL-terminated string and
+/// `params_json` null or one.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_call(
+    client: *mut GenmcpClient,
+    method: *const c_char,
+    params_json: *const c_char,
+) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        let client = handle(client)?;
+        let method = str_arg(method, "method")?;
+        let params = json_arg(params_json, "params_json")?.unwrap_or_else(|| json!({}));
+        let result = runtime().block_on(async {
+            let response = client
+                .client
+                .lock()
+                .await
+                .send_request(method, params)
+                .await?;
+            response.into_result()
+        })?;
+        owned_string(result.to_string())
+    })
+}
+
+/// Call the tool `name` with `arguments_json` (`NULL` for none) and return
+/// the `tools/call` result as JSON, like [`genmcp_client_call`].
+///
+/// A tool that reports an error still succeeds here; check `isError` in the
+/// result.
+///
+/// # Safety
+///
+/// As for [`genmcp_client_call`].
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_call_tool(
+    client: *mut GenmcpClient,
+    name: *const c_char,
+    arguments_json: *const c_char,
+) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        let mut params = json!({ "name": str_arg(name, "name")? });
+        if let Some(arguments) = json_arg(arguments_json, "arguments_json")? {
+            params["arguments"] = arguments;
+        }
+        let params = owned_string(params.to_string())?;
+        let result = genmcp_client_call(client, c"tools/call".as_ptr(), params);
+        crate::genmcp_string_free(params);
+        Ok(result)
+    })
+}
+
+/// Send a notification; `params_json` may be `NULL`.
+///
+/// # Safety
+///
+/// As for [`genmcp_client_call`].
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_notify(
+    client: *mut GenmcpClient,
+    method: *const c_char,
+    params_json: *const c_char,
+) -> c_int {
+    guard(GENMCP_ERROR, || {
+        let client = handle(client)?;
+        let method = str_arg(method, "method")?;
+        let params = json_arg(params_json, "params_json")?.unwrap_or_else(|| json!({}));
+        runtime().block_on(async {
+            client
+                .client
+                .lock()
+                .await
+                .send_notification(method, params)
+                .await
+        })?;
+        Ok(GENMCP_OK)
+    })
+}
+
+/// Call `callback` for every notification with `method`, or with `method`
+/// `NULL`, for every notification no other callback handles.
+///
+/// Register callbacks before [`genmcp_client_connect`] to see the
+/// notifications sent during the handshake. `user_data` is passed back
+/// untouched and must stay valid until the client is freed.
+///
+/// # Safety
+///
+/// `client` must be a live handle and `method` null or a NUL-terminated
+/// string. `callback` must be safe to call from any thread.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_on_notification(
+    client: *mut GenmcpClient,
+    method: *const c_char,
+    callback: GenmcpNotificationCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    guard(GENMCP_ERROR, || {
+        let client = handle(client)?;
+        let method = opt_str_arg(method, "method")?;
+        let user_data = UserData(user_data);
+        let deliver = move |notification: mcp_core::messages::JsonRpcNotification| {
+            let method = callback_string(notification.method);
+            let params = notification
+                .params
+                .unwrap_or_else(|| Value::Object(Default::default()));
+            let params = callback_string(params.to_string());
+            callback(method.as_ptr(), params.as_ptr(), user_data.get());
+            async { Ok(()) }
+        };
+        let notifications = runtime().block_on(client.client.lock()).notifications();
+        match method {
+            Some(method) => notifications.on_raw(method, deliver),
+            None => notifications.fallback(deliver),
+        };
+        Ok(GENMCP_OK)
+    })
+}
+
+/// Disconnect, stopping a stdio server. The handle stays valid and can
+/// connect again.
+///
+/// # Safety
+///
+/// `client` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_disconnect(client: *mut GenmcpClient) -> c_int {
+    guard(GENMCP_ERROR, || {
+        let client = handle(client)?;
+        runtime().block_on(async { client.client.lock().await.disconnect().await })?;
+        Ok(GENMCP_OK)
+    })
+}
+
+/// Disconnect if needed and free the handle. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `client` must be null or a live handle, not used again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_client_free(client: *mut GenmcpClient) {
+    if client.is_null() {
+        return;
+    }
+    guard((), || {
+        let client = Box::from_raw(client);
+        let mut inner = runtime().block_on(client.client.lock());
+        if runtime().block_on(inner.is_ready()) {
+            if let Err(e) = runtime().block_on(inner.disconnect()) {
+                tracing::debug!("Disconnect while freeing a client failed: {}", e);
+            }
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use std::sync::mpsc;
+
+    /// A stdio server that answers `tools/call` with its arguments and
+    /// announces each call with a custom notification
+    const ECHO_SERVER: &str = r#"while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":"\([^"]*\)".*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"echo","version":"1.0.0"}}}\n' "$id" ;;
+    *'"method":"tools/call"'*) printf '{"jsonrpc":"2.0","method":"notifications/called","params":{"tool":"echo"}}\n'; printf '{"jsonrpc":"2.0","id":"%s","result":{"content":[{"type":"text","text":"hi"}]}}\n' "$id" ;;
+    *'"method":"fail"'*) printf '{"jsonrpc":"2.0","id":"%s","error":{"code":-32601,"message":"no such method"}}\n' "$id" ;;
+    *'"id"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{}}\n' "$id" ;;
+  esac
+done"#;
+
+    extern "C" fn forward(method: *const c_char, params: *const c_char, user_data: *mut c_void) {
+        let sender = unsafe { &*(user_data as *const mpsc::Sender<(String, String)>) };
+        let text = |ptr| unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        sender.send((text(method), text(params))).unwrap();
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(crate::genmcp_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_connect_call_and_notify_through_the_c_api() {
+        let script = callback_string(ECHO_SERVER);
+        let argv = [c"-c".as_ptr(), script.as_ptr()];
+        let (sender, received) = mpsc::channel::<(String, String)>();
+        unsafe {
+            let client = genmcp_client_create_stdio(c"sh".as_ptr(), argv.as_ptr(), argv.len());
+            assert!(!client.is_null(), "{}", last_error());
+            let user_data = &sender as *const _ as *mut c_void;
+            let registered =
+                genmcp_client_on_notification(client, std::ptr::null(), forward, user_data);
+            assert_eq!(registered, GENMCP_OK);
+
+            let mut info = std::ptr::null_mut();
+            let connected =
+                genmcp_client_connect(client, c"host".as_ptr(), c"1.0".as_ptr(), &mut info);
+            assert_eq!(connected, GENMCP_OK, "{}", last_error());
+            let server: Value =
+                serde_json::from_str(CStr::from_ptr(info).to_str().unwrap()).unwrap();
+            assert_eq!(server["name"], "echo");
+            crate::genmcp_string_free(info);
+
+            let result =
+                genmcp_client_call_tool(client, c"echo".as_ptr(), c"{\"text\":\"hi\"}".as_ptr());
+            assert!(!result.is_null(), "{}", last_error());
+            let result: Value =
+                serde_json::from_str(CStr::from_ptr(result).to_str().unwrap()).unwrap();
+            assert_eq!(result["content"][0]["text"], "hi");
+
+            let (method, params) = received
+                .recv_timeout(std::time::Duration::from_secs(5))
+                .unwrap();
+            assert_eq!(method, "notifications/called");
+            assert_eq!(params, r#"{"tool":"echo"}"#);
+
+            assert!(genmcp_client_call(client, c"fail".as_ptr(), std::ptr::null()).is_null());
+            assert!(last_error().contains("no such method"), "{}", last_error());
+
+            assert_eq!(genmcp_client_disconnect(client), GENMCP_OK);
+            genmcp_client_free(client);
+        }
+    }
+
+    #[test]
+    fn test_invalid_configurations_are_reported() {
+        unsafe {
+            let client = genmcp_client_create(c"{\"type\":\"carrier_pigeon\"}".as_ptr());
+            assert!(client.is_null());
+            assert!(
+                last_error().starts_with("invalid transport configuration"),
+                "{}",
+                last_error()
+            );
+
+            assert_eq!(genmcp_client_disconnect(std::ptr::null_mut()), GENMCP_ERROR);
+            assert_eq!(last_error(), "client must not be NULL");
+        }
+    }
+}
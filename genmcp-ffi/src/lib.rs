@@ -0,0 +1,239 @@
+//! # genmcp-ffi
+//!
+//! A C ABI around the genmcp client and proxy, for hosts that cannot link
+//! Rust directly: an Electron app's native addon, a C++ IDE plugin, a Go or
+//! Swift program. The declarations are in `include/genmcp.h`; link against
+//! `libgenmcp_ffi` (a `cdylib` and a `staticlib` are both built).
+//!
+//! ## Surface
+//!
+//! - [`client`]: create a client from a transport, connect, call methods and
+//!   tools, receive notifications through a callback, disconnect
+//! - [`proxy`]: run a backend server behind a [`ProxySession`], feed it the
+//!   host's client messages, and capture every message in both directions,
+//!   as the monitor does
+//!
+//! ## Conventions
+//!
+//! - Calls block the calling thread until they finish; the work runs on a
+//!   shared tokio runtime started on first use. Do not call them from inside
+//!   a callback.
+//! - JSON goes in and out as NUL-terminated UTF-8 strings.
+//! - Strings returned by the library belong to the caller and are released
+//!   with [`genmcp_string_free`]. Handles are released with their `_free`
+//!   or `_stop` function.
+//! - Functions returning `int` return [`GENMCP_OK`] or [`GENMCP_ERROR`];
+//!   functions returning a pointer return `NULL` on failure. Either way
+//!   [`genmcp_last_error`] then describes the failure on the calling thread.
+//! - Callbacks run on runtime threads, never the caller's, and receive the
+//!   `user_data` pointer they were registered with. Strings passed to a
+//!   callback are only valid for the duration of the call.
+//! - A panic never crosses the boundary; it is reported as an error.
+//!
+//! [`ProxySession`]: mcp_transport::ProxySession
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Context, Result};
+use tokio::runtime::Runtime;
+
+pub mod client;
+pub mod proxy;
+
+/// Returned by functions that succeeded
+pub const GENMCP_OK: c_int = 0;
+
+/// Returned by functions that failed; see [`genmcp_last_error`]
+pub const GENMCP_ERROR: c_int = -1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// The runtime all calls and callbacks run on
+pub(crate) fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("genmcp")
+            .build()
+            .expect("failed to start the genmcp runtime")
+    })
+}
+
+/// Run the body of an exported function, turning errors and panics into
+/// `failed` and the thread's last error
+pub(crate) fn guard<T>(failed: T, body: impl FnOnce() -> Result<T>) -> T {
+    let error = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(e)) => e,
+        Err(panic) => {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .map(|reason| reason.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            anyhow!("genmcp panicked: {}", reason)
+        }
+    };
+    let message = format!("{:#}", error).replace('\0', " ");
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+    failed
+}
+
+/// A required string argument
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string that outlives `'a`.
+pub(crate) unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    if ptr.is_null() {
+        bail!("{} must not be NULL", name);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .with_context(|| format!("{} is not UTF-8", name))
+}
+
+/// An optional string argument, `None` for `NULL`
+///
+/// # Safety
+///
+/// As for [`str_arg`].
+pub(crate) unsafe fn opt_str_arg<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    str_arg(ptr, name).map(Some)
+}
+
+/// An optional JSON argument, `None` for `NULL`
+///
+/// # Safety
+///
+/// As for [`str_arg`].
+pub(crate) unsafe fn json_arg(ptr: *const c_char, name: &str) -> Result<Option<serde_json::Value>> {
+    opt_str_arg(ptr, name)?
+        .map(|json| serde_json::from_str(json).with_context(|| format!("{} is not JSON", name)))
+        .transpose()
+}
+
+/// An array of `len` strings
+///
+/// # Safety
+///
+/// `ptr` must point to `len` valid string pointers, or be null with `len` 0.
+pub(crate) unsafe fn str_array(
+    ptr: *const *const c_char,
+    len: usize,
+    name: &str,
+) -> Result<Vec<String>> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    if ptr.is_null() {
+        bail!("{} must not be NULL", name);
+    }
+    std::slice::from_raw_parts(ptr, len)
+        .iter()
+        .enumerate()
+        .map(|(index, &item)| str_arg(item, &format!("{}[{}]", name, index)).map(str::to_string))
+        .collect()
+}
+
+/// A string handed to the caller, who frees it with [`genmcp_string_free`]
+pub(crate) fn owned_string(value: impl Into<Vec<u8>>) -> Result<*mut c_char> {
+    Ok(CString::new(value)
+        .context("string contains a NUL byte")?
+        .into_raw())
+}
+
+/// Borrowed for the duration of a callback
+pub(crate) fn callback_string(value: impl Into<Vec<u8>>) -> CString {
+    CString::new(value).unwrap_or_default()
+}
+
+/// The caller's `user_data`, handed back to its callbacks on runtime threads
+#[derive(Clone, Copy)]
+pub(crate) struct UserData(pub *mut c_void);
+
+impl UserData {
+    /// The pointer to hand back; a method, so closures capture the wrapper
+    /// and not the bare pointer
+    pub(crate) fn get(self) -> *mut c_void {
+        self.0
+    }
+}
+
+// The caller promises `user_data` may be used from any thread by registering
+// a callback with it; the library never dereferences it
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+/// Describe the last failure on the calling thread, or `NULL` if nothing
+/// has failed on it yet.
+///
+/// The string stays valid until the next failing call on the same thread;
+/// do not free it.
+#[no_mangle]
+pub extern "C" fn genmcp_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Free a string returned by the library. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `value` must be null or a string returned by this library that was not
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// The library version, such as `"0.1.0"`; do not free it.
+#[no_mangle]
+pub extern "C" fn genmcp_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_and_panics_set_the_last_error() {
+        assert_eq!(guard(GENMCP_ERROR, || Ok(GENMCP_OK)), GENMCP_OK);
+
+        let failed = guard(GENMCP_OK, || -> Result<c_int> { bail!("no such server") });
+        assert_eq!(failed, GENMCP_OK);
+        let message = unsafe { CStr::from_ptr(genmcp_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "no such server");
+
+        let panicked = guard(std::ptr::null_mut::<c_char>(), || panic!("boom"));
+        assert!(panicked.is_null());
+        let message = unsafe { CStr::from_ptr(genmcp_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "genmcp panicked: boom");
+    }
+
+    #[test]
+    fn test_string_arguments_are_checked() {
+        let error = unsafe { str_arg(std::ptr::null(), "method") }.unwrap_err();
+        assert_eq!(error.to_string(), "method must not be NULL");
+
+        let args = [c"-c".as_ptr(), c"exit".as_ptr()];
+        let parsed = unsafe { str_array(args.as_ptr(), args.len(), "args") }.unwrap();
+        assert_eq!(parsed, ["-c", "exit"]);
+        assert!(unsafe { json_arg(c"{".as_ptr(), "params") }.is_err());
+    }
+}
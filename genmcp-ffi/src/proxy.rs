@@ -0,0 +1,314 @@
+//! Embed the proxy and capture the traffic through it.
+//!
+//! [`genmcp_proxy_start`] runs a backend server as a child process behind a
+//! [`ProxySession`], with the host as the client: the host sends messages
+//! with [`genmcp_proxy_send`] and gets the backend's answers, requests and
+//! notifications through its message callback. The optional capture
+//! callback sees every message in both directions as it passes the
+//! session's interceptors, which is what the monitor records.
+
+use std::ffi::{c_char, c_int, c_void};
+use std::process::Stdio;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use mcp_core::interceptor::{
+    InterceptAction, InterceptionResult, InterceptorManager, InterceptorStats, MessageContext,
+    MessageDirection, MessageInterceptor,
+};
+use mcp_core::McpResult;
+use mcp_transport::ProxySession;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, WriteHalf};
+use tokio::process::{Child, Command};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::{
+    callback_string, guard, runtime, str_arg, str_array, UserData, GENMCP_ERROR, GENMCP_OK,
+};
+
+/// Capacity of the in-memory pipe between the host and the session
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+/// A message from the client (the host) to the backend
+pub const GENMCP_TO_BACKEND: c_int = 0;
+
+/// A message from the backend to the client (the host)
+pub const GENMCP_FROM_BACKEND: c_int = 1;
+
+/// Receives one JSON-RPC message, as a JSON string, for the host.
+pub type GenmcpMessageCallback = extern "C" fn(message: *const c_char, user_data: *mut c_void);
+
+/// Receives a captured message and its direction, [`GENMCP_TO_BACKEND`] or
+/// [`GENMCP_FROM_BACKEND`].
+pub type GenmcpCaptureCallback =
+    extern "C" fn(direction: c_int, message: *const c_char, user_data: *mut c_void);
+
+/// A running proxy, opaque to C
+pub struct GenmcpProxy {
+    backend: Child,
+    to_session: Mutex<WriteHalf<DuplexStream>>,
+    session: JoinHandle<()>,
+    from_session: JoinHandle<()>,
+}
+
+/// Hands every message that passes to the capture callback, unchanged
+struct Capture {
+    callback: GenmcpCaptureCallback,
+    user_data: UserData,
+    stats: RwLock<InterceptorStats>,
+}
+
+#[async_trait]
+impl MessageInterceptor for Capture {
+    fn name(&self) -> &str {
+        "capture"
+    }
+
+    fn priority(&self) -> u32 {
+        // After everything that may still change the message
+        u32::MAX
+    }
+
+    async fn should_intercept(&self, _context: &MessageContext) -> bool {
+        true
+    }
+
+    async fn intercept(&self, mut context: MessageContext) -> McpResult<InterceptionResult> {
+        let action = self.intercept_in_place(&mut context).await?;
+        Ok(action.into_result(context.message))
+    }
+
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
+        let direction = match context.direction {
+            MessageDirection::Outgoing => GENMCP_TO_BACKEND,
+            MessageDirection::Incoming => GENMCP_FROM_BACKEND,
+        };
+        let message = callback_string(serde_json::to_string(&context.message)?);
+        (self.callback)(direction, message.as_ptr(), self.user_data.get());
+
+        let mut stats = self.stats.write().await;
+        stats.total_intercepted += 1;
+        Ok(InterceptAction::Continue)
+    }
+
+    async fn get_stats(&self) -> InterceptorStats {
+        self.stats.read().await.clone()
+    }
+}
+
+/// Start `command` with `argc` arguments as the backend and proxy it.
+///
+/// `on_message` receives what the backend sends the host; `on_capture`,
+/// which may be `NULL`, receives every message in both directions. Both get
+/// `user_data`, which must stay valid until [`genmcp_proxy_stop`]. Returns
+/// `NULL` on failure.
+///
+/// # Safety
+///
+/// `command` must be a NUL-terminated string and `argv` must point to `argc`
+/// of them (or be `NULL` with `argc` 0). The callbacks must be safe to call
+/// from any thread.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_proxy_start(
+    command: *const c_char,
+    argv: *const *const c_char,
+    argc: usize,
+    on_message: GenmcpMessageCallback,
+    on_capture: Option<GenmcpCaptureCallback>,
+    user_data: *mut c_void,
+) -> *mut GenmcpProxy {
+    guard(std::ptr::null_mut(), || {
+        let command = str_arg(command, "command")?;
+        let args = str_array(argv, argc, "argv")?;
+        let user_data = UserData(user_data);
+        let proxy = runtime().block_on(start(command, &args, on_message, on_capture, user_data))?;
+        Ok(Box::into_raw(Box::new(proxy)))
+    })
+}
+
+async fn start(
+    command: &str,
+    args: &[String],
+    on_message: GenmcpMessageCallback,
+    on_capture: Option<GenmcpCaptureCallback>,
+    user_data: UserData,
+) -> Result<GenmcpProxy> {
+    let mut backend = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start backend {}", command))?;
+    let backend_stdin = backend.stdin.take().context("Backend stdin is not piped")?;
+    let backend_stdout = backend
+        .stdout
+        .take()
+        .context("Backend stdout is not piped")?;
+
+    let (host, session_end) = tokio::io::duplex(PIPE_CAPACITY);
+    let (host_reader, host_writer) = tokio::io::split(host);
+    let (session_reader, session_writer) = tokio::io::split(session_end);
+    let mut session = ProxySession::new(
+        BufReader::new(session_reader),
+        session_writer,
+        BufReader::new(backend_stdout),
+        backend_stdin,
+    );
+    if let Some(callback) = on_capture {
+        let interceptors = Arc::new(InterceptorManager::new());
+        interceptors
+            .add_interceptor(Arc::new(Capture {
+                callback,
+                user_data,
+                stats: RwLock::new(InterceptorStats::default()),
+            }))
+            .await;
+        session = session
+            .with_interceptors(MessageDirection::Outgoing, interceptors.clone())
+            .with_interceptors(MessageDirection::Incoming, interceptors);
+    }
+
+    let session = tokio::spawn(async move {
+        if let Err(e) = session.run().await {
+            tracing::warn!("Embedded proxy session ended: {:#}", e);
+        }
+    });
+    let from_session = tokio::spawn(async move {
+        let mut lines = BufReader::new(host_reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let message = callback_string(line);
+            on_message(message.as_ptr(), user_data.get());
+        }
+    });
+    Ok(GenmcpProxy {
+        backend,
+        to_session: Mutex::new(host_writer),
+        session,
+        from_session,
+    })
+}
+
+/// Send one JSON-RPC message from the host to the backend.
+///
+/// # Safety
+///
+/// `proxy` must be a live handle and `message` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_proxy_send(
+    proxy: *mut GenmcpProxy,
+    message: *const c_char,
+) -> c_int {
+    guard(GENMCP_ERROR, || {
+        let proxy = proxy.as_ref().context("proxy must not be NULL")?;
+        let message = str_arg(message, "message")?;
+        serde_json::from_str::<serde_json::Value>(message).context("message is not JSON")?;
+        runtime()
+            .block_on(async {
+                let mut writer = proxy.to_session.lock().await;
+                writer.write_all(message.trim_end().as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+                writer.flush().await
+            })
+            .context("Proxy session has ended")?;
+        Ok(GENMCP_OK)
+    })
+}
+
+/// Stop the backend and free the proxy. `NULL` is ignored. No callback runs
+/// after this returns.
+///
+/// # Safety
+///
+/// `proxy` must be null or a live handle, not used again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn genmcp_proxy_stop(proxy: *mut GenmcpProxy) {
+    if proxy.is_null() {
+        return;
+    }
+    guard((), || {
+        let mut proxy = Box::from_raw(proxy);
+        runtime().block_on(async {
+            proxy.session.abort();
+            proxy.from_session.abort();
+            let _ = (&mut proxy.session).await;
+            let _ = (&mut proxy.from_session).await;
+            if let Err(e) = proxy.backend.kill().await {
+                tracing::debug!("Backend already exited: {}", e);
+            }
+        });
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::ffi::CStr;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// What the callbacks saw: `None` for a message to the host, otherwise
+    /// the capture direction
+    type Seen = mpsc::Sender<(Option<c_int>, Value)>;
+
+    extern "C" fn on_message(message: *const c_char, user_data: *mut c_void) {
+        let seen = unsafe { &*(user_data as *const Seen) };
+        let message = unsafe { CStr::from_ptr(message) }.to_str().unwrap();
+        seen.send((None, serde_json::from_str(message).unwrap()))
+            .unwrap();
+    }
+
+    extern "C" fn on_capture(direction: c_int, message: *const c_char, user_data: *mut c_void) {
+        let seen = unsafe { &*(user_data as *const Seen) };
+        let message = unsafe { CStr::from_ptr(message) }.to_str().unwrap();
+        seen.send((Some(direction), serde_json::from_str(message).unwrap()))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_host_traffic_is_proxied_and_captured() {
+        // Answers every request with an empty result
+        let script = callback_string(
+            r#"while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\("[^"]*"\|[0-9]*\).*/\1/p')
+  printf '{"jsonrpc":"2.0","id":%s,"result":{}}\n' "$id"
+done"#,
+        );
+        let argv = [c"-c".as_ptr(), script.as_ptr()];
+        let (sender, seen) = mpsc::channel::<(Option<c_int>, Value)>();
+        let user_data = &sender as *const Seen as *mut c_void;
+        unsafe {
+            let proxy = genmcp_proxy_start(
+                c"sh".as_ptr(),
+                argv.as_ptr(),
+                argv.len(),
+                on_message,
+                Some(on_capture),
+                user_data,
+            );
+            assert!(!proxy.is_null());
+            let ping =
+                callback_string(json!({"jsonrpc": "2.0", "id": 7, "method": "ping"}).to_string());
+            assert_eq!(genmcp_proxy_send(proxy, ping.as_ptr()), GENMCP_OK);
+
+            let next = || seen.recv_timeout(Duration::from_secs(5)).unwrap();
+            let (direction, request) = next();
+            assert_eq!(direction, Some(GENMCP_TO_BACKEND));
+            assert_eq!(request["method"], "ping");
+            let (direction, response) = next();
+            assert_eq!(direction, Some(GENMCP_FROM_BACKEND));
+            assert_eq!(response["id"], 7);
+            let (direction, delivered) = next();
+            assert_eq!(direction, None);
+            assert_eq!(delivered, json!({"jsonrpc": "2.0", "id": 7, "result": {}}));
+
+            assert_eq!(genmcp_proxy_send(proxy, c"not json".as_ptr()), GENMCP_ERROR);
+            genmcp_proxy_stop(proxy);
+        }
+    }
+}
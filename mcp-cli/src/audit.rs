@@ -0,0 +1,33 @@
+//! Check audit logs written by `proxy --audit-log`
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use mcp_transport::audit;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum AuditCommand {
+    /// Check that no record of an audit log was altered, removed or reordered
+    Verify {
+        /// Audit log to check
+        file: PathBuf,
+
+        /// Head printed by an earlier verification; fails if the log no
+        /// longer reaches it
+        #[arg(long, value_name = "HASH")]
+        since: Option<String>,
+    },
+}
+
+pub fn run_audit(command: AuditCommand) -> Result<()> {
+    match command {
+        AuditCommand::Verify { file, since } => {
+            let verification = audit::verify(&file, since.as_deref())?;
+            if !verification.is_intact() {
+                bail!("{}: {}", file.display(), verification);
+            }
+            println!("{}: {}", file.display(), verification);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,570 @@
+//! Connection broker that keeps server sessions alive between CLI invocations
+//!
+//! Launching a stdio server and running the initialize handshake dominates the
+//! cost of a scripted `assist-mcp` call. `assist-mcp broker` holds sessions
+//! open, keyed by the `--server` argument, and forwards requests for them
+//! received over a local IPC endpoint. Sessions left unused for the idle
+//! period are closed; `assist-mcp session close` closes them explicitly.
+//!
+//! CLI commands that go through the broker start one on demand. An
+//! on-demand broker exits once it has had no sessions for the idle period.
+//!
+//! With `--auth`, connections are authenticated by the peer's uid (Unix
+//! sockets only) and each call is checked against the identity's policies.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use mcp_common::{BrokerSession, IpcClient, IpcConnection, IpcMessage, IpcServer};
+use mcp_core::client::{McpClient, McpClientBuilder};
+use mcp_core::health::HealthConfig;
+use mcp_core::messages::{Implementation, JsonRpcRequest};
+use mcp_core::quirks::QuirkDatabase;
+use mcp_transport::auth::{AuthConfig, ClientGate, Credentials};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Endpoint the broker listens on when none is given
+#[cfg(unix)]
+pub const DEFAULT_BROKER_ENDPOINT: &str = "/tmp/assist-mcp-broker.sock";
+
+/// Endpoint the broker listens on when none is given
+#[cfg(windows)]
+pub const DEFAULT_BROKER_ENDPOINT: &str = r"\\.\pipe\assist-mcp-broker";
+
+/// Idle period of brokers started on demand
+pub const DEFAULT_IDLE: Duration = Duration::from_secs(300);
+
+/// How long a CLI invocation waits for an on-demand broker to come up
+const SPAWN_WAIT: Duration = Duration::from_secs(5);
+
+/// Options for `assist-mcp broker`
+pub struct BrokerOptions {
+    pub endpoint: String,
+    /// Close sessions unused for this long
+    pub idle: Duration,
+    /// Connect and request timeout for brokered sessions
+    pub timeout: Duration,
+    /// Exit once no sessions have been open for the idle period
+    pub exit_when_idle: bool,
+    /// Identities allowed to use the broker and their policies
+    pub auth: Option<AuthConfig>,
+}
+
+pub async fn run_broker(options: BrokerOptions) -> Result<()> {
+    let server = IpcServer::bind(&options.endpoint)
+        .await
+        .with_context(|| format!("Failed to listen on {}", options.endpoint))?;
+    let broker = Arc::new(Broker::new(options.idle, options.timeout));
+    let gate = options
+        .auth
+        .map(|auth| Arc::new(ClientGate::from_config(auth)));
+    eprintln!(
+        "Broker listening on {} (idle sessions close after {}s)",
+        options.endpoint,
+        options.idle.as_secs()
+    );
+
+    let mut sweep = tokio::time::interval(
+        (options.idle / 4).clamp(Duration::from_millis(100), Duration::from_secs(30)),
+    );
+    loop {
+        tokio::select! {
+            connection = server.accept() => {
+                let connection = connection?;
+                let broker = broker.clone();
+                let gate = gate.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_connection(broker, gate, connection).await {
+                        eprintln!("Broker connection failed: {:#}", e);
+                    }
+                });
+            }
+            _ = sweep.tick() => {
+                for server in broker.expire_idle().await {
+                    eprintln!("Closed idle session '{}'", server);
+                }
+                if options.exit_when_idle && broker.is_idle().await {
+                    break;
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    broker.close(None).await;
+    #[cfg(unix)]
+    if let Ok(mcp_common::IpcEndpoint::Unix(path)) =
+        mcp_common::IpcEndpoint::parse(&options.endpoint)
+    {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+async fn serve_connection(
+    broker: Arc<Broker>,
+    gate: Option<Arc<ClientGate>>,
+    mut connection: IpcConnection,
+) -> Result<()> {
+    let identity = gate.as_ref().map(|gate| {
+        let credentials = connection
+            .peer_uid()
+            .map_or(Credentials::None, Credentials::PeerUid);
+        gate.authenticate(&credentials)
+    });
+    while let Some(envelope) = connection.receive_message().await? {
+        let reply = match envelope.message {
+            IpcMessage::BrokerCall {
+                server,
+                method,
+                params,
+            } => {
+                let refusal = match (&gate, &identity) {
+                    (Some(gate), Some(Ok(identity))) => gate
+                        .authorize(
+                            identity,
+                            &JsonRpcRequest::new(0, method.clone(), params.clone()),
+                            Instant::now(),
+                        )
+                        .err(),
+                    (_, Some(Err(e))) => Some(e.to_string()),
+                    _ => None,
+                };
+                match refusal {
+                    Some(reason) => IpcMessage::Error {
+                        message: format!("Refused: {}", reason),
+                        proxy_id: None,
+                    },
+                    None => match broker.call(&server, &method, params).await {
+                        Ok((response, reused)) => IpcMessage::BrokerResult { response, reused },
+                        Err(e) => IpcMessage::Error {
+                            message: format!("{:#}", e),
+                            proxy_id: None,
+                        },
+                    },
+                }
+            }
+            IpcMessage::BrokerListSessions => IpcMessage::BrokerSessions(broker.sessions().await),
+            IpcMessage::BrokerCloseSession { server } => {
+                IpcMessage::BrokerClosed(broker.close(server.as_deref()).await)
+            }
+            IpcMessage::Ping => IpcMessage::Pong,
+            other => IpcMessage::Error {
+                message: format!("Unexpected broker message: {:?}", other),
+                proxy_id: None,
+            },
+        };
+        connection.send_message(reply).await?;
+    }
+    Ok(())
+}
+
+/// A brokered session; the client is `None` until the first call connects it
+struct Session {
+    client: tokio::sync::Mutex<Option<McpClient>>,
+    info: Mutex<BrokerSession>,
+}
+
+/// Open server sessions by server argument
+pub struct Broker {
+    sessions: tokio::sync::Mutex<HashMap<String, Arc<Session>>>,
+    idle: Duration,
+    timeout: Duration,
+    last_activity: Mutex<Instant>,
+}
+
+impl Broker {
+    pub fn new(idle: Duration, timeout: Duration) -> Self {
+        Self {
+            sessions: tokio::sync::Mutex::new(HashMap::new()),
+            idle,
+            timeout,
+            last_activity: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Send a request over the session for `server`, opening it if needed
+    ///
+    /// Returns the JSON-RPC response and whether an open session served it.
+    /// A session whose transport fails is dropped so the next call reconnects,
+    /// and one whose health checks fail is reconnected before use.
+    pub async fn call(&self, server: &str, method: &str, params: Value) -> Result<(Value, bool)> {
+        *self.last_activity.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        loop {
+            let session = self
+                .sessions
+                .lock()
+                .await
+                .entry(server.to_string())
+                .or_insert_with(|| Arc::new(Session::new(server)))
+                .clone();
+
+            let mut client = session.client.lock().await;
+            if !self.is_current(server, &session).await {
+                // Closed or failed while we waited for it
+                continue;
+            }
+            if client
+                .as_ref()
+                .and_then(|connected| connected.health_status())
+                .is_some_and(|health| health.is_unhealthy())
+            {
+                // Replace the session rather than let the call find out it is gone
+                if let Some(mut stale) = client.take() {
+                    let _ = stale.disconnect().await;
+                }
+            }
+            let reused = client.is_some();
+            if !reused {
+                match connect(server, self.timeout).await {
+                    Ok(connected) => *client = Some(connected),
+                    Err(e) => {
+                        self.remove(server, &session).await;
+                        return Err(e);
+                    }
+                }
+            }
+            let connected = client.as_mut().expect("session connected above");
+
+            let response = match connected.send_request(method, params).await {
+                Ok(response) => response,
+                Err(e) => {
+                    let _ = connected.disconnect().await;
+                    *client = None;
+                    self.remove(server, &session).await;
+                    return Err(anyhow!(e).context(format!("Session '{}' failed", server)));
+                }
+            };
+            let mut info = session.info.lock().unwrap_or_else(|e| e.into_inner());
+            info.last_used = Utc::now();
+            info.requests += 1;
+            return Ok((serde_json::to_value(response)?, reused));
+        }
+    }
+
+    /// Open sessions, oldest first
+    pub async fn sessions(&self) -> Vec<BrokerSession> {
+        let mut sessions: Vec<BrokerSession> = self
+            .sessions
+            .lock()
+            .await
+            .values()
+            .map(|session| {
+                session
+                    .info
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone()
+            })
+            .collect();
+        sessions.sort_by_key(|session| session.started_at);
+        sessions
+    }
+
+    /// Close the session for `server`, or every session; returns the closed names
+    pub async fn close(&self, server: Option<&str>) -> Vec<String> {
+        let removed: Vec<(String, Arc<Session>)> = {
+            let mut sessions = self.sessions.lock().await;
+            match server {
+                Some(server) => sessions.remove_entry(server).into_iter().collect(),
+                None => sessions.drain().collect(),
+            }
+        };
+        self.shut_down(removed).await
+    }
+
+    /// Close sessions unused for the idle period, skipping busy ones
+    pub async fn expire_idle(&self) -> Vec<String> {
+        let now = Utc::now();
+        let removed: Vec<(String, Arc<Session>)> = {
+            let mut sessions = self.sessions.lock().await;
+            let expired: Vec<String> = sessions
+                .iter()
+                .filter(|(_, session)| {
+                    let last_used = session
+                        .info
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .last_used;
+                    session.client.try_lock().is_ok()
+                        && (now - last_used).to_std().unwrap_or_default() >= self.idle
+                })
+                .map(|(server, _)| server.clone())
+                .collect();
+            expired
+                .into_iter()
+                .filter_map(|server| sessions.remove_entry(&server))
+                .collect()
+        };
+        self.shut_down(removed).await
+    }
+
+    /// Whether no session is open and none was used for the idle period
+    pub async fn is_idle(&self) -> bool {
+        self.sessions.lock().await.is_empty()
+            && self
+                .last_activity
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .elapsed()
+                >= self.idle
+    }
+
+    async fn shut_down(&self, removed: Vec<(String, Arc<Session>)>) -> Vec<String> {
+        let mut closed = Vec::new();
+        for (server, session) in removed {
+            if let Some(mut client) = session.client.lock().await.take() {
+                if let Err(e) = client.disconnect().await {
+                    eprintln!("Warning: failed to close session '{}': {}", server, e);
+                }
+            }
+            closed.push(server);
+        }
+        closed.sort();
+        closed
+    }
+
+    async fn is_current(&self, server: &str, session: &Arc<Session>) -> bool {
+        self.sessions
+            .lock()
+            .await
+            .get(server)
+            .is_some_and(|current| Arc::ptr_eq(current, session))
+    }
+
+    async fn remove(&self, server: &str, session: &Arc<Session>) {
+        let mut sessions = self.sessions.lock().await;
+        if sessions
+            .get(server)
+            .is_some_and(|current| Arc::ptr_eq(current, session))
+        {
+            sessions.remove(server);
+        }
+    }
+}
+
+impl Session {
+    fn new(server: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            client: tokio::sync::Mutex::new(None),
+            info: Mutex::new(BrokerSession {
+                server: server.to_string(),
+                started_at: now,
+                last_used: now,
+                requests: 0,
+            }),
+        }
+    }
+}
+
+async fn connect(server: &str, timeout: Duration) -> Result<McpClient> {
+    let (config, profile) = crate::export::resolve_server(server)?;
+    let mut builder = McpClientBuilder::new()
+        .transport(config)
+        .connect_deadline(timeout)
+        .request_timeout(timeout)
+        .health_check(HealthConfig::default())
+        .quirks(QuirkDatabase::load_default()?);
+    if let Some(profile) = profile {
+        builder = builder.profile(profile);
+    }
+    let mut client = builder.build().await?;
+    client
+        .connect(Implementation {
+            name: "assist-mcp-broker".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            metadata: HashMap::new(),
+        })
+        .await
+        .with_context(|| format!("Failed to connect to '{}'", server))?;
+    Ok(client)
+}
+
+/// Session key for a `--server` argument: profile files are made absolute so
+/// invocations from different directories share a session
+pub fn session_key(server: &str) -> String {
+    let path = Path::new(server);
+    if path.exists() {
+        if let Ok(path) = path.canonicalize() {
+            return path.display().to_string();
+        }
+    }
+    server.to_string()
+}
+
+/// CLI side of the broker protocol
+pub struct BrokerClient {
+    client: IpcClient,
+}
+
+impl BrokerClient {
+    /// Connect to a running broker
+    pub async fn connect(endpoint: &str) -> Result<Self> {
+        let client = IpcClient::connect(endpoint)
+            .await
+            .with_context(|| format!("No broker is listening on {}", endpoint))?;
+        Ok(Self { client })
+    }
+
+    /// Connect to the broker, starting one in the background if none runs
+    pub async fn connect_or_spawn(endpoint: &str) -> Result<Self> {
+        if let Ok(client) = Self::connect(endpoint).await {
+            return Ok(client);
+        }
+
+        std::process::Command::new(std::env::current_exe()?)
+            .args(["broker", "--exit-when-idle", "--endpoint", endpoint])
+            .args(["--idle", &DEFAULT_IDLE.as_secs().to_string()])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .context("Failed to start the broker")?;
+
+        let started = Instant::now();
+        loop {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            match Self::connect(endpoint).await {
+                Ok(client) => return Ok(client),
+                Err(e) if started.elapsed() >= SPAWN_WAIT => return Err(e),
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Send `method` to `server`; returns the JSON-RPC response and whether
+    /// an already open session served it
+    pub async fn call(
+        &mut self,
+        server: &str,
+        method: &str,
+        params: Value,
+    ) -> Result<(Value, bool)> {
+        let message = IpcMessage::BrokerCall {
+            server: session_key(server),
+            method: method.to_string(),
+            params,
+        };
+        match self.exchange(message).await? {
+            IpcMessage::BrokerResult { response, reused } => Ok((response, reused)),
+            other => bail!("Unexpected broker reply: {:?}", other),
+        }
+    }
+
+    /// Sessions the broker holds open
+    pub async fn sessions(&mut self) -> Result<Vec<BrokerSession>> {
+        match self.exchange(IpcMessage::BrokerListSessions).await? {
+            IpcMessage::BrokerSessions(sessions) => Ok(sessions),
+            other => bail!("Unexpected broker reply: {:?}", other),
+        }
+    }
+
+    /// Close the session for `server`, or all sessions
+    pub async fn close(&mut self, server: Option<&str>) -> Result<Vec<String>> {
+        let message = IpcMessage::BrokerCloseSession {
+            server: server.map(session_key),
+        };
+        match self.exchange(message).await? {
+            IpcMessage::BrokerClosed(closed) => Ok(closed),
+            other => bail!("Unexpected broker reply: {:?}", other),
+        }
+    }
+
+    async fn exchange(&mut self, message: IpcMessage) -> Result<IpcMessage> {
+        self.client.send(message).await?;
+        let envelope = self
+            .client
+            .receive()
+            .await?
+            .ok_or_else(|| anyhow!("Broker closed the connection"))?;
+        match envelope.message {
+            IpcMessage::Error { message, .. } => Err(anyhow!(message)),
+            message => Ok(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo_server::{serve_http, DemoServer};
+    use mcp_core::transport::origin::OriginPolicy;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_sessions_are_reused_listed_and_closed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost(),
+        ));
+
+        let ipc = IpcServer::bind("tcp:127.0.0.1:0").await.unwrap();
+        let endpoint = format!("tcp:{}", ipc.local_addr().unwrap());
+        let broker = Arc::new(Broker::new(DEFAULT_IDLE, Duration::from_secs(10)));
+        tokio::spawn(async move {
+            loop {
+                let connection = ipc.accept().await.unwrap();
+                tokio::spawn(serve_connection(broker.clone(), None, connection));
+            }
+        });
+
+        let params = json!({ "name": "add", "arguments": { "a": 2, "b": 3 } });
+        let mut first = BrokerClient::connect(&endpoint).await.unwrap();
+        let (response, reused) = first
+            .call(&url, "tools/call", params.clone())
+            .await
+            .unwrap();
+        assert!(!reused);
+        assert_eq!(response["result"]["content"][0]["text"], "5");
+
+        // A second invocation rides on the same session
+        let mut second = BrokerClient::connect(&endpoint).await.unwrap();
+        let (_, reused) = second.call(&url, "tools/call", params).await.unwrap();
+        assert!(reused);
+
+        let sessions = second.sessions().await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].server, url);
+        assert_eq!(sessions[0].requests, 2);
+
+        assert_eq!(second.close(Some(&url)).await.unwrap(), vec![url.clone()]);
+        assert!(second.sessions().await.unwrap().is_empty());
+        assert!(second.close(Some(&url)).await.unwrap().is_empty());
+
+        // Unknown servers surface as errors without leaving a session behind
+        assert!(first
+            .call("no-such-profile-xyz", "tools/list", json!({}))
+            .await
+            .is_err());
+        assert!(first.sessions().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_expire() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost(),
+        ));
+
+        let broker = Broker::new(Duration::from_millis(50), Duration::from_secs(10));
+        broker.call(&url, "ping", json!({})).await.unwrap();
+        assert!(broker.expire_idle().await.is_empty());
+        assert!(!broker.is_idle().await);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(broker.expire_idle().await, vec![url]);
+        assert!(broker.sessions().await.is_empty());
+        assert!(broker.is_idle().await);
+    }
+}
@@ -0,0 +1,185 @@
+//! Share interceptor pipelines as signed bundles
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use mcp_transport::bundle::{self, Bundle, Verification};
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum BundleCommand {
+    /// Create a signing key and print its public key
+    Keygen {
+        /// File to write the private key to
+        key: PathBuf,
+    },
+    /// Pack a pipeline directory (policy.toml, redaction.toml, transforms.toml, templates/) into a bundle
+    Export {
+        /// Pipeline directory to pack
+        dir: PathBuf,
+
+        /// Bundle file to write (tar)
+        output: PathBuf,
+
+        /// Bundle name (defaults to the directory name)
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Version of the bundle's content
+        #[arg(short, long, default_value = "0.1.0")]
+        version: String,
+
+        /// Oldest assist-mcp release able to apply the bundle (defaults to this one)
+        #[arg(long)]
+        min_version: Option<String>,
+
+        /// Private key to sign the bundle with
+        #[arg(short, long)]
+        sign_key: Option<PathBuf>,
+    },
+    /// Check a bundle's integrity, signature and compatibility
+    Verify {
+        /// Bundle file to check
+        file: PathBuf,
+
+        /// Public key (base64, or a file containing it) the bundle must be signed by
+        #[arg(short, long)]
+        trusted_key: Option<String>,
+    },
+    /// Verify a bundle and unpack it into a pipeline directory for `proxy --pipeline`
+    Import {
+        /// Bundle file to import
+        file: PathBuf,
+
+        /// Directory to unpack into
+        dir: PathBuf,
+
+        /// Public key (base64, or a file containing it) the bundle must be signed by
+        #[arg(short, long)]
+        trusted_key: Option<String>,
+
+        /// Import a bundle that carries no signature
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+}
+
+pub fn run_bundle(command: BundleCommand) -> Result<()> {
+    match command {
+        BundleCommand::Keygen { key } => {
+            let public_key = bundle::generate_key(&key)?;
+            println!("Wrote signing key to {}", key.display());
+            println!("Public key: {}", public_key);
+            Ok(())
+        }
+        BundleCommand::Export {
+            dir,
+            output,
+            name,
+            version,
+            min_version,
+            sign_key,
+        } => export(&dir, &output, name, version, min_version, sign_key),
+        BundleCommand::Verify { file, trusted_key } => {
+            let (bundle, verification) = open(&file, trusted_key.as_deref())?;
+            describe(&bundle, &verification);
+            Ok(())
+        }
+        BundleCommand::Import {
+            file,
+            dir,
+            trusted_key,
+            allow_unsigned,
+        } => {
+            let (bundle, verification) = open(&file, trusted_key.as_deref())?;
+            if verification.signed_by.is_none() && !allow_unsigned {
+                bail!("Bundle is unsigned; pass --allow-unsigned to import it anyway");
+            }
+            describe(&bundle, &verification);
+            bundle.extract(&dir)?;
+            println!("Imported into {}", dir.display());
+            println!(
+                "Use it with: assist-mcp proxy --pipeline {} ...",
+                dir.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn export(
+    dir: &Path,
+    output: &Path,
+    name: Option<String>,
+    version: String,
+    min_version: Option<String>,
+    sign_key: Option<PathBuf>,
+) -> Result<()> {
+    let name = match name {
+        Some(name) => name,
+        None => dir
+            .canonicalize()?
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .context("Cannot derive a bundle name from the directory; pass --name")?,
+    };
+
+    let mut bundle = Bundle::from_dir(dir, name, version)?;
+    if let Some(min_version) = min_version {
+        bundle.manifest.min_version = min_version;
+    }
+    bundle::check_compatibility(&bundle.manifest, bundle::RUNNING_VERSION)?;
+    match sign_key {
+        Some(key) => bundle.sign(&bundle::load_key(key)?)?,
+        None => eprintln!("Warning: bundle is unsigned"),
+    }
+    bundle.write(output)?;
+
+    println!(
+        "Wrote {} {} ({} file(s)) to {}",
+        bundle.manifest.name,
+        bundle.manifest.version,
+        bundle.manifest.files.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn open(file: &Path, trusted_key: Option<&str>) -> Result<(Bundle, Verification)> {
+    let trusted_key = trusted_key.map(read_public_key).transpose()?;
+    let bundle = Bundle::read(file)?;
+    let verification = bundle.verify(trusted_key.as_deref())?;
+    bundle.pipeline()?;
+    Ok((bundle, verification))
+}
+
+/// A public key given inline or as a file holding it
+fn read_public_key(key: &str) -> Result<String> {
+    let path = Path::new(key);
+    if path.is_file() {
+        let key = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read public key {}", path.display()))?;
+        return Ok(key.trim().to_string());
+    }
+    Ok(key.trim().to_string())
+}
+
+fn describe(bundle: &Bundle, verification: &Verification) {
+    let manifest = &bundle.manifest;
+    println!(
+        "{} {} (format {}, needs assist-mcp >= {}, created by {} at {})",
+        manifest.name,
+        manifest.version,
+        manifest.format,
+        manifest.min_version,
+        manifest.created_by,
+        manifest.created_at.to_rfc3339()
+    );
+    for file in bundle.files() {
+        println!("  {}", file);
+    }
+    match (&verification.signed_by, verification.trusted) {
+        (Some(key), true) => println!("Signed by trusted key {}", key),
+        (Some(key), false) => println!("Signed by {} (not checked against a trusted key)", key),
+        (None, _) => println!("Unsigned"),
+    }
+}
@@ -0,0 +1,485 @@
+//! One-shot `call` and `list` commands for scripts and CI
+//!
+//! Each invocation connects to the server, performs one operation, prints the
+//! result as JSON (YAML or a table with `--output`) and exits with a
+//! [`Status`] telling the caller what went wrong, if anything. Tool results are
+//! sorted into success, failure and partial success by the user's
+//! [`OutcomeRules`]; `--expect` turns the outcome into an assertion.
+
+use crate::output::{report, table, Failure, OutputFormat, Status, WithStatus};
+use anyhow::{anyhow, Context};
+use mcp_core::catalog::{CatalogQuery, ToolCatalog};
+use mcp_core::client::{McpClient, McpClientBuilder};
+use mcp_core::messages::Implementation;
+use mcp_core::outcome::{OutcomeKind, OutcomeRules, ToolOutcome};
+use mcp_core::quirks::QuirkDatabase;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Widest description shown in tables
+const DESCRIPTION_WIDTH: usize = 60;
+
+/// Outcome `call --expect` asserts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Expectation {
+    Success,
+    Failure,
+    Partial,
+}
+
+impl Expectation {
+    fn kind(self) -> OutcomeKind {
+        match self {
+            Expectation::Success => OutcomeKind::Success,
+            Expectation::Failure => OutcomeKind::Failure,
+            Expectation::Partial => OutcomeKind::Partial,
+        }
+    }
+}
+
+/// What `list` lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListKind {
+    Tools,
+    Resources,
+    Prompts,
+}
+
+impl ListKind {
+    fn method(self) -> &'static str {
+        match self {
+            ListKind::Tools => "tools/list",
+            ListKind::Resources => "resources/list",
+            ListKind::Prompts => "prompts/list",
+        }
+    }
+
+    /// Key of the items in a listing
+    fn key(self) -> &'static str {
+        match self {
+            ListKind::Tools => "tools",
+            ListKind::Resources => "resources",
+            ListKind::Prompts => "prompts",
+        }
+    }
+}
+
+/// Options for `assist-mcp call`
+pub struct CallOptions {
+    pub server: String,
+    pub tool: String,
+    /// Tool arguments as a JSON object
+    pub args: String,
+    pub format: OutputFormat,
+    pub timeout: Duration,
+    /// Exit successfully only for this outcome
+    pub expect: Option<Expectation>,
+}
+
+/// Options for `assist-mcp list`
+pub struct ListOptions {
+    pub server: String,
+    pub kind: ListKind,
+    /// Only keep items mentioning this text
+    pub filter: Option<String>,
+    pub format: OutputFormat,
+    pub timeout: Duration,
+}
+
+/// Call a tool and print its result
+pub async fn run_call(options: CallOptions) -> Status {
+    report(options.format, call(options).await)
+}
+
+/// List a server's tools, resources or prompts
+pub async fn run_list(options: ListOptions) -> Status {
+    report(options.format, list(options).await)
+}
+
+async fn call(options: CallOptions) -> Result<(String, Status), Failure> {
+    let arguments: Value = serde_json::from_str(&options.args)
+        .context("--args must be valid JSON")
+        .status(Status::InvalidInput)?;
+    if !arguments.is_object() {
+        return Err(anyhow!("--args must be a JSON object")).status(Status::InvalidInput);
+    }
+    let rules = OutcomeRules::load_default().status(Status::InvalidInput)?;
+
+    let mut client = connect(&options.server, options.timeout).await?;
+    let params = json!({ "name": options.tool, "arguments": arguments });
+    let result = request(&mut client, "tools/call", params).await;
+    disconnect(client).await;
+    let result = result?;
+
+    // The result exactly as the server sent it
+    let output = options.format.render(&result, content_table);
+    let outcome = rules.classify(&options.tool, &result);
+    if !outcome.is_success() {
+        eprintln!("{}", outcome);
+    }
+    let status = match options.expect {
+        Some(expected) if expected.kind() == outcome.kind() => Status::Success,
+        Some(expected) => {
+            eprintln!("Expected {}, got {}", expected.kind(), outcome.kind());
+            Status::UnexpectedOutcome
+        }
+        None => match outcome {
+            ToolOutcome::Success { .. } => Status::Success,
+            ToolOutcome::Failure { .. } => Status::ToolError,
+            ToolOutcome::Partial { .. } => Status::Partial,
+        },
+    };
+    Ok((output, status))
+}
+
+async fn list(options: ListOptions) -> Result<(String, Status), Failure> {
+    let mut client = connect(&options.server, options.timeout).await?;
+    let items = list_all(&mut client, options.kind).await;
+    disconnect(client).await;
+    let items = select(options.kind, items?, options.filter.as_deref());
+
+    let output = options.format.render(&Value::Array(items), |items| {
+        list_table(options.kind, items.as_array().map_or(&[], Vec::as_slice))
+    });
+    Ok((output, Status::Success))
+}
+
+pub(crate) async fn connect(server: &str, timeout: Duration) -> Result<McpClient, Failure> {
+    let (config, profile) = crate::export::resolve_server(server).status(Status::InvalidInput)?;
+    let mut builder = McpClientBuilder::new()
+        .transport(config)
+        .connect_deadline(timeout)
+        .request_timeout(timeout)
+        .quirks(QuirkDatabase::load_default().status(Status::InvalidInput)?);
+    if let Some(profile) = profile {
+        builder = builder.profile(profile);
+    }
+    let mut client = builder
+        .build()
+        .await
+        .with_context(|| format!("Failed to start '{}'", server))
+        .status(Status::ConnectFailed)?;
+    client
+        .connect(Implementation {
+            name: "assist-mcp".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            metadata: HashMap::new(),
+        })
+        .await
+        .with_context(|| format!("Failed to connect to '{}'", server))
+        .status(Status::ConnectFailed)?;
+    Ok(client)
+}
+
+/// The server's implementation and every tool it lists
+pub(crate) async fn fetch_tools(
+    server: &str,
+    timeout: Duration,
+) -> anyhow::Result<(Implementation, Vec<Value>)> {
+    let mut client = connect(server, timeout).await.map_err(|f| f.error)?;
+    let tools = list_all(&mut client, ListKind::Tools).await;
+    let implementation = client.server_info().await.map(|info| info.implementation);
+    disconnect(client).await;
+    let tools = tools.map_err(|f| f.error)?;
+    Ok((
+        implementation.ok_or_else(|| anyhow!("'{}' did not report server info", server))?,
+        tools,
+    ))
+}
+
+pub(crate) async fn disconnect(mut client: McpClient) {
+    if let Err(e) = client.disconnect().await {
+        eprintln!("Warning: failed to disconnect: {}", e);
+    }
+}
+
+/// Send one request and return its result
+async fn request(client: &mut McpClient, method: &str, params: Value) -> Result<Value, Failure> {
+    let response = client
+        .send_request(method, params)
+        .await
+        .with_context(|| format!("{} failed", method))
+        .status(Status::RequestFailed)?;
+    if let Some(error) = response.error {
+        return Err(anyhow!(
+            "{} failed: {} ({})",
+            method,
+            error.message,
+            error.code
+        ))
+        .status(Status::RequestFailed);
+    }
+    Ok(response.result.unwrap_or(Value::Null))
+}
+
+/// Every item of a paginated listing
+async fn list_all(client: &mut McpClient, kind: ListKind) -> Result<Vec<Value>, Failure> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let result = request(client, kind.method(), params).await?;
+        if let Some(page) = result[kind.key()].as_array() {
+            items.extend(page.iter().cloned());
+        }
+        match result["nextCursor"].as_str() {
+            Some(next) if cursor.as_deref() != Some(next) => cursor = Some(next.to_string()),
+            _ => return Ok(items),
+        }
+    }
+}
+
+/// Apply `--filter`; tools go through the catalog and come back sorted by name
+fn select(kind: ListKind, items: Vec<Value>, filter: Option<&str>) -> Vec<Value> {
+    match kind {
+        ListKind::Tools => {
+            let mut catalog = ToolCatalog::new();
+            catalog.insert_tools("server", items);
+            let mut query = CatalogQuery::new();
+            if let Some(filter) = filter {
+                query = query.text(filter);
+            }
+            catalog
+                .query(&query)
+                .entries
+                .into_iter()
+                .map(|entry| entry.tool.clone())
+                .collect()
+        }
+        ListKind::Resources | ListKind::Prompts => {
+            let Some(filter) = filter.map(str::to_lowercase) else {
+                return items;
+            };
+            items
+                .into_iter()
+                .filter(|item| {
+                    ["uri", "name", "description"].iter().any(|key| {
+                        item[*key]
+                            .as_str()
+                            .is_some_and(|text| text.to_lowercase().contains(&filter))
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+fn list_table(kind: ListKind, items: &[Value]) -> String {
+    let text = |item: &Value, key: &str| item[key].as_str().unwrap_or_default().to_string();
+    match kind {
+        ListKind::Tools => table(
+            &["NAME", "DESCRIPTION"],
+            items
+                .iter()
+                .map(|tool| vec![text(tool, "name"), cell(&text(tool, "description"))])
+                .collect(),
+        ),
+        ListKind::Resources => table(
+            &["URI", "NAME", "MIME TYPE"],
+            items
+                .iter()
+                .map(|resource| {
+                    vec![
+                        text(resource, "uri"),
+                        text(resource, "name"),
+                        text(resource, "mimeType"),
+                    ]
+                })
+                .collect(),
+        ),
+        ListKind::Prompts => table(
+            &["NAME", "ARGUMENTS", "DESCRIPTION"],
+            items
+                .iter()
+                .map(|prompt| {
+                    // Required arguments are marked with `*`
+                    let arguments: Vec<String> = prompt["arguments"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|argument| {
+                            let required = argument["required"].as_bool() == Some(true);
+                            format!(
+                                "{}{}",
+                                text(argument, "name"),
+                                if required { "*" } else { "" }
+                            )
+                        })
+                        .collect();
+                    vec![
+                        text(prompt, "name"),
+                        arguments.join(","),
+                        cell(&text(prompt, "description")),
+                    ]
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// One row per content block of a `tools/call` result
+fn content_table(result: &Value) -> String {
+    let rows = result["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|block| {
+            let kind = block["type"].as_str().unwrap_or("unknown");
+            let content = match kind {
+                "text" => block["text"].as_str().unwrap_or_default().to_string(),
+                "image" | "audio" => format!(
+                    "{} ({} base64 bytes)",
+                    block["mimeType"].as_str().unwrap_or("unknown type"),
+                    block["data"].as_str().map_or(0, str::len)
+                ),
+                "resource" => block
+                    .pointer("/resource/uri")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                "resource_link" => block["uri"].as_str().unwrap_or_default().to_string(),
+                _ => block.to_string(),
+            };
+            vec![kind.to_string(), content.replace('\n', " ")]
+        })
+        .collect();
+    table(&["TYPE", "CONTENT"], rows)
+}
+
+/// First line of a description, cut to fit a table
+fn cell(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > DESCRIPTION_WIDTH {
+        let cut: String = line.chars().take(DESCRIPTION_WIDTH - 1).collect();
+        format!("{}…", cut)
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo_server::{serve_http, DemoServer};
+    use mcp_core::transport::origin::OriginPolicy;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    async fn demo_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost(),
+        ));
+        url
+    }
+
+    fn call_options(server: &str, tool: &str, args: &str) -> CallOptions {
+        CallOptions {
+            server: server.to_string(),
+            tool: tool.to_string(),
+            args: args.to_string(),
+            format: OutputFormat::Table,
+            timeout: Duration::from_secs(10),
+            expect: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_reports_status() {
+        let url = demo_url().await;
+
+        let (output, status) = call(call_options(&url, "add", r#"{"a": 2, "b": 3}"#))
+            .await
+            .ok()
+            .unwrap();
+        assert_eq!(status, Status::Success);
+        assert_eq!(output, "TYPE  CONTENT\ntext  5\n");
+
+        let (_, status) = call(call_options(&url, "fail", r#"{"message": "boom"}"#))
+            .await
+            .ok()
+            .unwrap();
+        assert_eq!(status, Status::ToolError);
+
+        let expecting = |expect| CallOptions {
+            expect: Some(expect),
+            ..call_options(&url, "fail", r#"{"message": "Warning: disk almost full"}"#)
+        };
+        let (_, status) = call(expecting(Expectation::Partial)).await.ok().unwrap();
+        assert_eq!(status, Status::UnexpectedOutcome);
+        let (_, status) = call(expecting(Expectation::Failure)).await.ok().unwrap();
+        assert_eq!(status, Status::Success);
+
+        let failure = |options| async move { call(options).await.err().unwrap().status };
+        assert_eq!(
+            failure(call_options(&url, "add", "not json")).await,
+            Status::InvalidInput
+        );
+        assert_eq!(
+            failure(call_options(&url, "add", "{}")).await,
+            Status::RequestFailed
+        );
+        assert_eq!(
+            failure(CallOptions {
+                timeout: Duration::from_secs(1),
+                ..call_options("http://127.0.0.1:9/mcp", "add", "{}")
+            })
+            .await,
+            Status::ConnectFailed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_and_formats() {
+        let url = demo_url().await;
+        let options = |kind, filter: Option<&str>, format| ListOptions {
+            server: url.clone(),
+            kind,
+            filter: filter.map(str::to_string),
+            format,
+            timeout: Duration::from_secs(10),
+        };
+
+        let (output, _) = list(options(ListKind::Tools, None, OutputFormat::Json))
+            .await
+            .ok()
+            .unwrap();
+        let tools: Vec<Value> = serde_json::from_str(&output).unwrap();
+        let names: Vec<_> = tools
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["add", "countdown", "echo", "fail"]);
+
+        let (output, _) = list(options(ListKind::Tools, Some("count"), OutputFormat::Table))
+            .await
+            .ok()
+            .unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.lines().nth(1).unwrap().starts_with("countdown  "));
+
+        let (output, _) = list(options(ListKind::Prompts, None, OutputFormat::Table))
+            .await
+            .ok()
+            .unwrap();
+        assert!(output.starts_with("NAME   ARGUMENTS  DESCRIPTION\ngreet  name*"));
+
+        let (output, _) = list(options(
+            ListKind::Resources,
+            Some("readme"),
+            OutputFormat::Table,
+        ))
+        .await
+        .ok()
+        .unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.contains("demo://readme"));
+    }
+}
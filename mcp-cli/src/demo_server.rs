@@ -0,0 +1,735 @@
+//! Reference MCP server for demos, documentation and tests
+//!
+//! `assist-mcp demo-server` speaks the protocol over stdio (one JSON-RPC
+//! message per line) or, with `--http`, over Streamable HTTP at `/mcp`. It
+//! offers a few tools, two resources and a prompt, reports progress for the
+//! `countdown` tool and can fail every Nth request on purpose.
+//!
+//! Over HTTP it validates `Origin` and `Host` against DNS rebinding: bound to
+//! a loopback address it accepts only loopback hosts and origins, otherwise
+//! any host but no browser origin, plus whatever `--allow-origin` adds.
+
+use anyhow::{Context, Result};
+use mcp_core::transport::origin::OriginPolicy;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+const SERVER_NAME: &str = "assist-mcp-demo";
+const SUPPORTED_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26"];
+const LATEST_VERSION: &str = "2025-03-26";
+
+const README: &str = "# Demo server\n\nA reference MCP server bundled with assist-mcp.\n";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const RESOURCE_NOT_FOUND: i64 = -32002;
+const ORIGIN_REJECTED: i64 = -32001;
+
+/// Options for `assist-mcp demo-server`
+pub struct DemoOptions {
+    /// Address to serve Streamable HTTP on; stdio when absent
+    pub http: Option<String>,
+    /// Fail every Nth request with an internal error
+    pub fail_every: Option<u64>,
+    /// Browser origins allowed over HTTP besides the defaults
+    pub allowed_origins: Vec<String>,
+}
+
+pub async fn run_demo_server(options: DemoOptions) -> Result<()> {
+    let server = Arc::new(DemoServer::new(options.fail_every));
+    match options.http {
+        Some(addr) => {
+            let listener = TcpListener::bind(&addr)
+                .await
+                .with_context(|| format!("Failed to listen on {}", addr))?;
+            eprintln!(
+                "Demo MCP server listening on http://{}/mcp",
+                listener.local_addr()?
+            );
+            let origins = options.allowed_origins.into_iter().fold(
+                default_origins(listener.local_addr()?),
+                OriginPolicy::allow_origin,
+            );
+            serve_http(server, listener, origins).await
+        }
+        None => serve_stdio(server).await,
+    }
+}
+
+/// Protocol logic shared by both transports
+pub struct DemoServer {
+    fail_every: Option<u64>,
+    requests: AtomicU64,
+}
+
+impl DemoServer {
+    pub fn new(fail_every: Option<u64>) -> Self {
+        Self {
+            fail_every: fail_every.filter(|n| *n > 0),
+            requests: AtomicU64::new(0),
+        }
+    }
+
+    /// Handle raw message text: a message, a batch or garbage
+    ///
+    /// Notifications for the client, such as progress, are sent on `notify`
+    /// before the response is returned.
+    pub async fn handle_text(
+        &self,
+        text: &str,
+        notify: &mpsc::UnboundedSender<Value>,
+    ) -> Option<Value> {
+        let message: Value = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        match message {
+            Value::Array(batch) if batch.is_empty() => {
+                Some(error_response(Value::Null, INVALID_REQUEST, "Empty batch"))
+            }
+            Value::Array(batch) => {
+                let mut responses = Vec::new();
+                for message in batch {
+                    responses.extend(self.handle(message, notify).await);
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            message => self.handle(message, notify).await,
+        }
+    }
+
+    /// Handle one message, returning the response for requests
+    pub async fn handle(
+        &self,
+        message: Value,
+        notify: &mpsc::UnboundedSender<Value>,
+    ) -> Option<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests we never send, or malformed messages
+            let id = message.get("id").cloned().unwrap_or(Value::Null);
+            return (message.get("result").is_none() && message.get("error").is_none())
+                .then(|| error_response(id, INVALID_REQUEST, "Missing method"));
+        };
+        let id = message.get("id").cloned()?;
+        let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        let count = self.requests.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(n) = self.fail_every {
+            if method != "initialize" && count % n == 0 {
+                return Some(error_response(
+                    id,
+                    INTERNAL_ERROR,
+                    &format!("Induced failure (every {} requests)", n),
+                ));
+            }
+        }
+
+        let result = match method {
+            "initialize" => Ok(initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools() })),
+            "tools/call" => self.call_tool(&params, notify).await,
+            "resources/list" => Ok(json!({ "resources": resources() })),
+            "resources/read" => read_resource(&params),
+            "prompts/list" => Ok(json!({ "prompts": prompts() })),
+            "prompts/get" => get_prompt(&params),
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        params: &Value,
+        notify: &mpsc::UnboundedSender<Value>,
+    ) -> Result<Value, (i64, String)> {
+        let name = params["name"].as_str().unwrap_or_default();
+        let args = &params["arguments"];
+        let invalid = |reason: &str| (INVALID_PARAMS, format!("{}: {}", name, reason));
+
+        match name {
+            "echo" => {
+                let text = args["text"]
+                    .as_str()
+                    .ok_or_else(|| invalid("'text' is required"))?;
+                Ok(tool_result(text, false))
+            }
+            "add" => {
+                let (Some(a), Some(b)) = (args["a"].as_f64(), args["b"].as_f64()) else {
+                    return Err(invalid("'a' and 'b' must be numbers"));
+                };
+                Ok(tool_result(&(a + b).to_string(), false))
+            }
+            "countdown" => {
+                let steps = args["steps"].as_u64().unwrap_or(3).min(20);
+                let delay =
+                    Duration::from_millis(args["delay_ms"].as_u64().unwrap_or(100).min(5_000));
+                let token = params["_meta"]["progressToken"].clone();
+                for step in 1..=steps {
+                    tokio::time::sleep(delay).await;
+                    if !token.is_null() {
+                        let _ = notify.send(json!({
+                            "jsonrpc": "2.0",
+                            "method": "notifications/progress",
+                            "params": {
+                                "progressToken": token,
+                                "progress": step,
+                                "total": steps,
+                                "message": format!("{} of {}", step, steps),
+                            },
+                        }));
+                    }
+                }
+                Ok(tool_result(&format!("Done after {} steps", steps), false))
+            }
+            "fail" => {
+                let message = args["message"]
+                    .as_str()
+                    .unwrap_or("The tool failed as requested");
+                Ok(tool_result(message, true))
+            }
+            _ => Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+        }
+    }
+}
+
+fn initialize(params: &Value) -> Value {
+    // Agree to the client's version when we speak it, otherwise offer ours
+    let requested = params["protocolVersion"].as_str().unwrap_or(LATEST_VERSION);
+    let version = if SUPPORTED_VERSIONS.contains(&requested) {
+        requested
+    } else {
+        LATEST_VERSION
+    };
+    json!({
+        "protocolVersion": version,
+        "capabilities": {
+            "tools": { "listChanged": false },
+            "resources": { "subscribe": false, "listChanged": false },
+            "prompts": { "listChanged": false },
+        },
+        "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Reference server: try the echo, add, countdown and fail tools.",
+    })
+}
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "echo",
+            "description": "Return the given text",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"],
+            },
+        },
+        {
+            "name": "add",
+            "description": "Add two numbers",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+                "required": ["a", "b"],
+            },
+        },
+        {
+            "name": "countdown",
+            "description": "Count down slowly, reporting progress when a progress token is given",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "steps": { "type": "integer", "minimum": 0, "maximum": 20 },
+                    "delay_ms": { "type": "integer", "minimum": 0, "maximum": 5000 },
+                },
+            },
+        },
+        {
+            "name": "fail",
+            "description": "Return a tool error (isError) with the given message",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "message": { "type": "string" } },
+            },
+        },
+    ])
+}
+
+fn tool_result(text: &str, is_error: bool) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+fn resources() -> Value {
+    json!([
+        {
+            "uri": "demo://readme",
+            "name": "README",
+            "description": "About this server",
+            "mimeType": "text/markdown",
+        },
+        {
+            "uri": "demo://server.json",
+            "name": "Server details",
+            "mimeType": "application/json",
+        },
+    ])
+}
+
+fn read_resource(params: &Value) -> Result<Value, (i64, String)> {
+    let uri = params["uri"].as_str().unwrap_or_default();
+    let (mime_type, text) = match uri {
+        "demo://readme" => ("text/markdown", README.to_string()),
+        "demo://server.json" => (
+            "application/json",
+            json!({ "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") }).to_string(),
+        ),
+        _ => return Err((RESOURCE_NOT_FOUND, format!("Resource not found: {}", uri))),
+    };
+    Ok(json!({ "contents": [{ "uri": uri, "mimeType": mime_type, "text": text }] }))
+}
+
+fn prompts() -> Value {
+    json!([{
+        "name": "greet",
+        "description": "Ask the model to greet someone",
+        "arguments": [{ "name": "name", "description": "Who to greet", "required": true }],
+    }])
+}
+
+fn get_prompt(params: &Value) -> Result<Value, (i64, String)> {
+    match params["name"].as_str().unwrap_or_default() {
+        "greet" => {
+            let name = params["arguments"]["name"]
+                .as_str()
+                .ok_or_else(|| (INVALID_PARAMS, "greet: 'name' is required".to_string()))?;
+            Ok(json!({
+                "description": "Greeting",
+                "messages": [{
+                    "role": "user",
+                    "content": { "type": "text", "text": format!("Please greet {} warmly.", name) },
+                }],
+            }))
+        }
+        other => Err((INVALID_PARAMS, format!("Unknown prompt: {}", other))),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Serve newline-delimited JSON-RPC on stdin/stdout
+///
+/// Messages are handled concurrently, so a ping is answered while a
+/// countdown is still running.
+async fn serve_stdio(server: Arc<DemoServer>) -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = rx.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            if stdout.write_all(line.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let server = Arc::clone(&server);
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Some(response) = server.handle_text(&line, &tx).await {
+                let _ = tx.send(response);
+            }
+        });
+    }
+
+    // The writer ends once every in-flight request has answered
+    drop(tx);
+    writer.await?;
+    Ok(())
+}
+
+/// Origins and hosts accepted by a server listening on `addr`
+pub(crate) fn default_origins(addr: SocketAddr) -> OriginPolicy {
+    if addr.ip().is_loopback() {
+        OriginPolicy::localhost()
+    } else {
+        OriginPolicy::new()
+    }
+}
+
+/// Serve Streamable HTTP: POST JSON-RPC to any path, `/mcp` by convention,
+/// refusing requests whose Origin or Host `origins` does not allow
+pub(crate) async fn serve_http(
+    server: Arc<DemoServer>,
+    listener: TcpListener,
+    origins: OriginPolicy,
+) -> Result<()> {
+    let sessions = Arc::new(AtomicU64::new(0));
+    let origins = Arc::new(origins);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let server = Arc::clone(&server);
+        let sessions = Arc::clone(&sessions);
+        let origins = Arc::clone(&origins);
+        tokio::spawn(async move {
+            if let Err(e) = serve_connection(server, sessions, &origins, stream).await {
+                eprintln!("Connection from {} failed: {:#}", peer, e);
+            }
+        });
+    }
+}
+
+/// Answer HTTP/1.1 requests on one connection until the client closes it
+async fn serve_connection(
+    server: Arc<DemoServer>,
+    sessions: Arc<AtomicU64>,
+    origins: &OriginPolicy,
+    stream: TcpStream,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+        let method = request_line
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        let mut content_length = 0usize;
+        let mut accepts_sse = false;
+        let mut close = false;
+        let mut origin = None;
+        let mut host = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).await? == 0 {
+                return Ok(());
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let Some((name, value)) = header.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => {
+                    content_length = value.parse().context("Invalid Content-Length")?
+                }
+                "accept" => accepts_sse = value.contains("text/event-stream"),
+                "connection" => close = value.eq_ignore_ascii_case("close"),
+                "origin" => origin = Some(value.to_string()),
+                "host" => host = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).await?;
+
+        let response = match origins.check(origin.as_deref(), host.as_deref()) {
+            Err(rejection) => {
+                let body = json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": {
+                        "code": ORIGIN_REJECTED,
+                        "message": rejection.to_string(),
+                        "data": rejection,
+                    },
+                });
+                http_response("403 Forbidden", "", "application/json", &body.to_string())
+            }
+            Ok(()) => match method.as_str() {
+                "POST" => {
+                    let text = String::from_utf8_lossy(&body);
+                    let (tx, mut rx) = mpsc::unbounded_channel();
+                    let reply = server.handle_text(&text, &tx).await;
+                    drop(tx);
+                    let mut notifications = Vec::new();
+                    while let Some(notification) = rx.recv().await {
+                        notifications.push(notification);
+                    }
+
+                    let session = is_initialize(&text).then(|| {
+                        let n = sessions.fetch_add(1, Ordering::Relaxed);
+                        let nanos = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_nanos();
+                        format!("{:x}-{:x}", nanos, n)
+                    });
+                    let session_header = session
+                        .map(|id| format!("Mcp-Session-Id: {}\r\n", id))
+                        .unwrap_or_default();
+
+                    match reply {
+                        None => http_response("202 Accepted", &session_header, "", ""),
+                        Some(reply) if accepts_sse && !notifications.is_empty() => {
+                            let events: String = notifications
+                                .iter()
+                                .chain(std::iter::once(&reply))
+                                .map(|message| format!("event: message\ndata: {}\n\n", message))
+                                .collect();
+                            http_response("200 OK", &session_header, "text/event-stream", &events)
+                        }
+                        Some(reply) => http_response(
+                            "200 OK",
+                            &session_header,
+                            "application/json",
+                            &reply.to_string(),
+                        ),
+                    }
+                }
+                // Ending a session needs no cleanup; there is no server-initiated stream
+                "DELETE" => http_response("200 OK", "", "", ""),
+                _ => http_response("405 Method Not Allowed", "Allow: POST, DELETE\r\n", "", ""),
+            },
+        };
+
+        let stream = reader.get_mut();
+        stream.write_all(response.as_bytes()).await?;
+        stream.flush().await?;
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+fn is_initialize(text: &str) -> bool {
+    serde_json::from_str::<Value>(text).is_ok_and(|message| message["method"] == "initialize")
+}
+
+fn http_response(status: &str, extra_headers: &str, content_type: &str, body: &str) -> String {
+    let content_type = if content_type.is_empty() {
+        String::new()
+    } else {
+        format!("Content-Type: {}\r\n", content_type)
+    };
+    format!(
+        "HTTP/1.1 {}\r\n{}{}Content-Length: {}\r\n\r\n{}",
+        status,
+        content_type,
+        extra_headers,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::client::McpClientBuilder;
+    use mcp_core::messages::Implementation;
+    use mcp_core::TransportConfig;
+
+    async fn request(server: &DemoServer, method: &str, params: Value) -> (Value, Vec<Value>) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = server.handle(message, &tx).await.unwrap();
+        drop(tx);
+        let mut notifications = Vec::new();
+        while let Some(notification) = rx.recv().await {
+            notifications.push(notification);
+        }
+        (response, notifications)
+    }
+
+    #[tokio::test]
+    async fn test_tools_resources_prompts_and_errors() {
+        let server = DemoServer::new(None);
+
+        let (init, _) = request(
+            &server,
+            "initialize",
+            json!({ "protocolVersion": "2024-11-05" }),
+        )
+        .await;
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        let (init, _) = request(
+            &server,
+            "initialize",
+            json!({ "protocolVersion": "1999-01-01" }),
+        )
+        .await;
+        assert_eq!(init["result"]["protocolVersion"], LATEST_VERSION);
+
+        let (add, _) = request(
+            &server,
+            "tools/call",
+            json!({ "name": "add", "arguments": { "a": 2, "b": 3 } }),
+        )
+        .await;
+        assert_eq!(add["result"]["content"][0]["text"], "5");
+        let (fail, _) = request(&server, "tools/call", json!({ "name": "fail" })).await;
+        assert_eq!(fail["result"]["isError"], true);
+        let (missing, _) = request(
+            &server,
+            "tools/call",
+            json!({ "name": "echo", "arguments": {} }),
+        )
+        .await;
+        assert_eq!(missing["error"]["code"], INVALID_PARAMS);
+
+        let (countdown, progress) = request(
+            &server,
+            "tools/call",
+            json!({
+                "name": "countdown",
+                "arguments": { "steps": 2, "delay_ms": 0 },
+                "_meta": { "progressToken": "p1" },
+            }),
+        )
+        .await;
+        assert_eq!(
+            countdown["result"]["content"][0]["text"],
+            "Done after 2 steps"
+        );
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[1]["params"]["progress"], 2);
+
+        let (readme, _) =
+            request(&server, "resources/read", json!({ "uri": "demo://readme" })).await;
+        assert_eq!(readme["result"]["contents"][0]["text"], README);
+        let (prompt, _) = request(
+            &server,
+            "prompts/get",
+            json!({ "name": "greet", "arguments": { "name": "Ada" } }),
+        )
+        .await;
+        assert!(prompt["result"]["messages"][0]["content"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Ada"));
+
+        let (unknown, _) = request(&server, "sampling/createMessage", json!({})).await;
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let garbage = server.handle_text("{not json", &tx).await.unwrap();
+        assert_eq!(garbage["error"]["code"], PARSE_ERROR);
+        let notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert!(server.handle_text(notification, &tx).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fail_every_spares_initialize() {
+        let server = DemoServer::new(Some(2));
+        let (init, _) = request(&server, "initialize", json!({})).await;
+        assert!(init.get("result").is_some());
+        let (ping, _) = request(&server, "ping", json!({})).await;
+        assert_eq!(ping["error"]["code"], INTERNAL_ERROR);
+        let (ping, _) = request(&server, "ping", json!({})).await;
+        assert!(ping.get("result").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_http_round_trip_with_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost(),
+        ));
+
+        let config = TransportConfig::http_stream(format!("http://{}", addr)).unwrap();
+        let mut client = McpClientBuilder::new()
+            .transport(config)
+            .connect_deadline(Duration::from_secs(10))
+            .build()
+            .await
+            .unwrap();
+        let info = client
+            .connect(Implementation {
+                name: "test".to_string(),
+                version: "0.0.0".to_string(),
+                metadata: Default::default(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(info.implementation.name, SERVER_NAME);
+
+        let response = client
+            .send_request(
+                "tools/call",
+                json!({ "name": "echo", "arguments": { "text": "hi" } }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["content"][0]["text"], "hi");
+        assert_eq!(client.transport_info().metadata["has_session"], true);
+    }
+
+    #[tokio::test]
+    async fn test_rebound_http_requests_are_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost().allow_origin("https://inspector.example"),
+        ));
+
+        let post = |headers: String| async move {
+            let body = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let request = format!(
+                "POST /mcp HTTP/1.1\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                headers,
+                body.len(),
+                body
+            );
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            let (head, body) = response.split_once("\r\n\r\n").unwrap();
+            let status = head.split_whitespace().nth(1).unwrap().to_string();
+            (status, serde_json::from_str::<Value>(body).unwrap())
+        };
+
+        let local = format!("Host: {}\r\n", addr);
+        let (status, _) = post(local.clone()).await;
+        assert_eq!(status, "200");
+        let (status, _) = post(format!("{}Origin: https://inspector.example\r\n", local)).await;
+        assert_eq!(status, "200");
+
+        // evil.example rebound to 127.0.0.1
+        let (status, body) = post(format!(
+            "Host: evil.example:{}\r\nOrigin: http://evil.example:{}\r\n",
+            addr.port(),
+            addr.port()
+        ))
+        .await;
+        assert_eq!(status, "403");
+        assert_eq!(body["error"]["code"], ORIGIN_REJECTED);
+        assert_eq!(body["error"]["data"]["reason"], "host_not_allowed");
+
+        let (status, body) = post(format!("{}Origin: http://evil.example\r\n", local)).await;
+        assert_eq!(status, "403");
+        assert_eq!(body["error"]["data"]["reason"], "origin_not_allowed");
+        assert_eq!(body["error"]["data"]["origin"], "http://evil.example");
+    }
+}
@@ -0,0 +1,129 @@
+//! Compare a server's behaviour across two MCP protocol versions
+
+use anyhow::{bail, Context, Result};
+use mcp_core::differential::{self, DifferentialOptions, SampleCall};
+use mcp_core::messages::ProtocolVersion;
+use mcp_core::TransportConfig;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Options for `assist-mcp diff-versions`
+pub struct DiffOptions {
+    pub profile: Option<PathBuf>,
+    pub url: Option<String>,
+    pub command: Vec<String>,
+    pub left: String,
+    pub right: String,
+    pub calls: Vec<String>,
+    pub timeout: u64,
+    pub json: bool,
+}
+
+/// Run both sessions and print the divergences
+///
+/// Fails when the sessions diverge so the command can gate CI.
+pub async fn run_diff(options: DiffOptions) -> Result<()> {
+    let transport = transport_config(&options)?;
+
+    let mut calls = vec![SampleCall::new("ping", serde_json::json!({}))];
+    for spec in &options.calls {
+        calls.push(parse_call(spec)?);
+    }
+
+    let differential_options = DifferentialOptions {
+        left: parse_version(&options.left)?,
+        right: parse_version(&options.right)?,
+        calls,
+        timeout: Duration::from_secs(options.timeout),
+        ..Default::default()
+    };
+
+    let report = differential::run(transport, differential_options).await?;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{} vs {} ({} {})",
+            report.left.requested,
+            report.right.requested,
+            report.left.server.name,
+            report.left.server.version
+        );
+        for note in &report.notes {
+            println!("note: {}", note);
+        }
+        for divergence in &report.divergences {
+            println!("  {}", divergence);
+        }
+    }
+
+    if !report.is_consistent() {
+        bail!("{} divergence(s) found", report.divergences.len());
+    }
+    if !options.json {
+        println!("No divergences found");
+    }
+    Ok(())
+}
+
+fn transport_config(options: &DiffOptions) -> Result<TransportConfig> {
+    match (
+        &options.profile,
+        &options.url,
+        options.command.split_first(),
+    ) {
+        (Some(profile), None, None) => TransportConfig::from_file(profile)
+            .with_context(|| format!("Failed to load profile {}", profile.display())),
+        (None, Some(url), None) => Ok(TransportConfig::http_stream(url)?),
+        (None, None, Some((program, args))) => Ok(TransportConfig::stdio(program, args)),
+        _ => bail!("Specify exactly one of --profile, --url or a server command after --"),
+    }
+}
+
+fn parse_version(version: &str) -> Result<ProtocolVersion> {
+    Ok(serde_json::from_value(Value::String(version.to_string()))?)
+}
+
+/// Parse `METHOD` or `METHOD=JSON` (e.g. `tools/call={"name":"echo","arguments":{}}`)
+fn parse_call(spec: &str) -> Result<SampleCall> {
+    let (method, params) = match spec.split_once('=') {
+        Some((method, params)) => (
+            method,
+            serde_json::from_str(params)
+                .with_context(|| format!("Invalid JSON parameters in --call '{}'", spec))?,
+        ),
+        None => (spec, serde_json::json!({})),
+    };
+
+    if method.is_empty() {
+        bail!("Invalid --call '{}', expected METHOD or METHOD=JSON", spec);
+    }
+    Ok(SampleCall::new(method, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_call_and_version() {
+        let call = parse_call(r#"tools/call={"name":"echo"}"#).unwrap();
+        assert_eq!(call.label(), "tools/call echo");
+        assert_eq!(
+            parse_call("resources/list").unwrap().params,
+            serde_json::json!({})
+        );
+        assert!(parse_call("tools/call={oops").is_err());
+
+        assert_eq!(
+            parse_version("2024-11-05").unwrap(),
+            ProtocolVersion::V2024_11_05
+        );
+        assert_eq!(
+            parse_version("2025-06-18").unwrap(),
+            ProtocolVersion::Custom("2025-06-18".to_string())
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! `assist-mcp discover`: browse mDNS for MCP servers and save their profiles
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use mcp_core::discovery;
+
+/// List servers advertised on the local network, optionally saving a profile for each
+pub async fn run_discover(timeout: u64, save: Option<PathBuf>, force: bool) -> Result<()> {
+    println!("Browsing for MCP servers ({}s)...", timeout);
+    let servers = discovery::browse(Duration::from_secs(timeout)).await?;
+
+    if servers.is_empty() {
+        println!("No MCP servers found on the local network");
+        return Ok(());
+    }
+
+    println!("{:<24} {:<12} URL", "NAME", "TRANSPORT");
+    for server in &servers {
+        println!(
+            "{:<24} {:<12} {}",
+            server.name,
+            server.transport,
+            server.url()
+        );
+    }
+
+    if let Some(dir) = save {
+        std::fs::create_dir_all(&dir)?;
+        for server in &servers {
+            let config = match server.to_transport_config() {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", server.name, e);
+                    continue;
+                }
+            };
+            let Some(path) = profile_path(&dir, &server.name) else {
+                eprintln!("Skipping {:?}: not usable as a profile name", server.name);
+                continue;
+            };
+            if path.exists() && !force {
+                eprintln!(
+                    "Skipping {}: profile already exists at {} (use --force to overwrite)",
+                    server.name,
+                    path.display()
+                );
+                continue;
+            }
+            config.to_file(&path)?;
+            println!("Saved profile {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Where to save the profile of a server advertised as `name`.
+///
+/// Anyone on the network can advertise any instance name, so the name is
+/// reduced to `[A-Za-z0-9._-]` before it becomes a file name. Names that end
+/// up empty or start with a dot are refused.
+fn profile_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    let file_stem: String = name
+        .chars()
+        .map(|c| match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '.' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+    if file_stem.is_empty() || file_stem.starts_with('.') {
+        return None;
+    }
+    Some(dir.join(format!("{}.toml", file_stem)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_path_stays_in_save_dir() {
+        let dir = Path::new("/profiles");
+        assert_eq!(
+            profile_path(dir, "files on laptop").unwrap(),
+            Path::new("/profiles/files_on_laptop.toml")
+        );
+        assert_eq!(
+            profile_path(dir, "x/../../etc/cron.d/y").unwrap(),
+            Path::new("/profiles/x_.._.._etc_cron.d_y.toml")
+        );
+        assert_eq!(
+            profile_path(dir, "/root/.bashrc").unwrap(),
+            Path::new("/profiles/_root_.bashrc.toml")
+        );
+        assert!(profile_path(dir, "").is_none());
+        assert!(profile_path(dir, "..").is_none());
+        assert!(profile_path(dir, "../../etc/passwd").is_none());
+        assert!(profile_path(dir, ".hidden").is_none());
+    }
+}
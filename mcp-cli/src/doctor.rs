@@ -0,0 +1,680 @@
+//! `assist-mcp doctor`: check the local setup and say how to fix it
+//!
+//! The checks cover the monitor's IPC endpoint (socket directory
+//! permissions, stale or foreign sockets, whether a monitor answers), the
+//! server registry and saved profiles, and every configured server: stdio
+//! commands through the [`prerequisites`] checks, HTTP endpoints through a
+//! [`HealthChecker`] probe, and finally the MCP handshake. Each finding that
+//! is not ok carries a fix.
+
+use crate::output::{report, Failure, OutputFormat, Status};
+use futures::future::join_all;
+use mcp_common::IpcEndpoint;
+use mcp_core::health::{HealthChecker, HealthConfig, Probe, DEFAULT_HEALTH_PATH};
+use mcp_core::registry::ServerRegistry;
+use mcp_core::transport::prerequisites;
+use mcp_core::TransportConfig;
+use serde_json::{json, Value};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Options for `assist-mcp doctor`
+pub struct DoctorOptions {
+    pub ipc_socket: String,
+    /// Only check these profiles; every configured one when empty
+    pub profiles: Vec<String>,
+    /// Leave out the MCP handshake, which starts stdio servers
+    pub no_handshake: bool,
+    pub timeout: Duration,
+    pub format: OutputFormat,
+}
+
+/// How a check came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Ok,
+    Warning,
+    Problem,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Ok => "ok",
+            Level::Warning => "warning",
+            Level::Problem => "problem",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Level::Ok => "ok",
+            Level::Warning => "WARN",
+            Level::Problem => "FAIL",
+        }
+    }
+}
+
+/// The result of one check
+#[derive(Debug, Clone)]
+struct Finding {
+    level: Level,
+    check: String,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(check: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            level: Level::Ok,
+            check: check.into(),
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(
+        check: impl Into<String>,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            level: Level::Warning,
+            check: check.into(),
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn problem(
+        check: impl Into<String>,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            level: Level::Problem,
+            check: check.into(),
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Findings about one thing: the monitor, the registry or a profile
+struct Section {
+    name: String,
+    findings: Vec<Finding>,
+}
+
+/// A configured server to check
+struct Target {
+    name: String,
+    /// What [`crate::call::connect`] resolves to this server
+    server: String,
+    config: anyhow::Result<TransportConfig>,
+    /// Where the profile is defined, for fixes
+    source: String,
+}
+
+/// Run every check and print the findings
+pub async fn run_doctor(options: DoctorOptions) -> Status {
+    report(options.format, doctor(&options).await)
+}
+
+async fn doctor(options: &DoctorOptions) -> Result<(String, Status), Failure> {
+    let mut sections = vec![Section {
+        name: "monitor".to_string(),
+        findings: check_monitor(&options.ipc_socket, options.timeout).await,
+    }];
+
+    let (findings, mut targets) = configured_servers();
+    sections.push(Section {
+        name: "configuration".to_string(),
+        findings,
+    });
+    if !options.profiles.is_empty() {
+        for wanted in &options.profiles {
+            if !targets.iter().any(|target| &target.name == wanted) {
+                sections[1].findings.push(Finding::problem(
+                    format!("profile {}", wanted),
+                    "not in the registry or the saved profiles",
+                    "add it with `assist-mcp add` or to servers.toml (see `assist-mcp servers`)",
+                ));
+            }
+        }
+        targets.retain(|target| options.profiles.contains(&target.name));
+    }
+
+    let checks = targets
+        .iter()
+        .map(|target| check_profile(target, options.timeout, !options.no_handshake));
+    for (target, findings) in targets.iter().zip(join_all(checks).await) {
+        sections.push(Section {
+            name: format!("profile {}", target.name),
+            findings,
+        });
+    }
+
+    let document = document(&sections);
+    let status = if document["problems"].as_u64() == Some(0) {
+        Status::Success
+    } else {
+        Status::Unhealthy
+    };
+    Ok((options.format.render(&document, summary), status))
+}
+
+async fn check_monitor(endpoint: &str, timeout: Duration) -> Vec<Finding> {
+    let parsed = match IpcEndpoint::parse(endpoint) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return vec![Finding::problem(
+                "endpoint",
+                format!("{:#}", e),
+                "use a socket path, pipe:NAME or tcp:127.0.0.1:PORT",
+            )]
+        }
+    };
+    let mut findings = Vec::new();
+    if let IpcEndpoint::Unix(path) = &parsed {
+        match check_socket_path(Path::new(path)) {
+            Ok(finding) => findings.push(finding),
+            Err(finding) => return vec![finding],
+        }
+    }
+
+    let error = match crate::status::handshake(endpoint, timeout).await {
+        Ok(version) => {
+            findings.push(Finding::ok(
+                "monitor",
+                format!("answering at {} (IPC protocol {})", parsed, version),
+            ));
+            return findings;
+        }
+        Err(error) => error,
+    };
+    let kind = error
+        .downcast_ref::<std::io::Error>()
+        .map(std::io::Error::kind);
+    findings.push(match (kind, &parsed) {
+        (Some(ErrorKind::NotFound), _) | (Some(ErrorKind::ConnectionRefused), IpcEndpoint::Tcp(_)) => {
+            Finding::warning(
+                "monitor",
+                format!("none listening at {}; proxies run without one", parsed),
+                "start one with `assist-mcp monitor`",
+            )
+        }
+        (Some(ErrorKind::ConnectionRefused), _) => Finding::problem(
+            "monitor",
+            format!("stale socket at {}: nothing is listening on it", parsed),
+            format!(
+                "remove it (`rm {}`) or start `assist-mcp monitor`, which replaces it",
+                parsed
+            ),
+        ),
+        (Some(ErrorKind::PermissionDenied), _) => Finding::problem(
+            "monitor",
+            format!("permission denied connecting to {}", parsed),
+            "the socket belongs to another user; run as that user or pass another --ipc-socket",
+        ),
+        _ => Finding::problem(
+            "monitor",
+            format!("{} did not complete the IPC handshake: {:#}", parsed, error),
+            "make sure the monitor and proxies are the same genmcp version, and that nothing else uses this endpoint",
+        ),
+    });
+    findings
+}
+
+/// The socket's directory must be writable for the monitor to create it, and
+/// whatever is at the path must be a socket
+fn check_socket_path(path: &Path) -> Result<Finding, Finding> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        return Err(Finding::problem(
+            "socket directory",
+            format!("{} does not exist", dir.display()),
+            format!(
+                "create it (`mkdir -p {}`) or pass another --ipc-socket",
+                dir.display()
+            ),
+        ));
+    }
+    if let Err(e) = probe_writable(dir) {
+        return Err(Finding::problem(
+            "socket directory",
+            format!("cannot create files in {}: {}", dir.display(), e),
+            format!(
+                "make it writable (`chmod u+w {}`) or pass another --ipc-socket",
+                dir.display()
+            ),
+        ));
+    }
+
+    match std::fs::symlink_metadata(path) {
+        Err(_) => Ok(Finding::ok(
+            "socket directory",
+            format!("{} is writable", dir.display()),
+        )),
+        Ok(metadata) if is_socket(&metadata) => {
+            Ok(Finding::ok("socket", format!("{} exists", path.display())))
+        }
+        Ok(_) => Err(Finding::problem(
+            "socket",
+            format!("{} exists but is not a socket", path.display()),
+            format!(
+                "move the file away or pass another --ipc-socket (the monitor would delete {})",
+                path.display()
+            ),
+        )),
+    }
+}
+
+fn probe_writable(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".assist-mcp-doctor-{}", std::process::id()));
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)?;
+    std::fs::remove_file(&probe)
+}
+
+#[cfg(unix)]
+fn is_socket(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    metadata.file_type().is_socket()
+}
+
+#[cfg(not(unix))]
+fn is_socket(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
+/// Registry and saved profiles, with findings about loading them
+fn configured_servers() -> (Vec<Finding>, Vec<Target>) {
+    let mut findings = Vec::new();
+    let mut targets = Vec::new();
+
+    let registry_path = ServerRegistry::default_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "servers.toml".to_string());
+    match ServerRegistry::load_default() {
+        Ok(registry) if registry.is_empty() => findings.push(Finding::ok(
+            "registry",
+            format!("no profiles in {}", registry_path),
+        )),
+        Ok(registry) => {
+            findings.push(Finding::ok(
+                "registry",
+                format!(
+                    "{} profile(s) in {}",
+                    registry.profiles().count(),
+                    registry_path
+                ),
+            ));
+            for (name, _) in registry.profiles() {
+                targets.push(Target {
+                    name: name.to_string(),
+                    server: name.to_string(),
+                    config: registry.resolve(name).map_err(Into::into),
+                    source: format!("[server.{}] in {}", name, registry_path),
+                });
+            }
+        }
+        Err(e) => findings.push(Finding::problem(
+            "registry",
+            format!("{} does not load: {}", registry_path, e),
+            format!("fix the syntax of {}", registry_path),
+        )),
+    }
+
+    let dir = match crate::install::profiles_dir() {
+        Ok(dir) => dir,
+        Err(_) => return (findings, targets),
+    };
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    files.sort();
+    if !files.is_empty() {
+        findings.push(Finding::ok(
+            "saved profiles",
+            format!("{} in {}", files.len(), dir.display()),
+        ));
+    }
+    for path in files {
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        // Registry names win when both exist, as in `call --server`
+        if targets.iter().any(|target| target.name == name) {
+            findings.push(Finding::warning(
+                format!("profile {}", name),
+                format!(
+                    "{} is shadowed by the registry profile of that name",
+                    path.display()
+                ),
+                "rename one of them",
+            ));
+            continue;
+        }
+        targets.push(Target {
+            name: name.to_string(),
+            server: path.display().to_string(),
+            config: TransportConfig::from_file(&path).map_err(Into::into),
+            source: path.display().to_string(),
+        });
+    }
+    (findings, targets)
+}
+
+async fn check_profile(target: &Target, timeout: Duration, handshake: bool) -> Vec<Finding> {
+    let config = match &target.config {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![Finding::problem(
+                "configuration",
+                format!("{:#}", e),
+                format!("fix {}", target.source),
+            )]
+        }
+    };
+
+    let mut findings = match config {
+        TransportConfig::Stdio(stdio) => {
+            let mut findings: Vec<Finding> = prerequisites::check(stdio)
+                .await
+                .into_iter()
+                .map(|check| match check.remedy {
+                    None if check.passed => Finding::ok(check.subject, check.detail),
+                    remedy => Finding::problem(
+                        check.subject,
+                        check.detail,
+                        remedy.unwrap_or_else(|| format!("fix {}", target.source)),
+                    ),
+                })
+                .collect();
+            // A shell command's program is only found once the shell runs it
+            if let Some(program) = shell_program(stdio) {
+                findings.push(match prerequisites::find_on_path(program) {
+                    Some(path) => {
+                        Finding::ok(format!("command {}", program), path.display().to_string())
+                    }
+                    None => Finding::problem(
+                        format!("command {}", program),
+                        "not found on PATH",
+                        prerequisites::install_hint(program)
+                            .map(str::to_string)
+                            .unwrap_or_else(|| {
+                                format!("install {} and make sure it is on PATH", program)
+                            }),
+                    ),
+                });
+            }
+            findings
+        }
+        TransportConfig::HttpSse(http) => {
+            let probe = http
+                .base_url
+                .join(DEFAULT_HEALTH_PATH)
+                .ok()
+                .map(Probe::Http);
+            vec![check_reachable(http.base_url.as_str(), probe, timeout).await]
+        }
+        TransportConfig::HttpStream(http) => {
+            let probe = http
+                .base_url
+                .join(DEFAULT_HEALTH_PATH)
+                .ok()
+                .map(Probe::Http);
+            vec![check_reachable(http.base_url.as_str(), probe, timeout).await]
+        }
+        _ => Vec::new(),
+    };
+
+    let ready = findings
+        .iter()
+        .all(|finding| finding.level != Level::Problem);
+    if handshake && ready {
+        findings.push(check_handshake(&target.server, timeout).await);
+    }
+    findings
+}
+
+/// The program a `sh -c` command line starts, past any `VAR=value` prefixes
+fn shell_program(config: &mcp_core::transport::StdioConfig) -> Option<&str> {
+    match (config.command.as_str(), config.args.as_slice()) {
+        ("sh" | "bash", [flag, line, ..]) if flag == "-c" => line
+            .split_whitespace()
+            .find(|word| !word.contains('=') || word.starts_with('/')),
+        _ => None,
+    }
+}
+
+/// Whether the server's host answers HTTP at all, probed like the health
+/// checks of a session
+async fn check_reachable(base_url: &str, probe: Option<Probe>, timeout: Duration) -> Finding {
+    let Some(probe) = probe else {
+        return Finding::problem(
+            "reachable",
+            format!("{} has no host", base_url),
+            "use an absolute http(s) URL",
+        );
+    };
+    let checker = HealthChecker::new(HealthConfig::default().timeout(timeout), probe);
+    match checker.probe().await {
+        Ok(latency) => Finding::ok(
+            "reachable",
+            format!("{} answered in {}ms", base_url, latency.as_millis()),
+        ),
+        Err(reason) => Finding::problem(
+            "reachable",
+            reason,
+            format!(
+                "check that the server is running and that {} is right, including its port",
+                base_url
+            ),
+        ),
+    }
+}
+
+/// Connect, initialize and check the protocol version the server agreed to
+async fn check_handshake(server: &str, timeout: Duration) -> Finding {
+    let client = match crate::call::connect(server, timeout).await {
+        Ok(client) => client,
+        Err(failure) => {
+            let detail = format!("{:#}", failure.error);
+            let fix = if detail.contains("401") || detail.contains("403") {
+                "check the profile's auth credentials".to_string()
+            } else if detail.to_lowercase().contains("timed out") {
+                format!(
+                    "the server did not finish initializing within {}s; run it by hand to see its output, or raise --timeout",
+                    timeout.as_secs()
+                )
+            } else {
+                format!("run `assist-mcp probe --server {}` for details", server)
+            };
+            return Finding::problem("handshake", detail, fix);
+        }
+    };
+    let info = client.server_info().await;
+    crate::call::disconnect(client).await;
+
+    let Some(info) = info else {
+        return Finding::problem(
+            "handshake",
+            "the server did not report its info",
+            format!("run `assist-mcp probe --server {}` for details", server),
+        );
+    };
+    let detail = format!(
+        "{} {} speaks protocol {}",
+        info.implementation.name, info.implementation.version, info.protocol_version
+    );
+    if info.protocol_version.is_supported() {
+        Finding::ok("handshake", detail)
+    } else {
+        Finding::warning(
+            "handshake",
+            detail,
+            "upgrade the server or genmcp so both speak a common protocol version",
+        )
+    }
+}
+
+fn document(sections: &[Section]) -> Value {
+    let count = |level| {
+        sections
+            .iter()
+            .flat_map(|section| &section.findings)
+            .filter(|finding| finding.level == level)
+            .count()
+    };
+    let sections: Vec<Value> = sections
+        .iter()
+        .map(|section| {
+            let findings: Vec<Value> = section
+                .findings
+                .iter()
+                .map(|finding| {
+                    json!({
+                        "check": finding.check,
+                        "status": finding.level.name(),
+                        "detail": finding.detail,
+                        "fix": finding.fix,
+                    })
+                })
+                .collect();
+            json!({ "name": section.name, "checks": findings })
+        })
+        .collect();
+    json!({
+        "sections": sections,
+        "problems": count(Level::Problem),
+        "warnings": count(Level::Warning),
+    })
+}
+
+/// Findings grouped by section, each fix under its finding
+fn summary(document: &Value) -> String {
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let mut out = String::new();
+    let mut checks = 0;
+    for section in document["sections"].as_array().into_iter().flatten() {
+        out.push_str(&format!("{}\n", text(&section["name"])));
+        for check in section["checks"].as_array().into_iter().flatten() {
+            checks += 1;
+            let label = match check["status"].as_str() {
+                Some("problem") => Level::Problem.label(),
+                Some("warning") => Level::Warning.label(),
+                _ => Level::Ok.label(),
+            };
+            out.push_str(&format!(
+                "  {:<4}  {}: {}\n",
+                label,
+                text(&check["check"]),
+                text(&check["detail"])
+            ));
+            if let Some(fix) = check["fix"].as_str() {
+                out.push_str(&format!("        fix: {}\n", fix));
+            }
+        }
+    }
+    out.push_str(&format!(
+        "{} checks: {} warning(s), {} problem(s)\n",
+        checks, document["warnings"], document["problems"]
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo_server::{serve_http, DemoServer};
+    use mcp_core::transport::origin::OriginPolicy;
+    use mcp_core::transport::StdioConfig;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    fn target(server: &str, config: TransportConfig) -> Target {
+        Target {
+            name: "test".to_string(),
+            server: server.to_string(),
+            config: Ok(config),
+            source: "servers.toml".to_string(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stale_and_missing_sockets_are_told_apart() {
+        let path = std::env::temp_dir().join(format!("doctor-{}.sock", std::process::id()));
+        let endpoint = path.display().to_string();
+
+        let findings = check_monitor(&endpoint, Duration::from_secs(5)).await;
+        assert_eq!(findings.last().unwrap().level, Level::Warning);
+
+        // A listener that went away leaves its socket file behind
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let findings = check_monitor(&endpoint, Duration::from_secs(5)).await;
+        std::fs::remove_file(&path).unwrap();
+        let stale = findings.last().unwrap();
+        assert_eq!(stale.level, Level::Problem);
+        assert!(stale.detail.starts_with("stale socket"), "{}", stale.detail);
+        assert!(stale.fix.as_deref().unwrap().contains("rm "));
+    }
+
+    #[tokio::test]
+    async fn test_profiles_are_checked_up_to_the_handshake() {
+        let missing = StdioConfig::new("sh").args(["-c", "FOO=1 no-such-mcp-server --stdio"]);
+        let findings = check_profile(
+            &target("missing", TransportConfig::Stdio(missing)),
+            Duration::from_secs(5),
+            true,
+        )
+        .await;
+        let problem = findings.last().unwrap();
+        assert_eq!(problem.check, "command no-such-mcp-server");
+        assert_eq!(problem.level, Level::Problem);
+        assert!(!findings.iter().any(|finding| finding.check == "handshake"));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost(),
+        ));
+        let config = TransportConfig::http_stream(url.as_str()).unwrap();
+        let findings = check_profile(&target(&url, config), Duration::from_secs(10), true).await;
+        let checks: Vec<_> = findings
+            .iter()
+            .map(|finding| (finding.check.as_str(), finding.level))
+            .collect();
+        assert_eq!(
+            checks,
+            [("reachable", Level::Ok), ("handshake", Level::Ok)],
+            "{:?}",
+            findings
+        );
+
+        let text = summary(&document(&[Section {
+            name: "profile test".to_string(),
+            findings,
+        }]));
+        assert!(
+            text.starts_with("profile test\n  ok    reachable: "),
+            "{}",
+            text
+        );
+        assert!(text.ends_with("2 checks: 0 warning(s), 0 problem(s)\n"));
+    }
+}
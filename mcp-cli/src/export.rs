@@ -0,0 +1,367 @@
+//! Bulk download of a server's resources into a directory
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use futures::stream::{self, StreamExt};
+use mcp_core::client::McpClientBuilder;
+use mcp_core::messages::{Implementation, JsonRpcRequest};
+use mcp_core::quirks::QuirkDatabase;
+use mcp_core::registry::ServerRegistry;
+use mcp_core::transport::Transport;
+use mcp_core::TransportConfig;
+use mcp_transport::interceptors::policy::glob_match;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// Name of the manifest written next to the exported files
+const MANIFEST: &str = "manifest.json";
+
+/// Options for `assist-mcp export`
+pub struct ExportOptions {
+    pub server: String,
+    pub patterns: Vec<String>,
+    pub out: PathBuf,
+    pub concurrency: usize,
+    pub timeout: u64,
+}
+
+/// A resource advertised by `resources/list`
+struct Listed {
+    uri: String,
+    mime_type: Option<String>,
+}
+
+/// Export every resource matching the patterns, then write the manifest
+///
+/// Fails after writing the manifest when any resource could not be exported.
+pub async fn run_export(options: ExportOptions) -> Result<()> {
+    let (config, profile) = resolve_server(&options.server)?;
+    let timeout = Duration::from_secs(options.timeout);
+
+    let mut builder = McpClientBuilder::new()
+        .transport(config)
+        .connect_deadline(timeout)
+        .request_timeout(timeout)
+        .quirks(QuirkDatabase::load_default()?);
+    if let Some(profile) = profile {
+        builder = builder.profile(profile);
+    }
+    let mut client = builder.build().await?;
+    client
+        .connect(Implementation {
+            name: "assist-mcp".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            metadata: HashMap::new(),
+        })
+        .await
+        .with_context(|| format!("Failed to connect to '{}'", options.server))?;
+
+    let listed = list_resources(&mut client).await?;
+    let selected: Vec<Listed> = listed
+        .into_iter()
+        .filter(|resource| matches_any(&options.patterns, &resource.uri))
+        .collect();
+    println!(
+        "Exporting {} resource(s) to {}",
+        selected.len(),
+        options.out.display()
+    );
+
+    std::fs::create_dir_all(&options.out)
+        .with_context(|| format!("Failed to create {}", options.out.display()))?;
+
+    // Reads share the connection; each file is written as soon as it arrives
+    let transport = client.transport();
+    let mut paths = PathAllocator::default();
+    let mut entries = Vec::new();
+    let mut failures = Vec::new();
+    let mut reads = stream::iter(selected.into_iter().enumerate())
+        .map(|(i, resource)| {
+            let transport = transport.clone();
+            async move {
+                let contents = read_resource(&*transport, i, &resource.uri, timeout).await;
+                (resource, contents)
+            }
+        })
+        .buffer_unordered(options.concurrency.max(1));
+
+    while let Some((resource, contents)) = reads.next().await {
+        let contents = match contents {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("  failed {}: {:#}", resource.uri, e);
+                failures.push(json!({ "uri": resource.uri, "error": format!("{:#}", e) }));
+                continue;
+            }
+        };
+        for content in contents {
+            let uri = content.uri.unwrap_or_else(|| resource.uri.clone());
+            let relative = paths.allocate(&uri);
+            let path = options.out.join(&relative);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, &content.data)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("  {} -> {}", uri, relative.display());
+            entries.push(json!({
+                "uri": uri,
+                "path": relative.to_string_lossy().replace('\\', "/"),
+                "mimeType": content.mime_type.or_else(|| resource.mime_type.clone()),
+                "bytes": content.data.len(),
+            }));
+        }
+    }
+    drop(reads);
+
+    let manifest = json!({
+        "server": options.server,
+        "patterns": options.patterns,
+        "exportedAt": chrono::Utc::now().to_rfc3339(),
+        "resources": entries,
+        "failed": failures,
+    });
+    std::fs::write(
+        options.out.join(MANIFEST),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    if let Err(e) = client.disconnect().await {
+        eprintln!("Warning: failed to close session: {}", e);
+    }
+
+    println!(
+        "Exported {} file(s); manifest at {}",
+        entries.len(),
+        options.out.join(MANIFEST).display()
+    );
+    if !failures.is_empty() {
+        bail!("{} resource(s) could not be exported", failures.len());
+    }
+    Ok(())
+}
+
+/// Resolve `--server` as an HTTP URL, a registry profile, a profile file or a
+/// profile saved by `add`; registry profiles also return their name
+pub(crate) fn resolve_server(server: &str) -> Result<(TransportConfig, Option<String>)> {
+    if server.starts_with("http://") || server.starts_with("https://") {
+        return Ok((TransportConfig::http_stream(server)?, None));
+    }
+    let registry = ServerRegistry::load_default()?;
+    if registry.contains(server) {
+        return Ok((registry.resolve(server)?, Some(server.to_string())));
+    }
+    let path = Path::new(server);
+    let path = if path.exists() {
+        path.to_path_buf()
+    } else {
+        crate::install::profiles_dir()?.join(format!("{}.toml", server))
+    };
+    let config = TransportConfig::from_file(&path).with_context(|| {
+        format!(
+            "Failed to load profile for '{}' from {}",
+            server,
+            path.display()
+        )
+    })?;
+    Ok((config, None))
+}
+
+/// Page through `resources/list`
+async fn list_resources(client: &mut mcp_core::client::McpClient) -> Result<Vec<Listed>> {
+    let mut resources = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let response = client.send_request("resources/list", params).await?;
+        if let Some(error) = response.error {
+            bail!("resources/list failed: {}", error.message);
+        }
+        let result = response.result.unwrap_or(Value::Null);
+        for resource in result["resources"].as_array().into_iter().flatten() {
+            if let Some(uri) = resource["uri"].as_str() {
+                resources.push(Listed {
+                    uri: uri.to_string(),
+                    mime_type: resource["mimeType"].as_str().map(str::to_string),
+                });
+            }
+        }
+        match result["nextCursor"].as_str() {
+            Some(next) if cursor.as_deref() != Some(next) => cursor = Some(next.to_string()),
+            _ => return Ok(resources),
+        }
+    }
+}
+
+/// One entry of a `resources/read` result, decoded
+struct Content {
+    uri: Option<String>,
+    mime_type: Option<String>,
+    data: Vec<u8>,
+}
+
+async fn read_resource(
+    transport: &dyn Transport,
+    index: usize,
+    uri: &str,
+    timeout: Duration,
+) -> Result<Vec<Content>> {
+    let request = JsonRpcRequest::new(
+        format!("export-{}", index),
+        "resources/read",
+        json!({ "uri": uri }),
+    );
+    let response = transport.send_request(request, Some(timeout)).await?;
+    if let Some(error) = response.error {
+        bail!("{}", error.message);
+    }
+    let result = response.result.unwrap_or(Value::Null);
+    result["contents"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(decode_content)
+        .collect()
+}
+
+fn decode_content(content: &Value) -> Result<Content> {
+    let data = if let Some(text) = content["text"].as_str() {
+        text.as_bytes().to_vec()
+    } else if let Some(blob) = content["blob"].as_str() {
+        base64::engine::general_purpose::STANDARD
+            .decode(blob)
+            .context("Invalid base64 blob")?
+    } else {
+        bail!("Resource content has neither text nor blob");
+    };
+    Ok(Content {
+        uri: content["uri"].as_str().map(str::to_string),
+        mime_type: content["mimeType"].as_str().map(str::to_string),
+        data,
+    })
+}
+
+/// Whether `uri` or its export path matches one of the patterns (all when empty)
+fn matches_any(patterns: &[String], uri: &str) -> bool {
+    let relative = uri_to_path(uri);
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    patterns.is_empty()
+        || patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, uri) || glob_match(pattern, &relative))
+}
+
+/// Relative file path mirroring a URI: the scheme is dropped, the authority
+/// becomes the top directory and unsafe components are neutralized
+fn uri_to_path(uri: &str) -> PathBuf {
+    let rest = match uri.split_once("://") {
+        Some((_, rest)) => rest,
+        None => uri.split_once(':').map_or(uri, |(_, rest)| rest),
+    };
+
+    let mut path = PathBuf::new();
+    for segment in rest.split('/') {
+        let segment: String = segment
+            .chars()
+            .map(|c| match c {
+                ':' | '*' | '?' | '"' | '<' | '>' | '|' | '\\' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+        match Path::new(&segment).components().next() {
+            Some(Component::Normal(_)) => path.push(segment),
+            _ => continue,
+        }
+    }
+
+    if rest.ends_with('/') || path.as_os_str().is_empty() {
+        path.push("index");
+    }
+    path
+}
+
+/// Hands out distinct paths, suffixing `~N` when two URIs map to the same file
+#[derive(Default)]
+struct PathAllocator {
+    taken: HashSet<PathBuf>,
+}
+
+impl PathAllocator {
+    fn allocate(&mut self, uri: &str) -> PathBuf {
+        let base = uri_to_path(uri);
+        let mut candidate = base.clone();
+        let mut n = 1;
+        while candidate == Path::new(MANIFEST) || !self.taken.insert(candidate.clone()) {
+            let mut name = base.file_name().unwrap_or_default().to_os_string();
+            name.push(format!("~{}", n));
+            candidate = base.with_file_name(name);
+            n += 1;
+        }
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_to_path_keeps_structure_and_stays_inside() {
+        assert_eq!(
+            uri_to_path("file:///repo/docs/a.md"),
+            Path::new("repo/docs/a.md")
+        );
+        assert_eq!(
+            uri_to_path("github+repo://org/x/README.md"),
+            Path::new("org/x/README.md")
+        );
+        assert_eq!(
+            uri_to_path("test://static/resource/1"),
+            Path::new("static/resource/1")
+        );
+        assert_eq!(
+            uri_to_path("file:///../../etc/passwd"),
+            Path::new("etc/passwd")
+        );
+        assert_eq!(
+            uri_to_path("https://host/docs/"),
+            Path::new("host/docs/index")
+        );
+        assert_eq!(uri_to_path("db://h/q?id=1"), Path::new("h/q_id=1"));
+    }
+
+    #[test]
+    fn test_patterns_and_collisions() {
+        let patterns = vec!["docs/**".to_string()];
+        assert!(matches_any(&patterns, "docs://docs/guide/intro.md"));
+        assert!(matches_any(
+            &["file:///repo/*".to_string()],
+            "file:///repo/a"
+        ));
+        assert!(!matches_any(&patterns, "file:///src/main.rs"));
+        assert!(matches_any(&[], "anything://x"));
+
+        let mut paths = PathAllocator::default();
+        assert_eq!(paths.allocate("a://x/f"), Path::new("x/f"));
+        assert_eq!(paths.allocate("b://x/f"), Path::new("x/f~1"));
+        assert_eq!(
+            paths.allocate("file:///manifest.json"),
+            Path::new("manifest.json~1")
+        );
+    }
+
+    #[test]
+    fn test_decode_content() {
+        let text = decode_content(&json!({ "uri": "a://b", "text": "hi" })).unwrap();
+        assert_eq!(text.data, b"hi");
+        let blob = decode_content(&json!({ "blob": "AAEC", "mimeType": "x/y" })).unwrap();
+        assert_eq!(blob.data, vec![0, 1, 2]);
+        assert!(decode_content(&json!({ "uri": "a://b" })).is_err());
+    }
+}
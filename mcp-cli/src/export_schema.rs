@@ -0,0 +1,658 @@
+//! Export a server's tool definitions for code generation
+//!
+//! The JSON bundle is an OpenAPI 3.1 document with one `POST /tools/{name}`
+//! operation per tool and the input and output schemas under
+//! `components/schemas`. `--lang typescript` and `--lang rust` render the
+//! same schemas as type declarations instead.
+
+use anyhow::{Context, Result};
+use mcp_core::messages::Implementation;
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Output of `assist-mcp export-schema`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaLang {
+    /// OpenAPI-style JSON Schema bundle
+    Json,
+    /// TypeScript interfaces
+    Typescript,
+    /// Rust structs deriving serde
+    Rust,
+}
+
+/// Options for `assist-mcp export-schema`
+pub struct ExportSchemaOptions {
+    pub server: String,
+    pub lang: SchemaLang,
+    pub out: Option<PathBuf>,
+    pub timeout: Duration,
+}
+
+pub async fn run_export_schema(options: ExportSchemaOptions) -> Result<()> {
+    let (server, mut tools) = crate::call::fetch_tools(&options.server, options.timeout).await?;
+    tools.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+    let output = match options.lang {
+        SchemaLang::Json => serde_json::to_string_pretty(&openapi(&server, &tools))? + "\n",
+        SchemaLang::Typescript => typescript(&server, &tools),
+        SchemaLang::Rust => rust(&server, &tools),
+    };
+    match &options.out {
+        Some(path) => {
+            std::fs::write(path, output)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Exported {} tool(s) to {}", tools.len(), path.display());
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+fn tool_name(tool: &Value) -> &str {
+    tool["name"].as_str().unwrap_or_default()
+}
+
+/// The OpenAPI bundle
+fn openapi(server: &Implementation, tools: &[Value]) -> Value {
+    let mut paths = Map::new();
+    let mut schemas = Map::new();
+    for tool in tools {
+        let name = tool_name(tool);
+        let type_name = pascal_case(name);
+        let reference = |suffix: &str| json!({ "$ref": format!("#/components/schemas/{}{}", type_name, suffix) });
+
+        schemas.insert(format!("{}Input", type_name), input_schema(tool));
+        let output = match tool.get("outputSchema") {
+            Some(schema) => {
+                schemas.insert(format!("{}Output", type_name), schema.clone());
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "content": { "type": "array" },
+                        "structuredContent": reference("Output"),
+                        "isError": { "type": "boolean" },
+                    },
+                })
+            }
+            None => json!({ "$ref": "#/components/schemas/CallToolResult" }),
+        };
+
+        let mut operation = json!({
+            "operationId": name,
+            "requestBody": {
+                "required": true,
+                "content": { "application/json": { "schema": reference("Input") } },
+            },
+            "responses": {
+                "200": {
+                    "description": "Result of tools/call",
+                    "content": { "application/json": { "schema": output } },
+                },
+            },
+        });
+        if let Some(description) = tool["description"].as_str() {
+            operation["summary"] = json!(description);
+        }
+        if let Some(annotations) = tool.get("annotations") {
+            operation["x-mcp-annotations"] = annotations.clone();
+        }
+        paths.insert(format!("/tools/{}", name), json!({ "post": operation }));
+    }
+    schemas.insert(
+        "CallToolResult".to_string(),
+        json!({
+            "type": "object",
+            "properties": {
+                "content": { "type": "array", "items": { "type": "object" } },
+                "structuredContent": { "type": "object" },
+                "isError": { "type": "boolean" },
+            },
+            "required": ["content"],
+        }),
+    );
+
+    json!({
+        "openapi": "3.1.0",
+        "info": { "title": server.name, "version": server.version },
+        "paths": paths,
+        "components": { "schemas": schemas },
+    })
+}
+
+fn input_schema(tool: &Value) -> Value {
+    tool.get("inputSchema")
+        .cloned()
+        .unwrap_or_else(|| json!({ "type": "object" }))
+}
+
+/// A JSON Schema reduced to what the type generators can express
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Null,
+    Any,
+    Array(Box<Ty>),
+    Map(Box<Ty>),
+    Object(Vec<Field>),
+    Enum(Vec<String>),
+    Nullable(Box<Ty>),
+    Union(Vec<Ty>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: String,
+    description: Option<String>,
+    ty: Ty,
+    required: bool,
+}
+
+impl Ty {
+    fn parse(schema: &Value) -> Ty {
+        if let Some(values) = schema["enum"].as_array() {
+            if let Some(values) = values
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+            {
+                return Ty::Enum(values);
+            }
+        }
+        if let Some(value) = schema["const"].as_str() {
+            return Ty::Enum(vec![value.to_string()]);
+        }
+        for key in ["anyOf", "oneOf"] {
+            if let Some(variants) = schema[key].as_array() {
+                return Ty::union(variants.iter().map(Ty::parse).collect());
+            }
+        }
+
+        match &schema["type"] {
+            Value::String(kind) => Ty::of_type(kind, schema),
+            Value::Array(kinds) => Ty::union(
+                kinds
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|kind| Ty::of_type(kind, schema))
+                    .collect(),
+            ),
+            _ if schema.get("properties").is_some() => Ty::of_type("object", schema),
+            _ => Ty::Any,
+        }
+    }
+
+    fn of_type(kind: &str, schema: &Value) -> Ty {
+        match kind {
+            "string" => Ty::String,
+            "integer" => Ty::Integer,
+            "number" => Ty::Number,
+            "boolean" => Ty::Boolean,
+            "null" => Ty::Null,
+            "array" => Ty::Array(Box::new(Ty::parse(&schema["items"]))),
+            "object" => match schema["properties"].as_object() {
+                Some(properties) if !properties.is_empty() => {
+                    let required: Vec<&str> = schema["required"]
+                        .as_array()
+                        .map(|names| names.iter().filter_map(Value::as_str).collect())
+                        .unwrap_or_default();
+                    Ty::Object(
+                        properties
+                            .iter()
+                            .map(|(name, property)| Field {
+                                name: name.clone(),
+                                description: property["description"].as_str().map(str::to_string),
+                                ty: Ty::parse(property),
+                                required: required.contains(&name.as_str()),
+                            })
+                            .collect(),
+                    )
+                }
+                _ => match &schema["additionalProperties"] {
+                    Value::Object(_) => {
+                        Ty::Map(Box::new(Ty::parse(&schema["additionalProperties"])))
+                    }
+                    _ => Ty::Map(Box::new(Ty::Any)),
+                },
+            },
+            _ => Ty::Any,
+        }
+    }
+
+    /// Variants with `null` folded into [`Ty::Nullable`]
+    fn union(variants: Vec<Ty>) -> Ty {
+        let nullable = variants.contains(&Ty::Null);
+        let mut rest: Vec<Ty> = variants.into_iter().filter(|ty| *ty != Ty::Null).collect();
+        let ty = match rest.len() {
+            0 if nullable => return Ty::Null,
+            0 => Ty::Any,
+            1 => rest.remove(0),
+            _ => Ty::Union(rest),
+        };
+        if nullable {
+            Ty::Nullable(Box::new(ty))
+        } else {
+            ty
+        }
+    }
+}
+
+fn header(comment: &str, server: &Implementation) -> String {
+    format!(
+        "{} Generated by assist-mcp export-schema from {} {}; do not edit.\n",
+        comment, server.name, server.version
+    )
+}
+
+/// TypeScript declarations, one input (and output) type per tool
+fn typescript(server: &Implementation, tools: &[Value]) -> String {
+    let mut out = header("//", server);
+    for tool in tools {
+        let type_name = pascal_case(tool_name(tool));
+        out.push('\n');
+        if let Some(description) = tool["description"].as_str() {
+            out.push_str(&format!("/** {} */\n", description));
+        }
+        out.push_str(&ts_declaration(
+            &format!("{}Input", type_name),
+            &Ty::parse(&input_schema(tool)),
+        ));
+        if let Some(schema) = tool.get("outputSchema") {
+            out.push_str(&ts_declaration(
+                &format!("{}Output", type_name),
+                &Ty::parse(schema),
+            ));
+        }
+    }
+
+    let names: Vec<String> = tools
+        .iter()
+        .map(|tool| json!(tool_name(tool)).to_string())
+        .collect();
+    out.push_str(&format!(
+        "\nexport type ToolName = {};\n",
+        if names.is_empty() {
+            "never".to_string()
+        } else {
+            names.join(" | ")
+        }
+    ));
+    out.push_str("\nexport interface ToolInputs {\n");
+    for tool in tools {
+        let name = tool_name(tool);
+        out.push_str(&format!(
+            "  {}: {}Input;\n",
+            ts_key(name),
+            pascal_case(name)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn ts_declaration(name: &str, ty: &Ty) -> String {
+    match ty {
+        Ty::Object(_) => format!("export interface {} {}\n", name, ts_type(ty, 0)),
+        _ => format!("export type {} = {};\n", name, ts_type(ty, 0)),
+    }
+}
+
+fn ts_type(ty: &Ty, depth: usize) -> String {
+    match ty {
+        Ty::String => "string".to_string(),
+        Ty::Integer | Ty::Number => "number".to_string(),
+        Ty::Boolean => "boolean".to_string(),
+        Ty::Null => "null".to_string(),
+        Ty::Any => "unknown".to_string(),
+        Ty::Array(item) => match **item {
+            Ty::Union(_) | Ty::Nullable(_) | Ty::Enum(_) => {
+                format!("({})[]", ts_type(item, depth))
+            }
+            _ => format!("{}[]", ts_type(item, depth)),
+        },
+        Ty::Map(value) => format!("Record<string, {}>", ts_type(value, depth)),
+        Ty::Enum(values) => values
+            .iter()
+            .map(|value| json!(value).to_string())
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Ty::Nullable(inner) => format!("{} | null", ts_type(inner, depth)),
+        Ty::Union(variants) => variants
+            .iter()
+            .map(|variant| ts_type(variant, depth))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Ty::Object(fields) => {
+            let indent = "  ".repeat(depth + 1);
+            let mut out = "{\n".to_string();
+            for field in fields {
+                if let Some(description) = &field.description {
+                    out.push_str(&format!("{}/** {} */\n", indent, description));
+                }
+                out.push_str(&format!(
+                    "{}{}{}: {};\n",
+                    indent,
+                    ts_key(&field.name),
+                    if field.required { "" } else { "?" },
+                    ts_type(&field.ty, depth + 1)
+                ));
+            }
+            out.push_str(&"  ".repeat(depth));
+            out.push('}');
+            out
+        }
+    }
+}
+
+fn ts_key(name: &str) -> String {
+    let identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        name.to_string()
+    } else {
+        json!(name).to_string()
+    }
+}
+
+/// Rust types deriving serde, one input (and output) struct per tool
+fn rust(server: &Implementation, tools: &[Value]) -> String {
+    let mut generator = RustGenerator::default();
+    for tool in tools {
+        let name = tool_name(tool);
+        let type_name = pascal_case(name);
+        let input = format!("{}Input", type_name);
+        let ty = Ty::parse(&input_schema(tool));
+        generator.declare(&input, &ty, tool["description"].as_str());
+        if let Ty::Object(_) = ty {
+            generator.items.push(format!(
+                "impl {} {{\n    /// Name to pass to `tools/call`\n    pub const TOOL: &'static str = {};\n}}\n",
+                input,
+                json!(name)
+            ));
+        }
+        if let Some(schema) = tool.get("outputSchema") {
+            generator.declare(&format!("{}Output", type_name), &Ty::parse(schema), None);
+        }
+    }
+
+    let mut out = header("//", server);
+    out.push_str("\n#![allow(dead_code)]\n\nuse serde::{Deserialize, Serialize};\n");
+    if generator.uses_map {
+        out.push_str("use std::collections::HashMap;\n");
+    }
+    for item in &generator.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out
+}
+
+#[derive(Default)]
+struct RustGenerator {
+    items: Vec<String>,
+    uses_map: bool,
+}
+
+impl RustGenerator {
+    /// A named top-level type for `ty`
+    fn declare(&mut self, name: &str, ty: &Ty, description: Option<&str>) {
+        match ty {
+            Ty::Object(_) | Ty::Enum(_) => {
+                self.named(ty, name, description);
+            }
+            _ => {
+                let target = self.type_of(ty, &format!("{}Value", name));
+                self.items.push(format!(
+                    "{}pub type {} = {};\n",
+                    doc("", description),
+                    name,
+                    target
+                ));
+            }
+        }
+    }
+
+    /// The Rust type for `ty`, declaring structs and enums under `hint` as needed
+    fn type_of(&mut self, ty: &Ty, hint: &str) -> String {
+        match ty {
+            Ty::String => "String".to_string(),
+            Ty::Integer => "i64".to_string(),
+            Ty::Number => "f64".to_string(),
+            Ty::Boolean => "bool".to_string(),
+            Ty::Null => "()".to_string(),
+            Ty::Any | Ty::Union(_) => "serde_json::Value".to_string(),
+            Ty::Array(item) => format!("Vec<{}>", self.type_of(item, &format!("{}Item", hint))),
+            Ty::Map(value) => {
+                self.uses_map = true;
+                format!(
+                    "HashMap<String, {}>",
+                    self.type_of(value, &format!("{}Value", hint))
+                )
+            }
+            Ty::Nullable(inner) => format!("Option<{}>", self.type_of(inner, hint)),
+            Ty::Object(_) | Ty::Enum(_) => self.named(ty, hint, None),
+        }
+    }
+
+    fn named(&mut self, ty: &Ty, name: &str, description: Option<&str>) -> String {
+        let item = match ty {
+            Ty::Object(fields) => {
+                let mut body = String::new();
+                for field in fields {
+                    let ident = snake_case(&field.name);
+                    let mut ty =
+                        self.type_of(&field.ty, &format!("{}{}", name, pascal_case(&field.name)));
+                    body.push_str(&doc("    ", field.description.as_deref()));
+                    if ident.trim_start_matches("r#") != field.name {
+                        body.push_str(&format!("    #[serde(rename = {})]\n", json!(field.name)));
+                    }
+                    if !field.required {
+                        if !ty.starts_with("Option<") {
+                            ty = format!("Option<{}>", ty);
+                        }
+                        body.push_str(
+                            "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n",
+                        );
+                    }
+                    body.push_str(&format!("    pub {}: {},\n", ident, ty));
+                }
+                format!(
+                    "{}#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {} {{\n{}}}\n",
+                    doc("", description),
+                    name,
+                    body
+                )
+            }
+            Ty::Enum(values) => {
+                let mut body = String::new();
+                for value in values {
+                    let variant = match pascal_case(value) {
+                        variant if variant.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                            variant
+                        }
+                        variant => format!("V{}", variant),
+                    };
+                    if variant != *value {
+                        body.push_str(&format!("    #[serde(rename = {})]\n", json!(value)));
+                    }
+                    body.push_str(&format!("    {},\n", variant));
+                }
+                format!(
+                    "{}#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\npub enum {} {{\n{}}}\n",
+                    doc("", description),
+                    name,
+                    body
+                )
+            }
+            _ => unreachable!("only objects and enums are named"),
+        };
+        self.items.push(item);
+        name.to_string()
+    }
+}
+
+fn doc(indent: &str, description: Option<&str>) -> String {
+    description
+        .map(|text| {
+            text.lines()
+                .map(|line| format!("{}/// {}\n", indent, line).replace("/// \n", "///\n"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `search_issues` and `getUser` become `SearchIssues` and `GetUser`
+fn pascal_case(name: &str) -> String {
+    let mut out = String::new();
+    for part in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.push(first.to_ascii_uppercase());
+            out.extend(chars);
+        }
+    }
+    if out.is_empty() {
+        "Tool".to_string()
+    } else {
+        out
+    }
+}
+
+/// A Rust field name for a JSON property
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit()) {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+        previous = Some(c);
+    }
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    match out.as_str() {
+        "self" | "Self" | "super" | "crate" => format!("{}_", out),
+        "type" | "match" | "ref" | "mod" | "use" | "fn" | "impl" | "in" | "loop" | "move"
+        | "static" | "struct" | "trait" | "where" | "while" | "async" | "await" | "dyn"
+        | "enum" | "const" | "let" | "mut" | "pub" | "return" | "for" | "if" | "else" | "as"
+        | "break" | "continue" | "extern" | "false" | "true" | "unsafe" | "box" | "yield"
+        | "abstract" | "final" | "override" | "macro" | "priv" | "try" | "typeof" | "unsized"
+        | "virtual" | "become" | "do" | "gen" => format!("r#{}", out),
+        _ => out,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn server() -> Implementation {
+        Implementation {
+            name: "acme".to_string(),
+            version: "1.0.0".to_string(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn tools() -> Vec<Value> {
+        vec![json!({
+            "name": "search_issues",
+            "description": "Search issues",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Search text" },
+                    "maxResults": { "type": "integer" },
+                    "state": { "enum": ["open", "closed"] },
+                    "labels": { "type": "array", "items": { "type": "string" } },
+                    "type": { "type": ["string", "null"] },
+                    "filter": {
+                        "type": "object",
+                        "properties": { "since": { "type": "string" } },
+                        "required": ["since"],
+                    },
+                },
+                "required": ["query"],
+            },
+            "outputSchema": {
+                "type": "object",
+                "properties": { "total": { "type": "integer" } },
+                "required": ["total"],
+            },
+        })]
+    }
+
+    #[test]
+    fn test_openapi_bundle() {
+        let bundle = openapi(&server(), &tools());
+        assert_eq!(bundle["info"]["title"], "acme");
+        let operation = &bundle["paths"]["/tools/search_issues"]["post"];
+        assert_eq!(operation["operationId"], "search_issues");
+        assert_eq!(
+            operation["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/SearchIssuesInput"
+        );
+        let schemas = &bundle["components"]["schemas"];
+        assert_eq!(schemas["SearchIssuesInput"]["required"], json!(["query"]));
+        assert_eq!(schemas["SearchIssuesOutput"]["required"], json!(["total"]));
+    }
+
+    #[test]
+    fn test_typescript() {
+        let out = typescript(&server(), &tools());
+        assert!(out.contains("export interface SearchIssuesInput {\n"));
+        assert!(out.contains("  /** Search text */\n  query: string;\n"));
+        assert!(out.contains("  maxResults?: number;\n"));
+        assert!(out.contains("  state?: \"open\" | \"closed\";\n"));
+        assert!(out.contains("  labels?: string[];\n"));
+        assert!(out.contains("  type?: string | null;\n"));
+        assert!(out.contains("  filter?: {\n    since: string;\n  };\n"));
+        assert!(out.contains("export interface SearchIssuesOutput {\n  total: number;\n}\n"));
+        assert!(out.contains("export type ToolName = \"search_issues\";\n"));
+        assert!(out.contains("  search_issues: SearchIssuesInput;\n"));
+    }
+
+    #[test]
+    fn test_rust() {
+        let out = rust(&server(), &tools());
+        assert!(out.contains("/// Search issues\n#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct SearchIssuesInput {\n"));
+        assert!(out.contains("    /// Search text\n    pub query: String,\n"));
+        assert!(out.contains(
+            "    #[serde(rename = \"maxResults\")]\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub max_results: Option<i64>,\n"
+        ));
+        assert!(out.contains("    pub state: Option<SearchIssuesInputState>,\n"));
+        assert!(out.contains("    #[serde(rename = \"open\")]\n    Open,\n"));
+        assert!(out.contains("    pub r#type: Option<String>,\n"));
+        assert!(out.contains("pub struct SearchIssuesInputFilter {\n    pub since: String,\n}\n"));
+        assert!(out.contains("pub const TOOL: &'static str = \"search_issues\";"));
+        assert!(out.contains("pub struct SearchIssuesOutput {\n    pub total: i64,\n}\n"));
+        assert!(!out.contains("HashMap"));
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(pascal_case("get-user.v2"), "GetUserV2");
+        assert_eq!(pascal_case("getUser"), "GetUser");
+        assert_eq!(snake_case("maxResults"), "max_results");
+        assert_eq!(snake_case("self"), "self_");
+        assert_eq!(snake_case("2fa-code"), "_2fa_code");
+    }
+}
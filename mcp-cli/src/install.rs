@@ -0,0 +1,354 @@
+//! Install MCP servers from package registries as stdio profiles
+
+use anyhow::{bail, Context, Result};
+use mcp_core::client::McpClientBuilder;
+use mcp_core::messages::Implementation;
+use mcp_core::quirks::QuirkDatabase;
+use mcp_core::transport::prerequisites;
+use mcp_core::TransportConfig;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Launcher used to run a server package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Launcher {
+    /// Node packages via `npx -y`
+    Npx,
+    /// Python packages via `uvx`
+    Uvx,
+    /// Python packages via `pipx run`
+    Pipx,
+}
+
+impl Launcher {
+    /// Guess the launcher from a package name
+    ///
+    /// Scoped npm packages go to npx, the `mcp-server-*` naming used by the
+    /// Python reference servers goes to uvx, anything else defaults to npx.
+    pub fn detect(package: &str) -> Self {
+        if package.starts_with('@') {
+            Launcher::Npx
+        } else if package.starts_with("mcp-server-")
+            || package.contains('_')
+            || package.contains("==")
+        {
+            Launcher::Uvx
+        } else {
+            Launcher::Npx
+        }
+    }
+
+    /// Executable name for this launcher
+    pub fn program(self) -> &'static str {
+        match self {
+            Launcher::Npx => "npx",
+            Launcher::Uvx => "uvx",
+            Launcher::Pipx => "pipx",
+        }
+    }
+
+    /// Arguments passed to the launcher to run `package`
+    pub fn command_args(self, package: &str, extra: &[String]) -> Vec<String> {
+        let mut args = match self {
+            Launcher::Npx => vec!["-y".to_string(), package.to_string()],
+            Launcher::Uvx => vec![package.to_string()],
+            Launcher::Pipx => vec!["run".to_string(), package.to_string()],
+        };
+        args.extend(extra.iter().cloned());
+        args
+    }
+}
+
+/// A package reference, optionally prefixed with its registry (`npm:`, `pypi:`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    /// Package name as passed to the launcher, including any version
+    pub package: String,
+    /// Launcher used to run the package
+    pub launcher: Launcher,
+}
+
+impl PackageSpec {
+    /// Parse a package reference, using `launcher` if given instead of detection
+    pub fn parse(spec: &str, launcher: Option<Launcher>) -> Result<Self> {
+        let (package, registry) = if let Some(rest) = spec.strip_prefix("npm:") {
+            (rest, Some(Launcher::Npx))
+        } else if let Some(rest) = spec.strip_prefix("pypi:") {
+            (rest, Some(Launcher::Uvx))
+        } else {
+            (spec, None)
+        };
+
+        let package = package.trim();
+        if package.is_empty() {
+            bail!("Package name must not be empty");
+        }
+
+        Ok(Self {
+            package: package.to_string(),
+            launcher: launcher
+                .or(registry)
+                .unwrap_or_else(|| Launcher::detect(package)),
+        })
+    }
+
+    /// Package name without scope or version
+    fn base_name(&self) -> &str {
+        let name = self.package.split("==").next().unwrap_or(&self.package);
+        // Strip an npm version suffix (`name@1.2.3`) but not the scope marker
+        let name = match name.rfind('@') {
+            Some(idx) if idx > 0 => &name[..idx],
+            _ => name,
+        };
+        name.rsplit('/').next().unwrap_or(name)
+    }
+
+    /// Default profile name (e.g. `filesystem` for `@modelcontextprotocol/server-filesystem`)
+    pub fn profile_name(&self) -> String {
+        let base = self.base_name();
+        let mut name = base;
+        for prefix in ["mcp-server-", "server-"] {
+            if let Some(rest) = name.strip_prefix(prefix) {
+                name = rest;
+                break;
+            }
+        }
+        for suffix in ["-mcp-server", "-mcp"] {
+            if let Some(rest) = name.strip_suffix(suffix) {
+                name = rest;
+                break;
+            }
+        }
+
+        if name.is_empty() {
+            base.to_string()
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Stdio transport configuration that launches this package
+    pub fn transport_config(
+        &self,
+        extra_args: &[String],
+        environment: HashMap<String, String>,
+        timeout: Duration,
+    ) -> TransportConfig {
+        let mut config = TransportConfig::stdio(
+            self.launcher.program(),
+            &self.launcher.command_args(&self.package, extra_args),
+        );
+        if let TransportConfig::Stdio(stdio) = &mut config {
+            stdio.environment = environment;
+            stdio.timeout = timeout;
+        }
+        config
+    }
+}
+
+/// Options for `assist-mcp add`
+pub struct AddOptions {
+    pub package: String,
+    pub launcher: Option<Launcher>,
+    pub name: Option<String>,
+    pub args: Vec<String>,
+    pub env: Vec<String>,
+    pub dir: Option<PathBuf>,
+    pub timeout: u64,
+    pub no_verify: bool,
+    pub force: bool,
+}
+
+/// Resolve a package, verify it starts and save it as a stdio profile
+pub async fn run_add(options: AddOptions) -> Result<()> {
+    let spec = PackageSpec::parse(&options.package, options.launcher)?;
+    let name = options.name.unwrap_or_else(|| spec.profile_name());
+    let environment = parse_env(&options.env)?;
+    let timeout = Duration::from_secs(options.timeout);
+
+    let dir = match options.dir {
+        Some(dir) => dir,
+        None => profiles_dir()?,
+    };
+    let path = dir.join(format!("{}.toml", name));
+    if path.exists() && !options.force {
+        bail!(
+            "Profile '{}' already exists at {} (use --force to overwrite)",
+            name,
+            path.display()
+        );
+    }
+
+    let program = spec.launcher.program();
+    if prerequisites::find_on_path(program).is_none() {
+        bail!(
+            "'{}' was not found on PATH; {}",
+            program,
+            prerequisites::install_hint(program).unwrap_or("install it first")
+        );
+    }
+
+    let config = spec.transport_config(&options.args, environment, timeout);
+    let command_line = format!(
+        "{} {}",
+        program,
+        spec.launcher
+            .command_args(&spec.package, &options.args)
+            .join(" ")
+    );
+
+    if options.no_verify {
+        println!("Skipping verification of '{}'", command_line);
+    } else {
+        println!(
+            "Starting '{}' to verify it ({}s timeout)...",
+            command_line, options.timeout
+        );
+        let summary = verify(config.clone(), timeout)
+            .await
+            .with_context(|| format!("Server '{}' failed to start; profile not saved", name))?;
+        println!("Verified: {}", summary);
+    }
+
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create profile directory {}", dir.display()))?;
+    config.to_file(&path)?;
+
+    println!("Saved profile '{}' to {}", name, path.display());
+    println!(
+        "Run it with: assist-mcp proxy --transport stdio --command '{}'",
+        command_line
+    );
+    Ok(())
+}
+
+/// Connect to the server, run the initialize handshake and report what it offers
+async fn verify(config: TransportConfig, timeout: Duration) -> Result<String> {
+    let mut client = McpClientBuilder::new()
+        .transport(config)
+        .connect_deadline(timeout)
+        .quirks(QuirkDatabase::load_default()?)
+        .build()
+        .await?;
+
+    let info = client
+        .connect(Implementation {
+            name: "assist-mcp".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            metadata: HashMap::new(),
+        })
+        .await?;
+
+    let mut summary = format!(
+        "{} {}",
+        info.implementation.name, info.implementation.version
+    );
+
+    if info.capabilities.standard.tools.is_some() {
+        if let Ok(response) = client
+            .send_request("tools/list", serde_json::json!({}))
+            .await
+        {
+            let count = response
+                .result
+                .as_ref()
+                .and_then(|r| r.get("tools"))
+                .and_then(|t| t.as_array())
+                .map(|t| t.len());
+            if let Some(count) = count {
+                summary.push_str(&format!(" ({} tools)", count));
+            }
+        }
+    }
+
+    if let Err(e) = client.disconnect().await {
+        eprintln!("Warning: failed to stop verification session: {}", e);
+    }
+
+    Ok(summary)
+}
+
+fn parse_env(entries: &[String]) -> Result<HashMap<String, String>> {
+    entries
+        .iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+            _ => bail!("Invalid --env entry '{}', expected KEY=VALUE", entry),
+        })
+        .collect()
+}
+
+/// Default directory for saved profiles (`$XDG_CONFIG_HOME/genmcp/profiles`)
+pub(crate) fn profiles_dir() -> Result<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .context("Cannot determine config directory; pass --dir")?;
+    Ok(base.join("genmcp").join("profiles"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_launcher() {
+        assert_eq!(
+            Launcher::detect("@modelcontextprotocol/server-filesystem"),
+            Launcher::Npx
+        );
+        assert_eq!(Launcher::detect("mcp-server-fetch"), Launcher::Uvx);
+        assert_eq!(Launcher::detect("some-node-server"), Launcher::Npx);
+        assert_eq!(
+            PackageSpec::parse("pypi:weather", None).unwrap().launcher,
+            Launcher::Uvx
+        );
+        assert_eq!(
+            PackageSpec::parse("mcp-server-git", Some(Launcher::Pipx))
+                .unwrap()
+                .launcher,
+            Launcher::Pipx
+        );
+    }
+
+    #[test]
+    fn test_profile_name() {
+        let name = |spec: &str| PackageSpec::parse(spec, None).unwrap().profile_name();
+        assert_eq!(
+            name("@modelcontextprotocol/server-filesystem"),
+            "filesystem"
+        );
+        assert_eq!(
+            name("@modelcontextprotocol/server-github@2025.4.8"),
+            "github"
+        );
+        assert_eq!(name("mcp-server-fetch==1.0"), "fetch");
+        assert_eq!(name("notion-mcp"), "notion");
+        assert_eq!(name("server-"), "server-");
+    }
+
+    #[test]
+    fn test_transport_config() {
+        let spec = PackageSpec::parse("@modelcontextprotocol/server-filesystem", None).unwrap();
+        let env = parse_env(&["ROOT=/tmp".to_string()]).unwrap();
+        let config = spec.transport_config(&["/srv".to_string()], env, Duration::from_secs(60));
+
+        let TransportConfig::Stdio(stdio) = config else {
+            panic!("expected stdio config");
+        };
+        assert_eq!(stdio.command, "npx");
+        assert_eq!(
+            stdio.args,
+            vec!["-y", "@modelcontextprotocol/server-filesystem", "/srv"]
+        );
+        assert_eq!(stdio.environment.get("ROOT").unwrap(), "/tmp");
+        assert_eq!(stdio.timeout, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_parse_env_rejects_malformed() {
+        assert!(parse_env(&["NOVALUE".to_string()]).is_err());
+        assert!(parse_env(&["=x".to_string()]).is_err());
+    }
+}
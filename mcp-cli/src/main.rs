@@ -1,5 +1,26 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use output::OutputFormat;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+mod audit;
+mod broker;
+mod bundle;
+mod call;
+mod demo_server;
+mod differential;
+mod discover;
+mod doctor;
+mod export;
+mod export_schema;
+mod install;
+mod output;
+mod probe;
+mod serve;
+mod session;
+mod status;
 
 #[derive(Parser)]
 #[command(name = "assist-mcp")]
@@ -8,14 +29,21 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Output format of call, list, probe, doctor, monitor status and proxy status
+    #[arg(long = "output", value_name = "FORMAT", value_enum, global = true)]
+    pub output_format: Option<OutputFormat>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Start the MCP monitor (default if no subcommand provided)
     Monitor {
-        /// IPC socket path for proxy communication
-        #[arg(short, long, default_value = "/tmp/mcp-monitor.sock")]
+        #[command(subcommand)]
+        action: Option<status::MonitorCommand>,
+
+        /// IPC endpoint for proxy communication (socket path, pipe:NAME or tcp:HOST:PORT)
+        #[arg(short, long, default_value = mcp_common::DEFAULT_IPC_ENDPOINT)]
         ipc_socket: String,
 
         /// Verbose logging
@@ -24,10 +52,21 @@ pub enum Commands {
     },
     /// Start an MCP proxy server
     Proxy {
-        /// Transport type (stdio, http-sse, http-stream)
+        #[command(subcommand)]
+        action: Option<status::ProxyCommand>,
+
+        /// Transport type (stdio, http-sse, http-stream, aggregate)
         #[arg(short, long, default_value = "stdio")]
         transport: String,
 
+        /// Backends file to aggregate behind one proxy (implies --transport aggregate)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Named profile from the server registry (~/.config/genmcp/servers.toml)
+        #[arg(short, long, conflicts_with_all = ["config", "command", "url"])]
+        server: Option<String>,
+
         /// MCP server command (for stdio transport)
         #[arg(short, long)]
         command: Option<String>,
@@ -44,8 +83,8 @@ pub enum Commands {
         #[arg(short, long, default_value = "mcp-transport")]
         name: String,
 
-        /// IPC socket path for monitor communication
-        #[arg(short = 'i', long, default_value = "/tmp/mcp-monitor.sock")]
+        /// IPC endpoint for monitor communication (socket path, pipe:NAME or tcp:HOST:PORT)
+        #[arg(short = 'i', long, default_value = mcp_common::DEFAULT_IPC_ENDPOINT)]
         ipc_socket: String,
 
         /// Verbose logging
@@ -59,20 +98,480 @@ pub enum Commands {
         /// Skip connecting to monitor (standalone mode)
         #[arg(long, default_value_t = false)]
         no_monitor: bool,
+
+        /// Forwarding fidelity: normalize (re-serialize), preserve (keep untouched
+        /// messages byte-for-byte) or raw (bypass interceptors)
+        #[arg(long, default_value = "normalize")]
+        fidelity: String,
+
+        /// Log format: text or json (defaults to $GENMCP_LOG_FORMAT, else text)
+        #[arg(long, value_name = "FORMAT")]
+        log_format: Option<String>,
+
+        /// Write logs to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        log_file: Option<PathBuf>,
+
+        /// When --log-file is rotated: never, hourly, daily or a size such
+        /// as 10MB
+        #[arg(long, value_name = "WHEN", default_value = "daily")]
+        log_rotate: String,
+
+        /// Rotated log files to keep (0 keeps all)
+        #[arg(long, value_name = "N", default_value_t = 7)]
+        keep: usize,
+
+        /// TOML file of faults to inject into server replies (chaos testing)
+        #[arg(long, value_name = "FILE")]
+        faults: Option<PathBuf>,
+
+        /// Fix tool call arguments that do not match the tool's input
+        /// schema (e.g. numbers sent as strings) before they reach the server
+        #[arg(long)]
+        repair_arguments: bool,
+
+        /// Imported pipeline directory (see `bundle import`) whose interceptors
+        /// the stdio backend's traffic passes through
+        #[arg(long, value_name = "DIR")]
+        pipeline: Option<PathBuf>,
+
+        /// Per-message interceptor overhead budget in microseconds; enables
+        /// per-interceptor timing and warns when a message exceeds it
+        #[arg(long, value_name = "MICROS")]
+        overhead_budget: Option<u64>,
+
+        /// Notifications per second of one method forwarded before bursts are
+        /// coalesced (0 disables flood protection)
+        #[arg(long, default_value_t = 20)]
+        notification_limit: u32,
+
+        /// Seconds to mute a method that keeps flooding (0 only coalesces)
+        #[arg(long, default_value_t = 30)]
+        notification_mute: u64,
+
+        /// Shadow server command (stdio) that receives a copy of every
+        /// request; its answers are compared with the primary's
+        #[arg(long, value_name = "COMMAND")]
+        mirror: Option<String>,
+
+        /// JSON pointer left out of shadow comparisons (repeatable)
+        #[arg(long, value_name = "POINTER", requires = "mirror")]
+        mirror_ignore: Vec<String>,
+
+        /// Seconds to answer repeated tools/resources/prompts listings and
+        /// resource reads from a cache (0 disables caching)
+        #[arg(long, value_name = "SECS", default_value_t = 0)]
+        cache_ttl: u64,
+
+        /// File that keeps messages for the monitor while it is unavailable,
+        /// so they survive a proxy restart (default: memory only)
+        #[arg(long, value_name = "PATH")]
+        spool: Option<PathBuf>,
+
+        /// Messages kept for an unavailable monitor before the oldest are dropped
+        #[arg(long, value_name = "COUNT", default_value_t = mcp_transport::spool::DEFAULT_SPOOL_CAPACITY)]
+        spool_capacity: usize,
+
+        /// Seconds after which a message kept for the monitor is dropped
+        /// (0 keeps it until delivered)
+        #[arg(long, value_name = "SECS", default_value_t = 0)]
+        spool_max_age: u64,
+
+        /// Append a hash-chained record of every tool call to this file
+        /// (verify it with `assist-mcp audit verify`)
+        #[arg(long, value_name = "PATH")]
+        audit_log: Option<PathBuf>,
+
+        /// Identities allowed to use the proxy, with their tool policies and
+        /// rate limits (TOML); the client is identified by the owner of its pipe
+        #[arg(long, value_name = "FILE")]
+        auth: Option<PathBuf>,
+
+        /// Largest message in bytes either side may send; larger server
+        /// messages are dropped and larger client requests refused
+        /// (0 disables the limit)
+        #[arg(long, value_name = "BYTES", default_value_t = 0)]
+        max_message_size: usize,
+
+        /// Cut server messages over --max-message-size to the limit instead
+        /// of dropping them
+        #[arg(long)]
+        truncate_oversized: bool,
+
+        /// Directory that large base64 blobs in server messages are written
+        /// to instead of being held in memory
+        #[arg(long, value_name = "DIR")]
+        spill_dir: Option<PathBuf>,
+
+        /// Serve Prometheus metrics at http://ADDR/metrics, e.g.
+        /// 127.0.0.1:9464
+        #[arg(long, value_name = "ADDR")]
+        metrics_addr: Option<SocketAddr>,
+    },
+    /// Install an MCP server package (npx/uvx/pipx) as a stdio profile
+    Add {
+        /// Package name, optionally prefixed with npm: or pypi:
+        package: String,
+
+        /// Launcher to use instead of guessing from the package name
+        #[arg(short, long, value_enum)]
+        launcher: Option<install::Launcher>,
+
+        /// Profile name (derived from the package name by default)
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Environment variables for the server (KEY=VALUE, repeatable)
+        #[arg(short, long = "env")]
+        env: Vec<String>,
+
+        /// Directory to save the profile in (defaults to ~/.config/genmcp/profiles)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Seconds to wait for the server to start and initialize
+        #[arg(short, long, default_value_t = 60)]
+        timeout: u64,
+
+        /// Save the profile without starting the server
+        #[arg(long)]
+        no_verify: bool,
+
+        /// Overwrite an existing profile with the same name
+        #[arg(short, long)]
+        force: bool,
+
+        /// Extra arguments passed to the server (after --)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Discover MCP servers advertised on the local network via mDNS
+    Discover {
+        /// How long to listen for announcements, in seconds
+        #[arg(short, long, default_value_t = 3)]
+        timeout: u64,
+
+        /// Write a transport profile for each discovered server into this directory
+        #[arg(short, long)]
+        save: Option<PathBuf>,
+
+        /// Overwrite existing profiles with the same name
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Compare a server's capabilities, catalogs and responses across two protocol versions
+    DiffVersions {
+        /// Transport profile (TOML) describing the server
+        #[arg(short, long)]
+        profile: Option<PathBuf>,
+
+        /// HTTP streaming URL of the server
+        #[arg(short, long)]
+        url: Option<String>,
+
+        /// First protocol version
+        #[arg(long, default_value = "2024-11-05")]
+        left: String,
+
+        /// Second protocol version
+        #[arg(long, default_value = "2025-03-26")]
+        right: String,
+
+        /// Extra request to compare, as METHOD or METHOD=JSON (repeatable)
+        #[arg(short, long = "call")]
+        calls: Vec<String>,
+
+        /// Seconds allowed for connecting and for each request
+        #[arg(short, long, default_value_t = 30)]
+        timeout: u64,
+
+        /// Print the full report as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Server command and arguments for stdio servers (after --)
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Download a server's resources into a directory, with a manifest
+    Export {
+        /// Registry or saved profile name, profile file, or HTTP streaming URL of the server
+        #[arg(short, long)]
+        server: String,
+
+        /// Glob matched against resource URIs and their export paths (repeatable; all when omitted)
+        #[arg(short, long = "match")]
+        patterns: Vec<String>,
+
+        /// Number of resources read at once
+        #[arg(short, long, default_value_t = 8)]
+        concurrency: usize,
+
+        /// Seconds allowed for connecting and for each read
+        #[arg(short, long, default_value_t = 30)]
+        timeout: u64,
+
+        /// Directory to write the resources into
+        out: PathBuf,
+    },
+    /// Call a tool once and print its result (exit status: 0 ok, 1 tool error, 2 invalid input, 3 connection failed, 4 request failed, 5 partial success, 6 unexpected outcome)
+    Call {
+        /// Registry or saved profile name, profile file, or HTTP streaming URL of the server
+        #[arg(short, long)]
+        server: String,
+
+        /// Name of the tool to call
+        tool: String,
+
+        /// Tool arguments as a JSON object
+        #[arg(short, long, default_value = "{}")]
+        args: String,
+
+        /// Print the result as JSON (the default), YAML or a table of
+        /// content blocks; overrides --output
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Seconds allowed for connecting and for the call
+        #[arg(short, long, default_value_t = 30)]
+        timeout: u64,
+
+        /// Exit 0 only if the tool's outcome is this one, and 6 otherwise
+        #[arg(long, value_enum)]
+        expect: Option<call::Expectation>,
+    },
+    /// List a server's tools, resources or prompts (exit status as for `call`)
+    List {
+        /// What to list
+        #[arg(value_enum)]
+        kind: call::ListKind,
+
+        /// Registry or saved profile name, profile file, or HTTP streaming URL of the server
+        #[arg(short, long)]
+        server: String,
+
+        /// Only show items whose name, URI or description contains this text
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Print JSON (the default), YAML or a table; overrides --output
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Seconds allowed for connecting and for each request
+        #[arg(short, long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Check a server against the MCP specification and report violations (exit status as for `call`, and 7 when violations are found)
+    Probe {
+        /// Registry or saved profile name, profile file, or HTTP streaming URL of the server
+        #[arg(short, long)]
+        server: String,
+
+        /// Protocol version to request (defaults to the latest supported)
+        #[arg(long)]
+        protocol: Option<String>,
+
+        /// Also call read-only tools that take no required arguments
+        #[arg(long)]
+        call_tools: bool,
+
+        /// Check every frame against JSON-RPC 2.0 and report what breaks it
+        #[arg(long)]
+        strict: bool,
+
+        /// Resources read and prompts fetched, at most
+        #[arg(long, default_value_t = 5)]
+        samples: usize,
+
+        /// Seconds allowed for connecting and for each request
+        #[arg(short, long, default_value_t = 30)]
+        timeout: u64,
+
+        /// Print the full report as JSON (same as --output json)
+        #[arg(long)]
+        json: bool,
+
+        /// Also write the JSON report to this file
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+
+        /// List passed checks too
+        #[arg(long)]
+        all: bool,
+
+        /// Run the conformance suite of protocol edge cases instead
+        #[arg(long)]
+        conformance: bool,
+
+        /// Conformance cases to run, by id prefix (e.g. pagination,init.unsupported-version)
+        #[arg(long, value_delimiter = ',', requires = "conformance")]
+        cases: Vec<String>,
+    },
+    /// Check the local setup (monitor socket, server registry and profiles, reachability, handshakes) and suggest fixes (exit status 8 when problems are found)
+    Doctor {
+        /// Only check these registry or saved profiles
+        profiles: Vec<String>,
+
+        /// IPC endpoint of the monitor (socket path, pipe:NAME or tcp:HOST:PORT)
+        #[arg(short, long, default_value = mcp_common::DEFAULT_IPC_ENDPOINT)]
+        ipc_socket: String,
+
+        /// Skip the MCP handshake, which starts stdio servers
+        #[arg(long)]
+        no_handshake: bool,
+
+        /// Seconds allowed for each check
+        #[arg(short, long, default_value_t = 10)]
+        timeout: u64,
+    },
+    /// Export a server's tool definitions as an OpenAPI bundle, TypeScript or Rust types
+    ExportSchema {
+        /// Registry or saved profile name, profile file, or HTTP streaming URL of the server
+        #[arg(short, long)]
+        server: String,
+
+        /// What to generate
+        #[arg(short, long, value_enum, default_value_t = export_schema::SchemaLang::Json)]
+        lang: export_schema::SchemaLang,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+
+        /// Seconds allowed for connecting and for each request
+        #[arg(short, long, default_value_t = 30)]
+        timeout: u64,
+    },
+    /// Convert or inspect captured sessions, or use live sessions held by the broker
+    Session {
+        #[command(subcommand)]
+        command: session::SessionCommand,
+    },
+    /// List the named server profiles in ~/.config/genmcp/servers.toml
+    Servers {
+        /// Only show profiles with this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+    },
+    /// Verify tamper-evident audit logs written by `proxy --audit-log`
+    Audit {
+        #[command(subcommand)]
+        command: audit::AuditCommand,
+    },
+    /// Share interceptor and policy pipelines as signed bundles
+    Bundle {
+        #[command(subcommand)]
+        command: bundle::BundleCommand,
+    },
+    /// Run a small reference MCP server (tools, resources, a prompt, progress) for demos and tests
+    DemoServer {
+        /// Serve Streamable HTTP on this address (e.g. 127.0.0.1:8931) instead of stdio
+        #[arg(long)]
+        http: Option<String>,
+
+        /// Fail every Nth request with an internal error
+        #[arg(long)]
+        fail_every: Option<u64>,
+
+        /// Browser origin allowed to call the HTTP server, e.g.
+        /// https://inspector.example (loopback origins are allowed when
+        /// listening on loopback); may be repeated
+        #[arg(long = "allow-origin", value_name = "ORIGIN")]
+        allowed_origins: Vec<String>,
+    },
+    /// Publish a server as a Streamable HTTP endpoint for remote clients
+    Serve {
+        /// Server to publish: stdio:COMMAND, an http(s) URL, a registry name or a profile
+        #[arg(long)]
+        backend: String,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8931")]
+        listen: String,
+
+        /// Browser origin allowed to call the endpoint, e.g.
+        /// https://inspector.example (loopback origins are allowed when
+        /// listening on loopback); may be repeated
+        #[arg(long = "allow-origin", value_name = "ORIGIN")]
+        allowed_origins: Vec<String>,
+
+        /// Seconds a request may wait for the backend
+        #[arg(short, long, default_value_t = 60)]
+        timeout: u64,
+
+        /// Announce the endpoint on the local network under this name (see `discover`)
+        #[arg(long, value_name = "NAME")]
+        advertise: Option<String>,
+
+        /// Share this many backend connections among all clients instead of
+        /// starting one per session
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        shared_backends: usize,
+
+        /// Identities allowed to use the endpoint, with their tool policies
+        /// and rate limits (TOML); clients send an API key. Required to listen
+        /// beyond loopback or to advertise
+        #[arg(long, value_name = "FILE")]
+        auth: Option<PathBuf>,
+    },
+    /// Keep server sessions open for reuse by later invocations (see `session send`)
+    Broker {
+        /// IPC endpoint to listen on (socket path, pipe:NAME or tcp:HOST:PORT)
+        #[arg(long, default_value = broker::DEFAULT_BROKER_ENDPOINT)]
+        endpoint: String,
+
+        /// Seconds a session may stay unused before it is closed
+        #[arg(long, default_value_t = broker::DEFAULT_IDLE.as_secs())]
+        idle: u64,
+
+        /// Seconds allowed for connecting and for each request
+        #[arg(short, long, default_value_t = 60)]
+        timeout: u64,
+
+        /// Exit once no sessions have been open for the idle period
+        #[arg(long)]
+        exit_when_idle: bool,
+
+        /// Identities allowed to use the broker, with their tool policies and
+        /// rate limits (TOML); clients are identified by their uid
+        #[arg(long, value_name = "FILE")]
+        auth: Option<PathBuf>,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let output = cli.output_format;
 
     match cli.command {
         Some(Commands::Monitor {
+            action: Some(command),
+            ..
+        }) => {
+            let status =
+                status::run_monitor_status(command, output.unwrap_or(OutputFormat::Table)).await;
+            exit(status)
+        }
+        Some(Commands::Monitor {
+            action: None,
             ipc_socket,
             verbose,
         }) => run_monitor(ipc_socket, verbose).await,
         Some(Commands::Proxy {
+            action: Some(command),
+            ..
+        }) => {
+            let status =
+                status::run_proxy_status(command, output.unwrap_or(OutputFormat::Table)).await;
+            exit(status)
+        }
+        Some(Commands::Proxy {
+            action: None,
             transport,
+            config,
+            server,
             command,
             url,
             api_key,
@@ -81,14 +580,267 @@ async fn main() -> Result<()> {
             verbose,
             shell,
             no_monitor,
-        }) => run_proxy(transport, command, url, api_key, name, ipc_socket, verbose, shell, no_monitor).await,
+            fidelity,
+            log_format,
+            log_file,
+            log_rotate,
+            keep,
+            faults,
+            repair_arguments,
+            pipeline,
+            overhead_budget,
+            notification_limit,
+            notification_mute,
+            mirror,
+            mirror_ignore,
+            cache_ttl,
+            spool,
+            spool_capacity,
+            spool_max_age,
+            audit_log,
+            auth,
+            max_message_size,
+            truncate_oversized,
+            spill_dir,
+            metrics_addr,
+        }) => run_proxy(transport, config, server, command, url, api_key, name, ipc_socket, verbose, shell, no_monitor, fidelity, log_format, log_file, log_rotate, keep, faults, repair_arguments, pipeline, overhead_budget, notification_limit, notification_mute, mirror, mirror_ignore, cache_ttl, spool, spool_capacity, spool_max_age, audit_log, auth, max_message_size, truncate_oversized, spill_dir, metrics_addr).await,
+        Some(Commands::Add {
+            package,
+            launcher,
+            name,
+            env,
+            dir,
+            timeout,
+            no_verify,
+            force,
+            args,
+        }) => {
+            install::run_add(install::AddOptions {
+                package,
+                launcher,
+                name,
+                args,
+                env,
+                dir,
+                timeout,
+                no_verify,
+                force,
+            })
+            .await
+        }
+        Some(Commands::Discover {
+            timeout,
+            save,
+            force,
+        }) => discover::run_discover(timeout, save, force).await,
+        Some(Commands::DiffVersions {
+            profile,
+            url,
+            left,
+            right,
+            calls,
+            timeout,
+            json,
+            command,
+        }) => {
+            differential::run_diff(differential::DiffOptions {
+                profile,
+                url,
+                command,
+                left,
+                right,
+                calls,
+                timeout,
+                json,
+            })
+            .await
+        }
+        Some(Commands::Export {
+            server,
+            patterns,
+            concurrency,
+            timeout,
+            out,
+        }) => {
+            export::run_export(export::ExportOptions {
+                server,
+                patterns,
+                out,
+                concurrency,
+                timeout,
+            })
+            .await
+        }
+        Some(Commands::Call {
+            server,
+            tool,
+            args,
+            format,
+            timeout,
+            expect,
+        }) => {
+            let status = call::run_call(call::CallOptions {
+                server,
+                tool,
+                args,
+                format: format.or(output).unwrap_or(OutputFormat::Json),
+                timeout: Duration::from_secs(timeout),
+                expect,
+            })
+            .await;
+            exit(status)
+        }
+        Some(Commands::List {
+            kind,
+            server,
+            filter,
+            format,
+            timeout,
+        }) => {
+            let status = call::run_list(call::ListOptions {
+                server,
+                kind,
+                filter,
+                format: format.or(output).unwrap_or(OutputFormat::Json),
+                timeout: Duration::from_secs(timeout),
+            })
+            .await;
+            exit(status)
+        }
+        Some(Commands::Probe {
+            server,
+            protocol,
+            call_tools,
+            strict,
+            samples,
+            timeout,
+            json,
+            out,
+            all,
+            conformance,
+            cases,
+        }) => {
+            let status = probe::run_probe(probe::ProbeArgs {
+                server,
+                protocol,
+                call_tools,
+                strict,
+                samples,
+                timeout,
+                format: if json {
+                    OutputFormat::Json
+                } else {
+                    output.unwrap_or(OutputFormat::Table)
+                },
+                out,
+                all,
+                conformance,
+                cases,
+            })
+            .await;
+            exit(status)
+        }
+        Some(Commands::Doctor {
+            profiles,
+            ipc_socket,
+            no_handshake,
+            timeout,
+        }) => {
+            let status = doctor::run_doctor(doctor::DoctorOptions {
+                ipc_socket,
+                profiles,
+                no_handshake,
+                timeout: Duration::from_secs(timeout),
+                format: output.unwrap_or(OutputFormat::Table),
+            })
+            .await;
+            exit(status)
+        }
+        Some(Commands::ExportSchema {
+            server,
+            lang,
+            out,
+            timeout,
+        }) => {
+            export_schema::run_export_schema(export_schema::ExportSchemaOptions {
+                server,
+                lang,
+                out,
+                timeout: Duration::from_secs(timeout),
+            })
+            .await
+        }
+        Some(Commands::Session { command }) => session::run_session(command).await,
+        Some(Commands::Audit { command }) => audit::run_audit(command),
+        Some(Commands::Bundle { command }) => bundle::run_bundle(command),
+        Some(Commands::Servers { tag }) => run_servers(tag),
+        Some(Commands::DemoServer {
+            http,
+            fail_every,
+            allowed_origins,
+        }) => {
+            demo_server::run_demo_server(demo_server::DemoOptions {
+                http,
+                fail_every,
+                allowed_origins,
+            })
+            .await
+        }
+        Some(Commands::Serve {
+            backend,
+            listen,
+            allowed_origins,
+            timeout,
+            advertise,
+            shared_backends,
+            auth,
+        }) => {
+            serve::run_serve(serve::ServeOptions {
+                backend,
+                listen,
+                allowed_origins,
+                timeout: Duration::from_secs(timeout),
+                advertise,
+                shared_backends,
+                auth: auth
+                    .map(mcp_transport::auth::AuthConfig::from_file)
+                    .transpose()?,
+            })
+            .await
+        }
+        Some(Commands::Broker {
+            endpoint,
+            idle,
+            timeout,
+            exit_when_idle,
+            auth,
+        }) => {
+            broker::run_broker(broker::BrokerOptions {
+                endpoint,
+                idle: Duration::from_secs(idle),
+                timeout: Duration::from_secs(timeout),
+                exit_when_idle,
+                auth: auth
+                    .map(mcp_transport::auth::AuthConfig::from_file)
+                    .transpose()?,
+            })
+            .await
+        }
         None => {
             // Default to monitor
-            run_monitor("/tmp/mcp-monitor.sock".to_string(), false).await
+            run_monitor(mcp_common::DEFAULT_IPC_ENDPOINT.to_string(), false).await
         }
     }
 }
 
+/// Leave with the status of a scripted command
+fn exit(status: output::Status) -> Result<()> {
+    match status {
+        output::Status::Success => Ok(()),
+        status => std::process::exit(status.code()),
+    }
+}
+
 async fn run_monitor(ipc_socket: String, verbose: bool) -> Result<()> {
     // Import the monitor functionality
     use mcp_ui::{run_monitor_app, MonitorArgs};
@@ -103,6 +855,8 @@ async fn run_monitor(ipc_socket: String, verbose: bool) -> Result<()> {
 
 async fn run_proxy(
     transport: String,
+    config: Option<PathBuf>,
+    server: Option<String>,
     command: Option<String>,
     url: Option<String>,
     api_key: Option<String>,
@@ -111,18 +865,55 @@ async fn run_proxy(
     verbose: bool,
     shell: bool,
     no_monitor: bool,
+    fidelity: String,
+    log_format: Option<String>,
+    log_file: Option<PathBuf>,
+    log_rotate: String,
+    keep: usize,
+    faults: Option<PathBuf>,
+    repair_arguments: bool,
+    pipeline: Option<PathBuf>,
+    overhead_budget: Option<u64>,
+    notification_limit: u32,
+    notification_mute: u64,
+    mirror: Option<String>,
+    mirror_ignore: Vec<String>,
+    cache_ttl: u64,
+    spool: Option<PathBuf>,
+    spool_capacity: usize,
+    spool_max_age: u64,
+    audit_log: Option<PathBuf>,
+    auth: Option<PathBuf>,
+    max_message_size: usize,
+    truncate_oversized: bool,
+    spill_dir: Option<PathBuf>,
+    metrics_addr: Option<SocketAddr>,
 ) -> Result<()> {
     // Import the proxy functionality
-    use mcp_transport::{run_proxy_app, ProxyArgs, TransportConfig};
+    use mcp_common::LogFormat;
+    use mcp_core::registry::ServerRegistry;
+    use mcp_transport::auth::AuthConfig;
+    use mcp_transport::bundle::Pipeline;
+    use mcp_transport::{
+        log_file as proxy_log_file, message_limits, notification_limits, response_cache, run_proxy_app,
+        spool as monitor_spool, FaultConfig, MirrorConfig, ProxyArgs, TransportConfig,
+    };
 
     // Build transport config from CLI args
-    let transport_config = TransportConfig::from_cli_args(
-        &transport,
-        command,
-        url,
-        shell,
-        api_key,
-    )?;
+    let transport_config = match (config, server) {
+        (Some(path), _) => TransportConfig::aggregate_from_file(path)?,
+        (None, Some(server)) => {
+            let profile = ServerRegistry::load_default()?.profile(&server)?;
+            TransportConfig::from_profile(&server, &profile)?
+        }
+        (None, None) => TransportConfig::from_cli_args(
+            &transport,
+            command,
+            url,
+            shell,
+            api_key,
+        )?,
+    };
 
     let args = ProxyArgs {
         transport_config,
@@ -130,7 +921,57 @@ async fn run_proxy(
         ipc_socket,
         verbose,
         no_monitor,
+        fidelity: fidelity.parse()?,
+        log_format: LogFormat::resolve(log_format.as_deref(), LogFormat::Text)?,
+        log_file: proxy_log_file(log_file, &log_rotate, keep)?,
+        faults: faults.map(FaultConfig::from_file).transpose()?,
+        repair_arguments,
+        pipeline: pipeline.map(Pipeline::load).transpose()?,
+        overhead_budget: overhead_budget.map(Duration::from_micros),
+        notification_limits: notification_limits(notification_limit, notification_mute),
+        mirror: mirror.map(|command| MirrorConfig {
+            ignore: mirror_ignore,
+            ..MirrorConfig::new(TransportConfig::Stdio {
+                command,
+                use_shell: shell,
+            })
+        }),
+        response_cache: response_cache(cache_ttl),
+        spool: monitor_spool(spool, spool_capacity, spool_max_age),
+        audit_log,
+        auth: auth.map(AuthConfig::from_file).transpose()?,
+        message_limits: message_limits(max_message_size, truncate_oversized, spill_dir),
+        metrics_addr,
     };
 
     run_proxy_app(args).await
 }
+
+fn run_servers(tag: Option<String>) -> Result<()> {
+    use mcp_core::registry::ServerRegistry;
+
+    let registry = ServerRegistry::load_default()?;
+    let path = ServerRegistry::default_path();
+    if registry.is_empty() {
+        match path {
+            Some(path) => println!("No server profiles defined in {}", path.display()),
+            None => println!("No server profiles defined"),
+        }
+        return Ok(());
+    }
+
+    println!("{:<20} {:<12} {:<40} TAGS", "NAME", "TRANSPORT", "TARGET");
+    for (name, profile) in registry.profiles() {
+        if tag.as_deref().is_some_and(|tag| !profile.has_tag(tag)) {
+            continue;
+        }
+        println!(
+            "{:<20} {:<12} {:<40} {}",
+            name,
+            profile.transport,
+            profile.target(),
+            profile.tags.join(",")
+        );
+    }
+    Ok(())
+}
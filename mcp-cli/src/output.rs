@@ -0,0 +1,195 @@
+//! Output formats and exit statuses of the scripting commands
+//!
+//! `call`, `list`, `probe`, `doctor`, `monitor status` and `proxy status`
+//! print their result as JSON, YAML or a table, chosen with the global
+//! `--output` flag.
+//! JSON and YAML carry the same document. A failure is printed in the same
+//! format on stdout, as `{"error": {"status": ..., "code": ..., "message":
+//! ...}}`, and the exit [`Status`] tells transport failures, protocol errors
+//! and tool errors apart.
+
+use serde_json::{json, Value};
+
+/// How results are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON
+    Json,
+    /// The same document as YAML
+    Yaml,
+    /// Aligned columns for reading in a terminal
+    Table,
+}
+
+impl OutputFormat {
+    /// `document` as JSON or YAML, or laid out by `table`
+    pub fn render(self, document: &Value, table: impl FnOnce(&Value) -> String) -> String {
+        match self {
+            OutputFormat::Json => format!(
+                "{}\n",
+                serde_json::to_string_pretty(document).unwrap_or_default()
+            ),
+            OutputFormat::Yaml => serde_yaml::to_string(document).unwrap_or_default(),
+            OutputFormat::Table => table(document),
+        }
+    }
+}
+
+/// Exit status of the scripting commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success = 0,
+    /// The tool ran but failed
+    ToolError = 1,
+    /// The arguments or the server profile are invalid
+    InvalidInput = 2,
+    /// The server, monitor or proxy could not be reached, started or
+    /// initialized
+    ConnectFailed = 3,
+    /// The server answered with a JSON-RPC error, or not in time
+    RequestFailed = 4,
+    /// The tool produced output but also reported errors or warnings
+    Partial = 5,
+    /// The outcome differs from the one given with `--expect`
+    UnexpectedOutcome = 6,
+    /// `probe` found violations of the specification
+    Violations = 7,
+    /// `doctor` found problems
+    Unhealthy = 8,
+}
+
+impl Status {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Name of the status in structured output
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Success => "success",
+            Status::ToolError => "tool_error",
+            Status::InvalidInput => "invalid_input",
+            Status::ConnectFailed => "connect_failed",
+            Status::RequestFailed => "request_failed",
+            Status::Partial => "partial",
+            Status::UnexpectedOutcome => "unexpected_outcome",
+            Status::Violations => "violations",
+            Status::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+/// An error and the status it exits with
+pub(crate) struct Failure {
+    pub status: Status,
+    pub error: anyhow::Error,
+}
+
+impl Failure {
+    /// The error document printed for JSON and YAML output
+    fn document(&self) -> Value {
+        json!({
+            "error": {
+                "status": self.status.name(),
+                "code": self.status.code(),
+                "message": format!("{:#}", self.error),
+            }
+        })
+    }
+}
+
+pub(crate) trait WithStatus<T> {
+    fn status(self, status: Status) -> Result<T, Failure>;
+}
+
+impl<T, E: Into<anyhow::Error>> WithStatus<T> for Result<T, E> {
+    fn status(self, status: Status) -> Result<T, Failure> {
+        self.map_err(|error| Failure {
+            status,
+            error: error.into(),
+        })
+    }
+}
+
+/// Print a command's output or failure and return its exit status
+pub(crate) fn report(format: OutputFormat, outcome: Result<(String, Status), Failure>) -> Status {
+    match outcome {
+        Ok((output, status)) => {
+            print!("{}", output);
+            status
+        }
+        Err(failure) => {
+            match format {
+                OutputFormat::Table => eprintln!("Error: {:#}", failure.error),
+                format => print!("{}", format.render(&failure.document(), |_| String::new())),
+            }
+            failure.status
+        }
+    }
+}
+
+/// Left-aligned columns; the last column is not padded
+pub(crate) fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let headers = headers.iter().map(|header| header.to_string()).collect();
+    let mut out = String::new();
+    for row in std::iter::once(headers).chain(rows) {
+        let last = row.len().saturating_sub(1);
+        let line: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                if i == last {
+                    value.clone()
+                } else {
+                    format!("{:<width$}", value, width = widths[i])
+                }
+            })
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_documents_render_in_every_format() {
+        let document = json!({ "name": "add", "tags": ["math"] });
+        let table = |_: &Value| "NAME\nadd\n".to_string();
+
+        let json = OutputFormat::Json.render(&document, table);
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), document);
+        let yaml = OutputFormat::Yaml.render(&document, table);
+        assert_eq!(yaml, "name: add\ntags:\n- math\n");
+        assert_eq!(OutputFormat::Table.render(&document, table), "NAME\nadd\n");
+    }
+
+    #[test]
+    fn test_failures_have_a_stable_document() {
+        let failure = Err::<(), _>(anyhow!("connection refused"))
+            .status(Status::ConnectFailed)
+            .err()
+            .unwrap();
+        assert_eq!(
+            failure.document(),
+            json!({
+                "error": {
+                    "status": "connect_failed",
+                    "code": 3,
+                    "message": "connection refused",
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Check a server against the MCP specification
+
+use crate::output::{report, Failure, OutputFormat, Status, WithStatus};
+use anyhow::{Context, Result};
+use mcp_core::compliance::{self, ComplianceReport, ProbeOptions, Severity};
+use mcp_core::conformance::{self, ConformanceOptions, ConformanceReport, Outcome};
+use mcp_core::messages::ProtocolVersion;
+use mcp_core::TransportConfig;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Options for `assist-mcp probe`
+pub struct ProbeArgs {
+    pub server: String,
+    pub protocol: Option<String>,
+    pub call_tools: bool,
+    pub strict: bool,
+    pub samples: usize,
+    pub timeout: u64,
+    pub format: OutputFormat,
+    pub out: Option<PathBuf>,
+    pub all: bool,
+    pub conformance: bool,
+    pub cases: Vec<String>,
+}
+
+/// Probe the server and print the report
+///
+/// Exits with [`Status::Violations`] when a violation is found so the
+/// command can gate CI.
+pub async fn run_probe(args: ProbeArgs) -> Status {
+    report(args.format, probe(&args).await)
+}
+
+async fn probe(args: &ProbeArgs) -> Result<(String, Status), Failure> {
+    let (transport, _) =
+        crate::export::resolve_server(&args.server).status(Status::InvalidInput)?;
+    if args.conformance {
+        return run_conformance(args, transport).await;
+    }
+    let mut options = ProbeOptions {
+        call_tools: args.call_tools,
+        strict: args.strict,
+        max_samples: args.samples,
+        timeout: Duration::from_secs(args.timeout),
+        ..Default::default()
+    };
+    if let Some(version) = &args.protocol {
+        options.protocol_version = protocol_version(version).status(Status::InvalidInput)?;
+    }
+
+    let report = compliance::probe(transport, options)
+        .await
+        .with_context(|| format!("Failed to initialize '{}'", args.server))
+        .status(Status::ConnectFailed)?;
+
+    let output = write_report(
+        args,
+        &serde_json::to_value(&report).unwrap_or_default(),
+        |_| summary(&report, args.all),
+    )?;
+    Ok((output, verdict(report.count(Severity::Violation))))
+}
+
+/// Run the conformance suite and print its results
+///
+/// Fails when a case finds a violation, like the compliance probe.
+async fn run_conformance(
+    args: &ProbeArgs,
+    transport: TransportConfig,
+) -> Result<(String, Status), Failure> {
+    let mut options = ConformanceOptions {
+        timeout: Duration::from_secs(args.timeout),
+        only: args.cases.clone(),
+        ..Default::default()
+    };
+    if let Some(version) = &args.protocol {
+        options.protocol_version = protocol_version(version).status(Status::InvalidInput)?;
+    }
+
+    let report = conformance::run(transport, options)
+        .await
+        .with_context(|| format!("Failed to initialize '{}'", args.server))
+        .status(Status::ConnectFailed)?;
+
+    let output = write_report(
+        args,
+        &serde_json::to_value(&report).unwrap_or_default(),
+        |_| conformance_summary(&report, args.all),
+    )?;
+    Ok((output, verdict(report.violations().count())))
+}
+
+/// The exit status for a report with `violations`
+fn verdict(violations: usize) -> Status {
+    if violations > 0 {
+        eprintln!("{} violation(s) found", violations);
+        Status::Violations
+    } else {
+        Status::Success
+    }
+}
+
+fn protocol_version(version: &str) -> Result<ProtocolVersion> {
+    Ok(serde_json::from_value(Value::String(version.to_string()))?)
+}
+
+/// Write the report to `--out` and render it for printing, as a summary
+/// for tables
+fn write_report(
+    args: &ProbeArgs,
+    report: &Value,
+    summary: impl FnOnce(&Value) -> String,
+) -> Result<String, Failure> {
+    if let Some(path) = &args.out {
+        std::fs::write(
+            path,
+            serde_json::to_string_pretty(report).unwrap_or_default(),
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))
+        .status(Status::InvalidInput)?;
+    }
+    Ok(args.format.render(report, summary))
+}
+
+/// Human-readable conformance results; passed and skipped cases are listed
+/// only with `all`
+fn conformance_summary(report: &ConformanceReport, all: bool) -> String {
+    let mut out = format!(
+        "{} {} (protocol {})\n",
+        report.server.name, report.server.version, report.protocol_version
+    );
+    for result in &report.results {
+        let label = match (result.outcome, result.severity) {
+            (Outcome::Failed, Severity::Violation) => "FAIL",
+            (Outcome::Failed, _) => "WARN",
+            (Outcome::Skipped, _) if all => "skip",
+            (Outcome::Passed, _) if all => "ok",
+            _ => continue,
+        };
+        out.push_str(&format!(
+            "  {:<4}  {}: {}\n",
+            label, result.id, result.message
+        ));
+    }
+    out.push_str(&format!(
+        "{} cases: {} passed, {} warning(s), {} violation(s), {} skipped\n",
+        report.results.len(),
+        report.count(Outcome::Passed),
+        report.failures().count() - report.violations().count(),
+        report.violations().count(),
+        report.count(Outcome::Skipped)
+    ));
+    out
+}
+
+/// Human-readable report; passed checks are listed only with `all`
+fn summary(report: &ComplianceReport, all: bool) -> String {
+    let mut out = format!(
+        "{} {} (protocol {})\n",
+        report.server.name, report.server.version, report.protocol_version
+    );
+    for finding in &report.findings {
+        let label = match finding.severity {
+            Severity::Violation => "FAIL",
+            Severity::Warning => "WARN",
+            Severity::Pass if all => "ok",
+            Severity::Pass => continue,
+        };
+        out.push_str(&format!(
+            "  {:<4}  {}: {}\n",
+            label, finding.area, finding.message
+        ));
+    }
+    out.push_str(&format!(
+        "{} checks: {} passed, {} warning(s), {} violation(s)\n",
+        report.findings.len(),
+        report.count(Severity::Pass),
+        report.count(Severity::Warning),
+        report.count(Severity::Violation)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo_server::{serve_http, DemoServer};
+    use mcp_core::transport::origin::OriginPolicy;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_demo_server_is_compliant() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost(),
+        ));
+
+        let (transport, _) = crate::export::resolve_server(&url).unwrap();
+        let options = ProbeOptions {
+            timeout: Duration::from_secs(10),
+            ..Default::default()
+        };
+        let report = compliance::probe(transport, options).await.unwrap();
+        assert!(report.is_compliant(), "{}", summary(&report, false));
+
+        let text = summary(&report, false);
+        assert!(!text.contains("  ok  "));
+        assert!(text.ends_with("0 violation(s)\n"));
+        assert!(summary(&report, true).contains("  ok    ping: answered"));
+    }
+
+    #[tokio::test]
+    async fn test_demo_server_conforms() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(serve_http(
+            Arc::new(DemoServer::new(None)),
+            listener,
+            OriginPolicy::localhost(),
+        ));
+
+        let (transport, _) = crate::export::resolve_server(&url).unwrap();
+        let options = ConformanceOptions {
+            timeout: Duration::from_secs(10),
+            ..Default::default()
+        };
+        let report = conformance::run(transport, options).await.unwrap();
+        let text = conformance_summary(&report, false);
+        assert!(report.is_conformant(), "{}", text);
+        assert!(text.contains(" 0 violation(s), "));
+        assert!(!text.contains("  ok  "));
+        assert!(conformance_summary(&report, true).contains("  ok    concurrency.pings: "));
+    }
+}
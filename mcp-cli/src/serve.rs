@@ -0,0 +1,203 @@
+//! Publish a local server to remote clients over Streamable HTTP
+//!
+//! `assist-mcp serve --backend stdio:CMD` listens for Streamable HTTP
+//! clients and gives each of their sessions its own backend connection, or
+//! with `--shared-backends N` one of a pool of N shared by all sessions, see
+//! [`mcp_transport::serve`]. What each client did is printed on exit. Only
+//! loopback origins and hosts are accepted when listening on loopback;
+//! browser origins are added with `--allow-origin`. With `--advertise NAME`
+//! the endpoint is announced over mDNS, so `assist-mcp discover` finds it on
+//! other machines.
+//!
+//! With `--auth FILE`, clients must send an API key of one of the file's
+//! identities and their requests are checked against its policies, see
+//! [`mcp_transport::auth`]. Listening beyond loopback or advertising is
+//! refused without it.
+
+use anyhow::{bail, Context, Result};
+use mcp_core::discovery;
+use mcp_core::transport::origin::OriginPolicy;
+use mcp_core::TransportConfig;
+use mcp_transport::auth::{AuthConfig, ClientGate};
+use mcp_transport::serve::{Endpoint, ServeConfig, DEFAULT_PATH};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use crate::demo_server::default_origins;
+
+/// Options for `assist-mcp serve`
+pub struct ServeOptions {
+    /// `stdio:COMMAND`, an http(s) URL, a registry name or a profile
+    pub backend: String,
+    /// Address to serve Streamable HTTP on
+    pub listen: String,
+    /// Browser origins allowed besides the defaults
+    pub allowed_origins: Vec<String>,
+    /// How long a request may wait for the backend
+    pub timeout: Duration,
+    /// Name to announce the endpoint under over mDNS
+    pub advertise: Option<String>,
+    /// Backend connections shared by all sessions, 0 for one per session
+    pub shared_backends: usize,
+    /// Identities allowed to use the endpoint; required unless it is only
+    /// reachable from this machine
+    pub auth: Option<AuthConfig>,
+}
+
+pub async fn run_serve(options: ServeOptions) -> Result<()> {
+    let backend = backend_config(&options.backend)?;
+    let listener = TcpListener::bind(&options.listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", options.listen))?;
+    let addr = listener.local_addr()?;
+    let config = serve_config(backend, addr, &options)?;
+    eprintln!(
+        "Serving {} on http://{}{}",
+        options.backend, addr, DEFAULT_PATH
+    );
+    let _advertisement = match &options.advertise {
+        Some(name) => {
+            if addr.ip().is_loopback() {
+                eprintln!("Warning: advertising an endpoint only reachable from this machine");
+            }
+            let properties = HashMap::from([
+                ("path".to_string(), DEFAULT_PATH.to_string()),
+                ("transport".to_string(), "http-stream".to_string()),
+            ]);
+            let advertisement = discovery::advertise(name, addr.port(), properties)?;
+            eprintln!("Advertising as {} ({})", name, discovery::MCP_SERVICE_TYPE);
+            Some(advertisement)
+        }
+        None => None,
+    };
+    let endpoint = Endpoint::new(config);
+    let result = tokio::select! {
+        result = endpoint.run(listener) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
+    for client in endpoint.clients() {
+        eprintln!(
+            "Session {}: {} requests ({} failed), {} notifications sent, {} messages received, {} subscriptions",
+            client.session,
+            client.requests,
+            client.errors,
+            client.notifications,
+            client.delivered,
+            client.subscriptions
+        );
+    }
+    result
+}
+
+/// How to serve `backend` on `addr`
+///
+/// An endpoint other machines can reach, by its address or by being
+/// advertised, must authenticate its clients.
+fn serve_config(
+    backend: TransportConfig,
+    addr: SocketAddr,
+    options: &ServeOptions,
+) -> Result<ServeConfig> {
+    if options.auth.is_none() {
+        if !addr.ip().is_loopback() {
+            bail!(
+                "Refusing to serve on {} without --auth: anyone who can reach it could use the backend",
+                addr
+            );
+        }
+        if options.advertise.is_some() {
+            bail!(
+                "Refusing to advertise without --auth: anyone on the network could use the backend"
+            );
+        }
+    }
+    let origins = options
+        .allowed_origins
+        .iter()
+        .cloned()
+        .fold(default_origins(addr), OriginPolicy::allow_origin);
+    Ok(ServeConfig {
+        origins,
+        request_timeout: Some(options.timeout),
+        shared_backends: options.shared_backends,
+        gate: options.auth.clone().map(ClientGate::from_config),
+        ..ServeConfig::new(backend)
+    })
+}
+
+/// The backend a `--backend` value names
+fn backend_config(backend: &str) -> Result<TransportConfig> {
+    match backend.strip_prefix("stdio:") {
+        Some(command) => Ok(TransportConfig::stdio("sh", &["-c", command])),
+        None => Ok(crate::export::resolve_server(backend)?.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_transport::auth::IdentityConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn test_stdio_backends_run_through_the_shell() {
+        let TransportConfig::Stdio(config) = backend_config("stdio:npx -y server-fs /tmp").unwrap()
+        else {
+            panic!("expected a stdio backend");
+        };
+        assert_eq!(config.command, "sh");
+        assert_eq!(config.args, ["-c", "npx -y server-fs /tmp"]);
+        assert!(matches!(
+            backend_config("http://127.0.0.1:8931/mcp").unwrap(),
+            TransportConfig::HttpStream(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_network_listeners_require_credentials() {
+        let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut options = ServeOptions {
+            backend: "stdio:cat".to_string(),
+            listen: addr.to_string(),
+            allowed_origins: Vec::new(),
+            timeout: Duration::from_secs(5),
+            advertise: None,
+            shared_backends: 0,
+            auth: None,
+        };
+        let backend = || backend_config(&options.backend).unwrap();
+        assert!(serve_config(backend(), addr, &options).is_err());
+        let loopback = SocketAddr::from(([127, 0, 0, 1], addr.port()));
+        assert!(serve_config(backend(), loopback, &options).is_ok());
+        options.advertise = Some("laptop".to_string());
+        assert!(serve_config(backend(), loopback, &options).is_err());
+
+        options.auth = Some(AuthConfig {
+            identities: vec![IdentityConfig {
+                name: "ci".to_string(),
+                api_keys: vec!["ci-key".to_string()],
+                ..IdentityConfig::default()
+            }],
+        });
+        let config = serve_config(backend(), addr, &options).unwrap();
+        tokio::spawn(async move { Endpoint::new(config).run(listener).await });
+
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+        let mut stream = TcpStream::connect(loopback).await.unwrap();
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            DEFAULT_PATH,
+            addr,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+    }
+}
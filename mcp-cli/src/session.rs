@@ -0,0 +1,206 @@
+//! Convert and inspect captured MCP sessions, and manage live ones held by
+//! the connection broker
+
+use crate::broker::{BrokerClient, DEFAULT_BROKER_ENDPOINT};
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use mcp_common::{EntryKind, SessionArchive};
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum SessionCommand {
+    /// Convert between a session archive (.json) and a JSONL recording (.jsonl)
+    Convert {
+        /// Archive or recording to read (format is detected from the content)
+        input: PathBuf,
+
+        /// File to write; a .jsonl extension writes a recording, anything else an archive
+        output: PathBuf,
+
+        /// Note stored in the archive, e.g. a bug report reference
+        #[arg(long)]
+        comment: Option<String>,
+    },
+    /// Print a summary of every exchange in a session
+    Show {
+        /// Archive or recording to read
+        file: PathBuf,
+    },
+    /// Send a request over a live session kept open by the broker
+    Send {
+        /// Registry profile, HTTP URL, profile file or saved profile name
+        server: String,
+
+        /// JSON-RPC method, e.g. tools/list
+        method: String,
+
+        /// Request params as JSON
+        #[arg(default_value = "{}")]
+        params: String,
+
+        /// Broker endpoint (a broker is started there if none is running)
+        #[arg(long, default_value = DEFAULT_BROKER_ENDPOINT)]
+        endpoint: String,
+    },
+    /// List the live sessions held by the broker
+    Live {
+        /// Broker endpoint
+        #[arg(long, default_value = DEFAULT_BROKER_ENDPOINT)]
+        endpoint: String,
+    },
+    /// Close live sessions held by the broker
+    Close {
+        /// Server whose session to close
+        #[arg(required_unless_present = "all")]
+        server: Option<String>,
+
+        /// Close every session
+        #[arg(long, conflicts_with = "server")]
+        all: bool,
+
+        /// Broker endpoint
+        #[arg(long, default_value = DEFAULT_BROKER_ENDPOINT)]
+        endpoint: String,
+    },
+}
+
+pub async fn run_session(command: SessionCommand) -> Result<()> {
+    match command {
+        SessionCommand::Convert {
+            input,
+            output,
+            comment,
+        } => convert(&input, &output, comment),
+        SessionCommand::Show { file } => show(&file),
+        SessionCommand::Send {
+            server,
+            method,
+            params,
+            endpoint,
+        } => send(&endpoint, &server, &method, &params).await,
+        SessionCommand::Live { endpoint } => live(&endpoint).await,
+        SessionCommand::Close {
+            server,
+            all: _,
+            endpoint,
+        } => close(&endpoint, server.as_deref()).await,
+    }
+}
+
+async fn send(endpoint: &str, server: &str, method: &str, params: &str) -> Result<()> {
+    let params: serde_json::Value =
+        serde_json::from_str(params).context("Params must be valid JSON")?;
+    let mut broker = BrokerClient::connect_or_spawn(endpoint).await?;
+    let (response, _) = broker.call(server, method, params).await?;
+    if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+        bail!(
+            "{} failed: {}",
+            method,
+            error["message"].as_str().unwrap_or("unknown error")
+        );
+    }
+    println!("{}", serde_json::to_string_pretty(&response["result"])?);
+    Ok(())
+}
+
+async fn live(endpoint: &str) -> Result<()> {
+    let sessions = match BrokerClient::connect(endpoint).await {
+        Ok(mut broker) => broker.sessions().await?,
+        Err(_) => Vec::new(),
+    };
+    if sessions.is_empty() {
+        println!("No live sessions");
+    }
+    for session in sessions {
+        println!(
+            "{:<40} {:>6} requests  started {}  last used {}",
+            session.server,
+            session.requests,
+            session.started_at.format("%H:%M:%S"),
+            session.last_used.format("%H:%M:%S")
+        );
+    }
+    Ok(())
+}
+
+async fn close(endpoint: &str, server: Option<&str>) -> Result<()> {
+    let closed = match BrokerClient::connect(endpoint).await {
+        Ok(mut broker) => broker.close(server).await?,
+        Err(_) => Vec::new(),
+    };
+    match (closed.is_empty(), server) {
+        (true, Some(server)) => bail!("No live session for '{}'", server),
+        (true, None) => println!("No live sessions"),
+        (false, _) => {
+            for server in closed {
+                println!("Closed {}", server);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn convert(input: &Path, output: &Path, comment: Option<String>) -> Result<()> {
+    let mut archive = SessionArchive::load(input)?;
+    archive.creator.name = "assist-mcp".to_string();
+    archive.creator.version = env!("CARGO_PKG_VERSION").to_string();
+    if comment.is_some() {
+        archive.comment = comment;
+    }
+
+    if is_jsonl(output) {
+        std::fs::write(output, archive.to_jsonl()?)?;
+    } else {
+        archive.save(output)?;
+    }
+    println!(
+        "Wrote {} entries to {}",
+        archive.entries.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn show(file: &Path) -> Result<()> {
+    let archive = SessionArchive::load(file)?;
+    if let Some(ref comment) = archive.comment {
+        println!("# {}", comment);
+    }
+
+    for entry in &archive.entries {
+        let label = match entry.kind {
+            EntryKind::Request | EntryKind::Notification => {
+                entry.method.clone().unwrap_or_default()
+            }
+            EntryKind::Response => "(unmatched response)".to_string(),
+        };
+        let outcome = match (&entry.kind, &entry.response, entry.time_ms) {
+            (EntryKind::Request, Some(_), Some(ms)) if entry.is_error() => {
+                format!("error {:.1}ms", ms)
+            }
+            (EntryKind::Request, Some(_), Some(ms)) => format!("ok {:.1}ms", ms),
+            (EntryKind::Request, None, _) => "no response".to_string(),
+            _ => String::new(),
+        };
+        let id = entry
+            .id
+            .as_ref()
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+
+        println!(
+            "{}  {:<16} {:<6} {:<32} {}",
+            entry.started.format("%H:%M:%S%.3f"),
+            format!("{:?}", entry.direction),
+            id,
+            label,
+            outcome
+        );
+    }
+    println!("{} entries", archive.entries.len());
+    Ok(())
+}
+
+fn is_jsonl(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "jsonl")
+}
@@ -0,0 +1,405 @@
+//! `monitor status` and `proxy status`: whether a monitor or proxy is up and
+//! what it reports, for scripts and health checks
+//!
+//! A monitor is checked with the IPC handshake proxies use to reach it. A
+//! proxy is checked through the Prometheus endpoint it serves with
+//! `--metrics-addr`, whose samples are summed up into a stable document.
+
+use crate::output::{report, table, Failure, OutputFormat, Status, WithStatus};
+use anyhow::{anyhow, bail, Context};
+use clap::Subcommand;
+use mcp_common::{IpcClient, IpcProtocolVersion, PeerRole};
+use mcp_transport::exporter::METRICS_PATH;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Prefix of the metric names a proxy serves
+const METRIC_PREFIX: &str = "genmcp_proxy_";
+
+#[derive(Subcommand)]
+pub enum MonitorCommand {
+    /// Check that a monitor is listening and print the IPC protocol it speaks
+    Status {
+        /// IPC endpoint of the monitor (socket path, pipe:NAME or tcp:HOST:PORT)
+        #[arg(short, long, default_value = mcp_common::DEFAULT_IPC_ENDPOINT)]
+        ipc_socket: String,
+
+        /// Seconds allowed for connecting and the handshake
+        #[arg(short, long, default_value_t = 5)]
+        timeout: u64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProxyCommand {
+    /// Print the request, tool call and traffic totals of a running proxy
+    Status {
+        /// Address the proxy serves metrics on (its --metrics-addr)
+        #[arg(long, value_name = "ADDR")]
+        metrics_addr: SocketAddr,
+
+        /// Seconds allowed for fetching the metrics
+        #[arg(short, long, default_value_t = 5)]
+        timeout: u64,
+    },
+}
+
+/// Check a monitor and print what it answered
+pub async fn run_monitor_status(command: MonitorCommand, format: OutputFormat) -> Status {
+    let MonitorCommand::Status {
+        ipc_socket,
+        timeout,
+    } = command;
+    report(
+        format,
+        monitor_status(&ipc_socket, Duration::from_secs(timeout), format).await,
+    )
+}
+
+/// Print a proxy's totals
+pub async fn run_proxy_status(command: ProxyCommand, format: OutputFormat) -> Status {
+    let ProxyCommand::Status {
+        metrics_addr,
+        timeout,
+    } = command;
+    report(
+        format,
+        proxy_status(metrics_addr, Duration::from_secs(timeout), format).await,
+    )
+}
+
+async fn monitor_status(
+    endpoint: &str,
+    timeout: Duration,
+    format: OutputFormat,
+) -> Result<(String, Status), Failure> {
+    let version = handshake(endpoint, timeout)
+        .await
+        .with_context(|| format!("No monitor answering at {}", endpoint))
+        .status(Status::ConnectFailed)?;
+
+    let document = json!({
+        "endpoint": endpoint,
+        "running": true,
+        "protocol_version": version.to_string(),
+    });
+    let output = format.render(&document, |_| {
+        table(
+            &["ENDPOINT", "PROTOCOL"],
+            vec![vec![endpoint.to_string(), version.to_string()]],
+        )
+    });
+    Ok((output, Status::Success))
+}
+
+async fn proxy_status(
+    addr: SocketAddr,
+    timeout: Duration,
+    format: OutputFormat,
+) -> Result<(String, Status), Failure> {
+    let exposition = tokio::time::timeout(timeout, scrape(addr))
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("timed out after {:?}", timeout)))
+        .with_context(|| format!("No proxy metrics at http://{}{}", addr, METRICS_PATH))
+        .status(Status::ConnectFailed)?;
+    let document = summarize(&addr.to_string(), &exposition);
+    Ok((format.render(&document, proxy_table), Status::Success))
+}
+
+/// Connect to a monitor and agree on an IPC protocol version, the way a
+/// proxy does
+pub(crate) async fn handshake(
+    endpoint: &str,
+    timeout: Duration,
+) -> anyhow::Result<IpcProtocolVersion> {
+    let handshake = async {
+        let mut client = IpcClient::connect(endpoint).await?;
+        client
+            .handshake(
+                PeerRole::Proxy,
+                concat!("assist-mcp/", env!("CARGO_PKG_VERSION")),
+            )
+            .await
+    };
+    tokio::time::timeout(timeout, handshake)
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("timed out after {:?}", timeout)))
+}
+
+/// Fetch the metrics page; the endpoint answers once and closes
+async fn scrape(addr: SocketAddr) -> anyhow::Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        METRICS_PATH, addr
+    );
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .context("Malformed HTTP response")?;
+    let status_line = head.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        bail!("Metrics endpoint answered {}", status_line);
+    }
+    Ok(body.to_string())
+}
+
+/// Totals of one method
+#[derive(Default)]
+struct MethodTotals {
+    requests: u64,
+    errors: u64,
+    latency_secs: f64,
+}
+
+/// The proxy status document from its Prometheus text
+///
+/// Requests and tool calls are sorted by name; samples of other metrics are
+/// ignored, so the document keeps its shape as the proxy grows new ones.
+fn summarize(endpoint: &str, exposition: &str) -> Value {
+    let mut methods: BTreeMap<String, MethodTotals> = BTreeMap::new();
+    let mut tool_calls: BTreeMap<String, u64> = BTreeMap::new();
+    let mut totals: BTreeMap<&str, u64> = BTreeMap::new();
+
+    for line in exposition.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((series, value)) = line.rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<f64>() else {
+            continue;
+        };
+        let (name, labels) = match series.split_once('{') {
+            Some((name, labels)) => (name, parse_labels(labels.trim_end_matches('}'))),
+            None => (series, Vec::new()),
+        };
+        let Some(name) = name.strip_prefix(METRIC_PREFIX) else {
+            continue;
+        };
+        let label = |key: &str| {
+            labels
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.clone())
+        };
+
+        match name {
+            "requests_total" | "request_errors_total" | "request_duration_seconds_sum" => {
+                let Some(method) = label("method") else {
+                    continue;
+                };
+                let totals = methods.entry(method).or_default();
+                match name {
+                    "requests_total" => totals.requests = value as u64,
+                    "request_errors_total" => totals.errors = value as u64,
+                    _ => totals.latency_secs = value,
+                }
+            }
+            "tool_calls_total" => {
+                if let Some(tool) = label("tool") {
+                    tool_calls.insert(tool, value as u64);
+                }
+            }
+            "connected_clients" | "bytes_total" | "transport_errors_total" => {
+                totals.insert(name, value as u64);
+            }
+            _ => {}
+        }
+    }
+
+    let requests: Vec<Value> = methods
+        .into_iter()
+        .map(|(method, totals)| {
+            let mean_latency_ms = if totals.requests == 0 {
+                0.0
+            } else {
+                totals.latency_secs * 1000.0 / totals.requests as f64
+            };
+            json!({
+                "method": method,
+                "total": totals.requests,
+                "errors": totals.errors,
+                "mean_latency_ms": mean_latency_ms,
+            })
+        })
+        .collect();
+    let tool_calls: Vec<Value> = tool_calls
+        .into_iter()
+        .map(|(tool, total)| json!({ "tool": tool, "total": total }))
+        .collect();
+    let total = |name| totals.get(name).copied().unwrap_or(0);
+    json!({
+        "endpoint": endpoint,
+        "running": true,
+        "connected_clients": total("connected_clients"),
+        "bytes_total": total("bytes_total"),
+        "transport_errors_total": total("transport_errors_total"),
+        "requests": requests,
+        "tool_calls": tool_calls,
+    })
+}
+
+/// `name="value",...` with Prometheus escapes undone
+fn parse_labels(labels: &str) -> Vec<(String, String)> {
+    let mut parsed = Vec::new();
+    let mut rest = labels;
+    while let Some((name, after)) = rest.split_once("=\"") {
+        let mut value = String::new();
+        let mut chars = after.char_indices();
+        let mut end = after.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, escaped)) => value.push(escaped),
+                    None => {}
+                },
+                '"' => {
+                    end = i + 1;
+                    break;
+                }
+                c => value.push(c),
+            }
+        }
+        parsed.push((name.trim_start_matches(',').trim().to_string(), value));
+        rest = &after[end..];
+    }
+    parsed
+}
+
+fn proxy_table(document: &Value) -> String {
+    let number = |value: &Value| value.as_u64().unwrap_or_default().to_string();
+    let mut out = format!(
+        "{}: {} client(s), {} bytes, {} transport error(s)\n",
+        document["endpoint"].as_str().unwrap_or_default(),
+        number(&document["connected_clients"]),
+        number(&document["bytes_total"]),
+        number(&document["transport_errors_total"]),
+    );
+    let rows = document["requests"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|method| {
+            vec![
+                method["method"].as_str().unwrap_or_default().to_string(),
+                number(&method["total"]),
+                number(&method["errors"]),
+                format!("{:.1}", method["mean_latency_ms"].as_f64().unwrap_or(0.0)),
+            ]
+        })
+        .collect();
+    out.push_str(&table(&["METHOD", "REQUESTS", "ERRORS", "MEAN MS"], rows));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_common::{
+        IpcServer, ObservedMessage, ProxyId, ProxyMetrics, ProxyStats, TrafficDirection,
+    };
+    use mcp_transport::exporter::serve_metrics;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tokio::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_proxy_status_sums_up_the_metrics() {
+        let proxy_id = ProxyId::new();
+        let mut metrics = ProxyMetrics::new();
+        for (direction, payload) in [
+            (
+                TrafficDirection::ClientToServer,
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "a \"quoted\" tool"}}),
+            ),
+            (
+                TrafficDirection::ServerToClient,
+                json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32603, "message": "boom"}}),
+            ),
+        ] {
+            metrics.observe(&ObservedMessage::new(proxy_id.clone(), direction, payload));
+        }
+        let stats = ProxyStats {
+            active_connections: 2,
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_metrics(
+            listener,
+            Arc::new(Mutex::new(metrics)),
+            Arc::new(Mutex::new(stats)),
+        ));
+
+        let (output, status) = proxy_status(addr, Duration::from_secs(5), OutputFormat::Json)
+            .await
+            .ok()
+            .unwrap();
+        assert_eq!(status, Status::Success);
+        let document: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(document["connected_clients"], 2);
+        assert_eq!(document["requests"][0]["method"], "tools/call");
+        assert_eq!(document["requests"][0]["total"], 1);
+        assert_eq!(document["requests"][0]["errors"], 1);
+        assert_eq!(
+            document["tool_calls"],
+            json!([{ "tool": "a \"quoted\" tool", "total": 1 }])
+        );
+
+        let (output, _) = proxy_status(addr, Duration::from_secs(5), OutputFormat::Table)
+            .await
+            .ok()
+            .unwrap();
+        assert!(output.contains("2 client(s)"), "{}", output);
+        assert!(output.contains("\ntools/call  1         1"), "{}", output);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_monitor_and_proxy_fail_to_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let failure = proxy_status(addr, Duration::from_secs(5), OutputFormat::Json)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(failure.status, Status::ConnectFailed);
+        let endpoint = format!("tcp:{}", addr);
+        let failure = monitor_status(&endpoint, Duration::from_secs(5), OutputFormat::Json)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(failure.status, Status::ConnectFailed);
+    }
+
+    #[tokio::test]
+    async fn test_monitor_status_reports_the_protocol() {
+        let server = IpcServer::bind("tcp:127.0.0.1:0").await.unwrap();
+        let endpoint = format!("tcp:{}", server.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            while let Ok(Some(_)) = connection.receive_message().await {}
+        });
+
+        let (output, status) =
+            monitor_status(&endpoint, Duration::from_secs(5), OutputFormat::Yaml)
+                .await
+                .ok()
+                .unwrap();
+        assert_eq!(status, Status::Success);
+        assert!(output.contains("running: true\n"), "{}", output);
+        assert!(output.contains("protocol_version: '1.0'\n"), "{}", output);
+    }
+}
@@ -0,0 +1,279 @@
+//! Traces of routing decisions made by the LLM layer.
+//!
+//! Every routed request produces one [`DecisionTrace`]: the prompt given to
+//! the predictor and what the model answered, each candidate tool with its
+//! score and where it came from, the tool finally chosen and how long the
+//! decision took. [`DecisionTrace::explain`] renders it for people. Traces are
+//! appended to a JSONL [`DecisionTraceLog`], one trace per line, and sent to
+//! monitors as [`IpcMessage::DecisionTraced`](crate::IpcMessage).
+//!
+//! For `tools/call` requests the trace also keeps the tool the client asked
+//! for, so a log can be scored offline with [`DecisionTraceLog::summary`].
+
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Where a routing candidate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSource {
+    /// A stored routing rule matched the request
+    Rule,
+    /// The LLM predictor proposed it
+    Prediction,
+}
+
+/// A tool considered for a routed request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceCandidate {
+    pub tool: String,
+    /// Confidence in 0.0..=1.0
+    pub score: f64,
+    pub source: CandidateSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+}
+
+/// Everything that went into one routing decision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTrace {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    /// Routing mode in effect, e.g. `Hybrid`
+    pub mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<Value>,
+    /// Prompt given to the predictor, when it was consulted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Raw model output the prediction was read from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    pub candidates: Vec<TraceCandidate>,
+    /// Tool the router settled on, if any candidate cleared the threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chosen: Option<String>,
+    /// Whether the request was modified because of the decision
+    #[serde(default)]
+    pub applied: bool,
+    /// Tool named by the request itself, used as ground truth offline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_tool: Option<String>,
+    pub latency_ms: f64,
+}
+
+impl DecisionTrace {
+    /// A trace for a request, stamped now, with nothing decided yet
+    pub fn new(mode: impl Into<String>, request: &Value) -> Self {
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let requested_tool = match method.as_deref() {
+            Some("tools/call") => request
+                .pointer("/params/name")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        };
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            mode: mode.into(),
+            method,
+            request_id: request.get("id").cloned(),
+            prompt: None,
+            output: None,
+            candidates: Vec::new(),
+            chosen: None,
+            applied: false,
+            requested_tool,
+            latency_ms: 0.0,
+        }
+    }
+
+    /// Highest-scoring candidate
+    pub fn best(&self) -> Option<&TraceCandidate> {
+        self.candidates
+            .iter()
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+    }
+
+    /// Whether the choice matched the requested tool, when both are known
+    pub fn is_correct(&self) -> Option<bool> {
+        Some(self.chosen.as_ref()? == self.requested_tool.as_ref()?)
+    }
+
+    /// Why the router decided as it did, one line per fact, best candidate first
+    pub fn explain(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{} routing of {} took {:.1} ms",
+            self.mode,
+            self.method.as_deref().unwrap_or("a request"),
+            self.latency_ms
+        )];
+
+        let mut ranked: Vec<&TraceCandidate> = self.candidates.iter().collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        if ranked.is_empty() {
+            lines.push("No candidates were considered".to_string());
+        }
+        for (rank, candidate) in ranked.iter().enumerate() {
+            let source = match candidate.source {
+                CandidateSource::Rule => "routing rule",
+                CandidateSource::Prediction => "model prediction",
+            };
+            let mut line = format!(
+                "{}. {} scored {:.2} from a {}",
+                rank + 1,
+                candidate.tool,
+                candidate.score,
+                source
+            );
+            if let Some(reasoning) = &candidate.reasoning {
+                line.push_str(&format!(": {}", reasoning));
+            }
+            lines.push(line);
+        }
+
+        lines.push(match (&self.chosen, self.applied) {
+            (Some(tool), true) => format!("Chose {} and rewrote the request for it", tool),
+            (Some(tool), false) => format!("Chose {} but left the request unchanged", tool),
+            (None, _) => "No candidate cleared the confidence threshold".to_string(),
+        });
+        if let (Some(false), Some(requested)) = (self.is_correct(), &self.requested_tool) {
+            lines.push(format!("The client asked for {}", requested));
+        }
+        lines
+    }
+}
+
+/// Routing quality over a set of traces
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TraceSummary {
+    pub decisions: usize,
+    /// Decisions with a chosen tool
+    pub chosen: usize,
+    /// Decisions that can be scored against the requested tool
+    pub scored: usize,
+    pub correct: usize,
+    pub mean_latency_ms: f64,
+}
+
+impl TraceSummary {
+    /// Share of scored decisions that picked the requested tool
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.scored > 0).then(|| self.correct as f64 / self.scored as f64)
+    }
+}
+
+/// Append-only JSONL file of decision traces
+#[derive(Debug, Clone)]
+pub struct DecisionTraceLog {
+    path: PathBuf,
+}
+
+impl DecisionTraceLog {
+    /// Use `path`, created on the first append
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one trace as a line
+    pub fn append(&self, trace: &DecisionTrace) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open trace log {}", self.path.display()))?;
+        let mut line = serde_json::to_vec(trace)?;
+        line.push(b'\n');
+        file.write_all(&line)
+            .with_context(|| format!("Failed to write trace log {}", self.path.display()))
+    }
+
+    /// Every trace in the log, oldest first; a missing log is empty
+    pub fn load(&self) -> Result<Vec<DecisionTrace>> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open trace log {}", self.path.display()))
+            }
+        };
+
+        let mut traces = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let trace = serde_json::from_str(&line).with_context(|| {
+                format!(
+                    "Invalid trace on line {} of {}",
+                    number + 1,
+                    self.path.display()
+                )
+            })?;
+            traces.push(trace);
+        }
+        Ok(traces)
+    }
+
+    /// Write the traces recorded at or after `since` to another JSONL file
+    pub fn export(&self, to: impl AsRef<Path>, since: Option<DateTime<Utc>>) -> Result<usize> {
+        let to = to.as_ref();
+        let traces: Vec<_> = self
+            .load()?
+            .into_iter()
+            .filter(|trace| since.is_none_or(|since| trace.timestamp >= since))
+            .collect();
+        let mut out = Vec::new();
+        for trace in &traces {
+            serde_json::to_writer(&mut out, trace)?;
+            out.push(b'\n');
+        }
+        std::fs::write(to, out).with_context(|| format!("Failed to write {}", to.display()))?;
+        Ok(traces.len())
+    }
+
+    /// Score every trace in the log
+    pub fn summary(&self) -> Result<TraceSummary> {
+        Ok(summarize(&self.load()?))
+    }
+}
+
+/// Score a set of traces
+pub fn summarize(traces: &[DecisionTrace]) -> TraceSummary {
+    let mut summary = TraceSummary {
+        decisions: traces.len(),
+        ..TraceSummary::default()
+    };
+    for trace in traces {
+        if trace.chosen.is_some() {
+            summary.chosen += 1;
+        }
+        if let Some(correct) = trace.is_correct() {
+            summary.scored += 1;
+            summary.correct += usize::from(correct);
+        }
+    }
+    if !traces.is_empty() {
+        summary.mean_latency_ms =
+            traces.iter().map(|t| t.latency_ms).sum::<f64>() / traces.len() as f64;
+    }
+    summary
+}
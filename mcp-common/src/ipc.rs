@@ -1,49 +1,323 @@
-use crate::{IpcEnvelope, IpcMessage};
-use anyhow::Result;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use crate::{negotiate, Hello, HelloReply, IpcEnvelope, IpcMessage, IpcProtocolVersion, PeerRole};
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
 use tokio::net::{UnixListener, UnixStream};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+/// How long the connecting side waits for a `HelloReply`
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Endpoint used by monitors and proxies when none is given
+#[cfg(unix)]
+pub const DEFAULT_IPC_ENDPOINT: &str = "/tmp/mcp-monitor.sock";
+
+/// Endpoint used by monitors and proxies when none is given
+#[cfg(windows)]
+pub const DEFAULT_IPC_ENDPOINT: &str = r"\\.\pipe\mcp-monitor";
+
+/// The peer never answered the handshake, typically because it predates the
+/// versioned protocol
+#[derive(Debug)]
+pub struct HandshakeTimeout;
+
+impl std::fmt::Display for HandshakeTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Peer did not answer the IPC handshake within {:?}",
+            HANDSHAKE_TIMEOUT
+        )
+    }
+}
+
+impl std::error::Error for HandshakeTimeout {}
+
+/// Where the monitor listens for proxies
+///
+/// Parsed from the `--ipc-socket` argument:
+///
+/// - `tcp:127.0.0.1:7878` - localhost TCP, available everywhere
+/// - `pipe:mcp-monitor` or `\\.\pipe\mcp-monitor` - a Windows named pipe
+/// - `unix:/tmp/mcp-monitor.sock` or a bare path - a Unix domain socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpcEndpoint {
+    Unix(String),
+    NamedPipe(String),
+    Tcp(SocketAddr),
+}
+
+impl IpcEndpoint {
+    pub fn parse(endpoint: &str) -> Result<Self> {
+        if let Some(addr) = endpoint.strip_prefix("tcp:") {
+            let addr: SocketAddr = addr
+                .parse()
+                .with_context(|| format!("Invalid TCP IPC endpoint '{}'", endpoint))?;
+            // The IPC channel carries unauthenticated traffic; keep it local
+            if !addr.ip().is_loopback() {
+                bail!(
+                    "TCP IPC endpoint must use a loopback address, got {}",
+                    addr.ip()
+                );
+            }
+            return Ok(Self::Tcp(addr));
+        }
+
+        if let Some(name) = endpoint.strip_prefix("pipe:") {
+            return Ok(Self::NamedPipe(format!(r"\\.\pipe\{}", name)));
+        }
+
+        if endpoint.starts_with(r"\\.\pipe\") {
+            return Ok(Self::NamedPipe(endpoint.to_string()));
+        }
+
+        let path = endpoint.strip_prefix("unix:").unwrap_or(endpoint);
+        if path.is_empty() {
+            bail!("IPC endpoint must not be empty");
+        }
+        Ok(Self::Unix(path.to_string()))
+    }
+}
+
+impl fmt::Display for IpcEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "{}", path),
+            Self::NamedPipe(name) => write!(f, "{}", name),
+            Self::Tcp(addr) => write!(f, "tcp:{}", addr),
+        }
+    }
+}
+
+fn unsupported(endpoint: &IpcEndpoint) -> anyhow::Error {
+    anyhow!(
+        "IPC endpoint '{}' is not supported on this platform; use tcp:127.0.0.1:<port>",
+        endpoint
+    )
+}
+
+enum Listener {
+    #[cfg(unix)]
+    Unix(UnixListener),
+    #[cfg(windows)]
+    NamedPipe {
+        name: String,
+        next: tokio::sync::Mutex<tokio::net::windows::named_pipe::NamedPipeServer>,
+    },
+    Tcp(TcpListener),
+}
 
 pub struct IpcServer {
-    listener: UnixListener,
+    listener: Listener,
 }
 
 impl IpcServer {
     pub async fn bind(socket_path: &str) -> Result<Self> {
-        // Remove existing socket file if it exists
-        let _ = tokio::fs::remove_file(socket_path).await;
+        let endpoint = IpcEndpoint::parse(socket_path)?;
+
+        let listener = match endpoint {
+            #[cfg(unix)]
+            IpcEndpoint::Unix(ref path) => {
+                // Remove existing socket file if it exists
+                let _ = tokio::fs::remove_file(path).await;
+                Listener::Unix(UnixListener::bind(path)?)
+            }
+            #[cfg(windows)]
+            IpcEndpoint::NamedPipe(ref name) => {
+                use tokio::net::windows::named_pipe::ServerOptions;
 
-        let listener = UnixListener::bind(socket_path)?;
-        info!("IPC server listening on {}", socket_path);
+                let server = ServerOptions::new()
+                    .first_pipe_instance(true)
+                    .create(name)?;
+                Listener::NamedPipe {
+                    name: name.clone(),
+                    next: tokio::sync::Mutex::new(server),
+                }
+            }
+            IpcEndpoint::Tcp(addr) => Listener::Tcp(TcpListener::bind(addr).await?),
+            #[allow(unreachable_patterns)]
+            ref other => return Err(unsupported(other)),
+        };
+        info!("IPC server listening on {}", endpoint);
 
         Ok(Self { listener })
     }
 
+    /// Local address of a TCP listener (useful when bound to port 0)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self.listener {
+            Listener::Tcp(ref listener) => listener.local_addr().ok(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
     pub async fn accept(&self) -> Result<IpcConnection> {
-        let (stream, _) = self.listener.accept().await?;
-        Ok(IpcConnection::new(stream))
+        match self.listener {
+            #[cfg(unix)]
+            Listener::Unix(ref listener) => {
+                let (stream, _) = listener.accept().await?;
+                let peer_uid = stream.peer_cred().ok().map(|cred| cred.uid());
+                let mut connection = IpcConnection::new(stream);
+                connection.peer_uid = peer_uid;
+                Ok(connection)
+            }
+            #[cfg(windows)]
+            Listener::NamedPipe { ref name, ref next } => {
+                use tokio::net::windows::named_pipe::ServerOptions;
+
+                // A pipe instance serves one client; queue up the next one
+                // before handing this one out
+                let mut next = next.lock().await;
+                next.connect().await?;
+                let connected = std::mem::replace(&mut *next, ServerOptions::new().create(name)?);
+                Ok(IpcConnection::new(connected))
+            }
+            Listener::Tcp(ref listener) => {
+                let (stream, _) = listener.accept().await?;
+                stream.set_nodelay(true)?;
+                Ok(IpcConnection::new(stream))
+            }
+        }
     }
 }
 
+type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
 pub struct IpcConnection {
-    reader: BufReader<tokio::net::unix::OwnedReadHalf>,
-    writer: tokio::net::unix::OwnedWriteHalf,
+    reader: BufReader<BoxedReader>,
+    writer: BoxedWriter,
+    peer: Option<Hello>,
+    peer_uid: Option<u32>,
+    version: Option<IpcProtocolVersion>,
 }
 
 impl IpcConnection {
-    pub fn new(stream: UnixStream) -> Self {
-        let (read_half, write_half) = stream.into_split();
-        let reader = BufReader::new(read_half);
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (read_half, write_half) = tokio::io::split(stream);
+        let reader = BufReader::new(Box::new(read_half) as BoxedReader);
 
         Self {
             reader,
-            writer: write_half,
+            writer: Box::new(write_half),
+            peer: None,
+            peer_uid: None,
+            version: None,
+        }
+    }
+
+    /// Protocol version agreed during the handshake, if one took place
+    pub fn negotiated_version(&self) -> Option<IpcProtocolVersion> {
+        self.version
+    }
+
+    /// The peer's `Hello`, if it sent one
+    pub fn peer(&self) -> Option<&Hello> {
+        self.peer.as_ref()
+    }
+
+    /// User ID of the process on the other end of an accepted Unix socket
+    pub fn peer_uid(&self) -> Option<u32> {
+        self.peer_uid
+    }
+
+    /// Send a `Hello` and wait for the peer to accept it
+    pub async fn handshake(&mut self, hello: Hello) -> Result<IpcProtocolVersion> {
+        let versions = hello.versions.clone();
+        self.send_message(IpcMessage::Hello(hello)).await?;
+
+        let envelope = tokio::time::timeout(HANDSHAKE_TIMEOUT, self.receive_message())
+            .await
+            .map_err(|_| HandshakeTimeout)??
+            .ok_or_else(|| anyhow!("Connection closed during IPC handshake"))?;
+
+        match envelope.message {
+            IpcMessage::HelloReply(HelloReply::Accepted { version, software }) => {
+                if !versions
+                    .iter()
+                    .any(|v| v.major == version.major && v.minor >= version.minor)
+                {
+                    bail!("Peer selected unsupported IPC protocol version {}", version);
+                }
+                debug!(
+                    "IPC handshake with {} agreed on version {}",
+                    software, version
+                );
+                self.version = Some(version);
+                Ok(version)
+            }
+            IpcMessage::HelloReply(HelloReply::Rejected { reason, supported }) => {
+                let supported: Vec<String> = supported.iter().map(|v| v.to_string()).collect();
+                bail!(
+                    "IPC handshake rejected: {} (peer supports {})",
+                    reason,
+                    supported.join(", ")
+                )
+            }
+            other => bail!("Expected HelloReply during IPC handshake, got {:?}", other),
+        }
+    }
+
+    /// Answer a peer's `Hello`, closing the door on incompatible versions
+    async fn answer_hello(&mut self, hello: Hello) -> Result<()> {
+        let local = Hello::new(
+            PeerRole::Monitor,
+            concat!("mcp-common/", env!("CARGO_PKG_VERSION")),
+        );
+
+        match negotiate(&local.versions, &hello.versions) {
+            Some(version) => {
+                info!(
+                    "IPC peer {} ({:?}) connected with protocol version {}",
+                    hello.software, hello.role, version
+                );
+                self.version = Some(version);
+                self.peer = Some(hello);
+                self.send_message(IpcMessage::HelloReply(HelloReply::Accepted {
+                    version,
+                    software: local.software,
+                }))
+                .await
+            }
+            None => {
+                let offered: Vec<String> = hello.versions.iter().map(|v| v.to_string()).collect();
+                let reason = format!(
+                    "no common protocol version (offered {})",
+                    offered.join(", ")
+                );
+                warn!("Rejecting IPC peer {}: {}", hello.software, reason);
+                self.send_message(IpcMessage::HelloReply(HelloReply::Rejected {
+                    reason: reason.clone(),
+                    supported: local.versions,
+                }))
+                .await?;
+                bail!("Rejected IPC peer {}: {}", hello.software, reason)
+            }
         }
     }
 
     pub async fn connect(socket_path: &str) -> Result<Self> {
-        let stream = UnixStream::connect(socket_path).await?;
-        Ok(Self::new(stream))
+        match IpcEndpoint::parse(socket_path)? {
+            #[cfg(unix)]
+            IpcEndpoint::Unix(path) => Ok(Self::new(UnixStream::connect(path).await?)),
+            #[cfg(windows)]
+            IpcEndpoint::NamedPipe(name) => Ok(Self::new(connect_pipe(&name).await?)),
+            IpcEndpoint::Tcp(addr) => {
+                let stream = TcpStream::connect(addr).await?;
+                stream.set_nodelay(true)?;
+                Ok(Self::new(stream))
+            }
+            #[allow(unreachable_patterns)]
+            other => Err(unsupported(&other)),
+        }
     }
 
     pub async fn send_message(&mut self, message: IpcMessage) -> Result<()> {
@@ -63,25 +337,59 @@ impl IpcConnection {
         Ok(())
     }
 
+    /// Receive the next message
+    ///
+    /// A `Hello` from the peer is answered here and not returned; messages
+    /// with variants this build does not know are skipped.
     pub async fn receive_message(&mut self) -> Result<Option<IpcEnvelope>> {
-        let mut line = String::new();
-        let bytes_read = self.reader.read_line(&mut line).await?;
+        loop {
+            let mut line = String::new();
+            let bytes_read = self.reader.read_line(&mut line).await?;
 
-        if bytes_read == 0 {
-            return Ok(None); // Connection closed
-        }
+            if bytes_read == 0 {
+                return Ok(None); // Connection closed
+            }
 
-        match serde_json::from_str::<IpcEnvelope>(&line.trim()) {
-            Ok(envelope) => {
-                debug!("Received IPC message: {:?}", envelope.message);
-                Ok(Some(envelope))
+            match serde_json::from_str::<IpcEnvelope>(&line.trim()) {
+                Ok(IpcEnvelope {
+                    message: IpcMessage::Hello(hello),
+                    ..
+                }) => {
+                    self.answer_hello(hello).await?;
+                }
+                Ok(envelope) => {
+                    debug!("Received IPC message: {:?}", envelope.message);
+                    return Ok(Some(envelope));
+                }
+                Err(e) if e.to_string().starts_with("unknown variant") => {
+                    debug!("Skipping IPC message from a newer protocol version: {}", e);
+                }
+                Err(e) => {
+                    error!("Failed to deserialize IPC message: {}", e);
+                    return Err(e.into());
+                }
             }
-            Err(e) => {
-                error!("Failed to deserialize IPC message: {}", e);
-                Err(e.into())
+        }
+    }
+}
+
+/// Open a named pipe, waiting while all server instances are busy
+#[cfg(windows)]
+async fn connect_pipe(name: &str) -> Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    for _ in 0..40 {
+        match ClientOptions::new().open(name) {
+            Ok(client) => return Ok(client),
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                tokio::time::sleep(Duration::from_millis(50)).await;
             }
+            Err(e) => return Err(e.into()),
         }
     }
+    bail!("Named pipe {} stayed busy", name)
 }
 
 pub struct IpcClient {
@@ -101,4 +409,13 @@ impl IpcClient {
     pub async fn receive(&mut self) -> Result<Option<IpcEnvelope>> {
         self.connection.receive_message().await
     }
+
+    /// Perform the protocol handshake as the given role
+    pub async fn handshake(
+        &mut self,
+        role: PeerRole,
+        software: &str,
+    ) -> Result<IpcProtocolVersion> {
+        self.connection.handshake(Hello::new(role, software)).await
+    }
 }
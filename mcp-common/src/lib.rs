@@ -1,9 +1,21 @@
+pub mod decision_trace;
 pub mod ipc;
+pub mod logging;
 pub mod mcp;
 pub mod messages;
+pub mod metrics;
+pub mod monitor_config;
+pub mod protocol;
+pub mod session;
 pub mod types;
 
+pub use decision_trace::*;
 pub use ipc::*;
+pub use logging::*;
 pub use mcp::*;
 pub use messages::*;
+pub use metrics::*;
+pub use monitor_config::*;
+pub use protocol::*;
+pub use session::*;
 pub use types::*;
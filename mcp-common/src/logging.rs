@@ -0,0 +1,476 @@
+//! Log output shared by the binaries.
+//!
+//! Every binary logs through `tracing`. By default lines are human-readable
+//! text; with [`LogFormat::Json`] each event is written as one JSON object
+//! per line, so logs can be shipped to Loki or Elasticsearch as they are:
+//!
+//! ```json
+//! {"fields":{"attempt":2},"level":"WARN","message":"Retrying","method":"tools/call","request_id":"7","session":"3f2a…","target":"mcp_transport::proxy","timestamp":"2025-01-15T09:30:00.000Z"}
+//! ```
+//!
+//! `timestamp`, `level`, `target`, `message`, `session`, `method` and
+//! `request_id` are always present, `null` when unknown. The last three are
+//! taken from the event's fields or, failing that, from the spans it happened
+//! in; any other fields are kept under `fields`.
+//!
+//! Long-running proxies and monitors can log to a [`LogFile`] instead of the
+//! terminal. The file is started afresh every hour, every day or once it
+//! reaches a size; the old one is renamed with the time it was started, e.g.
+//! `proxy.log.2025-01-15`, and only the newest few renamed files are kept.
+
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::Record;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable choosing the log format when no flag does
+pub const LOG_FORMAT_ENV: &str = "GENMCP_LOG_FORMAT";
+
+/// Context fields every JSON line carries
+const CONTEXT_FIELDS: [&str; 3] = ["session", "method", "request_id"];
+
+/// How log events are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl LogFormat {
+    /// The format from `--log-format`, else [`LOG_FORMAT_ENV`], else `fallback`
+    pub fn resolve(flag: Option<&str>, fallback: LogFormat) -> Result<Self> {
+        match flag
+            .map(str::to_string)
+            .or_else(|| std::env::var(LOG_FORMAT_ENV).ok())
+        {
+            Some(name) => name.parse(),
+            None => Ok(fallback),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow!(
+                "Invalid log format: {}. Must be one of: text, json",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Install the global subscriber, writing events that pass `filter` to
+/// `file`, or to stdout without one
+pub fn init_logging(
+    format: LogFormat,
+    filter: impl Into<EnvFilter>,
+    file: Option<&LogFile>,
+) -> Result<()> {
+    let writer = match file {
+        Some(file) => BoxMakeWriter::new(Mutex::new(RotatingFile::open(file)?)),
+        None => BoxMakeWriter::new(io::stdout),
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter.into())
+        .with_writer(writer)
+        .with_ansi(file.is_none());
+    match format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder
+            .with_ansi(false)
+            .fmt_fields(JsonFields)
+            .event_format(JsonFormat)
+            .try_init(),
+    }
+    .map_err(|e| anyhow!("Failed to initialize logging: {}", e))
+}
+
+/// When a log file is set aside and a new one started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Rotation {
+    Never,
+    Hourly,
+    Daily,
+    /// Before a write would take the file past this many bytes
+    Size(u64),
+}
+
+impl Rotation {
+    /// How the start of a file is written into its name once set aside;
+    /// files of the same period share a name, so time-based rotation starts
+    /// a new file whenever it changes
+    fn stamp(self) -> &'static str {
+        match self {
+            Rotation::Hourly => "%Y-%m-%d-%H",
+            Rotation::Daily => "%Y-%m-%d",
+            Rotation::Never | Rotation::Size(_) => "%Y-%m-%dT%H-%M-%S",
+        }
+    }
+}
+
+impl FromStr for Rotation {
+    type Err = anyhow::Error;
+
+    /// `never`, `hourly`, `daily` or a size such as `10MB`
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "never" => return Ok(Rotation::Never),
+            "hourly" => return Ok(Rotation::Hourly),
+            "daily" => return Ok(Rotation::Daily),
+            _ => {}
+        }
+        let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let unit = match s[digits..].to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" => 1 << 10,
+            "M" | "MB" => 1 << 20,
+            "G" | "GB" => 1 << 30,
+            _ => 0,
+        };
+        match s[..digits].parse::<u64>() {
+            Ok(size) if size > 0 && unit > 0 => Ok(Rotation::Size(size.saturating_mul(unit))),
+            _ => Err(anyhow!(
+                "Invalid log rotation: {}. Must be never, hourly, daily or a size such as 10MB",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rotation::Never => write!(f, "never"),
+            Rotation::Hourly => write!(f, "hourly"),
+            Rotation::Daily => write!(f, "daily"),
+            Rotation::Size(size) => {
+                let (value, unit) = [(1 << 30, "GB"), (1 << 20, "MB"), (1 << 10, "KB")]
+                    .into_iter()
+                    .find(|(unit, _)| size % unit == 0)
+                    .map_or((*size, "B"), |(unit, name)| (size / unit, name));
+                write!(f, "{}{}", value, unit)
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Rotation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<Rotation> for String {
+    fn from(rotation: Rotation) -> Self {
+        rotation.to_string()
+    }
+}
+
+/// A file to log to and how it is rotated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub rotation: Rotation,
+    /// Set-aside files kept besides the current one; 0 keeps all of them
+    pub keep: usize,
+}
+
+impl LogFile {
+    /// Log to `path`, rotating daily and keeping a week of old files
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            rotation: Rotation::Daily,
+            keep: 7,
+        }
+    }
+
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep;
+        self
+    }
+}
+
+/// Writer appending to a [`LogFile`] and rotating it when due
+#[derive(Debug)]
+pub struct RotatingFile {
+    config: LogFile,
+    file: File,
+    size: u64,
+    /// When the current file was started
+    started: DateTime<Utc>,
+}
+
+impl RotatingFile {
+    /// Append to the file, creating it and its directory if needed
+    pub fn open(config: &LogFile) -> Result<Self> {
+        if let Some(dir) = config
+            .path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+        {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        }
+        let file = append(&config.path)
+            .with_context(|| format!("Failed to open log file {}", config.path.display()))?;
+        let metadata = file.metadata()?;
+        // A file left by an earlier run belongs to the period it was last written in
+        let started = match metadata.modified() {
+            Ok(modified) if metadata.len() > 0 => modified.into(),
+            _ => Utc::now(),
+        };
+        Ok(Self {
+            config: config.clone(),
+            file,
+            size: metadata.len(),
+            started,
+        })
+    }
+
+    /// Write `bytes` as of `now`, first rotating the file if it is due
+    pub fn write_at(&mut self, bytes: &[u8], now: DateTime<Utc>) -> io::Result<()> {
+        if self.size == 0 {
+            // A file starts with its first line
+            self.started = now;
+        } else if self.is_due(bytes.len() as u64, now) {
+            self.rotate(now)?;
+        }
+        self.file.write_all(bytes)?;
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn is_due(&self, incoming: u64, now: DateTime<Utc>) -> bool {
+        match self.config.rotation {
+            Rotation::Never => false,
+            Rotation::Size(max) => self.size + incoming > max,
+            Rotation::Hourly | Rotation::Daily => {
+                let stamp = self.config.rotation.stamp();
+                self.started.format(stamp).to_string() != now.format(stamp).to_string()
+            }
+        }
+    }
+
+    /// Set the current file aside under the time it was started and begin a new one
+    fn rotate(&mut self, now: DateTime<Utc>) -> io::Result<()> {
+        self.file.flush()?;
+        let base = format!(
+            "{}.{}",
+            self.config.path.display(),
+            self.started.format(self.config.rotation.stamp())
+        );
+        let mut aside = PathBuf::from(&base);
+        let mut n = 1;
+        while aside.exists() {
+            aside = PathBuf::from(format!("{}.{}", base, n));
+            n += 1;
+        }
+        fs::rename(&self.config.path, &aside)?;
+        self.file = append(&self.config.path)?;
+        self.size = 0;
+        self.started = now;
+        self.prune()
+    }
+
+    /// Remove all but the newest `keep` set-aside files
+    fn prune(&self) -> io::Result<()> {
+        if self.config.keep == 0 {
+            return Ok(());
+        }
+        let Some(name) = self.config.path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(());
+        };
+        let dir = match self.config.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let prefix = format!("{}.", name);
+        // Names end in the time each file was started, so they sort by age
+        let mut aside: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+            .map(|entry| entry.path())
+            .collect();
+        aside.sort_by(|a, b| b.cmp(a));
+        for path in aside.into_iter().skip(self.config.keep) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.write_at(bytes, Utc::now())?;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Event formatter writing one JSON object per line; pair it with [`JsonFields`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        // Outermost span first, so inner spans and the event itself win
+        let mut fields = Map::new();
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                if let Some(formatted) = span.extensions().get::<FormattedFields<N>>() {
+                    if let Ok(Value::Object(span_fields)) = serde_json::from_str(&formatted.fields)
+                    {
+                        fields.extend(span_fields);
+                    }
+                }
+            }
+        }
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        fields.extend(visitor.0);
+
+        let metadata = event.metadata();
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            Utc::now()
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+                .into(),
+        );
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("target".into(), metadata.target().into());
+        line.insert(
+            "message".into(),
+            fields.remove("message").unwrap_or(Value::Null),
+        );
+        for key in CONTEXT_FIELDS {
+            line.insert(key.into(), fields.remove(key).unwrap_or(Value::Null));
+        }
+        if !fields.is_empty() {
+            line.insert("fields".into(), Value::Object(fields));
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Span field formatter keeping each span's fields as a JSON object
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFields;
+
+impl<'writer> FormatFields<'writer> for JsonFields {
+    fn format_fields<R: tracing_subscriber::field::RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        write!(writer, "{}", Value::Object(visitor.0))
+    }
+
+    fn add_fields(
+        &self,
+        current: &'writer mut FormattedFields<Self>,
+        fields: &Record<'_>,
+    ) -> fmt::Result {
+        let known = serde_json::from_str(&current.fields).unwrap_or_default();
+        let mut visitor = JsonVisitor(known);
+        fields.record(&mut visitor);
+        current.fields = Value::Object(visitor.0).to_string();
+        Ok(())
+    }
+}
+
+/// Collects fields into a JSON object, keeping numbers and booleans typed
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.0.insert(field.name().into(), value.to_string().into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    AppliedTransformation, ClientId, ClientInfo, GatewayMetrics, GatewayState, HealthMetrics,
-    LogEntry, MessageFlow, ProxyId, ProxyInfo, ProxySession, ProxyStats, RoutingDecision,
-    RoutingRule, ServerId, ServerInfo, SessionId, TransformationRule,
+    AppliedTransformation, CircuitChange, ClientId, ClientInfo, GatewayMetrics, GatewayState,
+    HealthMetrics, LogEntry, MessageFlow, ProxyId, ProxyInfo, ProxySession, ProxyStats,
+    RoutingDecision, RoutingRule, ServerId, ServerInfo, SessionId, TransformationRule,
 };
+use crate::{DecisionTrace, Hello, HelloReply, ObservedMessage};
 use crate::{JsonRpcRequest, JsonRpcResponse};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -30,12 +31,28 @@ pub struct InterceptorManagerInfo {
     pub interceptors: Vec<InterceptorInfo>,
 }
 
+/// A server session kept open by the connection broker
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BrokerSession {
+    /// Server name as given by the CLI (profile name, URL or profile file)
+    pub server: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub last_used: chrono::DateTime<chrono::Utc>,
+    /// Requests forwarded over this session
+    pub requests: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum IpcMessage {
+    // Handshake (see the protocol module)
+    Hello(Hello),
+    HelloReply(HelloReply),
+
     // Proxy -> Monitor messages
     ProxyStarted(ProxyInfo),
     ProxyStopped(ProxyId),
     LogEntry(LogEntry),
+    MessageObserved(ObservedMessage),
     StatsUpdate(ProxyStats),
     InterceptorStats {
         proxy_id: ProxyId,
@@ -61,6 +78,8 @@ pub enum IpcMessage {
         server_id: ServerId,
         metrics: HealthMetrics,
     },
+    /// A backend's circuit breaker opened, half-opened or closed
+    CircuitChanged(CircuitChange),
     SessionStarted(ProxySession),
     SessionUpdated(ProxySession),
     SessionEnded(SessionId),
@@ -71,6 +90,8 @@ pub enum IpcMessage {
     },
     RoutingRules(Vec<RoutingRule>),
     RoutingDecision(RoutingDecision),
+    /// How the LLM router reached a decision
+    DecisionTraced(DecisionTrace),
     GatewayStateUpdated(GatewayState),
     GatewayMetrics(GatewayMetrics),
     MessageFlowUpdate(MessageFlow),
@@ -87,6 +108,28 @@ pub enum IpcMessage {
         interceptor_name: String,
     },
 
+    // CLI -> broker messages (only on the broker endpoint)
+    BrokerCall {
+        server: String,
+        method: String,
+        params: serde_json::Value,
+    },
+    BrokerListSessions,
+    /// Close the named session, or every session when `server` is `None`
+    BrokerCloseSession {
+        server: Option<String>,
+    },
+
+    // Broker -> CLI messages
+    BrokerResult {
+        /// The server's JSON-RPC response
+        response: serde_json::Value,
+        /// Whether an existing session served the call
+        reused: bool,
+    },
+    BrokerSessions(Vec<BrokerSession>),
+    BrokerClosed(Vec<String>),
+
     // Bidirectional messages
     Ping,
     Pong,
@@ -0,0 +1,442 @@
+//! Request metrics over a sliding window.
+//!
+//! A [`MetricsWindow`] sorts completed requests by server into buckets of
+//! fixed width and keeps the most recent ones. Each bucket gives the request
+//! rate, p50/p95 latency and error rate of its slice of time, so a monitor can
+//! chart how a backend has behaved lately. [`RequestTracker`] turns observed
+//! traffic into those samples by pairing requests with their responses.
+//!
+//! [`ProxyMetrics`] keeps running totals of a proxy's traffic instead, for
+//! scraping in the Prometheus text format.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::protocol::{ObservedMessage, TrafficDirection};
+use crate::types::{ProxyId, ProxyStats};
+
+/// Requests awaiting a response before the oldest are forgotten
+const MAX_PENDING: usize = 10_000;
+
+/// Requests completed within one slice of time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bucket {
+    pub requests: u64,
+    pub errors: u64,
+    /// Latency of each request, in milliseconds
+    pub latencies_ms: Vec<f64>,
+}
+
+impl Bucket {
+    /// Latency below which `quantile` of the requests completed, by nearest rank
+    pub fn percentile(&self, quantile: f64) -> Option<f64> {
+        if self.latencies_ms.is_empty() {
+            return None;
+        }
+        let mut sorted = self.latencies_ms.clone();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (quantile.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Share of requests that failed, from 0 to 1
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.requests as f64
+        }
+    }
+}
+
+/// One server's metrics per bucket, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Series {
+    pub requests_per_sec: Vec<f64>,
+    pub p50_ms: Vec<f64>,
+    pub p95_ms: Vec<f64>,
+    /// From 0 to 1
+    pub error_rate: Vec<f64>,
+}
+
+/// Completed requests per server, bucketed over a sliding window
+#[derive(Debug, Clone)]
+pub struct MetricsWindow {
+    bucket_width: Duration,
+    buckets: usize,
+    /// Buckets by index since the epoch, per server
+    servers: BTreeMap<String, VecDeque<(i64, Bucket)>>,
+    /// Index of the newest bucket recorded into
+    latest: Option<i64>,
+}
+
+impl Default for MetricsWindow {
+    /// Five minutes in five-second buckets
+    fn default() -> Self {
+        Self::new(Duration::from_secs(5), 60)
+    }
+}
+
+impl MetricsWindow {
+    /// A window of `buckets` buckets, each `bucket_width` long
+    pub fn new(bucket_width: Duration, buckets: usize) -> Self {
+        Self {
+            bucket_width: bucket_width.max(Duration::from_millis(1)),
+            buckets: buckets.max(1),
+            servers: BTreeMap::new(),
+            latest: None,
+        }
+    }
+
+    pub fn bucket_width(&self) -> Duration {
+        self.bucket_width
+    }
+
+    /// Length of time the window covers
+    pub fn span(&self) -> Duration {
+        self.bucket_width * self.buckets as u32
+    }
+
+    /// Servers with requests in the window
+    pub fn servers(&self) -> impl Iterator<Item = &str> {
+        self.servers.keys().map(String::as_str)
+    }
+
+    /// Start of the newest bucket, if anything was recorded
+    pub fn latest(&self) -> Option<DateTime<Utc>> {
+        let index = self.latest?;
+        DateTime::from_timestamp_millis(index * self.width_ms())
+    }
+
+    /// Count a request to `server` that completed at `at`
+    pub fn record(&mut self, server: &str, at: DateTime<Utc>, latency_ms: f64, error: bool) {
+        let index = at.timestamp_millis().div_euclid(self.width_ms());
+        let buckets = self.servers.entry(server.to_string()).or_default();
+        // Requests usually complete in order; a late one goes in its own bucket
+        let position = buckets.iter().rposition(|(i, _)| *i <= index);
+        let bucket = match position {
+            Some(p) if buckets[p].0 == index => &mut buckets[p].1,
+            Some(p) => {
+                buckets.insert(p + 1, (index, Bucket::default()));
+                &mut buckets[p + 1].1
+            }
+            None => {
+                buckets.push_front((index, Bucket::default()));
+                &mut buckets[0].1
+            }
+        };
+        bucket.requests += 1;
+        bucket.errors += u64::from(error);
+        bucket.latencies_ms.push(latency_ms);
+
+        let latest = self.latest.map_or(index, |latest| latest.max(index));
+        self.latest = Some(latest);
+        let oldest = latest - self.buckets as i64 + 1;
+        for buckets in self.servers.values_mut() {
+            while buckets.front().is_some_and(|(i, _)| *i < oldest) {
+                buckets.pop_front();
+            }
+        }
+        self.servers.retain(|_, buckets| !buckets.is_empty());
+    }
+
+    /// Every bucket of the window for `server`, oldest first, ending with the
+    /// newest bucket recorded for any server; empty buckets count as zero
+    pub fn series(&self, server: &str) -> Series {
+        let mut series = Series::default();
+        let (Some(latest), Some(buckets)) = (self.latest, self.servers.get(server)) else {
+            return series;
+        };
+        let seconds = self.bucket_width.as_secs_f64();
+        let empty = Bucket::default();
+        for index in latest - self.buckets as i64 + 1..=latest {
+            let bucket = buckets
+                .iter()
+                .find(|(i, _)| *i == index)
+                .map_or(&empty, |(_, bucket)| bucket);
+            series
+                .requests_per_sec
+                .push(bucket.requests as f64 / seconds);
+            series.p50_ms.push(bucket.percentile(0.5).unwrap_or(0.0));
+            series.p95_ms.push(bucket.percentile(0.95).unwrap_or(0.0));
+            series.error_rate.push(bucket.error_rate());
+        }
+        series
+    }
+
+    /// Requests to `server` in the buckets covering the newest `span` of the
+    /// window, merged into one
+    pub fn recent(&self, server: &str, span: Duration) -> Bucket {
+        let mut total = Bucket::default();
+        let (Some(latest), Some(buckets)) = (self.latest, self.servers.get(server)) else {
+            return total;
+        };
+        let count = span
+            .as_millis()
+            .div_ceil(self.bucket_width.as_millis().max(1))
+            .max(1) as i64;
+        for (_, bucket) in buckets.iter().filter(|(i, _)| *i > latest - count) {
+            total.requests += bucket.requests;
+            total.errors += bucket.errors;
+            total.latencies_ms.extend(&bucket.latencies_ms);
+        }
+        total
+    }
+
+    fn width_ms(&self) -> i64 {
+        self.bucket_width.as_millis().max(1) as i64
+    }
+}
+
+/// A request paired with its response
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedRequest {
+    pub proxy_id: ProxyId,
+    pub method: String,
+    /// When the response was seen
+    pub completed: DateTime<Utc>,
+    pub latency_ms: f64,
+    /// Whether the response carried an error
+    pub error: bool,
+}
+
+/// Pairs requests and responses seen by proxies
+#[derive(Debug, Clone, Default)]
+pub struct RequestTracker {
+    /// Method and start of each request awaiting a response
+    pending: HashMap<(ProxyId, TrafficDirection, String), (String, DateTime<Utc>)>,
+}
+
+impl RequestTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note a message, returning the request it completes if it is a response
+    pub fn observe(&mut self, message: &ObservedMessage) -> Option<CompletedRequest> {
+        let id = message.id.as_ref()?.to_string();
+        if let Some(method) = &message.method {
+            if self.pending.len() >= MAX_PENDING {
+                // Responses that never came; keep the newest half
+                let mut starts: Vec<DateTime<Utc>> =
+                    self.pending.values().map(|(_, start)| *start).collect();
+                starts.sort();
+                let cutoff = starts[starts.len() / 2];
+                self.pending.retain(|_, (_, start)| *start >= cutoff);
+            }
+            self.pending.insert(
+                (message.proxy_id.clone(), message.direction, id),
+                (method.clone(), message.timestamp),
+            );
+            return None;
+        }
+        // A response travels the other way from its request
+        let request_direction = match message.direction {
+            TrafficDirection::ClientToServer => TrafficDirection::ServerToClient,
+            TrafficDirection::ServerToClient => TrafficDirection::ClientToServer,
+        };
+        let (method, started) =
+            self.pending
+                .remove(&(message.proxy_id.clone(), request_direction, id))?;
+        let error = message.payload.get("error").is_some()
+            || message.payload.pointer("/result/isError") == Some(&serde_json::Value::Bool(true));
+        Some(CompletedRequest {
+            proxy_id: message.proxy_id.clone(),
+            method,
+            completed: message.timestamp,
+            latency_ms: (message.timestamp - started)
+                .num_microseconds()
+                .unwrap_or(0) as f64
+                / 1000.0,
+            error,
+        })
+    }
+}
+
+/// Upper bounds of the request latency histogram, in seconds
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Completed requests of one method
+#[derive(Debug, Clone, Default, PartialEq)]
+struct MethodTotals {
+    requests: u64,
+    errors: u64,
+    /// Requests per latency bucket; the ones over the last bound only count
+    /// in `requests`
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_secs: f64,
+}
+
+/// Running totals of the traffic through a proxy
+#[derive(Debug, Clone, Default)]
+pub struct ProxyMetrics {
+    tracker: RequestTracker,
+    methods: BTreeMap<String, MethodTotals>,
+    tool_calls: BTreeMap<String, u64>,
+    transport_errors: u64,
+}
+
+impl ProxyMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a message the proxy forwarded
+    pub fn observe(&mut self, message: &ObservedMessage) {
+        if message.direction == TrafficDirection::ClientToServer
+            && message.method.as_deref() == Some("tools/call")
+        {
+            if let Some(tool) = message
+                .payload
+                .pointer("/params/name")
+                .and_then(|n| n.as_str())
+            {
+                *self.tool_calls.entry(tool.to_string()).or_default() += 1;
+            }
+        }
+        let Some(completed) = self.tracker.observe(message) else {
+            return;
+        };
+        let totals = self.methods.entry(completed.method).or_default();
+        totals.requests += 1;
+        totals.errors += u64::from(completed.error);
+        let seconds = completed.latency_ms / 1000.0;
+        totals.latency_secs += seconds;
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            totals.buckets[bucket] += 1;
+        }
+    }
+
+    /// Count a message that could not be carried
+    pub fn transport_error(&mut self) {
+        self.transport_errors += 1;
+    }
+
+    /// The totals and `stats` in the Prometheus text exposition format
+    pub fn render(&self, stats: &ProxyStats) -> String {
+        let mut out = String::new();
+        family(
+            &mut out,
+            "requests_total",
+            "counter",
+            "Requests answered, by method.",
+        );
+        for (method, totals) in &self.methods {
+            let labels = format!("method=\"{}\"", escape(method));
+            sample(&mut out, "requests_total", &labels, totals.requests);
+        }
+        family(
+            &mut out,
+            "request_errors_total",
+            "counter",
+            "Requests answered with an error, by method.",
+        );
+        for (method, totals) in &self.methods {
+            let labels = format!("method=\"{}\"", escape(method));
+            sample(&mut out, "request_errors_total", &labels, totals.errors);
+        }
+        family(
+            &mut out,
+            "request_duration_seconds",
+            "histogram",
+            "Time from request to response, by method.",
+        );
+        for (method, totals) in &self.methods {
+            let labels = format!("method=\"{}\"", escape(method));
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(totals.buckets) {
+                cumulative += count;
+                let bucket = format!("{},le=\"{}\"", labels, bound);
+                sample(
+                    &mut out,
+                    "request_duration_seconds_bucket",
+                    &bucket,
+                    cumulative,
+                );
+            }
+            let bucket = format!("{},le=\"+Inf\"", labels);
+            sample(
+                &mut out,
+                "request_duration_seconds_bucket",
+                &bucket,
+                totals.requests,
+            );
+            sample(
+                &mut out,
+                "request_duration_seconds_sum",
+                &labels,
+                totals.latency_secs,
+            );
+            sample(
+                &mut out,
+                "request_duration_seconds_count",
+                &labels,
+                totals.requests,
+            );
+        }
+        family(
+            &mut out,
+            "tool_calls_total",
+            "counter",
+            "Tool calls sent, by tool.",
+        );
+        for (tool, count) in &self.tool_calls {
+            let labels = format!("tool=\"{}\"", escape(tool));
+            sample(&mut out, "tool_calls_total", &labels, count);
+        }
+        family(
+            &mut out,
+            "transport_errors_total",
+            "counter",
+            "Messages that could not be carried.",
+        );
+        sample(
+            &mut out,
+            "transport_errors_total",
+            "",
+            self.transport_errors,
+        );
+        family(
+            &mut out,
+            "connected_clients",
+            "gauge",
+            "Clients connected to the proxy.",
+        );
+        sample(&mut out, "connected_clients", "", stats.active_connections);
+        family(
+            &mut out,
+            "bytes_total",
+            "counter",
+            "Bytes forwarded in either direction.",
+        );
+        sample(&mut out, "bytes_total", "", stats.bytes_transferred);
+        out
+    }
+}
+
+/// The help and type lines that start a metric family
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP genmcp_proxy_{} {}", name, help).unwrap();
+    writeln!(out, "# TYPE genmcp_proxy_{} {}", name, kind).unwrap();
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: impl std::fmt::Display) {
+    if labels.is_empty() {
+        writeln!(out, "genmcp_proxy_{} {}", name, value).unwrap();
+    } else {
+        writeln!(out, "genmcp_proxy_{}{{{}}} {}", name, labels, value).unwrap();
+    }
+}
+
+/// A label value with the characters Prometheus requires escaped
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,404 @@
+//! Configuration shared by the monitors.
+//!
+//! A [`MonitorConfig`] sets how often a monitor redraws and polls the proxy,
+//! which panels it shows, how much history it keeps, which payload fields it
+//! masks and the colours it draws with. It is read from
+//! `$XDG_CONFIG_HOME/genmcp/monitor.toml` (under `~/.config` without it);
+//! every setting is optional and a missing file means the defaults:
+//!
+//! ```toml
+//! [refresh]
+//! redraw_ms = 100
+//! status_ms = 1000
+//!
+//! [panels]
+//! quick_access = false
+//!
+//! [history]
+//! activities = 500
+//!
+//! [redaction]
+//! keys = ["authorization", "api_key", "cookie"]
+//!
+//! [theme]
+//! preset = "light"
+//! accent = "magenta"
+//!
+//! [keys]
+//! toggle_inspector = ["ctrl+i", "f2"]
+//!
+//! [alerts]
+//! desktop = true
+//! webhook = "http://localhost:9000/hooks/genmcp"
+//! error_rate_percent = 20
+//!
+//! [logging]
+//! format = "json"
+//! file = "/var/log/genmcp/monitor.log"
+//! rotate = "daily"
+//! keep = 7
+//! ```
+//!
+//! [`MonitorConfig::watch`] reloads the file when it changes or the process
+//! gets `SIGHUP`, so a running monitor picks up edits without a restart.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::watch;
+
+use crate::logging::{LogFile, LogFormat, Rotation};
+
+/// How often a watched configuration file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Everything a monitor can be configured with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorConfig {
+    pub refresh: RefreshConfig,
+    pub panels: PanelConfig,
+    pub history: HistoryConfig,
+    pub redaction: RedactionRules,
+    pub theme: ThemeConfig,
+    /// Keys by action, replacing the default keys of each action listed
+    pub keys: BTreeMap<String, Vec<String>>,
+    pub alerts: AlertConfig,
+    pub logging: LoggingConfig,
+}
+
+/// How often the screen and the proxy status are refreshed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshConfig {
+    /// Longest wait for input between redraws, in milliseconds
+    pub redraw_ms: u64,
+    /// Time between polls of client and server status, in milliseconds
+    pub status_ms: u64,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            redraw_ms: 100,
+            status_ms: 1000,
+        }
+    }
+}
+
+impl RefreshConfig {
+    pub fn redraw(&self) -> Duration {
+        Duration::from_millis(self.redraw_ms.max(10))
+    }
+
+    pub fn status(&self) -> Duration {
+        Duration::from_millis(self.status_ms.max(100))
+    }
+}
+
+/// Which panels are shown; all of them by default
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelConfig {
+    pub clients: bool,
+    pub servers: bool,
+    pub activity: bool,
+    pub quick_access: bool,
+}
+
+impl Default for PanelConfig {
+    fn default() -> Self {
+        Self {
+            clients: true,
+            servers: true,
+            activity: true,
+            quick_access: true,
+        }
+    }
+}
+
+/// How many entries are kept before the oldest half is dropped
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub activities: usize,
+    pub server_logs: usize,
+    pub messages: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            activities: 100,
+            server_logs: 1000,
+            messages: 10_000,
+        }
+    }
+}
+
+/// Payload fields masked before they are shown or recorded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionRules {
+    /// Object keys whose values are masked, compared case-insensitively
+    pub keys: Vec<String>,
+    /// What masked values are replaced with
+    pub replacement: String,
+}
+
+impl Default for RedactionRules {
+    fn default() -> Self {
+        Self {
+            keys: ["authorization", "password", "token", "api_key", "secret"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            replacement: "[REDACTED]".to_string(),
+        }
+    }
+}
+
+impl RedactionRules {
+    /// Mask the value of every matching key in `value`, at any depth
+    pub fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+                        *field = Value::String(self.replacement.clone());
+                    } else {
+                        self.redact(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact(item)),
+            _ => {}
+        }
+    }
+}
+
+/// A colour preset and the roles it overrides
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// `"dark"` or `"light"`
+    pub preset: String,
+    /// Colours by role (`accent = "magenta"`), by name, as `"#rrggbb"` or a
+    /// 256-colour index
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            preset: "dark".to_string(),
+            colors: BTreeMap::new(),
+        }
+    }
+}
+
+/// A condition an alert can fire on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// A backend that was running stopped or failed
+    Disconnect,
+    /// A backend's requests failed more often than the threshold
+    ErrorRate,
+    /// A tool call was refused by policy
+    DeniedCall,
+    /// A server asked the client to sample an LLM
+    Sampling,
+}
+
+impl AlertKind {
+    pub fn all() -> [Self; 4] {
+        [
+            Self::Disconnect,
+            Self::ErrorRate,
+            Self::DeniedCall,
+            Self::Sampling,
+        ]
+    }
+}
+
+/// How the monitor's own logs are written; read once at startup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Overridden by `--log-format` and [`crate::LOG_FORMAT_ENV`]
+    pub format: LogFormat,
+    /// File to log to instead of stdout
+    pub file: Option<PathBuf>,
+    pub rotate: Rotation,
+    /// Rotated files kept besides the current one; 0 keeps all of them
+    pub keep: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            file: None,
+            rotate: Rotation::Daily,
+            keep: 7,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// The configured log file, if any
+    pub fn log_file(&self) -> Option<LogFile> {
+        let path = self.file.as_ref()?;
+        Some(LogFile::new(path).rotation(self.rotate).keep(self.keep))
+    }
+}
+
+/// When alerts fire and where they are sent; nothing is sent by default
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// Conditions that fire
+    pub on: Vec<AlertKind>,
+    /// Show a desktop notification
+    pub desktop: bool,
+    /// Shell command to run, with the alert in `GENMCP_ALERT_*` variables
+    pub command: Option<String>,
+    /// URL the alert is POSTed to as JSON
+    pub webhook: Option<String>,
+    /// Share of failed requests, in percent, that fires `error_rate`
+    pub error_rate_percent: u32,
+    /// Requests needed within the window before the error rate counts
+    pub min_requests: u64,
+    /// Span the error rate is measured over, in seconds
+    pub window_secs: u64,
+    /// Quiet time after an alert before the same one fires again, in seconds
+    pub cooldown_secs: u64,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            on: AlertKind::all().to_vec(),
+            desktop: false,
+            command: None,
+            webhook: None,
+            error_rate_percent: 25,
+            min_requests: 10,
+            window_secs: 60,
+            cooldown_secs: 60,
+        }
+    }
+}
+
+impl AlertConfig {
+    /// Whether alerts are sent anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.desktop || self.command.is_some() || self.webhook.is_some()
+    }
+
+    pub fn fires_on(&self, kind: AlertKind) -> bool {
+        self.on.contains(&kind)
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs.max(1))
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_secs)
+    }
+}
+
+impl MonitorConfig {
+    /// `$XDG_CONFIG_HOME/genmcp/monitor.toml`, or under `~/.config` without it
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("genmcp").join("monitor.toml"))
+    }
+
+    /// Load the user's configuration; a missing file means the defaults
+    pub fn load_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a configuration file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content, &path.display().to_string())
+    }
+
+    /// Parse configuration `content`; `source` names it in errors
+    pub fn parse(content: &str, source: &str) -> Result<Self> {
+        toml::from_str(content)
+            .with_context(|| format!("Invalid monitor configuration in {}", source))
+    }
+
+    /// Follow the file at `path`, starting from `initial`.
+    ///
+    /// The file is reloaded when its modification time changes and, on Unix,
+    /// when the process gets `SIGHUP`. A file that fails to load is reported
+    /// and the previous configuration kept. Watching stops once every
+    /// receiver is dropped. Must be called within a Tokio runtime.
+    pub fn watch(path: impl Into<PathBuf>, initial: Self) -> watch::Receiver<Self> {
+        let path = path.into();
+        let (tx, rx) = watch::channel(initial);
+        let mut last_modified = modified(&path);
+        tokio::spawn(async move {
+            #[cfg(unix)]
+            let mut hangups =
+                tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+            while !tx.is_closed() {
+                #[cfg(unix)]
+                let hangup = async {
+                    match hangups.as_mut() {
+                        Some(signal) => signal.recv().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                #[cfg(not(unix))]
+                let hangup = std::future::pending::<Option<()>>();
+
+                let forced = tokio::select! {
+                    _ = hangup => true,
+                    _ = tokio::time::sleep(WATCH_INTERVAL) => false,
+                };
+                let current = modified(&path);
+                if !forced && current == last_modified {
+                    continue;
+                }
+                last_modified = current;
+                match Self::load(&path) {
+                    Ok(config) => {
+                        tracing::info!("Reloaded monitor configuration from {}", path.display());
+                        tx.send_if_modified(|old| {
+                            let changed = *old != config;
+                            *old = config;
+                            changed
+                        });
+                    }
+                    Err(e) => tracing::warn!("Keeping the previous monitor configuration: {:#}", e),
+                }
+            }
+        });
+        rx
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
@@ -0,0 +1,193 @@
+//! Versioned proxy ↔ monitor IPC protocol
+//!
+//! Proxies and monitors talk over a Unix socket using newline-delimited JSON
+//! [`IpcEnvelope`](crate::IpcEnvelope)s. A connection starts with a handshake:
+//!
+//! 1. The connecting side sends `IpcMessage::Hello` listing the protocol
+//!    versions it speaks (one entry per supported major version).
+//! 2. The accepting side answers with `IpcMessage::HelloReply`, either
+//!    `Accepted` with the negotiated version or `Rejected` with the versions
+//!    it supports, after which it closes the connection.
+//!
+//! Within a major version, minor bumps only add message variants. Receivers
+//! skip variants they do not know, so a 1.0 monitor keeps working against a
+//! 1.1 proxy. Removing or changing a variant requires a new major version.
+//!
+//! Monitors that only care about the stable event stream can map incoming
+//! messages through [`MonitorEvent::from_ipc`].
+
+use crate::{
+    CircuitChange, DecisionTrace, InterceptorManagerInfo, IpcMessage, LogEntry, ProxyId, ProxyInfo,
+    ProxyStats,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Protocol version spoken by this build
+pub const IPC_PROTOCOL_VERSION: IpcProtocolVersion = IpcProtocolVersion::new(1, 0);
+
+/// A protocol version (`major.minor`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpcProtocolVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl IpcProtocolVersion {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for IpcProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Which end of the IPC connection a peer is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerRole {
+    Proxy,
+    Monitor,
+}
+
+/// First message sent on a new connection
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Hello {
+    pub role: PeerRole,
+    /// Free-form software identifier, e.g. `mcp-transport/0.2.0`
+    pub software: String,
+    /// Supported versions, highest minor for each major
+    pub versions: Vec<IpcProtocolVersion>,
+}
+
+impl Hello {
+    pub fn new(role: PeerRole, software: impl Into<String>) -> Self {
+        Self {
+            role,
+            software: software.into(),
+            versions: vec![IPC_PROTOCOL_VERSION],
+        }
+    }
+}
+
+/// Answer to a [`Hello`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HelloReply {
+    Accepted {
+        version: IpcProtocolVersion,
+        software: String,
+    },
+    Rejected {
+        reason: String,
+        supported: Vec<IpcProtocolVersion>,
+    },
+}
+
+/// Pick the version both sides speak: the highest shared major version, at
+/// the lower of the two minor versions.
+pub fn negotiate(
+    local: &[IpcProtocolVersion],
+    remote: &[IpcProtocolVersion],
+) -> Option<IpcProtocolVersion> {
+    local
+        .iter()
+        .filter_map(|ours| {
+            remote
+                .iter()
+                .find(|theirs| theirs.major == ours.major)
+                .map(|theirs| IpcProtocolVersion::new(ours.major, ours.minor.min(theirs.minor)))
+        })
+        .max()
+}
+
+/// Direction of an observed MCP message
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficDirection {
+    ClientToServer,
+    ServerToClient,
+}
+
+/// An MCP message seen by a proxy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservedMessage {
+    pub proxy_id: ProxyId,
+    pub direction: TrafficDirection,
+    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    /// JSON-RPC payload, with secrets already masked by the proxy
+    pub payload: serde_json::Value,
+    /// Whether an interceptor modified the message before forwarding it
+    #[serde(default)]
+    pub modified: bool,
+    /// Payload as the sender produced it, before interceptors modified it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<serde_json::Value>,
+}
+
+impl ObservedMessage {
+    /// Build an observation from a JSON-RPC payload
+    pub fn new(proxy_id: ProxyId, direction: TrafficDirection, payload: serde_json::Value) -> Self {
+        Self {
+            proxy_id,
+            direction,
+            timestamp: Utc::now(),
+            method: payload
+                .get("method")
+                .and_then(|m| m.as_str())
+                .map(str::to_string),
+            id: payload.get("id").cloned(),
+            payload,
+            modified: false,
+            original: None,
+        }
+    }
+}
+
+/// Stable, typed view of the events a monitor receives
+#[derive(Debug, Clone)]
+pub enum MonitorEvent {
+    ProxyRegistered(ProxyInfo),
+    ProxyDeregistered(ProxyId),
+    MessageObserved(ObservedMessage),
+    StatsSnapshot(ProxyStats),
+    InterceptorSnapshot {
+        proxy_id: ProxyId,
+        stats: InterceptorManagerInfo,
+    },
+    Log(LogEntry),
+    DecisionTraced(DecisionTrace),
+    CircuitChanged(CircuitChange),
+    Error {
+        proxy_id: Option<ProxyId>,
+        message: String,
+    },
+}
+
+impl MonitorEvent {
+    /// Map an IPC message to a monitor event, if it is one
+    pub fn from_ipc(message: IpcMessage) -> Option<Self> {
+        match message {
+            IpcMessage::ProxyStarted(info) => Some(Self::ProxyRegistered(info)),
+            IpcMessage::ProxyStopped(proxy_id) => Some(Self::ProxyDeregistered(proxy_id)),
+            IpcMessage::MessageObserved(observed) => Some(Self::MessageObserved(observed)),
+            IpcMessage::StatsUpdate(stats) => Some(Self::StatsSnapshot(stats)),
+            IpcMessage::InterceptorStats { proxy_id, stats } => {
+                Some(Self::InterceptorSnapshot { proxy_id, stats })
+            }
+            IpcMessage::LogEntry(entry) => Some(Self::Log(entry)),
+            IpcMessage::DecisionTraced(trace) => Some(Self::DecisionTraced(trace)),
+            IpcMessage::CircuitChanged(change) => Some(Self::CircuitChanged(change)),
+            IpcMessage::Error { message, proxy_id } => Some(Self::Error { proxy_id, message }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Portable archives of captured MCP sessions.
+//!
+//! A session archive is a single JSON document, modelled on HAR, that pairs
+//! every request with its response and keeps the timing of both. It is meant
+//! to be attached to bug reports and re-opened later for offline inspection.
+//!
+//! ```json
+//! {
+//!   "format": "mcp-session",
+//!   "version": 1,
+//!   "creator": { "name": "assist-mcp", "version": "0.2.0" },
+//!   "entries": [
+//!     {
+//!       "proxy_id": "6f1c…",
+//!       "direction": "client_to_server",
+//!       "kind": "request",
+//!       "method": "tools/call",
+//!       "id": 3,
+//!       "started": "2025-01-01T12:00:00Z",
+//!       "time_ms": 41.7,
+//!       "request": { "timestamp": "…", "payload": { "jsonrpc": "2.0", … } },
+//!       "response": { "timestamp": "…", "payload": { "jsonrpc": "2.0", … } }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! - `direction` is the direction of the message that opened the entry, so
+//!   server-to-client requests (sampling, roots) are kept as well.
+//! - `kind` is `request`, `notification`, or `response` for a response whose
+//!   request was not captured.
+//! - `time_ms` is the latency between request and response. It is absent for
+//!   notifications and for requests that were never answered.
+//! - Each recorded message also carries `modified: true` when an interceptor
+//!   changed it before forwarding, and then `original` with the payload as
+//!   it was before the change.
+//!
+//! Archives convert losslessly to and from recordings in JSONL form: one
+//! [`ObservedMessage`] per line, in capture order, as emitted on the monitor feed.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::protocol::{ObservedMessage, TrafficDirection};
+use crate::types::ProxyId;
+
+/// Value of the `format` field
+pub const SESSION_FORMAT: &str = "mcp-session";
+
+/// Newest archive version this build reads and the one it writes
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+/// A captured MCP session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionArchive {
+    pub format: String,
+    pub version: u32,
+    pub creator: SessionCreator,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub entries: Vec<SessionEntry>,
+}
+
+/// Tool that produced an archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCreator {
+    pub name: String,
+    pub version: String,
+}
+
+/// What opened a [`SessionEntry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Request,
+    Notification,
+    /// A response whose request is not part of the capture
+    Response,
+}
+
+/// One exchange: a request and its response, or a lone message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEntry {
+    pub proxy_id: ProxyId,
+    pub direction: TrafficDirection,
+    pub kind: EntryKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub started: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_ms: Option<f64>,
+    /// The message that opened the entry
+    pub request: RecordedMessage,
+    /// The matching response, if one was captured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<RecordedMessage>,
+}
+
+/// A message as it crossed the proxy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    pub timestamp: DateTime<Utc>,
+    pub payload: Value,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub modified: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<Value>,
+}
+
+impl SessionEntry {
+    /// Whether the response carries a JSON-RPC error
+    pub fn is_error(&self) -> bool {
+        self.response
+            .as_ref()
+            .is_some_and(|r| r.payload.get("error").is_some())
+    }
+}
+
+impl SessionArchive {
+    /// An empty archive stamped with the given creator
+    pub fn new(creator: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            format: SESSION_FORMAT.to_string(),
+            version: SESSION_FORMAT_VERSION,
+            creator: SessionCreator {
+                name: creator.into(),
+                version: version.into(),
+            },
+            comment: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add messages in capture order, pairing requests with responses
+    pub fn with_observed<'a>(
+        mut self,
+        messages: impl IntoIterator<Item = &'a ObservedMessage>,
+    ) -> Self {
+        // (proxy, direction of the request, id) -> entry index
+        let mut open: HashMap<(ProxyId, TrafficDirection, String), usize> = HashMap::new();
+
+        for message in messages {
+            let recorded = RecordedMessage {
+                timestamp: message.timestamp,
+                payload: message.payload.clone(),
+                modified: message.modified,
+                original: message.original.clone(),
+            };
+            let id_key = message.id.as_ref().map(Value::to_string);
+
+            match (&message.method, id_key) {
+                (Some(_), Some(id)) => {
+                    open.insert(
+                        (message.proxy_id.clone(), message.direction, id),
+                        self.entries.len(),
+                    );
+                    self.entries
+                        .push(Self::open_entry(message, EntryKind::Request, recorded));
+                }
+                (Some(_), None) => {
+                    self.entries
+                        .push(Self::open_entry(message, EntryKind::Notification, recorded));
+                }
+                (None, id) => {
+                    let key =
+                        id.map(|id| (message.proxy_id.clone(), opposite(message.direction), id));
+                    match key.and_then(|key| open.remove(&key)) {
+                        Some(index) => {
+                            let entry = &mut self.entries[index];
+                            let elapsed = recorded.timestamp - entry.started;
+                            entry.time_ms =
+                                Some(elapsed.num_microseconds().unwrap_or(0) as f64 / 1000.0);
+                            entry.response = Some(recorded);
+                        }
+                        None => {
+                            self.entries.push(Self::open_entry(
+                                message,
+                                EntryKind::Response,
+                                recorded,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        self
+    }
+
+    fn open_entry(
+        message: &ObservedMessage,
+        kind: EntryKind,
+        recorded: RecordedMessage,
+    ) -> SessionEntry {
+        SessionEntry {
+            proxy_id: message.proxy_id.clone(),
+            direction: message.direction,
+            kind,
+            method: message.method.clone(),
+            id: message.id.clone(),
+            started: message.timestamp,
+            time_ms: None,
+            request: recorded,
+            response: None,
+        }
+    }
+
+    /// Flatten back into individual messages, ordered by timestamp
+    pub fn to_observed(&self) -> Vec<ObservedMessage> {
+        let mut messages = Vec::new();
+        for entry in &self.entries {
+            messages.push(observed(
+                entry.proxy_id.clone(),
+                entry.direction,
+                &entry.request,
+            ));
+            if let Some(ref response) = entry.response {
+                messages.push(observed(
+                    entry.proxy_id.clone(),
+                    opposite(entry.direction),
+                    response,
+                ));
+            }
+        }
+        messages.sort_by_key(|m| m.timestamp);
+        messages
+    }
+
+    /// Add the messages of a JSONL recording (one [`ObservedMessage`] per line)
+    pub fn with_jsonl(self, jsonl: &str) -> Result<Self> {
+        let mut messages = Vec::new();
+        for (number, line) in jsonl.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message: ObservedMessage = serde_json::from_str(line)
+                .with_context(|| format!("Invalid recording at line {}", number + 1))?;
+            messages.push(message);
+        }
+        Ok(self.with_observed(&messages))
+    }
+
+    /// Render as a JSONL recording
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut jsonl = String::new();
+        for message in self.to_observed() {
+            jsonl.push_str(&serde_json::to_string(&message)?);
+            jsonl.push('\n');
+        }
+        Ok(jsonl)
+    }
+
+    /// Parse an archive, rejecting other formats and newer versions
+    pub fn from_json(json: &str) -> Result<Self> {
+        let archive: Self = serde_json::from_str(json).context("Invalid session archive")?;
+        if archive.format != SESSION_FORMAT {
+            bail!("Not a session archive: format is '{}'", archive.format);
+        }
+        if archive.version > SESSION_FORMAT_VERSION {
+            bail!(
+                "Session archive version {} is newer than supported version {}",
+                archive.version,
+                SESSION_FORMAT_VERSION
+            );
+        }
+        Ok(archive)
+    }
+
+    /// Load an archive or a JSONL recording, detected from the content
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let is_archive = serde_json::from_str::<Value>(&content)
+            .ok()
+            .is_some_and(|v| v.get("format").is_some());
+        if is_archive {
+            Self::from_json(&content)
+        } else {
+            Self::new("recording", env!("CARGO_PKG_VERSION"))
+                .with_jsonl(&content)
+                .with_context(|| format!("Failed to load {}", path.display()))
+        }
+    }
+
+    /// Write the archive as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn opposite(direction: TrafficDirection) -> TrafficDirection {
+    match direction {
+        TrafficDirection::ClientToServer => TrafficDirection::ServerToClient,
+        TrafficDirection::ServerToClient => TrafficDirection::ClientToServer,
+    }
+}
+
+fn observed(
+    proxy_id: ProxyId,
+    direction: TrafficDirection,
+    recorded: &RecordedMessage,
+) -> ObservedMessage {
+    let mut message = ObservedMessage::new(proxy_id, direction, recorded.payload.clone());
+    message.timestamp = recorded.timestamp;
+    message.modified = recorded.modified;
+    message.original = recorded.original.clone();
+    message
+}
@@ -72,6 +72,9 @@ pub struct ProxyStats {
     pub successful_requests: u64,
     pub failed_requests: u64,
     pub active_connections: u32,
+    /// Idle, pre-initialized sessions kept for failover
+    #[serde(default)]
+    pub standby_connections: u32,
     pub uptime: std::time::Duration,
     pub bytes_transferred: u64,
 }
@@ -84,6 +87,7 @@ impl Default for ProxyStats {
             successful_requests: 0,
             failed_requests: 0,
             active_connections: 0,
+            standby_connections: 0,
             uptime: std::time::Duration::from_secs(0),
             bytes_transferred: 0,
         }
@@ -285,6 +289,28 @@ pub struct HealthMetrics {
     pub last_error: Option<String>,
 }
 
+/// State of the circuit breaker in front of a backend server
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// A backend's circuit breaker changed state
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CircuitChange {
+    pub proxy_id: ProxyId,
+    pub backend: String,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub consecutive_failures: u32,
+    /// The failure that opened the circuit, if it opened
+    pub last_error: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerInfo {
     pub id: ServerId,
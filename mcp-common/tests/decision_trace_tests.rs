@@ -0,0 +1,96 @@
+use chrono::{Duration, Utc};
+use mcp_common::*;
+use serde_json::json;
+use tempfile::tempdir;
+
+fn trace(requested: &str, chosen: Option<&str>, latency_ms: f64) -> DecisionTrace {
+    let request =
+        json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": requested}});
+    let mut trace = DecisionTrace::new("Hybrid", &request);
+    trace.candidates = vec![
+        TraceCandidate {
+            tool: "read_file".to_string(),
+            score: 0.4,
+            source: CandidateSource::Rule,
+            reasoning: None,
+        },
+        TraceCandidate {
+            tool: "search".to_string(),
+            score: 0.9,
+            source: CandidateSource::Prediction,
+            reasoning: None,
+        },
+    ];
+    trace.chosen = chosen.map(str::to_string);
+    trace.latency_ms = latency_ms;
+    trace
+}
+
+#[test]
+fn test_trace_log_round_trip_and_summary() {
+    let dir = tempdir().unwrap();
+    let log = DecisionTraceLog::new(dir.path().join("traces.jsonl"));
+    assert!(log.load().unwrap().is_empty());
+
+    let first = trace("search", Some("search"), 10.0);
+    assert_eq!(first.requested_tool.as_deref(), Some("search"));
+    assert_eq!(first.best().unwrap().tool, "search");
+    log.append(&first).unwrap();
+    log.append(&trace("read_file", Some("search"), 20.0))
+        .unwrap();
+    log.append(&trace("read_file", None, 30.0)).unwrap();
+    assert_eq!(log.load().unwrap()[0], first);
+
+    let summary = log.summary().unwrap();
+    assert_eq!(
+        (
+            summary.decisions,
+            summary.chosen,
+            summary.scored,
+            summary.correct
+        ),
+        (3, 2, 2, 1)
+    );
+    assert_eq!(summary.accuracy(), Some(0.5));
+    assert_eq!(summary.mean_latency_ms, 20.0);
+
+    let export = dir.path().join("export.jsonl");
+    assert_eq!(log.export(&export, None).unwrap(), 3);
+    assert_eq!(
+        log.export(&export, Some(Utc::now() + Duration::hours(1)))
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_trace_reaches_monitors() {
+    let traced = trace("search", Some("search"), 1.0);
+    match MonitorEvent::from_ipc(IpcMessage::DecisionTraced(traced.clone())) {
+        Some(MonitorEvent::DecisionTraced(trace)) => assert_eq!(trace, traced),
+        other => panic!("Expected DecisionTraced event, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_explain_ranks_candidates_and_flags_misses() {
+    let mut missed = trace("read_file", Some("search"), 12.0);
+    missed.applied = true;
+    missed.candidates[1].reasoning = Some("query mentions docs".to_string());
+    assert_eq!(
+        missed.explain(),
+        vec![
+            "Hybrid routing of tools/call took 12.0 ms",
+            "1. search scored 0.90 from a model prediction: query mentions docs",
+            "2. read_file scored 0.40 from a routing rule",
+            "Chose search and rewrote the request for it",
+            "The client asked for read_file",
+        ]
+    );
+
+    let undecided = trace("search", None, 3.0);
+    assert_eq!(
+        undecided.explain().last().unwrap(),
+        "No candidate cleared the confidence threshold"
+    );
+}
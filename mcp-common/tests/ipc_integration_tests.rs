@@ -117,6 +117,7 @@ async fn test_proxy_stats_updates() {
                 successful_requests: i * 9,
                 failed_requests: i,
                 active_connections: 1,
+                standby_connections: 0,
                 uptime: Duration::from_secs(i * 60),
                 bytes_transferred: i * 1024,
             };
@@ -337,6 +338,7 @@ async fn test_message_types() {
             successful_requests: 1,
             failed_requests: 0,
             active_connections: 1,
+            standby_connections: 0,
             uptime: Duration::from_secs(60),
             bytes_transferred: 256,
         };
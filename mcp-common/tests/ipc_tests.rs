@@ -109,6 +109,7 @@ async fn test_ipc_client_wrapper() {
         successful_requests: 40,
         failed_requests: 2,
         active_connections: 1,
+        standby_connections: 0,
         uptime: std::time::Duration::from_secs(300),
         bytes_transferred: 1024,
     };
@@ -332,3 +333,50 @@ async fn test_concurrent_clients() {
         }
     }
 }
+
+#[test]
+fn test_ipc_endpoint_parsing() {
+    assert_eq!(
+        IpcEndpoint::parse("/tmp/mcp-monitor.sock").unwrap(),
+        IpcEndpoint::Unix("/tmp/mcp-monitor.sock".to_string())
+    );
+    assert_eq!(
+        IpcEndpoint::parse("unix:/tmp/a.sock").unwrap(),
+        IpcEndpoint::Unix("/tmp/a.sock".to_string())
+    );
+    assert_eq!(
+        IpcEndpoint::parse("pipe:mcp-monitor").unwrap(),
+        IpcEndpoint::NamedPipe(r"\\.\pipe\mcp-monitor".to_string())
+    );
+    assert_eq!(
+        IpcEndpoint::parse("tcp:127.0.0.1:7878").unwrap(),
+        IpcEndpoint::Tcp("127.0.0.1:7878".parse().unwrap())
+    );
+
+    // TCP must stay on loopback
+    assert!(IpcEndpoint::parse("tcp:0.0.0.0:7878").is_err());
+    assert!(IpcEndpoint::parse("tcp:localhost").is_err());
+    assert!(IpcEndpoint::parse("").is_err());
+}
+
+#[tokio::test]
+async fn test_tcp_endpoint_round_trip() {
+    let server = IpcServer::bind("tcp:127.0.0.1:0").await.unwrap();
+    let endpoint = format!("tcp:{}", server.local_addr().unwrap());
+
+    let client_task = tokio::spawn(async move {
+        let mut client = IpcClient::connect(&endpoint).await.unwrap();
+        client
+            .handshake(PeerRole::Proxy, "tcp-proxy")
+            .await
+            .unwrap();
+        client.send(IpcMessage::Ping).await.unwrap();
+    });
+
+    let mut connection = server.accept().await.unwrap();
+    let envelope = connection.receive_message().await.unwrap().unwrap();
+    assert!(matches!(envelope.message, IpcMessage::Ping));
+    assert_eq!(connection.peer().unwrap().software, "tcp-proxy");
+
+    client_task.await.unwrap();
+}
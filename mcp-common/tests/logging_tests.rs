@@ -0,0 +1,161 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use chrono::{TimeZone, Utc};
+use mcp_common::*;
+use serde_json::Value;
+use tempfile::tempdir;
+
+/// Log output collected in memory
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_log_format_parses_and_resolves() {
+    for format in [LogFormat::Text, LogFormat::Json] {
+        assert_eq!(format.to_string().parse::<LogFormat>().unwrap(), format);
+    }
+    assert!("yaml".parse::<LogFormat>().is_err());
+    assert_eq!(
+        LogFormat::resolve(Some("json"), LogFormat::Text).unwrap(),
+        LogFormat::Json
+    );
+    assert!(LogFormat::resolve(Some("xml"), LogFormat::Text).is_err());
+
+    let config = MonitorConfig::parse("[logging]\nformat = \"json\"\n", "monitor.toml").unwrap();
+    assert_eq!(config.logging.format, LogFormat::Json);
+}
+
+#[test]
+fn test_json_lines_carry_context_from_spans_and_fields() {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .fmt_fields(JsonFields)
+        .event_format(JsonFormat)
+        .finish();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("proxy", session = "s-1", method = "initialize");
+        let _entered = span.enter();
+        tracing::warn!(method = "tools/call", attempt = 2, "Retrying {}", "call");
+        tracing::info!("Bare");
+    });
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["level"], "WARN");
+    assert_eq!(lines[0]["target"], "logging_tests");
+    assert_eq!(lines[0]["message"], "Retrying call");
+    assert_eq!(lines[0]["session"], "s-1");
+    assert_eq!(lines[0]["method"], "tools/call");
+    assert_eq!(lines[0]["request_id"], Value::Null);
+    assert_eq!(lines[0]["fields"]["attempt"], 2);
+    assert!(lines[0]["timestamp"].as_str().unwrap().ends_with('Z'));
+
+    // Without fields of its own an event still has every key
+    assert_eq!(lines[1]["method"], "initialize");
+    assert!(lines[1].get("fields").is_none());
+    assert!(lines[1].as_object().unwrap().contains_key("request_id"));
+}
+
+#[test]
+fn test_rotation_parses_periods_and_sizes() {
+    for (text, rotation) in [
+        ("never", Rotation::Never),
+        ("hourly", Rotation::Hourly),
+        ("daily", Rotation::Daily),
+        ("10MB", Rotation::Size(10 << 20)),
+        ("512", Rotation::Size(512)),
+    ] {
+        assert_eq!(text.parse::<Rotation>().unwrap(), rotation);
+        assert_eq!(rotation.to_string(), text.replace("512", "512B"));
+    }
+    assert_eq!("4k".parse::<Rotation>().unwrap(), Rotation::Size(4096));
+    for invalid in ["weekly", "0MB", "10 MB", "MB"] {
+        assert!(invalid.parse::<Rotation>().is_err(), "{invalid}");
+    }
+
+    let config = MonitorConfig::parse(
+        "[logging]\nfile = \"monitor.log\"\nrotate = \"1GB\"\nkeep = 3\n",
+        "monitor.toml",
+    )
+    .unwrap();
+    assert_eq!(
+        config.logging.log_file(),
+        Some(
+            LogFile::new("monitor.log")
+                .rotation(Rotation::Size(1 << 30))
+                .keep(3)
+        )
+    );
+    assert_eq!(MonitorConfig::default().logging.log_file(), None);
+}
+
+#[test]
+fn test_daily_rotation_sets_files_aside_and_keeps_the_newest() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("logs").join("proxy.log");
+    let config = LogFile::new(&path).keep(2);
+    let day = |day| Utc.with_ymd_and_hms(2025, 1, day, 9, 30, 0).unwrap();
+
+    let mut file = RotatingFile::open(&config).unwrap();
+    file.write_at(b"first\n", day(1)).unwrap();
+    file.write_at(b"second\n", day(1)).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+    for d in 2..=4 {
+        file.write_at(format!("day {d}\n").as_bytes(), day(d))
+            .unwrap();
+        file.write_at(b"more\n", day(d)).unwrap();
+    }
+    file.flush().unwrap();
+
+    let mut names: Vec<String> = std::fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["proxy.log", "proxy.log.2025-01-02", "proxy.log.2025-01-03"]
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "day 4\nmore\n");
+}
+
+#[test]
+fn test_size_rotation_starts_a_new_file_before_the_limit() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("monitor.log");
+    let config = LogFile::new(&path).rotation(Rotation::Size(10)).keep(0);
+    let at = Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, 0).unwrap();
+
+    let mut file = RotatingFile::open(&config).unwrap();
+    file.write_at(b"12345678\n", at).unwrap();
+    file.write_at(b"abc\n", at).unwrap();
+    // A line longer than the limit still goes into a file of its own
+    file.write_at(b"0123456789abcdef\n", at).unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "0123456789abcdef\n"
+    );
+    let aside = dir.path().join("monitor.log.2025-01-15T09-30-00");
+    assert_eq!(std::fs::read_to_string(&aside).unwrap(), "12345678\n");
+    let collided = dir.path().join("monitor.log.2025-01-15T09-30-00.1");
+    assert_eq!(std::fs::read_to_string(collided).unwrap(), "abc\n");
+}
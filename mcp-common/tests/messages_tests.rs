@@ -34,6 +34,7 @@ fn test_ipc_message_stats_update_serialization() {
         successful_requests: 95,
         failed_requests: 5,
         active_connections: 2,
+        standby_connections: 0,
         uptime: std::time::Duration::from_secs(3600),
         bytes_transferred: 1024000,
     };
@@ -174,6 +175,7 @@ fn test_all_ipc_message_variants() {
             successful_requests: 48,
             failed_requests: 2,
             active_connections: 1,
+            standby_connections: 0,
             uptime: std::time::Duration::from_secs(1800),
             bytes_transferred: 256000,
         }),
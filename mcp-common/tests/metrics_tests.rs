@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use mcp_common::*;
+use serde_json::json;
+
+#[test]
+fn test_buckets_give_rate_percentiles_and_errors() {
+    let mut window = MetricsWindow::new(Duration::from_secs(10), 3);
+    let at = |second| Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, second).unwrap();
+    for (second, latency, error) in [(1, 10.0, false), (2, 20.0, false), (3, 200.0, true)] {
+        window.record("db", at(second), latency, error);
+    }
+    window.record("db", at(25), 5.0, false);
+    window.record("fs", at(21), 7.0, false);
+
+    assert_eq!(window.latest(), Some(at(20)));
+    let series = window.series("db");
+    assert_eq!(series.requests_per_sec, vec![0.3, 0.0, 0.1]);
+    assert_eq!(series.p50_ms, vec![20.0, 0.0, 5.0]);
+    assert_eq!(series.p95_ms, vec![200.0, 0.0, 5.0]);
+    assert!((series.error_rate[0] - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(window.series("fs").requests_per_sec, vec![0.0, 0.0, 0.1]);
+
+    // Buckets sliding out of the window are dropped, servers with them
+    window.record("fs", at(45), 7.0, false);
+    assert_eq!(window.servers().collect::<Vec<_>>(), vec!["db", "fs"]);
+    window.record("fs", at(59), 7.0, false);
+    assert_eq!(window.servers().collect::<Vec<_>>(), vec!["fs"]);
+    assert_eq!(window.series("db"), Series::default());
+}
+
+#[test]
+fn test_tracker_pairs_requests_with_responses() {
+    let proxy = ProxyId::new();
+    let mut tracker = RequestTracker::new();
+    let at = |millis: i64| {
+        Utc.timestamp_millis_opt(1_736_933_400_000 + millis)
+            .unwrap()
+    };
+    let observe = |tracker: &mut RequestTracker, direction, millis, payload| {
+        let mut message = ObservedMessage::new(proxy.clone(), direction, payload);
+        message.timestamp = at(millis);
+        tracker.observe(&message)
+    };
+
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call"});
+    assert_eq!(
+        observe(&mut tracker, TrafficDirection::ClientToServer, 0, request),
+        None
+    );
+    let notification = json!({"jsonrpc": "2.0", "method": "notifications/progress"});
+    assert_eq!(
+        observe(
+            &mut tracker,
+            TrafficDirection::ClientToServer,
+            5,
+            notification
+        ),
+        None
+    );
+    let response = json!({"jsonrpc": "2.0", "id": 1, "result": {"isError": true}});
+    let completed = observe(&mut tracker, TrafficDirection::ServerToClient, 42, response).unwrap();
+    assert_eq!(completed.method, "tools/call");
+    assert_eq!(completed.latency_ms, 42.0);
+    assert!(completed.error);
+
+    // Each request completes once
+    let again = json!({"jsonrpc": "2.0", "id": 1, "result": {}});
+    assert_eq!(
+        observe(&mut tracker, TrafficDirection::ServerToClient, 50, again),
+        None
+    );
+}
+
+#[test]
+fn test_recent_merges_the_newest_buckets() {
+    let mut window = MetricsWindow::new(Duration::from_secs(10), 6);
+    let at = |second| Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, second).unwrap();
+    window.record("db", at(5), 10.0, true);
+    window.record("db", at(25), 20.0, false);
+    window.record("db", at(35), 30.0, true);
+
+    let recent = window.recent("db", Duration::from_secs(15));
+    assert_eq!((recent.requests, recent.errors), (2, 1));
+    assert_eq!(recent.latencies_ms, vec![20.0, 30.0]);
+    assert_eq!(window.recent("db", Duration::from_secs(60)).requests, 3);
+    assert_eq!(
+        window.recent("fs", Duration::from_secs(60)),
+        Bucket::default()
+    );
+}
+
+#[test]
+fn test_proxy_metrics_render_prometheus_text() {
+    let proxy = ProxyId::new();
+    let mut metrics = ProxyMetrics::new();
+    let start = Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, 0).unwrap();
+    let mut request = ObservedMessage::new(
+        proxy.clone(),
+        TrafficDirection::ClientToServer,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "read\"file"}}),
+    );
+    request.timestamp = start;
+    let mut response = ObservedMessage::new(
+        proxy.clone(),
+        TrafficDirection::ServerToClient,
+        json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32603, "message": "boom"}}),
+    );
+    response.timestamp = start + chrono::Duration::milliseconds(30);
+    metrics.observe(&request);
+    metrics.observe(&response);
+    metrics.transport_error();
+
+    let stats = ProxyStats {
+        proxy_id: proxy,
+        active_connections: 1,
+        bytes_transferred: 512,
+        ..ProxyStats::default()
+    };
+    let text = metrics.render(&stats);
+    for line in [
+        "# TYPE genmcp_proxy_request_duration_seconds histogram",
+        "genmcp_proxy_requests_total{method=\"tools/call\"} 1",
+        "genmcp_proxy_request_errors_total{method=\"tools/call\"} 1",
+        "genmcp_proxy_request_duration_seconds_bucket{method=\"tools/call\",le=\"0.025\"} 0",
+        "genmcp_proxy_request_duration_seconds_bucket{method=\"tools/call\",le=\"0.05\"} 1",
+        "genmcp_proxy_request_duration_seconds_bucket{method=\"tools/call\",le=\"+Inf\"} 1",
+        "genmcp_proxy_request_duration_seconds_count{method=\"tools/call\"} 1",
+        "genmcp_proxy_tool_calls_total{tool=\"read\\\"file\"} 1",
+        "genmcp_proxy_transport_errors_total 1",
+        "genmcp_proxy_connected_clients 1",
+        "genmcp_proxy_bytes_total 512",
+    ] {
+        assert!(
+            text.lines().any(|l| l == line),
+            "missing {line:?} in\n{text}"
+        );
+    }
+}
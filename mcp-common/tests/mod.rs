@@ -1,4 +1,6 @@
 pub mod ipc_tests;
 pub mod mcp_tests;
 pub mod messages_tests;
+pub mod protocol_tests;
+pub mod session_tests;
 pub mod types_tests;
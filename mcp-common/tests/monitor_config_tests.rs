@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use mcp_common::*;
+use serde_json::json;
+use tempfile::tempdir;
+
+#[test]
+fn test_partial_files_keep_the_other_defaults() {
+    let config = MonitorConfig::parse(
+        "[panels]\nquick_access = false\n\n[history]\nactivities = 500\n",
+        "monitor.toml",
+    )
+    .unwrap();
+    assert!(!config.panels.quick_access);
+    assert!(config.panels.activity);
+    assert_eq!(config.history.activities, 500);
+    assert_eq!(
+        config.history.server_logs,
+        HistoryConfig::default().server_logs
+    );
+    assert_eq!(config.refresh, RefreshConfig::default());
+    assert_eq!(config.theme.preset, "dark");
+    assert!(config.theme.colors.is_empty());
+    assert!(config.keys.is_empty());
+
+    let error =
+        MonitorConfig::parse("[refresh]\nredraw_ms = \"fast\"\n", "monitor.toml").unwrap_err();
+    assert!(error.to_string().contains("monitor.toml"));
+}
+
+#[test]
+fn test_theme_roles_and_keys_are_read_alongside_the_preset() {
+    let config = MonitorConfig::parse(
+        "[theme]\npreset = \"light\"\naccent = \"#005f87\"\n\n[keys]\nquit = [\"ctrl+c\", \"q\"]\n",
+        "monitor.toml",
+    )
+    .unwrap();
+    assert_eq!(config.theme.preset, "light");
+    assert_eq!(config.theme.colors["accent"], "#005f87");
+    assert_eq!(config.keys["quit"], vec!["ctrl+c", "q"]);
+}
+
+#[test]
+fn test_redaction_masks_keys_at_any_depth() {
+    let rules = RedactionRules {
+        keys: vec!["Authorization".to_string(), "api_key".to_string()],
+        replacement: "***".to_string(),
+    };
+    let mut payload = json!({
+        "method": "tools/call",
+        "params": {
+            "arguments": {"API_KEY": "sk-123", "query": "weather"},
+            "headers": [{"authorization": "Bearer abc"}]
+        }
+    });
+    rules.redact(&mut payload);
+    assert_eq!(payload["params"]["arguments"]["API_KEY"], "***");
+    assert_eq!(payload["params"]["arguments"]["query"], "weather");
+    assert_eq!(payload["params"]["headers"][0]["authorization"], "***");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_watch_reloads_a_changed_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("monitor.toml");
+    std::fs::write(&path, "[refresh]\nredraw_ms = 100\n").unwrap();
+
+    let mut config = MonitorConfig::watch(&path, MonitorConfig::load(&path).unwrap());
+    std::fs::write(&path, "[refresh]\nredraw_ms = 250\n").unwrap();
+    tokio::time::timeout(Duration::from_secs(10), config.changed())
+        .await
+        .expect("configuration was not reloaded")
+        .unwrap();
+    assert_eq!(config.borrow().refresh.redraw_ms, 250);
+}
+
+#[test]
+fn test_alerts_fire_nowhere_until_a_destination_is_set() {
+    let config = MonitorConfig::default();
+    assert!(!config.alerts.is_enabled());
+    assert!(config.alerts.fires_on(AlertKind::Sampling));
+
+    let config = MonitorConfig::parse(
+        "[alerts]\non = [\"disconnect\", \"denied_call\"]\ncommand = \"notify.sh\"\n",
+        "monitor.toml",
+    )
+    .unwrap();
+    assert!(config.alerts.is_enabled());
+    assert!(config.alerts.fires_on(AlertKind::DeniedCall));
+    assert!(!config.alerts.fires_on(AlertKind::ErrorRate));
+    assert_eq!(config.alerts.window(), Duration::from_secs(60));
+}
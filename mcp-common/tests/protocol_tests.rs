@@ -0,0 +1,179 @@
+use mcp_common::*;
+use serde_json::json;
+use tempfile::tempdir;
+
+fn socket_path(dir: &tempfile::TempDir) -> String {
+    dir.path()
+        .join("protocol.sock")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_negotiate_versions() {
+    let v = IpcProtocolVersion::new;
+
+    assert_eq!(negotiate(&[v(1, 2)], &[v(1, 0)]), Some(v(1, 0)));
+    assert_eq!(
+        negotiate(&[v(1, 0), v(2, 1)], &[v(1, 3), v(2, 0)]),
+        Some(v(2, 0))
+    );
+    assert_eq!(negotiate(&[v(1, 0)], &[v(2, 0)]), None);
+    assert_eq!(v(1, 2).to_string(), "1.2");
+}
+
+#[test]
+fn test_v1_wire_format_is_stable() {
+    // Fixtures written by a 1.0 peer; these must keep decoding
+    let hello: IpcEnvelope = serde_json::from_value(json!({
+        "message": {
+            "Hello": {
+                "role": "proxy",
+                "software": "mcp-transport/0.2.0",
+                "versions": [{ "major": 1, "minor": 0 }]
+            }
+        },
+        "timestamp": "2025-01-01T00:00:00Z",
+        "correlation_id": null
+    }))
+    .unwrap();
+    match hello.message {
+        IpcMessage::Hello(hello) => {
+            assert_eq!(hello, Hello::new(PeerRole::Proxy, "mcp-transport/0.2.0"));
+        }
+        other => panic!("Expected Hello, got {:?}", other),
+    }
+
+    let reply: IpcMessage = serde_json::from_value(json!({
+        "HelloReply": { "status": "accepted", "version": { "major": 1, "minor": 0 }, "software": "monitor" }
+    }))
+    .unwrap();
+    assert!(matches!(
+        reply,
+        IpcMessage::HelloReply(HelloReply::Accepted { version, .. }) if version == IPC_PROTOCOL_VERSION
+    ));
+
+    let observed: IpcMessage = serde_json::from_value(json!({
+        "MessageObserved": {
+            "proxy_id": ProxyId::new(),
+            "direction": "client_to_server",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "method": "tools/list",
+            "id": 1,
+            "payload": { "jsonrpc": "2.0", "id": 1, "method": "tools/list" }
+        }
+    }))
+    .unwrap();
+    match MonitorEvent::from_ipc(observed) {
+        Some(MonitorEvent::MessageObserved(message)) => {
+            assert_eq!(message.direction, TrafficDirection::ClientToServer);
+            assert_eq!(message.method.as_deref(), Some("tools/list"));
+            assert!(!message.modified);
+        }
+        other => panic!("Expected MessageObserved event, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_monitor_event_mapping() {
+    let proxy_id = ProxyId::new();
+
+    assert!(matches!(
+        MonitorEvent::from_ipc(IpcMessage::ProxyStopped(proxy_id.clone())),
+        Some(MonitorEvent::ProxyDeregistered(id)) if id == proxy_id
+    ));
+    assert!(matches!(
+        MonitorEvent::from_ipc(IpcMessage::StatsUpdate(ProxyStats::default())),
+        Some(MonitorEvent::StatsSnapshot(_))
+    ));
+    assert!(MonitorEvent::from_ipc(IpcMessage::Ping).is_none());
+}
+
+#[tokio::test]
+async fn test_handshake_negotiates_version() {
+    let temp_dir = tempdir().unwrap();
+    let path = socket_path(&temp_dir);
+    let server = IpcServer::bind(&path).await.unwrap();
+
+    let client_path = path.clone();
+    let client_task = tokio::spawn(async move {
+        let mut client = IpcClient::connect(&client_path).await.unwrap();
+        let version = client
+            .handshake(PeerRole::Proxy, "test-proxy")
+            .await
+            .unwrap();
+        client.send(IpcMessage::Ping).await.unwrap();
+        version
+    });
+
+    let mut connection = server.accept().await.unwrap();
+    let envelope = connection.receive_message().await.unwrap().unwrap();
+
+    // The Hello is answered internally; the first message returned is the Ping
+    assert!(matches!(envelope.message, IpcMessage::Ping));
+    assert_eq!(connection.negotiated_version(), Some(IPC_PROTOCOL_VERSION));
+    assert_eq!(connection.peer().unwrap().software, "test-proxy");
+    assert_eq!(client_task.await.unwrap(), IPC_PROTOCOL_VERSION);
+}
+
+#[tokio::test]
+async fn test_handshake_rejects_incompatible_version() {
+    let temp_dir = tempdir().unwrap();
+    let path = socket_path(&temp_dir);
+    let server = IpcServer::bind(&path).await.unwrap();
+
+    let client_path = path.clone();
+    let client_task = tokio::spawn(async move {
+        let mut connection = IpcConnection::connect(&client_path).await.unwrap();
+        let hello = Hello {
+            role: PeerRole::Proxy,
+            software: "future-proxy".to_string(),
+            versions: vec![IpcProtocolVersion::new(99, 0)],
+        };
+        connection.handshake(hello).await
+    });
+
+    let mut connection = server.accept().await.unwrap();
+    assert!(connection.receive_message().await.is_err());
+
+    let error = client_task.await.unwrap().unwrap_err();
+    assert!(error.to_string().contains("rejected"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_unknown_variants_are_skipped() {
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixStream;
+
+    let temp_dir = tempdir().unwrap();
+    let path = socket_path(&temp_dir);
+    let server = IpcServer::bind(&path).await.unwrap();
+
+    let client_path = path.clone();
+    let client_task = tokio::spawn(async move {
+        let mut stream = UnixStream::connect(&client_path).await.unwrap();
+        let future = json!({
+            "message": { "SomethingFromV1_9": { "x": 1 } },
+            "timestamp": "2025-01-01T00:00:00Z",
+            "correlation_id": null
+        });
+        let ping = json!({
+            "message": "Ping",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "correlation_id": null
+        });
+        stream
+            .write_all(format!("{}\n{}\n", future, ping).as_bytes())
+            .await
+            .unwrap();
+        stream.flush().await.unwrap();
+    });
+
+    let mut connection = server.accept().await.unwrap();
+    let envelope = connection.receive_message().await.unwrap().unwrap();
+    assert!(matches!(envelope.message, IpcMessage::Ping));
+    assert_eq!(connection.negotiated_version(), None);
+
+    client_task.await.unwrap();
+}
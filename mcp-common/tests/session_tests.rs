@@ -0,0 +1,127 @@
+use chrono::{Duration, Utc};
+use mcp_common::*;
+use serde_json::json;
+use tempfile::tempdir;
+
+fn capture() -> Vec<ObservedMessage> {
+    let proxy_id = ProxyId::new();
+    let start = Utc::now();
+    let at = |ms: i64, direction, payload| {
+        let mut message = ObservedMessage::new(proxy_id.clone(), direction, payload);
+        message.timestamp = start + Duration::milliseconds(ms);
+        message
+    };
+
+    vec![
+        at(
+            0,
+            TrafficDirection::ClientToServer,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        ),
+        at(
+            5,
+            TrafficDirection::ClientToServer,
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        ),
+        at(
+            12,
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": []}}),
+        ),
+        at(
+            20,
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": "s1", "method": "roots/list"}),
+        ),
+        at(
+            25,
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": 9, "error": {"code": -1, "message": "late"}}),
+        ),
+    ]
+}
+
+#[test]
+fn test_session_pairs_requests_and_responses() {
+    let messages = capture();
+    let archive = SessionArchive::new("test", "1").with_observed(&messages);
+
+    let kinds: Vec<EntryKind> = archive.entries.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EntryKind::Request,
+            EntryKind::Notification,
+            EntryKind::Request,
+            EntryKind::Response
+        ]
+    );
+
+    let list = &archive.entries[0];
+    assert_eq!(list.method.as_deref(), Some("tools/list"));
+    assert_eq!(list.time_ms, Some(12.0));
+    assert!(list.response.is_some());
+
+    // A server-to-client request without an answer stays open
+    assert_eq!(
+        archive.entries[2].direction,
+        TrafficDirection::ServerToClient
+    );
+    assert!(archive.entries[2].response.is_none());
+    assert!(archive.entries[3].request.payload.get("error").is_some());
+}
+
+#[test]
+fn test_session_jsonl_round_trip() {
+    let messages = capture();
+    let archive = SessionArchive::new("test", "1").with_observed(&messages);
+
+    let jsonl = archive.to_jsonl().unwrap();
+    assert_eq!(jsonl.lines().count(), messages.len());
+
+    let reloaded = SessionArchive::new("test", "1").with_jsonl(&jsonl).unwrap();
+    assert_eq!(reloaded, archive);
+
+    let error = SessionArchive::new("test", "1")
+        .with_jsonl("{}\nnot json")
+        .unwrap_err();
+    assert!(error.to_string().contains("line 1"));
+}
+
+#[test]
+fn test_session_file_formats() {
+    let dir = tempdir().unwrap();
+    let archive = SessionArchive::new("test", "1").with_observed(&capture());
+
+    let json_path = dir.path().join("session.json");
+    archive.save(&json_path).unwrap();
+    assert_eq!(SessionArchive::load(&json_path).unwrap(), archive);
+
+    let jsonl_path = dir.path().join("recording.jsonl");
+    std::fs::write(&jsonl_path, archive.to_jsonl().unwrap()).unwrap();
+    assert_eq!(
+        SessionArchive::load(&jsonl_path).unwrap().entries,
+        archive.entries
+    );
+
+    let mut newer = serde_json::to_value(&archive).unwrap();
+    newer["version"] = json!(SESSION_FORMAT_VERSION + 1);
+    assert!(SessionArchive::from_json(&newer.to_string()).is_err());
+}
+
+#[test]
+fn test_session_keeps_pre_interceptor_payload() {
+    let mut messages = capture();
+    messages[0].modified = true;
+    messages[0].original =
+        Some(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list", "params": {"cursor": "x"}}));
+    let archive = SessionArchive::new("test", "1").with_observed(&messages);
+
+    let request = &archive.entries[0].request;
+    assert!(request.modified);
+    assert_eq!(request.original.as_ref().unwrap()["params"]["cursor"], "x");
+
+    let replayed = archive.to_observed();
+    assert_eq!(replayed[0].original, messages[0].original);
+    assert!(replayed[1].original.is_none());
+}
@@ -134,6 +134,7 @@ fn test_proxy_stats_serialization() {
         successful_requests: 95,
         failed_requests: 5,
         active_connections: 3,
+        standby_connections: 0,
         uptime: std::time::Duration::from_secs(3600),
         bytes_transferred: 1024000,
     };
@@ -241,6 +242,7 @@ fn test_proxy_info_complete() {
         successful_requests: 48,
         failed_requests: 2,
         active_connections: 1,
+        standby_connections: 0,
         uptime: std::time::Duration::from_secs(1800),
         bytes_transferred: 512000,
     };
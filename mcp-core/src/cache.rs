@@ -0,0 +1,387 @@
+//! Caching of responses to idempotent MCP methods.
+//!
+//! Clients re-list tools, resources and prompts far more often than servers
+//! change them. [`ResponseCache`] keeps the results of `tools/list`,
+//! `resources/list`, `prompts/list` and `resources/read` for a while, keyed by
+//! method, parameters and session, so a proxy can answer repeats without
+//! bothering the server. Entries expire after a time-to-live and are dropped
+//! as soon as the server announces a change: `list_changed` notifications
+//! invalidate the matching listing, and `notifications/resources/updated`
+//! invalidates reads of that resource.
+//!
+//! Like [`flood`](crate::flood), the cache does no I/O and takes the current
+//! time as an argument.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_core::cache::{CacheConfig, ResponseCache};
+//! use serde_json::json;
+//! use std::time::Instant;
+//!
+//! let mut cache = ResponseCache::new(CacheConfig::default());
+//! let now = Instant::now();
+//! let key = cache.key("tools/list", Some(&json!({})), "session-1").unwrap();
+//! cache.insert(key.clone(), json!({ "tools": [] }), now);
+//! assert!(cache.get(&key, now).is_some());
+//!
+//! cache.invalidate("notifications/tools/list_changed", None);
+//! assert!(cache.get(&key, now).is_none());
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Methods whose responses can be cached: they have no side effects.
+pub const CACHEABLE_METHODS: &[&str] = &[
+    "tools/list",
+    "resources/list",
+    "prompts/list",
+    "resources/read",
+];
+
+/// What to cache and for how long.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// How long a response stays valid without an invalidating notification
+    #[serde(with = "humantime_serde")]
+    pub ttl: Duration,
+
+    /// Methods to cache; a subset of [`CACHEABLE_METHODS`]
+    pub methods: Vec<String>,
+
+    /// Entries kept before the oldest are evicted
+    pub max_entries: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            methods: CACHEABLE_METHODS.iter().map(|m| m.to_string()).collect(),
+            max_entries: 1024,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Cache every cacheable method for `ttl`.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            ..Self::default()
+        }
+    }
+}
+
+/// Identity of a cached response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    method: String,
+    params: String,
+    session: String,
+}
+
+impl CacheKey {
+    /// Method of the cached request.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    result: Value,
+    stored: Instant,
+    /// Resource a `resources/read` entry holds
+    uri: Option<String>,
+}
+
+/// Counters for a [`ResponseCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Requests answered from the cache
+    pub hits: u64,
+    /// Cacheable requests that went to the server
+    pub misses: u64,
+    /// Entries dropped because the server announced a change
+    pub invalidations: u64,
+    /// Entries currently held
+    pub entries: usize,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hit(s), {} miss(es), {} invalidation(s), {} entries",
+            self.hits, self.misses, self.invalidations, self.entries
+        )
+    }
+}
+
+/// Cache of responses to idempotent methods.
+#[derive(Debug)]
+pub struct ResponseCache {
+    config: CacheConfig,
+    entries: HashMap<CacheKey, Entry>,
+    stats: CacheStats,
+}
+
+impl ResponseCache {
+    /// Create an empty cache.
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// The settings in effect.
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Key for a request, or `None` when its method is not cached.
+    ///
+    /// `_meta` is left out of the parameters, since progress tokens differ
+    /// between otherwise identical requests.
+    pub fn key(&self, method: &str, params: Option<&Value>, session: &str) -> Option<CacheKey> {
+        if !CACHEABLE_METHODS.contains(&method) || !self.config.methods.iter().any(|m| m == method)
+        {
+            return None;
+        }
+        let params = match params {
+            Some(Value::Object(map)) => {
+                let mut map = map.clone();
+                map.remove("_meta");
+                canonical(&Value::Object(map))
+            }
+            Some(Value::Null) | None => canonical(&Value::Object(Default::default())),
+            Some(other) => canonical(other),
+        };
+        Some(CacheKey {
+            method: method.to_string(),
+            params,
+            session: session.to_string(),
+        })
+    }
+
+    /// Cached result for `key`, if still fresh.
+    pub fn get(&mut self, key: &CacheKey, now: Instant) -> Option<Value> {
+        let fresh = match self.entries.get(key) {
+            Some(entry) => now.duration_since(entry.stored) < self.config.ttl,
+            None => false,
+        };
+        if !fresh {
+            self.entries.remove(key);
+            self.stats.misses += 1;
+            return None;
+        }
+        self.stats.hits += 1;
+        self.entries.get(key).map(|entry| entry.result.clone())
+    }
+
+    /// Store the result of a request.
+    pub fn insert(&mut self, key: CacheKey, result: Value, now: Instant) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+        if self.entries.len() >= self.config.max_entries && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        let uri = if key.method == "resources/read" {
+            serde_json::from_str::<Value>(&key.params)
+                .ok()
+                .and_then(|params| params["uri"].as_str().map(str::to_string))
+        } else {
+            None
+        };
+        self.entries.insert(
+            key,
+            Entry {
+                result,
+                stored: now,
+                uri,
+            },
+        );
+    }
+
+    /// Drop what a server notification makes stale; returns the number of
+    /// entries dropped.
+    pub fn invalidate(&mut self, notification: &str, params: Option<&Value>) -> usize {
+        let before = self.entries.len();
+        match notification {
+            "notifications/tools/list_changed" => self.drop_method("tools/list"),
+            "notifications/prompts/list_changed" => self.drop_method("prompts/list"),
+            "notifications/resources/list_changed" => self.drop_method("resources/list"),
+            "notifications/resources/updated" => {
+                match params.and_then(|params| params["uri"].as_str()) {
+                    Some(uri) => self
+                        .entries
+                        .retain(|_, entry| entry.uri.as_deref() != Some(uri)),
+                    None => self.drop_method("resources/read"),
+                }
+            }
+            _ => {}
+        }
+        let dropped = before - self.entries.len();
+        self.stats.invalidations += dropped as u64;
+        dropped
+    }
+
+    /// Drop every entry of a session, e.g. after the server restarted.
+    pub fn invalidate_session(&mut self, session: &str) {
+        self.entries.retain(|key, _| key.session != session);
+    }
+
+    /// Drop everything.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Counters since the cache was created.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    fn drop_method(&mut self, method: &str) {
+        self.entries.retain(|key, _| key.method != method);
+    }
+}
+
+/// JSON text with object keys sorted, so equal parameters give equal keys
+fn canonical(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), canonical(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_keys_and_expiry() {
+        let mut cache = ResponseCache::new(CacheConfig::with_ttl(Duration::from_secs(10)));
+        assert!(cache.key("tools/call", None, "s").is_none());
+
+        let key = cache
+            .key(
+                "resources/list",
+                Some(&json!({ "cursor": "a", "_meta": { "progressToken": 1 } })),
+                "s",
+            )
+            .unwrap();
+        let same = cache.key("resources/list", Some(&json!({ "cursor": "a" })), "s");
+        assert_eq!(Some(&key), same.as_ref());
+        assert_ne!(
+            Some(&key),
+            cache
+                .key("resources/list", Some(&json!({ "cursor": "a" })), "t")
+                .as_ref()
+        );
+
+        let now = Instant::now();
+        cache.insert(key.clone(), json!({ "resources": [] }), now);
+        assert_eq!(
+            cache.get(&key, now + Duration::from_secs(5)),
+            Some(json!({ "resources": [] }))
+        );
+        assert_eq!(cache.get(&key, now + Duration::from_secs(11)), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                invalidations: 0,
+                entries: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_notifications_invalidate() {
+        let mut cache = ResponseCache::new(CacheConfig::default());
+        let now = Instant::now();
+        let read = |cache: &ResponseCache, uri: &str| {
+            cache
+                .key("resources/read", Some(&json!({ "uri": uri })), "s")
+                .unwrap()
+        };
+        let tools = cache.key("tools/list", None, "s").unwrap();
+        cache.insert(tools.clone(), json!({ "tools": [] }), now);
+        cache.insert(read(&cache, "file:///a"), json!({ "contents": [] }), now);
+        cache.insert(read(&cache, "file:///b"), json!({ "contents": [] }), now);
+
+        let updated = json!({ "uri": "file:///a" });
+        assert_eq!(
+            cache.invalidate("notifications/resources/updated", Some(&updated)),
+            1
+        );
+        assert!(cache.get(&read(&cache, "file:///a"), now).is_none());
+        assert!(cache.get(&read(&cache, "file:///b"), now).is_some());
+
+        assert_eq!(
+            cache.invalidate("notifications/prompts/list_changed", None),
+            0
+        );
+        assert_eq!(
+            cache.invalidate("notifications/tools/list_changed", None),
+            1
+        );
+        assert!(cache.get(&tools, now).is_none());
+        assert_eq!(cache.stats().invalidations, 2);
+    }
+
+    #[test]
+    fn test_oldest_entry_is_evicted() {
+        let mut cache = ResponseCache::new(CacheConfig {
+            max_entries: 2,
+            ..CacheConfig::default()
+        });
+        let start = Instant::now();
+        let keys: Vec<CacheKey> = ["a", "b", "c"]
+            .iter()
+            .map(|cursor| {
+                cache
+                    .key("tools/list", Some(&json!({ "cursor": cursor })), "s")
+                    .unwrap()
+            })
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            cache.insert(key.clone(), json!(i), start + Duration::from_secs(i as u64));
+        }
+        let now = start + Duration::from_secs(3);
+        assert!(cache.get(&keys[0], now).is_none());
+        assert_eq!(cache.get(&keys[2], now), Some(json!(2)));
+    }
+}
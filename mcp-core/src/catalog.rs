@@ -0,0 +1,505 @@
+//! Cached tool catalogs with filtering, sorting and paging.
+//!
+//! A [`ToolCatalog`] holds the `tools/list` results of any number of servers
+//! together with call statistics, so consumers can ask for "read-only tools
+//! mentioning `issue`, most used first, 20 at a time" without going back to
+//! the servers. The TUI, the CLI list commands and the LLM router's candidate
+//! selection all describe what they want as a [`CatalogQuery`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_core::catalog::{CatalogQuery, CatalogSort, ToolAnnotation, ToolCatalog};
+//! use serde_json::json;
+//!
+//! let mut catalog = ToolCatalog::new();
+//! catalog.insert_listing("github", &json!({ "tools": [
+//!     { "name": "search_issues", "annotations": { "readOnlyHint": true } },
+//!     { "name": "create_issue" },
+//! ]}));
+//!
+//! let page = catalog.query(
+//!     &CatalogQuery::new()
+//!         .text("issue")
+//!         .annotation(ToolAnnotation::ReadOnly, true)
+//!         .sort(CatalogSort::Name)
+//!         .limit(20),
+//! );
+//! assert_eq!(page.entries[0].name(), "search_issues");
+//! assert_eq!(page.total, 1);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A tool offered by one server, with its call statistics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// Server that offers the tool
+    pub server: String,
+    /// The tool as listed by `tools/list`
+    pub tool: Value,
+    /// Calls recorded with [`ToolCatalog::record_call`]
+    pub calls: u64,
+    /// Sum of the recorded call latencies
+    #[serde(with = "humantime_serde")]
+    pub total_latency: Duration,
+}
+
+impl CatalogEntry {
+    /// Tool name
+    pub fn name(&self) -> &str {
+        self.tool["name"].as_str().unwrap_or_default()
+    }
+
+    /// Tool description, empty when the server gave none
+    pub fn description(&self) -> &str {
+        self.tool["description"].as_str().unwrap_or_default()
+    }
+
+    /// Display title from the tool's annotations, if any
+    pub fn title(&self) -> Option<&str> {
+        self.tool
+            .pointer("/annotations/title")
+            .or_else(|| self.tool.get("title"))
+            .and_then(Value::as_str)
+    }
+
+    /// Value of a behavior hint, `None` when the server did not say
+    pub fn annotation(&self, annotation: ToolAnnotation) -> Option<bool> {
+        let hint = self
+            .tool
+            .get("annotations")
+            .and_then(|annotations| annotations.get(annotation.hint()))
+            .and_then(Value::as_bool);
+        match annotation {
+            // Some servers predate annotations and send a top-level flag
+            ToolAnnotation::ReadOnly => hint.or_else(|| self.tool["readOnly"].as_bool()),
+            _ => hint,
+        }
+    }
+
+    /// Mean latency of the recorded calls
+    pub fn average_latency(&self) -> Option<Duration> {
+        u32::try_from(self.calls)
+            .ok()
+            .filter(|calls| *calls > 0)
+            .map(|calls| self.total_latency / calls)
+    }
+
+    fn matches_text(&self, needle: &str) -> bool {
+        [
+            self.name(),
+            self.description(),
+            self.title().unwrap_or_default(),
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(needle))
+    }
+}
+
+/// Behavior hints from a tool's `annotations`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolAnnotation {
+    /// `readOnlyHint`: the tool does not modify its environment
+    ReadOnly,
+    /// `destructiveHint`: the tool may delete or overwrite data
+    Destructive,
+    /// `idempotentHint`: repeating a call has no further effect
+    Idempotent,
+    /// `openWorldHint`: the tool reaches outside systems
+    OpenWorld,
+}
+
+impl ToolAnnotation {
+    /// Key of the hint inside `annotations`
+    pub fn hint(self) -> &'static str {
+        match self {
+            Self::ReadOnly => "readOnlyHint",
+            Self::Destructive => "destructiveHint",
+            Self::Idempotent => "idempotentHint",
+            Self::OpenWorld => "openWorldHint",
+        }
+    }
+}
+
+impl FromStr for ToolAnnotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read-only" | "readonly" => Ok(Self::ReadOnly),
+            "destructive" => Ok(Self::Destructive),
+            "idempotent" => Ok(Self::Idempotent),
+            "open-world" => Ok(Self::OpenWorld),
+            other => Err(format!(
+                "Unknown annotation '{}'; use read-only, destructive, idempotent or open-world",
+                other
+            )),
+        }
+    }
+}
+
+/// Order of query results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogSort {
+    /// Alphabetical by tool name, then server
+    #[default]
+    Name,
+    /// Fastest average latency first; never-called tools last
+    Latency,
+    /// Most called first
+    Popularity,
+}
+
+impl FromStr for CatalogSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Self::Name),
+            "latency" => Ok(Self::Latency),
+            "popularity" => Ok(Self::Popularity),
+            other => Err(format!(
+                "Unknown sort '{}'; use name, latency or popularity",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CatalogSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Name => "name",
+            Self::Latency => "latency",
+            Self::Popularity => "popularity",
+        })
+    }
+}
+
+/// Which catalog entries to return, in what order and how many
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CatalogQuery {
+    /// Case-insensitive substring of the name, description or title
+    pub text: Option<String>,
+    /// Only tools of this server
+    pub server: Option<String>,
+    /// Required hint values; a tool without the hint does not match
+    pub annotations: Vec<(ToolAnnotation, bool)>,
+    /// Result order
+    pub sort: CatalogSort,
+    /// Entries to skip after filtering and sorting
+    pub offset: usize,
+    /// Most entries to return; all when `None`
+    pub limit: Option<usize>,
+}
+
+impl CatalogQuery {
+    /// A query matching every tool, sorted by name
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match tools whose name, description or title contains `text`
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Match only the tools of `server`
+    pub fn server(mut self, server: impl Into<String>) -> Self {
+        self.server = Some(server.into());
+        self
+    }
+
+    /// Match only tools whose `annotation` hint is `value`
+    pub fn annotation(mut self, annotation: ToolAnnotation, value: bool) -> Self {
+        self.annotations.push((annotation, value));
+        self
+    }
+
+    /// Order results
+    pub fn sort(mut self, sort: CatalogSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Skip the first `offset` results
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return at most `limit` results
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `entry` passes the filters
+    pub fn matches(&self, entry: &CatalogEntry) -> bool {
+        if self
+            .server
+            .as_ref()
+            .is_some_and(|server| *server != entry.server)
+        {
+            return false;
+        }
+        if let Some(text) = &self.text {
+            if !entry.matches_text(&text.to_lowercase()) {
+                return false;
+            }
+        }
+        self.annotations
+            .iter()
+            .all(|(annotation, value)| entry.annotation(*annotation) == Some(*value))
+    }
+
+    fn compare(&self, a: &CatalogEntry, b: &CatalogEntry) -> Ordering {
+        let by_name = || a.name().cmp(b.name()).then_with(|| a.server.cmp(&b.server));
+        match self.sort {
+            CatalogSort::Name => by_name(),
+            CatalogSort::Latency => match (a.average_latency(), b.average_latency()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+            .then_with(by_name),
+            CatalogSort::Popularity => b.calls.cmp(&a.calls).then_with(by_name),
+        }
+    }
+}
+
+/// One page of query results
+#[derive(Debug, Clone)]
+pub struct CatalogPage<'a> {
+    /// Matching entries in this page
+    pub entries: Vec<&'a CatalogEntry>,
+    /// Matching entries across all pages
+    pub total: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// Tools of every known server, with call statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCatalog {
+    entries: Vec<CatalogEntry>,
+    /// When each server's listing was last stored
+    refreshed: HashMap<String, DateTime<Utc>>,
+}
+
+impl ToolCatalog {
+    /// An empty catalog
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace `server`'s tools, keeping the statistics of tools it still offers
+    pub fn insert_tools(&mut self, server: &str, tools: impl IntoIterator<Item = Value>) {
+        let mut previous: Vec<CatalogEntry> = Vec::new();
+        self.entries.retain(|entry| {
+            if entry.server == server {
+                previous.push(entry.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        for tool in tools {
+            if !tool["name"].is_string() {
+                continue;
+            }
+            let (calls, total_latency) = previous
+                .iter()
+                .find(|entry| entry.tool["name"] == tool["name"])
+                .map_or((0, Duration::ZERO), |entry| {
+                    (entry.calls, entry.total_latency)
+                });
+            self.entries.push(CatalogEntry {
+                server: server.to_string(),
+                tool,
+                calls,
+                total_latency,
+            });
+        }
+
+        self.refreshed.insert(server.to_string(), Utc::now());
+    }
+
+    /// Store a `tools/list` result for `server`
+    pub fn insert_listing(&mut self, server: &str, result: &Value) {
+        let tools = result["tools"].as_array().cloned().unwrap_or_default();
+        self.insert_tools(server, tools);
+    }
+
+    /// Drop everything known about `server`
+    pub fn remove_server(&mut self, server: &str) {
+        self.entries.retain(|entry| entry.server != server);
+        self.refreshed.remove(server);
+    }
+
+    /// When `server`'s tools were last stored
+    pub fn refreshed_at(&self, server: &str) -> Option<DateTime<Utc>> {
+        self.refreshed.get(server).copied()
+    }
+
+    /// Count a call of `tool` on `server` that took `latency`
+    pub fn record_call(&mut self, server: &str, tool: &str, latency: Duration) {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.server == server && entry.name() == tool)
+        {
+            entry.calls += 1;
+            entry.total_latency += latency;
+        }
+    }
+
+    /// The entry for `tool` on `server`
+    pub fn get(&self, server: &str, tool: &str) -> Option<&CatalogEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.server == server && entry.name() == tool)
+    }
+
+    /// Every entry, in insertion order
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    /// Number of tools across all servers
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no tools are known
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Filter, sort and page the catalog
+    pub fn query(&self, query: &CatalogQuery) -> CatalogPage<'_> {
+        let mut matching: Vec<&CatalogEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .collect();
+        matching.sort_by(|a, b| query.compare(a, b));
+
+        let total = matching.len();
+        let end = query
+            .limit
+            .map_or(total, |limit| query.offset.saturating_add(limit).min(total));
+        let entries = matching
+            .into_iter()
+            .skip(query.offset)
+            .take(end.saturating_sub(query.offset))
+            .collect();
+        CatalogPage {
+            entries,
+            total,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn catalog() -> ToolCatalog {
+        let mut catalog = ToolCatalog::new();
+        catalog.insert_listing(
+            "github",
+            &json!({ "tools": [
+                { "name": "search_issues", "description": "Find issues",
+                  "annotations": { "readOnlyHint": true, "openWorldHint": true } },
+                { "name": "create_issue", "description": "Open an issue",
+                  "annotations": { "readOnlyHint": false, "destructiveHint": false } },
+                { "name": "delete_repo", "annotations": { "destructiveHint": true } },
+            ]}),
+        );
+        catalog.insert_listing(
+            "files",
+            &json!({ "tools": [
+                { "name": "read_file", "description": "Read a file", "readOnly": true },
+                { "description": "nameless tools are skipped" },
+            ]}),
+        );
+        catalog
+    }
+
+    fn names(page: &CatalogPage<'_>) -> Vec<String> {
+        page.entries.iter().map(|e| e.name().to_string()).collect()
+    }
+
+    #[test]
+    fn test_filters() {
+        let catalog = catalog();
+        assert_eq!(catalog.len(), 4);
+
+        let page = catalog.query(&CatalogQuery::new().text("ISSUE"));
+        assert_eq!(names(&page), vec!["create_issue", "search_issues"]);
+
+        let page = catalog.query(&CatalogQuery::new().annotation(ToolAnnotation::ReadOnly, true));
+        assert_eq!(names(&page), vec!["read_file", "search_issues"]);
+
+        let page = catalog.query(
+            &CatalogQuery::new()
+                .server("github")
+                .annotation(ToolAnnotation::Destructive, false),
+        );
+        assert_eq!(names(&page), vec!["create_issue"]);
+    }
+
+    #[test]
+    fn test_sorting_and_paging() {
+        let mut catalog = catalog();
+        catalog.record_call("github", "create_issue", Duration::from_millis(300));
+        catalog.record_call("github", "create_issue", Duration::from_millis(100));
+        catalog.record_call("files", "read_file", Duration::from_millis(5));
+
+        let page = catalog.query(&CatalogQuery::new().sort(CatalogSort::Popularity).limit(2));
+        assert_eq!(names(&page), vec!["create_issue", "read_file"]);
+        assert_eq!((page.total, page.next_offset), (4, Some(2)));
+
+        let page = catalog.query(&CatalogQuery::new().sort(CatalogSort::Latency));
+        assert_eq!(
+            names(&page),
+            vec!["read_file", "create_issue", "delete_repo", "search_issues"]
+        );
+        assert_eq!(
+            page.entries[1].average_latency(),
+            Some(Duration::from_millis(200))
+        );
+
+        let page = catalog.query(&CatalogQuery::new().offset(3).limit(5));
+        assert_eq!(names(&page), vec!["search_issues"]);
+        assert_eq!(page.next_offset, None);
+        assert!(catalog
+            .query(&CatalogQuery::new().offset(9))
+            .entries
+            .is_empty());
+
+        // Relisting keeps the statistics of tools that are still offered
+        catalog.insert_listing("github", &json!({ "tools": [{ "name": "create_issue" }] }));
+        assert_eq!(catalog.get("github", "create_issue").unwrap().calls, 2);
+        assert!(catalog.get("github", "delete_repo").is_none());
+        assert!(catalog.refreshed_at("github").is_some());
+    }
+}
@@ -8,57 +8,209 @@
 //! abstracting away transport details and providing a clean async API.
 
 use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use tokio::sync::{mpsc, oneshot, RwLock};
-use tokio::time::{sleep, Instant};
+use tokio::sync::{broadcast, mpsc, watch, RwLock};
 
-use crate::error::{McpError, McpResult, ProtocolError};
+use crate::correlator::{CorrelatorStats, RequestCorrelator};
+use crate::error::{McpError, McpResult, ProtocolError, TransportError};
+use crate::health::{Health, HealthChecker, HealthConfig, Probe};
 use crate::interceptor::{InterceptorManager, MessageDirection};
 use crate::messages::{
-    Capabilities, Implementation, InitializeRequest, InitializeResponse, InitializedNotification,
-    JsonRpcId, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
-    ProgressNotification, PromptListChangedNotification, ProtocolVersion,
-    ResourceListChangedNotification, ResourceUpdatedNotification, ToolListChangedNotification,
+    Capabilities, CompleteResponse, CompletionArgument, Implementation, InitializeRequest, InitializeResponse, InitializedNotification,
+    JsonRpcId, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, LogLevel,
+    LoggingNotification, ProgressNotification, PromptListChangedNotification, ProtocolVersion,
+    ResourceListChangedNotification, ResourceUpdatedNotification, SamplingCapabilities,
+    SetLevelRequest, ToolListChangedNotification,
 };
-use crate::transport::{factory::TransportFactory, Transport, TransportConfig};
+use crate::messages::core::JsonRpcError;
+use crate::notifications::NotificationRegistry;
+use crate::quirks::{AppliedQuirks, Fingerprint, QuirkDatabase};
+use crate::restart::{session_identity, RestartReason, ServerRestarted};
+use crate::server_log::LogEntry;
+use crate::retry::{FailureKind, RetryPolicy};
+use crate::rt::{self, sleep, Instant};
+use crate::transport::{
+    factory::TransportFactory, BreakerConfig, BreakerTransport, Transport, TransportConfig,
+};
+use crate::violations::{ProtocolViolation, UnknownFields, ViolationReporter};
 
 use tracing::{debug, info, warn};
 
 /// Configuration options for MCP client behavior.
 #[derive(Debug, Clone)]
 pub struct ClientConfig {
-    /// Timeout for individual requests (default: 30 seconds)
+    /// Timeout for requests whose method class has no timeout of its own
+    /// (default: 30 seconds)
     pub request_timeout: Duration,
 
+    /// Timeouts per [`MethodClass`], taking precedence over `request_timeout`
+    pub method_timeouts: MethodTimeouts,
+
     /// Timeout for the initialization process (default: 10 seconds)  
     pub init_timeout: Duration,
 
-    /// Maximum number of retry attempts for failed operations
-    pub max_retries: u32,
-
-    /// Base delay for exponential backoff retries
-    pub retry_base_delay: Duration,
+    /// Which failed requests are sent again, how often and after how long
+    pub retry: RetryPolicy,
 
     /// Whether to automatically handle server notifications
     pub auto_handle_notifications: bool,
 
     /// Buffer size for incoming messages
     pub message_buffer_size: usize,
+
+    /// Overall deadline for [`McpClient::connect`], covering transport setup,
+    /// session discovery and protocol initialization (default: unbounded)
+    pub connect_deadline: Option<Duration>,
+
+    /// Protocol version requested in the `initialize` request
+    pub protocol_version: ProtocolVersion,
+
+    /// Known server quirks, applied to matching servers (default: none)
+    pub quirks: QuirkDatabase,
+
+    /// Registry profile the transport was resolved from, for matching quirks
+    pub profile: Option<String>,
+
+    /// Reconnect and re-initialize when the server restarts (default: true)
+    pub renegotiate_on_restart: bool,
+
+    /// Fail fast while the server is down, see
+    /// [`breaker`](crate::transport::breaker) (default: off)
+    pub circuit_breaker: Option<BreakerConfig>,
+
+    /// Probe the server and gate readiness on a first round trip, see
+    /// [`health`](crate::health) (default: off)
+    pub health_check: Option<HealthConfig>,
 }
 
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
             request_timeout: Duration::from_secs(30),
+            method_timeouts: MethodTimeouts::default(),
             init_timeout: Duration::from_secs(10),
-            max_retries: 3,
-            retry_base_delay: Duration::from_secs(1),
+            retry: RetryPolicy::default(),
             auto_handle_notifications: true,
             message_buffer_size: 1000,
+            connect_deadline: None,
+            protocol_version: ProtocolVersion::default(),
+            quirks: QuirkDatabase::default(),
+            profile: None,
+            renegotiate_on_restart: true,
+            circuit_breaker: None,
+            health_check: None,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Timeout for a request to `method` when the call sets none: the timeout
+    /// of its method class, or `request_timeout` if the class has none.
+    pub fn timeout_for(&self, method: &str) -> Duration {
+        self.method_timeouts
+            .get(MethodClass::of(method))
+            .unwrap_or(self.request_timeout)
+    }
+}
+
+/// Groups of methods with similar latency expectations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MethodClass {
+    /// Catalog listings such as `tools/list`; expected to be fast
+    List,
+    /// Fetching a single item: `resources/read`, `prompts/get`, `completion/complete`
+    Read,
+    /// `tools/call`, which may run arbitrary work on the server
+    Call,
+    /// `sampling/createMessage`, which waits on an LLM and possibly a human
+    Sampling,
+    /// Anything else, e.g. `ping` or `logging/setLevel`
+    Other,
+}
+
+impl MethodClass {
+    /// Classify a JSON-RPC method name.
+    pub fn of(method: &str) -> Self {
+        match method {
+            "tools/call" => MethodClass::Call,
+            "resources/read" | "prompts/get" | "completion/complete" => MethodClass::Read,
+            "sampling/createMessage" => MethodClass::Sampling,
+            m if m.ends_with("/list") => MethodClass::List,
+            _ => MethodClass::Other,
+        }
+    }
+}
+
+/// Per-[`MethodClass`] request timeouts; `None` falls back to
+/// [`ClientConfig::request_timeout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodTimeouts {
+    /// Timeout for listings (default: 10 seconds)
+    pub list: Option<Duration>,
+    /// Timeout for single-item reads (default: 30 seconds)
+    pub read: Option<Duration>,
+    /// Timeout for tool calls (default: 120 seconds)
+    pub call: Option<Duration>,
+    /// Timeout for sampling (default: 10 minutes)
+    pub sampling: Option<Duration>,
+}
+
+impl Default for MethodTimeouts {
+    fn default() -> Self {
+        Self {
+            list: Some(Duration::from_secs(10)),
+            read: Some(Duration::from_secs(30)),
+            call: Some(Duration::from_secs(120)),
+            sampling: Some(Duration::from_secs(600)),
+        }
+    }
+}
+
+impl MethodTimeouts {
+    /// Timeout configured for a method class, if any.
+    pub fn get(&self, class: MethodClass) -> Option<Duration> {
+        match class {
+            MethodClass::List => self.list,
+            MethodClass::Read => self.read,
+            MethodClass::Call => self.call,
+            MethodClass::Sampling => self.sampling,
+            MethodClass::Other => None,
+        }
+    }
+
+    /// Set or clear the timeout for a method class. `Other` always uses the
+    /// global request timeout, so setting it has no effect.
+    pub fn set(&mut self, class: MethodClass, timeout: Option<Duration>) {
+        match class {
+            MethodClass::List => self.list = timeout,
+            MethodClass::Read => self.read = timeout,
+            MethodClass::Call => self.call = timeout,
+            MethodClass::Sampling => self.sampling = timeout,
+            MethodClass::Other => {}
+        }
+    }
+}
+
+/// Phases of the connection pipeline bounded by [`ClientConfig::connect_deadline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectPhase {
+    /// Establishing the transport (process spawn, HTTP session discovery)
+    Transport,
+    /// Exchanging `initialize` / `initialized` with the server
+    Initialization,
+}
+
+impl fmt::Display for ConnectPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectPhase::Transport => write!(f, "transport"),
+            ConnectPhase::Initialization => write!(f, "initialization"),
         }
     }
 }
@@ -108,11 +260,16 @@ pub struct ClientStats {
     pub retries: u64,
     /// Number of connection attempts
     pub connection_attempts: u64,
+    /// Number of server restarts recovered from
+    pub restarts: u64,
     /// Last activity timestamp
     pub last_activity: Option<Instant>,
 }
 
 /// Handler for MCP notifications from the server
+///
+/// Covers the standard notifications; register handlers for other methods
+/// with a [`NotificationRegistry`].
 #[async_trait]
 pub trait NotificationHandler: Send + Sync {
     /// Handle progress notifications
@@ -165,6 +322,33 @@ pub struct DefaultNotificationHandler;
 #[async_trait]
 impl NotificationHandler for DefaultNotificationHandler {}
 
+/// Handler for `sampling/createMessage` requests from the server
+///
+/// A client with one advertises the `sampling` capability and answers the
+/// server's completion requests with it. Errors are returned to the server
+/// as JSON-RPC internal errors.
+#[async_trait]
+pub trait SamplingHandler: Send + Sync {
+    /// Complete the conversation in `request`
+    async fn create_message(&self, request: CompletionArgument) -> McpResult<CompleteResponse>;
+}
+
+/// Answer a sampling request with `handler`
+async fn answer_sampling(handler: &dyn SamplingHandler, request: JsonRpcRequest) -> JsonRpcResponse {
+    let params = request.params.unwrap_or(serde_json::Value::Null);
+    let argument: CompletionArgument = match serde_json::from_value(params) {
+        Ok(argument) => argument,
+        Err(e) => return JsonRpcResponse::error(request.id, JsonRpcError::invalid_params(e.to_string())),
+    };
+    match handler.create_message(argument).await {
+        Ok(completion) => match serde_json::to_value(completion) {
+            Ok(result) => JsonRpcResponse::success(request.id, result),
+            Err(e) => JsonRpcResponse::error(request.id, JsonRpcError::internal_error(e.to_string())),
+        },
+        Err(e) => JsonRpcResponse::error(request.id, JsonRpcError::internal_error(e.to_string())),
+    }
+}
+
 /// High-level MCP client for communicating with MCP servers.
 ///
 /// The `McpClient` handles the complete MCP protocol flow including:
@@ -174,16 +358,47 @@ impl NotificationHandler for DefaultNotificationHandler {}
 /// - Server notification handling
 /// - Automatic retries and error recovery
 pub struct McpClient {
-    transport: Box<dyn Transport>,
+    transport: Arc<dyn Transport>,
     config: ClientConfig,
     state: RwLock<ClientState>,
     server_info: RwLock<Option<ServerInfo>>,
     stats: Arc<RwLock<ClientStats>>,
     request_counter: AtomicU64,
-    pending_requests: Arc<RwLock<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>,
-    notification_handler: Arc<dyn NotificationHandler>,
+    correlator: RequestCorrelator,
+    notifications: Arc<NotificationRegistry>,
+    /// Answers the server's completion requests
+    sampling: Option<Arc<dyn SamplingHandler>>,
     interceptor_manager: Arc<InterceptorManager>,
+    violations: Arc<ViolationReporter>,
+    /// Command line or URL, for matching quirks
+    target: Option<String>,
+    /// Name and version the server reported, kept across reconnects
+    known_server: std::sync::Mutex<Option<(String, String)>>,
+    applied_quirks: std::sync::Mutex<AppliedQuirks>,
+    /// Client info sent at connect, for initializing again after a restart
+    client_info: Option<Implementation>,
+    /// Process or session the client initialized
+    session_identity: std::sync::Mutex<Option<String>>,
+    restarts: broadcast::Sender<ServerRestarted>,
+    logs: broadcast::Sender<LogEntry>,
     _message_sender: Option<mpsc::UnboundedSender<JsonRpcMessage>>,
+    /// Moves messages the transport does not correlate to the processing task
+    message_pump: Option<rt::Task>,
+    health: Option<Arc<HealthChecker>>,
+    /// Probes the server while the session lasts
+    health_task: Option<rt::Task>,
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        // The pump holds the transport; let it go with the client
+        if let Some(pump) = self.message_pump.take() {
+            pump.abort();
+        }
+        if let Some(task) = self.health_task.take() {
+            task.abort();
+        }
+    }
 }
 
 impl McpClient {
@@ -219,20 +434,75 @@ impl McpClient {
         client_config: ClientConfig,
         notification_handler: Box<dyn NotificationHandler>,
     ) -> McpResult<Self> {
-        let transport = TransportFactory::create(transport_config).await?;
+        let target = Fingerprint::target_of(&transport_config);
+        let name = client_config
+            .profile
+            .clone()
+            .unwrap_or_else(|| transport_config.transport_type().to_string());
+        let mut transport: Arc<dyn Transport> =
+            Arc::from(TransportFactory::create(transport_config).await?);
+        if let Some(breaker) = client_config.circuit_breaker.clone() {
+            transport = Arc::new(BreakerTransport::new(name, transport, breaker)?);
+        }
 
-        Ok(Self {
+        let mut client = Self::with_transport(transport, client_config, notification_handler);
+        client.target = target;
+        Ok(client)
+    }
+
+    /// Create a new MCP client over an already constructed transport.
+    ///
+    /// Use this for transports the factory cannot build from configuration,
+    /// such as one end of an [`InMemoryTransport`](crate::transport::InMemoryTransport) pair,
+    /// or one already shared with other tasks.
+    pub fn with_transport(
+        transport: Arc<dyn Transport>,
+        client_config: ClientConfig,
+        notification_handler: Box<dyn NotificationHandler>,
+    ) -> Self {
+        let notifications = NotificationRegistry::from_handler(notification_handler.into());
+        let health = client_config.health_check.clone().map(|config| {
+            let probe = Probe::for_transport(Arc::clone(&transport), &config.path);
+            Arc::new(HealthChecker::new(config, probe))
+        });
+        let logs = broadcast::channel(256).0;
+        let sender = logs.clone();
+        notifications.on("notifications/message", move |log: LoggingNotification| {
+            let _ = sender.send(LogEntry::from(log));
+            std::future::ready(Ok(()))
+        });
+
+        Self {
             transport,
             config: client_config,
             state: RwLock::new(ClientState::Disconnected),
             server_info: RwLock::new(None),
             stats: Arc::new(RwLock::new(ClientStats::default())),
             request_counter: AtomicU64::new(1),
-            pending_requests: Arc::new(RwLock::new(HashMap::new())),
-            notification_handler: notification_handler.into(),
+            correlator: RequestCorrelator::new("client"),
+            notifications: Arc::new(notifications),
+            sampling: None,
             interceptor_manager: Arc::new(InterceptorManager::new()),
+            violations: Arc::new(ViolationReporter::new()),
+            target: None,
+            known_server: std::sync::Mutex::new(None),
+            applied_quirks: std::sync::Mutex::new(AppliedQuirks::default()),
+            client_info: None,
+            session_identity: std::sync::Mutex::new(None),
+            restarts: broadcast::channel(16).0,
+            logs,
             _message_sender: None,
-        })
+            message_pump: None,
+            health,
+            health_task: None,
+        }
+    }
+
+    /// Answer the server's `sampling/createMessage` requests with `handler`.
+    ///
+    /// Takes effect on the next connect, which advertises the capability.
+    pub fn set_sampling_handler(&mut self, handler: Arc<dyn SamplingHandler>) {
+        self.sampling = Some(handler);
     }
 
     /// Create a new MCP client with default configuration and notification handler.
@@ -268,14 +538,62 @@ impl McpClient {
         self.stats.read().await.clone()
     }
 
+    /// Get response correlation counters, such as duplicate or unmatched responses.
+    pub fn correlation_stats(&self) -> CorrelatorStats {
+        self.correlator.stats()
+    }
+
     /// Check if the client is connected and ready for operations.
     pub async fn is_ready(&self) -> bool {
         matches!(self.state().await, ClientState::Ready)
     }
 
     /// Get transport information and metadata.
+    ///
+    /// Quirks in effect for the connection are listed under `quirks`.
     pub fn transport_info(&self) -> crate::transport::TransportInfo {
-        self.transport.get_info()
+        let mut info = self.transport.get_info();
+        let quirks = self.quirks();
+        if !quirks.is_empty() {
+            if let Ok(value) = serde_json::to_value(&quirks) {
+                info.metadata.insert("quirks".to_string(), value);
+            }
+        }
+        info
+    }
+
+    /// Quirks in effect for the current connection.
+    pub fn quirks(&self) -> AppliedQuirks {
+        self.applied_quirks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Look up quirks for what is known about the server so far.
+    fn lookup_quirks(&self) -> AppliedQuirks {
+        let fingerprint = Fingerprint {
+            profile: self.config.profile.clone(),
+            target: self.target.clone(),
+            server: self
+                .known_server
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        };
+        self.config.quirks.lookup(&fingerprint)
+    }
+
+    fn set_quirks(&self, quirks: AppliedQuirks) {
+        *self.applied_quirks.lock().unwrap_or_else(|e| e.into_inner()) = quirks;
+    }
+
+    /// Get a shared handle to the underlying transport.
+    ///
+    /// Other tasks, such as a keepalive or a monitor, can use it alongside
+    /// the client; the transport synchronizes internally.
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        Arc::clone(&self.transport)
     }
 
     /// Connect to the MCP server and perform protocol initialization.
@@ -287,6 +605,9 @@ impl McpClient {
     /// 4. Sends the initialized notification
     /// 5. Starts message processing
     ///
+    /// Once connected, the client watches for server restarts before each
+    /// request; see [`McpClient::server_restarts`].
+    ///
     /// # Arguments
     ///
     /// * `client_info` - Information about this client implementation
@@ -315,25 +636,87 @@ impl McpClient {
     pub async fn connect(&mut self, client_info: Implementation) -> McpResult<ServerInfo> {
         info!("Connecting MCP client to server");
 
+        let deadline = self
+            .config
+            .connect_deadline
+            .map(|budget| (Instant::now() + budget, budget));
+        self.client_info = Some(client_info.clone());
+
+        match self.connect_within(deadline, client_info).await {
+            Ok(server_info) => Ok(server_info),
+            Err((phase, error)) => {
+                if let McpError::Timeout { .. } = error {
+                    warn!("Connection deadline exceeded during {} phase", phase);
+                    // Tear down the transport so background discovery tasks stop
+                    if let Err(e) = self.transport.disconnect().await {
+                        debug!("Transport cleanup after deadline failed: {}", e);
+                    }
+                }
+                self.set_error_state(error.to_string());
+                Err(error)
+            }
+        }
+    }
+
+    /// Run the connection pipeline, tagging failures with the phase they occurred in.
+    async fn connect_within(
+        &mut self,
+        deadline: Option<(Instant, Duration)>,
+        client_info: Implementation,
+    ) -> Result<ServerInfo, (ConnectPhase, McpError)> {
         // Update state
         *self.state.write().await = ClientState::Connecting;
 
+        // An HTTP server that is down fails here, before a session is opened
+        let phase = ConnectPhase::Transport;
+        #[cfg(feature = "http")]
+        if let Some(checker) = self.health.clone() {
+            if let Probe::Http(_) = checker.probe_kind() {
+                let outcome = checker.probe().await;
+                if let Err(reason) = &outcome {
+                    let reason = format!("Health check failed: {reason}");
+                    return Err((
+                        phase,
+                        McpError::Protocol(ProtocolError::InitializationFailed { reason }),
+                    ));
+                }
+                checker.record(outcome);
+            }
+        }
+
         // Connect transport
-        self.transport.connect().await.map_err(|e| {
-            let error = format!("Transport connection failed: {e}");
-            self.set_error_state(error.clone());
-            McpError::Protocol(ProtocolError::InitializationFailed { reason: error })
-        })?;
+        Self::run_phase(deadline, phase, self.transport.connect())
+            .await
+            .map_err(|e| match e {
+                McpError::Timeout { .. } => (phase, e),
+                e => {
+                    let reason = format!("Transport connection failed: {e}");
+                    (
+                        phase,
+                        McpError::Protocol(ProtocolError::InitializationFailed { reason }),
+                    )
+                }
+            })?;
 
         // Start message processing
-        self.start_message_processing().await?;
+        self.start_message_processing()
+            .await
+            .map_err(|e| (phase, e))?;
 
         // Perform protocol initialization
-        let server_info = self.perform_initialization(client_info).await?;
+        let phase = ConnectPhase::Initialization;
+        let server_info =
+            Self::run_phase(deadline, phase, self.perform_initialization(client_info))
+                .await
+                .map_err(|e| (phase, e))?;
+        Self::run_phase(deadline, phase, self.readiness_check(&server_info))
+            .await
+            .map_err(|e| (phase, e))?;
 
         // Update state to ready
         *self.state.write().await = ClientState::Ready;
         *self.server_info.write().await = Some(server_info.clone());
+        self.record_session_identity();
 
         info!(
             "MCP client connected successfully to {}",
@@ -342,6 +725,56 @@ impl McpClient {
         Ok(server_info)
     }
 
+    /// Await a connection phase, failing with a timeout if the overall deadline passes.
+    async fn run_phase<T>(
+        deadline: Option<(Instant, Duration)>,
+        phase: ConnectPhase,
+        future: impl Future<Output = McpResult<T>>,
+    ) -> McpResult<T> {
+        let Some((deadline, budget)) = deadline else {
+            return future.await;
+        };
+
+        let started = Instant::now();
+        match rt::timeout_at(deadline, future).await {
+            Ok(result) => {
+                debug!("Connect {} phase took {:?}", phase, started.elapsed());
+                result
+            }
+            Err(_) => Err(McpError::timeout(
+                format!("connect ({phase} phase)"),
+                budget,
+            )),
+        }
+    }
+
+    /// With health checks configured, require a first `tools/list` (or
+    /// `ping`, for servers without tools) to succeed, then keep probing.
+    async fn readiness_check(&mut self, server_info: &ServerInfo) -> McpResult<()> {
+        let Some(checker) = self.health.clone() else {
+            return Ok(());
+        };
+        let method = if server_info.capabilities.standard.tools.is_some() {
+            "tools/list"
+        } else {
+            "ping"
+        };
+        let started = Instant::now();
+        let response = self
+            .send_initialization_request(method, serde_json::json!({}), None)
+            .await?;
+        if let Some(error) = response.error {
+            let reason = format!("Readiness check {} failed: {}", method, error.message);
+            checker.record(Err(reason.clone()));
+            return Err(McpError::Protocol(ProtocolError::InitializationFailed { reason }));
+        }
+        checker.record(Ok(started.elapsed()));
+        if let Some(task) = self.health_task.replace(checker.spawn()) {
+            task.abort();
+        }
+        Ok(())
+    }
+
     /// Disconnect from the MCP server.
     pub async fn disconnect(&mut self) -> McpResult<()> {
         info!("Disconnecting MCP client");
@@ -352,8 +785,15 @@ impl McpClient {
         // Clear server info
         *self.server_info.write().await = None;
 
-        // Clear pending requests
-        self.pending_requests.write().await.clear();
+        // Fail pending requests
+        self.correlator.cancel_all();
+
+        if let Some(pump) = self.message_pump.take() {
+            pump.abort();
+        }
+        if let Some(task) = self.health_task.take() {
+            task.abort();
+        }
 
         // Disconnect transport
         self.transport.disconnect().await?;
@@ -362,11 +802,70 @@ impl McpClient {
         Ok(())
     }
 
+    /// Handlers for server notifications, by method.
+    ///
+    /// Handlers registered here while connected receive the next notification.
+    pub fn notifications(&self) -> Arc<NotificationRegistry> {
+        Arc::clone(&self.notifications)
+    }
+
     /// Get access to the interceptor manager for adding/removing interceptors
     pub fn interceptor_manager(&self) -> Arc<InterceptorManager> {
         self.interceptor_manager.clone()
     }
 
+    /// Subscribe to protocol violations, such as unknown fields, seen from now on
+    pub fn protocol_violations(&self) -> broadcast::Receiver<ProtocolViolation> {
+        self.violations.subscribe()
+    }
+
+    /// Subscribe to server restarts the client recovers from from now on
+    pub fn server_restarts(&self) -> broadcast::Receiver<ServerRestarted> {
+        self.restarts.subscribe()
+    }
+
+    /// Subscribe to log messages the server sends from now on
+    pub fn server_logs(&self) -> broadcast::Receiver<LogEntry> {
+        self.logs.subscribe()
+    }
+
+    /// Health updates for the server, if health checks are configured
+    pub fn health(&self) -> Option<watch::Receiver<Health>> {
+        self.health.as_ref().map(|checker| checker.subscribe())
+    }
+
+    /// The server's current health, if health checks are configured
+    pub fn health_status(&self) -> Option<Health> {
+        self.health.as_ref().map(|checker| checker.health())
+    }
+
+    /// Ask the server to send log messages of `level` and more severe.
+    pub async fn set_level(&mut self, level: LogLevel) -> McpResult<()> {
+        self.send_request("logging/setLevel", SetLevelRequest::new(level))
+            .await?
+            .into_result()
+            .map(|_| ())
+    }
+
+    /// Parse a typed result, reporting any unknown fields it preserved
+    pub async fn parse_result<T>(&self, response: JsonRpcResponse) -> McpResult<T>
+    where
+        T: serde::de::DeserializeOwned + UnknownFields,
+    {
+        if let Some(error) = response.error {
+            return Err(error.into());
+        }
+        let result: T = crate::decode::from_value(response.result.unwrap_or_default())?;
+
+        let server = self.server_info.read().await;
+        let name = server
+            .as_ref()
+            .map(|info| info.implementation.name.as_str())
+            .unwrap_or("unknown");
+        self.violations.report_unknown_fields(name, &result);
+        Ok(result)
+    }
+
     /// Send a notification to the server.
     pub async fn send_notification<T>(&mut self, method: &str, params: T) -> McpResult<()>
     where
@@ -400,11 +899,209 @@ impl McpClient {
             }));
         }
 
-        self.send_request_with_timeout(method, params, None).await
+        self.request_watching_restarts(method, params, None, None)
+            .await
+    }
+
+    /// Send a request, retrying it by `policy` instead of the client's
+    /// [`RetryPolicy`].
+    pub async fn send_request_with_retry<T>(
+        &mut self,
+        method: &str,
+        params: T,
+        policy: &RetryPolicy,
+    ) -> McpResult<JsonRpcResponse>
+    where
+        T: serde::Serialize,
+    {
+        if !self.is_ready().await {
+            return Err(McpError::Protocol(ProtocolError::NotInitialized {
+                reason: "Client not ready for requests".to_string(),
+            }));
+        }
+
+        self.request_watching_restarts(method, params, None, Some(policy))
+            .await
+    }
+
+    /// Send a request with an explicit timeout, overriding the method-class
+    /// and global timeouts from [`ClientConfig`].
+    pub async fn send_request_with_timeout<T>(
+        &mut self,
+        method: &str,
+        params: T,
+        timeout: Duration,
+    ) -> McpResult<JsonRpcResponse>
+    where
+        T: serde::Serialize,
+    {
+        if !self.is_ready().await {
+            return Err(McpError::Protocol(ProtocolError::NotInitialized {
+                reason: "Client not ready for requests".to_string(),
+            }));
+        }
+
+        self.request_watching_restarts(method, params, Some(timeout), None)
+            .await
     }
 
     // Private helper methods
 
+    /// Send a request, first recovering from a server restart if one happened.
+    async fn request_watching_restarts<T>(
+        &mut self,
+        method: &str,
+        params: T,
+        timeout_duration: Option<Duration>,
+        retry: Option<&RetryPolicy>,
+    ) -> McpResult<JsonRpcResponse>
+    where
+        T: serde::Serialize,
+    {
+        if !self.config.renegotiate_on_restart {
+            return self
+                .request_with_timeout(method, params, timeout_duration, retry)
+                .await;
+        }
+
+        if let Some(reason) = self.detect_restart() {
+            self.renegotiate(reason).await?;
+        }
+
+        // Keep the parameters in case the request has to be sent again
+        let params = serde_json::to_value(params)?;
+        match self
+            .request_with_timeout(method, &params, timeout_duration, retry)
+            .await
+        {
+            Err(McpError::Transport(TransportError::HttpError {
+                status_code: 404, ..
+            })) if self.has_session() => {
+                // The server lost our session, most likely because it restarted
+                self.renegotiate(RestartReason::SessionExpired).await?;
+                self.request_with_timeout(method, &params, timeout_duration, retry)
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    fn has_session(&self) -> bool {
+        self.session_identity
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    fn record_session_identity(&self) {
+        *self
+            .session_identity
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = session_identity(&self.transport.get_info());
+    }
+
+    /// Tell whether the server restarted since it was initialized.
+    fn detect_restart(&self) -> Option<RestartReason> {
+        if !self.transport.is_connected() {
+            return Some(RestartReason::ProcessExited);
+        }
+
+        let previous = self
+            .session_identity
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()?;
+        let current = session_identity(&self.transport.get_info())?;
+        (previous != current).then_some(RestartReason::SessionChanged { previous, current })
+    }
+
+    /// Reconnect if needed, initialize again, refresh the tool list and
+    /// tell subscribers about the restart.
+    async fn renegotiate(&mut self, reason: RestartReason) -> McpResult<()> {
+        let Some(client_info) = self.client_info.clone() else {
+            return Ok(());
+        };
+        warn!("Server restart detected ({}); initializing again", reason);
+
+        // Requests in flight belonged to the old server
+        self.correlator.cancel_all();
+
+        // A changed session is already connected; the others need a new one
+        if matches!(
+            reason,
+            RestartReason::ProcessExited | RestartReason::SessionExpired
+        ) {
+            if let Err(e) = self.transport.disconnect().await {
+                debug!("Transport cleanup before reconnect failed: {}", e);
+            }
+            self.stats.write().await.connection_attempts += 1;
+            if let Err(e) = self.transport.connect().await {
+                self.set_error_state(e.to_string());
+                return Err(e);
+            }
+        }
+
+        let previous = self.server_info.read().await.clone();
+        let server = match self.perform_initialization(client_info).await {
+            Ok(server) => server,
+            Err(e) => {
+                self.set_error_state(e.to_string());
+                return Err(e);
+            }
+        };
+        *self.state.write().await = ClientState::Ready;
+        *self.server_info.write().await = Some(server.clone());
+        self.record_session_identity();
+
+        let tools = if server.capabilities.standard.tools.is_some() {
+            match self.list_all_tools().await {
+                Ok(tools) => Some(tools),
+                Err(e) => {
+                    warn!("Could not refresh tools after restart: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        self.stats.write().await.restarts += 1;
+        let event = ServerRestarted {
+            reason,
+            previous,
+            server,
+            tools,
+        };
+        info!("{}", event);
+        let _ = self.restarts.send(event);
+        Ok(())
+    }
+
+    /// List every tool, following `nextCursor` until the last page.
+    async fn list_all_tools(&mut self) -> McpResult<Vec<serde_json::Value>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match cursor {
+                Some(ref cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let response = self
+                .request_with_timeout("tools/list", params, None, None)
+                .await?;
+            let mut result = response.into_result()?;
+            if let Some(serde_json::Value::Array(page)) =
+                result.get_mut("tools").map(serde_json::Value::take)
+            {
+                tools.extend(page);
+            }
+            match result.get("nextCursor").and_then(serde_json::Value::as_str) {
+                Some(next) if cursor.as_deref() != Some(next) => cursor = Some(next.to_string()),
+                _ => return Ok(tools),
+            }
+        }
+    }
+
     fn set_error_state(&self, error: String) {
         if let Ok(mut state) = self.state.try_write() {
             *state = ClientState::Error(error);
@@ -419,15 +1116,17 @@ impl McpClient {
     async fn start_message_processing(&mut self) -> McpResult<()> {
         tracing::info!("Starting message processing task");
         let (sender, mut receiver) = mpsc::unbounded_channel();
-        self._message_sender = Some(sender);
+        self._message_sender = Some(sender.clone());
 
         // Clone necessary data for the task
-        let pending_requests = Arc::clone(&self.pending_requests);
+        let correlator = self.correlator.clone();
         let stats = Arc::clone(&self.stats);
-        let notification_handler = Arc::clone(&self.notification_handler);
+        let notifications = Arc::clone(&self.notifications);
+        let sampling = self.sampling.clone();
+        let responder = Arc::clone(&self.transport);
 
         // Start message processing task
-        tokio::spawn(async move {
+        rt::spawn(async move {
             tracing::debug!("Message processing task started, waiting for messages");
             while let Some(message) = receiver.recv().await {
                 tracing::debug!("Received message in processing task: {:?}", message);
@@ -435,93 +1134,56 @@ impl McpClient {
                     JsonRpcMessage::Response(response) => {
                         tracing::debug!("Processing response with ID: {}", response.id);
                         // Handle response correlation
-                        if let Some(sender) = pending_requests
-                            .write()
-                            .await
-                            .remove(&response.id.to_string())
-                        {
-                            tracing::debug!(
-                                "Found pending request for ID {}, sending response",
-                                response.id
-                            );
-                            let _ = sender.send(response);
-                            stats.write().await.responses_received += 1;
-                        } else {
+                        if let Some(response) = correlator.complete(response) {
                             tracing::warn!(
                                 "Received response for unknown request ID: {}",
                                 response.id
                             );
+                        } else {
+                            stats.write().await.responses_received += 1;
                         }
                     }
                     JsonRpcMessage::Notification(notification) => {
                         tracing::debug!("Processing notification: {}", notification.method);
                         // Handle server notifications
-                        Self::handle_notification(&*notification_handler, notification).await;
+                        notifications.dispatch(notification).await;
                         stats.write().await.notifications_received += 1;
                     }
-                    JsonRpcMessage::Request(_) => {
-                        // Server-to-client requests are rare in MCP but possible
-                        tracing::warn!("Received unexpected server-to-client request");
-                    }
+                    JsonRpcMessage::Request(request) => match &sampling {
+                        Some(handler) if request.method == "sampling/createMessage" => {
+                            // Completions can take minutes; keep reading meanwhile
+                            let handler = Arc::clone(handler);
+                            let responder = Arc::clone(&responder);
+                            rt::spawn(async move {
+                                let response = answer_sampling(handler.as_ref(), request).await;
+                                if let Err(e) = responder.send_response(response).await {
+                                    tracing::warn!("Failed to answer sampling request: {}", e);
+                                }
+                            });
+                        }
+                        _ => {
+                            // Server-to-client requests are rare in MCP but possible
+                            tracing::warn!("Received unexpected server-to-client request: {}", request.method);
+                        }
+                    },
                 }
             }
         });
 
-        Ok(())
-    }
-
-    async fn handle_notification(
-        handler: &dyn NotificationHandler,
-        notification: JsonRpcNotification,
-    ) {
-        match notification.method.as_str() {
-            "notifications/progress" => {
-                if let Some(params) = notification.params {
-                    if let Ok(progress) = serde_json::from_value::<ProgressNotification>(params) {
-                        let _ = handler.handle_progress(progress).await;
-                    }
-                }
-            }
-            "notifications/resources/updated" => {
-                if let Some(params) = notification.params {
-                    if let Ok(resource_updated) =
-                        serde_json::from_value::<ResourceUpdatedNotification>(params)
-                    {
-                        let _ = handler.handle_resource_updated(resource_updated).await;
-                    }
-                }
-            }
-            "notifications/resources/list_changed" => {
-                if let Some(params) = notification.params {
-                    if let Ok(list_changed) =
-                        serde_json::from_value::<ResourceListChangedNotification>(params)
-                    {
-                        let _ = handler.handle_resource_list_changed(list_changed).await;
-                    }
-                }
-            }
-            "notifications/tools/list_changed" => {
-                if let Some(params) = notification.params {
-                    if let Ok(list_changed) =
-                        serde_json::from_value::<ToolListChangedNotification>(params)
-                    {
-                        let _ = handler.handle_tool_list_changed(list_changed).await;
-                    }
-                }
-            }
-            "notifications/prompts/list_changed" => {
-                if let Some(params) = notification.params {
-                    if let Ok(list_changed) =
-                        serde_json::from_value::<PromptListChangedNotification>(params)
-                    {
-                        let _ = handler.handle_prompt_list_changed(list_changed).await;
-                    }
+        // Feed it the notifications and requests the transport queues
+        if let Some(pump) = self.message_pump.take() {
+            pump.abort();
+        }
+        let transport = Arc::clone(&self.transport);
+        self.message_pump = Some(rt::spawn(async move {
+            while let Ok(message) = transport.receive_message(None).await {
+                if sender.send(message).is_err() {
+                    break;
                 }
             }
-            _ => {
-                warn!("Unknown notification method: {}", notification.method);
-            }
-        }
+        }));
+
+        Ok(())
     }
 
     async fn perform_initialization(
@@ -544,30 +1206,51 @@ impl McpClient {
                 prompts: Some(crate::messages::PromptCapabilities {
                     list_changed: Some(true),
                 }),
+                sampling: self.sampling.as_ref().map(|_| SamplingCapabilities::default()),
                 ..Default::default()
             },
             ..Default::default()
         };
 
+        // Quirks decided before connecting shape the handshake itself
+        let quirks = self.lookup_quirks();
+        if !quirks.is_empty() {
+            info!("Applying server quirks: {}", quirks.rules.join("; "));
+        }
+        let protocol_version = quirks
+            .quirks
+            .protocol_version
+            .clone()
+            .unwrap_or_else(|| self.config.protocol_version.clone());
+        let init_timeout = quirks
+            .quirks
+            .init_timeout
+            .map_or(self.config.init_timeout, |t| t.max(self.config.init_timeout));
+        self.set_quirks(quirks.clone());
+
         let request = InitializeRequest {
-            protocol_version: ProtocolVersion::default(),
+            protocol_version,
             capabilities,
             client_info,
+            meta: None,
         };
 
         tracing::debug!("Sending initialize request: {:?}", request);
 
         // Send initialize request bypassing ready check (we're initializing!)
         let response = self
-            .send_initialization_request("initialize", request, Some(self.config.init_timeout))
+            .send_initialization_request("initialize", request, Some(init_timeout))
             .await?;
 
         // Parse initialize response
         tracing::debug!("Received initialize response: {:?}", response);
+        if let Some(error) = response.error {
+            return Err(error.into());
+        }
         let init_response: InitializeResponse = match response.result {
             Some(result) => {
                 tracing::debug!("Parsing initialize response result: {:?}", result);
-                serde_json::from_value(result)?
+                crate::decode::from_value(result)?
             }
             None => {
                 tracing::error!("Initialize response missing result field");
@@ -581,6 +1264,27 @@ impl McpClient {
             "Successfully parsed initialize response from server: {}",
             init_response.server_info.name
         );
+        self.violations
+            .report_unknown_fields(&init_response.server_info.name, &init_response);
+
+        // Rules keyed by server name can only be matched now
+        *self.known_server.lock().unwrap_or_else(|e| e.into_inner()) = Some((
+            init_response.server_info.name.clone(),
+            init_response.server_info.version.clone(),
+        ));
+        let learned = self.lookup_quirks();
+        if learned != quirks {
+            let handshake_changed = learned.quirks.protocol_version
+                != quirks.quirks.protocol_version
+                || learned.quirks.init_timeout != quirks.quirks.init_timeout;
+            if handshake_changed {
+                info!(
+                    "Server-specific quirks for {} change the handshake; they apply from the next connection",
+                    init_response.server_info.name
+                );
+            }
+            self.set_quirks(learned);
+        }
 
         // Send initialized notification
         let initialized = InitializedNotification {
@@ -620,10 +1324,11 @@ impl McpClient {
             params: Some(serde_json::to_value(params)?),
         };
 
-        let timeout_val = timeout_duration.unwrap_or(self.config.request_timeout);
+        let timeout_val = timeout_duration.unwrap_or_else(|| self.config.timeout_for(method));
 
         // Send request with retries (bypassing ready check)
-        self.send_request_with_retries(request, timeout_val).await
+        self.send_request_with_retries(request, timeout_val, None)
+            .await
     }
 
     /// Send initialization notification without ready state check
@@ -645,11 +1350,12 @@ impl McpClient {
         Ok(())
     }
 
-    async fn send_request_with_timeout<T>(
+    async fn request_with_timeout<T>(
         &mut self,
         method: &str,
         params: T,
         timeout_duration: Option<Duration>,
+        retry: Option<&RetryPolicy>,
     ) -> McpResult<JsonRpcResponse>
     where
         T: serde::Serialize,
@@ -665,19 +1371,33 @@ impl McpClient {
         let timeout_val = timeout_duration.unwrap_or(self.config.request_timeout);
 
         // Send request with retries
-        self.send_request_with_retries(request, timeout_val).await
+        self.send_request_with_retries(request, timeout_val, retry)
+            .await
     }
 
     async fn send_request_with_retries(
         &mut self,
         request: JsonRpcRequest,
         timeout_duration: Duration,
+        retry: Option<&RetryPolicy>,
     ) -> McpResult<JsonRpcResponse> {
+        let max_retries = retry.unwrap_or(&self.config.retry).max_retries;
         let mut last_error = None;
+        let mut request = Some(request);
+
+        for attempt in 0..=max_retries {
+            // Only copy the request while another attempt may still need it
+            let attempt_request = if attempt < max_retries {
+                request.clone()
+            } else {
+                request.take()
+            };
+            let Some(attempt_request) = attempt_request else {
+                break;
+            };
 
-        for attempt in 0..=self.config.max_retries {
             match self
-                .send_single_request(request.clone(), timeout_duration)
+                .send_single_request(attempt_request, timeout_duration)
                 .await
             {
                 Ok(response) => {
@@ -687,18 +1407,28 @@ impl McpClient {
                     return Ok(response);
                 }
                 Err(e) => {
+                    // The policy decides, knowing the method and what failed
+                    let delay = request.as_ref().and_then(|request| {
+                        retry.unwrap_or(&self.config.retry).retry_delay(
+                            &request.method,
+                            &e,
+                            attempt,
+                        )
+                    });
+                    let kind = FailureKind::of(&e);
                     last_error = Some(e);
 
-                    if attempt < self.config.max_retries {
-                        let delay = self.config.retry_base_delay * 2_u32.pow(attempt);
-                        debug!(
-                            "Request failed, retrying in {:?} (attempt {} of {})",
-                            delay,
-                            attempt + 1,
-                            self.config.max_retries + 1
-                        );
-                        sleep(delay).await;
-                    }
+                    let Some(delay) = delay else {
+                        break;
+                    };
+                    debug!(
+                        "Request failed ({:?}), retrying in {:?} (attempt {} of {})",
+                        kind,
+                        delay,
+                        attempt + 1,
+                        max_retries + 1
+                    );
+                    sleep(delay).await;
                 }
             }
         }
@@ -715,20 +1445,27 @@ impl McpClient {
         let request_id = request.id.to_string();
         tracing::debug!("Sending single request with ID: {}", request_id);
 
-        // Process outgoing request through interceptors
-        let interception_result = self.interceptor_manager
-            .process_message(JsonRpcMessage::Request(request.clone()), MessageDirection::Outgoing)
-            .await?;
+        // Without interceptors there is nothing to run, so skip copying the message
+        let intercepting = !self.interceptor_manager.is_empty().await;
 
-        if interception_result.block {
-            return Err(McpError::Protocol(ProtocolError::RequestBlocked {
-                reason: interception_result.reasoning.unwrap_or_else(|| "Request blocked by interceptor".to_string()),
-            }));
-        }
+        // Process outgoing request through interceptors
+        let final_request = if intercepting {
+            let interception_result = self.interceptor_manager
+                .process_message(JsonRpcMessage::Request(request.clone()), MessageDirection::Outgoing)
+                .await?;
+
+            if interception_result.block {
+                return Err(McpError::Protocol(ProtocolError::RequestBlocked {
+                    reason: interception_result.reasoning.unwrap_or_else(|| "Request blocked by interceptor".to_string()),
+                }));
+            }
 
-        let final_request = match interception_result.message {
-            JsonRpcMessage::Request(req) => req,
-            _ => request, // Fallback to original if interceptor returned wrong type
+            match interception_result.message {
+                JsonRpcMessage::Request(req) => req,
+                _ => request, // Fallback to original if interceptor returned wrong type
+            }
+        } else {
+            request
         };
 
         // Send request and get response from transport (handles SSE internally)
@@ -740,6 +1477,10 @@ impl McpClient {
 
         tracing::debug!("Received response for request ID: {}", response.id);
 
+        if !intercepting {
+            return Ok(response);
+        }
+
         // Process incoming response through interceptors
         let response_interception = self.interceptor_manager
             .process_message(JsonRpcMessage::Response(response.clone()), MessageDirection::Incoming)
@@ -765,6 +1506,8 @@ pub struct McpClientBuilder {
     transport_config: Option<TransportConfig>,
     client_config: ClientConfig,
     notification_handler: Option<Box<dyn NotificationHandler>>,
+    notifications: NotificationRegistry,
+    sampling: Option<Arc<dyn SamplingHandler>>,
 }
 
 impl McpClientBuilder {
@@ -774,6 +1517,8 @@ impl McpClientBuilder {
             transport_config: None,
             client_config: ClientConfig::default(),
             notification_handler: None,
+            notifications: NotificationRegistry::new(),
+            sampling: None,
         }
     }
 
@@ -795,21 +1540,105 @@ impl McpClientBuilder {
         self
     }
 
-    /// Set request timeout.
+    /// Answer the server's `sampling/createMessage` requests with `handler`.
+    pub fn sampling_handler(mut self, handler: Arc<dyn SamplingHandler>) -> Self {
+        self.sampling = Some(handler);
+        self
+    }
+
+    /// Handle notifications of `method` with params decoded into `T`.
+    ///
+    /// Runs after the notification handler, and after earlier handlers for
+    /// the same method.
+    pub fn on_notification<T, F, Fut>(self, method: impl Into<String>, handler: F) -> Self
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = McpResult<()>> + Send + 'static,
+    {
+        self.notifications.on(method, handler);
+        self
+    }
+
+    /// Handle notifications of methods nothing else is registered for.
+    pub fn notification_fallback<F, Fut>(self, handler: F) -> Self
+    where
+        F: Fn(JsonRpcNotification) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = McpResult<()>> + Send + 'static,
+    {
+        self.notifications.fallback(handler);
+        self
+    }
+
+    /// Set the fallback request timeout for methods without a class timeout.
     pub fn request_timeout(mut self, timeout: Duration) -> Self {
         self.client_config.request_timeout = timeout;
         self
     }
 
+    /// Set the timeout for one method class.
+    pub fn method_timeout(mut self, class: MethodClass, timeout: Duration) -> Self {
+        self.client_config.method_timeouts.set(class, Some(timeout));
+        self
+    }
+
     /// Set initialization timeout.
     pub fn init_timeout(mut self, timeout: Duration) -> Self {
         self.client_config.init_timeout = timeout;
         self
     }
 
+    /// Set the overall deadline for connecting and initializing.
+    pub fn connect_deadline(mut self, deadline: Duration) -> Self {
+        self.client_config.connect_deadline = Some(deadline);
+        self
+    }
+
+    /// Set the protocol version requested during initialization.
+    pub fn protocol_version(mut self, version: ProtocolVersion) -> Self {
+        self.client_config.protocol_version = version;
+        self
+    }
+
+    /// Set the quirks to apply to matching servers.
+    pub fn quirks(mut self, quirks: QuirkDatabase) -> Self {
+        self.client_config.quirks = quirks;
+        self
+    }
+
+    /// Name the registry profile the transport came from, for matching quirks.
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.client_config.profile = Some(name.into());
+        self
+    }
+
     /// Set maximum retry attempts.
     pub fn max_retries(mut self, retries: u32) -> Self {
-        self.client_config.max_retries = retries;
+        self.client_config.retry.max_retries = retries;
+        self
+    }
+
+    /// Set the rules for retrying failed requests.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client_config.retry = policy;
+        self
+    }
+
+    /// Put a circuit breaker in front of the server.
+    pub fn circuit_breaker(mut self, config: BreakerConfig) -> Self {
+        self.client_config.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Set whether to reconnect and re-initialize when the server restarts.
+    pub fn renegotiate_on_restart(mut self, enabled: bool) -> Self {
+        self.client_config.renegotiate_on_restart = enabled;
+        self
+    }
+
+    /// Probe the server and hold back readiness until a first round trip succeeds.
+    pub fn health_check(mut self, config: HealthConfig) -> Self {
+        self.client_config.health_check = Some(config);
         self
     }
 
@@ -825,7 +1654,11 @@ impl McpClientBuilder {
             .notification_handler
             .unwrap_or_else(|| Box::new(DefaultNotificationHandler));
 
-        McpClient::new(transport_config, self.client_config, notification_handler).await
+        let mut client =
+            McpClient::new(transport_config, self.client_config, notification_handler).await?;
+        client.notifications.extend(self.notifications);
+        client.sampling = self.sampling;
+        Ok(client)
     }
 }
 
@@ -866,6 +1699,471 @@ mod tests {
         let config = ClientConfig::default();
         assert_eq!(config.request_timeout, Duration::from_secs(30));
         assert_eq!(config.init_timeout, Duration::from_secs(10));
-        assert_eq!(config.max_retries, 3);
+        assert_eq!(config.retry.max_retries, 3);
+        assert!(config.connect_deadline.is_none());
+    }
+
+    #[test]
+    fn test_method_class_timeouts() {
+        let mut config = ClientConfig::default();
+        assert_eq!(config.timeout_for("tools/list"), Duration::from_secs(10));
+        assert_eq!(config.timeout_for("resources/templates/list"), Duration::from_secs(10));
+        assert_eq!(config.timeout_for("tools/call"), Duration::from_secs(120));
+        assert_eq!(config.timeout_for("ping"), config.request_timeout);
+
+        config.method_timeouts.set(MethodClass::Call, None);
+        config.request_timeout = Duration::from_secs(5);
+        assert_eq!(config.timeout_for("tools/call"), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_connect_deadline_reports_phase() {
+        // `cat` never answers the initialize request, so the budget runs out
+        let config = TransportConfig::stdio("cat", &[] as &[String]);
+        let mut client = McpClientBuilder::new()
+            .transport(config)
+            .connect_deadline(Duration::from_millis(200))
+            .build()
+            .await
+            .unwrap();
+
+        let client_info = Implementation {
+            name: "test".to_string(),
+            version: "0.0.0".to_string(),
+            metadata: HashMap::new(),
+        };
+
+        let started = std::time::Instant::now();
+        let err = client.connect(client_info).await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        match err {
+            McpError::Timeout { operation, .. } => {
+                assert!(operation.contains("initialization"), "{operation}");
+            }
+            other => panic!("expected timeout, got {other:?}"),
+        }
+        assert!(matches!(client.state().await, ClientState::Error(_)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_quirks_extend_init_timeout_and_are_reported() {
+        let quirks = QuirkDatabase::new(vec![crate::quirks::QuirkRule {
+            profile: None,
+            target: Some("cat".to_string()),
+            server: None,
+            version: None,
+            note: Some("slow to initialize".to_string()),
+            quirks: crate::quirks::Quirks {
+                init_timeout: Some(Duration::from_millis(400)),
+                ..Default::default()
+            },
+        }]);
+        let mut client = McpClientBuilder::new()
+            .transport(TransportConfig::stdio("cat", &[] as &[String]))
+            .init_timeout(Duration::from_millis(50))
+            .quirks(quirks)
+            .build()
+            .await
+            .unwrap();
+
+        let client_info = Implementation {
+            name: "test".to_string(),
+            version: "0.0.0".to_string(),
+            metadata: HashMap::new(),
+        };
+        let started = Instant::now();
+        assert!(client.connect(client_info).await.is_err());
+        assert!(started.elapsed() >= Duration::from_millis(400));
+
+        let info = client.transport_info();
+        assert_eq!(info.metadata["quirks"]["rules"][0], "slow to initialize");
+        assert_eq!(info.metadata["quirks"]["init_timeout"], "400ms");
+    }
+
+    /// A stdio server that exits after answering `test/exit`, reporting its
+    /// PID as its version so each incarnation is distinguishable
+    const FLAKY_SERVER: &str = r#"while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":"\([^"]*\)".*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"flaky","version":"%s"}}}\n' "$id" "$$" ;;
+    *'"method":"tools/list"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{"tools":[{"name":"echo"}]}}\n' "$id" ;;
+    *'"method":"test/exit"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{}}\n' "$id"; exit 0 ;;
+    *'"id"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{}}\n' "$id" ;;
+  esac
+done"#;
+
+    async fn crash_flaky_server(renegotiate: bool) -> (McpClient, ServerInfo) {
+        let mut client = McpClientBuilder::new()
+            .transport(TransportConfig::stdio("sh", &["-c", FLAKY_SERVER]))
+            .max_retries(0)
+            .renegotiate_on_restart(renegotiate)
+            .build()
+            .await
+            .unwrap();
+        let first = client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+
+        client
+            .send_request("test/exit", serde_json::json!({}))
+            .await
+            .unwrap();
+        let transport = client.transport();
+        for _ in 0..200 {
+            if !transport.is_connected() {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!transport.is_connected());
+        (client, first)
+    }
+
+    #[tokio::test]
+    async fn test_restarted_server_is_initialized_again() {
+        let (mut client, first) = crash_flaky_server(true).await;
+        let mut restarts = client.server_restarts();
+
+        let response = client
+            .send_request("ping", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(response.error.is_none());
+
+        let event = restarts.try_recv().unwrap();
+        assert_eq!(event.reason, RestartReason::ProcessExited);
+        assert!(event.version_changed());
+        assert_eq!(
+            event.previous.unwrap().implementation.version,
+            first.implementation.version
+        );
+        assert_eq!(event.tools.unwrap()[0]["name"], "echo");
+        assert_eq!(client.stats().await.restarts, 1);
+        assert!(client.is_ready().await);
+    }
+
+    #[tokio::test]
+    async fn test_restart_renegotiation_can_be_disabled() {
+        let (mut client, _) = crash_flaky_server(false).await;
+        let mut restarts = client.server_restarts();
+
+        let err = client
+            .send_request("ping", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, McpError::Transport(TransportError::NotConnected { .. })),
+            "{err:?}"
+        );
+        assert!(restarts.try_recv().is_err());
+    }
+
+    /// A stdio server that never answers `tools/call` but counts the calls
+    const SILENT_TOOL_SERVER: &str = r#"calls=0
+while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":"\([^"]*\)".*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"silent","version":"1"}}}\n' "$id" ;;
+    *'"method":"tools/call"'*) calls=$((calls + 1)) ;;
+    *'"method":"test/calls"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{"calls":%s}}\n' "$id" "$calls" ;;
+  esac
+done"#;
+
+    #[tokio::test]
+    async fn test_tool_calls_are_not_retried_after_timeouts() {
+        let policy = RetryPolicy::default().base_delay(Duration::from_millis(10));
+        let mut client = McpClientBuilder::new()
+            .transport(TransportConfig::stdio("sh", &["-c", SILENT_TOOL_SERVER]))
+            .request_timeout(Duration::from_millis(100))
+            .retry_policy(policy.clone())
+            .build()
+            .await
+            .unwrap();
+        client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+        let call = serde_json::json!({ "name": "transfer", "arguments": {} });
+
+        // The server may have run the tool, so it is not called again
+        let err = client.send_request("tools/call", &call).await.unwrap_err();
+        assert_eq!(FailureKind::of(&err), Some(FailureKind::Timeout), "{err:?}");
+        let calls = client
+            .send_request("test/calls", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(calls.result.unwrap()["calls"], 1);
+
+        // Unless this call says the tool is safe to repeat
+        let repeatable = policy.method("tools/call", crate::retry::Idempotency::Idempotent);
+        assert!(client
+            .send_request_with_retry("tools/call", &call, &repeatable)
+            .await
+            .is_err());
+        let calls = client
+            .send_request("test/calls", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(calls.result.unwrap()["calls"], 5);
+    }
+
+    /// A stdio server that logs and sends an experimental notification
+    /// before answering `test/notify`
+    const CHATTY_SERVER: &str = r#"while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":"\([^"]*\)".*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{"protocolVersion":"2025-03-26","capabilities":{"logging":{}},"serverInfo":{"name":"chatty","version":"1"}}}\n' "$id" ;;
+    *'"method":"test/notify"'*)
+      printf '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info","data":"indexing"}}\n'
+      printf '{"jsonrpc":"2.0","method":"experimental/tick"}\n'
+      printf '{"jsonrpc":"2.0","id":"%s","result":{}}\n' "$id" ;;
+    *'"id"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{}}\n' "$id" ;;
+  esac
+done"#;
+
+    #[tokio::test]
+    async fn test_notifications_reach_registered_handlers() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let logs = tx.clone();
+        let mut client = McpClientBuilder::new()
+            .transport(TransportConfig::stdio("sh", &["-c", CHATTY_SERVER]))
+            .on_notification(
+                "notifications/message",
+                move |log: crate::messages::LoggingNotification| {
+                    let logs = logs.clone();
+                    async move {
+                        let _ = logs.send(format!("log: {}", log.data));
+                        Ok(())
+                    }
+                },
+            )
+            .notification_fallback(move |notification| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send(format!("other: {}", notification.method));
+                    Ok(())
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+
+        client
+            .send_request("test/notify", serde_json::json!({}))
+            .await
+            .unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            vec![rx.recv().await.unwrap(), rx.recv().await.unwrap()]
+        })
+        .await
+        .unwrap();
+        assert_eq!(received, ["log: \"indexing\"", "other: experimental/tick"]);
+    }
+
+    #[tokio::test]
+    async fn test_readiness_waits_for_a_first_round_trip() {
+        let mut client = McpClientBuilder::new()
+            .transport(TransportConfig::stdio("sh", &["-c", CHATTY_SERVER]))
+            .health_check(crate::health::HealthConfig::default())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(
+            client.health_status().unwrap().status,
+            crate::health::HealthStatus::Unknown
+        );
+        client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+        assert!(client.is_ready().await);
+        assert!(client.health_status().unwrap().is_healthy());
+
+        // A server that offers tools but cannot list them never becomes ready
+        let broken = r#"while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":"\([^"]*\)".*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*) printf '{"jsonrpc":"2.0","id":"%s","result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"broken","version":"1"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*) printf '{"jsonrpc":"2.0","id":"%s","error":{"code":-32603,"message":"index not loaded"}}\n' "$id" ;;
+  esac
+done"#;
+        let mut client = McpClientBuilder::new()
+            .transport(TransportConfig::stdio("sh", &["-c", broken]))
+            .health_check(crate::health::HealthConfig::default())
+            .max_retries(0)
+            .build()
+            .await
+            .unwrap();
+        let error = client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("index not loaded"), "{}", error);
+        assert!(!client.is_ready().await);
+    }
+
+    #[tokio::test]
+    async fn test_server_logs_are_streamed() {
+        let mut client = McpClientBuilder::new()
+            .transport(TransportConfig::stdio("sh", &["-c", CHATTY_SERVER]))
+            .build()
+            .await
+            .unwrap();
+        client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+        let mut logs = client.server_logs();
+
+        client.set_level(LogLevel::Info).await.unwrap();
+        client
+            .send_request("test/notify", serde_json::json!({}))
+            .await
+            .unwrap();
+        let entry = tokio::time::timeout(Duration::from_secs(5), logs.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.level, LogLevel::Info);
+        assert_eq!(entry.message, "indexing");
+    }
+
+    struct Echo;
+
+    #[async_trait]
+    impl SamplingHandler for Echo {
+        async fn create_message(&self, request: CompletionArgument) -> McpResult<CompleteResponse> {
+            let last = match request.messages.last().map(|message| &message.content) {
+                Some(crate::messages::SamplingContent::Text { text }) => text.clone(),
+                _ => String::new(),
+            };
+            Ok(CompleteResponse {
+                completion: crate::messages::CompletionResult::text(format!("echo: {last}")),
+                model: Some("echo".to_string()),
+                stop_reason: Some(crate::messages::StopReason::EndTurn),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_requests_are_answered_by_the_handler() {
+        let (client_end, server) = crate::transport::InMemoryTransport::pair();
+        let mut client = McpClient::with_transport(
+            Arc::new(client_end),
+            ClientConfig::default(),
+            Box::new(DefaultNotificationHandler),
+        );
+        client.set_sampling_handler(Arc::new(Echo));
+
+        let server = tokio::spawn(async move {
+            server.connect().await.unwrap();
+            let JsonRpcMessage::Request(init) = server.receive_message(None).await.unwrap() else {
+                panic!("expected initialize");
+            };
+            let advertised = init.params.as_ref().unwrap()["capabilities"].get("sampling").is_some();
+            let result = serde_json::json!({
+                "protocolVersion": ProtocolVersion::default().as_str(),
+                "capabilities": {},
+                "serverInfo": {"name": "sampler", "version": "1"}
+            });
+            server.send_response(JsonRpcResponse::success(init.id, result)).await.unwrap();
+
+            let request = JsonRpcRequest::new(
+                "s1",
+                "sampling/createMessage",
+                serde_json::json!({
+                    "messages": [{"role": "user", "content": {"type": "text", "text": "hi"}}],
+                    "maxTokens": 16
+                }),
+            );
+            server.send_message(JsonRpcMessage::Request(request)).unwrap();
+            let bad = JsonRpcRequest::new("s2", "sampling/createMessage", serde_json::json!({}));
+            server.send_message(JsonRpcMessage::Request(bad)).unwrap();
+
+            let mut responses = Vec::new();
+            while responses.len() < 2 {
+                if let JsonRpcMessage::Response(response) = server.receive_message(None).await.unwrap() {
+                    responses.push(response);
+                }
+            }
+            responses.sort_by_key(|response| response.id.to_string());
+            // Keep the server end open until the client is done connecting
+            (advertised, responses, server)
+        });
+
+        let client_info = Implementation {
+            name: "test".to_string(),
+            version: "0.0.0".to_string(),
+            metadata: HashMap::new(),
+        };
+        client.connect(client_info).await.unwrap();
+        let (advertised, responses, _server) = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(advertised);
+        assert_eq!(responses[0].result.as_ref().unwrap()["completion"]["text"], "echo: hi");
+        assert_eq!(responses[1].error.as_ref().unwrap().code, -32602);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_back_off_in_virtual_time() {
+        let (client_end, server) = crate::transport::InMemoryTransport::pair();
+        let mut client = McpClient::with_transport(
+            Arc::new(client_end),
+            ClientConfig::default(),
+            Box::new(DefaultNotificationHandler),
+        );
+
+        // Answers initialize at once but only the third tools/list
+        tokio::spawn(async move {
+            server.connect().await.unwrap();
+            let mut listings = 0;
+            while let Ok(message) = server.receive_message(None).await {
+                let JsonRpcMessage::Request(request) = message else {
+                    continue;
+                };
+                let result = match request.method.as_str() {
+                    "initialize" => serde_json::json!({
+                        "protocolVersion": ProtocolVersion::default().as_str(),
+                        "capabilities": {},
+                        "serverInfo": {"name": "slow", "version": "1"}
+                    }),
+                    "tools/list" => {
+                        listings += 1;
+                        if listings < 3 {
+                            continue;
+                        }
+                        serde_json::json!({ "tools": [] })
+                    }
+                    _ => continue,
+                };
+                let response = JsonRpcResponse::success(request.id, result);
+                server.send_message(JsonRpcMessage::Response(response)).unwrap();
+            }
+        });
+        client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+
+        // Two 30 s timeouts, with 1 s and then 2 s of backoff after them
+        let started = Instant::now();
+        let response = client
+            .send_request("tools/list", serde_json::json!({}))
+            .await
+            .unwrap();
+        let elapsed = started.elapsed();
+        assert!(response.error.is_none());
+        assert!(elapsed >= Duration::from_secs(63), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(64), "{elapsed:?}");
+        assert_eq!(client.stats().await.retries, 2);
     }
 }
@@ -0,0 +1,941 @@
+//! Compliance probing of MCP servers.
+//!
+//! [`probe`] connects to a server and exercises what clients depend on:
+//! initialization, `ping`, capability discovery, the tool, resource and
+//! prompt catalogs (following pagination), tool input schemas, reading
+//! resources, getting prompts, and the errors returned for unknown methods,
+//! tools and resources. Every check yields a [`Finding`]. A
+//! [`Severity::Violation`] breaks the specification; a [`Severity::Warning`]
+//! is allowed but likely to trip up clients.
+//!
+//! Tools are validated but not called unless [`ProbeOptions::call_tools`] is
+//! set, and even then only read-only tools that take no required arguments.
+//!
+//! With [`ProbeOptions::strict`], every frame the server sends is also
+//! checked against JSON-RPC 2.0, and each problem becomes a finding under
+//! `framing`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_core::compliance::{self, ProbeOptions};
+//! use mcp_core::transport::TransportConfig;
+//!
+//! # async fn example() -> mcp_core::McpResult<()> {
+//! let config = TransportConfig::stdio("python", &["server.py"]);
+//! let report = compliance::probe(config, ProbeOptions::default()).await?;
+//!
+//! for finding in report.violations() {
+//!     println!("{}", finding);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use crate::client::{McpClient, McpClientBuilder};
+use crate::error::McpResult;
+use crate::messages::{Implementation, JsonRpcError, ProtocolVersion};
+use crate::transport::framing::{DiagnosticSeverity, ProtocolDiagnostic};
+use crate::transport::{ParseMode, TransportConfig};
+
+/// JSON-RPC code for an unknown method
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// Method no server implements, used to check error handling
+const UNKNOWN_METHOD: &str = "assist-mcp/probe-unknown-method";
+
+/// Tool name no server offers, used to check error handling
+const UNKNOWN_TOOL: &str = "assist-mcp-probe-unknown-tool";
+
+/// Resource URI no server offers, used to check error handling
+const UNKNOWN_RESOURCE: &str = "assist-mcp-probe://unknown-resource";
+
+/// Catalogs probed for each capability, as `(capability, list method, item key)`
+const CATALOGS: &[(&str, &str, &str)] = &[
+    ("tools", "tools/list", "tools"),
+    ("resources", "resources/list", "resources"),
+    ("prompts", "prompts/list", "prompts"),
+];
+
+/// JSON Schema keywords that give a property a shape
+const SCHEMA_SHAPES: &[&str] = &["type", "$ref", "anyOf", "oneOf", "allOf", "enum", "const"];
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The check passed
+    Pass,
+    /// Allowed by the specification, but clients may struggle with it
+    Warning,
+    /// The server does not follow the specification
+    Violation,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Pass => "pass",
+            Severity::Warning => "warning",
+            Severity::Violation => "violation",
+        })
+    }
+}
+
+/// The result of one check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    /// What was checked, e.g. `initialize`, `tools/list` or `tool search`
+    pub area: String,
+    /// How serious the finding is
+    pub severity: Severity,
+    /// What was observed
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.area, self.message)
+    }
+}
+
+/// Options for a compliance probe.
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    /// Client identity sent during initialization
+    pub client_info: Implementation,
+    /// Protocol version requested in `initialize`
+    pub protocol_version: ProtocolVersion,
+    /// Deadline for connecting and for each request
+    pub timeout: Duration,
+    /// Pages followed per catalog before giving up
+    pub max_pages: usize,
+    /// Resources read and prompts fetched per catalog
+    pub max_samples: usize,
+    /// Call read-only tools that need no arguments
+    pub call_tools: bool,
+    /// Parse the server's frames strictly, reporting JSON-RPC 2.0 breaches
+    pub strict: bool,
+}
+
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        Self {
+            client_info: Implementation::new("mcp-compliance", crate::VERSION),
+            protocol_version: ProtocolVersion::default(),
+            timeout: Duration::from_secs(30),
+            max_pages: 50,
+            max_samples: 5,
+            call_tools: false,
+            strict: false,
+        }
+    }
+}
+
+/// Everything learned while probing a server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    /// Server implementation details
+    pub server: Implementation,
+    /// Version the server answered with
+    pub protocol_version: ProtocolVersion,
+    /// Advertised server capabilities
+    pub capabilities: Value,
+    /// Number of items in each catalog that could be listed, keyed by list method
+    pub catalogs: BTreeMap<String, usize>,
+    /// Result of every check, in the order they ran
+    pub findings: Vec<Finding>,
+}
+
+impl ComplianceReport {
+    /// Findings that break the specification.
+    pub fn violations(&self) -> impl Iterator<Item = &Finding> {
+        self.with_severity(Severity::Violation)
+    }
+
+    /// Findings that are allowed but questionable.
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.with_severity(Severity::Warning)
+    }
+
+    /// Number of findings with the given severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.with_severity(severity).count()
+    }
+
+    /// Whether no check found a violation.
+    pub fn is_compliant(&self) -> bool {
+        self.violations().next().is_none()
+    }
+
+    fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.severity == severity)
+    }
+}
+
+/// Connect to a server and probe it.
+pub async fn probe(
+    transport: TransportConfig,
+    options: ProbeOptions,
+) -> McpResult<ComplianceReport> {
+    let transport = if options.strict {
+        transport.with_parse_mode(ParseMode::Strict)
+    } else {
+        transport
+    };
+    let client = McpClientBuilder::new()
+        .transport(transport)
+        .protocol_version(options.protocol_version.clone())
+        .connect_deadline(options.timeout)
+        .max_retries(0)
+        .build()
+        .await?;
+    probe_client(client, &options).await
+}
+
+/// Probe a server through a client that has not connected yet.
+///
+/// Fails only when initialization fails; everything after it is reported
+/// as findings. Frame diagnostics are reported whenever the client's
+/// transport publishes them; [`ProbeOptions::strict`] only takes effect
+/// through [`probe`].
+pub async fn probe_client(
+    mut client: McpClient,
+    options: &ProbeOptions,
+) -> McpResult<ComplianceReport> {
+    let mut diagnostics = client.transport().protocol_diagnostics();
+    let info = client.connect(options.client_info.clone()).await?;
+    let capabilities = serde_json::to_value(&info.capabilities)?;
+
+    let mut probe = Probe {
+        client: &mut client,
+        options,
+        findings: Vec::new(),
+        catalogs: BTreeMap::new(),
+    };
+    probe.initialize(&info.implementation, &info.protocol_version);
+    probe.ping().await;
+    for (capability, method, key) in CATALOGS {
+        let declared = capabilities.get(capability).is_some();
+        if let Some(items) = probe.catalog(capability, method, key, declared).await {
+            match *capability {
+                "tools" => probe.tools(&items).await,
+                "resources" => probe.resources(&items).await,
+                _ => probe.prompts(&items).await,
+            }
+        }
+    }
+    probe.unknown_method().await;
+    if let Some(ref mut diagnostics) = diagnostics {
+        probe.framing(diagnostics);
+    }
+
+    let Probe {
+        findings, catalogs, ..
+    } = probe;
+    if let Err(e) = client.disconnect().await {
+        tracing::debug!("Failed to disconnect compliance session: {}", e);
+    }
+
+    Ok(ComplianceReport {
+        server: info.implementation,
+        protocol_version: info.protocol_version,
+        capabilities,
+        catalogs,
+        findings,
+    })
+}
+
+/// How a request was answered.
+enum Reply {
+    Result(Value),
+    Error(JsonRpcError),
+    /// No usable answer, e.g. a timeout or a closed connection
+    Failed(String),
+}
+
+impl Reply {
+    fn describe(&self) -> String {
+        match self {
+            Reply::Result(_) => "succeeded".to_string(),
+            Reply::Error(error) => format!("returned error {} ({})", error.code, error.message),
+            Reply::Failed(reason) => format!("failed: {}", reason),
+        }
+    }
+}
+
+struct Probe<'a> {
+    client: &'a mut McpClient,
+    options: &'a ProbeOptions,
+    findings: Vec<Finding>,
+    catalogs: BTreeMap<String, usize>,
+}
+
+impl Probe<'_> {
+    fn record(&mut self, area: &str, severity: Severity, message: impl Into<String>) {
+        self.findings.push(Finding {
+            area: area.to_string(),
+            severity,
+            message: message.into(),
+        });
+    }
+
+    fn pass(&mut self, area: &str, message: impl Into<String>) {
+        self.record(area, Severity::Pass, message);
+    }
+
+    fn warn(&mut self, area: &str, message: impl Into<String>) {
+        self.record(area, Severity::Warning, message);
+    }
+
+    fn violation(&mut self, area: &str, message: impl Into<String>) {
+        self.record(area, Severity::Violation, message);
+    }
+
+    async fn request(&mut self, method: &str, params: Value) -> Reply {
+        match self
+            .client
+            .send_request_with_timeout(method, params, self.options.timeout)
+            .await
+        {
+            Ok(response) => match response.error {
+                Some(error) => Reply::Error(error),
+                None => Reply::Result(response.result.unwrap_or(Value::Null)),
+            },
+            Err(e) => Reply::Failed(e.to_string()),
+        }
+    }
+
+    /// Report what was wrong with the frames received so far
+    fn framing(&mut self, diagnostics: &mut broadcast::Receiver<ProtocolDiagnostic>) {
+        loop {
+            match diagnostics.try_recv() {
+                Ok(diagnostic) => {
+                    let mut message = format!("{}: {}", diagnostic.rule, diagnostic.message);
+                    if diagnostic.dropped {
+                        message.push_str(" (frame dropped)");
+                    }
+                    match diagnostic.severity {
+                        DiagnosticSeverity::Error => self.violation("framing", message),
+                        DiagnosticSeverity::Warning => self.warn("framing", message),
+                    }
+                }
+                Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                    self.warn(
+                        "framing",
+                        format!("{} diagnostics were not recorded", missed),
+                    );
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn initialize(&mut self, server: &Implementation, version: &ProtocolVersion) {
+        if server.name.trim().is_empty() {
+            self.violation("initialize", "serverInfo.name is empty");
+        }
+        if *version != self.options.protocol_version {
+            self.warn(
+                "initialize",
+                format!(
+                    "server answered protocol {} when {} was requested",
+                    version, self.options.protocol_version
+                ),
+            );
+        }
+        self.pass(
+            "initialize",
+            format!(
+                "initialized {} {} with protocol {}",
+                server.name, server.version, version
+            ),
+        );
+    }
+
+    async fn ping(&mut self) {
+        match self.request("ping", json!({})).await {
+            Reply::Result(result) if result.is_object() => self.pass("ping", "answered"),
+            Reply::Result(result) => {
+                self.violation("ping", format!("result must be an object, got {}", result))
+            }
+            reply => self.violation("ping", format!("ping {}", reply.describe())),
+        }
+    }
+
+    /// Every item of a catalog, or `None` when it could not be listed
+    async fn catalog(
+        &mut self,
+        capability: &str,
+        method: &str,
+        key: &str,
+        declared: bool,
+    ) -> Option<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut seen_cursors = HashSet::new();
+        let mut cursor: Option<String> = None;
+        for page in 1..=self.options.max_pages {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = match self.request(method, params).await {
+                Reply::Result(result) => result,
+                reply if declared => {
+                    self.violation(
+                        method,
+                        format!(
+                            "`{}` is declared but {} {}",
+                            capability,
+                            method,
+                            reply.describe()
+                        ),
+                    );
+                    return None;
+                }
+                _ => {
+                    self.pass(method, format!("`{}` is not declared", capability));
+                    return None;
+                }
+            };
+            if page == 1 && !declared {
+                self.warn(
+                    method,
+                    format!("answered without declaring the `{}` capability", capability),
+                );
+            }
+
+            match result.get(key).and_then(Value::as_array) {
+                Some(page_items) => items.extend(page_items.iter().cloned()),
+                None => {
+                    self.violation(method, format!("result has no `{}` array", key));
+                    return None;
+                }
+            }
+
+            match result.get("nextCursor") {
+                None | Some(Value::Null) => {
+                    let pages = if page == 1 {
+                        String::new()
+                    } else {
+                        format!(" over {} pages", page)
+                    };
+                    self.pass(method, format!("listed {} {}{}", items.len(), key, pages));
+                    self.catalogs.insert(method.to_string(), items.len());
+                    return Some(items);
+                }
+                Some(Value::String(next)) => {
+                    if !seen_cursors.insert(next.clone()) {
+                        self.violation(method, format!("nextCursor '{}' was returned twice", next));
+                        return None;
+                    }
+                    cursor = Some(next.clone());
+                }
+                Some(other) => {
+                    self.violation(
+                        method,
+                        format!("nextCursor must be a string, got {}", other),
+                    );
+                    return None;
+                }
+            }
+        }
+
+        self.warn(
+            method,
+            format!(
+                "stopped after {} pages; results may be incomplete",
+                self.options.max_pages
+            ),
+        );
+        self.catalogs.insert(method.to_string(), items.len());
+        Some(items)
+    }
+
+    async fn tools(&mut self, tools: &[Value]) {
+        let mut names = HashSet::new();
+        let mut callable = Vec::new();
+        for (index, tool) in tools.iter().enumerate() {
+            let Some(name) = tool.get("name").and_then(Value::as_str) else {
+                self.violation("tools/list", format!("tool #{} has no name", index));
+                continue;
+            };
+            let area = format!("tool {}", name);
+            if !names.insert(name) {
+                self.violation(&area, "name is listed more than once");
+            }
+            if !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            {
+                self.warn(
+                    &area,
+                    "name has characters outside A-Z, a-z, 0-9, _, - and .",
+                );
+            }
+            if tool
+                .get("description")
+                .and_then(Value::as_str)
+                .is_none_or(str::is_empty)
+            {
+                self.warn(&area, "no description");
+            }
+            let problems = schema_problems(tool.get("inputSchema"));
+            if problems.is_empty() {
+                self.pass(&area, "inputSchema is valid");
+            }
+            for (severity, problem) in problems {
+                self.record(&area, severity, problem);
+            }
+
+            let read_only = tool.pointer("/annotations/readOnlyHint") == Some(&Value::Bool(true));
+            let needs_arguments = tool
+                .pointer("/inputSchema/required")
+                .and_then(Value::as_array)
+                .is_some_and(|required| !required.is_empty());
+            if read_only && !needs_arguments {
+                callable.push(name.to_string());
+            }
+        }
+
+        if self.options.call_tools {
+            for name in callable {
+                self.call_tool(&name).await;
+            }
+        }
+
+        let area = "tools/call";
+        match self
+            .request(area, json!({ "name": UNKNOWN_TOOL, "arguments": {} }))
+            .await
+        {
+            Reply::Result(result) if result.get("isError") == Some(&Value::Bool(true)) => {
+                self.pass(area, "unknown tool reported as a tool error")
+            }
+            Reply::Result(_) => self.violation(area, "calling an unknown tool succeeded"),
+            Reply::Error(_) => self.pass(area, "unknown tool rejected"),
+            reply => self.violation(
+                area,
+                format!("calling an unknown tool {}", reply.describe()),
+            ),
+        }
+    }
+
+    async fn call_tool(&mut self, name: &str) {
+        let area = format!("tool {}", name);
+        match self
+            .request("tools/call", json!({ "name": name, "arguments": {} }))
+            .await
+        {
+            Reply::Result(result) => {
+                if result.get("content").and_then(Value::as_array).is_none() {
+                    self.violation(&area, "call result has no `content` array");
+                } else if result.get("isError") == Some(&Value::Bool(true)) {
+                    self.warn(&area, "read-only call without arguments reported an error");
+                } else {
+                    self.pass(&area, "read-only call succeeded");
+                }
+            }
+            reply => self.violation(&area, format!("read-only call {}", reply.describe())),
+        }
+    }
+
+    async fn resources(&mut self, resources: &[Value]) {
+        let mut uris = HashSet::new();
+        let mut readable = Vec::new();
+        for (index, resource) in resources.iter().enumerate() {
+            let Some(uri) = resource.get("uri").and_then(Value::as_str) else {
+                self.violation("resources/list", format!("resource #{} has no uri", index));
+                continue;
+            };
+            let area = format!("resource {}", uri);
+            if resource.get("name").and_then(Value::as_str).is_none() {
+                self.violation(&area, "no name");
+            }
+            if !uris.insert(uri) {
+                self.warn(&area, "uri is listed more than once");
+            }
+            readable.push(uri.to_string());
+        }
+
+        for uri in readable.into_iter().take(self.options.max_samples) {
+            let area = format!("resource {}", uri);
+            match self.request("resources/read", json!({ "uri": uri })).await {
+                Reply::Result(result) => match contents_problem(&result) {
+                    Some(problem) => self.violation(&area, problem),
+                    None => self.pass(&area, "read"),
+                },
+                reply => {
+                    self.violation(&area, format!("listed but reading it {}", reply.describe()))
+                }
+            }
+        }
+
+        let area = "resources/read";
+        match self.request(area, json!({ "uri": UNKNOWN_RESOURCE })).await {
+            Reply::Error(_) => self.pass(area, "unknown resource rejected"),
+            Reply::Result(_) => self.warn(area, "reading an unknown resource succeeded"),
+            reply => self.violation(
+                area,
+                format!("reading an unknown resource {}", reply.describe()),
+            ),
+        }
+    }
+
+    async fn prompts(&mut self, prompts: &[Value]) {
+        let mut gettable = Vec::new();
+        for (index, prompt) in prompts.iter().enumerate() {
+            let Some(name) = prompt.get("name").and_then(Value::as_str) else {
+                self.violation("prompts/list", format!("prompt #{} has no name", index));
+                continue;
+            };
+            let area = format!("prompt {}", name);
+            let arguments = match prompt.get("arguments") {
+                None | Some(Value::Null) => Vec::new(),
+                Some(Value::Array(arguments)) => arguments.clone(),
+                Some(_) => {
+                    self.violation(&area, "arguments must be an array");
+                    continue;
+                }
+            };
+            // Fill required arguments with a placeholder so the prompt can be rendered
+            let mut values = serde_json::Map::new();
+            for argument in &arguments {
+                match argument.get("name").and_then(Value::as_str) {
+                    Some(argument_name) if argument.get("required") == Some(&Value::Bool(true)) => {
+                        values.insert(argument_name.to_string(), json!("example"));
+                    }
+                    Some(_) => {}
+                    None => self.violation(&area, "an argument has no name"),
+                }
+            }
+            gettable.push((name.to_string(), values));
+        }
+
+        for (name, arguments) in gettable.into_iter().take(self.options.max_samples) {
+            let area = format!("prompt {}", name);
+            let params = json!({ "name": name, "arguments": arguments });
+            match self.request("prompts/get", params).await {
+                Reply::Result(result) => match messages_problem(&result) {
+                    Some(problem) => self.violation(&area, problem),
+                    None => self.pass(&area, "rendered"),
+                },
+                reply => {
+                    self.violation(&area, format!("listed but getting it {}", reply.describe()))
+                }
+            }
+        }
+    }
+
+    async fn unknown_method(&mut self) {
+        let area = "errors";
+        match self.request(UNKNOWN_METHOD, json!({})).await {
+            Reply::Error(error) if error.code == METHOD_NOT_FOUND => {
+                self.pass(area, "unknown method rejected with -32601")
+            }
+            Reply::Error(error) => self.warn(
+                area,
+                format!(
+                    "unknown method rejected with {} instead of -32601 (method not found)",
+                    error.code
+                ),
+            ),
+            Reply::Result(_) => self.violation(area, "an unknown method succeeded"),
+            reply => self.violation(area, format!("unknown method {}", reply.describe())),
+        }
+    }
+}
+
+/// Problems with a tool's `inputSchema`
+fn schema_problems(schema: Option<&Value>) -> Vec<(Severity, String)> {
+    let Some(schema) = schema else {
+        return vec![(Severity::Violation, "no inputSchema".to_string())];
+    };
+    if !schema.is_object() {
+        return vec![(
+            Severity::Violation,
+            "inputSchema must be an object".to_string(),
+        )];
+    }
+
+    let mut problems = Vec::new();
+    if schema.get("type") != Some(&json!("object")) {
+        problems.push((
+            Severity::Violation,
+            "inputSchema must have type \"object\"".to_string(),
+        ));
+    }
+
+    let properties = match schema.get("properties") {
+        None => None,
+        Some(Value::Object(properties)) => Some(properties),
+        Some(_) => {
+            problems.push((
+                Severity::Violation,
+                "inputSchema.properties must be an object".to_string(),
+            ));
+            None
+        }
+    };
+    for (name, property) in properties.into_iter().flatten() {
+        if !SCHEMA_SHAPES.iter().any(|key| property.get(key).is_some()) {
+            problems.push((
+                Severity::Warning,
+                format!("property '{}' has no type", name),
+            ));
+        }
+    }
+
+    match schema.get("required") {
+        None => {}
+        Some(Value::Array(required)) => {
+            for name in required {
+                let listed = name.as_str().is_some_and(|name| {
+                    properties.is_some_and(|properties| properties.contains_key(name))
+                });
+                if !listed {
+                    problems.push((
+                        Severity::Warning,
+                        format!("required property {} is not in properties", name),
+                    ));
+                }
+            }
+        }
+        Some(_) => problems.push((
+            Severity::Violation,
+            "inputSchema.required must be an array".to_string(),
+        )),
+    }
+    problems
+}
+
+/// What is wrong with a `resources/read` result, if anything
+fn contents_problem(result: &Value) -> Option<String> {
+    let Some(contents) = result.get("contents").and_then(Value::as_array) else {
+        return Some("read result has no `contents` array".to_string());
+    };
+    contents.iter().enumerate().find_map(|(index, content)| {
+        if content.get("uri").and_then(Value::as_str).is_none() {
+            return Some(format!("content #{} has no uri", index));
+        }
+        match (content.get("text"), content.get("blob")) {
+            (Some(Value::String(_)), None) | (None, Some(Value::String(_))) => None,
+            _ => Some(format!(
+                "content #{} must have either `text` or `blob`",
+                index
+            )),
+        }
+    })
+}
+
+/// What is wrong with a `prompts/get` result, if anything
+fn messages_problem(result: &Value) -> Option<String> {
+    let Some(messages) = result.get("messages").and_then(Value::as_array) else {
+        return Some("result has no `messages` array".to_string());
+    };
+    messages.iter().enumerate().find_map(|(index, message)| {
+        match message.get("role").and_then(Value::as_str) {
+            Some("user" | "assistant") => {}
+            _ => return Some(format!("message #{} role must be user or assistant", index)),
+        }
+        if !message.get("content").is_some_and(Value::is_object) {
+            return Some(format!("message #{} has no content", index));
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ClientConfig, DefaultNotificationHandler};
+    use crate::messages::{JsonRpcMessage, JsonRpcRequest, JsonRpcResponse};
+    use crate::transport::{InMemoryTransport, Transport};
+    use std::sync::Arc;
+
+    /// Serve `answer` over an in-memory pair and return a client for it
+    fn client_for(answer: fn(&JsonRpcRequest) -> Result<Value, JsonRpcError>) -> McpClient {
+        let (client, server) = InMemoryTransport::pair();
+        tokio::spawn(async move {
+            server.connect().await.unwrap();
+            while let Ok(message) = server.receive_message(None).await {
+                let JsonRpcMessage::Request(request) = message else {
+                    continue;
+                };
+                let response = match answer(&request) {
+                    Ok(result) => JsonRpcResponse::success(request.id.clone(), result),
+                    Err(error) => JsonRpcResponse::error(request.id.clone(), error),
+                };
+                server.send_message(JsonRpcMessage::Response(response)).ok();
+            }
+        });
+        McpClient::with_transport(
+            Arc::new(client),
+            ClientConfig::default(),
+            Box::new(DefaultNotificationHandler),
+        )
+    }
+
+    fn initialize(capabilities: Value) -> Value {
+        json!({
+            "protocolVersion": ProtocolVersion::default().as_str(),
+            "capabilities": capabilities,
+            "serverInfo": { "name": "probe-test", "version": "1.0.0" }
+        })
+    }
+
+    fn param<'a>(request: &'a JsonRpcRequest, key: &str) -> &'a str {
+        request
+            .params
+            .as_ref()
+            .and_then(|params| params.get(key))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
+
+    fn compliant(request: &JsonRpcRequest) -> Result<Value, JsonRpcError> {
+        match request.method.as_str() {
+            "initialize" => Ok(initialize(
+                json!({ "tools": {}, "resources": {}, "prompts": {} }),
+            )),
+            "ping" => Ok(json!({})),
+            // Two pages of tools
+            "tools/list" if param(request, "cursor").is_empty() => Ok(json!({
+                "tools": [{
+                    "name": "search",
+                    "description": "Search things",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": { "q": { "type": "string" } },
+                        "required": ["q"]
+                    }
+                }],
+                "nextCursor": "2"
+            })),
+            "tools/list" => Ok(json!({ "tools": [{
+                "name": "status",
+                "description": "Report status",
+                "inputSchema": { "type": "object" },
+                "annotations": { "readOnlyHint": true }
+            }]})),
+            "tools/call" if param(request, "name") == "status" => {
+                Ok(json!({ "content": [{ "type": "text", "text": "ok" }] }))
+            }
+            "tools/call" => Err(JsonRpcError::invalid_params("unknown tool")),
+            "resources/list" => Ok(json!({ "resources": [{ "uri": "demo://a", "name": "A" }] })),
+            "resources/read" if param(request, "uri") == "demo://a" => {
+                Ok(json!({ "contents": [{ "uri": "demo://a", "text": "hello" }] }))
+            }
+            "resources/read" => Err(JsonRpcError::new(-32002, "Resource not found", None)),
+            "prompts/list" => Ok(json!({ "prompts": [{
+                "name": "greet",
+                "arguments": [{ "name": "who", "required": true }]
+            }]})),
+            "prompts/get" => Ok(json!({ "messages": [{
+                "role": "user",
+                "content": { "type": "text", "text": "Hello" }
+            }]})),
+            other => Err(JsonRpcError::method_not_found(other)),
+        }
+    }
+
+    fn broken(request: &JsonRpcRequest) -> Result<Value, JsonRpcError> {
+        match request.method.as_str() {
+            "initialize" => Ok(initialize(json!({ "tools": {}, "resources": {} }))),
+            "tools/list" => Ok(json!({ "tools": [
+                { "name": "a", "description": "A" },
+                { "name": "a", "description": "A", "inputSchema": { "type": "object" } }
+            ]})),
+            "tools/call" => Ok(json!({ "content": [] })),
+            "resources/list" => Err(JsonRpcError::internal_error("no")),
+            // Undeclared, and the cursor never advances
+            "prompts/list" => Ok(json!({ "prompts": [], "nextCursor": "again" })),
+            _ => Ok(json!({})),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compliant_server_passes() {
+        let options = ProbeOptions {
+            call_tools: true,
+            timeout: Duration::from_secs(5),
+            ..ProbeOptions::default()
+        };
+        let report = probe_client(client_for(compliant), &options).await.unwrap();
+
+        assert!(report.is_compliant(), "{:#?}", report.findings);
+        assert_eq!(report.count(Severity::Warning), 0, "{:#?}", report.findings);
+        assert_eq!(report.catalogs["tools/list"], 2);
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.area == "tool status" && f.message == "read-only call succeeded"));
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.area == "prompt greet" && f.message == "rendered"));
+    }
+
+    #[tokio::test]
+    async fn test_violations_are_reported() {
+        let options = ProbeOptions {
+            timeout: Duration::from_secs(5),
+            ..ProbeOptions::default()
+        };
+        let report = probe_client(client_for(broken), &options).await.unwrap();
+        let violations: Vec<String> = report.violations().map(ToString::to_string).collect();
+
+        for expected in [
+            "[violation] tool a: no inputSchema",
+            "[violation] tool a: name is listed more than once",
+            "[violation] tools/call: calling an unknown tool succeeded",
+            "[violation] errors: an unknown method succeeded",
+            "[violation] prompts/list: nextCursor 'again' was returned twice",
+        ] {
+            assert!(
+                violations.iter().any(|v| v == expected),
+                "missing {}: {:#?}",
+                expected,
+                violations
+            );
+        }
+        assert!(violations
+            .iter()
+            .any(|v| v.starts_with("[violation] resources/list: `resources` is declared")));
+        // Undeclared but answered
+        assert!(report
+            .warnings()
+            .any(|f| f.area == "prompts/list" && f.message.contains("without declaring")));
+        assert!(!report.is_compliant());
+    }
+
+    #[test]
+    fn test_schema_problems() {
+        assert!(schema_problems(Some(&json!({ "type": "object" }))).is_empty());
+        let problems = schema_problems(Some(&json!({
+            "type": "object",
+            "properties": { "a": {}, "b": { "anyOf": [] } },
+            "required": ["a", "c"]
+        })));
+        assert_eq!(
+            problems,
+            vec![
+                (Severity::Warning, "property 'a' has no type".to_string()),
+                (
+                    Severity::Warning,
+                    "required property \"c\" is not in properties".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1280 @@
+//! Conformance test suite for MCP servers.
+//!
+//! Where [`compliance`](crate::compliance) walks what a server offers,
+//! [`run`] puts it through a fixed suite of edge cases over raw sessions:
+//!
+//! - initialization: requests before `initialize`, an unsupported protocol
+//!   version, missing parameters and a repeated `initialize`
+//! - invalid parameters for `tools/call`, `resources/read` and `prompts/get`
+//! - unknown methods
+//! - pagination of every declared catalog, including an unknown cursor
+//! - notifications: logging, progress tokens and well-formed methods
+//! - cancellation of unknown and in-flight requests
+//! - concurrent requests on one session
+//!
+//! Every case has a stable id, such as `init.unsupported-version`, and yields
+//! a [`CaseResult`]. A failed case carries a [`Severity`]: a
+//! [`Severity::Violation`] breaks the specification, a [`Severity::Warning`]
+//! is allowed but likely to trip up clients. Cases that do not apply, such as
+//! prompt checks against a server without prompts, are skipped.
+//! [`ConformanceOptions::only`] picks cases by id prefix.
+//!
+//! Initialization cases open a session of their own each, so a stdio server is
+//! spawned once per case.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_core::conformance::{self, ConformanceOptions};
+//! use mcp_core::transport::TransportConfig;
+//!
+//! # async fn example() -> mcp_core::McpResult<()> {
+//! let config = TransportConfig::stdio("python", &["server.py"]);
+//! let report = conformance::run(config, ConformanceOptions::default()).await?;
+//!
+//! for result in report.failures() {
+//!     println!("{}", result);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub use crate::compliance::Severity;
+use crate::error::{McpError, McpResult, ProtocolError};
+use crate::messages::{
+    Implementation, JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest,
+    JsonRpcResponse, ProtocolVersion,
+};
+use crate::transport::{Transport, TransportConfig, TransportFactory};
+
+/// JSON-RPC code for an unknown method
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// JSON-RPC code for invalid parameters
+const INVALID_PARAMS: i32 = -32602;
+
+/// Protocol version no server supports
+const UNSUPPORTED_VERSION: &str = "1999-01-01";
+
+/// Cursor no server handed out
+const UNKNOWN_CURSOR: &str = "assist-mcp-conformance-unknown-cursor";
+
+/// Progress token sent with the progress check
+const PROGRESS_TOKEN: &str = "assist-mcp-conformance-progress";
+
+/// How long to wait for unsolicited messages before moving on
+const DRAIN_WAIT: Duration = Duration::from_millis(50);
+
+/// Longest wait for an answer to a cancelled request
+const CANCEL_WAIT: Duration = Duration::from_secs(2);
+
+/// Requests with malformed parameters, as `(case, capability, method, params)`
+const INVALID_PARAMS_CASES: &[(&str, &str, &str, &str)] = &[
+    (
+        "params.tools-call-without-name",
+        "tools",
+        "tools/call",
+        r#"{"arguments": {}}"#,
+    ),
+    (
+        "params.tools-call-name-not-string",
+        "tools",
+        "tools/call",
+        r#"{"name": 42, "arguments": {}}"#,
+    ),
+    (
+        "params.resources-read-without-uri",
+        "resources",
+        "resources/read",
+        "{}",
+    ),
+    (
+        "params.prompts-get-without-name",
+        "prompts",
+        "prompts/get",
+        "{}",
+    ),
+];
+
+/// Methods no server implements, as `(case, method)`
+const UNKNOWN_METHODS: &[(&str, &str)] = &[
+    ("unknown.method", "assist-mcp/conformance-unknown"),
+    ("unknown.tools-method", "tools/conformance-unknown"),
+    ("unknown.unprefixed-method", "conformanceUnknown"),
+];
+
+/// Paginated catalogs, as `(capability, list method, item key, identity key)`
+const CATALOGS: &[(&str, &str, &str, &str)] = &[
+    ("tools", "tools/list", "tools", "name"),
+    ("resources", "resources/list", "resources", "uri"),
+    ("prompts", "prompts/list", "prompts", "name"),
+];
+
+/// What part of the protocol a case exercises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// The `initialize` handshake and what comes before it
+    Initialization,
+    /// Requests with malformed parameters
+    InvalidParams,
+    /// Requests for methods the server does not have
+    UnknownMethod,
+    /// Cursors of the list methods
+    Pagination,
+    /// Notifications the server sends
+    Notifications,
+    /// `notifications/cancelled`
+    Cancellation,
+    /// Several requests in flight at once
+    Concurrency,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Category::Initialization => "initialization",
+            Category::InvalidParams => "invalid_params",
+            Category::UnknownMethod => "unknown_method",
+            Category::Pagination => "pagination",
+            Category::Notifications => "notifications",
+            Category::Cancellation => "cancellation",
+            Category::Concurrency => "concurrency",
+        })
+    }
+}
+
+/// How a case went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// The server behaved as expected
+    Passed,
+    /// The server did not; see the result's severity
+    Failed,
+    /// The case does not apply to this server
+    Skipped,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Outcome::Passed => "passed",
+            Outcome::Failed => "failed",
+            Outcome::Skipped => "skipped",
+        })
+    }
+}
+
+/// The result of one case.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseResult {
+    /// Stable case id, e.g. `pagination.tools-unknown-cursor`
+    pub id: String,
+    /// What the case exercises
+    pub category: Category,
+    /// How it went
+    pub outcome: Outcome,
+    /// How serious a failure is; [`Severity::Pass`] unless the case failed
+    pub severity: Severity,
+    /// What was observed
+    pub message: String,
+    /// Time the case took
+    pub duration_ms: u64,
+}
+
+impl fmt::Display for CaseResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.outcome {
+            Outcome::Failed => write!(f, "[{}] {}: {}", self.severity, self.id, self.message),
+            outcome => write!(f, "[{}] {}: {}", outcome, self.id, self.message),
+        }
+    }
+}
+
+/// Options for a conformance run.
+#[derive(Debug, Clone)]
+pub struct ConformanceOptions {
+    /// Client identity sent during initialization
+    pub client_info: Implementation,
+    /// Protocol version requested in `initialize`
+    pub protocol_version: ProtocolVersion,
+    /// Deadline for each request
+    pub timeout: Duration,
+    /// Pages followed per catalog before giving up
+    pub max_pages: usize,
+    /// Requests in flight at once in the concurrency cases
+    pub concurrency: usize,
+    /// Only run cases whose id starts with one of these, e.g. `pagination`
+    /// or `init.repeated-initialize`; every case when empty
+    pub only: Vec<String>,
+}
+
+impl Default for ConformanceOptions {
+    fn default() -> Self {
+        Self {
+            client_info: Implementation::new("mcp-conformance", crate::VERSION),
+            protocol_version: ProtocolVersion::default(),
+            timeout: Duration::from_secs(30),
+            max_pages: 50,
+            concurrency: 8,
+            only: Vec::new(),
+        }
+    }
+}
+
+/// Everything a conformance run found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    /// Server implementation details
+    pub server: Implementation,
+    /// Version the server answered with
+    pub protocol_version: ProtocolVersion,
+    /// Advertised server capabilities
+    pub capabilities: Value,
+    /// Result of every case that ran, in order
+    pub results: Vec<CaseResult>,
+}
+
+impl ConformanceReport {
+    /// Cases that failed, whatever their severity.
+    pub fn failures(&self) -> impl Iterator<Item = &CaseResult> {
+        self.results
+            .iter()
+            .filter(|result| result.outcome == Outcome::Failed)
+    }
+
+    /// Failed cases that break the specification.
+    pub fn violations(&self) -> impl Iterator<Item = &CaseResult> {
+        self.failures()
+            .filter(|result| result.severity == Severity::Violation)
+    }
+
+    /// Number of cases with the given outcome.
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.outcome == outcome)
+            .count()
+    }
+
+    /// Whether no case found a violation.
+    pub fn is_conformant(&self) -> bool {
+        self.violations().next().is_none()
+    }
+}
+
+/// Run the suite against the server `transport` connects to.
+pub async fn run(
+    transport: TransportConfig,
+    options: ConformanceOptions,
+) -> McpResult<ConformanceReport> {
+    let open = || {
+        let transport = transport.clone();
+        async move {
+            TransportFactory::create(transport)
+                .await
+                .map(Arc::<dyn Transport>::from)
+        }
+    };
+    run_with(open, options).await
+}
+
+/// Run the suite with sessions from `open`.
+///
+/// `open` is called once for the main session and once for each
+/// initialization case, and returns a transport that has not connected yet.
+/// Fails only when the main session cannot be initialized; everything after
+/// that is reported as case results.
+pub async fn run_with<F, Fut>(open: F, options: ConformanceOptions) -> McpResult<ConformanceReport>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = McpResult<Arc<dyn Transport>>>,
+{
+    let session = Session::open(&open, options.timeout).await?;
+    let started = Instant::now();
+    let initialized = match session
+        .initialize(options.protocol_version.as_str(), &options.client_info)
+        .await
+    {
+        Reply::Result(result) => result,
+        reply => {
+            session.close().await;
+            return Err(McpError::Protocol(ProtocolError::InitializationFailed {
+                reason: format!("initialize {}", reply.describe()),
+            }));
+        }
+    };
+    let capabilities = initialized
+        .get("capabilities")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+
+    let mut suite = Suite {
+        options: &options,
+        capabilities: &capabilities,
+        results: Vec::new(),
+        notifications: Vec::new(),
+    };
+    suite.record(
+        "init.result-shape",
+        Category::Initialization,
+        started,
+        initialize_shape(&initialized),
+    );
+    suite.initialization(&open).await;
+    suite.invalid_params(&session).await;
+    suite.unknown_methods(&session).await;
+    suite.pagination(&session).await;
+    suite.notifications(&session).await;
+    suite.cancellation(&session).await;
+    suite.concurrency(&session).await;
+    suite.well_formed_notifications(&session).await;
+
+    let results = suite.results;
+    session.close().await;
+
+    Ok(ConformanceReport {
+        server: initialized
+            .get("serverInfo")
+            .cloned()
+            .and_then(|info| serde_json::from_value(info).ok())
+            .unwrap_or_else(|| Implementation::new("unknown", "unknown")),
+        protocol_version: initialized
+            .get("protocolVersion")
+            .cloned()
+            .and_then(|version| serde_json::from_value(version).ok())
+            .unwrap_or_default(),
+        capabilities,
+        results,
+    })
+}
+
+/// How a request was answered.
+enum Reply {
+    Result(Value),
+    Error(JsonRpcError),
+    /// No usable answer, e.g. a timeout or a closed connection
+    Failed(String),
+}
+
+impl Reply {
+    fn describe(&self) -> String {
+        match self {
+            Reply::Result(_) => "succeeded".to_string(),
+            Reply::Error(error) => format!("returned error {} ({})", error.code, error.message),
+            Reply::Failed(reason) => format!("failed: {}", reason),
+        }
+    }
+}
+
+/// What a case concluded.
+enum Verdict {
+    Pass(String),
+    Fail(Severity, String),
+    Skip(String),
+}
+
+impl Verdict {
+    fn warn(message: impl Into<String>) -> Self {
+        Verdict::Fail(Severity::Warning, message.into())
+    }
+
+    fn violation(message: impl Into<String>) -> Self {
+        Verdict::Fail(Severity::Violation, message.into())
+    }
+
+    /// Verdict for a request that should be rejected with `code`
+    fn expect_error(reply: Reply, code: i32, code_name: &str, accepted: &str) -> Self {
+        match reply {
+            Reply::Error(error) if error.code == code => {
+                Verdict::Pass(format!("rejected with {}", code))
+            }
+            Reply::Error(error) => Verdict::warn(format!(
+                "rejected with {} instead of {} ({})",
+                error.code, code, code_name
+            )),
+            Reply::Result(_) => Verdict::violation(accepted),
+            reply => Verdict::violation(format!("request {}", reply.describe())),
+        }
+    }
+}
+
+/// A connection driven without [`McpClient`](crate::client::McpClient), so
+/// requests can be sent in any order and with any parameters
+struct Session {
+    transport: Arc<dyn Transport>,
+    timeout: Duration,
+    next_id: AtomicU64,
+}
+
+impl Session {
+    async fn open<F, Fut>(open: &F, timeout: Duration) -> McpResult<Self>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = McpResult<Arc<dyn Transport>>>,
+    {
+        let transport = open().await?;
+        transport.connect().await?;
+        Ok(Self {
+            transport,
+            timeout,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    fn next_id(&self) -> String {
+        format!(
+            "conformance-{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Reply {
+        self.request_within(method, params, self.timeout).await
+    }
+
+    async fn request_within(&self, method: &str, params: Value, timeout: Duration) -> Reply {
+        let request = JsonRpcRequest::new(self.next_id(), method, params);
+        match self.transport.send_request(request, Some(timeout)).await {
+            Ok(response) => match response.error {
+                Some(error) => Reply::Error(error),
+                None => Reply::Result(response.result.unwrap_or(Value::Null)),
+            },
+            Err(e) => Reply::Failed(e.to_string()),
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> McpResult<()> {
+        self.transport
+            .send_notification(JsonRpcNotification::new(method, params))
+            .await
+    }
+
+    /// Send `initialize` and, when it succeeds, `notifications/initialized`
+    async fn initialize(&self, version: &str, client_info: &Implementation) -> Reply {
+        let params = json!({
+            "protocolVersion": version,
+            "capabilities": {},
+            "clientInfo": client_info,
+        });
+        let reply = self.request("initialize", params).await;
+        if let Reply::Result(_) = reply {
+            if let Err(e) = self.notify("notifications/initialized", json!({})).await {
+                tracing::debug!("Failed to send initialized notification: {}", e);
+            }
+        }
+        reply
+    }
+
+    /// Notifications the server sent since the last call; requests from the
+    /// server are refused as they arrive
+    async fn drain(&self) -> Vec<JsonRpcNotification> {
+        let mut notifications = Vec::new();
+        while let Ok(message) = self.transport.receive_message(Some(DRAIN_WAIT)).await {
+            match message {
+                JsonRpcMessage::Notification(notification) => notifications.push(notification),
+                JsonRpcMessage::Request(request) => {
+                    let refusal = JsonRpcResponse::error(
+                        request.id.clone(),
+                        JsonRpcError::method_not_found(&request.method),
+                    );
+                    if let Err(e) = self.transport.send_response(refusal).await {
+                        tracing::debug!("Failed to refuse {}: {}", request.method, e);
+                    }
+                }
+                JsonRpcMessage::Response(_) => {}
+            }
+        }
+        notifications
+    }
+
+    async fn close(self) {
+        if let Err(e) = self.transport.disconnect().await {
+            tracing::debug!("Failed to disconnect conformance session: {}", e);
+        }
+    }
+}
+
+struct Suite<'a> {
+    options: &'a ConformanceOptions,
+    capabilities: &'a Value,
+    results: Vec<CaseResult>,
+    /// Notifications received on the main session so far
+    notifications: Vec<JsonRpcNotification>,
+}
+
+impl Suite<'_> {
+    fn selected(&self, id: &str) -> bool {
+        self.options.only.is_empty()
+            || self
+                .options
+                .only
+                .iter()
+                .any(|prefix| id.starts_with(prefix.as_str()))
+    }
+
+    fn declares(&self, capability: &str) -> bool {
+        self.capabilities.get(capability).is_some()
+    }
+
+    fn record(&mut self, id: &str, category: Category, started: Instant, verdict: Verdict) {
+        if !self.selected(id) {
+            return;
+        }
+        let (outcome, severity, message) = match verdict {
+            Verdict::Pass(message) => (Outcome::Passed, Severity::Pass, message),
+            Verdict::Fail(severity, message) => (Outcome::Failed, severity, message),
+            Verdict::Skip(message) => (Outcome::Skipped, Severity::Pass, message),
+        };
+        self.results.push(CaseResult {
+            id: id.to_string(),
+            category,
+            outcome,
+            severity,
+            message,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    /// Run `check` as case `id` unless it was left out
+    async fn case(&mut self, id: &str, category: Category, check: impl Future<Output = Verdict>) {
+        if self.selected(id) {
+            let started = Instant::now();
+            let verdict = check.await;
+            self.record(id, category, started, verdict);
+        }
+    }
+
+    async fn initialization<F, Fut>(&mut self, open: &F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = McpResult<Arc<dyn Transport>>>,
+    {
+        let options = self.options;
+        let category = Category::Initialization;
+        self.case(
+            "init.request-before-initialize",
+            category,
+            fresh_session(open, options, |session| async move {
+                match session.request("tools/list", json!({})).await {
+                    Reply::Error(error) => Verdict::Pass(format!("rejected with {}", error.code)),
+                    Reply::Result(_) => Verdict::warn("answered tools/list before initialize"),
+                    reply => Verdict::violation(format!(
+                        "tools/list before initialize {}",
+                        reply.describe()
+                    )),
+                }
+            }),
+        )
+        .await;
+        self.case(
+            "init.unsupported-version",
+            category,
+            fresh_session(open, options, |session| async move {
+                match session
+                    .initialize(UNSUPPORTED_VERSION, &options.client_info)
+                    .await
+                {
+                    Reply::Result(result) => {
+                        match result.get("protocolVersion").and_then(Value::as_str) {
+                            Some(UNSUPPORTED_VERSION) => Verdict::violation(format!(
+                                "accepted unsupported protocol {}",
+                                UNSUPPORTED_VERSION
+                            )),
+                            Some(version) => Verdict::Pass(format!("answered with {}", version)),
+                            None => Verdict::violation("result has no protocolVersion"),
+                        }
+                    }
+                    Reply::Error(error) => Verdict::Pass(format!("rejected with {}", error.code)),
+                    reply => Verdict::violation(format!("initialize {}", reply.describe())),
+                }
+            }),
+        )
+        .await;
+        self.case(
+            "init.missing-params",
+            category,
+            fresh_session(open, options, |session| async move {
+                match session.request("initialize", json!({})).await {
+                    Reply::Result(_) => {
+                        Verdict::warn("initialized without protocolVersion or clientInfo")
+                    }
+                    reply => Verdict::expect_error(reply, INVALID_PARAMS, "invalid params", ""),
+                }
+            }),
+        )
+        .await;
+        self.case(
+            "init.repeated-initialize",
+            category,
+            fresh_session(open, options, |session| async move {
+                let version = options.protocol_version.as_str();
+                if let reply @ (Reply::Error(_) | Reply::Failed(_)) =
+                    session.initialize(version, &options.client_info).await
+                {
+                    return Verdict::Skip(format!("first initialize {}", reply.describe()));
+                }
+                match session.initialize(version, &options.client_info).await {
+                    Reply::Error(error) => Verdict::Pass(format!("rejected with {}", error.code)),
+                    Reply::Result(_) => Verdict::warn("accepted a second initialize"),
+                    reply => Verdict::violation(format!("second initialize {}", reply.describe())),
+                }
+            }),
+        )
+        .await;
+    }
+
+    async fn invalid_params(&mut self, session: &Session) {
+        for (id, capability, method, params) in INVALID_PARAMS_CASES {
+            let declared = self.declares(capability);
+            self.case(id, Category::InvalidParams, async {
+                if !declared {
+                    return Verdict::Skip(format!("`{}` is not declared", capability));
+                }
+                let params: Value = serde_json::from_str(params).expect("valid case parameters");
+                match session.request(method, params).await {
+                    Reply::Result(result) if result.get("isError") == Some(&Value::Bool(true)) => {
+                        Verdict::warn("reported as a tool error instead of -32602 (invalid params)")
+                    }
+                    reply => Verdict::expect_error(
+                        reply,
+                        INVALID_PARAMS,
+                        "invalid params",
+                        &format!("{} with invalid params succeeded", method),
+                    ),
+                }
+            })
+            .await;
+        }
+    }
+
+    async fn unknown_methods(&mut self, session: &Session) {
+        for (id, method) in UNKNOWN_METHODS {
+            self.case(id, Category::UnknownMethod, async {
+                let reply = session.request(method, json!({})).await;
+                Verdict::expect_error(
+                    reply,
+                    METHOD_NOT_FOUND,
+                    "method not found",
+                    &format!("unknown method {} succeeded", method),
+                )
+            })
+            .await;
+        }
+    }
+
+    async fn pagination(&mut self, session: &Session) {
+        let max_pages = self.options.max_pages;
+        for (capability, method, key, identity) in CATALOGS {
+            let declared = self.declares(capability);
+            let skip = || Verdict::Skip(format!("`{}` is not declared", capability));
+            self.case(
+                &format!("pagination.{}", capability),
+                Category::Pagination,
+                async {
+                    if !declared {
+                        return skip();
+                    }
+                    paginate(session, method, key, identity, max_pages).await
+                },
+            )
+            .await;
+            self.case(
+                &format!("pagination.{}-unknown-cursor", capability),
+                Category::Pagination,
+                async {
+                    if !declared {
+                        return skip();
+                    }
+                    match session
+                        .request(method, json!({ "cursor": UNKNOWN_CURSOR }))
+                        .await
+                    {
+                        Reply::Result(_) => Verdict::warn(
+                            "answered an unknown cursor instead of rejecting it with -32602",
+                        ),
+                        reply => Verdict::expect_error(reply, INVALID_PARAMS, "invalid params", ""),
+                    }
+                },
+            )
+            .await;
+        }
+    }
+
+    async fn notifications(&mut self, session: &Session) {
+        self.notifications.extend(session.drain().await);
+        let logging = self.declares("logging");
+        self.case("notifications.logging", Category::Notifications, async {
+            if !logging {
+                return Verdict::Skip("`logging` is not declared".to_string());
+            }
+            match session
+                .request("logging/setLevel", json!({ "level": "debug" }))
+                .await
+            {
+                Reply::Result(_) => Verdict::Pass("accepted logging/setLevel".to_string()),
+                reply => Verdict::violation(format!(
+                    "`logging` is declared but logging/setLevel {}",
+                    reply.describe()
+                )),
+            }
+        })
+        .await;
+
+        let started = Instant::now();
+        let reply = session
+            .request(
+                "ping",
+                json!({ "_meta": { "progressToken": PROGRESS_TOKEN } }),
+            )
+            .await;
+        self.notifications.extend(session.drain().await);
+        let verdict = match reply {
+            Reply::Result(_) => progress_problem(&self.notifications).map_or_else(
+                || Verdict::Pass("progress notifications carry known tokens".to_string()),
+                Verdict::violation,
+            ),
+            reply => Verdict::violation(format!("ping with a progress token {}", reply.describe())),
+        };
+        self.record(
+            "notifications.progress",
+            Category::Notifications,
+            started,
+            verdict,
+        );
+    }
+
+    async fn cancellation(&mut self, session: &Session) {
+        self.case(
+            "cancellation.unknown-request",
+            Category::Cancellation,
+            async {
+                let cancel = json!({
+                    "requestId": "assist-mcp-conformance-never-sent",
+                    "reason": "conformance check"
+                });
+                if let Err(e) = session.notify("notifications/cancelled", cancel).await {
+                    return Verdict::violation(format!("cancellation could not be sent: {}", e));
+                }
+                match session.request("ping", json!({})).await {
+                    Reply::Result(_) => Verdict::Pass(
+                        "kept serving after cancelling an unknown request".to_string(),
+                    ),
+                    reply => Verdict::violation(format!(
+                        "ping after cancelling an unknown request {}",
+                        reply.describe()
+                    )),
+                }
+            },
+        )
+        .await;
+
+        let method = if self.declares("tools") {
+            "tools/list"
+        } else {
+            "ping"
+        };
+        let wait = self.options.timeout.min(CANCEL_WAIT);
+        self.case("cancellation.in-flight", Category::Cancellation, async {
+            let id = session.next_id();
+            let request = session.transport.send_request(
+                JsonRpcRequest::new(id.clone(), method, json!({})),
+                Some(wait),
+            );
+            let cancel = async {
+                // Let the request go out first
+                tokio::task::yield_now().await;
+                session
+                    .notify(
+                        "notifications/cancelled",
+                        json!({ "requestId": id, "reason": "conformance check" }),
+                    )
+                    .await
+            };
+            let (answer, cancelled) = tokio::join!(request, cancel);
+            if let Err(e) = cancelled {
+                return Verdict::violation(format!("cancellation could not be sent: {}", e));
+            }
+            let fate = if answer.is_ok() {
+                "answered"
+            } else {
+                "dropped"
+            };
+            match session.request("ping", json!({})).await {
+                Reply::Result(_) => Verdict::Pass(format!(
+                    "cancelled {} was {}; later requests are still served",
+                    method, fate
+                )),
+                reply => Verdict::violation(format!(
+                    "ping after cancelling {} {}",
+                    method,
+                    reply.describe()
+                )),
+            }
+        })
+        .await;
+    }
+
+    async fn concurrency(&mut self, session: &Session) {
+        let count = self.options.concurrency.max(2);
+        self.case("concurrency.pings", Category::Concurrency, async {
+            let pings = (0..count).map(|_| session.request("ping", json!({})));
+            all_answered(join_all(pings).await, "pings")
+        })
+        .await;
+
+        let mut methods = vec!["ping"];
+        methods.extend(
+            CATALOGS
+                .iter()
+                .filter(|(capability, ..)| self.declares(capability))
+                .map(|(_, method, ..)| *method),
+        );
+        self.case("concurrency.mixed", Category::Concurrency, async {
+            let requests = methods
+                .iter()
+                .cycle()
+                .take(count.max(methods.len()))
+                .map(|method| session.request(method, json!({})));
+            all_answered(
+                join_all(requests).await,
+                &format!("requests across {}", methods.join(", ")),
+            )
+        })
+        .await;
+    }
+
+    /// Check every notification received on the main session
+    async fn well_formed_notifications(&mut self, session: &Session) {
+        let started = Instant::now();
+        self.notifications.extend(session.drain().await);
+        let odd: BTreeSet<&str> = self
+            .notifications
+            .iter()
+            .map(|notification| notification.method.as_str())
+            .filter(|method| !method.starts_with("notifications/"))
+            .collect();
+        let verdict = if odd.is_empty() {
+            Verdict::Pass(format!(
+                "received {} notification(s)",
+                self.notifications.len()
+            ))
+        } else {
+            Verdict::warn(format!(
+                "sent notifications outside notifications/: {}",
+                odd.into_iter().collect::<Vec<_>>().join(", ")
+            ))
+        };
+        self.record(
+            "notifications.well-formed",
+            Category::Notifications,
+            started,
+            verdict,
+        );
+    }
+}
+
+/// Run `check` on a session of its own, skipping when it cannot be opened
+async fn fresh_session<F, Fut, C, CFut>(open: &F, options: &ConformanceOptions, check: C) -> Verdict
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = McpResult<Arc<dyn Transport>>>,
+    C: FnOnce(Arc<Session>) -> CFut,
+    CFut: Future<Output = Verdict>,
+{
+    let session = match Session::open(open, options.timeout).await {
+        Ok(session) => Arc::new(session),
+        Err(e) => return Verdict::Skip(format!("could not open a session: {}", e)),
+    };
+    let verdict = check(session.clone()).await;
+    session.drain().await;
+    if let Err(e) = session.transport.disconnect().await {
+        tracing::debug!("Failed to disconnect conformance session: {}", e);
+    }
+    verdict
+}
+
+/// What is wrong with the `initialize` result, if anything
+fn initialize_shape(result: &Value) -> Verdict {
+    let mut problems = Vec::new();
+    if result
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .is_none()
+    {
+        problems.push("protocolVersion must be a string");
+    }
+    if !result.get("capabilities").is_some_and(Value::is_object) {
+        problems.push("capabilities must be an object");
+    }
+    if result
+        .pointer("/serverInfo/name")
+        .and_then(Value::as_str)
+        .is_none_or(|name| name.trim().is_empty())
+    {
+        problems.push("serverInfo.name must be a non-empty string");
+    }
+    if result
+        .pointer("/serverInfo/version")
+        .and_then(Value::as_str)
+        .is_none()
+    {
+        problems.push("serverInfo.version must be a string");
+    }
+    if problems.is_empty() {
+        Verdict::Pass("protocolVersion, capabilities and serverInfo are present".to_string())
+    } else {
+        Verdict::violation(problems.join("; "))
+    }
+}
+
+/// Follow every page of a catalog
+async fn paginate(
+    session: &Session,
+    method: &str,
+    key: &str,
+    identity: &str,
+    max_pages: usize,
+) -> Verdict {
+    let mut seen_cursors = HashSet::new();
+    let mut seen_items = HashSet::new();
+    let mut duplicates = Vec::new();
+    let mut cursor: Option<String> = None;
+    for page in 1..=max_pages {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let result = match session.request(method, params).await {
+            Reply::Result(result) => result,
+            reply => return Verdict::violation(format!("page {} {}", page, reply.describe())),
+        };
+        let Some(items) = result.get(key).and_then(Value::as_array) else {
+            return Verdict::violation(format!("page {} has no `{}` array", page, key));
+        };
+        for item in items {
+            if let Some(name) = item.get(identity).and_then(Value::as_str) {
+                if !seen_items.insert(name.to_string()) {
+                    duplicates.push(name.to_string());
+                }
+            }
+        }
+
+        match result.get("nextCursor") {
+            None | Some(Value::Null) if duplicates.is_empty() => {
+                return Verdict::Pass(format!(
+                    "listed {} {} over {} page(s)",
+                    seen_items.len(),
+                    key,
+                    page
+                ))
+            }
+            None | Some(Value::Null) => {
+                return Verdict::warn(format!(
+                    "listed more than once across pages: {}",
+                    duplicates.join(", ")
+                ))
+            }
+            Some(Value::String(next)) => {
+                if !seen_cursors.insert(next.clone()) {
+                    return Verdict::violation(format!("nextCursor '{}' was returned twice", next));
+                }
+                cursor = Some(next.clone());
+            }
+            Some(other) => {
+                return Verdict::violation(format!("nextCursor must be a string, got {}", other))
+            }
+        }
+    }
+    Verdict::warn(format!("stopped after {} pages", max_pages))
+}
+
+/// A progress notification that does not belong to a request, if any
+fn progress_problem(notifications: &[JsonRpcNotification]) -> Option<String> {
+    notifications
+        .iter()
+        .filter(|notification| notification.method == "notifications/progress")
+        .find_map(|notification| {
+            let params = notification.params.as_ref();
+            let token = params.and_then(|params| params.get("progressToken"));
+            if token != Some(&json!(PROGRESS_TOKEN)) {
+                return Some(format!(
+                    "progress notification for unknown token {}",
+                    token.unwrap_or(&Value::Null)
+                ));
+            }
+            if !params.is_some_and(|params| params.get("progress").is_some_and(Value::is_number)) {
+                return Some("progress notification without a numeric progress".to_string());
+            }
+            None
+        })
+}
+
+/// Verdict for a batch of concurrent requests
+fn all_answered(replies: Vec<Reply>, what: &str) -> Verdict {
+    let total = replies.len();
+    let failed: Vec<String> = replies
+        .into_iter()
+        .filter(|reply| !matches!(reply, Reply::Result(_)))
+        .map(|reply| reply.describe())
+        .collect();
+    match failed.first() {
+        None => Verdict::Pass(format!("answered {} concurrent {}", total, what)),
+        Some(first) => Verdict::violation(format!(
+            "{} of {} concurrent {} went wrong, e.g. {}",
+            failed.len(),
+            total,
+            what,
+            first
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::InMemoryTransport;
+
+    /// What one connection to a test server has seen
+    #[derive(Default)]
+    struct Connection {
+        initialized: bool,
+        /// Notifications sent ahead of the next response
+        outbox: Vec<JsonRpcNotification>,
+    }
+
+    type Behaviour = fn(&JsonRpcRequest, &mut Connection) -> Result<Value, JsonRpcError>;
+
+    /// Each call opens a connection to a fresh server acting out `behaviour`
+    fn server(
+        behaviour: Behaviour,
+    ) -> impl Fn() -> std::future::Ready<McpResult<Arc<dyn Transport>>> {
+        move || {
+            let (client, server) = InMemoryTransport::pair();
+            tokio::spawn(async move {
+                server.connect().await.unwrap();
+                let mut connection = Connection::default();
+                while let Ok(message) = server.receive_message(None).await {
+                    let JsonRpcMessage::Request(request) = message else {
+                        continue;
+                    };
+                    let response = match behaviour(&request, &mut connection) {
+                        Ok(result) => JsonRpcResponse::success(request.id.clone(), result),
+                        Err(error) => JsonRpcResponse::error(request.id.clone(), error),
+                    };
+                    for notification in connection.outbox.drain(..) {
+                        server
+                            .send_message(JsonRpcMessage::Notification(notification))
+                            .ok();
+                    }
+                    server.send_message(JsonRpcMessage::Response(response)).ok();
+                }
+            });
+            std::future::ready(Ok(Arc::new(client) as Arc<dyn Transport>))
+        }
+    }
+
+    fn param<'a>(request: &'a JsonRpcRequest, key: &str) -> Option<&'a Value> {
+        request.params.as_ref().and_then(|params| params.get(key))
+    }
+
+    fn initialize_result() -> Value {
+        json!({
+            "protocolVersion": ProtocolVersion::default().as_str(),
+            "capabilities": { "tools": {}, "resources": {}, "logging": {} },
+            "serverInfo": { "name": "conformance-test", "version": "1.0.0" }
+        })
+    }
+
+    fn conformant(
+        request: &JsonRpcRequest,
+        connection: &mut Connection,
+    ) -> Result<Value, JsonRpcError> {
+        match request.method.as_str() {
+            "initialize" if connection.initialized => {
+                Err(JsonRpcError::invalid_request("already initialized"))
+            }
+            "initialize" => {
+                if param(request, "protocolVersion").is_none() {
+                    return Err(JsonRpcError::invalid_params("protocolVersion is required"));
+                }
+                connection.initialized = true;
+                Ok(initialize_result())
+            }
+            "ping" => {
+                if let Some(token) = request
+                    .params
+                    .as_ref()
+                    .and_then(|params| params.pointer("/_meta/progressToken").cloned())
+                {
+                    connection.outbox.push(JsonRpcNotification::new(
+                        "notifications/progress",
+                        json!({ "progressToken": token, "progress": 1 }),
+                    ));
+                }
+                Ok(json!({}))
+            }
+            _ if !connection.initialized => Err(JsonRpcError::invalid_request("not initialized")),
+            "tools/list" => match param(request, "cursor").and_then(Value::as_str) {
+                None => Ok(json!({ "tools": [{ "name": "a" }], "nextCursor": "2" })),
+                Some("2") => Ok(json!({ "tools": [{ "name": "b" }] })),
+                Some(_) => Err(JsonRpcError::invalid_params("unknown cursor")),
+            },
+            "tools/call" if param(request, "name").is_some_and(Value::is_string) => {
+                Ok(json!({ "content": [] }))
+            }
+            "resources/list" if param(request, "cursor").is_some() => {
+                Err(JsonRpcError::invalid_params("unknown cursor"))
+            }
+            "resources/list" => Ok(json!({ "resources": [] })),
+            "tools/call" | "resources/read" => {
+                Err(JsonRpcError::invalid_params("missing parameters"))
+            }
+            "logging/setLevel" => {
+                connection.outbox.push(JsonRpcNotification::new(
+                    "notifications/message",
+                    json!({ "level": "info", "data": "level changed" }),
+                ));
+                Ok(json!({}))
+            }
+            other => Err(JsonRpcError::method_not_found(other)),
+        }
+    }
+
+    /// Says yes to everything, including twice the same cursor
+    fn permissive(
+        request: &JsonRpcRequest,
+        connection: &mut Connection,
+    ) -> Result<Value, JsonRpcError> {
+        match request.method.as_str() {
+            "initialize" => Ok(json!({
+                "protocolVersion": param(request, "protocolVersion"),
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "" }
+            })),
+            "tools/list" => Ok(json!({ "tools": [{ "name": "a" }], "nextCursor": "again" })),
+            "ping" => {
+                connection.outbox.push(JsonRpcNotification::new(
+                    "notifications/progress",
+                    json!({ "progressToken": "stale", "progress": 1 }),
+                ));
+                connection
+                    .outbox
+                    .push(JsonRpcNotification::new("server/heartbeat", json!({})));
+                Ok(json!({}))
+            }
+            _ => Ok(json!({})),
+        }
+    }
+
+    fn options() -> ConformanceOptions {
+        ConformanceOptions {
+            timeout: Duration::from_secs(5),
+            ..ConformanceOptions::default()
+        }
+    }
+
+    fn result<'a>(report: &'a ConformanceReport, id: &str) -> &'a CaseResult {
+        report
+            .results
+            .iter()
+            .find(|result| result.id == id)
+            .unwrap_or_else(|| panic!("no result for {}: {:#?}", id, report.results))
+    }
+
+    #[tokio::test]
+    async fn test_conformant_server_passes() {
+        let report = run_with(server(conformant), options()).await.unwrap();
+
+        assert!(report.is_conformant(), "{:#?}", report.results);
+        assert_eq!(report.failures().count(), 0, "{:#?}", report.results);
+        assert_eq!(report.server.name, "conformance-test");
+        assert_eq!(
+            result(&report, "pagination.tools").message,
+            "listed 2 tools over 2 page(s)"
+        );
+        // No prompts capability
+        assert_eq!(
+            result(&report, "params.prompts-get-without-name").outcome,
+            Outcome::Skipped
+        );
+        assert_eq!(report.count(Outcome::Skipped), 3);
+        assert_eq!(
+            result(&report, "notifications.well-formed").message,
+            "received 2 notification(s)"
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["results"][0]["id"], "init.result-shape");
+        assert_eq!(json["results"][0]["category"], "initialization");
+        assert_eq!(json["results"][0]["outcome"], "passed");
+    }
+
+    #[tokio::test]
+    async fn test_failures_carry_severity() {
+        let report = run_with(server(permissive), options()).await.unwrap();
+        let failures: Vec<String> = report.failures().map(ToString::to_string).collect();
+
+        for expected in [
+            "[violation] init.result-shape: serverInfo.name must be a non-empty string; serverInfo.version must be a string",
+            "[warning] init.request-before-initialize: answered tools/list before initialize",
+            "[violation] init.unsupported-version: accepted unsupported protocol 1999-01-01",
+            "[warning] init.repeated-initialize: accepted a second initialize",
+            "[violation] params.tools-call-without-name: tools/call with invalid params succeeded",
+            "[violation] unknown.method: unknown method assist-mcp/conformance-unknown succeeded",
+            "[violation] pagination.tools: nextCursor 'again' was returned twice",
+            "[violation] notifications.progress: progress notification for unknown token \"stale\"",
+            "[warning] notifications.well-formed: sent notifications outside notifications/: server/heartbeat",
+        ] {
+            assert!(
+                failures.iter().any(|failure| failure == expected),
+                "missing {}: {:#?}",
+                expected,
+                failures
+            );
+        }
+        assert_eq!(
+            result(&report, "concurrency.pings").outcome,
+            Outcome::Passed
+        );
+        assert!(!report.is_conformant());
+    }
+
+    #[tokio::test]
+    async fn test_only_runs_selected_cases() {
+        let options = ConformanceOptions {
+            only: vec!["pagination".to_string(), "unknown.method".to_string()],
+            ..options()
+        };
+        let report = run_with(server(conformant), options).await.unwrap();
+        let ids: Vec<&str> = report
+            .results
+            .iter()
+            .map(|result| result.id.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "unknown.method",
+                "pagination.tools",
+                "pagination.tools-unknown-cursor",
+                "pagination.resources",
+                "pagination.resources-unknown-cursor",
+                "pagination.prompts",
+                "pagination.prompts-unknown-cursor",
+            ]
+        );
+    }
+}
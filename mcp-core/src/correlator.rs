@@ -0,0 +1,319 @@
+//! Matching JSON-RPC responses to the requests waiting for them.
+//!
+//! [`RequestCorrelator`] is shared by the client and the transports so they
+//! agree on how a response finds its request: a caller registers the request
+//! ID before sending and gets a [`PendingResponse`] to wait on, and whichever
+//! task reads messages hands each response to [`RequestCorrelator::complete`].
+//!
+//! A pending entry is removed when it is answered, times out, is cancelled or
+//! its [`PendingResponse`] is dropped, so abandoned requests never leak.
+//! Duplicate request IDs are rejected, and responses are counted as
+//! duplicates when they repeat a recently answered ID, or as unmatched when
+//! nobody asked for them.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::error::TransportError;
+use crate::messages::JsonRpcResponse;
+
+/// Number of answered request IDs remembered to recognize duplicate responses
+const RECENTLY_COMPLETED: usize = 256;
+
+/// Counters describing how responses were correlated
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorrelatorStats {
+    /// Requests registered to wait for a response
+    pub registered: u64,
+    /// Responses delivered to their waiting request
+    pub completed: u64,
+    /// Requests that gave up waiting
+    pub timed_out: u64,
+    /// Requests cancelled or abandoned before a response arrived
+    pub cancelled: u64,
+    /// Registrations rejected because the ID was already in flight
+    pub duplicate_requests: u64,
+    /// Responses for an ID that was already answered
+    pub duplicate_responses: u64,
+    /// Responses nobody was waiting for
+    pub unmatched_responses: u64,
+    /// Requests currently waiting
+    pub pending: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    pending: HashMap<String, oneshot::Sender<JsonRpcResponse>>,
+    recent: VecDeque<String>,
+    recent_set: HashSet<String>,
+    stats: CorrelatorStats,
+}
+
+impl Inner {
+    fn remember_completed(&mut self, id: String) {
+        if self.recent.len() == RECENTLY_COMPLETED {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.recent_set.remove(&oldest);
+            }
+        }
+        self.recent_set.insert(id.clone());
+        self.recent.push_back(id);
+    }
+}
+
+/// Shared table of requests awaiting responses.
+///
+/// Cloning is cheap and every clone refers to the same table, so the reader
+/// task and the senders can each hold one.
+#[derive(Clone)]
+pub struct RequestCorrelator {
+    transport_type: Arc<str>,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl RequestCorrelator {
+    /// Create an empty correlator; `transport_type` labels its errors
+    pub fn new(transport_type: impl Into<String>) -> Self {
+        Self {
+            transport_type: transport_type.into().into(),
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        // A panic while holding the lock cannot leave the table inconsistent
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start waiting for the response to `id`.
+    ///
+    /// Fails if a request with the same ID is still waiting.
+    pub fn register(&self, id: impl Display) -> Result<PendingResponse, TransportError> {
+        let id = id.to_string();
+        let (sender, receiver) = oneshot::channel();
+
+        let mut inner = self.lock();
+        if inner.pending.contains_key(&id) {
+            inner.stats.duplicate_requests += 1;
+            return Err(TransportError::SendFailed {
+                transport_type: self.transport_type.to_string(),
+                reason: format!("Request ID {} is already awaiting a response", id),
+            });
+        }
+        inner.pending.insert(id.clone(), sender);
+        inner.recent_set.remove(&id);
+        inner.stats.registered += 1;
+        drop(inner);
+
+        Ok(PendingResponse {
+            id,
+            receiver,
+            correlator: self.clone(),
+            finished: false,
+        })
+    }
+
+    /// Deliver a response to the request waiting for it.
+    ///
+    /// The response is handed back when no request is waiting for its ID.
+    pub fn complete(&self, response: JsonRpcResponse) -> Option<JsonRpcResponse> {
+        let id = response.id.to_string();
+        let mut inner = self.lock();
+        let Some(sender) = inner.pending.remove(&id) else {
+            if inner.recent_set.contains(&id) {
+                inner.stats.duplicate_responses += 1;
+                tracing::warn!("Duplicate response for request ID: {}", id);
+            } else {
+                inner.stats.unmatched_responses += 1;
+                tracing::debug!("Response for unknown request ID: {}", id);
+            }
+            return Some(response);
+        };
+        inner.remember_completed(id);
+        drop(inner);
+
+        match sender.send(response) {
+            Ok(()) => {
+                self.lock().stats.completed += 1;
+                None
+            }
+            // The waiter went away between removal and delivery
+            Err(response) => Some(response),
+        }
+    }
+
+    /// Stop waiting for `id`, failing its waiter. Returns whether it was pending.
+    pub fn cancel(&self, id: impl Display) -> bool {
+        let mut inner = self.lock();
+        let cancelled = inner.pending.remove(&id.to_string()).is_some();
+        if cancelled {
+            inner.stats.cancelled += 1;
+        }
+        cancelled
+    }
+
+    /// Fail every waiting request, e.g. when the connection goes away
+    pub fn cancel_all(&self) {
+        let mut inner = self.lock();
+        let count = inner.pending.len() as u64;
+        inner.pending.clear();
+        inner.stats.cancelled += count;
+    }
+
+    /// Number of requests currently waiting
+    pub fn pending_count(&self) -> usize {
+        self.lock().pending.len()
+    }
+
+    /// Snapshot of the correlation counters
+    pub fn stats(&self) -> CorrelatorStats {
+        let inner = self.lock();
+        CorrelatorStats {
+            pending: inner.pending.len() as u64,
+            ..inner.stats.clone()
+        }
+    }
+
+    fn finish(&self, id: &str, timed_out: bool) {
+        let mut inner = self.lock();
+        if inner.pending.remove(id).is_some() {
+            if timed_out {
+                inner.stats.timed_out += 1;
+            } else {
+                inner.stats.cancelled += 1;
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for RequestCorrelator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestCorrelator")
+            .field("transport_type", &self.transport_type)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// A registered request waiting for its response.
+///
+/// Dropping it before the response arrives unregisters the request.
+pub struct PendingResponse {
+    id: String,
+    receiver: oneshot::Receiver<JsonRpcResponse>,
+    correlator: RequestCorrelator,
+    finished: bool,
+}
+
+impl PendingResponse {
+    /// ID of the request being waited on
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Wait up to `timeout` for the response
+    pub async fn wait(mut self, timeout: Duration) -> Result<JsonRpcResponse, TransportError> {
+        match crate::rt::timeout(timeout, &mut self.receiver).await {
+            Ok(Ok(response)) => {
+                self.finished = true;
+                Ok(response)
+            }
+            Ok(Err(_)) => {
+                self.finished = true;
+                Err(TransportError::ConnectionLost {
+                    transport_type: self.correlator.transport_type.to_string(),
+                    reason: format!(
+                        "Request {} was cancelled before a response arrived",
+                        self.id
+                    ),
+                })
+            }
+            Err(_) => Err(self.expire(timeout)),
+        }
+    }
+
+    /// Give up waiting after `waited`, recording a timeout.
+    ///
+    /// For callers that poll for the response themselves, e.g. with [`try_take`](Self::try_take).
+    pub fn expire(mut self, waited: Duration) -> TransportError {
+        self.correlator.finish(&self.id, true);
+        self.finished = true;
+        TransportError::TimeoutError {
+            transport_type: self.correlator.transport_type.to_string(),
+            reason: format!("Request {} timed out after {:?}", self.id, waited),
+        }
+    }
+
+    /// Take the response if it has already been delivered, without waiting
+    pub fn try_take(&mut self) -> Option<JsonRpcResponse> {
+        let response = self.receiver.try_recv().ok()?;
+        self.finished = true;
+        Some(response)
+    }
+}
+
+impl Drop for PendingResponse {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.correlator.finish(&self.id, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(id: &str) -> JsonRpcResponse {
+        JsonRpcResponse::success(id, json!({}))
+    }
+
+    #[tokio::test]
+    async fn test_response_reaches_waiter_and_duplicates_are_counted() {
+        let correlator = RequestCorrelator::new("test");
+        let pending = correlator.register("1").unwrap();
+        assert!(correlator.register("1").is_err());
+
+        assert!(correlator.complete(response("1")).is_none());
+        assert!(correlator.complete(response("1")).is_some());
+        assert!(correlator.complete(response("2")).is_some());
+
+        let delivered = pending.wait(Duration::from_secs(1)).await.unwrap();
+        assert_eq!(delivered.id.to_string(), "1");
+
+        let stats = correlator.stats();
+        assert_eq!(stats.registered, 1);
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.duplicate_requests, 1);
+        assert_eq!(stats.duplicate_responses, 1);
+        assert_eq!(stats.unmatched_responses, 1);
+        assert_eq!(stats.pending, 0);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_drop_and_cancel_release_entries() {
+        let correlator = RequestCorrelator::new("test");
+
+        let pending = correlator.register("slow").unwrap();
+        let error = pending.wait(Duration::from_millis(10)).await.unwrap_err();
+        assert!(matches!(error, TransportError::TimeoutError { .. }));
+
+        drop(correlator.register("abandoned").unwrap());
+
+        let pending = correlator.register("cancelled").unwrap();
+        correlator.cancel_all();
+        let error = pending.wait(Duration::from_secs(1)).await.unwrap_err();
+        assert!(matches!(error, TransportError::ConnectionLost { .. }));
+
+        let stats = correlator.stats();
+        assert_eq!(stats.timed_out, 1);
+        assert_eq!(stats.cancelled, 2);
+        assert_eq!(correlator.pending_count(), 0);
+    }
+}
@@ -0,0 +1,223 @@
+//! Deserialization that locates errors in large payloads.
+//!
+//! serde_json reports failures by line and column, which says little about a
+//! two-megabyte `tools/list` result sent as a single line. The functions here
+//! decode as usual and, only when that fails, decode again while tracking the
+//! path (with `serde_path_to_error`). The resulting
+//! [`PayloadError`] names the JSON pointer of the offending value and quotes
+//! the payload around it.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_core::decode;
+//! use mcp_core::messages::tools::ListToolsResponse;
+//!
+//! let payload = r#"{"tools": [{"name": "echo", "description": ""}, {"name": 7}]}"#;
+//! let error = decode::from_str::<ListToolsResponse>(payload).unwrap_err();
+//! assert!(error.to_string().contains("/tools/1/name"));
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{McpResult, PayloadError};
+
+/// Bytes of payload quoted on each side of a failure.
+const EXCERPT_RADIUS: usize = 40;
+
+/// Decode `payload`, locating any failure within it.
+pub fn from_str<T: DeserializeOwned>(payload: &str) -> McpResult<T> {
+    match serde_json::from_str(payload) {
+        Ok(value) => Ok(value),
+        Err(_) => Err(locate_in_str::<T>(payload).into()),
+    }
+}
+
+/// Decode `payload` bytes, locating any failure within them.
+pub fn from_slice<T: DeserializeOwned>(payload: &[u8]) -> McpResult<T> {
+    from_str(&String::from_utf8_lossy(payload))
+}
+
+/// Decode an already parsed `value`, locating any failure within it.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> McpResult<T> {
+    match T::deserialize(&value) {
+        Ok(decoded) => Ok(decoded),
+        Err(_) => Err(locate_in_value::<T>(&value).into()),
+    }
+}
+
+fn locate_in_str<T: DeserializeOwned>(payload: &str) -> PayloadError {
+    let mut deserializer = serde_json::Deserializer::from_str(payload);
+    let error = match serde_path_to_error::deserialize::<_, T>(&mut deserializer) {
+        // Trailing characters only show up once the value is complete
+        Ok(_) => match deserializer.end() {
+            Ok(()) => return unreachable_error::<T>(),
+            Err(error) => return payload_error::<T>(String::new(), &error, payload),
+        },
+        Err(error) => error,
+    };
+    payload_error::<T>(pointer(error.path()), error.inner(), payload)
+}
+
+fn payload_error<T>(pointer: String, error: &serde_json::Error, payload: &str) -> PayloadError {
+    PayloadError {
+        target: type_name::<T>(),
+        pointer,
+        reason: error.to_string(),
+        excerpt: excerpt(payload, error.line(), error.column()),
+    }
+}
+
+fn locate_in_value<T: DeserializeOwned>(value: &Value) -> PayloadError {
+    let error = match serde_path_to_error::deserialize::<_, T>(value) {
+        Ok(_) => return unreachable_error::<T>(),
+        Err(error) => error,
+    };
+    let pointer = pointer(error.path());
+    // The deepest part of the path that exists; a missing field points past it
+    let mut at = pointer.as_str();
+    let found = loop {
+        if let Some(found) = value.pointer(at) {
+            break found;
+        }
+        at = at.rsplit_once('/').map_or("", |(parent, _)| parent);
+    };
+    PayloadError {
+        target: type_name::<T>(),
+        pointer,
+        reason: error.inner().to_string(),
+        excerpt: truncate(&found.to_string(), 2 * EXCERPT_RADIUS),
+    }
+}
+
+/// The second, path-tracking decode succeeded where the first failed; only a
+/// non-deterministic `Deserialize` can do that
+fn unreachable_error<T>() -> PayloadError {
+    PayloadError {
+        target: type_name::<T>(),
+        pointer: String::new(),
+        reason: "decoding failed only on the first attempt".to_string(),
+        excerpt: String::new(),
+    }
+}
+
+/// JSON pointer for a serde path
+fn pointer(path: &serde_path_to_error::Path) -> String {
+    use serde_path_to_error::Segment;
+
+    let mut pointer = String::new();
+    for segment in path.iter() {
+        let token = match segment {
+            Segment::Seq { index } => index.to_string(),
+            Segment::Map { key } => key.replace('~', "~0").replace('/', "~1"),
+            Segment::Enum { variant } => variant.clone(),
+            Segment::Unknown => continue,
+        };
+        pointer.push('/');
+        pointer.push_str(&token);
+    }
+    pointer
+}
+
+/// Last segment of `T`'s name, without its module path and generics
+fn type_name<T>() -> String {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
+/// The payload around a serde_json position (1-based line, byte column),
+/// on one line and with `…` marking cut ends
+pub fn excerpt(payload: &str, line: usize, column: usize) -> String {
+    let line_start: usize = payload
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let at = (line_start + column.saturating_sub(1)).min(payload.len());
+
+    let mut start = at.saturating_sub(EXCERPT_RADIUS);
+    while !payload.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (at + EXCERPT_RADIUS).min(payload.len());
+    while !payload.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let window: String = payload[start..end]
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        window,
+        if end < payload.len() { "…" } else { "" }
+    )
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max).collect();
+    format!("{}…", cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::McpError;
+    use crate::messages::tools::ListToolsResponse;
+    use serde_json::json;
+
+    fn payload_error(error: McpError) -> PayloadError {
+        match error {
+            McpError::Payload(error) => error,
+            other => panic!("expected a payload error, got {other}"),
+        }
+    }
+
+    #[test]
+    fn test_error_located_in_long_line() {
+        let mut tools: Vec<Value> = (0..200)
+            .map(|i| json!({ "name": format!("tool_{i}"), "description": "" }))
+            .collect();
+        tools[150]["name"] = json!(150);
+        let payload = json!({ "tools": tools }).to_string();
+
+        let error = payload_error(from_str::<ListToolsResponse>(&payload).unwrap_err());
+        assert_eq!(error.target, "ListToolsResponse");
+        assert_eq!(error.pointer, "/tools/150/name");
+        assert!(error.excerpt.starts_with('…') && error.excerpt.ends_with('…'));
+        assert!(error.excerpt.contains("\"name\":150"));
+        assert!(error.excerpt.len() < 3 * EXCERPT_RADIUS);
+
+        let error =
+            payload_error(from_value::<ListToolsResponse>(json!({ "tools": tools })).unwrap_err());
+        assert_eq!(error.pointer, "/tools/150/name");
+        assert_eq!(error.excerpt, "150");
+    }
+
+    #[test]
+    fn test_missing_field_and_syntax_errors() {
+        let error = payload_error(
+            from_value::<ListToolsResponse>(json!({ "tools": [{ "description": "" }] }))
+                .unwrap_err(),
+        );
+        assert_eq!(error.pointer, "/tools/0");
+        assert!(error.reason.contains("missing field `name`"));
+        assert_eq!(error.excerpt, r#"{"description":""}"#);
+
+        let error = payload_error(from_str::<Value>("{\"a\": [1,\n 2,, 3]}").unwrap_err());
+        assert_eq!(error.pointer, "/a/2");
+        assert_eq!(error.excerpt, "{\"a\": [1,  2,, 3]}");
+        assert!(error
+            .to_string()
+            .starts_with("Value is invalid at /a/2: expected value"));
+
+        assert!(from_str::<Value>("{} trailing").is_err());
+    }
+}
@@ -0,0 +1,471 @@
+//! Differential testing across MCP protocol versions.
+//!
+//! Servers often advertise support for several protocol versions. This module
+//! connects to the same server once per version, captures what it advertises
+//! (capabilities, tool/resource/prompt catalogs) and the results of a sample
+//! of calls, then reports every place where the two sessions disagree.
+//!
+//! Catalog entries are compared by name (or URI) rather than position, so a
+//! server that merely reorders its tools is not reported.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_core::differential::{self, DifferentialOptions};
+//! use mcp_core::transport::TransportConfig;
+//!
+//! # async fn example() -> mcp_core::McpResult<()> {
+//! let config = TransportConfig::stdio("python", &["server.py"]);
+//! let report = differential::run(config, DifferentialOptions::default()).await?;
+//!
+//! for divergence in &report.divergences {
+//!     println!("{}", divergence);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::client::{McpClient, McpClientBuilder};
+use crate::error::McpResult;
+use crate::messages::{Implementation, ProtocolVersion};
+use crate::transport::TransportConfig;
+
+/// List methods captured for each capability.
+const CATALOGS: &[(&str, &str)] = &[
+    ("tools", "tools/list"),
+    ("resources", "resources/list"),
+    ("resources", "resources/templates/list"),
+    ("prompts", "prompts/list"),
+];
+
+/// Keys used to match catalog entries between sessions.
+const IDENTITY_KEYS: &[&str] = &["name", "uri", "uriTemplate"];
+
+/// A request issued in both sessions so its results can be compared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleCall {
+    /// JSON-RPC method
+    pub method: String,
+    /// Request parameters
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl SampleCall {
+    /// Create a sample call.
+    pub fn new(method: impl Into<String>, params: Value) -> Self {
+        Self {
+            method: method.into(),
+            params,
+        }
+    }
+
+    /// Label used in reports, e.g. `tools/call echo`.
+    pub fn label(&self) -> String {
+        let target = self
+            .params
+            .get("name")
+            .or_else(|| self.params.get("uri"))
+            .and_then(Value::as_str);
+
+        match target {
+            Some(target) => format!("{} {}", self.method, target),
+            None => self.method.clone(),
+        }
+    }
+}
+
+/// Options for a differential run.
+#[derive(Debug, Clone)]
+pub struct DifferentialOptions {
+    /// Protocol version requested by the first session
+    pub left: ProtocolVersion,
+    /// Protocol version requested by the second session
+    pub right: ProtocolVersion,
+    /// Calls issued in both sessions
+    pub calls: Vec<SampleCall>,
+    /// Client identity sent during initialization
+    pub client_info: Implementation,
+    /// Deadline for connecting and for each request
+    pub timeout: Duration,
+}
+
+impl Default for DifferentialOptions {
+    fn default() -> Self {
+        Self {
+            left: ProtocolVersion::V2024_11_05,
+            right: ProtocolVersion::V2025_03_26,
+            calls: vec![SampleCall::new("ping", json!({}))],
+            client_info: Implementation::new("mcp-differential", crate::VERSION),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Everything a server exposed in one session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionSnapshot {
+    /// Version requested in `initialize`
+    pub requested: ProtocolVersion,
+    /// Version the server answered with
+    pub negotiated: ProtocolVersion,
+    /// Server implementation details
+    pub server: Implementation,
+    /// Advertised server capabilities
+    pub capabilities: Value,
+    /// Results of the list methods, keyed by method
+    pub catalogs: BTreeMap<String, Value>,
+    /// Results of the sample calls, keyed by [`SampleCall::label`]
+    pub calls: BTreeMap<String, Value>,
+}
+
+/// A point where the two sessions disagree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    /// What was compared (`capabilities`, `tools/list`, `tools/call echo`, ...)
+    pub area: String,
+    /// JSON pointer to the differing value within the area
+    pub path: String,
+    /// Value in the first session, absent if missing
+    pub left: Option<Value>,
+    /// Value in the second session, absent if missing
+    pub right: Option<Value>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "<missing>".to_string(),
+        };
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(
+            f,
+            "{} {}: {} -> {}",
+            self.area,
+            path,
+            show(&self.left),
+            show(&self.right)
+        )
+    }
+}
+
+/// Result of comparing two sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifferentialReport {
+    /// Session using [`DifferentialOptions::left`]
+    pub left: VersionSnapshot,
+    /// Session using [`DifferentialOptions::right`]
+    pub right: VersionSnapshot,
+    /// Differences between the sessions
+    pub divergences: Vec<Divergence>,
+    /// Observations that are not divergences, such as version fallbacks
+    pub notes: Vec<String>,
+}
+
+impl DifferentialReport {
+    /// Whether both sessions behaved identically.
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Connect once per protocol version and compare the sessions.
+pub async fn run(
+    transport: TransportConfig,
+    options: DifferentialOptions,
+) -> McpResult<DifferentialReport> {
+    let left = snapshot(transport.clone(), options.left.clone(), &options).await?;
+    let right = snapshot(transport, options.right.clone(), &options).await?;
+
+    let notes = [&left, &right]
+        .iter()
+        .filter(|s| s.negotiated != s.requested)
+        .map(|s| {
+            format!(
+                "server answered {} when {} was requested",
+                s.negotiated, s.requested
+            )
+        })
+        .collect();
+
+    Ok(DifferentialReport {
+        divergences: compare(&left, &right),
+        left,
+        right,
+        notes,
+    })
+}
+
+/// Capture one session at the given protocol version.
+pub async fn snapshot(
+    transport: TransportConfig,
+    version: ProtocolVersion,
+    options: &DifferentialOptions,
+) -> McpResult<VersionSnapshot> {
+    let mut client = McpClientBuilder::new()
+        .transport(transport)
+        .protocol_version(version.clone())
+        .connect_deadline(options.timeout)
+        .max_retries(0)
+        .build()
+        .await?;
+
+    let info = client.connect(options.client_info.clone()).await?;
+    let capabilities = serde_json::to_value(&info.capabilities)?;
+
+    let mut catalogs = BTreeMap::new();
+    for (capability, method) in CATALOGS {
+        if capabilities.get(capability).is_some() {
+            catalogs.insert(
+                method.to_string(),
+                call(&mut client, method, json!({}), options.timeout).await,
+            );
+        }
+    }
+
+    let mut calls = BTreeMap::new();
+    for sample in &options.calls {
+        let outcome = call(
+            &mut client,
+            &sample.method,
+            sample.params.clone(),
+            options.timeout,
+        )
+        .await;
+        calls.insert(sample.label(), outcome);
+    }
+
+    if let Err(e) = client.disconnect().await {
+        tracing::debug!("Failed to disconnect differential session: {}", e);
+    }
+
+    Ok(VersionSnapshot {
+        requested: version,
+        negotiated: info.protocol_version,
+        server: info.implementation,
+        capabilities,
+        catalogs,
+        calls,
+    })
+}
+
+/// Issue a request, folding errors into the recorded outcome.
+async fn call(client: &mut McpClient, method: &str, params: Value, timeout: Duration) -> Value {
+    match client
+        .send_request_with_timeout(method, params, timeout)
+        .await
+    {
+        Ok(response) => match response.error {
+            Some(error) => json!({ "error": error }),
+            None => response.result.unwrap_or(Value::Null),
+        },
+        Err(e) => json!({ "error": { "message": e.to_string() } }),
+    }
+}
+
+/// List every difference between two snapshots.
+pub fn compare(left: &VersionSnapshot, right: &VersionSnapshot) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+
+    if left.server != right.server {
+        diff_values(
+            "serverInfo",
+            String::new(),
+            Some(&json!(left.server)),
+            Some(&json!(right.server)),
+            &mut divergences,
+        );
+    }
+
+    diff_values(
+        "capabilities",
+        String::new(),
+        Some(&left.capabilities),
+        Some(&right.capabilities),
+        &mut divergences,
+    );
+
+    for (areas_left, areas_right) in [
+        (&left.catalogs, &right.catalogs),
+        (&left.calls, &right.calls),
+    ] {
+        let mut areas: Vec<&String> = areas_left.keys().chain(areas_right.keys()).collect();
+        areas.sort();
+        areas.dedup();
+
+        for area in areas {
+            let a = areas_left.get(area).map(normalize);
+            let b = areas_right.get(area).map(normalize);
+            diff_values(
+                area,
+                String::new(),
+                a.as_ref(),
+                b.as_ref(),
+                &mut divergences,
+            );
+        }
+    }
+
+    divergences
+}
+
+/// List the differences between two results of the same request, matching
+/// catalog entries by name rather than position.
+pub fn diff_results(area: &str, left: &Value, right: &Value) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    diff_values(
+        area,
+        String::new(),
+        Some(&normalize(left)),
+        Some(&normalize(right)),
+        &mut divergences,
+    );
+    divergences
+}
+
+fn diff_values(
+    area: &str,
+    path: String,
+    left: Option<&Value>,
+    right: Option<&Value>,
+    out: &mut Vec<Divergence>,
+) {
+    if let (Some(Value::Object(a)), Some(Value::Object(b))) = (left, right) {
+        let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+            diff_values(area, child, a.get(key), b.get(key), out);
+        }
+        return;
+    }
+
+    if left != right {
+        out.push(Divergence {
+            area: area.to_string(),
+            path,
+            left: left.cloned(),
+            right: right.cloned(),
+        });
+    }
+}
+
+/// Turn arrays of named entries into objects keyed by their identity so
+/// entries are matched regardless of order.
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), normalize(value)))
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let identity = IDENTITY_KEYS.iter().find(|key| {
+                !items.is_empty()
+                    && items
+                        .iter()
+                        .all(|item| item.get(**key).is_some_and(Value::is_string))
+            });
+
+            match identity {
+                Some(key) => {
+                    let mut keyed = Map::new();
+                    for item in items {
+                        let id = item[*key].as_str().unwrap_or_default().to_string();
+                        keyed.insert(id, normalize(item));
+                    }
+                    Value::Object(keyed)
+                }
+                None => Value::Array(items.iter().map(normalize).collect()),
+            }
+        }
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_with(tools: Value, capabilities: Value) -> VersionSnapshot {
+        let mut catalogs = BTreeMap::new();
+        catalogs.insert("tools/list".to_string(), json!({ "tools": tools }));
+        VersionSnapshot {
+            requested: ProtocolVersion::V2024_11_05,
+            negotiated: ProtocolVersion::V2024_11_05,
+            server: Implementation::new("server", "1.0.0"),
+            capabilities,
+            catalogs,
+            calls: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_reordered_catalog_is_consistent() {
+        let left = snapshot_with(
+            json!([{ "name": "a" }, { "name": "b" }]),
+            json!({ "tools": {} }),
+        );
+        let right = snapshot_with(
+            json!([{ "name": "b" }, { "name": "a" }]),
+            json!({ "tools": {} }),
+        );
+
+        assert!(compare(&left, &right).is_empty());
+    }
+
+    #[test]
+    fn test_divergences_are_located() {
+        let left = snapshot_with(
+            json!([{ "name": "read/file", "description": "Read" }]),
+            json!({ "tools": {} }),
+        );
+        let mut right = snapshot_with(
+            json!([
+                { "name": "read/file", "description": "Read a file" },
+                { "name": "write" }
+            ]),
+            json!({ "tools": { "listChanged": true } }),
+        );
+        right.calls.insert("ping".to_string(), json!({}));
+
+        let divergences = compare(&left, &right);
+        let located: Vec<(&str, &str)> = divergences
+            .iter()
+            .map(|d| (d.area.as_str(), d.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            located,
+            vec![
+                ("capabilities", "/tools/listChanged"),
+                ("tools/list", "/tools/read~1file/description"),
+                ("tools/list", "/tools/write"),
+                ("ping", ""),
+            ]
+        );
+        assert!(divergences[2].left.is_none());
+        assert_eq!(divergences[3].to_string(), "ping /: <missing> -> {}");
+    }
+
+    #[test]
+    fn test_sample_call_label() {
+        let call = SampleCall::new("tools/call", json!({ "name": "echo", "arguments": {} }));
+        assert_eq!(call.label(), "tools/call echo");
+        assert_eq!(SampleCall::new("ping", Value::Null).label(), "ping");
+    }
+}
@@ -0,0 +1,322 @@
+//! Local network discovery of MCP servers via mDNS/zeroconf.
+//!
+//! MCP servers exposing an HTTP transport can advertise themselves with a
+//! DNS-SD service record of type [`MCP_SERVICE_TYPE`]. The TXT record carries
+//! the details needed to build a [`TransportConfig`]:
+//!
+//! | Key         | Meaning                                      | Default    |
+//! |-------------|----------------------------------------------|------------|
+//! | `path`      | Endpoint path on the server                  | `/mcp`     |
+//! | `transport` | `http-sse` or `http-stream`                  | `http-sse` |
+//! | `scheme`    | `http` or `https`                            | `http`     |
+//!
+//! Any other TXT entries are preserved in [`DiscoveredServer::properties`].
+//!
+//! Servers announce themselves with [`advertise`], which keeps the record
+//! published until the returned [`Advertisement`] is dropped.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_probe_core::discovery;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> mcp_probe_core::McpResult<()> {
+//! for server in discovery::browse(Duration::from_secs(3)).await? {
+//!     println!("{} -> {}", server.name, server.url());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ConfigError, McpResult, TransportError};
+use crate::transport::TransportConfig;
+
+/// DNS-SD service type advertised by MCP servers.
+pub const MCP_SERVICE_TYPE: &str = "_mcp._tcp.local.";
+
+/// An MCP server found on the local network.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveredServer {
+    /// Service instance name (e.g. `github-tools`)
+    pub name: String,
+    /// Advertised host name (without trailing dot)
+    pub hostname: String,
+    /// Resolved addresses for the host
+    pub addresses: Vec<IpAddr>,
+    /// Service port
+    pub port: u16,
+    /// URL scheme (`http` or `https`)
+    pub scheme: String,
+    /// Endpoint path on the server
+    pub path: String,
+    /// Transport type to use (`http-sse` or `http-stream`)
+    pub transport: String,
+    /// Remaining TXT record properties
+    pub properties: HashMap<String, String>,
+}
+
+impl DiscoveredServer {
+    /// Build a discovered server from the raw pieces of a resolved service record.
+    pub fn from_record(
+        fullname: &str,
+        hostname: &str,
+        addresses: impl IntoIterator<Item = IpAddr>,
+        port: u16,
+        mut properties: HashMap<String, String>,
+    ) -> Self {
+        let name = fullname
+            .strip_suffix(MCP_SERVICE_TYPE)
+            .unwrap_or(fullname)
+            .trim_end_matches('.')
+            .to_string();
+
+        let mut addresses: Vec<IpAddr> = addresses.into_iter().collect();
+        // Prefer IPv4 so generated URLs stay readable
+        addresses.sort_by_key(|addr| (addr.is_ipv6(), *addr));
+
+        let mut path = properties
+            .remove("path")
+            .unwrap_or_else(|| "/mcp".to_string());
+        if !path.starts_with('/') {
+            path.insert(0, '/');
+        }
+
+        Self {
+            name,
+            hostname: hostname.trim_end_matches('.').to_string(),
+            addresses,
+            port,
+            scheme: properties
+                .remove("scheme")
+                .unwrap_or_else(|| "http".to_string()),
+            path,
+            transport: properties
+                .remove("transport")
+                .unwrap_or_else(|| "http-sse".to_string()),
+            properties,
+        }
+    }
+
+    fn from_service_info(info: &ServiceInfo) -> Self {
+        Self::from_record(
+            info.get_fullname(),
+            info.get_hostname(),
+            info.get_addresses().iter().copied(),
+            info.get_port(),
+            info.get_properties().clone().into_property_map_str(),
+        )
+    }
+
+    /// The TXT record announcing this server, the inverse of [`from_record`](Self::from_record).
+    pub fn txt_record(&self) -> HashMap<String, String> {
+        let mut record = self.properties.clone();
+        record.insert("path".to_string(), self.path.clone());
+        record.insert("transport".to_string(), self.transport.clone());
+        record.insert("scheme".to_string(), self.scheme.clone());
+        record
+    }
+
+    /// Endpoint URL for this server, using the first resolved address if any.
+    pub fn url(&self) -> String {
+        let host = match self.addresses.first() {
+            Some(IpAddr::V4(addr)) => addr.to_string(),
+            Some(IpAddr::V6(addr)) => format!("[{}]", addr),
+            None => self.hostname.clone(),
+        };
+        format!("{}://{}:{}{}", self.scheme, host, self.port, self.path)
+    }
+
+    /// Create a transport configuration that connects to this server.
+    pub fn to_transport_config(&self) -> McpResult<TransportConfig> {
+        match self.transport.as_str() {
+            "http-sse" => TransportConfig::http_sse(self.url()),
+            "http-stream" => TransportConfig::http_stream(self.url()),
+            other => Err(ConfigError::InvalidValue {
+                parameter: "transport".to_string(),
+                value: other.to_string(),
+                reason: format!(
+                    "Server '{}' advertises an unsupported transport (expected http-sse or http-stream)",
+                    self.name
+                ),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Browse the local network for MCP servers.
+///
+/// Listens for service announcements for the given duration and returns every
+/// server that was resolved and not withdrawn in the meantime, sorted by name.
+pub async fn browse(duration: Duration) -> McpResult<Vec<DiscoveredServer>> {
+    let daemon = ServiceDaemon::new().map_err(discovery_error)?;
+    let receiver = daemon.browse(MCP_SERVICE_TYPE).map_err(discovery_error)?;
+
+    let mut found: HashMap<String, DiscoveredServer> = HashMap::new();
+    let deadline = tokio::time::Instant::now() + duration;
+
+    loop {
+        match tokio::time::timeout_at(deadline, receiver.recv_async()).await {
+            Ok(Ok(ServiceEvent::ServiceResolved(info))) => {
+                let server = DiscoveredServer::from_service_info(&info);
+                tracing::debug!("Discovered MCP server {} at {}", server.name, server.url());
+                found.insert(info.get_fullname().to_string(), server);
+            }
+            Ok(Ok(ServiceEvent::ServiceRemoved(_, fullname))) => {
+                tracing::debug!("MCP server withdrawn: {}", fullname);
+                found.remove(&fullname);
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(_)) | Err(_) => break,
+        }
+    }
+
+    if let Err(e) = daemon.shutdown() {
+        tracing::debug!("Failed to shut down mDNS daemon: {}", e);
+    }
+
+    let mut servers: Vec<DiscoveredServer> = found.into_values().collect();
+    servers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(servers)
+}
+
+/// An MCP server announced on the local network.
+///
+/// The announcement is withdrawn when this is dropped.
+pub struct Advertisement {
+    daemon: ServiceDaemon,
+    server: DiscoveredServer,
+}
+
+impl Advertisement {
+    /// The server as browsers will see it.
+    pub fn server(&self) -> &DiscoveredServer {
+        &self.server
+    }
+}
+
+impl Drop for Advertisement {
+    fn drop(&mut self) {
+        let fullname = format!("{}.{}", self.server.name, MCP_SERVICE_TYPE);
+        if let Err(e) = self.daemon.unregister(&fullname) {
+            tracing::debug!("Failed to withdraw {}: {}", fullname, e);
+        }
+        if let Err(e) = self.daemon.shutdown() {
+            tracing::debug!("Failed to shut down mDNS daemon: {}", e);
+        }
+    }
+}
+
+/// Announce an MCP server listening on `port` of this machine.
+///
+/// `properties` become the TXT record, so `path`, `transport` and `scheme`
+/// can be set there; see the [module documentation](self) for defaults. The
+/// record lists every address of the machine and follows interface changes.
+pub fn advertise(
+    name: &str,
+    port: u16,
+    properties: HashMap<String, String>,
+) -> McpResult<Advertisement> {
+    // Instance names may hold any characters, host names may not
+    let host: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let server =
+        DiscoveredServer::from_record(name, &format!("{}.local.", host), [], port, properties);
+    let info = ServiceInfo::new(
+        MCP_SERVICE_TYPE,
+        &server.name,
+        &format!("{}.", server.hostname),
+        (),
+        port,
+        server.txt_record(),
+    )
+    .map_err(discovery_error)?
+    .enable_addr_auto();
+
+    let daemon = ServiceDaemon::new().map_err(discovery_error)?;
+    daemon.register(info).map_err(discovery_error)?;
+    tracing::debug!("Advertising MCP server {} on port {}", server.name, port);
+    Ok(Advertisement { daemon, server })
+}
+
+fn discovery_error(error: mdns_sd::Error) -> crate::error::McpError {
+    TransportError::NetworkError {
+        transport_type: "mdns".to_string(),
+        reason: error.to_string(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(properties: &[(&str, &str)]) -> DiscoveredServer {
+        DiscoveredServer::from_record(
+            "github-tools._mcp._tcp.local.",
+            "devbox.local.",
+            ["fe80::1".parse().unwrap(), "192.168.1.20".parse().unwrap()],
+            8080,
+            properties
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_record_defaults() {
+        let server = record(&[("version", "2025-03-26")]);
+        assert_eq!(server.name, "github-tools");
+        assert_eq!(server.hostname, "devbox.local");
+        assert_eq!(server.url(), "http://192.168.1.20:8080/mcp");
+        assert_eq!(server.transport, "http-sse");
+        assert_eq!(server.properties.get("version").unwrap(), "2025-03-26");
+    }
+
+    #[test]
+    fn test_record_txt_overrides() {
+        let server = record(&[
+            ("path", "sse"),
+            ("scheme", "https"),
+            ("transport", "http-stream"),
+        ]);
+        assert_eq!(server.url(), "https://192.168.1.20:8080/sse");
+
+        let config = server.to_transport_config().unwrap();
+        assert_eq!(config.transport_type(), "http-stream");
+    }
+
+    #[test]
+    fn test_txt_record_round_trip() {
+        let server = record(&[
+            ("path", "/sse"),
+            ("transport", "http-stream"),
+            ("team", "infra"),
+        ]);
+        let found = DiscoveredServer::from_record(
+            "github-tools._mcp._tcp.local.",
+            "devbox.local.",
+            server.addresses.clone(),
+            server.port,
+            server.txt_record(),
+        );
+        assert_eq!(found, server);
+    }
+
+    #[test]
+    fn test_unsupported_transport() {
+        let server = record(&[("transport", "stdio")]);
+        assert!(server.to_transport_config().is_err());
+    }
+}
@@ -12,9 +12,12 @@
 //! - **Debuggable**: Include sufficient context for debugging
 //! - **User-friendly**: Format appropriately for end-user display
 
+use std::fmt;
 use std::time::Duration;
 use thiserror::Error;
 
+use crate::messages::JsonRpcError;
+
 /// The main error type for all MCP operations.
 ///
 /// This enum covers all possible error conditions that can occur during
@@ -71,6 +74,11 @@ pub enum McpError {
         source: serde_json::Error,
     },
 
+    /// A payload that did not match the type it was decoded into, located
+    /// within the payload
+    #[error("Serialization error: {0}")]
+    Payload(#[from] PayloadError),
+
     /// IO errors (file operations, network errors, etc.)
     #[error("IO error: {source}")]
     Io {
@@ -186,6 +194,40 @@ pub enum TransportError {
         transport_type: String,
         reason: String,
     },
+
+    /// Container runtime failed to start or run the server container
+    #[error("Container error ({runtime}): {reason}")]
+    ContainerError { runtime: String, reason: String },
+
+    /// Sandbox tool failed to set up the server's sandbox
+    #[error("Sandbox error ({tool}): {reason}")]
+    SandboxError { tool: String, reason: String },
+
+    /// An origin, host or address failed the transport's origin policy
+    #[error("Origin check failed: {0}")]
+    OriginRejected(#[from] OriginError),
+
+    /// A message was larger than the transport allows
+    #[error("Message too large ({transport_type}): {size} bytes exceeds the {limit}-byte limit")]
+    MessageTooLarge {
+        transport_type: String,
+        size: usize,
+        limit: usize,
+    },
+
+    /// The server turned the request away for now, possibly saying how long
+    /// to wait before sending it again
+    #[error("Server asked to retry later: {reason}")]
+    Throttled {
+        status_code: u16,
+        retry_after: Option<Duration>,
+        reason: String,
+    },
+
+    /// The circuit breaker in front of the server is open, so the request
+    /// was not sent
+    #[error("Circuit open for {name}; probing again in {retry_in:?}")]
+    CircuitOpen { name: String, retry_in: Duration },
 }
 
 /// Protocol-level errors related to MCP message handling.
@@ -224,7 +266,11 @@ pub enum ProtocolError {
 
     /// Server returned an error response
     #[error("Server error {code}: {message}")]
-    ServerError { code: i32, message: String },
+    ServerError {
+        code: ServerErrorCode,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
 
     /// Protocol state violation (e.g., calling method before initialization)
     #[error("Protocol state violation: {reason}")]
@@ -270,6 +316,78 @@ pub enum ProtocolError {
     ResponseBlocked { reason: String },
 }
 
+/// Error codes a server answers a failed request with.
+///
+/// Covers the JSON-RPC 2.0 codes and the ones MCP assigns in the
+/// implementation-defined range, so callers can match on what went wrong
+/// instead of parsing messages. Other codes are kept in
+/// [`ServerErrorCode::Other`]. Displays as the numeric code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerErrorCode {
+    /// The server could not parse the JSON it received (-32700)
+    ParseError,
+    /// The message was not a valid JSON-RPC request (-32600)
+    InvalidRequest,
+    /// The server does not implement the method (-32601)
+    MethodNotFound,
+    /// The method's parameters were invalid (-32602)
+    InvalidParams,
+    /// The server failed while handling the request (-32603)
+    InternalError,
+    /// The connection closed before the request completed (-32000)
+    ConnectionClosed,
+    /// The request timed out on the server (-32001)
+    RequestTimeout,
+    /// The requested resource does not exist (-32002)
+    ResourceNotFound,
+    /// Any other code
+    Other(i32),
+}
+
+impl ServerErrorCode {
+    /// Classify a numeric error code.
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            -32700 => Self::ParseError,
+            -32600 => Self::InvalidRequest,
+            -32601 => Self::MethodNotFound,
+            -32602 => Self::InvalidParams,
+            -32603 => Self::InternalError,
+            -32000 => Self::ConnectionClosed,
+            -32001 => Self::RequestTimeout,
+            -32002 => Self::ResourceNotFound,
+            other => Self::Other(other),
+        }
+    }
+
+    /// The numeric code sent on the wire.
+    pub fn code(self) -> i32 {
+        match self {
+            Self::ParseError => -32700,
+            Self::InvalidRequest => -32600,
+            Self::MethodNotFound => -32601,
+            Self::InvalidParams => -32602,
+            Self::InternalError => -32603,
+            Self::ConnectionClosed => -32000,
+            Self::RequestTimeout => -32001,
+            Self::ResourceNotFound => -32002,
+            Self::Other(code) => code,
+        }
+    }
+}
+
+impl From<i32> for ServerErrorCode {
+    fn from(code: i32) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl fmt::Display for ServerErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
 /// Validation errors for MCP capabilities and schemas.
 ///
 /// These errors occur during validation of server capabilities,
@@ -371,6 +489,68 @@ pub enum ConfigError {
     /// Conflicting configuration parameters
     #[error("Conflicting configuration: {reason}")]
     Conflict { reason: String },
+
+    /// Server prerequisites are not satisfied
+    #[error("Unmet prerequisites: {}", issues.join("; "))]
+    UnmetPrerequisites { issues: Vec<String> },
+}
+
+/// A payload that failed to deserialize, with where in the payload it failed.
+///
+/// Produced by [`decode`](crate::decode); see there for how the location is found.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error(
+    "{target} is invalid at {}: {reason} (near `{excerpt}`)",
+    if pointer.is_empty() { "the top level" } else { pointer }
+)]
+pub struct PayloadError {
+    /// Type the payload was decoded into
+    pub target: String,
+    /// JSON pointer of the value that failed (empty for the whole payload)
+    pub pointer: String,
+    /// What serde reported
+    pub reason: String,
+    /// The payload around the failure
+    pub excerpt: String,
+}
+
+/// Why a request or connection failed an
+/// [`OriginPolicy`](crate::transport::origin::OriginPolicy).
+///
+/// Serializes with a `reason` tag, for use as the `data` of a JSON-RPC error.
+#[derive(Error, Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum OriginError {
+    /// The Origin header names an origin outside the allowed list
+    #[error("Origin '{origin}' is not allowed")]
+    OriginNotAllowed {
+        /// Origin as sent
+        origin: String,
+    },
+
+    /// The request carried no Origin header and the policy requires one
+    #[error("Request has no Origin header")]
+    MissingOrigin,
+
+    /// The Host header names a host outside the allowed list
+    #[error("Host '{host}' is not allowed")]
+    HostNotAllowed {
+        /// Host as sent
+        host: String,
+    },
+
+    /// The request carried no Host header
+    #[error("Request has no Host header")]
+    MissingHost,
+
+    /// A name that is not a loopback name resolved to a loopback address
+    #[error("'{host}' resolved to loopback address {address}; possible DNS rebinding")]
+    Rebinding {
+        /// Host name that was resolved
+        host: String,
+        /// Address it resolved to
+        address: String,
+    },
 }
 
 /// Convenience type alias for Results using McpError.
@@ -443,10 +623,20 @@ impl McpError {
             McpError::Validation(_) => false,
             McpError::Config(_) => false,
             McpError::Serialization { .. } => false,
+            McpError::Payload(_) => false,
             McpError::Internal { .. } => false,
         }
     }
 
+    /// How long the server asked the client to wait before retrying, if it
+    /// said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            McpError::Transport(TransportError::Throttled { retry_after, .. }) => *retry_after,
+            _ => None,
+        }
+    }
+
     /// Get the error category for this error.
     ///
     /// This is useful for error reporting and metrics collection.
@@ -458,7 +648,7 @@ impl McpError {
             McpError::Auth(_) => "auth",
             McpError::Timeout { .. } => "timeout",
             McpError::Config(_) => "config",
-            McpError::Serialization { .. } => "serialization",
+            McpError::Serialization { .. } | McpError::Payload(_) => "serialization",
             McpError::Io { .. } => "io",
             McpError::Internal { .. } => "internal",
         }
@@ -487,10 +677,39 @@ impl TransportError {
             TransportError::InvalidConfig { .. } => false,
             TransportError::NotConnected { .. } => false,
             TransportError::SerializationError { .. } => false,
+            TransportError::ContainerError { .. } => false,
+            TransportError::SandboxError { .. } => false,
+            TransportError::OriginRejected(_) => false,
+            TransportError::MessageTooLarge { .. } => false,
+            TransportError::Throttled { .. } => true,
+            TransportError::CircuitOpen { .. } => false,
         }
     }
 }
 
+impl From<OriginError> for McpError {
+    fn from(err: OriginError) -> Self {
+        McpError::Transport(err.into())
+    }
+}
+
+impl From<JsonRpcError> for ProtocolError {
+    fn from(error: JsonRpcError) -> Self {
+        ProtocolError::ServerError {
+            code: ServerErrorCode::from_code(error.code),
+            message: error.message,
+            data: error.data,
+        }
+    }
+}
+
+impl From<JsonRpcError> for McpError {
+    fn from(error: JsonRpcError) -> Self {
+        McpError::Protocol(error.into())
+    }
+}
+
+#[cfg(feature = "http")]
 impl From<reqwest::Error> for McpError {
     fn from(err: reqwest::Error) -> Self {
         if err.is_timeout() {
@@ -514,6 +733,7 @@ impl From<reqwest::Error> for McpError {
     }
 }
 
+#[cfg(feature = "http")]
 impl From<url::ParseError> for McpError {
     fn from(err: url::ParseError) -> Self {
         McpError::Config(ConfigError::InvalidValue {
@@ -576,4 +796,40 @@ mod tests {
         };
         assert!(!invalid_config.is_retryable());
     }
+
+    #[test]
+    fn test_json_rpc_errors_are_typed() {
+        let error = McpError::from(JsonRpcError::new(
+            -32002,
+            "Resource not found",
+            Some(serde_json::json!({ "uri": "file:///missing" })),
+        ));
+        match &error {
+            McpError::Protocol(ProtocolError::ServerError {
+                code: ServerErrorCode::ResourceNotFound,
+                data: Some(data),
+                ..
+            }) => assert_eq!(data["uri"], "file:///missing"),
+            other => panic!("Expected a resource not found error, got {:?}", other),
+        }
+        assert_eq!(
+            error.to_string(),
+            "Protocol error: Server error -32002: Resource not found"
+        );
+        assert!(!error.is_retryable());
+
+        for code in [
+            -32700, -32600, -32601, -32602, -32603, -32000, -32001, -32002, -32050, 7,
+        ] {
+            assert_eq!(ServerErrorCode::from_code(code).code(), code);
+        }
+        assert_eq!(
+            ServerErrorCode::from(-32601),
+            ServerErrorCode::MethodNotFound
+        );
+        assert_eq!(
+            ServerErrorCode::from(-32050),
+            ServerErrorCode::Other(-32050)
+        );
+    }
 }
@@ -0,0 +1,425 @@
+//! Protection against notification floods.
+//!
+//! A buggy server can emit thousands of `list_changed` notifications per
+//! second. [`FloodGuard`] limits how many notifications of each method pass
+//! per window. The rest of a burst is coalesced: when the window closes, the
+//! latest suppressed notification is delivered once together with a
+//! [`FloodEvent::Coalesced`] count. A method that keeps flooding for several
+//! windows in a row is muted for a while, and the operator is told about it.
+//!
+//! The guard does no I/O and takes the current time as an argument, so it can
+//! sit in any forwarding loop. Call [`FloodGuard::tick`] periodically so that
+//! coalesced notifications are not held back until the method is seen again.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::flood::{FloodConfig, FloodGuard, FloodOutput};
+//! use std::time::Instant;
+//!
+//! let mut guard = FloodGuard::new(FloodConfig::default());
+//! let now = Instant::now();
+//! for output in guard.admit("notifications/tools/list_changed", "payload", now) {
+//!     match output {
+//!         FloodOutput::Deliver(payload) => println!("forward {}", payload),
+//!         FloodOutput::Event(event) => eprintln!("{}", event),
+//!     }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Rate limits for server notifications.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FloodConfig {
+    /// Notifications of one method delivered per window before coalescing
+    pub max_per_window: u32,
+
+    /// Length of a rate window
+    #[serde(with = "humantime_serde")]
+    pub window: Duration,
+
+    /// Limits for specific methods, overriding `max_per_window`
+    #[serde(default)]
+    pub limits: HashMap<String, u32>,
+
+    /// Consecutive flooded windows after which a method is muted
+    pub mute_after: u32,
+
+    /// How long a flooding method stays muted
+    #[serde(with = "humantime_serde")]
+    pub mute_for: Duration,
+}
+
+impl Default for FloodConfig {
+    fn default() -> Self {
+        Self {
+            max_per_window: 20,
+            window: Duration::from_secs(1),
+            limits: HashMap::new(),
+            mute_after: 5,
+            mute_for: Duration::from_secs(30),
+        }
+    }
+}
+
+impl FloodConfig {
+    /// Limit per window for notifications grouped under `key`.
+    pub fn limit_for(&self, key: &str) -> u32 {
+        let method = key.split_once('#').map_or(key, |(method, _)| method);
+        self.limits
+            .get(method)
+            .copied()
+            .unwrap_or(self.max_per_window)
+    }
+}
+
+/// Something the operator should know about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FloodEvent {
+    /// A burst was collapsed into its latest notification
+    Coalesced {
+        /// Method (and progress token) of the burst
+        key: String,
+        /// Notifications suppressed in the window
+        count: u32,
+    },
+    /// A method kept flooding and is now dropped entirely
+    Muted {
+        /// Method (and progress token) that was muted
+        key: String,
+        /// Notifications seen in the window that triggered the mute
+        rate: u32,
+        /// How long the mute lasts
+        duration: Duration,
+    },
+    /// A mute expired
+    Unmuted {
+        /// Method (and progress token) that was unmuted
+        key: String,
+        /// Notifications dropped while muted
+        dropped: u64,
+    },
+}
+
+impl FloodEvent {
+    /// Whether the event calls for operator attention rather than information.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, FloodEvent::Muted { .. })
+    }
+}
+
+impl fmt::Display for FloodEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloodEvent::Coalesced { key, count } => {
+                write!(f, "Coalesced {} {} notification(s) into one", count, key)
+            }
+            FloodEvent::Muted {
+                key,
+                rate,
+                duration,
+            } => write!(
+                f,
+                "Server is flooding {} ({} in one window); muted for {}s",
+                key,
+                rate,
+                duration.as_secs()
+            ),
+            FloodEvent::Unmuted { key, dropped } => write!(
+                f,
+                "Unmuted {} after dropping {} notification(s)",
+                key, dropped
+            ),
+        }
+    }
+}
+
+/// What the caller should do after admitting a notification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FloodOutput<T> {
+    /// Forward this notification
+    Deliver(T),
+    /// Report this to the operator
+    Event(FloodEvent),
+}
+
+#[derive(Debug)]
+struct KeyState<T> {
+    window_start: Instant,
+    seen: u32,
+    suppressed: u32,
+    latest: Option<T>,
+    flooded_windows: u32,
+    muted_until: Option<Instant>,
+    dropped: u64,
+}
+
+impl<T> KeyState<T> {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            seen: 0,
+            suppressed: 0,
+            latest: None,
+            flooded_windows: 0,
+            muted_until: None,
+            dropped: 0,
+        }
+    }
+}
+
+/// Per-method notification rate limiter with coalescing and muting.
+#[derive(Debug)]
+pub struct FloodGuard<T> {
+    config: FloodConfig,
+    keys: HashMap<String, KeyState<T>>,
+}
+
+impl<T> FloodGuard<T> {
+    /// Create a guard with the given limits.
+    pub fn new(config: FloodConfig) -> Self {
+        Self {
+            config,
+            keys: HashMap::new(),
+        }
+    }
+
+    /// The limits in effect.
+    pub fn config(&self) -> &FloodConfig {
+        &self.config
+    }
+
+    /// Whether notifications grouped under `key` are currently muted.
+    pub fn is_muted(&self, key: &str) -> bool {
+        self.keys
+            .get(key)
+            .is_some_and(|state| state.muted_until.is_some())
+    }
+
+    /// Admit a notification grouped under `key` (see [`flood_key`]).
+    pub fn admit(&mut self, key: &str, item: T, now: Instant) -> Vec<FloodOutput<T>> {
+        let mut outputs = Vec::new();
+        let limit = self.config.limit_for(key);
+        let state = self
+            .keys
+            .entry(key.to_string())
+            .or_insert_with(|| KeyState::new(now));
+        Self::roll(&self.config, key, state, now, &mut outputs);
+
+        if state.muted_until.is_some() {
+            state.dropped += 1;
+            state.latest = Some(item);
+            return outputs;
+        }
+
+        state.seen += 1;
+        if state.seen <= limit {
+            outputs.push(FloodOutput::Deliver(item));
+        } else {
+            state.suppressed += 1;
+            state.latest = Some(item);
+        }
+        outputs
+    }
+
+    /// Close windows and mutes that have run out.
+    pub fn tick(&mut self, now: Instant) -> Vec<FloodOutput<T>> {
+        let mut outputs = Vec::new();
+        for (key, state) in &mut self.keys {
+            Self::roll(&self.config, key, state, now, &mut outputs);
+        }
+        // Forget keys that have gone quiet
+        self.keys.retain(|_, state| {
+            state.seen > 0 || state.flooded_windows > 0 || state.muted_until.is_some()
+        });
+        outputs
+    }
+
+    fn roll(
+        config: &FloodConfig,
+        key: &str,
+        state: &mut KeyState<T>,
+        now: Instant,
+        outputs: &mut Vec<FloodOutput<T>>,
+    ) {
+        if let Some(until) = state.muted_until {
+            if now < until {
+                return;
+            }
+            outputs.push(FloodOutput::Event(FloodEvent::Unmuted {
+                key: key.to_string(),
+                dropped: state.dropped,
+            }));
+            // The client still learns the latest state
+            if let Some(latest) = state.latest.take() {
+                outputs.push(FloodOutput::Deliver(latest));
+            }
+            *state = KeyState::new(now);
+            return;
+        }
+
+        if now.duration_since(state.window_start) < config.window {
+            return;
+        }
+
+        let seen = state.seen;
+        if state.suppressed > 0 {
+            outputs.push(FloodOutput::Event(FloodEvent::Coalesced {
+                key: key.to_string(),
+                count: state.suppressed,
+            }));
+            if let Some(latest) = state.latest.take() {
+                outputs.push(FloodOutput::Deliver(latest));
+            }
+            state.flooded_windows += 1;
+        } else {
+            state.flooded_windows = 0;
+        }
+        state.window_start = now;
+        state.seen = 0;
+        state.suppressed = 0;
+
+        if config.mute_after > 0 && state.flooded_windows >= config.mute_after {
+            state.muted_until = Some(now + config.mute_for);
+            state.dropped = 0;
+            outputs.push(FloodOutput::Event(FloodEvent::Muted {
+                key: key.to_string(),
+                rate: seen,
+                duration: config.mute_for,
+            }));
+        }
+    }
+}
+
+/// Key notifications are limited under: the method, plus the progress token
+/// for progress notifications so concurrent requests do not starve each other.
+pub fn flood_key(method: &str, params: Option<&Value>) -> String {
+    match params.and_then(|params| params.get("progressToken")) {
+        Some(token) if method == "notifications/progress" => format!("{}#{}", method, token),
+        _ => method.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LIST_CHANGED: &str = "notifications/tools/list_changed";
+
+    fn config() -> FloodConfig {
+        FloodConfig {
+            max_per_window: 2,
+            mute_after: 2,
+            ..FloodConfig::default()
+        }
+    }
+
+    fn delivered<T: Clone>(outputs: &[FloodOutput<T>]) -> Vec<T> {
+        outputs
+            .iter()
+            .filter_map(|output| match output {
+                FloodOutput::Deliver(item) => Some(item.clone()),
+                FloodOutput::Event(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_burst_is_coalesced() {
+        let mut guard = FloodGuard::new(config());
+        let start = Instant::now();
+        let mut passed = Vec::new();
+        for n in 0..10 {
+            passed.extend(delivered(&guard.admit(LIST_CHANGED, n, start)));
+        }
+        assert_eq!(passed, vec![0, 1]);
+
+        let outputs = guard.tick(start + Duration::from_millis(1100));
+        assert_eq!(
+            outputs,
+            vec![
+                FloodOutput::Event(FloodEvent::Coalesced {
+                    key: LIST_CHANGED.to_string(),
+                    count: 8,
+                }),
+                FloodOutput::Deliver(9),
+            ]
+        );
+
+        // Quiet windows are forgotten and nothing is left to flush
+        assert!(guard.tick(start + Duration::from_secs(3)).is_empty());
+        assert!(guard.keys.is_empty());
+    }
+
+    #[test]
+    fn test_persistent_flood_is_muted_then_released() {
+        let mut guard = FloodGuard::new(config());
+        let start = Instant::now();
+        let mut events = Vec::new();
+        for second in 0..3u64 {
+            let now = start + Duration::from_secs(second);
+            for n in 0..5 {
+                for output in guard.admit(LIST_CHANGED, (second, n), now) {
+                    if let FloodOutput::Event(event) = output {
+                        events.push(event);
+                    }
+                }
+            }
+        }
+        assert!(matches!(
+            events.last(),
+            Some(FloodEvent::Muted { rate: 5, .. })
+        ));
+        assert!(events.last().unwrap().is_warning());
+        assert!(guard.is_muted(LIST_CHANGED));
+
+        // Everything in the third window was dropped
+        let unmuted = guard.tick(start + Duration::from_secs(40));
+        assert_eq!(
+            unmuted,
+            vec![
+                FloodOutput::Event(FloodEvent::Unmuted {
+                    key: LIST_CHANGED.to_string(),
+                    dropped: 5,
+                }),
+                FloodOutput::Deliver((2, 4)),
+            ]
+        );
+        assert!(!guard.is_muted(LIST_CHANGED));
+    }
+
+    #[test]
+    fn test_limits_per_method_and_progress_token() {
+        let mut limits = config();
+        limits
+            .limits
+            .insert("notifications/progress".to_string(), 1);
+        let mut guard = FloodGuard::new(limits);
+        let now = Instant::now();
+
+        let a = flood_key(
+            "notifications/progress",
+            Some(&json!({ "progressToken": "a" })),
+        );
+        let b = flood_key(
+            "notifications/progress",
+            Some(&json!({ "progressToken": 7 })),
+        );
+        assert_eq!(b, "notifications/progress#7");
+
+        assert_eq!(delivered(&guard.admit(&a, 1, now)), vec![1]);
+        assert!(delivered(&guard.admit(&a, 2, now)).is_empty());
+        assert_eq!(delivered(&guard.admit(&b, 3, now)), vec![3]);
+        assert_eq!(
+            flood_key(LIST_CHANGED, Some(&json!({ "progressToken": "a" }))),
+            LIST_CHANGED
+        );
+    }
+}
@@ -0,0 +1,471 @@
+//! Property-based fuzzing of the JSON-RPC frame parser and validators.
+//!
+//! Available with the `fuzzing` feature. The strategies here generate
+//! near-valid JSON-RPC frames: well-formed messages with one or more parts
+//! bent out of shape, such as a `jsonrpc` of `"1.0"`, an `id` that is a float
+//! or an object, a response with both `result` and `error`, params nested
+//! past serde_json's recursion limit, unknown members, or text cut short.
+//! Well-formed frames are generated too, so the checks cover acceptance as
+//! well as rejection.
+//!
+//! [`check_frame_handling`] and [`check_validation`] state what must hold
+//! for any input: nothing panics, and every rejected frame or parameter set
+//! comes with a structured explanation instead of a bare failure. The
+//! property tests in this module run them with `cargo test -p mcp-core
+//! --features fuzzing`; set `PROPTEST_CASES` to run more cases. Crates that
+//! wrap the parser can reuse the strategies and checks in their own
+//! property tests.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_probe_core::fuzz::{check_frame_handling, frame_text};
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn frames_never_panic(frame in frame_text()) {
+//!         check_frame_handling(&frame).map_err(TestCaseError::fail)?;
+//!     }
+//! }
+//! ```
+
+use proptest::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::transport::framing::{
+    check_frame, DiagnosticSeverity, FrameParser, FrameRule, ParseMode, ProtocolDiagnostic,
+};
+use crate::validation::ParameterValidator;
+
+/// Deepest nesting [`deeply_nested`] builds; serde_json refuses more than
+/// 128 levels, so frames on both sides of the limit are generated
+pub const MAX_NESTING: usize = 200;
+
+/// Methods frames are generated with
+const METHODS: &[&str] = &[
+    "initialize",
+    "ping",
+    "tools/list",
+    "tools/call",
+    "resources/read",
+    "notifications/progress",
+    "notifications/cancelled",
+];
+
+/// Any JSON scalar, including strings outside ASCII.
+pub fn json_scalar() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        "\\PC{0,12}".prop_map(Value::String),
+    ]
+}
+
+/// Arbitrary JSON of modest size and depth.
+pub fn json_value() -> impl Strategy<Value = Value> {
+    json_scalar().prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map("[a-z_]{1,8}", inner, 0..4)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+/// A single chain of arrays and objects up to [`MAX_NESTING`] levels deep.
+pub fn deeply_nested() -> impl Strategy<Value = Value> {
+    (1..=MAX_NESTING, any::<bool>(), json_scalar()).prop_map(|(depth, arrays, leaf)| {
+        (0..depth).fold(leaf, |inner, _| {
+            if arrays {
+                Value::Array(vec![inner])
+            } else {
+                json!({ "nested": inner })
+            }
+        })
+    })
+}
+
+/// A `jsonrpc` member, or its absence; usually `"2.0"`.
+pub fn jsonrpc_version() -> impl Strategy<Value = Option<Value>> {
+    prop_oneof![
+        6 => Just(Some(json!("2.0"))),
+        1 => Just(None),
+        1 => prop_oneof![
+            Just(json!("1.0")),
+            Just(json!("2")),
+            Just(json!("2.0 ")),
+            Just(json!(2.0)),
+            Just(Value::Null),
+        ]
+        .prop_map(Some),
+    ]
+}
+
+/// An `id` member, or its absence; usually a string or an integer.
+pub fn request_id() -> impl Strategy<Value = Option<Value>> {
+    prop_oneof![
+        3 => any::<i64>().prop_map(|id| Some(Value::from(id))),
+        3 => "[a-z0-9-]{0,12}".prop_map(|id| Some(Value::String(id))),
+        1 => Just(None),
+        1 => prop_oneof![
+            Just(Value::from(u64::MAX)),
+            any::<f64>().prop_map(Value::from),
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::Bool),
+            Just(json!({ "id": 1 })),
+            Just(json!([1])),
+        ]
+        .prop_map(Some),
+    ]
+}
+
+/// A `method` member; usually a known method name.
+pub fn method() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        4 => prop::sample::select(METHODS).prop_map(Value::from),
+        1 => "\\PC{0,16}".prop_map(Value::String),
+        1 => json_scalar(),
+    ]
+}
+
+/// A `params` member, or its absence: an object, an array, a scalar or
+/// something nested very deeply.
+pub fn params() -> impl Strategy<Value = Option<Value>> {
+    prop_oneof![
+        2 => Just(None),
+        4 => prop::collection::btree_map("[a-z_]{1,8}", json_value(), 0..4)
+            .prop_map(|map| Some(Value::Object(map.into_iter().collect()))),
+        1 => prop::collection::vec(json_value(), 0..4).prop_map(|items| Some(Value::Array(items))),
+        1 => json_scalar().prop_map(Some),
+        2 => deeply_nested().prop_map(|nested| Some(json!({ "arguments": nested }))),
+    ]
+}
+
+/// An `error` member: a well-formed error object or one missing or
+/// mistyping its `code` and `message`.
+pub fn error_object() -> impl Strategy<Value = Value> {
+    let code = prop_oneof![
+        4 => any::<i32>().prop_map(Value::from),
+        1 => json_scalar(),
+    ];
+    let message = prop_oneof![
+        4 => "\\PC{0,24}".prop_map(Value::String),
+        1 => json_scalar(),
+    ];
+    (
+        prop::option::weighted(0.9, code),
+        prop::option::weighted(0.9, message),
+        prop::option::of(json_value()),
+    )
+        .prop_map(|(code, message, data)| {
+            let mut error = Map::new();
+            if let Some(code) = code {
+                error.insert("code".to_string(), code);
+            }
+            if let Some(message) = message {
+                error.insert("message".to_string(), message);
+            }
+            if let Some(data) = data {
+                error.insert("data".to_string(), data);
+            }
+            Value::Object(error)
+        })
+}
+
+/// The kind of message a frame starts out as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Request,
+    Notification,
+    Success,
+    Failure,
+    /// A response with both `result` and `error`
+    Both,
+    /// A response with neither
+    Neither,
+}
+
+fn shape() -> impl Strategy<Value = Shape> {
+    prop_oneof![
+        3 => Just(Shape::Request),
+        2 => Just(Shape::Notification),
+        3 => Just(Shape::Success),
+        2 => Just(Shape::Failure),
+        1 => Just(Shape::Both),
+        1 => Just(Shape::Neither),
+    ]
+}
+
+/// A JSON-RPC frame that is valid or close to it.
+pub fn near_valid_frame() -> impl Strategy<Value = Value> {
+    (
+        shape(),
+        jsonrpc_version(),
+        request_id(),
+        method(),
+        params(),
+        json_value(),
+        error_object(),
+        prop::option::weighted(0.2, ("[a-z]{1,8}", json_value())),
+    )
+        .prop_map(
+            |(shape, version, id, method, params, result, error, extra)| {
+                let mut frame = Map::new();
+                if let Some(version) = version {
+                    frame.insert("jsonrpc".to_string(), version);
+                }
+                // A notification keeps its id only when the id is malformed anyway
+                if shape != Shape::Notification || matches!(id, Some(Value::Object(_))) {
+                    if let Some(id) = id {
+                        frame.insert("id".to_string(), id);
+                    }
+                }
+                match shape {
+                    Shape::Request | Shape::Notification => {
+                        frame.insert("method".to_string(), method);
+                        if let Some(params) = params {
+                            frame.insert("params".to_string(), params);
+                        }
+                    }
+                    Shape::Success => {
+                        frame.insert("result".to_string(), result);
+                    }
+                    Shape::Failure => {
+                        frame.insert("error".to_string(), error);
+                    }
+                    Shape::Both => {
+                        frame.insert("result".to_string(), result);
+                        frame.insert("error".to_string(), error);
+                    }
+                    Shape::Neither => {}
+                }
+                if let Some((member, value)) = extra {
+                    frame.entry(member).or_insert(value);
+                }
+                Value::Object(frame)
+            },
+        )
+}
+
+/// The text of a near-valid frame, sometimes cut short, followed by
+/// garbage or sent as a batch.
+pub fn frame_text() -> impl Strategy<Value = String> {
+    (near_valid_frame(), 0..10u8, any::<prop::sample::Index>()).prop_map(
+        |(frame, mangle, index)| {
+            let text = frame.to_string();
+            match mangle {
+                0 => {
+                    let cut = index.index(text.chars().count().max(1));
+                    text.chars().take(cut).collect()
+                }
+                1 => format!("{} }}", text),
+                2 => format!("[{}]", text),
+                _ => text,
+            }
+        },
+    )
+}
+
+/// Check what must hold however `frame` is malformed.
+///
+/// Strict and lenient parsing must not panic. A frame the strict parser
+/// accepts breaks no rule and is accepted, identically, by the lenient
+/// parser. A frame either parser drops is explained by a published
+/// [`ProtocolDiagnostic`] with an error severity, a non-empty message and
+/// the rule that was broken. Returns what did not hold.
+pub fn check_frame_handling(frame: &str) -> Result<(), String> {
+    let strict = FrameParser::new("fuzz", ParseMode::Strict);
+    let mut published = strict.subscribe();
+    let strict_result = strict.decode(frame);
+    let mut diagnostics = Vec::new();
+    while let Ok(diagnostic) = published.try_recv() {
+        diagnostics.push(diagnostic);
+    }
+
+    let issues = serde_json::from_str::<Value>(frame)
+        .ok()
+        .map(|value| check_frame(&value));
+    let broken: Vec<FrameRule> = issues
+        .iter()
+        .flatten()
+        .filter(|issue| issue.severity == DiagnosticSeverity::Error)
+        .map(|issue| issue.rule)
+        .collect();
+
+    let lenient = FrameParser::new("fuzz", ParseMode::Lenient);
+    let lenient_result = lenient.decode(frame);
+    if let Err(dropped) = &lenient_result {
+        explained(dropped, "lenient")?;
+        if dropped.rule != FrameRule::Undecodable {
+            return Err(format!(
+                "lenient parser dropped a frame as {}, not undecodable",
+                dropped.rule
+            ));
+        }
+    }
+
+    match strict_result {
+        Ok(message) => {
+            if !broken.is_empty() {
+                return Err(format!(
+                    "strict parser accepted a frame breaking {:?}",
+                    broken
+                ));
+            }
+            if let Some(diagnostic) = diagnostics.iter().find(|d| d.dropped) {
+                return Err(format!(
+                    "accepted frame reported as dropped: {}",
+                    diagnostic
+                ));
+            }
+            match lenient_result {
+                Ok(lenient) if lenient == message => Ok(()),
+                Ok(lenient) => Err(format!(
+                    "strict and lenient parsers disagree: {:?} vs {:?}",
+                    message, lenient
+                )),
+                Err(dropped) => Err(format!(
+                    "lenient parser dropped a frame the strict parser accepted: {}",
+                    dropped
+                )),
+            }
+        }
+        Err(dropped) => {
+            explained(&dropped, "strict")?;
+            if !diagnostics.contains(&dropped) {
+                return Err(format!("drop was not published: {}", dropped));
+            }
+            let expected = match &issues {
+                None => dropped.rule == FrameRule::InvalidJson,
+                Some(_) if broken.is_empty() => dropped.rule == FrameRule::Undecodable,
+                Some(_) => broken.contains(&dropped.rule),
+            };
+            if !expected {
+                return Err(format!(
+                    "dropped as {} but the frame breaks {:?}",
+                    dropped.rule, broken
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A dropped frame's diagnostic must say what went wrong
+fn explained(diagnostic: &ProtocolDiagnostic, parser: &str) -> Result<(), String> {
+    if !diagnostic.dropped || diagnostic.severity != DiagnosticSeverity::Error {
+        return Err(format!(
+            "{} parser's drop is not an error: {:?}",
+            parser, diagnostic
+        ));
+    }
+    if diagnostic.message.trim().is_empty() {
+        return Err(format!(
+            "{} parser dropped a frame without saying why",
+            parser
+        ));
+    }
+    Ok(())
+}
+
+/// A tool input schema with a few typed properties, some required.
+pub fn input_schema() -> impl Strategy<Value = Value> {
+    let property = (
+        prop::sample::select(&["string", "number", "integer", "boolean", "array", "object"][..]),
+        prop::option::of(prop::sample::select(&["A URL to fetch", "Count", ""][..])),
+    )
+        .prop_map(|(kind, description)| {
+            let mut property = json!({ "type": kind });
+            if let Some(description) = description {
+                property["description"] = json!(description);
+            }
+            property
+        });
+    (
+        prop::collection::btree_map("[a-z]{1,6}", property, 0..5),
+        prop::collection::vec("[a-z]{1,6}", 0..3),
+    )
+        .prop_map(|(properties, required)| {
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            })
+        })
+}
+
+/// Check what must hold for any parameters validated against `schema`.
+///
+/// Validation must not panic, must report at least one error exactly when
+/// the parameters are invalid, and every error must say something.
+pub fn check_validation(schema: &Value, params: &Value) -> Result<(), String> {
+    for validator in [ParameterValidator::new(), ParameterValidator::strict()] {
+        let result = validator.validate(schema, params);
+        if result.is_valid != result.errors.is_empty() {
+            return Err(format!(
+                "is_valid is {} with {} errors",
+                result.is_valid,
+                result.errors.len()
+            ));
+        }
+        if let Some(error) = result
+            .errors
+            .iter()
+            .find(|error| error.to_string().trim().is_empty())
+        {
+            return Err(format!("error without a message: {:?}", error));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_frames_are_handled(frame in frame_text()) {
+            check_frame_handling(&frame).map_err(TestCaseError::fail)?;
+        }
+
+        #[test]
+        fn test_arbitrary_text_is_handled(frame in "\\PC{0,64}") {
+            check_frame_handling(&frame).map_err(TestCaseError::fail)?;
+        }
+
+        #[test]
+        fn test_parameters_are_validated(
+            schema in prop_oneof![4 => input_schema(), 1 => json_value()],
+            params in prop_oneof![
+                json_value(),
+                params().prop_map(|params| params.unwrap_or(Value::Null)),
+            ],
+        ) {
+            check_validation(&schema, &params).map_err(TestCaseError::fail)?;
+        }
+    }
+
+    #[test]
+    fn test_nesting_past_the_recursion_limit_is_dropped() {
+        let nested = (0..MAX_NESTING).fold(json!(1), |inner, _| json!([inner]));
+        let frame = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "arguments": nested },
+        })
+        .to_string();
+        check_frame_handling(&frame).unwrap();
+
+        let strict = FrameParser::new("fuzz", ParseMode::Strict);
+        assert_eq!(
+            strict.decode(&frame).unwrap_err().rule,
+            FrameRule::InvalidJson
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Golden-file snapshots of message serialization.
+//!
+//! A [`Golden`] directory holds the JSON every snapshotted value serializes
+//! to, one `<name>.json` file per snapshot. Checking a value compares its
+//! JSON with the file, so a serde change that would alter the wire format,
+//! such as a renamed field or a dropped `skip_serializing_if`, fails a test
+//! instead of breaking peers. Each check also asserts round-trip stability:
+//! the value must deserialize from its own JSON and from the golden file,
+//! and serialize back to the same JSON both times.
+//!
+//! [`Golden::check_versions`] snapshots a value once per protocol version and
+//! [`Golden::check_session`] snapshots a whole recorded session of JSON-RPC
+//! messages.
+//!
+//! Missing and outdated golden files fail the check. After an intentional
+//! change, rerun the tests with `GENMCP_UPDATE_GOLDEN=1` to rewrite them and
+//! review the diff.
+//!
+//! The module is public so that crates building on these messages can
+//! snapshot their own extensions.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_probe_core::golden::Golden;
+//! use mcp_probe_core::messages::Tool;
+//! use serde_json::json;
+//!
+//! let golden = Golden::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"));
+//! let tool = Tool::new("search", "Search the index")
+//!     .with_input_schema(json!({ "type": "object" }));
+//! golden.assert("search_tool", &tool);
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::messages::{JsonRpcMessage, ProtocolVersion};
+
+/// Environment variable that makes checks write golden files instead of
+/// comparing against them
+pub const UPDATE_ENV: &str = "GENMCP_UPDATE_GOLDEN";
+
+/// Why a value does not match its snapshot.
+#[derive(Error, Debug)]
+pub enum GoldenError {
+    /// No golden file exists yet
+    #[error("{name}: no golden file at {}; rerun with {UPDATE_ENV}=1 to create it", path.display())]
+    Missing {
+        /// Snapshot name
+        name: String,
+        /// Where the file was expected
+        path: PathBuf,
+    },
+
+    /// The value serializes differently than recorded
+    #[error("{name}: serialization differs from {}; rerun with {UPDATE_ENV}=1 if intended\n{diff}", path.display())]
+    Changed {
+        /// Snapshot name
+        name: String,
+        /// Golden file
+        path: PathBuf,
+        /// Lines that differ
+        diff: LineDiff,
+    },
+
+    /// The value does not survive serializing and deserializing
+    #[error("{name}: does not round-trip: {reason}")]
+    RoundTrip {
+        /// Snapshot name
+        name: String,
+        /// What went wrong
+        reason: String,
+    },
+
+    /// The golden file could not be read or written
+    #[error("{name}: {source}")]
+    Io {
+        /// Snapshot name
+        name: String,
+        /// Underlying error
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Line-by-line difference between a golden file and a fresh serialization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiff {
+    /// Lines from the golden file
+    pub expected: Vec<String>,
+    /// Lines from the fresh serialization
+    pub actual: Vec<String>,
+}
+
+impl LineDiff {
+    fn new(expected: &str, actual: &str) -> Self {
+        Self {
+            expected: expected.lines().map(str::to_string).collect(),
+            actual: actual.lines().map(str::to_string).collect(),
+        }
+    }
+}
+
+impl fmt::Display for LineDiff {
+    /// Lines that differ, as `-` for the golden file and `+` for the value;
+    /// unchanged leading and trailing lines are left out
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = self
+            .expected
+            .iter()
+            .zip(&self.actual)
+            .take_while(|(expected, actual)| expected == actual)
+            .count();
+        let suffix = self.expected[prefix..]
+            .iter()
+            .rev()
+            .zip(self.actual[prefix..].iter().rev())
+            .take_while(|(expected, actual)| expected == actual)
+            .count();
+        for line in &self.expected[prefix..self.expected.len() - suffix] {
+            writeln!(f, "-{}", line)?;
+        }
+        for line in &self.actual[prefix..self.actual.len() - suffix] {
+            writeln!(f, "+{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A directory of golden JSON files.
+#[derive(Debug, Clone)]
+pub struct Golden {
+    dir: PathBuf,
+    update: bool,
+}
+
+impl Golden {
+    /// Golden files under `dir`, rewritten when [`UPDATE_ENV`] is set to
+    /// anything but `0`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let update = std::env::var(UPDATE_ENV).is_ok_and(|value| !value.is_empty() && value != "0");
+        Self {
+            dir: dir.into(),
+            update,
+        }
+    }
+
+    /// Write golden files instead of comparing against them.
+    pub fn updating(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// The golden file for a snapshot.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Compare `value` with its golden file and check that it round-trips.
+    pub fn check<T>(&self, name: &str, value: &T) -> Result<(), GoldenError>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let json = round_trip(name, value)?;
+        let path = self.path(name);
+        let actual = pretty(&json);
+
+        if self.update {
+            return write(name, &path, &actual);
+        }
+        let expected = match std::fs::read_to_string(&path) {
+            Ok(expected) => expected,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(GoldenError::Missing {
+                    name: name.to_string(),
+                    path,
+                })
+            }
+            Err(source) => {
+                return Err(GoldenError::Io {
+                    name: name.to_string(),
+                    source,
+                })
+            }
+        };
+        if expected != actual {
+            return Err(GoldenError::Changed {
+                name: name.to_string(),
+                path,
+                diff: LineDiff::new(&expected, &actual),
+            });
+        }
+
+        // What is on disk must still be readable, not only what we write now
+        let recorded: Value =
+            serde_json::from_str(&expected).map_err(|e| GoldenError::RoundTrip {
+                name: name.to_string(),
+                reason: format!("golden file is not JSON: {}", e),
+            })?;
+        let reread: T =
+            serde_json::from_value(recorded.clone()).map_err(|e| GoldenError::RoundTrip {
+                name: name.to_string(),
+                reason: format!("golden file no longer deserializes: {}", e),
+            })?;
+        if to_value(name, &reread)? != recorded {
+            return Err(GoldenError::RoundTrip {
+                name: name.to_string(),
+                reason: "golden file changes when read and written again".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Snapshot the value `build` makes for each protocol version, as
+    /// `<name>.<version>.json`.
+    pub fn check_versions<T, F>(
+        &self,
+        name: &str,
+        versions: &[ProtocolVersion],
+        build: F,
+    ) -> Result<(), GoldenError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Fn(&ProtocolVersion) -> T,
+    {
+        for version in versions {
+            self.check(&format!("{}.{}", name, version), &build(version))?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot a recorded session and check that every message comes back
+    /// as the same kind of message.
+    pub fn check_session(
+        &self,
+        name: &str,
+        messages: &[JsonRpcMessage],
+    ) -> Result<(), GoldenError> {
+        for (index, message) in messages.iter().enumerate() {
+            let reread: JsonRpcMessage =
+                serde_json::from_value(to_value(name, message)?).map_err(|e| {
+                    GoldenError::RoundTrip {
+                        name: name.to_string(),
+                        reason: format!("message #{} does not deserialize: {}", index, e),
+                    }
+                })?;
+            if kind(&reread) != kind(message) {
+                return Err(GoldenError::RoundTrip {
+                    name: name.to_string(),
+                    reason: format!(
+                        "message #{} was a {} but reads back as a {}",
+                        index,
+                        kind(message),
+                        kind(&reread)
+                    ),
+                });
+            }
+        }
+        self.check(name, &messages.to_vec())
+    }
+
+    /// [`check`](Self::check), panicking with the difference on failure.
+    pub fn assert<T>(&self, name: &str, value: &T)
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        if let Err(e) = self.check(name, value) {
+            panic!("{}", e);
+        }
+    }
+
+    /// [`check_versions`](Self::check_versions), panicking on failure.
+    pub fn assert_versions<T, F>(&self, name: &str, versions: &[ProtocolVersion], build: F)
+    where
+        T: Serialize + DeserializeOwned,
+        F: Fn(&ProtocolVersion) -> T,
+    {
+        if let Err(e) = self.check_versions(name, versions, build) {
+            panic!("{}", e);
+        }
+    }
+
+    /// [`check_session`](Self::check_session), panicking on failure.
+    pub fn assert_session(&self, name: &str, messages: &[JsonRpcMessage]) {
+        if let Err(e) = self.check_session(name, messages) {
+            panic!("{}", e);
+        }
+    }
+}
+
+/// Serialize, deserialize and serialize again, returning the JSON once both
+/// serializations agree
+fn round_trip<T>(name: &str, value: &T) -> Result<Value, GoldenError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let json = to_value(name, value)?;
+    let reread: T = serde_json::from_value(json.clone()).map_err(|e| GoldenError::RoundTrip {
+        name: name.to_string(),
+        reason: format!("own JSON does not deserialize: {}", e),
+    })?;
+    let again = to_value(name, &reread)?;
+    if again != json {
+        return Err(GoldenError::RoundTrip {
+            name: name.to_string(),
+            reason: format!(
+                "serialization changes after a round trip\n{}",
+                LineDiff::new(&pretty(&json), &pretty(&again))
+            ),
+        });
+    }
+    Ok(json)
+}
+
+fn to_value<T: Serialize>(name: &str, value: &T) -> Result<Value, GoldenError> {
+    serde_json::to_value(value).map_err(|e| GoldenError::RoundTrip {
+        name: name.to_string(),
+        reason: format!("does not serialize: {}", e),
+    })
+}
+
+/// Pretty JSON with a trailing newline; object keys come out sorted
+fn pretty(json: &Value) -> String {
+    let mut text = serde_json::to_string_pretty(json).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+fn write(name: &str, path: &Path, contents: &str) -> Result<(), GoldenError> {
+    let io = |source| GoldenError::Io {
+        name: name.to_string(),
+        source,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(io)?;
+    }
+    std::fs::write(path, contents).map_err(io)
+}
+
+fn kind(message: &JsonRpcMessage) -> &'static str {
+    match message {
+        JsonRpcMessage::Request(_) => "request",
+        JsonRpcMessage::Response(_) => "response",
+        JsonRpcMessage::Notification(_) => "notification",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    /// Serializes `limit` under a different name than it reads it
+    #[derive(Serialize, Deserialize)]
+    struct Lopsided {
+        #[serde(rename(serialize = "max"))]
+        limit: u32,
+    }
+
+    #[test]
+    fn test_missing_changed_and_updated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let golden = Golden::new(dir.path()).updating(false);
+        let value = json!({ "name": "search", "limit": 5 });
+
+        assert!(matches!(
+            golden.check("search", &value),
+            Err(GoldenError::Missing { .. })
+        ));
+        golden.clone().updating(true).assert("search", &value);
+        golden.assert("search", &value);
+
+        let error = golden
+            .check("search", &json!({ "name": "search", "limit": 6 }))
+            .unwrap_err();
+        assert!(matches!(error, GoldenError::Changed { .. }));
+        assert!(error
+            .to_string()
+            .ends_with("-  \"limit\": 5,\n+  \"limit\": 6,\n"));
+    }
+
+    #[test]
+    fn test_lopsided_serde_does_not_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let golden = Golden::new(dir.path()).updating(true);
+
+        let error = golden
+            .check("lopsided", &Lopsided { limit: 1 })
+            .unwrap_err();
+        assert!(matches!(error, GoldenError::RoundTrip { .. }), "{}", error);
+        assert!(!golden.path("lopsided").exists());
+    }
+}
@@ -0,0 +1,394 @@
+//! Health probing and the readiness gate.
+//!
+//! A [`HealthChecker`] tells whether a server is fit to take requests. HTTP
+//! servers are probed with a `GET` of a health path on the same host,
+//! [`DEFAULT_HEALTH_PATH`] unless configured otherwise; stdio servers must
+//! answer `ping`. A server is unhealthy after
+//! [`HealthConfig::unhealthy_after`] failed probes in a row, and healthy
+//! again after the next success.
+//!
+//! Many HTTP servers have no health route, so any answer below 500 counts as
+//! healthy: a server that says `404 Not Found` is still up.
+//!
+//! With health checks configured, [`McpClient`](crate::client::McpClient)
+//! probes HTTP servers before connecting, and holds back
+//! [`ClientState::Ready`](crate::client::ClientState::Ready) until the first
+//! `tools/list` (or `ping`, for servers without tools) after initialization
+//! has succeeded. Probes then repeat every [`HealthConfig::interval`] for as
+//! long as the session lasts.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_probe_core::client::McpClientBuilder;
+//! use mcp_probe_core::health::HealthConfig;
+//! use mcp_probe_core::transport::TransportConfig;
+//!
+//! # async fn example() -> mcp_probe_core::McpResult<()> {
+//! let client = McpClientBuilder::new()
+//!     .transport(TransportConfig::http_stream("http://localhost:8931/mcp")?)
+//!     .health_check(HealthConfig::default().path("/healthz"))
+//!     .build()
+//!     .await?;
+//! if let Some(mut health) = client.health() {
+//!     health.changed().await.ok();
+//!     println!("{:?}", health.borrow().status);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+#[cfg(feature = "http")]
+use url::Url;
+
+use crate::messages::JsonRpcRequest;
+use crate::rt::{self, Instant, Task};
+use crate::transport::Transport;
+#[cfg(feature = "http")]
+use crate::transport::TransportConfig;
+
+/// Path probed on HTTP servers unless configured otherwise
+pub const DEFAULT_HEALTH_PATH: &str = "/health";
+
+/// How and how often servers are probed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// Path probed on HTTP servers, resolved against the endpoint URL
+    pub path: String,
+    /// Time between probes during a session (default: 30 seconds)
+    #[serde(with = "humantime_serde")]
+    pub interval: Duration,
+    /// How long a probe may take (default: 5 seconds)
+    #[serde(with = "humantime_serde")]
+    pub timeout: Duration,
+    /// Failed probes in a row before the server is unhealthy (default: 3)
+    pub unhealthy_after: u32,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            path: DEFAULT_HEALTH_PATH.to_string(),
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+            unhealthy_after: 3,
+        }
+    }
+}
+
+impl HealthConfig {
+    /// Probe HTTP servers at `path`.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Probe every `interval` during a session.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Fail probes that take longer than `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Mark the server unhealthy after `failures` failed probes in a row.
+    pub fn unhealthy_after(mut self, failures: u32) -> Self {
+        self.unhealthy_after = failures.max(1);
+        self
+    }
+}
+
+/// Whether a server is fit to take requests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// Not probed successfully yet, and not failing enough to be unhealthy
+    Unknown,
+    /// The last probe succeeded
+    Healthy,
+    /// Too many probes in a row failed
+    Unhealthy {
+        /// Why the last probe failed
+        reason: String,
+    },
+}
+
+/// Health of a server and the probes behind it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Health {
+    /// Current verdict
+    pub status: HealthStatus,
+    /// When the server was last probed
+    pub last_checked: Option<DateTime<Utc>>,
+    /// How long the last successful probe took
+    pub latency: Option<Duration>,
+    /// Failed probes since the last success
+    pub consecutive_failures: u32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            status: HealthStatus::Unknown,
+            last_checked: None,
+            latency: None,
+            consecutive_failures: 0,
+        }
+    }
+}
+
+impl Health {
+    /// Whether the last probe succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+
+    /// Whether the server failed enough probes to be unhealthy
+    pub fn is_unhealthy(&self) -> bool {
+        matches!(self.status, HealthStatus::Unhealthy { .. })
+    }
+}
+
+/// How a server is probed
+#[derive(Clone)]
+pub enum Probe {
+    /// `GET` of this URL; any status below 500 is healthy
+    #[cfg(feature = "http")]
+    Http(Url),
+    /// `ping` request over the session's transport
+    Ping(Arc<dyn Transport>),
+}
+
+impl Probe {
+    /// The probe suited to `transport`: its health URL for HTTP transports,
+    /// `ping` otherwise.
+    #[cfg_attr(not(feature = "http"), allow(unused_variables))]
+    pub fn for_transport(transport: Arc<dyn Transport>, path: &str) -> Self {
+        #[cfg(feature = "http")]
+        {
+            let base_url = match transport.get_config() {
+                TransportConfig::HttpSse(config) => Some(&config.base_url),
+                TransportConfig::HttpStream(config) => Some(&config.base_url),
+                _ => None,
+            };
+            if let Some(url) = base_url.and_then(|url| url.join(path).ok()) {
+                return Self::Http(url);
+            }
+        }
+        Self::Ping(transport)
+    }
+}
+
+/// Probes one server and publishes its [`Health`]
+pub struct HealthChecker {
+    config: HealthConfig,
+    probe: Probe,
+    #[cfg(feature = "http")]
+    http: reqwest::Client,
+    pings: AtomicU64,
+    state: watch::Sender<Health>,
+}
+
+impl HealthChecker {
+    /// Probe with `probe` as `config` says
+    pub fn new(config: HealthConfig, probe: Probe) -> Self {
+        Self {
+            #[cfg(feature = "http")]
+            http: reqwest::Client::builder()
+                .timeout(config.timeout)
+                .build()
+                .unwrap_or_default(),
+            config,
+            probe,
+            pings: AtomicU64::new(0),
+            state: watch::channel(Health::default()).0,
+        }
+    }
+
+    /// How the server is probed
+    pub fn probe_kind(&self) -> &Probe {
+        &self.probe
+    }
+
+    /// The current health
+    pub fn health(&self) -> Health {
+        self.state.borrow().clone()
+    }
+
+    /// Health updates, starting with the current health
+    pub fn subscribe(&self) -> watch::Receiver<Health> {
+        self.state.subscribe()
+    }
+
+    /// Probe the server once, without recording the result.
+    ///
+    /// Returns how long the probe took, or why it failed.
+    pub async fn probe(&self) -> Result<Duration, String> {
+        let started = Instant::now();
+        match &self.probe {
+            #[cfg(feature = "http")]
+            Probe::Http(url) => {
+                let response = self
+                    .http
+                    .get(url.clone())
+                    .send()
+                    .await
+                    .map_err(|e| format!("GET {} failed: {}", url, e))?;
+                if response.status().is_server_error() {
+                    return Err(format!("GET {} answered {}", url, response.status()));
+                }
+            }
+            Probe::Ping(transport) => {
+                let id = format!("health-{}", self.pings.fetch_add(1, Ordering::Relaxed));
+                let response = transport
+                    .send_request(
+                        JsonRpcRequest::without_params(id, "ping"),
+                        Some(self.config.timeout),
+                    )
+                    .await
+                    .map_err(|e| format!("ping failed: {}", e))?;
+                if let Some(error) = response.error {
+                    return Err(format!("ping failed: {}", error.message));
+                }
+            }
+        }
+        Ok(started.elapsed())
+    }
+
+    /// Record the outcome of a probe and return the resulting health
+    pub fn record(&self, outcome: Result<Duration, String>) -> Health {
+        let unhealthy_after = self.config.unhealthy_after;
+        self.state.send_modify(|health| {
+            health.last_checked = Some(Utc::now());
+            match outcome {
+                Ok(latency) => {
+                    health.status = HealthStatus::Healthy;
+                    health.latency = Some(latency);
+                    health.consecutive_failures = 0;
+                }
+                Err(reason) => {
+                    health.consecutive_failures += 1;
+                    if health.consecutive_failures >= unhealthy_after {
+                        if !health.is_unhealthy() {
+                            tracing::warn!("Server is unhealthy: {}", reason);
+                        }
+                        health.status = HealthStatus::Unhealthy { reason };
+                    } else {
+                        tracing::debug!("Health probe failed: {}", reason);
+                    }
+                }
+            }
+        });
+        self.health()
+    }
+
+    /// Probe the server once and record the result
+    pub async fn check(&self) -> Health {
+        let outcome = self.probe().await;
+        self.record(outcome)
+    }
+
+    /// Probe every [`HealthConfig::interval`] until the checker is dropped
+    pub fn spawn(self: &Arc<Self>) -> Task {
+        let checker: Weak<Self> = Arc::downgrade(self);
+        let interval = self.config.interval;
+        rt::spawn(async move {
+            loop {
+                rt::sleep(interval).await;
+                let Some(checker) = checker.upgrade() else {
+                    return;
+                };
+                checker.check().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{JsonRpcMessage, JsonRpcResponse};
+    use crate::transport::InMemoryTransport;
+    use serde_json::json;
+
+    fn checker(unhealthy_after: u32) -> HealthChecker {
+        let (client, _server) = InMemoryTransport::pair();
+        HealthChecker::new(
+            HealthConfig::default().unhealthy_after(unhealthy_after),
+            Probe::Ping(Arc::new(client)),
+        )
+    }
+
+    #[test]
+    fn test_failures_in_a_row_make_a_server_unhealthy() {
+        let checker = checker(2);
+        let mut updates = checker.subscribe();
+        assert_eq!(checker.health().status, HealthStatus::Unknown);
+
+        assert!(checker.record(Ok(Duration::from_millis(3))).is_healthy());
+        let health = checker.record(Err("timed out".to_string()));
+        assert!(health.is_healthy());
+        assert_eq!(health.consecutive_failures, 1);
+
+        let health = checker.record(Err("timed out".to_string()));
+        assert_eq!(
+            health.status,
+            HealthStatus::Unhealthy {
+                reason: "timed out".to_string()
+            }
+        );
+        assert!(updates.has_changed().unwrap());
+        assert!(updates.borrow_and_update().is_unhealthy());
+
+        let health = checker.record(Ok(Duration::from_millis(4)));
+        assert!(health.is_healthy());
+        assert_eq!(health.consecutive_failures, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "http-stream")]
+    fn test_http_transports_are_probed_at_the_health_path() {
+        let (transport, _) = InMemoryTransport::pair();
+        assert!(matches!(
+            Probe::for_transport(Arc::new(transport), "/health"),
+            Probe::Ping(_)
+        ));
+
+        let http = crate::transport::http_stream::HttpStreamTransport::new(
+            "http://localhost:8931/mcp".to_string(),
+            None,
+        );
+        let Probe::Http(url) = Probe::for_transport(Arc::new(http), "/healthz") else {
+            panic!("expected an HTTP probe");
+        };
+        assert_eq!(url.as_str(), "http://localhost:8931/healthz");
+    }
+
+    #[tokio::test]
+    async fn test_stdio_style_servers_must_answer_ping() {
+        let (client, server) = InMemoryTransport::pair();
+        client.connect().await.unwrap();
+        server.connect().await.unwrap();
+        tokio::spawn(async move {
+            while let Ok(JsonRpcMessage::Request(request)) = server.receive_message(None).await {
+                let response = JsonRpcResponse::success(request.id, json!({}));
+                server
+                    .send_message(JsonRpcMessage::Response(response))
+                    .unwrap();
+            }
+        });
+        let checker = HealthChecker::new(HealthConfig::default(), Probe::Ping(Arc::new(client)));
+        assert!(checker.check().await.is_healthy());
+    }
+}
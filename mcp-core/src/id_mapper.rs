@@ -0,0 +1,260 @@
+//! Request ID remapping between two bridged sessions.
+//!
+//! Code that joins two JSON-RPC sessions, such as a proxy, a fan-out
+//! aggregator or a replayer, cannot forward request IDs as they are. Each
+//! side picks its own IDs, so the same ID may be in flight in both
+//! directions, or from several sources, at once. An [`IdMapper`] gives every
+//! forwarded request a fresh ID that is unique across both directions. It
+//! restores the original ID on the response and rewrites the `requestId` of
+//! `notifications/cancelled` the same way.
+//!
+//! Requests that are never answered would keep their mapping forever, so
+//! mappings older than the mapper's TTL are dropped by [`IdMapper::expire`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::id_mapper::{IdMapper, Side};
+//! use mcp_probe_core::messages::RequestId;
+//!
+//! let mut ids = IdMapper::new();
+//! // Both sides use ID 1 for their own requests
+//! let to_server = ids.map_request(Side::Client, RequestId::from(1));
+//! let to_client = ids.map_request(Side::Server, RequestId::from(1));
+//! assert_ne!(to_server, to_client);
+//!
+//! // The server answers with the ID it was sent
+//! assert_eq!(ids.map_response(Side::Server, &to_server), Some(RequestId::from(1)));
+//! ```
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::messages::{JsonRpcMessage, JsonRpcNotification, RequestId};
+
+/// How long an unanswered request keeps its mapping by default
+pub const DEFAULT_MAPPING_TTL: Duration = Duration::from_secs(600);
+
+/// One of the two bridged sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    /// The session toward the client
+    Client,
+    /// The session toward the server
+    Server,
+}
+
+impl Side {
+    /// The other session
+    pub fn peer(self) -> Self {
+        match self {
+            Self::Client => Self::Server,
+            Self::Server => Self::Client,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Mapping {
+    origin: Side,
+    original: RequestId,
+    created: Instant,
+}
+
+/// Forwarded request IDs and the requests they stand for
+#[derive(Debug, Clone)]
+pub struct IdMapper {
+    ttl: Duration,
+    next: i64,
+    /// Keyed by the forwarded ID
+    mappings: HashMap<i64, Mapping>,
+}
+
+impl Default for IdMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdMapper {
+    /// Create a mapper that keeps unanswered requests for [`DEFAULT_MAPPING_TTL`]
+    pub fn new() -> Self {
+        Self::with_ttl(DEFAULT_MAPPING_TTL)
+    }
+
+    /// Create a mapper that keeps unanswered requests for `ttl`
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            next: 0,
+            mappings: HashMap::new(),
+        }
+    }
+
+    /// Record a request sent by `from` and return the ID to forward it with
+    pub fn map_request(&mut self, from: Side, id: RequestId) -> RequestId {
+        self.next += 1;
+        self.mappings.insert(
+            self.next,
+            Mapping {
+                origin: from,
+                original: id,
+                created: Instant::now(),
+            },
+        );
+        RequestId::Number(self.next)
+    }
+
+    /// The original ID of the request a response sent by `from` answers.
+    ///
+    /// The mapping is removed. `None` means the response answers no request
+    /// the other side sent, or one whose mapping expired.
+    pub fn map_response(&mut self, from: Side, id: &RequestId) -> Option<RequestId> {
+        let RequestId::Number(forwarded) = id else {
+            return None;
+        };
+        match self.mappings.get(forwarded) {
+            Some(mapping) if mapping.origin == from.peer() => {
+                self.mappings.remove(forwarded).map(|m| m.original)
+            }
+            _ => None,
+        }
+    }
+
+    /// The ID a pending request sent by `from` was forwarded with
+    pub fn forwarded_id(&self, from: Side, original: &RequestId) -> Option<RequestId> {
+        self.mappings
+            .iter()
+            .filter(|(_, mapping)| mapping.origin == from && mapping.original == *original)
+            .min_by_key(|(id, _)| **id)
+            .map(|(id, _)| RequestId::Number(*id))
+    }
+
+    /// Point a `notifications/cancelled` sent by `from` at the forwarded ID
+    ///
+    /// Returns whether the notification was changed.
+    pub fn map_cancellation(&self, from: Side, notification: &mut JsonRpcNotification) -> bool {
+        if notification.method != "notifications/cancelled" {
+            return false;
+        }
+        let Some(params) = notification.params.as_mut() else {
+            return false;
+        };
+        let Some(original) = params
+            .get("requestId")
+            .and_then(|id| serde_json::from_value::<RequestId>(id.clone()).ok())
+        else {
+            return false;
+        };
+        let Some(forwarded) = self.forwarded_id(from, &original) else {
+            return false;
+        };
+        params["requestId"] = serde_json::json!(forwarded);
+        true
+    }
+
+    /// Rewrite a message sent by `from` for the other side.
+    ///
+    /// Returns `false` for a response to an unknown request, which should not
+    /// be forwarded.
+    pub fn map_message(&mut self, from: Side, message: &mut JsonRpcMessage) -> bool {
+        match message {
+            JsonRpcMessage::Request(request) => {
+                request.id = self.map_request(from, request.id.clone());
+                true
+            }
+            JsonRpcMessage::Response(response) => match self.map_response(from, &response.id) {
+                Some(original) => {
+                    response.id = original;
+                    true
+                }
+                None => false,
+            },
+            JsonRpcMessage::Notification(notification) => {
+                self.map_cancellation(from, notification);
+                true
+            }
+        }
+    }
+
+    /// Drop mappings older than the TTL at `now`
+    ///
+    /// Returns the sender and original ID of every request dropped.
+    pub fn expire(&mut self, now: Instant) -> Vec<(Side, RequestId)> {
+        let ttl = self.ttl;
+        let mut expired = Vec::new();
+        self.mappings.retain(|_, mapping| {
+            let stale = now.saturating_duration_since(mapping.created) >= ttl;
+            if stale {
+                expired.push((mapping.origin, mapping.original.clone()));
+            }
+            !stale
+        });
+        expired
+    }
+
+    /// Number of requests waiting for a response
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Whether no request is waiting for a response
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{JsonRpcRequest, JsonRpcResponse};
+    use serde_json::json;
+
+    #[test]
+    fn test_colliding_ids_map_to_distinct_requests() {
+        let mut ids = IdMapper::new();
+        let mut from_client =
+            JsonRpcMessage::Request(JsonRpcRequest::new(1, "tools/call", json!({})));
+        let mut from_server =
+            JsonRpcMessage::Request(JsonRpcRequest::without_params(1, "roots/list"));
+        assert!(ids.map_message(Side::Client, &mut from_client));
+        assert!(ids.map_message(Side::Server, &mut from_server));
+        let to_server = from_client.id().unwrap().clone();
+        let to_client = from_server.id().unwrap().clone();
+        assert_ne!(to_server, to_client);
+        assert_eq!(ids.len(), 2);
+
+        // A cancellation follows the request it names
+        let mut cancelled =
+            JsonRpcNotification::new("notifications/cancelled", json!({ "requestId": 1 }));
+        assert!(ids.map_cancellation(Side::Client, &mut cancelled));
+        assert_eq!(cancelled.params.unwrap()["requestId"], json!(to_server));
+
+        // Responses only match requests from the other side, and only once
+        assert_eq!(ids.map_response(Side::Client, &to_server), None);
+        let mut answer = JsonRpcMessage::Response(JsonRpcResponse::success(to_server, json!({})));
+        assert!(ids.map_message(Side::Server, &mut answer));
+        assert_eq!(answer.id(), Some(&RequestId::from(1)));
+        assert!(!ids.map_message(Side::Server, &mut answer));
+        assert_eq!(
+            ids.map_response(Side::Client, &to_client),
+            Some(RequestId::from(1))
+        );
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn test_unanswered_requests_expire() {
+        let ttl = Duration::from_secs(30);
+        let mut ids = IdMapper::with_ttl(ttl);
+        let forwarded = ids.map_request(Side::Client, RequestId::from("slow"));
+
+        assert!(ids.expire(Instant::now()).is_empty());
+        let expired = ids.expire(Instant::now() + ttl);
+        assert_eq!(expired, vec![(Side::Client, RequestId::from("slow"))]);
+        assert_eq!(ids.map_response(Side::Server, &forwarded), None);
+    }
+}
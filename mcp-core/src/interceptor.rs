@@ -2,11 +2,20 @@
 //!
 //! This module provides interfaces for intercepting, watching, and modifying
 //! MCP protocol messages as they flow between client and server.
+//!
+//! The [`InterceptorManager`] owns one [`MessageContext`] per message and
+//! lends it to each interceptor through
+//! [`MessageInterceptor::intercept_in_place`], so a message that nobody
+//! changes is never copied. Interceptors that only implement
+//! [`MessageInterceptor::intercept`] still work, at the cost of one clone of
+//! the context per call.
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 use crate::messages::JsonRpcMessage;
@@ -126,6 +135,48 @@ impl InterceptionResult {
     }
 }
 
+/// What an interceptor decided about a message it inspected in place
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterceptAction {
+    /// Leave the message as it is
+    Continue,
+    /// The message in the context was changed
+    Modified {
+        /// Why it was changed
+        reasoning: Option<String>,
+        /// Confidence in the change (0.0 to 1.0)
+        confidence: Option<f64>,
+    },
+    /// Drop the message
+    Block {
+        /// Why it was blocked
+        reasoning: Option<String>,
+    },
+}
+
+impl InterceptAction {
+    /// Turn the action into the result [`MessageInterceptor::intercept`] returns
+    pub fn into_result(self, message: JsonRpcMessage) -> InterceptionResult {
+        match self {
+            Self::Continue => InterceptionResult::pass_through(message),
+            Self::Modified {
+                reasoning,
+                confidence,
+            } => InterceptionResult {
+                modified: true,
+                message,
+                block: false,
+                reasoning,
+                confidence,
+            },
+            Self::Block { reasoning } => InterceptionResult {
+                reasoning,
+                ..InterceptionResult::blocked(String::new())
+            },
+        }
+    }
+}
+
 /// Trait for message interceptors that can watch and modify MCP traffic
 #[async_trait]
 pub trait MessageInterceptor: Send + Sync {
@@ -143,6 +194,29 @@ pub trait MessageInterceptor: Send + Sync {
     /// Intercept and potentially modify a message
     async fn intercept(&self, context: MessageContext) -> McpResult<InterceptionResult>;
 
+    /// Intercept a message without taking ownership of it.
+    ///
+    /// This is what the [`InterceptorManager`] calls. The default forwards a
+    /// clone of the context to [`intercept`](Self::intercept); interceptors on
+    /// hot paths override it to inspect or edit `context.message` directly.
+    /// On error the message must be left as it was.
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
+        let result = self.intercept(context.clone()).await?;
+        if result.block {
+            return Ok(InterceptAction::Block {
+                reasoning: result.reasoning,
+            });
+        }
+        if !result.modified {
+            return Ok(InterceptAction::Continue);
+        }
+        context.message = result.message;
+        Ok(InterceptAction::Modified {
+            reasoning: result.reasoning,
+            confidence: result.confidence,
+        })
+    }
+
     /// Get statistics about this interceptor
     async fn get_stats(&self) -> InterceptorStats;
 }
@@ -166,6 +240,160 @@ pub struct InterceptorStats {
 pub struct InterceptorManager {
     interceptors: Arc<RwLock<Vec<Arc<dyn MessageInterceptor>>>>,
     stats: Arc<RwLock<InterceptorManagerStats>>,
+    instrumentation: Arc<RwLock<Option<Instrumentation>>>,
+}
+
+/// Minimum time between two over-budget warnings
+const BUDGET_WARNING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Latency one interceptor added to the pipeline
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InterceptorCost {
+    /// Interceptor name
+    pub name: String,
+    /// Messages the interceptor was consulted for
+    pub calls: u64,
+    /// Total time spent in `should_intercept` and `intercept`
+    pub total: Duration,
+    /// Slowest single message
+    pub max: Duration,
+}
+
+impl InterceptorCost {
+    /// Average time per message
+    pub fn mean(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.calls as u32
+        }
+    }
+}
+
+/// Per-interceptor cost of the pipeline, collected in instrumented mode
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PipelineReport {
+    /// Messages processed since instrumentation was enabled
+    pub messages: u64,
+    /// Total pipeline time across all messages
+    pub total: Duration,
+    /// Per-message overhead budget, if one is set
+    pub budget: Option<Duration>,
+    /// Messages whose pipeline time exceeded the budget
+    pub over_budget: u64,
+    /// Interceptors, most expensive first
+    pub interceptors: Vec<InterceptorCost>,
+}
+
+impl PipelineReport {
+    /// Average pipeline time per message
+    pub fn mean(&self) -> Duration {
+        if self.messages == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.messages as u32
+        }
+    }
+}
+
+impl fmt::Display for PipelineReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} messages, {:?} mean pipeline overhead",
+            self.messages,
+            self.mean()
+        )?;
+        if let Some(budget) = self.budget {
+            write!(f, ", {} over the {:?} budget", self.over_budget, budget)?;
+        }
+        writeln!(f)?;
+
+        let total = self.total.as_secs_f64();
+        for cost in &self.interceptors {
+            let share = if total > 0.0 {
+                cost.total.as_secs_f64() / total * 100.0
+            } else {
+                0.0
+            };
+            writeln!(
+                f,
+                "  {:<28} {:>5.1}%  mean {:>10?}  max {:>10?}  calls {}",
+                cost.name,
+                share,
+                cost.mean(),
+                cost.max,
+                cost.calls
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Timing state of the instrumented mode
+#[derive(Debug, Default)]
+struct Instrumentation {
+    budget: Option<Duration>,
+    messages: u64,
+    total: Duration,
+    over_budget: u64,
+    costs: HashMap<String, InterceptorCost>,
+    last_warning: Option<Instant>,
+}
+
+impl Instrumentation {
+    fn record(&mut self, total: Duration, timings: Vec<(String, Duration)>) {
+        self.messages += 1;
+        self.total += total;
+        for (name, elapsed) in &timings {
+            let cost = self
+                .costs
+                .entry(name.clone())
+                .or_insert_with(|| InterceptorCost {
+                    name: name.clone(),
+                    ..Default::default()
+                });
+            cost.calls += 1;
+            cost.total += *elapsed;
+            cost.max = cost.max.max(*elapsed);
+        }
+
+        let Some(budget) = self.budget else { return };
+        if total <= budget {
+            return;
+        }
+        self.over_budget += 1;
+
+        let due = self
+            .last_warning
+            .is_none_or(|last| last.elapsed() >= BUDGET_WARNING_INTERVAL);
+        if due {
+            self.last_warning = Some(Instant::now());
+            let breakdown: Vec<String> = timings
+                .iter()
+                .map(|(name, elapsed)| format!("{} {:?}", name, elapsed))
+                .collect();
+            tracing::warn!(
+                "Interceptor pipeline took {:?}, over the {:?} budget ({} messages over so far): {}",
+                total,
+                budget,
+                self.over_budget,
+                breakdown.join(", ")
+            );
+        }
+    }
+
+    fn report(&self) -> PipelineReport {
+        let mut interceptors: Vec<InterceptorCost> = self.costs.values().cloned().collect();
+        interceptors.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+        PipelineReport {
+            messages: self.messages,
+            total: self.total,
+            budget: self.budget,
+            over_budget: self.over_budget,
+            interceptors,
+        }
+    }
 }
 
 /// Statistics for the interceptor manager
@@ -189,9 +417,38 @@ impl InterceptorManager {
         Self {
             interceptors: Arc::new(RwLock::new(Vec::new())),
             stats: Arc::new(RwLock::new(InterceptorManagerStats::default())),
+            instrumentation: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Start measuring how much latency each interceptor adds, discarding
+    /// earlier measurements. With a budget, messages whose whole pipeline
+    /// takes longer are counted and logged as warnings.
+    pub async fn enable_instrumentation(&self, budget: Option<Duration>) {
+        *self.instrumentation.write().await = Some(Instrumentation {
+            budget,
+            ..Default::default()
+        });
+    }
+
+    /// Stop measuring, returning the final report
+    pub async fn disable_instrumentation(&self) -> Option<PipelineReport> {
+        self.instrumentation
+            .write()
+            .await
+            .take()
+            .map(|instrumentation| instrumentation.report())
+    }
+
+    /// Interceptors ranked by the latency they added, if instrumented
+    pub async fn cost_report(&self) -> Option<PipelineReport> {
+        self.instrumentation
+            .read()
+            .await
+            .as_ref()
+            .map(Instrumentation::report)
+    }
+
     /// Add an interceptor to the manager
     pub async fn add_interceptor(&self, interceptor: Arc<dyn MessageInterceptor>) {
         let mut interceptors = self.interceptors.write().await;
@@ -201,6 +458,11 @@ impl InterceptorManager {
         interceptors.sort_by_key(|i| i.priority());
     }
 
+    /// Whether no interceptors are registered, so processing would be a no-op
+    pub async fn is_empty(&self) -> bool {
+        self.interceptors.read().await.is_empty()
+    }
+
     /// Remove an interceptor by name
     pub async fn remove_interceptor(&self, name: &str) -> bool {
         let mut interceptors = self.interceptors.write().await;
@@ -214,51 +476,62 @@ impl InterceptorManager {
         &self,
         message: JsonRpcMessage,
         direction: MessageDirection,
+    ) -> McpResult<InterceptionResult> {
+        self.process_with_context(MessageContext::new(message, direction))
+            .await
+    }
+
+    /// Process a message through all applicable interceptors, keeping the
+    /// session id and metadata of the supplied context
+    pub async fn process_with_context(
+        &self,
+        mut context: MessageContext,
     ) -> McpResult<InterceptionResult> {
         let start_time = std::time::Instant::now();
-        let mut context = MessageContext::new(message.clone(), direction);
-        
+
         let interceptors = self.interceptors.read().await;
-        let mut current_message = message;
         let mut was_modified = false;
         let mut modification_reasoning = Vec::new();
         let mut confidence_sum = 0.0;
         let mut confidence_count = 0;
+        let instrumented = self.instrumentation.read().await.is_some();
+        let mut timings = Vec::new();
 
         for interceptor in interceptors.iter() {
+            let interceptor_start = std::time::Instant::now();
             if interceptor.should_intercept(&context).await {
-                let interceptor_start = std::time::Instant::now();
-                
-                // Update context with current message
-                context.message = current_message.clone();
-                
-                match interceptor.intercept(context.clone()).await {
-                    Ok(result) => {
-                        if result.block {
-                            // Update stats
-                            let mut stats = self.stats.write().await;
-                            stats.total_messages_processed += 1;
-                            stats.total_messages_blocked += 1;
-                            stats.avg_processing_time_ms = 
-                                (stats.avg_processing_time_ms * (stats.total_messages_processed - 1) as f64 
-                                 + start_time.elapsed().as_millis() as f64) 
-                                / stats.total_messages_processed as f64;
-
-                            return Ok(result);
+                match interceptor.intercept_in_place(&mut context).await {
+                    Ok(InterceptAction::Continue) => {}
+                    Ok(InterceptAction::Modified {
+                        reasoning,
+                        confidence,
+                    }) => {
+                        was_modified = true;
+                        if let Some(reasoning) = reasoning {
+                            modification_reasoning.push(reasoning);
                         }
-
-                        if result.modified {
-                            was_modified = true;
-                            current_message = result.message.clone();
-                            if let Some(reasoning) = &result.reasoning {
-                                modification_reasoning.push(reasoning.clone());
-                            }
-                            if let Some(confidence) = result.confidence {
-                                confidence_sum += confidence;
-                                confidence_count += 1;
-                            }
+                        if let Some(confidence) = confidence {
+                            confidence_sum += confidence;
+                            confidence_count += 1;
                         }
                     }
+                    Ok(InterceptAction::Block { reasoning }) => {
+                        // Update stats
+                        let mut stats = self.stats.write().await;
+                        stats.total_messages_processed += 1;
+                        stats.total_messages_blocked += 1;
+                        stats.avg_processing_time_ms = 
+                            (stats.avg_processing_time_ms * (stats.total_messages_processed - 1) as f64 
+                             + start_time.elapsed().as_millis() as f64) 
+                            / stats.total_messages_processed as f64;
+                        drop(stats);
+
+                        if instrumented {
+                            timings.push((interceptor.name().to_string(), interceptor_start.elapsed()));
+                            self.record_timings(start_time.elapsed(), timings).await;
+                        }
+                        return Ok(InterceptAction::Block { reasoning }.into_result(context.message));
+                    }
                     Err(e) => {
                         tracing::warn!("Interceptor {} failed: {}", interceptor.name(), e);
                         // Continue with other interceptors
@@ -272,9 +545,15 @@ impl InterceptorManager {
                     interceptor_time.as_millis()
                 );
             }
+            if instrumented {
+                timings.push((interceptor.name().to_string(), interceptor_start.elapsed()));
+            }
         }
 
         let total_time = start_time.elapsed();
+        if instrumented {
+            self.record_timings(total_time, timings).await;
+        }
         
         // Update final stats
         {
@@ -289,7 +568,13 @@ impl InterceptorManager {
                 / stats.total_messages_processed as f64;
             
             if let Some(method) = context.method() {
-                *stats.messages_by_method.entry(method.to_string()).or_insert(0) += 1;
+                // Only allocate the key the first time a method is seen
+                match stats.messages_by_method.get_mut(method) {
+                    Some(count) => *count += 1,
+                    None => {
+                        stats.messages_by_method.insert(method.to_string(), 1);
+                    }
+                }
             }
         }
 
@@ -301,15 +586,21 @@ impl InterceptorManager {
 
         Ok(if was_modified {
             InterceptionResult::modified(
-                current_message,
+                context.message,
                 modification_reasoning.join("; "),
                 final_confidence,
             )
         } else {
-            InterceptionResult::pass_through(current_message)
+            InterceptionResult::pass_through(context.message)
         })
     }
 
+    async fn record_timings(&self, total: Duration, timings: Vec<(String, Duration)>) {
+        if let Some(instrumentation) = self.instrumentation.write().await.as_mut() {
+            instrumentation.record(total, timings);
+        }
+    }
+
     /// Get manager statistics
     pub async fn get_stats(&self) -> InterceptorManagerStats {
         self.stats.read().await.clone()
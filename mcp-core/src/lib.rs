@@ -48,9 +48,23 @@
 //! The library is organized into several key modules:
 //!
 //! - [`error`]: Comprehensive error types for all MCP operations
+//! - [`decode`]: Deserialization that locates errors in large payloads
 //! - [`messages`]: Complete MCP message type definitions  
 //! - [`transport`]: Transport abstraction and implementations
 //! - [`client`]: High-level MCP client interface
+//! - [`catalog`]: Cached tool catalogs with filtering, sorting and paging
+//! - [`registry`]: Named server profiles from the user's `servers.toml`
+//! - [`quirks`]: Per-server workarounds from the user's `quirks.toml`
+//! - [`flood`]: Per-method notification limits that coalesce and mute floods
+//! - [`health`]: Health probes and the readiness gate for new sessions
+//! - [`id_mapper`]: Request IDs rewritten between two bridged sessions
+//! - [`cache`]: Responses to idempotent methods, invalidated by change notifications
+//! - [`notifications`]: Server notifications dispatched to handlers by method
+//! - [`restart`]: Detection of server restarts and the events they raise
+//! - [`retry`]: Which failed requests are retried, by idempotency and failure kind
+//! - [`server_log`]: Log messages servers send, as structured entries
+//! - [`outcome`]: Success, failure and partial success of tool calls
+//! - [`tool_args`]: Tool call arguments built and checked against input schemas
 //!
 //! ## Transport Support
 //!
@@ -60,32 +74,105 @@
 //! - **http-sse**: HTTP + Server-Sent Events (enabled by default)  
 //! - **http-stream**: Full-duplex HTTP streaming (enabled by default)
 //!
-//! Transport support can be controlled via feature flags.
+//! Transport support can be controlled via feature flags. Either HTTP
+//! transport enables the shared `http` feature, which pulls in `reqwest` and
+//! `url`; a build with only `stdio` leaves the whole HTTP stack out:
+//!
+//! ```toml
+//! mcp-core = { version = "0.1", default-features = false, features = ["stdio"] }
+//! ```
+//!
+//! Without it, the HTTP configuration types are not compiled and server
+//! profiles that name an HTTP transport fail to resolve.
+//!
+//! The optional `discovery` feature adds mDNS/zeroconf browsing for MCP servers
+//! on the local network (see `discovery`).
+//!
+//! ## Browser Builds
+//!
+//! The client also compiles to `wasm32-unknown-unknown`. Browsers cannot
+//! spawn processes and their networking goes through `fetch` and
+//! `WebSocket`, so turn off the native transports and enable `wasm`:
+//!
+//! ```toml
+//! mcp-core = { version = "0.1", default-features = false, features = ["wasm"] }
+//! ```
+//!
+//! This adds a `browser` transport that talks to a server over a WebSocket
+//! or, for an `http(s)` URL, over streamable HTTP with `fetch`. Hand it to
+//! [`McpClient::with_transport`]. Timers and background tasks run on the
+//! page's event loop through [`rt`].
+//!
+//! ## Testing Timing Logic
+//!
+//! Timeouts, retry backoff, circuit breaker cool-downs, hedging delays and
+//! health probe intervals are all measured with [`rt`], which natively is
+//! `tokio::time`, never the system clock. Tests of them can run with
+//! `#[tokio::test(start_paused = true)]` (tokio's `test-util` feature): the
+//! clock then jumps straight to the next timer whenever every task is
+//! waiting, so minutes of backoff take no real time and always play out the
+//! same way. Pair a client with an
+//! [`InMemoryTransport`] to script the server
+//! side. A server in a real process keeps answering in real time, so a paused
+//! clock only suits tests where it is not expected to answer in time.
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::uninlined_format_args)]
 
+pub mod cache;
+pub mod catalog;
 pub mod client;
+pub mod compliance;
+pub mod conformance;
+pub mod correlator;
+pub mod decode;
+pub mod differential;
+#[cfg(feature = "discovery")]
+pub mod discovery;
 pub mod error;
+pub mod flood;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+pub mod golden;
+pub mod health;
+pub mod id_mapper;
 pub mod interceptor;
 pub mod messages;
+pub mod notifications;
+pub mod outcome;
+pub mod quirks;
+pub mod registry;
+pub mod restart;
+pub mod retry;
+pub mod rt;
+pub mod server_log;
+pub mod tool_args;
 pub mod transport;
 pub mod validation;
+pub mod violations;
 
 // Re-export commonly used types for convenience
-pub use client::{ClientConfig, ClientState, ClientStats, McpClient, ServerInfo};
+pub use client::{
+    ClientConfig, ClientState, ClientStats, ConnectPhase, McpClient, MethodClass, MethodTimeouts,
+    SamplingHandler, ServerInfo,
+};
+pub use correlator::{CorrelatorStats, PendingResponse, RequestCorrelator};
 pub use error::{McpError, McpResult};
+pub use id_mapper::IdMapper;
 pub use interceptor::{
-    InterceptorManager, InterceptorStats, InterceptionResult, MessageContext,
-    MessageDirection, MessageInterceptor,
+    InterceptAction, InterceptorCost, InterceptorManager, InterceptorStats, InterceptionResult, MessageContext,
+    MessageDirection, MessageInterceptor, PipelineReport,
 };
 pub use messages::{
     Capabilities, Implementation, InitializeRequest, InitializeResponse, InitializedNotification,
     JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, ProtocolVersion,
 };
-pub use transport::{Transport, TransportConfig, TransportFactory, TransportInfo};
+pub use notifications::NotificationRegistry;
+pub use transport::{InMemoryTransport, Transport, TransportConfig, TransportFactory, TransportInfo};
+pub use retry::RetryPolicy;
+pub use violations::{ProtocolViolation, UnknownFields, ViolationReporter};
 
 /// Current version of the mcp-core library
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -38,6 +38,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 
+use super::meta::{self, Meta};
+use crate::error::{McpError, McpResult, ServerErrorCode};
+
 /// JSON-RPC 2.0 request message.
 ///
 /// Represents a request from client to server that expects a response.
@@ -145,6 +148,18 @@ impl JsonRpcRequest {
             None => serde_json::from_value(Value::Null),
         }
     }
+
+    /// The `_meta` object of the parameters, if any.
+    pub fn meta(&self) -> Option<&Meta> {
+        meta::meta_in(self.params.as_ref())
+    }
+
+    /// The `_meta` object of the parameters, created if missing.
+    ///
+    /// Returns `None` when the parameters are not an object.
+    pub fn meta_mut(&mut self) -> Option<&mut Meta> {
+        meta::meta_in_mut(&mut self.params, true)
+    }
 }
 
 /// JSON-RPC 2.0 response message.
@@ -228,11 +243,34 @@ impl JsonRpcResponse {
         T: for<'de> Deserialize<'de>,
     {
         match (&self.result, &self.error) {
-            (Some(result), None) => Ok(serde_json::from_value(result.clone())?),
+            (Some(result), None) => Ok(crate::decode::from_value(result.clone())?),
             (None, Some(error)) => Err(format!("JSON-RPC error: {error}").into()),
             _ => Err("Invalid response: both result and error are present or missing".into()),
         }
     }
+
+    /// The result, or the error as a typed
+    /// [`ProtocolError::ServerError`](crate::error::ProtocolError::ServerError).
+    ///
+    /// A response without either yields `Value::Null`.
+    pub fn into_result(self) -> McpResult<Value> {
+        match self.error {
+            Some(error) => Err(McpError::Protocol(error.into())),
+            None => Ok(self.result.unwrap_or(Value::Null)),
+        }
+    }
+
+    /// The `_meta` object of the result, if any.
+    pub fn meta(&self) -> Option<&Meta> {
+        meta::meta_in(self.result.as_ref())
+    }
+
+    /// The `_meta` object of the result, created if missing.
+    ///
+    /// Returns `None` for error responses and results that are not objects.
+    pub fn meta_mut(&mut self) -> Option<&mut Meta> {
+        meta::meta_in_mut(&mut self.result, false)
+    }
 }
 
 /// JSON-RPC 2.0 notification message.
@@ -306,6 +344,18 @@ impl JsonRpcNotification {
             None => serde_json::from_value(Value::Null),
         }
     }
+
+    /// The `_meta` object of the parameters, if any.
+    pub fn meta(&self) -> Option<&Meta> {
+        meta::meta_in(self.params.as_ref())
+    }
+
+    /// The `_meta` object of the parameters, created if missing.
+    ///
+    /// Returns `None` when the parameters are not an object.
+    pub fn meta_mut(&mut self) -> Option<&mut Meta> {
+        meta::meta_in_mut(&mut self.params, true)
+    }
 }
 
 /// JSON-RPC 2.0 error object.
@@ -430,6 +480,11 @@ impl JsonRpcError {
     pub fn is_application_error(&self) -> bool {
         matches!(self.code, -32099..=-32000)
     }
+
+    /// The error code, classified.
+    pub fn kind(&self) -> ServerErrorCode {
+        ServerErrorCode::from_code(self.code)
+    }
 }
 
 impl std::fmt::Display for JsonRpcError {
@@ -531,6 +586,27 @@ impl JsonRpcMessage {
     pub fn expects_response(&self) -> bool {
         matches!(self, Self::Request(_))
     }
+
+    /// The `_meta` object of the parameters or result, if any.
+    pub fn meta(&self) -> Option<&Meta> {
+        match self {
+            Self::Request(req) => req.meta(),
+            Self::Response(resp) => resp.meta(),
+            Self::Notification(notif) => notif.meta(),
+        }
+    }
+
+    /// The `_meta` object of the parameters or result, created if missing.
+    ///
+    /// Returns `None` for error responses and for parameters or results that
+    /// are not objects.
+    pub fn meta_mut(&mut self) -> Option<&mut Meta> {
+        match self {
+            Self::Request(req) => req.meta_mut(),
+            Self::Response(resp) => resp.meta_mut(),
+            Self::Notification(notif) => notif.meta_mut(),
+        }
+    }
 }
 
 impl From<JsonRpcRequest> for JsonRpcMessage {
@@ -615,6 +691,27 @@ mod tests {
         assert!(app_error.is_application_error());
     }
 
+    #[test]
+    fn test_error_response_into_result() {
+        let response = JsonRpcResponse::error("1", JsonRpcError::method_not_found("tools/frob"));
+        assert_eq!(
+            response.error.as_ref().unwrap().kind(),
+            ServerErrorCode::MethodNotFound
+        );
+        assert!(matches!(
+            response.into_result(),
+            Err(McpError::Protocol(
+                crate::error::ProtocolError::ServerError {
+                    code: ServerErrorCode::MethodNotFound,
+                    ..
+                }
+            ))
+        ));
+
+        let response = JsonRpcResponse::success("2", json!({ "ok": true }));
+        assert_eq!(response.into_result().unwrap(), json!({ "ok": true }));
+    }
+
     #[test]
     fn test_request_id_variants() {
         let string_id = RequestId::from("test");
@@ -21,9 +21,11 @@
 //!     protocol_version: ProtocolVersion::V2024_11_05,
 //!     capabilities: Capabilities::default(),
 //!     client_info: Implementation::new("mcp-probe", "0.1.0"),
+//!     meta: None,
 //! };
 //! ```
 
+use super::meta::Meta;
 use super::{Capabilities, Implementation, ProtocolVersion};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -44,6 +46,10 @@ pub struct InitializeRequest {
     /// Information about the client implementation
     #[serde(rename = "clientInfo")]
     pub client_info: Implementation,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 impl InitializeRequest {
@@ -69,6 +75,7 @@ impl InitializeRequest {
             protocol_version,
             capabilities,
             client_info,
+            meta: None,
         }
     }
 
@@ -135,6 +142,14 @@ pub struct InitializeResponse {
     /// Optional instructions or additional information for the client
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+
+    /// Fields the server sent that this type does not model
+    #[serde(flatten)]
+    pub extra: std::collections::HashMap<String, Value>,
 }
 
 impl InitializeResponse {
@@ -163,6 +178,8 @@ impl InitializeResponse {
             capabilities,
             server_info,
             instructions,
+            meta: None,
+            extra: std::collections::HashMap::new(),
         }
     }
 
@@ -24,8 +24,12 @@ pub enum LogLevel {
     Warning,
     /// Error level logging
     Error,
-    /// Critical level logging (least verbose)
+    /// Critical level logging
     Critical,
+    /// Alert level logging, action must be taken immediately
+    Alert,
+    /// Emergency level logging, the system is unusable (least verbose)
+    Emergency,
 }
 
 impl LogLevel {
@@ -38,6 +42,8 @@ impl LogLevel {
             Self::Warning,
             Self::Error,
             Self::Critical,
+            Self::Alert,
+            Self::Emergency,
         ]
     }
 
@@ -61,6 +67,8 @@ impl std::fmt::Display for LogLevel {
             Self::Warning => "warning",
             Self::Error => "error",
             Self::Critical => "critical",
+            Self::Alert => "alert",
+            Self::Emergency => "emergency",
         };
         write!(f, "{}", s)
     }
@@ -323,6 +331,8 @@ mod tests {
         assert!(LogLevel::Notice < LogLevel::Warning);
         assert!(LogLevel::Warning < LogLevel::Error);
         assert!(LogLevel::Error < LogLevel::Critical);
+        assert!(LogLevel::Critical < LogLevel::Alert);
+        assert!(LogLevel::Alert < LogLevel::Emergency);
 
         assert!(LogLevel::Debug.is_more_verbose_than(&LogLevel::Error));
         assert!(LogLevel::Error.is_less_verbose_than(&LogLevel::Debug));
@@ -331,7 +341,17 @@ mod tests {
     #[test]
     fn test_log_level_serialization() {
         let levels = LogLevel::all();
-        let expected = ["debug", "info", "notice", "warning", "error", "critical"];
+        let expected = [
+            "debug",
+            "info",
+            "notice",
+            "warning",
+            "error",
+            "critical",
+            "alert",
+            "emergency",
+        ];
+        assert_eq!(levels.len(), expected.len());
 
         for (level, expected) in levels.iter().zip(expected.iter()) {
             let json = serde_json::to_string(level).unwrap();
@@ -0,0 +1,218 @@
+//! The `_meta` object carried by MCP messages.
+//!
+//! MCP reserves a `_meta` member on request parameters, results and content
+//! blocks for data outside the protocol proper: progress tokens, trace
+//! context, correlation IDs. The typed messages keep it in a `meta` field, so
+//! it survives a round trip through them, and [`HasMeta`] reads and writes it
+//! the same way on all of them.
+//!
+//! Interceptors see raw JSON-RPC messages rather than typed ones; for them
+//! [`JsonRpcMessage::meta`] and [`JsonRpcMessage::meta_mut`] reach the same
+//! object inside the request parameters or the result.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::messages::meta::HasMeta;
+//! use mcp_probe_core::messages::{CallToolRequest, JsonRpcRequest};
+//! use serde_json::json;
+//!
+//! let call: CallToolRequest = serde_json::from_value(json!({
+//!     "name": "search",
+//!     "_meta": { "traceparent": "00-4bf92f35-00f067aa-01" }
+//! }))
+//! .unwrap();
+//! assert_eq!(
+//!     call.meta_value("traceparent"),
+//!     Some(&json!("00-4bf92f35-00f067aa-01"))
+//! );
+//!
+//! let mut request = JsonRpcRequest::new("1", "tools/call", serde_json::to_value(&call).unwrap());
+//! request.meta_mut().unwrap().insert("correlationId".into(), json!("c-1"));
+//! assert_eq!(request.params.unwrap()["_meta"]["correlationId"], "c-1");
+//! ```
+
+use serde_json::{Map, Value};
+
+#[cfg(doc)]
+use super::JsonRpcMessage;
+use super::{
+    CallToolRequest, CallToolResponse, GetPromptRequest, GetPromptResponse, InitializeRequest,
+    InitializeResponse, ListPromptsRequest, ListPromptsResponse, ListResourcesRequest,
+    ListResourcesResponse, ListToolsRequest, ListToolsResponse, Prompt, ReadResourceRequest,
+    ReadResourceResponse, Resource, SubscribeRequest, Tool, UnsubscribeRequest,
+};
+
+/// Name of the metadata member in parameters, results and content blocks
+pub const META_KEY: &str = "_meta";
+
+/// Contents of a `_meta` object
+pub type Meta = Map<String, Value>;
+
+/// Messages and content blocks that carry a `_meta` object.
+pub trait HasMeta {
+    /// The `_meta` object, if the message has one.
+    fn meta(&self) -> Option<&Meta>;
+
+    /// The `_meta` object, created empty if the message has none.
+    fn meta_mut(&mut self) -> &mut Meta;
+
+    /// One entry of the `_meta` object.
+    fn meta_value(&self, key: &str) -> Option<&Value> {
+        self.meta()?.get(key)
+    }
+
+    /// Set one entry of the `_meta` object, returning the previous value.
+    fn set_meta(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        self.meta_mut().insert(key.into(), value)
+    }
+
+    /// Builder form of [`set_meta`](Self::set_meta).
+    fn with_meta(mut self, key: impl Into<String>, value: Value) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_meta(key, value);
+        self
+    }
+}
+
+macro_rules! impl_has_meta {
+    ($($message:ty),* $(,)?) => {
+        $(
+            impl HasMeta for $message {
+                fn meta(&self) -> Option<&Meta> {
+                    self.meta.as_ref()
+                }
+
+                fn meta_mut(&mut self) -> &mut Meta {
+                    self.meta.get_or_insert_with(Meta::new)
+                }
+            }
+        )*
+    };
+}
+
+impl_has_meta!(
+    InitializeRequest,
+    InitializeResponse,
+    ListToolsRequest,
+    ListToolsResponse,
+    Tool,
+    CallToolRequest,
+    CallToolResponse,
+    ListResourcesRequest,
+    ListResourcesResponse,
+    Resource,
+    ReadResourceRequest,
+    ReadResourceResponse,
+    SubscribeRequest,
+    UnsubscribeRequest,
+    ListPromptsRequest,
+    ListPromptsResponse,
+    Prompt,
+    GetPromptRequest,
+    GetPromptResponse,
+);
+
+/// The `_meta` object inside a JSON parameters or result value
+pub(crate) fn meta_in(value: Option<&Value>) -> Option<&Meta> {
+    value?.get(META_KEY)?.as_object()
+}
+
+/// The `_meta` object inside a JSON value, created if the value is an object
+/// without one. A missing value becomes an empty object when `create` is set.
+pub(crate) fn meta_in_mut(value: &mut Option<Value>, create: bool) -> Option<&mut Meta> {
+    if value.is_none() && create {
+        *value = Some(Value::Object(Map::new()));
+    }
+    let entry = value
+        .as_mut()?
+        .as_object_mut()?
+        .entry(META_KEY)
+        .or_insert_with(|| Value::Object(Map::new()));
+    entry.as_object_mut()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{JsonRpcMessage, JsonRpcResponse, ToolResult};
+    use serde_json::json;
+
+    #[test]
+    fn test_meta_round_trips_through_typed_messages() {
+        let wire = json!({
+            "content": [
+                { "type": "text", "text": "42", "_meta": { "source": "cache" } }
+            ],
+            "_meta": { "io.example/trace": { "span": "a1" } }
+        });
+        let response: CallToolResponse = serde_json::from_value(wire.clone()).unwrap();
+        assert_eq!(
+            response.meta_value("io.example/trace"),
+            Some(&json!({ "span": "a1" }))
+        );
+        assert_eq!(
+            response.content[0].meta_value("source"),
+            Some(&json!("cache"))
+        );
+        assert_eq!(serde_json::to_value(&response).unwrap(), wire);
+
+        let tool: Tool = serde_json::from_value(json!({
+            "name": "search",
+            "description": "Search",
+            "_meta": { "owner": "docs" }
+        }))
+        .unwrap();
+        assert!(tool.extra.is_empty());
+        assert_eq!(tool.meta_value("owner"), Some(&json!("docs")));
+
+        // Absent stays absent
+        let text = ToolResult::text("plain");
+        assert_eq!(
+            serde_json::to_value(&text).unwrap(),
+            json!({ "type": "text", "text": "plain" })
+        );
+        assert_eq!(
+            serde_json::to_value(text.with_meta("k", json!(1))).unwrap()["_meta"],
+            json!({ "k": 1 })
+        );
+    }
+
+    #[test]
+    fn test_meta_on_raw_messages() {
+        let mut message: JsonRpcMessage =
+            serde_json::from_value(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+                .unwrap();
+        assert!(message.meta().is_none());
+        message
+            .meta_mut()
+            .unwrap()
+            .insert("correlationId".into(), json!("c-1"));
+        assert_eq!(
+            serde_json::to_value(&message).unwrap()["params"],
+            json!({ "_meta": { "correlationId": "c-1" } })
+        );
+
+        let mut response =
+            JsonRpcMessage::Response(JsonRpcResponse::success(1i64, json!({ "tools": [] })));
+        response
+            .meta_mut()
+            .unwrap()
+            .insert("correlationId".into(), json!("c-1"));
+        assert_eq!(response.meta().unwrap()["correlationId"], "c-1");
+
+        // Errors and positional parameters have nowhere to put it
+        let mut error: JsonRpcMessage = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "nope" }
+        }))
+        .unwrap();
+        assert!(error.meta_mut().is_none());
+        let mut positional: JsonRpcMessage = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "method": "log", "params": ["a"]
+        }))
+        .unwrap();
+        assert!(positional.meta_mut().is_none());
+    }
+}
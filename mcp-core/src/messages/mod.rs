@@ -18,6 +18,7 @@
 //! - **Prompts**: Prompt templates and completion requests
 //! - **Sampling**: LLM completion requests from server to client
 //! - **Logging**: Server-to-client logging messages
+//! - **Metadata**: The `_meta` object on parameters, results and content blocks
 //!
 //! # Examples
 //!
@@ -34,6 +35,7 @@
 //!         version: "0.1.0".to_string(),
 //!         metadata: std::collections::HashMap::new(),
 //!     },
+//!     meta: None,
 //! };
 //!
 //! // Wrap in JSON-RPC request
@@ -47,6 +49,7 @@
 pub mod core;
 pub mod initialization;
 pub mod logging;
+pub mod meta;
 pub mod prompts;
 pub mod resources;
 pub mod sampling;
@@ -61,6 +64,7 @@ pub use logging::{
     ResourceUpdatedNotification as LoggingResourceUpdatedNotification, SetLevelRequest,
     ToolListChangedNotification as LoggingToolListChangedNotification,
 };
+pub use meta::{HasMeta, Meta};
 pub use prompts::{
     GetPromptRequest, GetPromptResponse, ListPromptsRequest, ListPromptsResponse,
     MessageRole as PromptMessageRole, Prompt, PromptContent, PromptListChangedNotification,
@@ -188,7 +192,11 @@ pub struct StandardCapabilities {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct ToolCapabilities {
     /// Whether the server supports listing changed tools
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "listChanged",
+        alias = "list_changed",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub list_changed: Option<bool>,
 }
 
@@ -200,7 +208,11 @@ pub struct ResourceCapabilities {
     pub subscribe: Option<bool>,
 
     /// Whether the server supports listing changed resources
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "listChanged",
+        alias = "list_changed",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub list_changed: Option<bool>,
 }
 
@@ -208,7 +220,11 @@ pub struct ResourceCapabilities {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct PromptCapabilities {
     /// Whether the server supports listing changed prompts
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "listChanged",
+        alias = "list_changed",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub list_changed: Option<bool>,
 }
 
@@ -232,7 +248,11 @@ pub struct LoggingCapabilities {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct RootsCapabilities {
     /// Whether the client supports providing root directories
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "listChanged",
+        alias = "list_changed",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub list_changed: Option<bool>,
 }
 
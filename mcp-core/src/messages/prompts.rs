@@ -10,12 +10,18 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use super::meta::{HasMeta, Meta};
+
 /// Request to list available prompts from the server.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ListPromptsRequest {
     /// Optional cursor for pagination
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Response containing the list of available prompts.
@@ -27,6 +33,10 @@ pub struct ListPromptsResponse {
     /// Optional cursor for next page of results
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Prompt definition including schema and metadata.
@@ -41,6 +51,14 @@ pub struct Prompt {
     /// JSON Schema for the prompt's arguments
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<Value>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+
+    /// Fields the server sent that this type does not model
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
 impl Prompt {
@@ -50,6 +68,8 @@ impl Prompt {
             name: name.into(),
             description: description.into(),
             arguments: None,
+            meta: None,
+            extra: HashMap::new(),
         }
     }
 
@@ -69,6 +89,10 @@ pub struct GetPromptRequest {
     /// Arguments to substitute in the prompt
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<Value>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Response containing the generated prompt content.
@@ -81,6 +105,10 @@ pub struct GetPromptResponse {
     /// Generated messages for the prompt
     #[serde(default)]
     pub messages: Vec<PromptMessage>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// A message in a prompt template.
@@ -136,6 +164,10 @@ pub enum PromptContent {
     Text {
         /// The text content
         text: String,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 
     /// Image content
@@ -147,6 +179,10 @@ pub enum PromptContent {
         /// MIME type of the image
         #[serde(rename = "mimeType")]
         mime_type: String,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 
     /// Resource reference
@@ -154,13 +190,20 @@ pub enum PromptContent {
     Resource {
         /// Resource reference
         resource: ResourceReference,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 }
 
 impl PromptContent {
     /// Create text content.
     pub fn text(text: impl Into<String>) -> Self {
-        Self::Text { text: text.into() }
+        Self::Text {
+            text: text.into(),
+            meta: None,
+        }
     }
 
     /// Create image content.
@@ -168,6 +211,7 @@ impl PromptContent {
         Self::Image {
             data: data.into(),
             mime_type: mime_type.into(),
+            meta: None,
         }
     }
 
@@ -178,6 +222,7 @@ impl PromptContent {
                 uri: uri.into(),
                 text: None,
             },
+            meta: None,
         }
     }
 
@@ -188,6 +233,25 @@ impl PromptContent {
                 uri: uri.into(),
                 text: Some(text.into()),
             },
+            meta: None,
+        }
+    }
+}
+
+impl HasMeta for PromptContent {
+    fn meta(&self) -> Option<&Meta> {
+        match self {
+            Self::Text { meta, .. } | Self::Image { meta, .. } | Self::Resource { meta, .. } => {
+                meta.as_ref()
+            }
+        }
+    }
+
+    fn meta_mut(&mut self) -> &mut Meta {
+        match self {
+            Self::Text { meta, .. } | Self::Image { meta, .. } | Self::Resource { meta, .. } => {
+                meta.get_or_insert_with(Meta::new)
+            }
         }
     }
 }
@@ -248,7 +312,10 @@ mod tests {
 
     #[test]
     fn test_list_prompts_request() {
-        let request = ListPromptsRequest { cursor: None };
+        let request = ListPromptsRequest {
+            cursor: None,
+            meta: None,
+        };
         let json = serde_json::to_string(&request).unwrap();
         let deserialized: ListPromptsRequest = serde_json::from_str(&json).unwrap();
         assert_eq!(request, deserialized);
@@ -259,6 +326,7 @@ mod tests {
         let request = GetPromptRequest {
             name: "code_review".to_string(),
             arguments: Some(json!({"language": "rust", "code": "fn main() {}"})),
+            meta: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
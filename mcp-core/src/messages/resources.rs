@@ -10,12 +10,18 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use super::meta::{HasMeta, Meta};
+
 /// Request to list available resources from the server.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ListResourcesRequest {
     /// Optional cursor for pagination
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Response containing the list of available resources.
@@ -27,6 +33,10 @@ pub struct ListResourcesResponse {
     /// Optional cursor for next page of results
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Resource definition including metadata and access information.
@@ -43,8 +53,16 @@ pub struct Resource {
     pub description: Option<String>,
 
     /// MIME type of the resource content
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "mimeType", skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+
+    /// Fields the server sent that this type does not model
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
 impl Resource {
@@ -55,6 +73,8 @@ impl Resource {
             name: name.into(),
             description: None,
             mime_type: None,
+            meta: None,
+            extra: HashMap::new(),
         }
     }
 
@@ -76,6 +96,10 @@ impl Resource {
 pub struct ReadResourceRequest {
     /// URI of the resource to read
     pub uri: String,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Response containing the content of a resource.
@@ -84,6 +108,10 @@ pub struct ReadResourceResponse {
     /// Content of the resource
     #[serde(default)]
     pub contents: Vec<ResourceContent>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Content of a resource.
@@ -103,6 +131,10 @@ pub enum ResourceContent {
         #[serde(rename = "mimeType")]
         #[serde(skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 
     /// Binary content (base64 encoded)
@@ -118,6 +150,10 @@ pub enum ResourceContent {
         #[serde(rename = "mimeType")]
         #[serde(skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 }
 
@@ -128,6 +164,7 @@ impl ResourceContent {
             text: text.into(),
             uri: uri.into(),
             mime_type: None,
+            meta: None,
         }
     }
 
@@ -141,6 +178,7 @@ impl ResourceContent {
             text: text.into(),
             uri: uri.into(),
             mime_type: Some(mime_type.into()),
+            meta: None,
         }
     }
 
@@ -150,6 +188,7 @@ impl ResourceContent {
             blob: blob.into(),
             uri: uri.into(),
             mime_type: None,
+            meta: None,
         }
     }
 
@@ -163,6 +202,7 @@ impl ResourceContent {
             blob: blob.into(),
             uri: uri.into(),
             mime_type: Some(mime_type.into()),
+            meta: None,
         }
     }
 
@@ -183,11 +223,29 @@ impl ResourceContent {
     }
 }
 
+impl HasMeta for ResourceContent {
+    fn meta(&self) -> Option<&Meta> {
+        match self {
+            Self::Text { meta, .. } | Self::Blob { meta, .. } => meta.as_ref(),
+        }
+    }
+
+    fn meta_mut(&mut self) -> &mut Meta {
+        match self {
+            Self::Text { meta, .. } | Self::Blob { meta, .. } => meta.get_or_insert_with(Meta::new),
+        }
+    }
+}
+
 /// Request to subscribe to changes in a resource.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SubscribeRequest {
     /// URI of the resource to subscribe to
     pub uri: String,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Request to unsubscribe from changes in a resource.
@@ -195,6 +253,10 @@ pub struct SubscribeRequest {
 pub struct UnsubscribeRequest {
     /// URI of the resource to unsubscribe from
     pub uri: String,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Notification that a resource has been updated.
@@ -264,7 +326,10 @@ mod tests {
 
     #[test]
     fn test_list_resources_request() {
-        let request = ListResourcesRequest { cursor: None };
+        let request = ListResourcesRequest {
+            cursor: None,
+            meta: None,
+        };
         let json = serde_json::to_string(&request).unwrap();
         let deserialized: ListResourcesRequest = serde_json::from_str(&json).unwrap();
         assert_eq!(request, deserialized);
@@ -274,6 +339,7 @@ mod tests {
     fn test_read_resource_request() {
         let request = ReadResourceRequest {
             uri: "file:///path/to/file.txt".to_string(),
+            meta: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -27,15 +27,15 @@ pub struct CompletionArgument {
     pub messages: Vec<SamplingMessage>,
 
     /// Optional model selection
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "modelPreferences")]
     pub model_preferences: Option<ModelPreferences>,
 
     /// System prompt for the completion
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "systemPrompt")]
     pub system_prompt: Option<String>,
 
     /// Include context about tools available to the model
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "includeContext")]
     pub include_context: Option<String>,
 
     /// Temperature for sampling (0.0 to 1.0)
@@ -43,11 +43,11 @@ pub struct CompletionArgument {
     pub temperature: Option<f64>,
 
     /// Maximum number of tokens to generate
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "maxTokens")]
     pub max_tokens: Option<i32>,
 
     /// Stop sequences for completion
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "stopSequences")]
     pub stop_sequences: Option<Vec<String>>,
 
     /// Additional metadata for the request
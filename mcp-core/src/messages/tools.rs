@@ -10,12 +10,18 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use super::meta::{HasMeta, Meta};
+
 /// Request to list available tools from the server.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ListToolsRequest {
     /// Optional cursor for pagination
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Response containing the list of available tools.
@@ -27,6 +33,10 @@ pub struct ListToolsResponse {
     /// Optional cursor for next page of results
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Tool definition including schema and metadata.
@@ -53,6 +63,14 @@ pub struct Tool {
     /// Return type schema for the tool
     #[serde(rename = "returnType", skip_serializing_if = "Option::is_none")]
     pub return_type: Option<Value>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+
+    /// Fields the server sent that this type does not model
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
 // Custom deserializer for Tool to handle multiple schema field names
@@ -61,7 +79,7 @@ impl<'de> Deserialize<'de> for Tool {
     where
         D: serde::Deserializer<'de>,
     {
-        use serde::de::{self, MapAccess, Visitor};
+        use serde::de::{self, IntoDeserializer, MapAccess, Visitor};
         use std::fmt;
 
         #[derive(Deserialize)]
@@ -77,6 +95,8 @@ impl<'de> Deserialize<'de> for Tool {
             #[serde(alias = "read_only")]
             ReadOnly,
             ReturnType,
+            #[serde(rename = "_meta")]
+            Meta,
             #[serde(other)]
             Unknown,
         }
@@ -100,9 +120,14 @@ impl<'de> Deserialize<'de> for Tool {
                 let mut extensions = None;
                 let mut read_only = None;
                 let mut return_type = None;
-
-                while let Some(key) = map.next_key()? {
-                    match key {
+                let mut meta = None;
+                let mut extra = HashMap::new();
+
+                while let Some(key) = map.next_key::<String>()? {
+                    let field: Field = Field::deserialize(
+                        IntoDeserializer::<V::Error>::into_deserializer(key.as_str()),
+                    )?;
+                    match field {
                         Field::Name => {
                             if name.is_some() {
                                 return Err(de::Error::duplicate_field("name"));
@@ -149,9 +174,14 @@ impl<'de> Deserialize<'de> for Tool {
                             }
                             return_type = Some(map.next_value()?);
                         }
+                        Field::Meta => {
+                            if meta.is_some() {
+                                return Err(de::Error::duplicate_field("_meta"));
+                            }
+                            meta = Some(map.next_value()?);
+                        }
                         Field::Unknown => {
-                            // Skip unknown fields
-                            let _: Value = map.next_value()?;
+                            extra.insert(key, map.next_value()?);
                         }
                     }
                 }
@@ -167,6 +197,8 @@ impl<'de> Deserialize<'de> for Tool {
                     extensions,
                     read_only,
                     return_type,
+                    meta,
+                    extra,
                 })
             }
         }
@@ -179,6 +211,7 @@ impl<'de> Deserialize<'de> for Tool {
             "extensions",
             "readOnly",
             "returnType",
+            "_meta",
         ];
         deserializer.deserialize_struct("Tool", FIELDS, ToolVisitor)
     }
@@ -194,6 +227,8 @@ impl Tool {
             extensions: None,
             read_only: None,
             return_type: None,
+            meta: None,
+            extra: HashMap::new(),
         }
     }
 
@@ -231,6 +266,10 @@ pub struct CallToolRequest {
     /// Arguments to pass to the tool
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<Value>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Response from a tool call operation.
@@ -243,6 +282,10 @@ pub struct CallToolResponse {
     /// Whether the tool is making a progress notification
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+
+    /// Metadata outside the protocol proper, sent as `_meta`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
 /// Result content from a tool execution.
@@ -254,6 +297,10 @@ pub enum ToolResult {
     Text {
         /// The text content
         text: String,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 
     /// Image content result
@@ -265,6 +312,10 @@ pub enum ToolResult {
         /// MIME type of the image
         #[serde(rename = "mimeType")]
         mime_type: String,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 
     /// Resource reference result
@@ -272,9 +323,61 @@ pub enum ToolResult {
     Resource {
         /// URI of the resource
         resource: ResourceReference,
+
+        /// Metadata outside the protocol proper, sent as `_meta`
+        #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
     },
 }
 
+impl ToolResult {
+    /// Create text content.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text {
+            text: text.into(),
+            meta: None,
+        }
+    }
+
+    /// Create image content.
+    pub fn image(data: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        Self::Image {
+            data: data.into(),
+            mime_type: mime_type.into(),
+            meta: None,
+        }
+    }
+
+    /// Create a resource reference.
+    pub fn resource(uri: impl Into<String>) -> Self {
+        Self::Resource {
+            resource: ResourceReference {
+                uri: uri.into(),
+                text: None,
+            },
+            meta: None,
+        }
+    }
+}
+
+impl HasMeta for ToolResult {
+    fn meta(&self) -> Option<&Meta> {
+        match self {
+            Self::Text { meta, .. } | Self::Image { meta, .. } | Self::Resource { meta, .. } => {
+                meta.as_ref()
+            }
+        }
+    }
+
+    fn meta_mut(&mut self) -> &mut Meta {
+        match self {
+            Self::Text { meta, .. } | Self::Image { meta, .. } | Self::Resource { meta, .. } => {
+                meta.get_or_insert_with(Meta::new)
+            }
+        }
+    }
+}
+
 /// Reference to a resource.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ResourceReference {
@@ -332,7 +435,10 @@ mod tests {
 
     #[test]
     fn test_list_tools_request() {
-        let request = ListToolsRequest { cursor: None };
+        let request = ListToolsRequest {
+            cursor: None,
+            meta: None,
+        };
         let json = serde_json::to_string(&request).unwrap();
         let deserialized: ListToolsRequest = serde_json::from_str(&json).unwrap();
         assert_eq!(request, deserialized);
@@ -343,6 +449,7 @@ mod tests {
         let request = CallToolRequest {
             name: "calculator".to_string(),
             arguments: Some(json!({"expression": "2 + 2"})),
+            meta: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -352,9 +459,7 @@ mod tests {
 
     #[test]
     fn test_tool_result_text() {
-        let result = ToolResult::Text {
-            text: "The answer is 4".to_string(),
-        };
+        let result = ToolResult::text("The answer is 4");
 
         let json = serde_json::to_value(&result).unwrap();
         assert_eq!(json["type"], "text");
@@ -363,10 +468,7 @@ mod tests {
 
     #[test]
     fn test_tool_result_image() {
-        let result = ToolResult::Image {
-            data: "base64data".to_string(),
-            mime_type: "image/png".to_string(),
-        };
+        let result = ToolResult::image("base64data", "image/png");
 
         let json = serde_json::to_value(&result).unwrap();
         assert_eq!(json["type"], "image");
@@ -415,4 +517,19 @@ mod tests {
         assert_eq!(tool.description, "A test tool");
         assert!(tool.input_schema.is_some());
     }
+
+    #[test]
+    fn test_tool_preserves_unknown_fields() {
+        let value = json!({
+            "name": "search",
+            "description": "Search the web",
+            "annotations": { "readOnlyHint": true }
+        });
+
+        let tool: Tool = serde_json::from_value(value).unwrap();
+        assert_eq!(tool.extra["annotations"], json!({ "readOnlyHint": true }));
+
+        let round_trip = serde_json::to_value(&tool).unwrap();
+        assert_eq!(round_trip["annotations"], json!({ "readOnlyHint": true }));
+    }
 }
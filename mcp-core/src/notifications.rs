@@ -0,0 +1,301 @@
+//! Server notifications dispatched to handlers registered by method.
+//!
+//! A [`NotificationRegistry`] routes each notification the client receives
+//! to the handlers registered for its method. Any method can be registered,
+//! including custom and experimental ones. A handler either takes the params
+//! decoded into a type of its choice or the raw [`JsonRpcNotification`].
+//! Several handlers may share a method and run in registration order; one
+//! failing does not stop the others.
+//!
+//! Notifications no handler is registered for go to the fallback handlers.
+//! Without fallbacks they are dropped with a debug log.
+//!
+//! A [`NotificationHandler`] passed to the client is registered for the
+//! methods it covers, see [`NotificationRegistry::register_handler`].
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_probe_core::messages::LoggingNotification;
+//! use mcp_probe_core::notifications::NotificationRegistry;
+//!
+//! let notifications = NotificationRegistry::new();
+//! notifications
+//!     .on("notifications/message", |log: LoggingNotification| async move {
+//!         println!("[{:?}] {}", log.level, log.data);
+//!         Ok(())
+//!     })
+//!     .fallback(|notification| async move {
+//!         println!("unhandled {}", notification.method);
+//!         Ok(())
+//!     });
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::client::NotificationHandler;
+use crate::error::McpResult;
+use crate::messages::{
+    JsonRpcNotification, ProgressNotification, PromptListChangedNotification,
+    ResourceListChangedNotification, ResourceUpdatedNotification, ToolListChangedNotification,
+};
+
+type Handler = Arc<dyn Fn(JsonRpcNotification) -> BoxFuture<'static, McpResult<()>> + Send + Sync>;
+
+#[derive(Default)]
+struct Inner {
+    methods: HashMap<String, Vec<Handler>>,
+    fallbacks: Vec<Handler>,
+}
+
+/// Notification handlers keyed by method.
+///
+/// Registration takes `&self`, so handlers can be added to a shared registry
+/// while the client is running.
+#[derive(Default)]
+pub struct NotificationRegistry {
+    inner: Mutex<Inner>,
+}
+
+impl NotificationRegistry {
+    /// Create a registry without handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry that passes the standard notifications to `handler`
+    pub fn from_handler(handler: Arc<dyn NotificationHandler>) -> Self {
+        let registry = Self::new();
+        registry.register_handler(handler);
+        registry
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // Handlers are only ever appended, so a panic cannot leave a partial entry
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Handle `method` with params decoded into `T`.
+    ///
+    /// Missing params decode from an empty object. A notification whose
+    /// params do not decode is logged as a handler failure.
+    pub fn on<T, F, Fut>(&self, method: impl Into<String>, handler: F) -> &Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = McpResult<()>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.on_raw(method, move |notification: JsonRpcNotification| {
+            let handler = Arc::clone(&handler);
+            async move {
+                let params = notification
+                    .params
+                    .unwrap_or_else(|| Value::Object(Default::default()));
+                let params: T = crate::decode::from_value(params)?;
+                handler(params).await
+            }
+        })
+    }
+
+    /// Handle `method` with the notification as received
+    pub fn on_raw<F, Fut>(&self, method: impl Into<String>, handler: F) -> &Self
+    where
+        F: Fn(JsonRpcNotification) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = McpResult<()>> + Send + 'static,
+    {
+        let handler = boxed(handler);
+        self.lock()
+            .methods
+            .entry(method.into())
+            .or_default()
+            .push(handler);
+        self
+    }
+
+    /// Handle notifications of every method without a registered handler
+    pub fn fallback<F, Fut>(&self, handler: F) -> &Self
+    where
+        F: Fn(JsonRpcNotification) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = McpResult<()>> + Send + 'static,
+    {
+        self.lock().fallbacks.push(boxed(handler));
+        self
+    }
+
+    /// Register `handler` for the notifications [`NotificationHandler`] covers
+    pub fn register_handler(&self, handler: Arc<dyn NotificationHandler>) -> &Self {
+        let h = Arc::clone(&handler);
+        self.on("notifications/progress", move |n: ProgressNotification| {
+            let h = Arc::clone(&h);
+            async move { h.handle_progress(n).await }
+        });
+        let h = Arc::clone(&handler);
+        self.on(
+            "notifications/resources/updated",
+            move |n: ResourceUpdatedNotification| {
+                let h = Arc::clone(&h);
+                async move { h.handle_resource_updated(n).await }
+            },
+        );
+        let h = Arc::clone(&handler);
+        self.on(
+            "notifications/resources/list_changed",
+            move |n: ResourceListChangedNotification| {
+                let h = Arc::clone(&h);
+                async move { h.handle_resource_list_changed(n).await }
+            },
+        );
+        let h = Arc::clone(&handler);
+        self.on(
+            "notifications/tools/list_changed",
+            move |n: ToolListChangedNotification| {
+                let h = Arc::clone(&h);
+                async move { h.handle_tool_list_changed(n).await }
+            },
+        );
+        self.on(
+            "notifications/prompts/list_changed",
+            move |n: PromptListChangedNotification| {
+                let h = Arc::clone(&handler);
+                async move { h.handle_prompt_list_changed(n).await }
+            },
+        )
+    }
+
+    /// Move every handler of `other` into this registry, after the existing ones
+    pub fn extend(&self, other: NotificationRegistry) -> &Self {
+        let other = other.inner.into_inner().unwrap_or_else(|e| e.into_inner());
+        let mut inner = self.lock();
+        for (method, handlers) in other.methods {
+            inner.methods.entry(method).or_default().extend(handlers);
+        }
+        inner.fallbacks.extend(other.fallbacks);
+        drop(inner);
+        self
+    }
+
+    /// Whether a handler is registered for `method` itself
+    pub fn handles(&self, method: &str) -> bool {
+        self.lock().methods.contains_key(method)
+    }
+
+    /// Run the handlers for a notification and return how many ran
+    pub async fn dispatch(&self, notification: JsonRpcNotification) -> usize {
+        let handlers = {
+            let inner = self.lock();
+            inner
+                .methods
+                .get(&notification.method)
+                .unwrap_or(&inner.fallbacks)
+                .clone()
+        };
+        if handlers.is_empty() {
+            debug!("No handler for notification {}", notification.method);
+        }
+        for handler in &handlers {
+            if let Err(e) = handler(notification.clone()).await {
+                warn!("Handler for {} failed: {}", notification.method, e);
+            }
+        }
+        handlers.len()
+    }
+}
+
+impl std::fmt::Debug for NotificationRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let inner = self.lock();
+        let mut methods: Vec<_> = inner.methods.keys().collect();
+        methods.sort();
+        f.debug_struct("NotificationRegistry")
+            .field("methods", &methods)
+            .field("fallbacks", &inner.fallbacks.len())
+            .finish()
+    }
+}
+
+fn boxed<F, Fut>(handler: F) -> Handler
+where
+    F: Fn(JsonRpcNotification) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = McpResult<()>> + Send + 'static,
+{
+    Arc::new(move |notification| -> BoxFuture<'static, McpResult<()>> {
+        Box::pin(handler(notification))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::DefaultNotificationHandler;
+    use crate::messages::{LogLevel, LoggingNotification};
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_typed_handlers_share_a_method() {
+        let registry = NotificationRegistry::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        for name in ["first", "second"] {
+            let tx = tx.clone();
+            registry.on("notifications/message", move |log: LoggingNotification| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send((name, log));
+                    Ok(())
+                }
+            });
+        }
+
+        let notification = JsonRpcNotification::new(
+            "notifications/message",
+            json!({ "level": "warning", "data": "disk almost full", "logger": "fs" }),
+        );
+        assert_eq!(registry.dispatch(notification).await, 2);
+
+        let (name, log) = rx.recv().await.unwrap();
+        assert_eq!(name, "first");
+        assert_eq!(log.level, LogLevel::Warning);
+        assert_eq!(log.logger.as_deref(), Some("fs"));
+        assert_eq!(rx.recv().await.unwrap().0, "second");
+
+        // Undecodable params fail the handler without affecting dispatch
+        let bad = JsonRpcNotification::new("notifications/message", json!({ "level": 3 }));
+        assert_eq!(registry.dispatch(bad).await, 2);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_methods_reach_fallbacks() {
+        let registry = NotificationRegistry::from_handler(Arc::new(DefaultNotificationHandler));
+        assert!(registry.handles("notifications/tools/list_changed"));
+        let list_changed = JsonRpcNotification::without_params("notifications/tools/list_changed");
+        assert_eq!(registry.dispatch(list_changed.clone()).await, 1);
+
+        let experimental = JsonRpcNotification::new("experimental/ping", json!({ "n": 1 }));
+        assert_eq!(registry.dispatch(experimental.clone()).await, 0);
+
+        let extra = NotificationRegistry::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        extra.fallback(move |notification| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(notification.method);
+                Ok(())
+            }
+        });
+        registry.extend(extra);
+
+        assert_eq!(registry.dispatch(experimental).await, 1);
+        assert_eq!(rx.recv().await.unwrap(), "experimental/ping");
+        assert_eq!(registry.dispatch(list_changed).await, 1);
+        assert!(rx.try_recv().is_err());
+    }
+}
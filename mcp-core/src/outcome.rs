@@ -0,0 +1,497 @@
+//! Typed outcomes of `tools/call` results.
+//!
+//! A [`CallToolResponse`](crate::messages::tools::CallToolResponse) signals
+//! failure only through `isError`, and servers freely mix successful output
+//! with error and warning text in the same `content` array. [`ToolOutcome`]
+//! sorts a result into success, failure or partial success, so callers such as
+//! `assist-mcp call` can pick an exit status without parsing text themselves.
+//!
+//! Which text blocks count as errors or warnings is decided by
+//! [`OutcomeHeuristics`], configurable per tool in
+//! `$XDG_CONFIG_HOME/genmcp/outcomes.toml` (falling back to
+//! `~/.config/genmcp/outcomes.toml`):
+//!
+//! ```toml
+//! [default]
+//! warning_prefixes = ["warning:", "warn:", "note:"]
+//!
+//! [tool.search]
+//! # search reports "No results" as an error, but the call still succeeded
+//! error_prefixes = ["error:"]
+//! partial_on_mixed = false
+//! ```
+//!
+//! Fields left out of a table take the built-in defaults.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::outcome::{FailureClass, OutcomeRules, ToolOutcome};
+//! use serde_json::json;
+//!
+//! let result = json!({
+//!     "content": [{ "type": "text", "text": "Error: permission denied for /etc" }],
+//!     "isError": true
+//! });
+//! let outcome = OutcomeRules::default().classify("read_file", &result);
+//! assert!(matches!(
+//!     outcome,
+//!     ToolOutcome::Failure { classification: FailureClass::PermissionDenied, .. }
+//! ));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{ConfigError, McpResult};
+use crate::messages::tools::CallToolResponse;
+
+/// Likely cause of a failed tool call, guessed from its error text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureClass {
+    /// The arguments were missing, malformed or out of range
+    InvalidArguments,
+    /// Something the tool was asked about does not exist
+    NotFound,
+    /// The tool was not allowed to do what it was asked
+    PermissionDenied,
+    /// The tool or something it depends on took too long
+    Timeout,
+    /// The tool or its backend is throttling requests
+    RateLimited,
+    /// A backend the tool depends on is down or unreachable
+    Unavailable,
+    /// Nothing in the error text gave the cause away
+    Other,
+}
+
+impl FailureClass {
+    /// Guess the class of a failure from its error text.
+    pub fn from_text(text: &str) -> Self {
+        let text = text.to_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|needle| text.contains(needle));
+        // Checked in order: "timed out connecting" is a timeout, not unavailability
+        if mentions(&["rate limit", "too many requests", "quota", "throttl"]) {
+            FailureClass::RateLimited
+        } else if mentions(&["timed out", "timeout", "deadline exceeded"]) {
+            FailureClass::Timeout
+        } else if mentions(&[
+            "permission denied",
+            "forbidden",
+            "unauthorized",
+            "not allowed",
+            "access denied",
+        ]) {
+            FailureClass::PermissionDenied
+        } else if mentions(&["not found", "no such", "does not exist", "unknown"]) {
+            FailureClass::NotFound
+        } else if mentions(&[
+            "invalid",
+            "missing",
+            "required",
+            "must be",
+            "expected",
+            "malformed",
+        ]) {
+            FailureClass::InvalidArguments
+        } else if mentions(&[
+            "unavailable",
+            "connection refused",
+            "unreachable",
+            "service down",
+        ]) {
+            FailureClass::Unavailable
+        } else {
+            FailureClass::Other
+        }
+    }
+}
+
+impl fmt::Display for FailureClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FailureClass::InvalidArguments => "invalid arguments",
+            FailureClass::NotFound => "not found",
+            FailureClass::PermissionDenied => "permission denied",
+            FailureClass::Timeout => "timeout",
+            FailureClass::RateLimited => "rate limited",
+            FailureClass::Unavailable => "unavailable",
+            FailureClass::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Which of the three outcomes a result is, without its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeKind {
+    /// The tool did what it was asked
+    Success,
+    /// The tool did not do what it was asked
+    Failure,
+    /// The tool produced output but also reported errors or warnings
+    Partial,
+}
+
+impl fmt::Display for OutcomeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutcomeKind::Success => "success",
+            OutcomeKind::Failure => "failure",
+            OutcomeKind::Partial => "partial",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What a tool call amounted to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ToolOutcome {
+    /// The tool succeeded
+    Success {
+        /// Content blocks of the result
+        content: Vec<Value>,
+    },
+    /// The tool failed
+    Failure {
+        /// Content blocks describing the error
+        error_content: Vec<Value>,
+        /// Likely cause of the failure
+        classification: FailureClass,
+    },
+    /// The tool produced usable output alongside errors or warnings
+    Partial {
+        /// Content blocks that are not errors or warnings
+        content: Vec<Value>,
+        /// Text of the error and warning blocks
+        warnings: Vec<String>,
+    },
+}
+
+impl ToolOutcome {
+    /// Sort a raw `tools/call` result using `heuristics`.
+    pub fn from_result(result: &Value, heuristics: &OutcomeHeuristics) -> Self {
+        let blocks: Vec<Value> = result["content"].as_array().cloned().unwrap_or_default();
+        let is_error = result["isError"].as_bool() == Some(true);
+
+        let mut content = Vec::new();
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        for block in blocks {
+            match block["text"].as_str() {
+                Some(text) if heuristics.is_error_text(text) => errors.push(block),
+                Some(text) if heuristics.is_warning_text(text) => warnings.push(block),
+                _ => content.push(block),
+            }
+        }
+
+        if is_error {
+            if content.is_empty() || !heuristics.partial_on_mixed || errors.is_empty() {
+                // Without an identifiable error block, every block describes the error
+                let mut error_content = errors;
+                error_content.extend(warnings);
+                error_content.extend(content);
+                return Self::failure(error_content);
+            }
+        } else if content.is_empty() && !errors.is_empty() {
+            // The server forgot `isError`, but every block is an error
+            errors.extend(warnings);
+            return Self::failure(errors);
+        } else if errors.is_empty() && warnings.is_empty() || !heuristics.partial_on_mixed {
+            content.extend(errors);
+            content.extend(warnings);
+            return ToolOutcome::Success { content };
+        }
+
+        let warnings = errors
+            .iter()
+            .chain(&warnings)
+            .filter_map(|block| block["text"].as_str())
+            .map(|text| text.trim().to_string())
+            .collect();
+        ToolOutcome::Partial { content, warnings }
+    }
+
+    /// Sort a typed `tools/call` response using `heuristics`.
+    pub fn from_response(response: &CallToolResponse, heuristics: &OutcomeHeuristics) -> Self {
+        let result = serde_json::json!({
+            "content": serde_json::to_value(&response.content).unwrap_or_default(),
+            "isError": response.is_error.unwrap_or(false),
+        });
+        Self::from_result(&result, heuristics)
+    }
+
+    fn failure(error_content: Vec<Value>) -> Self {
+        let text = text_of(&error_content);
+        ToolOutcome::Failure {
+            error_content,
+            classification: FailureClass::from_text(&text),
+        }
+    }
+
+    /// Which outcome this is.
+    pub fn kind(&self) -> OutcomeKind {
+        match self {
+            ToolOutcome::Success { .. } => OutcomeKind::Success,
+            ToolOutcome::Failure { .. } => OutcomeKind::Failure,
+            ToolOutcome::Partial { .. } => OutcomeKind::Partial,
+        }
+    }
+
+    /// Whether the tool succeeded without errors or warnings.
+    pub fn is_success(&self) -> bool {
+        self.kind() == OutcomeKind::Success
+    }
+
+    /// Text of the error content of a failure.
+    pub fn error_text(&self) -> Option<String> {
+        match self {
+            ToolOutcome::Failure { error_content, .. } => Some(text_of(error_content)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ToolOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolOutcome::Success { content } => {
+                write!(f, "Tool succeeded with {} content block(s)", content.len())
+            }
+            ToolOutcome::Failure {
+                error_content,
+                classification,
+            } => {
+                let text = text_of(error_content);
+                let first = text.lines().next().unwrap_or_default();
+                write!(f, "Tool failed ({}): {}", classification, first)
+            }
+            ToolOutcome::Partial { warnings, .. } => write!(
+                f,
+                "Tool partly succeeded with {} warning(s): {}",
+                warnings.len(),
+                warnings.join("; ")
+            ),
+        }
+    }
+}
+
+/// The text blocks of `blocks`, one per line
+fn text_of(blocks: &[Value]) -> String {
+    blocks
+        .iter()
+        .filter_map(|block| block["text"].as_str())
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How the text blocks of one tool's results are read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutcomeHeuristics {
+    /// Text blocks starting with one of these (ignoring case and leading
+    /// whitespace) are errors
+    pub error_prefixes: Vec<String>,
+
+    /// Text blocks starting with one of these are warnings
+    pub warning_prefixes: Vec<String>,
+
+    /// Treat a result mixing errors or warnings with other content as a partial
+    /// success; when off, `isError` alone decides between success and failure
+    pub partial_on_mixed: bool,
+}
+
+impl Default for OutcomeHeuristics {
+    fn default() -> Self {
+        Self {
+            error_prefixes: vec!["error:".to_string(), "failed:".to_string()],
+            warning_prefixes: vec!["warning:".to_string(), "warn:".to_string()],
+            partial_on_mixed: true,
+        }
+    }
+}
+
+impl OutcomeHeuristics {
+    fn is_error_text(&self, text: &str) -> bool {
+        starts_with_any(text, &self.error_prefixes)
+    }
+
+    fn is_warning_text(&self, text: &str) -> bool {
+        starts_with_any(text, &self.warning_prefixes)
+    }
+}
+
+fn starts_with_any(text: &str, prefixes: &[String]) -> bool {
+    let text = text.trim_start().to_lowercase();
+    prefixes
+        .iter()
+        .any(|prefix| !prefix.is_empty() && text.starts_with(&prefix.to_lowercase()))
+}
+
+/// Outcome heuristics for all tools, with overrides by tool name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeRules {
+    /// Heuristics for tools without their own entry
+    #[serde(default)]
+    pub default: OutcomeHeuristics,
+
+    /// Heuristics by tool name
+    #[serde(default, rename = "tool")]
+    pub tools: HashMap<String, OutcomeHeuristics>,
+}
+
+impl OutcomeRules {
+    /// Location of the user's outcome rules
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("genmcp").join("outcomes.toml"))
+    }
+
+    /// Load the user's outcome rules; a missing file means the defaults
+    pub fn load_default() -> McpResult<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load an outcome rules file
+    pub fn load(path: impl AsRef<Path>) -> McpResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|_| ConfigError::FileNotFound {
+            path: path.display().to_string(),
+        })?;
+        toml::from_str(&content).map_err(|e| {
+            ConfigError::InvalidFormat {
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Heuristics in effect for `tool`.
+    pub fn for_tool(&self, tool: &str) -> &OutcomeHeuristics {
+        self.tools.get(tool).unwrap_or(&self.default)
+    }
+
+    /// Sort a raw `tools/call` result of `tool`.
+    pub fn classify(&self, tool: &str, result: &Value) -> ToolOutcome {
+        ToolOutcome::from_result(result, self.for_tool(tool))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::tools::ToolResult;
+    use serde_json::json;
+
+    fn text(text: &str) -> Value {
+        json!({ "type": "text", "text": text })
+    }
+
+    #[test]
+    fn test_success_failure_and_partial() {
+        let rules = OutcomeRules::default();
+
+        let response = CallToolResponse {
+            content: vec![ToolResult::text("5")],
+            is_error: None,
+            meta: None,
+        };
+        assert_eq!(
+            ToolOutcome::from_response(&response, &rules.default),
+            ToolOutcome::Success {
+                content: vec![text("5")]
+            }
+        );
+
+        let failed = json!({ "content": [text("Request timed out after 30s")], "isError": true });
+        let outcome = rules.classify("fetch", &failed);
+        assert_eq!(outcome.kind(), OutcomeKind::Failure);
+        assert_eq!(
+            outcome.to_string(),
+            "Tool failed (timeout): Request timed out after 30s"
+        );
+
+        let mixed = json!({
+            "content": [text("3 files copied"), text("Error: b.txt: permission denied")],
+            "isError": true
+        });
+        assert_eq!(
+            rules.classify("copy", &mixed),
+            ToolOutcome::Partial {
+                content: vec![text("3 files copied")],
+                warnings: vec!["Error: b.txt: permission denied".to_string()],
+            }
+        );
+
+        // Warnings without isError, and errors the server did not flag
+        let warned = json!({ "content": [text("ok"), text("  WARNING: deprecated")] });
+        assert_eq!(rules.classify("any", &warned).kind(), OutcomeKind::Partial);
+        let unflagged = json!({ "content": [text("Error: no such table")] });
+        assert!(matches!(
+            rules.classify("query", &unflagged),
+            ToolOutcome::Failure {
+                classification: FailureClass::NotFound,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_rules_per_tool() {
+        let rules: OutcomeRules = toml::from_str(
+            r#"
+            [tool.search]
+            error_prefixes = ["no results"]
+            partial_on_mixed = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules.default, OutcomeHeuristics::default());
+        assert_eq!(
+            rules.for_tool("search").warning_prefixes,
+            OutcomeHeuristics::default().warning_prefixes
+        );
+
+        let mixed = json!({
+            "content": [text("match: a.rs"), text("No results in vendor/")],
+            "isError": true
+        });
+        assert_eq!(
+            rules.classify("search", &mixed).kind(),
+            OutcomeKind::Failure
+        );
+        let mixed = json!({ "content": [text("match: a.rs"), text("warning: slow")] });
+        assert!(rules.classify("search", &mixed).is_success());
+    }
+
+    #[test]
+    fn test_failure_classes() {
+        let cases = [
+            ("429 Too Many Requests", FailureClass::RateLimited),
+            ("Forbidden", FailureClass::PermissionDenied),
+            (
+                "missing required field 'path'",
+                FailureClass::InvalidArguments,
+            ),
+            ("Service unavailable", FailureClass::Unavailable),
+            ("something broke", FailureClass::Other),
+        ];
+        for (text, class) in cases {
+            assert_eq!(FailureClass::from_text(text), class, "{}", text);
+        }
+    }
+}
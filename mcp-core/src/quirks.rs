@@ -0,0 +1,390 @@
+//! Per-server workarounds ("quirks") applied automatically by the client.
+//!
+//! Real servers deviate from the specification in small ways: some only speak
+//! an older protocol version, some take longer than the default to answer
+//! `initialize`, some mishandle JSON-RPC batches. Rather than patching every
+//! caller, known deviations are recorded once in
+//! `$XDG_CONFIG_HOME/genmcp/quirks.toml` (falling back to
+//! `~/.config/genmcp/quirks.toml`):
+//!
+//! ```toml
+//! [[quirk]]
+//! profile = "legacy-search"
+//! init_timeout = "60s"
+//!
+//! [[quirk]]
+//! target = "https://*.example.com/*"
+//! protocol_version = "2024-11-05"
+//!
+//! [[quirk]]
+//! server = "acme-mcp"
+//! version = "0.3.*"
+//! disable_batching = true
+//! note = "acme-mcp 0.3 answers only the first message of a batch"
+//! ```
+//!
+//! A rule matches on any combination of a [`registry`](crate::registry)
+//! profile name, the transport target (command line or URL) and the server
+//! name and version reported by `initialize`; `*` and `?` act as wildcards.
+//! Profile and target rules are known before connecting and apply to the
+//! handshake itself. Server rules are learned from `initialize`, so quirks
+//! that affect the handshake take effect from the next connection.
+//!
+//! [`McpClient`](crate::client::McpClient) applies `protocol_version` and
+//! `init_timeout` itself. It never batches, so `disable_batching` is reported
+//! for components that do. Rules in effect are listed under the `quirks` key
+//! of [`TransportInfo::metadata`](crate::transport::TransportInfo).
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ConfigError, McpResult};
+use crate::messages::ProtocolVersion;
+use crate::transport::TransportConfig;
+
+/// Behavior toggles for one server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quirks {
+    /// Send requests one at a time instead of as JSON-RPC batches
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disable_batching: bool,
+
+    /// Protocol version to request in `initialize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<ProtocolVersion>,
+
+    /// Minimum time to wait for the `initialize` response
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub init_timeout: Option<Duration>,
+}
+
+impl Quirks {
+    /// Whether no behavior is changed
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Combine with `other`: toggles accumulate, the longest timeout wins and
+    /// `other`'s protocol version replaces ours
+    pub fn merge(&mut self, other: &Quirks) {
+        self.disable_batching |= other.disable_batching;
+        if other.protocol_version.is_some() {
+            self.protocol_version = other.protocol_version.clone();
+        }
+        self.init_timeout = self.init_timeout.max(other.init_timeout);
+    }
+}
+
+/// A set of quirks and the servers it applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuirkRule {
+    /// Registry profile name pattern
+    #[serde(default)]
+    pub profile: Option<String>,
+
+    /// Command line or URL pattern
+    #[serde(default)]
+    pub target: Option<String>,
+
+    /// Pattern for the server name reported by `initialize`
+    #[serde(default)]
+    pub server: Option<String>,
+
+    /// Pattern for the server version; only used with `server`
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// Why the rule exists
+    #[serde(default)]
+    pub note: Option<String>,
+
+    /// Behaviors to change
+    #[serde(flatten)]
+    pub quirks: Quirks,
+}
+
+impl QuirkRule {
+    /// Whether every criterion of the rule matches `fingerprint`
+    pub fn matches(&self, fingerprint: &Fingerprint) -> bool {
+        let criterion = |pattern: &Option<String>, value: Option<&str>| match pattern {
+            Some(pattern) => value.is_some_and(|value| wildcard_match(pattern, value)),
+            None => true,
+        };
+        let (name, version) = match &fingerprint.server {
+            Some((name, version)) => (Some(name.as_str()), Some(version.as_str())),
+            None => (None, None),
+        };
+        criterion(&self.profile, fingerprint.profile.as_deref())
+            && criterion(&self.target, fingerprint.target.as_deref())
+            && criterion(&self.server, name)
+            && criterion(&self.version, version)
+    }
+
+    /// Short description used when reporting the rule
+    pub fn label(&self) -> String {
+        if let Some(note) = &self.note {
+            return note.clone();
+        }
+        let mut criteria = Vec::new();
+        if let Some(profile) = &self.profile {
+            criteria.push(format!("profile={}", profile));
+        }
+        if let Some(target) = &self.target {
+            criteria.push(format!("target={}", target));
+        }
+        if let Some(server) = &self.server {
+            criteria.push(format!("server={}", server));
+        }
+        if let Some(version) = &self.version {
+            criteria.push(format!("version={}", version));
+        }
+        criteria.join(" ")
+    }
+}
+
+/// What is known about a server when looking up its quirks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// Registry profile the connection was made from
+    pub profile: Option<String>,
+    /// Command line or URL
+    pub target: Option<String>,
+    /// Name and version from `initialize`, once connected
+    pub server: Option<(String, String)>,
+}
+
+impl Fingerprint {
+    /// Command line or URL of a transport configuration
+    pub fn target_of(config: &TransportConfig) -> Option<String> {
+        match config {
+            TransportConfig::Stdio(config) => Some(
+                std::iter::once(config.command.as_str())
+                    .chain(config.args.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            #[cfg(feature = "http")]
+            TransportConfig::HttpSse(config) => Some(config.base_url.to_string()),
+            #[cfg(feature = "http")]
+            TransportConfig::HttpStream(config) => Some(config.base_url.to_string()),
+            TransportConfig::InMemory(_) => None,
+            #[cfg(feature = "wasm")]
+            TransportConfig::Browser(config) => Some(config.url.clone()),
+        }
+    }
+}
+
+/// Quirks in effect for a connection and the rules they came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AppliedQuirks {
+    /// Labels of the matching rules, in file order
+    pub rules: Vec<String>,
+    /// Combined behaviors
+    #[serde(flatten)]
+    pub quirks: Quirks,
+}
+
+impl AppliedQuirks {
+    /// Whether no rule matched
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+#[derive(Deserialize)]
+struct QuirkFile {
+    #[serde(default)]
+    quirk: Vec<QuirkRule>,
+}
+
+/// Ordered quirk rules
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuirkDatabase {
+    rules: Vec<QuirkRule>,
+}
+
+impl QuirkDatabase {
+    /// Database holding `rules`
+    pub fn new(rules: Vec<QuirkRule>) -> Self {
+        Self { rules }
+    }
+
+    /// `$XDG_CONFIG_HOME/genmcp/quirks.toml`, or under `~/.config` without it
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("genmcp").join("quirks.toml"))
+    }
+
+    /// Load the user's quirks; a missing file is an empty database
+    pub fn load_default() -> McpResult<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a quirks file
+    pub fn load(path: impl AsRef<Path>) -> McpResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|_| ConfigError::FileNotFound {
+            path: path.display().to_string(),
+        })?;
+        Self::parse(&content, &path.display().to_string())
+    }
+
+    /// Parse quirks `content`; `source` names it in errors
+    fn parse(content: &str, source: &str) -> McpResult<Self> {
+        let invalid = |reason: String| ConfigError::InvalidFormat {
+            path: source.to_string(),
+            reason,
+        };
+        let file: QuirkFile = toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+
+        for (i, rule) in file.quirk.iter().enumerate() {
+            if rule.profile.is_none() && rule.target.is_none() && rule.server.is_none() {
+                return Err(invalid(format!(
+                    "quirk #{} needs a profile, target or server to match",
+                    i + 1
+                ))
+                .into());
+            }
+            if rule.version.is_some() && rule.server.is_none() {
+                return Err(invalid(format!(
+                    "quirk #{} matches a version without a server",
+                    i + 1
+                ))
+                .into());
+            }
+        }
+        Ok(Self { rules: file.quirk })
+    }
+
+    /// Whether the database holds no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Every rule, in file order
+    pub fn rules(&self) -> &[QuirkRule] {
+        &self.rules
+    }
+
+    /// Combine the quirks of every rule matching `fingerprint`
+    pub fn lookup(&self, fingerprint: &Fingerprint) -> AppliedQuirks {
+        let mut applied = AppliedQuirks::default();
+        for rule in self.rules.iter().filter(|rule| rule.matches(fingerprint)) {
+            applied.rules.push(rule.label());
+            applied.quirks.merge(&rule.quirks);
+        }
+        applied
+    }
+}
+
+/// Match `text` against a pattern where `*` is any run and `?` any one character
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUIRKS: &str = r#"
+        [[quirk]]
+        profile = "legacy-*"
+        init_timeout = "60s"
+
+        [[quirk]]
+        target = "https://*.example.com/*"
+        protocol_version = "2024-11-05"
+        init_timeout = "20s"
+
+        [[quirk]]
+        server = "acme-mcp"
+        version = "0.3.*"
+        disable_batching = true
+        note = "acme batches"
+    "#;
+
+    #[test]
+    fn test_rules_match_and_merge() {
+        let database = QuirkDatabase::parse(QUIRKS, "quirks.toml").unwrap();
+        assert_eq!(database.rules().len(), 3);
+
+        let mut fingerprint = Fingerprint {
+            profile: Some("legacy-search".to_string()),
+            target: Some("https://search.example.com/mcp".to_string()),
+            server: None,
+        };
+        let applied = database.lookup(&fingerprint);
+        assert_eq!(
+            applied.rules,
+            vec![
+                "profile=legacy-*".to_string(),
+                "target=https://*.example.com/*".to_string()
+            ]
+        );
+        assert_eq!(applied.quirks.init_timeout, Some(Duration::from_secs(60)));
+        assert_eq!(
+            applied.quirks.protocol_version,
+            Some(ProtocolVersion::V2024_11_05)
+        );
+        assert!(!applied.quirks.disable_batching);
+
+        fingerprint.server = Some(("acme-mcp".to_string(), "0.3.1".to_string()));
+        let applied = database.lookup(&fingerprint);
+        assert_eq!(applied.rules.last().unwrap(), "acme batches");
+        assert!(applied.quirks.disable_batching);
+
+        fingerprint.server = Some(("acme-mcp".to_string(), "0.4.0".to_string()));
+        assert!(!database.lookup(&fingerprint).quirks.disable_batching);
+
+        let stdio = TransportConfig::stdio("node", &["server.js"]);
+        assert_eq!(
+            Fingerprint::target_of(&stdio).as_deref(),
+            Some("node server.js")
+        );
+    }
+
+    #[test]
+    fn test_rules_need_a_criterion() {
+        assert!(QuirkDatabase::parse("[[quirk]]\ndisable_batching = true", "q.toml").is_err());
+        assert!(
+            QuirkDatabase::parse("[[quirk]]\ntarget = \"x\"\nversion = \"1\"", "q.toml").is_err()
+        );
+        assert!(QuirkDatabase::parse(
+            "[[quirk]]\nprofile = \"x\"\ninit_timeout = \"soon\"",
+            "q.toml"
+        )
+        .is_err());
+        assert!(QuirkDatabase::parse("", "q.toml").unwrap().is_empty());
+    }
+}
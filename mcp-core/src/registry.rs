@@ -0,0 +1,444 @@
+//! Named server profiles loaded from a user-level registry file.
+//!
+//! Instead of repeating transport options on every command line, servers are
+//! described once in `$XDG_CONFIG_HOME/genmcp/servers.toml` (falling back to
+//! `~/.config/genmcp/servers.toml`) and referred to by name:
+//!
+//! ```toml
+//! [server.github]
+//! transport = "http-stream"
+//! url = "https://mcp.example.com/github"
+//! auth = { type = "bearer", token = "${GITHUB_TOKEN}" }
+//! timeout = "45s"
+//! tags = ["remote", "vcs"]
+//! description = "GitHub issues and pull requests"
+//!
+//! [server.files]
+//! command = "npx -y @modelcontextprotocol/server-filesystem ."
+//! tags = ["local"]
+//! ```
+//!
+//! Values may use `${ENV_VAR}` and `secret://` references (see
+//! [`crate::transport::secrets`]). They are expanded only when a profile is
+//! resolved, so listing the registry never needs every profile's credentials.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use mcp_probe_core::registry::ServerRegistry;
+//!
+//! let registry = ServerRegistry::load_default()?;
+//! for (name, profile) in registry.with_tag("remote") {
+//!     println!("{}: {}", name, profile.target());
+//! }
+//! let config = registry.resolve("github")?;
+//! # Ok::<(), mcp_probe_core::error::McpError>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{ConfigError, McpResult};
+use crate::transport::secrets::{expand_value, DefaultSecretResolver};
+use crate::transport::{AuthConfig, SandboxConfig, StdioConfig, TransportConfig};
+#[cfg(feature = "http")]
+use crate::transport::{HttpSseConfig, HttpStreamConfig};
+
+/// A named server: how to reach it and how to talk to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerProfile {
+    /// `stdio`, `http-sse` or `http-stream`
+    #[serde(default = "default_transport")]
+    pub transport: String,
+
+    /// Command line of a stdio server
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Run the stdio command through the shell
+    #[serde(default = "default_use_shell")]
+    pub use_shell: bool,
+
+    /// Endpoint of an HTTP server
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Credentials for HTTP servers
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+
+    /// Default timeout for the connection and requests
+    #[serde(default, with = "humantime_serde")]
+    pub timeout: Option<Duration>,
+
+    /// Labels for grouping and filtering profiles
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Human-readable summary
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Sandbox for an untrusted stdio server
+    #[serde(default)]
+    pub sandbox: Option<SandboxConfig>,
+}
+
+fn default_transport() -> String {
+    "stdio".to_string()
+}
+
+fn default_use_shell() -> bool {
+    true
+}
+
+impl ServerProfile {
+    /// Command or URL the profile points at
+    pub fn target(&self) -> &str {
+        match self.transport.as_str() {
+            "stdio" => self.command.as_deref().unwrap_or_default(),
+            _ => self.url.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// Whether the profile carries `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Build the client transport configuration for this profile
+    pub fn transport_config(&self) -> McpResult<TransportConfig> {
+        if self.sandbox.is_some() && self.transport != "stdio" {
+            return Err(ConfigError::InvalidValue {
+                parameter: "sandbox".to_string(),
+                value: self.transport.clone(),
+                reason: "Only stdio servers can be sandboxed".to_string(),
+            }
+            .into());
+        }
+        let config = match self.transport.as_str() {
+            "stdio" => {
+                let command =
+                    self.command
+                        .as_deref()
+                        .ok_or_else(|| ConfigError::MissingParameter {
+                            parameter: "command".to_string(),
+                        })?;
+                let mut config = if self.use_shell {
+                    StdioConfig::new("sh").args(["-c", command])
+                } else {
+                    let mut parts = command.split_whitespace();
+                    let program = parts.next().ok_or_else(|| ConfigError::InvalidValue {
+                        parameter: "command".to_string(),
+                        value: command.to_string(),
+                        reason: "Command cannot be empty".to_string(),
+                    })?;
+                    StdioConfig::new(program).args(parts)
+                };
+                if let Some(timeout) = self.timeout {
+                    config = config.timeout(timeout);
+                }
+                if let Some(sandbox) = &self.sandbox {
+                    config = config.sandbox(sandbox.clone());
+                }
+                TransportConfig::Stdio(config)
+            }
+            #[cfg(feature = "http")]
+            "http-sse" => {
+                let mut config = HttpSseConfig::new(self.url()?.parse()?);
+                if let Some(timeout) = self.timeout {
+                    config = config.timeout(timeout);
+                }
+                if let Some(auth) = &self.auth {
+                    config = config.auth(auth.clone());
+                }
+                TransportConfig::HttpSse(config)
+            }
+            #[cfg(feature = "http")]
+            "http-stream" => {
+                let mut config = HttpStreamConfig::new(self.url()?.parse()?);
+                if let Some(timeout) = self.timeout {
+                    config = config.timeout(timeout);
+                }
+                if let Some(auth) = &self.auth {
+                    config = config.auth(auth.clone());
+                }
+                TransportConfig::HttpStream(config)
+            }
+            #[cfg(not(feature = "http"))]
+            "http-sse" | "http-stream" => {
+                return Err(ConfigError::InvalidValue {
+                    parameter: "transport".to_string(),
+                    value: self.transport.clone(),
+                    reason: "This build has no HTTP transport support".to_string(),
+                }
+                .into())
+            }
+            other => {
+                return Err(ConfigError::InvalidValue {
+                    parameter: "transport".to_string(),
+                    value: other.to_string(),
+                    reason: "Must be one of: stdio, http-sse, http-stream".to_string(),
+                }
+                .into())
+            }
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[cfg(feature = "http")]
+    fn url(&self) -> McpResult<&str> {
+        Ok(self
+            .url
+            .as_deref()
+            .ok_or_else(|| ConfigError::MissingParameter {
+                parameter: "url".to_string(),
+            })?)
+    }
+}
+
+#[derive(Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    server: BTreeMap<String, Value>,
+}
+
+/// Server profiles by name
+#[derive(Debug, Clone, Default)]
+pub struct ServerRegistry {
+    profiles: BTreeMap<String, ServerProfile>,
+    /// Profiles as written, expanded on resolution
+    raw: BTreeMap<String, Value>,
+    resolver: DefaultSecretResolver,
+}
+
+impl ServerRegistry {
+    /// `$XDG_CONFIG_HOME/genmcp/servers.toml`, or under `~/.config` without it
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("genmcp").join("servers.toml"))
+    }
+
+    /// Load the user's registry; a missing file is an empty registry
+    pub fn load_default() -> McpResult<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a registry file
+    pub fn load(path: impl AsRef<Path>) -> McpResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|_| ConfigError::FileNotFound {
+            path: path.display().to_string(),
+        })?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::parse(
+            &content,
+            &path.display().to_string(),
+            DefaultSecretResolver::relative_to(base_dir),
+        )
+    }
+
+    /// Parse registry `content`; `source` names it in errors
+    fn parse(content: &str, source: &str, resolver: DefaultSecretResolver) -> McpResult<Self> {
+        let invalid = |reason: String| ConfigError::InvalidFormat {
+            path: source.to_string(),
+            reason,
+        };
+        let file: RegistryFile = toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+
+        let mut profiles = BTreeMap::new();
+        for (name, raw) in &file.server {
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+            if !valid {
+                return Err(invalid(format!(
+                    "Invalid server name '{}': use letters, digits, '-', '_' and '.'",
+                    name
+                ))
+                .into());
+            }
+            let profile: ServerProfile = serde_json::from_value(raw.clone())
+                .map_err(|e| invalid(format!("server '{}': {}", name, e)))?;
+            profiles.insert(name.clone(), profile);
+        }
+
+        Ok(Self {
+            profiles,
+            raw: file.server,
+            resolver,
+        })
+    }
+
+    /// Whether the registry defines no profiles
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Every profile by name, as written (placeholders unexpanded)
+    pub fn profiles(&self) -> impl Iterator<Item = (&str, &ServerProfile)> {
+        self.profiles
+            .iter()
+            .map(|(name, profile)| (name.as_str(), profile))
+    }
+
+    /// Profiles carrying `tag`, as written
+    pub fn with_tag<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a ServerProfile)> + 'a {
+        self.profiles()
+            .filter(move |(_, profile)| profile.has_tag(tag))
+    }
+
+    /// Whether a profile called `name` exists
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    /// The profile called `name` with environment and secrets expanded
+    pub fn profile(&self, name: &str) -> McpResult<ServerProfile> {
+        let Some(raw) = self.raw.get(name) else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            return Err(ConfigError::InvalidValue {
+                parameter: "server".to_string(),
+                value: name.to_string(),
+                reason: if known.is_empty() {
+                    "No server profiles are defined".to_string()
+                } else {
+                    format!("No such profile; known: {}", known.join(", "))
+                },
+            }
+            .into());
+        };
+
+        let mut raw = raw.clone();
+        expand_value(&mut raw, &self.resolver, &format!("server.{}", name))?;
+        Ok(
+            serde_json::from_value(raw).map_err(|e| ConfigError::InvalidValue {
+                parameter: format!("server.{}", name),
+                value: name.to_string(),
+                reason: e.to_string(),
+            })?,
+        )
+    }
+
+    /// Client transport configuration for the profile called `name`
+    pub fn resolve(&self, name: &str) -> McpResult<TransportConfig> {
+        self.profile(name)?.transport_config()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"
+        [server.github]
+        transport = "http-stream"
+        url = "https://mcp.example.com/github"
+        auth = { type = "bearer", token = "${MCP_REGISTRY_TEST_TOKEN}" }
+        timeout = "45s"
+        tags = ["remote", "vcs"]
+
+        [server.files]
+        command = "npx -y server-filesystem ."
+        tags = ["local"]
+    "#;
+
+    #[test]
+    fn test_profiles_list_and_resolve_lazily() {
+        let registry =
+            ServerRegistry::parse(REGISTRY, "servers.toml", DefaultSecretResolver::default())
+                .unwrap();
+        let names: Vec<_> = registry.profiles().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["files", "github"]);
+        let remote: Vec<_> = registry.with_tag("remote").map(|(name, _)| name).collect();
+        assert_eq!(remote, vec!["github"]);
+
+        // Listing works without the token; resolving needs it
+        assert!(registry.resolve("github").is_err());
+        std::env::set_var("MCP_REGISTRY_TEST_TOKEN", "t0ken");
+        #[cfg(feature = "http")]
+        {
+            let TransportConfig::HttpStream(config) = registry.resolve("github").unwrap() else {
+                panic!("expected an http-stream config");
+            };
+            assert_eq!(config.auth, Some(AuthConfig::bearer("t0ken")));
+            assert_eq!(config.timeout, Duration::from_secs(45));
+        }
+
+        let TransportConfig::Stdio(config) = registry.resolve("files").unwrap() else {
+            panic!("expected a stdio config");
+        };
+        assert_eq!(config.args, vec!["-c", "npx -y server-filesystem ."]);
+
+        let error = registry.resolve("gitlab").unwrap_err();
+        assert!(error.to_string().contains("known: files, github"));
+    }
+
+    #[test]
+    fn test_sandboxed_profile() {
+        let content = r#"
+            [server.untrusted]
+            command = "third-party-server --stdio"
+            use_shell = false
+            sandbox = { tool = "firejail", writable = ["/tmp/scratch"] }
+
+            [server.remote]
+            transport = "http-stream"
+            url = "https://mcp.example.com"
+            sandbox = { tool = "bubblewrap" }
+        "#;
+        let registry =
+            ServerRegistry::parse(content, "servers.toml", DefaultSecretResolver::default())
+                .unwrap();
+
+        let TransportConfig::Stdio(config) = registry.resolve("untrusted").unwrap() else {
+            panic!("expected a stdio config");
+        };
+        let (program, args) = config.launch_command().unwrap();
+        assert_eq!(program, "firejail");
+        assert!(args.contains(&"--read-write=/tmp/scratch".to_string()));
+        assert_eq!(&args[args.len() - 2..], ["third-party-server", "--stdio"]);
+
+        let error = registry.resolve("remote").unwrap_err();
+        assert!(error.to_string().contains("Only stdio servers"));
+    }
+
+    #[test]
+    fn test_invalid_profiles_are_rejected() {
+        let resolver = DefaultSecretResolver::default;
+        assert!(ServerRegistry::parse(
+            "[server.\"a b\"]\ncommand = \"x\"",
+            "servers.toml",
+            resolver()
+        )
+        .is_err());
+        assert!(ServerRegistry::parse(
+            "[server.x]\ntimeout = \"soon\"",
+            "servers.toml",
+            resolver()
+        )
+        .is_err());
+
+        let registry = ServerRegistry::parse(
+            "[server.x]\ntransport = \"http-sse\"",
+            "servers.toml",
+            resolver(),
+        )
+        .unwrap();
+        assert!(registry.resolve("x").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Detection of upstream server restarts.
+//!
+//! A server that crashes and comes back, or that forgets the client's
+//! session, may return with different capabilities and a different catalog.
+//! [`McpClient`](crate::client::McpClient) notices this before each request:
+//! the stdio process has exited, the transport reports another process or
+//! session than it did at connect time, or an HTTP server answers `404` for
+//! the session it handed out. The client then reconnects where needed,
+//! repeats the initialization handshake, lists the tools again and
+//! broadcasts a [`ServerRestarted`] event.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # async fn example(client: &mcp_probe_core::client::McpClient) {
+//! let mut restarts = client.server_restarts();
+//! while let Ok(event) = restarts.recv().await {
+//!     println!("{}", event);
+//!     if event.capabilities_changed() {
+//!         // rebuild anything derived from the old capabilities
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::client::ServerInfo;
+use crate::transport::TransportInfo;
+
+/// Why the client decided the server restarted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestartReason {
+    /// The server process exited and was launched again
+    ProcessExited,
+    /// The transport reports a different process or session than at connect time
+    SessionChanged {
+        /// Process or session the client initialized
+        previous: String,
+        /// Process or session the transport reports now
+        current: String,
+    },
+    /// The server no longer recognizes the client's session
+    SessionExpired,
+}
+
+impl fmt::Display for RestartReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestartReason::ProcessExited => write!(f, "server process exited"),
+            RestartReason::SessionChanged { previous, current } => {
+                write!(f, "session changed from {} to {}", previous, current)
+            }
+            RestartReason::SessionExpired => write!(f, "server no longer knows the session"),
+        }
+    }
+}
+
+/// A server restart the client recovered from.
+#[derive(Debug, Clone)]
+pub struct ServerRestarted {
+    /// What gave the restart away
+    pub reason: RestartReason,
+    /// Server as initialized before the restart
+    pub previous: Option<ServerInfo>,
+    /// Server as initialized after the restart
+    pub server: ServerInfo,
+    /// Tools listed after the restart; `None` when the server offers no
+    /// tools or the listing failed
+    pub tools: Option<Vec<Value>>,
+}
+
+impl ServerRestarted {
+    /// Whether the server came back with different capabilities.
+    pub fn capabilities_changed(&self) -> bool {
+        self.previous
+            .as_ref()
+            .is_none_or(|previous| previous.capabilities != self.server.capabilities)
+    }
+
+    /// Whether the server came back as a different implementation or version.
+    pub fn version_changed(&self) -> bool {
+        self.previous.as_ref().is_none_or(|previous| {
+            previous.implementation.name != self.server.implementation.name
+                || previous.implementation.version != self.server.implementation.version
+        })
+    }
+}
+
+impl fmt::Display for ServerRestarted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Server {} {} restarted ({})",
+            self.server.implementation.name, self.server.implementation.version, self.reason
+        )?;
+        if self.capabilities_changed() {
+            write!(f, "; capabilities changed")?;
+        }
+        if let Some(ref tools) = self.tools {
+            write!(f, "; {} tool(s) listed", tools.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Identity of the server process or session behind a transport: the child
+/// process ID for stdio, the session ID for HTTP.
+pub fn session_identity(info: &TransportInfo) -> Option<String> {
+    if let Some(pid) = info.metadata.get("process_id").and_then(Value::as_u64) {
+        return Some(format!("pid {}", pid));
+    }
+    info.metadata
+        .get("session_id")
+        .and_then(Value::as_str)
+        .map(|session| format!("session {}", session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{Capabilities, Implementation, ProtocolVersion};
+    use serde_json::json;
+    use tokio::time::Instant;
+
+    fn server(version: &str, capabilities: Capabilities) -> ServerInfo {
+        ServerInfo {
+            implementation: Implementation::new("demo", version),
+            protocol_version: ProtocolVersion::default(),
+            capabilities,
+            connected_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_session_identity() {
+        let mut stdio = TransportInfo::new("stdio");
+        stdio.add_metadata("process_id", json!(4242));
+        assert_eq!(session_identity(&stdio).as_deref(), Some("pid 4242"));
+
+        let mut http = TransportInfo::new("http-stream");
+        http.add_metadata("session_id", json!("abc"));
+        assert_eq!(session_identity(&http).as_deref(), Some("session abc"));
+
+        assert_eq!(session_identity(&TransportInfo::new("in-memory")), None);
+    }
+
+    #[test]
+    fn test_restart_changes() {
+        let mut capabilities = Capabilities::default();
+        let event = ServerRestarted {
+            reason: RestartReason::ProcessExited,
+            previous: Some(server("1.0.0", capabilities.clone())),
+            server: server("1.0.0", capabilities.clone()),
+            tools: Some(vec![json!({ "name": "echo" })]),
+        };
+        assert!(!event.capabilities_changed());
+        assert!(!event.version_changed());
+        assert_eq!(
+            event.to_string(),
+            "Server demo 1.0.0 restarted (server process exited); 1 tool(s) listed"
+        );
+
+        capabilities.standard.tools = Some(Default::default());
+        let upgraded = ServerRestarted {
+            server: server("1.1.0", capabilities),
+            tools: None,
+            ..event
+        };
+        assert!(upgraded.capabilities_changed());
+        assert!(upgraded.version_changed());
+    }
+}
@@ -0,0 +1,402 @@
+//! Which failed requests the client sends again, and when.
+//!
+//! Sending a listing or a read twice is harmless; sending `tools/call` twice
+//! may run the tool twice. A [`RetryPolicy`] therefore sorts failures by
+//! where they happened ([`FailureKind`]) and methods by whether they change
+//! anything on the server ([`Idempotency`]). Side-effecting calls are only
+//! retried after failures that show the server never acted on them: the
+//! connection could not be made, or the server turned the request away
+//! before running it.
+//!
+//! Servers that are throttling can say how long to wait in a `Retry-After`
+//! header; the policy waits that long instead of its own backoff.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::retry::{FailureKind, Idempotency, RetryPolicy};
+//! use std::time::Duration;
+//!
+//! // Retry only when the network fails, and treat one tool as safe to repeat
+//! let policy = RetryPolicy::default()
+//!     .max_retries(5)
+//!     .retry_on([FailureKind::Connect, FailureKind::Transport])
+//!     .method("tools/call", Idempotency::Idempotent);
+//! assert_eq!(policy.idempotency("tools/call"), Idempotency::Idempotent);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+#[cfg(feature = "http")]
+use reqwest::header::RETRY_AFTER;
+#[cfg(feature = "http")]
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::client::MethodClass;
+use crate::error::{McpError, ProtocolError, TransportError};
+
+/// Where a request failed, as far as retrying it is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// No connection could be made, so the request was never sent
+    Connect,
+    /// The connection failed while the request or its response was in transit
+    Transport,
+    /// No response arrived in time
+    Timeout,
+    /// The server failed with a 5xx status
+    Server,
+    /// The server turned the request away for now (HTTP 429, or 503 with
+    /// `Retry-After`)
+    Throttled,
+}
+
+impl FailureKind {
+    /// Every kind of failure.
+    pub const ALL: [FailureKind; 5] = [
+        FailureKind::Connect,
+        FailureKind::Transport,
+        FailureKind::Timeout,
+        FailureKind::Server,
+        FailureKind::Throttled,
+    ];
+
+    /// Classify an error, or `None` if sending the request again cannot help.
+    pub fn of(error: &McpError) -> Option<Self> {
+        match error {
+            McpError::Transport(error) => match error {
+                TransportError::ConnectionFailed { .. }
+                | TransportError::ConnectionError { .. } => Some(FailureKind::Connect),
+                TransportError::ConnectionLost { .. }
+                | TransportError::SendFailed { .. }
+                | TransportError::ReceiveFailed { .. }
+                | TransportError::NetworkError { .. }
+                | TransportError::DisconnectedError { .. }
+                | TransportError::SseError { .. }
+                | TransportError::StreamingError { .. } => Some(FailureKind::Transport),
+                TransportError::TimeoutError { .. } => Some(FailureKind::Timeout),
+                TransportError::Throttled { .. } => Some(FailureKind::Throttled),
+                TransportError::HttpError { status_code, .. } if *status_code >= 500 => {
+                    Some(FailureKind::Server)
+                }
+                _ => None,
+            },
+            McpError::Timeout { .. }
+            | McpError::Protocol(ProtocolError::RequestTimeout { .. })
+            | McpError::Protocol(ProtocolError::TimeoutError { .. }) => Some(FailureKind::Timeout),
+            McpError::Io { .. } => Some(FailureKind::Transport),
+            _ => None,
+        }
+    }
+
+    /// Whether the server may have acted on the request before it failed.
+    pub fn may_have_executed(self) -> bool {
+        !matches!(self, FailureKind::Connect | FailureKind::Throttled)
+    }
+}
+
+/// Whether sending a request twice has the same effect as sending it once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Idempotency {
+    /// Repeating the request changes nothing, e.g. listings and reads
+    Idempotent,
+    /// The request may change state on the server, e.g. `tools/call`
+    SideEffecting,
+}
+
+impl Idempotency {
+    /// Idempotency of a JSON-RPC method when no policy says otherwise.
+    pub fn of(method: &str) -> Self {
+        match MethodClass::of(method) {
+            MethodClass::Call => Idempotency::SideEffecting,
+            _ => Idempotency::Idempotent,
+        }
+    }
+}
+
+/// Rules for retrying failed requests.
+///
+/// The client uses the policy in [`ClientConfig`](crate::ClientConfig)
+/// unless a call passes its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts after the first (default: 3)
+    pub max_retries: u32,
+
+    /// Delay before the first retry, doubled for each one after it
+    /// (default: 1 second)
+    pub base_delay: Duration,
+
+    /// Longest wait between attempts, `Retry-After` hints included
+    /// (default: 30 seconds)
+    pub max_delay: Duration,
+
+    /// Failures worth retrying (default: all of them)
+    pub retry_on: HashSet<FailureKind>,
+
+    /// Retry side-effecting requests even after failures the server may have
+    /// acted on (default: false)
+    pub retry_side_effects: bool,
+
+    /// Wait as long as the server's `Retry-After` asks (default: true)
+    pub honor_retry_after: bool,
+
+    /// Idempotency of particular methods, overriding [`Idempotency::of`]
+    pub methods: HashMap<String, Idempotency>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            retry_on: FailureKind::ALL.into_iter().collect(),
+            retry_side_effects: false,
+            honor_retry_after: true,
+            methods: HashMap::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self::default().max_retries(0)
+    }
+
+    /// Set the number of attempts after the first.
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the longest wait between attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Retry only the given kinds of failure.
+    pub fn retry_on(mut self, kinds: impl IntoIterator<Item = FailureKind>) -> Self {
+        self.retry_on = kinds.into_iter().collect();
+        self
+    }
+
+    /// Retry side-effecting requests after any retryable failure.
+    pub fn retry_side_effects(mut self, enabled: bool) -> Self {
+        self.retry_side_effects = enabled;
+        self
+    }
+
+    /// Follow or ignore `Retry-After` hints.
+    pub fn honor_retry_after(mut self, enabled: bool) -> Self {
+        self.honor_retry_after = enabled;
+        self
+    }
+
+    /// Treat `method` as idempotent or side-effecting.
+    pub fn method(mut self, method: impl Into<String>, idempotency: Idempotency) -> Self {
+        self.methods.insert(method.into(), idempotency);
+        self
+    }
+
+    /// Idempotency of `method` under this policy.
+    pub fn idempotency(&self, method: &str) -> Idempotency {
+        self.methods
+            .get(method)
+            .copied()
+            .unwrap_or_else(|| Idempotency::of(method))
+    }
+
+    /// How long to wait before sending `method` again after its attempt
+    /// number `attempt` (counting from 0) failed with `error`, or `None` if
+    /// it should not be sent again.
+    pub fn retry_delay(&self, method: &str, error: &McpError, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        let kind = FailureKind::of(error)?;
+        if !self.retry_on.contains(&kind) {
+            return None;
+        }
+        if kind.may_have_executed()
+            && !self.retry_side_effects
+            && self.idempotency(method) == Idempotency::SideEffecting
+        {
+            return None;
+        }
+
+        let hint = error.retry_after().filter(|_| self.honor_retry_after);
+        let delay = hint.unwrap_or_else(|| {
+            self.base_delay
+                .checked_mul(2_u32.saturating_pow(attempt))
+                .unwrap_or(Duration::MAX)
+        });
+        Some(delay.min(self.max_delay))
+    }
+}
+
+/// The error for an HTTP response that turns the request away for now, if
+/// it is one.
+#[cfg(feature = "http")]
+pub(crate) fn throttled(response: &reqwest::Response) -> Option<TransportError> {
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => {}
+        StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => {}
+        _ => return None,
+    }
+    Some(TransportError::Throttled {
+        status_code: status.as_u16(),
+        retry_after,
+        reason: status.to_string(),
+    })
+}
+
+/// Parse a `Retry-After` value: a number of seconds or an HTTP date.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means now
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(kind: FailureKind) -> McpError {
+        let error = match kind {
+            FailureKind::Connect => TransportError::ConnectionFailed {
+                transport_type: "http".to_string(),
+                reason: "refused".to_string(),
+            },
+            FailureKind::Transport => TransportError::ConnectionLost {
+                transport_type: "http".to_string(),
+                reason: "reset".to_string(),
+            },
+            FailureKind::Timeout => TransportError::TimeoutError {
+                transport_type: "http".to_string(),
+                reason: "slow".to_string(),
+            },
+            FailureKind::Server => TransportError::HttpError {
+                status_code: 502,
+                reason: "bad gateway".to_string(),
+            },
+            FailureKind::Throttled => TransportError::Throttled {
+                status_code: 429,
+                retry_after: Some(Duration::from_secs(7)),
+                reason: "slow down".to_string(),
+            },
+        };
+        McpError::Transport(error)
+    }
+
+    #[test]
+    fn test_side_effecting_calls_retry_only_when_not_executed() {
+        let policy = RetryPolicy::default();
+        for kind in FailureKind::ALL {
+            assert_eq!(FailureKind::of(&error(kind)), Some(kind));
+            assert!(policy.retry_delay("tools/list", &error(kind), 0).is_some());
+            assert_eq!(
+                policy.retry_delay("tools/call", &error(kind), 0).is_some(),
+                !kind.may_have_executed(),
+                "{:?}",
+                kind
+            );
+        }
+
+        // Overridden per method, or for side effects as a whole
+        let trusted = policy.clone().method("tools/call", Idempotency::Idempotent);
+        assert!(trusted
+            .retry_delay("tools/call", &error(FailureKind::Timeout), 0)
+            .is_some());
+        let reckless = policy.retry_side_effects(true);
+        assert!(reckless
+            .retry_delay("tools/call", &error(FailureKind::Server), 0)
+            .is_some());
+
+        // Errors no retry can fix
+        let invalid = McpError::Transport(TransportError::HttpError {
+            status_code: 400,
+            reason: "bad request".to_string(),
+        });
+        assert_eq!(FailureKind::of(&invalid), None);
+        assert!(RetryPolicy::default()
+            .retry_delay("tools/list", &invalid, 0)
+            .is_none());
+    }
+
+    #[test]
+    fn test_delays_follow_backoff_and_hints() {
+        let policy = RetryPolicy::default()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(5))
+            .retry_on([FailureKind::Transport, FailureKind::Throttled]);
+        let lost = error(FailureKind::Transport);
+        assert_eq!(
+            policy.retry_delay("ping", &lost, 0),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.retry_delay("ping", &lost, 2),
+            Some(Duration::from_millis(400))
+        );
+        assert_eq!(policy.retry_delay("ping", &lost, 3), None);
+        assert_eq!(
+            policy.retry_delay("ping", &error(FailureKind::Timeout), 0),
+            None
+        );
+
+        // The hint wins over backoff, within the cap
+        let throttled = error(FailureKind::Throttled);
+        assert_eq!(
+            policy.retry_delay("ping", &throttled, 0),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            policy
+                .clone()
+                .honor_retry_after(false)
+                .retry_delay("ping", &throttled, 0),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(RetryPolicy::none().retry_delay("ping", &lost, 0), None);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after(" 120 "), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let soon = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        let wait = parse_retry_after(&soon).unwrap();
+        assert!(wait > Duration::from_secs(80) && wait <= Duration::from_secs(90));
+        assert_eq!(parse_retry_after("later"), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Timers and background tasks that work natively and in the browser.
+//!
+//! Native builds run on tokio, so tests can pause and advance its clock (see
+//! the crate docs). `wasm32` has neither a tokio timer nor a tokio executor:
+//! there, sleeping waits on the browser's `setTimeout`, instants come from
+//! `performance.now()` and tasks run on the page's event loop.
+//!
+//! Code that the client depends on uses these instead of `tokio::time` and
+//! `tokio::spawn`, so the same protocol logic compiles for both.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+/// A point in time on the clock timers run on
+pub use imp::Instant;
+
+/// The deadline passed before the future completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+/// Wait for `duration`.
+pub async fn sleep(duration: Duration) {
+    imp::sleep(duration).await
+}
+
+/// Wait until `deadline`.
+pub async fn sleep_until(deadline: Instant) {
+    imp::sleep_until(deadline).await
+}
+
+/// Run `future`, giving up once `duration` has passed.
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    imp::timeout(duration, future).await
+}
+
+/// Run `future`, giving up at `deadline`.
+pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Result<F::Output, Elapsed> {
+    imp::timeout(deadline.saturating_duration_since(Instant::now()), future).await
+}
+
+/// Handle to a task started with [`spawn`].
+///
+/// Dropping the handle leaves the task running.
+#[derive(Debug)]
+pub struct Task(imp::Handle);
+
+impl Task {
+    /// Stop the task at its next `.await`.
+    pub fn abort(&self) {
+        self.0.abort();
+    }
+}
+
+/// Run `future` in the background.
+///
+/// Natively the task goes to the current tokio runtime; in the browser it
+/// runs on the page's event loop.
+pub fn spawn<F>(future: F) -> Task
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    Task(imp::spawn(future))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod imp {
+    use std::future::Future;
+    use std::time::Duration;
+
+    pub use tokio::time::{sleep, sleep_until, Instant};
+
+    pub type Handle = tokio::task::JoinHandle<()>;
+
+    pub async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, super::Elapsed> {
+        tokio::time::timeout(duration, future)
+            .await
+            .map_err(|_| super::Elapsed)
+    }
+
+    pub fn spawn<F>(future: F) -> Handle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(future)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod imp {
+    use std::future::Future;
+    use std::time::Duration;
+
+    use futures::future::{abortable, select, AbortHandle, Either};
+    pub use web_time::Instant;
+
+    pub type Handle = AbortHandle;
+
+    pub async fn sleep(duration: Duration) {
+        // `setTimeout` takes at most `u32::MAX` milliseconds
+        gloo_timers::future::sleep(duration.min(Duration::from_millis(u32::MAX as u64))).await
+    }
+
+    pub async fn sleep_until(deadline: Instant) {
+        sleep(deadline.saturating_duration_since(Instant::now())).await
+    }
+
+    pub async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, super::Elapsed> {
+        match select(std::pin::pin!(future), std::pin::pin!(sleep(duration))).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(_) => Err(super::Elapsed),
+        }
+    }
+
+    pub fn spawn<F>(future: F) -> Handle
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let (future, handle) = abortable(future);
+        wasm_bindgen_futures::spawn_local(async move {
+            let _ = future.await;
+        });
+        handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timers_run_on_the_paused_clock() {
+        let started = Instant::now();
+        sleep(Duration::from_secs(60)).await;
+        assert_eq!(started.elapsed(), Duration::from_secs(60));
+
+        let slow = timeout(Duration::from_secs(1), sleep(Duration::from_secs(2))).await;
+        assert_eq!(slow, Err(Elapsed));
+        let deadline = Instant::now() + Duration::from_secs(3);
+        assert_eq!(timeout_at(deadline, async { 7 }).await, Ok(7));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_aborted_tasks_stop() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let task = spawn(async move {
+            for tick in 0.. {
+                sleep(Duration::from_secs(1)).await;
+                if tx.send(tick).is_err() {
+                    break;
+                }
+            }
+        });
+        assert_eq!(rx.recv().await, Some(0));
+        task.abort();
+        assert_eq!(rx.recv().await, None);
+    }
+}
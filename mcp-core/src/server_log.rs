@@ -0,0 +1,114 @@
+//! Log messages sent by servers.
+//!
+//! A server with the `logging` capability sends its log messages as
+//! `notifications/message`. [`McpClient`](crate::client::McpClient) turns each
+//! one into a [`LogEntry`] and broadcasts it to the receivers returned by
+//! [`McpClient::server_logs`](crate::client::McpClient::server_logs).
+//! [`McpClient::set_level`](crate::client::McpClient::set_level) sends
+//! `logging/setLevel` to choose the least severe level the server sends.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # async fn example(client: &mut mcp_probe_core::client::McpClient) -> mcp_probe_core::McpResult<()> {
+//! use mcp_probe_core::messages::LogLevel;
+//!
+//! let mut logs = client.server_logs();
+//! client.set_level(LogLevel::Warning).await?;
+//! while let Ok(entry) = logs.recv().await {
+//!     eprintln!("{}", entry);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::messages::{LogLevel, LoggingNotification};
+
+/// One log message from a server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// When the client received the message
+    pub timestamp: DateTime<Utc>,
+    /// Severity the server gave the message
+    pub level: LogLevel,
+    /// Name of the server's logger, if it gave one
+    pub logger: Option<String>,
+    /// Readable text of the message
+    pub message: String,
+    /// The message as sent, any JSON value
+    pub data: Value,
+}
+
+impl LogEntry {
+    /// Build an entry from a notification received now.
+    ///
+    /// String data is the message itself. For objects the `message` field is
+    /// used if it is a string; anything else is shown as compact JSON.
+    pub fn from_notification(notification: LoggingNotification) -> Self {
+        let message = match &notification.data {
+            Value::String(text) => text.clone(),
+            Value::Object(fields) => match fields.get("message") {
+                Some(Value::String(text)) => text.clone(),
+                _ => notification.data.to_string(),
+            },
+            other => other.to_string(),
+        };
+        Self {
+            timestamp: Utc::now(),
+            level: notification.level,
+            logger: notification.logger,
+            message,
+            data: notification.data,
+        }
+    }
+
+    /// Whether the entry is at least as severe as `level`
+    pub fn is_at_least(&self, level: &LogLevel) -> bool {
+        !self.level.is_more_verbose_than(level)
+    }
+}
+
+impl From<LoggingNotification> for LogEntry {
+    fn from(notification: LoggingNotification) -> Self {
+        Self::from_notification(notification)
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.level)?;
+        if let Some(logger) = &self.logger {
+            write!(f, " [{}]", logger)?;
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_entries_render_their_message() {
+        let entry = LogEntry::from(LoggingNotification::with_logger(
+            LogLevel::Warning,
+            json!({ "message": "disk almost full", "free": "2%" }),
+            "fs",
+        ));
+        assert_eq!(entry.message, "disk almost full");
+        assert_eq!(entry.data["free"], "2%");
+        assert_eq!(entry.to_string(), "warning [fs] disk almost full");
+        assert!(entry.is_at_least(&LogLevel::Warning));
+        assert!(!entry.is_at_least(&LogLevel::Error));
+
+        let entry = LogEntry::from(LoggingNotification::new(LogLevel::Info, json!([1, 2])));
+        assert_eq!(entry.to_string(), "info [1,2]");
+    }
+}
@@ -0,0 +1,333 @@
+//! Typed construction of `tools/call` arguments.
+//!
+//! Arguments written as `json!` blobs meet the tool's input schema only on
+//! the server, at runtime. [`ToolArgs`] builds the arguments object through a
+//! setter per JSON type and checks it against the tool's schema before the
+//! call is sent. Properties the schema does not allow, values of the wrong
+//! type, values outside an `enum` and missing required properties are
+//! reported as [`ValidationError::InvalidToolParameter`], naming the
+//! parameter.
+//!
+//! Arguments can also come from a serde model: [`ToolArgs::input`] takes any
+//! `Serialize` struct whose fields match the schema. Fields that serialize
+//! to `null`, such as `None`, are left out.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::messages::tools::Tool;
+//! use mcp_probe_core::tool_args::ToolArgs;
+//! use serde::Serialize;
+//! use serde_json::json;
+//!
+//! let tool = Tool::new("search", "Search documents").with_input_schema(json!({
+//!     "type": "object",
+//!     "properties": {
+//!         "query": { "type": "string" },
+//!         "limit": { "type": "integer" },
+//!         "order": { "type": "string", "enum": ["relevance", "date"] }
+//!     },
+//!     "required": ["query"]
+//! }));
+//!
+//! let request = ToolArgs::for_tool(&tool)
+//!     .string("query", "rust")
+//!     .integer("limit", 10)
+//!     .into_request()
+//!     .unwrap();
+//! assert_eq!(request.arguments, Some(json!({ "query": "rust", "limit": 10 })));
+//!
+//! #[derive(Serialize)]
+//! struct Search {
+//!     query: String,
+//!     order: Option<String>,
+//! }
+//!
+//! let error = ToolArgs::for_tool(&tool)
+//!     .input(&Search { query: "rust".into(), order: Some("size".into()) })
+//!     .build()
+//!     .unwrap_err();
+//! assert!(error.to_string().contains("'order'"));
+//! ```
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::{McpResult, ValidationError};
+use crate::messages::tools::{CallToolRequest, Tool};
+use crate::validation::{self, ParameterValidator};
+
+/// Builder for the arguments of one tool call.
+#[derive(Debug, Clone)]
+pub struct ToolArgs {
+    tool: String,
+    schema: Option<Value>,
+    arguments: Map<String, Value>,
+    /// First setter that failed, reported by [`build`](Self::build)
+    error: Option<ValidationError>,
+}
+
+impl ToolArgs {
+    /// Arguments for `tool`, without a schema to check them against.
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            schema: None,
+            arguments: Map::new(),
+            error: None,
+        }
+    }
+
+    /// Arguments for `tool`, checked against its input schema.
+    pub fn for_tool(tool: &Tool) -> Self {
+        Self {
+            schema: tool.input_schema.clone(),
+            ..Self::new(tool.name.clone())
+        }
+    }
+
+    /// Set a string parameter.
+    pub fn string(self, name: &str, value: impl Into<String>) -> Self {
+        self.value(name, Value::String(value.into()))
+    }
+
+    /// Set an integer parameter.
+    pub fn integer(self, name: &str, value: i64) -> Self {
+        self.value(name, Value::from(value))
+    }
+
+    /// Set a number parameter; NaN and infinities are rejected.
+    pub fn number(self, name: &str, value: f64) -> Self {
+        match serde_json::Number::from_f64(value) {
+            Some(number) => self.value(name, Value::Number(number)),
+            None => self.fail(name, format!("{} is not a JSON number", value)),
+        }
+    }
+
+    /// Set a boolean parameter.
+    pub fn boolean(self, name: &str, value: bool) -> Self {
+        self.value(name, Value::Bool(value))
+    }
+
+    /// Set an array parameter from any serializable items.
+    pub fn array<T: Serialize>(self, name: &str, items: impl IntoIterator<Item = T>) -> Self {
+        let items: Result<Vec<Value>, _> = items.into_iter().map(serde_json::to_value).collect();
+        match items {
+            Ok(items) => self.value(name, Value::Array(items)),
+            Err(e) => self.fail(name, e.to_string()),
+        }
+    }
+
+    /// Set an object parameter from a serializable value.
+    pub fn object<T: Serialize>(self, name: &str, value: &T) -> Self {
+        match serde_json::to_value(value) {
+            Ok(value @ Value::Object(_)) => self.value(name, value),
+            Ok(other) => self.fail(name, format!("expected an object, got {}", other)),
+            Err(e) => self.fail(name, e.to_string()),
+        }
+    }
+
+    /// Set a parameter to an arbitrary JSON value.
+    pub fn value(mut self, name: &str, value: Value) -> Self {
+        self.arguments.insert(name.to_string(), value);
+        self
+    }
+
+    /// Set every field of a serde model as a parameter, leaving out fields
+    /// that serialize to `null`.
+    pub fn input<T: Serialize>(mut self, input: &T) -> Self {
+        match serde_json::to_value(input) {
+            Ok(Value::Object(fields)) => {
+                for (name, value) in fields {
+                    if !value.is_null() {
+                        self.arguments.insert(name, value);
+                    }
+                }
+                self
+            }
+            Ok(other) => self.fail(
+                "",
+                format!("input must serialize to an object, got {}", other),
+            ),
+            Err(e) => self.fail("", e.to_string()),
+        }
+    }
+
+    /// The arguments object, once it satisfies the tool's input schema.
+    pub fn build(self) -> McpResult<Value> {
+        if let Some(error) = self.error {
+            return Err(error.into());
+        }
+        let arguments = Value::Object(self.arguments);
+        if let Some(ref schema) = self.schema {
+            check(&self.tool, schema, &arguments)?;
+        }
+        Ok(arguments)
+    }
+
+    /// A `tools/call` request with the built arguments.
+    pub fn into_request(self) -> McpResult<CallToolRequest> {
+        let name = self.tool.clone();
+        Ok(CallToolRequest {
+            name,
+            arguments: Some(self.build()?),
+            meta: None,
+        })
+    }
+
+    fn fail(mut self, name: &str, reason: String) -> Self {
+        if self.error.is_none() {
+            self.error = Some(invalid(&self.tool, name, reason));
+        }
+        self
+    }
+}
+
+/// Check `arguments` against `schema`: what the shared
+/// [`ParameterValidator`] checks, plus closed objects and enums
+fn check(tool: &str, schema: &Value, arguments: &Value) -> Result<(), ValidationError> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+    for (name, value) in arguments.as_object().into_iter().flatten() {
+        let Some(property) = properties.and_then(|properties| properties.get(name)) else {
+            if closed {
+                return Err(invalid(
+                    tool,
+                    name,
+                    "not in the tool's input schema".to_string(),
+                ));
+            }
+            continue;
+        };
+        if let Some(allowed) = property.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                return Err(invalid(
+                    tool,
+                    name,
+                    format!("{} is not one of {}", value, Value::Array(allowed.clone())),
+                ));
+            }
+        }
+    }
+
+    let result = ParameterValidator::strict().validate(schema, arguments);
+    match result.errors.into_iter().next() {
+        None => Ok(()),
+        Some(validation::ValidationError::MissingRequired { field }) => {
+            Err(invalid(tool, &field, "required but missing".to_string()))
+        }
+        Some(validation::ValidationError::ValidationFailed { field, reason })
+        | Some(validation::ValidationError::TransformationFailed { field, reason }) => {
+            Err(invalid(tool, &field, reason))
+        }
+        Some(validation::ValidationError::SchemaError(reason))
+        | Some(validation::ValidationError::InvalidSchema(reason)) => {
+            Err(ValidationError::SchemaValidation {
+                object_type: format!("input schema of tool '{}'", tool),
+                reason,
+            })
+        }
+    }
+}
+
+fn invalid(tool: &str, parameter: &str, reason: String) -> ValidationError {
+    ValidationError::InvalidToolParameter {
+        tool: tool.to_string(),
+        parameter: parameter.to_string(),
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::McpError;
+    use serde_json::json;
+
+    fn tool() -> Tool {
+        Tool::new("create_issue", "Open an issue").with_input_schema(json!({
+            "type": "object",
+            "properties": {
+                "title": { "type": "string" },
+                "priority": { "type": "integer" },
+                "labels": { "type": "array", "items": { "type": "string" } },
+                "state": { "type": "string", "enum": ["open", "draft"] },
+                "assignee": { "type": "object" }
+            },
+            "required": ["title"],
+            "additionalProperties": false
+        }))
+    }
+
+    fn parameter(error: McpError) -> String {
+        match error {
+            McpError::Validation(ValidationError::InvalidToolParameter { parameter, .. }) => {
+                parameter
+            }
+            other => panic!("expected an invalid parameter, got {other}"),
+        }
+    }
+
+    #[test]
+    fn test_setters_build_checked_arguments() {
+        let arguments = ToolArgs::for_tool(&tool())
+            .string("title", "Crash on start")
+            .integer("priority", 2)
+            .array("labels", ["bug", "p1"])
+            .string("state", "draft")
+            .object("assignee", &json!({ "login": "octocat" }))
+            .build()
+            .unwrap();
+        assert_eq!(
+            arguments,
+            json!({
+                "title": "Crash on start",
+                "priority": 2,
+                "labels": ["bug", "p1"],
+                "state": "draft",
+                "assignee": { "login": "octocat" }
+            })
+        );
+
+        let build = |args: ToolArgs| parameter(args.build().unwrap_err());
+        let base = || ToolArgs::for_tool(&tool()).string("title", "t");
+        assert_eq!(build(ToolArgs::for_tool(&tool())), "title");
+        assert_eq!(build(base().string("priority", "high")), "priority");
+        assert_eq!(build(base().string("state", "closed")), "state");
+        assert_eq!(build(base().boolean("urgent", true)), "urgent");
+        assert_eq!(build(base().number("priority", f64::NAN)), "priority");
+
+        // Without a schema anything goes
+        assert!(ToolArgs::new("anything")
+            .boolean("urgent", true)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_serde_models_become_arguments() {
+        #[derive(Serialize)]
+        struct Issue {
+            title: String,
+            priority: Option<i64>,
+            labels: Vec<String>,
+        }
+
+        let request = ToolArgs::for_tool(&tool())
+            .input(&Issue {
+                title: "Crash on start".to_string(),
+                priority: None,
+                labels: vec!["bug".to_string()],
+            })
+            .into_request()
+            .unwrap();
+        assert_eq!(request.name, "create_issue");
+        assert_eq!(
+            request.arguments,
+            Some(json!({ "title": "Crash on start", "labels": ["bug"] }))
+        );
+
+        let error = ToolArgs::for_tool(&tool()).input(&"not an object").build();
+        assert!(error.is_err());
+    }
+}
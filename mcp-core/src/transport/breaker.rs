@@ -0,0 +1,551 @@
+//! Circuit breaking for servers that stop answering.
+//!
+//! A [`CircuitBreaker`] counts consecutive failed requests. Once
+//! [`BreakerConfig::failure_threshold`] of them fail in a row the circuit
+//! opens, and requests fail at once with [`TransportError::CircuitOpen`]
+//! instead of waiting on a dead server. After [`BreakerConfig::cool_down`]
+//! the circuit is half-open: a few probe requests go through, the first one
+//! to succeed closes the circuit and a failing one opens it again.
+//!
+//! Only failures of the connection count (see [`FailureKind`]). A JSON-RPC
+//! error response shows the server is alive and counts as a success.
+//!
+//! [`BreakerTransport`] puts a breaker in front of any transport and reports
+//! state changes through [`Transport::circuit_events`].
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use super::{ProtocolDiagnostic, Transport, TransportConfig, TransportInfo};
+use crate::error::{ConfigError, McpError, McpResult, TransportError};
+use crate::messages::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::retry::FailureKind;
+use crate::rt::Instant;
+
+/// When a circuit opens and how it recovers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+
+    /// How long an open circuit fails requests before probing the server
+    #[serde(with = "humantime_serde")]
+    pub cool_down: Duration,
+
+    /// Probe requests let through at once while half-open
+    pub half_open_probes: u32,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
+impl BreakerConfig {
+    /// Open the circuit after `failures` consecutive failures
+    pub fn failure_threshold(mut self, failures: u32) -> Self {
+        self.failure_threshold = failures;
+        self
+    }
+
+    /// Probe the server `cool_down` after the circuit opens
+    pub fn cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+
+    /// Let `probes` requests through at once while half-open
+    pub fn half_open_probes(mut self, probes: u32) -> Self {
+        self.half_open_probes = probes;
+        self
+    }
+
+    /// Reject settings that would never let a request through
+    pub fn validate(&self) -> McpResult<()> {
+        for (parameter, value) in [
+            ("failure_threshold", self.failure_threshold),
+            ("half_open_probes", self.half_open_probes),
+        ] {
+            if value == 0 {
+                return Err(ConfigError::InvalidValue {
+                    parameter: parameter.to_string(),
+                    value: "0".to_string(),
+                    reason: "Must be at least 1".to_string(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// State of a circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests go through
+    Closed,
+    /// Requests fail at once
+    Open,
+    /// Probe requests go through to test the server
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half-open",
+        })
+    }
+}
+
+/// A circuit changed state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CircuitEvent {
+    /// Name of the breaker, usually the server it guards
+    pub name: String,
+    /// State before the change
+    pub from: CircuitState,
+    /// State after the change
+    pub to: CircuitState,
+    /// Failures in a row when the state changed
+    pub consecutive_failures: u32,
+    /// The failure that opened the circuit, if it opened
+    pub last_error: Option<String>,
+}
+
+impl fmt::Display for CircuitEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "circuit for {} {} -> {}", self.name, self.from, self.to)?;
+        if let Some(error) = &self.last_error {
+            write!(
+                f,
+                " after {} failures ({})",
+                self.consecutive_failures, error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Counters of a circuit breaker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BreakerStats {
+    /// Current state
+    pub state: CircuitState,
+    /// Failures in a row so far
+    pub consecutive_failures: u32,
+    /// Times the circuit opened
+    pub opened: u64,
+    /// Requests failed at once while the circuit was open
+    pub rejected: u64,
+    /// Most recent failure
+    pub last_error: Option<String>,
+}
+
+struct Circuit {
+    stats: BreakerStats,
+    opened_at: Option<Instant>,
+    probes: u32,
+}
+
+/// Closed/open/half-open circuit breaker guarding one server
+pub struct CircuitBreaker {
+    name: String,
+    config: BreakerConfig,
+    circuit: Mutex<Circuit>,
+    events: broadcast::Sender<CircuitEvent>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker named after the server it guards
+    pub fn new(name: impl Into<String>, config: BreakerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            circuit: Mutex::new(Circuit {
+                stats: BreakerStats {
+                    state: CircuitState::Closed,
+                    consecutive_failures: 0,
+                    opened: 0,
+                    rejected: 0,
+                    last_error: None,
+                },
+                opened_at: None,
+                probes: 0,
+            }),
+            events: broadcast::channel(64).0,
+        }
+    }
+
+    /// Current state; an open circuit past its cool-down turns half-open on
+    /// the next request
+    pub fn state(&self) -> CircuitState {
+        self.circuit().stats.state
+    }
+
+    /// Snapshot of the counters
+    pub fn stats(&self) -> BreakerStats {
+        self.circuit().stats.clone()
+    }
+
+    /// Subscribe to state changes from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitEvent> {
+        self.events.subscribe()
+    }
+
+    /// Ask to send a request. The returned admission must be told how the
+    /// request went; dropping it counts for neither side.
+    pub fn admit(&self) -> Result<Admission<'_>, TransportError> {
+        let mut circuit = self.circuit();
+        if circuit.stats.state == CircuitState::Open {
+            let waited = circuit.opened_at.map_or(Duration::MAX, |at| at.elapsed());
+            if waited < self.config.cool_down {
+                circuit.stats.rejected += 1;
+                return Err(self.open_error(self.config.cool_down - waited));
+            }
+            self.transition(&mut circuit, CircuitState::HalfOpen);
+        }
+
+        let probe = circuit.stats.state == CircuitState::HalfOpen;
+        if probe {
+            if circuit.probes >= self.config.half_open_probes {
+                circuit.stats.rejected += 1;
+                return Err(self.open_error(Duration::ZERO));
+            }
+            circuit.probes += 1;
+        }
+        Ok(Admission {
+            breaker: self,
+            probe,
+            recorded: false,
+        })
+    }
+
+    fn circuit(&self) -> MutexGuard<'_, Circuit> {
+        self.circuit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn open_error(&self, retry_in: Duration) -> TransportError {
+        TransportError::CircuitOpen {
+            name: self.name.clone(),
+            retry_in,
+        }
+    }
+
+    fn transition(&self, circuit: &mut Circuit, to: CircuitState) {
+        let from = circuit.stats.state;
+        circuit.stats.state = to;
+        match to {
+            CircuitState::Open => {
+                circuit.opened_at = Some(Instant::now());
+                circuit.stats.opened += 1;
+            }
+            CircuitState::Closed => circuit.stats.consecutive_failures = 0,
+            CircuitState::HalfOpen => {}
+        }
+        let event = CircuitEvent {
+            name: self.name.clone(),
+            from,
+            to,
+            consecutive_failures: circuit.stats.consecutive_failures,
+            last_error: (to == CircuitState::Open)
+                .then(|| circuit.stats.last_error.clone())
+                .flatten(),
+        };
+        tracing::info!("{}", event);
+        // Nobody listening is fine
+        let _ = self.events.send(event);
+    }
+
+    fn succeeded(&self, probe: bool) {
+        let mut circuit = self.circuit();
+        if probe {
+            circuit.probes = circuit.probes.saturating_sub(1);
+        }
+        circuit.stats.consecutive_failures = 0;
+        if circuit.stats.state == CircuitState::HalfOpen {
+            self.transition(&mut circuit, CircuitState::Closed);
+        }
+    }
+
+    fn failed(&self, probe: bool, error: &McpError) {
+        let mut circuit = self.circuit();
+        if probe {
+            circuit.probes = circuit.probes.saturating_sub(1);
+        }
+        circuit.stats.consecutive_failures += 1;
+        circuit.stats.last_error = Some(error.to_string());
+        let open = match circuit.stats.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => {
+                circuit.stats.consecutive_failures >= self.config.failure_threshold
+            }
+            CircuitState::Open => false,
+        };
+        if open {
+            self.transition(&mut circuit, CircuitState::Open);
+        }
+    }
+
+    fn abandoned(&self, probe: bool) {
+        if probe {
+            let mut circuit = self.circuit();
+            circuit.probes = circuit.probes.saturating_sub(1);
+        }
+    }
+}
+
+/// Permission to send one request through a [`CircuitBreaker`]
+pub struct Admission<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    recorded: bool,
+}
+
+impl Admission<'_> {
+    /// Count the outcome of the request. Connection failures count against
+    /// the server; other errors, like one the client caused, count for
+    /// neither side.
+    pub fn record<T>(mut self, result: &McpResult<T>) {
+        self.recorded = true;
+        match result {
+            Ok(_) => self.breaker.succeeded(self.probe),
+            Err(error) if FailureKind::of(error).is_some() => {
+                self.breaker.failed(self.probe, error)
+            }
+            Err(_) => self.breaker.abandoned(self.probe),
+        }
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.breaker.abandoned(self.probe);
+        }
+    }
+}
+
+/// Transport that fails fast while its server is down.
+///
+/// Requests, notifications and connection attempts pass through the breaker;
+/// messages from the server are read from the inner transport as they are.
+pub struct BreakerTransport {
+    inner: Arc<dyn Transport>,
+    breaker: CircuitBreaker,
+}
+
+impl BreakerTransport {
+    /// Guard `inner` with a breaker called `name`
+    pub fn new(
+        name: impl Into<String>,
+        inner: Arc<dyn Transport>,
+        config: BreakerConfig,
+    ) -> McpResult<Self> {
+        config.validate()?;
+        Ok(Self {
+            inner,
+            breaker: CircuitBreaker::new(name, config),
+        })
+    }
+
+    /// The breaker in front of the server
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+}
+
+#[async_trait]
+impl Transport for BreakerTransport {
+    async fn connect(&self) -> McpResult<()> {
+        let admission = self.breaker.admit()?;
+        let result = self.inner.connect().await;
+        admission.record(&result);
+        result
+    }
+
+    async fn disconnect(&self) -> McpResult<()> {
+        self.inner.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    async fn send_request(
+        &self,
+        request: JsonRpcRequest,
+        timeout: Option<Duration>,
+    ) -> McpResult<JsonRpcResponse> {
+        let admission = self.breaker.admit()?;
+        let result = self.inner.send_request(request, timeout).await;
+        admission.record(&result);
+        result
+    }
+
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
+        let admission = self.breaker.admit()?;
+        let result = self.inner.send_notification(notification).await;
+        admission.record(&result);
+        result
+    }
+
+    async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        self.inner.send_response(response).await
+    }
+
+    async fn receive_message(&self, timeout: Option<Duration>) -> McpResult<JsonRpcMessage> {
+        self.inner.receive_message(timeout).await
+    }
+
+    fn get_info(&self) -> TransportInfo {
+        let mut info = self.inner.get_info();
+        info.add_metadata("circuit", json!(self.breaker.stats()));
+        info
+    }
+
+    fn get_config(&self) -> &TransportConfig {
+        self.inner.get_config()
+    }
+
+    fn protocol_diagnostics(&self) -> Option<broadcast::Receiver<ProtocolDiagnostic>> {
+        self.inner.protocol_diagnostics()
+    }
+
+    fn circuit_events(&self) -> Option<broadcast::Receiver<CircuitEvent>> {
+        Some(self.breaker.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::InMemoryTransport;
+
+    fn lost() -> McpResult<()> {
+        Err(McpError::Transport(TransportError::ConnectionLost {
+            transport_type: "test".to_string(),
+            reason: "reset".to_string(),
+        }))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_circuit_opens_probes_and_closes() {
+        let breaker = CircuitBreaker::new(
+            "backend",
+            BreakerConfig::default()
+                .failure_threshold(2)
+                .cool_down(Duration::from_millis(50)),
+        );
+        let mut events = breaker.subscribe();
+
+        breaker.admit().unwrap().record(&lost());
+        // A JSON-RPC level error is not the server's connection failing
+        breaker
+            .admit()
+            .unwrap()
+            .record::<()>(&Err(McpError::Protocol(
+                crate::error::ProtocolError::RequestBlocked {
+                    reason: "policy".to_string(),
+                },
+            )));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.admit().unwrap().record(&lost());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let opened = events.try_recv().unwrap();
+        assert_eq!(
+            (opened.from, opened.to),
+            (CircuitState::Closed, CircuitState::Open)
+        );
+        assert_eq!(opened.consecutive_failures, 2);
+        assert!(opened.last_error.unwrap().contains("reset"));
+        match breaker.admit().err().unwrap() {
+            TransportError::CircuitOpen { name, retry_in } => {
+                assert_eq!(name, "backend");
+                assert_eq!(retry_in, Duration::from_millis(50));
+            }
+            other => panic!("expected an open circuit, got {other:?}"),
+        }
+
+        // After the cool-down one probe goes through at a time
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let probe = breaker.admit().unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.admit().is_err());
+        probe.record(&lost());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        // An abandoned probe frees its slot
+        drop(breaker.admit().unwrap());
+        breaker.admit().unwrap().record(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        let states: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| event.to)
+            .collect();
+        assert_eq!(
+            states,
+            [
+                CircuitState::HalfOpen,
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+                CircuitState::Closed
+            ]
+        );
+        let stats = breaker.stats();
+        assert_eq!(stats.opened, 2);
+        assert_eq!(stats.rejected, 2);
+        assert_eq!(stats.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_transport_fails_fast_while_server_is_down() {
+        let (client, server) = InMemoryTransport::pair();
+        let transport = BreakerTransport::new(
+            "memory",
+            Arc::new(client),
+            BreakerConfig::default().failure_threshold(1),
+        )
+        .unwrap();
+        let mut events = transport.circuit_events().unwrap();
+        transport.connect().await.unwrap();
+        // The server end goes away without ever answering
+        drop(server);
+
+        let request = JsonRpcRequest::new("1", "tools/list", json!({}));
+        let err = transport
+            .send_request(request.clone(), Some(Duration::from_millis(100)))
+            .await
+            .unwrap_err();
+        assert!(FailureKind::of(&err).is_some(), "{err:?}");
+        assert_eq!(events.try_recv().unwrap().to, CircuitState::Open);
+
+        let err = transport.send_request(request, None).await.unwrap_err();
+        assert!(
+            matches!(err, McpError::Transport(TransportError::CircuitOpen { .. })),
+            "{err:?}"
+        );
+        assert_eq!(transport.get_info().metadata["circuit"]["state"], "open");
+        assert!(BreakerConfig::default()
+            .half_open_probes(0)
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,362 @@
+//! Pumping messages between two transports.
+//!
+//! A [`Bridge`] joins two [`Transport`]s so that the peers behind them talk
+//! to each other, whatever the transport types. This is the core of a proxy,
+//! for example to publish a stdio server over HTTP or to reach an SSE server
+//! from an in-process client.
+//!
+//! Each request one peer sends is forwarded with [`Transport::send_request`]
+//! on the other transport, and the response goes back with
+//! [`Transport::send_response`]. Requests run concurrently, so a slow tool
+//! call does not hold up the rest. Notifications are forwarded as they
+//! arrive. A request that cannot be forwarded is answered with an internal
+//! error, so the sender is never left waiting.
+//!
+//! The bridge connects both transports if needed. When either peer goes
+//! away, or the shutdown future passed to [`Bridge::run_until`] completes,
+//! both transports are disconnected and [`Bridge::run`] returns its
+//! statistics.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use mcp_probe_core::transport::{bridge, InMemoryTransport, TransportConfig, TransportFactory};
+//!
+//! # async fn example() -> mcp_probe_core::McpResult<()> {
+//! let (client, front) = InMemoryTransport::pair();
+//! let backend = TransportFactory::create(TransportConfig::stdio("python", &["server.py"])).await?;
+//! let stats = bridge(Arc::new(front), Arc::from(backend)).await?;
+//! println!("{} requests forwarded to the server", stats.a_to_b.requests);
+//! # drop(client);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+
+use super::Transport;
+use crate::error::{McpError, McpResult, TransportError};
+use crate::messages::{JsonRpcError, JsonRpcMessage, JsonRpcResponse};
+
+/// Which transport of a bridge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeSide {
+    /// The first transport passed to the bridge
+    A,
+    /// The second transport passed to the bridge
+    B,
+}
+
+/// Why a bridge stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeEnd {
+    /// The peer behind this transport went away
+    Closed(BridgeSide),
+    /// The shutdown future completed
+    Shutdown,
+}
+
+/// Messages carried in one direction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowStats {
+    /// Requests forwarded
+    pub requests: u64,
+    /// Responses returned to the sender of a forwarded request
+    pub responses: u64,
+    /// Notifications forwarded
+    pub notifications: u64,
+    /// Messages that could not be forwarded or answered
+    pub failed: u64,
+}
+
+/// What a bridge carried, by direction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeStats {
+    /// Messages from the peer behind A to the peer behind B
+    pub a_to_b: FlowStats,
+    /// Messages from the peer behind B to the peer behind A
+    pub b_to_a: FlowStats,
+    /// How long the bridge has run
+    pub uptime: Duration,
+    /// Why the bridge stopped, if it has
+    pub ended: Option<BridgeEnd>,
+}
+
+#[derive(Default)]
+struct FlowCounters {
+    requests: AtomicU64,
+    responses: AtomicU64,
+    notifications: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl FlowCounters {
+    fn snapshot(&self) -> FlowStats {
+        FlowStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            responses: self.responses.load(Ordering::Relaxed),
+            notifications: self.notifications.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Two transports whose peers are joined together
+pub struct Bridge {
+    a: Arc<dyn Transport>,
+    b: Arc<dyn Transport>,
+    request_timeout: Option<Duration>,
+    a_to_b: Arc<FlowCounters>,
+    b_to_a: Arc<FlowCounters>,
+    started: Instant,
+}
+
+impl Bridge {
+    /// Join the peers behind `a` and `b`
+    pub fn new(a: Arc<dyn Transport>, b: Arc<dyn Transport>) -> Self {
+        Self {
+            a,
+            b,
+            request_timeout: None,
+            a_to_b: Arc::new(FlowCounters::default()),
+            b_to_a: Arc::new(FlowCounters::default()),
+            started: Instant::now(),
+        }
+    }
+
+    /// Give up on forwarded requests after `timeout` instead of the
+    /// transport's default
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// What the bridge has carried so far
+    pub fn stats(&self) -> BridgeStats {
+        BridgeStats {
+            a_to_b: self.a_to_b.snapshot(),
+            b_to_a: self.b_to_a.snapshot(),
+            uptime: self.started.elapsed(),
+            ended: None,
+        }
+    }
+
+    /// Forward messages until either peer goes away
+    pub async fn run(&self) -> McpResult<BridgeStats> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Forward messages until either peer goes away or `shutdown` completes
+    pub async fn run_until(&self, shutdown: impl Future<Output = ()>) -> McpResult<BridgeStats> {
+        for transport in [&self.a, &self.b] {
+            if !transport.is_connected() {
+                transport.connect().await?;
+            }
+        }
+
+        let ended = tokio::select! {
+            side = pump(BridgeSide::A, &self.a, &self.b, &self.a_to_b, self.request_timeout) => BridgeEnd::Closed(side),
+            side = pump(BridgeSide::B, &self.b, &self.a, &self.b_to_a, self.request_timeout) => BridgeEnd::Closed(side),
+            _ = shutdown => BridgeEnd::Shutdown,
+        };
+        tracing::debug!("Bridge stopped: {:?}", ended);
+
+        for transport in [&self.a, &self.b] {
+            if transport.is_connected() {
+                if let Err(e) = transport.disconnect().await {
+                    tracing::debug!("Failed to disconnect bridged transport: {}", e);
+                }
+            }
+        }
+        Ok(BridgeStats {
+            ended: Some(ended),
+            ..self.stats()
+        })
+    }
+}
+
+/// Bridge `a` and `b` until either peer goes away, see [`Bridge`]
+pub async fn bridge(a: Arc<dyn Transport>, b: Arc<dyn Transport>) -> McpResult<BridgeStats> {
+    Bridge::new(a, b).run().await
+}
+
+/// Forward what the peer behind `from` sends until it goes away
+async fn pump(
+    side: BridgeSide,
+    from: &Arc<dyn Transport>,
+    to: &Arc<dyn Transport>,
+    counters: &Arc<FlowCounters>,
+    request_timeout: Option<Duration>,
+) -> BridgeSide {
+    // Dropping the set aborts requests still in flight when the bridge stops
+    let mut in_flight = JoinSet::new();
+    loop {
+        let message = match from.receive_message(None).await {
+            Ok(message) => message,
+            Err(McpError::Transport(TransportError::TimeoutError { .. })) => continue,
+            Err(e) => {
+                tracing::debug!("Bridge side {:?} closed: {}", side, e);
+                return side;
+            }
+        };
+        while in_flight.try_join_next().is_some() {}
+
+        match message {
+            JsonRpcMessage::Request(request) => {
+                counters.requests.fetch_add(1, Ordering::Relaxed);
+                let (from, to, counters) = (Arc::clone(from), Arc::clone(to), Arc::clone(counters));
+                in_flight.spawn(async move {
+                    let id = request.id.clone();
+                    let response = match to.send_request(request, request_timeout).await {
+                        Ok(response) => response,
+                        Err(e) => {
+                            counters.failed.fetch_add(1, Ordering::Relaxed);
+                            JsonRpcResponse::error(id, JsonRpcError::internal_error(e.to_string()))
+                        }
+                    };
+                    match from.send_response(response).await {
+                        Ok(()) => counters.responses.fetch_add(1, Ordering::Relaxed),
+                        Err(e) => {
+                            tracing::warn!("Failed to return a bridged response: {}", e);
+                            counters.failed.fetch_add(1, Ordering::Relaxed)
+                        }
+                    };
+                });
+            }
+            JsonRpcMessage::Notification(notification) => {
+                match to.send_notification(notification).await {
+                    Ok(()) => counters.notifications.fetch_add(1, Ordering::Relaxed),
+                    Err(e) => {
+                        tracing::warn!("Failed to forward a bridged notification: {}", e);
+                        counters.failed.fetch_add(1, Ordering::Relaxed)
+                    }
+                };
+            }
+            JsonRpcMessage::Response(response) => {
+                // Responses to forwarded requests are taken by the transport's correlator
+                tracing::debug!("Dropping unmatched response {} on the bridge", response.id);
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{JsonRpcNotification, JsonRpcRequest};
+    use crate::transport::InMemoryTransport;
+    use serde_json::json;
+
+    /// Answer every request with its method and pass notifications back
+    async fn echo_server(server: InMemoryTransport) {
+        server.connect().await.unwrap();
+        while let Ok(message) = server.receive_message(None).await {
+            match message {
+                JsonRpcMessage::Request(request) => {
+                    let response =
+                        JsonRpcResponse::success(request.id, json!({ "method": request.method }));
+                    server
+                        .send_message(JsonRpcMessage::Response(response))
+                        .unwrap();
+                }
+                JsonRpcMessage::Notification(notification) => {
+                    server
+                        .send_message(JsonRpcMessage::Notification(notification))
+                        .unwrap();
+                }
+                JsonRpcMessage::Response(_) => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bridge_joins_two_transports() {
+        let (client, front) = InMemoryTransport::pair();
+        let (back, server) = InMemoryTransport::pair();
+        tokio::spawn(echo_server(server));
+        let bridge = Arc::new(Bridge::new(Arc::new(front), Arc::new(back)));
+        let running = tokio::spawn({
+            let bridge = Arc::clone(&bridge);
+            async move { bridge.run().await }
+        });
+
+        client.connect().await.unwrap();
+        let response = client
+            .send_request(
+                JsonRpcRequest::without_params(7, "tools/list"),
+                Some(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.id, 7.into());
+        assert_eq!(response.result, Some(json!({ "method": "tools/list" })));
+
+        client
+            .send_notification(JsonRpcNotification::without_params(
+                "notifications/initialized",
+            ))
+            .await
+            .unwrap();
+        let echoed = client
+            .receive_message(Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(echoed.method(), Some("notifications/initialized"));
+
+        // The client going away stops the bridge and closes the backend
+        client.disconnect().await.unwrap();
+        drop(client);
+        let stats = running.await.unwrap().unwrap();
+        assert_eq!(stats.ended, Some(BridgeEnd::Closed(BridgeSide::A)));
+        assert_eq!(stats.a_to_b.requests, 1);
+        assert_eq!(stats.a_to_b.responses, 1);
+        assert_eq!(stats.a_to_b.notifications, 1);
+        assert_eq!(stats.b_to_a.notifications, 1);
+        assert_eq!(stats.a_to_b.failed + stats.b_to_a.failed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_requests_get_an_error() {
+        let (client, front) = InMemoryTransport::pair();
+        // The server is connected but never answers
+        let (back, server) = InMemoryTransport::pair();
+        server.connect().await.unwrap();
+        let bridge =
+            Bridge::new(Arc::new(front), Arc::new(back)).request_timeout(Duration::from_millis(50));
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let running = tokio::spawn(async move {
+            bridge
+                .run_until(async {
+                    let _ = stopped.await;
+                })
+                .await
+        });
+
+        client.connect().await.unwrap();
+        let response = client
+            .send_request(
+                JsonRpcRequest::without_params("a", "ping"),
+                Some(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+        assert!(response.is_error());
+
+        stop.send(()).unwrap();
+        let stats = running.await.unwrap().unwrap();
+        assert_eq!(stats.ended, Some(BridgeEnd::Shutdown));
+        assert_eq!(stats.a_to_b.failed, 1);
+        assert_eq!(stats.a_to_b.responses, 1);
+        drop(server);
+    }
+}
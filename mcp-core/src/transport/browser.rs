@@ -0,0 +1,560 @@
+//! Browser transport over WebSocket or fetch, for `wasm32` builds.
+//!
+//! Web-based debugging frontends compile the client to
+//! `wasm32-unknown-unknown` and reach servers through the browser's own
+//! networking. The URL scheme picks how:
+//!
+//! - `ws://` and `wss://` open a WebSocket and exchange one JSON-RPC message
+//!   per text frame in both directions, so server notifications and requests
+//!   arrive as they are sent.
+//! - `http://` and `https://` POST each message with `fetch`, as streamable
+//!   HTTP does. A response body holds one JSON message or an SSE stream of
+//!   them, and the `Mcp-Session-Id` a server hands out is sent back on later
+//!   requests. Server messages only arrive in answer to a POST.
+//!
+//! JavaScript objects cannot leave the thread that made them, while
+//! [`Transport`] must be `Send + Sync`. The transport itself therefore only
+//! holds channels: the socket and the fetch calls live in tasks on the page's
+//! event loop, which pass every frame they receive back over a channel.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use mcp_probe_core::client::{ClientConfig, DefaultNotificationHandler, McpClient};
+//! use mcp_probe_core::messages::Implementation;
+//! use mcp_probe_core::transport::browser::BrowserTransport;
+//! use mcp_probe_core::transport::TransportConfig;
+//!
+//! # async fn example() -> mcp_probe_core::McpResult<()> {
+//! let config = TransportConfig::browser("wss://mcp.example.com/ws")?;
+//! let mut client = McpClient::with_transport(
+//!     Arc::new(BrowserTransport::new(config)),
+//!     ClientConfig::default(),
+//!     Box::new(DefaultNotificationHandler),
+//! );
+//! client.connect(Implementation::new("inspector", "0.1.0")).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use js_sys::Promise;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    BinaryType, CloseEvent, Event, Headers, MessageEvent, Request, RequestInit, Response,
+    WebSocket, Window, WorkerGlobalScope,
+};
+
+use super::config::BrowserConfig;
+use super::framing::{FrameParser, ProtocolDiagnostic};
+use super::{MessageReceiver, Transport, TransportConfig, TransportInfo};
+use crate::correlator::RequestCorrelator;
+use crate::error::{McpResult, TransportError};
+use crate::messages::{
+    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestId,
+};
+use crate::rt::{self, timeout, Task};
+
+const TRANSPORT_TYPE: &str = "browser";
+
+/// Header carrying the streamable HTTP session
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// A message on its way to the page task
+struct Outgoing {
+    text: String,
+    /// Set for requests, so a failed delivery can fail the request
+    id: Option<RequestId>,
+}
+
+/// What the page tasks report back
+enum Incoming {
+    /// A frame from the server
+    Frame(String),
+    /// A request never got an answer from the server
+    Failed { id: RequestId, reason: String },
+    /// The connection is gone
+    Closed(String),
+}
+
+/// Transport for MCP clients running in a browser.
+pub struct BrowserTransport {
+    config: TransportConfig,
+    settings: BrowserConfig,
+    info: Arc<Mutex<TransportInfo>>,
+    outbound: Mutex<Option<mpsc::UnboundedSender<Outgoing>>>,
+    messages: tokio::sync::Mutex<Option<MessageReceiver>>,
+    correlator: RequestCorrelator,
+    frames: FrameParser,
+    router: Mutex<Option<Task>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl BrowserTransport {
+    /// Create a browser transport from a [`TransportConfig::Browser`]
+    /// configuration; any other configuration gets the defaults for an
+    /// empty URL, which fails to connect.
+    pub fn new(config: TransportConfig) -> Self {
+        let settings = match &config {
+            TransportConfig::Browser(settings) => settings.clone(),
+            _ => BrowserConfig::new(""),
+        };
+        Self {
+            frames: FrameParser::new(TRANSPORT_TYPE, settings.parse_mode),
+            config,
+            settings,
+            info: Arc::new(Mutex::new(TransportInfo::new(TRANSPORT_TYPE))),
+            outbound: Mutex::new(None),
+            messages: tokio::sync::Mutex::new(None),
+            correlator: RequestCorrelator::new(TRANSPORT_TYPE),
+            router: Mutex::new(None),
+        }
+    }
+
+    fn send(&self, text: String, id: Option<RequestId>) -> McpResult<()> {
+        let connected = lock(&self.info).connected;
+        let outbound = match &*lock(&self.outbound) {
+            Some(outbound) if connected => outbound.clone(),
+            _ => {
+                return Err(TransportError::NotConnected {
+                    transport_type: TRANSPORT_TYPE.to_string(),
+                    reason: "Transport not connected".to_string(),
+                }
+                .into())
+            }
+        };
+        outbound
+            .send(Outgoing { text, id })
+            .map_err(|_| TransportError::ConnectionLost {
+                transport_type: TRANSPORT_TYPE.to_string(),
+                reason: "Connection closed".to_string(),
+            })?;
+        Ok(())
+    }
+
+    /// Route responses to their waiting requests and queue everything else.
+    ///
+    /// Ends when the connection closes, which closes the queue.
+    fn spawn_router(&self, mut incoming: mpsc::UnboundedReceiver<Incoming>) -> MessageReceiver {
+        let (queue_tx, queue_rx) = mpsc::unbounded_channel();
+        let correlator = self.correlator.clone();
+        let frames = self.frames.clone();
+        let info = Arc::clone(&self.info);
+        let handle = rt::spawn(async move {
+            while let Some(incoming) = incoming.recv().await {
+                let message = match incoming {
+                    Incoming::Frame(frame) => match frames.decode(&frame) {
+                        Ok(message) => message,
+                        Err(_) => continue,
+                    },
+                    Incoming::Failed { id, reason } => JsonRpcMessage::Response(
+                        JsonRpcResponse::error(id, JsonRpcError::internal_error(reason)),
+                    ),
+                    Incoming::Closed(reason) => {
+                        tracing::info!("Browser transport closed: {}", reason);
+                        break;
+                    }
+                };
+                let message = match message {
+                    JsonRpcMessage::Response(response) => match correlator.complete(response) {
+                        Some(unclaimed) => JsonRpcMessage::Response(unclaimed),
+                        None => continue,
+                    },
+                    other => other,
+                };
+                if queue_tx.send(message).is_err() {
+                    break;
+                }
+            }
+            correlator.cancel_all();
+            lock(&info).mark_disconnected();
+        });
+        *lock(&self.router) = Some(handle);
+        queue_rx
+    }
+}
+
+#[async_trait]
+impl Transport for BrowserTransport {
+    async fn connect(&self) -> McpResult<()> {
+        self.settings.validate()?;
+
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+        let (incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        if self.settings.is_websocket() {
+            let (opened_tx, opened_rx) = oneshot::channel();
+            spawn_local(run_websocket(
+                self.settings.url.clone(),
+                outbound_rx,
+                incoming_tx,
+                opened_tx,
+            ));
+            let opened = opened_rx
+                .await
+                .unwrap_or_else(|_| Err("Connection dropped".to_string()));
+            opened.map_err(|reason| TransportError::ConnectionFailed {
+                transport_type: TRANSPORT_TYPE.to_string(),
+                reason,
+            })?;
+        } else {
+            spawn_local(run_fetch(self.settings.clone(), outbound_rx, incoming_tx));
+        }
+
+        *lock(&self.outbound) = Some(outbound_tx);
+        *self.messages.lock().await = Some(self.spawn_router(incoming_rx));
+        lock(&self.info).mark_connected();
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> McpResult<()> {
+        // Dropping the sender ends the page task, which closes the socket
+        lock(&self.outbound).take();
+        if let Some(router) = lock(&self.router).take() {
+            router.abort();
+        }
+        self.correlator.cancel_all();
+        self.messages.lock().await.take();
+        lock(&self.info).mark_disconnected();
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        lock(&self.info).connected && lock(&self.outbound).is_some()
+    }
+
+    async fn send_request(
+        &self,
+        request: JsonRpcRequest,
+        timeout_duration: Option<Duration>,
+    ) -> McpResult<JsonRpcResponse> {
+        let pending = self.correlator.register(&request.id)?;
+        let id = request.id.clone();
+        self.send(serde_json::to_string(&request)?, Some(id))?;
+        lock(&self.info).increment_requests_sent();
+
+        let response = pending
+            .wait(timeout_duration.unwrap_or(self.settings.timeout))
+            .await?;
+
+        lock(&self.info).increment_responses_received();
+        Ok(response)
+    }
+
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.send(serde_json::to_string(&notification)?, None)?;
+        lock(&self.info).increment_notifications_sent();
+        Ok(())
+    }
+
+    async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        self.send(serde_json::to_string(&response)?, None)
+    }
+
+    async fn receive_message(
+        &self,
+        timeout_duration: Option<Duration>,
+    ) -> McpResult<JsonRpcMessage> {
+        let mut messages = self.messages.lock().await;
+        let queue = messages
+            .as_mut()
+            .ok_or_else(|| TransportError::NotConnected {
+                transport_type: TRANSPORT_TYPE.to_string(),
+                reason: "Transport not connected".to_string(),
+            })?;
+
+        let received = match timeout_duration {
+            Some(timeout_duration) => {
+                timeout(timeout_duration, queue.recv()).await.map_err(|_| {
+                    TransportError::TimeoutError {
+                        transport_type: TRANSPORT_TYPE.to_string(),
+                        reason: format!("Message receive timed out after {:?}", timeout_duration),
+                    }
+                })?
+            }
+            None => queue.recv().await,
+        };
+        drop(messages);
+
+        let message = received.ok_or_else(|| TransportError::ConnectionLost {
+            transport_type: TRANSPORT_TYPE.to_string(),
+            reason: "Connection closed".to_string(),
+        })?;
+
+        let mut info = lock(&self.info);
+        match &message {
+            JsonRpcMessage::Response(_) => info.increment_responses_received(),
+            JsonRpcMessage::Notification(_) => info.increment_notifications_received(),
+            JsonRpcMessage::Request(_) => {}
+        }
+        Ok(message)
+    }
+
+    fn get_info(&self) -> TransportInfo {
+        let mut info = lock(&self.info).clone();
+        info.add_metadata("url", serde_json::json!(self.settings.url));
+        info.add_metadata(
+            "mode",
+            serde_json::json!(if self.settings.is_websocket() {
+                "websocket"
+            } else {
+                "fetch"
+            }),
+        );
+        info.add_metadata(
+            "pending_requests",
+            serde_json::json!(self.correlator.pending_count()),
+        );
+        info
+    }
+
+    fn get_config(&self) -> &TransportConfig {
+        &self.config
+    }
+
+    fn protocol_diagnostics(&self) -> Option<broadcast::Receiver<ProtocolDiagnostic>> {
+        Some(self.frames.subscribe())
+    }
+}
+
+impl Drop for BrowserTransport {
+    fn drop(&mut self) {
+        if let Some(router) = lock(&self.router).take() {
+            router.abort();
+        }
+    }
+}
+
+/// Text of a JavaScript error, for error messages
+fn describe(error: JsValue) -> String {
+    error
+        .as_string()
+        .or_else(|| {
+            error
+                .dyn_ref::<js_sys::Error>()
+                .map(|e| String::from(e.message()))
+        })
+        .unwrap_or_else(|| format!("{:?}", error))
+}
+
+/// Own a WebSocket: send what arrives on `outbound`, report frames and the
+/// close on `incoming`, and close the socket once `outbound` closes
+async fn run_websocket(
+    url: String,
+    mut outbound: mpsc::UnboundedReceiver<Outgoing>,
+    incoming: mpsc::UnboundedSender<Incoming>,
+    opened: oneshot::Sender<Result<(), String>>,
+) {
+    let socket = match WebSocket::new(&url) {
+        Ok(socket) => socket,
+        Err(e) => {
+            let _ = opened.send(Err(describe(e)));
+            return;
+        }
+    };
+    socket.set_binary_type(BinaryType::Arraybuffer);
+
+    // Whichever of open, error and close comes first settles the connect
+    let opened = Rc::new(RefCell::new(Some(opened)));
+    let settle = {
+        let opened = Rc::clone(&opened);
+        move |result: Result<(), String>| {
+            if let Some(opened) = opened.borrow_mut().take() {
+                let _ = opened.send(result);
+            }
+        }
+    };
+
+    let on_open = Closure::<dyn FnMut(Event)>::new({
+        let settle = settle.clone();
+        move |_| settle(Ok(()))
+    });
+    let on_error = Closure::<dyn FnMut(Event)>::new({
+        let settle = settle.clone();
+        let url = url.clone();
+        move |_| settle(Err(format!("Could not open a WebSocket to {}", url)))
+    });
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new({
+        let incoming = incoming.clone();
+        move |event: MessageEvent| {
+            let data = event.data();
+            let frame = match data.as_string() {
+                Some(text) => text,
+                // Binary frames carry UTF-8 JSON too
+                None => {
+                    String::from_utf8_lossy(&js_sys::Uint8Array::new(&data).to_vec()).into_owned()
+                }
+            };
+            let _ = incoming.send(Incoming::Frame(frame));
+        }
+    });
+    let on_close = Closure::<dyn FnMut(CloseEvent)>::new({
+        let incoming = incoming.clone();
+        move |event: CloseEvent| {
+            let reason = format!("WebSocket closed ({}) {}", event.code(), event.reason());
+            settle(Err(reason.clone()));
+            let _ = incoming.send(Incoming::Closed(reason));
+        }
+    });
+    socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+    while let Some(message) = outbound.recv().await {
+        if let Err(e) = socket.send_with_str(&message.text) {
+            let reason = describe(e);
+            match message.id {
+                Some(id) => {
+                    let _ = incoming.send(Incoming::Failed { id, reason });
+                }
+                None => tracing::warn!("Failed to send over WebSocket: {}", reason),
+            }
+        }
+    }
+
+    // The closures must outlive the socket's use of them
+    socket.set_onopen(None);
+    socket.set_onerror(None);
+    socket.set_onmessage(None);
+    socket.set_onclose(None);
+    let _ = socket.close();
+    drop((on_open, on_error, on_message, on_close));
+}
+
+/// POST everything that arrives on `outbound`, each in its own task so a
+/// slow request does not hold up the others
+async fn run_fetch(
+    settings: BrowserConfig,
+    mut outbound: mpsc::UnboundedReceiver<Outgoing>,
+    incoming: mpsc::UnboundedSender<Incoming>,
+) {
+    let settings = Rc::new(settings);
+    let session: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    while let Some(message) = outbound.recv().await {
+        let settings = Rc::clone(&settings);
+        let session = Rc::clone(&session);
+        let incoming = incoming.clone();
+        spawn_local(async move {
+            let id = message.id.clone();
+            if let Err(reason) = post(&settings, &session, message.text, &incoming).await {
+                match id {
+                    Some(id) => {
+                        let _ = incoming.send(Incoming::Failed { id, reason });
+                    }
+                    None => tracing::warn!("Failed to post to {}: {}", settings.url, reason),
+                }
+            }
+        });
+    }
+}
+
+/// POST one message and report the messages in the response
+async fn post(
+    settings: &BrowserConfig,
+    session: &RefCell<Option<String>>,
+    body: String,
+    incoming: &mpsc::UnboundedSender<Incoming>,
+) -> Result<(), String> {
+    let headers = Headers::new().map_err(describe)?;
+    headers
+        .set("Content-Type", "application/json")
+        .map_err(describe)?;
+    headers
+        .set("Accept", "application/json, text/event-stream")
+        .map_err(describe)?;
+    if let Some(session) = session.borrow().as_deref() {
+        headers.set(SESSION_HEADER, session).map_err(describe)?;
+    }
+    for (name, value) in &settings.headers {
+        headers.set(name, value).map_err(describe)?;
+    }
+
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(&body));
+    let request = Request::new_with_str_and_init(&settings.url, &init).map_err(describe)?;
+
+    let response: Response = JsFuture::from(fetch(&request)?)
+        .await
+        .map_err(describe)?
+        .dyn_into()
+        .map_err(describe)?;
+    if let Ok(Some(id)) = response.headers().get(SESSION_HEADER) {
+        *session.borrow_mut() = Some(id);
+    }
+    let content_type = response
+        .headers()
+        .get("Content-Type")
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let text = JsFuture::from(response.text().map_err(describe)?)
+        .await
+        .map_err(describe)?
+        .as_string()
+        .unwrap_or_default();
+
+    if !response.ok() {
+        return Err(format!(
+            "HTTP {} {}: {}",
+            response.status(),
+            response.status_text(),
+            text
+        ));
+    }
+
+    let frames = if content_type.starts_with("text/event-stream") {
+        sse_data(&text)
+    } else {
+        vec![text]
+    };
+    for frame in frames {
+        // 202 Accepted answers notifications with an empty body
+        if !frame.trim().is_empty() {
+            let _ = incoming.send(Incoming::Frame(frame));
+        }
+    }
+    Ok(())
+}
+
+/// `fetch` from whichever global the client runs in: a page or a worker
+fn fetch(request: &Request) -> Result<Promise, String> {
+    let global = js_sys::global();
+    if let Some(window) = global.dyn_ref::<Window>() {
+        Ok(window.fetch_with_request(request))
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+        Ok(worker.fetch_with_request(request))
+    } else {
+        Err("fetch is not available in this context".to_string())
+    }
+}
+
+/// Payloads of the events in a complete SSE body
+fn sse_data(body: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    let mut data: Vec<&str> = Vec::new();
+    for line in body.lines().chain(std::iter::once("")) {
+        if line.is_empty() {
+            if !data.is_empty() {
+                events.push(data.join("\n"));
+                data.clear();
+            }
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    events
+}
@@ -0,0 +1,468 @@
+//! Compression of HTTP message bodies.
+//!
+//! With compression enabled, the HTTP transports ask for compressed responses
+//! with `Accept-Encoding: gzip, deflate` and decode whatever the server
+//! sends, including `text/event-stream` bodies, as they arrive. Requests are
+//! compressed only once the server has said it accepts compressed requests,
+//! by naming an encoding in an `Accept-Encoding` response header (RFC 7694),
+//! and only when they are large enough to gain from it. A server that
+//! answers a compressed request with `415 Unsupported Media Type` gets the
+//! request again uncompressed, and no compressed requests after that.
+//!
+//! [`CompressionStats`] counts the bytes on both sides of the encoding, so
+//! what compression saved can be read from a transport's info.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::transport::compression::{compress, decompress, Encoding};
+//!
+//! let body = br#"{"jsonrpc":"2.0","id":1,"result":{"tools":[]}}"#.repeat(20);
+//! let packed = compress(Encoding::Gzip, &body).unwrap();
+//! assert!(packed.len() < body.len());
+//! assert_eq!(decompress(Encoding::Gzip, &packed).unwrap(), body);
+//! assert_eq!(Encoding::from_header("x-gzip"), Some(Encoding::Gzip));
+//! ```
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use flate2::write::{GzDecoder, GzEncoder, ZlibDecoder, ZlibEncoder};
+use flate2::Compression as Level;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, ACCEPT_ENCODING, CONTENT_ENCODING};
+use reqwest::{RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+/// Encodings asked for in `Accept-Encoding`
+pub const ACCEPT: &str = "gzip, deflate";
+
+/// Requests smaller than this are sent uncompressed
+pub const MIN_COMPRESSED_REQUEST: usize = 1024;
+
+/// A content encoding the transports can produce and decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// `gzip`
+    Gzip,
+    /// `deflate`, which HTTP defines as the zlib format
+    Deflate,
+}
+
+impl Encoding {
+    /// The encoding named by one `Content-Encoding` token.
+    pub fn from_header(token: &str) -> Option<Self> {
+        match token.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            "deflate" => Some(Self::Deflate),
+            _ => None,
+        }
+    }
+
+    /// The first encoding in an `Accept-Encoding` list that is not refused
+    /// with `q=0`.
+    pub fn from_accept(list: &str) -> Option<Self> {
+        list.split(',').find_map(|item| {
+            let mut parts = item.split(';');
+            let encoding = Self::from_header(parts.next()?)?;
+            let refused = parts.any(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    == Some(0.0)
+            });
+            (!refused).then_some(encoding)
+        })
+    }
+
+    /// The header token for this encoding.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+        }
+    }
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Compress `body` with `encoding`.
+pub fn compress(encoding: Encoding, body: &[u8]) -> io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+        Encoding::Deflate => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+    }
+}
+
+/// Decompress a whole `body` encoded with `encoding`.
+pub fn decompress(encoding: Encoding, body: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decoder = Decoder::new(encoding);
+    let mut out = decoder.push(body)?;
+    out.extend_from_slice(&decoder.finish()?);
+    Ok(out)
+}
+
+/// Bytes on either side of the encoding, for requests and responses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressionStats {
+    /// Requests sent compressed
+    pub requests_compressed: u64,
+    /// Size of those requests before compression
+    pub request_bytes: u64,
+    /// Size of those requests as sent
+    pub request_bytes_sent: u64,
+    /// Responses that arrived compressed
+    pub responses_compressed: u64,
+    /// Size of those responses after decoding
+    pub response_bytes: u64,
+    /// Size of those responses as received
+    pub response_bytes_received: u64,
+}
+
+impl CompressionStats {
+    /// Bytes that did not cross the network thanks to compression.
+    pub fn bytes_saved(&self) -> u64 {
+        self.request_bytes.saturating_sub(self.request_bytes_sent)
+            + self
+                .response_bytes
+                .saturating_sub(self.response_bytes_received)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    requests_compressed: AtomicU64,
+    request_bytes: AtomicU64,
+    request_bytes_sent: AtomicU64,
+    responses_compressed: AtomicU64,
+    response_bytes: AtomicU64,
+    response_bytes_received: AtomicU64,
+}
+
+/// No request encoding negotiated yet
+const NONE: u8 = 0;
+const GZIP: u8 = 1;
+const DEFLATE: u8 = 2;
+
+/// Compression state shared by one transport's requests.
+#[derive(Debug, Clone)]
+pub(crate) struct Compression {
+    enabled: bool,
+    /// Encoding the server accepts requests in
+    request_encoding: Arc<AtomicU8>,
+    /// The server refused a compressed request
+    refused: Arc<AtomicBool>,
+    counters: Arc<Counters>,
+}
+
+impl Compression {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            request_encoding: Arc::new(AtomicU8::new(NONE)),
+            refused: Arc::new(AtomicBool::new(false)),
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Ask for compressed responses, if compression is on
+    pub(crate) fn accept(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.enabled {
+            request.header(ACCEPT_ENCODING, ACCEPT)
+        } else {
+            request
+        }
+    }
+
+    /// Set `body` on `request`, compressed if the server takes compressed
+    /// requests. Returns whether it was compressed.
+    pub(crate) fn body(&self, request: RequestBuilder, body: Bytes) -> (RequestBuilder, bool) {
+        let request = self.accept(request);
+        let Some(encoding) = self.request_encoding() else {
+            return (request.body(body), false);
+        };
+        if body.len() < MIN_COMPRESSED_REQUEST {
+            return (request.body(body), false);
+        }
+        match compress(encoding, &body) {
+            Ok(packed) if packed.len() < body.len() => {
+                let counters = &self.counters;
+                counters.requests_compressed.fetch_add(1, Ordering::Relaxed);
+                counters
+                    .request_bytes
+                    .fetch_add(body.len() as u64, Ordering::Relaxed);
+                counters
+                    .request_bytes_sent
+                    .fetch_add(packed.len() as u64, Ordering::Relaxed);
+                let request = request.header(CONTENT_ENCODING, encoding.as_str());
+                (request.body(packed), true)
+            }
+            _ => (request.body(body), false),
+        }
+    }
+
+    /// Learn from a response whether the server takes compressed requests
+    pub(crate) fn observe(&self, headers: &HeaderMap) {
+        if !self.enabled || self.refused.load(Ordering::Relaxed) {
+            return;
+        }
+        let accepted = headers
+            .get(ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Encoding::from_accept);
+        if let Some(encoding) = accepted {
+            let code = match encoding {
+                Encoding::Gzip => GZIP,
+                Encoding::Deflate => DEFLATE,
+            };
+            if self.request_encoding.swap(code, Ordering::Relaxed) == NONE {
+                tracing::debug!("Server accepts {} requests; compressing them", encoding);
+            }
+        }
+    }
+
+    /// Stop compressing requests after the server refused one
+    pub(crate) fn refuse(&self) {
+        tracing::warn!("Server refused a compressed request; sending requests uncompressed");
+        self.refused.store(true, Ordering::Relaxed);
+        self.request_encoding.store(NONE, Ordering::Relaxed);
+    }
+
+    fn request_encoding(&self) -> Option<Encoding> {
+        if !self.enabled {
+            return None;
+        }
+        match self.request_encoding.load(Ordering::Relaxed) {
+            GZIP => Some(Encoding::Gzip),
+            DEFLATE => Some(Encoding::Deflate),
+            _ => None,
+        }
+    }
+
+    /// The body of `response`, decoded as it arrives
+    pub(crate) fn decode(&self, response: Response) -> BoxStream<'static, io::Result<Bytes>> {
+        self.observe(response.headers());
+        let encoding = response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_ascii_lowercase());
+        let body = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(io::Error::other));
+        let encoding = match encoding.as_deref() {
+            None | Some("identity") | Some("") => return body.boxed(),
+            Some(token) => match Encoding::from_header(token) {
+                Some(encoding) => encoding,
+                None => {
+                    let error = io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unsupported Content-Encoding '{}'", token),
+                    );
+                    return stream::once(async { Err(error) }).boxed();
+                }
+            },
+        };
+
+        let counters = self.counters.clone();
+        counters
+            .responses_compressed
+            .fetch_add(1, Ordering::Relaxed);
+        let state = Some((body.boxed(), Decoder::new(encoding)));
+        stream::unfold(state, move |state| {
+            let counters = counters.clone();
+            async move {
+                let (mut body, mut decoder) = state?;
+                loop {
+                    let out = match body.next().await {
+                        Some(Ok(chunk)) => {
+                            counters
+                                .response_bytes_received
+                                .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                            match decoder.push(&chunk) {
+                                Ok(out) if out.is_empty() => continue,
+                                Ok(out) => out,
+                                Err(e) => return Some((Err(e), None)),
+                            }
+                        }
+                        Some(Err(e)) => return Some((Err(e), None)),
+                        None => match decoder.finish() {
+                            Ok(out) if out.is_empty() => return None,
+                            Ok(out) => {
+                                counters
+                                    .response_bytes
+                                    .fetch_add(out.len() as u64, Ordering::Relaxed);
+                                return Some((Ok(Bytes::from(out)), None));
+                            }
+                            Err(e) => return Some((Err(e), None)),
+                        },
+                    };
+                    counters
+                        .response_bytes
+                        .fetch_add(out.len() as u64, Ordering::Relaxed);
+                    return Some((Ok(Bytes::from(out)), Some((body, decoder))));
+                }
+            }
+        })
+        .boxed()
+    }
+
+    pub(crate) fn stats(&self) -> CompressionStats {
+        let counters = &self.counters;
+        CompressionStats {
+            requests_compressed: counters.requests_compressed.load(Ordering::Relaxed),
+            request_bytes: counters.request_bytes.load(Ordering::Relaxed),
+            request_bytes_sent: counters.request_bytes_sent.load(Ordering::Relaxed),
+            responses_compressed: counters.responses_compressed.load(Ordering::Relaxed),
+            response_bytes: counters.response_bytes.load(Ordering::Relaxed),
+            response_bytes_received: counters.response_bytes_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Incremental decoder for one body
+enum Decoder {
+    Gzip(GzDecoder<Vec<u8>>),
+    Deflate(ZlibDecoder<Vec<u8>>),
+}
+
+impl Decoder {
+    fn new(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip => Self::Gzip(GzDecoder::new(Vec::new())),
+            Encoding::Deflate => Self::Deflate(ZlibDecoder::new(Vec::new())),
+        }
+    }
+
+    /// Decode `chunk`, returning what it decoded to so far
+    fn push(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            Self::Deflate(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+        }
+    }
+
+    /// Decode what is left once the body has ended
+    fn finish(&mut self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip(decoder) => {
+                decoder.try_finish()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            Self::Deflate(decoder) => {
+                decoder.try_finish()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_accept_encoding_lists() {
+        assert_eq!(Encoding::from_accept("gzip"), Some(Encoding::Gzip));
+        assert_eq!(
+            Encoding::from_accept("br, deflate;q=0.5, gzip"),
+            Some(Encoding::Deflate)
+        );
+        assert_eq!(
+            Encoding::from_accept("gzip;q=0, deflate"),
+            Some(Encoding::Deflate)
+        );
+        assert_eq!(Encoding::from_accept("br, zstd"), None);
+    }
+
+    #[test]
+    fn test_decoding_across_chunks() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{"text":"hello"}}"#.repeat(50);
+        for encoding in [Encoding::Gzip, Encoding::Deflate] {
+            let packed = compress(encoding, body.as_bytes()).unwrap();
+            let mut decoder = Decoder::new(encoding);
+            let mut out = Vec::new();
+            for chunk in packed.chunks(7) {
+                out.extend(decoder.push(chunk).unwrap());
+            }
+            out.extend(decoder.finish().unwrap());
+            assert_eq!(out, body.as_bytes());
+        }
+
+        assert!(decompress(Encoding::Gzip, b"not gzip at all").is_err());
+    }
+
+    #[test]
+    fn test_requests_compressed_once_negotiated() {
+        let compression = Compression::new(true);
+        let client = reqwest::Client::new();
+        let large = vec![b'a'; 4096];
+        let send = |body: Vec<u8>| {
+            let body = Bytes::from(body);
+            let (request, compressed) = compression.body(client.post("http://localhost/"), body);
+            (request.build().unwrap(), compressed)
+        };
+
+        // Not before the server says it takes them
+        let (request, compressed) = send(large.clone());
+        assert!(!compressed);
+        assert_eq!(request.headers()[ACCEPT_ENCODING], ACCEPT);
+        assert!(request.headers().get(CONTENT_ENCODING).is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("gzip"));
+        compression.observe(&headers);
+        let (request, compressed) = send(large.clone());
+        assert!(compressed);
+        assert_eq!(request.headers()[CONTENT_ENCODING], "gzip");
+        assert!(!send(b"{}".to_vec()).1, "small requests stay as they are");
+
+        let stats = compression.stats();
+        assert_eq!(stats.requests_compressed, 1);
+        assert_eq!(stats.request_bytes, 4096);
+        assert!(stats.bytes_saved() > 4000);
+
+        compression.refuse();
+        compression.observe(&headers);
+        assert!(!send(large.clone()).1);
+
+        let off = Compression::new(false);
+        off.observe(&headers);
+        let (request, compressed) = off.body(client.post("http://localhost/"), large.into());
+        assert!(!compressed);
+        assert!(request
+            .build()
+            .unwrap()
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .is_none());
+    }
+}
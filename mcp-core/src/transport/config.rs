@@ -16,6 +16,12 @@
 //!     working_dir: Some("/path/to/server".to_string()),
 //!     timeout: Duration::from_secs(30),
 //!     environment: Default::default(),
+//!     runtime: Default::default(),
+//!     container: None,
+//!     prerequisites: Default::default(),
+//!     sandbox: None,
+//!     parse_mode: Default::default(),
+//!     limits: Default::default(),
 //! });
 //!
 //! // HTTP+SSE transport configuration  
@@ -24,14 +30,27 @@
 //!     timeout: Duration::from_secs(60),
 //!     headers: Default::default(),
 //!     auth: None,
+//!     max_in_flight: 32,
+//!     allowed_origins: Vec::new(),
+//!     parse_mode: Default::default(),
+//!     limits: Default::default(),
+//!     compression: true,
+//!     connection: Default::default(),
 //! });
 //! ```
 
+use super::container::{ContainerConfig, ProcessRuntime};
+use super::framing::ParseMode;
+use super::limits::MessageLimits;
+use super::prerequisites::Prerequisites;
+use super::sandbox::SandboxConfig;
+use super::secrets::{expand_value, DefaultSecretResolver, SecretResolver};
 use crate::error::{ConfigError, McpResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
+#[cfg(feature = "http")]
 use url::Url;
 
 /// Transport configuration enum supporting all MCP transport types.
@@ -40,15 +59,25 @@ use url::Url;
 /// ensuring that each transport gets the configuration parameters it needs.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[cfg_attr(not(feature = "http"), allow(clippy::large_enum_variant))]
 pub enum TransportConfig {
     /// Local process communication via stdio
     Stdio(StdioConfig),
 
     /// Remote HTTP server with Server-Sent Events
+    #[cfg(feature = "http")]
     HttpSse(HttpSseConfig),
 
     /// Full-duplex HTTP streaming
+    #[cfg(feature = "http")]
     HttpStream(HttpStreamConfig),
+
+    /// In-process channel pair, created with `InMemoryTransport::pair`
+    InMemory(InMemoryConfig),
+
+    /// WebSocket or fetch from a browser, in `wasm32` builds
+    #[cfg(feature = "wasm")]
+    Browser(BrowserConfig),
 }
 
 impl TransportConfig {
@@ -68,6 +97,12 @@ impl TransportConfig {
             working_dir: None,
             timeout: Duration::from_secs(30),
             environment: HashMap::new(),
+            runtime: ProcessRuntime::Native,
+            container: None,
+            prerequisites: Prerequisites::default(),
+            sandbox: None,
+            parse_mode: ParseMode::default(),
+            limits: MessageLimits::default(),
         })
     }
 
@@ -80,6 +115,7 @@ impl TransportConfig {
     ///
     /// let config = TransportConfig::http_sse("https://api.example.com/mcp").unwrap();
     /// ```
+    #[cfg(feature = "http")]
     pub fn http_sse(base_url: impl AsRef<str>) -> McpResult<Self> {
         let url = base_url
             .as_ref()
@@ -90,12 +126,7 @@ impl TransportConfig {
                 reason: format!("Invalid URL: {}", e),
             })?;
 
-        Ok(Self::HttpSse(HttpSseConfig {
-            base_url: url,
-            timeout: Duration::from_secs(60),
-            headers: HashMap::new(),
-            auth: None,
-        }))
+        Ok(Self::HttpSse(HttpSseConfig::new(url)))
     }
 
     /// Create a new HTTP streaming transport configuration.
@@ -107,6 +138,7 @@ impl TransportConfig {
     ///
     /// let config = TransportConfig::http_stream("https://stream.example.com/mcp").unwrap();
     /// ```
+    #[cfg(feature = "http")]
     pub fn http_stream(base_url: impl AsRef<str>) -> McpResult<Self> {
         let url = base_url
             .as_ref()
@@ -124,30 +156,123 @@ impl TransportConfig {
             auth: None,
             compression: true,
             flow_control_window: 65536,
+            parse_mode: ParseMode::default(),
+            limits: MessageLimits::default(),
+            connection: HttpConnectionConfig::default(),
         }))
     }
 
+    /// Create a browser transport configuration. `ws://` and `wss://` URLs
+    /// connect over a WebSocket, `http://` and `https://` ones with fetch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mcp_probe_core::transport::TransportConfig;
+    ///
+    /// let config = TransportConfig::browser("wss://mcp.example.com/ws").unwrap();
+    /// ```
+    #[cfg(feature = "wasm")]
+    pub fn browser(url: impl Into<String>) -> McpResult<Self> {
+        let config = BrowserConfig::new(url);
+        config.validate()?;
+        Ok(Self::Browser(config))
+    }
+
     /// Get a human-readable name for this transport type.
     pub fn transport_type(&self) -> &'static str {
         match self {
             Self::Stdio(_) => "stdio",
+            #[cfg(feature = "http")]
             Self::HttpSse(_) => "http-sse",
+            #[cfg(feature = "http")]
             Self::HttpStream(_) => "http-stream",
+            Self::InMemory(_) => "in-memory",
+            #[cfg(feature = "wasm")]
+            Self::Browser(_) => "browser",
+        }
+    }
+
+    /// How strictly the transport checks incoming frames.
+    pub fn parse_mode(&self) -> ParseMode {
+        match self {
+            Self::Stdio(config) => config.parse_mode,
+            #[cfg(feature = "http")]
+            Self::HttpSse(config) => config.parse_mode,
+            #[cfg(feature = "http")]
+            Self::HttpStream(config) => config.parse_mode,
+            Self::InMemory(_) => ParseMode::Lenient,
+            #[cfg(feature = "wasm")]
+            Self::Browser(config) => config.parse_mode,
+        }
+    }
+
+    /// Set how strictly the transport checks incoming frames. In-memory
+    /// transports exchange decoded messages and are not affected.
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        match &mut self {
+            Self::Stdio(config) => config.parse_mode = mode,
+            #[cfg(feature = "http")]
+            Self::HttpSse(config) => config.parse_mode = mode,
+            #[cfg(feature = "http")]
+            Self::HttpStream(config) => config.parse_mode = mode,
+            Self::InMemory(_) => {}
+            #[cfg(feature = "wasm")]
+            Self::Browser(config) => config.parse_mode = mode,
+        }
+        self
+    }
+
+    /// Size limits on the transport's messages. In-memory and browser
+    /// transports have none.
+    pub fn limits(&self) -> Option<&MessageLimits> {
+        match self {
+            Self::Stdio(config) => Some(&config.limits),
+            #[cfg(feature = "http")]
+            Self::HttpSse(config) => Some(&config.limits),
+            #[cfg(feature = "http")]
+            Self::HttpStream(config) => Some(&config.limits),
+            Self::InMemory(_) => None,
+            #[cfg(feature = "wasm")]
+            Self::Browser(_) => None,
         }
     }
 
+    /// Set the size limits on the transport's messages. In-memory and
+    /// browser transports are not affected.
+    pub fn with_limits(mut self, limits: MessageLimits) -> Self {
+        match &mut self {
+            Self::Stdio(config) => config.limits = limits,
+            #[cfg(feature = "http")]
+            Self::HttpSse(config) => config.limits = limits,
+            #[cfg(feature = "http")]
+            Self::HttpStream(config) => config.limits = limits,
+            Self::InMemory(_) => {}
+            #[cfg(feature = "wasm")]
+            Self::Browser(_) => {}
+        }
+        self
+    }
+
     /// Validate the configuration and return any errors.
     pub fn validate(&self) -> McpResult<()> {
         match self {
             Self::Stdio(config) => config.validate(),
+            #[cfg(feature = "http")]
             Self::HttpSse(config) => config.validate(),
+            #[cfg(feature = "http")]
             Self::HttpStream(config) => config.validate(),
+            Self::InMemory(_) => Ok(()),
+            #[cfg(feature = "wasm")]
+            Self::Browser(config) => config.validate(),
         }
     }
 
     /// Load configuration from a file.
     ///
     /// Supports JSON, YAML, and TOML formats based on file extension.
+    /// `${ENV_VAR}` placeholders and `secret://` references in string values
+    /// are expanded first, see [`secrets`](super::secrets).
     ///
     /// # Examples
     ///
@@ -158,12 +283,22 @@ impl TransportConfig {
     /// # Ok::<(), mcp_probe_core::error::McpError>(())
     /// ```
     pub fn from_file(path: impl AsRef<std::path::Path>) -> McpResult<Self> {
+        let path = path.as_ref();
+        let base_dir = path.parent().unwrap_or_else(|| std::path::Path::new("."));
+        Self::from_file_with(path, &DefaultSecretResolver::relative_to(base_dir))
+    }
+
+    /// Load configuration from a file, resolving `secret://` references with `resolver`.
+    pub fn from_file_with(
+        path: impl AsRef<std::path::Path>,
+        resolver: &dyn SecretResolver,
+    ) -> McpResult<Self> {
         let path = path.as_ref();
         let content = std::fs::read_to_string(path).map_err(|_e| ConfigError::FileNotFound {
             path: path.display().to_string(),
         })?;
 
-        let config: Self = match path.extension().and_then(|ext| ext.to_str()) {
+        let mut raw: serde_json::Value = match path.extension().and_then(|ext| ext.to_str()) {
             Some("json") => {
                 serde_json::from_str(&content).map_err(|e| ConfigError::InvalidFormat {
                     path: path.display().to_string(),
@@ -189,12 +324,21 @@ impl TransportConfig {
             }
         };
 
+        expand_value(&mut raw, resolver, "")?;
+        let config: Self = serde_json::from_value(raw).map_err(|e| ConfigError::InvalidFormat {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })?;
+
         config.validate()?;
         Ok(config)
     }
 
     /// Save configuration to a file.
     ///
+    /// Values are written as loaded, so a configuration read with secrets
+    /// expanded should not be saved back over the original file.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -258,6 +402,34 @@ pub struct StdioConfig {
 
     /// Environment variables to set for the process
     pub environment: HashMap<String, String>,
+
+    /// Runtime used to launch the process (`native`, `docker` or `podman`)
+    #[serde(default, skip_serializing_if = "is_native")]
+    pub runtime: ProcessRuntime,
+
+    /// Container settings, required for container runtimes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerConfig>,
+
+    /// Commands and environment variables required before launching
+    #[serde(default, skip_serializing_if = "Prerequisites::is_empty")]
+    pub prerequisites: Prerequisites,
+
+    /// Sandbox the native process runs in (Linux only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
+
+    /// How strictly incoming frames are checked against JSON-RPC 2.0
+    #[serde(default, skip_serializing_if = "ParseMode::is_lenient")]
+    pub parse_mode: ParseMode,
+
+    /// Size limits on messages in each direction
+    #[serde(default, skip_serializing_if = "MessageLimits::is_default")]
+    pub limits: MessageLimits,
+}
+
+fn is_native(runtime: &ProcessRuntime) -> bool {
+    !runtime.is_container()
 }
 
 impl StdioConfig {
@@ -269,6 +441,12 @@ impl StdioConfig {
             working_dir: None,
             timeout: Duration::from_secs(30),
             environment: HashMap::new(),
+            runtime: ProcessRuntime::Native,
+            container: None,
+            prerequisites: Prerequisites::default(),
+            sandbox: None,
+            parse_mode: ParseMode::default(),
+            limits: MessageLimits::default(),
         }
     }
 
@@ -302,8 +480,64 @@ impl StdioConfig {
         self
     }
 
+    /// Run the server in a container using the given runtime.
+    pub fn container(mut self, runtime: ProcessRuntime, container: ContainerConfig) -> Self {
+        self.runtime = runtime;
+        self.container = Some(container);
+        self
+    }
+
+    /// Set the prerequisites checked before launching.
+    pub fn prerequisites(mut self, prerequisites: Prerequisites) -> Self {
+        self.prerequisites = prerequisites;
+        self
+    }
+
+    /// Run the native process inside a sandbox.
+    pub fn sandbox(mut self, sandbox: SandboxConfig) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Set how strictly incoming frames are checked.
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Set the size limits on messages.
+    pub fn limits(mut self, limits: MessageLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Validate the stdio configuration.
     pub fn validate(&self) -> McpResult<()> {
+        self.limits.validate()?;
+
+        if let Some(ref sandbox) = self.sandbox {
+            if self.runtime.is_container() {
+                return Err(ConfigError::InvalidValue {
+                    parameter: "sandbox".to_string(),
+                    value: sandbox.tool.program().to_string(),
+                    reason: "Container runtimes are already isolated; use either a container or a sandbox".to_string(),
+                }
+                .into());
+            }
+            sandbox.validate()?;
+        }
+
+        if self.runtime.is_container() {
+            // An empty command runs the image's default entrypoint
+            return match self.container {
+                Some(ref container) => container.validate(),
+                None => Err(ConfigError::MissingParameter {
+                    parameter: "container".to_string(),
+                }
+                .into()),
+            };
+        }
+
         if self.command.is_empty() {
             return Err(ConfigError::MissingParameter {
                 parameter: "command".to_string(),
@@ -330,6 +564,7 @@ impl StdioConfig {
 ///
 /// This transport uses HTTP requests for client-to-server communication
 /// and Server-Sent Events for server-to-client communication.
+#[cfg(feature = "http")]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HttpSseConfig {
     /// Base URL for the MCP server
@@ -344,8 +579,48 @@ pub struct HttpSseConfig {
 
     /// Authentication configuration
     pub auth: Option<AuthConfig>,
+
+    /// Most requests a pipeline keeps outstanding at once; further requests
+    /// wait for a slot
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+
+    /// Origins besides the server's own that the transport may be redirected
+    /// to, as [`OriginPolicy`](super::origin::OriginPolicy) patterns. Listing
+    /// the server's own origin also lets its name resolve to a loopback
+    /// address, which is otherwise refused as DNS rebinding.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+
+    /// How strictly incoming frames are checked against JSON-RPC 2.0
+    #[serde(default, skip_serializing_if = "ParseMode::is_lenient")]
+    pub parse_mode: ParseMode,
+
+    /// Size limits on messages in each direction
+    #[serde(default, skip_serializing_if = "MessageLimits::is_default")]
+    pub limits: MessageLimits,
+
+    /// Ask for compressed responses and compress requests the server
+    /// accepts compressed, see [`compression`](super::compression)
+    #[serde(default = "default_compression")]
+    pub compression: bool,
+
+    /// HTTP/2 and connection pool settings
+    #[serde(default, skip_serializing_if = "HttpConnectionConfig::is_default")]
+    pub connection: HttpConnectionConfig,
+}
+
+#[cfg(feature = "http")]
+fn default_max_in_flight() -> usize {
+    32
 }
 
+#[cfg(feature = "http")]
+fn default_compression() -> bool {
+    true
+}
+
+#[cfg(feature = "http")]
 impl HttpSseConfig {
     /// Create a new HTTP+SSE configuration.
     pub fn new(base_url: Url) -> Self {
@@ -354,6 +629,12 @@ impl HttpSseConfig {
             timeout: Duration::from_secs(60),
             headers: HashMap::new(),
             auth: None,
+            max_in_flight: default_max_in_flight(),
+            allowed_origins: Vec::new(),
+            parse_mode: ParseMode::default(),
+            limits: MessageLimits::default(),
+            compression: default_compression(),
+            connection: HttpConnectionConfig::default(),
         }
     }
 
@@ -375,6 +656,42 @@ impl HttpSseConfig {
         self
     }
 
+    /// Enable or disable compression.
+    pub fn compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Set the HTTP/2 and connection pool settings.
+    pub fn connection(mut self, connection: HttpConnectionConfig) -> Self {
+        self.connection = connection;
+        self
+    }
+
+    /// Set how many requests may be in flight at once.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight;
+        self
+    }
+
+    /// Allow redirects to origins matching `pattern`.
+    pub fn allow_origin(mut self, pattern: impl Into<String>) -> Self {
+        self.allowed_origins.push(pattern.into());
+        self
+    }
+
+    /// Set how strictly incoming frames are checked.
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Set the size limits on messages.
+    pub fn limits(mut self, limits: MessageLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Validate the HTTP+SSE configuration.
     pub fn validate(&self) -> McpResult<()> {
         if self.base_url.scheme() != "http" && self.base_url.scheme() != "https" {
@@ -386,6 +703,18 @@ impl HttpSseConfig {
             .into());
         }
 
+        self.limits.validate()?;
+        self.connection.validate()?;
+
+        if self.max_in_flight == 0 {
+            return Err(ConfigError::InvalidValue {
+                parameter: "max_in_flight".to_string(),
+                value: "0".to_string(),
+                reason: "At least one request must be allowed in flight".to_string(),
+            }
+            .into());
+        }
+
         if let Some(ref auth) = self.auth {
             auth.validate()?;
         }
@@ -397,6 +726,7 @@ impl HttpSseConfig {
 /// Configuration for HTTP streaming transport.
 ///
 /// This transport uses full-duplex HTTP streaming for bidirectional communication.
+#[cfg(feature = "http")]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HttpStreamConfig {
     /// Base URL for the MCP server
@@ -412,13 +742,27 @@ pub struct HttpStreamConfig {
     /// Authentication configuration
     pub auth: Option<AuthConfig>,
 
-    /// Enable compression for the stream
+    /// Ask for compressed responses and compress requests the server
+    /// accepts compressed, see [`compression`](super::compression)
     pub compression: bool,
 
     /// Flow control window size
     pub flow_control_window: u32,
+
+    /// How strictly incoming frames are checked against JSON-RPC 2.0
+    #[serde(default, skip_serializing_if = "ParseMode::is_lenient")]
+    pub parse_mode: ParseMode,
+
+    /// Size limits on messages in each direction
+    #[serde(default, skip_serializing_if = "MessageLimits::is_default")]
+    pub limits: MessageLimits,
+
+    /// HTTP/2 and connection pool settings
+    #[serde(default, skip_serializing_if = "HttpConnectionConfig::is_default")]
+    pub connection: HttpConnectionConfig,
 }
 
+#[cfg(feature = "http")]
 impl HttpStreamConfig {
     /// Create a new HTTP streaming configuration.
     pub fn new(base_url: Url) -> Self {
@@ -429,6 +773,9 @@ impl HttpStreamConfig {
             auth: None,
             compression: true,
             flow_control_window: 65536,
+            parse_mode: ParseMode::default(),
+            limits: MessageLimits::default(),
+            connection: HttpConnectionConfig::default(),
         }
     }
 
@@ -456,12 +803,30 @@ impl HttpStreamConfig {
         self
     }
 
+    /// Set the HTTP/2 and connection pool settings.
+    pub fn connection(mut self, connection: HttpConnectionConfig) -> Self {
+        self.connection = connection;
+        self
+    }
+
     /// Set the flow control window size.
     pub fn flow_control_window(mut self, size: u32) -> Self {
         self.flow_control_window = size;
         self
     }
 
+    /// Set how strictly incoming frames are checked.
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Set the size limits on messages.
+    pub fn limits(mut self, limits: MessageLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Validate the HTTP streaming configuration.
     pub fn validate(&self) -> McpResult<()> {
         if self.base_url.scheme() != "http" && self.base_url.scheme() != "https" {
@@ -473,6 +838,9 @@ impl HttpStreamConfig {
             .into());
         }
 
+        self.limits.validate()?;
+        self.connection.validate()?;
+
         if self.flow_control_window == 0 {
             return Err(ConfigError::InvalidValue {
                 parameter: "flow_control_window".to_string(),
@@ -490,6 +858,205 @@ impl HttpStreamConfig {
     }
 }
 
+/// HTTP/2 and connection pool settings for the HTTP transports.
+///
+/// Each transport instance sends all its requests through one client, so
+/// connections are reused between calls; these settings control how. The
+/// defaults negotiate HTTP/2 through TLS ALPN and fall back to HTTP/1.1.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConnectionConfig {
+    /// Speak HTTP/2 from the first byte, without negotiating it. Needed for
+    /// cleartext (`http://`) HTTP/2 servers; fails against HTTP/1.1 ones.
+    pub http2_prior_knowledge: bool,
+
+    /// Most requests sent concurrently over the transport's connection
+    pub max_concurrent_streams: Option<usize>,
+
+    /// How often idle connections are pinged to keep them open
+    #[serde(with = "humantime_serde")]
+    pub keepalive_interval: Option<Duration>,
+
+    /// How long an unused connection stays in the pool
+    #[serde(with = "humantime_serde")]
+    pub pool_idle_timeout: Option<Duration>,
+
+    /// Most idle connections kept per host
+    pub pool_max_idle_per_host: Option<usize>,
+}
+
+#[cfg(feature = "http")]
+impl HttpConnectionConfig {
+    /// Whether these are the default settings.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Speak HTTP/2 without negotiating it.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Send at most `streams` requests at once.
+    pub fn max_concurrent_streams(mut self, streams: usize) -> Self {
+        self.max_concurrent_streams = Some(streams);
+        self
+    }
+
+    /// Ping idle connections every `interval`.
+    pub fn keepalive_interval(mut self, interval: Duration) -> Self {
+        self.keepalive_interval = Some(interval);
+        self
+    }
+
+    /// Close pooled connections unused for `timeout`.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Keep at most `max` idle connections per host.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Validate the connection settings.
+    pub fn validate(&self) -> McpResult<()> {
+        let zero = |parameter: &str, reason: &str| -> McpResult<()> {
+            Err(ConfigError::InvalidValue {
+                parameter: parameter.to_string(),
+                value: "0".to_string(),
+                reason: reason.to_string(),
+            }
+            .into())
+        };
+        if self.max_concurrent_streams == Some(0) {
+            return zero(
+                "max_concurrent_streams",
+                "At least one stream must be allowed",
+            );
+        }
+        if self.keepalive_interval == Some(Duration::ZERO) {
+            return zero("keepalive_interval", "Keepalive interval must be positive");
+        }
+        Ok(())
+    }
+
+    /// Apply the settings to a client being built.
+    pub(crate) fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(interval) = self.keepalive_interval {
+            builder = builder
+                .tcp_keepalive(interval)
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_while_idle(true);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        builder
+    }
+}
+
+/// Configuration for an in-memory transport.
+///
+/// There is nothing to connect to: the transport only exists as one end of a
+/// pair created in-process, so this carries just a label for logs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InMemoryConfig {
+    /// Name used to tell the ends of several pairs apart
+    pub name: String,
+}
+
+/// Configuration for the browser transport.
+///
+/// The URL scheme picks how messages travel: `ws://` and `wss://` over a
+/// WebSocket, `http://` and `https://` as fetch requests.
+#[cfg(feature = "wasm")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrowserConfig {
+    /// Server endpoint
+    pub url: String,
+
+    /// Timeout for requests
+    #[serde(with = "humantime_serde")]
+    pub timeout: Duration,
+
+    /// Extra headers for fetch requests. Browsers do not let pages set
+    /// headers on a WebSocket handshake.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// How strictly incoming frames are checked
+    #[serde(default)]
+    pub parse_mode: ParseMode,
+}
+
+#[cfg(feature = "wasm")]
+impl BrowserConfig {
+    /// Create a browser configuration for `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            timeout: Duration::from_secs(60),
+            headers: HashMap::new(),
+            parse_mode: ParseMode::default(),
+        }
+    }
+
+    /// Set the request timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Add a header to fetch requests.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Whether messages go over a WebSocket rather than fetch.
+    pub fn is_websocket(&self) -> bool {
+        matches!(self.scheme(), Some("ws" | "wss"))
+    }
+
+    fn scheme(&self) -> Option<&str> {
+        self.url.split_once("://").map(|(scheme, _)| scheme)
+    }
+
+    /// Validate the browser configuration.
+    pub fn validate(&self) -> McpResult<()> {
+        if !matches!(self.scheme(), Some("ws" | "wss" | "http" | "https")) {
+            return Err(ConfigError::InvalidValue {
+                parameter: "url".to_string(),
+                value: self.url.clone(),
+                reason: "URL must start with ws://, wss://, http:// or https://".to_string(),
+            }
+            .into());
+        }
+
+        if self.timeout.is_zero() {
+            return Err(ConfigError::InvalidValue {
+                parameter: "timeout".to_string(),
+                value: format!("{:?}", self.timeout),
+                reason: "Timeout must be greater than zero".to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
 /// Authentication configuration for HTTP-based transports.
 ///
 /// Supports various authentication schemes including basic auth,
@@ -505,6 +1072,7 @@ pub enum AuthConfig {
     Bearer { token: String },
 
     /// OAuth 2.0 authentication
+    #[cfg(feature = "http")]
     OAuth {
         client_id: String,
         client_secret: String,
@@ -533,6 +1101,7 @@ impl AuthConfig {
     }
 
     /// Create a new OAuth 2.0 authentication configuration.
+    #[cfg(feature = "http")]
     pub fn oauth(
         client_id: impl Into<String>,
         client_secret: impl Into<String>,
@@ -578,6 +1147,7 @@ impl AuthConfig {
                     .into());
                 }
             }
+            #[cfg(feature = "http")]
             Self::OAuth {
                 client_id,
                 client_secret,
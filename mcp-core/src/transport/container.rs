@@ -0,0 +1,357 @@
+//! Container launch support for stdio transports.
+//!
+//! A stdio profile with `runtime = "docker"` or `runtime = "podman"` runs the
+//! server inside a container instead of as a local process. The container is
+//! started with `run --rm -i` so stdin/stdout still carry the MCP traffic,
+//! and with locked-down defaults: a read-only root filesystem, no network and
+//! an image that must be pinned by digest.
+//!
+//! # Examples
+//!
+//! ```toml
+//! type = "stdio"
+//! command = ""
+//! args = []
+//! timeout = "30s"
+//! runtime = "docker"
+//!
+//! [environment]
+//! GITHUB_TOKEN = "..."
+//!
+//! [container]
+//! image = "ghcr.io/github/github-mcp-server@sha256:..."
+//! network = "bridge"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::StdioConfig;
+use crate::error::{ConfigError, McpResult, TransportError};
+
+/// How a stdio server process is launched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessRuntime {
+    /// Run the command directly on the host
+    #[default]
+    Native,
+    /// Run the command inside a Docker container
+    Docker,
+    /// Run the command inside a Podman container
+    Podman,
+}
+
+impl ProcessRuntime {
+    /// Whether this runtime launches a container.
+    pub fn is_container(&self) -> bool {
+        !matches!(self, ProcessRuntime::Native)
+    }
+
+    /// Name of the runtime executable.
+    pub fn program(&self) -> Option<&'static str> {
+        match self {
+            ProcessRuntime::Native => None,
+            ProcessRuntime::Docker => Some("docker"),
+            ProcessRuntime::Podman => Some("podman"),
+        }
+    }
+}
+
+/// Container settings used when [`StdioConfig::runtime`] is a container runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerConfig {
+    /// Image reference, pinned by digest (`name@sha256:...`)
+    pub image: String,
+
+    /// Allow image references that are not pinned by digest
+    #[serde(default)]
+    pub allow_unpinned: bool,
+
+    /// Mount the container root filesystem read-only
+    #[serde(default = "default_read_only")]
+    pub read_only: bool,
+
+    /// Network mode passed to `--network` (`none`, `bridge`, `host` or a named network)
+    #[serde(default = "default_network")]
+    pub network: String,
+
+    /// Bind mounts in `host:container[:ro]` form
+    #[serde(default)]
+    pub volumes: Vec<String>,
+
+    /// User to run as inside the container
+    #[serde(default)]
+    pub user: Option<String>,
+
+    /// Working directory inside the container
+    #[serde(default)]
+    pub workdir: Option<String>,
+
+    /// Extra arguments passed to `run` before the image
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+fn default_read_only() -> bool {
+    true
+}
+
+fn default_network() -> String {
+    "none".to_string()
+}
+
+impl ContainerConfig {
+    /// Create container settings for an image with the default hardening.
+    pub fn new(image: impl Into<String>) -> Self {
+        Self {
+            image: image.into(),
+            allow_unpinned: false,
+            read_only: default_read_only(),
+            network: default_network(),
+            volumes: Vec::new(),
+            user: None,
+            workdir: None,
+            extra_args: Vec::new(),
+        }
+    }
+
+    /// Whether the image reference is pinned by digest.
+    pub fn is_pinned(&self) -> bool {
+        self.image
+            .split_once("@sha256:")
+            .is_some_and(|(_, digest)| {
+                digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())
+            })
+    }
+
+    /// Validate the container settings.
+    pub fn validate(&self) -> McpResult<()> {
+        if self.image.is_empty() {
+            return Err(ConfigError::MissingParameter {
+                parameter: "container.image".to_string(),
+            }
+            .into());
+        }
+
+        if !self.allow_unpinned && !self.is_pinned() {
+            return Err(ConfigError::InvalidValue {
+                parameter: "container.image".to_string(),
+                value: self.image.clone(),
+                reason: "Image must be pinned by digest (name@sha256:<digest>) or set allow_unpinned = true".to_string(),
+            }
+            .into());
+        }
+
+        if self.network.is_empty() {
+            return Err(ConfigError::InvalidValue {
+                parameter: "container.network".to_string(),
+                value: self.network.clone(),
+                reason: "Network mode must not be empty".to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Arguments for `<runtime> run` that start this container.
+    ///
+    /// Environment variables are passed by name only (`-e KEY`) so their
+    /// values are inherited from the runtime process rather than appearing
+    /// on the command line.
+    pub fn run_args(
+        &self,
+        command: &str,
+        args: &[String],
+        environment: &HashMap<String, String>,
+    ) -> Vec<String> {
+        let mut run = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "-i".to_string(),
+            "--network".to_string(),
+            self.network.clone(),
+        ];
+
+        if self.read_only {
+            // Many servers still need somewhere to write scratch files
+            run.extend([
+                "--read-only".to_string(),
+                "--tmpfs".to_string(),
+                "/tmp".to_string(),
+            ]);
+        }
+
+        if let Some(ref user) = self.user {
+            run.extend(["--user".to_string(), user.clone()]);
+        }
+
+        if let Some(ref workdir) = self.workdir {
+            run.extend(["--workdir".to_string(), workdir.clone()]);
+        }
+
+        for volume in &self.volumes {
+            run.extend(["--volume".to_string(), volume.clone()]);
+        }
+
+        let mut keys: Vec<&String> = environment.keys().collect();
+        keys.sort();
+        for key in keys {
+            run.extend(["-e".to_string(), key.clone()]);
+        }
+
+        run.extend(self.extra_args.iter().cloned());
+        run.push(self.image.clone());
+
+        if !command.is_empty() {
+            run.push(command.to_string());
+        }
+        run.extend(args.iter().cloned());
+        run
+    }
+}
+
+impl StdioConfig {
+    /// The program and arguments actually spawned for this configuration.
+    ///
+    /// For the native runtime this is `command` and `args`, wrapped in the
+    /// sandbox invocation if one is configured; for container runtimes it is
+    /// the full `docker run`/`podman run` invocation.
+    pub fn launch_command(&self) -> McpResult<(String, Vec<String>)> {
+        let Some(program) = self.runtime.program() else {
+            return Ok(match self.sandbox {
+                Some(ref sandbox) => {
+                    sandbox.wrap(&self.command, &self.args, self.working_dir.as_deref())
+                }
+                None => (self.command.clone(), self.args.clone()),
+            });
+        };
+
+        let container = self
+            .container
+            .as_ref()
+            .ok_or_else(|| ConfigError::MissingParameter {
+                parameter: "container".to_string(),
+            })?;
+
+        Ok((
+            program.to_string(),
+            container.run_args(&self.command, &self.args, &self.environment),
+        ))
+    }
+}
+
+/// Translate a container runtime exit code into a lifecycle error.
+///
+/// `docker run` and `podman run` reserve 125 for runtime failures (bad
+/// image, daemon unavailable, ...), 126 when the command cannot be invoked
+/// and 127 when it cannot be found in the image.
+pub fn container_exit_error(
+    runtime: ProcessRuntime,
+    exit_code: Option<i32>,
+    stderr_tail: &[String],
+) -> Option<TransportError> {
+    let description = match exit_code? {
+        125 => "container runtime failed to start the container",
+        126 => "command in the container could not be invoked",
+        127 => "command not found in the container image",
+        _ => return None,
+    };
+
+    let mut reason = description.to_string();
+    if !stderr_tail.is_empty() {
+        reason.push_str(": ");
+        reason.push_str(&stderr_tail.join(" | "));
+    }
+
+    Some(TransportError::ContainerError {
+        runtime: runtime.program().unwrap_or("native").to_string(),
+        reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    fn pinned() -> ContainerConfig {
+        ContainerConfig::new(format!("mcp/fetch@sha256:{}", DIGEST))
+    }
+
+    #[test]
+    fn test_pinning_is_enforced() {
+        assert!(pinned().validate().is_ok());
+
+        let mut unpinned = ContainerConfig::new("mcp/fetch:latest");
+        assert!(unpinned.validate().is_err());
+
+        unpinned.allow_unpinned = true;
+        assert!(unpinned.validate().is_ok());
+    }
+
+    #[test]
+    fn test_run_args() {
+        let mut container = pinned();
+        container.volumes.push("/srv/data:/data:ro".to_string());
+
+        let mut environment = HashMap::new();
+        environment.insert("API_TOKEN".to_string(), "secret".to_string());
+
+        let args = container.run_args("mcp-server-fetch", &["--verbose".to_string()], &environment);
+        let expected: Vec<String> = [
+            "run",
+            "--rm",
+            "-i",
+            "--network",
+            "none",
+            "--read-only",
+            "--tmpfs",
+            "/tmp",
+            "--volume",
+            "/srv/data:/data:ro",
+            "-e",
+            "API_TOKEN",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .chain([
+            container.image.clone(),
+            "mcp-server-fetch".to_string(),
+            "--verbose".to_string(),
+        ])
+        .collect();
+
+        assert_eq!(args, expected);
+        assert!(!args.iter().any(|a| a.contains("secret")));
+    }
+
+    #[test]
+    fn test_launch_command() {
+        let mut config = StdioConfig::new("mcp-server-fetch");
+        assert_eq!(config.launch_command().unwrap().0, "mcp-server-fetch");
+
+        config.runtime = ProcessRuntime::Podman;
+        assert!(config.launch_command().is_err());
+
+        config.container = Some(pinned());
+        let (program, args) = config.launch_command().unwrap();
+        assert_eq!(program, "podman");
+        assert_eq!(args.last().unwrap(), "mcp-server-fetch");
+    }
+
+    #[test]
+    fn test_container_exit_error() {
+        let tail = vec!["Error response from daemon: manifest unknown".to_string()];
+        let error = container_exit_error(ProcessRuntime::Docker, Some(125), &tail).unwrap();
+        assert!(
+            matches!(error, TransportError::ContainerError { ref runtime, .. } if runtime == "docker")
+        );
+        assert!(error.to_string().contains("manifest unknown"));
+
+        assert!(container_exit_error(ProcessRuntime::Docker, Some(1), &tail).is_none());
+        assert!(container_exit_error(ProcessRuntime::Docker, None, &tail).is_none());
+    }
+}
@@ -12,6 +12,9 @@ use super::http_sse::HttpSseTransport;
 #[cfg(feature = "http-stream")]
 use super::http_stream::HttpStreamTransport;
 
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use super::browser::BrowserTransport;
+
 /// Factory for creating transport instances.
 ///
 /// This factory provides a unified interface for creating different types of MCP transports
@@ -72,7 +75,7 @@ impl TransportFactory {
             #[cfg(feature = "http-sse")]
             TransportConfig::HttpSse(_) => Ok(Box::new(HttpSseTransport::new(config)?)),
 
-            #[cfg(not(feature = "http-sse"))]
+            #[cfg(all(feature = "http", not(feature = "http-sse")))]
             TransportConfig::HttpSse(_) => Err(crate::error::ConfigError::InvalidValue {
                 parameter: "transport_type".to_string(),
                 value: "http-sse".to_string(),
@@ -98,13 +101,16 @@ impl TransportFactory {
                     }
                 });
 
-                Ok(Box::new(HttpStreamTransport::new(
-                    stream_config.base_url.to_string(),
-                    auth_header,
-                )))
+                Ok(Box::new(
+                    HttpStreamTransport::new(stream_config.base_url.to_string(), auth_header)
+                        .with_parse_mode(stream_config.parse_mode)
+                        .with_limits(stream_config.limits.clone())
+                        .with_compression(stream_config.compression)
+                        .with_connection(stream_config.connection.clone()),
+                ))
             }
 
-            #[cfg(not(feature = "http-stream"))]
+            #[cfg(all(feature = "http", not(feature = "http-stream")))]
             TransportConfig::HttpStream(_) => Err(crate::error::ConfigError::InvalidValue {
                 parameter: "transport_type".to_string(),
                 value: "http-stream".to_string(),
@@ -113,6 +119,24 @@ impl TransportFactory {
                         .to_string(),
             }
             .into()),
+
+            TransportConfig::InMemory(_) => Err(crate::error::ConfigError::InvalidValue {
+                parameter: "transport_type".to_string(),
+                value: "in-memory".to_string(),
+                reason: "in-memory transports are created with InMemoryTransport::pair".to_string(),
+            }
+            .into()),
+
+            #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+            TransportConfig::Browser(_) => Ok(Box::new(BrowserTransport::new(config))),
+
+            #[cfg(all(feature = "wasm", not(target_arch = "wasm32")))]
+            TransportConfig::Browser(_) => Err(crate::error::ConfigError::InvalidValue {
+                parameter: "transport_type".to_string(),
+                value: "browser".to_string(),
+                reason: "browser transport only runs in wasm32 builds".to_string(),
+            }
+            .into()),
         }
     }
 
@@ -131,6 +155,8 @@ impl TransportFactory {
             "http-sse",
             #[cfg(feature = "http-stream")]
             "http-stream",
+            #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+            "browser",
         ]
     }
 
@@ -170,7 +196,7 @@ impl TransportFactory {
                             retry_delay,
                             last_error.as_ref().unwrap()
                         );
-                        tokio::time::sleep(retry_delay).await;
+                        crate::rt::sleep(retry_delay).await;
                     }
                 }
             }
@@ -184,6 +210,7 @@ impl TransportFactory {
 ///
 /// This is a basic implementation for HTTP Basic Auth. For production systems
 /// requiring advanced base64 features, consider using a dedicated crate.
+#[cfg(feature = "http-stream")]
 fn base64_encode(input: &[u8]) -> String {
     const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
@@ -258,6 +285,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg(feature = "http")]
     async fn test_create_http_sse_transport() {
         let config = TransportConfig::http_sse("https://example.com/mcp").unwrap();
         let result = TransportFactory::create(config).await;
@@ -283,6 +311,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "http-stream")]
     fn test_base64_encode() {
         assert_eq!(base64_encode(b"hello"), "aGVsbG8=");
         assert_eq!(base64_encode(b"hello world"), "aGVsbG8gd29ybGQ=");
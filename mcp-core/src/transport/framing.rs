@@ -0,0 +1,518 @@
+//! Parsing of incoming JSON-RPC frames, leniently or strictly.
+//!
+//! By default transports accept whatever decodes into a
+//! [`JsonRpcMessage`]: a response carrying both `result` and `error`, a
+//! fractional `id` or a misspelled `jsonrpc` version go unnoticed, and a
+//! frame that does not decode at all is logged and dropped. For a debugging
+//! tool that hides exactly the bugs it is meant to show, so a transport can
+//! be switched to [`ParseMode::Strict`]. It then checks every frame against
+//! the JSON-RPC 2.0 rules before decoding it and drops frames that break
+//! them.
+//!
+//! In either mode, what was wrong with a frame is published as a
+//! [`ProtocolDiagnostic`] on a broadcast stream (see
+//! [`Transport::protocol_diagnostics`](super::Transport::protocol_diagnostics)),
+//! so a UI can show it next to the traffic instead of it disappearing into
+//! the log. Unknown top-level members are only warnings: the frame is still
+//! delivered.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::transport::framing::{FrameParser, FrameRule, ParseMode};
+//!
+//! let parser = FrameParser::new("stdio", ParseMode::Strict);
+//! let mut diagnostics = parser.subscribe();
+//!
+//! let frame = r#"{"jsonrpc":"2.0","id":1,"result":{},"error":{"code":1,"message":"x"}}"#;
+//! assert!(parser.decode(frame).is_err());
+//!
+//! let diagnostic = diagnostics.try_recv().unwrap();
+//! assert_eq!(diagnostic.rule, FrameRule::ResultAndError);
+//! assert!(diagnostic.dropped);
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::messages::JsonRpcMessage;
+
+/// Number of diagnostics buffered for slow subscribers before they lag
+const DIAGNOSTIC_CHANNEL_CAPACITY: usize = 256;
+
+/// Characters of the offending frame kept in a diagnostic
+const FRAME_EXCERPT_LEN: usize = 512;
+
+/// Members a JSON-RPC 2.0 message may have
+const KNOWN_MEMBERS: &[&str] = &["jsonrpc", "id", "method", "params", "result", "error"];
+
+/// How strictly a transport parses the frames it receives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParseMode {
+    /// Accept anything that decodes into a message
+    #[default]
+    Lenient,
+    /// Check frames against JSON-RPC 2.0 and drop those that break it
+    Strict,
+}
+
+impl ParseMode {
+    /// Whether this is the default, lenient mode.
+    pub fn is_lenient(&self) -> bool {
+        *self == Self::Lenient
+    }
+}
+
+/// JSON-RPC 2.0 rule a frame broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameRule {
+    /// The frame is not valid JSON
+    InvalidJson,
+    /// The frame is valid JSON but not a JSON-RPC object
+    NotAnObject,
+    /// The frame is a batch, which this client does not accept
+    Batch,
+    /// `jsonrpc` is missing or not `"2.0"`
+    Version,
+    /// `id` is not a string or an integer
+    IdType,
+    /// A request or notification whose `method` is not a string
+    Method,
+    /// `params` is neither an object nor an array
+    ParamsType,
+    /// A response with both `result` and `error`
+    ResultAndError,
+    /// A response with neither `result` nor `error`
+    NoResultOrError,
+    /// A response without an `id`
+    MissingId,
+    /// `error` lacks an integer `code` or a string `message`
+    ErrorObject,
+    /// A member JSON-RPC 2.0 does not define
+    UnknownField,
+    /// The frame follows the rules but does not decode into a message
+    Undecodable,
+    /// The frame is larger than the transport allows
+    TooLarge,
+}
+
+impl fmt::Display for FrameRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::InvalidJson => "invalid-json",
+            Self::NotAnObject => "not-an-object",
+            Self::Batch => "batch",
+            Self::Version => "version",
+            Self::IdType => "id-type",
+            Self::Method => "method",
+            Self::ParamsType => "params-type",
+            Self::ResultAndError => "result-and-error",
+            Self::NoResultOrError => "no-result-or-error",
+            Self::MissingId => "missing-id",
+            Self::ErrorObject => "error-object",
+            Self::UnknownField => "unknown-field",
+            Self::Undecodable => "undecodable",
+            Self::TooLarge => "too-large",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    /// The frame breaks JSON-RPC 2.0
+    Error,
+    /// The frame is unusual but was delivered
+    Warning,
+}
+
+/// One problem found in one rule check of a frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameIssue {
+    /// Rule the frame broke
+    pub rule: FrameRule,
+    /// How serious it is
+    pub severity: DiagnosticSeverity,
+    /// What exactly was wrong
+    pub message: String,
+}
+
+impl FrameIssue {
+    fn error(rule: FrameRule, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            severity: DiagnosticSeverity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(rule: FrameRule, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            severity: DiagnosticSeverity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// A problem with a frame a transport received.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolDiagnostic {
+    /// Transport that received the frame, e.g. `stdio`
+    pub transport: String,
+    /// Rule the frame broke
+    pub rule: FrameRule,
+    /// How serious it is
+    pub severity: DiagnosticSeverity,
+    /// What exactly was wrong
+    pub message: String,
+    /// The frame, cut short if long
+    pub frame: String,
+    /// Whether the frame was dropped rather than delivered
+    pub dropped: bool,
+    /// When the frame was received
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl fmt::Display for ProtocolDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.transport, self.rule, self.message)?;
+        if self.dropped {
+            f.write_str(" (frame dropped)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Check one decoded frame against the JSON-RPC 2.0 rules.
+pub fn check_frame(frame: &Value) -> Vec<FrameIssue> {
+    let object = match frame {
+        Value::Object(object) => object,
+        Value::Array(_) => {
+            return vec![FrameIssue::error(
+                FrameRule::Batch,
+                "batched messages are not supported",
+            )]
+        }
+        other => {
+            return vec![FrameIssue::error(
+                FrameRule::NotAnObject,
+                format!("expected an object, got {}", kind(other)),
+            )]
+        }
+    };
+
+    let mut issues = Vec::new();
+    match object.get("jsonrpc") {
+        Some(Value::String(version)) if version == "2.0" => {}
+        Some(other) => issues.push(FrameIssue::error(
+            FrameRule::Version,
+            format!("\"jsonrpc\" must be \"2.0\", got {}", other),
+        )),
+        None => issues.push(FrameIssue::error(
+            FrameRule::Version,
+            "\"jsonrpc\" is missing",
+        )),
+    }
+
+    match object.get("id") {
+        None | Some(Value::String(_)) => {}
+        Some(Value::Number(number)) if number.is_i64() || number.is_u64() => {}
+        Some(other) => issues.push(FrameIssue::error(
+            FrameRule::IdType,
+            format!("\"id\" must be a string or an integer, got {}", other),
+        )),
+    }
+
+    let has_result = object.contains_key("result");
+    let has_error = object.contains_key("error");
+    if let Some(method) = object.get("method") {
+        if !method.is_string() {
+            issues.push(FrameIssue::error(
+                FrameRule::Method,
+                format!("\"method\" must be a string, got {}", kind(method)),
+            ));
+        }
+        if let Some(params) = object.get("params") {
+            if !params.is_object() && !params.is_array() {
+                issues.push(FrameIssue::error(
+                    FrameRule::ParamsType,
+                    format!(
+                        "\"params\" must be an object or an array, got {}",
+                        kind(params)
+                    ),
+                ));
+            }
+        }
+        if has_result || has_error {
+            issues.push(FrameIssue::error(
+                FrameRule::ResultAndError,
+                "a request cannot carry \"result\" or \"error\"",
+            ));
+        }
+    } else {
+        if !object.contains_key("id") {
+            issues.push(FrameIssue::error(
+                FrameRule::MissingId,
+                "a response must have an \"id\"",
+            ));
+        }
+        match (has_result, has_error) {
+            (true, true) => issues.push(FrameIssue::error(
+                FrameRule::ResultAndError,
+                "\"result\" and \"error\" are mutually exclusive",
+            )),
+            (false, false) => issues.push(FrameIssue::error(
+                FrameRule::NoResultOrError,
+                "a response needs \"result\" or \"error\"",
+            )),
+            _ => {}
+        }
+        if let Some(error) = object.get("error") {
+            let code = error.get("code").is_some_and(|code| code.is_i64());
+            let message = error.get("message").is_some_and(Value::is_string);
+            if !code || !message {
+                issues.push(FrameIssue::error(
+                    FrameRule::ErrorObject,
+                    "\"error\" needs an integer \"code\" and a string \"message\"",
+                ));
+            }
+        }
+    }
+
+    for member in object.keys() {
+        if !KNOWN_MEMBERS.contains(&member.as_str()) {
+            issues.push(FrameIssue::warning(
+                FrameRule::UnknownField,
+                format!("unknown member \"{}\"", member),
+            ));
+        }
+    }
+    issues
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Decodes incoming frames and publishes what was wrong with them.
+///
+/// Clones share one diagnostic stream, so a transport can hand a clone to
+/// each of its reader tasks.
+#[derive(Debug, Clone)]
+pub struct FrameParser {
+    transport: String,
+    mode: ParseMode,
+    sender: broadcast::Sender<ProtocolDiagnostic>,
+}
+
+impl FrameParser {
+    /// Create a parser for frames received by `transport`.
+    pub fn new(transport: impl Into<String>, mode: ParseMode) -> Self {
+        let (sender, _) = broadcast::channel(DIAGNOSTIC_CHANNEL_CAPACITY);
+        Self {
+            transport: transport.into(),
+            mode,
+            sender,
+        }
+    }
+
+    /// How strictly frames are parsed.
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    /// Subscribe to diagnostics for frames received from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ProtocolDiagnostic> {
+        self.sender.subscribe()
+    }
+
+    /// Decode a frame into a message.
+    ///
+    /// Fails with the diagnostic explaining why the frame was dropped; it has
+    /// already been published.
+    pub fn decode(&self, frame: &str) -> Result<JsonRpcMessage, ProtocolDiagnostic> {
+        if self.mode.is_lenient() {
+            return crate::decode::from_str(frame).map_err(|e| {
+                tracing::warn!(
+                    "Failed to parse JSON-RPC message from {}: {}",
+                    self.transport,
+                    e
+                );
+                self.drop_frame(frame, FrameRule::Undecodable, e.to_string())
+            });
+        }
+
+        let value: Value = serde_json::from_str(frame)
+            .map_err(|e| self.drop_frame(frame, FrameRule::InvalidJson, e.to_string()))?;
+        self.check(&value, frame)?;
+        crate::decode::from_value(value)
+            .map_err(|e| self.drop_frame(frame, FrameRule::Undecodable, e.to_string()))
+    }
+
+    /// Check an already parsed frame, for transports that decode it
+    /// themselves. Always passes in lenient mode.
+    pub fn check(&self, value: &Value, frame: &str) -> Result<(), ProtocolDiagnostic> {
+        if self.mode.is_lenient() {
+            return Ok(());
+        }
+        let issues = check_frame(value);
+        let dropped = issues
+            .iter()
+            .any(|issue| issue.severity == DiagnosticSeverity::Error);
+        let mut first_error = None;
+        for issue in issues {
+            let diagnostic = self.diagnostic(frame, issue, dropped);
+            if first_error.is_none() && diagnostic.severity == DiagnosticSeverity::Error {
+                first_error = Some(diagnostic.clone());
+            }
+            self.publish(diagnostic);
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Report a frame over the transport's size limit, which was dropped or,
+    /// if not, truncated. Reported in either mode.
+    pub fn oversized(&self, head: &str, reason: impl Into<String>, dropped: bool) {
+        let issue = if dropped {
+            FrameIssue::error(FrameRule::TooLarge, reason)
+        } else {
+            FrameIssue::warning(FrameRule::TooLarge, reason)
+        };
+        let diagnostic = self.diagnostic(head, issue, dropped);
+        self.publish(diagnostic);
+    }
+
+    fn drop_frame(&self, frame: &str, rule: FrameRule, message: String) -> ProtocolDiagnostic {
+        let diagnostic = self.diagnostic(frame, FrameIssue::error(rule, message), true);
+        self.publish(diagnostic.clone());
+        diagnostic
+    }
+
+    fn diagnostic(&self, frame: &str, issue: FrameIssue, dropped: bool) -> ProtocolDiagnostic {
+        let mut excerpt: String = frame.chars().take(FRAME_EXCERPT_LEN).collect();
+        if excerpt.len() < frame.len() {
+            excerpt.push('…');
+        }
+        ProtocolDiagnostic {
+            transport: self.transport.clone(),
+            rule: issue.rule,
+            severity: issue.severity,
+            message: issue.message,
+            frame: excerpt,
+            dropped,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn publish(&self, diagnostic: ProtocolDiagnostic) {
+        tracing::debug!("Protocol diagnostic: {}", diagnostic);
+        // Nobody listening is fine
+        let _ = self.sender.send(diagnostic);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(frame: Value) -> Vec<FrameRule> {
+        check_frame(&frame)
+            .into_iter()
+            .map(|issue| issue.rule)
+            .collect()
+    }
+
+    #[test]
+    fn test_check_frame_rules() {
+        assert!(rules(json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" })).is_empty());
+        assert!(rules(json!({ "jsonrpc": "2.0", "id": "a", "result": {} })).is_empty());
+        assert!(rules(json!({ "jsonrpc": "2.0", "method": "log", "params": [1] })).is_empty());
+        assert!(rules(json!({
+            "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "nope" }
+        }))
+        .is_empty());
+
+        assert_eq!(rules(json!([])), [FrameRule::Batch]);
+        assert_eq!(rules(json!("hi")), [FrameRule::NotAnObject]);
+        assert_eq!(
+            rules(json!({ "jsonrpc": "1.0", "id": 1.5, "result": 1 })),
+            [FrameRule::Version, FrameRule::IdType]
+        );
+        assert_eq!(
+            rules(json!({ "jsonrpc": "2.0", "id": null, "result": 1 })),
+            [FrameRule::IdType]
+        );
+        assert_eq!(
+            rules(json!({ "jsonrpc": "2.0", "method": 3, "params": "x" })),
+            [FrameRule::Method, FrameRule::ParamsType]
+        );
+        assert_eq!(
+            rules(json!({ "jsonrpc": "2.0", "result": 1, "error": { "code": 1 } })),
+            [
+                FrameRule::MissingId,
+                FrameRule::ResultAndError,
+                FrameRule::ErrorObject
+            ]
+        );
+        assert_eq!(
+            rules(json!({ "jsonrpc": "2.0", "id": 1 })),
+            [FrameRule::NoResultOrError]
+        );
+        assert_eq!(
+            rules(json!({ "jsonrpc": "2.0", "id": 1, "result": {}, "trace": "x" })),
+            [FrameRule::UnknownField]
+        );
+    }
+
+    #[test]
+    fn test_parser_modes() {
+        let lax = r#"{"jsonrpc":"2.0","id":1,"result":{},"error":{"code":1,"message":"x"}}"#;
+
+        let lenient = FrameParser::new("stdio", ParseMode::Lenient);
+        let mut lenient_diagnostics = lenient.subscribe();
+        assert!(lenient.decode(lax).is_ok());
+        assert!(lenient_diagnostics.try_recv().is_err());
+        let dropped = lenient.decode("not json").unwrap_err();
+        assert_eq!(dropped.rule, FrameRule::Undecodable);
+        assert_eq!(lenient_diagnostics.try_recv().unwrap(), dropped);
+
+        let strict = FrameParser::new("stdio", ParseMode::Strict);
+        let mut diagnostics = strict.subscribe();
+        assert_eq!(
+            strict.decode(lax).unwrap_err().rule,
+            FrameRule::ResultAndError
+        );
+        assert!(diagnostics.try_recv().unwrap().dropped);
+        assert_eq!(strict.decode("{").unwrap_err().rule, FrameRule::InvalidJson);
+        diagnostics.try_recv().unwrap();
+
+        // Warnings are reported but the message is still delivered
+        let message = strict
+            .decode(r#"{"jsonrpc":"2.0","method":"ping","extra":true}"#)
+            .unwrap();
+        assert_eq!(message.method(), Some("ping"));
+        let warning = diagnostics.try_recv().unwrap();
+        assert_eq!(warning.severity, DiagnosticSeverity::Warning);
+        assert!(!warning.dropped);
+        assert_eq!(
+            warning.to_string(),
+            "[stdio] unknown-field: unknown member \"extra\""
+        );
+    }
+}
@@ -0,0 +1,532 @@
+//! Hedged requests across replicated upstreams.
+//!
+//! [`HedgedTransport`] sends each request to a primary transport and, if no
+//! answer arrives within [`HedgeConfig::delay`], sends the same request to a
+//! secondary replica. Whichever succeeds first is returned; the other request
+//! is abandoned and the server that was still working on it receives
+//! `notifications/cancelled`.
+//!
+//! Only methods listed in [`IDEMPOTENT_METHODS`] can be hedged, since a
+//! hedged request may run on both servers. Everything else, including
+//! `tools/call`, goes to the primary alone.
+//!
+//! Only the primary's notifications and requests reach the client. Those of
+//! the secondary are drained in the background so they do not pile up: its
+//! notifications are dropped and its requests, such as `roots/list`, are
+//! answered with an error.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use super::{CircuitEvent, ProtocolDiagnostic, Transport, TransportConfig, TransportInfo};
+use crate::error::{ConfigError, McpResult};
+use crate::messages::{
+    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestId,
+};
+use crate::rt;
+
+/// Read-only methods that are safe to run on more than one server
+pub const IDEMPOTENT_METHODS: &[&str] = &[
+    "ping",
+    "tools/list",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "prompts/list",
+    "prompts/get",
+    "completion/complete",
+];
+
+/// When and for which methods to hedge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HedgeConfig {
+    /// How long to wait for the primary before also asking the secondary
+    #[serde(with = "humantime_serde")]
+    pub delay: Duration,
+
+    /// Methods to hedge; each must be in [`IDEMPOTENT_METHODS`]
+    pub methods: Vec<String>,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(100))
+    }
+}
+
+impl HedgeConfig {
+    /// Hedge every idempotent method after `delay`
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            methods: IDEMPOTENT_METHODS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    /// Restrict hedging to these methods
+    pub fn methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.methods = methods.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Reject methods that could have side effects when run twice
+    pub fn validate(&self) -> McpResult<()> {
+        if let Some(method) = self
+            .methods
+            .iter()
+            .find(|method| !IDEMPOTENT_METHODS.contains(&method.as_str()))
+        {
+            return Err(ConfigError::InvalidValue {
+                parameter: "hedge.methods".to_string(),
+                value: method.clone(),
+                reason: "only idempotent methods can be hedged".to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn hedges(&self, method: &str) -> bool {
+        self.methods.iter().any(|m| m == method)
+    }
+}
+
+/// Counters describing how hedged requests were resolved
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HedgeStats {
+    /// Requests for hedgeable methods
+    pub eligible: u64,
+    /// Requests that were also sent to the secondary
+    pub hedged: u64,
+    /// Requests answered by the primary
+    pub primary_wins: u64,
+    /// Requests answered by the secondary
+    pub secondary_wins: u64,
+    /// Losing requests cancelled after the other server answered
+    pub cancelled: u64,
+    /// Requests that failed on both servers
+    pub both_failed: u64,
+}
+
+/// Transport that hedges read-only requests across two replicas.
+///
+/// Connecting and initialization reach both servers so either can answer.
+/// Server-initiated messages are read from the primary only; the
+/// secondary's are drained. A secondary that fails to connect disables
+/// hedging rather than failing the transport.
+pub struct HedgedTransport {
+    primary: Arc<dyn Transport>,
+    secondary: Arc<dyn Transport>,
+    config: HedgeConfig,
+    stats: Mutex<HedgeStats>,
+    drain: Mutex<Option<rt::Task>>,
+}
+
+/// Which replica answered or should be told to stop
+#[derive(Clone, Copy)]
+enum Replica {
+    Primary,
+    Secondary,
+}
+
+impl HedgedTransport {
+    /// Hedge between `primary` and `secondary` as `config` allows
+    pub fn new(
+        primary: Arc<dyn Transport>,
+        secondary: Arc<dyn Transport>,
+        config: HedgeConfig,
+    ) -> McpResult<Self> {
+        config.validate()?;
+        Ok(Self {
+            primary,
+            secondary,
+            config,
+            stats: Mutex::new(HedgeStats::default()),
+            drain: Mutex::new(None),
+        })
+    }
+
+    /// Snapshot of the hedging counters
+    pub fn stats(&self) -> HedgeStats {
+        self.stats_mut().clone()
+    }
+
+    fn stats_mut(&self) -> MutexGuard<'_, HedgeStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn drain_mut(&self) -> MutexGuard<'_, Option<rt::Task>> {
+        self.drain.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Read the secondary's server-initiated messages until it disconnects
+    fn spawn_drain(&self) {
+        let secondary = Arc::clone(&self.secondary);
+        let task = rt::spawn(async move {
+            while let Ok(message) = secondary.receive_message(None).await {
+                let JsonRpcMessage::Request(request) = message else {
+                    continue;
+                };
+                let response = JsonRpcResponse::error(
+                    request.id,
+                    JsonRpcError::internal_error(format!(
+                        "{} is not handled for a hedging secondary replica",
+                        request.method
+                    )),
+                );
+                if let Err(e) = secondary.send_response(response).await {
+                    tracing::debug!("Failed to answer secondary replica request: {}", e);
+                }
+            }
+        });
+        if let Some(previous) = self.drain_mut().replace(task) {
+            previous.abort();
+        }
+    }
+
+    fn replica(&self, replica: Replica) -> &Arc<dyn Transport> {
+        match replica {
+            Replica::Primary => &self.primary,
+            Replica::Secondary => &self.secondary,
+        }
+    }
+
+    fn record_win(&self, winner: Replica) {
+        let mut stats = self.stats_mut();
+        match winner {
+            Replica::Primary => stats.primary_wins += 1,
+            Replica::Secondary => stats.secondary_wins += 1,
+        }
+    }
+
+    /// Tell the losing server to stop working on `id`
+    async fn cancel(&self, loser: Replica, id: &RequestId) {
+        self.stats_mut().cancelled += 1;
+        let notification = JsonRpcNotification::new(
+            "notifications/cancelled",
+            json!({ "requestId": id, "reason": "Answered by another replica" }),
+        );
+        if let Err(e) = self.replica(loser).send_notification(notification).await {
+            tracing::debug!("Failed to cancel hedged request {}: {}", id, e);
+        }
+    }
+
+    async fn send_hedged(
+        &self,
+        request: JsonRpcRequest,
+        timeout: Option<Duration>,
+    ) -> McpResult<JsonRpcResponse> {
+        self.stats_mut().eligible += 1;
+
+        let primary = self.primary.send_request(request.clone(), timeout);
+        tokio::pin!(primary);
+
+        // Give the primary a head start before involving the secondary
+        let head_start = tokio::select! {
+            result = &mut primary => Some(result),
+            _ = rt::sleep(self.config.delay) => None,
+        };
+        let early_failure = match head_start {
+            Some(Ok(response)) => {
+                self.record_win(Replica::Primary);
+                return Ok(response);
+            }
+            Some(Err(e)) => e,
+            None => {
+                self.stats_mut().hedged += 1;
+                let secondary = self.secondary.send_request(request.clone(), timeout);
+                return self.race(primary, secondary, &request.id).await;
+            }
+        };
+
+        // The primary failed before the delay; the secondary is the only chance
+        tracing::debug!(
+            "Primary failed for {}, asking secondary: {}",
+            request.method,
+            early_failure
+        );
+        self.stats_mut().hedged += 1;
+        match self.secondary.send_request(request, timeout).await {
+            Ok(response) => {
+                self.record_win(Replica::Secondary);
+                Ok(response)
+            }
+            Err(_) => {
+                self.stats_mut().both_failed += 1;
+                Err(early_failure)
+            }
+        }
+    }
+
+    /// Return the first success, cancelling the request still outstanding
+    async fn race<P, S>(
+        &self,
+        primary: P,
+        secondary: S,
+        id: &RequestId,
+    ) -> McpResult<JsonRpcResponse>
+    where
+        P: std::future::Future<Output = McpResult<JsonRpcResponse>>,
+        S: std::future::Future<Output = McpResult<JsonRpcResponse>>,
+    {
+        tokio::pin!(primary);
+        tokio::pin!(secondary);
+
+        let (first, winner, remaining) = tokio::select! {
+            result = &mut primary => (result, Replica::Primary, Replica::Secondary),
+            result = &mut secondary => (result, Replica::Secondary, Replica::Primary),
+        };
+
+        let primary_error = match first {
+            Ok(response) => {
+                self.record_win(winner);
+                self.cancel(remaining, id).await;
+                return Ok(response);
+            }
+            Err(e) => e,
+        };
+
+        let second = match remaining {
+            Replica::Primary => primary.await,
+            Replica::Secondary => secondary.await,
+        };
+        match second {
+            Ok(response) => {
+                self.record_win(remaining);
+                Ok(response)
+            }
+            Err(second_error) => {
+                self.stats_mut().both_failed += 1;
+                // Prefer the primary's error, which is what an unhedged call would report
+                Err(match winner {
+                    Replica::Primary => primary_error,
+                    Replica::Secondary => second_error,
+                })
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for HedgedTransport {
+    async fn connect(&self) -> McpResult<()> {
+        self.primary.connect().await?;
+        match self.secondary.connect().await {
+            Ok(()) => self.spawn_drain(),
+            Err(e) => tracing::warn!("Secondary replica unavailable, hedging disabled: {}", e),
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> McpResult<()> {
+        if let Some(drain) = self.drain_mut().take() {
+            drain.abort();
+        }
+        if self.secondary.is_connected() {
+            if let Err(e) = self.secondary.disconnect().await {
+                tracing::warn!("Failed to disconnect secondary replica: {}", e);
+            }
+        }
+        self.primary.disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.primary.is_connected()
+    }
+
+    async fn send_request(
+        &self,
+        request: JsonRpcRequest,
+        timeout: Option<Duration>,
+    ) -> McpResult<JsonRpcResponse> {
+        if !self.secondary.is_connected() {
+            return self.primary.send_request(request, timeout).await;
+        }
+
+        // Both servers need a session before either can answer hedged calls
+        if request.method == "initialize" {
+            let (primary, secondary) = tokio::join!(
+                self.primary.send_request(request.clone(), timeout),
+                self.secondary.send_request(request, timeout),
+            );
+            if let Err(e) = secondary {
+                tracing::warn!("Secondary replica failed to initialize: {}", e);
+            }
+            return primary;
+        }
+
+        if self.config.hedges(&request.method) {
+            self.send_hedged(request, timeout).await
+        } else {
+            self.primary.send_request(request, timeout).await
+        }
+    }
+
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
+        if self.secondary.is_connected() {
+            if let Err(e) = self.secondary.send_notification(notification.clone()).await {
+                tracing::debug!("Secondary replica rejected notification: {}", e);
+            }
+        }
+        self.primary.send_notification(notification).await
+    }
+
+    async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        // Server requests are only received from the primary
+        self.primary.send_response(response).await
+    }
+
+    async fn receive_message(&self, timeout: Option<Duration>) -> McpResult<JsonRpcMessage> {
+        self.primary.receive_message(timeout).await
+    }
+
+    fn get_info(&self) -> TransportInfo {
+        let mut info = self.primary.get_info();
+        info.add_metadata("hedging", json!(self.stats()));
+        info.add_metadata("hedge_delay_ms", json!(self.config.delay.as_millis()));
+        info.add_metadata(
+            "secondary",
+            json!({
+                "transport_type": self.secondary.get_info().transport_type,
+                "connected": self.secondary.is_connected(),
+            }),
+        );
+        info
+    }
+
+    fn get_config(&self) -> &TransportConfig {
+        self.primary.get_config()
+    }
+
+    fn protocol_diagnostics(&self) -> Option<broadcast::Receiver<ProtocolDiagnostic>> {
+        self.primary.protocol_diagnostics()
+    }
+
+    fn circuit_events(&self) -> Option<broadcast::Receiver<CircuitEvent>> {
+        self.primary.circuit_events()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::InMemoryTransport;
+
+    /// Answer every request after `delay`, tagging the result with `name`
+    fn replica(name: &'static str, delay: Duration) -> Arc<dyn Transport> {
+        let (client, server) = InMemoryTransport::pair();
+        tokio::spawn(async move {
+            server.connect().await.unwrap();
+            let server = Arc::new(server);
+            while let Ok(message) = server.receive_message(None).await {
+                let JsonRpcMessage::Request(request) = message else {
+                    continue;
+                };
+                let server = Arc::clone(&server);
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let response = JsonRpcResponse::success(request.id, json!({ "from": name }));
+                    server.send_message(JsonRpcMessage::Response(response)).ok();
+                });
+            }
+        });
+        Arc::new(client)
+    }
+
+    fn answered_by(response: &JsonRpcResponse) -> &str {
+        response.result.as_ref().unwrap()["from"].as_str().unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_primary_is_hedged_and_side_effects_are_not() {
+        let transport = HedgedTransport::new(
+            replica("primary", Duration::from_millis(300)),
+            replica("secondary", Duration::from_millis(10)),
+            HedgeConfig::new(Duration::from_millis(20)),
+        )
+        .unwrap();
+        transport.connect().await.unwrap();
+
+        let list = JsonRpcRequest::new("1", "tools/list", json!({}));
+        let response = transport.send_request(list, None).await.unwrap();
+        assert_eq!(answered_by(&response), "secondary");
+
+        let call = JsonRpcRequest::new("2", "tools/call", json!({ "name": "write" }));
+        let response = transport.send_request(call, None).await.unwrap();
+        assert_eq!(answered_by(&response), "primary");
+
+        let stats = transport.stats();
+        assert_eq!(stats.eligible, 1);
+        assert_eq!(stats.hedged, 1);
+        assert_eq!(stats.secondary_wins, 1);
+        assert_eq!(stats.cancelled, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fast_primary_is_not_hedged() {
+        let transport = HedgedTransport::new(
+            replica("primary", Duration::ZERO),
+            replica("secondary", Duration::ZERO),
+            HedgeConfig::new(Duration::from_millis(200)),
+        )
+        .unwrap();
+        transport.connect().await.unwrap();
+
+        let read = JsonRpcRequest::new("1", "resources/read", json!({ "uri": "file:///a" }));
+        let response = transport.send_request(read, None).await.unwrap();
+        assert_eq!(answered_by(&response), "primary");
+        assert_eq!(transport.stats().hedged, 0);
+    }
+
+    #[tokio::test]
+    async fn test_secondary_server_messages_are_drained() {
+        let (secondary, server) = InMemoryTransport::pair();
+        let transport = HedgedTransport::new(
+            replica("primary", Duration::ZERO),
+            Arc::new(secondary),
+            HedgeConfig::default(),
+        )
+        .unwrap();
+        transport.connect().await.unwrap();
+        server.connect().await.unwrap();
+
+        let notification = JsonRpcNotification::new("notifications/message", json!({}));
+        server
+            .send_message(JsonRpcMessage::Notification(notification))
+            .unwrap();
+        let roots = JsonRpcRequest::new("roots-1", "roots/list", json!({}));
+        server.send_message(JsonRpcMessage::Request(roots)).unwrap();
+
+        let answer = server
+            .receive_message(Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        let JsonRpcMessage::Response(response) = answer else {
+            panic!("expected a response, got {:?}", answer);
+        };
+        assert_eq!(response.id, RequestId::from("roots-1"));
+        assert!(response.is_error());
+
+        // Neither message reaches the client through the primary
+        let received = transport
+            .receive_message(Some(Duration::from_millis(50)))
+            .await;
+        assert!(received.is_err());
+    }
+
+    #[test]
+    fn test_non_idempotent_methods_are_rejected() {
+        let config = HedgeConfig::default().methods(["tools/list", "tools/call"]);
+        assert!(config.validate().is_err());
+        assert!(HedgeConfig::default().validate().is_ok());
+    }
+}
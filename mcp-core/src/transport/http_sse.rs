@@ -6,22 +6,33 @@
 //! - Support for single JSON responses and SSE streams
 //! - Automatic session extraction and inclusion
 //! - Resumable connections with Last-Event-ID support
+//! - Compressed request and response bodies, including SSE streams
 //! - Security validations and localhost binding
 
-use std::sync::{Arc, Mutex};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use eventsource_stream::Eventsource;
+use futures::stream::BoxStream;
 use futures::StreamExt;
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
-use reqwest::{Client, Response, Url};
-use tokio::sync::mpsc;
+use reqwest::{Client, Response, StatusCode, Url};
+use tokio::sync::{broadcast, mpsc, Notify, Semaphore};
 use tokio::time::timeout;
 
-use super::{Transport, TransportConfig, TransportInfo};
+use super::compression::{Compression, CompressionStats};
+use super::framing::{FrameParser, ProtocolDiagnostic};
+use super::limits::{self, LimitedFrame, MessageLimits};
+use super::origin::{self, OriginPolicy};
+use super::{HttpSseConfig, Transport, TransportConfig, TransportInfo};
+use crate::correlator::RequestCorrelator;
 use crate::error::{McpResult, TransportError};
 use crate::messages::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::retry;
 
 /// SSE event with ID for resumability
 /// This infrastructure supports resumable connections per MCP spec
@@ -43,17 +54,26 @@ struct SseEvent {
 /// - Server responds with either single JSON or SSE stream based on Content-Type
 /// - Supports resumable connections and message replay via Last-Event-ID
 /// - Implements security best practices for Origin validation and localhost binding
+///
+/// Requests go through the connection's [`HttpSsePipeline`], so concurrent
+/// callers sharing one transport each have their own request in flight.
 pub struct HttpSseTransport {
     config: TransportConfig,
     http_client: Client,
-    info: TransportInfo,
-    session_id: Option<String>,
+    info: Mutex<TransportInfo>,
     base_url: Url,
-    sse_receiver: Option<mpsc::UnboundedReceiver<JsonRpcMessage>>,
-    _sse_task_handle: Option<tokio::task::JoinHandle<()>>,
-    last_event_id: Option<String>,
+    sse_task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    last_event_id: RwLock<Option<String>>,
     security_config: SecurityConfig,
-    session_manager: SessionManager,
+    /// Held while connecting or disconnecting, never while sending
+    session_manager: tokio::sync::Mutex<SessionManager>,
+    correlator: RequestCorrelator,
+    frames: FrameParser,
+    limits: MessageLimits,
+    compression: Compression,
+    pipeline: RwLock<Option<HttpSsePipeline>>,
+    messages: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<JsonRpcMessage>>>,
+    disconnected: Notify,
 }
 
 /// MCP protocol version for transport compatibility
@@ -68,7 +88,7 @@ enum McpProtocolVersion {
 }
 
 /// Generic session management for MCP SSE servers
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct SessionManager {
     /// Whether to automatically discover sessions
     auto_discover: bool,
@@ -84,8 +104,8 @@ struct SessionManager {
     _discovery_task: Option<Arc<tokio::task::JoinHandle<()>>>,
     /// Receiver for fresh session IDs from background task
     session_receiver: Option<Arc<Mutex<tokio::sync::mpsc::UnboundedReceiver<String>>>>,
-    /// Receiver for JSON-RPC messages from session monitor
-    jsonrpc_receiver: Option<Arc<Mutex<tokio::sync::mpsc::UnboundedReceiver<JsonRpcMessage>>>>,
+    /// Receiver for JSON-RPC messages from session monitor, until the pipeline takes it
+    jsonrpc_receiver: Option<mpsc::UnboundedReceiver<JsonRpcMessage>>,
     /// Detected or configured protocol version
     protocol_version: McpProtocolVersion,
 }
@@ -120,9 +140,10 @@ struct SecurityConfig {
     require_https: bool,
     /// Validate session ID format and security
     validate_session_ids: bool,
-    /// Allowed origins for CORS (used for SSE security validation)
-    #[allow(dead_code)]
-    allowed_origins: Vec<String>,
+    /// Origins requests may be redirected to
+    origins: OriginPolicy,
+    /// Refuse responses from a loopback address to a name that isn't one
+    check_peer: bool,
 }
 
 impl Default for SecurityConfig {
@@ -132,11 +153,27 @@ impl Default for SecurityConfig {
             enforce_localhost: true,
             require_https: false, // Allow HTTP for local development
             validate_session_ids: true,
-            allowed_origins: vec![
-                "http://localhost".to_string(),
-                "https://localhost".to_string(),
-            ],
+            origins: OriginPolicy::localhost(),
+            check_peer: true,
+        }
+    }
+}
+
+impl SecurityConfig {
+    /// Check where a response came from: its final URL must be within the
+    /// allowed origins, and a remote name must not have answered from a
+    /// loopback address
+    fn check_response(&self, response: &Response) -> McpResult<()> {
+        if !self.validate_origin {
+            return Ok(());
+        }
+        self.origins.check_url(response.url())?;
+        if self.check_peer {
+            if let Some(peer) = response.remote_addr() {
+                self.origins.check_peer(response.url(), peer)?;
+            }
         }
+        Ok(())
     }
 }
 
@@ -151,31 +188,61 @@ impl HttpSseTransport {
     ///
     /// A new transport instance ready for connection.
     pub fn new(config: TransportConfig) -> McpResult<Self> {
-        let (http_client, base_url) = Self::build_http_client(&config)?;
+        let TransportConfig::HttpSse(ref sse_config) = config else {
+            return Err(TransportError::InvalidConfig {
+                transport_type: "streamable-http".to_string(),
+                reason: "Invalid configuration type".to_string(),
+            }
+            .into());
+        };
+        let base_url = sse_config.base_url.clone();
+        let security_config = Self::build_security_config(sse_config)?;
+        let http_client = Self::build_http_client(sse_config, &security_config)?;
         let info = TransportInfo::new("streamable-http");
-        let security_config = Self::build_security_config(&config, &base_url)?;
+        let frames = FrameParser::new("streamable-http", sse_config.parse_mode);
+        let limits = sse_config.limits.clone();
+        let compression = Compression::new(sse_config.compression);
 
         Ok(Self {
             config,
             http_client,
-            info,
-            session_id: None,
+            info: Mutex::new(info),
             base_url,
-            sse_receiver: None,
-            _sse_task_handle: None,
-            last_event_id: None,
+            sse_task: Mutex::new(None),
+            last_event_id: RwLock::new(None),
             security_config,
-            session_manager: SessionManager::default(),
+            session_manager: tokio::sync::Mutex::new(SessionManager::default()),
+            correlator: RequestCorrelator::new("streamable-http"),
+            frames,
+            limits,
+            compression,
+            pipeline: RwLock::new(None),
+            messages: tokio::sync::Mutex::new(None),
+            disconnected: Notify::new(),
         })
     }
 
     /// Build security configuration based on transport config and URL
-    fn build_security_config(
-        _config: &TransportConfig,
-        base_url: &Url,
-    ) -> McpResult<SecurityConfig> {
+    fn build_security_config(config: &HttpSseConfig) -> McpResult<SecurityConfig> {
+        let base_url = &config.base_url;
         let mut security_config = SecurityConfig::default();
 
+        // Stay on the server's origin unless told otherwise
+        let listed = config
+            .allowed_origins
+            .iter()
+            .fold(OriginPolicy::new(), |policy, pattern| {
+                policy.allow_origin(pattern.clone())
+            });
+        security_config.check_peer =
+            !listed.allows_origin(&base_url.origin().ascii_serialization());
+        security_config.origins = config
+            .allowed_origins
+            .iter()
+            .fold(OriginPolicy::for_url(base_url), |policy, pattern| {
+                policy.allow_origin(pattern.clone())
+            });
+
         // Enforce HTTPS for non-localhost URLs
         if base_url.host_str() != Some("localhost") && base_url.host_str() != Some("127.0.0.1") {
             security_config.require_https = true;
@@ -206,88 +273,50 @@ impl HttpSseTransport {
     }
 
     /// Build the HTTP client with appropriate configuration.
-    fn build_http_client(config: &TransportConfig) -> McpResult<(Client, Url)> {
-        if let TransportConfig::HttpSse(sse_config) = config {
-            let mut builder = Client::builder();
-            builder = builder.timeout(sse_config.timeout);
-
-            // Add custom headers if specified
-            if !sse_config.headers.is_empty() {
-                let mut headers = HeaderMap::new();
-                for (key, value) in &sse_config.headers {
-                    if let (Ok(header_name), Ok(header_value)) = (
-                        key.parse::<reqwest::header::HeaderName>(),
-                        HeaderValue::from_str(value),
-                    ) {
-                        headers.insert(header_name, header_value);
-                    }
-                }
-                builder = builder.default_headers(headers);
-            }
-
-            let client = builder.build().map_err(|e| TransportError::InvalidConfig {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Failed to build HTTP client: {}", e),
-            })?;
-
-            Ok((client, sse_config.base_url.clone()))
-        } else {
-            Err(TransportError::InvalidConfig {
-                transport_type: "streamable-http".to_string(),
-                reason: "Invalid configuration type".to_string(),
-            }
-            .into())
-        }
-    }
-
-    /// Validate Origin header to prevent DNS rebinding attacks
-    fn validate_origin(&self, _request_builder: &reqwest::RequestBuilder) -> McpResult<()> {
-        if !self.security_config.validate_origin {
-            return Ok(());
-        }
-
-        // For local connections, we should validate the origin
-        if self.base_url.host_str() == Some("localhost")
-            || self.base_url.host_str() == Some("127.0.0.1")
-        {
-            // Origin validation is important for localhost to prevent DNS rebinding
-            tracing::debug!("Origin validation enabled for localhost connection");
-        }
-
-        Ok(())
-    }
-
-    /// Validate session ID security
-    fn validate_session_id(&self, session_id: &str) -> McpResult<()> {
-        if !self.security_config.validate_session_ids {
-            return Ok(());
+    fn build_http_client(
+        sse_config: &HttpSseConfig,
+        security_config: &SecurityConfig,
+    ) -> McpResult<Client> {
+        let mut builder = sse_config.connection.apply(Client::builder());
+        builder = builder.timeout(sse_config.timeout);
+        if security_config.validate_origin {
+            builder = builder.redirect(origin::redirect_policy(security_config.origins.clone()));
         }
 
-        // Check session ID format (should be cryptographically secure)
-        if session_id.len() < 16 {
-            return Err(TransportError::InvalidConfig {
-                transport_type: "streamable-http".to_string(),
-                reason: "Session ID too short - security risk".to_string(),
+        // Add custom headers if specified
+        if !sse_config.headers.is_empty() {
+            let mut headers = HeaderMap::new();
+            for (key, value) in &sse_config.headers {
+                if let (Ok(header_name), Ok(header_value)) = (
+                    key.parse::<reqwest::header::HeaderName>(),
+                    HeaderValue::from_str(value),
+                ) {
+                    headers.insert(header_name, header_value);
+                }
             }
-            .into());
+            builder = builder.default_headers(headers);
         }
 
-        // Check for basic format (alphanumeric and hyphens)
-        if !session_id.chars().all(|c| c.is_alphanumeric() || c == '-') {
-            return Err(TransportError::InvalidConfig {
+        builder.build().map_err(|e| {
+            TransportError::InvalidConfig {
                 transport_type: "streamable-http".to_string(),
-                reason: "Session ID contains invalid characters".to_string(),
+                reason: format!("Failed to build HTTP client: {}", e),
             }
-            .into());
-        }
+            .into()
+        })
+    }
 
-        Ok(())
+    /// Validate where a response came from, to prevent DNS rebinding attacks
+    ///
+    /// Redirects off the allowed origins are refused before they are followed.
+    fn validate_origin(&self, response: &Response) -> McpResult<()> {
+        self.security_config.check_response(response)
     }
 
     /// Detect MCP protocol version based on endpoint and server behavior
-    fn detect_protocol_version(&mut self) -> McpProtocolVersion {
-        if self.session_manager.protocol_version != McpProtocolVersion::AutoDetect {
-            return self.session_manager.protocol_version.clone();
+    fn detect_protocol_version(&self, manager: &mut SessionManager) -> McpProtocolVersion {
+        if manager.protocol_version != McpProtocolVersion::AutoDetect {
+            return manager.protocol_version.clone();
         }
 
         // Auto-detect based on endpoint patterns
@@ -296,12 +325,12 @@ impl HttpSseTransport {
                 tracing::info!(
                     "Detected Modern Streamable HTTP protocol (2025-03-26) - /mcp endpoint"
                 );
-                self.session_manager.protocol_version = McpProtocolVersion::StreamableHttp;
+                manager.protocol_version = McpProtocolVersion::StreamableHttp;
                 McpProtocolVersion::StreamableHttp
             }
             "/sse" => {
                 tracing::info!("Detected Legacy HTTP+SSE protocol (2024-11-05) - /sse endpoint");
-                self.session_manager.protocol_version = McpProtocolVersion::HttpSse;
+                manager.protocol_version = McpProtocolVersion::HttpSse;
                 McpProtocolVersion::HttpSse
             }
             path => {
@@ -309,264 +338,12 @@ impl HttpSseTransport {
                     "Unknown endpoint pattern: {}, defaulting to Modern Streamable HTTP",
                     path
                 );
-                self.session_manager.protocol_version = McpProtocolVersion::StreamableHttp;
+                manager.protocol_version = McpProtocolVersion::StreamableHttp;
                 McpProtocolVersion::StreamableHttp
             }
         }
     }
 
-    /// Send a request and handle both JSON and SSE responses according to MCP spec.
-    async fn send_mcp_request(
-        &mut self,
-        message: JsonRpcMessage,
-    ) -> McpResult<Option<JsonRpcResponse>> {
-        // Get the freshest session ID available
-        self.get_fresh_session_id().await;
-
-        // Detect protocol version and route accordingly
-        let protocol_version = self.detect_protocol_version();
-        match protocol_version {
-            McpProtocolVersion::StreamableHttp => {
-                tracing::info!("Using Modern Streamable HTTP protocol (header-based sessions)");
-                self.send_streamable_http_request(message).await
-            }
-            McpProtocolVersion::HttpSse => {
-                tracing::info!("Using Legacy HTTP+SSE protocol (query parameter sessions)");
-                self.send_legacy_sse_request(message).await
-            }
-            McpProtocolVersion::AutoDetect => {
-                // This shouldn't happen after detection, but fallback to modern
-                tracing::warn!(
-                    "Protocol auto-detection failed, falling back to Modern Streamable HTTP"
-                );
-                self.send_streamable_http_request(message).await
-            }
-        }
-    }
-
-    /// Send request using Modern Streamable HTTP protocol (2025-03-26)
-    async fn send_streamable_http_request(
-        &mut self,
-        message: JsonRpcMessage,
-    ) -> McpResult<Option<JsonRpcResponse>> {
-        let mut request_builder = self
-            .http_client
-            .post(self.base_url.clone())
-            .header(CONTENT_TYPE, "application/json")
-            .header("Accept", "application/json, text/event-stream");
-
-        // Validate Origin header for security
-        self.validate_origin(&request_builder)?;
-
-        // Include session ID in Mcp-Session-Id header (Modern protocol)
-        if let Some(ref session_id) = self.session_id {
-            request_builder = request_builder.header("Mcp-Session-Id", session_id);
-            tracing::info!("Using session ID in header (Modern): {}", session_id);
-        }
-
-        // Include Last-Event-ID for resumability
-        if let Some(ref last_event_id) = self.last_event_id {
-            request_builder = request_builder.header("Last-Event-ID", last_event_id);
-            tracing::debug!("Resuming from last event ID: {}", last_event_id);
-        }
-
-        // Send the request
-        let response = request_builder.json(&message).send().await.map_err(|e| {
-            TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Modern HTTP request failed: {}", e),
-            }
-        })?;
-
-        // Extract session ID from response header (for initialization)
-        if let Some(session_header) = response.headers().get("mcp-session-id") {
-            if let Ok(session_str) = session_header.to_str() {
-                self.validate_session_id(session_str)?;
-                tracing::info!("Extracted session ID from Modern response: {}", session_str);
-                self.session_id = Some(session_str.to_string());
-            }
-        }
-
-        // Handle response based on Content-Type
-        let content_type = response
-            .headers()
-            .get(CONTENT_TYPE)
-            .and_then(|ct| ct.to_str().ok())
-            .unwrap_or("application/json");
-
-        tracing::info!("=== MODERN HTTP RESPONSE DEBUG ===");
-        tracing::info!("Status: {}", response.status());
-        tracing::info!("Content-Type: {}", content_type);
-        tracing::info!("Headers: {:?}", response.headers());
-
-        match content_type {
-            ct if ct.contains("application/json") => {
-                // Single JSON response - standard case
-                let response_text =
-                    response
-                        .text()
-                        .await
-                        .map_err(|e| TransportError::SerializationError {
-                            transport_type: "streamable-http".to_string(),
-                            reason: format!("Failed to get Modern response text: {}", e),
-                        })?;
-
-                tracing::info!("=== MODERN JSON RESPONSE ===");
-                tracing::info!("{}", response_text);
-
-                let json_response: JsonRpcResponse =
-                    serde_json::from_str(&response_text).map_err(|e| {
-                        TransportError::SerializationError {
-                            transport_type: "streamable-http".to_string(),
-                            reason: format!("Failed to parse Modern JSON response: {}", e),
-                        }
-                    })?;
-                Ok(Some(json_response))
-            }
-            ct if ct.contains("text/event-stream") => {
-                // SSE stream response - for multiple messages
-                tracing::info!("Modern protocol returned SSE stream");
-                self.handle_sse_response(response).await?;
-
-                // Wait for response via SSE stream
-                if let JsonRpcMessage::Request(req) = message {
-                    tracing::info!("Waiting for Modern SSE response to request ID: {}", req.id);
-                    return Ok(Some(
-                        self.wait_for_sse_response(&req.id.to_string(), Duration::from_secs(10))
-                            .await?,
-                    ));
-                }
-                Ok(None)
-            }
-            _ => Err(TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Unexpected Modern content type: {}", content_type),
-            }
-            .into()),
-        }
-    }
-
-    /// Send request using Legacy HTTP+SSE protocol (2024-11-05)
-    async fn send_legacy_sse_request(
-        &mut self,
-        message: JsonRpcMessage,
-    ) -> McpResult<Option<JsonRpcResponse>> {
-        tracing::info!("Sending request using Legacy HTTP+SSE protocol");
-
-        // Wait for a fresh session ID before sending request
-        let mut attempts = 0;
-        while self.session_id.is_none() && attempts < 50 {
-            self.get_fresh_session_id().await;
-            if self.session_id.is_none() {
-                tokio::time::sleep(Duration::from_millis(100)).await;
-                attempts += 1;
-            }
-        }
-
-        // Build URL with session ID in query parameters (Legacy protocol)
-        let mut request_url = self.base_url.clone();
-        if let Some(ref session_id) = self.session_id {
-            request_url.set_query(Some(&format!("sessionId={}", session_id)));
-            tracing::info!(
-                "Using session ID in query parameter (Legacy): {}",
-                session_id
-            );
-        } else {
-            tracing::warn!("No session ID available for Legacy request after waiting");
-        }
-
-        tracing::info!("Sending Legacy POST request to: {}", request_url);
-
-        let request_builder = self
-            .http_client
-            .post(request_url)
-            .header(CONTENT_TYPE, "application/json")
-            .header("Accept", "application/json, text/event-stream");
-
-        // Send the JSON-RPC request
-        let response = request_builder.json(&message).send().await.map_err(|e| {
-            TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Legacy HTTP+SSE request failed: {}", e),
-            }
-        })?;
-
-        let content_type = response
-            .headers()
-            .get(CONTENT_TYPE)
-            .and_then(|ct| ct.to_str().ok())
-            .unwrap_or("");
-
-        tracing::info!("=== LEGACY HTTP+SSE RESPONSE DEBUG ===");
-        tracing::info!("Status: {}", response.status());
-        tracing::info!("Content-Type: {}", content_type);
-        tracing::info!("Headers: {:?}", response.headers());
-
-        // Handle response based on Status and Content-Type
-        match (response.status().as_u16(), content_type) {
-            (202, _) => {
-                // 202 Accepted - Legacy protocol, response will come via SSE stream
-                tracing::info!("Legacy protocol: Request accepted (202), waiting for SSE response");
-
-                // Wait for response via SSE stream
-                if let JsonRpcMessage::Request(req) = message {
-                    tracing::info!("Waiting for Legacy SSE response to request ID: {}", req.id);
-                    return Ok(Some(
-                        self.wait_for_sse_response(&req.id.to_string(), Duration::from_secs(10))
-                            .await?,
-                    ));
-                }
-                Ok(None)
-            }
-            (_, ct) if ct.contains("application/json") => {
-                // Direct JSON response
-                let response_text =
-                    response
-                        .text()
-                        .await
-                        .map_err(|e| TransportError::SerializationError {
-                            transport_type: "streamable-http".to_string(),
-                            reason: format!("Failed to get Legacy response text: {}", e),
-                        })?;
-
-                tracing::info!("=== LEGACY JSON RESPONSE ===");
-                tracing::info!("{}", response_text);
-
-                let json_response: JsonRpcResponse =
-                    serde_json::from_str(&response_text).map_err(|e| {
-                        TransportError::SerializationError {
-                            transport_type: "streamable-http".to_string(),
-                            reason: format!("Failed to parse Legacy JSON response: {}", e),
-                        }
-                    })?;
-                Ok(Some(json_response))
-            }
-            (_, ct) if ct.contains("text/event-stream") => {
-                // SSE stream response
-                tracing::info!("Legacy protocol returned SSE stream");
-                self.handle_sse_response(response).await?;
-
-                // Wait for response via SSE stream
-                if let JsonRpcMessage::Request(req) = message {
-                    tracing::info!("Waiting for Legacy SSE response to request ID: {}", req.id);
-                    return Ok(Some(
-                        self.wait_for_sse_response(&req.id.to_string(), Duration::from_secs(10))
-                            .await?,
-                    ));
-                }
-                Ok(None)
-            }
-            (status, ct) => Err(TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!(
-                    "Unexpected Legacy response - Status: {}, Content-Type: {}",
-                    status, ct
-                ),
-            }
-            .into()),
-        }
-    }
-
     /// Parse SSE event with ID tracking for resumability
     /// This infrastructure supports resumable connections per MCP spec
     #[allow(dead_code)]
@@ -579,106 +356,48 @@ impl HttpSseTransport {
         })
     }
 
-    /// Handle SSE stream responses for server-to-client communication with resumability.
-    async fn handle_sse_response(&mut self, response: Response) -> McpResult<()> {
-        let event_stream = response.bytes_stream().eventsource();
-        let (sender, receiver) = mpsc::unbounded_channel();
-        self.sse_receiver = Some(receiver);
-
-        // Track last event ID for resumability
-        let current_last_event_id = self.last_event_id.clone();
-
-        // Spawn task to handle SSE events
-        let task_handle = tokio::spawn(async move {
-            let mut stream = event_stream;
-            let mut event_count = 0u64;
-            let mut last_event_id = current_last_event_id;
-
-            while let Some(event) = stream.next().await {
-                match event {
-                    Ok(event) => {
-                        event_count += 1;
-
-                        // Track event ID for resumability
-                        if !event.id.is_empty() {
-                            last_event_id = Some(event.id.clone());
-                            tracing::trace!("Received SSE event with ID: {}", event.id);
-                        }
-
-                        // Parse event data as JSON-RPC message (skip session announcements)
-                        if event.data.starts_with("/sse?sessionId=")
-                            || event.data.starts_with("/mcp?sessionId=")
-                        {
-                            tracing::debug!("Skipping session announcement: {}", event.data);
-                        } else if let Ok(message) =
-                            serde_json::from_str::<JsonRpcMessage>(&event.data)
-                        {
-                            tracing::info!("Parsed JSON-RPC message from SSE: {:?}", message);
-                            if sender.send(message).is_err() {
-                                tracing::debug!(
-                                    "SSE receiver dropped, stopping stream after {} events",
-                                    event_count
-                                );
-                                break;
-                            }
-                        } else {
-                            tracing::warn!("Failed to parse SSE message: {}", event.data);
-                        }
-
-                        // Handle retry directive from server
-                        if let Some(retry_ms) = event.retry {
-                            tracing::debug!(
-                                "Server requested retry interval: {}ms",
-                                retry_ms.as_millis()
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("SSE stream error after {} events: {}", event_count, e);
-
-                        // For network errors, we might want to retry with Last-Event-ID
-                        if let Some(ref last_id) = last_event_id {
-                            tracing::info!(
-                                "Connection lost - can resume from event ID: {}",
-                                last_id
-                            );
-                        }
-                        break;
-                    }
-                }
-            }
-            tracing::debug!("SSE stream ended after {} events", event_count);
-        });
-
-        self._sse_task_handle = Some(task_handle);
+    /// Handle SSE stream responses for server-to-client communication.
+    ///
+    /// Responses complete their waiting requests; everything else is returned
+    /// by [`Transport::receive_message`].
+    fn handle_sse_response(&self, response: Response) -> McpResult<()> {
+        let inbound = self.pipeline()?.shared.inbound.clone();
+        let task_handle = tokio::spawn(read_event_stream(
+            self.correlator.clone(),
+            self.frames.clone(),
+            self.limits.clone(),
+            self.compression.decode(response),
+            inbound,
+        ));
+
+        if let Some(previous) = lock(&self.sse_task).replace(task_handle) {
+            previous.abort();
+        }
         Ok(())
     }
 
     /// Resume SSE connection from last event ID
-    pub async fn resume_sse_connection(&mut self) -> McpResult<()> {
-        if let Some(ref last_event_id) = self.last_event_id {
+    pub async fn resume_sse_connection(&self) -> McpResult<()> {
+        if let Some(last_event_id) = self.last_event_id() {
             tracing::info!("Resuming SSE connection from event ID: {}", last_event_id);
 
             // Make a GET request to establish SSE connection with Last-Event-ID
             let mut request_builder = self
-                .http_client
-                .get(self.base_url.clone())
+                .compression
+                .accept(self.http_client.get(self.base_url.clone()))
                 .header("Accept", "text/event-stream")
                 .header("Last-Event-ID", last_event_id);
 
             // Include session ID if we have one
-            if let Some(ref session_id) = self.session_id {
+            if let Some(session_id) = self.session_id() {
                 request_builder = request_builder.header("Mcp-Session-Id", session_id);
             }
 
-            let response =
-                request_builder
-                    .send()
-                    .await
-                    .map_err(|e| TransportError::NetworkError {
-                        transport_type: "streamable-http".to_string(),
-                        reason: format!("Failed to resume SSE connection: {}", e),
-                    })?;
+            let response = request_builder
+                .send()
+                .await
+                .map_err(|e| send_error(e, "Failed to resume SSE connection"))?;
+            self.validate_origin(&response)?;
 
             if response
                 .headers()
@@ -686,7 +405,7 @@ impl HttpSseTransport {
                 .and_then(|ct| ct.to_str().ok())
                 == Some("text/event-stream")
             {
-                self.handle_sse_response(response).await?;
+                self.handle_sse_response(response)?;
                 tracing::info!("SSE connection resumed successfully");
             } else {
                 return Err(TransportError::NetworkError {
@@ -700,24 +419,107 @@ impl HttpSseTransport {
         Ok(())
     }
 
+    /// Bytes sent and received compressed, and what compression saved.
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.compression.stats()
+    }
+
     /// Get current session ID for debugging.
-    pub fn session_id(&self) -> Option<&str> {
-        self.session_id.as_deref()
+    pub fn session_id(&self) -> Option<String> {
+        read(&self.pipeline)
+            .as_ref()
+            .and_then(|pipeline| read(&pipeline.shared.session_id).clone())
     }
 
     /// Get last event ID for resumability
-    pub fn last_event_id(&self) -> Option<&str> {
-        self.last_event_id.as_deref()
+    pub fn last_event_id(&self) -> Option<String> {
+        read(&self.last_event_id).clone()
     }
 
     /// Check if transport can resume from disconnection
     pub fn can_resume(&self) -> bool {
-        self.last_event_id.is_some()
+        read(&self.last_event_id).is_some()
+    }
+
+    /// Get a handle for sending requests concurrently over this session.
+    ///
+    /// The transport sends its own requests through this pipeline; the handle
+    /// is for callers that want it without going through [`Transport`]. It
+    /// keeps up to `max_in_flight` requests outstanding and later requests
+    /// wait for a slot. Responses are matched to requests by ID, and anything
+    /// else the server sends is returned by [`Transport::receive_message`].
+    ///
+    /// Every call during one connection returns a handle to the same pipeline.
+    pub fn pipeline(&self) -> McpResult<HttpSsePipeline> {
+        read(&self.pipeline).clone().ok_or_else(|| {
+            TransportError::NotConnected {
+                transport_type: "streamable-http".to_string(),
+                reason: "Transport not connected".to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Open the pipeline for a new connection.
+    ///
+    /// Responses from the session monitor go straight to their waiters; the
+    /// transport's own reads see only what nobody claimed.
+    fn open_pipeline(
+        &self,
+        manager: &mut SessionManager,
+        inbound: mpsc::UnboundedSender<JsonRpcMessage>,
+    ) -> HttpSsePipeline {
+        let legacy = self.detect_protocol_version(manager) == McpProtocolVersion::HttpSse;
+        // No more requests outstanding than the connection has streams for
+        let max_in_flight = match &self.config {
+            TransportConfig::HttpSse(config) => match config.connection.max_concurrent_streams {
+                Some(streams) => config.max_in_flight.min(streams),
+                None => config.max_in_flight,
+            },
+            _ => 1,
+        };
+
+        if let Some(monitored) = manager.jsonrpc_receiver.take() {
+            tokio::spawn(route_responses(
+                monitored,
+                self.correlator.clone(),
+                inbound.clone(),
+            ));
+        }
+
+        HttpSsePipeline {
+            shared: Arc::new(PipelineShared {
+                http_client: self.http_client.clone(),
+                base_url: self.base_url.clone(),
+                legacy,
+                session_id: RwLock::new(None),
+                session_receiver: manager.session_receiver.clone(),
+                security: self.security_config.clone(),
+                correlator: self.correlator.clone(),
+                frames: self.frames.clone(),
+                limits: self.limits.clone(),
+                compression: self.compression.clone(),
+                slots: Arc::new(Semaphore::new(max_in_flight)),
+                max_in_flight,
+                inbound,
+                closed: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    fn not_connected() -> TransportError {
+        TransportError::NotConnected {
+            transport_type: "streamable-http".to_string(),
+            reason: "Transport not connected".to_string(),
+        }
     }
 
     /// Start continuous session monitoring for MCP servers with ephemeral sessions
-    async fn start_continuous_session_monitoring(&mut self) -> McpResult<()> {
-        if !self.session_manager.auto_discover {
+    async fn start_continuous_session_monitoring(
+        &self,
+        manager: &mut SessionManager,
+    ) -> McpResult<()> {
+        if !manager.auto_discover {
             return Ok(());
         }
 
@@ -726,15 +528,18 @@ impl HttpSseTransport {
             tracing::info!(
                 "Modern Streamable HTTP protocol detected - skipping session monitoring"
             );
-            self.session_manager.protocol_version = McpProtocolVersion::StreamableHttp;
+            manager.protocol_version = McpProtocolVersion::StreamableHttp;
             return Ok(());
         }
 
         tracing::info!("Starting continuous session monitoring for MCP server");
 
         // Try each discovery endpoint to find one that works
-        for endpoint in &self.session_manager.discovery_endpoints.clone() {
-            if let Ok(Some(_)) = self.start_session_monitor_for_endpoint(endpoint).await {
+        for endpoint in &manager.discovery_endpoints.clone() {
+            if let Ok(Some(_)) = self
+                .start_session_monitor_for_endpoint(manager, endpoint)
+                .await
+            {
                 tracing::info!(
                     "Started continuous session monitoring via endpoint: {}",
                     endpoint
@@ -749,7 +554,8 @@ impl HttpSseTransport {
 
     /// Start background monitoring for a specific endpoint
     async fn start_session_monitor_for_endpoint(
-        &mut self,
+        &self,
+        manager: &mut SessionManager,
         endpoint: &str,
     ) -> McpResult<Option<()>> {
         // For SSE endpoints, we need to discover sessions via /events, not the SSE endpoint itself
@@ -776,10 +582,8 @@ impl HttpSseTransport {
             .header("Accept", "text/event-stream")
             .send()
             .await
-            .map_err(|e| TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Session monitor test failed: {}", e),
-            })?;
+            .map_err(|e| send_error(e, "Session monitor test failed"))?;
+        self.validate_origin(&test_response)?;
 
         let content_type = test_response
             .headers()
@@ -797,27 +601,28 @@ impl HttpSseTransport {
 
         // Start background session monitoring task
         let (session_sender, session_receiver) = tokio::sync::mpsc::unbounded_channel();
-        self.session_manager.session_receiver = Some(Arc::new(Mutex::new(session_receiver)));
+        manager.session_receiver = Some(Arc::new(Mutex::new(session_receiver)));
 
         // Create JSON-RPC message channel for routing responses
         let (jsonrpc_sender, jsonrpc_receiver) = tokio::sync::mpsc::unbounded_channel();
-        self.session_manager.jsonrpc_receiver = Some(Arc::new(Mutex::new(jsonrpc_receiver)));
+        manager.jsonrpc_receiver = Some(jsonrpc_receiver);
 
         let client = self.http_client.clone();
+        let compression = self.compression.clone();
         let url = discovery_url.clone();
 
         let task_handle = tokio::spawn(async move {
             tracing::info!("Background session monitor started for: {}", url);
 
             loop {
-                match client
-                    .get(url.clone())
+                match compression
+                    .accept(client.get(url.clone()))
                     .header("Accept", "text/event-stream")
                     .send()
                     .await
                 {
                     Ok(response) => {
-                        let event_stream = response.bytes_stream().eventsource();
+                        let event_stream = compression.decode(response).eventsource();
                         let mut stream = event_stream;
 
                         while let Some(event_result) = stream.next().await {
@@ -880,7 +685,7 @@ impl HttpSseTransport {
             }
         });
 
-        self.session_manager._discovery_task = Some(Arc::new(task_handle));
+        manager._discovery_task = Some(Arc::new(task_handle));
         Ok(Some(()))
     }
 
@@ -908,712 +713,153 @@ impl HttpSseTransport {
 
         None
     }
+}
 
-    /// Send JSON-RPC request to SSE endpoint with session management (Legacy HTTP+SSE protocol)
-    #[allow(dead_code)]
-    async fn establish_sse_connection_with_message(
-        &mut self,
-        message: JsonRpcMessage,
-    ) -> McpResult<Option<JsonRpcResponse>> {
-        tracing::info!(
-            "Sending JSON-RPC request to SSE endpoint (Legacy HTTP+SSE): {}",
-            self.base_url
-        );
-
-        // Wait for a fresh session ID before sending request
-        let mut attempts = 0;
-        while self.session_id.is_none() && attempts < 50 {
-            self.get_fresh_session_id().await;
-            if self.session_id.is_none() {
-                tokio::time::sleep(Duration::from_millis(100)).await;
-                attempts += 1;
-            }
-        }
-
-        // For legacy HTTP+SSE protocol, we need to use query parameters, not headers
-        let mut request_url = self.base_url.clone();
-        if let Some(ref session_id) = self.session_id {
-            request_url.set_query(Some(&format!("sessionId={}", session_id)));
-            tracing::info!(
-                "Using session ID in query parameter for legacy SSE request: {}",
-                session_id
-            );
-        } else {
-            tracing::warn!("No session ID available for SSE request after waiting");
-        }
-
-        tracing::info!("Sending POST request to: {}", request_url);
-
-        let request_builder = self
-            .http_client
-            .post(request_url)
-            .header(CONTENT_TYPE, "application/json")
-            .header("Accept", "application/json, text/event-stream");
-
-        // Send the JSON-RPC request
-        let response = request_builder.json(&message).send().await.map_err(|e| {
-            TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Legacy SSE JSON-RPC request failed: {}", e),
-            }
-        })?;
+#[async_trait]
+impl Transport for HttpSseTransport {
+    async fn connect(&self) -> McpResult<()> {
+        tracing::info!("Connecting Streamable HTTP transport to: {}", self.base_url);
+        let mut manager = self.session_manager.lock().await;
 
-        let content_type = response
-            .headers()
-            .get(CONTENT_TYPE)
-            .and_then(|ct| ct.to_str().ok())
-            .unwrap_or("");
+        // Step 1: Start continuous session monitoring for MCP servers that require it
+        self.start_continuous_session_monitoring(&mut manager)
+            .await?;
 
-        tracing::info!(
-            "SSE JSON-RPC Response - Status: {}, Content-Type: {}",
-            response.status(),
-            content_type
-        );
+        // Step 2: Test connectivity with a simple request
+        let test_response = self.http_client.head(self.base_url.clone()).send().await;
 
-        // Handle response based on Content-Type (same as /mcp endpoint)
-        match content_type {
-            ct if ct.contains("application/json") => {
-                // Direct JSON response
-                let response_text =
-                    response
-                        .text()
-                        .await
-                        .map_err(|e| TransportError::SerializationError {
-                            transport_type: "streamable-http".to_string(),
-                            reason: format!("Failed to get SSE response text: {}", e),
-                        })?;
-
-                tracing::info!("=== SSE JSON RESPONSE ===");
-                tracing::info!("{}", response_text);
-
-                let json_response: JsonRpcResponse =
-                    serde_json::from_str(&response_text).map_err(|e| {
-                        TransportError::SerializationError {
-                            transport_type: "streamable-http".to_string(),
-                            reason: format!("Failed to parse SSE JSON response: {}", e),
-                        }
-                    })?;
-                Ok(Some(json_response))
-            }
-            ct if ct.contains("text/event-stream") => {
-                // SSE stream response
-                tracing::info!("SSE endpoint returned event stream - handling SSE response");
-                self.handle_sse_response(response).await?;
-
-                // Wait for response via SSE stream
-                if let JsonRpcMessage::Request(req) = message {
-                    tracing::info!("Waiting for SSE response to request ID: {}", req.id);
-                    return Ok(Some(
-                        self.wait_for_sse_response(&req.id.to_string(), Duration::from_secs(10))
-                            .await?,
-                    ));
-                }
-                Ok(None)
+        match test_response {
+            Ok(response) => {
+                self.validate_origin(&response)?;
+                let (inbound, messages) = mpsc::unbounded_channel();
+                let pipeline = self.open_pipeline(&mut manager, inbound);
+                *write(&self.pipeline) = Some(pipeline);
+                *self.messages.lock().await = Some(messages);
+                lock(&self.info).mark_connected();
+                tracing::info!("Streamable HTTP transport connected successfully");
+                Ok(())
             }
-            _ => Err(TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Unexpected SSE response content type: {}", content_type),
+            Err(e) => Err(match origin::rejection(&e) {
+                Some(rejection) => rejection.into(),
+                None => TransportError::ConnectionError {
+                    transport_type: "streamable-http".to_string(),
+                    reason: format!("Failed to connect to server: {}", e),
+                },
             }
             .into()),
         }
     }
 
-    /// Send JSON-RPC request to SSE endpoint using GET with session parameters
-    #[allow(dead_code)]
-    async fn send_sse_get_request(
-        &mut self,
-        message: JsonRpcMessage,
-    ) -> McpResult<Option<JsonRpcResponse>> {
-        tracing::info!("Sending JSON-RPC to SSE endpoint via GET request");
+    async fn disconnect(&self) -> McpResult<()> {
+        tracing::info!("Disconnecting Streamable HTTP transport");
+        let mut manager = self.session_manager.lock().await;
 
-        // Wait for a fresh session ID before sending request
-        let mut attempts = 0;
-        while self.session_id.is_none() && attempts < 50 {
-            self.get_fresh_session_id().await;
-            if self.session_id.is_none() {
-                tokio::time::sleep(Duration::from_millis(100)).await;
-                attempts += 1;
-            }
+        // Terminate session if we have one
+        if let Some(session_id) = self.session_id() {
+            let _ = self
+                .http_client
+                .delete(self.base_url.clone())
+                .header("Mcp-Session-Id", session_id)
+                .send()
+                .await;
         }
 
-        // Build URL with session ID in query parameters (legacy HTTP+SSE protocol)
-        let mut request_url = self.base_url.clone();
-        if let Some(ref session_id) = self.session_id {
-            request_url.set_query(Some(&format!("sessionId={}", session_id)));
-            tracing::info!(
-                "Using session ID in query parameter for SSE GET: {}",
-                session_id
-            );
-        } else {
-            tracing::warn!("No session ID available for SSE GET request after waiting");
+        // Clean up SSE resources
+        if let Some(handle) = lock(&self.sse_task).take() {
+            handle.abort();
         }
 
-        tracing::info!("Sending GET request to: {}", request_url);
-
-        // Send GET request to establish SSE connection with session
-        let response = self
-            .http_client
-            .get(request_url)
-            .header("Accept", "text/event-stream")
-            .send()
-            .await
-            .map_err(|e| TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("SSE GET request failed: {}", e),
-            })?;
+        // Stop background session discovery
+        if let Some(handle) = manager._discovery_task.take() {
+            handle.abort();
+        }
+        manager.session_receiver = None;
+        manager.jsonrpc_receiver = None;
+        if let Some(pipeline) = write(&self.pipeline).take() {
+            pipeline.shared.closed.store(true, Ordering::SeqCst);
+        }
+        self.correlator.cancel_all();
+        lock(&self.info).mark_disconnected();
 
-        let content_type = response
-            .headers()
-            .get(CONTENT_TYPE)
-            .and_then(|ct| ct.to_str().ok())
-            .unwrap_or("");
+        // Wake any pending receive before dropping the message channel
+        self.disconnected.notify_waiters();
+        self.messages.lock().await.take();
 
-        tracing::info!(
-            "SSE GET Response - Status: {}, Content-Type: {}",
-            response.status(),
-            content_type
-        );
-
-        if content_type.contains("text/event-stream") {
-            tracing::info!("SSE connection established via GET - handling SSE stream");
-            self.handle_sse_response(response).await?;
-
-            // For SSE connections, we need to wait for the response to our message
-            if let JsonRpcMessage::Request(req) = message {
-                tracing::info!("Waiting for SSE response to request ID: {}", req.id);
-                return Ok(Some(
-                    self.wait_for_sse_response(&req.id.to_string(), Duration::from_secs(10))
-                        .await?,
-                ));
-            }
-
-            Ok(None)
-        } else {
-            Err(TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Expected SSE stream but got: {}", content_type),
-            }
-            .into())
-        }
-    }
-
-    /// Get the most recent session ID from the background monitor (only for Legacy protocol)
-    async fn get_fresh_session_id(&mut self) -> Option<String> {
-        // For Modern protocol, don't use session monitor - use response headers instead
-        if self.session_manager.protocol_version == McpProtocolVersion::StreamableHttp {
-            return self.session_id.clone();
-        }
-
-        if let Some(ref receiver_arc) = self.session_manager.session_receiver {
-            if let Ok(mut receiver) = receiver_arc.lock() {
-                // Try to get the most recent session (non-blocking)
-                while let Ok(session_info) = receiver.try_recv() {
-                    tracing::info!("Received fresh session: {}", session_info);
-
-                    // Extract session ID from either URL format or direct ID
-                    if session_info.starts_with("/sse?sessionId=") {
-                        // Extract session ID from URL format
-                        if let Some(session_id) = session_info.split("sessionId=").nth(1) {
-                            self.session_id = Some(session_id.to_string());
-                            tracing::info!("Extracted session ID from URL: {}", session_id);
-                        }
-                    } else {
-                        // Direct session ID
-                        self.session_id = Some(session_info.clone());
-                        tracing::info!("Updated to fresh session ID: {}", session_info);
-                    }
-                }
-            }
-        }
-        self.session_id.clone()
-    }
-
-    /// Try to discover session information from a specific endpoint
-    #[allow(dead_code)]
-    async fn try_discover_session_from_endpoint(
-        &mut self,
-        endpoint: &str,
-    ) -> McpResult<Option<String>> {
-        let discovery_url =
-            self.base_url
-                .join(endpoint)
-                .map_err(|e| TransportError::InvalidConfig {
-                    transport_type: "streamable-http".to_string(),
-                    reason: format!("Invalid discovery endpoint {}: {}", endpoint, e),
-                })?;
-
-        tracing::debug!("Trying session discovery at: {}", discovery_url);
-
-        // Try to get session information via SSE stream
-        let response = self
-            .http_client
-            .get(discovery_url.clone())
-            .header("Accept", "text/event-stream, application/json")
-            .send()
-            .await
-            .map_err(|e| TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Discovery request failed: {}", e),
-            })?;
-
-        let content_type = response
-            .headers()
-            .get(CONTENT_TYPE)
-            .and_then(|ct| ct.to_str().ok())
-            .unwrap_or("");
-
-        match content_type {
-            ct if ct.contains("text/event-stream") => self.parse_session_from_sse(response).await,
-            ct if ct.contains("application/json") => self.parse_session_from_json(response).await,
-            _ => {
-                tracing::debug!(
-                    "Unexpected content type for session discovery: {}",
-                    content_type
-                );
-                Ok(None)
-            }
-        }
-    }
-
-    /// Parse session information from SSE stream (e.g., Playwright-style)
-    #[allow(dead_code)]
-    async fn parse_session_from_sse(&mut self, response: Response) -> McpResult<Option<String>> {
-        use futures::StreamExt;
-
-        let event_stream = response.bytes_stream().eventsource();
-        let mut stream = event_stream;
-
-        // Listen for the first few events to find session information
-        let timeout_duration = Duration::from_secs(5);
-        let deadline = tokio::time::Instant::now() + timeout_duration;
-
-        while let Ok(Some(event_result)) = tokio::time::timeout_at(deadline, stream.next()).await {
-            match event_result {
-                Ok(event) => {
-                    tracing::debug!("Discovery SSE event: {} -> {}", event.event, event.data);
-
-                    // Look for session information in various formats
-                    if let Some(session_info) = self.extract_session_from_event_data(&event.data) {
-                        return Ok(Some(session_info));
-                    }
-                }
-                Err(e) => {
-                    tracing::debug!("SSE discovery error: {}", e);
-                    break;
-                }
-            }
-        }
-
-        Ok(None)
-    }
-
-    /// Parse session information from JSON response
-    #[allow(dead_code)]
-    async fn parse_session_from_json(&mut self, response: Response) -> McpResult<Option<String>> {
-        let json_text = response
-            .text()
-            .await
-            .map_err(|e| TransportError::SerializationError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Failed to read JSON discovery response: {}", e),
-            })?;
-
-        // Try to parse as JSON and look for session information
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&json_text) {
-            // Look for session info in common JSON patterns
-            if let Some(session_id) = value
-                .get("sessionId")
-                .or_else(|| value.get("session_id"))
-                .or_else(|| value.get("session"))
-                .and_then(|v| v.as_str())
-            {
-                return Ok(Some(session_id.to_string()));
-            }
-
-            // Look for endpoint URL patterns
-            if let Some(endpoint) = value
-                .get("endpoint")
-                .or_else(|| value.get("url"))
-                .and_then(|v| v.as_str())
-            {
-                if let Some(session_info) = self.extract_session_from_event_data(endpoint) {
-                    return Ok(Some(session_info));
-                }
-            }
-        }
-
-        Ok(None)
-    }
-
-    /// Extract session information from event data (handles multiple formats)
-    #[allow(dead_code)]
-    fn extract_session_from_event_data(&self, data: &str) -> Option<String> {
-        // Pattern 1: Full URL path with session (/sse?sessionId=...) - preferred
-        if let Some(url_start) = data.find("/sse?sessionId=") {
-            let session_path = &data[url_start..];
-            if let Some(session_end) = session_path.find(|c: char| c.is_whitespace() || c == '\n') {
-                return Some(session_path[..session_end].to_string());
-            } else {
-                return Some(session_path.to_string());
-            }
-        }
-
-        // Pattern 2: Direct sessionId=value format (like Playwright) - extract just the ID
-        if let Some(captures) = regex::Regex::new(r"sessionId=([a-fA-F0-9\-]+)")
-            .ok()
-            .and_then(|re| re.captures(data))
-        {
-            if let Some(session_match) = captures.get(1) {
-                return Some(session_match.as_str().to_string());
-            }
-        }
-
-        // Pattern 3: JSON-like format
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(data) {
-            if let Some(session_id) = value
-                .get("sessionId")
-                .or_else(|| value.get("session_id"))
-                .and_then(|v| v.as_str())
-            {
-                return Some(session_id.to_string());
-            }
-        }
-
-        None
-    }
-
-    /// Update session information from discovered data  
-    #[allow(dead_code)]
-    fn apply_discovered_session(&mut self, session_info: &str) -> McpResult<()> {
-        // If the session info looks like a complete URL path, update base URL for /sse endpoint
-        if session_info.starts_with('/') && session_info.contains("sse") {
-            match self.base_url.join(session_info) {
-                Ok(new_url) => {
-                    tracing::info!(
-                        "Updated base URL to use discovered session endpoint: {}",
-                        new_url
-                    );
-                    self.session_manager.active_session_url = Some(new_url.clone());
-                    self.base_url = new_url;
-
-                    // Also extract session ID for any header usage
-                    if let Some(session_start) = session_info.find("sessionId=") {
-                        let id_part = &session_info[session_start + 10..]; // Skip "sessionId="
-                        if let Some(id_end) =
-                            id_part.find(|c: char| !c.is_alphanumeric() && c != '-')
-                        {
-                            self.session_id = Some(id_part[..id_end].to_string());
-                        } else {
-                            self.session_id = Some(id_part.to_string());
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to update URL with session path {}: {}",
-                        session_info,
-                        e
-                    );
-                    return Ok(());
-                }
-            }
-        } else {
-            // Use as session ID directly for header-based sessions (like /mcp endpoint)
-            tracing::info!(
-                "Using session ID for header-based requests: {}",
-                session_info
-            );
-            self.session_id = Some(session_info.to_string());
-        }
-
-        Ok(())
-    }
-
-    /// Wait for a specific response from the SSE stream or session monitor
-    async fn wait_for_sse_response(
-        &mut self,
-        request_id: &str,
-        timeout_duration: Duration,
-    ) -> McpResult<JsonRpcResponse> {
-        tracing::debug!("Waiting for response to request ID: {}", request_id);
-
-        // For Legacy protocol, check session monitor's JSON-RPC receiver first
-        if let Some(ref jsonrpc_receiver_arc) = self.session_manager.jsonrpc_receiver {
-            tracing::debug!("Checking session monitor for Legacy protocol response");
-
-            let deadline = tokio::time::Instant::now() + timeout_duration;
-
-            while tokio::time::Instant::now() < deadline {
-                if let Ok(mut receiver) = jsonrpc_receiver_arc.lock() {
-                    match receiver.try_recv() {
-                        Ok(message) => {
-                            tracing::info!("Received message from session monitor: {:?}", message);
-                            match message {
-                                JsonRpcMessage::Response(response) => {
-                                    if response.id.to_string() == request_id {
-                                        tracing::info!("Found matching response via session monitor for request ID: {}", request_id);
-                                        self.info.increment_responses_received();
-                                        return Ok(response);
-                                    } else {
-                                        tracing::debug!(
-                                            "Response for different request ID: {} (expected: {})",
-                                            response.id,
-                                            request_id
-                                        );
-                                    }
-                                }
-                                _ => {
-                                    tracing::debug!("Non-response message from session monitor");
-                                }
-                            }
-                        }
-                        Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
-                            // No message available, continue checking
-                        }
-                        Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
-                            tracing::warn!("Session monitor JSON-RPC channel disconnected");
-                            break;
-                        }
-                    }
-                }
-
-                // Small delay before checking again
-                tokio::time::sleep(Duration::from_millis(50)).await;
-            }
-        }
-
-        // Fallback to main SSE receiver for Modern protocol
-        if let Some(receiver) = self.sse_receiver.as_mut() {
-            tracing::debug!("Checking main SSE receiver for Modern protocol response");
-
-            let deadline = tokio::time::Instant::now() + timeout_duration;
-
-            loop {
-                let remaining_time =
-                    deadline.saturating_duration_since(tokio::time::Instant::now());
-                if remaining_time.is_zero() {
-                    break;
-                }
-
-                let message = timeout(remaining_time, receiver.recv())
-                    .await
-                    .map_err(|_| TransportError::TimeoutError {
-                        transport_type: "streamable-http".to_string(),
-                        reason: format!("SSE response timeout for request ID: {}", request_id),
-                    })?
-                    .ok_or_else(|| TransportError::DisconnectedError {
-                        transport_type: "streamable-http".to_string(),
-                        reason: "SSE stream closed while waiting for response".to_string(),
-                    })?;
-
-                match message {
-                    JsonRpcMessage::Response(response) => {
-                        if response.id.to_string() == request_id {
-                            tracing::info!(
-                                "Found matching response via main SSE for request ID: {}",
-                                request_id
-                            );
-                            self.info.increment_responses_received();
-                            return Ok(response);
-                        } else {
-                            tracing::debug!(
-                                "Response for different request ID: {} (expected: {})",
-                                response.id,
-                                request_id
-                            );
-                        }
-                    }
-                    _ => {
-                        tracing::debug!("Non-response message from main SSE");
-                    }
-                }
-            }
-        }
-
-        Err(TransportError::TimeoutError {
-            transport_type: "streamable-http".to_string(),
-            reason: format!("Timeout waiting for response to request ID: {}", request_id),
-        }
-        .into())
-    }
-}
-
-#[async_trait]
-impl Transport for HttpSseTransport {
-    async fn connect(&mut self) -> McpResult<()> {
-        tracing::info!("Connecting Streamable HTTP transport to: {}", self.base_url);
-
-        // Step 1: Start continuous session monitoring for MCP servers that require it
-        self.start_continuous_session_monitoring().await?;
-
-        // Step 2: Test connectivity with a simple request
-        let test_response = self.http_client.head(self.base_url.clone()).send().await;
-
-        match test_response {
-            Ok(_) => {
-                self.info.mark_connected();
-                tracing::info!("Streamable HTTP transport connected successfully");
-                Ok(())
-            }
-            Err(e) => Err(TransportError::ConnectionError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("Failed to connect to server: {}", e),
-            }
-            .into()),
-        }
-    }
-
-    async fn disconnect(&mut self) -> McpResult<()> {
-        tracing::info!("Disconnecting Streamable HTTP transport");
-
-        // Terminate session if we have one
-        if let Some(ref session_id) = self.session_id {
-            let _ = self
-                .http_client
-                .delete(self.base_url.clone())
-                .header("Mcp-Session-Id", session_id)
-                .send()
-                .await;
-        }
-
-        // Clean up SSE resources
-        self.sse_receiver = None;
-        if let Some(handle) = self._sse_task_handle.take() {
-            handle.abort();
-        }
-
-        self.session_id = None;
-        self.info.mark_disconnected();
-
-        tracing::info!("Streamable HTTP transport disconnected");
-        Ok(())
-    }
+        tracing::info!("Streamable HTTP transport disconnected");
+        Ok(())
+    }
 
     fn is_connected(&self) -> bool {
-        self.info.connected
+        lock(&self.info).connected
     }
 
     async fn send_request(
-        &mut self,
+        &self,
         request: JsonRpcRequest,
         timeout_duration: Option<Duration>,
     ) -> McpResult<JsonRpcResponse> {
         if !self.is_connected() {
-            return Err(TransportError::NotConnected {
-                transport_type: "streamable-http".to_string(),
-                reason: "Transport not connected".to_string(),
-            }
-            .into());
+            return Err(Self::not_connected().into());
         }
+        let pipeline = self.pipeline()?;
 
-        let request_id = request.id.to_string();
         tracing::debug!(
             "HTTP SSE transport sending request: {} with ID: {}",
             request.method,
-            request_id
+            request.id
         );
         let timeout_duration = timeout_duration.unwrap_or(Duration::from_secs(30));
+        let response = pipeline.send_request(request, timeout_duration).await?;
 
-        // Send request with timeout
-        let response = timeout(
-            timeout_duration,
-            self.send_mcp_request(JsonRpcMessage::Request(request)),
-        )
-        .await
-        .map_err(|_| TransportError::TimeoutError {
-            transport_type: "streamable-http".to_string(),
-            reason: format!("Request timed out after {:?}", timeout_duration),
-        })??;
-
-        self.info.increment_requests_sent();
-
-        match response {
-            Some(json_response) => {
-                tracing::debug!(
-                    "HTTP SSE transport received direct JSON response for request ID: {}",
-                    json_response.id
-                );
-                self.info.increment_responses_received();
-                Ok(json_response)
-            }
-            None => {
-                // Response will come via SSE stream - wait for it
-                tracing::debug!(
-                    "HTTP SSE transport: waiting for response via SSE stream for request ID: {}",
-                    request_id
-                );
-                self.wait_for_sse_response(&request_id, timeout_duration)
-                    .await
-            }
-        }
+        let mut info = lock(&self.info);
+        info.increment_requests_sent();
+        info.increment_responses_received();
+        Ok(response)
     }
 
-    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
         if !self.is_connected() {
-            return Err(TransportError::NotConnected {
-                transport_type: "streamable-http".to_string(),
-                reason: "Transport not connected".to_string(),
-            }
-            .into());
+            return Err(Self::not_connected().into());
         }
 
         tracing::debug!(
             "HTTP SSE transport sending notification: {}",
             notification.method
         );
+        self.pipeline()?.send_notification(notification).await?;
 
-        // Notifications don't expect responses - send directly without parsing response
-        let mut request_builder = self
-            .http_client
-            .post(self.base_url.clone())
-            .header(CONTENT_TYPE, "application/json")
-            .header("Accept", "application/json, text/event-stream");
-
-        // Validate Origin header for security
-        self.validate_origin(&request_builder)?;
-
-        // Include session ID if we have one
-        if let Some(ref session_id) = self.session_id {
-            request_builder = request_builder.header("Mcp-Session-Id", session_id);
-        }
-
-        // Send the notification - ignore response content
-        let _response = request_builder
-            .json(&JsonRpcMessage::Notification(notification))
-            .send()
-            .await
-            .map_err(|e| TransportError::NetworkError {
-                transport_type: "streamable-http".to_string(),
-                reason: format!("HTTP notification failed: {}", e),
-            })?;
-
-        self.info.increment_notifications_sent();
+        lock(&self.info).increment_notifications_sent();
         tracing::debug!("HTTP SSE transport notification sent successfully");
         Ok(())
     }
 
+    async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        if !self.is_connected() {
+            return Err(Self::not_connected().into());
+        }
+        self.pipeline()?.send_response(response).await
+    }
+
     async fn receive_message(
-        &mut self,
+        &self,
         timeout_duration: Option<Duration>,
     ) -> McpResult<JsonRpcMessage> {
         if !self.is_connected() {
-            return Err(TransportError::NotConnected {
-                transport_type: "streamable-http".to_string(),
-                reason: "Transport not connected".to_string(),
-            }
-            .into());
+            return Err(Self::not_connected().into());
         }
 
-        let receiver = self
-            .sse_receiver
-            .as_mut()
-            .ok_or_else(|| TransportError::NotConnected {
-                transport_type: "streamable-http".to_string(),
-                reason: "No SSE stream available - server uses single JSON responses".to_string(),
-            })?;
+        // Concurrent receivers take turns; each message goes to exactly one
+        let mut messages = self.messages.lock().await;
+        let queue = messages.as_mut().ok_or_else(Self::not_connected)?;
+        let receiver = async {
+            tokio::select! {
+                message = queue.recv() => message,
+                _ = self.disconnected.notified() => None,
+            }
+        };
 
         let message = if let Some(timeout_duration) = timeout_duration {
-            timeout(timeout_duration, receiver.recv())
+            timeout(timeout_duration, receiver)
                 .await
                 .map_err(|_| TransportError::TimeoutError {
                     transport_type: "streamable-http".to_string(),
@@ -1625,13 +871,13 @@ impl Transport for HttpSseTransport {
                 })?
         } else {
             receiver
-                .recv()
                 .await
                 .ok_or_else(|| TransportError::DisconnectedError {
                     transport_type: "streamable-http".to_string(),
                     reason: "SSE stream closed".to_string(),
                 })?
         };
+        drop(messages);
 
         // Update statistics
         match &message {
@@ -1639,28 +885,45 @@ impl Transport for HttpSseTransport {
                 // Server-to-client request via SSE
             }
             JsonRpcMessage::Response(_) => {
-                self.info.increment_responses_received();
+                lock(&self.info).increment_responses_received();
             }
             JsonRpcMessage::Notification(_) => {
-                self.info.increment_notifications_received();
+                lock(&self.info).increment_notifications_received();
             }
         }
 
         Ok(message)
     }
 
+    fn protocol_diagnostics(&self) -> Option<broadcast::Receiver<ProtocolDiagnostic>> {
+        Some(self.frames.subscribe())
+    }
+
     fn get_info(&self) -> TransportInfo {
-        let mut info = self.info.clone();
+        let mut info = lock(&self.info).clone();
 
         // Add Streamable HTTP specific metadata
         info.add_metadata("base_url", serde_json::json!(self.base_url.to_string()));
-        info.add_metadata("session_id", serde_json::json!(self.session_id));
+        info.add_metadata("session_id", serde_json::json!(self.session_id()));
         info.add_metadata(
             "has_sse_stream",
-            serde_json::json!(self.sse_receiver.is_some()),
+            serde_json::json!(lock(&self.sse_task).is_some()),
         );
-        info.add_metadata("last_event_id", serde_json::json!(self.last_event_id));
+        info.add_metadata("last_event_id", serde_json::json!(self.last_event_id()));
+        if self.compression.is_enabled() {
+            let stats = self.compression_stats();
+            info.add_metadata(
+                "compression_bytes_saved",
+                serde_json::json!(stats.bytes_saved()),
+            );
+            info.add_metadata("compression", serde_json::json!(stats));
+        }
         info.add_metadata("can_resume", serde_json::json!(self.can_resume()));
+        info.add_metadata("correlation", serde_json::json!(self.correlator.stats()));
+        if let Some(pipeline) = &*read(&self.pipeline) {
+            info.add_metadata("in_flight", serde_json::json!(pipeline.in_flight()));
+            info.add_metadata("max_in_flight", serde_json::json!(pipeline.max_in_flight()));
+        }
         info.add_metadata(
             "security_enabled",
             serde_json::json!(self.security_config.validate_origin),
@@ -1678,6 +941,9 @@ impl Transport for HttpSseTransport {
                 "localhost_only",
                 serde_json::json!(self.security_config.enforce_localhost),
             );
+            if !config.connection.is_default() {
+                info.add_metadata("connection", serde_json::json!(config.connection));
+            }
         }
 
         info
@@ -1688,6 +954,429 @@ impl Transport for HttpSseTransport {
     }
 }
 
+/// Lock a mutex, recovering the data if a holder panicked
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read<T>(lock: &RwLock<T>) -> std::sync::RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Error for a request that failed to send, keeping origin rejections apart
+fn send_error(error: reqwest::Error, context: &str) -> TransportError {
+    match origin::rejection(&error) {
+        Some(rejection) => rejection.into(),
+        // Nothing reached the server, so even a tool call may be retried
+        None if error.is_connect() => TransportError::ConnectionFailed {
+            transport_type: "streamable-http".to_string(),
+            reason: format!("{}: {}", context, error),
+        },
+        None => TransportError::NetworkError {
+            transport_type: "streamable-http".to_string(),
+            reason: format!("{}: {}", context, error),
+        },
+    }
+}
+
+/// Check that a server-assigned session ID looks cryptographically random
+fn check_session_id(session_id: &str) -> McpResult<()> {
+    // Check session ID format (should be cryptographically secure)
+    if session_id.len() < 16 {
+        return Err(TransportError::InvalidConfig {
+            transport_type: "streamable-http".to_string(),
+            reason: "Session ID too short - security risk".to_string(),
+        }
+        .into());
+    }
+
+    // Check for basic format (alphanumeric and hyphens)
+    if !session_id.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        return Err(TransportError::InvalidConfig {
+            transport_type: "streamable-http".to_string(),
+            reason: "Session ID contains invalid characters".to_string(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Hand responses to their waiters and forward everything else
+async fn route_responses(
+    mut source: mpsc::UnboundedReceiver<JsonRpcMessage>,
+    correlator: RequestCorrelator,
+    unclaimed: mpsc::UnboundedSender<JsonRpcMessage>,
+) {
+    while let Some(message) = source.recv().await {
+        let message = match message {
+            JsonRpcMessage::Response(response) => match correlator.complete(response) {
+                None => continue,
+                Some(response) => JsonRpcMessage::Response(response),
+            },
+            other => other,
+        };
+        if unclaimed.send(message).is_err() {
+            break;
+        }
+    }
+}
+
+/// Shared state behind every [`HttpSsePipeline`] handle
+struct PipelineShared {
+    http_client: Client,
+    base_url: Url,
+    legacy: bool,
+    session_id: RwLock<Option<String>>,
+    session_receiver: Option<Arc<Mutex<mpsc::UnboundedReceiver<String>>>>,
+    security: SecurityConfig,
+    correlator: RequestCorrelator,
+    frames: FrameParser,
+    limits: MessageLimits,
+    compression: Compression,
+    slots: Arc<Semaphore>,
+    max_in_flight: usize,
+    inbound: mpsc::UnboundedSender<JsonRpcMessage>,
+    closed: AtomicBool,
+}
+
+/// Concurrent request handle for an [`HttpSseTransport`] session.
+///
+/// Created by [`HttpSseTransport::pipeline`]. Clones share the in-flight
+/// limit, so many tasks can issue requests at once without exceeding it.
+#[derive(Clone)]
+pub struct HttpSsePipeline {
+    shared: Arc<PipelineShared>,
+}
+
+impl HttpSsePipeline {
+    /// Send a request and wait up to `timeout_duration` for its response.
+    ///
+    /// The timeout includes time spent waiting for an in-flight slot.
+    pub async fn send_request(
+        &self,
+        request: JsonRpcRequest,
+        timeout_duration: Duration,
+    ) -> McpResult<JsonRpcResponse> {
+        let request_id = request.id.to_string();
+        match timeout(timeout_duration, self.exchange(request)).await {
+            Ok(result) => result,
+            Err(_) => Err(TransportError::TimeoutError {
+                transport_type: "streamable-http".to_string(),
+                reason: format!(
+                    "Request {} timed out after {:?}",
+                    request_id, timeout_duration
+                ),
+            }
+            .into()),
+        }
+    }
+
+    /// Send a notification without taking an in-flight slot
+    pub async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.ensure_open()?;
+        let message = JsonRpcMessage::Notification(notification);
+        self.shared
+            .limits
+            .check_outbound("streamable-http", &message)?;
+        self.post(&message).await?;
+        Ok(())
+    }
+
+    /// Answer a request the server sent
+    pub async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        self.ensure_open()?;
+        let message = JsonRpcMessage::Response(response);
+        self.shared
+            .limits
+            .check_outbound("streamable-http", &message)?;
+        self.post(&message).await?;
+        Ok(())
+    }
+
+    /// Requests currently outstanding
+    pub fn in_flight(&self) -> usize {
+        self.shared.max_in_flight - self.shared.slots.available_permits()
+    }
+
+    /// Most requests allowed outstanding at once
+    pub fn max_in_flight(&self) -> usize {
+        self.shared.max_in_flight
+    }
+
+    fn ensure_open(&self) -> McpResult<()> {
+        if self.shared.closed.load(Ordering::SeqCst) {
+            return Err(TransportError::NotConnected {
+                transport_type: "streamable-http".to_string(),
+                reason: "Transport was disconnected".to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    async fn exchange(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        self.ensure_open()?;
+        let _slot = self
+            .shared
+            .slots
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| TransportError::NotConnected {
+                transport_type: "streamable-http".to_string(),
+                reason: "Pipeline closed".to_string(),
+            })?;
+
+        let id = request.id.clone();
+        let message = JsonRpcMessage::Request(request);
+        self.shared
+            .limits
+            .check_outbound("streamable-http", &message)?;
+
+        // Register before sending so a fast response cannot be missed
+        let pending = self.shared.correlator.register(&id)?;
+        let response = self.post(&message).await?;
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+
+        if content_type.contains("application/json") {
+            let limit = self.shared.limits.max_inbound;
+            let body = self.shared.compression.decode(response);
+            let frame = limits::read_body(body, &mut self.shared.limits.inbound())
+                .await
+                .map_err(|e| TransportError::ReceiveFailed {
+                    transport_type: "streamable-http".to_string(),
+                    reason: format!("Failed to read pipelined JSON response: {}", e),
+                })?;
+            let (body, truncated) = match frame {
+                LimitedFrame::Complete(body) => (body, None),
+                LimitedFrame::Truncated { frame, size } => {
+                    let reason =
+                        format!("message of {} bytes cut to the {}-byte limit", size, limit);
+                    tracing::warn!("Truncated incoming {}", reason);
+                    self.shared.frames.oversized(&frame, reason, false);
+                    (frame, Some(size))
+                }
+                LimitedFrame::Rejected {
+                    size, head, reason, ..
+                } => {
+                    self.shared.frames.oversized(&head, reason, true);
+                    return Err(TransportError::MessageTooLarge {
+                        transport_type: "streamable-http".to_string(),
+                        size,
+                        limit,
+                    }
+                    .into());
+                }
+            };
+            let mut message = match self.shared.frames.decode(&body) {
+                Ok(message) => message,
+                Err(diagnostic) => {
+                    return Err(TransportError::SerializationError {
+                        transport_type: "streamable-http".to_string(),
+                        reason: format!(
+                            "Failed to parse pipelined JSON response: {}",
+                            diagnostic.message
+                        ),
+                    }
+                    .into())
+                }
+            };
+            if let Some(size) = truncated {
+                limits::mark_truncated(&mut message, size, limit);
+            }
+            let JsonRpcMessage::Response(reply) = message else {
+                return Err(TransportError::SerializationError {
+                    transport_type: "streamable-http".to_string(),
+                    reason: format!("Expected a response to request {}", pending.id()),
+                }
+                .into());
+            };
+            if let Some(other) = self.shared.correlator.complete(reply) {
+                return Err(TransportError::SerializationError {
+                    transport_type: "streamable-http".to_string(),
+                    reason: format!(
+                        "Server answered request {} with response ID {}",
+                        pending.id(),
+                        other.id
+                    ),
+                }
+                .into());
+            }
+        } else if content_type.contains("text/event-stream") {
+            tokio::spawn(read_event_stream(
+                self.shared.correlator.clone(),
+                self.shared.frames.clone(),
+                self.shared.limits.clone(),
+                self.shared.compression.decode(response),
+                self.shared.inbound.clone(),
+            ));
+        } else if !(self.shared.legacy && response.status().as_u16() == 202) {
+            return Err(TransportError::NetworkError {
+                transport_type: "streamable-http".to_string(),
+                reason: format!(
+                    "Unexpected pipelined response - Status: {}, Content-Type: {}",
+                    response.status(),
+                    content_type
+                ),
+            }
+            .into());
+        }
+        // A 202 on the legacy protocol means the answer arrives via the session monitor
+
+        // The caller's timeout bounds this wait
+        Ok(pending.wait(Duration::MAX).await?)
+    }
+
+    async fn post(&self, message: &JsonRpcMessage) -> McpResult<Response> {
+        let mut session_id = self.current_session_id();
+        // Legacy servers announce the session on the monitor stream shortly after connecting
+        let mut attempts = 0;
+        while self.shared.legacy && session_id.is_none() && attempts < 50 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            session_id = self.current_session_id();
+            attempts += 1;
+        }
+        if self.shared.legacy && session_id.is_none() {
+            tracing::warn!("No session ID available for Legacy request after waiting");
+        }
+        let mut url = self.shared.base_url.clone();
+        if self.shared.legacy {
+            if let Some(session_id) = &session_id {
+                url.set_query(Some(&format!("sessionId={}", session_id)));
+            }
+        }
+        let body = serde_json::to_vec(message).map_err(|e| TransportError::SerializationError {
+            transport_type: "streamable-http".to_string(),
+            reason: format!("Failed to serialize message: {}", e),
+        })?;
+        let body = Bytes::from(body);
+
+        let response = loop {
+            let mut request_builder = self.shared.http_client.post(url.clone());
+            if !self.shared.legacy {
+                if let Some(session_id) = &session_id {
+                    request_builder = request_builder.header("Mcp-Session-Id", session_id);
+                }
+            }
+            let request_builder = request_builder
+                .header(CONTENT_TYPE, "application/json")
+                .header("Accept", "application/json, text/event-stream");
+            let (request_builder, compressed) =
+                self.shared.compression.body(request_builder, body.clone());
+
+            let response = request_builder
+                .send()
+                .await
+                .map_err(|e| send_error(e, "Pipelined HTTP request failed"))?;
+            if compressed && response.status() == StatusCode::UNSUPPORTED_MEDIA_TYPE {
+                // Requests go uncompressed from now on
+                self.shared.compression.refuse();
+                continue;
+            }
+            break response;
+        };
+        self.shared.security.check_response(&response)?;
+        if let Some(throttled) = retry::throttled(&response) {
+            return Err(throttled.into());
+        }
+
+        if let Some(session_str) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|header| header.to_str().ok())
+        {
+            if self.shared.security.validate_session_ids {
+                check_session_id(session_str)?;
+            }
+            if let Ok(mut session_id) = self.shared.session_id.write() {
+                *session_id = Some(session_str.to_string());
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Session to send with, picking up any fresh legacy session first
+    fn current_session_id(&self) -> Option<String> {
+        if let Some(receiver_arc) = &self.shared.session_receiver {
+            if let Ok(mut receiver) = receiver_arc.lock() {
+                while let Ok(session_info) = receiver.try_recv() {
+                    let fresh = match session_info.split("sessionId=").nth(1) {
+                        Some(session_id) => session_id.to_string(),
+                        None => session_info,
+                    };
+                    if let Ok(mut session_id) = self.shared.session_id.write() {
+                        *session_id = Some(fresh);
+                    }
+                }
+            }
+        }
+        self.shared
+            .session_id
+            .read()
+            .ok()
+            .and_then(|session_id| session_id.clone())
+    }
+}
+
+impl std::fmt::Debug for HttpSsePipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpSsePipeline")
+            .field("base_url", &self.shared.base_url.as_str())
+            .field("in_flight", &self.in_flight())
+            .field("max_in_flight", &self.shared.max_in_flight)
+            .finish()
+    }
+}
+
+/// Read one per-request SSE stream, completing responses and forwarding the rest
+async fn read_event_stream(
+    correlator: RequestCorrelator,
+    frames: FrameParser,
+    limits: MessageLimits,
+    body: BoxStream<'static, io::Result<Bytes>>,
+    inbound: mpsc::UnboundedSender<JsonRpcMessage>,
+) {
+    let mut limiter = limits.inbound();
+    let mut stream = body.eventsource();
+    while let Some(event) = stream.next().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!("Pipelined SSE stream error: {}", e);
+                break;
+            }
+        };
+        limiter.push(event.data.as_bytes());
+        let frame = limiter.finish();
+        let Some(message) = limits::decode_frame(&frames, &correlator, frame, limits.max_inbound)
+        else {
+            continue;
+        };
+        let message = match message {
+            JsonRpcMessage::Response(response) => match correlator.complete(response) {
+                None => continue,
+                Some(response) => JsonRpcMessage::Response(response),
+            },
+            other => other,
+        };
+        if inbound.send(message).is_err() {
+            break;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1742,16 +1431,16 @@ mod tests {
         let config = TransportConfig::http_sse("http://localhost:3000/mcp").unwrap();
         let transport = HttpSseTransport::new(config).unwrap();
 
+        assert!(transport.security_config.validate_session_ids);
+
         // Valid session ID
-        assert!(transport
-            .validate_session_id("550e8400-e29b-41d4-a716-446655440000")
-            .is_ok());
+        assert!(check_session_id("550e8400-e29b-41d4-a716-446655440000").is_ok());
 
         // Invalid session ID (too short)
-        assert!(transport.validate_session_id("short").is_err());
+        assert!(check_session_id("short").is_err());
 
         // Invalid session ID (invalid characters)
-        assert!(transport.validate_session_id("invalid@session!id").is_err());
+        assert!(check_session_id("invalid@session!id").is_err());
     }
 
     #[test]
@@ -1763,4 +1452,235 @@ mod tests {
         assert!(!transport.can_resume());
         assert!(transport.last_event_id().is_none());
     }
+    /// Answers every request with a JSON result echoing its ID
+    struct EchoId;
+
+    impl wiremock::Respond for EchoId {
+        fn respond(&self, request: &wiremock::Request) -> wiremock::ResponseTemplate {
+            let request: JsonRpcRequest = serde_json::from_slice(&request.body).unwrap();
+            wiremock::ResponseTemplate::new(200)
+                .set_body_json(JsonRpcResponse::success(
+                    request.id.clone(),
+                    serde_json::json!({ "echo": request.id.to_string() }),
+                ))
+                .set_delay(Duration::from_millis(150))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_runs_requests_concurrently_within_limit() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(EchoId)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/mcp", server.uri()).parse().unwrap();
+        let config =
+            TransportConfig::HttpSse(crate::transport::HttpSseConfig::new(url).max_in_flight(4));
+        let transport = HttpSseTransport::new(config).unwrap();
+        assert!(transport.pipeline().is_err());
+        transport.connect().await.unwrap();
+        let pipeline = transport.pipeline().unwrap();
+
+        let started = tokio::time::Instant::now();
+        let calls = (0..8).map(|i| {
+            let pipeline = pipeline.clone();
+            tokio::spawn(async move {
+                let request =
+                    JsonRpcRequest::new(format!("req-{}", i), "tools/call", serde_json::json!({}));
+                pipeline.send_request(request, Duration::from_secs(5)).await
+            })
+        });
+        let responses = futures::future::join_all(calls).await;
+        let elapsed = started.elapsed();
+
+        for (i, response) in responses.into_iter().enumerate() {
+            let response = response.unwrap().unwrap();
+            assert_eq!(response.result.unwrap()["echo"], format!("req-{}", i));
+        }
+        // Two waves of four: faster than one at a time, slower than all at once
+        assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1200), "{:?}", elapsed);
+        assert_eq!(pipeline.in_flight(), 0);
+        assert_eq!(transport.correlator.stats().completed, 8);
+
+        transport.disconnect().await.unwrap();
+        let request = JsonRpcRequest::new("late", "tools/list", serde_json::json!({}));
+        assert!(pipeline
+            .send_request(request, Duration::from_secs(1))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_limited_to_concurrent_streams() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(EchoId)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/mcp", server.uri()).parse().unwrap();
+        let config = crate::transport::HttpSseConfig::new(url)
+            .max_in_flight(8)
+            .connection(
+                crate::transport::HttpConnectionConfig::default().max_concurrent_streams(3),
+            );
+        let transport = HttpSseTransport::new(TransportConfig::HttpSse(config)).unwrap();
+        transport.connect().await.unwrap();
+        assert_eq!(transport.pipeline().unwrap().max_in_flight(), 3);
+        transport.disconnect().await.unwrap();
+    }
+
+    /// Answers with a gzipped SSE stream, noting whether the request was
+    /// compressed, and says it accepts gzipped requests
+    struct GzipSse;
+
+    impl wiremock::Respond for GzipSse {
+        fn respond(&self, request: &wiremock::Request) -> wiremock::ResponseTemplate {
+            use crate::transport::compression::{compress, decompress, Encoding, ACCEPT};
+
+            assert_eq!(request.headers["accept-encoding"].to_str().unwrap(), ACCEPT);
+            let compressed = request.headers.contains_key("content-encoding");
+            let body = if compressed {
+                decompress(Encoding::Gzip, &request.body).unwrap()
+            } else {
+                request.body.clone()
+            };
+            let request: JsonRpcRequest = serde_json::from_slice(&body).unwrap();
+            let reply = JsonRpcResponse::success(
+                request.id.clone(),
+                serde_json::json!({ "compressed": compressed, "padding": "x".repeat(2000) }),
+            );
+            let event = format!(
+                "event: message\ndata: {}\n\n",
+                serde_json::to_string(&reply).unwrap()
+            );
+            wiremock::ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .insert_header("Accept-Encoding", "gzip")
+                .set_body_raw(
+                    compress(Encoding::Gzip, event.as_bytes()).unwrap(),
+                    "text/event-stream",
+                )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compressed_bodies_both_ways() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(GzipSse)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/mcp", server.uri()).parse().unwrap();
+        let transport = HttpSseTransport::new(TransportConfig::HttpSse(
+            crate::transport::HttpSseConfig::new(url),
+        ))
+        .unwrap();
+        transport.connect().await.unwrap();
+        let pipeline = transport.pipeline().unwrap();
+
+        let request = JsonRpcRequest::new("1", "tools/list", serde_json::json!({}));
+        let response = pipeline
+            .send_request(request, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["compressed"], false);
+
+        // The server said it takes gzip, so a large request goes compressed
+        let request = JsonRpcRequest::new(
+            "2",
+            "tools/call",
+            serde_json::json!({ "name": "echo", "arguments": { "text": "y".repeat(4000) } }),
+        );
+        let response = pipeline
+            .send_request(request, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["compressed"], true);
+
+        let stats = transport.compression_stats();
+        assert_eq!(stats.requests_compressed, 1);
+        assert_eq!(stats.responses_compressed, 2);
+        assert!(stats.bytes_saved() > 4000, "{:?}", stats);
+        assert_eq!(
+            transport.get_info().metadata["compression_bytes_saved"],
+            serde_json::json!(stats.bytes_saved())
+        );
+        transport.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_redirect_off_origin_is_refused() {
+        use crate::error::{McpError, OriginError};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .respond_with(
+                ResponseTemplate::new(307).insert_header("Location", "http://evil.example/mcp"),
+            )
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/mcp", server.uri()).parse().unwrap();
+        let transport = HttpSseTransport::new(TransportConfig::HttpSse(
+            crate::transport::HttpSseConfig::new(url),
+        ))
+        .unwrap();
+        transport.connect().await.unwrap();
+        let request = JsonRpcRequest::new("1", "tools/list", serde_json::json!({}));
+        let error = transport
+            .pipeline()
+            .unwrap()
+            .send_request(request, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                McpError::Transport(TransportError::OriginRejected(
+                    OriginError::OriginNotAllowed { ref origin }
+                )) if origin == "http://evil.example"
+            ),
+            "{error}"
+        );
+        transport.disconnect().await.unwrap();
+
+        // Redirects within the server's own origin are still followed
+        server.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .respond_with(ResponseTemplate::new(307).insert_header("Location", "/moved"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/moved"))
+            .respond_with(EchoId)
+            .mount(&server)
+            .await;
+        transport.connect().await.unwrap();
+        let request = JsonRpcRequest::new("2", "tools/list", serde_json::json!({}));
+        let response = transport
+            .pipeline()
+            .unwrap()
+            .send_request(request, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["echo"], "2");
+    }
 }
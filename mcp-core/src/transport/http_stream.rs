@@ -4,22 +4,32 @@
 //! - Single /mcp endpoint for all communication
 //! - Session management via mcp-session-id headers
 //! - Simple request/response pattern
+//! - Compressed request and response bodies, see [`compression`](super::compression)
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, RwLock};
 use std::time::Duration;
 
 use async_trait::async_trait;
-use reqwest::Client;
-use tokio::sync::{oneshot, Mutex};
+use bytes::Bytes;
+use futures::StreamExt;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Response, StatusCode};
+use tokio::sync::{broadcast, Semaphore};
 use tokio::time::timeout;
 use tracing::{debug, info};
 
-use super::{Transport, TransportConfig, TransportInfo};
+use super::compression::{Compression, CompressionStats};
+use super::framing::{FrameParser, ParseMode, ProtocolDiagnostic};
+use super::limits::{self, LimitedFrame, MessageLimits};
+use super::origin::{self, OriginPolicy};
+use super::{HttpConnectionConfig, Transport, TransportConfig, TransportInfo};
+use crate::correlator::RequestCorrelator;
 use crate::error::{McpError, McpResult, TransportError};
 use crate::messages::{
     JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestId,
 };
+use crate::retry;
 
 /// MCP Streamable HTTP transport implementation (2025-03-26)
 pub struct HttpStreamTransport {
@@ -32,19 +42,37 @@ pub struct HttpStreamTransport {
     /// Transport configuration
     config: TransportConfig,
     /// Current session ID from server
-    session_id: Option<String>,
+    session_id: RwLock<Option<String>>,
     /// Transport information
-    info: TransportInfo,
+    info: Mutex<TransportInfo>,
     /// Pending requests awaiting responses
-    pending_requests: Arc<Mutex<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>,
+    correlator: RequestCorrelator,
     /// Whether we're connected
-    connected: bool,
+    connected: AtomicBool,
+    /// Where responses may come from, when the base URL parses
+    origins: Option<OriginPolicy>,
+    /// Checks response frames and reports what is wrong with them
+    frames: FrameParser,
+    /// Size limits on requests and response bodies
+    limits: MessageLimits,
+    /// Compression negotiated with the server, and its counters
+    compression: Compression,
+    /// One permit per request allowed on the connection at once
+    streams: Option<Semaphore>,
 }
 
 impl HttpStreamTransport {
     /// Create a new MCP Streamable HTTP transport.
     pub fn new(base_url: String, auth_header: Option<String>) -> Self {
-        let client = Client::new();
+        // Redirects stay on the server's origin
+        let origins = base_url.parse().ok().map(|url| OriginPolicy::for_url(&url));
+        let client = match origins {
+            Some(ref origins) => Client::builder()
+                .redirect(origin::redirect_policy(origins.clone()))
+                .build()
+                .unwrap_or_default(),
+            None => Client::new(),
+        };
 
         Self {
             client,
@@ -59,12 +87,90 @@ impl HttpStreamTransport {
                 auth: auth_header.map(crate::transport::config::AuthConfig::bearer),
                 compression: true,
                 flow_control_window: 65536,
+                parse_mode: ParseMode::Lenient,
+                limits: MessageLimits::default(),
+                connection: HttpConnectionConfig::default(),
             }),
-            session_id: None,
-            info: TransportInfo::new("http-stream"),
-            pending_requests: Arc::new(Mutex::new(HashMap::new())),
-            connected: false,
+            session_id: RwLock::new(None),
+            info: Mutex::new(TransportInfo::new("http-stream")),
+            correlator: RequestCorrelator::new("http-stream"),
+            connected: AtomicBool::new(false),
+            origins,
+            frames: FrameParser::new("http-stream", ParseMode::Lenient),
+            limits: MessageLimits::default(),
+            compression: Compression::new(true),
+            streams: None,
+        }
+    }
+
+    /// Use `connection`'s HTTP/2 and connection pool settings.
+    ///
+    /// The client is rebuilt with them; requests beyond the stream limit
+    /// wait for an earlier one to finish.
+    pub fn with_connection(mut self, connection: HttpConnectionConfig) -> Self {
+        let mut builder = connection.apply(Client::builder());
+        if let Some(ref origins) = self.origins {
+            builder = builder.redirect(origin::redirect_policy(origins.clone()));
+        }
+        match builder.build() {
+            Ok(client) => self.client = client,
+            Err(e) => {
+                tracing::warn!("Keeping default HTTP client, connection settings refused: {e}")
+            }
+        }
+        self.streams = connection.max_concurrent_streams.map(Semaphore::new);
+        if let TransportConfig::HttpStream(ref mut config) = self.config {
+            config.connection = connection;
+        }
+        self
+    }
+
+    /// Check responses against JSON-RPC 2.0 as strictly as `mode` says.
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        if let TransportConfig::HttpStream(ref mut config) = self.config {
+            config.parse_mode = mode;
+        }
+        self.frames = FrameParser::new("http-stream", mode);
+        self
+    }
+
+    /// Ask for compressed responses and compress requests the server
+    /// accepts compressed, or not.
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        if let TransportConfig::HttpStream(ref mut config) = self.config {
+            config.compression = enabled;
+        }
+        self.compression = Compression::new(enabled);
+        self
+    }
+
+    /// Bytes sent and received compressed, and what compression saved.
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.compression.stats()
+    }
+
+    /// Hold requests and response bodies to `limits`.
+    ///
+    /// JSON bodies over the limit are rejected or truncated as `limits`
+    /// says; SSE bodies over it are always rejected.
+    pub fn with_limits(mut self, limits: MessageLimits) -> Self {
+        if let TransportConfig::HttpStream(ref mut config) = self.config {
+            config.limits = limits.clone();
         }
+        self.limits = limits;
+        self
+    }
+
+    fn info(&self) -> MutexGuard<'_, TransportInfo> {
+        self.info.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Session ID assigned by the server during initialization
+    fn session_id(&self) -> Option<String> {
+        self.session_id
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
     }
 
     /// Get the MCP endpoint URL
@@ -78,8 +184,66 @@ impl HttpStreamTransport {
         url
     }
 
+    /// Wait for a free stream when their number is limited
+    async fn acquire_stream(&self) -> Option<tokio::sync::SemaphorePermit<'_>> {
+        match &self.streams {
+            // The semaphore is never closed
+            Some(streams) => streams.acquire().await.ok(),
+            None => None,
+        }
+    }
+
+    /// POST a JSON body to `url`, compressed if the server takes compressed
+    /// requests, with the session ID when `with_session` is set. `what` names
+    /// the request in errors; a server that turns it away for now fails it
+    /// as [`TransportError::Throttled`].
+    async fn post(
+        &self,
+        url: &str,
+        json_body: String,
+        with_session: bool,
+        what: &str,
+    ) -> McpResult<Response> {
+        let body = Bytes::from(json_body);
+        loop {
+            let mut request_builder = self
+                .client
+                .post(url)
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json, text/event-stream");
+
+            // Add authentication if provided
+            if let Some(auth) = &self.auth_header {
+                request_builder = request_builder.header("Authorization", auth);
+            }
+
+            if with_session {
+                if let Some(session_id) = self.session_id() {
+                    request_builder = request_builder.header("mcp-session-id", session_id);
+                }
+            }
+
+            let (request_builder, compressed) =
+                self.compression.body(request_builder, body.clone());
+            let response = request_builder
+                .send()
+                .await
+                .map_err(|e| send_error(e, what))?;
+            if compressed && response.status() == StatusCode::UNSUPPORTED_MEDIA_TYPE {
+                // Requests go uncompressed from now on
+                self.compression.refuse();
+                continue;
+            }
+            if let Some(throttled) = retry::throttled(&response) {
+                return Err(throttled.into());
+            }
+            return Ok(response);
+        }
+    }
+
     /// Send a JSON-RPC message to the MCP server and parse response
     async fn send_mcp_request(&self, message: &JsonRpcMessage) -> McpResult<JsonRpcResponse> {
+        self.limits.check_outbound("http-stream", message)?;
         let url = self.get_mcp_url();
         let json_body = serde_json::to_string(message).map_err(|e| {
             McpError::Transport(TransportError::SerializationError {
@@ -90,30 +254,12 @@ impl HttpStreamTransport {
 
         debug!("Sending MCP request to {}: {}", url, json_body);
 
-        let mut request_builder = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json, text/event-stream")
-            .body(json_body);
-
-        // Add authentication if provided
-        if let Some(auth) = &self.auth_header {
-            request_builder = request_builder.header("Authorization", auth);
-        }
-
         // Add session ID if we have one (Modern Streamable HTTP)
-        if let Some(session_id) = &self.session_id {
-            request_builder = request_builder.header("mcp-session-id", session_id);
+        let response = self.post(&url, json_body, true, "HTTP request").await?;
+        if let (Some(origins), Some(peer)) = (&self.origins, response.remote_addr()) {
+            origins.check_peer(response.url(), peer)?;
         }
 
-        let response = request_builder.send().await.map_err(|e| {
-            McpError::Transport(TransportError::NetworkError {
-                transport_type: "http-stream".to_string(),
-                reason: format!("HTTP request failed: {}", e),
-            })
-        })?;
-
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
@@ -131,23 +277,83 @@ impl HttpStreamTransport {
             }
         }
 
-        let response_text = response.text().await.map_err(|e| {
+        self.read_response(response, "Failed to read response body")
+            .await
+    }
+
+    /// Read a response body within the inbound limit and parse it
+    async fn read_response(&self, response: Response, failure: &str) -> McpResult<JsonRpcResponse> {
+        let limit = self.limits.max_inbound;
+        let too_large = |size| {
+            McpError::Transport(TransportError::MessageTooLarge {
+                transport_type: "http-stream".to_string(),
+                size,
+                limit,
+            })
+        };
+        let read_failed = |e: std::io::Error| {
             McpError::Transport(TransportError::NetworkError {
                 transport_type: "http-stream".to_string(),
-                reason: format!("Failed to read response body: {}", e),
+                reason: format!("{}: {}", failure, e),
             })
-        })?;
+        };
 
-        debug!("Received MCP response: {}", response_text);
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .is_some_and(|ct| ct.contains("application/json"));
+        let chunks = self.compression.decode(response);
+        if !is_json {
+            // SSE text cannot be cut into valid JSON, so it is only counted
+            let mut body = Vec::new();
+            let mut chunks = chunks;
+            while let Some(chunk) = chunks.next().await {
+                let chunk = chunk.map_err(read_failed)?;
+                if body.len() + chunk.len() > limit {
+                    return Err(too_large(body.len() + chunk.len()));
+                }
+                body.extend_from_slice(&chunk);
+            }
+            let response_text = String::from_utf8_lossy(&body);
+            debug!("Received MCP response: {}", response_text);
+            return self.parse_response(&response_text);
+        }
 
-        // Parse response - handle both JSON and simple SSE formats
-        self.parse_response(&response_text)
+        match limits::read_body(chunks, &mut self.limits.inbound())
+            .await
+            .map_err(read_failed)?
+        {
+            LimitedFrame::Complete(response_text) => {
+                debug!("Received MCP response: {}", response_text);
+                self.parse_response(&response_text)
+            }
+            LimitedFrame::Truncated { frame, size } => {
+                let reason = format!("message of {} bytes cut to the {}-byte limit", size, limit);
+                tracing::warn!("Truncated incoming {}", reason);
+                self.frames.oversized(&frame, reason, false);
+                let mut message = JsonRpcMessage::Response(self.parse_response(&frame)?);
+                limits::mark_truncated(&mut message, size, limit);
+                match message {
+                    JsonRpcMessage::Response(response) => Ok(response),
+                    _ => unreachable!("marking a response keeps it a response"),
+                }
+            }
+            LimitedFrame::Rejected {
+                size, head, reason, ..
+            } => {
+                tracing::warn!("Dropped incoming {}", reason);
+                self.frames.oversized(&head, reason, true);
+                Err(too_large(size))
+            }
+        }
     }
 
     /// Parse response text that may be JSON or SSE format
     fn parse_response(&self, response_text: &str) -> McpResult<JsonRpcResponse> {
         // Try JSON first
         if let Ok(json_response) = serde_json::from_str::<serde_json::Value>(response_text) {
+            self.check_frame(&json_response, response_text)?;
             return self.parse_json_response(&json_response);
         }
 
@@ -172,10 +378,19 @@ impl HttpStreamTransport {
                 id: self.extract_request_id(json_response),
             })
         } else if let Some(error) = json_response.get("error") {
-            Err(McpError::Transport(TransportError::HttpError {
-                status_code: 400,
-                reason: format!("Server returned error: {}", error),
-            }))
+            // Error responses reach the caller like they do on other transports
+            match serde_json::from_value(error.clone()) {
+                Ok(error) => Ok(JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    result: None,
+                    error: Some(error),
+                    id: self.extract_request_id(json_response),
+                }),
+                Err(_) => Err(McpError::Transport(TransportError::HttpError {
+                    status_code: 400,
+                    reason: format!("Server returned error: {}", error),
+                })),
+            }
         } else {
             Err(McpError::Transport(TransportError::SerializationError {
                 transport_type: "http-stream".to_string(),
@@ -192,6 +407,7 @@ impl HttpStreamTransport {
                 if let Ok(json_response) = serde_json::from_str::<serde_json::Value>(json_text) {
                     if json_response.get("id").is_some() {
                         // Found a JSON-RPC response
+                        self.check_frame(&json_response, json_text)?;
                         return self.parse_json_response(&json_response);
                     }
                 }
@@ -204,6 +420,18 @@ impl HttpStreamTransport {
         }))
     }
 
+    /// Refuse a response frame that strict parsing rejects
+    fn check_frame(&self, json_response: &serde_json::Value, frame: &str) -> McpResult<()> {
+        self.frames
+            .check(json_response, frame)
+            .map_err(|diagnostic| {
+                McpError::Transport(TransportError::SerializationError {
+                    transport_type: "http-stream".to_string(),
+                    reason: diagnostic.to_string(),
+                })
+            })
+    }
+
     /// Extract RequestId from JSON response
     fn extract_request_id(&self, json_response: &serde_json::Value) -> RequestId {
         json_response
@@ -218,12 +446,11 @@ impl HttpStreamTransport {
     }
 
     /// Send initialization request and extract session ID
-    async fn send_initialize_request(
-        &mut self,
-        request: JsonRpcRequest,
-    ) -> McpResult<JsonRpcResponse> {
+    async fn send_initialize_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
         let url = self.get_mcp_url();
-        let json_body = serde_json::to_string(&JsonRpcMessage::Request(request)).map_err(|e| {
+        let message = JsonRpcMessage::Request(request);
+        self.limits.check_outbound("http-stream", &message)?;
+        let json_body = serde_json::to_string(&message).map_err(|e| {
             McpError::Transport(TransportError::SerializationError {
                 transport_type: "http-stream".to_string(),
                 reason: format!("Failed to serialize init request: {}", e),
@@ -232,23 +459,9 @@ impl HttpStreamTransport {
 
         debug!("Sending initialization request to {}: {}", url, json_body);
 
-        let mut request_builder = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json, text/event-stream")
-            .body(json_body);
-
-        if let Some(auth) = &self.auth_header {
-            request_builder = request_builder.header("Authorization", auth);
-        }
-
-        let response = request_builder.send().await.map_err(|e| {
-            McpError::Transport(TransportError::NetworkError {
-                transport_type: "http-stream".to_string(),
-                reason: format!("Initialization request failed: {}", e),
-            })
-        })?;
+        let response = self
+            .post(&url, json_body, false, "Initialization request")
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -263,46 +476,38 @@ impl HttpStreamTransport {
         if let Some(session_id) = response.headers().get("mcp-session-id") {
             if let Ok(session_str) = session_id.to_str() {
                 info!("Session established with ID: {}", session_str);
-                self.session_id = Some(session_str.to_string());
+                *self.session_id.write().unwrap_or_else(|e| e.into_inner()) =
+                    Some(session_str.to_string());
             }
         }
 
-        let response_text = response.text().await.map_err(|e| {
-            McpError::Transport(TransportError::NetworkError {
-                transport_type: "http-stream".to_string(),
-                reason: format!("Failed to read init response: {}", e),
-            })
-        })?;
-
-        debug!("Initialization response: {}", response_text);
-
-        // Parse the response
-        self.parse_response(&response_text)
+        self.read_response(response, "Failed to read init response")
+            .await
     }
 }
 
 #[async_trait]
 impl Transport for HttpStreamTransport {
     fn is_connected(&self) -> bool {
-        self.connected
+        self.connected.load(Ordering::SeqCst)
     }
 
-    async fn connect(&mut self) -> McpResult<()> {
+    async fn connect(&self) -> McpResult<()> {
         info!(
             "Connecting MCP Streamable HTTP transport to {}",
             self.base_url
         );
 
         // Just mark as connected - initialization happens in first request
-        self.connected = true;
-        self.info.mark_connected();
+        self.connected.store(true, Ordering::SeqCst);
+        self.info().mark_connected();
 
         info!("MCP Streamable HTTP transport connected successfully");
         Ok(())
     }
 
     async fn send_request(
-        &mut self,
+        &self,
         request: JsonRpcRequest,
         timeout_duration: Option<Duration>,
     ) -> McpResult<JsonRpcResponse> {
@@ -317,6 +522,7 @@ impl Transport for HttpStreamTransport {
         let is_initialize = request.method == "initialize";
 
         let result = timeout(timeout_duration, async {
+            let _stream = self.acquire_stream().await;
             if is_initialize {
                 // Special handling for initialization to extract session ID
                 self.send_initialize_request(request).await
@@ -330,8 +536,9 @@ impl Transport for HttpStreamTransport {
 
         match result {
             Ok(response) => {
-                self.info.increment_requests_sent();
-                self.info.increment_responses_received();
+                let mut info = self.info();
+                info.increment_requests_sent();
+                info.increment_responses_received();
                 response
             }
             Err(_) => Err(McpError::Transport(TransportError::TimeoutError {
@@ -341,7 +548,7 @@ impl Transport for HttpStreamTransport {
         }
     }
 
-    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
         if !self.is_connected() {
             return Err(McpError::Transport(TransportError::NotConnected {
                 transport_type: "http-stream".to_string(),
@@ -350,37 +557,21 @@ impl Transport for HttpStreamTransport {
         }
 
         // Send notification (no response expected)
+        let _stream = self.acquire_stream().await;
         let url = self.get_mcp_url();
-        let json_body = serde_json::to_string(&JsonRpcMessage::Notification(notification))
-            .map_err(|e| {
-                McpError::Transport(TransportError::SerializationError {
-                    transport_type: "http-stream".to_string(),
-                    reason: format!("Failed to serialize notification: {e}"),
-                })
-            })?;
-
-        let mut request_builder = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json, text/event-stream")
-            .body(json_body);
-
-        if let Some(auth) = &self.auth_header {
-            request_builder = request_builder.header("Authorization", auth);
-        }
-
-        if let Some(session_id) = &self.session_id {
-            request_builder = request_builder.header("mcp-session-id", session_id);
-        }
-
-        let response = request_builder.send().await.map_err(|e| {
-            McpError::Transport(TransportError::NetworkError {
+        let message = JsonRpcMessage::Notification(notification);
+        self.limits.check_outbound("http-stream", &message)?;
+        let json_body = serde_json::to_string(&message).map_err(|e| {
+            McpError::Transport(TransportError::SerializationError {
                 transport_type: "http-stream".to_string(),
-                reason: format!("Notification request failed: {e}"),
+                reason: format!("Failed to serialize notification: {e}"),
             })
         })?;
 
+        let response = self
+            .post(&url, json_body, true, "Notification request")
+            .await?;
+
         if !response.status().is_success() {
             return Err(McpError::Transport(TransportError::HttpError {
                 status_code: response.status().as_u16(),
@@ -388,12 +579,12 @@ impl Transport for HttpStreamTransport {
             }));
         }
 
-        self.info.increment_notifications_sent();
+        self.info().increment_notifications_sent();
         Ok(())
     }
 
     async fn receive_message(
-        &mut self,
+        &self,
         _timeout_duration: Option<Duration>,
     ) -> McpResult<JsonRpcMessage> {
         // For Modern Streamable HTTP, unsolicited messages would come via SSE
@@ -405,44 +596,58 @@ impl Transport for HttpStreamTransport {
         }))
     }
 
-    async fn disconnect(&mut self) -> McpResult<()> {
+    async fn disconnect(&self) -> McpResult<()> {
         info!("Disconnecting MCP Streamable HTTP transport");
 
-        self.session_id = None;
-        self.connected = false;
+        *self.session_id.write().unwrap_or_else(|e| e.into_inner()) = None;
+        self.connected.store(false, Ordering::SeqCst);
 
         // Clear pending requests
-        {
-            let mut pending = self.pending_requests.lock().await;
-            pending.clear();
-        }
+        self.correlator.cancel_all();
 
-        self.info.mark_disconnected();
+        self.info().mark_disconnected();
 
         info!("MCP Streamable HTTP transport disconnected");
         Ok(())
     }
 
     fn get_info(&self) -> TransportInfo {
-        let mut info = self.info.clone();
+        let mut info = self.info().clone();
+        let session_id = self.session_id();
 
         // Add MCP-specific metadata
         info.add_metadata("base_url", serde_json::json!(self.base_url));
         info.add_metadata("mcp_endpoint", serde_json::json!(self.get_mcp_url()));
         info.add_metadata("has_auth", serde_json::json!(self.auth_header.is_some()));
-        info.add_metadata("has_session", serde_json::json!(self.session_id.is_some()));
+        info.add_metadata("has_session", serde_json::json!(session_id.is_some()));
         info.add_metadata(
             "protocol",
             serde_json::json!("Modern Streamable HTTP (2025-03-26)"),
         );
 
-        if let Some(session_id) = &self.session_id {
+        if let Some(session_id) = session_id {
             info.add_metadata("session_id", serde_json::json!(session_id));
         }
 
         // Add pending requests count
-        if let Ok(pending) = self.pending_requests.try_lock() {
-            info.add_metadata("pending_requests", serde_json::json!(pending.len()));
+        info.add_metadata(
+            "pending_requests",
+            serde_json::json!(self.correlator.pending_count()),
+        );
+
+        if self.compression.is_enabled() {
+            let stats = self.compression_stats();
+            info.add_metadata(
+                "compression_bytes_saved",
+                serde_json::json!(stats.bytes_saved()),
+            );
+            info.add_metadata("compression", serde_json::json!(stats));
+        }
+
+        if let TransportConfig::HttpStream(config) = &self.config {
+            if !config.connection.is_default() {
+                info.add_metadata("connection", serde_json::json!(config.connection));
+            }
         }
 
         info
@@ -451,6 +656,26 @@ impl Transport for HttpStreamTransport {
     fn get_config(&self) -> &TransportConfig {
         &self.config
     }
+
+    fn protocol_diagnostics(&self) -> Option<broadcast::Receiver<ProtocolDiagnostic>> {
+        Some(self.frames.subscribe())
+    }
+}
+
+/// Error for a request that failed to send, telling apart requests that
+/// never reached the server
+fn send_error(error: reqwest::Error, what: &str) -> McpError {
+    McpError::Transport(match origin::rejection(&error) {
+        Some(rejection) => rejection.into(),
+        None if error.is_connect() => TransportError::ConnectionFailed {
+            transport_type: "http-stream".to_string(),
+            reason: format!("{what} failed: {error}"),
+        },
+        None => TransportError::NetworkError {
+            transport_type: "http-stream".to_string(),
+            reason: format!("{what} failed: {error}"),
+        },
+    })
 }
 
 #[cfg(test)]
@@ -498,4 +723,60 @@ mod tests {
         let transport_no_auth = HttpStreamTransport::new("http://localhost:3001".to_string(), None);
         assert!(transport_no_auth.auth_header.is_none());
     }
+
+    /// Answers every request with an empty JSON result, slowly
+    struct SlowEcho;
+
+    impl wiremock::Respond for SlowEcho {
+        fn respond(&self, request: &wiremock::Request) -> wiremock::ResponseTemplate {
+            let request: JsonRpcRequest = serde_json::from_slice(&request.body).unwrap();
+            wiremock::ResponseTemplate::new(200)
+                .set_body_json(JsonRpcResponse::success(request.id, serde_json::json!({})))
+                .set_delay(Duration::from_millis(150))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_share_limited_http2_streams() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(SlowEcho)
+            .mount(&server)
+            .await;
+
+        let connection = HttpConnectionConfig::default()
+            .http2_prior_knowledge()
+            .max_concurrent_streams(2)
+            .keepalive_interval(Duration::from_secs(30));
+        let transport = std::sync::Arc::new(
+            HttpStreamTransport::new(server.uri(), None).with_connection(connection.clone()),
+        );
+        transport.connect().await.unwrap();
+
+        let started = tokio::time::Instant::now();
+        let calls = (0..4).map(|i| {
+            let transport = transport.clone();
+            tokio::spawn(async move {
+                let request =
+                    JsonRpcRequest::new(format!("req-{i}"), "tools/call", serde_json::json!({}));
+                transport.send_request(request, None).await
+            })
+        });
+        // Prior knowledge only gets answers from a server speaking HTTP/2
+        for response in futures::future::join_all(calls).await {
+            assert!(response.unwrap().unwrap().result.is_some());
+        }
+        // Two waves of two
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(600), "{:?}", elapsed);
+
+        assert_eq!(
+            transport.get_info().metadata["connection"],
+            serde_json::to_value(&connection).unwrap()
+        );
+    }
 }
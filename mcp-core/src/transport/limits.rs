@@ -0,0 +1,1162 @@
+//! Size limits on the messages a transport sends and receives.
+//!
+//! A misbehaving server can answer with a frame of any size, and a transport
+//! that buffers whole frames will try to hold it all in memory. With
+//! [`MessageLimits`] incoming frames are read through a [`FrameLimiter`],
+//! which never buffers more than [`MessageLimits::max_inbound`] bytes of a
+//! frame. What happens to the rest depends on [`Oversized`]: the frame is
+//! either rejected, in which case the request it answers fails with an
+//! error, or cut to the limit and repaired into valid JSON, in which case
+//! the message is delivered with `_meta.truncated` set.
+//!
+//! Binary resource contents are often the bulk of a large frame. When
+//! [`MessageLimits::spill_dir`] is set, any `blob` longer than
+//! [`MessageLimits::spill_threshold`] is decoded straight into a file in that
+//! directory instead. The message is delivered with an empty `blob` and the
+//! file noted in the content's `_meta.spill`; spilled bytes do not count
+//! towards the limit. The files are left for the caller to remove.
+//!
+//! Outgoing messages larger than [`MessageLimits::max_outbound`] are refused
+//! before they are sent, with [`TransportError::MessageTooLarge`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::transport::limits::{LimitedFrame, MessageLimits, Oversized};
+//!
+//! let limits = MessageLimits::default()
+//!     .max_inbound(48)
+//!     .oversized(Oversized::Truncate);
+//! let mut limiter = limits.inbound();
+//!
+//! limiter.push(br#"{"jsonrpc":"2.0","id":1,"result":{"text":"a long answer, cut short"}}"#);
+//! let LimitedFrame::Truncated { frame, .. } = limiter.finish() else {
+//!     panic!("expected a truncated frame");
+//! };
+//! assert_eq!(frame, r#"{"jsonrpc":"2.0","id":1,"result":{"text":"a long"}}"#);
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+#[cfg(feature = "http")]
+use bytes::Bytes;
+#[cfg(feature = "http")]
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+#[cfg(any(feature = "stdio", feature = "http-sse"))]
+use super::framing::FrameParser;
+#[cfg(any(feature = "stdio", feature = "http-sse"))]
+use crate::correlator::RequestCorrelator;
+use crate::error::{ConfigError, McpResult, TransportError};
+#[cfg(any(feature = "stdio", feature = "http-sse"))]
+use crate::messages::{JsonRpcError, JsonRpcResponse};
+use crate::messages::{JsonRpcMessage, RequestId};
+
+/// Default limit on the size of one message in either direction: 16 MiB
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Default size above which a `blob` is spilled to disk: 1 MiB
+pub const DEFAULT_SPILL_THRESHOLD: usize = 1024 * 1024;
+
+/// `_meta` key noting that a message was cut to the limit
+pub const TRUNCATED_META_KEY: &str = "truncated";
+
+/// `_meta` key noting where a spilled blob was written
+pub const SPILL_META_KEY: &str = "spill";
+
+/// Bytes of a frame kept to describe it when the rest is dropped
+const HEAD_LEN: usize = 512;
+
+/// Longest key or `id` value remembered while scanning a frame
+const TOKEN_LEN: usize = 256;
+
+/// Base64 characters decoded at a time while spilling
+const SPILL_CHUNK: usize = 64 * 1024;
+
+/// What to do with an incoming frame larger than the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Oversized {
+    /// Drop the frame; a response fails the request it answers
+    #[default]
+    Reject,
+    /// Cut the frame to the limit and deliver what is left
+    Truncate,
+}
+
+/// Limits on the size of messages in each direction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageLimits {
+    /// Largest incoming frame, in bytes
+    pub max_inbound: usize,
+    /// Largest outgoing message, in bytes
+    pub max_outbound: usize,
+    /// What to do with an incoming frame over the limit
+    pub oversized: Oversized,
+    /// Directory large blobs are written to instead of being kept in memory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spill_dir: Option<PathBuf>,
+    /// Size above which a blob is spilled, in bytes of base64
+    pub spill_threshold: usize,
+}
+
+impl Default for MessageLimits {
+    fn default() -> Self {
+        Self {
+            max_inbound: DEFAULT_MAX_MESSAGE_SIZE,
+            max_outbound: DEFAULT_MAX_MESSAGE_SIZE,
+            oversized: Oversized::default(),
+            spill_dir: None,
+            spill_threshold: DEFAULT_SPILL_THRESHOLD,
+        }
+    }
+}
+
+impl MessageLimits {
+    /// Limit messages in both directions to `bytes`.
+    pub fn max_message_size(self, bytes: usize) -> Self {
+        self.max_inbound(bytes).max_outbound(bytes)
+    }
+
+    /// Limit incoming frames to `bytes`.
+    pub fn max_inbound(mut self, bytes: usize) -> Self {
+        self.max_inbound = bytes;
+        self
+    }
+
+    /// Limit outgoing messages to `bytes`.
+    pub fn max_outbound(mut self, bytes: usize) -> Self {
+        self.max_outbound = bytes;
+        self
+    }
+
+    /// Set what happens to incoming frames over the limit.
+    pub fn oversized(mut self, oversized: Oversized) -> Self {
+        self.oversized = oversized;
+        self
+    }
+
+    /// Write blobs larger than the spill threshold to files in `dir`.
+    pub fn spill_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = Some(dir.into());
+        self
+    }
+
+    /// Spill blobs longer than `bytes` of base64.
+    pub fn spill_threshold(mut self, bytes: usize) -> Self {
+        self.spill_threshold = bytes;
+        self
+    }
+
+    /// Check that every message can have at least one byte.
+    pub fn validate(&self) -> McpResult<()> {
+        for (parameter, value) in [
+            ("limits.max_inbound", self.max_inbound),
+            ("limits.max_outbound", self.max_outbound),
+        ] {
+            if value == 0 {
+                return Err(ConfigError::InvalidValue {
+                    parameter: parameter.to_string(),
+                    value: value.to_string(),
+                    reason: "Message size limit must be greater than 0".to_string(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether these are the default limits.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// A limiter for frames received from the peer.
+    pub fn inbound(&self) -> FrameLimiter {
+        let spill = self
+            .spill_dir
+            .clone()
+            .map(|dir| (dir, self.spill_threshold));
+        FrameLimiter::new(self.max_inbound, self.oversized, spill)
+    }
+
+    /// A limiter for frames a proxy receives from its client, which are
+    /// rejected when over [`max_outbound`](Self::max_outbound).
+    pub fn outbound(&self) -> FrameLimiter {
+        FrameLimiter::new(self.max_outbound, Oversized::Reject, None)
+    }
+
+    /// Refuse a message larger than [`max_outbound`](Self::max_outbound).
+    pub fn check_outbound(
+        &self,
+        transport_type: &str,
+        message: &JsonRpcMessage,
+    ) -> Result<(), TransportError> {
+        let size = encoded_len(message);
+        if size > self.max_outbound {
+            return Err(TransportError::MessageTooLarge {
+                transport_type: transport_type.to_string(),
+                size,
+                limit: self.max_outbound,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Length of a message serialized as JSON, without building the string
+pub fn encoded_len(message: &impl Serialize) -> usize {
+    struct Counter(usize);
+
+    impl Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    match serde_json::to_writer(&mut counter, message) {
+        Ok(()) => counter.0,
+        // Unserializable messages fail later, where they are sent
+        Err(_) => 0,
+    }
+}
+
+/// Note in a message's `_meta` that it was cut from `size` bytes to `limit`.
+pub fn mark_truncated(message: &mut JsonRpcMessage, size: usize, limit: usize) {
+    if let Some(meta) = message.meta_mut() {
+        meta.insert(
+            TRUNCATED_META_KEY.to_string(),
+            json!({ "size": size, "limit": limit }),
+        );
+    }
+}
+
+/// One incoming frame, as a [`FrameLimiter`] let it through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitedFrame {
+    /// The frame was within the limit
+    Complete(String),
+    /// The frame was over the limit, cut to it and repaired into valid JSON
+    Truncated {
+        /// What is left of the frame
+        frame: String,
+        /// Size of the whole frame, in bytes
+        size: usize,
+    },
+    /// The frame was dropped
+    Rejected {
+        /// ID of the message, when it could be found
+        id: Option<RequestId>,
+        /// Size of the whole frame, in bytes
+        size: usize,
+        /// Start of the frame
+        head: String,
+        /// Why it was dropped
+        reason: String,
+    },
+}
+
+impl LimitedFrame {
+    /// Size of the whole frame, in bytes, not counting spilled blobs.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Complete(frame) => frame.len(),
+            Self::Truncated { size, .. } | Self::Rejected { size, .. } => *size,
+        }
+    }
+}
+
+/// Read one newline-delimited frame through `limiter`.
+///
+/// Returns `None` at the end of the stream. Cancelling the read loses
+/// nothing: bytes taken from the reader are already in the limiter, and the
+/// next call carries on with the same frame.
+pub async fn read_frame<R>(
+    reader: &mut R,
+    limiter: &mut FrameLimiter,
+) -> io::Result<Option<LimitedFrame>>
+where
+    R: AsyncBufRead + Unpin + ?Sized,
+{
+    loop {
+        let buffer = reader.fill_buf().await?;
+        if buffer.is_empty() {
+            if limiter.is_empty() {
+                return Ok(None);
+            }
+            return Ok(Some(limiter.finish()));
+        }
+        match buffer.iter().position(|&b| b == b'\n') {
+            Some(end) => {
+                limiter.push(&buffer[..end]);
+                reader.consume(end + 1);
+                return Ok(Some(limiter.finish()));
+            }
+            None => {
+                let read = buffer.len();
+                limiter.push(buffer);
+                reader.consume(read);
+            }
+        }
+    }
+}
+
+/// Read a whole response body through `limiter`.
+#[cfg(feature = "http")]
+pub(crate) async fn read_body<S>(
+    mut body: S,
+    limiter: &mut FrameLimiter,
+) -> io::Result<LimitedFrame>
+where
+    S: Stream<Item = io::Result<Bytes>> + Unpin,
+{
+    while let Some(chunk) = body.next().await {
+        limiter.push(&chunk?);
+    }
+    Ok(limiter.finish())
+}
+
+/// Decode a frame that came through a limiter with `frames`, reporting what
+/// the limit did to it.
+///
+/// A rejected response fails the request it answers with an internal error
+/// response, as if the server had sent one, instead of leaving it to time
+/// out. Returns `None` when there is nothing to deliver.
+#[cfg(any(feature = "stdio", feature = "http-sse"))]
+pub(crate) fn decode_frame(
+    frames: &FrameParser,
+    correlator: &RequestCorrelator,
+    frame: LimitedFrame,
+    limit: usize,
+) -> Option<JsonRpcMessage> {
+    match frame {
+        LimitedFrame::Complete(frame) => {
+            let frame = frame.trim();
+            if frame.is_empty() {
+                return None;
+            }
+            // Frames that fail to parse were already logged and reported
+            frames.decode(frame).ok()
+        }
+        LimitedFrame::Truncated { frame, size } => {
+            let reason = format!("message of {} bytes cut to the {}-byte limit", size, limit);
+            tracing::warn!("Truncated incoming {}", reason);
+            frames.oversized(&frame, reason, false);
+            let mut message = frames.decode(&frame).ok()?;
+            mark_truncated(&mut message, size, limit);
+            Some(message)
+        }
+        LimitedFrame::Rejected {
+            id,
+            size,
+            head,
+            reason,
+        } => {
+            tracing::warn!("Dropped incoming {}", reason);
+            frames.oversized(&head, reason.clone(), true);
+            if let Some(id) = id {
+                let error = JsonRpcError::new(
+                    -32603,
+                    format!("Response dropped: {}", reason),
+                    Some(json!({ "size": size, "limit": limit })),
+                );
+                correlator.complete(JsonRpcResponse::error(id, error));
+            }
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Object,
+    Array,
+}
+
+/// An object or array the scanner is inside
+#[derive(Debug)]
+struct Container {
+    kind: Kind,
+    /// The next string is a key
+    expect_key: bool,
+    /// Last key, cut short
+    key: Vec<u8>,
+    /// `_meta` is one of the keys
+    meta_seen: bool,
+    /// Blob spilled from this object and not yet noted in its `_meta`
+    spilled: Option<String>,
+    /// A member was injected first, so the next key needs a comma
+    needs_comma: bool,
+}
+
+impl Container {
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            expect_key: kind == Kind::Object,
+            key: Vec::new(),
+            meta_seen: false,
+            spilled: None,
+            needs_comma: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Backslash,
+    /// Inside `\uXXXX`, with the digits left and the value so far
+    Unicode(u8, u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overflow {
+    /// Under the limit
+    No,
+    /// Rejected: only counting, and looking for the `id`
+    Counting,
+    /// Truncated: the repaired frame is complete
+    Frozen,
+}
+
+/// A blob being decoded into a file
+struct Spiller {
+    path: PathBuf,
+    file: BufWriter<File>,
+    pending: Vec<u8>,
+    written: u64,
+}
+
+impl Spiller {
+    fn create(dir: &std::path::Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.bin", uuid::Uuid::new_v4()));
+        let file = BufWriter::new(File::create(&path)?);
+        Ok(Self {
+            path,
+            file,
+            pending: Vec::new(),
+            written: 0,
+        })
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> io::Result<()> {
+        // `\/` is the only escape base64 can contain
+        self.pending
+            .extend(bytes.iter().copied().filter(|&b| b != b'\\'));
+        if self.pending.len() >= SPILL_CHUNK {
+            let whole = self.pending.len() / 4 * 4;
+            let rest = self.pending.split_off(whole);
+            let chunk = std::mem::replace(&mut self.pending, rest);
+            self.decode(&chunk)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<(PathBuf, u64)> {
+        let rest = std::mem::take(&mut self.pending);
+        self.decode(&rest)?;
+        self.file.flush()?;
+        Ok((self.path, self.written))
+    }
+
+    fn decode(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let engine = GeneralPurpose::new(
+            &alphabet::STANDARD,
+            GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+        let bytes = engine
+            .decode(chunk)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.file.write_all(&bytes)?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// Applies [`MessageLimits`] to incoming frames as their bytes arrive.
+///
+/// Feed the bytes of a frame to [`push`](Self::push) and collect it with
+/// [`finish`](Self::finish), which readies the limiter for the next one.
+/// Frames are scanned as JSON while they are copied, so an oversized frame
+/// can be cut where its JSON can be closed and the `id` of a rejected one
+/// can still be found. Input that is not JSON is copied as it is and left
+/// for the decoder to report.
+pub struct FrameLimiter {
+    max: usize,
+    oversized: Oversized,
+    spill: Option<(PathBuf, usize)>,
+    out: Vec<u8>,
+    head: Vec<u8>,
+    size: usize,
+    overflow: Overflow,
+    stack: Vec<Container>,
+    /// Inside a string; `Some(true)` for a key
+    string: Option<bool>,
+    string_start: usize,
+    escape: Escape,
+    escape_start: usize,
+    /// Start of a `\u` escape for a high surrogate just before the cut point
+    high_surrogate: Option<usize>,
+    /// End of the last complete value or opening bracket
+    safe: usize,
+    /// Start of a blob that may be spilled
+    blob_start: Option<usize>,
+    spiller: Option<Spiller>,
+    spilled: Vec<PathBuf>,
+    id: Option<Vec<u8>>,
+    capturing_id: bool,
+    error: Option<String>,
+}
+
+impl std::fmt::Debug for FrameLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameLimiter")
+            .field("max", &self.max)
+            .field("oversized", &self.oversized)
+            .field("spill", &self.spill)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl FrameLimiter {
+    /// Limit frames to `max` bytes, spilling blobs to `spill` (a directory
+    /// and a threshold) when given.
+    pub fn new(max: usize, oversized: Oversized, spill: Option<(PathBuf, usize)>) -> Self {
+        Self {
+            max,
+            oversized,
+            spill,
+            out: Vec::new(),
+            head: Vec::new(),
+            size: 0,
+            overflow: Overflow::No,
+            stack: Vec::new(),
+            string: None,
+            string_start: 0,
+            escape: Escape::None,
+            escape_start: 0,
+            high_surrogate: None,
+            safe: 0,
+            blob_start: None,
+            spiller: None,
+            spilled: Vec::new(),
+            id: None,
+            capturing_id: false,
+            error: None,
+        }
+    }
+
+    /// Whether no bytes of the current frame have arrived.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Add bytes of the current frame.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.size += bytes.len();
+        if self.head.len() < HEAD_LEN {
+            let take = (HEAD_LEN - self.head.len()).min(bytes.len());
+            self.head.extend_from_slice(&bytes[..take]);
+        }
+        if self.overflow == Overflow::Frozen || self.error.is_some() {
+            return;
+        }
+        for &byte in bytes {
+            if self.overflow == Overflow::Frozen || self.error.is_some() {
+                break;
+            }
+            self.scan(byte);
+        }
+    }
+
+    /// Finish the current frame.
+    pub fn finish(&mut self) -> LimitedFrame {
+        let frame = if let Some(reason) = self.error.take() {
+            self.discard_spills();
+            self.rejected(reason)
+        } else {
+            match self.overflow {
+                Overflow::No => {
+                    if self.spiller.is_some() {
+                        // An unterminated blob; keep what was spilled
+                        self.end_spill();
+                    }
+                    LimitedFrame::Complete(text(std::mem::take(&mut self.out)))
+                }
+                Overflow::Frozen => LimitedFrame::Truncated {
+                    frame: text(std::mem::take(&mut self.out)),
+                    size: self.size,
+                },
+                Overflow::Counting => {
+                    let reason = format!(
+                        "message of {} bytes exceeds the {}-byte limit",
+                        self.size, self.max
+                    );
+                    self.rejected(reason)
+                }
+            }
+        };
+        if !self.spilled.is_empty() {
+            tracing::info!(
+                "Spilled {} blob(s) of a {}-byte frame to disk",
+                self.spilled.len(),
+                self.size
+            );
+        }
+        self.reset();
+        frame
+    }
+
+    fn rejected(&mut self, reason: String) -> LimitedFrame {
+        let id = self
+            .id
+            .take()
+            .and_then(|raw| serde_json::from_slice::<RequestId>(&raw).ok());
+        LimitedFrame::Rejected {
+            id,
+            size: self.size,
+            head: text(std::mem::take(&mut self.head)),
+            reason,
+        }
+    }
+
+    fn reset(&mut self) {
+        let spill = self.spill.take();
+        *self = Self::new(self.max, self.oversized, spill);
+    }
+
+    fn copying(&self) -> bool {
+        self.overflow == Overflow::No
+    }
+
+    /// Copy a byte of the frame, cutting the frame if it is full
+    fn emit(&mut self, byte: u8) {
+        if !self.copying() {
+            return;
+        }
+        if self.out.len() >= self.max {
+            self.overflow();
+            return;
+        }
+        self.out.push(byte);
+    }
+
+    fn scan(&mut self, byte: u8) {
+        match self.string {
+            Some(is_key) => self.scan_string(byte, is_key),
+            None => self.scan_structure(byte),
+        }
+    }
+
+    fn scan_string(&mut self, byte: u8, is_key: bool) {
+        self.capture(byte);
+        let mut closed = false;
+        match self.escape {
+            Escape::Backslash => {
+                self.escape = if byte == b'u' {
+                    Escape::Unicode(4, 0)
+                } else {
+                    self.high_surrogate = None;
+                    Escape::None
+                };
+            }
+            Escape::Unicode(left, value) => {
+                let digit = (byte as char).to_digit(16).unwrap_or(0) as u16;
+                let value = value << 4 | digit;
+                if left == 1 {
+                    self.escape = Escape::None;
+                    self.high_surrogate = (0xD800..=0xDBFF)
+                        .contains(&value)
+                        .then_some(self.escape_start);
+                } else {
+                    self.escape = Escape::Unicode(left - 1, value);
+                }
+            }
+            Escape::None => match byte {
+                b'\\' => {
+                    self.escape = Escape::Backslash;
+                    self.escape_start = self.out.len();
+                }
+                b'"' => closed = true,
+                _ => self.high_surrogate = None,
+            },
+        }
+
+        if closed {
+            self.string = None;
+            if is_key {
+                self.end_key();
+                self.emit(byte);
+            } else {
+                if self.spiller.is_some() {
+                    self.end_spill();
+                }
+                self.blob_start = None;
+                self.emit(byte);
+                self.capturing_id = false;
+                self.safe = self.out.len();
+            }
+            return;
+        }
+
+        if is_key {
+            if let Some(container) = self.stack.last_mut() {
+                if container.key.len() < TOKEN_LEN {
+                    container.key.push(byte);
+                }
+            }
+            self.emit(byte);
+            return;
+        }
+
+        if let Some(spiller) = self.spiller.as_mut() {
+            if let Err(e) = spiller.push(&[byte]) {
+                self.spill_failed(e);
+            }
+            return;
+        }
+        self.emit(byte);
+        if let (Some(start), Some((_, threshold))) = (self.blob_start, self.spill.as_ref()) {
+            if self.copying() && self.out.len() - start > *threshold {
+                self.start_spill(start);
+            }
+        }
+    }
+
+    fn scan_structure(&mut self, byte: u8) {
+        match byte {
+            b'"' => {
+                let is_key = self
+                    .stack
+                    .last()
+                    .is_some_and(|c| c.kind == Kind::Object && c.expect_key);
+                if is_key {
+                    if let Some(container) = self.stack.last_mut() {
+                        container.key.clear();
+                        if container.needs_comma {
+                            container.needs_comma = false;
+                            self.emit(b',');
+                        }
+                    }
+                } else {
+                    self.begin_value(byte);
+                    if self.may_spill() {
+                        self.blob_start = Some(self.out.len() + 1);
+                    }
+                }
+                self.emit(byte);
+                self.string = Some(is_key);
+                self.string_start = self.out.len();
+                self.escape = Escape::None;
+                self.high_surrogate = None;
+            }
+            b'{' | b'[' => {
+                self.begin_value(byte);
+                let kind = if byte == b'{' {
+                    Kind::Object
+                } else {
+                    Kind::Array
+                };
+                let annotation = match self.stack.last_mut() {
+                    Some(parent) if kind == Kind::Object && parent.key == b"_meta" => {
+                        parent.spilled.take()
+                    }
+                    _ => None,
+                };
+                self.emit(byte);
+                self.stack.push(Container::new(kind));
+                if let Some(annotation) = annotation {
+                    for b in format!("\"{}\":{}", SPILL_META_KEY, annotation).into_bytes() {
+                        self.emit(b);
+                    }
+                    if let Some(container) = self.stack.last_mut() {
+                        container.needs_comma = true;
+                    }
+                }
+                self.safe = self.out.len();
+            }
+            b'}' | b']' => {
+                if self.stack.len() == 1 {
+                    self.capturing_id = false;
+                }
+                if let Some(container) = self.stack.pop() {
+                    if let Some(annotation) = container.spilled.filter(|_| !container.meta_seen) {
+                        let member =
+                            format!(",\"_meta\":{{\"{}\":{}}}", SPILL_META_KEY, annotation);
+                        for b in member.into_bytes() {
+                            self.emit(b);
+                        }
+                    }
+                }
+                self.emit(byte);
+                self.safe = self.out.len();
+            }
+            b',' => {
+                if self.stack.len() == 1 {
+                    self.capturing_id = false;
+                }
+                if self.copying() {
+                    self.safe = self.out.len();
+                }
+                self.emit(byte);
+                if let Some(container) = self.stack.last_mut() {
+                    container.expect_key = container.kind == Kind::Object;
+                }
+            }
+            b' ' | b'\t' | b'\r' | b'\n' | b':' => self.emit(byte),
+            _ => {
+                if !self.begin_value(byte) {
+                    self.capture(byte);
+                }
+                self.emit(byte);
+            }
+        }
+    }
+
+    /// Start capturing a top-level `id` value, if this is one
+    fn begin_value(&mut self, byte: u8) -> bool {
+        let at_id = self.stack.len() == 1
+            && self.stack[0].kind == Kind::Object
+            && self.stack[0].key == b"id"
+            && self.id.is_none();
+        if at_id {
+            self.id = Some(vec![byte]);
+            self.capturing_id = true;
+        }
+        at_id
+    }
+
+    fn capture(&mut self, byte: u8) {
+        if !self.capturing_id {
+            return;
+        }
+        if let Some(id) = self.id.as_mut() {
+            if id.len() < TOKEN_LEN {
+                id.push(byte);
+            }
+        }
+    }
+
+    fn end_key(&mut self) {
+        if let Some(container) = self.stack.last_mut() {
+            container.expect_key = false;
+            if container.key == b"_meta" {
+                container.meta_seen = true;
+            }
+        }
+    }
+
+    /// Whether the string value starting now is a blob that may be spilled
+    fn may_spill(&self) -> bool {
+        self.spill.is_some()
+            && self.copying()
+            && self
+                .stack
+                .last()
+                .is_some_and(|c| c.kind == Kind::Object && c.key == b"blob" && !c.meta_seen)
+    }
+
+    fn start_spill(&mut self, start: usize) {
+        let Some((dir, _)) = self.spill.as_ref() else {
+            return;
+        };
+        match Spiller::create(dir) {
+            Ok(mut spiller) => {
+                let result = spiller.push(&self.out[start..]);
+                self.out.truncate(start);
+                self.spiller = Some(spiller);
+                if let Err(e) = result {
+                    self.spill_failed(e);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Cannot spill blob to {}: {}", dir.display(), e);
+                // Keep the blob in memory instead
+                self.blob_start = None;
+            }
+        }
+    }
+
+    fn end_spill(&mut self) {
+        let Some(spiller) = self.spiller.take() else {
+            return;
+        };
+        match spiller.finish() {
+            Ok((path, written)) => {
+                let annotation = json!({ "path": path, "size": written }).to_string();
+                if let Some(container) = self.stack.last_mut() {
+                    container.spilled = Some(annotation);
+                }
+                self.spilled.push(path);
+            }
+            Err(e) => self.spill_failed(e),
+        }
+    }
+
+    fn spill_failed(&mut self, error: io::Error) {
+        if let Some(spiller) = self.spiller.take() {
+            self.spilled.push(spiller.path);
+        }
+        self.error = Some(format!("failed to spill blob: {}", error));
+    }
+
+    fn discard_spills(&mut self) {
+        if let Some(spiller) = self.spiller.take() {
+            self.spilled.push(spiller.path);
+        }
+        for path in self.spilled.drain(..) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// The frame is full: stop copying it, and either give up on it or cut
+    /// it where its JSON can be closed
+    fn overflow(&mut self) {
+        if self.oversized == Oversized::Reject {
+            self.overflow = Overflow::Counting;
+            self.out = Vec::new();
+            self.discard_spills();
+            return;
+        }
+
+        if self.string == Some(false) {
+            // Cut inside a string value, at a character boundary and
+            // outside any escape sequence
+            let mut cut = self.out.len();
+            if self.escape != Escape::None {
+                cut = self.escape_start;
+            }
+            if let Some(start) = self.high_surrogate {
+                cut = cut.min(start);
+            }
+            cut = char_boundary(&self.out, self.string_start, cut.max(self.string_start));
+            self.out.truncate(cut);
+            if self.spiller.is_some() {
+                self.end_spill();
+            }
+            self.out.push(b'"');
+        } else {
+            self.out.truncate(self.safe);
+        }
+
+        while let Some(container) = self.stack.pop() {
+            match container.kind {
+                Kind::Object => {
+                    if let Some(annotation) = container.spilled.filter(|_| !container.meta_seen) {
+                        let member =
+                            format!(",\"_meta\":{{\"{}\":{}}}", SPILL_META_KEY, annotation);
+                        self.out.extend_from_slice(member.as_bytes());
+                    }
+                    self.out.push(b'}');
+                }
+                Kind::Array => self.out.push(b']'),
+            }
+        }
+        self.overflow = Overflow::Frozen;
+    }
+}
+
+/// `cut`, or the start of the character it would split
+fn char_boundary(bytes: &[u8], start: usize, cut: usize) -> usize {
+    let mut lead = cut;
+    while lead > start && bytes[lead - 1] & 0xC0 == 0x80 {
+        lead -= 1;
+    }
+    if lead == start {
+        return cut;
+    }
+    let lead = lead - 1;
+    let width = match bytes[lead] {
+        b if b >= 0xF0 => 4,
+        b if b >= 0xE0 => 3,
+        b if b >= 0xC0 => 2,
+        _ => 1,
+    };
+    if lead + width > cut {
+        lead
+    } else {
+        cut
+    }
+}
+
+fn text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn limit(limiter: &mut FrameLimiter, frame: &str) -> LimitedFrame {
+        // Arrive in pieces, as from a socket
+        for chunk in frame.as_bytes().chunks(7) {
+            limiter.push(chunk);
+        }
+        limiter.finish()
+    }
+
+    fn truncated(max: usize, frame: &str) -> Value {
+        let mut limiter = FrameLimiter::new(max, Oversized::Truncate, None);
+        match limit(&mut limiter, frame) {
+            LimitedFrame::Truncated { frame, .. } => serde_json::from_str(&frame).unwrap(),
+            other => panic!("expected a truncated frame, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_frames_within_the_limit_pass_unchanged() {
+        let frame = r#"{"jsonrpc":"2.0", "id":1, "result":{"text":"café \"ok\""}}"#;
+        let mut limiter = MessageLimits::default().inbound();
+        assert_eq!(
+            limit(&mut limiter, frame),
+            LimitedFrame::Complete(frame.to_string())
+        );
+        // The limiter is ready for the next frame
+        assert!(limiter.is_empty());
+        assert_eq!(
+            limit(&mut limiter, "[]"),
+            LimitedFrame::Complete("[]".into())
+        );
+    }
+
+    #[test]
+    fn test_oversized_frames_are_rejected_with_their_id() {
+        let mut limiter = MessageLimits::default().max_inbound(32).inbound();
+        let frame = r#"{"jsonrpc":"2.0","result":{"items":[1,2,3,4,5,6,7,8]},"id":"req-9"}"#;
+        match limit(&mut limiter, frame) {
+            LimitedFrame::Rejected {
+                id, size, reason, ..
+            } => {
+                assert_eq!(id, Some(RequestId::from("req-9")));
+                assert_eq!(size, frame.len());
+                assert!(reason.contains("32-byte limit"), "{}", reason);
+            }
+            other => panic!("expected a rejected frame, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_frames_are_valid_json() {
+        let frame = r#"{"jsonrpc":"2.0","id":1,"result":{"content":[{"type":"text","text":"abcdefghijklmnop"}]}}"#;
+        let value = truncated(70, frame);
+        assert_eq!(value["id"], 1);
+        let text = value["result"]["content"][0]["text"].as_str().unwrap();
+        assert!("abcdefghijklmnop".starts_with(text) && text.len() < 16);
+
+        // Cut inside a key or a number: back to the last complete value
+        let value = truncated(28, r#"{"jsonrpc":"2.0","id":1,"result":{"count":12345}}"#);
+        assert_eq!(value, json!({ "jsonrpc": "2.0", "id": 1 }));
+        let value = truncated(46, r#"{"jsonrpc":"2.0","id":1,"result":{"count":12345}}"#);
+        assert_eq!(value, json!({ "jsonrpc": "2.0", "id": 1, "result": {} }));
+
+        // Never inside an escape or a character
+        let value = truncated(38, r#"{"jsonrpc":"2.0","id":1,"result":"ab😀cd"}"#);
+        assert_eq!(value["result"], "ab");
+        let value = truncated(
+            36,
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":\"a\u{e9}\u{e9}\"}",
+        );
+        assert_eq!(value["result"], "a");
+    }
+
+    #[test]
+    fn test_large_blobs_spill_to_disk() {
+        let dir = std::env::temp_dir().join(format!("mcp-spill-{}", uuid::Uuid::new_v4()));
+        let data: Vec<u8> = (0..=255u8).cycle().take(3000).collect();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
+        let frame = json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "result": { "contents": [{ "uri": "file:///a.bin", "blob": encoded }] }
+        })
+        .to_string();
+
+        let limits = MessageLimits::default()
+            .max_inbound(1024)
+            .spill_to(&dir)
+            .spill_threshold(100);
+        let mut limiter = limits.inbound();
+        let LimitedFrame::Complete(kept) = limit(&mut limiter, &frame) else {
+            panic!("expected the frame to fit once the blob was spilled");
+        };
+        let value: Value = serde_json::from_str(&kept).unwrap();
+        let content = &value["result"]["contents"][0];
+        assert_eq!(content["blob"], "");
+        assert_eq!(content["_meta"][SPILL_META_KEY]["size"], 3000);
+        let path = content["_meta"][SPILL_META_KEY]["path"].as_str().unwrap();
+        assert_eq!(fs::read(path).unwrap(), data);
+
+        // An existing `_meta` after the blob gets the note added to it
+        let frame = format!(
+            r#"{{"jsonrpc":"2.0","id":5,"result":{{"contents":[{{"blob":"{}","_meta":{{"k":1}}}}]}}}}"#,
+            encoded
+        );
+        let LimitedFrame::Complete(kept) = limit(&mut limiter, &frame) else {
+            panic!("expected a complete frame");
+        };
+        let value: Value = serde_json::from_str(&kept).unwrap();
+        let meta = &value["result"]["contents"][0]["_meta"];
+        assert_eq!(meta["k"], 1);
+        assert_eq!(meta[SPILL_META_KEY]["size"], 3000);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_splits_lines() {
+        let input = b"{\"a\":1}\n\n{\"b\":2}".to_vec();
+        let mut reader = tokio::io::BufReader::with_capacity(4, &input[..]);
+        let mut limiter = MessageLimits::default().inbound();
+
+        let mut frames = Vec::new();
+        while let Some(frame) = read_frame(&mut reader, &mut limiter).await.unwrap() {
+            frames.push(frame);
+        }
+        assert_eq!(
+            frames,
+            [
+                LimitedFrame::Complete("{\"a\":1}".into()),
+                LimitedFrame::Complete(String::new()),
+                LimitedFrame::Complete("{\"b\":2}".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outbound_limit() {
+        let limits = MessageLimits::default().max_outbound(64);
+        let small =
+            JsonRpcMessage::Request(crate::messages::JsonRpcRequest::without_params(1, "ping"));
+        assert!(limits.check_outbound("stdio", &small).is_ok());
+
+        let large = JsonRpcMessage::Request(crate::messages::JsonRpcRequest::new(
+            1,
+            "tools/call",
+            json!({ "name": "echo", "arguments": { "text": "x".repeat(100) } }),
+        ));
+        let error = limits.check_outbound("stdio", &large).unwrap_err();
+        assert!(matches!(
+            error,
+            TransportError::MessageTooLarge { limit: 64, .. }
+        ));
+    }
+}
@@ -0,0 +1,425 @@
+//! In-memory transport for in-process client/server pairs.
+//!
+//! [`InMemoryTransport::pair`] returns two connected ends backed by channels.
+//! Whatever one end sends, the other receives, so a client can talk to a mock
+//! or embedded server without spawning processes or opening sockets. This is
+//! mainly useful for fast tests of client initialization, interceptors and
+//! notification handling.
+//!
+//! Both ends implement [`Transport`]. The serving end reads requests with
+//! [`Transport::receive_message`] and answers with
+//! [`InMemoryTransport::send_message`].
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use super::config::InMemoryConfig;
+use super::{MessageReceiver, MessageSender, Transport, TransportConfig, TransportInfo};
+use crate::correlator::RequestCorrelator;
+use crate::error::{McpResult, TransportError};
+use crate::messages::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::rt::{self, timeout, Task};
+
+const TRANSPORT_TYPE: &str = "in-memory";
+
+/// One end of an in-process channel pair.
+///
+/// Once connected, a background task hands each response to the request
+/// waiting for its ID, so several requests can be outstanding at once.
+/// Anything else is queued and returned by [`Transport::receive_message`].
+pub struct InMemoryTransport {
+    config: TransportConfig,
+    info: Arc<Mutex<TransportInfo>>,
+    outbound: Mutex<Option<MessageSender>>,
+    inbound: Mutex<Option<MessageReceiver>>,
+    messages: tokio::sync::Mutex<Option<MessageReceiver>>,
+    correlator: RequestCorrelator,
+    router: Mutex<Option<Task>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl InMemoryTransport {
+    /// Create two ends that talk to each other, conventionally used as
+    /// `(client, server)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mcp_probe_core::transport::{InMemoryTransport, Transport};
+    ///
+    /// let (client, server) = InMemoryTransport::pair();
+    /// assert!(!client.is_connected());
+    /// ```
+    pub fn pair() -> (Self, Self) {
+        Self::named_pair("client", "server")
+    }
+
+    /// Create a pair whose ends carry the given names in their config.
+    pub fn named_pair(first: impl Into<String>, second: impl Into<String>) -> (Self, Self) {
+        let (first_tx, first_rx) = mpsc::unbounded_channel();
+        let (second_tx, second_rx) = mpsc::unbounded_channel();
+        (
+            Self::new(first.into(), second_tx, first_rx),
+            Self::new(second.into(), first_tx, second_rx),
+        )
+    }
+
+    fn new(name: String, outbound: MessageSender, inbound: MessageReceiver) -> Self {
+        Self {
+            config: TransportConfig::InMemory(InMemoryConfig { name }),
+            info: Arc::new(Mutex::new(TransportInfo::new(TRANSPORT_TYPE))),
+            outbound: Mutex::new(Some(outbound)),
+            inbound: Mutex::new(Some(inbound)),
+            messages: tokio::sync::Mutex::new(None),
+            correlator: RequestCorrelator::new(TRANSPORT_TYPE),
+            router: Mutex::new(None),
+        }
+    }
+
+    /// Send any message to the other end.
+    ///
+    /// This is how a serving end answers requests, since [`Transport`] only
+    /// sends requests and notifications.
+    pub fn send_message(&self, message: JsonRpcMessage) -> McpResult<()> {
+        let outbound = self.outbound()?;
+        outbound
+            .send(message)
+            .map_err(|_| TransportError::ConnectionLost {
+                transport_type: TRANSPORT_TYPE.to_string(),
+                reason: "Other end was dropped".to_string(),
+            })?;
+        Ok(())
+    }
+
+    fn outbound(&self) -> McpResult<MessageSender> {
+        let connected = lock(&self.info).connected;
+        match &*lock(&self.outbound) {
+            Some(outbound) if connected => Ok(outbound.clone()),
+            _ => Err(TransportError::NotConnected {
+                transport_type: TRANSPORT_TYPE.to_string(),
+                reason: "Transport not connected".to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Route responses to their waiting requests and queue everything else.
+    ///
+    /// Ends when the other end goes away, which closes the queue.
+    fn spawn_router(&self, mut inbound: MessageReceiver) -> MessageReceiver {
+        let (queue_tx, queue_rx) = mpsc::unbounded_channel();
+        let correlator = self.correlator.clone();
+        let handle = rt::spawn(async move {
+            while let Some(message) = inbound.recv().await {
+                let message = match message {
+                    JsonRpcMessage::Response(response) => match correlator.complete(response) {
+                        Some(unclaimed) => JsonRpcMessage::Response(unclaimed),
+                        None => continue,
+                    },
+                    other => other,
+                };
+                if queue_tx.send(message).is_err() {
+                    break;
+                }
+            }
+            correlator.cancel_all();
+        });
+        *lock(&self.router) = Some(handle);
+        queue_rx
+    }
+
+    fn count_received(&self, message: &JsonRpcMessage) {
+        let mut info = lock(&self.info);
+        match message {
+            JsonRpcMessage::Response(_) => info.increment_responses_received(),
+            JsonRpcMessage::Notification(_) => info.increment_notifications_received(),
+            JsonRpcMessage::Request(_) => {}
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for InMemoryTransport {
+    async fn connect(&self) -> McpResult<()> {
+        match &*lock(&self.outbound) {
+            Some(outbound) if !outbound.is_closed() => {}
+            Some(_) => {
+                return Err(TransportError::ConnectionFailed {
+                    transport_type: TRANSPORT_TYPE.to_string(),
+                    reason: "Other end was dropped".to_string(),
+                }
+                .into())
+            }
+            None => {
+                return Err(TransportError::ConnectionFailed {
+                    transport_type: TRANSPORT_TYPE.to_string(),
+                    reason: "In-memory transports cannot reconnect once disconnected".to_string(),
+                }
+                .into())
+            }
+        }
+
+        let inbound = lock(&self.inbound).take();
+        if let Some(inbound) = inbound {
+            *self.messages.lock().await = Some(self.spawn_router(inbound));
+        }
+        lock(&self.info).mark_connected();
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> McpResult<()> {
+        // Dropping the sender lets the other end observe the disconnect
+        lock(&self.outbound).take();
+        if let Some(router) = lock(&self.router).take() {
+            router.abort();
+        }
+        self.correlator.cancel_all();
+        self.messages.lock().await.take();
+        lock(&self.info).mark_disconnected();
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        lock(&self.info).connected && lock(&self.outbound).is_some()
+    }
+
+    async fn send_request(
+        &self,
+        request: JsonRpcRequest,
+        timeout_duration: Option<Duration>,
+    ) -> McpResult<JsonRpcResponse> {
+        let pending = self.correlator.register(&request.id)?;
+        self.send_message(JsonRpcMessage::Request(request))?;
+        lock(&self.info).increment_requests_sent();
+
+        let response = pending
+            .wait(timeout_duration.unwrap_or(Duration::from_secs(30)))
+            .await?;
+
+        lock(&self.info).increment_responses_received();
+        Ok(response)
+    }
+
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
+        self.send_message(JsonRpcMessage::Notification(notification))?;
+        lock(&self.info).increment_notifications_sent();
+        Ok(())
+    }
+
+    async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        self.send_message(JsonRpcMessage::Response(response))
+    }
+
+    async fn receive_message(
+        &self,
+        timeout_duration: Option<Duration>,
+    ) -> McpResult<JsonRpcMessage> {
+        self.outbound()?;
+
+        let mut messages = self.messages.lock().await;
+        let queue = messages
+            .as_mut()
+            .ok_or_else(|| TransportError::NotConnected {
+                transport_type: TRANSPORT_TYPE.to_string(),
+                reason: "Transport not connected".to_string(),
+            })?;
+
+        let received = match timeout_duration {
+            Some(timeout_duration) => {
+                timeout(timeout_duration, queue.recv()).await.map_err(|_| {
+                    TransportError::TimeoutError {
+                        transport_type: TRANSPORT_TYPE.to_string(),
+                        reason: format!("Message receive timed out after {:?}", timeout_duration),
+                    }
+                })?
+            }
+            None => queue.recv().await,
+        };
+        drop(messages);
+
+        let message = received.ok_or_else(|| TransportError::ConnectionLost {
+            transport_type: TRANSPORT_TYPE.to_string(),
+            reason: "Other end disconnected".to_string(),
+        })?;
+
+        self.count_received(&message);
+        Ok(message)
+    }
+
+    fn get_info(&self) -> TransportInfo {
+        let mut info = lock(&self.info).clone();
+        if let TransportConfig::InMemory(config) = &self.config {
+            info.add_metadata("name", serde_json::json!(config.name));
+        }
+        info.add_metadata(
+            "pending_requests",
+            serde_json::json!(self.correlator.pending_count()),
+        );
+        info
+    }
+
+    fn get_config(&self) -> &TransportConfig {
+        &self.config
+    }
+}
+
+impl Drop for InMemoryTransport {
+    fn drop(&mut self) {
+        if let Some(router) = lock(&self.router).take() {
+            router.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ClientConfig, DefaultNotificationHandler, McpClient};
+    use crate::messages::{Implementation, JsonRpcError};
+    use serde_json::json;
+
+    /// Answer requests like a minimal server until the client goes away.
+    async fn serve(server: InMemoryTransport) {
+        server.connect().await.unwrap();
+        while let Ok(message) = server.receive_message(None).await {
+            let JsonRpcMessage::Request(request) = message else {
+                continue;
+            };
+            let response = match request.method.as_str() {
+                "initialize" => JsonRpcResponse::success(
+                    request.id,
+                    json!({
+                        "protocolVersion": "2024-11-05",
+                        "capabilities": { "tools": {} },
+                        "serverInfo": { "name": "mock", "version": "1.0.0" }
+                    }),
+                ),
+                "tools/list" => {
+                    server
+                        .send_notification(JsonRpcNotification::new(
+                            "notifications/tools/list_changed",
+                            json!({}),
+                        ))
+                        .await
+                        .unwrap();
+                    JsonRpcResponse::success(
+                        request.id,
+                        json!({ "tools": [{ "name": "echo", "description": "Echo", "inputSchema": {} }] }),
+                    )
+                }
+                _ => JsonRpcResponse::error(
+                    request.id,
+                    JsonRpcError::new(-32601, "Method not found", None),
+                ),
+            };
+            server
+                .send_message(JsonRpcMessage::Response(response))
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_initializes_against_in_process_server() {
+        let (client_end, server_end) = InMemoryTransport::pair();
+        tokio::spawn(serve(server_end));
+
+        let mut client = McpClient::with_transport(
+            Arc::new(client_end),
+            ClientConfig::default(),
+            Box::new(DefaultNotificationHandler),
+        );
+        let server_info = client
+            .connect(Implementation::new("test-client", "0.1.0"))
+            .await
+            .unwrap();
+        assert_eq!(server_info.implementation.name, "mock");
+
+        let response = client.send_request("tools/list", json!({})).await.unwrap();
+        assert_eq!(response.result.unwrap()["tools"][0]["name"], "echo");
+    }
+
+    #[tokio::test]
+    async fn test_messages_arriving_during_request_are_queued() {
+        let (client, server) = InMemoryTransport::pair();
+        tokio::spawn(serve(server));
+        client.connect().await.unwrap();
+
+        let response = client
+            .send_request(JsonRpcRequest::new("1", "tools/list", json!({})), None)
+            .await
+            .unwrap();
+        assert!(response.result.is_some());
+
+        let queued = client
+            .receive_message(Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            queued,
+            JsonRpcMessage::Notification(ref n) if n.method == "notifications/tools/list_changed"
+        ));
+        assert_eq!(client.get_info().notifications_received, 1);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_is_seen_by_other_end() {
+        let (client, server) = InMemoryTransport::pair();
+        client.connect().await.unwrap();
+        server.connect().await.unwrap();
+
+        client.disconnect().await.unwrap();
+        assert!(server.receive_message(None).await.is_err());
+        assert!(client.connect().await.is_err());
+        assert!(client
+            .send_notification(JsonRpcNotification::new("ping", json!({})))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shared_transport_carries_concurrent_requests() {
+        let (client, server) = InMemoryTransport::pair();
+        server.connect().await.unwrap();
+        let client: Arc<dyn Transport> = Arc::new(client);
+        client.connect().await.unwrap();
+
+        // Answer requests in reverse order of arrival
+        tokio::spawn(async move {
+            let mut requests = Vec::new();
+            while requests.len() < 4 {
+                if let Ok(JsonRpcMessage::Request(request)) = server.receive_message(None).await {
+                    requests.push(request);
+                }
+            }
+            for request in requests.into_iter().rev() {
+                let response = JsonRpcResponse::success(request.id, json!({}));
+                server
+                    .send_message(JsonRpcMessage::Response(response))
+                    .unwrap();
+            }
+            server.receive_message(None).await.ok();
+        });
+
+        let requests = (0..4).map(|i| {
+            let client = Arc::clone(&client);
+            tokio::spawn(async move {
+                let id = format!("req-{}", i);
+                let response = client
+                    .send_request(JsonRpcRequest::new(id.clone(), "ping", json!({})), None)
+                    .await
+                    .unwrap();
+                assert_eq!(response.id.to_string(), id);
+            })
+        });
+        for request in requests.collect::<Vec<_>>() {
+            request.await.unwrap();
+        }
+        assert_eq!(client.get_info().responses_received, 4);
+    }
+}
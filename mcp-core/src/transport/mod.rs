@@ -4,6 +4,7 @@
 //! - **stdio**: Local process communication via stdin/stdout
 //! - **HTTP+SSE**: Remote servers using HTTP requests + Server-Sent Events
 //! - **HTTP Streaming**: Full-duplex HTTP streaming for bidirectional communication
+//! - **In-memory**: Channel pairs for in-process servers and tests
 //!
 //! The transport layer is designed to be:
 //! - **Transport-agnostic**: Same interface for all transport types
@@ -24,7 +25,7 @@
 //!     // Create a transport from configuration
 //!     let config = TransportConfig::stdio("python", &["server.py"]);
 //!         
-//!     let mut transport = TransportFactory::create(config).await?;
+//!     let transport = TransportFactory::create(config).await?;
 //!     
 //!     // Connect transport
 //!     transport.connect().await?;
@@ -38,8 +39,23 @@
 //! }
 //! ```
 
+pub mod breaker;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bridge;
+#[cfg(feature = "http")]
+pub mod compression;
 pub mod config;
+pub mod container;
 pub mod factory;
+pub mod framing;
+pub mod hedge;
+pub mod limits;
+pub mod memory;
+#[cfg(feature = "http")]
+pub mod origin;
+pub mod prerequisites;
+pub mod sandbox;
+pub mod secrets;
 
 #[cfg(feature = "stdio")]
 pub mod stdio;
@@ -50,14 +66,30 @@ pub mod http_sse;
 #[cfg(feature = "http-stream")]
 pub mod http_stream;
 
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod browser;
+
+pub use breaker::{BreakerConfig, BreakerTransport, CircuitBreaker, CircuitEvent, CircuitState};
+#[cfg(not(target_arch = "wasm32"))]
+pub use bridge::{bridge, Bridge, BridgeEnd, BridgeSide, BridgeStats, FlowStats};
+#[cfg(feature = "http")]
+pub use compression::CompressionStats;
 pub use config::*;
+pub use container::{ContainerConfig, ProcessRuntime};
 pub use factory::*;
+pub use framing::{FrameParser, ParseMode, ProtocolDiagnostic};
+pub use hedge::{HedgeConfig, HedgeStats, HedgedTransport};
+pub use limits::{MessageLimits, Oversized};
+pub use memory::InMemoryTransport;
+pub use prerequisites::{CommandRequirement, PrerequisiteCheck, Prerequisites};
+pub use sandbox::{SandboxConfig, SandboxTool};
+pub use secrets::{DefaultSecretResolver, SecretResolver};
 
 use crate::error::{McpResult, TransportError};
 use crate::messages::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
 use async_trait::async_trait;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
 /// Core transport trait for MCP communication.
 ///
@@ -72,19 +104,22 @@ use tokio::sync::mpsc;
 /// - **Async**: All operations are async for maximum concurrency
 /// - **Reliable**: Handle connection failures and provide retry mechanisms
 /// - **Observable**: Provide hooks for monitoring and debugging
+/// - **Shareable**: Every method takes `&self` and implementations synchronize
+///   internally, so one `Arc<dyn Transport>` can serve the client, a keepalive
+///   task and a monitor at once, with requests from each in flight together
 #[async_trait]
 pub trait Transport: Send + Sync {
     /// Connect to the MCP server.
     ///
     /// This establishes the underlying connection (process spawn, HTTP connection, etc.)
     /// but does not perform MCP protocol initialization.
-    async fn connect(&mut self) -> McpResult<()>;
+    async fn connect(&self) -> McpResult<()>;
 
     /// Disconnect from the MCP server.
     ///
     /// This cleanly closes the connection and releases any resources.
     /// Should be called when the MCP session is complete.
-    async fn disconnect(&mut self) -> McpResult<()>;
+    async fn disconnect(&self) -> McpResult<()>;
 
     /// Check if the transport is currently connected.
     fn is_connected(&self) -> bool;
@@ -103,7 +138,7 @@ pub trait Transport: Send + Sync {
     ///
     /// The corresponding JSON-RPC response, or an error if the request fails.
     async fn send_request(
-        &mut self,
+        &self,
         request: JsonRpcRequest,
         timeout: Option<Duration>,
     ) -> McpResult<JsonRpcResponse>;
@@ -116,7 +151,20 @@ pub trait Transport: Send + Sync {
     /// # Arguments
     ///
     /// * `notification` - The JSON-RPC notification to send
-    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()>;
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()>;
+
+    /// Answer a request received with [`Transport::receive_message`].
+    ///
+    /// Servers send requests of their own, such as `sampling/createMessage`
+    /// or `roots/list`. The default reports that the transport cannot answer
+    /// them.
+    async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        Err(TransportError::SendFailed {
+            transport_type: self.get_config().transport_type().to_string(),
+            reason: format!("Cannot answer server request {}", response.id),
+        }
+        .into())
+    }
 
     /// Receive the next message from the server.
     ///
@@ -127,7 +175,7 @@ pub trait Transport: Send + Sync {
     /// # Arguments
     ///
     /// * `timeout` - Optional timeout for receiving (blocks indefinitely if None)
-    async fn receive_message(&mut self, timeout: Option<Duration>) -> McpResult<JsonRpcMessage>;
+    async fn receive_message(&self, timeout: Option<Duration>) -> McpResult<JsonRpcMessage>;
 
     /// Get transport-specific metadata and statistics.
     ///
@@ -137,6 +185,22 @@ pub trait Transport: Send + Sync {
 
     /// Get the transport configuration used for this instance.
     fn get_config(&self) -> &TransportConfig;
+
+    /// Subscribe to diagnostics about frames received from now on.
+    ///
+    /// `None` for transports that do not parse frames themselves, such as
+    /// the in-memory transport.
+    fn protocol_diagnostics(&self) -> Option<broadcast::Receiver<ProtocolDiagnostic>> {
+        None
+    }
+
+    /// Subscribe to state changes of the circuit breaker in front of the
+    /// server.
+    ///
+    /// `None` for transports without one; see [`BreakerTransport`].
+    fn circuit_events(&self) -> Option<broadcast::Receiver<CircuitEvent>> {
+        None
+    }
 }
 
 /// Transport information and statistics.
@@ -264,6 +328,7 @@ pub trait TransportHelper {
     }
 
     /// Create a timeout future for the given duration.
+    #[cfg(not(target_arch = "wasm32"))]
     fn timeout_future(duration: Duration) -> tokio::time::Sleep {
         tokio::time::sleep(duration)
     }
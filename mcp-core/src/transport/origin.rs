@@ -0,0 +1,367 @@
+//! Origin and Host validation against DNS rebinding.
+//!
+//! A page on `http://evil.example` can rebind its name to `127.0.0.1` and then
+//! talk to a server listening on localhost as if the server were its own
+//! origin. The MCP transport security guidance therefore asks servers to
+//! validate the `Origin` header of incoming requests, and local servers to
+//! accept only requests addressed to a loopback host: a rebound request still
+//! carries the attacker's name in both headers. [`OriginPolicy`] performs both
+//! checks.
+//!
+//! Clients use the same policy from the other side: the HTTP transports
+//! refuse redirects to origins outside it, and notice when a name that is not
+//! a loopback name answers from a loopback address.
+//!
+//! Origin patterns are `scheme://host[:port]`, where the port defaults to the
+//! scheme's and may be `*`, and the host may be `*.domain`. `*` allows any
+//! origin and `null` the opaque origin. Host patterns are host names without
+//! a port, optionally `*.domain`, or `*` for any host.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use mcp_probe_core::transport::origin::OriginPolicy;
+//!
+//! let policy = OriginPolicy::localhost();
+//! assert!(policy
+//!     .check(Some("http://localhost:6274"), Some("127.0.0.1:3000"))
+//!     .is_ok());
+//!
+//! // After rebinding, the page still sends its own origin and host
+//! assert!(policy
+//!     .check(Some("http://evil.example:3000"), Some("evil.example:3000"))
+//!     .is_err());
+//! ```
+
+use std::net::{IpAddr, SocketAddr};
+
+use url::Url;
+
+use crate::error::OriginError;
+
+/// Host names that always refer to the local machine
+pub const LOOPBACK_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// Which origins and hosts requests may come from or go to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginPolicy {
+    origins: Vec<String>,
+    hosts: Vec<String>,
+    require_origin: bool,
+}
+
+impl Default for OriginPolicy {
+    fn default() -> Self {
+        Self::localhost()
+    }
+}
+
+impl OriginPolicy {
+    /// A policy that allows no browser origin but any host; add to it with
+    /// [`allow_origin`](Self::allow_origin) and restrict it with
+    /// [`allow_host`](Self::allow_host).
+    pub fn new() -> Self {
+        Self {
+            origins: Vec::new(),
+            hosts: vec!["*".to_string()],
+            require_origin: false,
+        }
+    }
+
+    /// Policy for a server listening on loopback: pages from a loopback
+    /// origin on any port, and requests addressed to a loopback host.
+    pub fn localhost() -> Self {
+        Self {
+            origins: LOOPBACK_HOSTS
+                .iter()
+                .flat_map(|host| ["http", "https"].map(|scheme| format!("{scheme}://{host}:*")))
+                .collect(),
+            hosts: LOOPBACK_HOSTS.iter().map(|host| host.to_string()).collect(),
+            require_origin: false,
+        }
+    }
+
+    /// Policy for a client of `url`: its own origin, and loopback origins
+    /// as well when it is local.
+    pub fn for_url(url: &Url) -> Self {
+        let mut policy = if is_loopback_host(url.host_str().unwrap_or_default()) {
+            Self::localhost()
+        } else {
+            Self::new()
+        };
+        policy.origins.push(url.origin().ascii_serialization());
+        policy.hosts = vec!["*".to_string()];
+        policy
+    }
+
+    /// Also allow origins matching `pattern`.
+    pub fn allow_origin(mut self, pattern: impl Into<String>) -> Self {
+        self.origins.push(pattern.into());
+        self
+    }
+
+    /// Also allow requests addressed to hosts matching `pattern`. The first
+    /// call replaces the "any host" of [`new`](Self::new).
+    pub fn allow_host(mut self, pattern: impl Into<String>) -> Self {
+        if self.hosts == ["*"] {
+            self.hosts.clear();
+        }
+        self.hosts.push(pattern.into());
+        self
+    }
+
+    /// Refuse requests without an Origin header. Off by default, since
+    /// clients other than browsers do not send one.
+    pub fn require_origin(mut self, require: bool) -> Self {
+        self.require_origin = require;
+        self
+    }
+
+    /// Whether `origin` matches one of the allowed patterns.
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.origins
+            .iter()
+            .any(|pattern| origin_matches(pattern, origin))
+    }
+
+    /// Check the Origin and Host headers of an incoming request.
+    pub fn check(&self, origin: Option<&str>, host: Option<&str>) -> Result<(), OriginError> {
+        self.check_host(host)?;
+        self.check_origin(origin)
+    }
+
+    /// Check the Origin header of an incoming request.
+    pub fn check_origin(&self, origin: Option<&str>) -> Result<(), OriginError> {
+        match origin {
+            None if self.require_origin => Err(OriginError::MissingOrigin),
+            None => Ok(()),
+            Some(origin) if self.allows_origin(origin) => Ok(()),
+            Some(origin) => Err(OriginError::OriginNotAllowed {
+                origin: origin.to_string(),
+            }),
+        }
+    }
+
+    /// Check the Host header of an incoming request.
+    pub fn check_host(&self, host: Option<&str>) -> Result<(), OriginError> {
+        if self.hosts.iter().any(|pattern| pattern == "*") {
+            return Ok(());
+        }
+        let Some(header) = host else {
+            return Err(OriginError::MissingHost);
+        };
+        let allowed =
+            split_origin(&format!("http://{}", header.trim())).is_some_and(|(_, name, _)| {
+                self.hosts
+                    .iter()
+                    .any(|pattern| name_matches(pattern, &name))
+            });
+        if allowed {
+            Ok(())
+        } else {
+            Err(OriginError::HostNotAllowed {
+                host: header.to_string(),
+            })
+        }
+    }
+
+    /// Check a URL a client is about to follow, e.g. a redirect target.
+    pub fn check_url(&self, url: &Url) -> Result<(), OriginError> {
+        self.check_origin(Some(&url.origin().ascii_serialization()))
+    }
+
+    /// Check the address a response to `url` came from.
+    ///
+    /// A name that is not a loopback name answering from a loopback address
+    /// is what DNS rebinding looks like from the client's side.
+    pub fn check_peer(&self, url: &Url, peer: SocketAddr) -> Result<(), OriginError> {
+        let host = url.host_str().unwrap_or_default();
+        if peer.ip().is_loopback() && !is_loopback_host(host) {
+            return Err(OriginError::Rebinding {
+                host: host.to_string(),
+                address: peer.ip().to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Whether `host` (a URL host, brackets allowed) names the local machine
+pub fn is_loopback_host(host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    if host == "localhost" || host.ends_with(".localhost") {
+        return true;
+    }
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Redirect policy that follows redirects only within `policy`
+pub(crate) fn redirect_policy(policy: OriginPolicy) -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= 10 {
+            return attempt.error("too many redirects");
+        }
+        match policy.check_url(attempt.url()) {
+            Ok(()) => attempt.follow(),
+            Err(e) => attempt.error(e),
+        }
+    })
+}
+
+/// The origin check behind a failed request, if that is why it failed
+pub(crate) fn rejection(error: &reqwest::Error) -> Option<OriginError> {
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        if let Some(rejection) = error.downcast_ref::<OriginError>() {
+            return Some(rejection.clone());
+        }
+        source = error.source();
+    }
+    None
+}
+
+fn origin_matches(pattern: &str, origin: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern == "*" || pattern.eq_ignore_ascii_case(origin.trim()) {
+        return true;
+    }
+    let (Some(pattern), Some(origin)) = (split_origin(pattern), split_origin(origin)) else {
+        return false;
+    };
+    pattern.0 == origin.0
+        && name_matches(&pattern.1, &origin.1)
+        && (pattern.2 == "*" || pattern.2 == origin.2)
+}
+
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_prefix("*.") {
+        Some(domain) => name.ends_with(&format!(".{domain}")),
+        None => pattern == name,
+    }
+}
+
+/// Scheme, host and port of a serialized origin, lowercased, with the
+/// scheme's default port filled in
+fn split_origin(origin: &str) -> Option<(String, String, String)> {
+    let (scheme, rest) = origin.trim().split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    let rest = rest.strip_suffix('/').unwrap_or(rest);
+    if rest.is_empty() || rest.contains(['/', '?', '#', '@']) {
+        return None;
+    }
+    let (host, port) = match rest.strip_prefix('[') {
+        Some(bracketed) => {
+            let (address, after) = bracketed.split_once(']')?;
+            let port = match after {
+                "" => None,
+                after => Some(after.strip_prefix(':')?),
+            };
+            (format!("[{address}]"), port)
+        }
+        None => match rest.rsplit_once(':') {
+            Some((host, port)) => (host.to_string(), Some(port)),
+            None => (rest.to_string(), None),
+        },
+    };
+    let port = match port {
+        Some("*") => "*".to_string(),
+        Some(port) => port.parse::<u16>().ok()?.to_string(),
+        None => match scheme.as_str() {
+            "http" | "ws" => "80".to_string(),
+            "https" | "wss" => "443".to_string(),
+            _ => return None,
+        },
+    };
+    Some((scheme, host.to_ascii_lowercase(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebound_requests_are_refused() {
+        let policy = OriginPolicy::localhost();
+
+        // Local tools and non-browser clients
+        assert!(policy.check(None, Some("localhost:3000")).is_ok());
+        assert!(policy
+            .check(Some("http://127.0.0.1:6274"), Some("127.0.0.1:3000"))
+            .is_ok());
+        assert!(policy
+            .check(Some("https://[::1]"), Some("[::1]:3000"))
+            .is_ok());
+
+        // evil.example rebound to 127.0.0.1: same-origin from the browser's view
+        assert_eq!(
+            policy.check(Some("http://evil.example:3000"), Some("evil.example:3000")),
+            Err(OriginError::HostNotAllowed {
+                host: "evil.example:3000".to_string()
+            })
+        );
+        // Without an Origin, as for a plain GET of the event stream
+        assert!(policy.check(None, Some("evil.example:3000")).is_err());
+        // A cross-origin page sending to localhost directly
+        assert_eq!(
+            policy.check(Some("http://evil.example"), Some("localhost:3000")),
+            Err(OriginError::OriginNotAllowed {
+                origin: "http://evil.example".to_string()
+            })
+        );
+        // Look-alikes and sandboxed frames
+        for origin in [
+            "http://localhost.evil.example",
+            "http://127.0.0.1.nip.io:3000",
+            "null",
+            "not an origin",
+        ] {
+            assert!(policy.check_origin(Some(origin)).is_err(), "{origin}");
+        }
+        assert_eq!(policy.check(None, None), Err(OriginError::MissingHost));
+    }
+
+    #[test]
+    fn test_patterns_and_client_checks() {
+        let policy = OriginPolicy::new()
+            .allow_origin("https://*.example.com")
+            .allow_origin("http://app.test:*")
+            .allow_host("mcp.example.com")
+            .require_origin(true);
+        assert!(policy.allows_origin("https://ide.example.com:443"));
+        assert!(!policy.allows_origin("https://ide.example.com:8443"));
+        assert!(!policy.allows_origin("http://ide.example.com"));
+        assert!(policy.allows_origin("HTTP://APP.TEST:9000"));
+        assert!(policy.check_host(Some("MCP.example.com:443")).is_ok());
+        assert!(policy.check_host(Some("localhost")).is_err());
+        assert_eq!(policy.check_origin(None), Err(OriginError::MissingOrigin));
+
+        let remote = Url::parse("https://mcp.example.com/mcp").unwrap();
+        let client = OriginPolicy::for_url(&remote);
+        assert!(client.check_url(&remote.join("/other").unwrap()).is_ok());
+        assert!(client
+            .check_url(&Url::parse("http://localhost:8080/admin").unwrap())
+            .is_err());
+        let loopback: SocketAddr = "127.0.0.1:443".parse().unwrap();
+        assert!(matches!(
+            client.check_peer(&remote, loopback),
+            Err(OriginError::Rebinding { .. })
+        ));
+        assert!(client
+            .check_peer(&remote, "93.184.216.34:443".parse().unwrap())
+            .is_ok());
+
+        let local = Url::parse("http://localhost:3000/mcp").unwrap();
+        let client = OriginPolicy::for_url(&local);
+        assert!(client.check_peer(&local, loopback).is_ok());
+        assert!(client
+            .check_url(&Url::parse("http://127.0.0.1:3001/mcp").unwrap())
+            .is_ok());
+    }
+}
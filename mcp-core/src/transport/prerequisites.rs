@@ -0,0 +1,414 @@
+//! Prerequisite checks for stdio server profiles.
+//!
+//! A profile can declare what it needs before the server can start: commands
+//! on `PATH` (optionally with a minimum version) and environment variables.
+//! The launch program itself is always checked. Failures carry a remedy such
+//! as "install Node.js" or "set GITHUB_TOKEN" instead of surfacing later as an
+//! opaque spawn error.
+//!
+//! # Examples
+//!
+//! ```toml
+//! type = "stdio"
+//! command = "npx"
+//! args = ["-y", "@modelcontextprotocol/server-github"]
+//! timeout = "30s"
+//!
+//! [environment]
+//!
+//! [prerequisites]
+//! env = ["GITHUB_PERSONAL_ACCESS_TOKEN"]
+//!
+//! [[prerequisites.commands]]
+//! name = "node"
+//! min_version = "18.0.0"
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::process::Command;
+
+use super::StdioConfig;
+use crate::error::{ConfigError, McpResult};
+
+/// How long a `--version` probe may take.
+#[cfg(not(target_arch = "wasm32"))]
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default pattern used to pull a version number out of `--version` output.
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_VERSION_PATTERN: &str = r"(\d+(?:\.\d+)+)";
+
+/// Requirements that must hold before a stdio server is launched.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Prerequisites {
+    /// Commands that must be available
+    #[serde(default)]
+    pub commands: Vec<CommandRequirement>,
+
+    /// Environment variables that must be set (in the profile or the parent environment)
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+impl Prerequisites {
+    /// Whether no prerequisites are declared.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.env.is_empty()
+    }
+}
+
+/// A command that must be on `PATH`, optionally at a minimum version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandRequirement {
+    /// Executable name or path
+    pub name: String,
+
+    /// Minimum acceptable version (e.g. `18.0.0`)
+    #[serde(default)]
+    pub min_version: Option<String>,
+
+    /// Arguments used to print the version
+    #[serde(default = "default_version_args")]
+    pub version_args: Vec<String>,
+
+    /// Regex extracting the version from the output; the first capture group
+    /// is used if present
+    #[serde(default)]
+    pub version_pattern: Option<String>,
+
+    /// Custom advice shown when the requirement is not met
+    #[serde(default)]
+    pub install_hint: Option<String>,
+}
+
+fn default_version_args() -> Vec<String> {
+    vec!["--version".to_string()]
+}
+
+impl CommandRequirement {
+    /// Require a command to be present.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            min_version: None,
+            version_args: default_version_args(),
+            version_pattern: None,
+            install_hint: None,
+        }
+    }
+
+    /// Require at least the given version.
+    pub fn min_version(mut self, version: impl Into<String>) -> Self {
+        self.min_version = Some(version.into());
+        self
+    }
+}
+
+/// Result of evaluating one prerequisite.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrerequisiteCheck {
+    /// What was checked (e.g. `command node`, `env GITHUB_TOKEN`)
+    pub subject: String,
+    /// Whether the prerequisite is satisfied
+    pub passed: bool,
+    /// What was found
+    pub detail: String,
+    /// What to do about a failure
+    pub remedy: Option<String>,
+}
+
+impl PrerequisiteCheck {
+    fn pass(subject: String, detail: String) -> Self {
+        Self {
+            subject,
+            passed: true,
+            detail,
+            remedy: None,
+        }
+    }
+
+    fn fail(subject: String, detail: String, remedy: String) -> Self {
+        Self {
+            subject,
+            passed: false,
+            detail,
+            remedy: Some(remedy),
+        }
+    }
+}
+
+/// Evaluate every prerequisite of a stdio configuration.
+pub async fn check(config: &StdioConfig) -> Vec<PrerequisiteCheck> {
+    let mut checks = Vec::new();
+
+    // The launch program is always required
+    let launch = match config.launch_command() {
+        Ok((program, _)) => program,
+        Err(_) => config.command.clone(),
+    };
+    let declared = |name: &str| {
+        config
+            .prerequisites
+            .commands
+            .iter()
+            .any(|requirement| requirement.name == name)
+    };
+    if !launch.is_empty() && !declared(&launch) {
+        checks.push(check_command(&CommandRequirement::new(launch.clone())).await);
+    }
+
+    // A sandbox also needs its tooling, and runs the command from the host filesystem
+    if let Some(ref sandbox) = config.sandbox {
+        let mut implied: Vec<&str> = sandbox.required_commands();
+        implied.push(&config.command);
+        for name in implied {
+            if name != launch && !name.is_empty() && !declared(name) {
+                checks.push(check_command(&CommandRequirement::new(name)).await);
+            }
+        }
+    }
+
+    for requirement in &config.prerequisites.commands {
+        checks.push(check_command(requirement).await);
+    }
+
+    for name in &config.prerequisites.env {
+        let subject = format!("env {}", name);
+        let set = config.environment.get(name).is_some_and(|v| !v.is_empty())
+            || std::env::var_os(name).is_some_and(|v| !v.is_empty());
+        checks.push(if set {
+            PrerequisiteCheck::pass(subject, "set".to_string())
+        } else {
+            PrerequisiteCheck::fail(
+                subject,
+                "not set".to_string(),
+                format!(
+                    "set {} in the environment or the profile's [environment] table",
+                    name
+                ),
+            )
+        });
+    }
+
+    checks
+}
+
+/// Evaluate prerequisites and fail with every unmet one if any are missing.
+pub async fn ensure(config: &StdioConfig) -> McpResult<()> {
+    let issues: Vec<String> = check(config)
+        .await
+        .into_iter()
+        .filter(|c| !c.passed)
+        .map(|c| match c.remedy {
+            Some(remedy) => format!("{} ({}): {}", c.subject, c.detail, remedy),
+            None => format!("{} ({})", c.subject, c.detail),
+        })
+        .collect();
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::UnmetPrerequisites { issues }.into())
+    }
+}
+
+async fn check_command(requirement: &CommandRequirement) -> PrerequisiteCheck {
+    let subject = format!("command {}", requirement.name);
+    let hint = || {
+        requirement
+            .install_hint
+            .clone()
+            .or_else(|| install_hint(&requirement.name).map(str::to_string))
+            .unwrap_or_else(|| format!("install {} and make sure it is on PATH", requirement.name))
+    };
+
+    let Some(path) = find_on_path(&requirement.name) else {
+        return PrerequisiteCheck::fail(subject, "not found on PATH".to_string(), hint());
+    };
+
+    let Some(ref minimum) = requirement.min_version else {
+        return PrerequisiteCheck::pass(subject, path.display().to_string());
+    };
+
+    let remedy = || format!("upgrade to {} or newer: {}", minimum, hint());
+    match probe_version(&path, requirement).await {
+        Some(found) if compare_versions(&found, minimum) != Ordering::Less => {
+            PrerequisiteCheck::pass(subject, format!("version {}", found))
+        }
+        Some(found) => PrerequisiteCheck::fail(
+            subject,
+            format!("version {} is older than {}", found, minimum),
+            remedy(),
+        ),
+        None => {
+            PrerequisiteCheck::fail(subject, "could not determine version".to_string(), remedy())
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn probe_version(path: &Path, requirement: &CommandRequirement) -> Option<String> {
+    let pattern = requirement
+        .version_pattern
+        .as_deref()
+        .unwrap_or(DEFAULT_VERSION_PATTERN);
+    let regex = Regex::new(pattern).ok()?;
+
+    let output = tokio::time::timeout(
+        VERSION_PROBE_TIMEOUT,
+        Command::new(path)
+            .args(&requirement.version_args)
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let captures = regex.captures(&text)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str().to_string())
+}
+
+/// Browsers cannot run commands, so no version is ever found
+#[cfg(target_arch = "wasm32")]
+async fn probe_version(_path: &Path, _requirement: &CommandRequirement) -> Option<String> {
+    None
+}
+
+/// Compare dotted version strings numerically (`1.10.0` > `1.9`).
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|part| {
+                part.chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect()
+    };
+
+    let (a, b) = (parse(a), parse(b));
+    let len = a.len().max(b.len());
+    for i in 0..len {
+        match a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    Ordering::Equal
+}
+
+/// Locate an executable on `PATH`; names containing a path separator are
+/// checked as-is.
+pub fn find_on_path(program: &str) -> Option<PathBuf> {
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+
+    let extensions: &[&str] = if cfg!(windows) {
+        &["exe", "cmd", "bat"]
+    } else {
+        &[""]
+    };
+
+    std::env::split_paths(&std::env::var_os("PATH")?).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let path = dir.join(program);
+            let path = if ext.is_empty() {
+                path
+            } else {
+                path.with_extension(ext)
+            };
+            path.is_file().then_some(path)
+        })
+    })
+}
+
+/// Installation advice for well-known launchers.
+pub fn install_hint(program: &str) -> Option<&'static str> {
+    let name = Path::new(program).file_stem()?.to_str()?;
+    Some(match name {
+        "node" | "npx" | "npm" => "install Node.js from https://nodejs.org",
+        "uv" | "uvx" => "install uv from https://docs.astral.sh/uv",
+        "pipx" => "install pipx from https://pipx.pypa.io",
+        "python" | "python3" => "install Python from https://www.python.org",
+        "docker" => "install Docker from https://docs.docker.com/get-docker",
+        "podman" => "install Podman from https://podman.io",
+        "bwrap" => "install bubblewrap (e.g. `apt install bubblewrap`)",
+        "firejail" => "install firejail (e.g. `apt install firejail`)",
+        "aa-exec" => "install the AppArmor utilities (e.g. `apt install apparmor-utils`)",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("v18.2.0", "18.2"), Ordering::Equal);
+        assert_eq!(compare_versions("3.11.4", "3.12"), Ordering::Less);
+    }
+
+    #[tokio::test]
+    async fn test_missing_command_and_env() {
+        let mut config = StdioConfig::new("definitely-not-a-real-mcp-server");
+        config
+            .prerequisites
+            .env
+            .push("MCP_PREREQ_TEST_UNSET".to_string());
+
+        let checks = check(&config).await;
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|c| !c.passed));
+        assert!(checks[1]
+            .remedy
+            .as_ref()
+            .unwrap()
+            .contains("set MCP_PREREQ_TEST_UNSET"));
+
+        let error = ensure(&config).await.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("definitely-not-a-real-mcp-server"));
+    }
+
+    #[tokio::test]
+    async fn test_env_from_profile_and_version_probe() {
+        let mut config = StdioConfig::new("sh").env("MCP_PREREQ_TOKEN", "abc");
+        config
+            .prerequisites
+            .env
+            .push("MCP_PREREQ_TOKEN".to_string());
+        config.prerequisites.commands.push(CommandRequirement {
+            version_args: vec!["-c".to_string(), "echo tool version 2.4.1".to_string()],
+            ..CommandRequirement::new("sh").min_version("2.4")
+        });
+        assert!(ensure(&config).await.is_ok());
+
+        config.prerequisites.commands[0].min_version = Some("3.0".to_string());
+        let checks = check(&config).await;
+        assert!(checks[0].detail.contains("older than 3.0"));
+    }
+}
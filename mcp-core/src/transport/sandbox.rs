@@ -0,0 +1,373 @@
+//! Sandboxed launch support for stdio transports.
+//!
+//! A stdio profile with a `[sandbox]` table runs an untrusted server under
+//! bubblewrap or firejail on Linux. The server gets a read-only view of the
+//! host filesystem, a private `/tmp` and no network unless the profile opts
+//! in. A compiled seccomp filter (bubblewrap) or the tool's built-in filter
+//! (firejail) can be applied, and an AppArmor profile can confine the whole
+//! sandbox through `aa-exec`.
+//!
+//! # Examples
+//!
+//! ```toml
+//! type = "stdio"
+//! command = "npx"
+//! args = ["-y", "some-third-party-mcp-server"]
+//! timeout = "30s"
+//!
+//! [environment]
+//!
+//! [sandbox]
+//! tool = "bubblewrap"
+//! writable = ["/home/me/scratch"]
+//! seccomp = "/etc/assist-mcp/deny-ptrace.bpf"
+//! apparmor = "mcp-untrusted"
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ConfigError, McpResult, TransportError};
+
+/// Value of [`SandboxConfig::seccomp`] selecting the tool's built-in filter.
+pub const DEFAULT_SECCOMP: &str = "default";
+
+/// File descriptor bubblewrap reads the seccomp filter from.
+const SECCOMP_FD: &str = "3";
+
+/// Program used to isolate a stdio server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxTool {
+    /// bubblewrap (`bwrap`)
+    Bubblewrap,
+    /// firejail
+    Firejail,
+}
+
+impl SandboxTool {
+    /// Name of the sandbox executable.
+    pub fn program(&self) -> &'static str {
+        match self {
+            SandboxTool::Bubblewrap => "bwrap",
+            SandboxTool::Firejail => "firejail",
+        }
+    }
+}
+
+/// Sandbox settings for a stdio server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    /// Sandbox program to launch the server with
+    pub tool: SandboxTool,
+
+    /// Keep the host network; by default the server runs in an empty network namespace
+    #[serde(default)]
+    pub allow_network: bool,
+
+    /// Host paths the server may write to; everything else is read-only
+    #[serde(default)]
+    pub writable: Vec<String>,
+
+    /// Seccomp filter: `"default"` for firejail's built-in filter, or the
+    /// path of a compiled BPF program for bubblewrap
+    #[serde(default)]
+    pub seccomp: Option<String>,
+
+    /// AppArmor profile applied to the sandbox with `aa-exec`
+    #[serde(default)]
+    pub apparmor: Option<String>,
+
+    /// Extra arguments passed to the sandbox program before the command
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+impl SandboxConfig {
+    /// Create sandbox settings for a tool with the default isolation.
+    pub fn new(tool: SandboxTool) -> Self {
+        Self {
+            tool,
+            allow_network: false,
+            writable: Vec::new(),
+            seccomp: None,
+            apparmor: None,
+            extra_args: Vec::new(),
+        }
+    }
+
+    /// Validate the sandbox settings.
+    pub fn validate(&self) -> McpResult<()> {
+        if !cfg!(target_os = "linux") {
+            return Err(ConfigError::InvalidValue {
+                parameter: "sandbox".to_string(),
+                value: self.tool.program().to_string(),
+                reason: "Sandboxing is only supported on Linux".to_string(),
+            }
+            .into());
+        }
+
+        if let Some(ref seccomp) = self.seccomp {
+            let reason = match self.tool {
+                SandboxTool::Bubblewrap if seccomp == DEFAULT_SECCOMP => Some(
+                    "bubblewrap has no built-in filter; give the path of a compiled BPF program",
+                ),
+                SandboxTool::Bubblewrap if !std::path::Path::new(seccomp).is_file() => {
+                    Some("Seccomp filter file does not exist")
+                }
+                SandboxTool::Firejail if seccomp != DEFAULT_SECCOMP => Some(
+                    "firejail only supports its built-in filter; use \"default\" or switch to bubblewrap",
+                ),
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                return Err(ConfigError::InvalidValue {
+                    parameter: "sandbox.seccomp".to_string(),
+                    value: seccomp.clone(),
+                    reason: reason.to_string(),
+                }
+                .into());
+            }
+        }
+
+        if self.apparmor.as_deref() == Some("") {
+            return Err(ConfigError::InvalidValue {
+                parameter: "sandbox.apparmor".to_string(),
+                value: String::new(),
+                reason: "AppArmor profile name must not be empty".to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Commands that must be installed for this sandbox to start.
+    pub fn required_commands(&self) -> Vec<&'static str> {
+        let mut commands = vec![self.tool.program()];
+        if self.apparmor.is_some() {
+            commands.push("aa-exec");
+        }
+        commands
+    }
+
+    /// The program and arguments that run `command` inside the sandbox.
+    pub fn wrap(
+        &self,
+        command: &str,
+        args: &[String],
+        working_dir: Option<&str>,
+    ) -> (String, Vec<String>) {
+        let mut wrapped = match self.tool {
+            SandboxTool::Bubblewrap => self.bubblewrap_args(working_dir),
+            SandboxTool::Firejail => self.firejail_args(),
+        };
+        wrapped.extend(self.extra_args.iter().cloned());
+        wrapped.push("--".to_string());
+        wrapped.push(command.to_string());
+        wrapped.extend(args.iter().cloned());
+
+        let mut program = self.tool.program().to_string();
+        if let (SandboxTool::Bubblewrap, Some(filter)) = (self.tool, &self.seccomp) {
+            // bwrap reads the filter from a descriptor, so open it in a shell first
+            let mut shell = vec![
+                "-c".to_string(),
+                format!(
+                    "exec bwrap --seccomp {} \"$@\" {}<\"$0\"",
+                    SECCOMP_FD, SECCOMP_FD
+                ),
+                filter.clone(),
+            ];
+            shell.append(&mut wrapped);
+            program = "sh".to_string();
+            wrapped = shell;
+        }
+
+        if let Some(ref profile) = self.apparmor {
+            let mut confined = vec!["-p".to_string(), profile.clone(), "--".to_string(), program];
+            confined.append(&mut wrapped);
+            return ("aa-exec".to_string(), confined);
+        }
+        (program, wrapped)
+    }
+
+    fn bubblewrap_args(&self, working_dir: Option<&str>) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "--die-with-parent",
+            "--new-session",
+            "--unshare-all",
+            "--ro-bind",
+            "/",
+            "/",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--tmpfs",
+            "/tmp",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        if self.allow_network {
+            args.push("--share-net".to_string());
+        }
+        for path in &self.writable {
+            args.extend(["--bind".to_string(), path.clone(), path.clone()]);
+        }
+        if let Some(dir) = working_dir {
+            args.extend(["--chdir".to_string(), dir.to_string()]);
+        }
+        args
+    }
+
+    fn firejail_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--quiet".to_string(),
+            "--noprofile".to_string(),
+            "--private-tmp".to_string(),
+            "--read-only=/".to_string(),
+        ];
+        if !self.allow_network {
+            args.push("--net=none".to_string());
+        }
+        for path in &self.writable {
+            args.push(format!("--read-write={}", path));
+        }
+        if self.seccomp.is_some() {
+            args.push("--seccomp".to_string());
+        }
+        args
+    }
+}
+
+/// Recognize a sandbox that failed to set up from the server's stderr.
+///
+/// Both tools report setup failures on stderr before the server starts:
+/// bubblewrap prefixes them with `bwrap:`, firejail with `Error:`, and
+/// `aa-exec` with its own name.
+pub fn sandbox_exit_error(
+    sandbox: &SandboxConfig,
+    stderr_tail: &[String],
+) -> Option<TransportError> {
+    let prefix = match sandbox.tool {
+        SandboxTool::Bubblewrap => "bwrap:",
+        SandboxTool::Firejail => "Error:",
+    };
+    let line = stderr_tail.iter().find(|line| {
+        let line = line.trim_start();
+        line.starts_with(prefix) || line.starts_with("aa-exec:")
+    })?;
+
+    let mut reason = line.trim().to_string();
+    if reason.contains("No permissions to create new namespace")
+        || reason.contains("setting up uid map")
+    {
+        reason.push_str(" (unprivileged user namespaces may be disabled on this host)");
+    }
+
+    Some(TransportError::SandboxError {
+        tool: sandbox.tool.program().to_string(),
+        reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::StdioConfig;
+
+    #[test]
+    fn test_bubblewrap_args() {
+        let mut sandbox = SandboxConfig::new(SandboxTool::Bubblewrap);
+        sandbox.writable.push("/srv/scratch".to_string());
+
+        let (program, args) = sandbox.wrap("server", &["--stdio".to_string()], Some("/srv"));
+        assert_eq!(program, "bwrap");
+        assert!(args.contains(&"--unshare-all".to_string()));
+        assert!(!args.contains(&"--share-net".to_string()));
+        assert!(args
+            .windows(3)
+            .any(|w| w == ["--bind", "/srv/scratch", "/srv/scratch"]));
+        assert!(args.windows(2).any(|w| w == ["--chdir", "/srv"]));
+        assert_eq!(&args[args.len() - 3..], ["--", "server", "--stdio"]);
+
+        sandbox.allow_network = true;
+        assert!(sandbox
+            .wrap("server", &[], None)
+            .1
+            .contains(&"--share-net".to_string()));
+    }
+
+    #[test]
+    fn test_seccomp_and_apparmor_wrapping() {
+        let mut sandbox = SandboxConfig::new(SandboxTool::Bubblewrap);
+        sandbox.seccomp = Some("/etc/filter.bpf".to_string());
+        sandbox.apparmor = Some("mcp-untrusted".to_string());
+
+        let (program, args) = sandbox.wrap("server", &[], None);
+        assert_eq!(program, "aa-exec");
+        assert_eq!(&args[..5], ["-p", "mcp-untrusted", "--", "sh", "-c"]);
+        assert!(args[5].starts_with("exec bwrap --seccomp 3"));
+        assert_eq!(args[6], "/etc/filter.bpf");
+        assert_eq!(sandbox.required_commands(), ["bwrap", "aa-exec"]);
+
+        let mut firejail = SandboxConfig::new(SandboxTool::Firejail);
+        firejail.seccomp = Some(DEFAULT_SECCOMP.to_string());
+        let (program, args) = firejail.wrap("server", &[], None);
+        assert_eq!(program, "firejail");
+        assert!(args.contains(&"--net=none".to_string()));
+        assert!(args.contains(&"--seccomp".to_string()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_validate_seccomp() {
+        let mut sandbox = SandboxConfig::new(SandboxTool::Bubblewrap);
+        assert!(sandbox.validate().is_ok());
+
+        sandbox.seccomp = Some(DEFAULT_SECCOMP.to_string());
+        assert!(sandbox
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("compiled BPF"));
+
+        sandbox.seccomp = Some("/definitely/missing.bpf".to_string());
+        assert!(sandbox.validate().is_err());
+
+        sandbox.tool = SandboxTool::Firejail;
+        assert!(sandbox
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("built-in filter"));
+    }
+
+    #[test]
+    fn test_launch_command_and_container_conflict() {
+        let config = StdioConfig::new("server").sandbox(SandboxConfig::new(SandboxTool::Firejail));
+        let (program, args) = config.launch_command().unwrap();
+        assert_eq!(program, "firejail");
+        assert_eq!(args.last().unwrap(), "server");
+
+        let mut contained = config.clone();
+        contained.runtime = crate::transport::ProcessRuntime::Docker;
+        assert!(contained.validate().is_err());
+    }
+
+    #[test]
+    fn test_sandbox_exit_error() {
+        let sandbox = SandboxConfig::new(SandboxTool::Bubblewrap);
+        let tail = vec![
+            "starting".to_string(),
+            "bwrap: No permissions to create new namespace".to_string(),
+        ];
+        let error = sandbox_exit_error(&sandbox, &tail).unwrap();
+        assert!(error
+            .to_string()
+            .contains("user namespaces may be disabled"));
+
+        assert!(sandbox_exit_error(&sandbox, &["Error: boom".to_string()]).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+//! Environment interpolation and secret references in configuration files.
+//!
+//! [`TransportConfig::from_file`](super::TransportConfig::from_file) runs every
+//! string value of a configuration file through [`expand_value`] before the
+//! file is parsed, so credentials can stay out of committed files:
+//!
+//! - `${NAME}` is replaced by the environment variable `NAME`, and
+//!   `${NAME:-fallback}` uses `fallback` when it is unset. `$${` writes a
+//!   literal `${`.
+//! - A value that is exactly `secret://<provider>/<key>` is replaced by the
+//!   secret the [`SecretResolver`] returns. [`DefaultSecretResolver`] knows
+//!   `secret://env/NAME` and `secret://file/<path>`, with relative paths taken
+//!   from the configuration file's directory; other stores such as a keyring
+//!   plug in through
+//!   [`TransportConfig::from_file_with`](super::TransportConfig::from_file_with).
+//!
+//! ```toml
+//! type = "http_sse"
+//! base_url = "https://${MCP_HOST}/mcp"
+//!
+//! [auth]
+//! type = "bearer"
+//! token = "secret://file/token.txt"
+//!
+//! [headers]
+//! X-Team = "${TEAM:-platform}"
+//! ```
+//!
+//! Errors name the offending field and reference, never the secret itself.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::{ConfigError, McpResult};
+
+/// Prefix marking a value as a reference into a secret store
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Looks up secrets referenced as `secret://<provider>/<key>`
+pub trait SecretResolver {
+    /// Return the secret stored under `key` by `provider`
+    fn resolve(&self, provider: &str, key: &str) -> Result<String, String>;
+}
+
+/// Resolves `env` and `file` secret references
+#[derive(Debug, Clone, Default)]
+pub struct DefaultSecretResolver {
+    base_dir: Option<PathBuf>,
+}
+
+impl DefaultSecretResolver {
+    /// Resolve relative `secret://file/` paths against `dir`
+    pub fn relative_to(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: Some(dir.into()),
+        }
+    }
+}
+
+impl SecretResolver for DefaultSecretResolver {
+    fn resolve(&self, provider: &str, key: &str) -> Result<String, String> {
+        match provider {
+            "env" => {
+                std::env::var(key).map_err(|_| format!("environment variable {key} is not set"))
+            }
+            "file" => {
+                let path = Path::new(key);
+                let path = match &self.base_dir {
+                    Some(dir) if path.is_relative() => dir.join(path),
+                    _ => path.to_path_buf(),
+                };
+                let secret = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+                Ok(secret.trim_end_matches(['\r', '\n']).to_string())
+            }
+            other => Err(format!("unknown secret provider '{other}'")),
+        }
+    }
+}
+
+/// Expand every string in `value` in place; `path` names the root in errors
+pub fn expand_value(value: &mut Value, resolver: &dyn SecretResolver, path: &str) -> McpResult<()> {
+    match value {
+        Value::String(s) => *s = expand_str(s, resolver, path)?,
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                expand_value(item, resolver, &format!("{path}[{i}]"))?;
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                expand_value(field, resolver, &path)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Interpolate the environment into `input`, then resolve it if it is a secret reference
+pub fn expand_str(
+    input: &str,
+    resolver: &dyn SecretResolver,
+    parameter: &str,
+) -> McpResult<String> {
+    let expanded = interpolate_env(input, parameter)?;
+    let Some(reference) = expanded.strip_prefix(SECRET_SCHEME) else {
+        return Ok(expanded);
+    };
+
+    let invalid = |reason: String| ConfigError::InvalidValue {
+        parameter: parameter.to_string(),
+        value: input.to_string(),
+        reason,
+    };
+    let (provider, key) = reference
+        .split_once('/')
+        .filter(|(provider, key)| !provider.is_empty() && !key.is_empty())
+        .ok_or_else(|| invalid("expected secret://<provider>/<key>".to_string()))?;
+    Ok(resolver.resolve(provider, key).map_err(invalid)?)
+}
+
+fn interpolate_env(input: &str, parameter: &str) -> McpResult<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        if let Some(after) = tail.strip_prefix("$${") {
+            output.push_str("${");
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("${") {
+            let end = after.find('}').ok_or_else(|| ConfigError::InvalidValue {
+                parameter: parameter.to_string(),
+                value: input.to_string(),
+                reason: "unterminated ${".to_string(),
+            })?;
+            let (name, fallback) = match after[..end].split_once(":-") {
+                Some((name, fallback)) => (name, Some(fallback)),
+                None => (&after[..end], None),
+            };
+            match (std::env::var(name), fallback) {
+                (Ok(value), _) => output.push_str(&value),
+                (Err(_), Some(fallback)) => output.push_str(fallback),
+                (Err(_), None) => {
+                    return Err(ConfigError::InvalidValue {
+                        parameter: parameter.to_string(),
+                        value: input.to_string(),
+                        reason: format!("environment variable {name} is not set"),
+                    }
+                    .into())
+                }
+            }
+            rest = &after[end + 1..];
+        } else {
+            output.push('$');
+            rest = &tail[1..];
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_env_interpolation_with_fallback_and_escape() {
+        std::env::set_var("MCP_SECRETS_TEST_HOST", "example.com");
+        let resolver = DefaultSecretResolver::default();
+
+        let expanded = expand_str(
+            "https://${MCP_SECRETS_TEST_HOST}/${MCP_SECRETS_TEST_UNSET:-mcp}?q=$${x}&cost=$5",
+            &resolver,
+            "base_url",
+        )
+        .unwrap();
+        assert_eq!(expanded, "https://example.com/mcp?q=${x}&cost=$5");
+
+        let error = expand_str("${MCP_SECRETS_TEST_UNSET}", &resolver, "token").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("MCP_SECRETS_TEST_UNSET is not set"));
+    }
+
+    #[test]
+    fn test_secret_references_resolve_without_leaking() {
+        let dir = std::env::temp_dir().join(format!("mcp-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("token.txt"), "s3cr3t\n").unwrap();
+        std::env::set_var("MCP_SECRETS_TEST_KEY", "from-env");
+
+        let mut config = json!({
+            "auth": { "type": "bearer", "token": "secret://file/token.txt" },
+            "headers": { "X-Key": "secret://env/MCP_SECRETS_TEST_KEY" },
+        });
+        let resolver = DefaultSecretResolver::relative_to(&dir);
+        expand_value(&mut config, &resolver, "").unwrap();
+        assert_eq!(config["auth"]["token"], "s3cr3t");
+        assert_eq!(config["headers"]["X-Key"], "from-env");
+
+        let error = expand_str("secret://vault/db", &resolver, "auth.token").unwrap_err();
+        assert!(error.to_string().contains("auth.token"));
+        assert!(error.to_string().contains("unknown secret provider"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -5,20 +5,28 @@
 //! local development, testing, and integrating with language-specific
 //! MCP server implementations.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::timeout;
 
-use super::{Transport, TransportConfig, TransportInfo};
+use super::container::{container_exit_error, ProcessRuntime};
+use super::framing::{FrameParser, ProtocolDiagnostic};
+use super::limits::{self, MessageLimits};
+use super::sandbox::sandbox_exit_error;
+use super::{StdioConfig, Transport, TransportConfig, TransportInfo};
+use crate::correlator::RequestCorrelator;
 use crate::error::{McpResult, TransportError};
 use crate::messages::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
 
+/// Number of stderr lines kept for error reports.
+const STDERR_TAIL_LINES: usize = 5;
+
 /// Stdio transport for local process MCP communication.
 ///
 /// This transport implementation provides:
@@ -28,15 +36,17 @@ use crate::messages::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonR
 /// - Environment variable and working directory configuration
 /// - Request/response correlation and timeout handling
 /// - Automatic cleanup of child processes
+/// - Optional launch inside a Docker or Podman container
 pub struct StdioTransport {
     config: TransportConfig,
-    info: TransportInfo,
-    child_process: Option<Child>,
-    message_sender: Option<mpsc::UnboundedSender<JsonRpcMessage>>,
-    message_receiver: Option<mpsc::UnboundedReceiver<JsonRpcMessage>>,
-    outbound_sender: Option<mpsc::UnboundedSender<JsonRpcMessage>>,
-    outbound_receiver: Option<mpsc::UnboundedReceiver<JsonRpcMessage>>,
-    pending_requests: Arc<Mutex<HashMap<String, tokio::sync::oneshot::Sender<JsonRpcResponse>>>>,
+    info: Mutex<TransportInfo>,
+    child_process: Mutex<Option<Child>>,
+    message_receiver: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<JsonRpcMessage>>>,
+    outbound_sender: Mutex<Option<mpsc::UnboundedSender<JsonRpcMessage>>>,
+    correlator: RequestCorrelator,
+    stderr_tail: Arc<Mutex<VecDeque<String>>>,
+    frames: FrameParser,
+    limits: MessageLimits,
 }
 
 impl StdioTransport {
@@ -51,31 +61,98 @@ impl StdioTransport {
     /// A new transport instance ready for connection.
     pub fn new(config: TransportConfig) -> Self {
         let info = TransportInfo::new("stdio");
+        let frames = FrameParser::new("stdio", config.parse_mode());
+        let limits = config.limits().cloned().unwrap_or_default();
 
         Self {
             config,
-            info,
-            child_process: None,
-            message_sender: None,
-            message_receiver: None,
-            outbound_sender: None,
-            outbound_receiver: None,
-            pending_requests: Arc::new(Mutex::new(HashMap::new())),
+            info: Mutex::new(info),
+            child_process: Mutex::new(None),
+            message_receiver: tokio::sync::Mutex::new(None),
+            outbound_sender: Mutex::new(None),
+            correlator: RequestCorrelator::new("stdio"),
+            stderr_tail: Arc::new(Mutex::new(VecDeque::new())),
+            frames,
+            limits,
         }
     }
 
+    fn info(&self) -> MutexGuard<'_, TransportInfo> {
+        self.info.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn child_process(&self) -> MutexGuard<'_, Option<Child>> {
+        self.child_process.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn outbound_sender(&self) -> Option<mpsc::UnboundedSender<JsonRpcMessage>> {
+        self.outbound_sender
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Whether the child process exists and has not exited.
+    fn child_running(&self) -> bool {
+        self.child_process()
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(None)))
+    }
+
+    fn runtime(&self) -> ProcessRuntime {
+        match &self.config {
+            TransportConfig::Stdio(config) => config.runtime,
+            _ => ProcessRuntime::Native,
+        }
+    }
+
+    /// Replace a process failure with a container lifecycle error when the
+    /// container runtime exited with one of its reserved exit codes, or with
+    /// a sandbox error when the sandbox tool could not set up.
+    fn lifecycle_error(&self, error: TransportError) -> TransportError {
+        let runtime = self.runtime();
+        if let TransportConfig::Stdio(StdioConfig {
+            sandbox: Some(ref sandbox),
+            ..
+        }) = self.config
+        {
+            let tail: Vec<String> = self
+                .stderr_tail
+                .lock()
+                .map(|tail| tail.iter().cloned().collect())
+                .unwrap_or_default();
+            return sandbox_exit_error(sandbox, &tail).unwrap_or(error);
+        }
+        if !runtime.is_container() {
+            return error;
+        }
+
+        let exit_code = self
+            .child_process()
+            .as_mut()
+            .and_then(|child| child.try_wait().ok().flatten())
+            .and_then(|status| status.code());
+        let tail: Vec<String> = self
+            .stderr_tail
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default();
+
+        container_exit_error(runtime, exit_code, &tail).unwrap_or(error)
+    }
+
     /// Spawn the child process and set up communication channels.
-    async fn spawn_process(&mut self) -> McpResult<()> {
+    async fn spawn_process(&self) -> McpResult<()> {
         if let TransportConfig::Stdio(stdio_config) = &self.config {
-            tracing::debug!(
-                "Spawning process: {} {:?}",
-                stdio_config.command,
-                stdio_config.args
-            );
+            // Fail early with actionable advice instead of an opaque spawn error
+            super::prerequisites::ensure(stdio_config).await?;
 
-            let mut command = Command::new(&stdio_config.command);
+            let (program, args) = stdio_config.launch_command()?;
+            tracing::debug!("Spawning process: {} {:?}", program, args);
+
+            let mut command = Command::new(&program);
             command
-                .args(&stdio_config.args)
+                .args(&args)
                 .stdin(std::process::Stdio::piped())
                 .stdout(std::process::Stdio::piped())
                 .stderr(std::process::Stdio::piped());
@@ -91,12 +168,23 @@ impl StdioTransport {
             }
 
             // Spawn the child process
-            let mut child = command
-                .spawn()
-                .map_err(|e| TransportError::ConnectionError {
-                    transport_type: "stdio".to_string(),
-                    reason: format!("Failed to spawn process: {}", e),
-                })?;
+            let runtime = stdio_config.runtime;
+            let mut child = command.spawn().map_err(|e| {
+                if runtime.is_container() {
+                    TransportError::ContainerError {
+                        runtime: program.clone(),
+                        reason: format!(
+                            "Failed to run {} (is it installed and on PATH?): {}",
+                            program, e
+                        ),
+                    }
+                } else {
+                    TransportError::ConnectionError {
+                        transport_type: "stdio".to_string(),
+                        reason: format!("Failed to spawn process: {}", e),
+                    }
+                }
+            })?;
 
             // Extract streams from child process
             let stdin = child
@@ -128,24 +216,26 @@ impl StdioTransport {
             let (outbound_sender, outbound_receiver) = mpsc::unbounded_channel();
 
             // Store channels
-            self.message_sender = Some(inbound_sender.clone());
-            self.message_receiver = Some(inbound_receiver);
-            self.outbound_sender = Some(outbound_sender);
+            *self.message_receiver.lock().await = Some(inbound_receiver);
+            *self
+                .outbound_sender
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(outbound_sender);
 
             // Start I/O processing tasks
-            let pending_requests = self.pending_requests.clone();
+            let correlator = self.correlator.clone();
             self.start_io_tasks(
                 stdin,
                 stdout,
                 stderr,
                 inbound_sender,
                 outbound_receiver,
-                pending_requests,
+                correlator,
             )
             .await;
 
             // Store the child process
-            self.child_process = Some(child);
+            *self.child_process() = Some(child);
 
             Ok(())
         } else {
@@ -159,63 +249,50 @@ impl StdioTransport {
 
     /// Start the I/O processing tasks for reading from and writing to the child process.
     async fn start_io_tasks(
-        &mut self,
+        &self,
         mut stdin: tokio::process::ChildStdin,
         stdout: tokio::process::ChildStdout,
         stderr: tokio::process::ChildStderr,
         inbound_sender: mpsc::UnboundedSender<JsonRpcMessage>,
         mut outbound_receiver: mpsc::UnboundedReceiver<JsonRpcMessage>,
-        pending_requests: Arc<
-            Mutex<HashMap<String, tokio::sync::oneshot::Sender<JsonRpcResponse>>>,
-        >,
+        correlator: RequestCorrelator,
     ) {
         // Start stdout reader task
         let stdout_sender = inbound_sender.clone();
-        let pending_requests_clone = pending_requests.clone();
+        let frames = self.frames.clone();
+        let mut limiter = self.limits.inbound();
+        let max_inbound = self.limits.max_inbound;
         tokio::spawn(async move {
             let mut stdout_reader = BufReader::new(stdout);
-            let mut line = String::new();
 
             loop {
-                line.clear();
-                match stdout_reader.read_line(&mut line).await {
-                    Ok(0) => {
+                match limits::read_frame(&mut stdout_reader, &mut limiter).await {
+                    Ok(None) => {
                         tracing::debug!("Child process stdout closed (EOF)");
                         break;
                     }
-                    Ok(_) => {
-                        let trimmed = line.trim();
-                        if !trimmed.is_empty() {
-                            tracing::debug!("Received from stdout: {}", trimmed);
-                            match serde_json::from_str::<JsonRpcMessage>(trimmed) {
-                                Ok(message) => {
-                                    // Handle response correlation for request/response messages
-                                    if let JsonRpcMessage::Response(ref response) = message {
-                                        let maybe_response_sender = pending_requests_clone
-                                            .lock()
-                                            .await
-                                            .remove(&response.id.to_string());
-
-                                        if let Some(response_sender) = maybe_response_sender {
-                                            // Send response directly to the waiting request
-                                            let _ = response_sender.send(response.clone());
-                                            continue; // Don't send to inbound_sender for responses
-                                        }
-                                    }
-
-                                    // Send other messages (notifications, server requests) to inbound_sender
-                                    if stdout_sender.send(message).is_err() {
-                                        tracing::warn!("Failed to send stdout message to handler");
-                                        break;
+                    Ok(Some(frame)) => {
+                        if let limits::LimitedFrame::Complete(ref line) = frame {
+                            tracing::debug!("Received from stdout: {}", line.trim());
+                        }
+                        if let Some(message) =
+                            limits::decode_frame(&frames, &correlator, frame, max_inbound)
+                        {
+                            // Send responses directly to the waiting request
+                            let message = match message {
+                                JsonRpcMessage::Response(response) => {
+                                    match correlator.complete(response) {
+                                        None => continue,
+                                        Some(response) => JsonRpcMessage::Response(response),
                                     }
                                 }
-                                Err(e) => {
-                                    tracing::warn!(
-                                        "Failed to parse JSON message from stdout: {} ({})",
-                                        e,
-                                        trimmed
-                                    );
-                                }
+                                other => other,
+                            };
+
+                            // Send other messages (notifications, server requests) to inbound_sender
+                            if stdout_sender.send(message).is_err() {
+                                tracing::warn!("Failed to send stdout message to handler");
+                                break;
                             }
                         }
                     }
@@ -229,6 +306,7 @@ impl StdioTransport {
         });
 
         // Start stderr reader task
+        let stderr_tail = self.stderr_tail.clone();
         tokio::spawn(async move {
             let mut stderr_reader = BufReader::new(stderr);
             let mut line = String::new();
@@ -244,6 +322,12 @@ impl StdioTransport {
                         let trimmed = line.trim();
                         if !trimmed.is_empty() {
                             tracing::warn!("MCP process stderr: {}", trimmed);
+                            if let Ok(mut tail) = stderr_tail.lock() {
+                                if tail.len() == STDERR_TAIL_LINES {
+                                    tail.pop_front();
+                                }
+                                tail.push_back(trimmed.to_string());
+                            }
                         }
                     }
                     Err(e) => {
@@ -284,8 +368,9 @@ impl StdioTransport {
     }
 
     /// Kill the child process if it exists.
-    async fn kill_process(&mut self) -> McpResult<()> {
-        if let Some(mut child) = self.child_process.take() {
+    async fn kill_process(&self) -> McpResult<()> {
+        let child = self.child_process().take();
+        if let Some(mut child) = child {
             tracing::debug!("Terminating child process (PID: {:?})", child.id());
 
             // Try graceful shutdown first
@@ -316,101 +401,90 @@ impl StdioTransport {
 
 #[async_trait]
 impl Transport for StdioTransport {
-    async fn connect(&mut self) -> McpResult<()> {
+    async fn connect(&self) -> McpResult<()> {
         tracing::info!("Connecting stdio transport");
 
         // Spawn the child process and set up communication
         self.spawn_process().await?;
 
         // Update transport info
-        self.info.mark_connected();
+        self.info().mark_connected();
 
         tracing::info!("Stdio transport connected successfully");
         Ok(())
     }
 
-    async fn disconnect(&mut self) -> McpResult<()> {
+    async fn disconnect(&self) -> McpResult<()> {
         tracing::info!("Disconnecting stdio transport");
 
-        // Close message channels
-        self.message_sender = None;
-        self.message_receiver = None;
-        self.outbound_sender = None;
-        self.outbound_receiver = None;
+        // Close the outbound channel, which stops the stdin writer
+        self.outbound_sender
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
 
-        // Kill the child process
+        // Kill the child process; its stdout closing ends any pending receive
         self.kill_process().await?;
+        self.message_receiver.lock().await.take();
 
-        // Clear pending requests
-        self.pending_requests.lock().await.clear();
+        // Fail pending requests
+        self.correlator.cancel_all();
 
         // Update transport info
-        self.info.mark_disconnected();
+        self.info().mark_disconnected();
 
         tracing::info!("Stdio transport disconnected");
         Ok(())
     }
 
     fn is_connected(&self) -> bool {
-        self.info.connected
-            && self.child_process.is_some()
-            && self.message_sender.is_some()
-            && self.outbound_sender.is_some()
+        self.info().connected && self.outbound_sender().is_some() && self.child_running()
     }
 
     async fn send_request(
-        &mut self,
+        &self,
         request: JsonRpcRequest,
         timeout_duration: Option<Duration>,
     ) -> McpResult<JsonRpcResponse> {
         if !self.is_connected() {
-            return Err(TransportError::NotConnected {
-                transport_type: "stdio".to_string(),
-                reason: "Transport not connected".to_string(),
-            }
-            .into());
+            return Err(self
+                .lifecycle_error(TransportError::NotConnected {
+                    transport_type: "stdio".to_string(),
+                    reason: "Transport not connected".to_string(),
+                })
+                .into());
         }
 
-        let request_id = request.id.clone();
-        let (response_sender, response_receiver) = tokio::sync::oneshot::channel();
+        let id = request.id.clone();
+        let message = JsonRpcMessage::Request(request);
+        self.limits.check_outbound("stdio", &message)?;
 
-        // Store the response sender for correlation
-        self.pending_requests
-            .lock()
-            .await
-            .insert(request_id.to_string(), response_sender);
+        // Register before sending so a fast response cannot be missed
+        let pending = self.correlator.register(&id)?;
 
         // Send the request
-        if let Some(sender) = &self.outbound_sender {
-            sender.send(JsonRpcMessage::Request(request)).map_err(|_| {
-                TransportError::ProcessError {
+        if let Some(sender) = self.outbound_sender() {
+            sender
+                .send(message)
+                .map_err(|_| TransportError::ProcessError {
                     reason: "Failed to send request to child process".to_string(),
-                }
-            })?;
+                })?;
         }
 
-        self.info.increment_requests_sent();
+        self.info().increment_requests_sent();
 
         // Wait for response with timeout
         let timeout_duration = timeout_duration.unwrap_or(Duration::from_secs(30));
-        let response = timeout(timeout_duration, response_receiver)
-            .await
-            .map_err(|_| TransportError::TimeoutError {
-                transport_type: "stdio".to_string(),
-                reason: format!(
-                    "Request {} timed out after {:?}",
-                    request_id, timeout_duration
-                ),
-            })?
-            .map_err(|_| TransportError::ProcessError {
-                reason: "Response channel closed unexpectedly".to_string(),
-            })?;
+        let response = match pending.wait(timeout_duration).await {
+            Ok(response) => response,
+            Err(e) => return Err(self.lifecycle_error(e).into()),
+        };
 
-        self.info.increment_responses_received();
+        self.info().increment_responses_received();
         Ok(response)
     }
 
-    async fn send_notification(&mut self, notification: JsonRpcNotification) -> McpResult<()> {
+    async fn send_notification(&self, notification: JsonRpcNotification) -> McpResult<()> {
         if !self.is_connected() {
             return Err(TransportError::NotConnected {
                 transport_type: "stdio".to_string(),
@@ -419,20 +493,45 @@ impl Transport for StdioTransport {
             .into());
         }
 
-        if let Some(sender) = &self.outbound_sender {
+        let message = JsonRpcMessage::Notification(notification);
+        self.limits.check_outbound("stdio", &message)?;
+
+        if let Some(sender) = self.outbound_sender() {
             sender
-                .send(JsonRpcMessage::Notification(notification))
+                .send(message)
                 .map_err(|_| TransportError::ProcessError {
                     reason: "Failed to send notification to child process".to_string(),
                 })?;
         }
 
-        self.info.increment_notifications_sent();
+        self.info().increment_notifications_sent();
+        Ok(())
+    }
+
+    async fn send_response(&self, response: JsonRpcResponse) -> McpResult<()> {
+        if !self.is_connected() {
+            return Err(TransportError::NotConnected {
+                transport_type: "stdio".to_string(),
+                reason: "Transport not connected".to_string(),
+            }
+            .into());
+        }
+
+        let message = JsonRpcMessage::Response(response);
+        self.limits.check_outbound("stdio", &message)?;
+
+        if let Some(sender) = self.outbound_sender() {
+            sender
+                .send(message)
+                .map_err(|_| TransportError::ProcessError {
+                    reason: "Failed to send response to child process".to_string(),
+                })?;
+        }
         Ok(())
     }
 
     async fn receive_message(
-        &mut self,
+        &self,
         timeout_duration: Option<Duration>,
     ) -> McpResult<JsonRpcMessage> {
         if !self.is_connected() {
@@ -443,31 +542,37 @@ impl Transport for StdioTransport {
             .into());
         }
 
-        let receiver =
-            self.message_receiver
-                .as_mut()
-                .ok_or_else(|| TransportError::NotConnected {
-                    transport_type: "stdio".to_string(),
-                    reason: "Message receiver not available".to_string(),
-                })?;
+        // Concurrent receivers take turns; each message goes to exactly one
+        let mut receiver_guard = self.message_receiver.lock().await;
+        let receiver = receiver_guard
+            .as_mut()
+            .ok_or_else(|| TransportError::NotConnected {
+                transport_type: "stdio".to_string(),
+                reason: "Message receiver not available".to_string(),
+            })?;
 
-        let message = if let Some(timeout_duration) = timeout_duration {
+        let received = if let Some(timeout_duration) = timeout_duration {
             timeout(timeout_duration, receiver.recv())
                 .await
                 .map_err(|_| TransportError::TimeoutError {
                     transport_type: "stdio".to_string(),
                     reason: format!("Message receive timed out after {:?}", timeout_duration),
                 })?
-                .ok_or_else(|| TransportError::ProcessError {
-                    reason: "Child process stdout closed".to_string(),
-                })?
         } else {
-            receiver
-                .recv()
-                .await
-                .ok_or_else(|| TransportError::ProcessError {
-                    reason: "Child process stdout closed".to_string(),
-                })?
+            receiver.recv().await
+        };
+
+        drop(receiver_guard);
+
+        let message = match received {
+            Some(message) => message,
+            None => {
+                return Err(self
+                    .lifecycle_error(TransportError::ProcessError {
+                        reason: "Child process stdout closed".to_string(),
+                    })
+                    .into())
+            }
         };
 
         // Response correlation is now handled in the stdout reader task
@@ -482,7 +587,7 @@ impl Transport for StdioTransport {
                 // Already handled above
             }
             JsonRpcMessage::Notification(_) => {
-                self.info.increment_notifications_received();
+                self.info().increment_notifications_received();
             }
         }
 
@@ -490,12 +595,16 @@ impl Transport for StdioTransport {
     }
 
     fn get_info(&self) -> TransportInfo {
-        let mut info = self.info.clone();
+        let mut info = self.info().clone();
 
         // Add stdio-specific metadata
         if let TransportConfig::Stdio(config) = &self.config {
             info.add_metadata("command", serde_json::json!(config.command));
             info.add_metadata("args", serde_json::json!(config.args));
+            info.add_metadata("runtime", serde_json::json!(config.runtime));
+            if let Some(ref container) = config.container {
+                info.add_metadata("image", serde_json::json!(container.image));
+            }
             info.add_metadata("working_dir", serde_json::json!(config.working_dir));
             info.add_metadata("timeout", serde_json::json!(config.timeout.as_secs()));
             info.add_metadata(
@@ -504,19 +613,18 @@ impl Transport for StdioTransport {
             );
         }
 
-        // TODO: Figure out how to handle async here
-        // info.add_metadata(
-        //     "pending_requests",
-        //     serde_json::json!(self.pending_requests.lock().await.len()),
-        // );
         info.add_metadata(
-            "has_process",
-            serde_json::json!(self.child_process.is_some()),
+            "pending_requests",
+            serde_json::json!(self.correlator.pending_count()),
         );
+        info.add_metadata("correlation", serde_json::json!(self.correlator.stats()));
+        let child_process = self.child_process();
+        info.add_metadata("has_process", serde_json::json!(child_process.is_some()));
 
-        if let Some(ref child) = self.child_process {
+        if let Some(ref child) = *child_process {
             info.add_metadata("process_id", serde_json::json!(child.id()));
         }
+        drop(child_process);
 
         info
     }
@@ -524,12 +632,16 @@ impl Transport for StdioTransport {
     fn get_config(&self) -> &TransportConfig {
         &self.config
     }
+
+    fn protocol_diagnostics(&self) -> Option<broadcast::Receiver<ProtocolDiagnostic>> {
+        Some(self.frames.subscribe())
+    }
 }
 
 impl Drop for StdioTransport {
     fn drop(&mut self) {
         // Ensure child process is cleaned up when transport is dropped
-        if let Some(mut child) = self.child_process.take() {
+        if let Some(mut child) = self.child_process().take() {
             let _ = child.start_kill();
         }
     }
@@ -563,7 +675,7 @@ mod tests {
     #[tokio::test]
     async fn test_process_spawn_failure() {
         let config = TransportConfig::stdio("nonexistent_command_12345", &[] as &[String]);
-        let mut transport = StdioTransport::new(config);
+        let transport = StdioTransport::new(config);
 
         let result = transport.connect().await;
         assert!(result.is_err());
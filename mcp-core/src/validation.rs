@@ -4,6 +4,7 @@
 //! interactive TUI mode, non-interactive CLI mode, and validation engines.
 
 use anyhow::Result;
+use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
 use thiserror::Error;
@@ -429,6 +430,22 @@ pub fn validate_parameters_strict(schema: &Value, params: &Value) -> ValidationR
     ParameterValidator::strict().validate(schema, params)
 }
 
+/// Fixes tool arguments that automatic transformations could not.
+///
+/// Implemented by model-backed repairers, such as the argument synthesizer in
+/// `mcp-llm`, and used by the proxy's argument repair interceptor.
+#[async_trait]
+pub trait ArgumentRepairer: Send + Sync {
+    /// Arguments for `tool` that fit `schema`, given ones that did not and why
+    async fn repair(
+        &self,
+        tool: &str,
+        schema: &Value,
+        arguments: &Value,
+        errors: &[String],
+    ) -> crate::McpResult<Value>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
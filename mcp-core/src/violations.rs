@@ -0,0 +1,215 @@
+//! Protocol violations observed while talking to a server.
+//!
+//! A violation does not fail the session: the offending message is still
+//! accepted, but the deviation is published on a broadcast stream so tools can
+//! surface servers that stray from, or extend, the specification.
+//!
+//! Typed messages that are commonly extended by servers (tools, resources,
+//! prompts and the initialize result) keep fields they do not model in an
+//! `extra` map instead of discarding them. Those fields are reported once per
+//! server, message type and field name.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Number of violations buffered for slow subscribers before they lag
+const VIOLATION_CHANNEL_CAPACITY: usize = 256;
+
+/// A deviation from the MCP specification
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProtocolViolation {
+    /// A message carried a field the typed message does not define
+    UnknownField {
+        /// Name the server reported in its `serverInfo`
+        server: String,
+        /// Typed message the field appeared on, e.g. `Tool`
+        message_type: String,
+        /// Name of the field as sent by the server
+        field: String,
+    },
+}
+
+impl fmt::Display for ProtocolViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolViolation::UnknownField {
+                server,
+                message_type,
+                field,
+            } => write!(
+                f,
+                "Server '{}' sent unknown field '{}' on {}",
+                server, field, message_type
+            ),
+        }
+    }
+}
+
+/// Typed messages that preserve fields they do not model
+pub trait UnknownFields {
+    /// Every preserved field as `(message type, field name)`, including those
+    /// of nested messages
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)>;
+}
+
+fn own_fields<'a>(
+    message_type: &'static str,
+    extra: &'a HashMap<String, Value>,
+) -> Vec<(&'static str, &'a str)> {
+    extra
+        .keys()
+        .map(|field| (message_type, field.as_str()))
+        .collect()
+}
+
+impl UnknownFields for crate::messages::Tool {
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)> {
+        own_fields("Tool", &self.extra)
+    }
+}
+
+impl UnknownFields for crate::messages::Resource {
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)> {
+        own_fields("Resource", &self.extra)
+    }
+}
+
+impl UnknownFields for crate::messages::Prompt {
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)> {
+        own_fields("Prompt", &self.extra)
+    }
+}
+
+impl UnknownFields for crate::messages::InitializeResponse {
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)> {
+        own_fields("InitializeResponse", &self.extra)
+    }
+}
+
+impl UnknownFields for crate::messages::ListToolsResponse {
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)> {
+        self.tools
+            .iter()
+            .flat_map(UnknownFields::unknown_fields)
+            .collect()
+    }
+}
+
+impl UnknownFields for crate::messages::ListResourcesResponse {
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)> {
+        self.resources
+            .iter()
+            .flat_map(UnknownFields::unknown_fields)
+            .collect()
+    }
+}
+
+impl UnknownFields for crate::messages::ListPromptsResponse {
+    fn unknown_fields(&self) -> Vec<(&'static str, &str)> {
+        self.prompts
+            .iter()
+            .flat_map(UnknownFields::unknown_fields)
+            .collect()
+    }
+}
+
+/// Publishes protocol violations, suppressing repeats
+#[derive(Debug)]
+pub struct ViolationReporter {
+    sender: broadcast::Sender<ProtocolViolation>,
+    seen: Mutex<HashSet<ProtocolViolation>>,
+}
+
+impl ViolationReporter {
+    /// Create a reporter with no subscribers
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(VIOLATION_CHANNEL_CAPACITY);
+        Self {
+            sender,
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Receive violations reported from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ProtocolViolation> {
+        self.sender.subscribe()
+    }
+
+    /// Publish a violation unless the same one was already reported.
+    ///
+    /// Returns whether the violation was new.
+    pub fn report(&self, violation: ProtocolViolation) -> bool {
+        let is_new = self
+            .seen
+            .lock()
+            .map(|mut seen| seen.insert(violation.clone()))
+            .unwrap_or(true);
+        if is_new {
+            tracing::warn!("Protocol violation: {}", violation);
+            // Nobody listening is fine; the warning above is still logged
+            let _ = self.sender.send(violation);
+        }
+        is_new
+    }
+
+    /// Report every unknown field preserved on a message received from `server`
+    pub fn report_unknown_fields<T: UnknownFields + ?Sized>(&self, server: &str, message: &T) {
+        for (message_type, field) in message.unknown_fields() {
+            self.report(ProtocolViolation::UnknownField {
+                server: server.to_string(),
+                message_type: message_type.to_string(),
+                field: field.to_string(),
+            });
+        }
+    }
+}
+
+impl Default for ViolationReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::ListToolsResponse;
+    use serde_json::json;
+
+    #[test]
+    fn test_unknown_fields_reported_once() {
+        let response: ListToolsResponse = serde_json::from_value(json!({
+            "tools": [
+                { "name": "a", "description": "", "annotations": { "readOnlyHint": true } },
+                { "name": "b", "description": "", "annotations": {}, "x-cost": 3 }
+            ]
+        }))
+        .unwrap();
+
+        let reporter = ViolationReporter::new();
+        let mut violations = reporter.subscribe();
+        reporter.report_unknown_fields("srv", &response);
+        reporter.report_unknown_fields("srv", &response);
+        reporter.report_unknown_fields("other", &response.tools[0]);
+
+        let mut received = Vec::new();
+        while let Ok(violation) = violations.try_recv() {
+            received.push(violation.to_string());
+        }
+        received.sort();
+        assert_eq!(
+            received,
+            vec![
+                "Server 'other' sent unknown field 'annotations' on Tool",
+                "Server 'srv' sent unknown field 'annotations' on Tool",
+                "Server 'srv' sent unknown field 'x-cost' on Tool",
+            ]
+        );
+    }
+}
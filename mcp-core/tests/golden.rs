@@ -0,0 +1,346 @@
+//! Golden snapshots of the wire format of every message type.
+//!
+//! Each test serializes fixture messages and compares them with the files in
+//! `golden/`. After an intentional change to the serde model, rewrite the
+//! files with `GENMCP_UPDATE_GOLDEN=1 cargo test -p mcp-core --test golden`
+//! and review the diff.
+
+use std::collections::HashMap;
+
+use mcp_core::golden::Golden;
+use mcp_core::messages::*;
+use serde_json::json;
+
+fn golden() -> Golden {
+    Golden::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"))
+}
+
+fn versions() -> Vec<ProtocolVersion> {
+    ProtocolVersion::supported_versions()
+}
+
+fn capabilities() -> Capabilities {
+    Capabilities {
+        standard: StandardCapabilities {
+            tools: Some(ToolCapabilities {
+                list_changed: Some(true),
+            }),
+            resources: Some(ResourceCapabilities {
+                subscribe: Some(true),
+                list_changed: Some(false),
+            }),
+            prompts: Some(PromptCapabilities::default()),
+            logging: Some(LoggingCapabilities::default()),
+            ..Default::default()
+        },
+        custom: HashMap::from([("experimental".to_string(), json!({ "traces": {} }))]),
+    }
+}
+
+fn search_tool() -> Tool {
+    Tool::new("search", "Search the index")
+        .with_input_schema(json!({
+            "type": "object",
+            "properties": { "query": { "type": "string" } },
+            "required": ["query"]
+        }))
+        .with_read_only(true)
+}
+
+#[test]
+fn test_jsonrpc_envelopes() {
+    let golden = golden();
+    golden.assert(
+        "jsonrpc_request",
+        &JsonRpcRequest::new("req-1", "tools/list", json!({ "cursor": "2" })),
+    );
+    golden.assert(
+        "jsonrpc_request_numeric_id",
+        &JsonRpcRequest::without_params(7, "ping"),
+    );
+    golden.assert(
+        "jsonrpc_response",
+        &JsonRpcResponse::success("req-1", json!({ "tools": [] })),
+    );
+    golden.assert(
+        "jsonrpc_error_response",
+        &JsonRpcResponse::error(7, JsonRpcError::method_not_found("tools/unknown")),
+    );
+    golden.assert(
+        "jsonrpc_notification",
+        &JsonRpcNotification::new(
+            "notifications/cancelled",
+            json!({ "requestId": "req-1", "reason": "user aborted" }),
+        ),
+    );
+}
+
+#[test]
+fn test_initialization_across_versions() {
+    let golden = golden();
+    golden.assert_versions("initialize_request", &versions(), |version| {
+        InitializeRequest::new(
+            version.clone(),
+            capabilities(),
+            Implementation::new("golden-client", "1.0.0"),
+        )
+    });
+    golden.assert_versions("initialize_response", &versions(), |version| {
+        InitializeResponse::new(
+            version.clone(),
+            capabilities(),
+            Implementation::new("golden-server", "2.1.0"),
+            Some("Call search before fetch".to_string()),
+        )
+    });
+    golden.assert("initialized_notification", &InitializedNotification::new());
+    golden.assert("ping_request", &PingRequest::new());
+    golden.assert("pong_response", &PongResponse::new());
+}
+
+#[test]
+fn test_tool_messages() {
+    let golden = golden();
+    golden.assert(
+        "list_tools_request",
+        &ListToolsRequest {
+            cursor: Some("page-2".to_string()),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "list_tools_response",
+        &ListToolsResponse {
+            tools: vec![search_tool(), Tool::new("status", "Report status")],
+            next_cursor: Some("page-3".to_string()),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "call_tool_request",
+        &CallToolRequest {
+            name: "search".to_string(),
+            arguments: Some(json!({ "query": "rust" })),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "call_tool_response",
+        &CallToolResponse {
+            content: vec![
+                ToolResult::text("3 results"),
+                ToolResult::image("iVBORw0KGgo=", "image/png"),
+                ToolResult::resource("file:///srv/results.json"),
+            ],
+            is_error: Some(false),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "tool_list_changed_notification",
+        &ToolListChangedNotification::new(),
+    );
+}
+
+#[test]
+fn test_resource_messages() {
+    let golden = golden();
+    golden.assert(
+        "list_resources_request",
+        &ListResourcesRequest {
+            cursor: None,
+            meta: None,
+        },
+    );
+    golden.assert(
+        "list_resources_response",
+        &ListResourcesResponse {
+            resources: vec![Resource::new("file:///srv/readme.md", "README")
+                .with_description("Project overview")
+                .with_mime_type("text/markdown")],
+            next_cursor: None,
+            meta: None,
+        },
+    );
+    golden.assert(
+        "read_resource_request",
+        &ReadResourceRequest {
+            uri: "file:///srv/readme.md".to_string(),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "read_resource_response",
+        &ReadResourceResponse {
+            contents: vec![
+                ResourceContent::text_with_mime_type(
+                    "file:///srv/readme.md",
+                    "# Hello",
+                    "text/markdown",
+                ),
+                ResourceContent::blob("file:///srv/logo.png", "iVBORw0KGgo="),
+            ],
+            meta: None,
+        },
+    );
+    golden.assert(
+        "subscribe_request",
+        &SubscribeRequest {
+            uri: "file:///srv/readme.md".to_string(),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "unsubscribe_request",
+        &UnsubscribeRequest {
+            uri: "file:///srv/readme.md".to_string(),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "resource_updated_notification",
+        &ResourceUpdatedNotification::new("file:///srv/readme.md"),
+    );
+    golden.assert(
+        "resource_list_changed_notification",
+        &ResourceListChangedNotification::new(),
+    );
+}
+
+#[test]
+fn test_prompt_messages() {
+    let golden = golden();
+    golden.assert(
+        "list_prompts_request",
+        &ListPromptsRequest {
+            cursor: None,
+            meta: None,
+        },
+    );
+    golden.assert(
+        "list_prompts_response",
+        &ListPromptsResponse {
+            prompts: vec![
+                Prompt::new("review", "Review a change").with_arguments(json!([
+                    { "name": "diff", "required": true }
+                ])),
+            ],
+            next_cursor: None,
+            meta: None,
+        },
+    );
+    golden.assert(
+        "get_prompt_request",
+        &GetPromptRequest {
+            name: "review".to_string(),
+            arguments: Some(json!({ "diff": "+fn main() {}" })),
+            meta: None,
+        },
+    );
+    golden.assert(
+        "get_prompt_response",
+        &GetPromptResponse {
+            description: Some("Review a change".to_string()),
+            messages: vec![
+                PromptMessage::user("Review this diff"),
+                PromptMessage::new(
+                    PromptMessageRole::User,
+                    PromptContent::resource_with_text("file:///srv/change.diff", "+fn main() {}"),
+                ),
+                PromptMessage::assistant("Looks good"),
+            ],
+            meta: None,
+        },
+    );
+    golden.assert(
+        "prompt_list_changed_notification",
+        &PromptListChangedNotification::new(),
+    );
+}
+
+#[test]
+fn test_sampling_messages() {
+    let golden = golden();
+    let argument = CompletionArgument::new(vec![
+        SamplingMessage::user("What is 2+2?"),
+        SamplingMessage::new(
+            MessageRole::User,
+            SamplingContent::image("iVBORw0KGgo=", "image/png"),
+        ),
+    ])
+    .with_model_preferences(
+        ModelPreferences::new()
+            .with_models(vec!["claude".to_string()])
+            .with_cost_priority(CostPriority::Low)
+            .with_speed_priority(SpeedPriority::High)
+            .with_intelligence_priority(IntelligencePriority::Medium),
+    )
+    .with_system_prompt("Be brief")
+    .with_temperature(0.5)
+    .with_max_tokens(64)
+    .with_stop_sequences(vec!["\n\n".to_string()]);
+    golden.assert("complete_request", &CompleteRequest { argument });
+    golden.assert(
+        "complete_response",
+        &CompleteResponse {
+            completion: CompletionResult::text("4"),
+            model: Some("claude".to_string()),
+            stop_reason: Some(StopReason::EndTurn),
+        },
+    );
+}
+
+#[test]
+fn test_logging_messages() {
+    let golden = golden();
+    golden.assert(
+        "set_level_request",
+        &SetLevelRequest::new(LogLevel::Warning),
+    );
+    golden.assert(
+        "logging_notification",
+        &LoggingNotification::with_logger(LogLevel::Error, json!({ "code": 17 }), "indexer"),
+    );
+    golden.assert(
+        "progress_notification",
+        &ProgressNotification::with_total("upload-1", 0.5, 10),
+    );
+}
+
+#[test]
+fn test_recorded_session() {
+    let session = vec![
+        JsonRpcMessage::Request(JsonRpcRequest::new(
+            1,
+            "initialize",
+            json!(InitializeRequest::basic("golden-client", "1.0.0")),
+        )),
+        JsonRpcMessage::Response(JsonRpcResponse::success(
+            1,
+            json!(InitializeResponse::basic("golden-server", "2.1.0")),
+        )),
+        JsonRpcMessage::Notification(JsonRpcNotification::new(
+            "notifications/initialized",
+            json!({}),
+        )),
+        JsonRpcMessage::Request(JsonRpcRequest::new(
+            2,
+            "tools/call",
+            json!({ "name": "search", "arguments": { "query": "rust" }, "_meta": { "progressToken": "p-2" } }),
+        )),
+        JsonRpcMessage::Notification(JsonRpcNotification::new(
+            "notifications/progress",
+            json!(ProgressNotification::new("p-2", 1.0)),
+        )),
+        JsonRpcMessage::Response(JsonRpcResponse::success(
+            2,
+            json!({ "content": [{ "type": "text", "text": "3 results" }], "isError": false }),
+        )),
+        JsonRpcMessage::Request(JsonRpcRequest::without_params(3, "tools/unknown")),
+        JsonRpcMessage::Response(JsonRpcResponse::error(
+            3,
+            JsonRpcError::method_not_found("tools/unknown"),
+        )),
+    ];
+    golden().assert_session("session_tool_call", &session);
+}
@@ -0,0 +1,18 @@
+-- Routing decision audit trail
+
+-- One row per routed request; the full trace is kept as JSON for explanations
+CREATE TABLE IF NOT EXISTS decision_traces (
+    id TEXT PRIMARY KEY,
+    timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    mode TEXT NOT NULL,
+    method TEXT,
+    chosen_tool TEXT,
+    requested_tool TEXT,
+    applied BOOLEAN NOT NULL DEFAULT FALSE,
+    latency_ms REAL NOT NULL DEFAULT 0.0,
+    trace_data TEXT NOT NULL -- JSON
+);
+
+-- Create indexes for decision traces
+CREATE INDEX IF NOT EXISTS idx_decision_traces_timestamp ON decision_traces(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_decision_traces_chosen_tool ON decision_traces(chosen_tool);
@@ -0,0 +1,13 @@
+-- Session snapshots so routing context survives restarts
+
+-- Latest snapshot of each session; context is the full SessionPredictionContext
+CREATE TABLE IF NOT EXISTS session_snapshots (
+    session_id TEXT PRIMARY KEY,
+    summary TEXT NOT NULL,
+    context_data TEXT NOT NULL, -- JSON
+    last_updated DATETIME NOT NULL,
+    saved_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Create indexes for session snapshots
+CREATE INDEX IF NOT EXISTS idx_session_snapshots_last_updated ON session_snapshots(last_updated DESC);
@@ -0,0 +1,80 @@
+//! Database operations for the routing decision audit trail
+
+use sqlx::SqlitePool;
+use mcp_common::DecisionTrace;
+use crate::error::{LlmError, LlmResult};
+
+#[derive(Debug, Clone)]
+pub struct DecisionTracesDatabase {
+    pool: SqlitePool,
+}
+
+impl DecisionTracesDatabase {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+    
+    /// Store a finished decision trace
+    pub async fn record_trace(&self, trace: &DecisionTrace) -> LlmResult<()> {
+        let trace_data = serde_json::to_string(trace)?;
+        
+        sqlx::query!(
+            "INSERT INTO decision_traces (id, timestamp, mode, method, chosen_tool, requested_tool, applied, latency_ms, trace_data) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            trace.id,
+            trace.timestamp,
+            trace.mode,
+            trace.method,
+            trace.chosen,
+            trace.requested_tool,
+            trace.applied,
+            trace.latency_ms,
+            trace_data
+        )
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Look up one decision by trace ID
+    pub async fn get_trace(&self, trace_id: &str) -> LlmResult<Option<DecisionTrace>> {
+        let row = sqlx::query!(
+            "SELECT trace_data FROM decision_traces WHERE id = ?",
+            trace_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        row.map(|row| parse_trace(&row.trace_data)).transpose()
+    }
+    
+    /// Latest decisions, newest first
+    pub async fn recent_traces(&self, limit: i64) -> LlmResult<Vec<DecisionTrace>> {
+        let rows = sqlx::query!(
+            "SELECT trace_data FROM decision_traces ORDER BY timestamp DESC LIMIT ?",
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter().map(|row| parse_trace(&row.trace_data)).collect()
+    }
+    
+    /// Latest decisions that settled on `tool`, newest first
+    pub async fn traces_for_tool(&self, tool: &str, limit: i64) -> LlmResult<Vec<DecisionTrace>> {
+        let rows = sqlx::query!(
+            "SELECT trace_data FROM decision_traces WHERE chosen_tool = ? ORDER BY timestamp DESC LIMIT ?",
+            tool,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter().map(|row| parse_trace(&row.trace_data)).collect()
+    }
+}
+
+fn parse_trace(data: &str) -> LlmResult<DecisionTrace> {
+    serde_json::from_str(data)
+        .map_err(|e| LlmError::TraceError(format!("Stored trace is unreadable: {}", e)))
+}
@@ -2,7 +2,7 @@
 
 use sqlx::SqlitePool;
 use crate::error::{LlmError, LlmResult};
-use super::{RoutingRulesDatabase, PredictionsDatabase, MetricsDatabase};
+use super::{RoutingRulesDatabase, PredictionsDatabase, MetricsDatabase, DecisionTracesDatabase, SessionsDatabase};
 
 /// Main LLM database coordinator
 pub struct LlmDatabase {
@@ -10,6 +10,8 @@ pub struct LlmDatabase {
     pub routing_rules: RoutingRulesDatabase,
     pub predictions: PredictionsDatabase,
     pub metrics: MetricsDatabase,
+    pub decision_traces: DecisionTracesDatabase,
+    pub sessions: SessionsDatabase,
 }
 
 impl LlmDatabase {
@@ -24,6 +26,8 @@ impl LlmDatabase {
             routing_rules: RoutingRulesDatabase::new(pool.clone()),
             predictions: PredictionsDatabase::new(pool.clone()),
             metrics: MetricsDatabase::new(pool.clone()),
+            decision_traces: DecisionTracesDatabase::new(pool.clone()),
+            sessions: SessionsDatabase::new(pool.clone()),
             pool,
         })
     }
@@ -36,6 +40,8 @@ impl Clone for LlmDatabase {
             routing_rules: self.routing_rules.clone(),
             predictions: self.predictions.clone(),
             metrics: self.metrics.clone(),
+            decision_traces: self.decision_traces.clone(),
+            sessions: self.sessions.clone(),
         }
     }
 }
\ No newline at end of file
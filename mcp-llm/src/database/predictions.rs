@@ -128,6 +128,19 @@ impl PredictionsDatabase {
         Ok(predictions)
     }
     
+    /// Get the latest predictions whose actual tool is known
+    pub async fn get_labeled_predictions(&self, limit: i64) -> LlmResult<Vec<PredictionRecord>> {
+        let predictions = sqlx::query_as!(
+            PredictionRecord,
+            "SELECT * FROM predictions WHERE actual_tool IS NOT NULL ORDER BY timestamp DESC LIMIT ?",
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(predictions)
+    }
+    
     async fn get_prediction_tool(&self, prediction_id: &str) -> LlmResult<Option<String>> {
         let result = sqlx::query!(
             "SELECT predicted_tool FROM predictions WHERE id = ?",
@@ -0,0 +1,82 @@
+//! Database operations for session snapshots
+
+use sqlx::SqlitePool;
+use chrono::{DateTime, Utc};
+use crate::error::{LlmError, LlmResult};
+use crate::session_management::SessionSnapshot;
+
+#[derive(Debug, Clone)]
+pub struct SessionsDatabase {
+    pool: SqlitePool,
+}
+
+impl SessionsDatabase {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+    
+    /// Store a snapshot, replacing the session's previous one
+    pub async fn save_snapshot(&self, snapshot: &SessionSnapshot) -> LlmResult<()> {
+        let session_id = snapshot.context.session_id.0.to_string();
+        let context_data = serde_json::to_string(&snapshot.context)?;
+        
+        sqlx::query!(
+            "INSERT OR REPLACE INTO session_snapshots (session_id, summary, context_data, last_updated, saved_at) VALUES (?, ?, ?, ?, ?)",
+            session_id,
+            snapshot.summary,
+            context_data,
+            snapshot.context.last_updated,
+            snapshot.saved_at
+        )
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Snapshots of sessions active after `since`, most recent first
+    pub async fn load_snapshots(&self, since: DateTime<Utc>) -> LlmResult<Vec<SessionSnapshot>> {
+        let rows = sqlx::query!(
+            "SELECT summary, context_data, saved_at FROM session_snapshots WHERE last_updated > ? ORDER BY last_updated DESC",
+            since
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.into_iter()
+            .map(|row| {
+                let context = serde_json::from_str(&row.context_data)
+                    .map_err(|e| LlmError::ConfigError(format!("Stored session is unreadable: {}", e)))?;
+                Ok(SessionSnapshot {
+                    context,
+                    summary: row.summary,
+                    saved_at: row.saved_at,
+                })
+            })
+            .collect()
+    }
+    
+    /// Delete one session's snapshot
+    pub async fn delete_snapshot(&self, session_id: &str) -> LlmResult<()> {
+        sqlx::query!(
+            "DELETE FROM session_snapshots WHERE session_id = ?",
+            session_id
+        )
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Delete snapshots of sessions idle since before `cutoff`, returning how many
+    pub async fn expire_snapshots(&self, cutoff: DateTime<Utc>) -> LlmResult<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM session_snapshots WHERE last_updated <= ?",
+            cutoff
+        )
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,211 @@
+//! Decision tracing for routed requests
+//!
+//! A [`PendingTrace`] follows one request through the router, collecting the
+//! prompt, the model's answer and every candidate considered.
+//! [`DecisionTracer::record`] stamps the latency, appends the trace to the
+//! JSONL log and the SQLite audit trail, and forwards it to a monitor (the
+//! TUI) when one is connected. [`DecisionTracer::explain`] answers why a given
+//! request was routed the way it was.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use mcp_common::{
+    CandidateSource, DecisionTrace, DecisionTraceLog, IpcClient, IpcMessage, TraceCandidate,
+    TraceSummary,
+};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::database::DecisionTracesDatabase;
+use crate::error::{LlmError, LlmResult};
+
+/// Traces kept in memory for [`DecisionTracer::recent`]
+const RECENT_TRACES: usize = 200;
+
+/// A routing decision in progress
+pub struct PendingTrace {
+    started: Instant,
+    trace: DecisionTrace,
+}
+
+impl PendingTrace {
+    /// Start timing a decision for `request` under the given routing mode
+    pub fn new(mode: &str, request: &Value) -> Self {
+        Self {
+            started: Instant::now(),
+            trace: DecisionTrace::new(mode, request),
+        }
+    }
+
+    /// Prompt given to the predictor
+    pub fn prompt(&mut self, prompt: &str) {
+        self.trace.prompt = Some(prompt.to_string());
+    }
+
+    /// What the model answered
+    pub fn output(&mut self, output: &str) {
+        self.trace.output = Some(output.to_string());
+    }
+
+    /// Add a tool that was considered
+    pub fn candidate(
+        &mut self,
+        tool: &str,
+        score: f64,
+        source: CandidateSource,
+        reasoning: Option<&str>,
+    ) {
+        self.trace.candidates.push(TraceCandidate {
+            tool: tool.to_string(),
+            score,
+            source,
+            reasoning: reasoning.map(str::to_string),
+        });
+    }
+
+    /// Settle on a tool; `applied` is whether the request was changed for it
+    pub fn choose(&mut self, tool: &str, applied: bool) {
+        self.trace.chosen = Some(tool.to_string());
+        self.trace.applied = applied;
+    }
+
+    /// Stop the clock
+    pub fn finish(mut self) -> DecisionTrace {
+        self.trace.latency_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        self.trace
+    }
+}
+
+/// Persists decision traces and streams them to a monitor
+pub struct DecisionTracer {
+    log: DecisionTraceLog,
+    store: Option<DecisionTracesDatabase>,
+    monitor: Option<tokio::sync::Mutex<IpcClient>>,
+    recent: Mutex<VecDeque<DecisionTrace>>,
+}
+
+impl DecisionTracer {
+    /// Append traces to the JSONL file at `path`
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            log: DecisionTraceLog::new(path.as_ref()),
+            store: None,
+            monitor: None,
+            recent: Mutex::new(VecDeque::with_capacity(RECENT_TRACES)),
+        }
+    }
+
+    /// Also keep every trace in the SQLite audit trail
+    pub fn with_store(mut self, store: DecisionTracesDatabase) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Also send every trace to a monitor
+    pub fn with_monitor(mut self, monitor: IpcClient) -> Self {
+        self.monitor = Some(tokio::sync::Mutex::new(monitor));
+        self
+    }
+
+    /// Finish and store a trace
+    pub async fn record(&self, pending: PendingTrace) -> LlmResult<DecisionTrace> {
+        let trace = pending.finish();
+        self.log
+            .append(&trace)
+            .map_err(|e| LlmError::TraceError(format!("{:#}", e)))?;
+        if let Some(store) = &self.store {
+            store.record_trace(&trace).await?;
+        }
+
+        {
+            let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+            if recent.len() == RECENT_TRACES {
+                recent.pop_front();
+            }
+            recent.push_back(trace.clone());
+        }
+
+        // A missing monitor must never fail routing
+        if let Some(monitor) = &self.monitor {
+            let mut monitor = monitor.lock().await;
+            if let Err(e) = monitor
+                .send(IpcMessage::DecisionTraced(trace.clone()))
+                .await
+            {
+                warn!("Failed to send decision trace: {}", e);
+            }
+        }
+        Ok(trace)
+    }
+
+    /// Latest traces, oldest first
+    pub fn recent(&self) -> Vec<DecisionTrace> {
+        let recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.iter().cloned().collect()
+    }
+
+    /// Why the request traced as `trace_id` was routed as it was, looked up
+    /// in memory first and then in the audit trail
+    pub async fn explain(&self, trace_id: &str) -> LlmResult<Option<Vec<String>>> {
+        let cached = self.recent().into_iter().find(|trace| trace.id == trace_id);
+        let trace = match (cached, &self.store) {
+            (Some(trace), _) => Some(trace),
+            (None, Some(store)) => store.get_trace(trace_id).await?,
+            (None, None) => None,
+        };
+        Ok(trace.map(|trace| trace.explain()))
+    }
+
+    /// Copy the persisted traces to `to` as JSONL for offline evaluation
+    pub fn export(&self, to: impl AsRef<Path>) -> LlmResult<usize> {
+        self.log
+            .export(to, None)
+            .map_err(|e| LlmError::TraceError(format!("{:#}", e)))
+    }
+
+    /// Routing accuracy and latency over the whole log
+    pub fn summary(&self) -> LlmResult<TraceSummary> {
+        self.log
+            .summary()
+            .map_err(|e| LlmError::TraceError(format!("{:#}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tracer_persists_decisions() {
+        let path =
+            std::env::temp_dir().join(format!("mcp-llm-traces-{}.jsonl", std::process::id()));
+        let tracer = DecisionTracer::new(&path);
+
+        let request = json!({"jsonrpc": "2.0", "id": 7, "method": "tools/call", "params": {"name": "search"}});
+        let mut pending = PendingTrace::new("Semantic", &request);
+        pending.prompt("{\"method\":\"tools/call\"}");
+        pending.output("{\"tool_name\":\"search\",\"confidence\":0.92}");
+        pending.candidate(
+            "search",
+            0.92,
+            CandidateSource::Prediction,
+            Some("matches query"),
+        );
+        pending.choose("search", true);
+        let trace = tracer.record(pending).await.unwrap();
+
+        assert_eq!(tracer.recent(), vec![trace.clone()]);
+        let explanation = tracer.explain(&trace.id).await.unwrap().unwrap();
+        assert_eq!(
+            explanation[1],
+            "1. search scored 0.92 from a model prediction: matches query"
+        );
+        assert!(tracer.explain("unknown").await.unwrap().is_none());
+        assert_eq!(tracer.summary().unwrap().accuracy(), Some(1.0));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -14,6 +14,18 @@ pub enum LlmError {
 
     #[error("Invalid configuration: {0}")]
     ConfigError(String),
+
+    #[error("Prediction error: {0}")]
+    PredictionError(String),
+
+    #[error("Decision trace error: {0}")]
+    TraceError(String),
+
+    #[error("LLM provider error: {0}")]
+    ProviderError(String),
+
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
 }
 
 pub type LlmResult<T> = Result<T, LlmError>;
@@ -0,0 +1,399 @@
+//! Offline evaluation of tool prediction quality
+//!
+//! An [`EvalSet`] holds labeled queries: contexts whose actual tool is known,
+//! taken from predictions that received feedback or from decision traces.
+//! [`EvalSet::run`] replays them through the current predictor and scores the
+//! answers in an [`EvalReport`]. An [`EvalGate`] compares a report with a
+//! baseline, so GEPA only keeps an optimized prompt that measurably helps.
+
+use std::time::Instant;
+
+use async_trait::async_trait;
+use mcp_common::DecisionTrace;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::database::{PredictionRecord, PredictionsDatabase};
+use crate::error::LlmResult;
+use crate::predictors::ToolPredictor;
+
+/// Candidates that count towards top-k accuracy unless set otherwise
+pub const DEFAULT_TOP_K: usize = 3;
+
+/// A query whose correct tool is known
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalExample {
+    /// Context given to the predictor
+    pub context: String,
+    /// Tool that was actually called
+    pub expected_tool: String,
+}
+
+impl EvalExample {
+    /// A prediction with feedback; records saved without their context are skipped
+    pub fn from_record(record: &PredictionRecord) -> Option<Self> {
+        Some(Self {
+            context: record
+                .prediction_data
+                .get("mcp_context")?
+                .as_str()?
+                .to_string(),
+            expected_tool: record.actual_tool.clone()?,
+        })
+    }
+
+    /// A traced `tools/call` decision whose prompt was recorded
+    pub fn from_trace(trace: &DecisionTrace) -> Option<Self> {
+        Some(Self {
+            context: trace.prompt.clone()?,
+            expected_tool: trace.requested_tool.clone()?,
+        })
+    }
+}
+
+/// A predictor that can be evaluated
+#[async_trait]
+pub trait RankingPredictor: Send + Sync {
+    /// Candidate tools for `context`, most likely first
+    async fn rank(&self, context: &str) -> LlmResult<Vec<String>>;
+}
+
+#[async_trait]
+impl RankingPredictor for ToolPredictor {
+    async fn rank(&self, context: &str) -> LlmResult<Vec<String>> {
+        let prediction = self.predict_tool(context).await?;
+        let mut ranked = vec![prediction.tool_name];
+        ranked.extend(prediction.alternatives);
+        Ok(ranked)
+    }
+}
+
+/// Labeled queries to replay against a predictor
+#[derive(Debug, Clone)]
+pub struct EvalSet {
+    examples: Vec<EvalExample>,
+    top_k: usize,
+}
+
+impl EvalSet {
+    pub fn new(examples: Vec<EvalExample>) -> Self {
+        Self {
+            examples,
+            top_k: DEFAULT_TOP_K,
+        }
+    }
+
+    /// The latest `limit` predictions that received feedback
+    pub async fn from_feedback(database: &PredictionsDatabase, limit: i64) -> LlmResult<Self> {
+        let records = database.get_labeled_predictions(limit).await?;
+        Ok(Self::new(
+            records
+                .iter()
+                .filter_map(EvalExample::from_record)
+                .collect(),
+        ))
+    }
+
+    /// Every decision in a trace log that can be scored
+    pub fn from_traces(traces: &[DecisionTrace]) -> Self {
+        Self::new(traces.iter().filter_map(EvalExample::from_trace).collect())
+    }
+
+    /// Count a hit when the expected tool is among the first `k` candidates
+    pub fn with_top_k(mut self, k: usize) -> Self {
+        self.top_k = k.max(1);
+        self
+    }
+
+    pub fn examples(&self) -> &[EvalExample] {
+        &self.examples
+    }
+
+    pub fn len(&self) -> usize {
+        self.examples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.examples.is_empty()
+    }
+
+    /// Replay every example through `predictor`, one at a time so latencies are comparable
+    pub async fn run(&self, predictor: &dyn RankingPredictor) -> EvalReport {
+        let mut outcomes = Vec::with_capacity(self.examples.len());
+        for example in &self.examples {
+            let started = Instant::now();
+            let ranked = match predictor.rank(&example.context).await {
+                Ok(ranked) => Some(ranked),
+                Err(e) => {
+                    warn!("Prediction failed during evaluation: {}", e);
+                    None
+                }
+            };
+            outcomes.push(EvalOutcome {
+                expected_tool: example.expected_tool.clone(),
+                ranked,
+                latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+            });
+        }
+        EvalReport::score(&outcomes, self.top_k)
+    }
+}
+
+/// What the predictor answered for one example
+#[derive(Debug, Clone)]
+pub struct EvalOutcome {
+    pub expected_tool: String,
+    /// Ranked candidates, `None` when the prediction failed
+    pub ranked: Option<Vec<String>>,
+    pub latency_ms: f64,
+}
+
+/// Prediction quality over an evaluation set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub examples: usize,
+    /// Examples whose first candidate was the expected tool
+    pub correct: usize,
+    pub top_k: usize,
+    /// Examples with the expected tool among the first `top_k` candidates
+    pub top_k_hits: usize,
+    /// Failed predictions; they count as misses
+    pub errors: usize,
+    pub mean_latency_ms: f64,
+    pub p95_latency_ms: f64,
+}
+
+impl EvalReport {
+    /// Score a set of outcomes
+    pub fn score(outcomes: &[EvalOutcome], top_k: usize) -> Self {
+        let mut report = Self {
+            examples: outcomes.len(),
+            top_k,
+            ..Self::default()
+        };
+        for outcome in outcomes {
+            let Some(ranked) = &outcome.ranked else {
+                report.errors += 1;
+                continue;
+            };
+            if ranked.first() == Some(&outcome.expected_tool) {
+                report.correct += 1;
+            }
+            if ranked
+                .iter()
+                .take(top_k)
+                .any(|tool| *tool == outcome.expected_tool)
+            {
+                report.top_k_hits += 1;
+            }
+        }
+
+        let mut latencies: Vec<f64> = outcomes.iter().map(|outcome| outcome.latency_ms).collect();
+        if !latencies.is_empty() {
+            latencies.sort_by(f64::total_cmp);
+            report.mean_latency_ms = latencies.iter().sum::<f64>() / latencies.len() as f64;
+            let rank = (latencies.len() as f64 * 0.95).ceil() as usize;
+            report.p95_latency_ms = latencies[rank.clamp(1, latencies.len()) - 1];
+        }
+        report
+    }
+
+    /// Share of examples predicted exactly
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.correct, self.examples)
+    }
+
+    /// Share of examples with the expected tool in the top `top_k`
+    pub fn top_k_accuracy(&self) -> f64 {
+        ratio(self.top_k_hits, self.examples)
+    }
+
+    /// How this report differs from `baseline`
+    pub fn compare(&self, baseline: &EvalReport) -> Comparison {
+        Comparison {
+            accuracy_delta: self.accuracy() - baseline.accuracy(),
+            top_k_delta: self.top_k_accuracy() - baseline.top_k_accuracy(),
+            latency_delta_ms: self.mean_latency_ms - baseline.mean_latency_ms,
+        }
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Change from a baseline report to a candidate; positive accuracy deltas are better
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Comparison {
+    pub accuracy_delta: f64,
+    pub top_k_delta: f64,
+    pub latency_delta_ms: f64,
+}
+
+/// When a candidate counts as an improvement over the baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalGate {
+    /// Fewer labeled examples than this cannot show an improvement
+    pub min_examples: usize,
+    /// Required gain in exact accuracy
+    pub min_accuracy_gain: f64,
+    /// Largest tolerated increase in mean latency
+    pub max_latency_increase_ms: f64,
+}
+
+impl Default for EvalGate {
+    fn default() -> Self {
+        Self {
+            min_examples: 20,
+            min_accuracy_gain: 0.01,
+            max_latency_increase_ms: 100.0,
+        }
+    }
+}
+
+/// Outcome of [`EvalGate::check`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GateDecision {
+    pub baseline: EvalReport,
+    pub candidate: EvalReport,
+    pub comparison: Comparison,
+    pub passed: bool,
+    /// Why the candidate was kept or rejected
+    pub reason: String,
+}
+
+impl EvalGate {
+    /// Decide whether `candidate` improves enough on `baseline`
+    pub fn check(&self, baseline: &EvalReport, candidate: &EvalReport) -> GateDecision {
+        let comparison = candidate.compare(baseline);
+        let accuracy = format!(
+            "accuracy {:.3} -> {:.3}",
+            baseline.accuracy(),
+            candidate.accuracy()
+        );
+        let (passed, reason) = if candidate.examples < self.min_examples {
+            (
+                false,
+                format!(
+                    "only {} labeled examples, {} needed",
+                    candidate.examples, self.min_examples
+                ),
+            )
+        } else if comparison.accuracy_delta < self.min_accuracy_gain {
+            (
+                false,
+                format!(
+                    "{}, below the required gain of {}",
+                    accuracy, self.min_accuracy_gain
+                ),
+            )
+        } else if comparison.latency_delta_ms > self.max_latency_increase_ms {
+            (
+                false,
+                format!(
+                    "{}, but mean latency rose {:.1}ms",
+                    accuracy, comparison.latency_delta_ms
+                ),
+            )
+        } else {
+            (true, accuracy)
+        };
+        GateDecision {
+            baseline: baseline.clone(),
+            candidate: candidate.clone(),
+            comparison,
+            passed,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Answers from a fixed table
+    struct TablePredictor(HashMap<String, Vec<String>>);
+
+    #[async_trait]
+    impl RankingPredictor for TablePredictor {
+        async fn rank(&self, context: &str) -> LlmResult<Vec<String>> {
+            self.0
+                .get(context)
+                .cloned()
+                .ok_or_else(|| crate::error::LlmError::RuntimeError("no answer".to_string()))
+        }
+    }
+
+    fn example(context: &str, tool: &str) -> EvalExample {
+        EvalExample {
+            context: context.to_string(),
+            expected_tool: tool.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eval_set_scores_accuracy_and_top_k() {
+        let set = EvalSet::new(vec![
+            example("a", "search"),
+            example("b", "read"),
+            example("c", "write"),
+            example("d", "delete"),
+        ])
+        .with_top_k(2);
+        let predictor = TablePredictor(HashMap::from([
+            ("a".to_string(), vec!["search".to_string()]),
+            (
+                "b".to_string(),
+                vec!["search".to_string(), "read".to_string()],
+            ),
+            (
+                "c".to_string(),
+                vec![
+                    "read".to_string(),
+                    "search".to_string(),
+                    "write".to_string(),
+                ],
+            ),
+        ]));
+
+        let report = set.run(&predictor).await;
+        assert_eq!(report.examples, 4);
+        assert_eq!(report.correct, 1);
+        assert_eq!(report.top_k_hits, 2);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.accuracy(), 0.25);
+        assert_eq!(report.top_k_accuracy(), 0.5);
+    }
+
+    #[test]
+    fn test_gate_requires_measurable_gain() {
+        let report = |correct, mean_latency_ms| EvalReport {
+            examples: 40,
+            correct,
+            top_k: DEFAULT_TOP_K,
+            top_k_hits: correct,
+            mean_latency_ms,
+            ..EvalReport::default()
+        };
+        let gate = EvalGate::default();
+        let baseline = report(20, 50.0);
+
+        assert!(gate.check(&baseline, &report(24, 60.0)).passed);
+        assert!(!gate.check(&baseline, &report(20, 50.0)).passed);
+        assert!(!gate.check(&baseline, &report(30, 400.0)).passed);
+
+        let small = EvalReport {
+            examples: 5,
+            ..report(5, 50.0)
+        };
+        let decision = gate.check(&baseline, &small);
+        assert!(!decision.passed);
+        assert!(decision.reason.contains("only 5 labeled examples"));
+    }
+}
@@ -6,6 +6,7 @@ use async_trait::async_trait;
 use crate::lm_provider::LiteRTLMProvider;
 use crate::signatures::PromptOptimizationSignature;
 use crate::database::{PredictionsDatabase, AccuracyMetrics};
+use crate::evaluation::{EvalGate, EvalSet, GateDecision, RankingPredictor};
 use crate::error::{LlmError, LlmResult};
 
 /// GEPA optimizer for prompt improvement
@@ -34,6 +35,8 @@ pub struct OptimizationResult {
     pub final_improvement: f64,
     pub success: bool,
     pub total_time_ms: u64,
+    /// Evaluation against the baseline, for gated runs
+    pub gate: Option<GateDecision>,
 }
 
 impl GEPAOptimizer {
@@ -106,9 +109,37 @@ impl GEPAOptimizer {
             final_improvement: best_improvement,
             success: best_improvement >= self.improvement_threshold,
             total_time_ms,
+            gate: None,
         })
     }
     
+    /// Optimize, then keep the new prompt only if it beats the current one
+    ///
+    /// The module is evaluated on `eval` before and after optimization. When
+    /// `gate` rejects the result, the original prompt is restored and the run
+    /// is reported as unsuccessful.
+    pub async fn optimize_gated<T: Module + RankingPredictor>(
+        &mut self,
+        module: &mut T,
+        train_examples: Vec<Example>,
+        eval: &EvalSet,
+        gate: &EvalGate,
+    ) -> LlmResult<OptimizationResult> {
+        let baseline = eval.run(&*module).await;
+        let original_prompt = self.extract_current_prompt(module).await?;
+        
+        let mut result = self.optimize_module(module, train_examples).await?;
+        
+        let candidate = eval.run(&*module).await;
+        let decision = gate.check(&baseline, &candidate);
+        if !decision.passed {
+            self.apply_prompt_to_module(module, &original_prompt).await?;
+            result.success = false;
+        }
+        result.gate = Some(decision);
+        Ok(result)
+    }
+    
     /// Generate improved prompt based on execution traces
     async fn optimize_prompt_iteration(
         &self,
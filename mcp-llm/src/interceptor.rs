@@ -4,9 +4,12 @@ use std::sync::Arc;
 use crate::predictors::ToolPredictor;
 use crate::routing_modes::RoutingMode;
 use crate::database::{RoutingRulesDatabase, PredictionsDatabase};
+use crate::decision_trace::{DecisionTracer, PendingTrace};
 use crate::error::{LlmError, LlmResult};
 use mcp_core::interceptor::{MessageInterceptor, InterceptionResult, JsonRpcMessage};
+use mcp_common::CandidateSource;
 use serde_json::Value;
+use tracing::warn;
 
 /// LLM-powered interceptor for intelligent request processing
 pub struct LlmInterceptor {
@@ -15,6 +18,7 @@ pub struct LlmInterceptor {
     predictions_db: PredictionsDatabase,
     routing_mode: RoutingMode,
     confidence_threshold: f32,
+    tracer: Option<Arc<DecisionTracer>>,
 }
 
 impl LlmInterceptor {
@@ -30,8 +34,15 @@ impl LlmInterceptor {
             predictions_db: PredictionsDatabase::placeholder(),
             routing_mode,
             confidence_threshold: 0.8,
+            tracer: None,
         }
     }
+
+    /// Record why each request was routed the way it was
+    pub fn with_tracer(mut self, tracer: Arc<DecisionTracer>) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
     
     /// Set routing mode
     pub fn set_routing_mode(&mut self, mode: RoutingMode) {
@@ -46,30 +57,55 @@ impl LlmInterceptor {
     /// Predict and route request
     async fn predict_and_route(&self, message: &mut JsonRpcMessage) -> LlmResult<InterceptionResult> {
         let context = self.extract_mcp_context(message)?;
+        let mut trace = PendingTrace::new(self.routing_mode.display_name(), &serde_json::from_str(&context)?);
         
-        match self.routing_mode {
+        let result = match self.routing_mode {
             RoutingMode::Bypass => Ok(InterceptionResult::Pass),
-            RoutingMode::Semantic => self.semantic_routing(message, &context).await,
-            RoutingMode::Hybrid => self.hybrid_routing(message, &context).await,
+            RoutingMode::Semantic => self.semantic_routing(message, &context, &mut trace).await,
+            RoutingMode::Hybrid => self.hybrid_routing(message, &context, &mut trace).await,
+        };
+        
+        // Bypassed requests are not decisions worth tracing
+        if let (Some(tracer), false) = (&self.tracer, self.routing_mode == RoutingMode::Bypass) {
+            if let Err(e) = tracer.record(trace).await {
+                warn!("Failed to record decision trace: {}", e);
+            }
         }
+        result
     }
     
     /// Semantic routing using LLM predictions
-    async fn semantic_routing(&self, message: &mut JsonRpcMessage, context: &str) -> LlmResult<InterceptionResult> {
+    async fn semantic_routing(
+        &self,
+        message: &mut JsonRpcMessage,
+        context: &str,
+        trace: &mut PendingTrace,
+    ) -> LlmResult<InterceptionResult> {
+        trace.prompt(context);
         let prediction = self.predictor.predict_tool(context).await?;
+        trace.output(&serde_json::to_string(&prediction)?);
+        trace.candidate(
+            &prediction.tool_name,
+            prediction.confidence as f64,
+            CandidateSource::Prediction,
+            Some(&prediction.reasoning),
+        );
         
-        // Record prediction
+        // Record prediction, keeping the context so it can be replayed for evaluation
         let context_hash = self.hash_context(context);
+        let mut prediction_data = serde_json::to_value(&prediction)?;
+        prediction_data["mcp_context"] = Value::String(context.to_string());
         self.predictions_db.record_prediction(
             &context_hash,
             &prediction.tool_name,
             prediction.confidence as f64,
-            serde_json::to_value(&prediction)?,
+            prediction_data,
         ).await?;
         
         if prediction.confidence >= self.confidence_threshold {
             // Modify request based on prediction
             self.enhance_request_with_prediction(message, &prediction).await?;
+            trace.choose(&prediction.tool_name, true);
             Ok(InterceptionResult::Modified)
         } else {
             Ok(InterceptionResult::Pass)
@@ -77,15 +113,22 @@ impl LlmInterceptor {
     }
     
     /// Hybrid routing combining database rules and LLM predictions
-    async fn hybrid_routing(&self, message: &mut JsonRpcMessage, context: &str) -> LlmResult<InterceptionResult> {
+    async fn hybrid_routing(
+        &self,
+        message: &mut JsonRpcMessage,
+        context: &str,
+        trace: &mut PendingTrace,
+    ) -> LlmResult<InterceptionResult> {
         // First check database rules
         if let Some(rule) = self.routing_db.find_matching_rule(context).await? {
+            trace.candidate(&rule.target_tool, rule.confidence, CandidateSource::Rule, Some(&rule.pattern));
             self.apply_routing_rule(message, &rule).await?;
+            trace.choose(&rule.target_tool, true);
             return Ok(InterceptionResult::Modified);
         }
         
         // Fall back to LLM prediction
-        self.semantic_routing(message, context).await
+        self.semantic_routing(message, context, trace).await
     }
     
     /// Extract MCP context from message
@@ -6,6 +6,13 @@
 //! - SQLite-backed routing and optimization
 //! - GEPA prompt optimization
 //! - Real-time tool prediction and routing
+//! - Hosted OpenAI-compatible and Anthropic models when no local model is available
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::database::SessionsDatabase;
 
 // Include generated bindings
 #[allow(non_upper_case_globals)]
@@ -29,6 +36,11 @@ pub mod lm_provider;
 pub mod routing_modes;
 pub mod metrics;
 pub mod interceptor;
+pub mod decision_trace;
+pub mod evaluation;
+pub mod provider;
+pub mod synthesis;
+pub mod sampling;
 
 pub mod routing_modes;
 pub mod metrics;
@@ -37,36 +49,106 @@ pub mod interceptor;
 // Re-export main types
 pub use error::{LlmError, LlmResult};
 pub use litert_wrapper::{LiteRTEngine, LiteRTSession, LiteRTBackend};
-pub use session_management::{SessionManager, SessionPredictionContext, SessionPrediction};
+pub use session_management::{SessionManager, SessionPredictionContext, SessionPrediction, SessionRetention, SessionSnapshot};
 pub use conversation_context::{ConversationContextBuilder, ConversationAnalyzer};
 pub use dspy_signatures::{ToolPrediction, ToolPredictionSignature};
-pub use predictors::{ToolPredictor, AdvancedToolPredictor};
+pub use predictors::{ToolPredictor, AdvancedToolPredictor, SuggestedCall};
 pub use gepa_optimizer::GEPAOptimizer;
+pub use decision_trace::{DecisionTracer, PendingTrace};
+pub use evaluation::{EvalGate, EvalReport, EvalSet, RankingPredictor};
+pub use provider::{LlmProvider, ProviderConfig};
+pub use synthesis::{ArgumentSynthesizer, SynthesizedArguments, ValidationReport};
+pub use sampling::{LlmSamplingHandler, ModelProfile};
 
 /// High-level LLM Manager for easy use
 pub struct LlmManager {
-    engine: LiteRTEngine,
+    provider: Arc<dyn LlmProvider>,
+    model_name: String,
     session_manager: SessionManager,
 }
 
 impl LlmManager {
+    /// Run against a local LiteRT-LM model
     pub async fn new(model_path: &str) -> LlmResult<Self> {
-        let engine = LiteRTEngine::new(model_path, LiteRTBackend::Cpu)?;
+        Self::from_config(&LlmConfig::litert(model_path)).await
+    }
+
+    /// Run against the configured provider
+    pub async fn from_config(config: &LlmConfig) -> LlmResult<Self> {
+        let provider = config.provider.build(config.temperature, config.max_tokens)?;
         let predictor = Arc::new(AdvancedToolPredictor::new()?);
         let gepa_optimizer = Arc::new(GEPAOptimizer::new()?);
-        let session_manager = SessionManager::new(predictor, gepa_optimizer);
-        
+        let session_manager = SessionManager::new(predictor, gepa_optimizer)
+            .with_retention(config.sessions.clone());
+
         Ok(Self {
-            engine,
+            provider: Arc::from(provider),
+            model_name: config.provider.model_name(),
             session_manager,
         })
     }
+
+    /// The model predictions and routing run against
+    pub fn provider(&self) -> Arc<dyn LlmProvider> {
+        self.provider.clone()
+    }
+
+    /// Answer servers' sampling requests with the configured model; add more
+    /// models with [`LlmSamplingHandler::with_model`]
+    pub fn sampling_handler(&self) -> LlmSamplingHandler {
+        LlmSamplingHandler::new(self.model_name.clone(), self.provider.clone())
+    }
+
+    /// Keep sessions in `store` and bring back the ones saved before the last
+    /// restart that have not expired
+    pub async fn with_session_store(mut self, store: SessionsDatabase) -> LlmResult<Self> {
+        self.session_manager = self.session_manager.with_store(store);
+        let restored = self.session_manager.restore_sessions().await?;
+        tracing::info!("Restored {} sessions", restored);
+        Ok(self)
+    }
+
+    /// Per-session prediction context
+    pub fn sessions(&self) -> &SessionManager {
+        &self.session_manager
+    }
 }
 
 /// Simple config for LlmManager
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LlmConfig {
-    pub model_path: String,
+    #[serde(flatten)]
+    pub provider: ProviderConfig,
+    #[serde(default = "default_temperature")]
+    pub temperature: f32,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+    /// How long idle sessions are kept and how much of each is saved
+    #[serde(default)]
+    pub sessions: SessionRetention,
+}
+
+impl LlmConfig {
+    /// A local LiteRT-LM model on the CPU
+    pub fn litert(model_path: impl Into<String>) -> Self {
+        Self {
+            provider: ProviderConfig::LiteRt {
+                model_path: model_path.into(),
+                gpu: false,
+            },
+            temperature: default_temperature(),
+            max_tokens: default_max_tokens(),
+            sessions: SessionRetention::default(),
+        }
+    }
+}
+
+fn default_temperature() -> f32 {
+    0.7
+}
+
+fn default_max_tokens() -> u32 {
+    1000
 }
 
 #[cfg(test)]
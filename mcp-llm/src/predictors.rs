@@ -11,6 +11,8 @@ use crate::signatures::{
     RoutingDecision
 };
 use crate::error::{LlmError, LlmResult};
+use crate::litert_wrapper::Tool;
+use crate::synthesis::{ArgumentSynthesizer, ValidationReport};
 
 /// Main tool predictor for MCP requests
 pub struct ToolPredictor {
@@ -69,6 +71,40 @@ impl ToolPredictor {
         
         Ok(predictions)
     }
+
+    /// Predict a tool from `tools` and synthesize arguments for it that fit
+    /// its schema
+    pub async fn suggest_call(
+        &self,
+        mcp_context: &str,
+        tools: &[Tool],
+        synthesizer: &ArgumentSynthesizer,
+    ) -> LlmResult<SuggestedCall> {
+        let prediction = self.predict_tool(mcp_context).await?;
+        let tool = tools
+            .iter()
+            .find(|tool| tool.name == prediction.tool_name)
+            .ok_or_else(|| {
+                LlmError::PredictionError(format!("Predicted unknown tool '{}'", prediction.tool_name))
+            })?;
+
+        let synthesized = synthesizer
+            .synthesize(mcp_context, tool, &prediction.parameters)
+            .await?;
+        Ok(SuggestedCall {
+            prediction,
+            arguments: synthesized.arguments,
+            report: synthesized.report,
+        })
+    }
+}
+
+/// A predicted tool together with arguments checked against its schema
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SuggestedCall {
+    pub prediction: ToolPrediction,
+    pub arguments: serde_json::Value,
+    pub report: ValidationReport,
 }
 
 #[async_trait]
@@ -0,0 +1,538 @@
+//! Model backends behind one interface
+//!
+//! Prediction and routing need three things from a model: text generation,
+//! embeddings and tokenization. [`LlmProvider`] covers those, so they can
+//! run against a local LiteRT-LM model or, when none is available, a hosted
+//! one: any OpenAI-compatible API (OpenAI, vLLM, Ollama, llama.cpp) or
+//! Anthropic. [`ProviderConfig`] selects the backend and builds it.
+//!
+//! Not every backend offers every operation. LiteRT-LM only generates,
+//! Anthropic has no embeddings and no hosted API hands out token IDs; those
+//! calls fail with [`LlmError::Unsupported`]. Anthropic does count tokens, so
+//! [`LlmProvider::count_tokens`] works there all the same.
+//!
+//! [`LlmProvider::generate_with`] takes per-call [`GenerationOptions`] for
+//! callers that set their own limits, such as MCP sampling requests. Hosted
+//! APIs apply them natively. Elsewhere the output is cut at the first stop
+//! sequence after generation.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{LlmError, LlmResult};
+use crate::litert_wrapper::{LiteRTBackend, LiteRTEngine, LiteRTSession};
+
+/// Anthropic API version sent with every request
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Longest wait for a hosted model to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Per-call overrides of the configured generation settings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerationOptions {
+    pub system: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// Generation stops before the first of these
+    pub stop: Vec<String>,
+}
+
+/// Why generation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationStop {
+    EndTurn,
+    MaxTokens,
+    StopSequence,
+}
+
+/// Text generated with [`LlmProvider::generate_with`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    pub text: String,
+    pub stop: GenerationStop,
+}
+
+impl Generation {
+    /// Cut `text` before the first stop sequence in it
+    pub fn truncated(text: String, stop: &[String]) -> Self {
+        let cut = stop
+            .iter()
+            .filter(|sequence| !sequence.is_empty())
+            .filter_map(|sequence| text.find(sequence.as_str()))
+            .min();
+        match cut {
+            Some(at) => Self {
+                text: text[..at].to_string(),
+                stop: GenerationStop::StopSequence,
+            },
+            None => Self {
+                text,
+                stop: GenerationStop::EndTurn,
+            },
+        }
+    }
+}
+
+/// A model that prediction and routing can run against
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Short name for logs and errors
+    fn name(&self) -> &'static str;
+
+    /// Complete `prompt`
+    async fn generate(&self, prompt: &str) -> LlmResult<String>;
+
+    /// Complete `prompt` with per-call options. The default puts the system
+    /// prompt in front, ignores the sampling limits and applies stop
+    /// sequences afterwards
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerationOptions,
+    ) -> LlmResult<Generation> {
+        let text = match &options.system {
+            Some(system) => self.generate(&format!("{}\n\n{}", system, prompt)).await?,
+            None => self.generate(prompt).await?,
+        };
+        Ok(Generation::truncated(text, &options.stop))
+    }
+
+    /// Embedding vector of `text`
+    async fn embed(&self, _text: &str) -> LlmResult<Vec<f32>> {
+        Err(unsupported(self.name(), "embeddings"))
+    }
+
+    /// Token IDs of `text`
+    async fn tokenize(&self, _text: &str) -> LlmResult<Vec<u32>> {
+        Err(unsupported(self.name(), "tokenization"))
+    }
+
+    /// Number of tokens in `text`
+    async fn count_tokens(&self, text: &str) -> LlmResult<usize> {
+        Ok(self.tokenize(text).await?.len())
+    }
+}
+
+fn unsupported(provider: &str, operation: &str) -> LlmError {
+    LlmError::Unsupported(format!("{} does not support {}", provider, operation))
+}
+
+/// Which backend to use and how to reach it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum ProviderConfig {
+    /// A local LiteRT-LM model
+    LiteRt {
+        model_path: String,
+        #[serde(default)]
+        gpu: bool,
+    },
+    /// Any server speaking the OpenAI chat completions API
+    OpenAi {
+        /// API root including the version, e.g. `https://api.openai.com/v1`
+        base_url: String,
+        model: String,
+        /// Model for [`LlmProvider::embed`]; embeddings fail without one
+        #[serde(default)]
+        embedding_model: Option<String>,
+        /// Read from `OPENAI_API_KEY` when unset; local servers need none
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+    /// Anthropic's Messages API
+    Anthropic {
+        model: String,
+        /// Read from `ANTHROPIC_API_KEY` when unset
+        #[serde(default)]
+        api_key: Option<String>,
+        #[serde(default = "default_anthropic_url")]
+        base_url: String,
+    },
+}
+
+fn default_anthropic_url() -> String {
+    "https://api.anthropic.com".to_string()
+}
+
+impl ProviderConfig {
+    /// Name of the configured model, the file name for a local one
+    pub fn model_name(&self) -> String {
+        match self {
+            ProviderConfig::LiteRt { model_path, .. } => {
+                std::path::Path::new(model_path).file_stem().map_or_else(
+                    || model_path.clone(),
+                    |stem| stem.to_string_lossy().into_owned(),
+                )
+            }
+            ProviderConfig::OpenAi { model, .. } | ProviderConfig::Anthropic { model, .. } => {
+                model.clone()
+            }
+        }
+    }
+
+    /// Create the configured backend
+    pub fn build(&self, temperature: f32, max_tokens: u32) -> LlmResult<Box<dyn LlmProvider>> {
+        Ok(match self {
+            ProviderConfig::LiteRt { model_path, gpu } => {
+                let backend = if *gpu {
+                    LiteRTBackend::Gpu
+                } else {
+                    LiteRTBackend::Cpu
+                };
+                Box::new(LiteRtProvider::new(model_path, backend)?)
+            }
+            ProviderConfig::OpenAi {
+                base_url,
+                model,
+                embedding_model,
+                api_key,
+            } => Box::new(OpenAiProvider {
+                client: http_client()?,
+                base_url: base_url.trim_end_matches('/').to_string(),
+                model: model.clone(),
+                embedding_model: embedding_model.clone(),
+                api_key: api_key
+                    .clone()
+                    .or_else(|| std::env::var("OPENAI_API_KEY").ok()),
+                temperature,
+                max_tokens,
+            }),
+            ProviderConfig::Anthropic {
+                model,
+                api_key,
+                base_url,
+            } => {
+                let api_key = api_key
+                    .clone()
+                    .or_else(|| std::env::var("ANTHROPIC_API_KEY").ok())
+                    .ok_or_else(|| {
+                        LlmError::ConfigError(
+                            "Anthropic needs an API key or ANTHROPIC_API_KEY".to_string(),
+                        )
+                    })?;
+                Box::new(AnthropicProvider {
+                    client: http_client()?,
+                    base_url: base_url.trim_end_matches('/').to_string(),
+                    model: model.clone(),
+                    api_key,
+                    temperature,
+                    max_tokens,
+                })
+            }
+        })
+    }
+}
+
+fn http_client() -> LlmResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| LlmError::ConfigError(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// A local model run through LiteRT-LM
+pub struct LiteRtProvider {
+    // Dropped before the engine it was created from
+    session: LiteRTSession,
+    _engine: LiteRTEngine,
+}
+
+impl LiteRtProvider {
+    pub fn new(model_path: &str, backend: LiteRTBackend) -> LlmResult<Self> {
+        let engine = LiteRTEngine::new(model_path, backend)?;
+        let session = engine.create_session()?;
+        Ok(Self {
+            session,
+            _engine: engine,
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for LiteRtProvider {
+    fn name(&self) -> &'static str {
+        "LiteRT-LM"
+    }
+
+    async fn generate(&self, prompt: &str) -> LlmResult<String> {
+        self.session.generate(prompt)
+    }
+}
+
+/// A hosted or local server speaking the OpenAI API
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    embedding_model: Option<String>,
+    api_key: Option<String>,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+impl OpenAiProvider {
+    async fn post(&self, path: &str, body: Value) -> LlmResult<Value> {
+        let mut request = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body);
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+        send(self.name(), request).await
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "OpenAI-compatible API"
+    }
+
+    async fn generate(&self, prompt: &str) -> LlmResult<String> {
+        Ok(self
+            .generate_with(prompt, &GenerationOptions::default())
+            .await?
+            .text)
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerationOptions,
+    ) -> LlmResult<Generation> {
+        let mut messages = Vec::new();
+        if let Some(system) = &options.system {
+            messages.push(json!({"role": "system", "content": system}));
+        }
+        messages.push(json!({"role": "user", "content": prompt}));
+        let mut body = json!({
+            "model": self.model,
+            "messages": messages,
+            "temperature": options.temperature.unwrap_or(self.temperature),
+            "max_tokens": options.max_tokens.unwrap_or(self.max_tokens),
+        });
+        if !options.stop.is_empty() {
+            body["stop"] = json!(options.stop);
+        }
+        openai_generation(&self.post("/chat/completions", body).await?)
+    }
+
+    async fn embed(&self, text: &str) -> LlmResult<Vec<f32>> {
+        let model = self
+            .embedding_model
+            .as_ref()
+            .ok_or_else(|| unsupported(self.name(), "embeddings without an embedding_model"))?;
+        let body = json!({"model": model, "input": text});
+        openai_embedding(&self.post("/embeddings", body).await?)
+    }
+}
+
+/// Anthropic's hosted models
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: String,
+    temperature: f32,
+    max_tokens: u32,
+}
+
+impl AnthropicProvider {
+    async fn post(&self, path: &str, body: Value) -> LlmResult<Value> {
+        let request = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body);
+        send(self.name(), request).await
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "Anthropic"
+    }
+
+    async fn generate(&self, prompt: &str) -> LlmResult<String> {
+        Ok(self
+            .generate_with(prompt, &GenerationOptions::default())
+            .await?
+            .text)
+    }
+
+    async fn generate_with(
+        &self,
+        prompt: &str,
+        options: &GenerationOptions,
+    ) -> LlmResult<Generation> {
+        let mut body = json!({
+            "model": self.model,
+            "messages": [{"role": "user", "content": prompt}],
+            "temperature": options.temperature.unwrap_or(self.temperature),
+            "max_tokens": options.max_tokens.unwrap_or(self.max_tokens),
+        });
+        if let Some(system) = &options.system {
+            body["system"] = json!(system);
+        }
+        if !options.stop.is_empty() {
+            body["stop_sequences"] = json!(options.stop);
+        }
+        let response = self.post("/v1/messages", body).await?;
+        let stop = match response["stop_reason"].as_str() {
+            Some("max_tokens") => GenerationStop::MaxTokens,
+            Some("stop_sequence") => GenerationStop::StopSequence,
+            _ => GenerationStop::EndTurn,
+        };
+        Ok(Generation {
+            text: anthropic_text(&response)?,
+            stop,
+        })
+    }
+
+    async fn count_tokens(&self, text: &str) -> LlmResult<usize> {
+        let body = json!({
+            "model": self.model,
+            "messages": [{"role": "user", "content": text}],
+        });
+        let response = self.post("/v1/messages/count_tokens", body).await?;
+        response["input_tokens"]
+            .as_u64()
+            .map(|count| count as usize)
+            .ok_or_else(|| malformed("Anthropic", &response))
+    }
+}
+
+/// Send a request and return its JSON body, failing on error statuses
+async fn send(provider: &str, request: reqwest::RequestBuilder) -> LlmResult<Value> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| LlmError::ProviderError(format!("{} request failed: {}", provider, e)))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| LlmError::ProviderError(format!("{} response unreadable: {}", provider, e)))?;
+    if !status.is_success() {
+        return Err(LlmError::ProviderError(format!(
+            "{} returned {}: {}",
+            provider, status, body
+        )));
+    }
+    Ok(serde_json::from_str(&body)?)
+}
+
+fn malformed(provider: &str, response: &Value) -> LlmError {
+    LlmError::ProviderError(format!("Unexpected {} response: {}", provider, response))
+}
+
+/// Text of the first choice of a chat completion
+fn openai_text(response: &Value) -> LlmResult<String> {
+    response
+        .pointer("/choices/0/message/content")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| malformed("OpenAI-compatible", response))
+}
+
+/// First choice of a chat completion and why it ended
+fn openai_generation(response: &Value) -> LlmResult<Generation> {
+    let stop = match response
+        .pointer("/choices/0/finish_reason")
+        .and_then(Value::as_str)
+    {
+        Some("length") => GenerationStop::MaxTokens,
+        // OpenAI reports a stop sequence as a plain stop
+        _ => GenerationStop::EndTurn,
+    };
+    Ok(Generation {
+        text: openai_text(response)?,
+        stop,
+    })
+}
+
+fn openai_embedding(response: &Value) -> LlmResult<Vec<f32>> {
+    response
+        .pointer("/data/0/embedding")
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_f64)
+                .map(|v| v as f32)
+                .collect()
+        })
+        .ok_or_else(|| malformed("OpenAI-compatible", response))
+}
+
+/// Text blocks of a message, joined
+fn anthropic_text(response: &Value) -> LlmResult<String> {
+    let blocks = response["content"]
+        .as_array()
+        .ok_or_else(|| malformed("Anthropic", response))?;
+    Ok(blocks
+        .iter()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_selects_provider_by_tag() {
+        let config: ProviderConfig = serde_json::from_value(json!({
+            "provider": "open_ai",
+            "base_url": "http://localhost:11434/v1",
+            "model": "llama3.2",
+        }))
+        .unwrap();
+        assert!(matches!(
+            config,
+            ProviderConfig::OpenAi { ref model, api_key: None, embedding_model: None, .. } if model == "llama3.2"
+        ));
+
+        let config: ProviderConfig =
+            serde_json::from_value(json!({"provider": "anthropic", "model": "claude-haiku"}))
+                .unwrap();
+        assert!(matches!(
+            config,
+            ProviderConfig::Anthropic { ref base_url, .. } if base_url == "https://api.anthropic.com"
+        ));
+    }
+
+    #[test]
+    fn test_responses_are_read_per_api() {
+        let completion =
+            json!({"choices": [{"message": {"role": "assistant", "content": "read_file"}}]});
+        assert_eq!(openai_text(&completion).unwrap(), "read_file");
+        let cut_off =
+            json!({"choices": [{"message": {"content": "rea"}, "finish_reason": "length"}]});
+        assert_eq!(
+            openai_generation(&cut_off).unwrap().stop,
+            GenerationStop::MaxTokens
+        );
+        let embedding = json!({"data": [{"embedding": [0.5, -1.0]}]});
+        assert_eq!(openai_embedding(&embedding).unwrap(), vec![0.5, -1.0]);
+
+        let message = json!({"content": [
+            {"type": "text", "text": "read"},
+            {"type": "tool_use", "name": "x"},
+            {"type": "text", "text": "_file"}
+        ]});
+        assert_eq!(anthropic_text(&message).unwrap(), "read_file");
+        assert!(matches!(
+            anthropic_text(&json!({"error": {}})),
+            Err(LlmError::ProviderError(_))
+        ));
+    }
+}
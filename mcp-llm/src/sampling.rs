@@ -0,0 +1,314 @@
+//! MCP sampling answered by the configured models
+//!
+//! Servers ask their client for completions with `sampling/createMessage`.
+//! [`LlmSamplingHandler`] answers those requests with the models mcp-llm runs
+//! against, so servers behind the proxy get a completion even when the client
+//! cannot provide one.
+//!
+//! A request's [`ModelPreferences`] choose the model. Model name hints are
+//! tried first, in order, against the configured model names. Without a
+//! match, each model's [`ModelProfile`] is scored against the cost, speed and
+//! intelligence priorities and the best one is used. `maxTokens`, the
+//! temperature, the system prompt and stop sequences are passed on to the
+//! model.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mcp_core::client::SamplingHandler;
+use mcp_core::error::{McpError, McpResult};
+use mcp_core::messages::{
+    CompleteResponse, CompletionArgument, CompletionResult, CostPriority, IntelligencePriority,
+    MessageRole, ModelPreferences, SamplingContent, SamplingMessage, SpeedPriority, StopReason,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::provider::{Generation, GenerationOptions, GenerationStop, LlmProvider};
+
+/// How a model compares to the others, each in 0.0..=1.0
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelProfile {
+    /// 1.0 is the most expensive
+    pub cost: f32,
+    /// 1.0 is the fastest
+    pub speed: f32,
+    /// 1.0 is the most capable
+    pub intelligence: f32,
+}
+
+impl Default for ModelProfile {
+    fn default() -> Self {
+        Self {
+            cost: 0.5,
+            speed: 0.5,
+            intelligence: 0.5,
+        }
+    }
+}
+
+impl ModelProfile {
+    /// How well this model fits `preferences`; higher is better
+    fn score(&self, preferences: &ModelPreferences) -> f32 {
+        let cheapness = match preferences.cost_priority {
+            Some(CostPriority::Low) => 1.0,
+            Some(CostPriority::Medium) => 0.5,
+            Some(CostPriority::High) | None => 0.0,
+        };
+        let speed = match preferences.speed_priority {
+            Some(SpeedPriority::High) => 1.0,
+            Some(SpeedPriority::Medium) => 0.5,
+            Some(SpeedPriority::Low) | None => 0.0,
+        };
+        let intelligence = match preferences.intelligence_priority {
+            Some(IntelligencePriority::High) => 1.0,
+            Some(IntelligencePriority::Medium) => 0.5,
+            Some(IntelligencePriority::Low) | None => 0.0,
+        };
+        cheapness * (1.0 - self.cost) + speed * self.speed + intelligence * self.intelligence
+    }
+}
+
+/// A model sampling requests can be sent to
+pub struct SamplingModel {
+    pub name: String,
+    pub provider: Arc<dyn LlmProvider>,
+    pub profile: ModelProfile,
+}
+
+/// Answers `sampling/createMessage` with the best fitting model
+pub struct LlmSamplingHandler {
+    models: Vec<SamplingModel>,
+}
+
+impl LlmSamplingHandler {
+    /// Answer every request with one model
+    pub fn new(name: impl Into<String>, provider: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            models: vec![SamplingModel {
+                name: name.into(),
+                provider,
+                profile: ModelProfile::default(),
+            }],
+        }
+    }
+
+    /// Set the profile of the model most recently added
+    pub fn with_profile(mut self, profile: ModelProfile) -> Self {
+        if let Some(model) = self.models.last_mut() {
+            model.profile = profile;
+        }
+        self
+    }
+
+    /// Offer another model to choose from
+    pub fn with_model(
+        mut self,
+        name: impl Into<String>,
+        provider: Arc<dyn LlmProvider>,
+        profile: ModelProfile,
+    ) -> Self {
+        self.models.push(SamplingModel {
+            name: name.into(),
+            provider,
+            profile,
+        });
+        self
+    }
+
+    /// The model to answer a request with these preferences
+    pub fn select(&self, preferences: Option<&ModelPreferences>) -> &SamplingModel {
+        let first = &self.models[0];
+        let Some(preferences) = preferences else {
+            return first;
+        };
+
+        for hint in preferences.models.iter().flatten() {
+            let hint = hint.to_lowercase();
+            if let Some(model) = self
+                .models
+                .iter()
+                .find(|model| model.name.to_lowercase().contains(&hint))
+            {
+                return model;
+            }
+        }
+
+        // Ties go to the model added first
+        let mut best = first;
+        let mut best_score = first.profile.score(preferences);
+        for model in &self.models[1..] {
+            let score = model.profile.score(preferences);
+            if score > best_score {
+                best = model;
+                best_score = score;
+            }
+        }
+        best
+    }
+}
+
+#[async_trait]
+impl SamplingHandler for LlmSamplingHandler {
+    async fn create_message(&self, request: CompletionArgument) -> McpResult<CompleteResponse> {
+        let model = self.select(request.model_preferences.as_ref());
+        let options = GenerationOptions {
+            system: request.system_prompt.clone(),
+            temperature: request.temperature.map(|t| t as f32),
+            max_tokens: request
+                .max_tokens
+                .and_then(|n| u32::try_from(n).ok())
+                .filter(|n| *n > 0),
+            stop: request.stop_sequences.clone().unwrap_or_default(),
+        };
+
+        let generation = model
+            .provider
+            .generate_with(&conversation(&request.messages), &options)
+            .await
+            .map_err(|e| McpError::Internal {
+                message: format!("{} could not complete: {}", model.name, e),
+            })?;
+        // Not every backend honours stop sequences itself
+        let cut = Generation::truncated(generation.text, &options.stop);
+        let stop = match (cut.stop, generation.stop) {
+            (GenerationStop::StopSequence, _) | (_, GenerationStop::StopSequence) => {
+                StopReason::StopSequence
+            }
+            (_, GenerationStop::MaxTokens) => StopReason::MaxTokens,
+            _ => StopReason::EndTurn,
+        };
+
+        Ok(CompleteResponse {
+            completion: CompletionResult::text(cut.text),
+            model: Some(model.name.clone()),
+            stop_reason: Some(stop),
+        })
+    }
+}
+
+/// The messages as a transcript for a single prompt
+fn conversation(messages: &[SamplingMessage]) -> String {
+    // A lone user message needs no transcript around it
+    if let [message] = messages {
+        if let (MessageRole::User, SamplingContent::Text { text }) =
+            (&message.role, &message.content)
+        {
+            return text.clone();
+        }
+    }
+
+    let mut transcript: Vec<String> = messages
+        .iter()
+        .map(|message| {
+            let role = match message.role {
+                MessageRole::System => "System",
+                MessageRole::User => "User",
+                MessageRole::Assistant => "Assistant",
+            };
+            let content = match &message.content {
+                SamplingContent::Text { text } => text.clone(),
+                SamplingContent::Image { mime_type, .. } => format!("[{} image]", mime_type),
+            };
+            format!("{}: {}", role, content)
+        })
+        .collect();
+    transcript.push("Assistant:".to_string());
+    transcript.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::LlmResult;
+    use std::sync::Mutex;
+
+    /// Replies with a fixed text, remembering the options it was given
+    struct Fixed {
+        reply: &'static str,
+        options: Mutex<Option<GenerationOptions>>,
+    }
+
+    impl Fixed {
+        fn new(reply: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                reply,
+                options: Mutex::new(None),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        async fn generate(&self, _prompt: &str) -> LlmResult<String> {
+            Ok(self.reply.to_string())
+        }
+
+        async fn generate_with(
+            &self,
+            _prompt: &str,
+            options: &GenerationOptions,
+        ) -> LlmResult<Generation> {
+            *self.options.lock().unwrap() = Some(options.clone());
+            Ok(Generation {
+                text: self.reply.to_string(),
+                stop: GenerationStop::EndTurn,
+            })
+        }
+    }
+
+    fn handler() -> LlmSamplingHandler {
+        let profile = |cost, speed, intelligence| ModelProfile {
+            cost,
+            speed,
+            intelligence,
+        };
+        LlmSamplingHandler::new("local-gemma", Fixed::new("local"))
+            .with_profile(profile(0.0, 0.4, 0.3))
+            .with_model("gpt-4o-mini", Fixed::new("mini"), profile(0.3, 0.9, 0.6))
+            .with_model("claude-opus", Fixed::new("opus"), profile(1.0, 0.2, 1.0))
+    }
+
+    #[test]
+    fn test_preferences_pick_a_model() {
+        let handler = handler();
+        assert_eq!(handler.select(None).name, "local-gemma");
+
+        let hinted = ModelPreferences::new().with_models(vec!["sonnet".into(), "Opus".into()]);
+        assert_eq!(handler.select(Some(&hinted)).name, "claude-opus");
+
+        let cheap = ModelPreferences::new().with_cost_priority(CostPriority::Low);
+        assert_eq!(handler.select(Some(&cheap)).name, "local-gemma");
+        let fast = ModelPreferences::new().with_speed_priority(SpeedPriority::High);
+        assert_eq!(handler.select(Some(&fast)).name, "gpt-4o-mini");
+        let smart = ModelPreferences::new()
+            .with_intelligence_priority(IntelligencePriority::High)
+            .with_cost_priority(CostPriority::High);
+        assert_eq!(handler.select(Some(&smart)).name, "claude-opus");
+    }
+
+    #[tokio::test]
+    async fn test_request_limits_reach_the_model() {
+        let provider = Fixed::new("The answer is 4.\nUser: and 3+3?");
+        let handler = LlmSamplingHandler::new("local", provider.clone());
+        let request = CompletionArgument::new(vec![SamplingMessage::user("2+2?")])
+            .with_system_prompt("Be brief")
+            .with_max_tokens(32)
+            .with_stop_sequences(vec!["\nUser:".to_string()]);
+
+        let response = handler.create_message(request).await.unwrap();
+        assert_eq!(
+            response.completion,
+            CompletionResult::text("The answer is 4.")
+        );
+        assert_eq!(response.stop_reason, Some(StopReason::StopSequence));
+        assert_eq!(response.model.as_deref(), Some("local"));
+
+        let options = provider.options.lock().unwrap().clone().unwrap();
+        assert_eq!(options.max_tokens, Some(32));
+        assert_eq!(options.system.as_deref(), Some("Be brief"));
+    }
+}
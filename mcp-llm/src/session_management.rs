@@ -4,19 +4,22 @@
 //! - Conversation context building from message history
 //! - Per-session prediction tracking
 //! - Session-level optimization via GEPA
+//! - Snapshots in SQLite, so routing context survives a proxy or TUI restart
 
 use std::sync::Arc;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 use chrono::{DateTime, Utc};
-use mcp_common::types::{SessionId, MessageId, MessageFlow, ProxySession};
+use serde::{Deserialize, Serialize};
+use mcp_common::types::{SessionId, MessageId, MessageFlow, MessageStatus, ProxySession};
 use crate::predictors::{ToolPredictor, AdvancedToolPredictor};
 use crate::signatures::ToolPrediction;
 use crate::gepa_optimizer::GEPAOptimizer;
+use crate::database::SessionsDatabase;
 use crate::error::{LlmError, LlmResult};
 
 /// Session-level tracking for LLM predictions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionPredictionContext {
     pub session_id: SessionId,
     pub message_history: Vec<MessageFlow>,
@@ -121,10 +124,109 @@ impl SessionPredictionContext {
 
         context
     }
+
+    /// One-line account of the conversation: how many messages, the
+    /// methods called most and the last tool used
+    pub fn summarize_conversation(&self) -> String {
+        let mut methods: Vec<(&str, usize)> = Vec::new();
+        for message in &self.message_history {
+            let method = message.client_request.method.as_str();
+            match methods.iter_mut().find(|(name, _)| *name == method) {
+                Some((_, count)) => *count += 1,
+                None => methods.push((method, 1)),
+            }
+        }
+        methods.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let failed = self.message_history
+            .iter()
+            .filter(|message| matches!(message.status, MessageStatus::Failed(_)))
+            .count();
+        let mut summary = format!("{} messages", self.message_history.len());
+        if !methods.is_empty() {
+            let top: Vec<String> = methods
+                .iter()
+                .take(3)
+                .map(|(method, count)| format!("{} x{}", method, count))
+                .collect();
+            summary.push_str(&format!(" ({})", top.join(", ")));
+        }
+        if failed > 0 {
+            summary.push_str(&format!(", {} failed", failed));
+        }
+        let last_tool = self.message_history
+            .iter()
+            .rev()
+            .filter(|message| message.client_request.method == "tools/call")
+            .find_map(|message| message.client_request.params.as_ref()?.get("name")?.as_str());
+        if let Some(tool) = last_tool {
+            summary.push_str(&format!("; last tool {}", tool));
+        }
+        summary
+    }
+}
+
+/// A session's context as saved to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub context: SessionPredictionContext,
+    pub summary: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl SessionSnapshot {
+    /// Snapshot `context`, keeping only the history `retention` allows
+    pub fn new(context: &SessionPredictionContext, retention: &SessionRetention) -> Self {
+        let mut context = context.clone();
+        let excess = context.message_history.len().saturating_sub(retention.max_messages);
+        context.message_history.drain(..excess);
+        let excess = context.predictions.len().saturating_sub(retention.max_messages);
+        context.predictions.drain(..excess);
+        Self {
+            summary: context.summarize_conversation(),
+            context,
+            saved_at: Utc::now(),
+        }
+    }
+}
+
+/// How long idle sessions are kept and how much of each is saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRetention {
+    /// Sessions idle longer than this are expired
+    #[serde(default = "default_max_age_hours")]
+    pub max_age_hours: i64,
+    /// Messages and predictions kept per snapshot, newest first
+    #[serde(default = "default_max_messages")]
+    pub max_messages: usize,
+}
+
+impl Default for SessionRetention {
+    fn default() -> Self {
+        Self {
+            max_age_hours: default_max_age_hours(),
+            max_messages: default_max_messages(),
+        }
+    }
+}
+
+impl SessionRetention {
+    /// Sessions last updated at or before this are expired
+    pub fn cutoff(&self) -> DateTime<Utc> {
+        Utc::now() - chrono::Duration::hours(self.max_age_hours)
+    }
+}
+
+fn default_max_age_hours() -> i64 {
+    24
+}
+
+fn default_max_messages() -> usize {
+    100
 }
 
 /// A prediction made for a specific message in a session
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionPrediction {
     pub message_id: MessageId,
     pub prediction: ToolPrediction,
@@ -165,6 +267,8 @@ pub struct SessionManager {
     sessions: Arc<RwLock<HashMap<SessionId, SessionPredictionContext>>>,
     predictor: Arc<AdvancedToolPredictor>,
     gepa_optimizer: Arc<GEPAOptimizer>,
+    store: Option<SessionsDatabase>,
+    retention: SessionRetention,
 }
 
 impl SessionManager {
@@ -176,9 +280,23 @@ impl SessionManager {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             predictor,
             gepa_optimizer,
+            store: None,
+            retention: SessionRetention::default(),
         }
     }
 
+    /// Save snapshots to SQLite so sessions can be restored after a restart
+    pub fn with_store(mut self, store: SessionsDatabase) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Set how long idle sessions are kept and how much of each is saved
+    pub fn with_retention(mut self, retention: SessionRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
     /// Get or create a session context
     pub async fn get_or_create_session(
         &self,
@@ -283,6 +401,81 @@ impl SessionManager {
         sessions.keys().cloned().collect()
     }
 
+    /// Save one session, returning the snapshot taken
+    pub async fn snapshot_session(&self, session_id: &SessionId) -> LlmResult<Option<SessionSnapshot>> {
+        let snapshot = {
+            let sessions = self.sessions.read().await;
+            sessions.get(session_id).map(|context| SessionSnapshot::new(context, &self.retention))
+        };
+        if let (Some(store), Some(snapshot)) = (&self.store, &snapshot) {
+            store.save_snapshot(snapshot).await?;
+        }
+        Ok(snapshot)
+    }
+
+    /// Save every session, returning how many were saved
+    pub async fn snapshot_all(&self) -> LlmResult<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let snapshots: Vec<SessionSnapshot> = {
+            let sessions = self.sessions.read().await;
+            sessions
+                .values()
+                .map(|context| SessionSnapshot::new(context, &self.retention))
+                .collect()
+        };
+        for snapshot in &snapshots {
+            store.save_snapshot(snapshot).await?;
+        }
+        Ok(snapshots.len())
+    }
+
+    /// Load saved sessions that have not expired; sessions already in memory
+    /// are newer and are kept. Returns how many were restored
+    pub async fn restore_sessions(&self) -> LlmResult<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let snapshots = store.load_snapshots(self.retention.cutoff()).await?;
+        let mut sessions = self.sessions.write().await;
+        let mut restored = 0;
+        for snapshot in snapshots {
+            let session_id = snapshot.context.session_id.clone();
+            if !sessions.contains_key(&session_id) {
+                sessions.insert(session_id, snapshot.context);
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Put a snapshot back in memory, replacing the live session
+    pub async fn restore_snapshot(&self, snapshot: SessionSnapshot) {
+        let mut sessions = self.sessions.write().await;
+        sessions.insert(snapshot.context.session_id.clone(), snapshot.context);
+    }
+
+    /// Drop one session from memory and the store
+    pub async fn forget_session(&self, session_id: &SessionId) -> LlmResult<()> {
+        self.sessions.write().await.remove(session_id);
+        if let Some(store) = &self.store {
+            store.delete_snapshot(&session_id.0.to_string()).await?;
+        }
+        Ok(())
+    }
+
+    /// Drop sessions idle past the retention period from memory and the
+    /// store, returning how many stored snapshots were removed
+    pub async fn expire_sessions(&self) -> LlmResult<u64> {
+        let cutoff = self.retention.cutoff();
+        self.sessions.write().await.retain(|_id, context| context.last_updated > cutoff);
+        match &self.store {
+            Some(store) => store.expire_snapshots(cutoff).await,
+            None => Ok(0),
+        }
+    }
+
     /// Clear old inactive sessions
     pub async fn cleanup_inactive_sessions(&self, max_age_hours: i64) {
         let mut sessions = self.sessions.write().await;
@@ -302,4 +495,34 @@ pub struct SessionStats {
     pub optimization_score: f32,
     pub message_count: u64,
     pub last_updated: DateTime<Utc>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(method: &str, tool: Option<&str>, status: MessageStatus) -> MessageFlow {
+        let mut message = MessageFlow::default();
+        message.client_request.method = method.to_string();
+        message.client_request.params = tool.map(|name| json!({"name": name}));
+        message.status = status;
+        message
+    }
+
+    #[test]
+    fn test_snapshot_keeps_recent_history_and_summarizes_it() {
+        let mut context = SessionPredictionContext::new(SessionId::new());
+        context.add_message(message("tools/list", None, MessageStatus::Completed));
+        context.add_message(message("tools/call", Some("read_file"), MessageStatus::Completed));
+        context.add_message(message("tools/call", Some("search"), MessageStatus::Failed("timeout".to_string())));
+
+        let retention = SessionRetention { max_messages: 2, ..SessionRetention::default() };
+        let snapshot = SessionSnapshot::new(&context, &retention);
+        assert_eq!(snapshot.context.message_history.len(), 2);
+        assert_eq!(snapshot.summary, "2 messages (tools/call x2), 1 failed; last tool search");
+
+        let restored: SessionSnapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        assert_eq!(restored.context.session_id, context.session_id);
+        assert_eq!(restored.summary, snapshot.summary);
+    }
+}
@@ -24,6 +24,9 @@ pub struct ToolPrediction {
     pub confidence: f32,
     pub reasoning: String,
     pub parameters: serde_json::Value,
+    /// Other likely tools, most likely first
+    #[serde(default)]
+    pub alternatives: Vec<String>,
 }
 
 /// Signature for optimizing prompts based on execution traces
@@ -128,6 +131,7 @@ impl ToolPredictionSignature {
                 confidence: 0.0,
                 reasoning: String::new(),
                 parameters: serde_json::Value::Null,
+                alternatives: Vec::new(),
             },
         }
     }
@@ -140,7 +144,8 @@ impl ToolPredictionSignature {
                 "tool_name": {"type": "string"},
                 "confidence": {"type": "number", "minimum": 0.0, "maximum": 1.0},
                 "reasoning": {"type": "string"},
-                "parameters": {"type": "object"}
+                "parameters": {"type": "object"},
+                "alternatives": {"type": "array", "items": {"type": "string"}}
             },
             "required": ["tool_name", "confidence", "reasoning", "parameters"]
         })
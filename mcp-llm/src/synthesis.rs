@@ -0,0 +1,383 @@
+//! Argument synthesis for predicted tool calls
+//!
+//! Once a tool is chosen, [`ArgumentSynthesizer`] asks the model for an
+//! argument object that fits the tool's input schema. Every answer is
+//! constrained to the schema before it is checked: keys the schema does not
+//! allow are dropped and missing fields with a `default` are filled in. It
+//! is then validated with [`ParameterValidator`]. An answer that still fails
+//! is sent back with the errors until one passes or the attempts run out, so
+//! the caller always gets the best candidate along with a [`ValidationReport`]
+//! saying whether it can be sent as is.
+//!
+//! The synthesizer is also an [`ArgumentRepairer`], so the proxy's argument
+//! repair interceptor can have it fix calls the validator alone cannot.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mcp_core::error::{McpError, McpResult};
+use mcp_core::validation::{ArgumentRepairer, ParameterValidator};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::LlmResult;
+use crate::litert_wrapper::Tool;
+use crate::provider::LlmProvider;
+
+/// Model answers tried before settling for an invalid candidate
+const DEFAULT_ATTEMPTS: usize = 3;
+
+/// How a synthesized argument object fared against the schema
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    /// Model answers it took; 0 when the predicted parameters already fit
+    pub attempts: usize,
+    /// Why the arguments do not fit, empty when they do
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Changes made to fit the schema, such as coerced types or filled defaults
+    pub transformations: Vec<String>,
+}
+
+/// Arguments for a tool call and how they were checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SynthesizedArguments {
+    pub arguments: Value,
+    pub report: ValidationReport,
+}
+
+/// Asks a model for schema-conforming tool arguments
+pub struct ArgumentSynthesizer {
+    provider: Arc<dyn LlmProvider>,
+    validator: ParameterValidator,
+    max_attempts: usize,
+}
+
+impl ArgumentSynthesizer {
+    pub fn new(provider: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            provider,
+            validator: ParameterValidator::new(),
+            max_attempts: DEFAULT_ATTEMPTS,
+        }
+    }
+
+    /// Model answers to try before giving up on a valid object
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Arguments for calling `tool` in `context`, starting from `seed`
+    /// (e.g. the parameters a prediction came with) when it is an object
+    pub async fn synthesize(
+        &self,
+        context: &str,
+        tool: &Tool,
+        seed: &Value,
+    ) -> LlmResult<SynthesizedArguments> {
+        let schema = &tool.parameters;
+        if seed.is_object() {
+            let (arguments, report) = self.check(schema, seed.clone());
+            if report.valid {
+                return Ok(SynthesizedArguments { arguments, report });
+            }
+        }
+
+        let mut prompt = synthesis_prompt(context, tool, seed);
+        let mut best = None;
+        for attempt in 1..=self.max_attempts {
+            let answer = self.provider.generate(&prompt).await?;
+            let (arguments, mut report) = match extract_object(&answer) {
+                Some(candidate) => self.check(schema, candidate),
+                None => (
+                    Value::Object(Map::new()),
+                    ValidationReport {
+                        errors: vec!["Answer was not a JSON object".to_string()],
+                        ..ValidationReport::default()
+                    },
+                ),
+            };
+            report.attempts = attempt;
+            if report.valid {
+                return Ok(SynthesizedArguments { arguments, report });
+            }
+            prompt = retry_prompt(&prompt, &answer, &report.errors);
+            best = Some(SynthesizedArguments { arguments, report });
+        }
+        // max_attempts is at least 1
+        Ok(best.expect("at least one attempt"))
+    }
+
+    /// Constrain `candidate` to `schema` and validate it
+    fn check(&self, schema: &Value, mut candidate: Value) -> (Value, ValidationReport) {
+        let mut transformations = constrain(schema, &mut candidate);
+        let result = self.validator.validate(schema, &candidate);
+        let mut errors: Vec<String> = result.errors.iter().map(ToString::to_string).collect();
+        errors.extend(enum_violations(schema, &result.validated_params));
+        transformations.extend(result.transformations);
+        let report = ValidationReport {
+            valid: result.is_valid && errors.is_empty(),
+            attempts: 0,
+            errors,
+            warnings: result.warnings,
+            transformations,
+        };
+        (result.validated_params, report)
+    }
+}
+
+#[async_trait]
+impl ArgumentRepairer for ArgumentSynthesizer {
+    async fn repair(
+        &self,
+        tool: &str,
+        schema: &Value,
+        arguments: &Value,
+        errors: &[String],
+    ) -> McpResult<Value> {
+        let tool = Tool {
+            name: tool.to_string(),
+            description: schema
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            parameters: schema.clone(),
+        };
+        let context = format!(
+            "A client called `{}` with arguments that do not match its schema:\n- {}",
+            tool.name,
+            errors.join("\n- ")
+        );
+        let result = self
+            .synthesize(&context, &tool, arguments)
+            .await
+            .map_err(|e| McpError::Internal {
+                message: format!("Argument repair failed: {}", e),
+            })?;
+        if !result.report.valid {
+            return Err(McpError::Internal {
+                message: format!(
+                    "Could not repair arguments for {}: {}",
+                    tool.name,
+                    result.report.errors.join("; ")
+                ),
+            });
+        }
+        Ok(result.arguments)
+    }
+}
+
+fn synthesis_prompt(context: &str, tool: &Tool, seed: &Value) -> String {
+    let mut prompt = format!(
+        "{}\n\nCall the tool `{}`: {}\nIts arguments must match this JSON schema:\n{}\n",
+        context,
+        tool.name,
+        tool.description,
+        serde_json::to_string_pretty(&tool.parameters).unwrap_or_default()
+    );
+    if seed.is_object() {
+        prompt.push_str(&format!(
+            "A first guess at the arguments, which may be wrong: {}\n",
+            seed
+        ));
+    }
+    prompt.push_str("Respond with the arguments as a single JSON object and nothing else.");
+    prompt
+}
+
+fn retry_prompt(prompt: &str, answer: &str, errors: &[String]) -> String {
+    format!(
+        "{}\n\nYour answer was:\n{}\nIt does not match the schema:\n- {}\nRespond with a corrected JSON object only.",
+        prompt,
+        answer.trim(),
+        errors.join("\n- ")
+    )
+}
+
+/// The outermost JSON object in a model answer, which may be wrapped in prose
+/// or a code fence
+fn extract_object(answer: &str) -> Option<Value> {
+    let start = answer.find('{')?;
+    let end = answer.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    serde_json::from_str::<Value>(&answer[start..=end])
+        .ok()
+        .filter(Value::is_object)
+}
+
+/// Drop keys the schema forbids and fill in defaults, returning what changed
+fn constrain(schema: &Value, candidate: &mut Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    let (Some(properties), Some(arguments)) = (
+        schema.get("properties").and_then(Value::as_object),
+        candidate.as_object_mut(),
+    ) else {
+        return changes;
+    };
+    if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+        arguments.retain(|key, _| {
+            let known = properties.contains_key(key);
+            if !known {
+                changes.push(format!(
+                    "Dropped '{}', which the schema does not allow",
+                    key
+                ));
+            }
+            known
+        });
+    }
+    for (key, property) in properties {
+        if let (false, Some(default)) = (arguments.contains_key(key), property.get("default")) {
+            arguments.insert(key.clone(), default.clone());
+            changes.push(format!("Filled '{}' with its default {}", key, default));
+        }
+    }
+    changes
+}
+
+/// Values outside the `enum` their property allows
+fn enum_violations(schema: &Value, arguments: &Value) -> Vec<String> {
+    let (Some(properties), Some(arguments)) = (
+        schema.get("properties").and_then(Value::as_object),
+        arguments.as_object(),
+    ) else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .filter_map(|(key, property)| {
+            let allowed = property.get("enum")?.as_array()?;
+            let value = arguments.get(key)?;
+            (!allowed.contains(value)).then(|| {
+                format!(
+                    "Parameter '{}' must be one of {}",
+                    key,
+                    Value::Array(allowed.clone())
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Answers prompts from a script, remembering what it was asked
+    struct Scripted {
+        answers: Mutex<Vec<&'static str>>,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl LlmProvider for Scripted {
+        fn name(&self) -> &'static str {
+            "scripted"
+        }
+
+        async fn generate(&self, prompt: &str) -> LlmResult<String> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(self.answers.lock().unwrap().remove(0).to_string())
+        }
+    }
+
+    fn search_tool() -> Tool {
+        Tool {
+            name: "search".to_string(),
+            description: "Search the index".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string"},
+                    "limit": {"type": "integer", "default": 10},
+                    "order": {"type": "string", "enum": ["relevance", "date"]}
+                },
+                "required": ["query"],
+                "additionalProperties": false
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_answers_are_retried_with_their_errors() {
+        let provider = Arc::new(Scripted {
+            answers: Mutex::new(vec![
+                "Sure! {\"order\": \"newest\"}",
+                "```json\n{\"query\": \"rust\", \"order\": \"date\", \"verbose\": true}\n```",
+            ]),
+            prompts: Mutex::new(Vec::new()),
+        });
+        let synthesizer = ArgumentSynthesizer::new(provider.clone());
+
+        let result = synthesizer
+            .synthesize("Find recent posts about rust", &search_tool(), &Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.arguments,
+            json!({"query": "rust", "order": "date", "limit": 10})
+        );
+        assert!(result.report.valid);
+        assert_eq!(result.report.attempts, 2);
+        assert_eq!(result.report.transformations.len(), 2);
+
+        let prompts = provider.prompts.lock().unwrap();
+        assert!(prompts[1].contains("'query' is required"));
+        assert!(prompts[1].contains("must be one of [\"relevance\",\"date\"]"));
+    }
+
+    #[tokio::test]
+    async fn test_fitting_seed_needs_no_model_and_misfits_are_reported() {
+        let provider = Arc::new(Scripted {
+            answers: Mutex::new(vec!["no idea"]),
+            prompts: Mutex::new(Vec::new()),
+        });
+        let synthesizer = ArgumentSynthesizer::new(provider.clone()).max_attempts(1);
+
+        let seeded = synthesizer
+            .synthesize("", &search_tool(), &json!({"query": "rust", "limit": "5"}))
+            .await
+            .unwrap();
+        assert_eq!(seeded.arguments, json!({"query": "rust", "limit": 5}));
+        assert_eq!(seeded.report.attempts, 0);
+        assert!(provider.prompts.lock().unwrap().is_empty());
+
+        let failed = synthesizer
+            .synthesize("", &search_tool(), &json!({}))
+            .await
+            .unwrap();
+        assert!(!failed.report.valid);
+        assert_eq!(failed.report.attempts, 1);
+        assert_eq!(failed.report.errors, vec!["Answer was not a JSON object"]);
+    }
+
+    #[tokio::test]
+    async fn test_repair_fixes_rejected_arguments() {
+        let provider = Arc::new(Scripted {
+            answers: Mutex::new(vec!["{\"query\": \"rust\", \"limit\": 5}", "{}"]),
+            prompts: Mutex::new(Vec::new()),
+        });
+        let synthesizer = ArgumentSynthesizer::new(provider.clone()).max_attempts(1);
+        let schema = search_tool().parameters;
+        let errors = vec!["Parameter 'query' is required".to_string()];
+
+        let repaired = synthesizer
+            .repair("search", &schema, &json!({"limit": 5}), &errors)
+            .await
+            .unwrap();
+        assert_eq!(repaired, json!({"query": "rust", "limit": 5}));
+        assert!(provider.prompts.lock().unwrap()[0].contains("'query' is required"));
+
+        assert!(synthesizer
+            .repair("search", &schema, &json!({}), &errors)
+            .await
+            .is_err());
+    }
+}
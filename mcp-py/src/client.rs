@@ -0,0 +1,458 @@
+//! The client and the typed answers it returns.
+
+use std::sync::Arc;
+
+use mcp_core::client::{McpClient, McpClientBuilder, ServerInfo};
+use mcp_core::messages::resources::{
+    ListResourcesRequest, ListResourcesResponse, ReadResourceRequest, ReadResourceResponse,
+};
+use mcp_core::messages::tools::{
+    CallToolRequest, CallToolResponse, ListToolsRequest, ListToolsResponse,
+};
+use mcp_core::messages::{self, Implementation};
+use mcp_core::outcome::{OutcomeHeuristics, ToolOutcome};
+use mcp_core::tool_args::ToolArgs;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use pyo3_async_runtimes::tokio::future_into_py;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::convert::Json;
+use crate::interceptor::Interceptor;
+use crate::transport::Transport;
+use crate::{seconds, to_py_err};
+
+/// A connected MCP client.
+///
+/// Create one with `await Client.connect(transport)`. Requests on one client
+/// are sent one at a time; open several clients for parallel calls. Use it
+/// as an async context manager, or call `close()`, to shut the server down.
+#[pyclass(module = "genmcp", frozen)]
+pub struct Client {
+    inner: Arc<Mutex<McpClient>>,
+    server: Value,
+}
+
+#[pymethods]
+impl Client {
+    /// Connect to the server and run the initialization handshake.
+    ///
+    /// `request_timeout` is in seconds; `max_retries` retries failed
+    /// requests that are safe to repeat; `interceptors` run in priority
+    /// order on every message.
+    #[staticmethod]
+    #[pyo3(signature = (
+        transport,
+        *,
+        name = "genmcp-python".to_string(),
+        version = env!("CARGO_PKG_VERSION").to_string(),
+        request_timeout = None,
+        max_retries = None,
+        interceptors = Vec::new(),
+    ))]
+    fn connect<'py>(
+        py: Python<'py>,
+        transport: Transport,
+        name: String,
+        version: String,
+        request_timeout: Option<f64>,
+        max_retries: Option<u32>,
+        interceptors: Vec<Interceptor>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let mut builder = McpClientBuilder::new().transport(transport.config);
+        if let Some(timeout) = request_timeout {
+            builder = builder.request_timeout(seconds(timeout)?);
+        }
+        if let Some(retries) = max_retries {
+            builder = builder.max_retries(retries);
+        }
+        future_into_py(py, async move {
+            let mut client = builder.build().await.map_err(to_py_err)?;
+            let manager = client.interceptor_manager();
+            for interceptor in interceptors {
+                manager.add_interceptor(interceptor.inner).await;
+            }
+            let info = client
+                .connect(Implementation::new(name, version))
+                .await
+                .map_err(to_py_err)?;
+            Ok(Client {
+                inner: Arc::new(Mutex::new(client)),
+                server: server_json(&info),
+            })
+        })
+    }
+
+    /// The server's name, version, protocol version and capabilities
+    #[getter]
+    fn server_info(&self) -> Json {
+        Json(self.server.clone())
+    }
+
+    /// Every tool the server offers, following pagination.
+    fn list_tools<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let mut client = inner.lock().await;
+            let mut tools = Vec::new();
+            let mut cursor = None;
+            loop {
+                let request = ListToolsRequest {
+                    cursor: cursor.clone(),
+                    meta: None,
+                };
+                let response = client
+                    .send_request("tools/list", request)
+                    .await
+                    .map_err(to_py_err)?;
+                let page: ListToolsResponse =
+                    client.parse_result(response).await.map_err(to_py_err)?;
+                tools.extend(page.tools.into_iter().map(|tool| Tool { tool }));
+                match page.next_cursor {
+                    Some(next) if cursor.as_ref() != Some(&next) => cursor = Some(next),
+                    _ => return Ok(tools),
+                }
+            }
+        })
+    }
+
+    /// Call a tool with `arguments` (a `dict`).
+    ///
+    /// Given a `Tool` from `list_tools`, the arguments are checked against
+    /// its input schema first and a `ValidationError` is raised instead of
+    /// sending a call the server would reject. `timeout` (seconds) overrides
+    /// the client's timeout for this call.
+    #[pyo3(signature = (tool, arguments = None, timeout = None))]
+    fn call_tool<'py>(
+        &self,
+        py: Python<'py>,
+        tool: &Bound<'py, PyAny>,
+        arguments: Option<Json>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let arguments = arguments.map(|arguments| arguments.0);
+        let request = match tool.downcast::<Tool>() {
+            Ok(tool) => ToolArgs::for_tool(&tool.get().tool)
+                .input(&arguments.unwrap_or_else(|| json!({})))
+                .into_request()
+                .map_err(to_py_err)?,
+            Err(_) => CallToolRequest {
+                name: tool.extract()?,
+                arguments,
+                meta: None,
+            },
+        };
+        let timeout = timeout.map(seconds).transpose()?;
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let mut client = inner.lock().await;
+            let response = match timeout {
+                Some(timeout) => {
+                    client
+                        .send_request_with_timeout("tools/call", request, timeout)
+                        .await
+                }
+                None => client.send_request("tools/call", request).await,
+            }
+            .map_err(to_py_err)?;
+            let result = response.into_result().map_err(to_py_err)?;
+            let response: CallToolResponse =
+                mcp_core::decode::from_value(result).map_err(to_py_err)?;
+            Ok(ToolResult { response })
+        })
+    }
+
+    /// Every resource the server offers, following pagination.
+    fn list_resources<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let mut client = inner.lock().await;
+            let mut resources = Vec::new();
+            let mut cursor = None;
+            loop {
+                let request = ListResourcesRequest {
+                    cursor: cursor.clone(),
+                    meta: None,
+                };
+                let response = client
+                    .send_request("resources/list", request)
+                    .await
+                    .map_err(to_py_err)?;
+                let page: ListResourcesResponse =
+                    client.parse_result(response).await.map_err(to_py_err)?;
+                resources.extend(
+                    page.resources
+                        .into_iter()
+                        .map(|resource| Resource { resource }),
+                );
+                match page.next_cursor {
+                    Some(next) if cursor.as_ref() != Some(&next) => cursor = Some(next),
+                    _ => return Ok(resources),
+                }
+            }
+        })
+    }
+
+    /// The contents of a resource, as a list of `dict`s with `uri` and
+    /// either `text` or base64 `blob`.
+    fn read_resource<'py>(&self, py: Python<'py>, uri: String) -> PyResult<Bound<'py, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let mut client = inner.lock().await;
+            let request = ReadResourceRequest { uri, meta: None };
+            let response = client
+                .send_request("resources/read", request)
+                .await
+                .map_err(to_py_err)?;
+            let result = response.into_result().map_err(to_py_err)?;
+            let read: ReadResourceResponse =
+                mcp_core::decode::from_value(result).map_err(to_py_err)?;
+            let contents = serde_json::to_value(read.contents).map_err(|e| to_py_err(e.into()))?;
+            Ok(Json(contents))
+        })
+    }
+
+    /// Send any request and return its `result`.
+    #[pyo3(signature = (method, params = None, timeout = None))]
+    fn request<'py>(
+        &self,
+        py: Python<'py>,
+        method: String,
+        params: Option<Json>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let params = params.map_or_else(|| json!({}), |params| params.0);
+        let timeout = timeout.map(seconds).transpose()?;
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let mut client = inner.lock().await;
+            let response = match timeout {
+                Some(timeout) => {
+                    client
+                        .send_request_with_timeout(&method, params, timeout)
+                        .await
+                }
+                None => client.send_request(&method, params).await,
+            }
+            .map_err(to_py_err)?;
+            Ok(Json(response.into_result().map_err(to_py_err)?))
+        })
+    }
+
+    /// Send a notification.
+    #[pyo3(signature = (method, params = None))]
+    fn notify<'py>(
+        &self,
+        py: Python<'py>,
+        method: String,
+        params: Option<Json>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let params = params.map_or_else(|| json!({}), |params| params.0);
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let mut client = inner.lock().await;
+            client
+                .send_notification(&method, params)
+                .await
+                .map_err(to_py_err)
+        })
+    }
+
+    /// Run another interceptor on every later message.
+    fn add_interceptor<'py>(
+        &self,
+        py: Python<'py>,
+        interceptor: Interceptor,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let manager = inner.lock().await.interceptor_manager();
+            manager.add_interceptor(interceptor.inner).await;
+            Ok(())
+        })
+    }
+
+    /// Disconnect, stopping a stdio server.
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            inner.lock().await.disconnect().await.map_err(to_py_err)
+        })
+    }
+
+    fn __aenter__<'py>(slf: Py<Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    #[pyo3(signature = (*_exc_info))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_info: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.close(py)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Client(server={:?})",
+            self.server["name"].as_str().unwrap_or("unknown")
+        )
+    }
+}
+
+fn server_json(info: &ServerInfo) -> Value {
+    json!({
+        "name": info.implementation.name,
+        "version": info.implementation.version,
+        "protocol_version": info.protocol_version.to_string(),
+        "capabilities": info.capabilities,
+    })
+}
+
+/// A tool the server offers.
+#[pyclass(module = "genmcp", frozen)]
+#[derive(Debug, Clone)]
+pub struct Tool {
+    pub(crate) tool: messages::Tool,
+}
+
+#[pymethods]
+impl Tool {
+    /// The tool's name
+    #[getter]
+    fn name(&self) -> &str {
+        &self.tool.name
+    }
+
+    /// What the tool does
+    #[getter]
+    fn description(&self) -> &str {
+        &self.tool.description
+    }
+
+    /// JSON Schema of the arguments, if the server gave one
+    #[getter]
+    fn input_schema(&self) -> Option<Json> {
+        self.tool.input_schema.clone().map(Json)
+    }
+
+    /// Whether the server says the tool changes nothing
+    #[getter]
+    fn read_only(&self) -> Option<bool> {
+        self.tool.read_only
+    }
+
+    /// The tool as the server sent it
+    fn to_dict(&self) -> PyResult<Json> {
+        serde_json::to_value(&self.tool)
+            .map(Json)
+            .map_err(|e| to_py_err(e.into()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Tool({:?})", self.tool.name)
+    }
+}
+
+/// What a tool call returned.
+#[pyclass(module = "genmcp", frozen)]
+#[derive(Debug, Clone)]
+pub struct ToolResult {
+    response: CallToolResponse,
+}
+
+#[pymethods]
+impl ToolResult {
+    /// The content blocks, as `dict`s with a `type` of `text`, `image` or
+    /// `resource`
+    #[getter]
+    fn content(&self) -> PyResult<Json> {
+        serde_json::to_value(&self.response.content)
+            .map(Json)
+            .map_err(|e| to_py_err(e.into()))
+    }
+
+    /// The text blocks, joined by newlines
+    #[getter]
+    fn text(&self) -> String {
+        self.response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                messages::tools::ToolResult::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Whether the server flagged the result as an error
+    #[getter]
+    fn is_error(&self) -> bool {
+        self.response.is_error.unwrap_or(false)
+    }
+
+    /// `"success"`, `"failure"` or `"partial"`, also counting error and
+    /// warning text in the content, as `assist-mcp call` does
+    #[getter]
+    fn outcome(&self) -> String {
+        ToolOutcome::from_response(&self.response, &OutcomeHeuristics::default())
+            .kind()
+            .to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ToolResult(outcome={:?}, blocks={})",
+            self.outcome(),
+            self.response.content.len()
+        )
+    }
+}
+
+/// A resource the server offers.
+#[pyclass(module = "genmcp", frozen)]
+#[derive(Debug, Clone)]
+pub struct Resource {
+    resource: messages::Resource,
+}
+
+#[pymethods]
+impl Resource {
+    /// The resource's URI, for `read_resource`
+    #[getter]
+    fn uri(&self) -> &str {
+        &self.resource.uri
+    }
+
+    /// Human-readable name
+    #[getter]
+    fn name(&self) -> &str {
+        &self.resource.name
+    }
+
+    /// What the resource holds
+    #[getter]
+    fn description(&self) -> Option<&str> {
+        self.resource.description.as_deref()
+    }
+
+    /// MIME type of the contents
+    #[getter]
+    fn mime_type(&self) -> Option<&str> {
+        self.resource.mime_type.as_deref()
+    }
+
+    /// The resource as the server sent it
+    fn to_dict(&self) -> PyResult<Json> {
+        serde_json::to_value(&self.resource)
+            .map(Json)
+            .map_err(|e| to_py_err(e.into()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Resource({:?})", self.resource.uri)
+    }
+}
@@ -0,0 +1,167 @@
+//! JSON values to and from Python objects.
+//!
+//! Objects become `dict`, arrays `list`, and numbers `int` or `float`. Going
+//! the other way, tuples are read as arrays as well, `bool` is checked
+//! before `int` (Python's `True` is an `int`), and anything else, such as a
+//! `dict` with non-string keys or a `float('nan')`, raises `TypeError` or
+//! `ValueError`.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use serde_json::{Map, Number, Value};
+
+/// A JSON value passed to or returned from Python
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Json(pub Value);
+
+impl<'py> IntoPyObject<'py> for Json {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        to_py(py, &self.0)
+    }
+}
+
+impl<'py> FromPyObject<'py> for Json {
+    fn extract_bound(object: &Bound<'py, PyAny>) -> PyResult<Self> {
+        from_py(object).map(Json)
+    }
+}
+
+/// The Python object for a JSON value
+pub fn to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(value) => PyBool::new(py, *value).to_owned().into_any(),
+        Value::Number(number) => {
+            if let Some(value) = number.as_i64() {
+                value.into_pyobject(py)?.into_any()
+            } else if let Some(value) = number.as_u64() {
+                value.into_pyobject(py)?.into_any()
+            } else {
+                number
+                    .as_f64()
+                    .unwrap_or(f64::NAN)
+                    .into_pyobject(py)?
+                    .into_any()
+            }
+        }
+        Value::String(value) => PyString::new(py, value).into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(to_py(py, item)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(members) => {
+            let dict = PyDict::new(py);
+            for (key, value) in members {
+                dict.set_item(key, to_py(py, value)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+/// The JSON value of a Python object
+pub fn from_py(object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if object.is_none() {
+        return Ok(Value::Null);
+    }
+    if let Ok(value) = object.downcast::<PyBool>() {
+        return Ok(Value::Bool(value.is_true()));
+    }
+    if let Ok(value) = object.downcast::<PyInt>() {
+        if let Ok(value) = value.extract::<i64>() {
+            return Ok(value.into());
+        }
+        return value
+            .extract::<u64>()
+            .map(Value::from)
+            .map_err(|_| PyValueError::new_err(format!("{} does not fit in 64 bits", value)));
+    }
+    if let Ok(value) = object.downcast::<PyFloat>() {
+        return Number::from_f64(value.value())
+            .map(Value::Number)
+            .ok_or_else(|| PyValueError::new_err(format!("{} is not a JSON number", value)));
+    }
+    if let Ok(value) = object.downcast::<PyString>() {
+        return Ok(Value::String(value.to_str()?.to_string()));
+    }
+    if let Ok(dict) = object.downcast::<PyDict>() {
+        let mut members = Map::new();
+        for (key, value) in dict.iter() {
+            let key = key.downcast::<PyString>().map_err(|_| {
+                PyTypeError::new_err(format!(
+                    "JSON object keys must be str, not {}",
+                    type_name(&key)
+                ))
+            })?;
+            members.insert(key.to_str()?.to_string(), from_py(&value)?);
+        }
+        return Ok(Value::Object(members));
+    }
+    if let Ok(list) = object.downcast::<PyList>() {
+        return list.iter().map(|item| from_py(&item)).collect();
+    }
+    if let Ok(tuple) = object.downcast::<PyTuple>() {
+        return tuple.iter().map(|item| from_py(&item)).collect();
+    }
+    Err(PyTypeError::new_err(format!(
+        "{} is not JSON serializable",
+        type_name(object)
+    )))
+}
+
+fn type_name(object: &Bound<'_, PyAny>) -> String {
+    object
+        .get_type()
+        .name()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|_| "object".to_string())
+}
+
+#[cfg(all(test, feature = "auto-initialize"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_values_round_trip() {
+        Python::with_gil(|py| {
+            let value = json!({
+                "name": "search",
+                "limit": 10,
+                "big": u64::MAX,
+                "ratio": 0.5,
+                "strict": true,
+                "tags": ["a", null],
+            });
+            let object = to_py(py, &value).unwrap();
+            assert!(object.downcast::<PyDict>().is_ok());
+            assert_eq!(from_py(&object).unwrap(), value);
+        });
+    }
+
+    #[test]
+    fn test_python_only_values_are_rejected() {
+        Python::with_gil(|py| {
+            let tuple = PyTuple::new(py, [1, 2]).unwrap();
+            assert_eq!(from_py(tuple.as_any()).unwrap(), json!([1, 2]));
+
+            let keyed = PyDict::new(py);
+            keyed.set_item(1, "one").unwrap();
+            let error = from_py(keyed.as_any()).unwrap_err();
+            assert!(error.is_instance_of::<PyTypeError>(py));
+
+            let nan = f64::NAN.into_pyobject(py).unwrap();
+            assert!(from_py(nan.as_any())
+                .unwrap_err()
+                .is_instance_of::<PyValueError>(py));
+        });
+    }
+}
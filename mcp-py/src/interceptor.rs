@@ -0,0 +1,281 @@
+//! Interceptors from Python: callables, or the built-in ones from
+//! `mcp-transport`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mcp_core::interceptor::{
+    InterceptAction, InterceptionResult, InterceptorStats, MessageContext, MessageDirection,
+    MessageInterceptor,
+};
+use mcp_core::messages::JsonRpcMessage;
+use mcp_core::{McpError, McpResult};
+use mcp_transport::interceptors::{
+    ArgumentRepairInterceptor, LoggingInterceptor, RateLimitInterceptor, RedactionConfig,
+    RedactionInterceptor, Redactor, ValidationInterceptor,
+};
+use pyo3::prelude::*;
+use tokio::sync::RwLock;
+
+use crate::convert::{from_py, to_py};
+use crate::to_py_err;
+
+/// Runs on every message the client sends or receives.
+///
+/// `Interceptor(callback)` calls `callback(message, direction)` with the
+/// JSON-RPC message as a `dict` and `direction` as `"outgoing"` or
+/// `"incoming"`. Returning `None` or `True` passes the message on, a `dict`
+/// replaces it, and `False` drops it. An exception fails the request.
+///
+/// The static methods build the interceptors that ship with genmcp.
+#[pyclass(module = "genmcp", frozen)]
+#[derive(Clone)]
+pub struct Interceptor {
+    pub(crate) inner: Arc<dyn MessageInterceptor>,
+}
+
+#[pymethods]
+impl Interceptor {
+    #[new]
+    #[pyo3(signature = (callback, name = None, priority = 50))]
+    fn new(callback: Bound<'_, PyAny>, name: Option<String>, priority: u32) -> PyResult<Self> {
+        let name = match name {
+            Some(name) => name,
+            None => callback
+                .getattr("__name__")
+                .and_then(|name| name.extract())
+                .unwrap_or_else(|_| "python".to_string()),
+        };
+        Ok(Self {
+            inner: Arc::new(Callback {
+                name,
+                priority,
+                callback: callback.unbind(),
+                stats: RwLock::new(InterceptorStats::default()),
+            }),
+        })
+    }
+
+    /// Log every message with `tracing`, optionally with its content.
+    #[staticmethod]
+    #[pyo3(signature = (log_content = false))]
+    fn logging(log_content: bool) -> Self {
+        Self {
+            inner: Arc::new(LoggingInterceptor::new(log_content)),
+        }
+    }
+
+    /// Check messages against JSON-RPC; with `strict`, drop those that fail.
+    #[staticmethod]
+    #[pyo3(signature = (strict = false))]
+    fn validation(strict: bool) -> Self {
+        Self {
+            inner: Arc::new(ValidationInterceptor::new(strict)),
+        }
+    }
+
+    /// Mask secrets: the built-in patterns and sensitive keys, plus
+    /// `patterns` (regexes) and `keys`.
+    #[staticmethod]
+    #[pyo3(signature = (patterns = Vec::new(), keys = Vec::new()))]
+    fn redaction(patterns: Vec<String>, keys: Vec<String>) -> PyResult<Self> {
+        let mut config = RedactionConfig::default();
+        config.patterns.extend(patterns);
+        config.sensitive_keys.extend(keys);
+        let redactor = Redactor::new(config).map_err(to_py_err)?;
+        Ok(Self {
+            inner: Arc::new(RedactionInterceptor::new(Arc::new(redactor))),
+        })
+    }
+
+    /// Allow at most `max_requests` requests every `window_secs` seconds.
+    #[staticmethod]
+    fn rate_limit(max_requests: usize, window_secs: u64) -> Self {
+        Self {
+            inner: Arc::new(RateLimitInterceptor::new(max_requests, window_secs)),
+        }
+    }
+
+    /// Fix up tool arguments that almost match the tool's input schema.
+    #[staticmethod]
+    fn argument_repair() -> Self {
+        Self {
+            inner: Arc::new(ArgumentRepairInterceptor::new()),
+        }
+    }
+
+    /// The interceptor's name
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Interceptor({:?})", self.inner.name())
+    }
+}
+
+/// What a Python callback decided
+enum Verdict {
+    Continue,
+    Replace(JsonRpcMessage),
+    Block,
+}
+
+/// A Python callable run as an interceptor
+struct Callback {
+    name: String,
+    priority: u32,
+    callback: Py<PyAny>,
+    stats: RwLock<InterceptorStats>,
+}
+
+impl Callback {
+    fn call(&self, context: &MessageContext) -> PyResult<Verdict> {
+        let message = serde_json::to_value(&context.message)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let direction = match context.direction {
+            MessageDirection::Outgoing => "outgoing",
+            MessageDirection::Incoming => "incoming",
+        };
+        Python::with_gil(|py| {
+            let returned = self
+                .callback
+                .bind(py)
+                .call1((to_py(py, &message)?, direction))?;
+            if returned.is_none() {
+                return Ok(Verdict::Continue);
+            }
+            if let Ok(keep) = returned.extract::<bool>() {
+                return Ok(if keep {
+                    Verdict::Continue
+                } else {
+                    Verdict::Block
+                });
+            }
+            let replacement = serde_json::from_value(from_py(&returned)?)
+                .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+            Ok(Verdict::Replace(replacement))
+        })
+    }
+}
+
+#[async_trait]
+impl MessageInterceptor for Callback {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    async fn should_intercept(&self, _context: &MessageContext) -> bool {
+        true
+    }
+
+    async fn intercept(&self, mut context: MessageContext) -> McpResult<InterceptionResult> {
+        let action = self.intercept_in_place(&mut context).await?;
+        Ok(action.into_result(context.message))
+    }
+
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
+        let start = std::time::Instant::now();
+        let verdict = self.call(context).map_err(|e| McpError::Internal {
+            message: format!("interceptor {} raised {}", self.name, e),
+        })?;
+
+        let mut stats = self.stats.write().await;
+        stats.total_intercepted += 1;
+        stats.last_processed = Some(chrono::Utc::now());
+        let elapsed = start.elapsed().as_millis() as f64;
+        stats.avg_processing_time_ms =
+            (stats.avg_processing_time_ms * (stats.total_intercepted - 1) as f64 + elapsed)
+                / stats.total_intercepted as f64;
+
+        Ok(match verdict {
+            Verdict::Continue => InterceptAction::Continue,
+            Verdict::Replace(message) => {
+                stats.total_modified += 1;
+                context.message = message;
+                InterceptAction::Modified {
+                    reasoning: Some(format!("replaced by {}", self.name)),
+                    confidence: None,
+                }
+            }
+            Verdict::Block => {
+                stats.total_blocked += 1;
+                InterceptAction::Block {
+                    reasoning: Some(format!("dropped by {}", self.name)),
+                }
+            }
+        })
+    }
+
+    async fn get_stats(&self) -> InterceptorStats {
+        self.stats.read().await.clone()
+    }
+}
+
+#[cfg(all(test, feature = "auto-initialize"))]
+mod tests {
+    use super::*;
+    use mcp_core::messages::JsonRpcRequest;
+    use pyo3::ffi::c_str;
+    use serde_json::json;
+
+    fn interceptor(source: &std::ffi::CStr) -> Interceptor {
+        Python::with_gil(|py| {
+            let callback = py.eval(source, None, None).unwrap();
+            Interceptor::new(callback, None, 50).unwrap()
+        })
+    }
+
+    fn context() -> MessageContext {
+        MessageContext::new(
+            JsonRpcMessage::Request(JsonRpcRequest::new(1, "tools/call", json!({ "name": "a" }))),
+            MessageDirection::Outgoing,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_callbacks_pass_replace_and_drop_messages() {
+        let pass = interceptor(c_str!("lambda message, direction: None"));
+        let mut message = context();
+        assert_eq!(
+            pass.inner.intercept_in_place(&mut message).await.unwrap(),
+            InterceptAction::Continue
+        );
+
+        let rename = interceptor(c_str!(
+            "lambda message, direction: {**message, 'params': {'name': 'b'}} if direction == 'outgoing' else None"
+        ));
+        let mut message = context();
+        let action = rename.inner.intercept_in_place(&mut message).await.unwrap();
+        assert!(matches!(action, InterceptAction::Modified { .. }));
+        match message.message {
+            JsonRpcMessage::Request(request) => {
+                assert_eq!(request.params, Some(json!({ "name": "b" })))
+            }
+            other => panic!("expected a request, got {:?}", other),
+        }
+
+        let drop = interceptor(c_str!("lambda message, direction: False"));
+        let action = drop.inner.intercept_in_place(&mut context()).await.unwrap();
+        assert!(matches!(action, InterceptAction::Block { .. }));
+        assert_eq!(drop.inner.get_stats().await.total_blocked, 1);
+    }
+
+    #[tokio::test]
+    async fn test_raising_callbacks_fail_the_message() {
+        let raising = interceptor(c_str!("lambda message, direction: 1 / 0"));
+        let mut message = context();
+        let error = raising
+            .inner
+            .intercept_in_place(&mut message)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("ZeroDivisionError"), "{}", error);
+        assert_eq!(message.message, context().message);
+    }
+}
@@ -0,0 +1,179 @@
+//! # genmcp for Python
+//!
+//! Python bindings for the [`mcp_core`] client, built with pyo3. Python
+//! agent frameworks get the same transports, retries, timeouts and
+//! interceptors as the Rust client instead of a second implementation.
+//!
+//! The bindings are behind the `python` feature, so workspace builds do not
+//! need a Python toolchain. The extension module is called `genmcp`. Build
+//! and install it into the active virtualenv with maturin, which turns on the
+//! `extension-module` feature (and with it `python`):
+//!
+//! ```text
+//! cd mcp-py && maturin develop --release
+//! ```
+//!
+//! The tests embed an interpreter, which needs libpython:
+//!
+//! ```text
+//! cargo test -p mcp-py --features auto-initialize
+//! ```
+//!
+//! Every call that talks to the server returns an asyncio awaitable, driven
+//! by a tokio runtime the module starts on first use:
+//!
+//! ```python
+//! import asyncio
+//! import genmcp
+//!
+//! async def main():
+//!     transport = genmcp.Transport.stdio("python", ["server.py"])
+//!     async with await genmcp.Client.connect(
+//!         transport,
+//!         request_timeout=30,
+//!         interceptors=[genmcp.Interceptor.redaction()],
+//!     ) as client:
+//!         tools = await client.list_tools()
+//!         result = await client.call_tool(tools[0], {"query": "rust"})
+//!         print(result.text)
+//!
+//! asyncio.run(main())
+//! ```
+//!
+//! ## Module Contents
+//!
+//! - `Transport`: stdio, HTTP+SSE and HTTP streaming configurations, or a
+//!   named profile from the server registry
+//! - `Client`: connects, lists and calls tools, lists and reads resources,
+//!   and sends raw requests and notifications
+//! - `Tool`, `ToolResult`, `Resource`: typed views of the server's answers
+//! - `Interceptor`: a Python callable, or one of the built-in interceptors,
+//!   run on every message
+//! - `McpError` and its subclasses, raised for failures
+//!
+//! JSON values cross the boundary as plain Python objects: `dict`, `list`,
+//! `str`, `int`, `float`, `bool` and `None`.
+
+#![cfg(feature = "python")]
+
+use std::time::Duration;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+
+pub mod client;
+pub mod convert;
+pub mod interceptor;
+pub mod transport;
+
+pub use client::{Client, Resource, Tool, ToolResult};
+pub use interceptor::Interceptor;
+pub use transport::Transport;
+
+create_exception!(
+    genmcp,
+    McpError,
+    PyException,
+    "Base class of genmcp errors."
+);
+create_exception!(
+    genmcp,
+    TransportError,
+    McpError,
+    "The connection to the server failed."
+);
+create_exception!(
+    genmcp,
+    ProtocolError,
+    McpError,
+    "The server broke the protocol or answered with an error."
+);
+create_exception!(
+    genmcp,
+    ServerError,
+    ProtocolError,
+    "The server answered with a JSON-RPC error; see `code` and `data`."
+);
+create_exception!(
+    genmcp,
+    ValidationError,
+    McpError,
+    "A message or tool argument failed validation."
+);
+create_exception!(
+    genmcp,
+    AuthError,
+    McpError,
+    "The server refused the credentials."
+);
+create_exception!(
+    genmcp,
+    TimeoutError,
+    McpError,
+    "An operation ran out of time."
+);
+create_exception!(genmcp, ConfigError, McpError, "A configuration is invalid.");
+
+/// Raise a client error as the matching Python exception
+pub fn to_py_err(error: mcp_core::McpError) -> PyErr {
+    use mcp_core::McpError as E;
+
+    let message = error.to_string();
+    match error {
+        E::Transport(_) => TransportError::new_err(message),
+        E::Protocol(mcp_core::error::ProtocolError::ServerError { code, data, .. }) => {
+            let error = ServerError::new_err(message);
+            Python::with_gil(|py| {
+                let value = error.value(py);
+                let data = match data {
+                    Some(data) => convert::to_py(py, &data),
+                    None => Ok(py.None().into_bound(py)),
+                };
+                let attached = value
+                    .setattr("code", code.code())
+                    .and_then(|_| value.setattr("data", data?));
+                match attached {
+                    Ok(()) => error,
+                    Err(e) => e,
+                }
+            })
+        }
+        E::Protocol(_) => ProtocolError::new_err(message),
+        E::Validation(_) => ValidationError::new_err(message),
+        E::Auth(_) => AuthError::new_err(message),
+        E::Timeout { .. } => TimeoutError::new_err(message),
+        E::Config(_) => ConfigError::new_err(message),
+        _ => McpError::new_err(message),
+    }
+}
+
+/// A duration given in seconds from Python
+pub(crate) fn seconds(seconds: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| PyValueError::new_err(format!("{} is not a valid number of seconds", seconds)))
+}
+
+/// The `genmcp` extension module
+#[pymodule]
+fn genmcp(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+
+    m.add_class::<Transport>()?;
+    m.add_class::<Client>()?;
+    m.add_class::<Tool>()?;
+    m.add_class::<ToolResult>()?;
+    m.add_class::<Resource>()?;
+    m.add_class::<Interceptor>()?;
+
+    m.add("McpError", py.get_type::<McpError>())?;
+    m.add("TransportError", py.get_type::<TransportError>())?;
+    m.add("ProtocolError", py.get_type::<ProtocolError>())?;
+    m.add("ServerError", py.get_type::<ServerError>())?;
+    m.add("ValidationError", py.get_type::<ValidationError>())?;
+    m.add("AuthError", py.get_type::<AuthError>())?;
+    m.add("TimeoutError", py.get_type::<TimeoutError>())?;
+    m.add("ConfigError", py.get_type::<ConfigError>())?;
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Transport configurations built from Python.
+
+use std::collections::HashMap;
+
+use mcp_core::registry::ServerRegistry;
+use mcp_core::transport::{
+    AuthConfig, HttpSseConfig, HttpStreamConfig, StdioConfig, TransportConfig,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use url::Url;
+
+use crate::{seconds, to_py_err};
+
+/// How to reach a server.
+///
+/// Build one with a static method: `Transport.stdio`, `Transport.http_sse`,
+/// `Transport.http_stream`, `Transport.profile` or `Transport.from_file`.
+#[pyclass(module = "genmcp", frozen)]
+#[derive(Debug, Clone)]
+pub struct Transport {
+    pub(crate) config: TransportConfig,
+}
+
+impl Transport {
+    /// Wrap a configuration, rejecting one that is invalid
+    pub fn new(config: TransportConfig) -> PyResult<Self> {
+        config.validate().map_err(to_py_err)?;
+        Ok(Self { config })
+    }
+
+    /// The wrapped configuration
+    pub fn config(&self) -> &TransportConfig {
+        &self.config
+    }
+}
+
+#[pymethods]
+impl Transport {
+    /// Run a server as a child process and talk to it over stdin and stdout.
+    #[staticmethod]
+    #[pyo3(signature = (command, args = Vec::new(), env = None, cwd = None, timeout = None))]
+    fn stdio(
+        command: String,
+        args: Vec<String>,
+        env: Option<HashMap<String, String>>,
+        cwd: Option<String>,
+        timeout: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut config = StdioConfig::new(command).args(args);
+        for (key, value) in env.unwrap_or_default() {
+            config = config.env(key, value);
+        }
+        if let Some(cwd) = cwd {
+            config = config.working_dir(cwd);
+        }
+        if let Some(timeout) = timeout {
+            config = config.timeout(seconds(timeout)?);
+        }
+        Self::new(TransportConfig::Stdio(config))
+    }
+
+    /// Connect to a server over HTTP with Server-Sent Events.
+    #[staticmethod]
+    #[pyo3(signature = (url, headers = None, bearer_token = None, timeout = None))]
+    fn http_sse(
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        bearer_token: Option<String>,
+        timeout: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut config = HttpSseConfig::new(parse_url(url)?);
+        for (key, value) in headers.unwrap_or_default() {
+            config = config.header(key, value);
+        }
+        if let Some(token) = bearer_token {
+            config = config.auth(AuthConfig::bearer(token));
+        }
+        if let Some(timeout) = timeout {
+            config = config.timeout(seconds(timeout)?);
+        }
+        Self::new(TransportConfig::HttpSse(config))
+    }
+
+    /// Connect to a server over Streamable HTTP.
+    #[staticmethod]
+    #[pyo3(signature = (url, headers = None, bearer_token = None, timeout = None))]
+    fn http_stream(
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        bearer_token: Option<String>,
+        timeout: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut config = HttpStreamConfig::new(parse_url(url)?);
+        for (key, value) in headers.unwrap_or_default() {
+            config = config.header(key, value);
+        }
+        if let Some(token) = bearer_token {
+            config = config.auth(AuthConfig::bearer(token));
+        }
+        if let Some(timeout) = timeout {
+            config = config.timeout(seconds(timeout)?);
+        }
+        Self::new(TransportConfig::HttpStream(config))
+    }
+
+    /// A named server from the registry, by default
+    /// `~/.config/genmcp/servers.toml`.
+    #[staticmethod]
+    #[pyo3(signature = (name, registry = None))]
+    fn profile(name: &str, registry: Option<&str>) -> PyResult<Self> {
+        let registry = match registry {
+            Some(path) => ServerRegistry::load(path),
+            None => ServerRegistry::load_default(),
+        }
+        .map_err(to_py_err)?;
+        Self::new(registry.resolve(name).map_err(to_py_err)?)
+    }
+
+    /// A transport configuration file in JSON, YAML or TOML.
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        Self::new(TransportConfig::from_file(path).map_err(to_py_err)?)
+    }
+
+    /// The transport type, such as `"stdio"` or `"http-sse"`
+    #[getter]
+    fn kind(&self) -> &'static str {
+        self.config.transport_type()
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.config {
+            TransportConfig::Stdio(config) => {
+                format!("Transport.stdio({:?}, {:?})", config.command, config.args)
+            }
+            TransportConfig::HttpSse(config) => {
+                format!("Transport.http_sse({:?})", config.base_url.as_str())
+            }
+            TransportConfig::HttpStream(config) => {
+                format!("Transport.http_stream({:?})", config.base_url.as_str())
+            }
+            other => format!("Transport({:?})", other.transport_type()),
+        }
+    }
+}
+
+fn parse_url(url: &str) -> PyResult<Url> {
+    Url::parse(url).map_err(|e| PyValueError::new_err(format!("invalid URL {:?}: {}", url, e)))
+}
@@ -0,0 +1,1387 @@
+//! Fan-out aggregation of several backend MCP servers behind one proxy.
+//!
+//! The aggregating proxy speaks MCP over stdio to its client and holds an
+//! [`McpClient`] session with every backend listed in a TOML file:
+//!
+//! ```toml
+//! separator = "__"
+//!
+//! [[backend]]
+//! name = "github"
+//! command = "npx -y @modelcontextprotocol/server-github"
+//!
+//! [[backend]]
+//! name = "search"
+//! transport = "http-sse"
+//! url = "http://localhost:3000/sse"
+//! critical = true
+//!
+//! [circuit_breaker]
+//! failure_threshold = 5
+//! cool_down = "30s"
+//! ```
+//!
+//! Tools and prompts are exposed as `<backend><separator><name>`
+//! (`github__create_issue`) and resources as `<backend>+<uri>`
+//! (`github+repo://owner/name`), which keeps them valid URIs. Calls are routed
+//! back to the owning backend with the original identifier restored.
+//!
+//! Critical backends keep a warm standby session for instant failover, see
+//! [`crate::standby`]. A backend listing `replicas` is served by several
+//! interchangeable copies with load balancing, see [`crate::balance`].
+//!
+//! Every backend session sits behind a circuit breaker: after
+//! `failure_threshold` connection failures in a row its calls fail at once
+//! until `cool_down` has passed and a probe succeeds. State changes are logged
+//! and sent to the monitor.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use mcp_common::{CircuitChange, IpcMessage, LogEntry, LogLevel, ProxyId, ProxyStats};
+use mcp_core::client::{McpClient, McpClientBuilder, NotificationHandler};
+use mcp_core::error::{McpError, TransportError};
+use mcp_core::messages::{
+    Capabilities, Implementation, JsonRpcError, JsonRpcMessage, JsonRpcNotification,
+    JsonRpcRequest, JsonRpcResponse, PromptListChangedNotification, ProtocolVersion, RequestId,
+    ResourceListChangedNotification, ResourceUpdatedNotification, ToolListChangedNotification,
+};
+use mcp_core::restart::ServerRestarted;
+use mcp_core::transport::{BreakerConfig, CircuitEvent, CircuitState, Transport};
+use mcp_core::McpResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, warn};
+
+use crate::balance::{BalanceConfig, BalancedBackend};
+use crate::buffered_ipc_client::BufferedIpcClient;
+use crate::interceptors::Redactor;
+use crate::standby::{Connector, StandbyBackend};
+use crate::transport_config::TransportConfig;
+
+/// Separator placed between the backend name and a tool or prompt name
+pub const DEFAULT_SEPARATOR: &str = "__";
+
+/// Upper bound on pages fetched from one backend for a single list request
+const MAX_LIST_PAGES: usize = 100;
+
+/// Backends fronted by an aggregating proxy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateConfig {
+    #[serde(default = "default_separator")]
+    pub separator: String,
+
+    /// Seconds between standby validations for critical backends
+    #[serde(default = "default_standby_check_secs")]
+    pub standby_check_secs: u64,
+
+    /// Circuit breaker placed in front of every backend
+    #[serde(default)]
+    pub circuit_breaker: BreakerConfig,
+
+    #[serde(rename = "backend")]
+    pub backends: Vec<BackendConfig>,
+}
+
+fn default_separator() -> String {
+    DEFAULT_SEPARATOR.to_string()
+}
+
+fn default_standby_check_secs() -> u64 {
+    30
+}
+
+/// One backend server; fields mirror the `proxy` command line options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendConfig {
+    pub name: String,
+
+    #[serde(default = "default_transport")]
+    pub transport: String,
+
+    #[serde(default)]
+    pub command: Option<String>,
+
+    #[serde(default)]
+    pub url: Option<String>,
+
+    #[serde(default = "default_use_shell")]
+    pub use_shell: bool,
+
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Keep an initialized standby session for instant failover
+    #[serde(default)]
+    pub critical: bool,
+
+    /// Commands or URLs of interchangeable copies of this backend, in place
+    /// of `command` or `url`
+    #[serde(default)]
+    pub replicas: Vec<String>,
+
+    /// How requests are spread over the replicas
+    #[serde(default)]
+    pub balance: BalanceConfig,
+}
+
+fn default_transport() -> String {
+    "stdio".to_string()
+}
+
+fn default_use_shell() -> bool {
+    true
+}
+
+impl AggregateConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read backends file {}", path.display()))?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Invalid backends file {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.backends.is_empty() {
+            bail!("At least one [[backend]] is required");
+        }
+        if self.separator.is_empty() {
+            bail!("separator must not be empty");
+        }
+        if self.standby_check_secs == 0 {
+            bail!("standby_check_secs must be at least 1");
+        }
+        self.circuit_breaker
+            .validate()
+            .context("Invalid [circuit_breaker]")?;
+
+        let mut seen = HashSet::new();
+        for backend in &self.backends {
+            // Names become URI schemes (`name+scheme:...`), so keep to scheme characters
+            let valid = backend
+                .name
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic())
+                && backend
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+            if !valid {
+                bail!(
+                    "Invalid backend name '{}': use letters, digits, '-' and '.', starting with a letter",
+                    backend.name
+                );
+            }
+            if backend.name.contains(&self.separator) {
+                bail!(
+                    "Backend name '{}' contains the separator '{}'",
+                    backend.name,
+                    self.separator
+                );
+            }
+            if !seen.insert(backend.name.as_str()) {
+                bail!("Duplicate backend name '{}'", backend.name);
+            }
+            if !backend.replicas.is_empty() {
+                if backend.command.is_some() || backend.url.is_some() {
+                    bail!(
+                        "Backend '{}' sets replicas as well as command or url",
+                        backend.name
+                    );
+                }
+                if backend.critical {
+                    bail!(
+                        "Backend '{}' has replicas, which already fail over; drop critical",
+                        backend.name
+                    );
+                }
+                backend
+                    .balance
+                    .validate()
+                    .with_context(|| format!("Invalid [backend.balance] of '{}'", backend.name))?;
+            }
+            for replica in backend.replica_configs() {
+                replica.transport_config()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BackendConfig {
+    /// One config per replica, or this backend alone when it has none
+    pub fn replica_configs(&self) -> Vec<BackendConfig> {
+        if self.replicas.is_empty() {
+            return vec![self.clone()];
+        }
+        self.replicas
+            .iter()
+            .map(|target| {
+                let mut replica = BackendConfig {
+                    replicas: Vec::new(),
+                    ..self.clone()
+                };
+                if replica.transport == "stdio" {
+                    replica.command = Some(target.clone());
+                } else {
+                    replica.url = Some(target.clone());
+                }
+                replica
+            })
+            .collect()
+    }
+
+    pub fn transport_config(&self) -> Result<TransportConfig> {
+        TransportConfig::from_cli_args(
+            &self.transport,
+            self.command.clone(),
+            self.url.clone(),
+            self.use_shell,
+            self.api_key.clone(),
+        )
+        .with_context(|| format!("Backend '{}'", self.name))
+    }
+}
+
+/// A connected backend session
+#[async_trait]
+pub trait Backend: Send + Sync {
+    /// Send a request on behalf of a downstream session and return its
+    /// result or JSON-RPC error.
+    ///
+    /// `session` is the client's `Mcp-Session-Id` when the frontend serves
+    /// several clients, and `None` for a single stdio client.
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError>;
+
+    /// Forget what was kept for a downstream session that has ended
+    fn end_session(&self, _session: &str) {}
+
+    /// Whether the connection is gone for good, so requests can never succeed
+    fn is_lost(&self) -> bool {
+        false
+    }
+}
+
+/// [`Backend`] backed by an MCP client session
+pub struct McpBackend {
+    client: Mutex<McpClient>,
+    lost: AtomicBool,
+}
+
+impl McpBackend {
+    fn new(client: McpClient) -> Self {
+        Self {
+            client: Mutex::new(client),
+            lost: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Errors after which the session cannot be used again
+fn is_connection_lost(error: &McpError) -> bool {
+    matches!(
+        error,
+        McpError::Transport(
+            TransportError::ConnectionLost { .. }
+                | TransportError::ConnectionFailed { .. }
+                | TransportError::ConnectionError { .. }
+                | TransportError::DisconnectedError { .. }
+                | TransportError::NotConnected { .. }
+                | TransportError::ProcessError { .. }
+        )
+    )
+}
+
+#[async_trait]
+impl Backend for McpBackend {
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        _session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        let mut client = self.client.lock().await;
+        match client.send_request(method, params).await {
+            Ok(response) => match response.error {
+                Some(error) => Err(error),
+                None => Ok(response.result.unwrap_or(Value::Null)),
+            },
+            Err(e) => {
+                if is_connection_lost(&e) {
+                    self.lost.store(true, Ordering::SeqCst);
+                }
+                Err(JsonRpcError::internal_error(e.to_string()))
+            }
+        }
+    }
+
+    fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+}
+
+/// Opens sessions for one configured backend
+struct ConfigConnector {
+    backend: BackendConfig,
+    breaker: BreakerConfig,
+    notifications: mpsc::UnboundedSender<JsonRpcNotification>,
+    circuits: mpsc::UnboundedSender<CircuitEvent>,
+}
+
+#[async_trait]
+impl Connector for ConfigConnector {
+    async fn connect(&self) -> Result<Arc<dyn Backend>> {
+        let (_, session) = Aggregator::connect_backend(
+            &self.backend,
+            &self.breaker,
+            self.notifications.clone(),
+            self.circuits.clone(),
+        )
+        .await?;
+        Ok(session)
+    }
+}
+
+/// Forwards backend list-change notifications to the frontend
+struct ForwardingHandler {
+    backend: String,
+    notifications: mpsc::UnboundedSender<JsonRpcNotification>,
+}
+
+impl ForwardingHandler {
+    fn forward(&self, method: &str, params: Value) {
+        let _ = self
+            .notifications
+            .send(JsonRpcNotification::new(method, params));
+    }
+}
+
+#[async_trait]
+impl NotificationHandler for ForwardingHandler {
+    async fn handle_resource_updated(
+        &self,
+        notification: ResourceUpdatedNotification,
+    ) -> McpResult<()> {
+        let uri = namespace_uri(&self.backend, &notification.uri);
+        self.forward("notifications/resources/updated", json!({ "uri": uri }));
+        Ok(())
+    }
+
+    async fn handle_resource_list_changed(
+        &self,
+        _: ResourceListChangedNotification,
+    ) -> McpResult<()> {
+        self.forward("notifications/resources/list_changed", json!({}));
+        Ok(())
+    }
+
+    async fn handle_tool_list_changed(&self, _: ToolListChangedNotification) -> McpResult<()> {
+        self.forward("notifications/tools/list_changed", json!({}));
+        Ok(())
+    }
+
+    async fn handle_prompt_list_changed(&self, _: PromptListChangedNotification) -> McpResult<()> {
+        self.forward("notifications/prompts/list_changed", json!({}));
+        Ok(())
+    }
+}
+
+struct BackendHandle {
+    name: String,
+    capabilities: Capabilities,
+    backend: Arc<dyn Backend>,
+    standby: Option<Arc<StandbyBackend>>,
+}
+
+/// Merges several backends into a single MCP server
+pub struct Aggregator {
+    name: String,
+    separator: String,
+    backends: Vec<BackendHandle>,
+}
+
+impl Aggregator {
+    pub fn new(name: impl Into<String>, separator: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            separator: separator.into(),
+            backends: Vec::new(),
+        }
+    }
+
+    pub fn add_backend(
+        &mut self,
+        name: impl Into<String>,
+        capabilities: Capabilities,
+        backend: Arc<dyn Backend>,
+    ) {
+        self.backends.push(BackendHandle {
+            name: name.into(),
+            capabilities,
+            backend,
+            standby: None,
+        });
+    }
+
+    /// Add a backend that fails over to a warm standby session
+    pub fn add_critical_backend(
+        &mut self,
+        name: impl Into<String>,
+        capabilities: Capabilities,
+        backend: Arc<StandbyBackend>,
+    ) {
+        self.backends.push(BackendHandle {
+            name: name.into(),
+            capabilities,
+            backend: backend.clone(),
+            standby: Some(backend),
+        });
+    }
+
+    /// Standby sessions currently held open for critical backends
+    pub fn standby_connections(&self) -> u32 {
+        self.backends
+            .iter()
+            .filter(|b| b.standby.as_ref().is_some_and(|s| s.standby_ready()))
+            .count() as u32
+    }
+
+    /// Connect to every configured backend
+    ///
+    /// Backends that fail to start are skipped with a warning; it is an error
+    /// only if none can be reached. Circuit breaker changes arrive on
+    /// `circuits`, named after the backend.
+    pub async fn connect(
+        name: impl Into<String>,
+        config: &AggregateConfig,
+        notifications: mpsc::UnboundedSender<JsonRpcNotification>,
+        circuits: mpsc::UnboundedSender<CircuitEvent>,
+    ) -> Result<Self> {
+        let mut aggregator = Self::new(name, config.separator.clone());
+
+        for backend in &config.backends {
+            if !backend.replicas.is_empty() {
+                match Self::connect_replicas(backend, config, &notifications, &circuits).await {
+                    Ok((capabilities, group)) => {
+                        info!(
+                            "Connected backend '{}' with {} of {} replicas",
+                            backend.name,
+                            group.healthy_replicas(),
+                            backend.replicas.len()
+                        );
+                        aggregator.add_backend(backend.name.clone(), capabilities, group);
+                    }
+                    Err(e) => warn!("Skipping backend '{}': {:#}", backend.name, e),
+                }
+                continue;
+            }
+            let connected = Self::connect_backend(
+                backend,
+                &config.circuit_breaker,
+                notifications.clone(),
+                circuits.clone(),
+            )
+            .await;
+            match connected {
+                Ok((capabilities, session)) if backend.critical => {
+                    info!("Connected critical backend '{}'", backend.name);
+                    let connector = Arc::new(ConfigConnector {
+                        backend: backend.clone(),
+                        breaker: config.circuit_breaker.clone(),
+                        notifications: notifications.clone(),
+                        circuits: circuits.clone(),
+                    });
+                    let session = StandbyBackend::new(backend.name.clone(), session, connector);
+                    session.spawn_maintenance(Duration::from_secs(config.standby_check_secs));
+                    aggregator.add_critical_backend(backend.name.clone(), capabilities, session);
+                }
+                Ok((capabilities, session)) => {
+                    info!("Connected backend '{}'", backend.name);
+                    aggregator.add_backend(backend.name.clone(), capabilities, session);
+                }
+                Err(e) => warn!("Skipping backend '{}': {:#}", backend.name, e),
+            }
+        }
+
+        if aggregator.backends.is_empty() {
+            bail!(
+                "None of the {} configured backends could be reached",
+                config.backends.len()
+            );
+        }
+        Ok(aggregator)
+    }
+
+    /// Connect every replica of a backend, leaving out those that fail to start
+    async fn connect_replicas(
+        backend: &BackendConfig,
+        config: &AggregateConfig,
+        notifications: &mpsc::UnboundedSender<JsonRpcNotification>,
+        circuits: &mpsc::UnboundedSender<CircuitEvent>,
+    ) -> Result<(Capabilities, Arc<BalancedBackend>)> {
+        let mut capabilities = None;
+        let mut replicas = Vec::new();
+        for (index, replica) in backend.replica_configs().iter().enumerate() {
+            let connected = Self::connect_backend(
+                replica,
+                &config.circuit_breaker,
+                notifications.clone(),
+                circuits.clone(),
+            )
+            .await;
+            match connected {
+                Ok((offered, session)) => {
+                    capabilities.get_or_insert(offered);
+                    replicas.push(session);
+                }
+                Err(e) => warn!(
+                    "Skipping replica {} of '{}': {:#}",
+                    index + 1,
+                    backend.name,
+                    e
+                ),
+            }
+        }
+        let Some(capabilities) = capabilities else {
+            bail!(
+                "None of the {} replicas could be reached",
+                backend.replicas.len()
+            );
+        };
+        let group = BalancedBackend::new(backend.name.clone(), backend.balance.clone(), replicas);
+        Ok((capabilities, Arc::new(group)))
+    }
+
+    async fn connect_backend(
+        backend: &BackendConfig,
+        breaker: &BreakerConfig,
+        notifications: mpsc::UnboundedSender<JsonRpcNotification>,
+        circuits: mpsc::UnboundedSender<CircuitEvent>,
+    ) -> Result<(Capabilities, Arc<dyn Backend>)> {
+        let transport = backend.transport_config()?.to_mcp_config()?;
+        let mut client = McpClientBuilder::new()
+            .transport(transport)
+            .circuit_breaker(breaker.clone())
+            .notification_handler(Box::new(ForwardingHandler {
+                backend: backend.name.clone(),
+                notifications: notifications.clone(),
+            }))
+            .build()
+            .await?;
+
+        let info = client
+            .connect(Implementation::new(
+                "mcp-transport",
+                env!("CARGO_PKG_VERSION"),
+            ))
+            .await?;
+        Self::forward_restarts(&backend.name, client.server_restarts(), notifications);
+        Self::forward_circuit(&backend.name, client.transport(), circuits);
+
+        Ok((info.capabilities, Arc::new(McpBackend::new(client))))
+    }
+
+    /// Pass a session's circuit breaker changes on, renamed after the backend
+    fn forward_circuit(
+        backend: &str,
+        transport: Arc<dyn Transport>,
+        circuits: mpsc::UnboundedSender<CircuitEvent>,
+    ) {
+        let Some(mut events) = transport.circuit_events() else {
+            return;
+        };
+        let backend = backend.to_string();
+        tokio::spawn(async move {
+            loop {
+                let mut event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                event.name = backend.clone();
+                if circuits.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Tell the frontend to list again whenever a backend comes back from a restart
+    fn forward_restarts(
+        backend: &str,
+        mut restarts: broadcast::Receiver<ServerRestarted>,
+        notifications: mpsc::UnboundedSender<JsonRpcNotification>,
+    ) {
+        let backend = backend.to_string();
+        tokio::spawn(async move {
+            loop {
+                let event = match restarts.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                warn!("Backend {}: {}", backend, event);
+                if event.capabilities_changed() {
+                    warn!(
+                        "Backend {} changed capabilities; reconnect the aggregate to pick them up",
+                        backend
+                    );
+                }
+
+                let standard = &event.server.capabilities.standard;
+                let changed = [
+                    (standard.tools.is_some(), "notifications/tools/list_changed"),
+                    (
+                        standard.resources.is_some(),
+                        "notifications/resources/list_changed",
+                    ),
+                    (
+                        standard.prompts.is_some(),
+                        "notifications/prompts/list_changed",
+                    ),
+                ];
+                for (_, method) in changed.iter().filter(|(offered, _)| *offered) {
+                    let _ = notifications.send(JsonRpcNotification::new(*method, json!({})));
+                }
+            }
+        });
+    }
+
+    /// Names of the connected backends
+    pub fn backend_names(&self) -> Vec<&str> {
+        self.backends.iter().map(|b| b.name.as_str()).collect()
+    }
+
+    /// Capabilities advertised to the frontend client
+    pub fn capabilities(&self) -> Value {
+        let any = |f: fn(&Capabilities) -> bool| self.backends.iter().any(|b| f(&b.capabilities));
+        let mut capabilities = serde_json::Map::new();
+
+        if any(|c| c.standard.tools.is_some()) {
+            capabilities.insert("tools".into(), json!({ "listChanged": true }));
+        }
+        if any(|c| c.standard.resources.is_some()) {
+            let subscribe = any(|c| {
+                c.standard
+                    .resources
+                    .as_ref()
+                    .is_some_and(|r| r.subscribe == Some(true))
+            });
+            capabilities.insert(
+                "resources".into(),
+                json!({ "listChanged": true, "subscribe": subscribe }),
+            );
+        }
+        if any(|c| c.standard.prompts.is_some()) {
+            capabilities.insert("prompts".into(), json!({ "listChanged": true }));
+        }
+        if any(|c| c.standard.logging.is_some()) {
+            capabilities.insert("logging".into(), json!({}));
+        }
+        Value::Object(capabilities)
+    }
+
+    /// Answer one frontend request from `session`, see [`Backend::request`]
+    pub async fn handle_request(
+        &self,
+        request: JsonRpcRequest,
+        session: Option<&str>,
+    ) -> JsonRpcResponse {
+        let params = request.params.unwrap_or_else(|| json!({}));
+        match self.dispatch(&request.method, params, session).await {
+            Ok(result) => JsonRpcResponse::success(request.id, result),
+            Err(error) => JsonRpcResponse::error(request.id, error),
+        }
+    }
+
+    /// Release what the backends keep for a frontend session that has ended
+    pub fn end_session(&self, session: &str) {
+        for handle in &self.backends {
+            handle.backend.end_session(session);
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        method: &str,
+        params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => {
+                self.list("tools", "tools/list", "tools", ListedBy::Name, session)
+                    .await
+            }
+            "prompts/list" => {
+                self.list(
+                    "prompts",
+                    "prompts/list",
+                    "prompts",
+                    ListedBy::Name,
+                    session,
+                )
+                .await
+            }
+            "resources/list" => {
+                self.list(
+                    "resources",
+                    "resources/list",
+                    "resources",
+                    ListedBy::Uri("uri"),
+                    session,
+                )
+                .await
+            }
+            "resources/templates/list" => {
+                self.list(
+                    "resources",
+                    "resources/templates/list",
+                    "resourceTemplates",
+                    ListedBy::Uri("uriTemplate"),
+                    session,
+                )
+                .await
+            }
+            "tools/call" | "prompts/get" => self.route_by_name(method, params, session).await,
+            "resources/read" | "resources/subscribe" | "resources/unsubscribe" => {
+                self.route_by_uri(method, params, session).await
+            }
+            "logging/setLevel" => self.broadcast_logging_level(params, session).await,
+            _ => Err(JsonRpcError::method_not_found(method)),
+        }
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested: Option<ProtocolVersion> = params
+            .get("protocolVersion")
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+        let version = requested
+            .filter(ProtocolVersion::is_supported)
+            .unwrap_or_default();
+
+        json!({
+            "protocolVersion": version,
+            "capabilities": self.capabilities(),
+            "serverInfo": { "name": self.name, "version": env!("CARGO_PKG_VERSION") },
+            "instructions": format!(
+                "Aggregates {}. Tool and prompt names are prefixed with '<backend>{}'.",
+                self.backend_names().join(", "),
+                self.separator
+            ),
+        })
+    }
+
+    /// Collect a catalog from every backend that supports it, namespacing entries
+    async fn list(
+        &self,
+        capability: &str,
+        method: &str,
+        key: &str,
+        listed_by: ListedBy,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        let mut merged = Vec::new();
+
+        for handle in &self.backends {
+            let supported = serde_json::to_value(&handle.capabilities)
+                .ok()
+                .is_some_and(|c| c.get(capability).is_some());
+            if !supported {
+                continue;
+            }
+
+            let mut cursor: Option<Value> = None;
+            for _ in 0..MAX_LIST_PAGES {
+                let params = match cursor.take() {
+                    Some(cursor) => json!({ "cursor": cursor }),
+                    None => json!({}),
+                };
+
+                let page = match handle.backend.request(method, params, session).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        // One broken backend should not hide the others' catalogs
+                        warn!(
+                            "{} failed on backend '{}': {}",
+                            method, handle.name, e.message
+                        );
+                        break;
+                    }
+                };
+
+                if let Some(items) = page.get(key).and_then(Value::as_array) {
+                    for item in items {
+                        let mut item = item.clone();
+                        self.rename_entry(&handle.name, &mut item, listed_by);
+                        merged.push(item);
+                    }
+                }
+
+                match page.get("nextCursor") {
+                    Some(next) if !next.is_null() => cursor = Some(next.clone()),
+                    _ => break,
+                }
+            }
+        }
+
+        Ok(json!({ key: merged }))
+    }
+
+    fn rename_entry(&self, backend: &str, item: &mut Value, listed_by: ListedBy) {
+        let Some(entry) = item.as_object_mut() else {
+            return;
+        };
+
+        let field = match listed_by {
+            ListedBy::Name => "name",
+            ListedBy::Uri(field) => field,
+        };
+        if let Some(Value::String(original)) = entry.get(field) {
+            let renamed = match listed_by {
+                ListedBy::Name => self.namespace_name(backend, original),
+                ListedBy::Uri(_) => namespace_uri(backend, original),
+            };
+            entry.insert(field.to_string(), Value::String(renamed));
+        }
+    }
+
+    fn namespace_name(&self, backend: &str, name: &str) -> String {
+        format!("{}{}{}", backend, self.separator, name)
+    }
+
+    /// Find the backend owning a namespaced tool or prompt name
+    fn resolve_name<'a>(&self, name: &'a str) -> Option<(&BackendHandle, &'a str)> {
+        let (backend, original) = name.split_once(self.separator.as_str())?;
+        let handle = self.backends.iter().find(|b| b.name == backend)?;
+        Some((handle, original))
+    }
+
+    /// Find the backend owning a namespaced resource URI
+    fn resolve_uri<'a>(&self, uri: &'a str) -> Option<(&BackendHandle, &'a str)> {
+        let (backend, original) = uri.split_once('+')?;
+        let handle = self.backends.iter().find(|b| b.name == backend)?;
+        Some((handle, original))
+    }
+
+    async fn route_by_name(
+        &self,
+        method: &str,
+        mut params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::invalid_params("Missing 'name'"))?
+            .to_string();
+        let (handle, original) = self
+            .resolve_name(&name)
+            .ok_or_else(|| JsonRpcError::invalid_params(format!("Unknown name '{}'", name)))?;
+
+        params["name"] = Value::String(original.to_string());
+        handle.backend.request(method, params, session).await
+    }
+
+    async fn route_by_uri(
+        &self,
+        method: &str,
+        mut params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        let uri = params
+            .get("uri")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::invalid_params("Missing 'uri'"))?
+            .to_string();
+        let (handle, original) = self
+            .resolve_uri(&uri)
+            .ok_or_else(|| JsonRpcError::invalid_params(format!("Unknown resource '{}'", uri)))?;
+
+        params["uri"] = Value::String(original.to_string());
+        let mut result = handle.backend.request(method, params, session).await?;
+
+        // Contents echo the URI they were read from; keep it in the frontend namespace
+        if let Some(contents) = result.get_mut("contents").and_then(Value::as_array_mut) {
+            for content in contents {
+                if let Some(Value::String(inner)) = content.get("uri") {
+                    content["uri"] = Value::String(namespace_uri(&handle.name, inner));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn broadcast_logging_level(
+        &self,
+        params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        for handle in self
+            .backends
+            .iter()
+            .filter(|b| b.capabilities.standard.logging.is_some())
+        {
+            let result = handle
+                .backend
+                .request("logging/setLevel", params.clone(), session)
+                .await;
+            if let Err(e) = result {
+                warn!(
+                    "logging/setLevel failed on backend '{}': {}",
+                    handle.name, e.message
+                );
+            }
+        }
+        Ok(json!({}))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ListedBy {
+    Name,
+    Uri(&'static str),
+}
+
+fn namespace_uri(backend: &str, uri: &str) -> String {
+    format!("{}+{}", backend, uri)
+}
+
+/// Serves an [`Aggregator`] over the proxy's stdin/stdout
+pub struct AggregateHandler {
+    proxy_id: ProxyId,
+    stats: Arc<Mutex<ProxyStats>>,
+    ipc_client: Option<Arc<BufferedIpcClient>>,
+    redactor: Arc<Redactor>,
+}
+
+impl AggregateHandler {
+    pub async fn new(
+        proxy_id: ProxyId,
+        stats: Arc<Mutex<ProxyStats>>,
+        ipc_client: Option<Arc<BufferedIpcClient>>,
+    ) -> Result<Self> {
+        Ok(Self {
+            proxy_id,
+            stats,
+            ipc_client,
+            redactor: Arc::new(Redactor::default()),
+        })
+    }
+
+    pub async fn handle_communication(
+        &mut self,
+        name: &str,
+        config: &AggregateConfig,
+        mut shutdown_rx: broadcast::Receiver<()>,
+    ) -> Result<()> {
+        let (notification_tx, mut notification_rx) = mpsc::unbounded_channel();
+        let (circuit_tx, mut circuit_rx) = mpsc::unbounded_channel();
+        let aggregator =
+            Arc::new(Aggregator::connect(name, config, notification_tx, circuit_tx).await?);
+        self.log(
+            LogLevel::Info,
+            format!(
+                "Aggregating backends: {}",
+                aggregator.backend_names().join(", ")
+            ),
+        )
+        .await;
+
+        let (response_tx, mut response_rx) = mpsc::unbounded_channel::<JsonRpcResponse>();
+        let mut user_stdin = BufReader::new(tokio::io::stdin());
+        let mut user_stdout = tokio::io::stdout();
+        let mut stats_interval = interval(Duration::from_secs(1));
+
+        loop {
+            let outgoing = tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    info!("Received shutdown signal");
+                    break;
+                }
+
+                _ = stats_interval.tick() => {
+                    if let Some(ref client) = self.ipc_client {
+                        let stats = {
+                            let mut stats = self.stats.lock().await;
+                            stats.standby_connections = aggregator.standby_connections();
+                            stats.clone()
+                        };
+                        if let Err(e) = client.send(IpcMessage::StatsUpdate(stats)).await {
+                            warn!("Failed to send stats update: {}", e);
+                        }
+                    }
+                    continue;
+                }
+
+                result = async {
+                    let mut input = String::new();
+                    let bytes_read = user_stdin.read_line(&mut input).await?;
+                    Ok::<(usize, String), std::io::Error>((bytes_read, input))
+                } => {
+                    match result {
+                        Ok((0, _)) => break, // EOF
+                        Ok((_, input)) => {
+                            self.log(LogLevel::Request, format!("→ {}", input.trim())).await;
+                            self.accept(&input, &aggregator, &response_tx).await;
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("Failed to read from user stdin: {}", e);
+                            break;
+                        }
+                    }
+                }
+
+                Some(response) = response_rx.recv() => {
+                    let mut stats = self.stats.lock().await;
+                    if response.error.is_some() {
+                        stats.failed_requests += 1;
+                    } else {
+                        stats.successful_requests += 1;
+                    }
+                    JsonRpcMessage::Response(response)
+                }
+
+                Some(notification) = notification_rx.recv() => JsonRpcMessage::Notification(notification),
+
+                Some(event) = circuit_rx.recv() => {
+                    self.report_circuit(event).await;
+                    continue;
+                }
+            };
+
+            let line = serde_json::to_string(&outgoing)? + "\n";
+            self.log(LogLevel::Response, format!("← {}", line.trim()))
+                .await;
+            self.stats.lock().await.bytes_transferred += line.len() as u64;
+
+            if let Err(e) = user_stdout.write_all(line.as_bytes()).await {
+                error!("Failed to write to user stdout: {}", e);
+                break;
+            }
+            if let Err(e) = user_stdout.flush().await {
+                error!("Failed to flush user stdout: {}", e);
+                break;
+            }
+        }
+
+        info!("Aggregating proxy shutting down");
+        Ok(())
+    }
+
+    /// Start answering a frontend message; responses arrive on `responses`
+    async fn accept(
+        &self,
+        input: &str,
+        aggregator: &Arc<Aggregator>,
+        responses: &mpsc::UnboundedSender<JsonRpcResponse>,
+    ) {
+        {
+            let mut stats = self.stats.lock().await;
+            stats.total_requests += 1;
+            stats.bytes_transferred += input.len() as u64;
+        }
+
+        match mcp_core::decode::from_str::<JsonRpcMessage>(input.trim()) {
+            Ok(JsonRpcMessage::Request(request)) => {
+                // Requests run concurrently so a slow backend does not stall the others
+                let aggregator = aggregator.clone();
+                let responses = responses.clone();
+                // The proxy's only client is on stdio, so it has no session ID
+                tokio::spawn(async move {
+                    let _ = responses.send(aggregator.handle_request(request, None).await);
+                });
+            }
+            Ok(JsonRpcMessage::Notification(notification)) => {
+                debug!("Ignoring client notification {}", notification.method);
+            }
+            Ok(JsonRpcMessage::Response(_)) => {
+                debug!("Ignoring client response; backends do not send requests through the aggregator");
+            }
+            Err(e) => {
+                warn!("Invalid JSON-RPC from client: {}", e);
+                let _ = responses.send(JsonRpcResponse::error(
+                    RequestId::Null,
+                    JsonRpcError::parse_error(),
+                ));
+            }
+        }
+    }
+
+    /// Tell the monitor a backend's circuit changed state
+    async fn report_circuit(&self, event: CircuitEvent) {
+        let level = match event.to {
+            CircuitState::Open => LogLevel::Warning,
+            _ => LogLevel::Info,
+        };
+        self.log(level, event.to_string()).await;
+
+        if let Some(ref client) = self.ipc_client {
+            let change = CircuitChange {
+                proxy_id: self.proxy_id.clone(),
+                backend: event.name,
+                from: circuit_state(event.from),
+                to: circuit_state(event.to),
+                consecutive_failures: event.consecutive_failures,
+                last_error: event.last_error,
+                timestamp: chrono::Utc::now(),
+            };
+            if let Err(e) = client.send(IpcMessage::CircuitChanged(change)).await {
+                warn!("Failed to send circuit change: {}", e);
+            }
+        }
+    }
+
+    async fn log(&self, level: LogLevel, message: String) {
+        if let Some(ref client) = self.ipc_client {
+            let message = self.redactor.redact_text(&message);
+            let log_entry = LogEntry::new(level, message, self.proxy_id.clone());
+            if let Err(e) = client.send(IpcMessage::LogEntry(log_entry)).await {
+                warn!("Failed to send log entry: {}", e);
+            }
+        }
+    }
+}
+
+fn circuit_state(state: CircuitState) -> mcp_common::CircuitState {
+    match state {
+        CircuitState::Closed => mcp_common::CircuitState::Closed,
+        CircuitState::Open => mcp_common::CircuitState::Open,
+        CircuitState::HalfOpen => mcp_common::CircuitState::HalfOpen,
+    }
+}
+
+impl TransportConfig {
+    /// Build an aggregating configuration from a backends file
+    pub fn aggregate_from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(TransportConfig::Aggregate {
+            config: AggregateConfig::from_file(path)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balance::Strategy;
+    use mcp_core::messages::{StandardCapabilities, ToolCapabilities};
+    use std::collections::HashMap;
+
+    /// Answers list calls from canned pages and echoes everything else
+    struct FakeBackend {
+        pages: HashMap<String, Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl Backend for FakeBackend {
+        async fn request(
+            &self,
+            method: &str,
+            params: Value,
+            _session: Option<&str>,
+        ) -> Result<Value, JsonRpcError> {
+            if let Some(pages) = self.pages.get(method) {
+                let index = params.get("cursor").and_then(Value::as_u64).unwrap_or(0) as usize;
+                return Ok(pages[index].clone());
+            }
+            Ok(json!({ "method": method, "params": params }))
+        }
+    }
+
+    fn tools_capabilities() -> Capabilities {
+        Capabilities {
+            standard: StandardCapabilities {
+                tools: Some(ToolCapabilities::default()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn aggregator() -> Aggregator {
+        let mut aggregator = Aggregator::new("hub", DEFAULT_SEPARATOR);
+
+        let mut github = HashMap::new();
+        github.insert(
+            "tools/list".to_string(),
+            vec![
+                json!({ "tools": [{ "name": "create_issue" }], "nextCursor": 1 }),
+                json!({ "tools": [{ "name": "search" }] }),
+            ],
+        );
+        aggregator.add_backend(
+            "github",
+            tools_capabilities(),
+            Arc::new(FakeBackend { pages: github }),
+        );
+
+        let mut files = HashMap::new();
+        files.insert(
+            "tools/list".to_string(),
+            vec![json!({ "tools": [{ "name": "search" }] })],
+        );
+        aggregator.add_backend(
+            "files",
+            tools_capabilities(),
+            Arc::new(FakeBackend { pages: files }),
+        );
+
+        aggregator
+    }
+
+    #[tokio::test]
+    async fn test_merged_catalog_is_namespaced() {
+        let result = aggregator()
+            .dispatch("tools/list", json!({}), None)
+            .await
+            .unwrap();
+        let names: Vec<&str> = result["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(
+            names,
+            vec!["github__create_issue", "github__search", "files__search"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_calls_are_demultiplexed() {
+        let aggregator = aggregator();
+
+        let result = aggregator
+            .dispatch(
+                "tools/call",
+                json!({ "name": "files__search", "arguments": { "q": "x" } }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result["params"],
+            json!({ "name": "search", "arguments": { "q": "x" } })
+        );
+
+        let result = aggregator
+            .dispatch(
+                "resources/read",
+                json!({ "uri": "github+repo://a/b" }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["params"]["uri"], "repo://a/b");
+
+        let error = aggregator
+            .dispatch("tools/call", json!({ "name": "nope__search" }), None)
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("Invalid params") || error.code == -32602);
+
+        assert_eq!(
+            aggregator
+                .dispatch("sampling/createMessage", json!({}), None)
+                .await
+                .unwrap_err()
+                .code,
+            -32601
+        );
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config: AggregateConfig = toml::from_str(
+            r#"
+            [[backend]]
+            name = "github"
+            command = "npx -y @modelcontextprotocol/server-github"
+
+            [[backend]]
+            name = "search"
+            transport = "http-sse"
+            url = "http://localhost:3000/sse"
+            critical = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.separator, DEFAULT_SEPARATOR);
+        assert!(config.backends[1].critical && !config.backends[0].critical);
+        assert_eq!(config.circuit_breaker, BreakerConfig::default());
+        assert!(config.validate().is_ok());
+
+        let mut no_threshold = config.clone();
+        no_threshold.circuit_breaker = BreakerConfig::default().failure_threshold(0);
+        assert!(no_threshold.validate().is_err());
+
+        let mut duplicate = config.clone();
+        duplicate.backends[1].name = "github".to_string();
+        assert!(duplicate.validate().is_err());
+
+        let mut bad_name = config.clone();
+        bad_name.backends[0].name = "git_hub".to_string();
+        bad_name.separator = "_".to_string();
+        assert!(bad_name.validate().is_err());
+
+        let mut missing_url = config;
+        missing_url.backends[1].url = None;
+        assert!(missing_url.validate().is_err());
+    }
+
+    #[test]
+    fn test_replicas_are_configured_per_backend() {
+        let config: AggregateConfig = toml::from_str(
+            r#"
+            [[backend]]
+            name = "search"
+            transport = "http-stream"
+            replicas = ["http://10.0.0.1:3000/mcp", "http://10.0.0.2:3000/mcp"]
+
+            [backend.balance]
+            strategy = "sticky"
+            eject_for = "1m"
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        let backend = &config.backends[0];
+        assert_eq!(backend.balance.strategy, Strategy::Sticky);
+        assert_eq!(backend.balance.eject_after, 3);
+        assert_eq!(backend.balance.eject_for, Duration::from_secs(60));
+        let urls: Vec<_> = backend
+            .replica_configs()
+            .into_iter()
+            .map(|replica| replica.url.unwrap())
+            .collect();
+        assert_eq!(
+            urls,
+            ["http://10.0.0.1:3000/mcp", "http://10.0.0.2:3000/mcp"]
+        );
+
+        let mut both = config.clone();
+        both.backends[0].url = Some("http://10.0.0.3:3000/mcp".to_string());
+        assert!(both.validate().is_err());
+        let mut never_ejected = config;
+        never_ejected.backends[0].balance.eject_after = 0;
+        assert!(never_ejected.validate().is_err());
+    }
+}
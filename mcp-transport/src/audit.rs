@@ -0,0 +1,649 @@
+//! Tamper-evident audit trail of tool calls.
+//!
+//! With an audit log configured the proxy appends one JSON line per
+//! `tools/call`: when it happened, which client asked, which server ran it, a
+//! SHA-256 of the arguments (never the arguments themselves), how it turned
+//! out and how long it took. Calls an interceptor blocked are recorded too.
+//!
+//! Each record carries the hash of the one before it and a hash of its own
+//! content, so editing, removing or reordering records breaks the chain from
+//! that point on. [`verify`] walks the chain and reports the first break, and
+//! the hash of the last record. Kept elsewhere, that hash also lets a later
+//! verification notice a log that was cut short or replaced. A log is picked up where it left off when the
+//! proxy starts again.
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use mcp_core::messages::{JsonRpcMessage, RequestId};
+use mcp_core::outcome::{OutcomeKind, OutcomeRules};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::bundle::sha256_hex;
+
+/// `prev` of the first record in a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// How an audited tool call turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStatus {
+    /// The tool reported success
+    Success,
+    /// The tool reported an error (`isError`)
+    Failure,
+    /// The tool succeeded with errors or warnings mixed into its output
+    Partial,
+    /// The server answered with a JSON-RPC error
+    RpcError,
+    /// An interceptor blocked the call before it reached the server
+    Blocked,
+    /// The proxy stopped or the backend was replaced before an answer came
+    Unanswered,
+}
+
+impl fmt::Display for AuditStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AuditStatus::Success => "success",
+            AuditStatus::Failure => "failure",
+            AuditStatus::Partial => "partial",
+            AuditStatus::RpcError => "rpc_error",
+            AuditStatus::Blocked => "blocked",
+            AuditStatus::Unanswered => "unanswered",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A finished tool call, before it is chained into the log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    pub client: String,
+    pub server: String,
+    pub tool: String,
+    /// SHA-256 (hex) of the arguments as JSON with sorted keys
+    pub arguments_sha256: String,
+    pub status: AuditStatus,
+    /// `None` when the call was blocked or never answered
+    pub duration_ms: Option<u64>,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position in the log, starting at 1
+    pub seq: u64,
+    /// RFC 3339 time the record was written
+    pub timestamp: String,
+    pub client: String,
+    pub server: String,
+    pub tool: String,
+    pub arguments_sha256: String,
+    pub status: AuditStatus,
+    pub duration_ms: Option<u64>,
+    /// Hash of the previous record, [`GENESIS_HASH`] for the first
+    pub prev: String,
+    /// SHA-256 (hex) of this record serialized with an empty `hash`
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// The hash this record should carry
+    fn expected_hash(&self) -> Result<String> {
+        let unhashed = AuditRecord {
+            hash: String::new(),
+            ..self.clone()
+        };
+        Ok(sha256_hex(&serde_json::to_vec(&unhashed)?))
+    }
+}
+
+#[derive(Debug)]
+struct PendingCall {
+    tool: String,
+    arguments_sha256: String,
+    started: Instant,
+}
+
+/// Pairs `tools/call` requests with their answers
+///
+/// Takes the current time as an argument and does no I/O.
+#[derive(Debug)]
+pub struct AuditTrail {
+    /// Authenticated identity of the client, see [`crate::auth`]
+    identity: Option<String>,
+    client: String,
+    server: String,
+    outcomes: OutcomeRules,
+    pending: HashMap<RequestId, PendingCall>,
+}
+
+impl AuditTrail {
+    /// Track calls to `server`, classifying results with `outcomes`
+    pub fn new(server: impl Into<String>, outcomes: OutcomeRules) -> Self {
+        Self {
+            identity: None,
+            client: "unknown".to_string(),
+            server: server.into(),
+            outcomes,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Attribute later calls to another server, e.g. after a backend swap
+    pub fn set_server(&mut self, server: impl Into<String>) {
+        self.server = server.into();
+    }
+
+    /// Attribute later calls to an authenticated identity as well
+    pub fn set_identity(&mut self, identity: impl Into<String>) {
+        self.identity = Some(identity.into());
+    }
+
+    /// Record a line the client sent to the server
+    ///
+    /// `initialize` names the client for the calls that follow.
+    pub fn observe_client(&mut self, line: &str, now: Instant) {
+        let Ok(JsonRpcMessage::Request(request)) = serde_json::from_str(line.trim()) else {
+            return;
+        };
+        let params = request.params.unwrap_or_default();
+        match request.method.as_str() {
+            "initialize" => {
+                if let Some(name) = params.pointer("/clientInfo/name").and_then(Value::as_str) {
+                    self.client = match params
+                        .pointer("/clientInfo/version")
+                        .and_then(Value::as_str)
+                    {
+                        Some(version) => format!("{} {}", name, version),
+                        None => name.to_string(),
+                    };
+                }
+            }
+            "tools/call" => {
+                let (tool, arguments_sha256) = call_target(&params);
+                self.pending.insert(
+                    request.id,
+                    PendingCall {
+                        tool,
+                        arguments_sha256,
+                        started: now,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a line the server sent; returns the finished call it answers
+    pub fn observe_server(&mut self, line: &str, now: Instant) -> Option<AuditEvent> {
+        let Ok(JsonRpcMessage::Response(response)) = serde_json::from_str(line.trim()) else {
+            return None;
+        };
+        let call = self.pending.remove(&response.id)?;
+        let status = match (&response.error, &response.result) {
+            (Some(_), _) => AuditStatus::RpcError,
+            (None, result) => {
+                let result = result.clone().unwrap_or_default();
+                match self.outcomes.classify(&call.tool, &result).kind() {
+                    OutcomeKind::Success => AuditStatus::Success,
+                    OutcomeKind::Failure => AuditStatus::Failure,
+                    OutcomeKind::Partial => AuditStatus::Partial,
+                }
+            }
+        };
+        let duration_ms = now.duration_since(call.started).as_millis() as u64;
+        Some(self.event(call.tool, call.arguments_sha256, status, Some(duration_ms)))
+    }
+
+    /// Record a client line an interceptor kept from the server
+    pub fn observe_blocked(&mut self, line: &str) -> Option<AuditEvent> {
+        let Ok(JsonRpcMessage::Request(request)) = serde_json::from_str(line.trim()) else {
+            return None;
+        };
+        if request.method != "tools/call" {
+            return None;
+        }
+        let (tool, arguments_sha256) = call_target(&request.params.unwrap_or_default());
+        Some(self.event(tool, arguments_sha256, AuditStatus::Blocked, None))
+    }
+
+    /// Give up on every call still waiting for an answer
+    pub fn abandon(&mut self) -> Vec<AuditEvent> {
+        let pending: Vec<PendingCall> = self.pending.drain().map(|(_, call)| call).collect();
+        pending
+            .into_iter()
+            .map(|call| {
+                self.event(
+                    call.tool,
+                    call.arguments_sha256,
+                    AuditStatus::Unanswered,
+                    None,
+                )
+            })
+            .collect()
+    }
+
+    fn event(
+        &self,
+        tool: String,
+        arguments_sha256: String,
+        status: AuditStatus,
+        duration_ms: Option<u64>,
+    ) -> AuditEvent {
+        let client = match self.identity {
+            Some(ref identity) => format!("{} ({})", identity, self.client),
+            None => self.client.clone(),
+        };
+        AuditEvent {
+            client,
+            server: self.server.clone(),
+            tool,
+            arguments_sha256,
+            status,
+            duration_ms,
+        }
+    }
+}
+
+/// Tool name and argument hash of a `tools/call`
+fn call_target(params: &Value) -> (String, String) {
+    let tool = params["name"].as_str().unwrap_or_default().to_string();
+    let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+    (tool, sha256_hex(canonical(&arguments).as_bytes()))
+}
+
+/// JSON text with object keys sorted, so equal arguments hash alike
+fn canonical(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), canonical(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Append-only, hash-chained audit log file
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    file: File,
+    seq: u64,
+    head: String,
+}
+
+impl AuditLog {
+    /// Open a log for appending, continuing the chain of an existing one
+    ///
+    /// Refuses a log whose last record cannot be read, rather than starting
+    /// a new chain after it.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let (seq, head) = match File::open(&path) {
+            Ok(file) => {
+                let mut last = None;
+                for line in BufReader::new(file).lines() {
+                    let line = line
+                        .with_context(|| format!("Failed to read audit log {}", path.display()))?;
+                    if !line.trim().is_empty() {
+                        last = Some(line);
+                    }
+                }
+                match last {
+                    Some(line) => {
+                        let record: AuditRecord = serde_json::from_str(&line).with_context(|| {
+                            format!("Last record of audit log {} is damaged; run `audit verify` on it", path.display())
+                        })?;
+                        (record.seq, record.hash)
+                    }
+                    None => (0, GENESIS_HASH.to_string()),
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (0, GENESIS_HASH.to_string()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open audit log {}", path.display()))
+            }
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+        Ok(Self {
+            path,
+            file,
+            seq,
+            head,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records written so far, including by earlier runs
+    pub fn len(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seq == 0
+    }
+
+    /// Hash of the last record
+    pub fn head(&self) -> &str {
+        &self.head
+    }
+
+    /// Chain an event onto the log and write it out
+    pub fn append(&mut self, event: AuditEvent) -> Result<AuditRecord> {
+        let mut record = AuditRecord {
+            seq: self.seq + 1,
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            client: event.client,
+            server: event.server,
+            tool: event.tool,
+            arguments_sha256: event.arguments_sha256,
+            status: event.status,
+            duration_ms: event.duration_ms,
+            prev: self.head.clone(),
+            hash: String::new(),
+        };
+        record.hash = record.expected_hash()?;
+
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.flush())
+            .with_context(|| format!("Failed to write audit log {}", self.path.display()))?;
+        self.seq = record.seq;
+        self.head = record.hash.clone();
+        Ok(record)
+    }
+}
+
+/// Result of checking an audit log's chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditVerification {
+    /// Every record is unaltered and in place
+    Intact {
+        records: u64,
+        /// Hash of the last record, [`GENESIS_HASH`] for an empty log
+        head: String,
+    },
+    /// The chain breaks at a line; records before it are intact
+    Broken { line: usize, reason: String },
+}
+
+impl AuditVerification {
+    pub fn is_intact(&self) -> bool {
+        matches!(self, AuditVerification::Intact { .. })
+    }
+}
+
+impl fmt::Display for AuditVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditVerification::Intact { records, head } => {
+                write!(f, "{} record(s) intact; head {}", records, head)
+            }
+            AuditVerification::Broken { line, reason } => {
+                write!(f, "Chain broken at line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+/// Check every record of an audit log against its hash and its predecessor
+///
+/// `since` is the head of an earlier verification; the chain must still
+/// contain that record.
+pub fn verify(path: impl AsRef<Path>, since: Option<&str>) -> Result<AuditVerification> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).with_context(|| format!("Failed to open audit log {}", path.display()))?;
+
+    let mut records = 0;
+    let mut head = GENESIS_HASH.to_string();
+    let mut lines = 0;
+    let mut found = since.is_none_or(|since| since == GENESIS_HASH);
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("Failed to read audit log {}", path.display()))?;
+        let broken = |reason: String| AuditVerification::Broken {
+            line: index + 1,
+            reason,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: AuditRecord = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(e) => return Ok(broken(format!("unreadable record: {}", e))),
+        };
+        if record.seq != records + 1 {
+            return Ok(broken(format!(
+                "expected record {}, found {}",
+                records + 1,
+                record.seq
+            )));
+        }
+        if record.prev != head {
+            return Ok(broken(format!(
+                "record {} does not follow the one before it",
+                record.seq
+            )));
+        }
+        if record.hash != record.expected_hash()? {
+            return Ok(broken(format!("record {} was altered", record.seq)));
+        }
+        found |= since == Some(record.hash.as_str());
+        records = record.seq;
+        head = record.hash;
+        lines = index + 1;
+    }
+    if !found {
+        return Ok(AuditVerification::Broken {
+            line: lines + 1,
+            reason: "the record of the earlier head is missing; the log was cut short or replaced"
+                .to_string(),
+        });
+    }
+    Ok(AuditVerification::Intact { records, head })
+}
+
+/// Audit trail and log of a proxied session
+#[derive(Debug)]
+pub struct Auditor {
+    trail: AuditTrail,
+    log: AuditLog,
+}
+
+impl Auditor {
+    /// Audit calls to `server` into the log at `path`
+    pub fn open(path: impl Into<PathBuf>, server: impl Into<String>) -> Result<Self> {
+        let log = AuditLog::open(path)?;
+        let outcomes = OutcomeRules::load_default().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring tool outcome rules: {}", e);
+            OutcomeRules::default()
+        });
+        Ok(Self {
+            trail: AuditTrail::new(server, outcomes),
+            log,
+        })
+    }
+
+    /// Attribute later calls to another server, e.g. after a backend swap
+    pub fn set_server(&mut self, server: impl Into<String>) {
+        self.trail.set_server(server);
+    }
+
+    /// Attribute later calls to an authenticated identity as well
+    pub fn set_identity(&mut self, identity: impl Into<String>) {
+        self.trail.set_identity(identity);
+    }
+
+    pub fn log(&self) -> &AuditLog {
+        &self.log
+    }
+
+    /// Note a line the client sent to the server
+    pub fn client_line(&mut self, line: &str) {
+        self.trail.observe_client(line, Instant::now());
+    }
+
+    /// Note a line the server sent, or the proxy sent on its behalf
+    pub fn server_line(&mut self, line: &str) -> Result<()> {
+        match self.trail.observe_server(line, Instant::now()) {
+            Some(event) => self.log.append(event).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Note a client line an interceptor blocked
+    pub fn blocked_line(&mut self, line: &str) -> Result<()> {
+        match self.trail.observe_blocked(line) {
+            Some(event) => self.log.append(event).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Record calls still waiting for an answer as unanswered
+    pub fn finish(&mut self) -> Result<()> {
+        for event in self.trail.abandon() {
+            self.log.append(event)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn trail() -> AuditTrail {
+        AuditTrail::new("python server.py", OutcomeRules::default())
+    }
+
+    #[test]
+    fn test_calls_are_paired_with_answers() {
+        let mut trail = trail();
+        let start = Instant::now();
+        trail.observe_client(
+            r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"clientInfo":{"name":"agent","version":"1.2"}}}"#,
+            start,
+        );
+        trail.observe_client(
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo","arguments":{"b":1,"a":2}}}"#,
+            start,
+        );
+        trail.observe_client(
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo","arguments":{"a":2,"b":1}}}"#,
+            start,
+        );
+
+        let ok = trail
+            .observe_server(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"content":[{"type":"text","text":"hi"}]}}"#,
+                start + Duration::from_millis(12),
+            )
+            .unwrap();
+        assert_eq!(ok.client, "agent 1.2");
+        assert_eq!(ok.server, "python server.py");
+        assert_eq!(ok.status, AuditStatus::Success);
+        assert_eq!(ok.duration_ms, Some(12));
+
+        let failed = trail
+            .observe_server(
+                r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"bad"}}"#,
+                start,
+            )
+            .unwrap();
+        assert_eq!(failed.status, AuditStatus::RpcError);
+        // Key order does not change the argument hash
+        assert_eq!(ok.arguments_sha256, failed.arguments_sha256);
+
+        assert!(trail
+            .observe_server(r#"{"jsonrpc":"2.0","id":3,"result":{}}"#, start)
+            .is_none());
+        trail.observe_client(
+            r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"slow"}}"#,
+            start,
+        );
+        let abandoned = trail.abandon();
+        assert_eq!(abandoned.len(), 1);
+        assert_eq!(abandoned[0].status, AuditStatus::Unanswered);
+    }
+
+    #[test]
+    fn test_chain_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let event = |tool: &str| AuditEvent {
+            client: "agent".to_string(),
+            server: "server".to_string(),
+            tool: tool.to_string(),
+            arguments_sha256: sha256_hex(b"null"),
+            status: AuditStatus::Success,
+            duration_ms: Some(1),
+        };
+
+        let mut log = AuditLog::open(&path).unwrap();
+        log.append(event("a")).unwrap();
+        log.append(event("b")).unwrap();
+        drop(log);
+        // A later run continues the chain
+        let mut log = AuditLog::open(&path).unwrap();
+        assert_eq!(log.len(), 2);
+        let earlier = log.head().to_string();
+        let last = log.append(event("c")).unwrap();
+        assert_eq!(
+            verify(&path, Some(&earlier)).unwrap(),
+            AuditVerification::Intact {
+                records: 3,
+                head: last.hash
+            }
+        );
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replace(r#""tool":"b""#, r#""tool":"x""#)).unwrap();
+        assert_eq!(
+            verify(&path, None).unwrap(),
+            AuditVerification::Broken {
+                line: 2,
+                reason: "record 2 was altered".to_string()
+            }
+        );
+
+        let lines: Vec<&str> = text.lines().collect();
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(verify(&path, None)
+            .unwrap()
+            .to_string()
+            .starts_with("Chain broken at line 2: expected record 2"));
+
+        // Cut short after the earlier verification
+        std::fs::write(&path, format!("{}\n", lines[0])).unwrap();
+        assert!(verify(&path, None).unwrap().is_intact());
+        assert!(!verify(&path, Some(&earlier)).unwrap().is_intact());
+    }
+}
@@ -0,0 +1,441 @@
+//! Authentication and per-identity authorization of proxy clients.
+//!
+//! A [`ClientGate`] sits in front of the backends. When a client connects, an
+//! [`Authenticator`] turns what the transport knows about it
+//! ([`Credentials`]: an API key from HTTP headers, or the user ID on the other
+//! end of a Unix socket or stdio pipe) into an [`Identity`]. Every request the
+//! client then makes is put to the gate's [`AuthorizationHook`]s before it is
+//! forwarded; the first hook to object rejects it.
+//!
+//! Both are traits, so embedders can plug in their own. The built-in ones are
+//! driven by an auth file:
+//!
+//! ```toml
+//! [[identity]]
+//! name = "ci-agent"
+//! # Plain keys or their SHA-256, written as "sha256:<hex>"
+//! api_keys = ["sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"]
+//! uids = [1001]
+//! requests_per_minute = 60
+//!
+//! [identity.tools]
+//! allow = ["search_*", "read_file"]
+//! deny = ["delete_*"]
+//! ```
+//!
+//! `tools` is a [`ToolPolicy`], as used by the policy interceptor.
+
+use anyhow::{bail, Context, Result};
+use mcp_core::messages::JsonRpcRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::bundle::sha256_hex;
+use crate::interceptors::policy::ToolPolicy;
+
+/// Window `requests_per_minute` is counted over
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// What a transport can tell about the client on the other end
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// Key presented in an HTTP `Authorization: Bearer` or `X-API-Key` header
+    ApiKey(String),
+    /// User ID of the process on the other end of a Unix socket or pipe
+    PeerUid(u32),
+    /// Nothing to go by
+    None,
+}
+
+impl Credentials {
+    /// Credentials carried by HTTP request headers
+    pub fn from_headers<'a>(headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        for (name, value) in headers {
+            if name.eq_ignore_ascii_case("authorization") {
+                if let Some((scheme, key)) = value.trim().split_once(' ') {
+                    if scheme.eq_ignore_ascii_case("bearer") {
+                        return Credentials::ApiKey(key.trim().to_string());
+                    }
+                }
+            } else if name.eq_ignore_ascii_case("x-api-key") {
+                return Credentials::ApiKey(value.trim().to_string());
+            }
+        }
+        Credentials::None
+    }
+
+    /// Credentials of whoever feeds this process's stdin: the owner of the pipe
+    #[cfg(unix)]
+    pub fn stdio_peer() -> Self {
+        use std::os::fd::AsFd;
+        use std::os::unix::fs::MetadataExt;
+
+        std::io::stdin()
+            .as_fd()
+            .try_clone_to_owned()
+            .map(std::fs::File::from)
+            .and_then(|stdin| stdin.metadata())
+            .map_or(Credentials::None, |metadata| {
+                Credentials::PeerUid(metadata.uid())
+            })
+    }
+
+    /// Credentials of whoever feeds this process's stdin
+    #[cfg(not(unix))]
+    pub fn stdio_peer() -> Self {
+        Credentials::None
+    }
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Never print the key itself
+            Credentials::ApiKey(_) => write!(f, "API key"),
+            Credentials::PeerUid(uid) => write!(f, "uid {}", uid),
+            Credentials::None => write!(f, "no credentials"),
+        }
+    }
+}
+
+/// An authenticated client
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Identity {
+    pub name: String,
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Why a client was turned away at connect time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// The transport had no credentials to offer
+    MissingCredentials,
+    /// No identity has the API key
+    UnknownApiKey,
+    /// No identity lists the user ID
+    UidNotAllowed(u32),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingCredentials => write!(f, "Authentication required"),
+            AuthError::UnknownApiKey => write!(f, "Unknown API key"),
+            AuthError::UidNotAllowed(uid) => {
+                write!(f, "uid {} is not allowed to use this proxy", uid)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Decides who a client is
+pub trait Authenticator: Send + Sync {
+    fn authenticate(&self, credentials: &Credentials) -> Result<Identity, AuthError>;
+}
+
+/// Decides whether an identified client may make a request
+///
+/// Hooks are called for every request before it reaches a backend; `Err`
+/// holds the reason given to the client.
+pub trait AuthorizationHook: Send + Sync {
+    fn authorize(
+        &self,
+        identity: &Identity,
+        request: &JsonRpcRequest,
+        now: Instant,
+    ) -> Result<(), String>;
+}
+
+/// One `[[identity]]` of an auth file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdentityConfig {
+    pub name: String,
+    /// Keys that authenticate as this identity, plain or `sha256:<hex>`
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    /// User IDs that authenticate as this identity over Unix sockets and pipes
+    #[serde(default)]
+    pub uids: Vec<u32>,
+    /// Tools this identity may call
+    #[serde(default)]
+    pub tools: ToolPolicy,
+    /// Requests allowed per minute; unlimited when absent
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+}
+
+impl IdentityConfig {
+    fn has_key(&self, key: &str) -> bool {
+        let hashed = format!("sha256:{}", sha256_hex(key.as_bytes()));
+        self.api_keys
+            .iter()
+            .any(|known| match known.strip_prefix("sha256:") {
+                Some(_) => known.eq_ignore_ascii_case(&hashed),
+                None => known == key,
+            })
+    }
+}
+
+/// Identities allowed to use the proxy, from an auth file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    #[serde(default, rename = "identity")]
+    pub identities: Vec<IdentityConfig>,
+}
+
+impl AuthConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read auth file {}", path.display()))?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Invalid auth file {}", path.display()))?;
+        if config.identities.is_empty() {
+            bail!(
+                "Auth file {} defines no [[identity]]; every client would be refused",
+                path.display()
+            );
+        }
+        Ok(config)
+    }
+
+    fn identity(&self, name: &str) -> Option<&IdentityConfig> {
+        self.identities
+            .iter()
+            .find(|identity| identity.name == name)
+    }
+}
+
+impl Authenticator for AuthConfig {
+    fn authenticate(&self, credentials: &Credentials) -> Result<Identity, AuthError> {
+        let found = match credentials {
+            Credentials::ApiKey(key) => self
+                .identities
+                .iter()
+                .find(|identity| identity.has_key(key))
+                .ok_or(AuthError::UnknownApiKey)?,
+            Credentials::PeerUid(uid) => self
+                .identities
+                .iter()
+                .find(|identity| identity.uids.contains(uid))
+                .ok_or(AuthError::UidNotAllowed(*uid))?,
+            Credentials::None => return Err(AuthError::MissingCredentials),
+        };
+        Ok(Identity {
+            name: found.name.clone(),
+        })
+    }
+}
+
+/// Tool policies and rate limits of the identities in an auth file
+pub struct IdentityPolicies {
+    config: AuthConfig,
+    /// Recent request times by identity
+    requests: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl IdentityPolicies {
+    pub fn new(config: AuthConfig) -> Self {
+        Self {
+            config,
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl AuthorizationHook for IdentityPolicies {
+    fn authorize(
+        &self,
+        identity: &Identity,
+        request: &JsonRpcRequest,
+        now: Instant,
+    ) -> Result<(), String> {
+        let Some(config) = self.config.identity(&identity.name) else {
+            return Ok(());
+        };
+
+        if request.method == "tools/call" {
+            let params = request.params.clone().unwrap_or_default();
+            let tool = params["name"].as_str().unwrap_or_default();
+            config
+                .tools
+                .evaluate(tool, params.get("arguments"))
+                .map_err(|violation| format!("{} (identity '{}')", violation, identity))?;
+        }
+
+        if let Some(limit) = config.requests_per_minute {
+            let mut requests = self
+                .requests
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let recent = requests.entry(identity.name.clone()).or_default();
+            while recent
+                .front()
+                .is_some_and(|at| now.duration_since(*at) >= RATE_WINDOW)
+            {
+                recent.pop_front();
+            }
+            if recent.len() >= limit as usize {
+                return Err(format!(
+                    "Rate limit of {} requests per minute exceeded for identity '{}'",
+                    limit, identity
+                ));
+            }
+            recent.push_back(now);
+        }
+        Ok(())
+    }
+}
+
+/// Authentication and authorization in front of the backends
+#[derive(Clone)]
+pub struct ClientGate {
+    authenticator: Arc<dyn Authenticator>,
+    hooks: Vec<Arc<dyn AuthorizationHook>>,
+}
+
+impl ClientGate {
+    pub fn new(authenticator: Arc<dyn Authenticator>) -> Self {
+        Self {
+            authenticator,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Gate driven by an auth file: its identities and their policies
+    pub fn from_config(config: AuthConfig) -> Self {
+        Self::new(Arc::new(config.clone())).with_hook(Arc::new(IdentityPolicies::new(config)))
+    }
+
+    /// Also put every request to `hook`
+    pub fn with_hook(mut self, hook: Arc<dyn AuthorizationHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    pub fn authenticate(&self, credentials: &Credentials) -> Result<Identity, AuthError> {
+        self.authenticator.authenticate(credentials)
+    }
+
+    /// Check a request against every hook, in the order they were added
+    pub fn authorize(
+        &self,
+        identity: &Identity,
+        request: &JsonRpcRequest,
+        now: Instant,
+    ) -> Result<(), String> {
+        self.hooks
+            .iter()
+            .try_for_each(|hook| hook.authorize(identity, request, now))
+    }
+}
+
+impl fmt::Debug for ClientGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientGate")
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gate() -> ClientGate {
+        let config: AuthConfig = toml::from_str(
+            r#"
+            [[identity]]
+            name = "ci"
+            api_keys = ["sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"]
+            requests_per_minute = 2
+            [identity.tools]
+            allow = ["search_*"]
+
+            [[identity]]
+            name = "ops"
+            api_keys = ["plain-key"]
+            uids = [1001]
+            "#,
+        )
+        .unwrap();
+        ClientGate::from_config(config)
+    }
+
+    fn call(tool: &str) -> JsonRpcRequest {
+        JsonRpcRequest::new(1, "tools/call", json!({ "name": tool, "arguments": {} }))
+    }
+
+    #[test]
+    fn test_credentials_identify_clients() {
+        let gate = gate();
+        let headers = [
+            ("Content-Type", "application/json"),
+            ("authorization", "Bearer test"),
+        ];
+        let ci = gate
+            .authenticate(&Credentials::from_headers(headers))
+            .unwrap();
+        assert_eq!(ci.name, "ci");
+
+        let ops = gate
+            .authenticate(&Credentials::from_headers([("X-API-Key", "plain-key")]))
+            .unwrap();
+        assert_eq!(ops.name, "ops");
+        assert_eq!(gate.authenticate(&Credentials::PeerUid(1001)), Ok(ops));
+
+        assert_eq!(
+            gate.authenticate(&Credentials::ApiKey("guess".to_string())),
+            Err(AuthError::UnknownApiKey)
+        );
+        assert_eq!(
+            gate.authenticate(&Credentials::PeerUid(0)),
+            Err(AuthError::UidNotAllowed(0))
+        );
+        assert_eq!(
+            gate.authenticate(&Credentials::from_headers([("Authorization", "Basic abc")])),
+            Err(AuthError::MissingCredentials)
+        );
+    }
+
+    #[test]
+    fn test_requests_checked_per_identity() {
+        let gate = gate();
+        let ci = Identity {
+            name: "ci".to_string(),
+        };
+        let ops = Identity {
+            name: "ops".to_string(),
+        };
+        let start = Instant::now();
+
+        let denied = gate
+            .authorize(&ci, &call("delete_repo"), start)
+            .unwrap_err();
+        assert!(denied.contains("not in the policy allowlist"));
+        assert!(gate.authorize(&ops, &call("delete_repo"), start).is_ok());
+
+        // Denied calls do not count toward the limit
+        assert!(gate.authorize(&ci, &call("search_code"), start).is_ok());
+        assert!(gate.authorize(&ci, &call("search_docs"), start).is_ok());
+        let limited = gate
+            .authorize(&ci, &call("search_code"), start)
+            .unwrap_err();
+        assert!(limited.starts_with("Rate limit of 2 requests per minute"));
+        assert!(gate
+            .authorize(&ci, &call("search_code"), start + RATE_WINDOW)
+            .is_ok());
+    }
+}
@@ -0,0 +1,529 @@
+//! Load balancing over replicas of one backend.
+//!
+//! A backend in the backends file may list `replicas`, interchangeable copies
+//! of the same server given by their command or URL. A [`BalancedBackend`]
+//! spreads the backend's requests over them with the strategy set in its
+//! `[backend.balance]` table:
+//!
+//! * `round-robin` takes the replicas in turn.
+//! * `least-in-flight` takes the replica with the fewest requests waiting.
+//! * `sticky` keeps every request of one downstream session on one replica,
+//!   so state the server keeps for it (for HTTP replicas, the session named
+//!   by their `Mcp-Session-Id`) stays where it was created. Each new session
+//!   is pinned to the replica holding the fewest sessions, and moves to
+//!   another replica only when its own is ejected.
+//!
+//! The downstream session is the one passed to [`Backend::request`]: the
+//! client's `Mcp-Session-Id` for frontends that serve several clients, or
+//! `None` for the aggregating proxy's single stdio client. A frontend calls
+//! [`Backend::end_session`] when a session ends, which releases its pin.
+//!
+//! Whatever the strategy, `resources/unsubscribe` goes to the replica that
+//! took the same session's matching `resources/subscribe`, and
+//! `logging/setLevel` reaches every replica.
+//!
+//! A request fails when the connection breaks or times out, or when the
+//! replica answers with an internal error. After `eject_after` failures in a
+//! row, or once its connection is lost, a replica is ejected: it gets no
+//! requests for `eject_for`, after which it is tried again and one more
+//! failure ejects it again. If every replica is ejected, requests are spread
+//! over all of them rather than refused.
+//!
+//! ```toml
+//! [[backend]]
+//! name = "search"
+//! transport = "http-stream"
+//! replicas = ["http://10.0.0.1:3000/mcp", "http://10.0.0.2:3000/mcp"]
+//!
+//! [backend.balance]
+//! strategy = "least-in-flight"
+//! eject_after = 3
+//! eject_for = "30s"
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use mcp_core::messages::JsonRpcError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::aggregator::Backend;
+
+/// JSON-RPC code of an internal error, also used for failed connections
+const INTERNAL_ERROR: i32 = -32603;
+
+/// How a replica is chosen for a request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// The replicas in turn
+    #[default]
+    RoundRobin,
+    /// The replica with the fewest requests waiting
+    LeastInFlight,
+    /// The same replica for each session until it is ejected
+    Sticky,
+}
+
+/// Balancing of one backend's replicas
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceConfig {
+    pub strategy: Strategy,
+
+    /// Failed requests in a row that eject a replica
+    pub eject_after: u32,
+
+    /// How long an ejected replica gets no requests
+    #[serde(with = "humantime_serde")]
+    pub eject_for: Duration,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        Self {
+            strategy: Strategy::default(),
+            eject_after: 3,
+            eject_for: Duration::from_secs(30),
+        }
+    }
+}
+
+impl BalanceConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.eject_after == 0 {
+            bail!("eject_after must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+struct Replica {
+    backend: Arc<dyn Backend>,
+    in_flight: AtomicUsize,
+    failures: AtomicU32,
+    ejected_until: Mutex<Option<Instant>>,
+}
+
+impl Replica {
+    fn ejected_until(&self) -> MutexGuard<'_, Option<Instant>> {
+        self.ejected_until.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_ejected(&self, now: Instant) -> bool {
+        self.ejected_until().is_some_and(|until| until > now)
+    }
+}
+
+/// Counts a request as waiting on a replica until dropped, also when the
+/// request is cancelled
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn start(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self(count)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Replicas of one backend behind a single [`Backend`]
+pub struct BalancedBackend {
+    name: String,
+    config: BalanceConfig,
+    replicas: Vec<Replica>,
+    /// Next replica in turn for round-robin
+    next: AtomicUsize,
+    /// Replica holding each downstream session for sticky balancing
+    pinned: Mutex<HashMap<Option<String>, usize>>,
+    /// Replica that took each subscription, by session and URI
+    subscriptions: Mutex<HashMap<(Option<String>, String), usize>>,
+}
+
+impl BalancedBackend {
+    /// Balance `name` over `replicas`
+    pub fn new(
+        name: impl Into<String>,
+        config: BalanceConfig,
+        replicas: Vec<Arc<dyn Backend>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            replicas: replicas
+                .into_iter()
+                .map(|backend| Replica {
+                    backend,
+                    in_flight: AtomicUsize::new(0),
+                    failures: AtomicU32::new(0),
+                    ejected_until: Mutex::new(None),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+            pinned: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replicas currently given requests
+    pub fn healthy_replicas(&self) -> usize {
+        let now = Instant::now();
+        self.replicas
+            .iter()
+            .filter(|replica| !replica.backend.is_lost() && !replica.is_ejected(now))
+            .count()
+    }
+
+    fn pinned(&self) -> MutexGuard<'_, HashMap<Option<String>, usize>> {
+        self.pinned.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn subscriptions(&self) -> MutexGuard<'_, HashMap<(Option<String>, String), usize>> {
+        self.subscriptions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replicas a request may go to: the healthy ones, or every one not lost
+    /// when none is healthy
+    fn candidates(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let live: Vec<usize> = (0..self.replicas.len())
+            .filter(|&index| !self.replicas[index].backend.is_lost())
+            .collect();
+        let healthy: Vec<usize> = live
+            .iter()
+            .copied()
+            .filter(|&index| !self.replicas[index].is_ejected(now))
+            .collect();
+        if healthy.is_empty() {
+            live
+        } else {
+            healthy
+        }
+    }
+
+    fn pick(&self, session: Option<&str>) -> Option<usize> {
+        let candidates = self.candidates();
+        let least_in_flight = || {
+            candidates
+                .iter()
+                .copied()
+                .min_by_key(|&index| self.replicas[index].in_flight.load(Ordering::SeqCst))
+        };
+        match self.config.strategy {
+            Strategy::RoundRobin => {
+                let turn = self.next.fetch_add(1, Ordering::SeqCst);
+                candidates.get(turn % candidates.len().max(1)).copied()
+            }
+            Strategy::LeastInFlight => least_in_flight(),
+            Strategy::Sticky => {
+                let mut pinned = self.pinned();
+                let key = session.map(str::to_string);
+                if let Some(&index) = pinned.get(&key).filter(|index| candidates.contains(index)) {
+                    return Some(index);
+                }
+                let sessions_on = |index: usize| pinned.values().filter(|&&i| i == index).count();
+                let index = candidates.iter().copied().min_by_key(|&index| {
+                    (
+                        sessions_on(index),
+                        self.replicas[index].in_flight.load(Ordering::SeqCst),
+                    )
+                })?;
+                if let Some(previous) = pinned.insert(key, index) {
+                    info!(
+                        "Session {} of '{}' moved from replica {} to replica {}",
+                        session.unwrap_or("(stdio)"),
+                        self.name,
+                        previous + 1,
+                        index + 1
+                    );
+                }
+                Some(index)
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        index: usize,
+        method: &str,
+        params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        let replica = &self.replicas[index];
+        let result = {
+            let _waiting = InFlight::start(&replica.in_flight);
+            replica.backend.request(method, params, session).await
+        };
+
+        let failed = replica.backend.is_lost()
+            || result
+                .as_ref()
+                .is_err_and(|error| error.code == INTERNAL_ERROR);
+        if !failed {
+            replica.failures.store(0, Ordering::SeqCst);
+            return result;
+        }
+        let failures = replica.failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if failures >= self.config.eject_after || replica.backend.is_lost() {
+            replica.failures.store(0, Ordering::SeqCst);
+            *replica.ejected_until() = Some(Instant::now() + self.config.eject_for);
+            warn!(
+                "Ejected replica {} of '{}' for {:?} after {} failed request(s)",
+                index + 1,
+                self.name,
+                self.config.eject_for,
+                failures
+            );
+        }
+        result
+    }
+
+    fn unavailable(&self) -> JsonRpcError {
+        JsonRpcError::internal_error(format!("No replica of '{}' is available", self.name))
+    }
+}
+
+#[async_trait]
+impl Backend for BalancedBackend {
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        let uri = params
+            .get("uri")
+            .and_then(Value::as_str)
+            .map(|uri| (session.map(str::to_string), uri.to_string()));
+        match (method, uri) {
+            ("logging/setLevel", _) => {
+                let mut outcome = Err(self.unavailable());
+                for index in self.candidates() {
+                    let result = self.send(index, method, params.clone(), session).await;
+                    if outcome.is_err() {
+                        outcome = result;
+                    }
+                }
+                outcome
+            }
+            ("resources/subscribe", Some(uri)) => {
+                let index = self.pick(session).ok_or_else(|| self.unavailable())?;
+                let result = self.send(index, method, params, session).await;
+                if result.is_ok() {
+                    self.subscriptions().insert(uri, index);
+                }
+                result
+            }
+            ("resources/unsubscribe", Some(uri)) => {
+                let subscribed = self.subscriptions().remove(&uri);
+                let index = subscribed
+                    .or_else(|| self.pick(session))
+                    .ok_or_else(|| self.unavailable())?;
+                self.send(index, method, params, session).await
+            }
+            _ => {
+                let index = self.pick(session).ok_or_else(|| self.unavailable())?;
+                self.send(index, method, params, session).await
+            }
+        }
+    }
+
+    fn end_session(&self, session: &str) {
+        self.pinned().remove(&Some(session.to_string()));
+        self.subscriptions()
+            .retain(|(subscriber, _), _| subscriber.as_deref() != Some(session));
+        for replica in &self.replicas {
+            replica.backend.end_session(session);
+        }
+    }
+
+    fn is_lost(&self) -> bool {
+        self.replicas
+            .iter()
+            .all(|replica| replica.backend.is_lost())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::AtomicBool;
+
+    /// Answers with its number, or fails like a broken connection
+    struct Member {
+        number: usize,
+        failing: AtomicBool,
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Backend for Member {
+        async fn request(
+            &self,
+            method: &str,
+            _params: Value,
+            _session: Option<&str>,
+        ) -> Result<Value, JsonRpcError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(JsonRpcError::internal_error("connection reset"));
+            }
+            if method == "slow" {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            Ok(json!({ "replica": self.number }))
+        }
+    }
+
+    fn group(strategy: Strategy, size: usize) -> (Arc<BalancedBackend>, Vec<Arc<Member>>) {
+        let members: Vec<Arc<Member>> = (0..size)
+            .map(|number| {
+                Arc::new(Member {
+                    number,
+                    failing: AtomicBool::new(false),
+                    requests: AtomicUsize::new(0),
+                })
+            })
+            .collect();
+        let replicas = members
+            .iter()
+            .map(|member| member.clone() as Arc<dyn Backend>)
+            .collect();
+        let config = BalanceConfig {
+            strategy,
+            ..BalanceConfig::default()
+        };
+        (
+            Arc::new(BalancedBackend::new("search", config, replicas)),
+            members,
+        )
+    }
+
+    async fn replica(backend: &BalancedBackend, method: &str) -> Option<u64> {
+        in_session(backend, None, method).await
+    }
+
+    async fn in_session(
+        backend: &BalancedBackend,
+        session: Option<&str>,
+        method: &str,
+    ) -> Option<u64> {
+        let result = backend.request(method, json!({}), session).await.ok()?;
+        result["replica"].as_u64()
+    }
+
+    #[tokio::test]
+    async fn test_strategies_pick_replicas() {
+        let (round_robin, _) = group(Strategy::RoundRobin, 3);
+        let mut picked = Vec::new();
+        for _ in 0..4 {
+            picked.push(replica(&round_robin, "tools/call").await.unwrap());
+        }
+        assert_eq!(picked, [0, 1, 2, 0]);
+
+        // A request waiting on the first replica sends the next to another
+        let (least, _) = group(Strategy::LeastInFlight, 2);
+        let waiting = tokio::spawn({
+            let least = least.clone();
+            async move { least.request("slow", json!({}), None).await }
+        });
+        tokio::task::yield_now().await;
+        assert_eq!(replica(&least, "tools/call").await, Some(1));
+        waiting.abort();
+
+        let (sticky, _) = group(Strategy::Sticky, 3);
+        for _ in 0..3 {
+            assert_eq!(replica(&sticky, "tools/call").await, Some(0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sticky_sessions_are_pinned_separately() {
+        let (sticky, _) = group(Strategy::Sticky, 2);
+        for _ in 0..3 {
+            assert_eq!(
+                in_session(&sticky, Some("alice"), "tools/call").await,
+                Some(0)
+            );
+            assert_eq!(
+                in_session(&sticky, Some("bob"), "tools/call").await,
+                Some(1)
+            );
+        }
+
+        // An ended session's pin is released for the next one
+        sticky.end_session("alice");
+        assert_eq!(
+            in_session(&sticky, Some("carol"), "tools/call").await,
+            Some(0)
+        );
+        assert_eq!(
+            in_session(&sticky, Some("bob"), "tools/call").await,
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failing_replicas_are_ejected_and_tried_again() {
+        tokio::time::pause();
+        let (sticky, members) = group(Strategy::Sticky, 2);
+        members[0].failing.store(true, Ordering::SeqCst);
+
+        // Three failures in a row move the session to the other replica
+        for _ in 0..3 {
+            assert!(replica(&sticky, "tools/call").await.is_none());
+        }
+        assert_eq!(sticky.healthy_replicas(), 1);
+        assert_eq!(replica(&sticky, "tools/call").await, Some(1));
+        assert_eq!(replica(&sticky, "tools/call").await, Some(1));
+
+        // Once the ejection is over the first replica takes requests again
+        members[0].failing.store(false, Ordering::SeqCst);
+        members[1].failing.store(true, Ordering::SeqCst);
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert_eq!(sticky.healthy_replicas(), 2);
+        for _ in 0..3 {
+            assert!(replica(&sticky, "tools/call").await.is_none());
+        }
+        assert_eq!(replica(&sticky, "tools/call").await, Some(0));
+        assert_eq!(members[0].requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_follows_its_subscription() {
+        let (round_robin, members) = group(Strategy::RoundRobin, 2);
+        let uri = json!({ "uri": "file:///a" });
+        round_robin
+            .request("resources/subscribe", uri.clone(), None)
+            .await
+            .unwrap();
+        let unsubscribed = round_robin
+            .request("resources/unsubscribe", uri, None)
+            .await
+            .unwrap();
+        assert_eq!(unsubscribed["replica"], 0);
+
+        round_robin
+            .request("logging/setLevel", json!({ "level": "debug" }), None)
+            .await
+            .unwrap();
+        let requests: Vec<usize> = members
+            .iter()
+            .map(|member| member.requests.load(Ordering::SeqCst))
+            .collect();
+        assert_eq!(requests, [3, 1]);
+    }
+}
@@ -1,26 +1,45 @@
 use anyhow::Result;
-use mcp_common::{IpcClient, IpcMessage};
-use std::collections::VecDeque;
+use mcp_common::{HandshakeTimeout, IpcClient, IpcMessage, PeerRole};
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::{mpsc, Mutex};
 use tokio::time::{sleep, Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-const MAX_BUFFER_SIZE: usize = 10_000; // Maximum number of messages to buffer
+use crate::spool::{Spool, SpoolConfig, SpoolStats};
+
 const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
 const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
 const RECONNECT_BACKOFF_FACTOR: u32 = 2;
 
 pub struct BufferedIpcClient {
-    buffer: Arc<Mutex<VecDeque<IpcMessage>>>,
+    buffer: Arc<Mutex<Spool>>,
     sender: mpsc::Sender<IpcMessage>,
     shutdown_tx: Option<mpsc::Sender<()>>,
     task_handle: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl BufferedIpcClient {
+    /// Connect to the monitor, buffering messages in memory while it is unavailable
     pub async fn new(socket_path: String) -> Self {
-        let buffer = Arc::new(Mutex::new(VecDeque::new()));
+        Self::start(socket_path, Spool::in_memory(SpoolConfig::default()))
+    }
+
+    /// Connect to the monitor, buffering messages in a spool while it is
+    /// unavailable; a spool file left by a previous run is flushed first
+    pub async fn with_spool(socket_path: String, config: SpoolConfig) -> Result<Self> {
+        let spool = Spool::open(config)?;
+        if !spool.is_empty() {
+            info!(
+                "{} message(s) spooled by a previous run are waiting for the monitor",
+                spool.len()
+            );
+        }
+        Ok(Self::start(socket_path, spool))
+    }
+
+    fn start(socket_path: String, spool: Spool) -> Self {
+        let buffer = Arc::new(Mutex::new(spool));
         let (sender, receiver) = mpsc::channel(1000);
         let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
 
@@ -32,33 +51,70 @@ impl BufferedIpcClient {
             shutdown_rx,
         ));
 
-        let client = Self {
+        Self {
             buffer,
             sender,
             shutdown_tx: Some(shutdown_tx),
             task_handle: Some(task_handle),
-        };
-
-        client
+        }
     }
 
     pub async fn send(&self, message: IpcMessage) -> Result<()> {
         // Try to send through the channel (which will handle buffering if needed)
-        if let Err(_) = self.sender.send(message.clone()).await {
-            // If channel is full or closed, add directly to buffer
-            let mut buffer = self.buffer.lock().await;
-            if buffer.len() < MAX_BUFFER_SIZE {
-                buffer.push_back(message);
-            } else {
-                warn!("Buffer full, dropping message");
-            }
+        if let Err(mpsc::error::SendError(message)) = self.sender.send(message).await {
+            // If the channel is closed, add directly to the buffer
+            Self::spool(&self.buffer, message).await;
         }
         Ok(())
     }
 
+    /// Counters of messages buffered while the monitor was unavailable
+    pub async fn spool_stats(&self) -> SpoolStats {
+        self.buffer.lock().await.stats()
+    }
+
+    async fn spool(buffer: &Mutex<Spool>, message: IpcMessage) {
+        let mut spool = buffer.lock().await;
+        let dropped = spool.stats().dropped;
+        if let Err(e) = spool.push(message, SystemTime::now()) {
+            error!("Failed to spool message for the monitor: {}", e);
+        }
+        // Warn once per burst rather than for every message
+        if spool.stats().dropped > dropped && dropped % 1000 == 0 {
+            warn!(
+                "Monitor spool full, dropping oldest messages ({})",
+                spool.stats()
+            );
+        }
+    }
+
+    /// Deliver spooled messages oldest first; returns false if the
+    /// connection failed on the way
+    async fn flush(ipc_client: &mut IpcClient, buffer: &Mutex<Spool>) -> bool {
+        let mut spool = buffer.lock().await;
+        if let Err(e) = spool.expire(SystemTime::now()) {
+            error!("Failed to expire spooled messages: {}", e);
+        }
+        if spool.is_empty() {
+            return true;
+        }
+        info!("Flushing {} buffered messages", spool.len());
+        while let Some(message) = spool.front().cloned() {
+            if let Err(e) = ipc_client.send(message).await {
+                error!("Failed to flush buffered message: {}", e);
+                return false;
+            }
+            if let Err(e) = spool.delivered() {
+                error!("Failed to update monitor spool: {}", e);
+            }
+        }
+        info!("Monitor spool flushed: {}", spool.stats());
+        true
+    }
+
     async fn run_client_task(
         socket_path: String,
-        buffer: Arc<Mutex<VecDeque<IpcMessage>>>,
+        buffer: Arc<Mutex<Spool>>,
         mut receiver: mpsc::Receiver<IpcMessage>,
         mut shutdown_rx: mpsc::Receiver<()>,
     ) {
@@ -76,61 +132,47 @@ impl BufferedIpcClient {
 
                 // Try to receive new messages
                 Some(message) = receiver.recv() => {
-                    // Try to send the message
-                    if let Some(ref mut ipc_client) = client {
-                        if let Err(e) = ipc_client.send(message.clone()).await {
-                            warn!("Failed to send message, will buffer: {}", e);
-                            // Connection failed, reset client
-                            client = None;
-                            // Buffer the message
-                            let mut buf = buffer.lock().await;
-                            if buf.len() < MAX_BUFFER_SIZE {
-                                buf.push_back(message);
+                    // Only send directly once older messages have gone out
+                    let spooled = !buffer.lock().await.is_empty();
+                    match client {
+                        Some(ref mut ipc_client) if !spooled => {
+                            if let Err(e) = ipc_client.send(message.clone()).await {
+                                warn!("Failed to send message, will buffer: {}", e);
+                                // Connection failed, reset client
+                                client = None;
+                                Self::spool(&buffer, message).await;
                             }
                         }
-                    } else {
-                        // No connection, buffer the message
-                        let mut buf = buffer.lock().await;
-                        if buf.len() < MAX_BUFFER_SIZE {
-                            buf.push_back(message);
+                        Some(ref mut ipc_client) => {
+                            Self::spool(&buffer, message).await;
+                            if !Self::flush(ipc_client, &buffer).await {
+                                client = None;
+                            }
                         }
+                        // No connection, buffer the message
+                        None => Self::spool(&buffer, message).await,
                     }
                 }
 
                 // Periodic reconnection attempts
                 _ = sleep(Duration::from_millis(100)) => {
+                    if client.is_none() {
+                        let mut spool = buffer.lock().await;
+                        if let Err(e) = spool.expire(SystemTime::now()) {
+                            error!("Failed to expire spooled messages: {}", e);
+                        }
+                    }
                     if client.is_none() && last_connect_attempt.elapsed() >= reconnect_delay {
                         last_connect_attempt = Instant::now();
 
-                        match IpcClient::connect(&socket_path).await {
-                            Ok(new_client) => {
+                        match Self::connect(&socket_path).await {
+                            Ok(mut new_client) => {
                                 info!("Successfully connected to monitor at {}", socket_path);
-                                client = Some(new_client);
                                 reconnect_delay = INITIAL_RECONNECT_DELAY;
 
                                 // Flush buffered messages
-                                let messages_to_send: Vec<IpcMessage> = {
-                                    let mut buf = buffer.lock().await;
-                                    buf.drain(..).collect()
-                                };
-
-                                if !messages_to_send.is_empty() {
-                                    info!("Flushing {} buffered messages", messages_to_send.len());
-                                    if let Some(ref mut ipc_client) = client {
-                                        for msg in messages_to_send {
-                                            if let Err(e) = ipc_client.send(msg.clone()).await {
-                                                error!("Failed to flush buffered message: {}", e);
-                                                // Re-buffer failed messages
-                                                let mut buf = buffer.lock().await;
-                                                if buf.len() < MAX_BUFFER_SIZE {
-                                                    buf.push_back(msg);
-                                                }
-                                                // Connection failed during flush
-                                                client = None;
-                                                break;
-                                            }
-                                        }
-                                    }
+                                if Self::flush(&mut new_client, &buffer).await {
+                                    client = Some(new_client);
                                 }
                             }
                             Err(e) => {
@@ -146,6 +188,40 @@ impl BufferedIpcClient {
                 }
             }
         }
+
+        // Keep what is still in flight for the next connection or run
+        receiver.close();
+        while let Ok(message) = receiver.try_recv() {
+            match client {
+                Some(ref mut ipc_client) if buffer.lock().await.is_empty() => {
+                    if ipc_client.send(message.clone()).await.is_err() {
+                        client = None;
+                        Self::spool(&buffer, message).await;
+                    }
+                }
+                _ => Self::spool(&buffer, message).await,
+            }
+        }
+        let stats = buffer.lock().await.stats();
+        if stats.queued > 0 {
+            info!("Monitor spool: {}", stats);
+        }
+    }
+
+    /// Connect to the monitor and negotiate the IPC protocol version
+    async fn connect(socket_path: &str) -> Result<IpcClient> {
+        let mut client = IpcClient::connect(socket_path).await?;
+
+        let software = concat!("mcp-transport/", env!("CARGO_PKG_VERSION"));
+        match client.handshake(PeerRole::Proxy, software).await {
+            Ok(version) => debug!("Monitor speaks IPC protocol {}", version),
+            Err(e) if e.is::<HandshakeTimeout>() => {
+                warn!("Monitor did not answer the IPC handshake; assuming a legacy monitor");
+            }
+            Err(e) => return Err(e),
+        }
+
+        Ok(client)
     }
 
     pub async fn shutdown(mut self) {
@@ -0,0 +1,618 @@
+//! Signed, versioned bundles of interceptor pipelines.
+//!
+//! A pipeline directory holds the configuration a team hardens once and
+//! shares everywhere:
+//!
+//! ```text
+//! policy.toml       ToolPolicy: allow/deny patterns and argument rules
+//! redaction.toml    RedactionConfig: patterns, JSONPaths and sensitive keys
+//! transforms.toml   [[rule]] entries of TransformRule
+//! templates/*       Text templates carried along verbatim
+//! ```
+//!
+//! [`Bundle::from_dir`] packs such a directory into a tar archive whose first
+//! entry, `manifest.json`, records the bundle's name and version, the oldest
+//! assist-mcp release that can run it, and a SHA-256 digest of every other
+//! file. [`Bundle::sign`] adds an Ed25519 signature over the manifest, which
+//! covers the files through their digests.
+//!
+//! [`Bundle::read`] rejects archives with unexpected entries or files that do
+//! not match their digest, and [`Bundle::verify`] checks the signature, the
+//! signer against a trusted key and the version requirements before a bundle
+//! is imported with [`Bundle::extract`]. The proxy loads an imported directory
+//! with [`Pipeline::load`].
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Component, Path};
+use std::sync::Arc;
+
+use anyhow::{bail, ensure, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use mcp_core::interceptor::InterceptorManager;
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+
+use crate::interceptors::{
+    PolicyInterceptor, RedactionConfig, RedactionInterceptor, Redactor, ToolPolicy,
+    TransformInterceptor, TransformRule,
+};
+
+/// Bundle layout version written by this release
+pub const BUNDLE_FORMAT: u32 = 1;
+
+/// Release of assist-mcp doing the reading and writing
+pub const RUNNING_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const MANIFEST: &str = "manifest.json";
+const POLICY: &str = "policy.toml";
+const REDACTION: &str = "redaction.toml";
+const TRANSFORMS: &str = "transforms.toml";
+const TEMPLATES: &str = "templates";
+
+/// Largest file accepted in a bundle, to refuse archive bombs
+const MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// What a bundle says about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Layout version, see [`BUNDLE_FORMAT`]
+    pub format: u32,
+    pub name: String,
+    /// Version of the bundle's content, chosen by its authors
+    pub version: String,
+    /// Oldest assist-mcp release able to apply the bundle
+    pub min_version: String,
+    /// assist-mcp release that created the bundle
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    /// SHA-256 (hex) of every file, keyed by its path in the bundle
+    pub files: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<BundleSignature>,
+}
+
+/// Ed25519 signature over the manifest without this field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleSignature {
+    pub algorithm: String,
+    /// Signer's public key, base64
+    pub public_key: String,
+    /// Signature, base64
+    pub value: String,
+}
+
+/// Outcome of a successful [`Bundle::verify`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    /// Public key (base64) of the signer, if the bundle is signed
+    pub signed_by: Option<String>,
+    /// Whether the signer is the trusted key that was asked for
+    pub trusted: bool,
+}
+
+/// The interceptor configuration carried by a bundle
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    pub policy: Option<ToolPolicy>,
+    pub redaction: Option<RedactionConfig>,
+    pub transforms: Vec<TransformRule>,
+    /// Template name to content
+    pub templates: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TransformsFile {
+    #[serde(rename = "rule", default)]
+    rules: Vec<TransformRule>,
+}
+
+impl Pipeline {
+    /// Load an imported pipeline directory
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let files = read_pipeline_dir(dir)?;
+        ensure!(
+            !files.is_empty(),
+            "{} contains no pipeline files",
+            dir.display()
+        );
+        Self::parse(&files)
+    }
+
+    fn parse(files: &BTreeMap<String, Vec<u8>>) -> Result<Self> {
+        let text = |path: &str| -> Result<Option<&str>> {
+            files
+                .get(path)
+                .map(|bytes| {
+                    std::str::from_utf8(bytes).with_context(|| format!("{path} is not UTF-8"))
+                })
+                .transpose()
+        };
+
+        let mut pipeline = Self::default();
+        if let Some(policy) = text(POLICY)? {
+            pipeline.policy = Some(toml::from_str(policy).context("Invalid policy.toml")?);
+        }
+        if let Some(redaction) = text(REDACTION)? {
+            let config: RedactionConfig =
+                toml::from_str(redaction).context("Invalid redaction.toml")?;
+            // Compile now so a bad pattern fails the bundle, not the proxy
+            Redactor::new(config.clone()).context("Invalid redaction.toml")?;
+            pipeline.redaction = Some(config);
+        }
+        if let Some(transforms) = text(TRANSFORMS)? {
+            let file: TransformsFile =
+                toml::from_str(transforms).context("Invalid transforms.toml")?;
+            pipeline.transforms = file.rules;
+        }
+        for path in files.keys() {
+            if let Some(name) = path.strip_prefix("templates/") {
+                pipeline.templates.insert(
+                    name.to_string(),
+                    text(path)?.unwrap_or_default().to_string(),
+                );
+            }
+        }
+        Ok(pipeline)
+    }
+
+    /// Number of interceptors [`Pipeline::install`] adds
+    pub fn interceptor_count(&self) -> usize {
+        self.policy.is_some() as usize
+            + self.redaction.is_some() as usize
+            + !self.transforms.is_empty() as usize
+    }
+
+    /// Add the pipeline's interceptors to `manager`
+    pub async fn install(&self, manager: &InterceptorManager) -> Result<()> {
+        if let Some(policy) = &self.policy {
+            manager
+                .add_interceptor(Arc::new(PolicyInterceptor::new(policy.clone())))
+                .await;
+        }
+        if let Some(redaction) = &self.redaction {
+            let redactor = Redactor::new(redaction.clone())?;
+            manager
+                .add_interceptor(Arc::new(RedactionInterceptor::new(Arc::new(redactor))))
+                .await;
+        }
+        if !self.transforms.is_empty() {
+            let transforms = TransformInterceptor::new();
+            for rule in &self.transforms {
+                transforms.add_rule(rule.clone()).await;
+            }
+            manager.add_interceptor(Arc::new(transforms)).await;
+        }
+        Ok(())
+    }
+}
+
+/// A pipeline packed with its manifest
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub manifest: BundleManifest,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl Bundle {
+    /// Pack a pipeline directory, requiring the running release or newer to apply it
+    pub fn from_dir(
+        dir: impl AsRef<Path>,
+        name: impl Into<String>,
+        version: impl Into<String>,
+    ) -> Result<Self> {
+        let dir = dir.as_ref();
+        let files = read_pipeline_dir(dir)?;
+        ensure!(
+            !files.is_empty(),
+            "{} contains no pipeline files",
+            dir.display()
+        );
+        Pipeline::parse(&files)?;
+
+        Ok(Self {
+            manifest: BundleManifest {
+                format: BUNDLE_FORMAT,
+                name: name.into(),
+                version: version.into(),
+                min_version: RUNNING_VERSION.to_string(),
+                created_by: RUNNING_VERSION.to_string(),
+                created_at: Utc::now(),
+                files: files
+                    .iter()
+                    .map(|(path, bytes)| (path.clone(), sha256_hex(bytes)))
+                    .collect(),
+                signature: None,
+            },
+            files,
+        })
+    }
+
+    /// Paths of the files in the bundle
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Parse the carried pipeline
+    pub fn pipeline(&self) -> Result<Pipeline> {
+        Pipeline::parse(&self.files)
+    }
+
+    /// Sign the manifest with a key from [`generate_key`]
+    pub fn sign(&mut self, key: &Ed25519KeyPair) -> Result<()> {
+        self.manifest.signature = None;
+        let signature = key.sign(&signed_bytes(&self.manifest)?);
+        self.manifest.signature = Some(BundleSignature {
+            algorithm: "ed25519".to_string(),
+            public_key: BASE64.encode(key.public_key().as_ref()),
+            value: BASE64.encode(signature.as_ref()),
+        });
+        Ok(())
+    }
+
+    /// Check the signature and that this release can apply the bundle.
+    ///
+    /// With `trusted_key` (base64) the bundle must be signed by that key;
+    /// without it an unsigned bundle passes but reports no signer.
+    pub fn verify(&self, trusted_key: Option<&str>) -> Result<Verification> {
+        check_compatibility(&self.manifest, RUNNING_VERSION)?;
+
+        let Some(signature) = &self.manifest.signature else {
+            ensure!(
+                trusted_key.is_none(),
+                "Bundle is unsigned but a trusted key was required"
+            );
+            return Ok(Verification {
+                signed_by: None,
+                trusted: false,
+            });
+        };
+
+        ensure!(
+            signature.algorithm == "ed25519",
+            "Unsupported signature algorithm '{}'",
+            signature.algorithm
+        );
+        let public_key = BASE64
+            .decode(&signature.public_key)
+            .context("Invalid signer public key")?;
+        let value = BASE64
+            .decode(&signature.value)
+            .context("Invalid signature encoding")?;
+        UnparsedPublicKey::new(&ED25519, &public_key)
+            .verify(&signed_bytes(&self.manifest)?, &value)
+            .map_err(|_| anyhow::anyhow!("Bundle signature does not match its contents"))?;
+
+        let trusted = match trusted_key {
+            Some(trusted_key) => {
+                ensure!(
+                    trusted_key.trim() == signature.public_key,
+                    "Bundle is signed by {} which is not the trusted key",
+                    signature.public_key
+                );
+                true
+            }
+            None => false,
+        };
+        Ok(Verification {
+            signed_by: Some(signature.public_key.clone()),
+            trusted,
+        })
+    }
+
+    /// Write the bundle as a tar archive
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create bundle {}", path.display()))?;
+        let mut archive = tar::Builder::new(file);
+
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        append(&mut archive, MANIFEST, &manifest)?;
+        for (name, bytes) in &self.files {
+            append(&mut archive, name, bytes)?;
+        }
+        archive.into_inner()?;
+        Ok(())
+    }
+
+    /// Read a bundle, checking every file against the manifest
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open bundle {}", path.display()))?;
+        let mut archive = tar::Archive::new(file);
+
+        let mut manifest = None;
+        let mut files = BTreeMap::new();
+        for entry in archive.entries()? {
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path()?.to_string_lossy().replace('\\', "/");
+            ensure!(
+                entry.size() <= MAX_FILE_SIZE,
+                "Bundle entry {} is larger than {} bytes",
+                name,
+                MAX_FILE_SIZE
+            );
+            let mut bytes = Vec::new();
+            entry.take(MAX_FILE_SIZE).read_to_end(&mut bytes)?;
+
+            if name == MANIFEST {
+                manifest = Some(
+                    serde_json::from_slice::<BundleManifest>(&bytes)
+                        .context("Invalid bundle manifest")?,
+                );
+            } else {
+                ensure!(
+                    is_pipeline_file(&name),
+                    "Unexpected file {} in bundle",
+                    name
+                );
+                files.insert(name, bytes);
+            }
+        }
+
+        let manifest = manifest.context("Bundle has no manifest.json")?;
+        for (name, bytes) in &files {
+            match manifest.files.get(name) {
+                Some(expected) if *expected == sha256_hex(bytes) => {}
+                Some(_) => bail!("{} does not match its digest in the manifest", name),
+                None => bail!("{} is not listed in the manifest", name),
+            }
+        }
+        if let Some(missing) = manifest
+            .files
+            .keys()
+            .find(|name| !files.contains_key(*name))
+        {
+            bail!("{} is listed in the manifest but missing", missing);
+        }
+
+        Ok(Self { manifest, files })
+    }
+
+    /// Write the pipeline files into `dir`, replacing files of the same name
+    pub fn extract(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        for (name, bytes) in &self.files {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, bytes)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        std::fs::write(
+            dir.join(MANIFEST),
+            serde_json::to_vec_pretty(&self.manifest)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Create a signing key at `path` and return its public key (base64).
+///
+/// The key is stored as PKCS#8; keep it private.
+pub fn generate_key(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| anyhow::anyhow!("Failed to generate a signing key"))?;
+    write_private(path, pkcs8.as_ref())?;
+    let key = load_key(path)?;
+    Ok(BASE64.encode(key.public_key().as_ref()))
+}
+
+/// Load a signing key written by [`generate_key`]
+pub fn load_key(path: impl AsRef<Path>) -> Result<Ed25519KeyPair> {
+    let path = path.as_ref();
+    let pkcs8 = std::fs::read(path)
+        .with_context(|| format!("Failed to read signing key {}", path.display()))?;
+    Ed25519KeyPair::from_pkcs8(&pkcs8)
+        .map_err(|_| anyhow::anyhow!("{} is not an Ed25519 signing key", path.display()))
+}
+
+/// Fail unless a release `running` can apply a bundle described by `manifest`
+pub fn check_compatibility(manifest: &BundleManifest, running: &str) -> Result<()> {
+    ensure!(
+        manifest.format <= BUNDLE_FORMAT,
+        "Bundle format {} is newer than this release supports ({})",
+        manifest.format,
+        BUNDLE_FORMAT
+    );
+    ensure!(
+        parse_version(&manifest.min_version)? <= parse_version(running)?,
+        "Bundle '{}' requires assist-mcp {} or newer, this is {}",
+        manifest.name,
+        manifest.min_version,
+        running
+    );
+    Ok(())
+}
+
+/// `1.2.3` (pre-release and build suffixes ignored) as comparable numbers
+fn parse_version(version: &str) -> Result<Vec<u64>> {
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    core.split('.')
+        .map(|part| {
+            part.parse()
+                .with_context(|| format!("Invalid version '{}'", version))
+        })
+        .collect()
+}
+
+fn signed_bytes(manifest: &BundleManifest) -> Result<Vec<u8>> {
+    let unsigned = BundleManifest {
+        signature: None,
+        ..manifest.clone()
+    };
+    Ok(serde_json::to_vec(&unsigned)?)
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    digest(&SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Known top-level files, or a flat entry under `templates/`
+fn is_pipeline_file(name: &str) -> bool {
+    match name.strip_prefix("templates/") {
+        Some(template) => {
+            !template.is_empty()
+                && !template.contains('/')
+                && matches!(
+                    Path::new(template).components().next(),
+                    Some(Component::Normal(_))
+                )
+        }
+        None => [POLICY, REDACTION, TRANSFORMS].contains(&name),
+    }
+}
+
+fn read_pipeline_dir(dir: &Path) -> Result<BTreeMap<String, Vec<u8>>> {
+    ensure!(dir.is_dir(), "{} is not a directory", dir.display());
+    let mut files = BTreeMap::new();
+    for name in [POLICY, REDACTION, TRANSFORMS] {
+        let path = dir.join(name);
+        if path.is_file() {
+            files.insert(name.to_string(), std::fs::read(&path)?);
+        }
+    }
+
+    let templates = dir.join(TEMPLATES);
+    if templates.is_dir() {
+        for entry in std::fs::read_dir(&templates)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let name = format!("templates/{}", entry.file_name().to_string_lossy());
+            if is_pipeline_file(&name) {
+                files.insert(name, std::fs::read(entry.path())?);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn append(archive: &mut tar::Builder<std::fs::File>, name: &str, bytes: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, name, bytes)?;
+    Ok(())
+}
+
+fn write_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create signing key {}", path.display()))?;
+    std::io::Write::write_all(&mut file, bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline_dir(root: &Path) -> std::path::PathBuf {
+        let dir = root.join("pipeline");
+        std::fs::create_dir_all(dir.join("templates")).unwrap();
+        std::fs::write(dir.join(POLICY), "deny = [\"shell_*\"]\n").unwrap();
+        std::fs::write(dir.join(REDACTION), "patterns = [\"ACME-[0-9]+\"]\n").unwrap();
+        std::fs::write(
+            dir.join(TRANSFORMS),
+            "[[rule]]\nname = \"quiet\"\nmethod_pattern = \"tools/call\"\npath = \"params.arguments.verbose\"\noperation = { type = \"set\", value = false }\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("templates/incident.md"), "# Incident\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_signed_bundle_round_trips_and_verifies() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = pipeline_dir(root.path());
+        let key_path = root.path().join("team.key");
+        let public_key = generate_key(&key_path).unwrap();
+
+        let mut bundle = Bundle::from_dir(&dir, "hardened", "1.0.0").unwrap();
+        bundle.sign(&load_key(&key_path).unwrap()).unwrap();
+        let archive = root.path().join("hardened.tar");
+        bundle.write(&archive).unwrap();
+
+        let read = Bundle::read(&archive).unwrap();
+        let verification = read.verify(Some(&public_key)).unwrap();
+        assert!(verification.trusted);
+
+        let pipeline = read.pipeline().unwrap();
+        assert_eq!(pipeline.policy.unwrap().deny, vec!["shell_*"]);
+        assert_eq!(pipeline.transforms.len(), 1);
+        assert_eq!(pipeline.templates["incident.md"], "# Incident\n");
+
+        let imported = root.path().join("imported");
+        read.extract(&imported).unwrap();
+        assert_eq!(Pipeline::load(&imported).unwrap().interceptor_count(), 3);
+
+        // Another key is not trusted
+        let other = generate_key(root.path().join("other.key")).unwrap();
+        assert!(read.verify(Some(&other)).is_err());
+    }
+
+    #[test]
+    fn test_tampering_and_incompatible_bundles_are_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = pipeline_dir(root.path());
+        let key = load_key({
+            let path = root.path().join("team.key");
+            generate_key(&path).unwrap();
+            path
+        })
+        .unwrap();
+
+        // A file that no longer matches its digest
+        let mut bundle = Bundle::from_dir(&dir, "hardened", "1.0.0").unwrap();
+        bundle.sign(&key).unwrap();
+        bundle
+            .files
+            .insert(POLICY.to_string(), b"allow = [\"*\"]\n".to_vec());
+        let archive = root.path().join("tampered.tar");
+        bundle.write(&archive).unwrap();
+        let error = Bundle::read(&archive).unwrap_err();
+        assert!(error.to_string().contains("does not match its digest"));
+
+        // A manifest edited after signing
+        let mut bundle = Bundle::from_dir(&dir, "hardened", "1.0.0").unwrap();
+        bundle.sign(&key).unwrap();
+        bundle.manifest.version = "9.9.9".to_string();
+        assert!(bundle.verify(None).is_err());
+
+        // Requires a newer release
+        bundle.manifest.min_version = "99.0.0".to_string();
+        bundle.sign(&key).unwrap();
+        let error = bundle.verify(None).unwrap_err();
+        assert!(error.to_string().contains("requires assist-mcp 99.0.0"));
+
+        assert!(is_pipeline_file("templates/a.md"));
+        assert!(!is_pipeline_file("templates/../policy.toml"));
+        assert!(!is_pipeline_file("../policy.toml"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Prometheus metrics endpoint for a proxy.
+//!
+//! With `--metrics-addr` the proxy serves its [`ProxyMetrics`] over plain
+//! HTTP at [`METRICS_PATH`], so it can be scraped into existing dashboards
+//! next to the services it fronts. The endpoint answers nothing but scrapes:
+//! every connection gets one response and is closed.
+
+use anyhow::Result;
+use mcp_common::{ProxyMetrics, ProxyStats};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Path the metrics are served at
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Content type of the Prometheus text exposition format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Largest request head read before giving up on a connection
+const MAX_HEAD: usize = 8 * 1024;
+
+/// Answer scrapes on `listener` until the task is aborted
+pub async fn serve_metrics(
+    listener: TcpListener,
+    metrics: Arc<Mutex<ProxyMetrics>>,
+    stats: Arc<Mutex<ProxyStats>>,
+) -> Result<()> {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("Failed to accept metrics connection: {}", e);
+                continue;
+            }
+        };
+        let metrics = metrics.clone();
+        let stats = stats.clone();
+        tokio::spawn(async move {
+            if let Err(e) = answer(stream, &metrics, &stats).await {
+                debug!("Metrics request from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn answer(
+    mut stream: TcpStream,
+    metrics: &Mutex<ProxyMetrics>,
+    stats: &Mutex<ProxyStats>,
+) -> Result<()> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_HEAD {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&chunk[..read]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+    // Scrapers may add a query string; it selects nothing here
+    let path = path.split('?').next().unwrap_or_default();
+
+    let (status, body) = match (method, path) {
+        ("GET" | "HEAD", METRICS_PATH) => {
+            let stats = stats.lock().await.clone();
+            ("200 OK", metrics.lock().await.render(&stats))
+        }
+        (_, METRICS_PATH) => ("405 Method Not Allowed", String::new()),
+        _ => ("404 Not Found", String::new()),
+    };
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        CONTENT_TYPE,
+        body.len()
+    );
+    if status.starts_with("405") {
+        response.push_str("Allow: GET, HEAD\r\n");
+    }
+    response.push_str("\r\n");
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(addr: std::net::SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics_and_nothing_else() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let metrics = Arc::new(Mutex::new(ProxyMetrics::new()));
+        metrics.lock().await.transport_error();
+        let stats = Arc::new(Mutex::new(ProxyStats::default()));
+        let server = tokio::spawn(serve_metrics(listener, metrics, stats));
+
+        let response = get(addr, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("text/plain; version=0.0.4"));
+        assert!(response.contains("\ngenmcp_proxy_transport_errors_total 1\n"));
+
+        let response = get(addr, "HEAD /metrics HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\n"));
+
+        let response = get(addr, "POST /metrics HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+        let response = get(addr, "GET / HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        server.abort();
+    }
+}
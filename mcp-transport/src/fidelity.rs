@@ -0,0 +1,83 @@
+//! How faithfully the proxy forwards messages it does not change.
+//!
+//! Interceptors work on typed messages, and re-serializing them can reorder
+//! keys, drop fields the types do not know about and change number or
+//! whitespace formatting. Some servers and clients are picky about that, so
+//! the forwarding behaviour is selectable per proxy.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Forwarding fidelity for a proxied upstream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FidelityMode {
+    /// Every JSON-RPC message is re-serialized from its typed form
+    #[default]
+    Normalize,
+    /// Interceptors run, but messages they leave untouched are forwarded byte-for-byte
+    Preserve,
+    /// Interceptors are bypassed and every line is forwarded byte-for-byte;
+    /// only the id and method are inspected for logging
+    Raw,
+}
+
+impl FidelityMode {
+    /// Whether interceptors see traffic in this mode
+    pub fn runs_interceptors(self) -> bool {
+        self != FidelityMode::Raw
+    }
+
+    /// Whether a message interceptors left untouched keeps its original bytes
+    pub fn preserves_unmodified(self) -> bool {
+        self != FidelityMode::Normalize
+    }
+}
+
+impl FromStr for FidelityMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "normalize" => Ok(FidelityMode::Normalize),
+            "preserve" => Ok(FidelityMode::Preserve),
+            "raw" => Ok(FidelityMode::Raw),
+            _ => Err(anyhow!(
+                "Invalid fidelity mode: {}. Must be one of: normalize, preserve, raw",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FidelityMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FidelityMode::Normalize => "normalize",
+            FidelityMode::Preserve => "preserve",
+            FidelityMode::Raw => "raw",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for mode in [
+            FidelityMode::Normalize,
+            FidelityMode::Preserve,
+            FidelityMode::Raw,
+        ] {
+            assert_eq!(mode.to_string().parse::<FidelityMode>().unwrap(), mode);
+        }
+        assert!("exact".parse::<FidelityMode>().is_err());
+        assert!(!FidelityMode::Raw.runs_interceptors());
+        assert!(!FidelityMode::Normalize.preserves_unmodified());
+    }
+}
@@ -0,0 +1,303 @@
+//! Replacing the proxied stdio server without dropping the client session.
+//!
+//! The proxy records the client's `initialize` handshake and the requests
+//! still waiting for an answer. On a swap it starts the new backend, replays
+//! the handshake against it, and only then retires the old process: requests
+//! it never answered are failed, and the client is told to re-list whatever
+//! the new server may have changed.
+
+use anyhow::{anyhow, bail, Context, Result};
+use mcp_core::messages::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, RequestId};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+use crate::transport_config::TransportConfig;
+
+/// How long a replacement backend gets to answer the replayed `initialize`
+pub const SWAP_INIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Request id used for the replayed `initialize`
+const SWAP_INIT_ID: &str = "proxy-swap-initialize";
+
+/// A request to change the backend behind a running proxy
+#[derive(Debug, Clone)]
+pub enum SwapRequest {
+    /// Restart the current backend with the same configuration
+    Restart,
+    /// Replace the backend with a different one
+    Replace(TransportConfig),
+}
+
+/// Triggers backend swaps on a running proxy
+#[derive(Debug, Clone)]
+pub struct SwapHandle {
+    sender: mpsc::Sender<SwapRequest>,
+}
+
+impl SwapHandle {
+    pub(crate) fn new(sender: mpsc::Sender<SwapRequest>) -> Self {
+        Self { sender }
+    }
+
+    /// Restart the current backend
+    pub async fn restart(&self) -> Result<()> {
+        self.send(SwapRequest::Restart).await
+    }
+
+    /// Replace the backend with a new one
+    pub async fn replace(&self, config: TransportConfig) -> Result<()> {
+        self.send(SwapRequest::Replace(config)).await
+    }
+
+    async fn send(&self, request: SwapRequest) -> Result<()> {
+        self.sender
+            .send(request)
+            .await
+            .map_err(|_| anyhow!("Proxy is no longer running"))
+    }
+}
+
+/// Client-side session state needed to re-establish a backend
+#[derive(Debug, Clone, Default)]
+pub struct SessionTracker {
+    initialize: Option<JsonRpcRequest>,
+    initialized: bool,
+    capabilities: Option<Value>,
+    pending: HashSet<RequestId>,
+}
+
+impl SessionTracker {
+    /// Record a line the client sent to the backend
+    pub fn observe_outgoing(&mut self, line: &str) {
+        match serde_json::from_str::<JsonRpcMessage>(line.trim()) {
+            Ok(JsonRpcMessage::Request(request)) => {
+                self.pending.insert(request.id.clone());
+                if request.method == "initialize" {
+                    self.initialize = Some(request);
+                }
+            }
+            Ok(JsonRpcMessage::Notification(notification))
+                if notification.method == "notifications/initialized" =>
+            {
+                self.initialized = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a line the backend sent to the client
+    pub fn observe_incoming(&mut self, line: &str) {
+        if let Ok(JsonRpcMessage::Response(response)) = serde_json::from_str(line.trim()) {
+            self.pending.remove(&response.id);
+
+            let answers_initialize = self
+                .initialize
+                .as_ref()
+                .is_some_and(|request| request.id == response.id);
+            if answers_initialize {
+                if let Some(capabilities) =
+                    response.result.as_ref().and_then(|r| r.get("capabilities"))
+                {
+                    self.capabilities = Some(capabilities.clone());
+                }
+            }
+        }
+    }
+
+    /// Requests the current backend has not answered yet; clears them
+    pub fn take_pending(&mut self) -> Vec<RequestId> {
+        self.pending.drain().collect()
+    }
+
+    /// Capabilities announced by the current backend
+    pub fn capabilities(&self) -> Option<&Value> {
+        self.capabilities.as_ref()
+    }
+
+    /// Record the capabilities of a backend that was swapped in
+    pub fn set_capabilities(&mut self, capabilities: Option<Value>) {
+        self.capabilities = capabilities;
+    }
+}
+
+/// A started and initialized replacement backend
+pub struct ReplacementBackend {
+    pub child: Child,
+    pub stdin: BufWriter<ChildStdin>,
+    pub stdout: BufReader<ChildStdout>,
+    pub stderr: BufReader<ChildStderr>,
+    pub capabilities: Option<Value>,
+}
+
+/// Spawn a backend and replay the client's handshake against it
+pub async fn start_backend(
+    config: &TransportConfig,
+    session: &SessionTracker,
+) -> Result<ReplacementBackend> {
+    let TransportConfig::Stdio { command, use_shell } = config else {
+        bail!(
+            "Only stdio backends can be swapped in, got {:?}",
+            config.transport_type()
+        );
+    };
+
+    let mut child = crate::proxy::spawn_stdio_server(command, *use_shell)?;
+    let mut stdin = BufWriter::new(child.stdin.take().context("Failed to get child stdin")?);
+    let mut stdout = BufReader::new(child.stdout.take().context("Failed to get child stdout")?);
+    let stderr = BufReader::new(child.stderr.take().context("Failed to get child stderr")?);
+
+    let mut capabilities = None;
+    if let Some(ref initialize) = session.initialize {
+        let replay = JsonRpcRequest {
+            id: RequestId::from(SWAP_INIT_ID),
+            ..initialize.clone()
+        };
+        write_line(&mut stdin, &serde_json::to_string(&replay)?).await?;
+
+        let result = timeout(SWAP_INIT_TIMEOUT, read_response(&mut stdout, &replay.id))
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "New backend did not answer initialize within {:?}",
+                    SWAP_INIT_TIMEOUT
+                )
+            })??;
+        capabilities = result.get("capabilities").cloned();
+
+        if session.initialized {
+            let initialized = JsonRpcNotification::new("notifications/initialized", json!({}));
+            write_line(&mut stdin, &serde_json::to_string(&initialized)?).await?;
+        }
+    }
+
+    Ok(ReplacementBackend {
+        child,
+        stdin,
+        stdout,
+        stderr,
+        capabilities,
+    })
+}
+
+async fn write_line(stdin: &mut BufWriter<ChildStdin>, line: &str) -> Result<()> {
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.write_all(b"\n").await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+async fn read_response(stdout: &mut BufReader<ChildStdout>, id: &RequestId) -> Result<Value> {
+    loop {
+        let mut line = String::new();
+        if stdout.read_line(&mut line).await? == 0 {
+            bail!("New backend closed its output during initialize");
+        }
+
+        match serde_json::from_str::<JsonRpcMessage>(line.trim()) {
+            Ok(JsonRpcMessage::Response(response)) if &response.id == id => {
+                if let Some(error) = response.error {
+                    bail!("New backend rejected initialize: {}", error.message);
+                }
+                return Ok(response.result.unwrap_or(Value::Null));
+            }
+            // Nothing else can be meaningfully delivered before the handshake completes
+            _ => debug!("Dropping backend output during swap: {}", line.trim()),
+        }
+    }
+}
+
+/// How the backend's capabilities changed across a swap
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CapabilityDiff {
+    /// Capabilities the new backend announces that the old one did not
+    pub added: Vec<String>,
+    /// Capabilities the old backend announced that the new one does not
+    pub removed: Vec<String>,
+    /// `list_changed` notifications the client should receive
+    pub notifications: Vec<&'static str>,
+}
+
+impl CapabilityDiff {
+    pub fn between(old: Option<&Value>, new: Option<&Value>) -> Self {
+        let keys = |caps: Option<&Value>| -> HashSet<String> {
+            caps.and_then(Value::as_object)
+                .map(|map| map.keys().cloned().collect())
+                .unwrap_or_default()
+        };
+        let (old_keys, new_keys) = (keys(old), keys(new));
+
+        let mut added: Vec<String> = new_keys.difference(&old_keys).cloned().collect();
+        let mut removed: Vec<String> = old_keys.difference(&new_keys).cloned().collect();
+        added.sort();
+        removed.sort();
+
+        // Catalogs may differ even when the capability itself is unchanged
+        let notifications = [
+            ("tools", "notifications/tools/list_changed"),
+            ("resources", "notifications/resources/list_changed"),
+            ("prompts", "notifications/prompts/list_changed"),
+        ]
+        .into_iter()
+        .filter(|(key, _)| old_keys.contains(*key) || new_keys.contains(*key))
+        .map(|(_, method)| method)
+        .collect();
+
+        Self {
+            added,
+            removed,
+            notifications,
+        }
+    }
+}
+
+/// Wait for the next swap request, or forever if swapping is disabled
+pub async fn next_swap(receiver: &mut Option<mpsc::Receiver<SwapRequest>>) -> Option<SwapRequest> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_tracking() {
+        let mut session = SessionTracker::default();
+        session.observe_outgoing(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05"}}"#,
+        );
+        session
+            .observe_incoming(r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{"tools":{}}}}"#);
+        session.observe_outgoing(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+        session.observe_outgoing(r#"{"jsonrpc":"2.0","id":"a","method":"tools/call","params":{}}"#);
+
+        assert!(session.initialized);
+        assert_eq!(session.capabilities(), Some(&json!({ "tools": {} })));
+        assert_eq!(session.take_pending(), vec![RequestId::from("a")]);
+        assert!(session.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_capability_diff() {
+        let old = json!({ "tools": {}, "logging": {} });
+        let new = json!({ "tools": {}, "prompts": {} });
+        let diff = CapabilityDiff::between(Some(&old), Some(&new));
+
+        assert_eq!(diff.added, vec!["prompts"]);
+        assert_eq!(diff.removed, vec!["logging"]);
+        assert_eq!(
+            diff.notifications,
+            vec![
+                "notifications/tools/list_changed",
+                "notifications/prompts/list_changed"
+            ]
+        );
+    }
+}
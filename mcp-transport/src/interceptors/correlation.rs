@@ -0,0 +1,240 @@
+//! Correlation interceptor that tags each request and its response with a shared ID
+//!
+//! Outgoing requests get a correlation ID in their `_meta` object, unless the
+//! client already set one, and the matching response gets the same ID in the
+//! `_meta` of its result. Servers that propagate `_meta` into their own logs or
+//! traces can then be joined with the proxy's log on that ID.
+
+use async_trait::async_trait;
+use mcp_core::interceptor::{
+    InterceptAction, InterceptionResult, InterceptorStats, MessageContext, MessageDirection,
+    MessageInterceptor,
+};
+use mcp_core::messages::JsonRpcMessage;
+use mcp_core::McpResult;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// `_meta` key the correlation ID is stored under by default
+pub const DEFAULT_CORRELATION_KEY: &str = "correlationId";
+
+/// Requests awaiting a response before the oldest are forgotten
+const MAX_PENDING: usize = 10_000;
+
+/// Correlation IDs of requests still waiting for their response
+#[derive(Default)]
+struct Pending {
+    ids: HashMap<String, Value>,
+    order: VecDeque<String>,
+}
+
+impl Pending {
+    fn insert(&mut self, request_id: String, correlation: Value) {
+        if self.ids.len() >= MAX_PENDING {
+            while let Some(oldest) = self.order.pop_front() {
+                if self.ids.remove(&oldest).is_some() {
+                    break;
+                }
+            }
+        }
+        self.order.push_back(request_id.clone());
+        self.ids.insert(request_id, correlation);
+    }
+
+    fn take(&mut self, request_id: &str) -> Option<Value> {
+        let correlation = self.ids.remove(request_id)?;
+        if self.ids.is_empty() {
+            self.order.clear();
+        }
+        Some(correlation)
+    }
+}
+
+/// Interceptor that carries a correlation ID in `_meta` from request to response
+pub struct CorrelationInterceptor {
+    name: String,
+    key: String,
+    stats: Arc<RwLock<InterceptorStats>>,
+    pending: Arc<RwLock<Pending>>,
+}
+
+impl CorrelationInterceptor {
+    /// Create a correlation interceptor using [`DEFAULT_CORRELATION_KEY`]
+    pub fn new() -> Self {
+        Self {
+            name: "CorrelationInterceptor".to_string(),
+            key: DEFAULT_CORRELATION_KEY.to_string(),
+            stats: Arc::new(RwLock::new(InterceptorStats::default())),
+            pending: Arc::new(RwLock::new(Pending::default())),
+        }
+    }
+
+    /// Store the correlation ID under another `_meta` key
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+}
+
+impl Default for CorrelationInterceptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MessageInterceptor for CorrelationInterceptor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> u32 {
+        // Run late so the ID is attached to the message as it will be sent
+        90
+    }
+
+    async fn should_intercept(&self, context: &MessageContext) -> bool {
+        matches!(
+            (&context.direction, &context.message),
+            (MessageDirection::Outgoing, JsonRpcMessage::Request(_))
+                | (MessageDirection::Incoming, JsonRpcMessage::Response(_))
+        )
+    }
+
+    async fn intercept(&self, mut context: MessageContext) -> McpResult<InterceptionResult> {
+        let action = self.intercept_in_place(&mut context).await?;
+        Ok(action.into_result(context.message))
+    }
+
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
+        let Some(request_id) = context.id() else {
+            return Ok(InterceptAction::Continue);
+        };
+
+        let modified = match context.direction {
+            MessageDirection::Outgoing => {
+                let Some(meta) = context.message.meta_mut() else {
+                    return Ok(InterceptAction::Continue);
+                };
+                let (correlation, added) = match meta.get(&self.key) {
+                    Some(existing) => (existing.clone(), false),
+                    None => {
+                        let correlation = Value::String(Uuid::new_v4().to_string());
+                        meta.insert(self.key.clone(), correlation.clone());
+                        (correlation, true)
+                    }
+                };
+                self.pending.write().await.insert(request_id, correlation);
+                added
+            }
+            MessageDirection::Incoming => {
+                let Some(correlation) = self.pending.write().await.take(&request_id) else {
+                    return Ok(InterceptAction::Continue);
+                };
+                match context.message.meta_mut() {
+                    Some(meta) if meta.get(&self.key) != Some(&correlation) => {
+                        meta.insert(self.key.clone(), correlation);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        };
+
+        let mut stats = self.stats.write().await;
+        stats.total_intercepted += 1;
+        stats.last_processed = Some(chrono::Utc::now());
+        if modified {
+            stats.total_modified += 1;
+            Ok(InterceptAction::Modified {
+                reasoning: Some(format!("Tagged with _meta.{}", self.key)),
+                confidence: Some(1.0),
+            })
+        } else {
+            Ok(InterceptAction::Continue)
+        }
+    }
+
+    async fn get_stats(&self) -> InterceptorStats {
+        self.stats.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn context(direction: MessageDirection, message: Value) -> MessageContext {
+        MessageContext::new(serde_json::from_value(message).unwrap(), direction)
+    }
+
+    #[tokio::test]
+    async fn test_response_carries_request_correlation_id() {
+        let interceptor = CorrelationInterceptor::new();
+
+        let mut request = context(
+            MessageDirection::Outgoing,
+            json!({ "jsonrpc": "2.0", "id": 7, "method": "tools/call", "params": { "name": "echo" } }),
+        );
+        assert!(interceptor.should_intercept(&request).await);
+        let action = interceptor.intercept_in_place(&mut request).await.unwrap();
+        assert!(matches!(action, InterceptAction::Modified { .. }));
+        let correlation = request.message.meta().unwrap()[DEFAULT_CORRELATION_KEY].clone();
+        assert!(correlation.is_string());
+
+        // The server dropped the ID; the proxy puts it back
+        let mut response = context(
+            MessageDirection::Incoming,
+            json!({ "jsonrpc": "2.0", "id": 7, "result": { "content": [] } }),
+        );
+        interceptor.intercept_in_place(&mut response).await.unwrap();
+        assert_eq!(
+            response.message.meta().unwrap()[DEFAULT_CORRELATION_KEY],
+            correlation
+        );
+
+        // A second response to the same ID is left alone
+        let mut duplicate = context(
+            MessageDirection::Incoming,
+            json!({ "jsonrpc": "2.0", "id": 7, "result": {} }),
+        );
+        let action = interceptor
+            .intercept_in_place(&mut duplicate)
+            .await
+            .unwrap();
+        assert_eq!(action, InterceptAction::Continue);
+        assert!(duplicate.message.meta().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_client_supplied_id_is_kept() {
+        let interceptor = CorrelationInterceptor::new().with_key("io.example/trace");
+
+        let mut request = context(
+            MessageDirection::Outgoing,
+            json!({
+                "jsonrpc": "2.0", "id": "a", "method": "tools/list",
+                "params": { "_meta": { "io.example/trace": "t-1", "progressToken": 3 } }
+            }),
+        );
+        let action = interceptor.intercept_in_place(&mut request).await.unwrap();
+        assert_eq!(action, InterceptAction::Continue);
+
+        let mut response = context(
+            MessageDirection::Incoming,
+            json!({ "jsonrpc": "2.0", "id": "a", "result": { "tools": [], "_meta": { "cached": true } } }),
+        );
+        interceptor.intercept_in_place(&mut response).await.unwrap();
+        assert_eq!(
+            response.message.meta().unwrap(),
+            json!({ "cached": true, "io.example/trace": "t-1" })
+                .as_object()
+                .unwrap()
+        );
+        assert_eq!(interceptor.get_stats().await.total_modified, 1);
+    }
+}
@@ -0,0 +1,521 @@
+//! Fault injection interceptor for chaos testing MCP clients
+//!
+//! Rules degrade what the server sends back: responses and notifications can
+//! be delayed or dropped, response fields corrupted, results replaced with
+//! synthetic JSON-RPC errors, and requests left unanswered so the caller's
+//! own timeout fires. Delays are applied in-line, so later messages queue
+//! behind a delayed one just as they would behind a slow stdio server.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use mcp_core::interceptor::{
+    InterceptionResult, InterceptorStats, MessageContext, MessageDirection, MessageInterceptor,
+};
+use mcp_core::messages::{JsonRpcError, JsonRpcMessage, JsonRpcResponse};
+use mcp_core::McpResult;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tokio::time::Duration;
+use tracing::warn;
+
+use super::policy::glob_match;
+
+/// A degradation applied to a server message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fault {
+    /// Hold the message back before delivering it
+    Delay { ms: u64 },
+    /// Discard the message (response or notification)
+    Drop,
+    /// Replace the value at a JSON pointer inside the response `result`,
+    /// or remove it when no value is given
+    Corrupt {
+        pointer: String,
+        #[serde(default)]
+        value: Option<Value>,
+    },
+    /// Replace the response with a JSON-RPC error
+    Error { code: i32, message: String },
+    /// Never answer the request, so the caller times out
+    Timeout,
+}
+
+/// When to apply a [`Fault`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaultRule {
+    /// Method pattern the rule applies to (e.g. "tools/call", "*/list", "*")
+    pub method: String,
+    /// Chance of the fault firing for a matching message, from 0.0 to 1.0
+    #[serde(default = "FaultRule::always")]
+    pub probability: f64,
+    /// Fault to inject
+    pub fault: Fault,
+}
+
+impl FaultRule {
+    /// A rule that fires on every message of the matching methods
+    pub fn new(method: impl Into<String>, fault: Fault) -> Self {
+        Self {
+            method: method.into(),
+            probability: Self::always(),
+            fault,
+        }
+    }
+
+    /// Fire only with the given probability
+    pub fn with_probability(mut self, probability: f64) -> Self {
+        self.probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    fn always() -> f64 {
+        1.0
+    }
+
+    /// Whether the fault can apply to a notification rather than a response
+    fn applies_to_notifications(&self) -> bool {
+        matches!(self.fault, Fault::Delay { .. } | Fault::Drop)
+    }
+}
+
+/// Fault rules loaded from a TOML file
+///
+/// ```toml
+/// seed = 42
+///
+/// [[rule]]
+/// method = "tools/call"
+/// probability = 0.2
+/// fault = { type = "error", code = -32603, message = "Internal error" }
+///
+/// [[rule]]
+/// method = "*/list"
+/// fault = { type = "delay", ms = 1500 }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultConfig {
+    /// Random seed for reproducible runs
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Rules, checked in order
+    #[serde(rename = "rule", default)]
+    pub rules: Vec<FaultRule>,
+}
+
+impl FaultConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read faults file {}", path.display()))?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Invalid faults file {}", path.display()))?;
+        if let Some(rule) = config
+            .rules
+            .iter()
+            .find(|rule| !(0.0..=1.0).contains(&rule.probability))
+        {
+            bail!(
+                "Fault rule for '{}' has probability {}, expected 0.0 to 1.0",
+                rule.method,
+                rule.probability
+            );
+        }
+        Ok(config)
+    }
+}
+
+/// Interceptor that injects faults into server traffic
+///
+/// Rules are checked in order and the first one that matches and fires is
+/// applied, so at most one fault hits a given message. Responses are matched
+/// by the method of the request they answer.
+pub struct FaultInjectionInterceptor {
+    name: String,
+    stats: Arc<RwLock<InterceptorStats>>,
+    rules: Arc<RwLock<Vec<FaultRule>>>,
+    /// Request id -> method, for requests still awaiting a response
+    pending: Arc<RwLock<HashMap<String, String>>>,
+    rng: Mutex<StdRng>,
+}
+
+impl FaultInjectionInterceptor {
+    /// Create a fault injector with the given rules
+    pub fn new(rules: Vec<FaultRule>) -> Self {
+        Self {
+            name: "FaultInjectionInterceptor".to_string(),
+            stats: Arc::new(RwLock::new(InterceptorStats::default())),
+            rules: Arc::new(RwLock::new(rules)),
+            pending: Arc::new(RwLock::new(HashMap::new())),
+            rng: Mutex::new(StdRng::from_entropy()),
+        }
+    }
+
+    /// Create a fault injector from a loaded configuration
+    pub fn from_config(config: FaultConfig) -> Self {
+        let interceptor = Self::new(config.rules);
+        match config.seed {
+            Some(seed) => interceptor.with_seed(seed),
+            None => interceptor,
+        }
+    }
+
+    /// Use a fixed random seed so a chaos run can be reproduced
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+            ..self
+        }
+    }
+
+    /// Replace the fault rules
+    pub async fn set_rules(&self, rules: Vec<FaultRule>) {
+        *self.rules.write().await = rules;
+    }
+
+    fn roll(&self, probability: f64) -> bool {
+        if probability >= 1.0 {
+            return true;
+        }
+        self.rng
+            .lock()
+            .map(|mut rng| rng.gen_bool(probability.max(0.0)))
+            .unwrap_or(false)
+    }
+
+    /// Pick the fault for a server message, if any fires
+    async fn select(&self, method: &str, is_notification: bool) -> Option<Fault> {
+        let rules = self.rules.read().await;
+        rules
+            .iter()
+            .filter(|rule| glob_match(&rule.method, method))
+            .filter(|rule| !is_notification || rule.applies_to_notifications())
+            .find(|rule| self.roll(rule.probability))
+            .map(|rule| rule.fault.clone())
+    }
+
+    /// Apply a fault to a server message
+    async fn inject(
+        &self,
+        fault: &Fault,
+        message: JsonRpcMessage,
+        method: &str,
+    ) -> InterceptionResult {
+        match fault {
+            Fault::Delay { ms } => {
+                tokio::time::sleep(Duration::from_millis(*ms)).await;
+                InterceptionResult::pass_through(message)
+            }
+            Fault::Drop => {
+                InterceptionResult::blocked(format!("Fault injected: dropped {}", method))
+            }
+            Fault::Timeout => {
+                InterceptionResult::blocked(format!("Fault injected: {} left unanswered", method))
+            }
+            Fault::Corrupt { pointer, value } => {
+                let JsonRpcMessage::Response(mut response) = message else {
+                    return InterceptionResult::pass_through(message);
+                };
+                let Some(result) = response.result.as_mut() else {
+                    return InterceptionResult::pass_through(JsonRpcMessage::Response(response));
+                };
+                if !corrupt(result, pointer, value.clone()) {
+                    return InterceptionResult::pass_through(JsonRpcMessage::Response(response));
+                }
+                InterceptionResult::modified(
+                    JsonRpcMessage::Response(response),
+                    format!("Fault injected: corrupted {} in {} result", pointer, method),
+                    1.0,
+                )
+            }
+            Fault::Error {
+                code,
+                message: text,
+            } => {
+                let JsonRpcMessage::Response(response) = message else {
+                    return InterceptionResult::pass_through(message);
+                };
+                let error = JsonRpcResponse::error(
+                    response.id,
+                    JsonRpcError::new(*code, text.clone(), None),
+                );
+                InterceptionResult::modified(
+                    JsonRpcMessage::Response(error),
+                    format!("Fault injected: {} answered with error {}", method, code),
+                    1.0,
+                )
+            }
+        }
+    }
+}
+
+/// Replace or remove the value at `pointer`; returns whether anything changed
+fn corrupt(target: &mut Value, pointer: &str, value: Option<Value>) -> bool {
+    match value {
+        Some(value) => match target.pointer_mut(pointer) {
+            Some(slot) => {
+                *slot = value;
+                true
+            }
+            None => false,
+        },
+        None => {
+            let Some((parent, key)) = pointer.rsplit_once('/') else {
+                return false;
+            };
+            let key = key.replace("~1", "/").replace("~0", "~");
+            match target.pointer_mut(parent) {
+                Some(Value::Object(map)) => map.remove(&key).is_some(),
+                Some(Value::Array(items)) => match key.parse::<usize>() {
+                    Ok(index) if index < items.len() => {
+                        items.remove(index);
+                        true
+                    }
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MessageInterceptor for FaultInjectionInterceptor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> u32 {
+        // Run last so other interceptors see the server's real traffic
+        90
+    }
+
+    async fn should_intercept(&self, context: &MessageContext) -> bool {
+        match context.direction {
+            MessageDirection::Outgoing => matches!(context.message, JsonRpcMessage::Request(_)),
+            MessageDirection::Incoming => !matches!(context.message, JsonRpcMessage::Request(_)),
+        }
+    }
+
+    async fn intercept(&self, context: MessageContext) -> McpResult<InterceptionResult> {
+        let (method, is_notification) = match &context.message {
+            JsonRpcMessage::Request(request) => {
+                // Remember what the response will answer
+                self.pending
+                    .write()
+                    .await
+                    .insert(request.id.to_string(), request.method.clone());
+                return Ok(InterceptionResult::pass_through(context.message));
+            }
+            JsonRpcMessage::Response(response) => {
+                match self.pending.write().await.remove(&response.id.to_string()) {
+                    Some(method) => (method, false),
+                    None => return Ok(InterceptionResult::pass_through(context.message)),
+                }
+            }
+            JsonRpcMessage::Notification(notification) => (notification.method.clone(), true),
+        };
+
+        let Some(fault) = self.select(&method, is_notification).await else {
+            return Ok(InterceptionResult::pass_through(context.message));
+        };
+        let result = self.inject(&fault, context.message, &method).await;
+
+        let mut stats = self.stats.write().await;
+        stats.total_intercepted += 1;
+        stats.last_processed = Some(chrono::Utc::now());
+        if result.block {
+            stats.total_blocked += 1;
+        }
+        if result.modified {
+            stats.total_modified += 1;
+        }
+        drop(stats);
+
+        if let Some(ref reasoning) = result.reasoning {
+            warn!("[{}] {}", self.name, reasoning);
+        }
+        Ok(result)
+    }
+
+    async fn get_stats(&self) -> InterceptorStats {
+        self.stats.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::messages::{JsonRpcNotification, JsonRpcRequest, RequestId};
+    use serde_json::json;
+
+    fn request(id: i64, method: &str) -> MessageContext {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(id),
+            method: method.to_string(),
+            params: None,
+        };
+        MessageContext::new(JsonRpcMessage::Request(request), MessageDirection::Outgoing)
+    }
+
+    fn response(id: i64, result: Value) -> MessageContext {
+        MessageContext::new(
+            JsonRpcMessage::Response(JsonRpcResponse::success(RequestId::from(id), result)),
+            MessageDirection::Incoming,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_faults_follow_request_method() {
+        let interceptor = FaultInjectionInterceptor::new(vec![
+            FaultRule::new(
+                "tools/call",
+                Fault::Error {
+                    code: -32000,
+                    message: "chaos".to_string(),
+                },
+            ),
+            FaultRule::new(
+                "tools/list",
+                Fault::Corrupt {
+                    pointer: "/tools/0/name".to_string(),
+                    value: Some(json!(42)),
+                },
+            ),
+            FaultRule::new("prompts/*", Fault::Timeout),
+        ]);
+
+        interceptor
+            .intercept(request(1, "tools/call"))
+            .await
+            .unwrap();
+        let result = interceptor.intercept(response(1, json!({}))).await.unwrap();
+        let JsonRpcMessage::Response(reply) = result.message else {
+            panic!("expected response")
+        };
+        assert_eq!(reply.error.unwrap().code, -32000);
+
+        interceptor
+            .intercept(request(2, "tools/list"))
+            .await
+            .unwrap();
+        let result = interceptor
+            .intercept(response(2, json!({ "tools": [{ "name": "echo" }] })))
+            .await
+            .unwrap();
+        let JsonRpcMessage::Response(reply) = result.message else {
+            panic!("expected response")
+        };
+        assert_eq!(reply.result.unwrap()["tools"][0]["name"], json!(42));
+
+        interceptor
+            .intercept(request(3, "prompts/get"))
+            .await
+            .unwrap();
+        assert!(
+            interceptor
+                .intercept(response(3, json!({})))
+                .await
+                .unwrap()
+                .block
+        );
+
+        // Unmatched responses and notifications with response-only faults pass through
+        assert!(
+            !interceptor
+                .intercept(response(9, json!({})))
+                .await
+                .unwrap()
+                .block
+        );
+        let notification = MessageContext::new(
+            JsonRpcMessage::Notification(JsonRpcNotification::new(
+                "prompts/list_changed",
+                json!({}),
+            )),
+            MessageDirection::Incoming,
+        );
+        assert!(!interceptor.intercept(notification).await.unwrap().block);
+
+        let stats = interceptor.get_stats().await;
+        assert_eq!(stats.total_modified, 2);
+        assert_eq!(stats.total_blocked, 1);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_probability_is_reproducible() {
+        let run = || async {
+            let interceptor =
+                FaultInjectionInterceptor::new(vec![
+                    FaultRule::new("*", Fault::Drop).with_probability(0.5)
+                ])
+                .with_seed(7);
+            let mut dropped = Vec::new();
+            for id in 0..20 {
+                interceptor.intercept(request(id, "ping")).await.unwrap();
+                dropped.push(
+                    interceptor
+                        .intercept(response(id, json!({})))
+                        .await
+                        .unwrap()
+                        .block,
+                );
+            }
+            dropped
+        };
+
+        let first = run().await;
+        assert_eq!(first, run().await);
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+
+    #[test]
+    fn test_config_parsing() {
+        let config: FaultConfig = toml::from_str(
+            r#"
+            seed = 3
+
+            [[rule]]
+            method = "tools/call"
+            probability = 0.25
+            fault = { type = "corrupt", pointer = "/content" }
+
+            [[rule]]
+            method = "*"
+            fault = { type = "timeout" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.seed, Some(3));
+        assert_eq!(
+            config.rules,
+            vec![
+                FaultRule::new(
+                    "tools/call",
+                    Fault::Corrupt {
+                        pointer: "/content".to_string(),
+                        value: None
+                    }
+                )
+                .with_probability(0.25),
+                FaultRule::new("*", Fault::Timeout),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_corrupt_removes_field() {
+        let mut result = json!({ "content": [{ "type": "text", "text": "hi" }] });
+        assert!(corrupt(&mut result, "/content/0/text", None));
+        assert_eq!(result, json!({ "content": [{ "type": "text" }] }));
+        assert!(!corrupt(&mut result, "/missing/field", None));
+    }
+}
@@ -2,19 +2,23 @@
 
 use async_trait::async_trait;
 use mcp_core::interceptor::{
-    InterceptionResult, InterceptorStats, MessageContext, MessageInterceptor,
+    InterceptAction, InterceptionResult, InterceptorStats, MessageContext, MessageInterceptor,
 };
 use mcp_core::McpResult;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
+use super::redaction::Redactor;
+
 /// Interceptor that logs all MCP messages for debugging and monitoring
 pub struct LoggingInterceptor {
     name: String,
     stats: Arc<RwLock<InterceptorStats>>,
     /// Whether to log message content (can be verbose)
     log_content: bool,
+    /// Masks secrets in logged content
+    redactor: Option<Arc<Redactor>>,
 }
 
 impl LoggingInterceptor {
@@ -24,8 +28,15 @@ impl LoggingInterceptor {
             name: "LoggingInterceptor".to_string(),
             stats: Arc::new(RwLock::new(InterceptorStats::default())),
             log_content,
+            redactor: None,
         }
     }
+
+    /// Mask secrets with the given redactor before logging message content
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
 }
 
 #[async_trait]
@@ -44,7 +55,12 @@ impl MessageInterceptor for LoggingInterceptor {
         true
     }
 
-    async fn intercept(&self, context: MessageContext) -> McpResult<InterceptionResult> {
+    async fn intercept(&self, mut context: MessageContext) -> McpResult<InterceptionResult> {
+        let action = self.intercept_in_place(&mut context).await?;
+        Ok(action.into_result(context.message))
+    }
+
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
         let start = std::time::Instant::now();
 
         // Log based on message type
@@ -57,7 +73,10 @@ impl MessageInterceptor for LoggingInterceptor {
                 self.name,
                 direction,
                 method,
-                serde_json::to_string(&context.message)?
+                match &self.redactor {
+                    Some(redactor) => redactor.redact_message(&context.message).0.to_string(),
+                    None => serde_json::to_string(&context.message)?,
+                }
             );
         } else {
             debug!(
@@ -80,7 +99,7 @@ impl MessageInterceptor for LoggingInterceptor {
             / stats.total_intercepted as f64;
 
         // Pass through without modification
-        Ok(InterceptionResult::pass_through(context.message))
+        Ok(InterceptAction::Continue)
     }
 
     async fn get_stats(&self) -> InterceptorStats {
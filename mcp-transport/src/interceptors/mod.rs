@@ -1,14 +1,25 @@
 //! Built-in interceptors for MCP traffic modification
 //!
 //! This module provides concrete implementations of the MessageInterceptor trait
-//! for common use cases like logging, validation, rate limiting, and transformation.
+//! for common use cases like logging, secret redaction, validation, tool policy, rate limiting,
+//! transformation, fault injection, and request/response correlation.
 
 pub mod logging;
+pub mod redaction;
 pub mod validation;
+pub mod policy;
 pub mod rate_limit;
 pub mod transform;
+pub mod fault;
+pub mod correlation;
+pub mod repair;
 
 pub use logging::LoggingInterceptor;
+pub use redaction::{RedactionConfig, RedactionInterceptor, Redactor};
 pub use validation::ValidationInterceptor;
+pub use policy::{ArgumentCheck, ArgumentRule, PolicyInterceptor, PolicyViolation, ToolPolicy};
 pub use rate_limit::RateLimitInterceptor;
 pub use transform::{TransformInterceptor, TransformOperation, TransformRule};
+pub use fault::{Fault, FaultConfig, FaultInjectionInterceptor, FaultRule};
+pub use correlation::CorrelationInterceptor;
+pub use repair::ArgumentRepairInterceptor;
@@ -0,0 +1,472 @@
+//! Policy interceptor that allows or denies tool calls
+
+use async_trait::async_trait;
+use mcp_core::interceptor::{
+    InterceptionResult, InterceptorStats, MessageContext, MessageDirection, MessageInterceptor,
+};
+use mcp_core::messages::JsonRpcMessage;
+use mcp_core::McpResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Check applied to a single tool argument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArgumentCheck {
+    /// Value must be a filesystem path inside one of the given roots
+    PathWithin { roots: Vec<String> },
+    /// Value must not match any of the given glob patterns
+    Deny { patterns: Vec<String> },
+}
+
+/// A rule restricting the arguments a tool may be called with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArgumentRule {
+    /// Tool name pattern this rule applies to (e.g. "fs_*", "*" for all)
+    pub tool_pattern: String,
+    /// Dotted path inside the call arguments (e.g. "path", "options.dir")
+    pub argument: String,
+    /// Check to apply to the argument value
+    pub check: ArgumentCheck,
+}
+
+/// Allow/deny policy for `tools/call` requests
+///
+/// Deny patterns take precedence over allow patterns. An empty allowlist
+/// permits every tool that is not denied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolPolicy {
+    /// Tool name patterns that may be called
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Tool name patterns that may never be called
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Argument-content rules
+    #[serde(default)]
+    pub arguments: Vec<ArgumentRule>,
+}
+
+/// Reason a tool call was rejected by a [`ToolPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// Tool matched a deny pattern
+    Denied { tool: String, pattern: String },
+    /// Tool did not match any allow pattern
+    NotAllowed { tool: String },
+    /// An argument failed an argument rule
+    ArgumentRejected {
+        tool: String,
+        argument: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Denied { tool, pattern } => {
+                write!(
+                    f,
+                    "Tool '{}' is denied by policy pattern '{}'",
+                    tool, pattern
+                )
+            }
+            Self::NotAllowed { tool } => {
+                write!(f, "Tool '{}' is not in the policy allowlist", tool)
+            }
+            Self::ArgumentRejected {
+                tool,
+                argument,
+                reason,
+            } => write!(
+                f,
+                "Argument '{}' of tool '{}' rejected by policy: {}",
+                argument, tool, reason
+            ),
+        }
+    }
+}
+
+impl ToolPolicy {
+    /// Create an empty policy that allows everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an allowed tool pattern
+    pub fn allow(mut self, pattern: impl Into<String>) -> Self {
+        self.allow.push(pattern.into());
+        self
+    }
+
+    /// Add a denied tool pattern
+    pub fn deny(mut self, pattern: impl Into<String>) -> Self {
+        self.deny.push(pattern.into());
+        self
+    }
+
+    /// Add an argument rule
+    pub fn argument_rule(mut self, rule: ArgumentRule) -> Self {
+        self.arguments.push(rule);
+        self
+    }
+
+    /// Check a tool call against this policy
+    pub fn evaluate(&self, tool: &str, arguments: Option<&Value>) -> Result<(), PolicyViolation> {
+        if let Some(pattern) = self.deny.iter().find(|p| glob_match(p, tool)) {
+            return Err(PolicyViolation::Denied {
+                tool: tool.to_string(),
+                pattern: pattern.clone(),
+            });
+        }
+
+        if !self.allow.is_empty() && !self.allow.iter().any(|p| glob_match(p, tool)) {
+            return Err(PolicyViolation::NotAllowed {
+                tool: tool.to_string(),
+            });
+        }
+
+        for rule in self
+            .arguments
+            .iter()
+            .filter(|r| glob_match(&r.tool_pattern, tool))
+        {
+            let value = arguments.and_then(|args| {
+                rule.argument
+                    .split('.')
+                    .try_fold(args, |current, part| current.get(part))
+            });
+
+            if let Some(value) = value {
+                rule.check
+                    .apply(value)
+                    .map_err(|reason| PolicyViolation::ArgumentRejected {
+                        tool: tool.to_string(),
+                        argument: rule.argument.clone(),
+                        reason,
+                    })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ArgumentCheck {
+    fn apply(&self, value: &Value) -> Result<(), String> {
+        // Arrays are checked element by element
+        if let Value::Array(items) = value {
+            return items.iter().try_for_each(|item| self.apply(item));
+        }
+
+        match self {
+            ArgumentCheck::PathWithin { roots } => {
+                let path = value
+                    .as_str()
+                    .ok_or_else(|| "expected a string path".to_string())?;
+                if roots.iter().any(|root| path_within(path, root)) {
+                    Ok(())
+                } else {
+                    Err(format!("path '{}' is outside the allowed roots", path))
+                }
+            }
+            ArgumentCheck::Deny { patterns } => {
+                let text = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                match patterns.iter().find(|p| glob_match(p, &text)) {
+                    Some(pattern) => Err(format!("value matches denied pattern '{}'", pattern)),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Lexically resolve `path` against `root` and check it does not escape it
+fn path_within(path: &str, root: &str) -> bool {
+    let root = normalize(Path::new(root));
+    let candidate = if Path::new(path).is_absolute() {
+        normalize(Path::new(path))
+    } else {
+        normalize(&root.join(path))
+    };
+    candidate.starts_with(&root)
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Match `text` against a glob pattern supporting `*` and `?`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Interceptor that enforces tool allow/deny policies on `tools/call` requests
+///
+/// Per-client policies are selected by the `client_id` metadata entry of the
+/// message context (falling back to the session id) and replace the default
+/// policy for that client.
+pub struct PolicyInterceptor {
+    name: String,
+    stats: Arc<RwLock<InterceptorStats>>,
+    default_policy: Arc<RwLock<ToolPolicy>>,
+    client_policies: Arc<RwLock<HashMap<String, ToolPolicy>>>,
+}
+
+impl PolicyInterceptor {
+    /// Create a new policy interceptor with the given default policy
+    pub fn new(policy: ToolPolicy) -> Self {
+        Self {
+            name: "PolicyInterceptor".to_string(),
+            stats: Arc::new(RwLock::new(InterceptorStats::default())),
+            default_policy: Arc::new(RwLock::new(policy)),
+            client_policies: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Replace the default policy
+    pub async fn set_default_policy(&self, policy: ToolPolicy) {
+        *self.default_policy.write().await = policy;
+    }
+
+    /// Set the policy for a specific client
+    pub async fn set_client_policy(&self, client_id: impl Into<String>, policy: ToolPolicy) {
+        self.client_policies
+            .write()
+            .await
+            .insert(client_id.into(), policy);
+    }
+
+    /// Remove a client-specific policy
+    pub async fn remove_client_policy(&self, client_id: &str) -> bool {
+        self.client_policies
+            .write()
+            .await
+            .remove(client_id)
+            .is_some()
+    }
+
+    fn client_id(context: &MessageContext) -> Option<String> {
+        context
+            .metadata
+            .get("client_id")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or_else(|| context.session_id.clone())
+    }
+
+    async fn evaluate(&self, context: &MessageContext) -> Result<(), PolicyViolation> {
+        let JsonRpcMessage::Request(request) = &context.message else {
+            return Ok(());
+        };
+        let Some(params) = request.params.as_ref() else {
+            return Ok(());
+        };
+        let Some(tool) = params.get("name").and_then(|n| n.as_str()) else {
+            return Ok(());
+        };
+        let arguments = params.get("arguments");
+
+        if let Some(client_id) = Self::client_id(context) {
+            if let Some(policy) = self.client_policies.read().await.get(&client_id) {
+                return policy.evaluate(tool, arguments);
+            }
+        }
+
+        self.default_policy.read().await.evaluate(tool, arguments)
+    }
+}
+
+#[async_trait]
+impl MessageInterceptor for PolicyInterceptor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> u32 {
+        // Run after validation but before rate limiting and transforms
+        25
+    }
+
+    async fn should_intercept(&self, context: &MessageContext) -> bool {
+        matches!(context.direction, MessageDirection::Outgoing)
+            && context.method() == Some("tools/call")
+    }
+
+    async fn intercept(&self, context: MessageContext) -> McpResult<InterceptionResult> {
+        let start = std::time::Instant::now();
+
+        let verdict = self.evaluate(&context).await;
+
+        let mut stats = self.stats.write().await;
+        stats.total_intercepted += 1;
+        stats.last_processed = Some(chrono::Utc::now());
+
+        let elapsed = start.elapsed().as_millis() as f64;
+        stats.avg_processing_time_ms =
+            (stats.avg_processing_time_ms * (stats.total_intercepted - 1) as f64 + elapsed)
+                / stats.total_intercepted as f64;
+
+        match verdict {
+            Ok(()) => Ok(InterceptionResult::pass_through(context.message)),
+            Err(violation) => {
+                stats.total_blocked += 1;
+                drop(stats);
+
+                warn!("[{}] {}", self.name, violation);
+                Ok(InterceptionResult::blocked(violation.to_string()))
+            }
+        }
+    }
+
+    async fn get_stats(&self) -> InterceptorStats {
+        self.stats.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::messages::{JsonRpcRequest, RequestId};
+    use serde_json::json;
+
+    fn tool_call(name: &str, arguments: Value) -> MessageContext {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            method: "tools/call".to_string(),
+            params: Some(json!({ "name": name, "arguments": arguments })),
+        };
+        MessageContext::new(JsonRpcMessage::Request(request), MessageDirection::Outgoing)
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("fs_*", "fs_read"));
+        assert!(glob_match("fs_?ead", "fs_read"));
+        assert!(glob_match("*_delete*", "repo_delete_branch"));
+        assert!(!glob_match("fs_*", "git_status"));
+        assert!(!glob_match("fs_read", "fs_read_all"));
+    }
+
+    #[test]
+    fn test_path_within() {
+        assert!(path_within("/srv/data/a.txt", "/srv/data"));
+        assert!(path_within("notes/a.txt", "/srv/data"));
+        assert!(!path_within("/srv/data/../secrets", "/srv/data"));
+        assert!(!path_within("../../etc/passwd", "/srv/data"));
+        assert!(!path_within("/srv/database", "/srv/data"));
+    }
+
+    #[tokio::test]
+    async fn test_deny_takes_precedence() {
+        let policy = ToolPolicy::new().allow("*").deny("*_delete*");
+        let interceptor = PolicyInterceptor::new(policy);
+
+        let result = interceptor
+            .intercept(tool_call("repo_delete_branch", json!({})))
+            .await
+            .unwrap();
+        assert!(result.block);
+        assert!(result.reasoning.unwrap().contains("*_delete*"));
+
+        let result = interceptor
+            .intercept(tool_call("repo_list", json!({})))
+            .await
+            .unwrap();
+        assert!(!result.block);
+
+        let stats = interceptor.get_stats().await;
+        assert_eq!(stats.total_intercepted, 2);
+        assert_eq!(stats.total_blocked, 1);
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_and_argument_rules() {
+        let policy = ToolPolicy::new().allow("fs_*").argument_rule(ArgumentRule {
+            tool_pattern: "fs_*".to_string(),
+            argument: "path".to_string(),
+            check: ArgumentCheck::PathWithin {
+                roots: vec!["/sandbox".to_string()],
+            },
+        });
+        let interceptor = PolicyInterceptor::new(policy);
+
+        let result = interceptor
+            .intercept(tool_call("shell_exec", json!({})))
+            .await
+            .unwrap();
+        assert!(result.block);
+
+        let result = interceptor
+            .intercept(tool_call("fs_read", json!({ "path": "/etc/passwd" })))
+            .await
+            .unwrap();
+        assert!(result.block);
+
+        let result = interceptor
+            .intercept(tool_call("fs_read", json!({ "path": "/sandbox/notes.md" })))
+            .await
+            .unwrap();
+        assert!(!result.block);
+    }
+
+    #[tokio::test]
+    async fn test_client_policy_overrides_default() {
+        let interceptor = PolicyInterceptor::new(ToolPolicy::new().deny("*"));
+        interceptor
+            .set_client_policy("trusted-agent", ToolPolicy::new())
+            .await;
+
+        let mut context = tool_call("anything", json!({}));
+        assert!(interceptor.intercept(context.clone()).await.unwrap().block);
+
+        context
+            .metadata
+            .insert("client_id".to_string(), json!("trusted-agent"));
+        assert!(!interceptor.intercept(context).await.unwrap().block);
+    }
+}
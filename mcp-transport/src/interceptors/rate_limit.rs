@@ -2,7 +2,8 @@
 
 use async_trait::async_trait;
 use mcp_core::interceptor::{
-    InterceptionResult, InterceptorStats, MessageContext, MessageDirection, MessageInterceptor,
+    InterceptAction, InterceptionResult, InterceptorStats, MessageContext, MessageDirection,
+    MessageInterceptor,
 };
 use mcp_core::McpResult;
 use std::collections::HashMap;
@@ -119,7 +120,12 @@ impl MessageInterceptor for RateLimitInterceptor {
         matches!(context.direction, MessageDirection::Outgoing) && context.method().is_some()
     }
 
-    async fn intercept(&self, context: MessageContext) -> McpResult<InterceptionResult> {
+    async fn intercept(&self, mut context: MessageContext) -> McpResult<InterceptionResult> {
+        let action = self.intercept_in_place(&mut context).await?;
+        Ok(action.into_result(context.message))
+    }
+
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
         let start = std::time::Instant::now();
 
         let method = context.method().unwrap_or("unknown");
@@ -142,7 +148,7 @@ impl MessageInterceptor for RateLimitInterceptor {
 
         if allowed {
             // Under rate limit
-            Ok(InterceptionResult::pass_through(context.message))
+            Ok(InterceptAction::Continue)
         } else {
             // Rate limit exceeded
             stats.total_blocked += 1;
@@ -153,10 +159,12 @@ impl MessageInterceptor for RateLimitInterceptor {
                 self.name, method, current_rate
             );
 
-            Ok(InterceptionResult::blocked(format!(
-                "Rate limit exceeded for method '{}' ({}/window)",
-                method, current_rate
-            )))
+            Ok(InterceptAction::Block {
+                reasoning: Some(format!(
+                    "Rate limit exceeded for method '{}' ({}/window)",
+                    method, current_rate
+                )),
+            })
         }
     }
 
@@ -185,7 +193,7 @@ mod tests {
         assert!(!limiter.check_and_record("test/method"));
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_rate_limiter_sliding_window() {
         let mut limiter = RateLimiter::new(2, 1); // 2 requests per second
 
@@ -0,0 +1,344 @@
+//! Argument repair interceptor that fixes schema-invalid tool calls
+//!
+//! Tool schemas are learned from the server's `tools/list` responses. Each
+//! `tools/call` is then checked against its tool's schema before it reaches
+//! the server. Arguments the [`ParameterValidator`] can fix on its own, such as
+//! numbers sent as strings, are fixed. Arguments that are still invalid go to
+//! an [`ArgumentRepairer`], when one is set, and its answer is validated once
+//! more. A repaired call is forwarded in place of the original, with
+//! `_meta.genmcp/argumentRepair` saying who repaired it and what changed.
+//! Calls that cannot be repaired are forwarded untouched, so the server still
+//! reports the error.
+
+use async_trait::async_trait;
+use mcp_core::interceptor::{
+    InterceptAction, InterceptionResult, InterceptorStats, MessageContext, MessageDirection,
+    MessageInterceptor,
+};
+use mcp_core::messages::JsonRpcMessage;
+use mcp_core::validation::{ArgumentRepairer, ParameterValidator};
+use mcp_core::McpResult;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// `_meta` entry describing a repair
+pub const REPAIR_META_KEY: &str = "genmcp/argumentRepair";
+
+/// Interceptor that repairs `tools/call` arguments against the tool's schema
+pub struct ArgumentRepairInterceptor {
+    name: String,
+    stats: Arc<RwLock<InterceptorStats>>,
+    validator: ParameterValidator,
+    /// Input schemas by tool name
+    schemas: RwLock<HashMap<String, Value>>,
+    repairer: Option<Arc<dyn ArgumentRepairer>>,
+}
+
+impl ArgumentRepairInterceptor {
+    /// Create a repair interceptor that only applies validator transformations
+    pub fn new() -> Self {
+        Self {
+            name: "ArgumentRepairInterceptor".to_string(),
+            stats: Arc::new(RwLock::new(InterceptorStats::default())),
+            validator: ParameterValidator::new(),
+            schemas: RwLock::new(HashMap::new()),
+            repairer: None,
+        }
+    }
+
+    /// Ask `repairer` to fix arguments the validator cannot
+    pub fn with_repairer(mut self, repairer: Arc<dyn ArgumentRepairer>) -> Self {
+        self.repairer = Some(repairer);
+        self
+    }
+
+    /// Know a tool's input schema before the server lists it
+    pub fn with_schema(mut self, tool: impl Into<String>, schema: Value) -> Self {
+        self.schemas.get_mut().insert(tool.into(), schema);
+        self
+    }
+
+    /// Remember the input schemas in a `tools/list` result
+    async fn learn_schemas(&self, result: &Value) {
+        let Some(tools) = result.get("tools").and_then(Value::as_array) else {
+            return;
+        };
+        let mut schemas = self.schemas.write().await;
+        for tool in tools {
+            if let (Some(name), Some(schema)) = (
+                tool.get("name").and_then(Value::as_str),
+                tool.get("inputSchema"),
+            ) {
+                schemas.insert(name.to_string(), schema.clone());
+            }
+        }
+    }
+
+    /// Repaired arguments, who repaired them and the changes made; `None`
+    /// when the arguments are fine as they are or cannot be repaired
+    async fn repair(
+        &self,
+        tool: &str,
+        arguments: &Value,
+    ) -> Option<(Value, &'static str, Vec<String>)> {
+        let schema = self.schemas.read().await.get(tool).cloned()?;
+        let checked = self.validator.validate(&schema, arguments);
+        if checked.is_valid {
+            return (!checked.transformations.is_empty()).then_some((
+                checked.validated_params,
+                "validator",
+                checked.transformations,
+            ));
+        }
+
+        let repairer = self.repairer.as_ref()?;
+        let errors: Vec<String> = checked.errors.iter().map(ToString::to_string).collect();
+        let candidate = match repairer
+            .repair(tool, &schema, &checked.validated_params, &errors)
+            .await
+        {
+            Ok(candidate) => candidate,
+            Err(e) => {
+                warn!(
+                    "[{}] Could not repair arguments for {}: {}",
+                    self.name, tool, e
+                );
+                return None;
+            }
+        };
+        let rechecked = self.validator.validate(&schema, &candidate);
+        if !rechecked.is_valid {
+            debug!(
+                "[{}] Repaired arguments for {} are still invalid",
+                self.name, tool
+            );
+            return None;
+        }
+        let mut changes = checked.transformations;
+        changes.extend(errors.into_iter().map(|error| format!("Fixed: {}", error)));
+        changes.extend(rechecked.transformations);
+        Some((rechecked.validated_params, "repairer", changes))
+    }
+}
+
+impl Default for ArgumentRepairInterceptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MessageInterceptor for ArgumentRepairInterceptor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn priority(&self) -> u32 {
+        // Run after validation but before policy, so policy sees the repaired call
+        22
+    }
+
+    async fn should_intercept(&self, context: &MessageContext) -> bool {
+        match (&context.direction, &context.message) {
+            (MessageDirection::Outgoing, JsonRpcMessage::Request(request)) => {
+                request.method == "tools/call"
+            }
+            (MessageDirection::Incoming, JsonRpcMessage::Response(response)) => response
+                .result
+                .as_ref()
+                .is_some_and(|result| result.get("tools").is_some()),
+            _ => false,
+        }
+    }
+
+    async fn intercept(&self, mut context: MessageContext) -> McpResult<InterceptionResult> {
+        let action = self.intercept_in_place(&mut context).await?;
+        Ok(action.into_result(context.message))
+    }
+
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
+        let request = match &mut context.message {
+            JsonRpcMessage::Response(response) => {
+                if let Some(result) = &response.result {
+                    self.learn_schemas(result).await;
+                }
+                return Ok(InterceptAction::Continue);
+            }
+            JsonRpcMessage::Request(request) if request.method == "tools/call" => request,
+            _ => return Ok(InterceptAction::Continue),
+        };
+        let Some(tool) = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        else {
+            return Ok(InterceptAction::Continue);
+        };
+        let arguments = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("arguments"))
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+
+        let repaired = self.repair(&tool, &arguments).await;
+        let mut stats = self.stats.write().await;
+        stats.total_intercepted += 1;
+        stats.last_processed = Some(chrono::Utc::now());
+        let Some((arguments, by, changes)) = repaired else {
+            return Ok(InterceptAction::Continue);
+        };
+
+        if let Some(params) = request.params.as_mut() {
+            params["arguments"] = arguments;
+        }
+        if let Some(meta) = request.meta_mut() {
+            meta.insert(
+                REPAIR_META_KEY.to_string(),
+                json!({ "by": by, "changes": changes }),
+            );
+        }
+        stats.total_modified += 1;
+        Ok(InterceptAction::Modified {
+            reasoning: Some(format!(
+                "Repaired arguments for {} ({}): {}",
+                tool,
+                by,
+                changes.join("; ")
+            )),
+            confidence: Some(if by == "validator" { 1.0 } else { 0.8 }),
+        })
+    }
+
+    async fn get_stats(&self) -> InterceptorStats {
+        self.stats.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::messages::{JsonRpcRequest, JsonRpcResponse};
+
+    /// Supplies the missing query
+    struct AddQuery;
+
+    #[async_trait]
+    impl ArgumentRepairer for AddQuery {
+        async fn repair(
+            &self,
+            _tool: &str,
+            _schema: &Value,
+            arguments: &Value,
+            errors: &[String],
+        ) -> McpResult<Value> {
+            assert!(errors[0].contains("'query' is required"));
+            let mut arguments = arguments.clone();
+            arguments["query"] = json!("rust");
+            Ok(arguments)
+        }
+    }
+
+    fn call(arguments: Value) -> MessageContext {
+        let request = JsonRpcRequest::new(
+            "1",
+            "tools/call",
+            json!({"name": "search", "arguments": arguments}),
+        );
+        MessageContext::new(JsonRpcMessage::Request(request), MessageDirection::Outgoing)
+    }
+
+    fn listing() -> MessageContext {
+        let response = JsonRpcResponse::success(
+            "0",
+            json!({"tools": [{
+                "name": "search",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string"},
+                        "limit": {"type": "integer"}
+                    },
+                    "required": ["query"]
+                }
+            }]}),
+        );
+        MessageContext::new(
+            JsonRpcMessage::Response(response),
+            MessageDirection::Incoming,
+        )
+    }
+
+    fn params(result: &InterceptionResult) -> &Value {
+        match &result.message {
+            JsonRpcMessage::Request(request) => request.params.as_ref().unwrap(),
+            other => panic!("Expected a request, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validator_fixes_are_applied_and_annotated() {
+        let interceptor = ArgumentRepairInterceptor::new();
+        // Unknown tools pass untouched
+        let result = interceptor
+            .intercept(call(json!({"query": "rust", "limit": "5"})))
+            .await
+            .unwrap();
+        assert!(!result.modified);
+
+        assert!(interceptor.should_intercept(&listing()).await);
+        interceptor.intercept(listing()).await.unwrap();
+        let result = interceptor
+            .intercept(call(json!({"query": "rust", "limit": "5"})))
+            .await
+            .unwrap();
+        assert!(result.modified);
+        assert_eq!(
+            params(&result)["arguments"],
+            json!({"query": "rust", "limit": 5})
+        );
+        assert_eq!(params(&result)["_meta"][REPAIR_META_KEY]["by"], "validator");
+
+        let result = interceptor
+            .intercept(call(json!({"query": "rust"})))
+            .await
+            .unwrap();
+        assert!(!result.modified);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_calls_go_to_the_repairer() {
+        let unrepaired = ArgumentRepairInterceptor::new();
+        unrepaired.intercept(listing()).await.unwrap();
+        let result = unrepaired
+            .intercept(call(json!({"limit": 3})))
+            .await
+            .unwrap();
+        assert!(!result.modified);
+
+        let interceptor = ArgumentRepairInterceptor::new().with_repairer(Arc::new(AddQuery));
+        interceptor.intercept(listing()).await.unwrap();
+        let result = interceptor
+            .intercept(call(json!({"limit": "3"})))
+            .await
+            .unwrap();
+        assert!(result.modified);
+        assert_eq!(
+            params(&result)["arguments"],
+            json!({"query": "rust", "limit": 3})
+        );
+        let repair = &params(&result)["_meta"][REPAIR_META_KEY];
+        assert_eq!(repair["by"], "repairer");
+        assert!(repair["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|change| change.as_str().unwrap().starts_with("Fixed: ")));
+        assert_eq!(interceptor.get_stats().await.total_modified, 1);
+    }
+}
@@ -2,7 +2,7 @@
 
 use async_trait::async_trait;
 use mcp_core::interceptor::{
-    InterceptionResult, InterceptorStats, MessageContext, MessageInterceptor,
+    InterceptAction, InterceptionResult, InterceptorStats, MessageContext, MessageInterceptor,
 };
 use mcp_core::messages::JsonRpcMessage;
 use mcp_core::McpResult;
@@ -91,7 +91,12 @@ impl MessageInterceptor for ValidationInterceptor {
         true
     }
 
-    async fn intercept(&self, context: MessageContext) -> McpResult<InterceptionResult> {
+    async fn intercept(&self, mut context: MessageContext) -> McpResult<InterceptionResult> {
+        let action = self.intercept_in_place(&mut context).await?;
+        Ok(action.into_result(context.message))
+    }
+
+    async fn intercept_in_place(&self, context: &mut MessageContext) -> McpResult<InterceptAction> {
         let start = std::time::Instant::now();
 
         // Validate the message
@@ -108,7 +113,7 @@ impl MessageInterceptor for ValidationInterceptor {
                         + elapsed)
                         / stats.total_intercepted as f64;
 
-                Ok(InterceptionResult::pass_through(context.message))
+                Ok(InterceptAction::Continue)
             }
             Err(err) => {
                 warn!(
@@ -124,13 +129,12 @@ impl MessageInterceptor for ValidationInterceptor {
                 if self.strict_mode {
                     // Block invalid messages in strict mode
                     stats.total_blocked += 1;
-                    Ok(InterceptionResult::blocked(format!(
-                        "Protocol validation failed: {}",
-                        err
-                    )))
+                    Ok(InterceptAction::Block {
+                        reasoning: Some(format!("Protocol validation failed: {}", err)),
+                    })
                 } else {
                     // Just warn but pass through
-                    Ok(InterceptionResult::pass_through(context.message))
+                    Ok(InterceptAction::Continue)
                 }
             }
         }
@@ -1,9 +1,29 @@
-use anyhow::Result;
-use mcp_common::ProxyId;
-use tracing::info;
+use anyhow::{bail, Context, Result};
+use mcp_common::{init_logging, LogFile, LogFormat, ProxyId};
+use mcp_core::cache::CacheConfig;
+use mcp_core::flood::FloodConfig;
+use mcp_core::transport::{MessageLimits, Oversized};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, info_span, warn, Instrument};
 
+mod aggregator;
+pub mod audit;
+pub mod auth;
+pub mod balance;
+pub mod bundle;
 mod buffered_ipc_client;
+pub mod exporter;
+mod fidelity;
+mod hot_swap;
+pub mod mirror;
 mod proxy;
+pub mod session;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod spool;
+pub mod standby;
 mod stdio_handler;
 mod http_handler;
 mod transport_config;
@@ -12,7 +32,14 @@ pub mod interceptors;
 use proxy::MCPProxy;
 
 // Export modules for testing
+pub use aggregator::{AggregateConfig, AggregateHandler, Aggregator, Backend, BackendConfig};
 pub use buffered_ipc_client::BufferedIpcClient;
+pub use fidelity::FidelityMode;
+pub use hot_swap::{CapabilityDiff, SwapHandle, SwapRequest};
+pub use interceptors::{ArgumentRepairInterceptor, FaultConfig};
+pub use mirror::MirrorConfig;
+pub use session::ProxySession;
+pub use spool::{SpoolConfig, SpoolStats};
 pub use stdio_handler::StdioHandler;
 pub use http_handler::HttpHandler;
 pub use transport_config::TransportConfig;
@@ -20,21 +47,138 @@ pub use transport_config::TransportConfig;
 pub struct ProxyArgs {
     pub transport_config: TransportConfig,
     pub name: String,
+    /// How the proxy's own logs are written, see [`mcp_common::logging`]
+    pub log_format: LogFormat,
+    /// File to log to instead of stdout, see [`mcp_common::logging`]
+    pub log_file: Option<LogFile>,
+    /// Monitor IPC endpoint, see [`mcp_common::IpcEndpoint`]
     pub ipc_socket: String,
     pub verbose: bool,
     pub no_monitor: bool,
+    /// How untouched messages are forwarded to and from the backend
+    pub fidelity: FidelityMode,
+    /// Faults to inject into the backend's replies, for chaos testing clients
+    pub faults: Option<FaultConfig>,
+    /// Repair schema-invalid tool call arguments, see [`interceptors::repair`]
+    pub repair_arguments: bool,
+    /// Interceptors imported from a bundle, see [`bundle`]
+    pub pipeline: Option<bundle::Pipeline>,
+    /// Measure interceptor overhead and warn when a message exceeds this budget
+    pub overhead_budget: Option<Duration>,
+    /// Rate limits for the backend's notifications, see [`mcp_core::flood`]
+    pub notification_limits: Option<FloodConfig>,
+    /// Shadow backend that receives a copy of the traffic, see [`mirror`]
+    pub mirror: Option<MirrorConfig>,
+    /// Cache for listings and resource reads, see [`mcp_core::cache`]
+    pub response_cache: Option<CacheConfig>,
+    /// Where messages wait while the monitor is unavailable, see [`spool`]
+    pub spool: SpoolConfig,
+    /// Hash-chained log of every tool call, see [`audit`]
+    pub audit_log: Option<PathBuf>,
+    /// Identities allowed to use the proxy and their policies, see [`auth`]
+    pub auth: Option<auth::AuthConfig>,
+    /// Size limits on messages, see [`mcp_core::transport::limits`]
+    pub message_limits: Option<MessageLimits>,
+    /// Where to serve Prometheus metrics, see [`exporter`]
+    pub metrics_addr: Option<SocketAddr>,
+}
+
+/// Flood protection for `--notification-limit` and `--notification-mute`
+///
+/// A limit of 0 turns the protection off; a mute of 0 only coalesces.
+pub fn notification_limits(per_second: u32, mute_secs: u64) -> Option<FloodConfig> {
+    if per_second == 0 {
+        return None;
+    }
+    Some(FloodConfig {
+        max_per_window: per_second,
+        window: Duration::from_secs(1),
+        mute_after: if mute_secs == 0 { 0 } else { FloodConfig::default().mute_after },
+        mute_for: Duration::from_secs(mute_secs),
+        ..FloodConfig::default()
+    })
+}
+
+/// Response cache for `--cache-ttl`; a TTL of 0 turns caching off
+pub fn response_cache(ttl_secs: u64) -> Option<CacheConfig> {
+    (ttl_secs > 0).then(|| CacheConfig::with_ttl(Duration::from_secs(ttl_secs)))
+}
+
+/// Monitor spool for `--spool`, `--spool-capacity` and `--spool-max-age`
+///
+/// Without a path messages are buffered in memory only; a maximum age of 0
+/// keeps them until they are delivered or pushed out.
+pub fn spool(path: Option<PathBuf>, capacity: usize, max_age_secs: u64) -> SpoolConfig {
+    SpoolConfig {
+        path,
+        capacity,
+        max_age: (max_age_secs > 0).then(|| Duration::from_secs(max_age_secs)),
+    }
+}
+
+/// Log file for `--log-file`, `--log-rotate` and `--keep`
+pub fn log_file(path: Option<PathBuf>, rotate: &str, keep: usize) -> Result<Option<LogFile>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    Ok(Some(LogFile::new(path).rotation(rotate.parse()?).keep(keep)))
+}
+
+/// Message size limits for `--max-message-size`, `--truncate-oversized`
+/// and `--spill-dir`
+///
+/// A size of 0 means the default limit when truncating or spilling, and no
+/// limit otherwise.
+pub fn message_limits(max_size: usize, truncate: bool, spill_dir: Option<PathBuf>) -> Option<MessageLimits> {
+    if max_size == 0 && !truncate && spill_dir.is_none() {
+        return None;
+    }
+    let mut limits = MessageLimits::default();
+    if max_size > 0 {
+        limits = limits.max_message_size(max_size);
+    }
+    if truncate {
+        limits = limits.oversized(Oversized::Truncate);
+    }
+    if let Some(dir) = spill_dir {
+        limits = limits.spill_to(dir);
+    }
+    Some(limits)
 }
 
 pub async fn run_proxy_app(args: ProxyArgs) -> Result<()> {
     // Initialize tracing
     let log_level = if args.verbose { "debug" } else { "info" };
-    tracing_subscriber::fmt()
-        .with_env_filter(format!("mcp_transport={},mcp_common={}", log_level, log_level))
-        .init();
+    init_logging(
+        args.log_format,
+        format!("mcp_transport={},mcp_common={}", log_level, log_level),
+        args.log_file.as_ref(),
+    )?;
 
     info!("Starting MCP Transport: {}", args.name);
     info!("Transport type: {:?}", args.transport_config.transport_type());
     info!("Target: {}", args.transport_config.display_target());
+    if args.mirror.is_some() && !matches!(args.transport_config, TransportConfig::Stdio { .. }) {
+        bail!("Mirroring is only supported for stdio backends");
+    }
+    if args.response_cache.is_some() && !matches!(args.transport_config, TransportConfig::Stdio { .. }) {
+        bail!("Response caching is only supported for stdio backends");
+    }
+    if args.audit_log.is_some() && !matches!(args.transport_config, TransportConfig::Stdio { .. }) {
+        bail!("Audit logging is only supported for stdio backends");
+    }
+    if args.auth.is_some() && !matches!(args.transport_config, TransportConfig::Stdio { .. }) {
+        bail!("Client authentication is only supported for stdio backends");
+    }
+    if args.message_limits.is_some() && !matches!(args.transport_config, TransportConfig::Stdio { .. }) {
+        bail!("Message size limits are only supported for stdio backends");
+    }
+    if args.metrics_addr.is_some() && !matches!(args.transport_config, TransportConfig::Stdio { .. }) {
+        bail!("Metrics are only supported for stdio backends");
+    }
+    if args.repair_arguments && !matches!(args.transport_config, TransportConfig::Stdio { .. }) {
+        bail!("Argument repair is only supported for stdio backends");
+    }
 
     // Create proxy instance
     let proxy_id = ProxyId::new();
@@ -43,7 +187,50 @@ pub async fn run_proxy_app(args: ProxyArgs) -> Result<()> {
         args.name.clone(),
         args.transport_config.clone(),
     )
-    .await?;
+    .await?
+    .with_fidelity(args.fidelity)
+    .with_spool(args.spool);
+    if let Some(faults) = args.faults {
+        proxy = proxy.with_faults(faults);
+    }
+    if args.repair_arguments {
+        proxy = proxy.with_argument_repair(ArgumentRepairInterceptor::new());
+    }
+    if let Some(pipeline) = args.pipeline {
+        proxy = proxy.with_pipeline(pipeline);
+    }
+    if let Some(budget) = args.overhead_budget {
+        proxy = proxy.with_overhead_budget(budget);
+    }
+    if let Some(limits) = args.notification_limits {
+        proxy = proxy.with_notification_limits(limits);
+    }
+    if let Some(mirror) = args.mirror {
+        proxy = proxy.with_mirror(mirror);
+    }
+    if let Some(cache) = args.response_cache {
+        proxy = proxy.with_response_cache(cache);
+    }
+    if let Some(path) = args.audit_log {
+        proxy = proxy.with_audit_log(path);
+    }
+    if let Some(auth) = args.auth {
+        proxy = proxy.with_auth(auth);
+    }
+    if let Some(limits) = args.message_limits {
+        proxy = proxy.with_message_limits(limits);
+    }
+    if let Some(addr) = args.metrics_addr {
+        // Bind before starting so a taken port fails the proxy, not the scrape
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind metrics endpoint to {}", addr))?;
+        if !addr.ip().is_loopback() {
+            warn!("Metrics endpoint {} is reachable from other hosts", addr);
+        }
+        info!("Serving metrics at http://{}{}", listener.local_addr()?, exporter::METRICS_PATH);
+        proxy = proxy.with_metrics(listener);
+    }
 
     // Start the proxy
     let ipc_socket = if args.no_monitor {
@@ -51,7 +238,11 @@ pub async fn run_proxy_app(args: ProxyArgs) -> Result<()> {
     } else {
         Some(args.ipc_socket.as_str())
     };
-    proxy.start(ipc_socket).await?;
+    // Tie every log line of this run to the proxy's session
+    proxy
+        .start(ipc_socket)
+        .instrument(info_span!("proxy", session = %proxy_id.0))
+        .await?;
 
     Ok(())
 }
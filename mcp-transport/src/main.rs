@@ -1,8 +1,17 @@
 use anyhow::Result;
 use clap::Parser;
-use mcp_transport::{run_proxy_app, ProxyArgs, TransportConfig};
+use mcp_common::LogFormat;
+use mcp_transport::auth::AuthConfig;
+use mcp_transport::bundle::Pipeline;
+use mcp_transport::{
+    log_file, message_limits, notification_limits, response_cache, run_proxy_app, spool,
+    FaultConfig, MirrorConfig, ProxyArgs, TransportConfig,
+};
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "mcp-transport")]
@@ -16,8 +25,8 @@ pub struct Args {
     #[arg(short, long)]
     pub name: Option<String>,
 
-    /// IPC socket path for monitor communication
-    #[arg(short, long, default_value = "/tmp/mcp-monitor.sock")]
+    /// IPC endpoint for monitor communication (socket path, pipe:NAME or tcp:HOST:PORT)
+    #[arg(short, long, default_value = mcp_common::DEFAULT_IPC_ENDPOINT)]
     pub ipc_socket: String,
 
     /// Verbose logging
@@ -31,6 +40,111 @@ pub struct Args {
     /// Skip connecting to monitor (standalone mode)
     #[arg(long, default_value_t = false)]
     pub no_monitor: bool,
+
+    /// Forwarding fidelity (normalize, preserve, raw)
+    #[arg(long, default_value = "normalize")]
+    pub fidelity: String,
+
+    /// Log format: text or json (defaults to $GENMCP_LOG_FORMAT, else text)
+    #[arg(long, value_name = "FORMAT")]
+    pub log_format: Option<String>,
+
+    /// Write logs to this file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// When --log-file is rotated: never, hourly, daily or a size such as 10MB
+    #[arg(long, value_name = "WHEN", default_value = "daily")]
+    pub log_rotate: String,
+
+    /// Rotated log files to keep (0 keeps all)
+    #[arg(long, value_name = "N", default_value_t = 7)]
+    pub keep: usize,
+
+    /// TOML file of faults to inject into server replies (chaos testing)
+    #[arg(long)]
+    pub faults: Option<PathBuf>,
+
+    /// Fix tool call arguments that do not match the tool's input schema
+    /// (e.g. numbers sent as strings) before they reach the server
+    #[arg(long)]
+    pub repair_arguments: bool,
+
+    /// Imported pipeline directory (see `assist-mcp bundle import`) whose
+    /// interceptors the traffic passes through
+    #[arg(long, value_name = "DIR")]
+    pub pipeline: Option<PathBuf>,
+
+    /// Per-message interceptor overhead budget in microseconds; enables
+    /// per-interceptor timing and warns when a message exceeds it
+    #[arg(long, value_name = "MICROS")]
+    pub overhead_budget: Option<u64>,
+
+    /// Notifications per second of one method forwarded before bursts are
+    /// coalesced (0 disables flood protection)
+    #[arg(long, default_value_t = 20)]
+    pub notification_limit: u32,
+
+    /// Seconds to mute a method that keeps flooding (0 only coalesces)
+    #[arg(long, default_value_t = 30)]
+    pub notification_mute: u64,
+
+    /// Shadow server command that receives a copy of every request; its
+    /// answers are compared with the primary's and differences reported
+    #[arg(long, value_name = "COMMAND")]
+    pub mirror: Option<String>,
+
+    /// JSON pointer left out of shadow comparisons (repeatable)
+    #[arg(long, value_name = "POINTER", requires = "mirror")]
+    pub mirror_ignore: Vec<String>,
+
+    /// Seconds to answer repeated tools/resources/prompts listings and
+    /// resource reads from a cache (0 disables caching)
+    #[arg(long, value_name = "SECS", default_value_t = 0)]
+    pub cache_ttl: u64,
+
+    /// File that keeps messages for the monitor while it is unavailable,
+    /// so they survive a proxy restart (default: memory only)
+    #[arg(long, value_name = "PATH")]
+    pub spool: Option<PathBuf>,
+
+    /// Messages kept for an unavailable monitor before the oldest are dropped
+    #[arg(long, value_name = "COUNT", default_value_t = mcp_transport::spool::DEFAULT_SPOOL_CAPACITY)]
+    pub spool_capacity: usize,
+
+    /// Seconds after which a message kept for the monitor is dropped
+    /// (0 keeps it until delivered)
+    #[arg(long, value_name = "SECS", default_value_t = 0)]
+    pub spool_max_age: u64,
+
+    /// Append a hash-chained record of every tool call to this file
+    /// (verify it with `assist-mcp audit verify`)
+    #[arg(long, value_name = "PATH")]
+    pub audit_log: Option<PathBuf>,
+    /// Identities allowed to use the proxy, with their tool policies and
+    /// rate limits (TOML); the client is identified by the owner of its pipe
+    #[arg(long, value_name = "FILE")]
+    pub auth: Option<PathBuf>,
+
+    /// Largest message in bytes either side may send; larger server
+    /// messages are dropped and larger client requests refused
+    /// (0 disables the limit)
+    #[arg(long, value_name = "BYTES", default_value_t = 0)]
+    pub max_message_size: usize,
+
+    /// Cut server messages over --max-message-size to the limit instead of
+    /// dropping them
+    #[arg(long)]
+    pub truncate_oversized: bool,
+
+    /// Directory that large base64 blobs in server messages are written
+    /// to instead of being held in memory
+    #[arg(long, value_name = "DIR")]
+    pub spill_dir: Option<PathBuf>,
+
+    /// Serve Prometheus metrics at http://ADDR/metrics, e.g. 127.0.0.1:9464
+    #[arg(long, value_name = "ADDR")]
+    pub metrics_addr: Option<SocketAddr>,
 }
 
 #[tokio::main]
@@ -47,6 +161,14 @@ async fn main() -> Result<()> {
         format!("mcp-proxy-{}", random_suffix)
     });
 
+    let mirror = args.mirror.map(|command| MirrorConfig {
+        ignore: args.mirror_ignore,
+        ..MirrorConfig::new(TransportConfig::Stdio {
+            command,
+            use_shell: args.shell,
+        })
+    });
+
     // Create transport config from command (this binary only supports stdio)
     let transport_config = TransportConfig::Stdio {
         command: args.command,
@@ -59,6 +181,25 @@ async fn main() -> Result<()> {
         ipc_socket: args.ipc_socket,
         verbose: args.verbose,
         no_monitor: args.no_monitor,
+        fidelity: args.fidelity.parse()?,
+        log_format: LogFormat::resolve(args.log_format.as_deref(), LogFormat::Text)?,
+        log_file: log_file(args.log_file, &args.log_rotate, args.keep)?,
+        faults: args.faults.map(FaultConfig::from_file).transpose()?,
+        repair_arguments: args.repair_arguments,
+        pipeline: args.pipeline.map(Pipeline::load).transpose()?,
+        overhead_budget: args.overhead_budget.map(Duration::from_micros),
+        notification_limits: notification_limits(args.notification_limit, args.notification_mute),
+        mirror,
+        response_cache: response_cache(args.cache_ttl),
+        spool: spool(args.spool, args.spool_capacity, args.spool_max_age),
+        audit_log: args.audit_log,
+        auth: args.auth.map(AuthConfig::from_file).transpose()?,
+        message_limits: message_limits(
+            args.max_message_size,
+            args.truncate_oversized,
+            args.spill_dir,
+        ),
+        metrics_addr: args.metrics_addr,
     };
 
     run_proxy_app(proxy_args).await
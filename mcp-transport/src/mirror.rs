@@ -0,0 +1,587 @@
+//! Shadow traffic: mirroring client requests to a second backend.
+//!
+//! In mirror mode the proxy forwards every client message to the primary
+//! backend as usual and also to a shadow backend started next to it. The
+//! shadow's answers never reach the client. Once both backends have answered
+//! a request their results are compared, and any difference is reported to
+//! the monitor, so a new server version can be validated against production
+//! traffic without being able to affect it.
+//!
+//! Results are compared with [`mcp_core::differential::diff_results`], so
+//! catalogs that merely list entries in another order match. Values that
+//! legitimately differ between runs, such as timestamps or the server
+//! version in `initialize`, can be left out by JSON pointer prefix.
+
+use anyhow::{bail, Context, Result};
+use mcp_common::{IpcMessage, LogEntry, LogLevel, ProxyId};
+use mcp_core::differential::{diff_results, Divergence, SampleCall};
+use mcp_core::messages::{JsonRpcError, JsonRpcMessage, JsonRpcResponse, RequestId};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+use crate::buffered_ipc_client::BufferedIpcClient;
+use crate::transport_config::TransportConfig;
+
+/// How long both backends get to answer a mirrored request
+pub const DEFAULT_MIRROR_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Divergences spelled out in one report; the rest are counted
+const REPORTED_DIVERGENCES: usize = 5;
+
+/// Where mirrored traffic goes and how results are compared
+#[derive(Debug, Clone)]
+pub struct MirrorConfig {
+    /// Shadow backend; only stdio backends can be mirrored to
+    pub shadow: TransportConfig,
+    /// How long to wait for both answers before reporting a missing one
+    pub timeout: Duration,
+    /// JSON pointer prefixes left out of comparisons, e.g. `/serverInfo/version`
+    pub ignore: Vec<String>,
+}
+
+impl MirrorConfig {
+    pub fn new(shadow: TransportConfig) -> Self {
+        Self {
+            shadow,
+            timeout: DEFAULT_MIRROR_TIMEOUT,
+            ignore: Vec::new(),
+        }
+    }
+
+    /// Leave values under a JSON pointer out of comparisons
+    pub fn ignore(mut self, pointer: impl Into<String>) -> Self {
+        self.ignore.push(pointer.into());
+        self
+    }
+
+    fn is_ignored(&self, path: &str) -> bool {
+        self.ignore.iter().any(|prefix| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// How one mirrored request turned out
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorReport {
+    pub id: RequestId,
+    /// Method and target, e.g. `tools/call echo`
+    pub label: String,
+    /// Primary latency, `None` if it did not answer in time
+    pub primary: Option<Duration>,
+    /// Shadow latency, `None` if it did not answer in time
+    pub shadow: Option<Duration>,
+    /// Differences between the two results
+    pub divergences: Vec<Divergence>,
+}
+
+impl MirrorReport {
+    /// Whether both backends answered alike
+    pub fn is_match(&self) -> bool {
+        self.primary.is_some() && self.shadow.is_some() && self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for MirrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Some(primary), Some(shadow)) = (self.primary, self.shadow) else {
+            let missing = if self.primary.is_none() {
+                "Primary"
+            } else {
+                "Shadow"
+            };
+            return write!(
+                f,
+                "{} did not answer {} (id {}) in time",
+                missing, self.label, self.id
+            );
+        };
+
+        let latency = format!("primary {:?}, shadow {:?}", primary, shadow);
+        if self.divergences.is_empty() {
+            return write!(
+                f,
+                "Shadow matched {} (id {}); {}",
+                self.label, self.id, latency
+            );
+        }
+
+        let shown: Vec<String> = self
+            .divergences
+            .iter()
+            .take(REPORTED_DIVERGENCES)
+            .map(|divergence| divergence.to_string())
+            .collect();
+        // Each divergence names the request itself
+        write!(f, "Shadow diverged (id {}): {}", self.id, shown.join("; "))?;
+        if self.divergences.len() > REPORTED_DIVERGENCES {
+            write!(
+                f,
+                " (+{} more)",
+                self.divergences.len() - REPORTED_DIVERGENCES
+            )?;
+        }
+        write!(f, "; {}", latency)
+    }
+}
+
+/// Running totals of a mirroring session
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MirrorStats {
+    /// Requests both backends answered
+    pub compared: u64,
+    /// Compared requests whose results differed
+    pub diverged: u64,
+    /// Requests one of the backends did not answer in time
+    pub timed_out: u64,
+}
+
+impl fmt::Display for MirrorStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} compared, {} diverged, {} timed out",
+            self.compared, self.diverged, self.timed_out
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Exchange {
+    label: String,
+    started: Instant,
+    primary: Option<(Value, Duration)>,
+    shadow: Option<(Value, Duration)>,
+}
+
+/// Pairs up primary and shadow answers to mirrored requests
+///
+/// Takes the current time as an argument and does no I/O.
+#[derive(Debug)]
+pub struct MirrorTracker {
+    config: MirrorConfig,
+    pending: HashMap<RequestId, Exchange>,
+    stats: MirrorStats,
+}
+
+impl MirrorTracker {
+    pub fn new(config: MirrorConfig) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+            stats: MirrorStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> MirrorStats {
+        self.stats
+    }
+
+    /// Record a line the client sent; returns whether the shadow should get it
+    ///
+    /// Requests and notifications are mirrored. Client responses answer
+    /// requests only the primary made, so they are not.
+    pub fn observe_client(&mut self, line: &str, now: Instant) -> bool {
+        match serde_json::from_str::<JsonRpcMessage>(line.trim()) {
+            Ok(JsonRpcMessage::Request(request)) => {
+                let label =
+                    SampleCall::new(request.method, request.params.unwrap_or_default()).label();
+                self.pending.insert(
+                    request.id,
+                    Exchange {
+                        label,
+                        started: now,
+                        primary: None,
+                        shadow: None,
+                    },
+                );
+                true
+            }
+            Ok(JsonRpcMessage::Notification(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Record a line from the primary backend
+    pub fn observe_primary(&mut self, line: &str, now: Instant) -> Option<MirrorReport> {
+        self.observe_response(line, now, true)
+    }
+
+    /// Record a line from the shadow backend
+    pub fn observe_shadow(&mut self, line: &str, now: Instant) -> Option<MirrorReport> {
+        self.observe_response(line, now, false)
+    }
+
+    fn observe_response(
+        &mut self,
+        line: &str,
+        now: Instant,
+        primary: bool,
+    ) -> Option<MirrorReport> {
+        let Ok(JsonRpcMessage::Response(response)) = serde_json::from_str(line.trim()) else {
+            return None;
+        };
+        let exchange = self.pending.get_mut(&response.id)?;
+        let outcome = (outcome(&response), now.duration_since(exchange.started));
+        if primary {
+            exchange.primary = Some(outcome);
+        } else {
+            exchange.shadow = Some(outcome);
+        }
+        if exchange.primary.is_none() || exchange.shadow.is_none() {
+            return None;
+        }
+
+        let exchange = self.pending.remove(&response.id)?;
+        let (primary, primary_latency) = exchange.primary?;
+        let (shadow, shadow_latency) = exchange.shadow?;
+        let divergences: Vec<Divergence> = diff_results(&exchange.label, &primary, &shadow)
+            .into_iter()
+            .filter(|divergence| !self.config.is_ignored(&divergence.path))
+            .collect();
+
+        self.stats.compared += 1;
+        if !divergences.is_empty() {
+            self.stats.diverged += 1;
+        }
+        Some(MirrorReport {
+            id: response.id,
+            label: exchange.label,
+            primary: Some(primary_latency),
+            shadow: Some(shadow_latency),
+            divergences,
+        })
+    }
+
+    /// Give up on requests that have waited longer than the timeout
+    pub fn expire(&mut self, now: Instant) -> Vec<MirrorReport> {
+        let timeout = self.config.timeout;
+        let expired: Vec<RequestId> = self
+            .pending
+            .iter()
+            .filter(|(_, exchange)| now.duration_since(exchange.started) >= timeout)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut reports = Vec::new();
+        for id in expired {
+            if let Some(exchange) = self.pending.remove(&id) {
+                self.stats.timed_out += 1;
+                reports.push(MirrorReport {
+                    id,
+                    label: exchange.label,
+                    primary: exchange.primary.map(|(_, latency)| latency),
+                    shadow: exchange.shadow.map(|(_, latency)| latency),
+                    divergences: Vec::new(),
+                });
+            }
+        }
+        reports
+    }
+}
+
+/// What a response is compared by: its result, or its error
+fn outcome(response: &JsonRpcResponse) -> Value {
+    match response.error {
+        Some(ref error) => json!({ "error": error }),
+        None => response.result.clone().unwrap_or(Value::Null),
+    }
+}
+
+enum Observed {
+    Client(String),
+    Primary(String),
+}
+
+/// A running shadow backend fed with copies of the proxied traffic
+pub struct Mirror {
+    sender: mpsc::UnboundedSender<Observed>,
+    task: JoinHandle<MirrorStats>,
+}
+
+impl Mirror {
+    /// Start the shadow backend and the task that compares its answers
+    pub fn start(
+        config: MirrorConfig,
+        proxy_id: ProxyId,
+        ipc_client: Option<Arc<BufferedIpcClient>>,
+    ) -> Result<Self> {
+        let TransportConfig::Stdio { command, use_shell } = &config.shadow else {
+            bail!(
+                "Only stdio backends can receive mirrored traffic, got {:?}",
+                config.shadow.transport_type()
+            );
+        };
+        let mut child = crate::proxy::spawn_stdio_server(command, *use_shell)
+            .context("Failed to start shadow backend")?;
+        info!("Mirroring traffic to shadow backend: {}", command);
+
+        let stdin = BufWriter::new(child.stdin.take().context("Failed to get shadow stdin")?);
+        let stdout = BufReader::new(child.stdout.take().context("Failed to get shadow stdout")?);
+        let stderr = BufReader::new(child.stderr.take().context("Failed to get shadow stderr")?);
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let reporter = Reporter {
+            proxy_id,
+            ipc_client,
+        };
+        let tracker = MirrorTracker::new(config);
+        let task = tokio::spawn(async move {
+            let stats = run(tracker, receiver, stdin, stdout, stderr, &reporter).await;
+            if let Err(e) = child.kill().await {
+                debug!("Failed to kill shadow backend: {}", e);
+            }
+            stats
+        });
+
+        Ok(Self { sender, task })
+    }
+
+    /// Copy a line the client sent to the primary
+    pub fn client_line(&self, line: &str) {
+        let _ = self.sender.send(Observed::Client(line.to_string()));
+    }
+
+    /// Copy a line the primary sent, before interceptors touch it
+    pub fn primary_line(&self, line: &str) {
+        let _ = self.sender.send(Observed::Primary(line.to_string()));
+    }
+
+    /// Stop the shadow backend and return the session totals
+    pub async fn shutdown(self) -> MirrorStats {
+        drop(self.sender);
+        self.task.await.unwrap_or_default()
+    }
+}
+
+struct Reporter {
+    proxy_id: ProxyId,
+    ipc_client: Option<Arc<BufferedIpcClient>>,
+}
+
+impl Reporter {
+    async fn report(&self, report: MirrorReport) {
+        if report.is_match() {
+            debug!("{}", report);
+            return;
+        }
+        warn!("{}", report);
+        self.log(LogLevel::Warning, report.to_string()).await;
+    }
+
+    async fn log(&self, level: LogLevel, message: String) {
+        if let Some(ref client) = self.ipc_client {
+            let entry = LogEntry::new(level, message, self.proxy_id.clone());
+            if let Err(e) = client.send(IpcMessage::LogEntry(entry)).await {
+                warn!("Failed to send log entry: {}", e);
+            }
+        }
+    }
+}
+
+async fn run(
+    mut tracker: MirrorTracker,
+    mut receiver: mpsc::UnboundedReceiver<Observed>,
+    mut stdin: BufWriter<tokio::process::ChildStdin>,
+    mut stdout: BufReader<tokio::process::ChildStdout>,
+    mut stderr: BufReader<tokio::process::ChildStderr>,
+    reporter: &Reporter,
+) -> MirrorStats {
+    let mut expiry = interval(Duration::from_secs(1));
+    let mut stderr_open = true;
+
+    loop {
+        tokio::select! {
+            observed = receiver.recv() => match observed {
+                None => break,
+                Some(Observed::Client(line)) => {
+                    if tracker.observe_client(&line, Instant::now()) {
+                        if let Err(e) = write_line(&mut stdin, &line).await {
+                            reporter
+                                .log(LogLevel::Warning, format!("Shadow backend stopped accepting traffic; mirroring disabled: {}", e))
+                                .await;
+                            break;
+                        }
+                    }
+                }
+                Some(Observed::Primary(line)) => {
+                    if let Some(report) = tracker.observe_primary(&line, Instant::now()) {
+                        reporter.report(report).await;
+                    }
+                }
+            },
+
+            result = async {
+                let mut line = String::new();
+                stdout.read_line(&mut line).await.map(|n| (n, line))
+            } => match result {
+                Ok((0, _)) | Err(_) => {
+                    reporter
+                        .log(LogLevel::Warning, "Shadow backend exited; mirroring disabled".to_string())
+                        .await;
+                    break;
+                }
+                Ok((_, line)) => {
+                    // The shadow cannot reach the client, so refuse what it asks of it
+                    if let Ok(JsonRpcMessage::Request(request)) = serde_json::from_str(line.trim()) {
+                        let refusal = JsonRpcResponse::error(
+                            request.id,
+                            JsonRpcError::internal_error("Client requests are not available to a shadow backend"),
+                        );
+                        if let Ok(refusal) = serde_json::to_string(&refusal) {
+                            let _ = write_line(&mut stdin, &refusal).await;
+                        }
+                    } else if let Some(report) = tracker.observe_shadow(&line, Instant::now()) {
+                        reporter.report(report).await;
+                    }
+                }
+            },
+
+            result = async {
+                let mut line = String::new();
+                stderr.read_line(&mut line).await.map(|n| (n, line))
+            }, if stderr_open => match result {
+                Ok((0, _)) | Err(_) => stderr_open = false,
+                Ok((_, line)) => debug!("Shadow stderr: {}", line.trim()),
+            },
+
+            _ = expiry.tick() => {
+                for report in tracker.expire(Instant::now()) {
+                    reporter.report(report).await;
+                }
+            }
+        }
+    }
+
+    let stats = tracker.stats();
+    info!("Mirroring finished: {}", stats);
+    reporter
+        .log(LogLevel::Info, format!("Mirroring finished: {}", stats))
+        .await;
+    stats
+}
+
+async fn write_line(
+    stdin: &mut BufWriter<tokio::process::ChildStdin>,
+    line: &str,
+) -> std::io::Result<()> {
+    stdin.write_all(line.trim_end().as_bytes()).await?;
+    stdin.write_all(b"\n").await?;
+    stdin.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> MirrorTracker {
+        let shadow = TransportConfig::Stdio {
+            command: "shadow".to_string(),
+            use_shell: false,
+        };
+        MirrorTracker::new(MirrorConfig::new(shadow).ignore("/serverInfo/version"))
+    }
+
+    #[test]
+    fn test_answers_are_paired_and_compared() {
+        let mut tracker = tracker();
+        let start = Instant::now();
+
+        assert!(tracker.observe_client(
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo"}}"#,
+            start
+        ));
+        assert!(tracker.observe_client(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            start
+        ));
+        assert!(!tracker.observe_client(r#"{"jsonrpc":"2.0","id":"s1","result":{}}"#, start));
+
+        let later = start + Duration::from_millis(5);
+        assert!(tracker
+            .observe_primary(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"content":[{"type":"text","text":"hi"}]}}"#,
+                later
+            )
+            .is_none());
+        let report = tracker
+            .observe_shadow(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"content":[{"type":"text","text":"HI"}]}}"#,
+                later + Duration::from_millis(5),
+            )
+            .unwrap();
+
+        assert_eq!(report.label, "tools/call echo");
+        assert_eq!(report.divergences.len(), 1);
+        assert_eq!(report.divergences[0].path, "/content");
+        assert_eq!(report.shadow, Some(Duration::from_millis(10)));
+        assert!(report
+            .to_string()
+            .starts_with("Shadow diverged (id 1): tools/call echo /content: "));
+        assert_eq!(tracker.stats().diverged, 1);
+    }
+
+    #[test]
+    fn test_ignored_paths_and_errors() {
+        let mut tracker = tracker();
+        let now = Instant::now();
+
+        tracker.observe_client(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+            now,
+        );
+        tracker.observe_primary(
+            r#"{"jsonrpc":"2.0","id":1,"result":{"serverInfo":{"name":"s","version":"1.0"}}}"#,
+            now,
+        );
+        let report = tracker
+            .observe_shadow(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"serverInfo":{"name":"s","version":"2.0"}}}"#,
+                now,
+            )
+            .unwrap();
+        assert!(report.is_match());
+
+        tracker.observe_client(r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#, now);
+        tracker.observe_primary(r#"{"jsonrpc":"2.0","id":2,"result":{}}"#, now);
+        let report = tracker
+            .observe_shadow(
+                r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"Method not found"}}"#,
+                now,
+            )
+            .unwrap();
+        assert!(report.divergences.iter().any(|d| d.path == "/error"));
+    }
+
+    #[test]
+    fn test_missing_answers_expire() {
+        let mut tracker = tracker();
+        let start = Instant::now();
+
+        tracker.observe_client(r#"{"jsonrpc":"2.0","id":"a","method":"tools/list"}"#, start);
+        tracker.observe_primary(r#"{"jsonrpc":"2.0","id":"a","result":{"tools":[]}}"#, start);
+        assert!(tracker.expire(start + Duration::from_secs(1)).is_empty());
+
+        let reports = tracker.expire(start + DEFAULT_MIRROR_TIMEOUT);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(
+            reports[0].to_string(),
+            "Shadow did not answer tools/list (id a) in time"
+        );
+        assert_eq!(tracker.stats().timed_out, 1);
+
+        // A late answer has nothing left to pair with
+        assert!(tracker
+            .observe_shadow(r#"{"jsonrpc":"2.0","id":"a","result":{"tools":[]}}"#, start)
+            .is_none());
+    }
+}
@@ -1,12 +1,28 @@
 use anyhow::Result;
-use mcp_common::{IpcMessage, ProxyId, ProxyInfo, ProxyStats, ProxyStatus};
+use mcp_core::cache::CacheConfig;
+use mcp_core::flood::FloodConfig;
+use mcp_core::transport::MessageLimits;
+use mcp_common::{IpcMessage, ProxyId, ProxyInfo, ProxyMetrics, ProxyStats, ProxyStatus};
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
 use tokio::process::{Child, Command};
-use tokio::sync::{broadcast, Mutex};
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tracing::{info, warn};
 
+use crate::aggregator::AggregateHandler;
+use crate::audit::Auditor;
+use crate::auth::{AuthConfig, ClientGate};
 use crate::buffered_ipc_client::BufferedIpcClient;
+use crate::bundle::Pipeline;
+use crate::exporter::serve_metrics;
+use crate::fidelity::FidelityMode;
+use crate::hot_swap::{SwapHandle, SwapRequest};
+use crate::interceptors::{ArgumentRepairInterceptor, FaultConfig, FaultInjectionInterceptor};
+use crate::mirror::{Mirror, MirrorConfig};
+use crate::spool::SpoolConfig;
 use crate::stdio_handler::StdioHandler;
 use crate::http_handler::HttpHandler;
 use crate::transport_config::TransportConfig;
@@ -15,24 +31,142 @@ pub struct MCPProxy {
     id: ProxyId,
     name: String,
     transport_config: TransportConfig,
+    fidelity: FidelityMode,
+    faults: Option<FaultConfig>,
+    argument_repair: Option<ArgumentRepairInterceptor>,
+    pipeline: Option<Pipeline>,
+    overhead_budget: Option<Duration>,
+    notification_limits: Option<FloodConfig>,
+    mirror: Option<MirrorConfig>,
+    response_cache: Option<CacheConfig>,
+    spool: SpoolConfig,
+    audit_log: Option<PathBuf>,
+    auth: Option<AuthConfig>,
+    message_limits: Option<MessageLimits>,
+    metrics_listener: Option<TcpListener>,
     stats: Arc<Mutex<ProxyStats>>,
     shutdown_tx: Option<broadcast::Sender<()>>,
+    swap_tx: mpsc::Sender<SwapRequest>,
+    swap_rx: Option<mpsc::Receiver<SwapRequest>>,
 }
 
 impl MCPProxy {
     pub async fn new(id: ProxyId, name: String, transport_config: TransportConfig) -> Result<Self> {
         let mut stats = ProxyStats::default();
         stats.proxy_id = id.clone();
+        let (swap_tx, swap_rx) = mpsc::channel(4);
 
         Ok(Self {
             id,
             name,
             transport_config,
+            fidelity: FidelityMode::default(),
+            faults: None,
+            argument_repair: None,
+            pipeline: None,
+            overhead_budget: None,
+            notification_limits: None,
+            mirror: None,
+            response_cache: None,
+            spool: SpoolConfig::default(),
+            audit_log: None,
+            auth: None,
+            message_limits: None,
+            metrics_listener: None,
             stats: Arc::new(Mutex::new(stats)),
             shutdown_tx: None,
+            swap_tx,
+            swap_rx: Some(swap_rx),
         })
     }
 
+    /// Set how faithfully the stdio backend's traffic is forwarded
+    pub fn with_fidelity(mut self, fidelity: FidelityMode) -> Self {
+        self.fidelity = fidelity;
+        self
+    }
+
+    /// Inject faults into the stdio backend's replies
+    pub fn with_faults(mut self, faults: FaultConfig) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Repair schema-invalid `tools/call` arguments before they reach the
+    /// stdio backend
+    pub fn with_argument_repair(mut self, repair: ArgumentRepairInterceptor) -> Self {
+        self.argument_repair = Some(repair);
+        self
+    }
+
+    /// Run the stdio backend's traffic through an imported interceptor pipeline
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.pipeline = Some(pipeline);
+        self
+    }
+
+    /// Measure per-interceptor latency and warn about messages whose
+    /// interceptor pipeline takes longer than `budget`
+    pub fn with_overhead_budget(mut self, budget: Duration) -> Self {
+        self.overhead_budget = Some(budget);
+        self
+    }
+
+    /// Coalesce bursts of the stdio backend's notifications and mute floods
+    pub fn with_notification_limits(mut self, limits: FloodConfig) -> Self {
+        self.notification_limits = Some(limits);
+        self
+    }
+
+    /// Send a copy of the stdio backend's traffic to a shadow backend and
+    /// report where their answers differ
+    pub fn with_mirror(mut self, mirror: MirrorConfig) -> Self {
+        self.mirror = Some(mirror);
+        self
+    }
+
+    /// Answer repeated listings and resource reads of the stdio backend from a cache
+    pub fn with_response_cache(mut self, config: CacheConfig) -> Self {
+        self.response_cache = Some(config);
+        self
+    }
+
+    /// Buffer messages for an unavailable monitor according to `spool`
+    pub fn with_spool(mut self, spool: SpoolConfig) -> Self {
+        self.spool = spool;
+        self
+    }
+
+    /// Record the stdio backend's tool calls in a hash-chained audit log
+    pub fn with_audit_log(mut self, path: PathBuf) -> Self {
+        self.audit_log = Some(path);
+        self
+    }
+
+    /// Authenticate the stdio client and enforce per-identity policies
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Hold the stdio backend's lines to size limits, truncating or
+    /// rejecting what is over them
+    pub fn with_message_limits(mut self, limits: MessageLimits) -> Self {
+        self.message_limits = Some(limits);
+        self
+    }
+
+    /// Serve Prometheus metrics for the stdio backend's traffic on `listener`
+    pub fn with_metrics(mut self, listener: TcpListener) -> Self {
+        self.metrics_listener = Some(listener);
+        self
+    }
+
+    /// Handle for replacing or restarting the backend while the proxy runs
+    pub fn swap_handle(&self) -> SwapHandle {
+        SwapHandle::new(self.swap_tx.clone())
+    }
+
     pub async fn start(&mut self, ipc_socket_path: Option<&str>) -> Result<()> {
         info!("Starting MCP proxy: {}", self.name);
 
@@ -47,7 +181,7 @@ impl MCPProxy {
                 socket_path
             );
             Some(Arc::new(
-                BufferedIpcClient::new(socket_path.to_string()).await,
+                BufferedIpcClient::with_spool(socket_path.to_string(), self.spool.clone()).await?,
             ))
         } else {
             info!("Running in standalone mode (monitor disabled)");
@@ -79,13 +213,77 @@ impl MCPProxy {
 
                 // Create STDIO handler
                 let mut handler =
-                    StdioHandler::new(self.id.clone(), self.stats.clone(), buffered_client.clone()).await?;
+                    StdioHandler::new(self.id.clone(), self.stats.clone(), buffered_client.clone())
+                        .await?
+                        .with_fidelity(self.fidelity);
+                if let Some(limits) = self.notification_limits.take() {
+                    handler = handler.with_flood_guard(limits);
+                }
+                if let Some(cache) = self.response_cache.take() {
+                    handler = handler.with_response_cache(cache);
+                }
+                if let Some(path) = self.audit_log.take() {
+                    let auditor = Auditor::open(&path, self.transport_config.display_target())?;
+                    info!("Auditing tool calls to {} ({} earlier record(s))", path.display(), auditor.log().len());
+                    handler = handler.with_audit(auditor);
+                }
+                if let Some(auth) = self.auth.take() {
+                    handler = handler.with_gate(ClientGate::from_config(auth));
+                }
+                if let Some(limits) = self.message_limits.take() {
+                    handler = handler.with_limits(limits);
+                }
+                if let Some(mirror) = self.mirror.take() {
+                    let mirror = Mirror::start(mirror, self.id.clone(), buffered_client.clone())?;
+                    handler = handler.with_mirror(mirror);
+                }
+                if let Some(swap_rx) = self.swap_rx.take() {
+                    handler = handler.with_hot_swap(self.transport_config.clone(), swap_rx);
+                }
+                let mut exporter = None;
+                if let Some(listener) = self.metrics_listener.take() {
+                    let metrics = Arc::new(Mutex::new(ProxyMetrics::new()));
+                    exporter = Some(tokio::spawn(serve_metrics(listener, metrics.clone(), self.stats.clone())));
+                    handler = handler.with_metrics(metrics);
+                }
+                if let Some(pipeline) = self.pipeline.take() {
+                    info!("Installing {} interceptor(s) from pipeline", pipeline.interceptor_count());
+                    pipeline.install(handler.interceptor_manager()).await?;
+                }
+                if let Some(faults) = self.faults.take() {
+                    warn!("Fault injection enabled with {} rule(s)", faults.rules.len());
+                    handler
+                        .interceptor_manager()
+                        .add_interceptor(Arc::new(FaultInjectionInterceptor::from_config(faults)))
+                        .await;
+                }
+                if let Some(repair) = self.argument_repair.take() {
+                    info!("Repairing schema-invalid tool call arguments");
+                    handler
+                        .interceptor_manager()
+                        .add_interceptor(Arc::new(repair))
+                        .await;
+                }
+                if let Some(budget) = self.overhead_budget {
+                    handler
+                        .interceptor_manager()
+                        .enable_instrumentation(Some(budget))
+                        .await;
+                }
+                let sighup = restart_on_sighup(self.swap_handle());
 
                 // Handle STDIO communication
                 let result = handler.handle_communication(&mut child, shutdown_rx).await;
 
                 // Clean up
                 info!("Proxy {} shutting down", self.name);
+                if let Some(report) = handler.interceptor_manager().disable_instrumentation().await {
+                    info!("Interceptor overhead: {}", report);
+                }
+                sighup.abort();
+                if let Some(exporter) = exporter {
+                    exporter.abort();
+                }
                 if let Err(e) = child.kill().await {
                     warn!("Failed to kill MCP server process: {}", e);
                 }
@@ -125,6 +323,30 @@ impl MCPProxy {
                     }
                 }
 
+                result
+            }
+            TransportConfig::Aggregate { config } => {
+                // Create aggregating handler
+                let mut handler =
+                    AggregateHandler::new(self.id.clone(), self.stats.clone(), buffered_client.clone()).await?;
+
+                // Serve the merged backends over stdio
+                let result = handler.handle_communication(&self.name, config, shutdown_rx).await;
+
+                // Clean up
+                info!("Aggregating proxy {} shutting down", self.name);
+
+                // Send proxy stopped message and shutdown buffered client
+                if let Some(client) = buffered_client {
+                    if let Err(e) = client.send(IpcMessage::ProxyStopped(self.id.clone())).await {
+                        warn!("Failed to send proxy stopped message: {}", e);
+                    }
+                    // Take the client out of the Arc and shutdown
+                    if let Ok(client) = Arc::try_unwrap(client) {
+                        client.shutdown().await;
+                    }
+                }
+
                 result
             }
         }
@@ -136,38 +358,70 @@ impl MCPProxy {
             _ => return Err(anyhow::anyhow!("start_mcp_server only works for stdio transport")),
         };
 
-        if command.is_empty() {
-            return Err(anyhow::anyhow!("No command specified"));
+        spawn_stdio_server(command, *use_shell)
+    }
+}
+
+/// Spawn an MCP server process with piped stdio
+pub(crate) fn spawn_stdio_server(command: &str, use_shell: bool) -> Result<Child> {
+    if command.is_empty() {
+        return Err(anyhow::anyhow!("No command specified"));
+    }
+
+    let child = if use_shell {
+        // Use shell to execute the command
+        Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?
+    } else {
+        // Parse command and arguments
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        if parts.is_empty() {
+            return Err(anyhow::anyhow!("Empty command"));
         }
 
-        let child = if *use_shell {
-            // Use shell to execute the command
-            Command::new("sh")
-                .arg("-c")
-                .arg(command)
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()?
-        } else {
-            // Parse command and arguments
-            let parts: Vec<&str> = command.split_whitespace().collect();
-            if parts.is_empty() {
-                return Err(anyhow::anyhow!("Empty command"));
-            }
+        let mut cmd = Command::new(parts[0]);
+        if parts.len() > 1 {
+            cmd.args(&parts[1..]);
+        }
 
-            let mut cmd = Command::new(parts[0]);
-            if parts.len() > 1 {
-                cmd.args(&parts[1..]);
-            }
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?
+    };
+
+    info!("Started MCP server process: {}", command);
+    Ok(child)
+}
 
-            cmd.stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()?
+/// Restart the backend whenever the proxy receives SIGHUP
+#[cfg(unix)]
+fn restart_on_sighup(handle: SwapHandle) -> tokio::task::JoinHandle<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tokio::spawn(async move {
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                warn!("Failed to listen for SIGHUP: {}", e);
+                return;
+            }
         };
+        while hangups.recv().await.is_some() {
+            info!("SIGHUP received, restarting backend");
+            if handle.restart().await.is_err() {
+                break;
+            }
+        }
+    })
+}
 
-        info!("Started MCP server process: {}", command);
-        Ok(child)
-    }
+#[cfg(not(unix))]
+fn restart_on_sighup(_handle: SwapHandle) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async {})
 }
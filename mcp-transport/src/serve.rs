@@ -0,0 +1,1562 @@
+//! Publishing a backend as a Streamable HTTP endpoint.
+//!
+//! [`serve`] is the reverse of the proxy: remote clients speak Streamable
+//! HTTP to it, and each of their sessions is carried to its own connection
+//! to the backend, usually a local stdio server. This lets a client on
+//! another machine, or one that only speaks HTTP, reach a server that only
+//! runs locally.
+//!
+//! With [`ServeConfig::shared_backends`] set, sessions instead share a pool
+//! of that many backend connections, each session joining the least used.
+//! The backend is initialized by the first session on a connection and later
+//! sessions get the same answer, though each session's own client
+//! capabilities are kept. Sessions keep their own request IDs and progress
+//! tokens, and only see the progress and resource updates of their own
+//! requests and subscriptions; other notifications go to every session on
+//! the connection. [`Endpoint::clients`] reports what each session did.
+//!
+//! A request the backend sends, such as `sampling/createMessage` or
+//! `roots/list`, goes to the session that caused it: the only session with a
+//! request waiting on the backend, or the connection's only session. It is
+//! answered with an error when no session can be told apart, or when that
+//! session's `initialize` did not declare the capability it needs.
+//!
+//! * `POST` carries a message or a batch to the backend. Requests are
+//!   answered with their JSON responses; a body of only notifications and
+//!   responses is accepted with `202 Accepted`.
+//! * An `initialize` request opens a session: a fresh backend connection,
+//!   named by the `Mcp-Session-Id` header of the response. Later requests
+//!   must carry that header; unknown sessions get `404 Not Found`, so
+//!   clients know to initialize again.
+//! * `GET` with `Accept: text/event-stream` streams what the backend sends
+//!   on its own: notifications and requests such as `roots/list`, which the
+//!   client answers with a `POST`.
+//! * `DELETE` ends the session and closes its backend connection.
+//!
+//! Requests whose Origin or Host the [`OriginPolicy`] does not allow are
+//! refused with `403 Forbidden`, as a protection against DNS rebinding.
+//!
+//! With [`ServeConfig::gate`] set, every request must carry an API key in an
+//! `Authorization: Bearer` or `X-API-Key` header, or it is refused with
+//! `401 Unauthorized`. A session belongs to the identity that opened it, and
+//! each of its requests is put to the gate, see [`crate::auth`].
+
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use http_body_util::{combinators::BoxBody, BodyExt, Full};
+use hyper::body::{Bytes, Frame, Incoming};
+use hyper::header::{
+    HeaderValue, ACCEPT, ALLOW, CACHE_CONTROL, CONTENT_TYPE, HOST, ORIGIN, WWW_AUTHENTICATE,
+};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use mcp_core::error::{McpError, TransportError};
+use mcp_core::messages::{
+    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestId,
+};
+use mcp_core::transport::origin::OriginPolicy;
+use mcp_core::transport::{Transport, TransportFactory};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::auth::{AuthError, ClientGate, Credentials, Identity};
+use crate::stdio_handler::BLOCKED_REQUEST_CODE;
+
+/// Path the endpoint is served on unless configured otherwise
+pub const DEFAULT_PATH: &str = "/mcp";
+
+const SESSION_HEADER: &str = "mcp-session-id";
+const ORIGIN_REJECTED: i32 = -32001;
+/// Backend messages kept for a slow event stream before it misses some
+const STREAM_BUFFER: usize = 256;
+/// How long a progress token is still routed after its request was answered
+const PROGRESS_GRACE: Duration = Duration::from_secs(1);
+
+type Body = BoxBody<Bytes, Infallible>;
+
+/// What to publish and to whom
+#[derive(Debug, Clone)]
+pub struct ServeConfig {
+    /// Backend each session connects to
+    pub backend: mcp_core::TransportConfig,
+    /// Origins and hosts requests may come from
+    pub origins: OriginPolicy,
+    /// Path of the endpoint; other paths are not found
+    pub path: String,
+    /// How long a request may wait for the backend, the transport's default if unset
+    pub request_timeout: Option<Duration>,
+    /// Backend connections shared by all sessions; 0 gives every session its own
+    pub shared_backends: usize,
+    /// Clients allowed in and what they may do; anyone may connect if unset
+    pub gate: Option<ClientGate>,
+}
+
+impl ServeConfig {
+    /// Publish `backend` on [`DEFAULT_PATH`] to loopback origins
+    pub fn new(backend: mcp_core::TransportConfig) -> Self {
+        Self {
+            backend,
+            origins: OriginPolicy::localhost(),
+            path: DEFAULT_PATH.to_string(),
+            request_timeout: None,
+            shared_backends: 0,
+            gate: None,
+        }
+    }
+}
+
+/// What one client session has done, see [`Endpoint::clients`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientStats {
+    /// The session's `Mcp-Session-Id`
+    pub session: String,
+    /// Who opened the session, when clients authenticate
+    pub identity: Option<String>,
+    /// When the client initialized the session
+    pub opened_at: DateTime<Utc>,
+    /// Requests the client sent, including those answered by the endpoint
+    pub requests: u64,
+    /// Requests answered with an error
+    pub errors: u64,
+    /// Notifications the client sent
+    pub notifications: u64,
+    /// Backend notifications and requests put on the client's event stream
+    pub delivered: u64,
+    /// Resources the client is subscribed to
+    pub subscriptions: usize,
+}
+
+/// One client session and the backend connection it uses
+struct Session {
+    id: String,
+    connection: Arc<Connection>,
+    /// Who opened the session, when clients authenticate
+    identity: Option<Identity>,
+    messages: broadcast::Sender<JsonRpcMessage>,
+    /// Requests waiting for the backend: the client's ID and the forwarded one
+    in_flight: Mutex<HashMap<RequestId, RequestId>>,
+    /// Client capabilities declared by the session's own `initialize`
+    capabilities: Mutex<Value>,
+    stats: Mutex<ClientStats>,
+}
+
+/// A backend connection and the sessions using it
+///
+/// Sessions keep their own request IDs; every request is forwarded with an
+/// ID unique on the connection, and progress tokens are rewritten the same
+/// way, so what the backend sends back reaches only the session it concerns.
+struct Connection {
+    backend: Arc<dyn Transport>,
+    /// Whether the connection belongs to the pool rather than to one session
+    shared: bool,
+    /// The backend's answer to `initialize`, given to sessions that join later
+    initialized: tokio::sync::Mutex<Option<Value>>,
+    /// Whether `notifications/initialized` was forwarded
+    ready: AtomicBool,
+    next_id: AtomicI64,
+    routes: Mutex<Routes>,
+    pump: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[derive(Default)]
+struct Routes {
+    sessions: HashMap<String, Weak<Session>>,
+    /// Forwarded progress tokens, as JSON, and the session and token they stand for
+    progress: HashMap<String, (String, Value)>,
+    /// Resource URIs and the sessions subscribed to them
+    subscriptions: HashMap<String, HashSet<String>>,
+    /// Requests the backend sent and the session asked to answer
+    backend_requests: HashMap<RequestId, String>,
+}
+
+impl Session {
+    /// Open session `id` on `connection` for `identity`
+    fn join(id: String, connection: Arc<Connection>, identity: Option<Identity>) -> Arc<Self> {
+        let (messages, _) = broadcast::channel(STREAM_BUFFER);
+        let session = Arc::new(Self {
+            id: id.clone(),
+            connection: Arc::clone(&connection),
+            identity: identity.clone(),
+            messages,
+            in_flight: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(json!({})),
+            stats: Mutex::new(ClientStats {
+                session: id.clone(),
+                identity: identity.map(|identity| identity.name),
+                opened_at: Utc::now(),
+                requests: 0,
+                errors: 0,
+                notifications: 0,
+                delivered: 0,
+                subscriptions: 0,
+            }),
+        });
+        connection
+            .routes()
+            .sessions
+            .insert(id, Arc::downgrade(&session));
+        session
+    }
+
+    fn stats(&self) -> MutexGuard<'_, ClientStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn in_flight(&self) -> MutexGuard<'_, HashMap<RequestId, RequestId>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn request(
+        &self,
+        mut request: JsonRpcRequest,
+        timeout: Option<Duration>,
+    ) -> JsonRpcResponse {
+        let connection = &self.connection;
+        let id = request.id.clone();
+        if request.method == "initialize" {
+            if let Some(capabilities) = param(&request.params, "capabilities") {
+                *self.capabilities.lock().unwrap_or_else(|e| e.into_inner()) = capabilities;
+            }
+            return connection.initialize(request, timeout).await;
+        }
+        if let Some(answer) = self.subscription(&request) {
+            return answer;
+        }
+
+        let token = connection.forward_progress(&self.id, &mut request);
+        request.id = connection.next_id();
+        self.in_flight().insert(id.clone(), request.id.clone());
+        let subscribe = (request.method == "resources/subscribe").then(|| resource_uri(&request));
+        let response = connection.send(request, timeout).await;
+        self.in_flight().remove(&id);
+        if let Some(token) = token {
+            // Progress sent just before the response may be routed after it
+            let connection = Arc::clone(connection);
+            tokio::spawn(async move {
+                tokio::time::sleep(PROGRESS_GRACE).await;
+                connection.routes().progress.remove(&token);
+            });
+        }
+        if let (Some(Some(uri)), true) = (subscribe, response.is_error()) {
+            // The backend refused, so nobody is subscribed
+            connection.routes().subscriptions.remove(&uri);
+        }
+        JsonRpcResponse { id, ..response }
+    }
+
+    /// Answer a subscription change that need not reach the backend
+    ///
+    /// A resource is subscribed to on the backend by its first subscriber
+    /// and unsubscribed from by its last, so sessions never cancel each
+    /// other's subscriptions.
+    fn subscription(&self, request: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let subscribe = match request.method.as_str() {
+            "resources/subscribe" => true,
+            "resources/unsubscribe" => false,
+            _ => return None,
+        };
+        let uri = resource_uri(request)?;
+        let mut routes = self.connection.routes();
+        let forward = if subscribe {
+            let subscribers = routes.subscriptions.entry(uri).or_default();
+            subscribers.insert(self.id.clone());
+            subscribers.len() == 1
+        } else {
+            let subscribers = routes.subscriptions.get_mut(&uri)?;
+            let last = subscribers.remove(&self.id) && subscribers.is_empty();
+            if subscribers.is_empty() {
+                routes.subscriptions.remove(&uri);
+            }
+            last
+        };
+        (!forward).then(|| JsonRpcResponse::success(request.id.clone(), json!({})))
+    }
+
+    async fn notify(&self, mut notification: JsonRpcNotification) {
+        match notification.method.as_str() {
+            "notifications/initialized" if self.connection.ready.swap(true, Ordering::SeqCst) => {
+                return
+            }
+            "notifications/cancelled" => {
+                let forwarded = notification
+                    .params
+                    .as_ref()
+                    .and_then(|params| params.get("requestId"))
+                    .and_then(|id| serde_json::from_value::<RequestId>(id.clone()).ok())
+                    .and_then(|id| self.in_flight().get(&id).cloned());
+                let (Some(forwarded), Some(params)) = (forwarded, notification.params.as_mut())
+                else {
+                    debug!("Dropping cancellation of a request not in flight");
+                    return;
+                };
+                params["requestId"] = json!(forwarded);
+            }
+            _ => {}
+        }
+        if let Err(e) = self
+            .connection
+            .backend
+            .send_notification(notification)
+            .await
+        {
+            warn!("Failed to forward a notification to the backend: {}", e);
+        }
+    }
+
+    async fn respond(&self, response: JsonRpcResponse) {
+        let asked = self
+            .connection
+            .routes()
+            .backend_requests
+            .remove(&response.id);
+        if asked.as_ref() != Some(&self.id) {
+            warn!(
+                "Dropping response of session {} to request {} it was not asked",
+                self.id, response.id
+            );
+            return;
+        }
+        if let Err(e) = self.connection.backend.send_response(response).await {
+            warn!("Failed to forward a response to the backend: {}", e);
+        }
+    }
+
+    /// Whether the client declared the capability a backend request needs
+    fn accepts(&self, method: &str) -> bool {
+        let capability = match method {
+            "sampling/createMessage" => "sampling",
+            "roots/list" => "roots",
+            method if method.starts_with("elicitation/") => "elicitation",
+            _ => return true,
+        };
+        let capabilities = self.capabilities.lock().unwrap_or_else(|e| e.into_inner());
+        capabilities.get(capability).is_some()
+    }
+
+    fn deliver(&self, message: JsonRpcMessage) -> bool {
+        let delivered = self.messages.send(message).is_ok();
+        if delivered {
+            self.stats().delivered += 1;
+        }
+        delivered
+    }
+}
+
+impl Connection {
+    fn new(backend: Arc<dyn Transport>, shared: bool) -> Self {
+        Self {
+            backend,
+            shared,
+            initialized: tokio::sync::Mutex::new(None),
+            ready: AtomicBool::new(false),
+            next_id: AtomicI64::new(1),
+            routes: Mutex::new(Routes::default()),
+            pump: Mutex::new(None),
+        }
+    }
+
+    fn routes(&self) -> MutexGuard<'_, Routes> {
+        self.routes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn next_id(&self) -> RequestId {
+        RequestId::Number(self.next_id.fetch_add(1, Ordering::SeqCst))
+    }
+
+    async fn send(&self, request: JsonRpcRequest, timeout: Option<Duration>) -> JsonRpcResponse {
+        let id = request.id.clone();
+        self.backend
+            .send_request(request, timeout)
+            .await
+            .unwrap_or_else(|e| {
+                JsonRpcResponse::error(id, JsonRpcError::internal_error(e.to_string()))
+            })
+    }
+
+    /// Initialize the backend once and give every later session its answer
+    async fn initialize(
+        &self,
+        mut request: JsonRpcRequest,
+        timeout: Option<Duration>,
+    ) -> JsonRpcResponse {
+        let id = request.id.clone();
+        let mut initialized = self.initialized.lock().await;
+        if let Some(result) = initialized.as_ref() {
+            return JsonRpcResponse::success(id, result.clone());
+        }
+        request.id = self.next_id();
+        let response = self.send(request, timeout).await;
+        if let Some(result) = response.result.as_ref().filter(|_| response.is_success()) {
+            *initialized = Some(result.clone());
+        }
+        JsonRpcResponse { id, ..response }
+    }
+
+    /// Give the request's progress token one unique on the connection,
+    /// returning its key in [`Routes::progress`]
+    fn forward_progress(&self, session: &str, request: &mut JsonRpcRequest) -> Option<String> {
+        let meta = request.params.as_mut()?.get_mut("_meta")?.as_object_mut()?;
+        let token = meta.get_mut("progressToken")?;
+        let forwarded = json!(format!("{}-{}", session, token));
+        let original = std::mem::replace(token, forwarded.clone());
+        let key = forwarded.to_string();
+        self.routes()
+            .progress
+            .insert(key.clone(), (session.to_string(), original));
+        Some(key)
+    }
+
+    /// Hand a message the backend sent on its own to the sessions it concerns
+    ///
+    /// Progress goes to the session that asked for it, resource updates to
+    /// the resource's subscribers and other notifications to every session.
+    /// A request is asked of the session that caused it, see [`requester`].
+    /// For a request no session took, the error to answer it with is
+    /// returned.
+    ///
+    /// [`requester`]: Connection::requester
+    fn route(&self, mut message: JsonRpcMessage) -> Option<JsonRpcResponse> {
+        let mut routes = self.routes();
+        let live: Vec<Arc<Session>> = routes.sessions.values().filter_map(Weak::upgrade).collect();
+        let named = |id: Option<&String>| -> Vec<Arc<Session>> {
+            live.iter()
+                .filter(|session| Some(&session.id) == id)
+                .cloned()
+                .collect()
+        };
+
+        let recipients = match &mut message {
+            JsonRpcMessage::Request(request) => {
+                let Some(session) = Self::requester(&live) else {
+                    debug!("Cannot tell which session {} is for", request.method);
+                    let error =
+                        JsonRpcError::internal_error("Cannot tell which client the request is for");
+                    return Some(JsonRpcResponse::error(request.id.clone(), error));
+                };
+                if !session.accepts(&request.method) {
+                    debug!(
+                        "Session {} did not declare support for {}",
+                        session.id, request.method
+                    );
+                    let error = JsonRpcError::method_not_found(request.method.clone());
+                    return Some(JsonRpcResponse::error(request.id.clone(), error));
+                }
+                routes
+                    .backend_requests
+                    .insert(request.id.clone(), session.id.clone());
+                vec![session]
+            }
+            JsonRpcMessage::Notification(notification) => match notification.method.as_str() {
+                "notifications/progress" => {
+                    let token = param(&notification.params, "progressToken").unwrap_or_default();
+                    let Some((session, original)) =
+                        routes.progress.get(&token.to_string()).cloned()
+                    else {
+                        debug!("Dropping progress for unknown token {}", token);
+                        return None;
+                    };
+                    if let Some(params) = notification.params.as_mut() {
+                        params["progressToken"] = original;
+                    }
+                    named(Some(&session))
+                }
+                "notifications/resources/updated" => {
+                    let uri = param(&notification.params, "uri");
+                    let subscribers = uri
+                        .as_ref()
+                        .and_then(Value::as_str)
+                        .and_then(|uri| routes.subscriptions.get(uri));
+                    live.iter()
+                        .filter(|session| subscribers.is_some_and(|ids| ids.contains(&session.id)))
+                        .cloned()
+                        .collect()
+                }
+                "notifications/cancelled" => {
+                    let id = param(&notification.params, "requestId")
+                        .and_then(|id| serde_json::from_value::<RequestId>(id).ok());
+                    named(
+                        id.and_then(|id| routes.backend_requests.remove(&id))
+                            .as_ref(),
+                    )
+                }
+                _ => live.clone(),
+            },
+            JsonRpcMessage::Response(response) => {
+                debug!(
+                    "Dropping unsolicited response {} from the backend",
+                    response.id
+                );
+                return None;
+            }
+        };
+        drop(routes);
+
+        let mut delivered = false;
+        for session in &recipients {
+            delivered |= session.deliver(message.clone());
+        }
+        match message {
+            JsonRpcMessage::Request(request) if !delivered => {
+                self.routes().backend_requests.remove(&request.id);
+                let error = JsonRpcError::internal_error("No client event stream is open");
+                Some(JsonRpcResponse::error(request.id, error))
+            }
+            _ => None,
+        }
+    }
+
+    /// The session whose request caused a backend request: the only one
+    /// waiting on the backend or, when none is, the connection's only session
+    fn requester(live: &[Arc<Session>]) -> Option<Arc<Session>> {
+        let waiting: Vec<&Arc<Session>> = live
+            .iter()
+            .filter(|session| !session.in_flight().is_empty())
+            .collect();
+        match (waiting.as_slice(), live) {
+            ([session], _) => Some(Arc::clone(session)),
+            ([], [session]) => Some(Arc::clone(session)),
+            _ => None,
+        }
+    }
+
+    /// Forget a session, unsubscribing from what only it was subscribed to
+    async fn leave(&self, session: &Session) {
+        let unsubscribe: Vec<String> = {
+            let mut routes = self.routes();
+            routes.sessions.remove(&session.id);
+            routes.progress.retain(|_, (id, _)| *id != session.id);
+            routes.backend_requests.retain(|_, id| *id != session.id);
+            let mut unsubscribe = Vec::new();
+            routes.subscriptions.retain(|uri, subscribers| {
+                if subscribers.remove(&session.id) && subscribers.is_empty() {
+                    unsubscribe.push(uri.clone());
+                }
+                !subscribers.is_empty()
+            });
+            unsubscribe
+        };
+        if !self.shared {
+            self.close().await;
+            return;
+        }
+        for uri in unsubscribe {
+            let request = JsonRpcRequest::new(
+                self.next_id(),
+                "resources/unsubscribe",
+                json!({ "uri": uri }),
+            );
+            let response = self.send(request, None).await;
+            if let Some(error) = response.error {
+                debug!("Failed to unsubscribe from {}: {}", uri, error.message);
+            }
+        }
+    }
+
+    async fn close(&self) {
+        if let Some(pump) = self.pump.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            pump.abort();
+        }
+        if let Err(e) = self.backend.disconnect().await {
+            debug!("Failed to disconnect session backend: {}", e);
+        }
+    }
+}
+
+fn param(params: &Option<Value>, name: &str) -> Option<Value> {
+    params.as_ref().and_then(|params| params.get(name)).cloned()
+}
+
+fn resource_uri(request: &JsonRpcRequest) -> Option<String> {
+    let uri = request.params.as_ref()?.get("uri")?.as_str()?;
+    Some(uri.to_string())
+}
+
+struct Gateway {
+    config: ServeConfig,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+    /// Connections shared by the sessions, up to `config.shared_backends`
+    pool: tokio::sync::Mutex<Vec<Arc<Connection>>>,
+}
+
+/// A Streamable HTTP endpoint for a backend, see [`serve`]
+pub struct Endpoint {
+    gateway: Arc<Gateway>,
+}
+
+impl Endpoint {
+    pub fn new(config: ServeConfig) -> Self {
+        Self {
+            gateway: Arc::new(Gateway {
+                config,
+                sessions: Mutex::new(HashMap::new()),
+                pool: tokio::sync::Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// What each open session has done so far, oldest first
+    pub fn clients(&self) -> Vec<ClientStats> {
+        let mut clients: Vec<ClientStats> = self
+            .gateway
+            .sessions()
+            .values()
+            .map(|session| {
+                let mut stats = session.stats().clone();
+                stats.subscriptions = session
+                    .connection
+                    .routes()
+                    .subscriptions
+                    .values()
+                    .filter(|subscribers| subscribers.contains(&session.id))
+                    .count();
+                stats
+            })
+            .collect();
+        clients.sort_by_key(|client| client.opened_at);
+        clients
+    }
+
+    /// Serve HTTP clients connecting to `listener`
+    pub async fn run(&self, listener: TcpListener) -> Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let gateway = Arc::clone(&self.gateway);
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let gateway = Arc::clone(&gateway);
+                    async move { Ok::<_, Infallible>(gateway.handle(request).await) }
+                });
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    debug!("Connection from {} failed: {}", peer, e);
+                }
+            });
+        }
+    }
+}
+
+/// Serve `config.backend` to HTTP clients connecting to `listener`
+pub async fn serve(listener: TcpListener, config: ServeConfig) -> Result<()> {
+    Endpoint::new(config).run(listener).await
+}
+
+impl Gateway {
+    fn sessions(&self) -> MutexGuard<'_, HashMap<String, Arc<Session>>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn handle(self: Arc<Self>, request: Request<Incoming>) -> Response<Body> {
+        if request.uri().path() != self.config.path {
+            return status(StatusCode::NOT_FOUND);
+        }
+        let header = |name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+        };
+        if let Err(rejection) = self.config.origins.check(header(ORIGIN), header(HOST)) {
+            let error = JsonRpcError::new(
+                ORIGIN_REJECTED,
+                rejection.to_string(),
+                Some(json!(rejection)),
+            );
+            return json_response(
+                StatusCode::FORBIDDEN,
+                &json!(JsonRpcResponse::error(RequestId::Null, error)),
+            );
+        }
+        let identity = match self.authenticate(&request) {
+            Ok(identity) => identity,
+            Err(e) => return unauthorized(e),
+        };
+
+        match *request.method() {
+            Method::POST => self.post(request, identity).await,
+            Method::GET => self.stream(&request, identity.as_ref()),
+            Method::DELETE => self.delete(&request, identity.as_ref()).await,
+            _ => {
+                let mut response = status(StatusCode::METHOD_NOT_ALLOWED);
+                response
+                    .headers_mut()
+                    .insert(ALLOW, HeaderValue::from_static("GET, POST, DELETE"));
+                response
+            }
+        }
+    }
+
+    /// Who a request comes from by its headers; nobody in particular without a gate
+    fn authenticate(&self, request: &Request<Incoming>) -> Result<Option<Identity>, AuthError> {
+        let Some(ref gate) = self.config.gate else {
+            return Ok(None);
+        };
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        gate.authenticate(&Credentials::from_headers(headers))
+            .map(Some)
+    }
+
+    /// The session a request names, or the status refusing it
+    ///
+    /// Sessions of other identities are not found.
+    fn session(
+        &self,
+        request: &Request<Incoming>,
+        identity: Option<&Identity>,
+    ) -> Result<Arc<Session>, StatusCode> {
+        let id = request
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        self.sessions()
+            .get(id)
+            .filter(|session| session.identity.as_ref() == identity)
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    async fn post(
+        self: Arc<Self>,
+        request: Request<Incoming>,
+        identity: Option<Identity>,
+    ) -> Response<Body> {
+        let session = self.session(&request, identity.as_ref());
+        let body = match request.into_body().collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(e) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    JsonRpcError::invalid_request(e.to_string()),
+                )
+            }
+        };
+        let Some((messages, batch)) = parse_body(&body) else {
+            return error_response(StatusCode::BAD_REQUEST, JsonRpcError::parse_error());
+        };
+
+        let initialize = messages
+            .iter()
+            .any(|message| message.method() == Some("initialize"));
+        let (session, opened) = if initialize {
+            match self.open_session(identity).await {
+                Ok(opened) => opened,
+                Err(e) => {
+                    warn!("Failed to connect a session backend: {:#}", e);
+                    return error_response(
+                        StatusCode::BAD_GATEWAY,
+                        JsonRpcError::internal_error(format!("Backend unavailable: {:#}", e)),
+                    );
+                }
+            }
+        } else {
+            match session {
+                Ok(session) => (session, None),
+                Err(refused) => return refuse(refused),
+            }
+        };
+
+        let mut responses = Vec::new();
+        for message in messages {
+            if let Some(response) = self.forward(&session, message).await {
+                responses.push(response);
+            }
+        }
+
+        let mut response = match responses.as_slice() {
+            [] => status(StatusCode::ACCEPTED),
+            [response] if !batch => json_response(StatusCode::OK, &json!(response)),
+            responses => json_response(StatusCode::OK, &json!(responses)),
+        };
+        if let Some(id) = opened {
+            if responses.iter().any(|response| response.is_error()) {
+                // A session whose initialization failed is of no use to the client
+                self.end_session(&id).await;
+            } else if let Ok(value) = HeaderValue::from_str(&id) {
+                response.headers_mut().insert(SESSION_HEADER, value);
+            }
+        }
+        response
+    }
+
+    /// Send one client message to the backend, returning the answer to a request
+    async fn forward(&self, session: &Session, message: JsonRpcMessage) -> Option<JsonRpcResponse> {
+        match message {
+            JsonRpcMessage::Request(request) => {
+                let response = match self.authorize(session, &request) {
+                    Ok(()) => session.request(request, self.config.request_timeout).await,
+                    Err(reason) => {
+                        debug!(
+                            "Session {} refused {}: {}",
+                            session.id, request.method, reason
+                        );
+                        let error = JsonRpcError::application_error(
+                            BLOCKED_REQUEST_CODE,
+                            "Request blocked",
+                            reason,
+                        );
+                        JsonRpcResponse::error(request.id, error)
+                    }
+                };
+                let mut stats = session.stats();
+                stats.requests += 1;
+                if response.is_error() {
+                    stats.errors += 1;
+                }
+                Some(response)
+            }
+            JsonRpcMessage::Notification(notification) => {
+                session.stats().notifications += 1;
+                session.notify(notification).await;
+                None
+            }
+            JsonRpcMessage::Response(response) => {
+                session.respond(response).await;
+                None
+            }
+        }
+    }
+
+    /// Put a session's request to the gate, if there is one
+    fn authorize(&self, session: &Session, request: &JsonRpcRequest) -> Result<(), String> {
+        match (&self.config.gate, &session.identity) {
+            (Some(gate), Some(identity)) => gate.authorize(identity, request, Instant::now()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Register a new session under a fresh ID, on a connection of its own
+    /// or the least used one of the pool
+    async fn open_session(
+        self: &Arc<Self>,
+        identity: Option<Identity>,
+    ) -> Result<(Arc<Session>, Option<String>)> {
+        let connection = self.connection().await?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let session = Session::join(id.clone(), connection, identity);
+        self.sessions().insert(id.clone(), Arc::clone(&session));
+        match session.identity {
+            Some(ref identity) => info!("Opened session {} for '{}'", id, identity),
+            None => info!("Opened session {}", id),
+        }
+        Ok((session, Some(id)))
+    }
+
+    async fn connection(self: &Arc<Self>) -> Result<Arc<Connection>> {
+        let shared = self.config.shared_backends > 0;
+        let mut pool = self.pool.lock().await;
+        if shared && pool.len() >= self.config.shared_backends {
+            if let Some(connection) = pool
+                .iter()
+                .min_by_key(|connection| connection.routes().sessions.len())
+            {
+                return Ok(Arc::clone(connection));
+            }
+        }
+
+        let backend: Arc<dyn Transport> =
+            Arc::from(TransportFactory::create(self.config.backend.clone()).await?);
+        backend.connect().await?;
+        let connection = Arc::new(Connection::new(Arc::clone(&backend), shared));
+        let pump = tokio::spawn(pump(
+            Arc::downgrade(self),
+            Arc::downgrade(&connection),
+            backend,
+        ));
+        *connection.pump.lock().unwrap_or_else(|e| e.into_inner()) = Some(pump);
+        if shared {
+            pool.push(Arc::clone(&connection));
+            info!(
+                "Connected shared backend {} of {}",
+                pool.len(),
+                self.config.shared_backends
+            );
+        }
+        Ok(connection)
+    }
+
+    async fn end_session(&self, id: &str) {
+        let session = self.sessions().remove(id);
+        if let Some(session) = session {
+            session.connection.leave(&session).await;
+            let stats = session.stats().clone();
+            info!(
+                "Closed session {} after {} requests ({} failed)",
+                id, stats.requests, stats.errors
+            );
+        }
+    }
+
+    /// Drop a connection whose backend went away, and the sessions on it
+    async fn drop_connection(&self, connection: &Arc<Connection>) {
+        self.pool
+            .lock()
+            .await
+            .retain(|pooled| !Arc::ptr_eq(pooled, connection));
+        self.sessions()
+            .retain(|_, session| !Arc::ptr_eq(&session.connection, connection));
+    }
+
+    /// Stream the messages the backend sends on its own as server-sent events
+    fn stream(&self, request: &Request<Incoming>, identity: Option<&Identity>) -> Response<Body> {
+        let accepts_sse = request
+            .headers()
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|accept| accept.contains("text/event-stream"));
+        if !accepts_sse {
+            return status(StatusCode::NOT_ACCEPTABLE);
+        }
+        let session = match self.session(request, identity) {
+            Ok(session) => session,
+            Err(refused) => return refuse(refused),
+        };
+
+        let mut messages = session.messages.subscribe();
+        let (events, body) = mpsc::channel(16);
+        tokio::spawn(async move {
+            loop {
+                let message = match messages.recv().await {
+                    Ok(message) => message,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Event stream fell behind and missed {} messages", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let Ok(data) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                let event = Bytes::from(format!("event: message\ndata: {}\n\n", data));
+                if events.send(event).await.is_err() {
+                    // The client closed the stream
+                    return;
+                }
+            }
+        });
+
+        let mut response = Response::new(EventStream(body).boxed());
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        response
+    }
+
+    async fn delete(
+        &self,
+        request: &Request<Incoming>,
+        identity: Option<&Identity>,
+    ) -> Response<Body> {
+        if let Err(refused) = self.session(request, identity) {
+            return refuse(refused);
+        }
+        if let Some(id) = request
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            self.end_session(id).await;
+        }
+        status(StatusCode::OK)
+    }
+}
+
+/// Publish what a backend sends until it goes away
+async fn pump(gateway: Weak<Gateway>, connection: Weak<Connection>, backend: Arc<dyn Transport>) {
+    loop {
+        let message = match backend.receive_message(None).await {
+            Ok(message) => message,
+            Err(McpError::Transport(TransportError::TimeoutError { .. })) => continue,
+            Err(e) => {
+                debug!("Session backend closed: {}", e);
+                break;
+            }
+        };
+        let Some(connection) = connection.upgrade() else {
+            return;
+        };
+        if let Some(refusal) = connection.route(message) {
+            // Nobody would ever answer, so the backend is not left waiting
+            let _ = backend.send_response(refusal).await;
+        }
+    }
+    if let (Some(gateway), Some(connection)) = (gateway.upgrade(), connection.upgrade()) {
+        gateway.drop_connection(&connection).await;
+    }
+}
+
+/// A single message or a batch; the flag tells a batch of one from a message
+fn parse_body(body: &[u8]) -> Option<(Vec<JsonRpcMessage>, bool)> {
+    match serde_json::from_slice::<Value>(body).ok()? {
+        Value::Array(messages) => messages
+            .into_iter()
+            .map(|message| serde_json::from_value(message).ok())
+            .collect::<Option<Vec<_>>>()
+            .map(|messages| (messages, true)),
+        message => Some((vec![serde_json::from_value(message).ok()?], false)),
+    }
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Full::new(Bytes::new()).boxed());
+    *response.status_mut() = status;
+    response
+}
+
+/// Why a request without a usable session is refused
+fn refuse(refused: StatusCode) -> Response<Body> {
+    match refused {
+        StatusCode::BAD_REQUEST => error_response(
+            refused,
+            JsonRpcError::invalid_request("Missing Mcp-Session-Id header"),
+        ),
+        // Unknown or ended sessions tell the client to initialize again
+        _ => status(refused),
+    }
+}
+
+/// Why a client that failed authentication is refused
+fn unauthorized(e: AuthError) -> Response<Body> {
+    debug!("Refused an unauthenticated request: {}", e);
+    let error =
+        JsonRpcError::application_error(BLOCKED_REQUEST_CODE, "Unauthorized", e.to_string());
+    let mut response = error_response(StatusCode::UNAUTHORIZED, error);
+    response
+        .headers_mut()
+        .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())).boxed());
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error_response(status: StatusCode, error: JsonRpcError) -> Response<Body> {
+    json_response(
+        status,
+        &json!(JsonRpcResponse::error(RequestId::Null, error)),
+    )
+}
+
+/// Response body fed with server-sent events by a task
+struct EventStream(mpsc::Receiver<Bytes>);
+
+impl hyper::body::Body for EventStream {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        self.0
+            .poll_recv(cx)
+            .map(|event| event.map(|event| Ok(Frame::data(event))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::client::McpClientBuilder;
+    use mcp_core::messages::Implementation;
+    use mcp_core::transport::InMemoryTransport;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Answers initialize and tool calls, logging before each call's result
+    const BACKEND: &str = r#"while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\("[^"]*"\|[0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"local","version":"1"}}}\n' "$id" ;;
+    *'"method":"tools/call"'*)
+      printf '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info","data":"calling"}}\n'
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"done"}]}}\n' "$id" ;;
+    *'"id"'*) printf '{"jsonrpc":"2.0","id":%s,"result":{}}\n' "$id" ;;
+  esac
+done"#;
+
+    async fn start() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let backend = mcp_core::TransportConfig::stdio("sh", &["-c", BACKEND]);
+        tokio::spawn(serve(listener, ServeConfig::new(backend)));
+        addr
+    }
+
+    async fn connect(addr: SocketAddr) -> mcp_core::client::McpClient {
+        let config = mcp_core::TransportConfig::http_stream(format!("http://{}", addr)).unwrap();
+        let mut client = McpClientBuilder::new()
+            .transport(config)
+            .build()
+            .await
+            .unwrap();
+        client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+        client
+    }
+
+    /// Send one request on its own connection and return the whole response
+    async fn raw(addr: SocketAddr, method: &str, headers: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "{} /mcp HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+            method,
+            addr,
+            body.len(),
+            headers,
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    fn session_id(response: &str) -> String {
+        response
+            .lines()
+            .find_map(|line| line.strip_prefix("mcp-session-id: "))
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_clients_reach_a_stdio_backend() {
+        let addr = start().await;
+        let config = mcp_core::TransportConfig::http_stream(format!("http://{}", addr)).unwrap();
+        let mut client = McpClientBuilder::new()
+            .transport(config)
+            .build()
+            .await
+            .unwrap();
+        let info = client
+            .connect(Implementation::new("test", "0.0.0"))
+            .await
+            .unwrap();
+        assert_eq!(info.implementation.name, "local");
+
+        let response = client
+            .send_request("tools/call", json!({ "name": "build", "arguments": {} }))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["content"][0]["text"], "done");
+    }
+
+    #[tokio::test]
+    async fn test_sessions_and_event_stream() {
+        let addr = start().await;
+
+        let missing = raw(
+            addr,
+            "POST",
+            "",
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+        )
+        .await;
+        assert!(missing.starts_with("HTTP/1.1 400"), "{}", missing);
+        let unknown = raw(
+            addr,
+            "POST",
+            "Mcp-Session-Id: nope\r\n",
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+        )
+        .await;
+        assert!(unknown.starts_with("HTTP/1.1 404"), "{}", unknown);
+        let rebound = raw(addr, "POST", "Origin: http://evil.example\r\n", "{}").await;
+        assert!(rebound.starts_with("HTTP/1.1 403"), "{}", rebound);
+
+        let initialized = raw(
+            addr,
+            "POST",
+            "",
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+        )
+        .await;
+        assert!(initialized.starts_with("HTTP/1.1 200"), "{}", initialized);
+        let session = session_id(&initialized);
+        let with_session = format!("Mcp-Session-Id: {}\r\n", session);
+
+        // The backend's log message arrives on the event stream
+        let mut events = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "GET /mcp HTTP/1.1\r\nHost: {}\r\nAccept: text/event-stream\r\n{}\r\n",
+            addr, with_session
+        );
+        events.write_all(request.as_bytes()).await.unwrap();
+        let mut received = Vec::new();
+        let mut chunk = [0; 1024];
+        while !String::from_utf8_lossy(&received).contains("\r\n\r\n") {
+            let read = events.read(&mut chunk).await.unwrap();
+            received.extend_from_slice(&chunk[..read]);
+        }
+        assert!(String::from_utf8_lossy(&received).starts_with("HTTP/1.1 200"));
+
+        let called = raw(
+            addr,
+            "POST",
+            &with_session,
+            r#"[{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"build"}},{"jsonrpc":"2.0","method":"notifications/initialized"}]"#,
+        )
+        .await;
+        let (_, body) = called.split_once("\r\n\r\n").unwrap();
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body[0]["id"], 2);
+        assert_eq!(body[0]["result"]["content"][0]["text"], "done");
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !String::from_utf8_lossy(&received).contains("notifications/message") {
+                let read = events.read(&mut chunk).await.unwrap();
+                received.extend_from_slice(&chunk[..read]);
+            }
+        })
+        .await
+        .unwrap();
+
+        let deleted = raw(addr, "DELETE", &with_session, "").await;
+        assert!(deleted.starts_with("HTTP/1.1 200"), "{}", deleted);
+        let gone = raw(
+            addr,
+            "POST",
+            &with_session,
+            r#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#,
+        )
+        .await;
+        assert!(gone.starts_with("HTTP/1.1 404"), "{}", gone);
+    }
+
+    #[tokio::test]
+    async fn test_gated_endpoint_requires_credentials() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let auth: crate::auth::AuthConfig = toml::from_str(
+            r#"
+            [[identity]]
+            name = "ci"
+            api_keys = ["ci-key"]
+            [identity.tools]
+            allow = ["search_*"]
+
+            [[identity]]
+            name = "ops"
+            api_keys = ["ops-key"]
+            "#,
+        )
+        .unwrap();
+        let backend = mcp_core::TransportConfig::stdio("sh", &["-c", BACKEND]);
+        tokio::spawn(serve(
+            listener,
+            ServeConfig {
+                gate: Some(ClientGate::from_config(auth)),
+                ..ServeConfig::new(backend)
+            },
+        ));
+        let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+
+        for headers in ["", "Authorization: Bearer guess\r\n"] {
+            let refused = raw(addr, "POST", headers, initialize).await;
+            assert!(refused.starts_with("HTTP/1.1 401"), "{}", refused);
+            assert!(refused.contains("www-authenticate: Bearer"), "{}", refused);
+        }
+
+        let initialized = raw(addr, "POST", "X-API-Key: ci-key\r\n", initialize).await;
+        assert!(initialized.starts_with("HTTP/1.1 200"), "{}", initialized);
+        let session = format!("Mcp-Session-Id: {}\r\n", session_id(&initialized));
+
+        // Requests are put to the identity's policy
+        let call = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"build"}}"#;
+        let blocked = raw(
+            addr,
+            "POST",
+            &format!("{}X-API-Key: ci-key\r\n", session),
+            call,
+        )
+        .await;
+        let (_, body) = blocked.split_once("\r\n\r\n").unwrap();
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["error"]["code"], BLOCKED_REQUEST_CODE);
+
+        // A session belongs to the identity that opened it
+        let other = raw(
+            addr,
+            "POST",
+            &format!("{}X-API-Key: ops-key\r\n", session),
+            call,
+        )
+        .await;
+        assert!(other.starts_with("HTTP/1.1 404"), "{}", other);
+    }
+
+    #[tokio::test]
+    async fn test_clients_share_a_pooled_backend() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let backend = mcp_core::TransportConfig::stdio("sh", &["-c", BACKEND]);
+        let endpoint = Arc::new(Endpoint::new(ServeConfig {
+            shared_backends: 1,
+            ..ServeConfig::new(backend)
+        }));
+        tokio::spawn({
+            let endpoint = Arc::clone(&endpoint);
+            async move { endpoint.run(listener).await }
+        });
+
+        // Both clients number their requests from the same start
+        let mut first = connect(addr).await;
+        let mut second = connect(addr).await;
+        let call = json!({ "name": "build", "arguments": {} });
+        let (a, b) = tokio::join!(
+            first.send_request("tools/call", call.clone()),
+            second.send_request("tools/call", call)
+        );
+        assert_eq!(a.unwrap().result.unwrap()["content"][0]["text"], "done");
+        assert_eq!(b.unwrap().result.unwrap()["content"][0]["text"], "done");
+
+        assert_eq!(endpoint.gateway.pool.lock().await.len(), 1);
+        let clients = endpoint.clients();
+        assert_eq!(clients.len(), 2);
+        for client in clients {
+            assert_eq!(
+                (client.requests, client.errors, client.notifications),
+                (2, 0, 1)
+            );
+        }
+    }
+
+    /// The next message put on a session's event stream
+    async fn next(events: &mut broadcast::Receiver<JsonRpcMessage>) -> Value {
+        let message = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        serde_json::to_value(message).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sessions_on_a_shared_connection_are_isolated() {
+        let (near, far) = InMemoryTransport::pair();
+        let backend: Arc<dyn Transport> = Arc::new(near);
+        let far = Arc::new(far);
+        backend.connect().await.unwrap();
+        far.connect().await.unwrap();
+        let connection = Arc::new(Connection::new(Arc::clone(&backend), true));
+        tokio::spawn(pump(Weak::new(), Arc::downgrade(&connection), backend));
+
+        // Reports progress on every request, then answers with the ID it got
+        let (seen, mut methods) = mpsc::unbounded_channel();
+        tokio::spawn({
+            let far = Arc::clone(&far);
+            async move {
+                while let Ok(message) = far.receive_message(None).await {
+                    let _ = seen.send(message.method().unwrap_or_default().to_string());
+                    let JsonRpcMessage::Request(request) = message else {
+                        continue;
+                    };
+                    let token = request
+                        .params
+                        .as_ref()
+                        .and_then(|params| params.pointer("/_meta/progressToken"));
+                    if let Some(token) = token {
+                        let progress = JsonRpcNotification::new(
+                            "notifications/progress",
+                            json!({ "progressToken": token, "progress": 1 }),
+                        );
+                        far.send_notification(progress).await.unwrap();
+                    }
+                    let result = json!({ "forwardedAs": request.id });
+                    let response = JsonRpcResponse::success(request.id, result);
+                    far.send_message(JsonRpcMessage::Response(response))
+                        .unwrap();
+                }
+            }
+        });
+
+        let a = Session::join("a".to_string(), Arc::clone(&connection), None);
+        let b = Session::join("b".to_string(), Arc::clone(&connection), None);
+        let (mut a_events, mut b_events) = (a.messages.subscribe(), b.messages.subscribe());
+        let request = |method: &str, params: Value| JsonRpcRequest::new(1, method, params);
+        let initialized = || JsonRpcNotification::new("notifications/initialized", json!({}));
+
+        for session in [&a, &b] {
+            let response = session
+                .request(request("initialize", json!({})), None)
+                .await;
+            assert_eq!(response.id, RequestId::Number(1));
+            assert!(response.is_success());
+            session.notify(initialized()).await;
+        }
+
+        let call = json!({ "name": "build", "_meta": { "progressToken": 7 } });
+        let (from_a, from_b) = tokio::join!(
+            a.request(request("tools/call", call.clone()), None),
+            b.request(request("tools/call", call), None)
+        );
+        assert_eq!(
+            (&from_a.id, &from_b.id),
+            (&RequestId::Number(1), &RequestId::Number(1))
+        );
+        assert_ne!(from_a.result, from_b.result);
+
+        let subscribe = json!({ "uri": "file:///x" });
+        for (session, method) in [
+            (&a, "resources/subscribe"),
+            (&b, "resources/subscribe"),
+            (&a, "resources/unsubscribe"),
+        ] {
+            let response = session
+                .request(request(method, subscribe.clone()), None)
+                .await;
+            assert!(response.is_success());
+        }
+        for method in [
+            "notifications/resources/updated",
+            "notifications/tools/list_changed",
+        ] {
+            let notification = JsonRpcNotification::new(method, subscribe.clone());
+            far.send_notification(notification).await.unwrap();
+        }
+
+        let progress = next(&mut a_events).await;
+        assert_eq!(progress["params"]["progressToken"], 7);
+        assert_eq!(
+            next(&mut a_events).await["method"],
+            "notifications/tools/list_changed"
+        );
+        assert_eq!(next(&mut b_events).await["params"]["progressToken"], 7);
+        assert_eq!(
+            next(&mut b_events).await["method"],
+            "notifications/resources/updated"
+        );
+        assert_eq!(
+            next(&mut b_events).await["method"],
+            "notifications/tools/list_changed"
+        );
+
+        // The backend was initialized and subscribed once, and never unsubscribed
+        let mut forwarded = Vec::new();
+        while let Ok(method) = methods.try_recv() {
+            forwarded.push(method);
+        }
+        assert_eq!(
+            forwarded,
+            [
+                "initialize",
+                "notifications/initialized",
+                "tools/call",
+                "tools/call",
+                "resources/subscribe"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backend_requests_reach_only_the_session_that_caused_them() {
+        let (near, far) = InMemoryTransport::pair();
+        let backend: Arc<dyn Transport> = Arc::new(near);
+        backend.connect().await.unwrap();
+        far.connect().await.unwrap();
+        let connection = Arc::new(Connection::new(Arc::clone(&backend), true));
+        tokio::spawn(pump(Weak::new(), Arc::downgrade(&connection), backend));
+
+        let a = Session::join("a".to_string(), Arc::clone(&connection), None);
+        let b = Session::join("b".to_string(), Arc::clone(&connection), None);
+        let (mut a_events, mut b_events) = (a.messages.subscribe(), b.messages.subscribe());
+        let receive = || async {
+            tokio::time::timeout(Duration::from_secs(5), far.receive_message(None))
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        // Only the first initialize reaches the backend, but both sessions'
+        // capabilities count
+        let initialize = |capabilities: Value| {
+            JsonRpcRequest::new(1, "initialize", json!({ "capabilities": capabilities }))
+        };
+        let initialized = tokio::spawn({
+            let a = Arc::clone(&a);
+            let request = initialize(json!({ "sampling": {}, "roots": {} }));
+            async move { a.request(request, None).await }
+        });
+        let JsonRpcMessage::Request(forwarded) = receive().await else {
+            panic!("expected initialize");
+        };
+        let result = json!({ "protocolVersion": "2025-03-26", "capabilities": {} });
+        let response = JsonRpcResponse::success(forwarded.id, result);
+        far.send_message(JsonRpcMessage::Response(response))
+            .unwrap();
+        assert!(initialized.await.unwrap().is_success());
+        let response = b.request(initialize(json!({ "roots": {} })), None).await;
+        assert!(response.is_success());
+        let sample = |id: &str| {
+            JsonRpcMessage::Request(JsonRpcRequest::new(id, "sampling/createMessage", json!({})))
+        };
+
+        // While a tool call of one session is waiting, the backend's request
+        // is asked of that session alone, or refused if it cannot sample
+        for (session, events) in [(&a, &mut a_events), (&b, &mut b_events)] {
+            let call = JsonRpcRequest::new(1, "tools/call", json!({ "name": "write" }));
+            let answered = tokio::spawn({
+                let session = Arc::clone(session);
+                async move { session.request(call, None).await }
+            });
+            let JsonRpcMessage::Request(forwarded) = receive().await else {
+                panic!("expected the tool call");
+            };
+
+            far.send_message(sample(&session.id)).unwrap();
+            if session.id == "a" {
+                assert_eq!(next(events).await["method"], "sampling/createMessage");
+            } else {
+                let JsonRpcMessage::Response(refused) = receive().await else {
+                    panic!("expected the sampling request to be refused");
+                };
+                assert_eq!(refused.id, RequestId::from("b"));
+                assert!(refused.is_error());
+            }
+
+            let done = JsonRpcResponse::success(forwarded.id, json!({}));
+            far.send_message(JsonRpcMessage::Response(done)).unwrap();
+            assert!(answered.await.unwrap().is_success());
+        }
+
+        // With nothing waiting there is no telling which session is meant
+        let roots = JsonRpcRequest::new("roots", "roots/list", json!({}));
+        far.send_message(JsonRpcMessage::Request(roots)).unwrap();
+        let JsonRpcMessage::Response(refused) = receive().await else {
+            panic!("expected roots/list to be refused");
+        };
+        assert_eq!(refused.id, RequestId::from("roots"));
+        assert!(refused.is_error());
+
+        assert!(a_events.try_recv().is_err());
+        assert!(b_events.try_recv().is_err());
+    }
+}
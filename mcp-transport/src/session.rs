@@ -0,0 +1,393 @@
+//! Bidirectional proxying between a client and a backend.
+//!
+//! A [`ProxySession`] acts as a server toward the client and as a client
+//! toward the backend. Either side may send requests: the client calls tools,
+//! while the backend may ask the client for sampling, roots or elicitation.
+//! Both sides pick their own request ids, so the same id can be in flight in
+//! both directions at once. The session therefore gives every forwarded
+//! request an id of its own and restores the original id on the response.
+//! `notifications/cancelled` is remapped the same way, see
+//! [`mcp_core::id_mapper`]. Mappings of requests left unanswered for the ID
+//! TTL are dropped.
+//!
+//! Each direction can have its own interceptor chain. Client messages run
+//! through it as [`MessageDirection::Outgoing`] and backend messages as
+//! [`MessageDirection::Incoming`]. Interceptors always see the ids chosen by
+//! the side that sent the request, so responses correlate with their
+//! requests. A blocked request is answered with the same error as in the
+//! stdio proxy, code `-32001`; other blocked messages are dropped.
+
+use anyhow::{Context, Result};
+use mcp_core::id_mapper::{IdMapper, Side};
+use mcp_core::interceptor::{InterceptorManager, MessageDirection};
+use mcp_core::messages::{JsonRpcError, JsonRpcMessage, JsonRpcResponse};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+use tokio::process::Child;
+use tracing::{debug, warn};
+
+use crate::stdio_handler::BLOCKED_REQUEST_CODE;
+
+type Reader = Lines<Box<dyn AsyncBufRead + Send + Unpin>>;
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// How often mappings of unanswered requests are checked for expiry
+const EXPIRY_CHECK: Duration = Duration::from_secs(30);
+
+/// One end of the session
+struct Peer {
+    name: &'static str,
+    reader: Reader,
+    writer: Writer,
+    side: Side,
+    /// Interceptors for the messages this peer sends
+    interceptors: Option<Arc<InterceptorManager>>,
+    direction: MessageDirection,
+}
+
+impl Peer {
+    fn new(
+        name: &'static str,
+        reader: impl AsyncBufRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        side: Side,
+        direction: MessageDirection,
+    ) -> Self {
+        let reader: Box<dyn AsyncBufRead + Send + Unpin> = Box::new(reader);
+        Self {
+            name,
+            reader: reader.lines(),
+            writer: Box::new(writer),
+            side,
+            interceptors: None,
+            direction,
+        }
+    }
+
+    async fn send(&mut self, message: &JsonRpcMessage) -> Result<()> {
+        let line = serde_json::to_string(message)?;
+        self.send_line(&line).await
+    }
+
+    async fn send_line(&mut self, line: &str) -> Result<()> {
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        self.writer
+            .flush()
+            .await
+            .with_context(|| format!("Failed to write to the {}", self.name))
+    }
+
+    /// Run a message through this peer's interceptors; `Err` holds why it was blocked
+    async fn intercept(&mut self, message: JsonRpcMessage) -> Result<JsonRpcMessage, String> {
+        let Some(manager) = &self.interceptors else {
+            return Ok(message);
+        };
+        match manager
+            .process_message(message.clone(), self.direction.clone())
+            .await
+        {
+            Ok(result) if result.block => {
+                warn!(
+                    "Message from the {} blocked by interceptor: {:?}",
+                    self.name, result.reasoning
+                );
+                Err(result.reasoning.unwrap_or_default())
+            }
+            Ok(result) => Ok(result.message),
+            Err(e) => {
+                warn!("Interceptor processing failed: {}", e);
+                Ok(message)
+            }
+        }
+    }
+}
+
+/// A client and a backend connected through the proxy
+pub struct ProxySession {
+    client: Peer,
+    backend: Peer,
+    ids: IdMapper,
+}
+
+impl ProxySession {
+    /// Connect a client and a backend, each given as a line reader and a writer
+    pub fn new(
+        client_reader: impl AsyncBufRead + Send + Unpin + 'static,
+        client_writer: impl AsyncWrite + Send + Unpin + 'static,
+        backend_reader: impl AsyncBufRead + Send + Unpin + 'static,
+        backend_writer: impl AsyncWrite + Send + Unpin + 'static,
+    ) -> Self {
+        Self {
+            client: Peer::new(
+                "client",
+                client_reader,
+                client_writer,
+                Side::Client,
+                MessageDirection::Outgoing,
+            ),
+            backend: Peer::new(
+                "backend",
+                backend_reader,
+                backend_writer,
+                Side::Server,
+                MessageDirection::Incoming,
+            ),
+            ids: IdMapper::new(),
+        }
+    }
+
+    /// Connect our stdin and stdout to a backend process started with piped stdio
+    pub fn stdio(backend: &mut Child) -> Result<Self> {
+        let stdin = backend.stdin.take().context("Backend stdin is not piped")?;
+        let stdout = backend
+            .stdout
+            .take()
+            .context("Backend stdout is not piped")?;
+        Ok(Self::new(
+            BufReader::new(tokio::io::stdin()),
+            tokio::io::stdout(),
+            BufReader::new(stdout),
+            stdin,
+        ))
+    }
+
+    /// Run messages in `direction` through `interceptors`
+    ///
+    /// [`MessageDirection::Outgoing`] covers messages from the client to the
+    /// backend, [`MessageDirection::Incoming`] those from the backend.
+    pub fn with_interceptors(
+        mut self,
+        direction: MessageDirection,
+        interceptors: Arc<InterceptorManager>,
+    ) -> Self {
+        match direction {
+            MessageDirection::Outgoing => self.client.interceptors = Some(interceptors),
+            MessageDirection::Incoming => self.backend.interceptors = Some(interceptors),
+        }
+        self
+    }
+
+    /// Forget requests left unanswered for `ttl` instead of the default
+    pub fn with_id_ttl(mut self, ttl: Duration) -> Self {
+        self.ids = IdMapper::with_ttl(ttl);
+        self
+    }
+
+    /// Forward messages both ways until either side closes its output
+    pub async fn run(mut self) -> Result<()> {
+        let mut expiry = tokio::time::interval(EXPIRY_CHECK);
+        loop {
+            tokio::select! {
+                line = self.client.reader.next_line() => match line.context("Failed to read from the client")? {
+                    Some(line) => relay(&mut self.client, &mut self.backend, &mut self.ids, &line).await?,
+                    None => {
+                        debug!("Client closed the session");
+                        break;
+                    }
+                },
+                line = self.backend.reader.next_line() => match line.context("Failed to read from the backend")? {
+                    Some(line) => relay(&mut self.backend, &mut self.client, &mut self.ids, &line).await?,
+                    None => {
+                        debug!("Backend closed the session");
+                        break;
+                    }
+                },
+                _ = expiry.tick() => {
+                    for (side, id) in self.ids.expire(Instant::now()) {
+                        warn!("Request {} from the {:?} side was never answered", id, side);
+                    }
+                }
+            }
+        }
+        let _ = self.backend.writer.shutdown().await;
+        Ok(())
+    }
+}
+
+/// Forward one line `from` one peer `to` the other
+async fn relay(from: &mut Peer, to: &mut Peer, ids: &mut IdMapper, line: &str) -> Result<()> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(());
+    }
+    let Ok(message) = serde_json::from_str::<JsonRpcMessage>(line) else {
+        debug!(
+            "Forwarding non JSON-RPC line from the {} unchanged",
+            from.name
+        );
+        return to.send_line(line).await;
+    };
+
+    match message {
+        JsonRpcMessage::Request(request) => {
+            let id = request.id.clone();
+            match from.intercept(JsonRpcMessage::Request(request)).await {
+                Ok(JsonRpcMessage::Request(mut request)) => {
+                    request.id = ids.map_request(from.side, request.id);
+                    to.send(&JsonRpcMessage::Request(request)).await
+                }
+                Ok(other) => to.send(&other).await,
+                Err(reason) => {
+                    let reply = JsonRpcResponse::error(
+                        id,
+                        JsonRpcError::application_error(
+                            BLOCKED_REQUEST_CODE,
+                            "Request blocked",
+                            reason,
+                        ),
+                    );
+                    from.send(&JsonRpcMessage::Response(reply)).await
+                }
+            }
+        }
+        JsonRpcMessage::Response(mut response) => {
+            let Some(original) = ids.map_response(from.side, &response.id) else {
+                warn!(
+                    "Dropping response from the {} to unknown request {}",
+                    from.name, response.id
+                );
+                return Ok(());
+            };
+            response.id = original;
+            match from.intercept(JsonRpcMessage::Response(response)).await {
+                Ok(message) => to.send(&message).await,
+                Err(_) => Ok(()),
+            }
+        }
+        JsonRpcMessage::Notification(notification) => {
+            match from
+                .intercept(JsonRpcMessage::Notification(notification))
+                .await
+            {
+                Ok(JsonRpcMessage::Notification(mut notification)) => {
+                    ids.map_cancellation(from.side, &mut notification);
+                    to.send(&JsonRpcMessage::Notification(notification)).await
+                }
+                Ok(other) => to.send(&other).await,
+                Err(_) => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interceptors::{PolicyInterceptor, ToolPolicy};
+    use serde_json::{json, Value};
+    use tokio::io::{duplex, DuplexStream, ReadHalf, WriteHalf};
+
+    /// The far end of one side of a session
+    struct Remote {
+        lines: Lines<BufReader<ReadHalf<DuplexStream>>>,
+        writer: WriteHalf<DuplexStream>,
+    }
+
+    impl Remote {
+        async fn send(&mut self, message: Value) {
+            let line = message.to_string() + "\n";
+            self.writer.write_all(line.as_bytes()).await.unwrap();
+        }
+
+        async fn recv(&mut self) -> Value {
+            let line = self.lines.next_line().await.unwrap().unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+    }
+
+    fn pipe() -> (Remote, ReadHalf<DuplexStream>, WriteHalf<DuplexStream>) {
+        let (near, far) = duplex(4096);
+        let (reader, writer) = tokio::io::split(far);
+        let (near_reader, near_writer) = tokio::io::split(near);
+        let remote = Remote {
+            lines: BufReader::new(near_reader).lines(),
+            writer: near_writer,
+        };
+        (remote, reader, writer)
+    }
+
+    fn session() -> (Remote, Remote, ProxySession) {
+        let (client, client_reader, client_writer) = pipe();
+        let (backend, backend_reader, backend_writer) = pipe();
+        let session = ProxySession::new(
+            BufReader::new(client_reader),
+            client_writer,
+            BufReader::new(backend_reader),
+            backend_writer,
+        );
+        (client, backend, session)
+    }
+
+    #[tokio::test]
+    async fn test_colliding_ids_are_remapped_both_ways() {
+        let (mut client, mut backend, session) = session();
+        let running = tokio::spawn(session.run());
+
+        // Both sides use id 1 at the same time
+        client
+            .send(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "echo"}}))
+            .await;
+        backend
+            .send(json!({"jsonrpc": "2.0", "id": 1, "method": "roots/list"}))
+            .await;
+
+        let call = backend.recv().await;
+        assert_eq!(call["method"], "tools/call");
+        let roots = client.recv().await;
+        assert_eq!(roots["method"], "roots/list");
+
+        // The client cancels and the backend answers its forwarded id
+        client
+            .send(json!({"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 1}}))
+            .await;
+        let cancelled = backend.recv().await;
+        assert_eq!(cancelled["params"]["requestId"], call["id"]);
+
+        backend
+            .send(json!({"jsonrpc": "2.0", "id": call["id"], "result": {"content": []}}))
+            .await;
+        client
+            .send(json!({"jsonrpc": "2.0", "id": roots["id"], "result": {"roots": []}}))
+            .await;
+
+        let result = client.recv().await;
+        assert_eq!(result["id"], 1);
+        assert_eq!(result["result"], json!({"content": []}));
+        let result = backend.recv().await;
+        assert_eq!(result["id"], 1);
+        assert_eq!(result["result"], json!({"roots": []}));
+
+        drop(client);
+        running.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_blocked_requests_are_answered_by_the_proxy() {
+        let (mut client, mut backend, session) = session();
+        let interceptors = Arc::new(InterceptorManager::new());
+        interceptors
+            .add_interceptor(Arc::new(PolicyInterceptor::new(
+                ToolPolicy::new().deny("rm"),
+            )))
+            .await;
+        let session = session.with_interceptors(MessageDirection::Outgoing, interceptors);
+        tokio::spawn(session.run());
+
+        client
+            .send(json!({"jsonrpc": "2.0", "id": "a", "method": "tools/call", "params": {"name": "rm"}}))
+            .await;
+        let reply = client.recv().await;
+        assert_eq!(reply["id"], "a");
+        assert_eq!(reply["error"]["code"], BLOCKED_REQUEST_CODE);
+
+        // Allowed requests still go through, and the backend never saw the blocked one
+        client
+            .send(json!({"jsonrpc": "2.0", "id": "b", "method": "tools/list"}))
+            .await;
+        let request = backend.recv().await;
+        assert_eq!(request["method"], "tools/list");
+        assert_eq!(request["id"], 1);
+    }
+}
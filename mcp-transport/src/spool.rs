@@ -0,0 +1,385 @@
+//! Offline buffering of messages for the monitor.
+//!
+//! While the monitor is unreachable, the proxy keeps what it would have sent
+//! in a [`Spool`]: a bounded ring that drops its oldest entries when full and
+//! can expire entries that are too old to be worth replaying. With a path
+//! configured the ring is also kept on disk as JSON lines, so messages queued
+//! by a proxy that is restarted before the monitor comes back are not lost.
+//! The queue is flushed, oldest first, once the monitor accepts a connection
+//! again. Delivered messages are removed from the file in batches and when
+//! the spool is dropped, so a proxy that crashes mid-flush may deliver some
+//! of them twice.
+//!
+//! Like [`mirror::MirrorTracker`](crate::mirror::MirrorTracker), the spool
+//! takes the current time as an argument; its only I/O is the spool file.
+
+use anyhow::{Context, Result};
+use mcp_common::IpcMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Messages held for the monitor before the oldest are dropped
+pub const DEFAULT_SPOOL_CAPACITY: usize = 10_000;
+
+/// Where undeliverable messages are kept and for how long
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpoolConfig {
+    /// Spool file; `None` keeps the queue in memory only
+    pub path: Option<PathBuf>,
+    /// Messages held before the oldest are dropped
+    pub capacity: usize,
+    /// Age after which a message is no longer worth delivering
+    pub max_age: Option<Duration>,
+}
+
+impl Default for SpoolConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            capacity: DEFAULT_SPOOL_CAPACITY,
+            max_age: None,
+        }
+    }
+}
+
+impl SpoolConfig {
+    /// Keep the queue in `path` as well as in memory
+    pub fn on_disk(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Counters for a [`Spool`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SpoolStats {
+    /// Messages queued because the monitor was unavailable
+    pub queued: u64,
+    /// Queued messages delivered once it was back
+    pub delivered: u64,
+    /// Messages dropped because the spool was full
+    pub dropped: u64,
+    /// Messages dropped because they waited longer than the maximum age
+    pub expired: u64,
+    /// Messages waiting now
+    pub pending: usize,
+}
+
+impl fmt::Display for SpoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} queued, {} delivered, {} dropped, {} expired, {} pending",
+            self.queued, self.delivered, self.dropped, self.expired, self.pending
+        )
+    }
+}
+
+/// One line of the spool file
+#[derive(Debug, Serialize, Deserialize)]
+struct Spooled {
+    /// Milliseconds since the Unix epoch
+    queued_at: u64,
+    message: IpcMessage,
+}
+
+/// Bounded queue of messages waiting for the monitor
+#[derive(Debug)]
+pub struct Spool {
+    config: SpoolConfig,
+    entries: VecDeque<Spooled>,
+    file: Option<File>,
+    /// Lines at the head of the file whose messages have left the queue
+    stale: usize,
+    stats: SpoolStats,
+}
+
+impl Spool {
+    /// An empty spool held in memory only, whatever the configured path
+    pub fn in_memory(config: SpoolConfig) -> Self {
+        Self {
+            config: SpoolConfig {
+                path: None,
+                ..config
+            },
+            entries: VecDeque::new(),
+            file: None,
+            stale: 0,
+            stats: SpoolStats::default(),
+        }
+    }
+
+    /// Open the spool, picking up messages a previous run left in its file
+    pub fn open(config: SpoolConfig) -> Result<Self> {
+        let Some(path) = config.path.clone() else {
+            return Ok(Self::in_memory(config));
+        };
+        let mut spool = Self::in_memory(config.clone());
+        spool.config = config;
+
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create spool directory {}", parent.display())
+            })?;
+        }
+        match File::open(&path) {
+            Ok(file) => {
+                for (number, line) in BufReader::new(file).lines().enumerate() {
+                    let line = line
+                        .with_context(|| format!("Failed to read spool file {}", path.display()))?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<Spooled>(&line) {
+                        Ok(entry) => spool.entries.push_back(entry),
+                        Err(e) => warn!(
+                            "Skipping unreadable line {} of spool file {}: {}",
+                            number + 1,
+                            path.display(),
+                            e
+                        ),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open spool file {}", path.display()))
+            }
+        }
+        while spool.entries.len() > spool.config.capacity {
+            spool.entries.pop_front();
+            spool.stats.dropped += 1;
+        }
+        spool.rewrite()?;
+        Ok(spool)
+    }
+
+    /// The settings in effect
+    pub fn config(&self) -> &SpoolConfig {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Queue a message, dropping the oldest one if the spool is full
+    pub fn push(&mut self, message: IpcMessage, now: SystemTime) -> Result<()> {
+        if self.config.capacity == 0 {
+            self.stats.dropped += 1;
+            return Ok(());
+        }
+        if self.entries.len() >= self.config.capacity {
+            self.entries.pop_front();
+            self.stale += 1;
+            self.stats.dropped += 1;
+        }
+        let entry = Spooled {
+            queued_at: millis(now),
+            message,
+        };
+        if let Some(ref mut file) = self.file {
+            let mut line = serde_json::to_string(&entry)?;
+            line.push('\n');
+            file.write_all(line.as_bytes())
+                .context("Failed to append to spool file")?;
+        }
+        self.entries.push_back(entry);
+        self.stats.queued += 1;
+        self.compact_if_needed()
+    }
+
+    /// Oldest message still waiting
+    pub fn front(&self) -> Option<&IpcMessage> {
+        self.entries.front().map(|entry| &entry.message)
+    }
+
+    /// Record that the oldest message was delivered
+    pub fn delivered(&mut self) -> Result<()> {
+        if self.entries.pop_front().is_some() {
+            self.stale += 1;
+            self.stats.delivered += 1;
+        }
+        self.compact_if_needed()
+    }
+
+    /// Drop messages older than the maximum age; returns how many were dropped
+    pub fn expire(&mut self, now: SystemTime) -> Result<usize> {
+        let Some(max_age) = self.config.max_age else {
+            return Ok(0);
+        };
+        let cutoff = millis(now).saturating_sub(max_age.as_millis() as u64);
+        let mut expired = 0;
+        while self
+            .entries
+            .front()
+            .is_some_and(|entry| entry.queued_at < cutoff)
+        {
+            self.entries.pop_front();
+            expired += 1;
+        }
+        if expired > 0 {
+            self.stale += expired;
+            self.stats.expired += expired as u64;
+            self.compact_if_needed()?;
+        }
+        Ok(expired)
+    }
+
+    /// Counters since the spool was opened
+    pub fn stats(&self) -> SpoolStats {
+        SpoolStats {
+            pending: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    /// Rewrite the file once it holds more stale lines than live ones, or
+    /// truncate it once the queue is empty
+    fn compact_if_needed(&mut self) -> Result<()> {
+        if self.file.is_some()
+            && self.stale > 0
+            && (self.entries.is_empty() || self.stale >= self.entries.len())
+        {
+            self.rewrite()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the spool file with the entries still queued
+    fn rewrite(&mut self) -> Result<()> {
+        let Some(ref path) = self.config.path else {
+            return Ok(());
+        };
+        let temp = path.with_extension("tmp");
+        {
+            let mut file = File::create(&temp)
+                .with_context(|| format!("Failed to write spool file {}", temp.display()))?;
+            for entry in &self.entries {
+                let mut line = serde_json::to_string(entry)?;
+                line.push('\n');
+                file.write_all(line.as_bytes())?;
+            }
+        }
+        fs::rename(&temp, path)
+            .with_context(|| format!("Failed to replace spool file {}", path.display()))?;
+        self.file = Some(
+            OpenOptions::new()
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open spool file {}", path.display()))?,
+        );
+        self.stale = 0;
+        Ok(())
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        if self.file.is_some() && self.stale > 0 {
+            if let Err(e) = self.rewrite() {
+                warn!("Failed to compact spool file: {}", e);
+            }
+        }
+    }
+}
+
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_common::{LogEntry, LogLevel, ProxyId};
+
+    fn log(text: &str) -> IpcMessage {
+        IpcMessage::LogEntry(LogEntry::new(
+            LogLevel::Info,
+            text.to_string(),
+            ProxyId::new(),
+        ))
+    }
+
+    fn text(message: Option<&IpcMessage>) -> Option<&str> {
+        match message {
+            Some(IpcMessage::LogEntry(entry)) => Some(&entry.message),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_ring_drops_oldest_and_expires() {
+        let mut spool = Spool::in_memory(SpoolConfig {
+            capacity: 2,
+            max_age: Some(Duration::from_secs(60)),
+            ..SpoolConfig::default()
+        });
+        let start = SystemTime::now();
+        for (i, name) in ["a", "b", "c"].iter().enumerate() {
+            spool
+                .push(log(name), start + Duration::from_secs(i as u64 * 30))
+                .unwrap();
+        }
+        assert_eq!(text(spool.front()), Some("b"));
+
+        // "b" was queued at +30s
+        assert_eq!(spool.expire(start + Duration::from_secs(91)).unwrap(), 1);
+        assert_eq!(text(spool.front()), Some("c"));
+        spool.delivered().unwrap();
+        assert!(spool.is_empty());
+        assert_eq!(
+            spool.stats(),
+            SpoolStats {
+                queued: 3,
+                delivered: 1,
+                dropped: 1,
+                expired: 1,
+                pending: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_disk_spool_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spool").join("monitor.jsonl");
+        let now = SystemTime::now();
+
+        let mut spool = Spool::open(SpoolConfig::on_disk(&path)).unwrap();
+        for name in ["a", "b", "c"] {
+            spool.push(log(name), now).unwrap();
+        }
+        spool.delivered().unwrap();
+        drop(spool);
+
+        let mut spool = Spool::open(SpoolConfig {
+            capacity: 1,
+            ..SpoolConfig::on_disk(&path)
+        })
+        .unwrap();
+        assert_eq!(spool.len(), 1);
+        assert_eq!(text(spool.front()), Some("c"));
+        assert_eq!(spool.stats().dropped, 1);
+
+        spool.delivered().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    }
+}
@@ -0,0 +1,302 @@
+//! Warm standby sessions for critical backends.
+//!
+//! A backend marked `critical = true` in the backends file is served by a
+//! [`StandbyBackend`]: next to the active session it keeps a second session
+//! that is already initialized and is pinged on every maintenance tick. When
+//! the active session's connection is lost, the standby is promoted at once,
+//! so the client never waits for a process to start or a handshake to finish.
+//! A replacement standby is then connected in the background.
+//!
+//! The failed request is retried on the promoted session only if repeating it
+//! is harmless, as classified by [`Idempotency::of`]: listings, reads, `ping`
+//! and the like. A `tools/call` may already have run on the lost session, so
+//! its error is returned instead of calling the tool a second time.
+//!
+//! The price is one extra server process or HTTP session per critical
+//! backend, reported as `standby_connections` in the proxy stats.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use mcp_core::messages::JsonRpcError;
+use mcp_core::retry::Idempotency;
+use serde_json::{json, Value};
+use tokio::sync::{Notify, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::aggregator::Backend;
+
+/// Opens new sessions to one backend
+#[async_trait]
+pub trait Connector: Send + Sync {
+    /// Connect and initialize a fresh session
+    async fn connect(&self) -> Result<Arc<dyn Backend>>;
+}
+
+/// A backend with an initialized spare session ready to take over
+pub struct StandbyBackend {
+    name: String,
+    connector: Arc<dyn Connector>,
+    active: RwLock<Arc<dyn Backend>>,
+    standby: Mutex<Option<Arc<dyn Backend>>>,
+    replenish: Arc<Notify>,
+    failovers: AtomicU64,
+}
+
+impl StandbyBackend {
+    /// Serve `name` from `active`, opening standbys with `connector`
+    pub fn new(
+        name: impl Into<String>,
+        active: Arc<dyn Backend>,
+        connector: Arc<dyn Connector>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.into(),
+            connector,
+            active: RwLock::new(active),
+            standby: Mutex::new(None),
+            replenish: Arc::new(Notify::new()),
+            failovers: AtomicU64::new(0),
+        })
+    }
+
+    /// Keep a validated standby until the backend is dropped.
+    ///
+    /// Runs a maintenance pass right away, then every `interval` and whenever
+    /// a failover used up the standby.
+    pub fn spawn_maintenance(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let backend = Arc::downgrade(self);
+        let replenish = self.replenish.clone();
+        tokio::spawn(async move {
+            loop {
+                let Some(this) = backend.upgrade() else {
+                    break;
+                };
+                this.maintain().await;
+                drop(this);
+
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = replenish.notified() => {}
+                }
+            }
+        })
+    }
+
+    /// Whether a standby session is connected and validated
+    pub fn standby_ready(&self) -> bool {
+        self.standby().is_some()
+    }
+
+    /// Times the standby took over from a lost session
+    pub fn failovers(&self) -> u64 {
+        self.failovers.load(Ordering::Relaxed)
+    }
+
+    fn standby(&self) -> MutexGuard<'_, Option<Arc<dyn Backend>>> {
+        self.standby.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Validate the standby, replacing it if it stopped answering
+    async fn maintain(&self) {
+        let current = self.standby().clone();
+        if let Some(standby) = current {
+            let healthy =
+                !standby.is_lost() && standby.request("ping", json!({}), None).await.is_ok();
+            if healthy {
+                return;
+            }
+            warn!("Standby session for '{}' failed validation", self.name);
+            let mut slot = self.standby();
+            if slot.as_ref().is_some_and(|s| Arc::ptr_eq(s, &standby)) {
+                *slot = None;
+            }
+        }
+
+        match self.connector.connect().await {
+            Ok(standby) => {
+                debug!("Standby session for '{}' is ready", self.name);
+                *self.standby() = Some(standby);
+            }
+            Err(e) => warn!(
+                "Failed to open standby session for '{}': {:#}",
+                self.name, e
+            ),
+        }
+    }
+
+    /// Promote the standby in place of `failed`, returning the session to use
+    async fn fail_over(&self, failed: &Arc<dyn Backend>) -> Option<Arc<dyn Backend>> {
+        let mut active = self.active.write().await;
+        if !Arc::ptr_eq(&active, failed) {
+            // Another request already failed over
+            return Some(active.clone());
+        }
+
+        let standby = self.standby().take();
+        self.replenish.notify_one();
+        let standby = standby?;
+        *active = standby.clone();
+        self.failovers.fetch_add(1, Ordering::Relaxed);
+        info!("Session for '{}' was lost; standby took over", self.name);
+        Some(standby)
+    }
+}
+
+#[async_trait]
+impl Backend for StandbyBackend {
+    async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        session: Option<&str>,
+    ) -> Result<Value, JsonRpcError> {
+        let active = self.active.read().await.clone();
+        let result = active.request(method, params.clone(), session).await;
+        if result.is_ok() || !active.is_lost() {
+            return result;
+        }
+
+        match self.fail_over(&active).await {
+            Some(replacement) if Idempotency::of(method) == Idempotency::Idempotent => {
+                replacement.request(method, params, session).await
+            }
+            _ => result,
+        }
+    }
+
+    fn is_lost(&self) -> bool {
+        self.active
+            .try_read()
+            .is_ok_and(|active| active.is_lost() && !self.standby_ready())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    /// Answers with its session number until marked lost
+    struct Session {
+        number: u64,
+        lost: AtomicBool,
+        received: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Backend for Session {
+        async fn request(
+            &self,
+            method: &str,
+            _params: Value,
+            _session: Option<&str>,
+        ) -> Result<Value, JsonRpcError> {
+            self.received.lock().unwrap().push(method.to_string());
+            if self.lost.load(Ordering::SeqCst) {
+                return Err(JsonRpcError::internal_error("connection lost"));
+            }
+            Ok(json!({ "session": self.number }))
+        }
+
+        fn is_lost(&self) -> bool {
+            self.lost.load(Ordering::SeqCst)
+        }
+    }
+
+    #[derive(Default)]
+    struct Sessions {
+        opened: Mutex<Vec<Arc<Session>>>,
+    }
+
+    impl Sessions {
+        fn open(&self) -> Arc<Session> {
+            let mut opened = self.opened.lock().unwrap();
+            let session = Arc::new(Session {
+                number: opened.len() as u64,
+                lost: AtomicBool::new(false),
+                received: Mutex::new(Vec::new()),
+            });
+            opened.push(session.clone());
+            session
+        }
+
+        fn lose(&self, number: usize) {
+            self.opened.lock().unwrap()[number]
+                .lost
+                .store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl Connector for Sessions {
+        async fn connect(&self) -> Result<Arc<dyn Backend>> {
+            Ok(self.open())
+        }
+    }
+
+    async fn wait_for_standby(backend: &StandbyBackend) {
+        for _ in 0..100 {
+            if backend.standby_ready() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("standby never became ready");
+    }
+
+    #[tokio::test]
+    async fn test_lost_session_fails_over_to_standby_and_is_replenished() {
+        let sessions = Arc::new(Sessions::default());
+        let backend = StandbyBackend::new("files", sessions.open(), sessions.clone());
+        backend.spawn_maintenance(Duration::from_secs(60));
+        wait_for_standby(&backend).await;
+
+        let result = backend
+            .request("tools/list", json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(result["session"], 0);
+
+        sessions.lose(0);
+        let result = backend
+            .request("tools/list", json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(result["session"], 1);
+        assert_eq!(backend.failovers(), 1);
+
+        // The used-up standby is replaced without waiting for the next tick
+        wait_for_standby(&backend).await;
+        assert_eq!(sessions.opened.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_on_lost_session_is_not_sent_again() {
+        let sessions = Arc::new(Sessions::default());
+        let backend = StandbyBackend::new("files", sessions.open(), sessions.clone());
+        backend.spawn_maintenance(Duration::from_secs(60));
+        wait_for_standby(&backend).await;
+
+        sessions.lose(0);
+        let result = backend
+            .request("tools/call", json!({ "name": "rm" }), None)
+            .await;
+        assert!(result.is_err());
+
+        // The standby took over, but the call only ever reached the lost session
+        assert_eq!(backend.failovers(), 1);
+        let opened = sessions.opened.lock().unwrap().clone();
+        assert_eq!(*opened[0].received.lock().unwrap(), ["tools/call"]);
+        assert!(opened[1].received.lock().unwrap().is_empty());
+
+        let result = backend
+            .request("tools/list", json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(result["session"], 1);
+    }
+}
@@ -1,15 +1,34 @@
 use anyhow::Result;
-use mcp_common::{IpcMessage, InterceptorInfo, InterceptorManagerInfo, LogEntry, LogLevel, ProxyId, ProxyStats};
+use mcp_common::{
+    InterceptorInfo, InterceptorManagerInfo, IpcMessage, LogEntry, LogLevel, ObservedMessage, ProxyId,
+    ProxyMetrics, ProxyStats, TrafficDirection,
+};
+use mcp_core::cache::{CacheConfig, CacheKey, ResponseCache};
+use mcp_core::flood::{flood_key, FloodConfig, FloodGuard, FloodOutput};
 use mcp_core::interceptor::{InterceptorManager, MessageDirection};
-use mcp_core::messages::JsonRpcMessage;
+use mcp_core::messages::{JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcResponse};
+use mcp_core::transport::limits::{self, FrameLimiter, LimitedFrame, MessageLimits};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
-use tokio::process::Child;
-use tokio::sync::{broadcast, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter, Stdout};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio::time::{interval, Duration};
 use tracing::{debug, error, info, warn};
 
+use crate::audit::Auditor;
+use crate::auth::{AuthError, ClientGate, Credentials, Identity};
 use crate::buffered_ipc_client::BufferedIpcClient;
+use crate::fidelity::FidelityMode;
+use crate::hot_swap::{self, CapabilityDiff, SessionTracker, SwapRequest};
+use crate::interceptors::Redactor;
+use crate::mirror::Mirror;
+use crate::transport_config::TransportConfig;
+
+/// JSON-RPC error code returned to the client when an interceptor blocks a request
+pub const BLOCKED_REQUEST_CODE: i32 = -32001;
 
 pub struct StdioHandler {
     proxy_id: ProxyId,
@@ -17,19 +36,55 @@ pub struct StdioHandler {
     ipc_client: Option<Arc<BufferedIpcClient>>,
     stats_interval: tokio::time::Interval,
     interceptor_manager: Arc<InterceptorManager>,
+    redactor: Arc<Redactor>,
+    fidelity: FidelityMode,
+    session: SessionTracker,
+    backend_config: Option<TransportConfig>,
+    swap_rx: Option<mpsc::Receiver<SwapRequest>>,
+    flood_guard: Option<FloodGuard<Incoming>>,
+    mirror: Option<Mirror>,
+    cache: Option<ResponseCache>,
+    /// Cacheable requests awaiting their response, by request ID
+    cache_pending: HashMap<String, CacheKey>,
+    /// Client session the cache is keyed by; bumped on `initialize` and backend swaps
+    cache_session: u64,
+    audit: Option<Auditor>,
+    /// Client gate and who it found the stdio client to be
+    gate: Option<(ClientGate, Result<Identity, AuthError>)>,
+    /// Size limits on lines in each direction
+    limits: Option<MessageLimits>,
+    /// Totals served to Prometheus, see [`crate::exporter`]
+    metrics: Option<Arc<Mutex<ProxyMetrics>>>,
+}
+
+/// A server line on its way to the client
+struct Incoming {
+    processed: String,
+    modified: bool,
+    original: String,
 }
 
+type BackendPipes = (BufWriter<ChildStdin>, BufReader<ChildStdout>, BufReader<ChildStderr>);
+
 impl StdioHandler {
     pub async fn new(
         proxy_id: ProxyId,
         stats: Arc<Mutex<ProxyStats>>,
         ipc_client: Option<Arc<BufferedIpcClient>>,
     ) -> Result<Self> {
-        use crate::interceptors::{TransformInterceptor, TransformRule, TransformOperation};
+        use crate::interceptors::{
+            RedactionInterceptor, TransformInterceptor, TransformOperation, TransformRule,
+        };
         use serde_json::json;
 
         let manager = InterceptorManager::new();
 
+        // Keep secrets out of logs and the monitor feed
+        let redactor = Arc::new(Redactor::default());
+        manager
+            .add_interceptor(Arc::new(RedactionInterceptor::new(redactor.clone())))
+            .await;
+
         // Replace "santa" with current timestamp in tool calls
         let transformer = TransformInterceptor::new();
         transformer.add_rule(TransformRule {
@@ -49,6 +104,7 @@ impl StdioHandler {
             Arc::new(manager),
         )
         .await
+        .map(|handler| handler.with_redactor(redactor))
     }
 
     pub async fn with_interceptors(
@@ -65,9 +121,95 @@ impl StdioHandler {
             ipc_client,
             stats_interval,
             interceptor_manager,
+            redactor: Arc::new(Redactor::default()),
+            fidelity: FidelityMode::default(),
+            session: SessionTracker::default(),
+            backend_config: None,
+            swap_rx: None,
+            flood_guard: None,
+            mirror: None,
+            cache: None,
+            cache_pending: HashMap::new(),
+            cache_session: 0,
+            audit: None,
+            gate: None,
+            limits: None,
+            metrics: None,
         })
     }
 
+    /// Choose how faithfully untouched messages are forwarded
+    pub fn with_fidelity(mut self, fidelity: FidelityMode) -> Self {
+        self.fidelity = fidelity;
+        self
+    }
+
+    /// Accept backend swaps; `current` is what a restart relaunches
+    pub fn with_hot_swap(mut self, current: TransportConfig, swap_rx: mpsc::Receiver<SwapRequest>) -> Self {
+        self.backend_config = Some(current);
+        self.swap_rx = Some(swap_rx);
+        self
+    }
+
+    /// Rate-limit server notifications, coalescing bursts and muting floods
+    pub fn with_flood_guard(mut self, config: FloodConfig) -> Self {
+        self.flood_guard = Some(FloodGuard::new(config));
+        self
+    }
+
+    /// Copy client traffic to a shadow backend and compare its answers
+    pub fn with_mirror(mut self, mirror: Mirror) -> Self {
+        self.mirror = Some(mirror);
+        self
+    }
+
+    /// Answer repeated listings and resource reads from a cache
+    pub fn with_response_cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(ResponseCache::new(config));
+        self
+    }
+
+    /// Record every tool call in a tamper-evident audit log
+    pub fn with_audit(mut self, auditor: Auditor) -> Self {
+        self.audit = Some(auditor);
+        self
+    }
+
+    /// Authenticate the stdio client by the owner of its pipe and check
+    /// each of its requests with `gate`
+    pub fn with_gate(mut self, gate: ClientGate) -> Self {
+        let credentials = Credentials::stdio_peer();
+        let identity = gate.authenticate(&credentials);
+        match identity {
+            Ok(ref identity) => info!("Client authenticated as '{}' ({})", identity, credentials),
+            Err(ref e) => warn!("Client not authenticated ({}): {}; its requests will be refused", credentials, e),
+        }
+        if let (Some(ref mut audit), Ok(ref identity)) = (&mut self.audit, &identity) {
+            audit.set_identity(identity.name.clone());
+        }
+        self.gate = Some((gate, identity));
+        self
+    }
+
+    /// Hold server lines to `limits.max_inbound` and client lines to
+    /// `limits.max_outbound`, reading neither past its limit
+    pub fn with_limits(mut self, limits: MessageLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Count forwarded traffic into `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<Mutex<ProxyMetrics>>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Use a custom redactor for traffic sent to logs and the monitor
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
     /// Get the interceptor manager for this handler
     pub fn interceptor_manager(&self) -> &Arc<InterceptorManager> {
         &self.interceptor_manager
@@ -98,7 +240,14 @@ impl StdioHandler {
         let mut user_stdin = BufReader::new(tokio::io::stdin());
         let mut user_stdout = tokio::io::stdout();
 
-        // Channels removed - not needed for direct STDIO handling
+        // Client frames read while a backend swap was in progress
+        let mut queued_input = VecDeque::new();
+
+        let mut server_frames = self.limits.as_ref().map(MessageLimits::inbound);
+        let mut client_frames = self.limits.as_ref().map(MessageLimits::outbound);
+
+        // The stdio client is connected for as long as the loop runs
+        self.stats.lock().await.active_connections = 1;
 
         loop {
             tokio::select! {
@@ -126,42 +275,110 @@ impl StdioHandler {
                             warn!("Failed to send interceptor stats: {}", e);
                         }
                     }
+
+                    // Release bursts whose window has closed
+                    if let Some(ref mut guard) = self.flood_guard {
+                        let outputs = guard.tick(Instant::now());
+                        if let Err(e) = self.emit_incoming(&mut user_stdout, outputs).await {
+                            error!("Failed to write to user stdout: {}", e);
+                            self.count_transport_error().await;
+                            break;
+                        }
+                    }
                 }
 
-                // Read from user stdin and forward to child
+                // Replace or restart the backend
+                Some(request) = hot_swap::next_swap(&mut self.swap_rx) => {
+                    match self
+                        .swap_backend(
+                            request,
+                            child,
+                            &mut child_stdout,
+                            server_frames.as_mut(),
+                            &mut user_stdin,
+                            client_frames.as_mut(),
+                            &mut user_stdout,
+                            &mut queued_input,
+                        )
+                        .await
+                    {
+                        Ok((stdin, stdout, stderr)) => {
+                            child_stdin = stdin;
+                            child_stdout = stdout;
+                            child_stderr = stderr;
+                            server_frames = self.limits.as_ref().map(MessageLimits::inbound);
+                            self.reset_cache();
+                        }
+                        Err(e) => {
+                            warn!("Backend swap failed, keeping the current backend: {:#}", e);
+                            self.log_event(LogLevel::Error, format!("Backend swap failed: {:#}", e)).await;
+                        }
+                    }
+                }
+
+                // Read from user stdin (or the swap queue) and forward to child
                 result = async {
-                    let mut input = String::new();
-                    let bytes_read = user_stdin.read_line(&mut input).await?;
-                    Ok::<(usize, String), std::io::Error>((bytes_read, input))
+                    if let Some(frame) = queued_input.pop_front() {
+                        return Ok(Some(frame));
+                    }
+                    read_line(&mut user_stdin, client_frames.as_mut()).await
                 } => {
                     match result {
-                        Ok((0, _)) => break, // EOF
-                        Ok((_, input)) => {
+                        Ok(None) => break, // EOF
+                        Ok(Some(frame)) => {
+                            let input = match frame {
+                                LimitedFrame::Complete(input) => input,
+                                oversized => {
+                                    self.refuse_oversized(&mut user_stdout, oversized).await;
+                                    continue; // Skip sending to child
+                                }
+                            };
+
+                            // Turn away requests the client may not make
+                            if let Some(reason) = self.refusal(&input) {
+                                warn!("Request refused: {}", reason);
+                                self.refuse(&mut user_stdout, &input, &reason).await;
+                                continue; // Skip sending to child
+                            }
+
                             // Process through interceptors
                             let (processed_input, modified) = match self.process_outgoing(&input).await {
                                 Ok(result) => result,
                                 Err(e) => {
                                     warn!("Message blocked or failed processing: {}", e);
-                                    // Log the blocked message
-                                    self.log_request(&input, false).await;
-                                    {
-                                        let mut stats = self.stats.lock().await;
-                                        stats.failed_requests += 1;
-                                    }
+                                    self.refuse(&mut user_stdout, &input, &e.to_string()).await;
                                     continue; // Skip sending to child
                                 }
                             };
 
-                            self.log_request(&processed_input, modified).await;
+                            self.log_request(&processed_input, modified, Some(&input)).await;
+
+                            if let Some(reply) = self.cached_reply(&processed_input) {
+                                if let Err(e) = self.send_cached(&mut user_stdout, &reply).await {
+                                    error!("Failed to write to user stdout: {}", e);
+                                    self.count_transport_error().await;
+                                    break;
+                                }
+                                continue; // Answered without the server
+                            }
+                            self.session.observe_outgoing(&processed_input);
+                            if let Some(ref mut audit) = self.audit {
+                                audit.client_line(&processed_input);
+                            }
 
                             if let Err(e) = child_stdin.write_all(processed_input.as_bytes()).await {
                                 error!("Failed to write to child stdin: {}", e);
+                                self.count_transport_error().await;
                                 break;
                             }
                             if let Err(e) = child_stdin.flush().await {
                                 error!("Failed to flush child stdin: {}", e);
+                                self.count_transport_error().await;
                                 break;
                             }
+                            if let Some(ref mirror) = self.mirror {
+                                mirror.client_line(&processed_input);
+                            }
 
                             // Update stats
                             {
@@ -178,49 +395,18 @@ impl StdioHandler {
                 }
 
                 // Read from child stdout and forward to user
-                result = async {
-                    let mut output = String::new();
-                    let bytes_read = child_stdout.read_line(&mut output).await?;
-                    Ok::<(usize, String), std::io::Error>((bytes_read, output))
-                } => {
+                result = read_line(&mut child_stdout, server_frames.as_mut()) => {
                     match result {
-                        Ok((0, _)) => {
+                        Ok(None) => {
                             info!("Child stdout closed");
                             break;
                         }
-                        Ok((_, output)) => {
-                            // Process through interceptors
-                            let (processed_output, modified) = match self.process_incoming(&output).await {
-                                Ok(result) => result,
-                                Err(e) => {
-                                    warn!("Message blocked or failed processing: {}", e);
-                                    // Log the blocked message
-                                    self.log_response(&output, false).await;
-                                    {
-                                        let mut stats = self.stats.lock().await;
-                                        stats.failed_requests += 1;
-                                    }
-                                    continue; // Skip sending to user
-                                }
-                            };
-
-                            self.log_response(&processed_output, modified).await;
-
-                            if let Err(e) = user_stdout.write_all(processed_output.as_bytes()).await {
+                        Ok(Some(frame)) => {
+                            if let Err(e) = self.relay_server_frame(&mut user_stdout, frame).await {
                                 error!("Failed to write to user stdout: {}", e);
+                                self.count_transport_error().await;
                                 break;
                             }
-                            if let Err(e) = user_stdout.flush().await {
-                                error!("Failed to flush user stdout: {}", e);
-                                break;
-                            }
-
-                            // Update stats
-                            {
-                                let mut stats = self.stats.lock().await;
-                                stats.successful_requests += 1;
-                                stats.bytes_transferred += processed_output.len() as u64;
-                            }
                         }
                         Err(e) => {
                             error!("Failed to read from child stdout: {}", e);
@@ -228,6 +414,7 @@ impl StdioHandler {
                                 let mut stats = self.stats.lock().await;
                                 stats.failed_requests += 1;
                             }
+                            self.count_transport_error().await;
                             break;
                         }
                     }
@@ -263,8 +450,8 @@ impl StdioHandler {
                         Ok(exit_status) => {
                             info!("Child process exited with status: {}", exit_status);
                             if !exit_status.success() {
-                                let mut stats = self.stats.lock().await;
-                                stats.failed_requests += 1;
+                                self.stats.lock().await.failed_requests += 1;
+                                self.count_transport_error().await;
                             }
                         }
                         Err(e) => {
@@ -276,11 +463,332 @@ impl StdioHandler {
             }
         }
 
+        self.stats.lock().await.active_connections = 0;
+        if let Some(mirror) = self.mirror.take() {
+            mirror.shutdown().await;
+        }
+        if let Some(ref cache) = self.cache {
+            let summary = format!("Response cache: {}", cache.stats());
+            info!("{}", summary);
+            self.log_event(LogLevel::Info, summary).await;
+        }
+        if let Some(mut audit) = self.audit.take() {
+            if let Err(e) = audit.finish() {
+                self.audit_failed(e).await;
+            }
+            info!(
+                "Audit log {}: {} record(s), head {}",
+                audit.log().path().display(),
+                audit.log().len(),
+                audit.log().head()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Start a new backend, then retire the current one
+    ///
+    /// Client frames read while the new backend initializes are queued,
+    /// still held to the size limit, and handled afterwards. The current
+    /// backend's output keeps being forwarded until it is shut down. On
+    /// failure the current backend is left running.
+    #[allow(clippy::too_many_arguments)]
+    async fn swap_backend<R, W>(
+        &mut self,
+        request: SwapRequest,
+        child: &mut Child,
+        child_stdout: &mut BufReader<ChildStdout>,
+        mut server_frames: Option<&mut FrameLimiter>,
+        user_stdin: &mut R,
+        mut client_frames: Option<&mut FrameLimiter>,
+        user_stdout: &mut W,
+        queued_input: &mut VecDeque<LimitedFrame>,
+    ) -> Result<BackendPipes>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let config = match request {
+            SwapRequest::Restart => self
+                .backend_config
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("No backend configuration to restart"))?,
+            SwapRequest::Replace(config) => config,
+        };
+        info!("Swapping backend to {}", config.display_target());
+
+        // The current backend's answers update the session while the new one starts
+        let handshake = self.session.clone();
+        let mut client_open = true;
+        let mut server_open = true;
+        let backend = {
+            let start = hot_swap::start_backend(&config, &handshake);
+            tokio::pin!(start);
+            loop {
+                tokio::select! {
+                    result = &mut start => break result?,
+
+                    result = read_line(child_stdout, server_frames.as_deref_mut()), if server_open => {
+                        match result {
+                            Ok(None) => server_open = false,
+                            Ok(Some(frame)) => self.relay_server_frame(user_stdout, frame).await?,
+                            Err(e) => {
+                                warn!("Failed to read from previous MCP server: {}", e);
+                                server_open = false;
+                            }
+                        }
+                    }
+
+                    result = read_line(user_stdin, client_frames.as_deref_mut()), if client_open => {
+                        match result {
+                            Ok(None) => client_open = false,
+                            Ok(Some(frame)) => queued_input.push_back(frame),
+                            Err(e) => {
+                                error!("Failed to read from user stdin: {}", e);
+                                client_open = false;
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        // Requests the old backend never answered would otherwise hang the client
+        for id in self.session.take_pending() {
+            let response = JsonRpcResponse::error(
+                id,
+                JsonRpcError::internal_error("Backend was replaced before responding; retry the request"),
+            );
+            let response = serde_json::to_string(&response)?;
+            if let Some(Err(e)) = self.audit.as_mut().map(|audit| audit.server_line(&response)) {
+                self.audit_failed(e).await;
+            }
+            self.send_to_client(user_stdout, &response).await?;
+        }
+
+        if let Err(e) = child.kill().await {
+            warn!("Failed to kill previous MCP server process: {}", e);
+        }
+        *child = backend.child;
+
+        let diff = CapabilityDiff::between(self.session.capabilities(), backend.capabilities.as_ref());
+        self.session.set_capabilities(backend.capabilities);
+
+        let mut summary = format!("Backend swapped to {}", config.display_target());
+        if !diff.added.is_empty() {
+            summary.push_str(&format!("; capabilities added: {}", diff.added.join(", ")));
+        }
+        if !diff.removed.is_empty() {
+            summary.push_str(&format!("; capabilities removed: {}", diff.removed.join(", ")));
+        }
+        let level = if diff.removed.is_empty() { LogLevel::Info } else { LogLevel::Warning };
+        self.log_event(level, summary).await;
+
+        for method in diff.notifications {
+            let notification = JsonRpcNotification::new(method, json!({}));
+            self.send_to_client(user_stdout, &serde_json::to_string(&notification)?).await?;
+        }
+
+        if let Some(ref mut audit) = self.audit {
+            audit.set_server(config.display_target());
+        }
+        self.backend_config = Some(config);
+        Ok((backend.stdin, backend.stdout, backend.stderr))
+    }
+
+    /// Forward a frame read from the server to the client, through the size
+    /// limit, interceptors, cache, audit log and flood guard
+    async fn relay_server_frame<W>(&mut self, user_stdout: &mut W, frame: LimitedFrame) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let Some(output) = self.limit_incoming(frame).await else {
+            return Ok(()); // Nothing left to forward
+        };
+        if let Some(ref mirror) = self.mirror {
+            mirror.primary_line(&output);
+        }
+
+        // Process through interceptors
+        let (processed_output, modified) = match self.process_incoming(&output).await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("Message blocked or failed processing: {}", e);
+                // Log the blocked message
+                self.log_response(&output, false, None).await;
+                {
+                    let mut stats = self.stats.lock().await;
+                    stats.failed_requests += 1;
+                }
+                return Ok(()); // Skip sending to user
+            }
+        };
+
+        self.observe_for_cache(&processed_output);
+        if let Some(Err(e)) = self.audit.as_mut().map(|audit| audit.server_line(&processed_output)) {
+            self.audit_failed(e).await;
+        }
+
+        let incoming = Incoming {
+            processed: processed_output,
+            modified,
+            original: output,
+        };
+        self.forward_incoming(user_stdout, incoming).await
+    }
+
+    /// Pass a server line through the flood guard, then forward what it lets through
+    async fn forward_incoming<W>(&mut self, user_stdout: &mut W, incoming: Incoming) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let outputs = match (&mut self.flood_guard, notification_key(&incoming.processed)) {
+            (Some(guard), Some(key)) => guard.admit(&key, incoming, Instant::now()),
+            _ => vec![FloodOutput::Deliver(incoming)],
+        };
+        self.emit_incoming(user_stdout, outputs).await
+    }
+
+    /// Log and forward delivered server lines and report flood events
+    async fn emit_incoming<W>(
+        &mut self,
+        user_stdout: &mut W,
+        outputs: Vec<FloodOutput<Incoming>>,
+    ) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        for output in outputs {
+            let incoming = match output {
+                FloodOutput::Deliver(incoming) => incoming,
+                FloodOutput::Event(event) => {
+                    let level = if event.is_warning() {
+                        warn!("{}", event);
+                        LogLevel::Warning
+                    } else {
+                        debug!("{}", event);
+                        LogLevel::Info
+                    };
+                    self.log_event(level, event.to_string()).await;
+                    continue;
+                }
+            };
+
+            self.log_response(&incoming.processed, incoming.modified, Some(&incoming.original)).await;
+            self.session.observe_incoming(&incoming.processed);
+
+            user_stdout.write_all(incoming.processed.as_bytes()).await?;
+            user_stdout.flush().await?;
+
+            // Update stats
+            let mut stats = self.stats.lock().await;
+            stats.successful_requests += 1;
+            stats.bytes_transferred += incoming.processed.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Cached response to a client request, with the request's ID
+    ///
+    /// Cacheable requests that miss are remembered so their response can be stored.
+    fn cached_reply(&mut self, line: &str) -> Option<String> {
+        let cache = self.cache.as_mut()?;
+        let message: Value = serde_json::from_str(line.trim()).ok()?;
+        let method = message.get("method")?.as_str()?;
+        let id = message.get("id")?;
+        if method == "initialize" {
+            // A new client session must not see the previous one's answers
+            self.cache_session += 1;
+            return None;
+        }
+
+        let key = cache.key(method, message.get("params"), &self.cache_session.to_string())?;
+        match cache.get(&key, Instant::now()) {
+            Some(result) => {
+                // Fields in the usual order; json! would sort them
+                Some(format!(r#"{{"jsonrpc":"2.0","id":{},"result":{}}}"#, id, result) + "\n")
+            }
+            None => {
+                self.cache_pending.insert(id.to_string(), key);
+                None
+            }
+        }
+    }
+
+    /// Store responses to remembered requests and drop entries the server's
+    /// notifications make stale
+    fn observe_for_cache(&mut self, line: &str) {
+        let Some(ref mut cache) = self.cache else {
+            return;
+        };
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            return;
+        };
+        let id = message.get("id");
+        match message.get("method").and_then(Value::as_str) {
+            Some(method) if id.is_none() => {
+                let dropped = cache.invalidate(method, message.get("params"));
+                if dropped > 0 {
+                    debug!("{} invalidated {} cached response(s)", method, dropped);
+                }
+            }
+            Some(_) => {}
+            None => {
+                let key = id.and_then(|id| self.cache_pending.remove(&id.to_string()));
+                if let (Some(key), Some(result)) = (key, message.get("result")) {
+                    cache.insert(key, result.clone(), Instant::now());
+                }
+            }
+        }
+    }
+
+    /// Forget cached responses, e.g. after the backend was replaced
+    fn reset_cache(&mut self) {
+        if let Some(ref mut cache) = self.cache {
+            cache.clear();
+            self.cache_pending.clear();
+            self.cache_session += 1;
+        }
+    }
+
+    /// Write a response served from the cache to the client
+    async fn send_cached(&mut self, user_stdout: &mut Stdout, reply: &str) -> Result<()> {
+        self.log_response(reply, false, None).await;
+        user_stdout.write_all(reply.as_bytes()).await?;
+        user_stdout.flush().await?;
+
+        let mut stats = self.stats.lock().await;
+        stats.total_requests += 1;
+        stats.successful_requests += 1;
+        Ok(())
+    }
+
+    /// Write a proxy-originated message to the client
+    async fn send_to_client<W>(&mut self, user_stdout: &mut W, message: &str) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let line = format!("{}\n", message);
+        self.log_response(&line, true, None).await;
+        user_stdout.write_all(line.as_bytes()).await?;
+        user_stdout.flush().await?;
         Ok(())
     }
 
+    /// Whether every line would come out unchanged, so parsing it can be skipped
+    async fn forwards_verbatim(&self) -> bool {
+        self.fidelity.preserves_unmodified() && self.interceptor_manager.is_empty().await
+    }
+
     /// Process an outgoing message (client -> server) through interceptors
-    async fn process_outgoing(&self, content: &str) -> Result<(String, bool)> {
+    ///
+    /// Returns the line to forward and whether an interceptor changed it.
+    pub async fn process_outgoing(&self, content: &str) -> Result<(String, bool)> {
+        if !self.fidelity.runs_interceptors() || self.forwards_verbatim().await {
+            return Ok((content.to_string(), false));
+        }
+
         // Try to parse as JSON-RPC message
         match serde_json::from_str::<JsonRpcMessage>(content.trim()) {
             Ok(message) => {
@@ -299,6 +807,10 @@ impl StdioHandler {
                             ));
                         }
 
+                        if !result.modified && self.fidelity.preserves_unmodified() {
+                            return Ok((content.to_string(), false));
+                        }
+
                         let modified_content = serde_json::to_string(&result.message)?;
                         Ok((modified_content + "\n", result.modified))
                     }
@@ -316,8 +828,138 @@ impl StdioHandler {
         }
     }
 
+    /// Log, audit and count a client line kept from the backend, and answer
+    /// it if it is a request so the client isn't left waiting
+    async fn refuse(&mut self, user_stdout: &mut Stdout, input: &str, reason: &str) {
+        self.log_request(input, false, None).await;
+        if let Some(Err(e)) = self.audit.as_mut().map(|audit| audit.blocked_line(input)) {
+            self.audit_failed(e).await;
+        }
+        {
+            let mut stats = self.stats.lock().await;
+            stats.failed_requests += 1;
+        }
+        if let Some(reply) = Self::blocked_reply(input, reason) {
+            if let Err(e) = user_stdout.write_all(reply.as_bytes()).await {
+                error!("Failed to write to user stdout: {}", e);
+            }
+            let _ = user_stdout.flush().await;
+        }
+    }
+
+    /// Log and count a client line over the size limit, and answer it if
+    /// its request ID could be read
+    async fn refuse_oversized(&mut self, user_stdout: &mut Stdout, frame: LimitedFrame) {
+        let (id, size) = match frame {
+            LimitedFrame::Rejected { id, size, .. } => (id, size),
+            other => (None, other.size()),
+        };
+        let limit = self.limits.as_ref().map_or(0, |limits| limits.max_outbound);
+        let reason = format!("message of {} bytes exceeds the {}-byte limit", size, limit);
+        warn!("Request refused: {}", reason);
+        self.log_event(LogLevel::Warning, format!("Request refused: {}", reason)).await;
+        {
+            let mut stats = self.stats.lock().await;
+            stats.failed_requests += 1;
+        }
+        if let Some(id) = id {
+            let response = JsonRpcResponse::error(
+                id,
+                JsonRpcError::application_error(BLOCKED_REQUEST_CODE, "Request blocked", reason),
+            );
+            if let Ok(reply) = serde_json::to_string(&response) {
+                if let Err(e) = self.send_to_client(user_stdout, &reply).await {
+                    error!("Failed to write to user stdout: {}", e);
+                }
+            }
+        }
+    }
+
+    /// The server line to forward for a frame read through the size limit
+    ///
+    /// A truncated frame is forwarded with `_meta.truncated` set. A rejected
+    /// one is replaced by an error response when its ID could be read, so
+    /// the client isn't left waiting, and dropped otherwise.
+    async fn limit_incoming(&mut self, frame: LimitedFrame) -> Option<String> {
+        let limit = self.limits.as_ref().map_or(0, |limits| limits.max_inbound);
+        match frame {
+            LimitedFrame::Complete(output) => Some(output),
+            LimitedFrame::Truncated { frame, size } => {
+                let reason = format!("message of {} bytes cut to the {}-byte limit", size, limit);
+                warn!("Truncated server {}", reason);
+                self.log_event(LogLevel::Warning, format!("Truncated server {}", reason)).await;
+                match serde_json::from_str::<JsonRpcMessage>(&frame) {
+                    Ok(mut message) => {
+                        limits::mark_truncated(&mut message, size, limit);
+                        serde_json::to_string(&message).ok().map(|line| line + "\n")
+                    }
+                    Err(_) => Some(frame + "\n"),
+                }
+            }
+            LimitedFrame::Rejected { id, size, reason, .. } => {
+                warn!("Dropped server {}", reason);
+                self.log_event(LogLevel::Warning, format!("Dropped server {}", reason)).await;
+                {
+                    let mut stats = self.stats.lock().await;
+                    stats.failed_requests += 1;
+                }
+                let response = JsonRpcResponse::error(
+                    id?,
+                    JsonRpcError::new(
+                        -32603,
+                        format!("Response dropped: {}", reason),
+                        Some(json!({ "size": size, "limit": limit })),
+                    ),
+                );
+                serde_json::to_string(&response).ok().map(|line| line + "\n")
+            }
+        }
+    }
+
+    /// Why the client gate refuses a line, if it does
+    ///
+    /// Nothing from a client that failed authentication gets through. An
+    /// authenticated client's requests are put to the gate; its notifications
+    /// and responses pass, as they ask the backend to do no work. Lines the
+    /// gate can't read as one JSON-RPC message, batches included, are refused.
+    fn refusal(&self, line: &str) -> Option<String> {
+        let (gate, identity) = self.gate.as_ref()?;
+        let identity = match identity {
+            Ok(identity) => identity,
+            Err(e) => return Some(e.to_string()),
+        };
+        if line.trim().is_empty() {
+            return None;
+        }
+        match serde_json::from_str(line.trim()) {
+            Ok(JsonRpcMessage::Request(request)) => gate.authorize(identity, &request, Instant::now()).err(),
+            Ok(_) => None,
+            Err(_) => Some("Not a single JSON-RPC message; the client gate can't check it".to_string()),
+        }
+    }
+
+    /// Build a JSON-RPC error response for a request that was blocked
+    fn blocked_reply(content: &str, reason: &str) -> Option<String> {
+        match serde_json::from_str::<JsonRpcMessage>(content.trim()) {
+            Ok(JsonRpcMessage::Request(request)) => {
+                let response = JsonRpcResponse::error(
+                    request.id,
+                    JsonRpcError::application_error(BLOCKED_REQUEST_CODE, "Request blocked", reason),
+                );
+                serde_json::to_string(&response).ok().map(|s| s + "\n")
+            }
+            _ => None,
+        }
+    }
+
     /// Process an incoming message (server -> client) through interceptors
-    async fn process_incoming(&self, content: &str) -> Result<(String, bool)> {
+    ///
+    /// Returns the line to forward and whether an interceptor changed it.
+    pub async fn process_incoming(&self, content: &str) -> Result<(String, bool)> {
+        if !self.fidelity.runs_interceptors() || self.forwards_verbatim().await {
+            return Ok((content.to_string(), false));
+        }
+
         // Try to parse as JSON-RPC message
         match serde_json::from_str::<JsonRpcMessage>(content.trim()) {
             Ok(message) => {
@@ -336,6 +978,10 @@ impl StdioHandler {
                             ));
                         }
 
+                        if !result.modified && self.fidelity.preserves_unmodified() {
+                            return Ok((content.to_string(), false));
+                        }
+
                         let modified_content = serde_json::to_string(&result.message)?;
                         Ok((modified_content + "\n", result.modified))
                     }
@@ -353,7 +999,10 @@ impl StdioHandler {
         }
     }
 
-    async fn log_request(&mut self, content: &str, modified: bool) {
+    /// Log an outgoing message; `original` is the text before interceptors ran
+    async fn log_request(&mut self, content: &str, modified: bool, original: Option<&str>) {
+        let content = self.redactor.redact_text(content);
+        let content = content.as_str();
         let prefix = if modified { "→ [MODIFIED]" } else { "→" };
         let log_entry = LogEntry::new(
             LogLevel::Request,
@@ -366,11 +1015,19 @@ impl StdioHandler {
                 warn!("Failed to send log entry: {}", e);
             }
         }
+        let original = original.filter(|_| modified).map(|o| self.redactor.redact_text(o));
+        self.send_observed(TrafficDirection::ClientToServer, content, modified, original.as_deref()).await;
 
-        debug!("Request{}: {}", if modified { " (modified)" } else { "" }, content.trim());
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            let (method, request_id) = line_ids(content);
+            debug!(method, request_id, "Request{}: {}", if modified { " (modified)" } else { "" }, content.trim());
+        }
     }
 
-    async fn log_response(&mut self, content: &str, modified: bool) {
+    /// Log an incoming message; `original` is the text before interceptors ran
+    async fn log_response(&mut self, content: &str, modified: bool, original: Option<&str>) {
+        let content = self.redactor.redact_text(content);
+        let content = content.as_str();
         let prefix = if modified { "← [MODIFIED]" } else { "←" };
         let log_entry = LogEntry::new(
             LogLevel::Response,
@@ -383,11 +1040,68 @@ impl StdioHandler {
                 warn!("Failed to send log entry: {}", e);
             }
         }
+        let original = original.filter(|_| modified).map(|o| self.redactor.redact_text(o));
+        self.send_observed(TrafficDirection::ServerToClient, content, modified, original.as_deref()).await;
+
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            let (method, request_id) = line_ids(content);
+            debug!(method, request_id, "Response{}: {}", if modified { " (modified)" } else { "" }, content.trim());
+        }
+    }
+
+    /// Send a typed observation of a (redacted) JSON-RPC message to the monitor
+    async fn send_observed(
+        &self,
+        direction: TrafficDirection,
+        content: &str,
+        modified: bool,
+        original: Option<&str>,
+    ) {
+        if self.ipc_client.is_none() && self.metrics.is_none() {
+            return;
+        }
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(content.trim()) else {
+            return;
+        };
+
+        let mut observed = ObservedMessage::new(self.proxy_id.clone(), direction, payload);
+        if let Some(ref metrics) = self.metrics {
+            metrics.lock().await.observe(&observed);
+        }
+        let Some(ref client) = self.ipc_client else {
+            return;
+        };
+        observed.modified = modified;
+        observed.original = original.and_then(|o| serde_json::from_str(o.trim()).ok());
+        if let Err(e) = client.send(IpcMessage::MessageObserved(observed)).await {
+            warn!("Failed to send observed message: {}", e);
+        }
+    }
+
+    async fn count_transport_error(&self) {
+        if let Some(ref metrics) = self.metrics {
+            metrics.lock().await.transport_error();
+        }
+    }
+
+    async fn log_event(&self, level: LogLevel, message: String) {
+        if let Some(ref client) = self.ipc_client {
+            let log_entry = LogEntry::new(level, message, self.proxy_id.clone());
+            if let Err(e) = client.send(IpcMessage::LogEntry(log_entry)).await {
+                warn!("Failed to send log entry: {}", e);
+            }
+        }
+    }
 
-        debug!("Response{}: {}", if modified { " (modified)" } else { "" }, content.trim());
+    /// Report a tool call that could not be written to the audit log
+    async fn audit_failed(&self, e: anyhow::Error) {
+        error!("Failed to write audit log: {:#}", e);
+        self.log_event(LogLevel::Error, format!("Failed to write audit log: {:#}", e)).await;
     }
 
     async fn log_error(&mut self, content: &str) {
+        let content = self.redactor.redact_text(content);
+        let content = content.as_str();
         let log_entry = LogEntry::new(
             LogLevel::Error,
             format!("stderr: {}", content.trim()),
@@ -433,3 +1147,156 @@ impl StdioHandler {
         }
     }
 }
+
+/// Read one line, through `limiter` when there is one
+///
+/// Lines that come through whole keep their newline, as `read_line` leaves it.
+async fn read_line<R>(reader: &mut R, limiter: Option<&mut FrameLimiter>) -> std::io::Result<Option<LimitedFrame>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let Some(limiter) = limiter else {
+        let mut line = String::new();
+        return match reader.read_line(&mut line).await? {
+            0 => Ok(None),
+            _ => Ok(Some(LimitedFrame::Complete(line))),
+        };
+    };
+    Ok(limits::read_frame(reader, limiter).await?.map(|frame| match frame {
+        LimitedFrame::Complete(line) => LimitedFrame::Complete(line + "\n"),
+        other => other,
+    }))
+}
+
+/// Method and request ID of a JSON-RPC line, for structured logs
+fn line_ids(line: &str) -> (Option<String>, Option<String>) {
+    let Ok(message) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+        return (None, None);
+    };
+    let method = message.get("method").and_then(|m| m.as_str()).map(str::to_string);
+    let request_id = message.get("id").filter(|id| !id.is_null()).map(|id| match id.as_str() {
+        Some(id) => id.to_string(),
+        None => id.to_string(),
+    });
+    (method, request_id)
+}
+
+/// Flood key of a line that holds a JSON-RPC notification
+fn notification_key(line: &str) -> Option<String> {
+    if !line.contains("\"method\"") {
+        return None;
+    }
+    let message: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    if message.get("id").is_some() {
+        return None;
+    }
+    Some(flood_key(message.get("method")?.as_str()?, message.get("params")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthConfig;
+
+    async fn handler() -> StdioHandler {
+        StdioHandler::with_interceptors(
+            ProxyId::new(),
+            Arc::new(Mutex::new(ProxyStats::default())),
+            None,
+            Arc::new(InterceptorManager::new()),
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn gated(identity: Result<Identity, AuthError>) -> StdioHandler {
+        let config: AuthConfig = toml::from_str(
+            r#"
+            [[identity]]
+            name = "ci"
+            uids = [1001]
+            [identity.tools]
+            allow = ["search_*"]
+            "#,
+        )
+        .unwrap();
+        let mut handler = handler().await;
+        handler.gate = Some((ClientGate::from_config(config), identity));
+        handler
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_client_gets_nothing_through() {
+        let handler = gated(Err(AuthError::UidNotAllowed(0))).await;
+        for line in [
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            r#"{"jsonrpc":"2.0","id":7,"result":{}}"#,
+            r#"[{"jsonrpc":"2.0","id":2,"method":"tools/call"}]"#,
+            "not json\n",
+        ] {
+            let reason = handler.refusal(line).unwrap_or_else(|| panic!("forwarded {}", line));
+            assert!(reason.contains("uid 0 is not allowed"), "{}", reason);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticated_client_requests_are_checked() {
+        let handler = gated(Ok(Identity { name: "ci".to_string() })).await;
+        let call = |tool: &str| {
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{{"name":"{}"}}}}"#,
+                tool
+            )
+        };
+        assert_eq!(handler.refusal(&call("search_code")), None);
+        assert!(handler.refusal(&call("delete_repo")).is_some());
+
+        // Notifications and responses pass; batches and junk can't be checked
+        assert_eq!(handler.refusal(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#), None);
+        assert_eq!(handler.refusal(r#"{"jsonrpc":"2.0","id":7,"result":{}}"#), None);
+        assert!(handler.refusal(&format!("[{}]", call("search_code"))).is_some());
+        assert!(handler.refusal("not json\n").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_old_backend_answers_during_swap() {
+        let mut handler = handler().await;
+        handler.session.observe_outgoing(r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#);
+        handler.session.observe_incoming(r#"{"jsonrpc":"2.0","id":0,"result":{"capabilities":{}}}"#);
+        handler.session.observe_outgoing(r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{}}"#);
+
+        // The old backend answers the pending call while the new one is still starting
+        let mut child = crate::proxy::spawn_stdio_server(
+            r#"sleep 0.1; echo '{"jsonrpc":"2.0","id":1,"result":{}}'; sleep 30"#,
+            true,
+        )
+        .unwrap();
+        let mut child_stdout = BufReader::new(child.stdout.take().unwrap());
+        let replacement = TransportConfig::Stdio {
+            command: r#"read line; sleep 1; echo '{"jsonrpc":"2.0","id":"proxy-swap-initialize","result":{"capabilities":{}}}'; cat >/dev/null"#
+                .to_string(),
+            use_shell: true,
+        };
+
+        let mut client_out = Vec::new();
+        handler
+            .swap_backend(
+                SwapRequest::Replace(replacement),
+                &mut child,
+                &mut child_stdout,
+                None,
+                &mut BufReader::new(tokio::io::empty()),
+                None,
+                &mut client_out,
+                &mut VecDeque::new(),
+            )
+            .await
+            .unwrap();
+        child.kill().await.unwrap();
+
+        let output = String::from_utf8(client_out).unwrap();
+        assert_eq!(output.lines().next(), Some(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#));
+        assert!(!output.contains("Backend was replaced"), "{}", output);
+    }
+}
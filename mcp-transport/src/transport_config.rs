@@ -1,7 +1,11 @@
 use anyhow::{anyhow, Result};
 use mcp_common::TransportType;
+use mcp_core::registry::ServerProfile;
+use mcp_core::transport::AuthConfig;
 use serde::{Deserialize, Serialize};
 
+use crate::aggregator::AggregateConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TransportConfig {
     Stdio {
@@ -16,6 +20,10 @@ pub enum TransportConfig {
         url: String,
         api_key: Option<String>,
     },
+    /// Several backends merged into one stdio-facing server
+    Aggregate {
+        config: AggregateConfig,
+    },
 }
 
 impl TransportConfig {
@@ -24,6 +32,8 @@ impl TransportConfig {
             TransportConfig::Stdio { .. } => TransportType::Stdio,
             TransportConfig::HttpSse { .. } => TransportType::HttpSse,
             TransportConfig::HttpStream { .. } => TransportType::HttpStream,
+            // The client talks to the aggregator over stdio
+            TransportConfig::Aggregate { .. } => TransportType::Stdio,
         }
     }
 
@@ -32,9 +42,87 @@ impl TransportConfig {
             TransportConfig::Stdio { command, .. } => command.clone(),
             TransportConfig::HttpSse { url, .. } => url.clone(),
             TransportConfig::HttpStream { url, .. } => url.clone(),
+            TransportConfig::Aggregate { config } => format!(
+                "aggregate: {}",
+                config
+                    .backends
+                    .iter()
+                    .map(|b| b.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// Equivalent client-side configuration for connecting with `mcp-core`
+    pub fn to_mcp_config(&self) -> Result<mcp_core::TransportConfig> {
+        match self {
+            TransportConfig::Stdio { command, use_shell } => {
+                if *use_shell {
+                    Ok(mcp_core::TransportConfig::stdio("sh", &["-c", command.as_str()]))
+                } else {
+                    let mut parts = command.split_whitespace();
+                    let program = parts
+                        .next()
+                        .ok_or_else(|| anyhow!("Empty stdio command"))?;
+                    Ok(mcp_core::TransportConfig::stdio(
+                        program,
+                        &parts.collect::<Vec<_>>(),
+                    ))
+                }
+            }
+            TransportConfig::HttpSse { url, api_key } => {
+                let mcp_core::TransportConfig::HttpSse(mut config) =
+                    mcp_core::TransportConfig::http_sse(url)?
+                else {
+                    unreachable!("http_sse builds an HttpSse config")
+                };
+                if let Some(key) = api_key {
+                    config = config.auth(AuthConfig::bearer(key));
+                }
+                Ok(mcp_core::TransportConfig::HttpSse(config))
+            }
+            TransportConfig::HttpStream { url, api_key } => {
+                let mcp_core::TransportConfig::HttpStream(mut config) =
+                    mcp_core::TransportConfig::http_stream(url)?
+                else {
+                    unreachable!("http_stream builds an HttpStream config")
+                };
+                if let Some(key) = api_key {
+                    config = config.auth(AuthConfig::bearer(key));
+                }
+                Ok(mcp_core::TransportConfig::HttpStream(config))
+            }
+            TransportConfig::Aggregate { .. } => {
+                Err(anyhow!("Aggregate configurations cannot be nested"))
+            }
         }
     }
 
+    /// Proxy configuration for a named server profile.
+    ///
+    /// The proxy forwards bearer credentials only; other auth schemes are rejected.
+    pub fn from_profile(name: &str, profile: &ServerProfile) -> Result<Self> {
+        let api_key = match &profile.auth {
+            None => None,
+            Some(AuthConfig::Bearer { token }) => Some(token.clone()),
+            Some(_) => {
+                return Err(anyhow!(
+                    "Server profile '{}' uses an auth scheme the proxy cannot forward; use bearer",
+                    name
+                ))
+            }
+        };
+        Self::from_cli_args(
+            &profile.transport,
+            profile.command.clone(),
+            profile.url.clone(),
+            profile.use_shell,
+            api_key,
+        )
+        .map_err(|e| anyhow!("Server profile '{}': {}", name, e))
+    }
+
     pub fn from_cli_args(
         transport: &str,
         command: Option<String>,
@@ -61,8 +149,11 @@ impl TransportConfig {
                 })?;
                 Ok(TransportConfig::HttpStream { url, api_key })
             }
+            "aggregate" => Err(anyhow!(
+                "--config is required for aggregate transport"
+            )),
             _ => Err(anyhow!(
-                "Invalid transport type: {}. Must be one of: stdio, http-sse, http-stream",
+                "Invalid transport type: {}. Must be one of: stdio, http-sse, http-stream, aggregate",
                 transport
             )),
         }
@@ -1,5 +1,5 @@
 use mcp_common::*;
-use mcp_transport::BufferedIpcClient;
+use mcp_transport::{BufferedIpcClient, SpoolConfig};
 use tempfile::tempdir;
 use tokio::time::{sleep, Duration};
 
@@ -260,3 +260,50 @@ async fn test_buffered_client_connection_failure_recovery() {
 
     client.shutdown().await;
 }
+
+#[tokio::test]
+async fn test_spooled_messages_survive_client_restart() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir
+        .path()
+        .join("test.sock")
+        .to_string_lossy()
+        .to_string();
+    let spool = SpoolConfig::on_disk(temp_dir.path().join("monitor.spool"));
+
+    // No monitor yet: messages go to the spool file
+    let client = BufferedIpcClient::with_spool(socket_path.clone(), spool.clone())
+        .await
+        .unwrap();
+    let proxy_id = ProxyId::new();
+    for text in ["Spooled 1", "Spooled 2"] {
+        let message = IpcMessage::LogEntry(LogEntry::new(
+            LogLevel::Info,
+            text.to_string(),
+            proxy_id.clone(),
+        ));
+        client.send(message).await.unwrap();
+    }
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(client.spool_stats().await.pending, 2);
+    client.shutdown().await;
+
+    // A new client picks the spool up and flushes it once the monitor is there
+    let server = IpcServer::bind(&socket_path).await.unwrap();
+    let client = BufferedIpcClient::with_spool(socket_path.clone(), spool)
+        .await
+        .unwrap();
+    let mut server_connection = server.accept().await.unwrap();
+    for expected in ["Spooled 1", "Spooled 2"] {
+        let envelope = server_connection.receive_message().await.unwrap().unwrap();
+        match envelope.message {
+            IpcMessage::LogEntry(entry) => assert_eq!(entry.message, expected),
+            _ => panic!("Expected LogEntry message"),
+        }
+    }
+
+    sleep(Duration::from_millis(100)).await;
+    let stats = client.spool_stats().await;
+    assert_eq!((stats.delivered, stats.pending), (2, 0));
+    client.shutdown().await;
+}
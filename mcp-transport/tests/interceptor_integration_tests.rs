@@ -149,3 +149,126 @@ async fn test_interceptor_manager_stats_tracking() {
     // Check messages by method
     assert_eq!(*stats.messages_by_method.get("tools/list").unwrap(), 5);
 }
+
+#[tokio::test]
+async fn test_instrumentation_ranks_interceptors_by_cost() {
+    use mcp_core::interceptor::MessageDirection;
+    use mcp_core::messages::{JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, RequestId};
+    use mcp_transport::interceptors::{Fault, FaultInjectionInterceptor, FaultRule};
+    use serde_json::json;
+    use std::time::Duration;
+
+    let manager = InterceptorManager::new();
+    manager.add_interceptor(Arc::new(LoggingInterceptor::new(false))).await;
+    manager
+        .add_interceptor(Arc::new(FaultInjectionInterceptor::new(vec![FaultRule::new(
+            "tools/call",
+            Fault::Delay { ms: 5 },
+        )])))
+        .await;
+    assert!(manager.cost_report().await.is_none());
+
+    manager.enable_instrumentation(Some(Duration::from_millis(2))).await;
+    for id in 0..3i64 {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(id),
+            method: "tools/call".to_string(),
+            params: Some(json!({ "name": "echo" })),
+        };
+        manager
+            .process_message(JsonRpcMessage::Request(request), MessageDirection::Outgoing)
+            .await
+            .unwrap();
+        let response = JsonRpcResponse::success(RequestId::from(id), json!({}));
+        manager
+            .process_message(JsonRpcMessage::Response(response), MessageDirection::Incoming)
+            .await
+            .unwrap();
+    }
+
+    let report = manager.disable_instrumentation().await.unwrap();
+    assert_eq!(report.messages, 6);
+    assert_eq!(report.over_budget, 3);
+    assert_eq!(report.interceptors[0].name, "FaultInjectionInterceptor");
+    assert_eq!(report.interceptors[0].calls, 6);
+    assert!(report.interceptors[0].max >= Duration::from_millis(5));
+    assert!(manager.cost_report().await.is_none());
+}
+
+/// Interceptor that only implements the owned `intercept`, like external ones written before
+/// `intercept_in_place` existed
+struct TagParams;
+
+#[async_trait::async_trait]
+impl MessageInterceptor for TagParams {
+    fn name(&self) -> &str {
+        "TagParams"
+    }
+
+    fn priority(&self) -> u32 {
+        5
+    }
+
+    async fn should_intercept(&self, _context: &MessageContext) -> bool {
+        true
+    }
+
+    async fn intercept(&self, context: MessageContext) -> McpResult<InterceptionResult> {
+        use mcp_core::messages::JsonRpcMessage;
+
+        let mut message = context.message;
+        if let JsonRpcMessage::Request(request) = &mut message {
+            request.params = Some(serde_json::json!({ "tagged": true }));
+        }
+        Ok(InterceptionResult::modified(message, "tagged".to_string(), 0.5))
+    }
+
+    async fn get_stats(&self) -> InterceptorStats {
+        InterceptorStats::default()
+    }
+}
+
+#[tokio::test]
+async fn test_owned_and_in_place_interceptors_share_one_message() {
+    use mcp_core::interceptor::MessageDirection;
+    use mcp_core::messages::{JsonRpcMessage, JsonRpcRequest, RequestId};
+    use serde_json::json;
+
+    let manager = InterceptorManager::new();
+    assert!(manager.is_empty().await);
+    manager.add_interceptor(Arc::new(TagParams)).await;
+    manager.add_interceptor(Arc::new(LoggingInterceptor::new(true))).await;
+    manager.add_interceptor(Arc::new(ValidationInterceptor::new(true))).await;
+    manager.add_interceptor(Arc::new(RateLimitInterceptor::new(1, 60))).await;
+
+    let request = |id: i64| JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: RequestId::from(id),
+        method: "tools/list".to_string(),
+        params: None,
+    };
+
+    let result = manager
+        .process_message(JsonRpcMessage::Request(request(1)), MessageDirection::Outgoing)
+        .await
+        .unwrap();
+    assert!(result.modified);
+    assert_eq!(result.reasoning.as_deref(), Some("tagged"));
+    let JsonRpcMessage::Request(forwarded) = result.message else {
+        panic!("expected a request");
+    };
+    assert_eq!(forwarded.params, Some(json!({ "tagged": true })));
+
+    // The rate limiter blocks in place and its reason survives
+    let result = manager
+        .process_message(JsonRpcMessage::Request(request(2)), MessageDirection::Outgoing)
+        .await
+        .unwrap();
+    assert!(result.block);
+    assert!(result.reasoning.unwrap().contains("Rate limit exceeded"));
+
+    let stats = manager.get_stats().await;
+    assert_eq!(stats.total_modifications_made, 1);
+    assert_eq!(stats.total_messages_blocked, 1);
+}
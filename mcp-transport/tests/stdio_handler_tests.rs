@@ -257,6 +257,7 @@ async fn test_stdio_handler_stats_updates() {
         successful_requests: 4,
         failed_requests: 1,
         active_connections: 2,
+        standby_connections: 0,
         uptime: Duration::from_secs(60),
         bytes_transferred: 2048,
     }));
@@ -321,3 +322,59 @@ async fn test_stdio_handler_stats_updates() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_stdio_handler_fidelity_modes() {
+    let line = "{\"id\": 7, \"jsonrpc\": \"2.0\", \"method\": \"tools/list\", \"x-extra\": true}\n";
+
+    let handler = |fidelity: FidelityMode| async move {
+        StdioHandler::new(
+            ProxyId::new(),
+            Arc::new(Mutex::new(ProxyStats::default())),
+            None,
+        )
+        .await
+        .unwrap()
+        .with_fidelity(fidelity)
+    };
+
+    // Re-serializing drops the unknown field and reorders keys
+    let (forwarded, modified) = handler(FidelityMode::Normalize)
+        .await
+        .process_outgoing(line)
+        .await
+        .unwrap();
+    assert!(!modified);
+    assert_ne!(forwarded, line);
+
+    for fidelity in [FidelityMode::Preserve, FidelityMode::Raw] {
+        let handler = handler(fidelity).await;
+        assert_eq!(
+            handler.process_outgoing(line).await.unwrap(),
+            (line.to_string(), false)
+        );
+        assert_eq!(
+            handler.process_incoming(line).await.unwrap(),
+            (line.to_string(), false)
+        );
+    }
+
+    // Messages an interceptor changes are still re-serialized in preserve mode
+    let call = "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\",\"params\":{\"name\":\"echo\",\"arguments\":{\"message\":\"santa\"}}}\n";
+    let (forwarded, modified) = handler(FidelityMode::Preserve)
+        .await
+        .process_outgoing(call)
+        .await
+        .unwrap();
+    assert!(modified);
+    assert!(!forwarded.contains("santa"));
+
+    // Raw mode bypasses interceptors entirely
+    let (forwarded, modified) = handler(FidelityMode::Raw)
+        .await
+        .process_outgoing(call)
+        .await
+        .unwrap();
+    assert!(!modified);
+    assert_eq!(forwarded, call);
+}
@@ -1,22 +1,264 @@
+//! Live feed of what clients, servers and the operator did.
+//!
+//! Typing while the feed has focus filters it. Words match the client, server,
+//! action and payload of an activity, ignoring case; `/pattern/` matches a
+//! regular expression instead. `server:NAME` and `method:TEXT` keep
+//! activities whose server or action contains the text, and `dir:in` or
+//! `dir:out` keeps traffic going one way; each shows as a chip above the list.
+//! `ctrl+f` pauses the feed so it stops scrolling while new activities are
+//! kept, and Enter opens the selected activity as JSON. Esc closes the JSON,
+//! then clears the filter.
+
+use mcp_common::TrafficDirection;
 use ratatui::{
-    layout::Rect,
-    style::{Color, Style},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     Frame,
 };
+use regex::{Regex, RegexBuilder};
 
 use crate::components::ActivityItem;
+use crate::content;
+use crate::theme;
+
+/// How the free text of a filter is matched
+#[derive(Debug, Clone)]
+pub enum TextMatch {
+    /// Lowercased substring
+    Plain(String),
+    Regex(Regex),
+    /// A `/pattern/` that does not compile; nothing matches it
+    Invalid(String),
+}
+
+/// Which activities the feed shows
+#[derive(Debug, Clone, Default)]
+pub struct ActivityFilter {
+    /// Substring of the server, ignoring case
+    pub server: Option<String>,
+    /// Substring of the action, ignoring case
+    pub method: Option<String>,
+    pub direction: Option<TrafficDirection>,
+    pub text: Option<TextMatch>,
+}
+
+impl ActivityFilter {
+    /// Parse a filter line such as `server:db dir:in /timeout|refused/`
+    pub fn parse(line: &str) -> Self {
+        let mut filter = Self::default();
+        let mut words = Vec::new();
+        for term in line.split_whitespace() {
+            if let Some(server) = term.strip_prefix("server:") {
+                filter.server = Some(server.to_lowercase()).filter(|s| !s.is_empty());
+            } else if let Some(method) = term.strip_prefix("method:") {
+                filter.method = Some(method.to_lowercase()).filter(|m| !m.is_empty());
+            } else if let Some(direction) = term.strip_prefix("dir:") {
+                filter.direction = match direction {
+                    "out" => Some(TrafficDirection::ClientToServer),
+                    "in" => Some(TrafficDirection::ServerToClient),
+                    _ => None,
+                };
+            } else {
+                words.push(term);
+            }
+        }
+        let text = words.join(" ");
+        filter.text = match text.strip_prefix('/').and_then(|t| t.strip_suffix('/')) {
+            Some(pattern) => Some(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_or_else(
+                        |_| TextMatch::Invalid(pattern.to_string()),
+                        TextMatch::Regex,
+                    ),
+            ),
+            None if text.is_empty() => None,
+            None => Some(TextMatch::Plain(text.to_lowercase())),
+        };
+        filter
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.server.is_none()
+            && self.method.is_none()
+            && self.direction.is_none()
+            && self.text.is_none()
+    }
+
+    pub fn matches(&self, item: &ActivityItem) -> bool {
+        if self
+            .server
+            .as_ref()
+            .is_some_and(|server| !item.server.to_lowercase().contains(server))
+        {
+            return false;
+        }
+        if self
+            .method
+            .as_ref()
+            .is_some_and(|method| !item.action.to_lowercase().contains(method))
+        {
+            return false;
+        }
+        if self.direction.is_some() && item.direction != self.direction {
+            return false;
+        }
+        let Some(text) = &self.text else {
+            return true;
+        };
+        let detail = item
+            .detail
+            .as_ref()
+            .map(|detail| detail.to_string())
+            .unwrap_or_default();
+        let fields = [&item.client, &item.server, &item.action, &detail];
+        match text {
+            TextMatch::Plain(text) => fields
+                .iter()
+                .any(|field| field.to_lowercase().contains(text)),
+            TextMatch::Regex(regex) => fields.iter().any(|field| regex.is_match(field)),
+            TextMatch::Invalid(_) => false,
+        }
+    }
+
+    /// One chip per active condition
+    fn chips(&self) -> Vec<Span<'static>> {
+        let theme = theme::current();
+        let chip = |text: String| {
+            Span::styled(
+                format!(" {} ", text),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::REVERSED),
+            )
+        };
+        let mut chips = Vec::new();
+        if let Some(server) = &self.server {
+            chips.push(chip(format!("server:{}", server)));
+        }
+        if let Some(method) = &self.method {
+            chips.push(chip(format!("method:{}", method)));
+        }
+        match self.direction {
+            Some(TrafficDirection::ClientToServer) => chips.push(chip("dir:out".to_string())),
+            Some(TrafficDirection::ServerToClient) => chips.push(chip("dir:in".to_string())),
+            None => {}
+        }
+        match &self.text {
+            Some(TextMatch::Plain(text)) => chips.push(chip(format!("\"{}\"", text))),
+            Some(TextMatch::Regex(regex)) => chips.push(chip(format!("/{}/", regex.as_str()))),
+            Some(TextMatch::Invalid(pattern)) => chips.push(Span::styled(
+                format!(" /{}/ invalid ", pattern),
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::REVERSED),
+            )),
+            None => {}
+        }
+        chips
+    }
+}
 
 pub struct ActivityFeed {
     state: ListState,
+    /// Page of the selected activity's detail view
+    detail_page: usize,
+    /// Filter line as typed
+    query: String,
+    filter: ActivityFilter,
+    /// How many activities there were when the feed was paused; only those show
+    paused: Option<usize>,
+    /// Whether the selected activity fills the feed as JSON
+    expanded: bool,
 }
 
 impl ActivityFeed {
     pub fn new() -> Self {
         let mut state = ListState::default();
         state.select(Some(0));
-        Self { state }
+        Self {
+            state,
+            detail_page: 0,
+            query: String::new(),
+            filter: ActivityFilter::default(),
+            paused: None,
+            expanded: false,
+        }
+    }
+
+    /// The filter line as typed
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Stop showing new activities, out of `len` so far, or show them again
+    pub fn toggle_pause(&mut self, len: usize) {
+        self.paused = match self.paused {
+            Some(_) => None,
+            None => Some(len),
+        };
+    }
+
+    /// Account for the oldest `count` activities being dropped
+    pub fn pruned(&mut self, count: usize) {
+        if let Some(paused) = &mut self.paused {
+            *paused = paused.saturating_sub(count);
+        }
+    }
+
+    pub fn input(&mut self, character: char) {
+        self.query.push(character);
+        self.refilter();
+    }
+
+    pub fn backspace(&mut self) -> bool {
+        let changed = self.query.pop().is_some();
+        self.refilter();
+        changed
+    }
+
+    /// Show the selected activity as JSON, or go back to the list
+    pub fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+        self.detail_page = 0;
+    }
+
+    /// Close the JSON view, else clear the filter; false when neither is open
+    pub fn escape(&mut self) -> bool {
+        if self.expanded {
+            self.toggle_expanded();
+        } else if !self.query.is_empty() {
+            self.query.clear();
+            self.refilter();
+        } else {
+            return false;
+        }
+        true
+    }
+
+    fn refilter(&mut self) {
+        self.filter = ActivityFilter::parse(&self.query);
+        self.state.select(Some(0));
+        self.detail_page = 0;
+    }
+
+    /// The activities shown, newest first
+    pub fn visible<'a>(&self, activities: &'a [ActivityItem]) -> Vec<&'a ActivityItem> {
+        let shown = self
+            .paused
+            .map_or(activities.len(), |n| n.min(activities.len()));
+        activities[..shown]
+            .iter()
+            .rev()
+            .filter(|activity| self.filter.matches(activity))
+            .collect()
     }
 
     pub fn focus(&mut self) {
@@ -33,11 +275,23 @@ impl ActivityFeed {
         }
         let next = if idx + 1 >= len { len - 1 } else { idx + 1 };
         self.state.select(Some(next));
+        self.detail_page = 0;
     }
 
     pub fn previous(&mut self) {
         let idx = self.state.selected().unwrap_or(0);
         self.state.select(Some(idx.saturating_sub(1)));
+        self.detail_page = 0;
+    }
+
+    /// Show the next page of the selected activity's details
+    pub fn page_down(&mut self) {
+        // Clamped to the last page when rendered
+        self.detail_page += 1;
+    }
+
+    pub fn page_up(&mut self) {
+        self.detail_page = self.detail_page.saturating_sub(1);
     }
 
     pub fn render(
@@ -47,16 +301,47 @@ impl ActivityFeed {
         activities: &[ActivityItem],
         focused: bool,
     ) {
+        let visible = self.visible(activities);
+        let mut title = "Activity Feed".to_string();
+        if !self.filter.is_empty() {
+            title.push_str(&format!(" {}/{}", visible.len(), activities.len()));
+        }
+        if let Some(paused) = self.paused {
+            title.push_str(&format!(
+                " · PAUSED, {} new (ctrl+f resume)",
+                activities.len().saturating_sub(paused)
+            ));
+        }
+        let selected = self
+            .state
+            .selected()
+            .and_then(|idx| visible.get(idx.min(visible.len().saturating_sub(1))));
+        if let (true, Some(activity)) = (self.expanded, selected) {
+            self.render_json(frame, area, activity, &title, focused);
+            return;
+        }
         let block = Block::default()
-            .title("Activity Feed")
+            .title(title)
             .borders(Borders::ALL)
             .border_style(border_style(focused));
 
-        let items: Vec<ListItem> = activities
-            .iter()
-            .rev()
-            .map(|activity| list_item(activity))
-            .collect();
+        let area = if self.query.is_empty() {
+            area
+        } else {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+                .split(area);
+            let mut chips = vec![Span::styled(
+                "Filter ",
+                Style::default().fg(theme::current().muted),
+            )];
+            chips.extend(self.filter.chips());
+            frame.render_widget(Paragraph::new(Line::from(chips)), chunks[0]);
+            chunks[1]
+        };
+
+        let items: Vec<ListItem> = visible.iter().map(|activity| list_item(activity)).collect();
 
         let mut state = self.state.clone();
         // Ensure selection stays inside bounds after updates.
@@ -65,8 +350,78 @@ impl ActivityFeed {
             state.select(Some(idx.min(max_index)));
         }
 
-        frame.render_stateful_widget(List::new(items).block(block), area, &mut state);
+        // Split off a detail view when the selected activity carries a payload
+        let detail = state
+            .selected()
+            .and_then(|idx| visible.get(idx))
+            .and_then(|activity| activity.detail.as_ref());
+        let (list_area, detail_area) = match detail {
+            Some(_) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+                    .split(area);
+                (chunks[0], Some(chunks[1]))
+            }
+            None => (area, None),
+        };
+
+        frame.render_stateful_widget(List::new(items).block(block), list_area, &mut state);
         self.state = state;
+
+        if let (Some(detail), Some(area)) = (detail, detail_area) {
+            self.render_detail(frame, area, detail);
+        }
+    }
+
+    /// The whole activity as JSON, filling the feed
+    fn render_json(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        activity: &ActivityItem,
+        title: &str,
+        focused: bool,
+    ) {
+        let value = serde_json::to_value(activity).unwrap_or_default();
+        let page = content::paginate(
+            content::render_json(&value, area.width.saturating_sub(2)),
+            self.detail_page,
+            usize::from(area.height.saturating_sub(2)),
+        );
+        self.detail_page = page.index;
+        let block = Block::default()
+            .title(format!(
+                "{} · JSON {}/{} (PgUp/PgDn, Esc back)",
+                title,
+                page.index + 1,
+                page.count
+            ))
+            .borders(Borders::ALL)
+            .border_style(border_style(focused));
+        frame.render_widget(Paragraph::new(page.lines).block(block), area);
+    }
+
+    fn render_detail(&mut self, frame: &mut Frame, area: Rect, detail: &serde_json::Value) {
+        let width = area.width.saturating_sub(2);
+        let height = usize::from(area.height.saturating_sub(2));
+        let page = content::paginate(
+            content::render_payload(detail, width),
+            self.detail_page,
+            height,
+        );
+        self.detail_page = page.index;
+
+        let title = if page.count > 1 {
+            format!("Details {}/{} (PgUp/PgDn)", page.index + 1, page.count)
+        } else {
+            "Details".to_string()
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().muted));
+        frame.render_widget(Paragraph::new(page.lines).block(block), area);
     }
 }
 
@@ -76,13 +431,16 @@ fn list_item(item: &ActivityItem) -> ListItem<'static> {
     let content = vec![Line::from(vec![
         Span::styled(
             format!("[{}] ", timestamp),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme::current().muted),
         ),
         Span::styled(
             format!("{} → {} ", item.client, item.server),
             Style::default(),
         ),
-        Span::styled(item.action.clone(), Style::default().fg(Color::White)),
+        Span::styled(
+            item.action.clone(),
+            Style::default().fg(theme::current().text),
+        ),
         Span::raw(" "),
         Span::styled(format!("[{}]", item.status.label()), status_style),
     ])];
@@ -91,8 +449,79 @@ fn list_item(item: &ActivityItem) -> ListItem<'static> {
 
 fn border_style(focused: bool) -> Style {
     if focused {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(theme::current().accent)
     } else {
         Style::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::ActivityStatus;
+    use chrono::Utc;
+
+    fn activity(server: &str, action: &str, direction: Option<TrafficDirection>) -> ActivityItem {
+        ActivityItem {
+            timestamp: Utc::now(),
+            client: "Client".to_string(),
+            server: server.to_string(),
+            action: action.to_string(),
+            status: ActivityStatus::Success,
+            detail: Some(serde_json::json!({ "error": { "message": "Connection refused" } })),
+            direction,
+        }
+    }
+
+    #[test]
+    fn test_filter_chips_and_search() {
+        let call = activity(
+            "database",
+            "tools/call query",
+            Some(TrafficDirection::ClientToServer),
+        );
+        let filter = ActivityFilter::parse("server:DATA method:tools dir:out");
+        assert!(filter.matches(&call));
+        assert_eq!(filter.chips().len(), 3);
+        assert!(!ActivityFilter::parse("dir:in").matches(&call));
+        assert!(!ActivityFilter::parse("server:github").matches(&call));
+
+        // Text reaches the payload; slashes make it a regular expression
+        assert!(ActivityFilter::parse("refused").matches(&call));
+        assert!(ActivityFilter::parse("/conn\\w+ (refused|reset)/").matches(&call));
+        assert!(!ActivityFilter::parse("/timeout/").matches(&call));
+        let invalid = ActivityFilter::parse("/(unclosed/");
+        assert!(matches!(invalid.text, Some(TextMatch::Invalid(_))));
+        assert!(!invalid.matches(&call));
+        assert!(ActivityFilter::parse("").is_empty());
+    }
+
+    #[test]
+    fn test_pause_freezes_the_feed_until_resumed() {
+        let mut activities = vec![activity("a", "first", None), activity("b", "second", None)];
+        let mut feed = ActivityFeed::new();
+        feed.toggle_pause(activities.len());
+        activities.push(activity("c", "third", None));
+        let actions = |feed: &ActivityFeed, activities: &[ActivityItem]| {
+            feed.visible(activities)
+                .iter()
+                .map(|a| a.action.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(actions(&feed, &activities), vec!["second", "first"]);
+
+        // Dropping the oldest keeps the frozen view on the same activities
+        activities.remove(0);
+        feed.pruned(1);
+        assert_eq!(actions(&feed, &activities), vec!["second"]);
+
+        feed.toggle_pause(activities.len());
+        assert_eq!(actions(&feed, &activities), vec!["third", "second"]);
+
+        feed.input('t');
+        feed.input('h');
+        assert_eq!(actions(&feed, &activities), vec!["third"]);
+        assert!(feed.escape());
+        assert!(!feed.escape());
+    }
+}
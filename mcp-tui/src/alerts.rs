@@ -0,0 +1,407 @@
+//! Alerts for events worth interrupting the operator for.
+//!
+//! The monitor often runs in a background terminal. An [`Alerter`] watches
+//! the events applied to the state for the conditions of the `[alerts]`
+//! configuration section: a backend disconnecting, its error rate crossing a
+//! threshold, a tool call refused by policy or a server asking to sample an
+//! LLM. [`deliver`] then sends each alert as a desktop notification, to a
+//! shell command and to a webhook, as configured.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use mcp_common::{AlertConfig, AlertKind, TrafficDirection};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::components::ServerStatus;
+use crate::state::{AppEvent, AppState};
+
+/// JSON-RPC error code a proxy answers a request refused by policy with
+const BLOCKED_REQUEST_CODE: i64 = -32001;
+
+/// How long a webhook may take to answer
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A condition that fired
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    /// Name of the server the alert is about
+    pub server: String,
+    pub summary: String,
+    pub at: DateTime<Utc>,
+}
+
+impl Alert {
+    pub fn title(&self) -> &'static str {
+        match self.kind {
+            AlertKind::Disconnect => "Backend disconnected",
+            AlertKind::ErrorRate => "Error rate exceeded",
+            AlertKind::DeniedCall => "Tool call denied",
+            AlertKind::Sampling => "Sampling requested",
+        }
+    }
+}
+
+/// Raises alerts from applied events
+#[derive(Debug, Clone, Default)]
+pub struct Alerter {
+    config: AlertConfig,
+    /// Last status of each server, to notice disconnects
+    statuses: HashMap<String, ServerStatus>,
+    /// When each condition last fired for each server
+    fired: HashMap<(AlertKind, String), DateTime<Utc>>,
+}
+
+impl Alerter {
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> &AlertConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: AlertConfig) {
+        self.config = config;
+    }
+
+    /// Alerts raised by `event`, which was just applied to `state`.
+    ///
+    /// A condition that fired for a server stays quiet for that server until
+    /// the cooldown has passed.
+    pub fn check(&mut self, state: &AppState, event: &AppEvent, now: DateTime<Utc>) -> Vec<Alert> {
+        let mut alerts = self.conditions(state, event, now);
+        alerts.retain(|alert| {
+            if !self.config.fires_on(alert.kind) {
+                return false;
+            }
+            let key = (alert.kind, alert.server.clone());
+            let cooldown = chrono::Duration::from_std(self.config.cooldown()).unwrap_or_default();
+            if self
+                .fired
+                .get(&key)
+                .is_some_and(|last| now - *last < cooldown)
+            {
+                return false;
+            }
+            self.fired.insert(key, now);
+            true
+        });
+        alerts
+    }
+
+    fn conditions(&mut self, state: &AppState, event: &AppEvent, now: DateTime<Utc>) -> Vec<Alert> {
+        let alert = |kind, server: &str, summary: String| Alert {
+            kind,
+            server: server.to_string(),
+            summary,
+            at: now,
+        };
+        match event {
+            AppEvent::ServerUpdated { server } => {
+                let previous = self
+                    .statuses
+                    .insert(server.id.clone(), server.status.clone());
+                let was_up = matches!(
+                    previous,
+                    Some(ServerStatus::Running | ServerStatus::Degraded)
+                );
+                let is_down = matches!(server.status, ServerStatus::Stopped | ServerStatus::Error);
+                if !(was_up && is_down) {
+                    return Vec::new();
+                }
+                let summary = format!("{} status: {}", server.name, server.status.label());
+                vec![alert(AlertKind::Disconnect, &server.name, summary)]
+            }
+            AppEvent::RequestTimed {
+                server,
+                error: true,
+                ..
+            } => {
+                let name = state.servers.get(server).map_or(server, |s| &s.name);
+                self.error_rate(state, server, name, now)
+                    .into_iter()
+                    .collect()
+            }
+            AppEvent::ActivityRecorded { activity } => activity
+                .detail
+                .as_ref()
+                .and_then(denial)
+                .map(|reason| alert(AlertKind::DeniedCall, &activity.server, reason))
+                .into_iter()
+                .collect(),
+            // Old traffic, such as an imported session, is history rather than news
+            AppEvent::MessageCaptured { message }
+                if now - message.timestamp < chrono::Duration::minutes(1) =>
+            {
+                // Named like the activity feed names the proxy
+                let proxy = message.proxy_id.0.to_string();
+                let proxy = &proxy[..8];
+                let mut alerts = Vec::new();
+                if message.direction == TrafficDirection::ServerToClient
+                    && message.method.as_deref() == Some("sampling/createMessage")
+                {
+                    let summary = format!("A server behind {} asked to sample an LLM", proxy);
+                    alerts.push(alert(AlertKind::Sampling, proxy, summary));
+                }
+                if let Some(reason) = denial(&message.payload) {
+                    alerts.push(alert(AlertKind::DeniedCall, proxy, reason));
+                }
+                if message.method.is_none() && message.payload.get("error").is_some() {
+                    alerts.extend(self.error_rate(state, proxy, proxy, now));
+                }
+                alerts
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// An alert if `server` failed too many of its recent requests
+    fn error_rate(
+        &self,
+        state: &AppState,
+        server: &str,
+        name: &str,
+        now: DateTime<Utc>,
+    ) -> Option<Alert> {
+        let recent = state.metrics.recent(server, self.config.window());
+        let percent = recent.error_rate() * 100.0;
+        if recent.requests < self.config.min_requests
+            || percent < f64::from(self.config.error_rate_percent)
+        {
+            return None;
+        }
+        Some(Alert {
+            kind: AlertKind::ErrorRate,
+            server: name.to_string(),
+            summary: format!(
+                "{}: {:.0}% of {} requests failed in the last {}s",
+                name,
+                percent,
+                recent.requests,
+                self.config.window().as_secs()
+            ),
+            at: now,
+        })
+    }
+}
+
+/// Why a response refused the request, if a proxy refused it by policy
+fn denial(response: &Value) -> Option<String> {
+    let error = response.get("error")?;
+    if error["code"].as_i64() != Some(BLOCKED_REQUEST_CODE) {
+        return None;
+    }
+    let reason = error["data"].as_str().or(error["message"].as_str());
+    Some(reason.unwrap_or("Request blocked").to_string())
+}
+
+/// Send `alert` everywhere `config` says, in the background
+pub fn deliver(config: &AlertConfig, alert: Alert) {
+    let config = config.clone();
+    tokio::spawn(async move {
+        if config.desktop {
+            if let Err(e) = notify_desktop(&alert).await {
+                warn!("Desktop notification failed: {:#}", e);
+            }
+        }
+        if let Some(command) = &config.command {
+            if let Err(e) = run_command(command, &alert).await {
+                warn!("Alert command failed: {:#}", e);
+            }
+        }
+        if let Some(url) = &config.webhook {
+            if let Err(e) = post_webhook(url, &alert).await {
+                warn!("Alert webhook {} failed: {:#}", url, e);
+            }
+        }
+    });
+}
+
+async fn notify_desktop(alert: &Alert) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {:?} with title {:?}",
+            alert.summary,
+            alert.title()
+        ));
+        command
+    };
+    #[cfg(not(target_os = "macos"))]
+    let command = {
+        let mut command = Command::new("notify-send");
+        command.arg(alert.title()).arg(&alert.summary);
+        command
+    };
+    run(command).await
+}
+
+/// Run `command` through the shell with the alert in its environment
+async fn run_command(command: &str, alert: &Alert) -> Result<()> {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    };
+    let kind = serde_json::to_value(alert.kind)?;
+    shell
+        .env("GENMCP_ALERT", serde_json::to_string(alert)?)
+        .env("GENMCP_ALERT_KIND", kind.as_str().unwrap_or_default())
+        .env("GENMCP_ALERT_SERVER", &alert.server)
+        .env("GENMCP_ALERT_SUMMARY", &alert.summary);
+    run(shell).await
+}
+
+/// Run a child process that must not draw over the TUI
+async fn run(mut command: Command) -> Result<()> {
+    let program = command
+        .as_std()
+        .get_program()
+        .to_string_lossy()
+        .into_owned();
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
+
+async fn post_webhook(url: &str, alert: &Alert) -> Result<()> {
+    reqwest::Client::new()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(alert)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Server;
+    use crate::ui::UI;
+    use mcp_common::{ObservedMessage, ProxyId};
+    use serde_json::json;
+
+    fn apply(state: &mut AppState, event: &AppEvent) {
+        state.apply(&mut UI::new(), event);
+    }
+
+    #[test]
+    fn test_disconnects_fire_once_per_cooldown() {
+        let mut alerter = Alerter::new(AlertConfig::default());
+        let mut state = AppState::default();
+        let now = Utc::now();
+        let mut update = |status, at| {
+            let event = AppEvent::ServerUpdated {
+                server: Server::new("fs", "filesystem", "", status),
+            };
+            apply(&mut state, &event);
+            alerter.check(&state, &event, at)
+        };
+
+        assert!(update(ServerStatus::Stopped, now).is_empty());
+        assert!(update(ServerStatus::Running, now).is_empty());
+        let alerts = update(ServerStatus::Error, now);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::Disconnect);
+        assert_eq!(alerts[0].summary, "filesystem status: Error");
+
+        update(ServerStatus::Running, now);
+        assert!(update(ServerStatus::Stopped, now).is_empty());
+        update(ServerStatus::Running, now);
+        let later = now + chrono::Duration::minutes(2);
+        assert_eq!(update(ServerStatus::Stopped, later).len(), 1);
+    }
+
+    #[test]
+    fn test_error_rate_denials_and_sampling() {
+        let config = AlertConfig {
+            min_requests: 4,
+            error_rate_percent: 50,
+            ..AlertConfig::default()
+        };
+        let mut alerter = Alerter::new(config);
+        let mut state = AppState::default();
+        let now = Utc::now();
+
+        let mut fired = Vec::new();
+        for error in [false, true, false, true] {
+            let event = AppEvent::RequestTimed {
+                server: "db".to_string(),
+                method: "tools/call".to_string(),
+                at: now,
+                latency_ms: 5.0,
+                error,
+            };
+            apply(&mut state, &event);
+            fired.extend(alerter.check(&state, &event, now));
+        }
+        assert_eq!(fired.len(), 1);
+        assert_eq!(
+            fired[0].summary,
+            "db: 50% of 4 requests failed in the last 60s"
+        );
+
+        let proxy = ProxyId::new();
+        let captured = |direction, payload| AppEvent::MessageCaptured {
+            message: ObservedMessage::new(proxy.clone(), direction, payload),
+        };
+        let sampling = captured(
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": 7, "method": "sampling/createMessage"}),
+        );
+        let denied = captured(
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": 1, "error": {
+                "code": -32001,
+                "message": "Request blocked",
+                "data": "Tool 'rm' is denied by policy pattern 'rm'"
+            }}),
+        );
+        let kinds: Vec<AlertKind> = [sampling, denied]
+            .iter()
+            .flat_map(|event| alerter.check(&state, event, now))
+            .map(|alert| alert.kind)
+            .collect();
+        assert_eq!(kinds, [AlertKind::Sampling, AlertKind::DeniedCall]);
+
+        // Captured traffic from long ago raises nothing
+        let mut old = ObservedMessage::new(
+            proxy.clone(),
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": 8, "method": "sampling/createMessage"}),
+        );
+        old.timestamp = now - chrono::Duration::hours(1);
+        let later = now + chrono::Duration::minutes(5);
+        let event = AppEvent::MessageCaptured { message: old };
+        assert!(alerter.check(&state, &event, later).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -6,17 +6,28 @@ use crossterm::{
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
 // MCP Gateway integration
 use mcp_common::types::{ProxySession, SessionId, LogEntry};
+use mcp_common::{
+    DecisionTrace, DecisionTraceLog, EntryKind, MonitorConfig, ObservedMessage, SessionArchive,
+    SessionEntry, TrafficDirection,
+};
 use mcp_core::{McpClient, ServerInfo, ClientConfig, TransportConfig};
+use mcp_core::catalog::{CatalogQuery, CatalogSort};
 
-use crate::components::{ActivityItem, Client, Server};
+use crate::components::{
+    ActivityItem, ActivityStatus, Client, PromptBrowser, PromptStage, Server, ToolForm,
+};
 use crate::events::{Event, EventHandler};
-use crate::ui::{NavigationContext, UI};
+use crate::keymap::Keymap;
+use crate::state::{AppEvent, AppState, EventLog};
+use crate::theme::{self, ColorSupport, Theme};
+use crate::ui::UI;
 
 /// Main application state
 pub struct App {
@@ -24,14 +35,10 @@ pub struct App {
     pub ui: UI,
     /// Event handler for user input
     pub events: EventHandler,
-    /// Connected clients (AI assistants, tools)
-    pub clients: HashMap<String, Client>,
-    /// Available servers (backend services)
-    pub servers: HashMap<String, Server>,
-    /// Activity feed items
-    pub activities: Vec<ActivityItem>,
-    /// Current query input
-    pub query_input: String,
+    /// Clients, servers, activities and the query input, changed only via events
+    pub state: AppState,
+    /// Every event applied to `state`, for replay and undo
+    pub log: EventLog,
     /// Application running state
     pub running: bool,
     /// Last update time
@@ -46,6 +53,27 @@ pub struct App {
     pub activity_log: Vec<LogEntry>,
     /// Connected MCP servers info
     pub mcp_servers: HashMap<String, ServerInfo>,
+    /// Sessions with registry profiles and discovered servers, opened on first use
+    pub connections: HashMap<String, McpClient>,
+    /// How to reach each server found over mDNS, keyed by server id
+    pub discovered: HashMap<String, mcp_core::TransportConfig>,
+    /// Log messages streamed by each connected profile
+    pub log_streams: Vec<(String, tokio::sync::broadcast::Receiver<mcp_core::server_log::LogEntry>)>,
+    /// Server id and URI of each `notifications/resources/updated` received
+    resource_updates: tokio::sync::mpsc::UnboundedReceiver<(String, String)>,
+    /// Handed to the notification handler of each new connection
+    resource_updates_tx: tokio::sync::mpsc::UnboundedSender<(String, String)>,
+    /// Where session exports are written and the sessions browser looks
+    pub sessions_dir: PathBuf,
+    /// Refresh rates, panels, history limits, redaction and theme
+    pub config: MonitorConfig,
+    /// File the configuration came from, reloaded on change or SIGHUP
+    pub config_path: Option<PathBuf>,
+    /// Reloaded configurations, while the application runs
+    config_updates: Option<tokio::sync::watch::Receiver<MonitorConfig>>,
+    /// Watches applied events for the configured alert conditions
+    #[cfg(feature = "alerts")]
+    alerter: crate::alerts::Alerter,
 }
 
 impl App {
@@ -53,16 +81,16 @@ impl App {
     pub async fn new() -> Result<Self> {
         debug!("Initializing MCP TUI application");
 
-        let ui = UI::new();
-        let events = EventHandler::new();
-
-        Ok(Self {
-            ui,
-            events,
-            clients: HashMap::new(),
-            servers: HashMap::new(),
-            activities: Vec::new(),
-            query_input: String::new(),
+        let config = MonitorConfig::load_default().unwrap_or_else(|e| {
+            warn!("Using the default monitor configuration: {:#}", e);
+            MonitorConfig::default()
+        });
+        let (resource_updates_tx, resource_updates) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = Self {
+            ui: UI::new(),
+            events: EventHandler::new(),
+            state: AppState::default(),
+            log: EventLog::new(),
             running: true,
             last_update: Instant::now(),
             
@@ -71,7 +99,54 @@ impl App {
             proxy_sessions: HashMap::new(),
             activity_log: Vec::new(),
             mcp_servers: HashMap::new(),
-        })
+            connections: HashMap::new(),
+            discovered: HashMap::new(),
+            log_streams: Vec::new(),
+            resource_updates,
+            resource_updates_tx,
+            sessions_dir: PathBuf::from("."),
+            config: MonitorConfig::default(),
+            config_path: MonitorConfig::default_path(),
+            config_updates: None,
+            #[cfg(feature = "alerts")]
+            alerter: crate::alerts::Alerter::default(),
+        };
+        app.apply_config(config);
+        Ok(app)
+    }
+
+    /// Use the configuration file at `path` instead of the default one
+    pub fn load_config(&mut self, path: impl Into<PathBuf>) -> Result<()> {
+        let path = path.into();
+        self.apply_config(MonitorConfig::load(&path)?);
+        self.config_path = Some(path);
+        Ok(())
+    }
+
+    /// Apply a new configuration to the running application
+    pub fn apply_config(&mut self, config: MonitorConfig) {
+        let theme = Theme::from_config(&config.theme).unwrap_or_else(|e| {
+            warn!("Using the default theme: {:#}", e);
+            Theme::default()
+        });
+        theme::set(theme.adapt(ColorSupport::detect()));
+
+        let keymap = Keymap::with_overrides(&config.keys).unwrap_or_else(|e| {
+            warn!("Using the default keys: {:#}", e);
+            Keymap::default()
+        });
+        self.events.set_keymap(keymap.clone());
+        self.ui.keymap = keymap;
+        self.ui.configure(&config);
+        #[cfg(feature = "alerts")]
+        self.alerter.set_config(config.alerts.clone());
+        self.config = config;
+    }
+
+    /// Give a freshly replayed UI the configured panels and keys
+    fn reconfigure_ui(&mut self) {
+        self.ui.keymap = self.events.keymap().clone();
+        self.ui.configure(&self.config);
     }
 
     /// Run the main application loop
@@ -85,24 +160,43 @@ impl App {
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
-        // Initialize with sample data for demonstration
-        self.init_sample_data();
+        // Initialize with sample data for demonstration, unless a session or replay was loaded
+        if self.log.is_empty() {
+            self.init_sample_data();
+        }
+        self.load_server_profiles();
+
+        // Pick up configuration edits without a restart
+        if let Some(path) = &self.config_path {
+            self.config_updates = Some(MonitorConfig::watch(path, self.config.clone()));
+        }
 
         // Main event loop
         while self.running {
+            let reloaded = self
+                .config_updates
+                .as_mut()
+                .filter(|updates| updates.has_changed().unwrap_or(false))
+                .map(|updates| updates.borrow_and_update().clone());
+            if let Some(config) = reloaded {
+                self.apply_config(config);
+            }
+
             // Draw the UI
             terminal.draw(|f| {
                 self.ui.draw(
                     f,
-                    &self.clients,
-                    &self.servers,
-                    &self.activities,
-                    &self.query_input,
+                    &self.state.clients,
+                    &self.state.servers,
+                    &self.state.activities,
+                    &self.state.query_input,
+                    self.state.tool_form.as_ref(),
+                    self.state.overlay(),
                 );
             })?;
 
             // Handle events with timeout
-            match crossterm::event::poll(Duration::from_millis(100)) {
+            match crossterm::event::poll(self.config.refresh.redraw()) {
                 Ok(true) => {
                     if let Ok(event) = self.events.next().await {
                         self.handle_event(event).await?;
@@ -116,7 +210,7 @@ impl App {
             }
 
             // Update state periodically
-            if self.last_update.elapsed() > Duration::from_secs(1) {
+            if self.last_update.elapsed() > self.config.refresh.status() {
                 self.update_state().await;
                 self.last_update = Instant::now();
             }
@@ -170,18 +264,283 @@ impl App {
         Ok(())
     }
 
+    /// Apply an event to the state and record it, returning whether anything changed
+    pub fn dispatch(&mut self, event: AppEvent) -> bool {
+        if let AppEvent::Undo = event {
+            if !self.log.can_undo() {
+                return false;
+            }
+            self.log.push(event);
+            (self.state, self.ui) = self.log.replay();
+            self.reconfigure_ui();
+            return true;
+        }
+
+        let changed = self.state.apply(&mut self.ui, &event);
+        if changed {
+            #[cfg(feature = "alerts")]
+            self.raise_alerts(&event);
+            self.log.push(event);
+        }
+        changed
+    }
+
+    /// Send the alerts an applied event raises
+    #[cfg(feature = "alerts")]
+    fn raise_alerts(&mut self, event: &AppEvent) {
+        if !self.alerter.config().is_enabled() {
+            return;
+        }
+        for alert in self.alerter.check(&self.state, event, chrono::Utc::now()) {
+            info!("Alert: {}", alert.summary);
+            crate::alerts::deliver(self.alerter.config(), alert);
+        }
+    }
+
+    /// Rebuild the state from a log written with [`App::save_event_log`]
+    pub fn replay(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        self.log = EventLog::load(path)?;
+        (self.state, self.ui) = self.log.replay();
+        self.reconfigure_ui();
+        Ok(self.log.len())
+    }
+
+    /// Save every event applied so far, for replay with [`App::replay`]
+    pub fn save_event_log(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.log.save(path)
+    }
+
+    fn record_activity(&mut self, server: &str, action: String, status: ActivityStatus) {
+        self.dispatch(AppEvent::ActivityRecorded {
+            activity: ActivityItem {
+                timestamp: chrono::Utc::now(),
+                client: "User".to_string(),
+                server: server.to_string(),
+                action,
+                status,
+                detail: None,
+                direction: None,
+            },
+        });
+    }
+
     /// Handle user input events
     async fn handle_event(&mut self, event: Event) -> Result<()> {
         debug!("Handling event: {:?}", event);
 
-        // Let UI handle navigation first
-        let nav_ctx = NavigationContext {
-            client_len: self.clients.len(),
-            server_len: self.servers.len(),
-            activity_len: self.activities.len(),
-        };
+        // The help shows over everything else; Esc or its own key closes it
+        if event == Event::ToggleHelp || (self.state.help_open && event == Event::Quit) {
+            if self.state.help_open {
+                self.dispatch(AppEvent::HelpClosed);
+            } else {
+                self.dispatch(AppEvent::HelpOpened);
+            }
+            return Ok(());
+        }
+        if self.state.help_open {
+            return Ok(());
+        }
+
+        if event == Event::ToggleSessions {
+            if self.state.session_browser.is_some() {
+                self.dispatch(AppEvent::SessionsClosed);
+            } else {
+                self.open_sessions();
+            }
+            return Ok(());
+        }
+
+        if event == Event::ToggleInspector {
+            if self.state.inspector.is_some() {
+                self.dispatch(AppEvent::InspectorClosed);
+            } else {
+                self.dispatch(AppEvent::SessionsClosed);
+                self.dispatch(AppEvent::ServerLogsClosed);
+                self.dispatch(AppEvent::ResourcesClosed);
+                self.dispatch(AppEvent::PromptsClosed);
+                self.dispatch(AppEvent::StatsClosed);
+                self.dispatch(AppEvent::InspectorOpened);
+            }
+            return Ok(());
+        }
+
+        if event == Event::ToggleServerLogs {
+            if self.state.server_log_view.is_some() {
+                self.dispatch(AppEvent::ServerLogsClosed);
+            } else {
+                self.dispatch(AppEvent::InspectorClosed);
+                self.dispatch(AppEvent::SessionsClosed);
+                self.dispatch(AppEvent::ResourcesClosed);
+                self.dispatch(AppEvent::PromptsClosed);
+                self.dispatch(AppEvent::StatsClosed);
+                self.dispatch(AppEvent::ServerLogsOpened);
+            }
+            return Ok(());
+        }
+
+        if event == Event::ToggleStats {
+            if self.state.stats_panel.is_some() {
+                self.dispatch(AppEvent::StatsClosed);
+            } else {
+                self.dispatch(AppEvent::InspectorClosed);
+                self.dispatch(AppEvent::SessionsClosed);
+                self.dispatch(AppEvent::ServerLogsClosed);
+                self.dispatch(AppEvent::ResourcesClosed);
+                self.dispatch(AppEvent::PromptsClosed);
+                self.dispatch(AppEvent::StatsOpened);
+            }
+            return Ok(());
+        }
+
+        if event == Event::ToggleResources {
+            if self.state.resource_browser.is_some() {
+                self.dispatch(AppEvent::ResourcesClosed);
+            } else {
+                self.open_resources().await;
+            }
+            return Ok(());
+        }
+
+        if event == Event::TogglePrompts {
+            if self.state.prompt_browser.is_some() {
+                self.dispatch(AppEvent::PromptsClosed);
+            } else {
+                self.open_prompts().await;
+            }
+            return Ok(());
+        }
+
+        // The server logs panel takes every key; Enter sends its level to the servers
+        if let Some(view) = &self.state.server_log_view {
+            match event {
+                Event::Quit => {
+                    self.dispatch(AppEvent::ServerLogsClosed);
+                }
+                Event::Enter => {
+                    let level = view.min_level.clone();
+                    self.set_server_log_level(level).await;
+                }
+                event => {
+                    self.dispatch(AppEvent::ServerLogsInput { event });
+                }
+            }
+            return Ok(());
+        }
+
+        // The inspector takes every key while open; Esc on its list closes it
+        if let Some(inspector) = &self.state.inspector {
+            if event == Event::Quit && inspector.at_list() {
+                self.dispatch(AppEvent::InspectorClosed);
+            } else {
+                self.dispatch(AppEvent::InspectorInput { event });
+            }
+            return Ok(());
+        }
+
+        // Likewise for the prompt browser, where the server completes arguments
+        // and fetches the prompt, and the preview is sent on or copied
+        if let Some(browser) = &self.state.prompt_browser {
+            match (browser.stage(), event) {
+                (PromptStage::List, Event::Quit) if browser.filter.is_empty() => {
+                    self.dispatch(AppEvent::PromptsClosed);
+                }
+                (PromptStage::Arguments, Event::Tab) if !browser.has_completions() => {
+                    if let Some((server, params)) = browser.completion_request() {
+                        self.complete_prompt_argument(&server, params).await;
+                    }
+                }
+                (PromptStage::Preview, Event::Enter) => {
+                    if let Some(text) = browser.assembled() {
+                        self.send_prompt(text).await;
+                    }
+                }
+                (PromptStage::Preview, Event::CopyPrompt) => {
+                    if let Some(text) = browser.assembled() {
+                        self.copy_prompt(&text);
+                    }
+                }
+                (_, event) => {
+                    self.dispatch(AppEvent::PromptsInput { event });
+                    let request = self
+                        .state
+                        .prompt_browser
+                        .as_ref()
+                        .and_then(PromptBrowser::request);
+                    if let Some((server, name, arguments)) = request {
+                        self.get_prompt(&server, &name, arguments).await;
+                    }
+                }
+            }
+            return Ok(());
+        }
 
-        if self.ui.handle_navigation(nav_ctx, event.clone()) {
+        // Likewise for the resource browser: Enter reads, ctrl+s (un)subscribes
+        if let Some(browser) = &self.state.resource_browser {
+            let selected = browser
+                .selected(&self.state.resources)
+                .map(|entry| (entry.server.clone(), entry.uri.clone(), entry.subscribed));
+            match (event, selected) {
+                (Event::Quit, _) if browser.filter.is_empty() => {
+                    self.dispatch(AppEvent::ResourcesClosed);
+                }
+                (Event::Enter, Some((server, uri, _))) => self.read_resource(&server, &uri).await,
+                (Event::ToggleSubscription, Some((server, uri, subscribed))) => {
+                    self.subscribe_resource(&server, &uri, !subscribed).await;
+                }
+                (event, _) => {
+                    self.dispatch(AppEvent::ResourcesInput { event });
+                }
+            }
+            return Ok(());
+        }
+
+        // Likewise for the stats panel
+        if self.state.stats_panel.is_some() {
+            if event == Event::Quit {
+                self.dispatch(AppEvent::StatsClosed);
+            } else {
+                self.dispatch(AppEvent::StatsInput { event });
+            }
+            return Ok(());
+        }
+
+        // Likewise for the sessions browser, where Enter on a request replays it
+        if let Some(browser) = &self.state.session_browser {
+            if event == Event::Quit && browser.at_sessions() {
+                self.dispatch(AppEvent::SessionsClosed);
+            } else if let (Event::Enter, Some((method, params))) = (&event, browser.replayable()) {
+                self.replay_request(&method, params).await;
+            } else {
+                self.dispatch(AppEvent::SessionsInput { event });
+            }
+            return Ok(());
+        }
+
+        // An open tool form takes every key but focus changes; Esc cancels it
+        if self.state.tool_form.is_some()
+            && self.ui.get_focus() == crate::components::FocusArea::QueryInput
+            && !matches!(event, Event::FocusNext | Event::FocusPrev)
+        {
+            if event == Event::Quit {
+                self.dispatch(AppEvent::ToolFormClosed);
+                return Ok(());
+            }
+            self.dispatch(AppEvent::ToolFormInput { event });
+            let submitted = self.state.tool_form.as_ref().and_then(|form| {
+                let arguments = form.submission()?.clone();
+                Some((form.server.clone(), form.tool.clone(), arguments))
+            });
+            if let Some((server, tool, arguments)) = submitted {
+                self.dispatch(AppEvent::ToolFormClosed);
+                self.call_tool(&server, &tool, arguments).await;
+            }
+            return Ok(());
+        }
+
+        // Let UI handle navigation first
+        if self.dispatch(AppEvent::Navigated {
+            event: event.clone(),
+        }) {
             return Ok(());
         }
 
@@ -190,49 +549,66 @@ impl App {
             Event::Quit => {
                 self.running = false;
             }
+            Event::Undo => {
+                self.dispatch(AppEvent::Undo);
+            }
             Event::Input(character) => {
                 if self.ui.get_focus() == crate::components::FocusArea::QueryInput {
-                    self.query_input.push(character);
+                    let mut query = self.state.query_input.clone();
+                    query.push(character);
+                    self.dispatch(AppEvent::QueryChanged { query });
                 }
             }
             Event::Backspace => {
                 if self.ui.get_focus() == crate::components::FocusArea::QueryInput {
-                    self.query_input.pop();
+                    let mut query = self.state.query_input.clone();
+                    query.pop();
+                    self.dispatch(AppEvent::QueryChanged { query });
                 }
             }
             Event::Enter => {
                 match self.ui.get_focus() {
                     crate::components::FocusArea::QueryInput => {
-                        if !self.query_input.is_empty() {
+                        if let Some((server, tool)) = self.find_tool(&self.state.query_input) {
+                            self.dispatch(AppEvent::QueryChanged {
+                                query: String::new(),
+                            });
+                            if ToolForm::is_structured(&tool) {
+                                self.dispatch(AppEvent::ToolFormOpened { server, tool });
+                            } else {
+                                let name = tool["name"].as_str().unwrap_or_default().to_string();
+                                self.call_tool(&server, &name, serde_json::json!({})).await;
+                            }
+                        } else if !self.state.query_input.is_empty() {
                             self.process_query().await;
-                            self.query_input.clear();
+                            self.dispatch(AppEvent::QueryChanged {
+                                query: String::new(),
+                            });
                         }
                     }
+                    crate::components::FocusArea::Servers => self.list_tools().await,
                     crate::components::FocusArea::QuickAccess => {
-                        if let Some(message) = self.ui.quick_access.execute_selected_action() {
+                        let action = self.ui.quick_access.execute_selected_action();
+                        if action.as_deref() == Some("discover_servers") {
+                            self.discover_servers().await;
+                        } else if action.as_deref() == Some("export_session") {
+                            self.export_session_to_default_path();
+                        } else if action.as_deref() == Some("browse_sessions") {
+                            self.open_sessions();
+                        } else if action.as_deref() == Some("inspect_traffic") {
+                            self.dispatch(AppEvent::InspectorOpened);
+                        } else if action.as_deref() == Some("server_logs") {
+                            self.dispatch(AppEvent::ServerLogsOpened);
+                        } else if action.as_deref() == Some("clear_activity") {
+                            self.dispatch(AppEvent::ActivitiesCleared);
+                        } else if let Some(message) = action {
                             // Add the action result to activity feed
-                            let activity = crate::components::ActivityItem {
-                                timestamp: chrono::Utc::now(),
-                                client: "User".to_string(),
-                                server: "System".to_string(),
-                                action: message,
-                                status: crate::components::ActivityStatus::Success,
-                            };
-                            self.activities.push(activity);
+                            self.record_activity("System", message, ActivityStatus::Success);
                         }
                     }
                     _ => {}
                 }
             }
-            Event::Tab => {
-                self.ui.cycle_focus();
-            }
-            Event::FocusNext => {
-                self.ui.focus_next();
-            }
-            Event::FocusPrev => {
-                self.ui.focus_prev();
-            }
             _ => {}
         }
 
@@ -241,44 +617,339 @@ impl App {
 
     /// Process user query from input
     async fn process_query(&mut self) {
-        let query = self.query_input.clone();
+        let query = self.state.query_input.clone();
         info!("Processing query: {}", query);
 
         // Add to activity feed
-        let activity = ActivityItem {
-            timestamp: chrono::Utc::now(),
-            client: "User".to_string(),
-            server: "System".to_string(),
-            action: format!("Query: {}", query),
-            status: crate::components::ActivityStatus::Processing,
-        };
-
-        self.activities.push(activity);
+        self.record_activity(
+            "System",
+            format!("Query: {}", query),
+            ActivityStatus::Processing,
+        );
 
         // Process query (placeholder for actual LLM integration)
         // TODO: Integrate with mcp-llm for natural language processing
     }
 
+    /// A listed tool named by the query, as `tool` or `server/tool`
+    fn find_tool(&self, query: &str) -> Option<(String, serde_json::Value)> {
+        let query = query.trim();
+        let (server_name, tool_name) = match query.split_once('/') {
+            Some((server, tool)) => (Some(server), tool),
+            None => (None, query),
+        };
+
+        // When several servers offer the tool, the most used one wins
+        let query = CatalogQuery::new().text(tool_name).sort(CatalogSort::Popularity);
+        self.state
+            .catalog
+            .query(&query)
+            .entries
+            .into_iter()
+            .filter(|entry| entry.name() == tool_name)
+            .find(|entry| {
+                let name = self.state.servers.get(&entry.server).map(|s| s.name.as_str());
+                server_name.is_none_or(|wanted| Some(wanted) == name || wanted == entry.server)
+            })
+            .map(|entry| (entry.server.clone(), entry.tool.clone()))
+    }
+
+    /// The session for a registry profile or discovered server, connecting on first use
+    async fn connection(&mut self, server: &str) -> Result<&mut McpClient> {
+        if !self.connections.contains_key(server) {
+            let (name, config) = if let Some(name) = server.strip_prefix("profile:") {
+                (name, mcp_core::registry::ServerRegistry::load_default()?.resolve(name)?)
+            } else if let Some(config) = self.discovered.get(server) {
+                (server.strip_prefix("mdns:").unwrap_or(server), config.clone())
+            } else {
+                return Err(anyhow!("Only server registry profiles and discovered servers can be connected"));
+            };
+            let mut client = mcp_core::client::McpClientBuilder::new()
+                .transport(config)
+                .quirks(mcp_core::quirks::QuirkDatabase::load_default()?)
+                .profile(name)
+                .build()
+                .await?;
+            client
+                .connect(mcp_core::messages::Implementation {
+                    name: "mcp-tui".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    metadata: HashMap::new(),
+                })
+                .await?;
+            self.log_streams.push((server.to_string(), client.server_logs()));
+            let updates = self.resource_updates_tx.clone();
+            let id = server.to_string();
+            client.notifications().on(
+                "notifications/resources/updated",
+                move |n: mcp_core::messages::ResourceUpdatedNotification| {
+                    updates.send((id.clone(), n.uri)).ok();
+                    async { Ok(()) }
+                },
+            );
+            self.connections.insert(server.to_string(), client);
+
+            if let Some(mut connected) = self.state.servers.get(server).cloned() {
+                connected.status = crate::components::ServerStatus::Running;
+                connected.last_activity = chrono::Utc::now();
+                self.dispatch(AppEvent::ServerUpdated { server: connected });
+            }
+        }
+        Ok(self
+            .connections
+            .get_mut(server)
+            .expect("connection opened above"))
+    }
+
+    /// Send a request to `server`, timing it for the stats panel
+    async fn request(
+        &mut self,
+        server: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<mcp_core::messages::JsonRpcResponse> {
+        let client = self.connection(server).await?;
+        let started = Instant::now();
+        let response = client.send_request(method, params).await;
+        let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let error = match &response {
+            Ok(response) => {
+                response.error.is_some()
+                    || response
+                        .result
+                        .as_ref()
+                        .and_then(|result| result["isError"].as_bool())
+                        .unwrap_or(false)
+            }
+            Err(_) => true,
+        };
+        self.dispatch(AppEvent::RequestTimed {
+            server: server.to_string(),
+            method: method.to_string(),
+            at: chrono::Utc::now(),
+            latency_ms,
+            error,
+        });
+        Ok(response?)
+    }
+
+    /// List the selected server's tools so the query input can open their forms
+    async fn list_tools(&mut self) {
+        let Some(server) = self
+            .ui
+            .servers_panel
+            .selected(&self.state.servers)
+            .map(|server| (server.id.clone(), server.name.clone()))
+        else {
+            return;
+        };
+        let (id, name) = server;
+
+        let listed: Result<Vec<serde_json::Value>> = async {
+            let mut tools = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let params = match &cursor {
+                    Some(cursor) => serde_json::json!({ "cursor": cursor }),
+                    None => serde_json::json!({}),
+                };
+                let response = self.request(&id, "tools/list", params).await?;
+                if let Some(error) = response.error {
+                    return Err(anyhow!("tools/list failed: {}", error.message));
+                }
+                let result = response.result.unwrap_or_default();
+                tools.extend(result["tools"].as_array().cloned().unwrap_or_default());
+                match result["nextCursor"].as_str() {
+                    Some(next) if cursor.as_deref() != Some(next) => {
+                        cursor = Some(next.to_string())
+                    }
+                    _ => return Ok(tools),
+                }
+            }
+        }
+        .await;
+
+        match listed {
+            Ok(tools) => {
+                let names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
+                self.record_activity(
+                    &name,
+                    format!("{} tool(s): {}", names.len(), names.join(", ")),
+                    ActivityStatus::Success,
+                );
+                self.dispatch(AppEvent::ToolsListed { server: id, tools });
+            }
+            Err(e) => {
+                warn!("Failed to list tools of {}: {}", name, e);
+                self.record_activity(
+                    &name,
+                    format!("tools/list failed: {}", e),
+                    ActivityStatus::Failed,
+                );
+            }
+        }
+    }
+
+    /// Send `tools/call` and show the result in the activity feed
+    async fn call_tool(&mut self, server: &str, tool: &str, arguments: serde_json::Value) {
+        let name = self
+            .state
+            .servers
+            .get(server)
+            .map_or_else(|| server.to_string(), |s| s.name.clone());
+        let params = serde_json::json!({ "name": tool, "arguments": arguments });
+        let started = Instant::now();
+        let response = self.request(server, "tools/call", params).await;
+
+        let (status, detail) = match response {
+            Ok(response) => {
+                let failed = response.error.is_some()
+                    || response
+                        .result
+                        .as_ref()
+                        .and_then(|result| result["isError"].as_bool())
+                        .unwrap_or(false);
+                let status = if failed {
+                    ActivityStatus::Failed
+                } else {
+                    ActivityStatus::Success
+                };
+                self.dispatch(AppEvent::ToolCalled {
+                    server: server.to_string(),
+                    tool: tool.to_string(),
+                    latency_ms: started.elapsed().as_millis() as u64,
+                });
+                (status, serde_json::to_value(&response).ok())
+            }
+            Err(e) => {
+                warn!("tools/call {} on {} failed: {}", tool, name, e);
+                (
+                    ActivityStatus::Failed,
+                    Some(serde_json::json!({ "error": { "message": e.to_string() } })),
+                )
+            }
+        };
+        self.dispatch(AppEvent::ActivityRecorded {
+            activity: ActivityItem {
+                timestamp: chrono::Utc::now(),
+                client: "User".to_string(),
+                server: name,
+                action: format!("{}()", tool),
+                status,
+                detail,
+                direction: Some(TrafficDirection::ClientToServer),
+            },
+        });
+    }
+
+    /// Add the named profiles from the user's server registry to the servers panel
+    fn load_server_profiles(&mut self) {
+        let registry = match mcp_core::registry::ServerRegistry::load_default() {
+            Ok(registry) => registry,
+            Err(e) => {
+                warn!("Failed to load server registry: {}", e);
+                self.record_activity(
+                    "Registry",
+                    format!("Failed to load server profiles: {}", e),
+                    ActivityStatus::Failed,
+                );
+                return;
+            }
+        };
+
+        let profiles: Vec<Server> = registry
+            .profiles()
+            .map(|(name, profile)| {
+                let description = match &profile.description {
+                    Some(description) => description.clone(),
+                    None => format!("{} ({})", profile.target(), profile.transport),
+                };
+                Server::new(
+                    format!("profile:{}", name),
+                    name,
+                    description,
+                    crate::components::ServerStatus::Stopped,
+                )
+            })
+            .filter(|server| !self.state.servers.contains_key(&server.id))
+            .collect();
+        for server in profiles {
+            self.dispatch(AppEvent::ServerUpdated { server });
+        }
+    }
+
+    /// Browse the local network and add mDNS-advertised servers to the servers panel
+    #[cfg(feature = "discovery")]
+    async fn discover_servers(&mut self) {
+        let (action, status) =
+            match mcp_core::discovery::browse(Duration::from_secs(3)).await {
+                Ok(found) => {
+                    let count = found.len();
+                    for server in found {
+                        let id = format!("mdns:{}", server.name);
+                        match server.to_transport_config() {
+                            Ok(config) => {
+                                self.discovered.insert(id.clone(), config);
+                            }
+                            Err(e) => warn!("Cannot connect to {}: {}", server.name, e),
+                        }
+                        if !self.state.servers.contains_key(&id) {
+                            self.dispatch(AppEvent::ServerUpdated {
+                                server: Server::new(
+                                    id,
+                                    server.name.clone(),
+                                    format!("{} ({})", server.url(), server.transport),
+                                    crate::components::ServerStatus::Stopped,
+                                ),
+                            });
+                        }
+                    }
+                    (
+                        format!("Discovered {} server(s) via mDNS", count),
+                        crate::components::ActivityStatus::Success,
+                    )
+                }
+                Err(e) => {
+                    warn!("mDNS discovery failed: {}", e);
+                    (
+                        format!("Discovery failed: {}", e),
+                        crate::components::ActivityStatus::Failed,
+                    )
+                }
+            };
+
+        self.record_activity("mDNS", action, status);
+    }
+
+    #[cfg(not(feature = "discovery"))]
+    async fn discover_servers(&mut self) {
+        self.record_activity(
+            "mDNS",
+            "Discovery support not compiled in (enable 'discovery' feature)".to_string(),
+            ActivityStatus::Failed,
+        );
+    }
+
     /// Update application state
     async fn update_state(&mut self) {
         // Sync with MCP gateway state
-        if let Some(client) = &self.gateway_client {
+        if let Some(client) = self.gateway_client.clone() {
             // Check for new activity log entries
             match client.get_activity_log().await {
                 Ok(new_entries) => {
                     // Only add entries that are newer than our latest activity
-                    if let Some(latest_activity) = self.activities.last() {
+                    if let Some(latest) = self.state.activities.last().map(|a| a.timestamp) {
                         for entry in new_entries {
-                            if entry.timestamp > latest_activity.timestamp {
+                            if entry.timestamp > latest {
                                 let activity = self.log_entry_to_activity(&entry);
-                                self.activities.push(activity);
+                                self.dispatch(AppEvent::ActivityRecorded { activity });
                             }
                         }
                     } else {
                         // No existing activities, add all recent entries
                         for entry in new_entries.into_iter().take(20) {
                             let activity = self.log_entry_to_activity(&entry);
-                            self.activities.push(activity);
+                            self.dispatch(AppEvent::ActivityRecorded { activity });
                         }
                     }
                 }
@@ -289,7 +960,7 @@ impl App {
             match client.list_sessions().await {
                 Ok(sessions) => {
                     for session in sessions {
-                        if let Some(client) = self.clients.get_mut(&session.id) {
+                        if let Some(mut client) = self.state.clients.get(&session.id).cloned() {
                             client.status = if session.status == SessionStatus::Active {
                                 crate::components::ClientStatus::Connected
                             } else {
@@ -297,6 +968,7 @@ impl App {
                             };
                             client.requests_sent = session.request_count;
                             client.last_activity = session.last_activity;
+                            self.dispatch(AppEvent::ClientUpdated { client });
                         }
                     }
                 }
@@ -306,7 +978,7 @@ impl App {
             match client.list_servers().await {
                 Ok(servers) => {
                     for server in servers {
-                        if let Some(ui_server) = self.servers.get_mut(&server.id) {
+                        if let Some(mut ui_server) = self.state.servers.get(&server.id).cloned() {
                             ui_server.status = if server.is_healthy {
                                 crate::components::ServerStatus::Running
                             } else {
@@ -314,6 +986,7 @@ impl App {
                             };
                             ui_server.requests_received = server.request_count;
                             ui_server.last_activity = server.last_activity;
+                            self.dispatch(AppEvent::ServerUpdated { server: ui_server });
                         }
                     }
                 }
@@ -321,18 +994,463 @@ impl App {
             }
         }
         
-        // Clean up old activities to prevent memory issues
-        if self.activities.len() > 100 {
-            self.activities.drain(0..50);
+        self.drain_server_logs();
+        while let Ok((server, uri)) = self.resource_updates.try_recv() {
+            self.dispatch(AppEvent::ResourceUpdated { server, uri });
+        }
+
+        // Clean up old history to prevent memory issues
+        let history = self.config.history.clone();
+        if self.state.activities.len() > history.activities {
+            self.dispatch(AppEvent::ActivitiesPruned { count: history.activities / 2 });
+        }
+        if self.state.server_logs.len() > history.server_logs {
+            self.dispatch(AppEvent::ServerLogsPruned { count: history.server_logs / 2 });
+        }
+        if self.state.captured.len() > history.messages {
+            self.dispatch(AppEvent::MessagesPruned { count: history.messages / 2 });
         }
     }
 
+    /// Load a session archive or JSONL recording for offline inspection
+    pub fn import_session(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let archive = SessionArchive::load(path)?;
+        for entry in &archive.entries {
+            self.dispatch(AppEvent::ActivityRecorded {
+                activity: session_entry_to_activity(entry),
+            });
+        }
+        for message in archive.to_observed() {
+            self.capture(message);
+        }
+        Ok(archive.entries.len())
+    }
+
+    /// Keep a protocol message, with the configured fields masked
+    pub fn capture(&mut self, mut message: ObservedMessage) {
+        let rules = &self.config.redaction;
+        rules.redact(&mut message.payload);
+        if let Some(original) = message.original.as_mut() {
+            rules.redact(original);
+        }
+        self.dispatch(AppEvent::MessageCaptured { message });
+    }
+
+    /// Load routing decision traces exported by the LLM layer
+    pub fn import_traces(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let traces = DecisionTraceLog::new(path.as_ref()).load()?;
+        let count = traces.len();
+        for trace in traces {
+            self.record_trace(trace);
+        }
+        Ok(count)
+    }
+
+    /// Show a routing decision in the activity feed, with its explanation and
+    /// the full trace as its detail
+    pub fn record_trace(&mut self, trace: DecisionTrace) {
+        let chosen = trace.chosen.as_deref().unwrap_or("no tool");
+        let status = if trace.is_correct() == Some(false) {
+            ActivityStatus::Failed
+        } else {
+            ActivityStatus::Success
+        };
+        let activity = ActivityItem {
+            timestamp: trace.timestamp,
+            client: "Router".to_string(),
+            server: trace.mode.clone(),
+            action: format!(
+                "{} → {} ({} candidates, {:.1} ms)",
+                trace.method.as_deref().unwrap_or("request"),
+                chosen,
+                trace.candidates.len(),
+                trace.latency_ms
+            ),
+            status,
+            detail: Some(serde_json::json!({
+                "explanation": trace.explain(),
+                "trace": trace,
+            })),
+            direction: None,
+        };
+        self.dispatch(AppEvent::DecisionTraced { trace });
+        self.dispatch(AppEvent::ActivityRecorded { activity });
+    }
+
+    /// Save captured traffic as a session archive
+    pub fn export_session(&self, path: impl AsRef<Path>) -> Result<()> {
+        SessionArchive::new("mcp-tui", env!("CARGO_PKG_VERSION"))
+            .with_observed(&self.state.captured)
+            .save(path)
+    }
+
+    fn export_session_to_default_path(&mut self) {
+        let path = self.sessions_dir.join(format!(
+            "mcp-session-{}.json",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        let (action, status) = match self.export_session(&path) {
+            Ok(()) => (
+                format!(
+                    "Exported {} messages to {}",
+                    self.state.captured.len(),
+                    path.display()
+                ),
+                crate::components::ActivityStatus::Success,
+            ),
+            Err(e) => (
+                format!("Session export failed: {}", e),
+                crate::components::ActivityStatus::Failed,
+            ),
+        };
+        self.record_activity("System", action, status);
+    }
+
+    /// Open the sessions browser on the archives in the sessions directory
+    /// Move the log messages received from connected servers into the state
+    fn drain_server_logs(&mut self) {
+        let mut received = Vec::new();
+        for (server, logs) in &mut self.log_streams {
+            loop {
+                match logs.try_recv() {
+                    Ok(entry) => received.push((server.clone(), entry)),
+                    Err(tokio::sync::broadcast::error::TryRecvError::Lagged(skipped)) => {
+                        warn!("Dropped {} log messages from {}", skipped, server);
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+        for (server, entry) in received {
+            self.dispatch(AppEvent::ServerLogged { server, entry });
+        }
+    }
+
+    /// Ask every connected server to send log messages at `level` and above
+    async fn set_server_log_level(&mut self, level: mcp_core::messages::LogLevel) {
+        let servers: Vec<String> = self.connections.keys().cloned().collect();
+        if servers.is_empty() {
+            self.record_activity(
+                "System",
+                "Set log level: no connected servers".to_string(),
+                ActivityStatus::Failed,
+            );
+        }
+        for server in servers {
+            let Some(client) = self.connections.get_mut(&server) else {
+                continue;
+            };
+            let (action, status) = match client.set_level(level.clone()).await {
+                Ok(()) => (format!("Set log level to {}", level), ActivityStatus::Success),
+                Err(e) => (format!("Set log level failed: {}", e), ActivityStatus::Failed),
+            };
+            self.record_activity(&server, action, status);
+        }
+    }
+
+    fn open_sessions(&mut self) {
+        if self.state.inspector.is_some() {
+            self.dispatch(AppEvent::InspectorClosed);
+        }
+        if self.state.server_log_view.is_some() {
+            self.dispatch(AppEvent::ServerLogsClosed);
+        }
+        if self.state.resource_browser.is_some() {
+            self.dispatch(AppEvent::ResourcesClosed);
+        }
+        if self.state.prompt_browser.is_some() {
+            self.dispatch(AppEvent::PromptsClosed);
+        }
+        if self.state.stats_panel.is_some() {
+            self.dispatch(AppEvent::StatsClosed);
+        }
+        let sessions = crate::session_browser::scan(&self.sessions_dir);
+        self.dispatch(AppEvent::SessionsOpened { sessions });
+    }
+
+    /// Open the resource browser on the resources of every connected server, or
+    /// of the selected server when none is connected yet
+    async fn open_resources(&mut self) {
+        self.dispatch(AppEvent::InspectorClosed);
+        self.dispatch(AppEvent::ServerLogsClosed);
+        self.dispatch(AppEvent::SessionsClosed);
+        self.dispatch(AppEvent::PromptsClosed);
+        self.dispatch(AppEvent::StatsClosed);
+
+        for server in self.listed_servers() {
+            match self.list_all(&server, "resources/list", "resources").await {
+                Ok(resources) => {
+                    self.dispatch(AppEvent::ResourcesListed { server, resources });
+                }
+                Err(e) => {
+                    warn!("Failed to list resources of {}: {}", server, e);
+                    self.record_activity(
+                        &server,
+                        format!("resources/list failed: {}", e),
+                        ActivityStatus::Failed,
+                    );
+                }
+            }
+        }
+        self.dispatch(AppEvent::ResourcesOpened);
+    }
+
+    /// Open the prompt browser on the prompts of every connected server, or of
+    /// the selected server when none is connected yet
+    async fn open_prompts(&mut self) {
+        self.dispatch(AppEvent::InspectorClosed);
+        self.dispatch(AppEvent::ServerLogsClosed);
+        self.dispatch(AppEvent::SessionsClosed);
+        self.dispatch(AppEvent::ResourcesClosed);
+        self.dispatch(AppEvent::StatsClosed);
+
+        for server in self.listed_servers() {
+            match self.list_all(&server, "prompts/list", "prompts").await {
+                Ok(prompts) => {
+                    self.dispatch(AppEvent::PromptsListed { server, prompts });
+                }
+                Err(e) => {
+                    warn!("Failed to list prompts of {}: {}", server, e);
+                    self.record_activity(
+                        &server,
+                        format!("prompts/list failed: {}", e),
+                        ActivityStatus::Failed,
+                    );
+                }
+            }
+        }
+        self.dispatch(AppEvent::PromptsOpened);
+    }
+
+    /// Connected servers, or the selected server when none is connected yet
+    fn listed_servers(&self) -> Vec<String> {
+        let mut servers: Vec<String> = self.connections.keys().cloned().collect();
+        if servers.is_empty() {
+            servers.extend(
+                self.ui
+                    .servers_panel
+                    .selected(&self.state.servers)
+                    .map(|server| server.id.clone()),
+            );
+        }
+        servers.sort();
+        servers
+    }
+
+    /// Every page of a `*/list` method from `server`, collecting the `key` array
+    async fn list_all(
+        &mut self,
+        server: &str,
+        method: &str,
+        key: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let response = self.request(server, method, params).await?;
+            if let Some(error) = response.error {
+                return Err(anyhow!("{}", error.message));
+            }
+            let result = response.result.unwrap_or_default();
+            items.extend(result[key].as_array().cloned().unwrap_or_default());
+            match result["nextCursor"].as_str() {
+                Some(next) if cursor.as_deref() != Some(next) => cursor = Some(next.to_string()),
+                _ => return Ok(items),
+            }
+        }
+    }
+
+    /// Send `resources/read` and preview the response in the resource browser
+    async fn read_resource(&mut self, server: &str, uri: &str) {
+        let response = self
+            .request(server, "resources/read", serde_json::json!({ "uri": uri }))
+            .await;
+        let response = match response {
+            Ok(response) => serde_json::to_value(&response).unwrap_or_default(),
+            Err(e) => {
+                warn!("resources/read {} on {} failed: {}", uri, server, e);
+                serde_json::json!({ "error": { "message": e.to_string() } })
+            }
+        };
+        self.dispatch(AppEvent::ResourceRead {
+            server: server.to_string(),
+            uri: uri.to_string(),
+            response,
+        });
+    }
+
+    /// Subscribe to updates of a resource, or unsubscribe from them
+    async fn subscribe_resource(&mut self, server: &str, uri: &str, subscribe: bool) {
+        let method = if subscribe {
+            "resources/subscribe"
+        } else {
+            "resources/unsubscribe"
+        };
+        let sent: Result<()> = async {
+            let response = self
+                .request(server, method, serde_json::json!({ "uri": uri }))
+                .await?;
+            match response.error {
+                Some(error) => Err(anyhow!("{}", error.message)),
+                None => Ok(()),
+            }
+        }
+        .await;
+        match sent {
+            Ok(()) => {
+                self.dispatch(AppEvent::ResourceSubscribed {
+                    server: server.to_string(),
+                    uri: uri.to_string(),
+                    subscribed: subscribe,
+                });
+            }
+            Err(e) => self.record_activity(
+                server,
+                format!("{} {} failed: {}", method, uri, e),
+                ActivityStatus::Failed,
+            ),
+        }
+    }
+
+    /// Ask the server for values of the prompt argument being filled
+    async fn complete_prompt_argument(&mut self, server: &str, params: serde_json::Value) {
+        let completed: Result<Vec<String>> = async {
+            let response = self.request(server, "completion/complete", params).await?;
+            if let Some(error) = response.error {
+                return Err(anyhow!("{}", error.message));
+            }
+            let result = response.result.unwrap_or_default();
+            Ok(result["completion"]["values"]
+                .as_array()
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|value| value.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+        .await;
+        match completed {
+            Ok(values) => {
+                self.dispatch(AppEvent::PromptCompleted { values });
+            }
+            Err(e) => self.record_activity(
+                server,
+                format!("completion/complete failed: {}", e),
+                ActivityStatus::Failed,
+            ),
+        }
+    }
+
+    /// Send `prompts/get` and preview the response in the prompt browser
+    async fn get_prompt(&mut self, server: &str, name: &str, arguments: serde_json::Value) {
+        let params = serde_json::json!({ "name": name, "arguments": arguments });
+        let response = self.request(server, "prompts/get", params).await;
+        let response = match response {
+            Ok(response) => serde_json::to_value(&response).unwrap_or_default(),
+            Err(e) => {
+                warn!("prompts/get {} on {} failed: {}", name, server, e);
+                serde_json::json!({ "error": { "message": e.to_string() } })
+            }
+        };
+        self.dispatch(AppEvent::PromptFetched { response });
+    }
+
+    /// Send an assembled prompt on as if it had been typed as a query
+    async fn send_prompt(&mut self, text: String) {
+        self.dispatch(AppEvent::PromptsClosed);
+        self.dispatch(AppEvent::QueryChanged { query: text });
+        self.process_query().await;
+        self.dispatch(AppEvent::QueryChanged {
+            query: String::new(),
+        });
+    }
+
+    /// Put an assembled prompt on the clipboard through the terminal
+    fn copy_prompt(&mut self, text: &str) {
+        use std::io::Write;
+        let mut stdout = std::io::stdout();
+        let copied = stdout
+            .write_all(crate::prompt_browser::clipboard_sequence(text).as_bytes())
+            .and_then(|()| stdout.flush());
+        let (action, status) = match copied {
+            Ok(()) => (
+                format!("Copied prompt ({} characters)", text.chars().count()),
+                ActivityStatus::Success,
+            ),
+            Err(e) => (format!("Copy failed: {}", e), ActivityStatus::Failed),
+        };
+        self.record_activity("System", action, status);
+    }
+
+    /// Send a recorded request to the selected server, next to the recorded response
+    async fn replay_request(&mut self, method: &str, params: serde_json::Value) {
+        let Some((id, name)) = self
+            .ui
+            .servers_panel
+            .selected(&self.state.servers)
+            .map(|server| (server.id.clone(), server.name.clone()))
+        else {
+            self.record_activity(
+                "System",
+                "Select a server to replay against".to_string(),
+                ActivityStatus::Failed,
+            );
+            return;
+        };
+        let recorded = self
+            .state
+            .session_browser
+            .as_ref()
+            .and_then(|browser| browser.selected_entry())
+            .and_then(|entry| entry.response.as_ref())
+            .map(|response| response.payload.clone());
+
+        let response = self.request(&id, method, params.clone()).await;
+        let (status, replayed) = match response {
+            Ok(response) if response.error.is_none() => (
+                ActivityStatus::Success,
+                serde_json::to_value(&response).unwrap_or_default(),
+            ),
+            Ok(response) => (
+                ActivityStatus::Failed,
+                serde_json::to_value(&response).unwrap_or_default(),
+            ),
+            Err(e) => {
+                warn!("Replaying {} on {} failed: {}", method, name, e);
+                (
+                    ActivityStatus::Failed,
+                    serde_json::json!({ "error": { "message": e.to_string() } }),
+                )
+            }
+        };
+        self.dispatch(AppEvent::ActivityRecorded {
+            activity: ActivityItem {
+                timestamp: chrono::Utc::now(),
+                client: "Replay".to_string(),
+                server: name,
+                action: format!("Replayed {}", method),
+                status,
+                detail: Some(serde_json::json!({
+                    "params": params,
+                    "response": replayed,
+                    "recorded": recorded,
+                })),
+                direction: Some(TrafficDirection::ClientToServer),
+            },
+        });
+    }
+
     /// Initialize with sample data for demonstration
     fn init_sample_data(&mut self) {
         // Sample clients
-        self.clients.insert(
-            "ai-assistant".to_string(),
-            Client {
+        self.dispatch(AppEvent::ClientUpdated {
+            client: Client {
                 id: "ai-assistant".to_string(),
                 name: "AI Assistant".to_string(),
                 description: "General purpose AI assistant".to_string(),
@@ -340,11 +1458,10 @@ impl App {
                 requests_sent: 12,
                 last_activity: chrono::Utc::now(),
             },
-        );
+        });
 
-        self.clients.insert(
-            "code-editor".to_string(),
-            Client {
+        self.dispatch(AppEvent::ClientUpdated {
+            client: Client {
                 id: "code-editor".to_string(),
                 name: "Code Editor".to_string(),
                 description: "Development environment tools".to_string(),
@@ -352,12 +1469,11 @@ impl App {
                 requests_sent: 8,
                 last_activity: chrono::Utc::now(),
             },
-        );
+        });
 
         // Sample servers
-        self.servers.insert(
-            "python-server".to_string(),
-            Server {
+        self.dispatch(AppEvent::ServerUpdated {
+            server: Server {
                 id: "python-server".to_string(),
                 name: "Python Server".to_string(),
                 description: "Python execution environment".to_string(),
@@ -365,11 +1481,10 @@ impl App {
                 requests_received: 15,
                 last_activity: chrono::Utc::now(),
             },
-        );
+        });
 
-        self.servers.insert(
-            "database".to_string(),
-            Server {
+        self.dispatch(AppEvent::ServerUpdated {
+            server: Server {
                 id: "database".to_string(),
                 name: "Database".to_string(),
                 description: "PostgreSQL database".to_string(),
@@ -377,24 +1492,74 @@ impl App {
                 requests_received: 22,
                 last_activity: chrono::Utc::now(),
             },
-        );
+        });
 
         // Sample activities
         let now = chrono::Utc::now();
-        self.activities.push(ActivityItem {
-            timestamp: now - chrono::Duration::minutes(2),
-            client: "AI Assistant".to_string(),
-            server: "Python Server".to_string(),
-            action: "get_weather()".to_string(),
-            status: crate::components::ActivityStatus::Success,
+        self.dispatch(AppEvent::ActivityRecorded {
+            activity: ActivityItem {
+                timestamp: now - chrono::Duration::minutes(2),
+                client: "AI Assistant".to_string(),
+                server: "Python Server".to_string(),
+                action: "get_weather()".to_string(),
+                status: crate::components::ActivityStatus::Success,
+                detail: Some(serde_json::json!({
+                    "result": {
+                        "content": [
+                            { "type": "text", "text": "{\"city\":\"Paris\",\"temp_c\":21,\"sky\":\"clear\"}" },
+                            { "type": "text", "text": "## Forecast\n- **Today**: clear, 21°C\n- **Tomorrow**: showers" }
+                        ]
+                    }
+                })),
+                direction: Some(TrafficDirection::ClientToServer),
+            },
         });
 
-        self.activities.push(ActivityItem {
-            timestamp: now - chrono::Duration::minutes(5),
-            client: "Code Editor".to_string(),
-            server: "Database".to_string(),
-            action: "SELECT * FROM users".to_string(),
-            status: crate::components::ActivityStatus::Processing,
+        self.dispatch(AppEvent::ActivityRecorded {
+            activity: ActivityItem {
+                timestamp: now - chrono::Duration::minutes(5),
+                client: "Code Editor".to_string(),
+                server: "Database".to_string(),
+                action: "SELECT * FROM users".to_string(),
+                status: crate::components::ActivityStatus::Processing,
+                detail: None,
+                direction: Some(TrafficDirection::ClientToServer),
+            },
         });
     }
 }
+
+fn session_entry_to_activity(entry: &SessionEntry) -> ActivityItem {
+    let proxy = entry.proxy_id.0.to_string();
+    let proxy = proxy[..8].to_string();
+    let (client, server) = match entry.direction {
+        TrafficDirection::ClientToServer => ("Client".to_string(), proxy),
+        TrafficDirection::ServerToClient => (proxy, "Client".to_string()),
+    };
+
+    let mut action = match entry.kind {
+        EntryKind::Response => "(unmatched response)".to_string(),
+        _ => entry.method.clone().unwrap_or_default(),
+    };
+    if let Some(name) = entry.request.payload.pointer("/params/name").and_then(|n| n.as_str()) {
+        action = format!("{} {}", action, name);
+    }
+
+    let status = match (entry.kind, &entry.response) {
+        (EntryKind::Request, None) => crate::components::ActivityStatus::Processing,
+        _ if entry.is_error() || entry.request.payload.get("error").is_some() => {
+            crate::components::ActivityStatus::Failed
+        }
+        _ => crate::components::ActivityStatus::Success,
+    };
+
+    ActivityItem {
+        timestamp: entry.started,
+        client,
+        server,
+        action,
+        status,
+        detail: entry.response.as_ref().map(|response| response.payload.clone()),
+        direction: Some(entry.direction),
+    }
+}
@@ -2,13 +2,14 @@ use std::collections::HashMap;
 
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
 
 use crate::components::Client;
+use crate::theme;
 
 pub struct ClientsPanel {
     state: ListState,
@@ -54,7 +55,7 @@ impl ClientsPanel {
         let list_items: Vec<ListItem> = items.iter().map(|client| render_item(client)).collect();
         let mut block = Block::default().title("Clients").borders(Borders::ALL);
         if focused {
-            block = block.border_style(Style::default().fg(Color::Cyan));
+            block = block.border_style(Style::default().fg(theme::current().accent));
         }
 
         let mut state = self.state.clone();
@@ -72,7 +73,10 @@ fn render_item(client: &Client) -> ListItem<'static> {
     let status_style = client.status.style();
     let content = vec![
         Line::from(vec![
-            Span::styled(client.name.clone(), Style::default().fg(Color::White)),
+            Span::styled(
+                client.name.clone(),
+                Style::default().fg(theme::current().text),
+            ),
             Span::raw(" "),
             Span::styled(format!("[{}]", status), status_style),
         ]),
@@ -80,7 +84,7 @@ fn render_item(client: &Client) -> ListItem<'static> {
             Span::styled("  ", Style::default()),
             Span::styled(
                 client.description.clone(),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().muted),
             ),
         ]),
     ];
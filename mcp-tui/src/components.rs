@@ -1,11 +1,22 @@
 use chrono::{DateTime, Utc};
-use ratatui::style::{Color, Style};
+use mcp_common::TrafficDirection;
+use ratatui::style::Style;
+use serde::{Deserialize, Serialize};
+
+use crate::theme;
 
 pub use crate::activity_feed::ActivityFeed;
 pub use crate::clients_panel::ClientsPanel;
+pub use crate::prompt_browser::{PromptArgument, PromptBrowser, PromptEntry, PromptStage};
 pub use crate::query_input::QueryInput;
 pub use crate::quick_access::{QuickAccess, QuickAction};
+pub use crate::resource_browser::{ResourceBrowser, ResourceEntry};
+pub use crate::server_logs::{ServerLogLine, ServerLogView};
 pub use crate::servers_panel::ServersPanel;
+pub use crate::session_browser::{BrowserPane, SavedSession, SessionBrowser};
+pub use crate::stats_panel::StatsPanel;
+pub use crate::tool_form::{FieldValue, FormField, ToolForm};
+pub use crate::traffic_inspector::{InspectorView, TrafficFilter, TrafficInspector};
 
 /// Identifies which widget currently owns input focus.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,7 +29,7 @@ pub enum FocusArea {
 }
 
 /// Connection status for a client.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClientStatus {
     Connected,
     Disconnected,
@@ -36,15 +47,15 @@ impl ClientStatus {
 
     pub fn style(&self) -> Style {
         match self {
-            Self::Connected => Style::default().fg(Color::Green),
-            Self::Disconnected => Style::default().fg(Color::DarkGray),
-            Self::Error => Style::default().fg(Color::Red),
+            Self::Connected => Style::default().fg(theme::current().success),
+            Self::Disconnected => Style::default().fg(theme::current().muted),
+            Self::Error => Style::default().fg(theme::current().error),
         }
     }
 }
 
 /// Status for a server.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ServerStatus {
     Starting,
     Running,
@@ -66,17 +77,17 @@ impl ServerStatus {
 
     pub fn style(&self) -> Style {
         match self {
-            Self::Starting => Style::default().fg(Color::Yellow),
-            Self::Running => Style::default().fg(Color::Green),
-            Self::Degraded => Style::default().fg(Color::LightYellow),
-            Self::Stopped => Style::default().fg(Color::DarkGray),
-            Self::Error => Style::default().fg(Color::Red),
+            Self::Starting => Style::default().fg(theme::current().warning),
+            Self::Running => Style::default().fg(theme::current().success),
+            Self::Degraded => Style::default().fg(theme::current().warning),
+            Self::Stopped => Style::default().fg(theme::current().muted),
+            Self::Error => Style::default().fg(theme::current().error),
         }
     }
 }
 
 /// Activity execution status.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ActivityStatus {
     Processing,
     Success,
@@ -94,15 +105,15 @@ impl ActivityStatus {
 
     pub fn style(&self) -> Style {
         match self {
-            Self::Processing => Style::default().fg(Color::Yellow),
-            Self::Success => Style::default().fg(Color::Green),
-            Self::Failed => Style::default().fg(Color::Red),
+            Self::Processing => Style::default().fg(theme::current().warning),
+            Self::Success => Style::default().fg(theme::current().success),
+            Self::Failed => Style::default().fg(theme::current().error),
         }
     }
 }
 
 /// Domain model for a known client.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Client {
     pub id: String,
     pub name: String,
@@ -131,7 +142,7 @@ impl Client {
 }
 
 /// Domain model for a known MCP server.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Server {
     pub id: String,
     pub name: String,
@@ -160,11 +171,17 @@ impl Server {
 }
 
 /// Item rendered in the activity feed.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActivityItem {
     pub timestamp: DateTime<Utc>,
     pub client: String,
     pub server: String,
     pub action: String,
     pub status: ActivityStatus,
+    /// Response payload shown in the detail view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<serde_json::Value>,
+    /// Which way the message went, for activities that are protocol traffic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<TrafficDirection>,
 }
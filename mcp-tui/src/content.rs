@@ -0,0 +1,737 @@
+//! Rendering of MCP content for the activity feed's detail view.
+//!
+//! Results are shown as styled lines instead of raw JSON. Content blocks are
+//! understood by type: text holding JSON is pretty-printed, markdown is
+//! rendered, code in fences or typed resources is highlighted, PNG images are
+//! previewed as half-block art and everything else falls back to highlighted
+//! JSON. Lines are wrapped to the pane and split into pages.
+
+use std::io::Cursor;
+
+use base64::Engine;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use serde_json::Value;
+
+use crate::theme;
+
+/// Tallest image preview, in terminal rows (two pixels per row)
+const MAX_IMAGE_ROWS: usize = 24;
+
+/// Render a JSON-RPC response payload, or a bare result, wrapped to `width` columns
+pub fn render_payload(payload: &Value, width: u16) -> Vec<Line<'static>> {
+    let width = usize::from(width.max(1));
+    let mut lines = Vec::new();
+    if let Some(error) = payload.get("error") {
+        render_error(error, &mut lines);
+    } else {
+        render_result(payload.get("result").unwrap_or(payload), width, &mut lines);
+    }
+    lines
+        .into_iter()
+        .flat_map(|line| wrap(line, width))
+        .collect()
+}
+
+/// Any JSON value pretty-printed and highlighted, wrapped to `width` columns
+pub fn render_json(value: &Value, width: u16) -> Vec<Line<'static>> {
+    let width = usize::from(width.max(1));
+    json_lines(value)
+        .into_iter()
+        .flat_map(|line| wrap(line, width))
+        .collect()
+}
+
+/// One page of rendered lines
+pub struct Page {
+    pub lines: Vec<Line<'static>>,
+    /// Zero-based page shown, clamped to the last page
+    pub index: usize,
+    pub count: usize,
+}
+
+/// Cut `lines` into pages of `height` rows and return page `index`
+pub fn paginate(lines: Vec<Line<'static>>, index: usize, height: usize) -> Page {
+    let height = height.max(1);
+    let count = lines.len().div_ceil(height).max(1);
+    let index = index.min(count - 1);
+    let lines = lines
+        .into_iter()
+        .skip(index * height)
+        .take(height)
+        .collect();
+    Page {
+        lines,
+        index,
+        count,
+    }
+}
+
+fn render_error(error: &Value, lines: &mut Vec<Line<'static>>) {
+    let code = error["code"].as_i64().unwrap_or_default();
+    let message = error["message"].as_str().unwrap_or("(no message)");
+    lines.push(Line::from(Span::styled(
+        format!("Error {}: {}", code, message),
+        Style::default()
+            .fg(theme::current().error)
+            .add_modifier(Modifier::BOLD),
+    )));
+    if let Some(data) = error.get("data") {
+        lines.extend(json_lines(data));
+    }
+}
+
+fn render_result(result: &Value, width: usize, lines: &mut Vec<Line<'static>>) {
+    if let Some(blocks) = result["content"].as_array() {
+        // tools/call
+        if result["isError"] == true {
+            lines.push(Line::from(Span::styled(
+                "Tool reported an error",
+                Style::default()
+                    .fg(theme::current().error)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        for (i, block) in blocks.iter().enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+            render_block(block, width, lines);
+        }
+    } else if let Some(contents) = result["contents"].as_array() {
+        // resources/read
+        for resource in contents {
+            render_resource(resource, width, lines);
+        }
+    } else if let Some(messages) = result["messages"].as_array() {
+        // prompts/get
+        for message in messages {
+            let role = message["role"].as_str().unwrap_or("message");
+            let color = match role {
+                "user" => theme::current().accent,
+                "assistant" => theme::current().success,
+                _ => theme::current().warning,
+            };
+            lines.push(Line::from(Span::styled(
+                format!("{}:", role),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )));
+            render_block(&message["content"], width, lines);
+            lines.push(Line::default());
+        }
+    } else {
+        lines.extend(json_lines(result));
+    }
+}
+
+fn render_block(block: &Value, width: usize, lines: &mut Vec<Line<'static>>) {
+    match block["type"].as_str() {
+        Some("text") => lines.extend(text_lines(block["text"].as_str().unwrap_or_default())),
+        Some("image") => render_image(
+            block["data"].as_str().unwrap_or_default(),
+            block["mimeType"].as_str().unwrap_or("image"),
+            width,
+            lines,
+        ),
+        Some("audio") => {
+            let bytes = decoded_len(block["data"].as_str().unwrap_or_default());
+            lines.push(dim(format!(
+                "[{} audio, {}]",
+                block["mimeType"].as_str().unwrap_or("unknown"),
+                format_bytes(bytes)
+            )));
+        }
+        Some("resource") => render_resource(&block["resource"], width, lines),
+        Some("resource_link") => lines.push(Line::from(vec![
+            Span::styled("↗ ", Style::default().fg(theme::current().accent)),
+            Span::raw(block["uri"].as_str().unwrap_or_default().to_string()),
+            Span::styled(
+                block["name"]
+                    .as_str()
+                    .map(|name| format!(" ({})", name))
+                    .unwrap_or_default(),
+                Style::default().fg(theme::current().muted),
+            ),
+        ])),
+        _ => lines.extend(json_lines(block)),
+    }
+}
+
+fn render_resource(resource: &Value, width: usize, lines: &mut Vec<Line<'static>>) {
+    let uri = resource["uri"].as_str().unwrap_or_default();
+    let mime = resource["mimeType"].as_str().unwrap_or_default();
+    lines.push(dim(if mime.is_empty() {
+        format!("── {}", uri)
+    } else {
+        format!("── {} ({})", uri, mime)
+    }));
+
+    if let Some(text) = resource["text"].as_str() {
+        if mime.contains("json") {
+            match serde_json::from_str::<Value>(text) {
+                Ok(value) => lines.extend(json_lines(&value)),
+                Err(_) => lines.extend(plain_lines(text)),
+            }
+        } else if mime == "text/markdown" {
+            lines.extend(markdown_lines(text));
+        } else if let Some(language) = language_of(mime, uri) {
+            lines.extend(highlight_code(text, language));
+        } else {
+            lines.extend(text_lines(text));
+        }
+    } else if let Some(blob) = resource["blob"].as_str() {
+        if mime.starts_with("image/") {
+            render_image(blob, mime, width, lines);
+        } else {
+            lines.push(dim(format!(
+                "[binary, {}]",
+                format_bytes(decoded_len(blob))
+            )));
+        }
+    }
+}
+
+/// Text shown as JSON, markdown or plain lines depending on what it holds
+fn text_lines(text: &str) -> Vec<Line<'static>> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        if let Ok(value) = serde_json::from_str::<Value>(text) {
+            return json_lines(&value);
+        }
+    }
+    if looks_like_markdown(text) {
+        markdown_lines(text)
+    } else {
+        plain_lines(text)
+    }
+}
+
+fn plain_lines(text: &str) -> Vec<Line<'static>> {
+    text.lines()
+        .map(|line| Line::from(line.to_string()))
+        .collect()
+}
+
+fn dim(text: String) -> Line<'static> {
+    Line::from(Span::styled(
+        text,
+        Style::default().fg(theme::current().muted),
+    ))
+}
+
+fn looks_like_markdown(text: &str) -> bool {
+    text.lines().any(|line| {
+        let line = line.trim_start();
+        line.starts_with("# ")
+            || line.starts_with("## ")
+            || line.starts_with("```")
+            || line.starts_with("- ")
+            || line.starts_with("* ")
+            || line.starts_with("> ")
+            || line.contains("**")
+            || line.contains("](")
+    })
+}
+
+/// Pretty-printed JSON with keys, strings, numbers and literals colored
+fn json_lines(value: &Value) -> Vec<Line<'static>> {
+    let pretty = serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+    pretty.lines().map(json_line).collect()
+}
+
+fn json_line(line: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            let is_key = chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&':');
+            let color = if is_key {
+                theme::current().accent
+            } else {
+                theme::current().success
+            };
+            spans.push(Span::styled(
+                chars[start..i].iter().collect::<String>(),
+                Style::default().fg(color),
+            ));
+        } else if c == '-' || c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || "+-.".contains(chars[i]))
+            {
+                i += 1;
+            }
+            spans.push(Span::styled(
+                chars[start..i].iter().collect::<String>(),
+                Style::default().fg(theme::current().warning),
+            ));
+        } else if c.is_ascii_alphabetic() {
+            while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            spans.push(Span::styled(
+                chars[start..i].iter().collect::<String>(),
+                Style::default().fg(theme::current().highlight),
+            ));
+        } else {
+            while i < chars.len()
+                && !(chars[i] == '"' || chars[i] == '-' || chars[i].is_ascii_alphanumeric())
+            {
+                i += 1;
+            }
+            spans.push(Span::raw(chars[start..i].iter().collect::<String>()));
+        }
+    }
+    Line::from(spans)
+}
+
+/// Headings, lists, quotes, inline code and bold; fenced code is highlighted
+fn markdown_lines(text: &str) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut fence: Option<(String, String)> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            match fence.take() {
+                Some((language, code)) => lines.extend(highlight_code(&code, &language)),
+                None => fence = Some((info.trim().to_string(), String::new())),
+            }
+            continue;
+        }
+        if let Some((_, code)) = &mut fence {
+            code.push_str(line);
+            code.push('\n');
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let mut style = Style::default()
+                .fg(theme::current().accent)
+                .add_modifier(Modifier::BOLD);
+            if level == 1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            lines.push(Line::from(Span::styled(
+                trimmed[level..].trim().to_string(),
+                style,
+            )));
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            let indent = line.len() - trimmed.len();
+            let mut spans = vec![Span::raw(format!("{}• ", " ".repeat(indent)))];
+            spans.extend(inline_spans(item, Style::default()));
+            lines.push(Line::from(spans));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let style = Style::default()
+                .fg(theme::current().secondary)
+                .add_modifier(Modifier::ITALIC);
+            let mut spans = vec![Span::styled(
+                "│ ",
+                Style::default().fg(theme::current().muted),
+            )];
+            spans.extend(inline_spans(quote.trim_start(), style));
+            lines.push(Line::from(spans));
+        } else {
+            lines.push(Line::from(inline_spans(line, Style::default())));
+        }
+    }
+
+    // An unterminated fence still shows its code
+    if let Some((language, code)) = fence {
+        lines.extend(highlight_code(&code, &language));
+    }
+    lines
+}
+
+/// Split `text` on `code` and **bold** markers
+fn inline_spans(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    loop {
+        let code = rest.find('`');
+        let bold = rest.find("**");
+        let (start, marker, style) = match (code, bold) {
+            (Some(c), Some(b)) if b < c => (b, "**", base.add_modifier(Modifier::BOLD)),
+            (Some(c), _) => (c, "`", base.fg(theme::current().warning)),
+            (None, Some(b)) => (b, "**", base.add_modifier(Modifier::BOLD)),
+            (None, None) => break,
+        };
+        let after = &rest[start + marker.len()..];
+        let Some(end) = after.find(marker) else {
+            break;
+        };
+        if start > 0 {
+            spans.push(Span::styled(rest[..start].to_string(), base));
+        }
+        spans.push(Span::styled(after[..end].to_string(), style));
+        rest = &after[end + marker.len()..];
+    }
+    if !rest.is_empty() {
+        spans.push(Span::styled(rest.to_string(), base));
+    }
+    spans
+}
+
+/// Language to highlight a resource as, from its MIME type or extension
+fn language_of(mime: &str, uri: &str) -> Option<&'static str> {
+    let extension = uri.rsplit('.').next().unwrap_or_default();
+    let language = match (mime, extension) {
+        (_, "rs") | ("text/x-rust", _) => "rust",
+        (_, "py") | ("text/x-python", _) => "python",
+        (_, "js" | "mjs" | "ts" | "tsx" | "jsx")
+        | ("text/javascript" | "application/javascript", _) => "javascript",
+        (_, "sh" | "bash") | ("application/x-sh", _) => "sh",
+        (_, "sql") | ("application/sql", _) => "sql",
+        (_, "toml" | "yaml" | "yml") => "toml",
+        (_, "go") => "go",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Color comments, strings, numbers and keywords line by line
+fn highlight_code(code: &str, language: &str) -> Vec<Line<'static>> {
+    let comment = match language {
+        "python" | "py" | "sh" | "bash" | "toml" | "yaml" | "yml" => "#",
+        "sql" => "--",
+        _ => "//",
+    };
+    let keywords: &[&str] = match language {
+        "rust" | "rs" => &[
+            "as", "async", "await", "break", "const", "continue", "else", "enum", "fn", "for",
+            "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+            "return", "self", "Self", "static", "struct", "trait", "type", "use", "where", "while",
+            "true", "false",
+        ],
+        "python" | "py" => &[
+            "and", "as", "async", "await", "class", "def", "elif", "else", "except", "for", "from",
+            "if", "import", "in", "is", "lambda", "not", "or", "pass", "raise", "return", "try",
+            "while", "with", "yield", "None", "True", "False",
+        ],
+        "sql" => &[
+            "SELECT", "FROM", "WHERE", "INSERT", "INTO", "UPDATE", "DELETE", "CREATE", "TABLE",
+            "JOIN", "ON", "AND", "OR", "NOT", "NULL", "ORDER", "BY", "GROUP", "LIMIT", "AS",
+            "select", "from", "where", "insert", "into", "update", "delete", "create", "table",
+            "join", "on", "and", "or", "not", "null", "order", "by", "group", "limit", "as",
+        ],
+        "sh" | "bash" => &[
+            "if", "then", "else", "fi", "for", "do", "done", "while", "case", "esac", "export",
+            "function", "return", "local",
+        ],
+        _ => &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "class",
+            "const",
+            "continue",
+            "else",
+            "export",
+            "for",
+            "func",
+            "function",
+            "if",
+            "import",
+            "interface",
+            "let",
+            "new",
+            "package",
+            "return",
+            "struct",
+            "switch",
+            "type",
+            "var",
+            "while",
+            "true",
+            "false",
+            "null",
+        ],
+    };
+
+    let code_style = Style::default().fg(theme::current().text);
+    code.lines()
+        .map(|line| {
+            let mut spans = Vec::new();
+            let chars: Vec<char> = line.chars().collect();
+            let mut i = 0;
+            while i < chars.len() {
+                let start = i;
+                let c = chars[i];
+                let rest: String = chars[i..].iter().collect();
+                if rest.starts_with(comment) {
+                    spans.push(Span::styled(
+                        rest,
+                        Style::default().fg(theme::current().muted),
+                    ));
+                    break;
+                } else if c == '"' || c == '\'' {
+                    i += 1;
+                    while i < chars.len() && chars[i] != c {
+                        i += if chars[i] == '\\' { 2 } else { 1 };
+                    }
+                    i = (i + 1).min(chars.len());
+                    spans.push(Span::styled(
+                        chars[start..i].iter().collect::<String>(),
+                        Style::default().fg(theme::current().success),
+                    ));
+                } else if c.is_ascii_digit() {
+                    while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                        i += 1;
+                    }
+                    spans.push(Span::styled(
+                        chars[start..i].iter().collect::<String>(),
+                        Style::default().fg(theme::current().warning),
+                    ));
+                } else if c.is_alphabetic() || c == '_' {
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    let style = if keywords.contains(&word.as_str()) {
+                        Style::default()
+                            .fg(theme::current().highlight)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        code_style
+                    };
+                    spans.push(Span::styled(word, style));
+                } else {
+                    i += 1;
+                    spans.push(Span::styled(c.to_string(), code_style));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn render_image(data: &str, mime: &str, width: usize, lines: &mut Vec<Line<'static>>) {
+    let bytes = match base64::engine::general_purpose::STANDARD.decode(data.trim()) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            lines.push(dim(format!("[{}, invalid base64]", mime)));
+            return;
+        }
+    };
+    let decoded = (mime == "image/png").then(|| decode_png(&bytes)).flatten();
+    let Some(image) = decoded else {
+        lines.push(dim(format!(
+            "[{}, {}; preview needs PNG]",
+            mime,
+            format_bytes(bytes.len())
+        )));
+        return;
+    };
+
+    lines.push(dim(format!(
+        "[{} {}×{}, {}]",
+        mime,
+        image.width,
+        image.height,
+        format_bytes(bytes.len())
+    )));
+    lines.extend(half_blocks(&image, width));
+}
+
+/// Decoded pixels, composited onto black
+struct Rgb {
+    width: usize,
+    height: usize,
+    pixels: Vec<(u8, u8, u8)>,
+}
+
+fn decode_png(bytes: &[u8]) -> Option<Rgb> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).ok()?;
+    let channels = frame.color_type.samples();
+
+    let pixels = buffer[..frame.buffer_size()]
+        .chunks_exact(channels)
+        .map(|p| {
+            let (r, g, b, a) = match p {
+                [l] => (*l, *l, *l, 255),
+                [l, a] => (*l, *l, *l, *a),
+                [r, g, b] => (*r, *g, *b, 255),
+                [r, g, b, a, ..] => (*r, *g, *b, *a),
+                [] => (0, 0, 0, 0),
+            };
+            let blend = |c: u8| (u16::from(c) * u16::from(a) / 255) as u8;
+            (blend(r), blend(g), blend(b))
+        })
+        .collect();
+    Some(Rgb {
+        width: frame.width as usize,
+        height: frame.height as usize,
+        pixels,
+    })
+}
+
+/// Nearest-neighbour downscale where each cell shows two pixels as `▀`
+fn half_blocks(image: &Rgb, width: usize) -> Vec<Line<'static>> {
+    if image.width == 0 || image.height == 0 {
+        return Vec::new();
+    }
+    let columns = image.width.min(width);
+    let rows = (image.height * columns / image.width)
+        .div_ceil(2)
+        .clamp(1, MAX_IMAGE_ROWS);
+    // Keep the aspect ratio when the row cap shrinks the image
+    let columns = columns
+        .min((rows * 2 * image.width).div_ceil(image.height))
+        .max(1);
+
+    let pixel = |column: usize, row: usize| {
+        let x = column * image.width / columns;
+        let y = (row * image.height / (rows * 2)).min(image.height - 1);
+        let (r, g, b) = image.pixels[y * image.width + x];
+        Color::Rgb(r, g, b)
+    };
+    (0..rows)
+        .map(|row| {
+            Line::from(
+                (0..columns)
+                    .map(|column| {
+                        Span::styled(
+                            "▀",
+                            Style::default()
+                                .fg(pixel(column, row * 2))
+                                .bg(pixel(column, row * 2 + 1)),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+fn decoded_len(data: &str) -> usize {
+    data.trim().len() / 4 * 3
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Break a styled line into rows of at most `width` characters
+fn wrap(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    if line.width() <= width {
+        return vec![line];
+    }
+    let mut rows = Vec::new();
+    let mut row: Vec<Span<'static>> = Vec::new();
+    let mut used = 0;
+    for span in line.spans {
+        let mut text = span.content.as_ref();
+        while !text.is_empty() {
+            let take = text
+                .char_indices()
+                .nth(width - used)
+                .map_or(text.len(), |(i, _)| i);
+            row.push(Span::styled(text[..take].to_string(), span.style));
+            used += text[..take].chars().count();
+            text = &text[take..];
+            if used == width {
+                rows.push(Line::from(std::mem::take(&mut row)));
+                used = 0;
+            }
+        }
+    }
+    if !row.is_empty() {
+        rows.push(Line::from(row));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_text_blocks_render_by_what_they_hold() {
+        let payload = json!({ "result": { "content": [
+            { "type": "text", "text": "{\"temp\":21}" },
+            { "type": "text", "text": "# Weather\n- **sunny** all `day`\n```rust\nlet x = 1; // done\n```" },
+        ]}});
+        let lines = render_payload(&payload, 80);
+        assert_eq!(
+            text(&lines),
+            vec![
+                "{",
+                "  \"temp\": 21",
+                "}",
+                "",
+                "Weather",
+                "• sunny all day",
+                "let x = 1; // done",
+            ]
+        );
+        // Keys are colored apart from values; keywords and comments in code
+        assert_eq!(lines[1].spans[1].style.fg, Some(Color::Cyan));
+        let code = &lines[6].spans;
+        assert_eq!(code[0].style.fg, Some(Color::Magenta));
+        assert_eq!(code.last().unwrap().style.fg, Some(Color::DarkGray));
+    }
+
+    #[test]
+    fn test_errors_images_and_pages() {
+        let error = json!({ "error": { "code": -32602, "message": "bad args" } });
+        assert_eq!(
+            text(&render_payload(&error, 80)),
+            vec!["Error -32602: bad args"]
+        );
+
+        // A 2×2 PNG previews as one row of two half-blocks
+        let mut png_bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_bytes, 2, 2);
+            encoder.set_color(png::ColorType::Rgb);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&[255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255])
+                .unwrap();
+        }
+        let data = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
+        let image =
+            json!({ "content": [{ "type": "image", "data": data, "mimeType": "image/png" }] });
+        let lines = render_payload(&image, 80);
+        assert!(text(&lines)[0].starts_with("[image/png 2×2"));
+        assert_eq!(text(&lines)[1], "▀▀");
+        assert_eq!(lines[1].spans[0].style.fg, Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(lines[1].spans[0].style.bg, Some(Color::Rgb(0, 0, 255)));
+
+        let long = json!({ "content": [{ "type": "text", "text": "abcdefghij\n1\n2\n3" }] });
+        let lines = render_payload(&long, 4);
+        assert_eq!(text(&lines)[..3], ["abcd", "efgh", "ij"]);
+        let page = paginate(lines, 9, 2);
+        assert_eq!((page.index, page.count), (2, 3));
+        assert_eq!(text(&page.lines), vec!["2", "3"]);
+    }
+}
@@ -1,12 +1,14 @@
 use anyhow::{Context, Result};
-use crossterm::event::{
-    self, Event as CrosstermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
-};
+use crossterm::event::{self, Event as CrosstermEvent, KeyEventKind};
+use serde::{Deserialize, Serialize};
 use tokio::task;
 use tracing::warn;
 
+use crate::keymap::Keymap;
+
 /// High level events understood by the application.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Event {
     Quit,
     /// Textual input from the user.
@@ -20,14 +22,53 @@ pub enum Event {
     Down,
     Left,
     Right,
+    /// Scroll the activity detail view by a page.
+    PageUp,
+    PageDown,
+    /// Revert the last destructive action.
+    Undo,
+    /// Open or close the traffic inspector.
+    ToggleInspector,
+    /// Open or close the saved sessions browser.
+    ToggleSessions,
+    /// Open or close the server logs panel.
+    ToggleServerLogs,
+    /// Open or close the keybinding help.
+    ToggleHelp,
+    /// Open or close the resource browser.
+    ToggleResources,
+    /// Subscribe to or unsubscribe from the selected resource.
+    ToggleSubscription,
+    /// Open or close the prompt browser.
+    TogglePrompts,
+    /// Copy the previewed prompt to the clipboard.
+    CopyPrompt,
+    /// Pause or resume the activity feed.
+    TogglePause,
+    /// Open or close the per-server stats panel.
+    ToggleStats,
 }
 
 /// Blocking event reader wrapped for async callers.
-pub struct EventHandler;
+pub struct EventHandler {
+    keymap: Keymap,
+}
 
 impl EventHandler {
     pub fn new() -> Self {
-        Self
+        Self {
+            keymap: Keymap::default(),
+        }
+    }
+
+    /// How keys are translated
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Translate keys with `keymap` from now on
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
     }
 
     pub async fn next(&mut self) -> Result<Event> {
@@ -43,51 +84,16 @@ impl EventHandler {
                 }
             };
 
-            if let Some(app_event) = map_event(event) {
+            if let Some(app_event) = map_event(&self.keymap, event) {
                 return Ok(app_event);
             }
         }
     }
 }
 
-fn map_event(event: CrosstermEvent) -> Option<Event> {
+fn map_event(keymap: &Keymap, event: CrosstermEvent) -> Option<Event> {
     match event {
-        CrosstermEvent::Key(KeyEvent {
-            code,
-            modifiers,
-            kind,
-            ..
-        }) => {
-            if kind != KeyEventKind::Press {
-                return None;
-            }
-            match code {
-                KeyCode::Esc => Some(Event::Quit),
-                KeyCode::Enter => Some(Event::Enter),
-                KeyCode::Tab => {
-                    if modifiers.contains(KeyModifiers::SHIFT) {
-                        Some(Event::FocusPrev)
-                    } else if modifiers.contains(KeyModifiers::CONTROL) {
-                        Some(Event::FocusNext)
-                    } else {
-                        Some(Event::Tab)
-                    }
-                }
-                KeyCode::BackTab => Some(Event::FocusPrev),
-                KeyCode::Backspace => Some(Event::Backspace),
-                KeyCode::Left => Some(Event::Left),
-                KeyCode::Right => Some(Event::Right),
-                KeyCode::Up => Some(Event::Up),
-                KeyCode::Down => Some(Event::Down),
-                KeyCode::Char('c') | KeyCode::Char('q')
-                    if modifiers.contains(KeyModifiers::CONTROL) =>
-                {
-                    Some(Event::Quit)
-                }
-                KeyCode::Char(character) => Some(Event::Input(character)),
-                _ => None,
-            }
-        }
+        CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => keymap.event_for(key),
         _ => None,
     }
 }
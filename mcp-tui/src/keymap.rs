@@ -0,0 +1,344 @@
+//! Keybindings.
+//!
+//! A [`Keymap`] turns key presses into [`Event`]s. The defaults can be
+//! changed per action from the `[keys]` table of the monitor configuration,
+//! where each action lists every key that should trigger it:
+//!
+//! ```toml
+//! [keys]
+//! quit = ["ctrl+c", "ctrl+q"]
+//! toggle_inspector = ["ctrl+i", "f2"]
+//! ```
+//!
+//! Actions are the snake_case names of [`Event`]s; keys are a key name
+//! (`esc`, `tab`, `pageup`, `f1`, a single character) after any of the
+//! `ctrl+`, `alt+` and `shift+` modifiers. A key bound to a listed action is
+//! taken away from whatever it did before. Characters without a binding are
+//! typed as input.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::events::Event;
+
+/// A key with its modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Shift is part of the character, and of back-tab, not a modifier
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers.difference(KeyModifiers::SHIFT),
+            _ => modifiers,
+        };
+        Self {
+            code,
+            modifiers: modifiers
+                .intersection(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT),
+        }
+    }
+
+    fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::CONTROL)
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = s.split('+').collect();
+        // "ctrl++" binds the plus key
+        if s.ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let (key, prefix) = parts
+            .split_last()
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| anyhow!("Empty key binding"))?;
+        for modifier in prefix {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => bail!("Unknown modifier '{}' in key '{}'", other, s),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => bail!("Unknown key '{}'", key),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// What each bound key does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(KeyBinding, Event)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+        let bindings = vec![
+            (KeyBinding::key(Esc), Event::Quit),
+            (KeyBinding::ctrl(Char('c')), Event::Quit),
+            (KeyBinding::ctrl(Char('q')), Event::Quit),
+            (KeyBinding::key(Enter), Event::Enter),
+            (KeyBinding::key(Backspace), Event::Backspace),
+            (KeyBinding::key(Tab), Event::Tab),
+            (KeyBinding::ctrl(Tab), Event::FocusNext),
+            (KeyBinding::key(BackTab), Event::FocusPrev),
+            (KeyBinding::new(Tab, KeyModifiers::SHIFT), Event::FocusPrev),
+            (KeyBinding::key(Up), Event::Up),
+            (KeyBinding::key(Down), Event::Down),
+            (KeyBinding::key(Left), Event::Left),
+            (KeyBinding::key(Right), Event::Right),
+            (KeyBinding::key(PageUp), Event::PageUp),
+            (KeyBinding::key(PageDown), Event::PageDown),
+            (KeyBinding::ctrl(Char('z')), Event::Undo),
+            (KeyBinding::ctrl(Char('t')), Event::ToggleInspector),
+            (KeyBinding::ctrl(Char('o')), Event::ToggleSessions),
+            (KeyBinding::ctrl(Char('l')), Event::ToggleServerLogs),
+            (KeyBinding::ctrl(Char('r')), Event::ToggleResources),
+            (KeyBinding::ctrl(Char('s')), Event::ToggleSubscription),
+            (KeyBinding::ctrl(Char('p')), Event::TogglePrompts),
+            (KeyBinding::ctrl(Char('y')), Event::CopyPrompt),
+            (KeyBinding::ctrl(Char('f')), Event::TogglePause),
+            (KeyBinding::ctrl(Char('g')), Event::ToggleStats),
+            (KeyBinding::key(F(1)), Event::ToggleHelp),
+        ];
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// The default keymap with the keys of each listed action replaced
+    pub fn with_overrides(overrides: &BTreeMap<String, Vec<String>>) -> Result<Self> {
+        let mut keymap = Self::default();
+        for (action, keys) in overrides {
+            let event: Event = serde_json::from_value(serde_json::Value::String(action.clone()))
+                .ok()
+                .filter(|event| !matches!(event, Event::Input(_)))
+                .ok_or_else(|| anyhow!("Unknown action '{}' in [keys]", action))?;
+            let keys = keys
+                .iter()
+                .map(|key| key.parse())
+                .collect::<Result<Vec<KeyBinding>>>()?;
+            keymap
+                .bindings
+                .retain(|(key, bound)| *bound != event && !keys.contains(key));
+            keymap
+                .bindings
+                .extend(keys.into_iter().map(|key| (key, event.clone())));
+        }
+        Ok(keymap)
+    }
+
+    /// What a key press does, if anything
+    pub fn event_for(&self, key: KeyEvent) -> Option<Event> {
+        let binding = KeyBinding::from(key);
+        if let Some((_, event)) = self.bindings.iter().find(|(bound, _)| *bound == binding) {
+            return Some(event.clone());
+        }
+        match key.code {
+            KeyCode::Char(c) => Some(Event::Input(c)),
+            _ => None,
+        }
+    }
+
+    /// Every action with a key, with its keys, in the order shown in help
+    pub fn help(&self) -> Vec<(&'static str, Vec<KeyBinding>)> {
+        let mut help: Vec<(&'static str, Vec<KeyBinding>)> = Vec::new();
+        for (key, event) in &self.bindings {
+            let label = describe(event);
+            match help.iter_mut().find(|(existing, _)| *existing == label) {
+                Some((_, keys)) => keys.push(*key),
+                None => help.push((label, vec![*key])),
+            }
+        }
+        help
+    }
+}
+
+/// What an action does, for the help overlay
+fn describe(event: &Event) -> &'static str {
+    match event {
+        Event::Quit => "Quit, or close the open view",
+        Event::Input(_) => "Type",
+        Event::Enter => "Send the query or confirm",
+        Event::Backspace => "Delete the last character",
+        Event::Tab => "Next panel",
+        Event::FocusNext => "Next panel",
+        Event::FocusPrev => "Previous panel",
+        Event::Up => "Move up",
+        Event::Down => "Move down",
+        Event::Left => "Move left",
+        Event::Right => "Move right",
+        Event::PageUp => "Scroll the detail view up",
+        Event::PageDown => "Scroll the detail view down",
+        Event::Undo => "Undo the last destructive action",
+        Event::ToggleInspector => "Traffic inspector",
+        Event::ToggleSessions => "Saved sessions",
+        Event::ToggleServerLogs => "Server logs",
+        Event::ToggleResources => "Resource browser",
+        Event::ToggleSubscription => "Subscribe to the selected resource",
+        Event::TogglePrompts => "Prompt browser",
+        Event::CopyPrompt => "Copy the previewed prompt",
+        Event::TogglePause => "Pause or resume the activity feed",
+        Event::ToggleStats => "Open or close the stats panel",
+        Event::ToggleHelp => "This help",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventKind;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+    }
+
+    #[test]
+    fn test_key_bindings_round_trip() {
+        for key in [
+            "ctrl+t",
+            "f1",
+            "esc",
+            "shift+tab",
+            "alt+pagedown",
+            "q",
+            "ctrl++",
+        ] {
+            assert_eq!(key.parse::<KeyBinding>().unwrap().to_string(), key);
+        }
+        assert_eq!(
+            "Ctrl+PgUp".parse::<KeyBinding>().unwrap(),
+            KeyBinding::ctrl(KeyCode::PageUp)
+        );
+        assert!("hyper+x".parse::<KeyBinding>().is_err());
+        assert!("f99".parse::<KeyBinding>().is_err());
+        assert!("".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn test_overrides_replace_an_actions_keys() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.event_for(press(KeyCode::Char('t'), KeyModifiers::CONTROL)),
+            Some(Event::ToggleInspector)
+        );
+        assert_eq!(
+            keymap.event_for(press(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            Some(Event::FocusPrev)
+        );
+        assert_eq!(
+            keymap.event_for(press(KeyCode::Char('T'), KeyModifiers::SHIFT)),
+            Some(Event::Input('T'))
+        );
+
+        let overrides = BTreeMap::from([(
+            "toggle_inspector".to_string(),
+            vec!["f2".to_string(), "ctrl+o".to_string()],
+        )]);
+        let keymap = Keymap::with_overrides(&overrides).unwrap();
+        assert_eq!(
+            keymap.event_for(press(KeyCode::Char('t'), KeyModifiers::CONTROL)),
+            Some(Event::Input('t'))
+        );
+        assert_eq!(
+            keymap.event_for(press(KeyCode::F(2), KeyModifiers::NONE)),
+            Some(Event::ToggleInspector)
+        );
+        // ctrl+o no longer opens the sessions browser
+        assert_eq!(
+            keymap.event_for(press(KeyCode::Char('o'), KeyModifiers::CONTROL)),
+            Some(Event::ToggleInspector)
+        );
+        let help = keymap.help();
+        assert!(!help.iter().any(|(label, _)| *label == "Saved sessions"));
+
+        let unknown = BTreeMap::from([("launch".to_string(), vec!["f3".to_string()])]);
+        assert!(Keymap::with_overrides(&unknown).is_err());
+    }
+}
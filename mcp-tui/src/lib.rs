@@ -1,17 +1,33 @@
 mod activity_feed;
+#[cfg(feature = "alerts")]
+pub mod alerts;
 pub mod app;
 mod clients_panel;
 pub mod components;
+pub mod content;
 pub mod events;
+pub mod keymap;
+mod prompt_browser;
 mod query_input;
 mod quick_access;
+mod resource_browser;
+mod server_logs;
 mod servers_panel;
+mod session_browser;
+pub mod state;
+mod stats_panel;
+pub mod theme;
+mod tool_form;
+mod traffic_inspector;
 pub mod ui;
 
 // Re-export key types for external use
 pub use app::App;
 pub use components::*;
 pub use events::{Event, EventHandler};
+pub use keymap::{KeyBinding, Keymap};
+pub use state::{AppEvent, AppState, EventLog};
+pub use theme::{ColorSupport, Theme};
 
 use anyhow::Result;
 
@@ -1,21 +1,85 @@
 use anyhow::Result;
+use mcp_common::{init_logging, LogFormat};
 use tracing::{error, info};
-use tracing_subscriber;
+use tracing_subscriber::EnvFilter;
 
 use mcp_tui::App;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+    // Initialize the application
+    let mut app = App::new().await?;
+
+    let args: Vec<String> = std::env::args().collect();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+    };
+
+    // Use a monitor configuration other than ~/.config/genmcp/monitor.toml
+    if let Some(path) = flag("--config") {
+        app.load_config(path)?;
+    }
+
+    // Initialize logging: --log-format, else $GENMCP_LOG_FORMAT, else [logging] in the config
+    let format = LogFormat::resolve(
+        flag("--log-format").map(String::as_str),
+        app.config.logging.format,
+    )?;
+    // Log to a file when --log-file or [logging] file names one; the flags
+    // override the configured rotation
+    let mut logging = app.config.logging.clone();
+    if let Some(path) = flag("--log-file") {
+        logging.file = Some(path.into());
+    }
+    if let Some(rotate) = flag("--log-rotate") {
+        logging.rotate = rotate.parse()?;
+    }
+    if let Some(keep) = flag("--keep") {
+        logging.keep = keep.parse()?;
+    }
+    init_logging(
+        format,
+        EnvFilter::from_default_env(),
+        logging.log_file().as_ref(),
+    )?;
 
     info!("Starting MCP TUI - Next Generation Interface");
 
-    // Initialize the application
-    let mut app = App::new().await?;
+    // Restore exactly what an operator saw from a recorded event log: mcp-tui --replay FILE
+    if let Some(path) = flag("--replay") {
+        let events = app.replay(path)?;
+        info!("Replayed {} events from {}", events, path);
+    }
+
+    // Open a captured session for offline inspection: mcp-tui --session FILE
+    if let Some(path) = flag("--session") {
+        let entries = app.import_session(path)?;
+        info!("Imported {} session entries from {}", entries, path);
+    }
+
+    // Browse and export sessions somewhere other than the working directory
+    if let Some(dir) = flag("--sessions-dir") {
+        app.sessions_dir = dir.into();
+    }
+
+    // Review why the LLM router chose each tool: mcp-tui --traces FILE
+    if let Some(path) = flag("--traces") {
+        let traces = app.import_traces(path)?;
+        info!("Imported {} decision traces from {}", traces, path);
+    }
 
     // Run the TUI
-    if let Err(e) = app.run().await {
+    let result = app.run().await;
+
+    // Keep the event log for later replay: mcp-tui --record FILE
+    if let Some(path) = flag("--record") {
+        app.save_event_log(path)?;
+        info!("Recorded {} events to {}", app.log.len(), path);
+    }
+
+    if let Err(e) = result {
         error!("Application error: {}", e);
         return Err(e);
     }
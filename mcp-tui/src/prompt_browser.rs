@@ -0,0 +1,654 @@
+//! Browser for the prompts connected servers offer.
+//!
+//! Opening it lists the prompts of every connected server; typing filters
+//! them by name. `Enter` opens the selected prompt's arguments, where `Tab`
+//! asks the server to complete the selected argument with
+//! `completion/complete` and cycles through its suggestions. `Enter` then
+//! fetches the prompt with `prompts/get` and previews its messages by role.
+//! From the preview `Enter` sends the assembled prompt on like a typed query
+//! and `ctrl+y` copies it to the clipboard.
+
+use base64::Engine;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::content;
+use crate::events::Event;
+use crate::theme;
+
+/// An argument a prompt takes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: Option<String>,
+    pub required: bool,
+}
+
+/// A prompt offered by a server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptEntry {
+    /// Id of the server offering it
+    pub server: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<PromptArgument>,
+}
+
+impl PromptEntry {
+    /// An entry for a prompt from a `prompts/list` result
+    pub fn from_listing(server: &str, prompt: &Value) -> Option<Self> {
+        let arguments = prompt["arguments"]
+            .as_array()
+            .map(|arguments| {
+                arguments
+                    .iter()
+                    .filter_map(|argument| {
+                        Some(PromptArgument {
+                            name: argument["name"].as_str()?.to_string(),
+                            description: argument["description"].as_str().map(str::to_string),
+                            required: argument["required"].as_bool().unwrap_or(false),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            server: server.to_string(),
+            name: prompt["name"].as_str()?.to_string(),
+            description: prompt["description"].as_str().map(str::to_string),
+            arguments,
+        })
+    }
+}
+
+/// Where the browser is, from picking a prompt to previewing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PromptStage {
+    #[default]
+    List,
+    Arguments,
+    Preview,
+}
+
+/// Filterable list of prompts beside the arguments or preview of one
+#[derive(Debug, Clone, Default)]
+pub struct PromptBrowser {
+    /// Only names containing this, ignoring case, are listed
+    pub filter: String,
+    /// Position in the filtered list
+    selected: usize,
+    stage: PromptStage,
+    /// The prompt whose arguments are being filled
+    open: Option<PromptEntry>,
+    /// Value of each of its arguments, in order
+    values: Vec<String>,
+    field: usize,
+    /// Suggestions for the selected argument and the one last applied
+    completions: Vec<String>,
+    completion: Option<usize>,
+    error: Option<String>,
+    /// Arguments accepted by `Enter`, waiting for `prompts/get`
+    submission: Option<Value>,
+    /// Response to `prompts/get`
+    response: Option<Value>,
+    preview_page: usize,
+}
+
+impl PromptBrowser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stage(&self) -> PromptStage {
+        self.stage
+    }
+
+    /// Positions in `prompts` of the entries matching the filter
+    pub fn visible(&self, prompts: &[PromptEntry]) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        prompts
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.name.to_lowercase().contains(&filter))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The selected entry, if any match the filter
+    pub fn selected<'a>(&self, prompts: &'a [PromptEntry]) -> Option<&'a PromptEntry> {
+        let visible = self.visible(prompts);
+        let position = self.selected.min(visible.len().checked_sub(1)?);
+        prompts.get(visible[position])
+    }
+
+    /// Whether the selected argument has suggestions to cycle through
+    pub fn has_completions(&self) -> bool {
+        !self.completions.is_empty()
+    }
+
+    /// Server and `completion/complete` params for the selected argument
+    pub fn completion_request(&self) -> Option<(String, Value)> {
+        let prompt = self.open.as_ref()?;
+        let argument = prompt.arguments.get(self.field)?;
+        Some((
+            prompt.server.clone(),
+            serde_json::json!({
+                "ref": { "type": "ref/prompt", "name": prompt.name },
+                "argument": { "name": argument.name, "value": self.values[self.field] },
+                "context": { "arguments": self.filled() },
+            }),
+        ))
+    }
+
+    /// Server, prompt and arguments to send with `prompts/get`, once accepted
+    pub fn request(&self) -> Option<(String, String, Value)> {
+        let prompt = self.open.as_ref()?;
+        let arguments = self.submission.clone()?;
+        Some((prompt.server.clone(), prompt.name.clone(), arguments))
+    }
+
+    /// Offer suggestions for the selected argument, applying the first
+    pub fn set_completions(&mut self, values: Vec<String>) {
+        self.completions = values;
+        self.completion = None;
+        self.next_completion();
+    }
+
+    /// Show the response to `prompts/get`
+    pub fn set_response(&mut self, response: Value) {
+        self.submission = None;
+        self.response = Some(response);
+        self.preview_page = 0;
+        self.stage = PromptStage::Preview;
+    }
+
+    /// The previewed messages as plain text, one `role: text` paragraph each
+    pub fn assembled(&self) -> Option<String> {
+        let messages = self.response.as_ref()?.pointer("/result/messages")?;
+        let paragraphs: Vec<String> = messages
+            .as_array()?
+            .iter()
+            .map(|message| {
+                let content = &message["content"];
+                let text = match content["type"].as_str() {
+                    Some("text") => content["text"].as_str().unwrap_or_default().to_string(),
+                    Some("resource") => content["resource"]["text"]
+                        .as_str()
+                        .or_else(|| content["resource"]["uri"].as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    Some(other) => format!(
+                        "[{} {}]",
+                        other,
+                        content["mimeType"].as_str().unwrap_or_default()
+                    ),
+                    None => content.to_string(),
+                };
+                format!("{}: {}", message["role"].as_str().unwrap_or("user"), text)
+            })
+            .collect();
+        Some(paragraphs.join("\n\n"))
+    }
+
+    /// Apply a key, returning whether the view changed.
+    ///
+    /// `Esc` steps back from the preview to the arguments and from there to
+    /// the list, where it clears the filter; once it is empty the caller
+    /// closes the browser.
+    pub fn handle(&mut self, event: &Event, prompts: &[PromptEntry]) -> bool {
+        match self.stage {
+            PromptStage::List => self.handle_list(event, prompts),
+            PromptStage::Arguments => self.handle_arguments(event),
+            PromptStage::Preview => match event {
+                Event::PageUp => {
+                    let page = self.preview_page.saturating_sub(1);
+                    std::mem::replace(&mut self.preview_page, page) != page
+                }
+                Event::PageDown => {
+                    self.preview_page += 1;
+                    true
+                }
+                Event::Quit => {
+                    self.stage = PromptStage::Arguments;
+                    if self.open.as_ref().is_some_and(|p| p.arguments.is_empty()) {
+                        self.close_prompt();
+                    }
+                    true
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn handle_list(&mut self, event: &Event, prompts: &[PromptEntry]) -> bool {
+        let last = self.visible(prompts).len().saturating_sub(1);
+        match event {
+            Event::Up => self.select(self.selected.min(last).saturating_sub(1)),
+            Event::Down => self.select((self.selected + 1).min(last)),
+            Event::Input(c) => {
+                self.filter.push(*c);
+                self.select(0);
+                true
+            }
+            Event::Backspace => {
+                self.select(0);
+                self.filter.pop().is_some()
+            }
+            Event::Quit if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.select(0);
+                true
+            }
+            Event::Enter => {
+                let Some(prompt) = self.selected(prompts).cloned() else {
+                    return false;
+                };
+                self.values = vec![String::new(); prompt.arguments.len()];
+                self.field = 0;
+                self.error = None;
+                self.response = None;
+                self.stage = PromptStage::Arguments;
+                let takes_arguments = !prompt.arguments.is_empty();
+                self.open = Some(prompt);
+                if !takes_arguments {
+                    self.submit();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn handle_arguments(&mut self, event: &Event) -> bool {
+        let fields = self.values.len();
+        match event {
+            Event::Up if self.field > 0 => self.focus(self.field - 1),
+            Event::Down if self.field + 1 < fields => self.focus(self.field + 1),
+            Event::Tab => self.next_completion(),
+            Event::Input(c) if fields > 0 => {
+                self.values[self.field].push(*c);
+                self.edited();
+                true
+            }
+            Event::Backspace if fields > 0 => {
+                let changed = self.values[self.field].pop().is_some();
+                self.edited();
+                changed
+            }
+            Event::Enter => {
+                self.submit();
+                true
+            }
+            Event::Quit => {
+                self.close_prompt();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Accept the arguments unless a required one is empty
+    fn submit(&mut self) {
+        let Some(prompt) = &self.open else {
+            return;
+        };
+        let missing: Vec<&str> = prompt
+            .arguments
+            .iter()
+            .zip(&self.values)
+            .filter(|(argument, value)| argument.required && value.is_empty())
+            .map(|(argument, _)| argument.name.as_str())
+            .collect();
+        if missing.is_empty() {
+            self.error = None;
+            self.submission = Some(Value::Object(self.filled()));
+        } else {
+            self.error = Some(format!("Required: {}", missing.join(", ")));
+            self.submission = None;
+        }
+    }
+
+    /// Arguments given a value so far
+    fn filled(&self) -> Map<String, Value> {
+        let Some(prompt) = &self.open else {
+            return Map::new();
+        };
+        prompt
+            .arguments
+            .iter()
+            .zip(&self.values)
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(argument, value)| (argument.name.clone(), Value::String(value.clone())))
+            .collect()
+    }
+
+    fn next_completion(&mut self) -> bool {
+        if self.completions.is_empty() {
+            return false;
+        }
+        let next = self
+            .completion
+            .map_or(0, |i| (i + 1) % self.completions.len());
+        self.completion = Some(next);
+        self.values[self.field] = self.completions[next].clone();
+        true
+    }
+
+    fn edited(&mut self) {
+        self.completions.clear();
+        self.completion = None;
+        self.error = None;
+    }
+
+    fn focus(&mut self, field: usize) -> bool {
+        self.field = field;
+        self.edited();
+        true
+    }
+
+    fn close_prompt(&mut self) {
+        self.stage = PromptStage::List;
+        self.open = None;
+        self.values.clear();
+        self.submission = None;
+        self.edited();
+    }
+
+    fn select(&mut self, selected: usize) -> bool {
+        std::mem::replace(&mut self.selected, selected) != selected
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, prompts: &[PromptEntry]) {
+        let theme = theme::current();
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .split(area);
+
+        let visible = self.visible(prompts);
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|&i| {
+                let entry = &prompts[i];
+                ListItem::new(vec![
+                    Line::from(Span::styled(
+                        entry.name.clone(),
+                        Style::default().fg(theme.text),
+                    )),
+                    Line::from(Span::styled(
+                        format!("  {}", entry.server),
+                        Style::default().fg(theme.muted),
+                    )),
+                ])
+            })
+            .collect();
+        let mut title = format!("Prompts {}/{}", visible.len(), prompts.len());
+        if !self.filter.is_empty() {
+            title.push_str(&format!(" · name ~ {}", self.filter));
+        }
+        let mut state = ListState::default();
+        if !visible.is_empty() {
+            state.select(Some(self.selected.min(visible.len() - 1)));
+        }
+        let mut block = Block::default().title(title).borders(Borders::ALL);
+        if self.stage == PromptStage::List {
+            block = block.border_style(Style::default().fg(theme.accent));
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, panes[0], &mut state);
+
+        let block = Block::default().borders(Borders::ALL).border_style(
+            if self.stage == PromptStage::List {
+                Style::default()
+            } else {
+                Style::default().fg(theme.accent)
+            },
+        );
+        let inner = block.inner(panes[1]);
+        let (title, lines) = match (self.stage, &self.response) {
+            (PromptStage::Preview, Some(response)) => {
+                let page = content::paginate(
+                    content::render_payload(response, inner.width),
+                    self.preview_page,
+                    usize::from(inner.height),
+                );
+                (
+                    format!(
+                        "Preview {}/{} (Enter send · ctrl+y copy · Esc back)",
+                        page.index + 1,
+                        page.count
+                    ),
+                    page.lines,
+                )
+            }
+            (PromptStage::Arguments, _) => (
+                "Arguments (Tab complete · Enter get · Esc back)".to_string(),
+                self.argument_lines(),
+            ),
+            _ => (
+                "Prompt (Enter fill arguments · Esc close)".to_string(),
+                self.selected(prompts)
+                    .map(summary_lines)
+                    .unwrap_or_default(),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block.title(title))
+                .wrap(Wrap { trim: false }),
+            panes[1],
+        );
+    }
+
+    fn argument_lines(&self) -> Vec<Line<'static>> {
+        let theme = theme::current();
+        let Some(prompt) = &self.open else {
+            return Vec::new();
+        };
+        let mut lines = vec![Line::from(Span::styled(
+            prompt.name.clone(),
+            Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+        ))];
+        if prompt.arguments.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No arguments; fetching",
+                Style::default().fg(theme.muted),
+            )));
+        }
+        for (i, (argument, value)) in prompt.arguments.iter().zip(&self.values).enumerate() {
+            let label = format!(
+                "{}{}",
+                argument.name,
+                if argument.required { "*" } else { " " }
+            );
+            let mut value_style = Style::default();
+            if i == self.field {
+                value_style = value_style.add_modifier(Modifier::REVERSED);
+            }
+            lines.push(Line::from(vec![
+                Span::styled(
+                    label,
+                    Style::default().fg(if argument.required {
+                        theme.warning
+                    } else {
+                        theme.secondary
+                    }),
+                ),
+                Span::styled(format!(" {} ", value), value_style),
+                Span::styled(
+                    argument
+                        .description
+                        .as_ref()
+                        .map(|description| format!("  {}", description))
+                        .unwrap_or_default(),
+                    Style::default().fg(theme.muted),
+                ),
+            ]));
+            if i == self.field && !self.completions.is_empty() {
+                let spans: Vec<Span> = self
+                    .completions
+                    .iter()
+                    .enumerate()
+                    .map(|(j, value)| {
+                        let style = if Some(j) == self.completion {
+                            Style::default()
+                                .fg(theme.accent)
+                                .add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default().fg(theme.muted)
+                        };
+                        Span::styled(format!("  {}", value), style)
+                    })
+                    .collect();
+                lines.push(Line::from(spans));
+            }
+        }
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::default().fg(theme.error),
+            )));
+        }
+        lines
+    }
+}
+
+/// Description and arguments of a prompt, shown before it is opened
+fn summary_lines(prompt: &PromptEntry) -> Vec<Line<'static>> {
+    let theme = theme::current();
+    let mut lines = vec![Line::from(Span::styled(
+        prompt.description.clone().unwrap_or_default(),
+        Style::default().fg(theme.text),
+    ))];
+    for argument in &prompt.arguments {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(
+                    "  {}{}",
+                    argument.name,
+                    if argument.required { "*" } else { "" }
+                ),
+                Style::default().fg(theme.secondary),
+            ),
+            Span::styled(
+                argument
+                    .description
+                    .as_ref()
+                    .map(|description| format!("  {}", description))
+                    .unwrap_or_default(),
+                Style::default().fg(theme.muted),
+            ),
+        ]));
+    }
+    lines
+}
+
+/// Escape sequence that has the terminal put `text` on the clipboard (OSC 52)
+pub fn clipboard_sequence(text: &str) -> String {
+    format!(
+        "\x1b]52;c;{}\x07",
+        base64::engine::general_purpose::STANDARD.encode(text)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn prompts() -> Vec<PromptEntry> {
+        [
+            json!({
+                "name": "code_review",
+                "description": "Review a change",
+                "arguments": [
+                    { "name": "language", "required": true },
+                    { "name": "focus" }
+                ]
+            }),
+            json!({ "name": "greeting" }),
+        ]
+        .iter()
+        .filter_map(|prompt| PromptEntry::from_listing("profile:demo", prompt))
+        .collect()
+    }
+
+    fn press(browser: &mut PromptBrowser, prompts: &[PromptEntry], events: &[Event]) {
+        for event in events {
+            browser.handle(event, prompts);
+        }
+    }
+
+    #[test]
+    fn test_arguments_complete_and_submit() {
+        let prompts = prompts();
+        let mut browser = PromptBrowser::new();
+        press(&mut browser, &prompts, &[Event::Enter, Event::Enter]);
+        assert_eq!(browser.stage(), PromptStage::Arguments);
+        assert!(browser.request().is_none());
+
+        press(&mut browser, &prompts, &[Event::Input('r')]);
+        let (server, params) = browser.completion_request().unwrap();
+        assert_eq!(server, "profile:demo");
+        assert_eq!(
+            params["argument"],
+            json!({ "name": "language", "value": "r" })
+        );
+
+        browser.set_completions(vec!["rust".to_string(), "ruby".to_string()]);
+        press(&mut browser, &prompts, &[Event::Tab, Event::Enter]);
+        assert_eq!(
+            browser.request(),
+            Some((
+                "profile:demo".to_string(),
+                "code_review".to_string(),
+                json!({ "language": "ruby" })
+            ))
+        );
+
+        // Typing drops the suggestions
+        press(&mut browser, &prompts, &[Event::Down, Event::Input('x')]);
+        assert!(!browser.has_completions());
+    }
+
+    #[test]
+    fn test_preview_assembles_messages_by_role() {
+        let prompts = prompts();
+        let mut browser = PromptBrowser::new();
+        press(&mut browser, &prompts, &[Event::Down, Event::Enter]);
+        assert_eq!(
+            browser
+                .request()
+                .map(|(_, name, arguments)| (name, arguments)),
+            Some(("greeting".to_string(), json!({})))
+        );
+
+        browser.set_response(json!({
+            "result": {
+                "messages": [
+                    { "role": "user", "content": { "type": "text", "text": "Hello" } },
+                    { "role": "assistant", "content": { "type": "image", "data": "", "mimeType": "image/png" } }
+                ]
+            }
+        }));
+        assert_eq!(browser.stage(), PromptStage::Preview);
+        assert_eq!(
+            browser.assembled().unwrap(),
+            "user: Hello\n\nassistant: [image image/png]"
+        );
+
+        // A prompt without arguments goes straight back to the list
+        press(&mut browser, &prompts, &[Event::Quit]);
+        assert_eq!(browser.stage(), PromptStage::List);
+        assert_eq!(clipboard_sequence("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
@@ -1,11 +1,13 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
+use crate::theme;
+
 pub struct QueryInput {
     placeholder: String,
 }
@@ -20,13 +22,13 @@ impl QueryInput {
     pub fn render(&self, frame: &mut Frame, area: Rect, value: &str, focused: bool) {
         let mut block = Block::default().title("Query").borders(Borders::ALL);
         if focused {
-            block = block.border_style(Style::default().fg(Color::Cyan));
+            block = block.border_style(Style::default().fg(theme::current().accent));
         }
 
         let display = if value.is_empty() {
             Line::from(Span::styled(
                 &self.placeholder,
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().muted),
             ))
         } else {
             Line::from(Span::raw(value.to_string()))
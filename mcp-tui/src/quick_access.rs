@@ -1,11 +1,13 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
 
+use crate::theme;
+
 #[derive(Clone, Debug)]
 pub struct QuickAction {
     pub label: String,
@@ -67,7 +69,7 @@ impl QuickAccess {
             .title("Quick Actions")
             .borders(Borders::ALL);
         if focused {
-            block = block.border_style(Style::default().fg(Color::Cyan));
+            block = block.border_style(Style::default().fg(theme::current().accent));
         }
         let items: Vec<ListItem> = self.items.iter().map(render_item).collect();
 
@@ -93,13 +95,13 @@ fn render_item(action: &QuickAction) -> ListItem<'static> {
     let lines = vec![
         Line::from(Span::styled(
             action.label.clone(),
-            Style::default().fg(Color::White),
+            Style::default().fg(theme::current().text),
         )),
         Line::from(vec![
             Span::raw("  "),
             Span::styled(
                 action.description.clone(),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().muted),
             ),
         ]),
     ];
@@ -123,5 +125,35 @@ fn default_actions() -> Vec<QuickAction> {
             description: "Start a new interactive session".to_string(),
             command: "open_session".to_string(),
         },
+        QuickAction {
+            label: "Discover local servers".to_string(),
+            description: "Browse the network for mDNS-advertised MCP servers".to_string(),
+            command: "discover_servers".to_string(),
+        },
+        QuickAction {
+            label: "Export session".to_string(),
+            description: "Save captured traffic as a session archive".to_string(),
+            command: "export_session".to_string(),
+        },
+        QuickAction {
+            label: "Inspect traffic".to_string(),
+            description: "Filter, fold and diff captured messages (Ctrl+T)".to_string(),
+            command: "inspect_traffic".to_string(),
+        },
+        QuickAction {
+            label: "Browse saved sessions".to_string(),
+            description: "Search past sessions and replay requests (Ctrl+O)".to_string(),
+            command: "browse_sessions".to_string(),
+        },
+        QuickAction {
+            label: "Show server logs".to_string(),
+            description: "Follow and filter log messages from servers (Ctrl+L)".to_string(),
+            command: "server_logs".to_string(),
+        },
+        QuickAction {
+            label: "Clear activity feed".to_string(),
+            description: "Remove all entries (Ctrl+Z to undo)".to_string(),
+            command: "clear_activity".to_string(),
+        },
     ]
 }
@@ -0,0 +1,261 @@
+//! Browser for the resources connected servers offer.
+//!
+//! Opening it lists the resources of every connected server. Typing filters
+//! them by URI, `Enter` reads the selected one with `resources/read` and
+//! previews its contents, and `ctrl+s` subscribes to or unsubscribes from it.
+//! Each `notifications/resources/updated` for a resource adds to a badge next
+//! to it until the resource is read again.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::content;
+use crate::events::Event;
+use crate::theme;
+
+/// A resource offered by a server, with what the browser learned about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceEntry {
+    /// Id of the server offering it
+    pub server: String,
+    pub uri: String,
+    pub name: String,
+    pub mime_type: Option<String>,
+    pub subscribed: bool,
+    /// `resources/updated` notifications since the resource was last read
+    pub updates: usize,
+    /// Response to the last `resources/read`
+    pub contents: Option<Value>,
+}
+
+impl ResourceEntry {
+    /// An entry for a resource from a `resources/list` result
+    pub fn from_listing(server: &str, resource: &Value) -> Option<Self> {
+        let uri = resource["uri"].as_str()?;
+        Some(Self {
+            server: server.to_string(),
+            uri: uri.to_string(),
+            name: resource["name"].as_str().unwrap_or(uri).to_string(),
+            mime_type: resource["mimeType"].as_str().map(str::to_string),
+            subscribed: false,
+            updates: 0,
+            contents: None,
+        })
+    }
+}
+
+/// Filterable list of resources beside a preview of the selected one
+#[derive(Debug, Clone, Default)]
+pub struct ResourceBrowser {
+    /// Only URIs containing this, ignoring case, are listed
+    pub filter: String,
+    /// Position in the filtered list
+    selected: usize,
+    /// Page of the preview
+    preview_page: usize,
+}
+
+impl ResourceBrowser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Positions in `resources` of the entries matching the filter
+    pub fn visible(&self, resources: &[ResourceEntry]) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        resources
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.uri.to_lowercase().contains(&filter))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The selected entry, if any match the filter
+    pub fn selected<'a>(&self, resources: &'a [ResourceEntry]) -> Option<&'a ResourceEntry> {
+        let visible = self.visible(resources);
+        let position = self.selected.min(visible.len().checked_sub(1)?);
+        resources.get(visible[position])
+    }
+
+    /// Apply a key, returning whether the view changed.
+    ///
+    /// `Esc` clears the filter; once it is empty the caller closes the browser.
+    pub fn handle(&mut self, event: &Event, resources: &[ResourceEntry]) -> bool {
+        let last = self.visible(resources).len().saturating_sub(1);
+        match event {
+            Event::Up => self.select(self.selected.min(last).saturating_sub(1)),
+            Event::Down => self.select((self.selected + 1).min(last)),
+            Event::PageUp => {
+                let page = self.preview_page.saturating_sub(1);
+                std::mem::replace(&mut self.preview_page, page) != page
+            }
+            Event::PageDown => {
+                self.preview_page += 1;
+                true
+            }
+            Event::Input(c) => {
+                self.filter.push(*c);
+                self.select(0);
+                true
+            }
+            Event::Backspace => {
+                self.select(0);
+                self.filter.pop().is_some()
+            }
+            Event::Quit if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.select(0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn select(&mut self, selected: usize) -> bool {
+        if selected == self.selected {
+            return false;
+        }
+        self.selected = selected;
+        self.preview_page = 0;
+        true
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, resources: &[ResourceEntry]) {
+        let theme = theme::current();
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)].as_ref())
+            .split(area);
+
+        let visible = self.visible(resources);
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|&i| {
+                let entry = &resources[i];
+                let mut spans = vec![
+                    Span::styled(
+                        if entry.subscribed { "● " } else { "  " },
+                        Style::default().fg(theme.accent),
+                    ),
+                    Span::styled(entry.uri.clone(), Style::default().fg(theme.text)),
+                ];
+                if entry.updates > 0 {
+                    spans.push(Span::styled(
+                        format!(" [{} new]", entry.updates),
+                        Style::default()
+                            .fg(theme.warning)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+                let detail = match &entry.mime_type {
+                    Some(mime) => format!("    {} · {} · {}", entry.name, mime, entry.server),
+                    None => format!("    {} · {}", entry.name, entry.server),
+                };
+                ListItem::new(vec![
+                    Line::from(spans),
+                    Line::from(Span::styled(detail, Style::default().fg(theme.muted))),
+                ])
+            })
+            .collect();
+
+        let mut title = format!("Resources {}/{}", visible.len(), resources.len());
+        if !self.filter.is_empty() {
+            title.push_str(&format!(" · uri ~ {}", self.filter));
+        }
+        title.push_str(" (type to filter, Enter read, ctrl+s subscribe, Esc close)");
+        let mut state = ListState::default();
+        if !visible.is_empty() {
+            state.select(Some(self.selected.min(visible.len() - 1)));
+        }
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.accent)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, panes[0], &mut state);
+
+        let block = Block::default().borders(Borders::ALL);
+        let inner = block.inner(panes[1]);
+        let (title, lines) = match self.selected(resources) {
+            Some(ResourceEntry {
+                contents: Some(contents),
+                ..
+            }) => {
+                let page = content::paginate(
+                    content::render_payload(contents, inner.width),
+                    self.preview_page,
+                    usize::from(inner.height),
+                );
+                (
+                    format!("Preview {}/{} (PgUp/PgDn)", page.index + 1, page.count),
+                    page.lines,
+                )
+            }
+            Some(_) => (
+                "Preview".to_string(),
+                vec![Line::from(Span::styled(
+                    "Enter reads the resource",
+                    Style::default().fg(theme.muted),
+                ))],
+            ),
+            None => ("Preview".to_string(), Vec::new()),
+        };
+        frame.render_widget(Paragraph::new(lines).block(block.title(title)), panes[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entries() -> Vec<ResourceEntry> {
+        [
+            json!({"uri": "file:///notes.md", "name": "notes", "mimeType": "text/markdown"}),
+            json!({"uri": "file:///config.json"}),
+            json!({"uri": "db://users/42", "name": "user 42"}),
+            json!({"name": "no uri"}),
+        ]
+        .iter()
+        .filter_map(|resource| ResourceEntry::from_listing("profile:fs", resource))
+        .collect()
+    }
+
+    #[test]
+    fn test_filter_by_uri_and_select() {
+        let resources = entries();
+        assert_eq!(resources.len(), 3);
+        assert_eq!(resources[1].name, "file:///config.json");
+
+        let mut browser = ResourceBrowser::new();
+        assert!(browser.handle(&Event::Down, &resources));
+        assert!(browser.handle(&Event::Down, &resources));
+        assert!(!browser.handle(&Event::Down, &resources));
+        assert_eq!(browser.selected(&resources).unwrap().uri, "db://users/42");
+
+        for c in "FILE".chars() {
+            browser.handle(&Event::Input(c), &resources);
+        }
+        assert_eq!(browser.visible(&resources), vec![0, 1]);
+        assert_eq!(
+            browser.selected(&resources).unwrap().uri,
+            "file:///notes.md"
+        );
+
+        // Esc clears the filter before the browser closes
+        assert!(browser.handle(&Event::Quit, &resources));
+        assert_eq!(browser.visible(&resources).len(), 3);
+        assert!(!browser.handle(&Event::Quit, &resources));
+    }
+}
@@ -0,0 +1,199 @@
+//! Panel for the log messages servers send.
+//!
+//! Every connected registry profile streams its `notifications/message` log
+//! entries here. `←`/`→` choose the least severe level shown, and `Enter`
+//! asks every connected server to send only that level and above.
+
+use mcp_core::messages::LogLevel;
+use mcp_core::server_log::LogEntry;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::events::Event;
+use crate::theme;
+
+/// A log entry and the server that sent it
+pub type ServerLogLine = (String, LogEntry);
+
+/// Scrollable list of server log entries, filtered by level
+#[derive(Debug, Clone)]
+pub struct ServerLogView {
+    pub min_level: LogLevel,
+    /// Position in the filtered list; `None` follows the newest entry
+    selected: Option<usize>,
+}
+
+impl Default for ServerLogView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerLogView {
+    pub fn new() -> Self {
+        Self {
+            min_level: LogLevel::Debug,
+            selected: None,
+        }
+    }
+
+    /// The entries at or above the minimum level, oldest first
+    pub fn visible<'a>(&self, logs: &'a [ServerLogLine]) -> Vec<&'a ServerLogLine> {
+        logs.iter()
+            .filter(|(_, entry)| entry.is_at_least(&self.min_level))
+            .collect()
+    }
+
+    /// Apply a key, returning whether the view changed
+    pub fn handle(&mut self, event: &Event, logs: &[ServerLogLine]) -> bool {
+        let len = self.visible(logs).len();
+        let last = len.saturating_sub(1);
+        match event {
+            Event::Up => {
+                let current = self.selected.unwrap_or(last);
+                self.select(Some(current.saturating_sub(1)))
+            }
+            Event::Down => match self.selected {
+                Some(position) if position + 1 >= last => self.select(None),
+                Some(position) => self.select(Some(position + 1)),
+                None => false,
+            },
+            Event::Left => self.step_level(-1),
+            Event::Right => self.step_level(1),
+            _ => false,
+        }
+    }
+
+    fn select(&mut self, selected: Option<usize>) -> bool {
+        let changed = selected != self.selected;
+        self.selected = selected;
+        changed
+    }
+
+    fn step_level(&mut self, step: isize) -> bool {
+        let levels = LogLevel::all();
+        let current = levels
+            .iter()
+            .position(|level| *level == self.min_level)
+            .unwrap_or(0);
+        let next = current.saturating_add_signed(step).min(levels.len() - 1);
+        if next == current {
+            return false;
+        }
+        self.min_level = levels[next].clone();
+        self.selected = None;
+        true
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, logs: &[ServerLogLine]) {
+        let visible = self.visible(logs);
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|(server, entry)| {
+                let mut spans = vec![
+                    Span::styled(
+                        format!("[{}] ", entry.timestamp.format("%H:%M:%S")),
+                        Style::default().fg(theme::current().muted),
+                    ),
+                    Span::styled(
+                        format!("{:<9} ", entry.level),
+                        Style::default().fg(level_color(&entry.level)),
+                    ),
+                    Span::styled(
+                        format!("{} ", server),
+                        Style::default().fg(theme::current().accent),
+                    ),
+                ];
+                if let Some(logger) = &entry.logger {
+                    spans.push(Span::styled(
+                        format!("[{}] ", logger),
+                        Style::default().fg(theme::current().highlight),
+                    ));
+                }
+                spans.push(Span::raw(entry.message.clone()));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        if !visible.is_empty() {
+            state.select(Some(
+                self.selected
+                    .unwrap_or(visible.len() - 1)
+                    .min(visible.len() - 1),
+            ));
+        }
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "Server logs {}/{} · level ≥ {} (←/→ filter, Enter send to servers, Esc close)",
+                        visible.len(),
+                        logs.len(),
+                        self.min_level
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme::current().accent)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+fn level_color(level: &LogLevel) -> Color {
+    match level {
+        LogLevel::Debug => theme::current().muted,
+        LogLevel::Info | LogLevel::Notice => theme::current().text,
+        LogLevel::Warning => theme::current().warning,
+        LogLevel::Error => theme::current().error,
+        LogLevel::Critical | LogLevel::Alert | LogLevel::Emergency => theme::current().critical,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::messages::LoggingNotification;
+    use serde_json::json;
+
+    fn line(level: LogLevel, message: &str) -> ServerLogLine {
+        (
+            "fs".to_string(),
+            LogEntry::from(LoggingNotification::new(level, json!(message))),
+        )
+    }
+
+    #[test]
+    fn test_level_filter_and_follow() {
+        let logs = vec![
+            line(LogLevel::Debug, "scanning"),
+            line(LogLevel::Warning, "disk almost full"),
+            line(LogLevel::Error, "write failed"),
+        ];
+        let mut view = ServerLogView::new();
+        assert_eq!(view.visible(&logs).len(), 3);
+
+        assert!(!view.handle(&Event::Left, &logs));
+        assert!(view.handle(&Event::Right, &logs));
+        assert_eq!(view.min_level, LogLevel::Info);
+        view.min_level = LogLevel::Warning;
+        let messages: Vec<_> = view
+            .visible(&logs)
+            .iter()
+            .map(|(_, entry)| entry.message.as_str())
+            .collect();
+        assert_eq!(messages, ["disk almost full", "write failed"]);
+
+        // Scrolling up leaves the newest entry; scrolling back down follows it again
+        assert!(view.handle(&Event::Up, &logs));
+        assert_eq!(view.selected, Some(0));
+        assert!(view.handle(&Event::Down, &logs));
+        assert_eq!(view.selected, None);
+        assert!(!view.handle(&Event::Down, &logs));
+    }
+}
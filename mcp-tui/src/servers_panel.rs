@@ -2,13 +2,14 @@ use std::collections::HashMap;
 
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
 
 use crate::components::Server;
+use crate::theme;
 
 pub struct ServersPanel {
     state: ListState,
@@ -41,6 +42,14 @@ impl ServersPanel {
         self.state.select(Some(prev));
     }
 
+    /// The highlighted server, in the panel's name order
+    pub fn selected<'a>(&self, servers: &'a HashMap<String, Server>) -> Option<&'a Server> {
+        let mut items: Vec<&Server> = servers.values().collect();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        let idx = self.state.selected()?;
+        items.get(idx.min(items.len().saturating_sub(1))).copied()
+    }
+
     pub fn render(
         &mut self,
         frame: &mut Frame,
@@ -54,7 +63,7 @@ impl ServersPanel {
         let rendered: Vec<ListItem> = items.iter().map(|server| render_item(server)).collect();
         let mut block = Block::default().title("Servers").borders(Borders::ALL);
         if focused {
-            block = block.border_style(Style::default().fg(Color::Cyan));
+            block = block.border_style(Style::default().fg(theme::current().accent));
         }
         let mut state = self.state.clone();
         if let Some(selected) = state.selected() {
@@ -69,7 +78,10 @@ impl ServersPanel {
 fn render_item(server: &Server) -> ListItem<'static> {
     let content = vec![
         Line::from(vec![
-            Span::styled(server.name.clone(), Style::default().fg(Color::White)),
+            Span::styled(
+                server.name.clone(),
+                Style::default().fg(theme::current().text),
+            ),
             Span::raw(" "),
             Span::styled(
                 format!("[{}]", server.status.label()),
@@ -80,7 +92,7 @@ fn render_item(server: &Server) -> ListItem<'static> {
             Span::styled("  ", Style::default()),
             Span::styled(
                 server.description.clone(),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme::current().muted),
             ),
         ]),
     ];
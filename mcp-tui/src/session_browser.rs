@@ -0,0 +1,449 @@
+//! Browser for saved sessions.
+//!
+//! Lists the session archives and JSONL recordings in the sessions directory,
+//! newest first. Typed text searches the entries of every session by method
+//! and message text; the session list shows how many entries match, and the
+//! entry list shows the matches in the selected session. Enter on a request
+//! replays it against the server selected in the Servers panel.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use mcp_common::{EntryKind, SessionArchive, SessionEntry, TrafficDirection};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::content;
+use crate::events::Event;
+use crate::theme;
+
+/// A session archive found on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub path: PathBuf,
+    pub modified: DateTime<Utc>,
+    pub archive: SessionArchive,
+}
+
+impl SavedSession {
+    /// File name shown in the list
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+/// Load every readable session in `dir`, newest first; other files are skipped
+pub fn scan(dir: &Path) -> Vec<SavedSession> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<SavedSession> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "json" || ext == "jsonl")
+        })
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            match SessionArchive::load(&path) {
+                Ok(archive) => Some(SavedSession {
+                    path,
+                    modified: modified.into(),
+                    archive,
+                }),
+                Err(e) => {
+                    debug!("Skipping {}: {:#}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.modified));
+    sessions
+}
+
+/// Whether an entry matches a search, by method or by message text
+pub fn entry_matches(entry: &SessionEntry, query: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return true;
+    }
+    let method = entry.method.as_deref().unwrap_or_default().to_lowercase();
+    if method.contains(&query) {
+        return true;
+    }
+    std::iter::once(&entry.request)
+        .chain(entry.response.as_ref())
+        .any(|message| message.payload.to_string().to_lowercase().contains(&query))
+}
+
+/// Which list has the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserPane {
+    Sessions,
+    Entries,
+}
+
+/// Saved-session browser shown over the main panels
+#[derive(Debug, Clone)]
+pub struct SessionBrowser {
+    pub sessions: Vec<SavedSession>,
+    pub query: String,
+    pub pane: BrowserPane,
+    session: usize,
+    entry: usize,
+}
+
+impl SessionBrowser {
+    pub fn new(sessions: Vec<SavedSession>) -> Self {
+        Self {
+            sessions,
+            query: String::new(),
+            pane: BrowserPane::Sessions,
+            session: 0,
+            entry: 0,
+        }
+    }
+
+    /// The session under the cursor
+    pub fn selected_session(&self) -> Option<&SavedSession> {
+        self.sessions.get(self.session)
+    }
+
+    /// Entries of the selected session that match the search
+    pub fn entries(&self) -> Vec<&SessionEntry> {
+        self.selected_session()
+            .map(|session| {
+                session
+                    .archive
+                    .entries
+                    .iter()
+                    .filter(|entry| entry_matches(entry, &self.query))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The entry under the cursor
+    pub fn selected_entry(&self) -> Option<&SessionEntry> {
+        let entries = self.entries();
+        entries
+            .get(self.entry.min(entries.len().saturating_sub(1)))
+            .copied()
+    }
+
+    /// Method and params of the selected client request, when it can be replayed
+    pub fn replayable(&self) -> Option<(String, Value)> {
+        if self.pane != BrowserPane::Entries {
+            return None;
+        }
+        let entry = self.selected_entry()?;
+        if entry.kind != EntryKind::Request || entry.direction != TrafficDirection::ClientToServer {
+            return None;
+        }
+        let params = entry
+            .request
+            .payload
+            .get("params")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+        Some((entry.method.clone()?, params))
+    }
+
+    /// Whether Esc should close the browser rather than go back
+    pub fn at_sessions(&self) -> bool {
+        self.pane == BrowserPane::Sessions
+    }
+
+    /// Apply a key, returning whether anything changed
+    pub fn handle(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Up => match self.pane {
+                BrowserPane::Sessions => self.select_session(self.session.saturating_sub(1)),
+                BrowserPane::Entries => self.select_entry(self.entry.saturating_sub(1)),
+            },
+            Event::Down => match self.pane {
+                BrowserPane::Sessions => self.select_session(self.session + 1),
+                BrowserPane::Entries => self.select_entry(self.entry + 1),
+            },
+            Event::Right | Event::Enter if self.pane == BrowserPane::Sessions => {
+                if self.entries().is_empty() {
+                    return false;
+                }
+                self.pane = BrowserPane::Entries;
+                true
+            }
+            Event::Left | Event::Quit if self.pane == BrowserPane::Entries => {
+                self.pane = BrowserPane::Sessions;
+                true
+            }
+            Event::Input(c) => {
+                self.query.push(*c);
+                self.entry = 0;
+                true
+            }
+            Event::Backspace => {
+                self.entry = 0;
+                self.query.pop().is_some()
+            }
+            _ => false,
+        }
+    }
+
+    fn select_session(&mut self, index: usize) -> bool {
+        let index = index.min(self.sessions.len().saturating_sub(1));
+        let changed = index != self.session;
+        self.session = index;
+        self.entry = 0;
+        changed
+    }
+
+    fn select_entry(&mut self, index: usize) -> bool {
+        let index = index.min(self.entries().len().saturating_sub(1));
+        let changed = index != self.entry;
+        self.entry = index;
+        changed
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+            .split(area);
+        let search = Paragraph::new(self.query.as_str()).block(
+            Block::default()
+                .title("Search sessions by method or text (Esc close)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::current().accent)),
+        );
+        frame.render_widget(search, rows[0]);
+
+        // Fixed lengths, as percentages that fall on half a cell round either way
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Length(rows[1].width * 35 / 100),
+                    Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(rows[1]);
+        self.render_sessions(frame, columns[0]);
+
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(columns[1].height.div_ceil(2)),
+                    Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(columns[1]);
+        self.render_entries(frame, right[0]);
+        self.render_detail(frame, right[1]);
+    }
+
+    fn render_sessions(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .sessions
+            .iter()
+            .map(|session| {
+                let matches = session
+                    .archive
+                    .entries
+                    .iter()
+                    .filter(|entry| entry_matches(entry, &self.query))
+                    .count();
+                let count_style = if matches == 0 {
+                    Style::default().fg(theme::current().muted)
+                } else {
+                    Style::default().fg(theme::current().warning)
+                };
+                ListItem::new(vec![
+                    Line::from(Span::raw(session.name())),
+                    Line::from(vec![
+                        Span::styled(
+                            format!("  {}/{} entries", matches, session.archive.entries.len()),
+                            count_style,
+                        ),
+                        Span::styled(
+                            format!(" · {}", session.modified.format("%Y-%m-%d %H:%M")),
+                            Style::default().fg(theme::current().muted),
+                        ),
+                    ]),
+                ])
+            })
+            .collect();
+        let empty = items.is_empty();
+        let mut state = ListState::default();
+        state.select((!empty).then_some(self.session));
+        let mut block = Block::default()
+            .title(format!("Sessions ({})", self.sessions.len()))
+            .borders(Borders::ALL);
+        if self.pane == BrowserPane::Sessions {
+            block = block.border_style(Style::default().fg(theme::current().accent));
+        }
+        if empty {
+            let hint = Paragraph::new("No session archives found").block(block);
+            frame.render_widget(hint, area);
+            return;
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_entries(&self, frame: &mut Frame, area: Rect) {
+        let entries = self.entries();
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|entry| {
+                let arrow = match entry.direction {
+                    TrafficDirection::ClientToServer => "→",
+                    TrafficDirection::ServerToClient => "←",
+                };
+                let mut spans = vec![
+                    Span::styled(
+                        format!("[{}] ", entry.started.format("%H:%M:%S")),
+                        Style::default().fg(theme::current().muted),
+                    ),
+                    Span::raw(format!(
+                        "{} {}",
+                        arrow,
+                        entry.method.as_deref().unwrap_or("(response)")
+                    )),
+                ];
+                if let Some(time_ms) = entry.time_ms {
+                    spans.push(Span::styled(
+                        format!(" {:.0} ms", time_ms),
+                        Style::default().fg(theme::current().muted),
+                    ));
+                }
+                if entry.is_error() {
+                    spans.push(Span::styled(
+                        " [ERROR]",
+                        Style::default().fg(theme::current().error),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let mut state = ListState::default();
+        if self.pane == BrowserPane::Entries && !items.is_empty() {
+            state.select(Some(self.entry.min(items.len() - 1)));
+        }
+        let mut block = Block::default()
+            .title("Entries (Enter replay on selected server)")
+            .borders(Borders::ALL);
+        if self.pane == BrowserPane::Entries {
+            block = block.border_style(Style::default().fg(theme::current().accent));
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_detail(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title("Exchange")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().muted));
+        let Some(entry) = self
+            .selected_entry()
+            .filter(|_| self.pane == BrowserPane::Entries)
+        else {
+            frame.render_widget(block, area);
+            return;
+        };
+        let exchange = serde_json::json!({
+            "request": entry.request.payload,
+            "response": entry.response.as_ref().map(|response| &response.payload),
+        });
+        let lines = content::render_payload(&exchange, area.width.saturating_sub(2));
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_common::{ObservedMessage, ProxyId};
+    use serde_json::json;
+
+    fn session() -> SavedSession {
+        let proxy = ProxyId::new();
+        let messages = [
+            (
+                TrafficDirection::ClientToServer,
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+            ),
+            (
+                TrafficDirection::ServerToClient,
+                json!({"jsonrpc": "2.0", "id": 1, "result": {"tools": [{"name": "weather"}]}}),
+            ),
+            (
+                TrafficDirection::ClientToServer,
+                json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                       "params": {"name": "weather", "arguments": {"city": "Oslo"}}}),
+            ),
+        ]
+        .map(|(direction, payload)| ObservedMessage::new(proxy.clone(), direction, payload));
+        SavedSession {
+            path: PathBuf::from("/tmp/mcp-session-1.json"),
+            modified: Utc::now(),
+            archive: SessionArchive::new("test", "1").with_observed(&messages),
+        }
+    }
+
+    #[test]
+    fn test_search_matches_method_and_text() {
+        let mut browser = SessionBrowser::new(vec![session()]);
+        assert_eq!(browser.entries().len(), 2);
+
+        "oslo".chars().for_each(|c| {
+            browser.handle(&Event::Input(c));
+        });
+        let methods: Vec<_> = browser
+            .entries()
+            .iter()
+            .map(|entry| entry.method.clone())
+            .collect();
+        assert_eq!(methods, vec![Some("tools/call".to_string())]);
+
+        browser.query = "weather".to_string();
+        assert_eq!(browser.entries().len(), 2, "matches the listed tool too");
+        browser.query = "TOOLS/LIST".to_string();
+        assert_eq!(browser.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_replay_selected_request() {
+        let mut browser = SessionBrowser::new(vec![session()]);
+        assert!(browser.replayable().is_none(), "nothing selected yet");
+
+        assert!(browser.handle(&Event::Enter));
+        assert!(browser.handle(&Event::Down));
+        let (method, params) = browser.replayable().unwrap();
+        assert_eq!(method, "tools/call");
+        assert_eq!(params["arguments"]["city"], "Oslo");
+
+        assert!(browser.handle(&Event::Quit));
+        assert!(browser.at_sessions());
+    }
+}
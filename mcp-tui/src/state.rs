@@ -0,0 +1,810 @@
+//! Event-sourced application state.
+//!
+//! Every change to what the TUI shows is an [`AppEvent`] appended to an
+//! [`EventLog`]. Replaying a log from the start rebuilds the same state,
+//! including focus and list selection, so a recorded log shows exactly what
+//! the operator saw. Events carry their own timestamps and results, so replay
+//! never re-runs side effects such as discovery or exports.
+//!
+//! Destructive actions are undoable: an [`AppEvent::Undo`] cancels the latest
+//! undoable event still in effect, and the state is rebuilt without it.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use mcp_common::{DecisionTrace, MetricsWindow, ObservedMessage, RequestTracker};
+use mcp_core::catalog::ToolCatalog;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    ActivityItem, Client, PromptBrowser, PromptEntry, ResourceBrowser, ResourceEntry, SavedSession,
+    Server, ServerLogLine, ServerLogView, SessionBrowser, StatsPanel, ToolForm, TrafficInspector,
+};
+use crate::events::Event;
+use crate::ui::{NavigationContext, Overlay, UI};
+
+/// A single change to the application state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AppEvent {
+    /// A client was added or its details changed
+    ClientUpdated { client: Client },
+    /// A server was added or its details changed
+    ServerUpdated { server: Server },
+    /// An entry was appended to the activity feed
+    ActivityRecorded { activity: ActivityItem },
+    /// The oldest activities were dropped to bound memory
+    ActivitiesPruned { count: usize },
+    /// The operator cleared the activity feed
+    ActivitiesCleared,
+    /// A protocol message was captured for session export
+    MessageCaptured { message: ObservedMessage },
+    /// The oldest captured messages were dropped to bound memory
+    MessagesPruned { count: usize },
+    /// The query input now holds this text
+    QueryChanged { query: String },
+    /// Focus or selection moved in response to a key
+    Navigated { event: Event },
+    /// A server's `tools/list` result arrived
+    ToolsListed {
+        server: String,
+        tools: Vec<serde_json::Value>,
+    },
+    /// The call form for a tool replaced the query input
+    ToolFormOpened {
+        server: String,
+        tool: serde_json::Value,
+    },
+    /// A key was applied to the open tool form
+    ToolFormInput { event: Event },
+    /// A `tools/call` finished after `latency_ms`
+    ToolCalled {
+        server: String,
+        tool: String,
+        latency_ms: u64,
+    },
+    /// The tool form was submitted or cancelled
+    ToolFormClosed,
+    /// The traffic inspector replaced the main panels
+    InspectorOpened,
+    /// A key was applied to the traffic inspector
+    InspectorInput { event: Event },
+    /// The traffic inspector was closed
+    InspectorClosed,
+    /// The sessions browser opened on the sessions found on disk
+    SessionsOpened { sessions: Vec<SavedSession> },
+    /// A key was applied to the sessions browser
+    SessionsInput { event: Event },
+    /// The sessions browser was closed
+    SessionsClosed,
+    /// A connected server sent a log message
+    ServerLogged {
+        server: String,
+        entry: mcp_core::server_log::LogEntry,
+    },
+    /// The oldest server log entries were dropped to bound memory
+    ServerLogsPruned { count: usize },
+    /// The server logs panel replaced the main panels
+    ServerLogsOpened,
+    /// A key was applied to the server logs panel
+    ServerLogsInput { event: Event },
+    /// The server logs panel was closed
+    ServerLogsClosed,
+    /// A server listed its resources
+    ResourcesListed {
+        server: String,
+        resources: Vec<serde_json::Value>,
+    },
+    /// The resource browser replaced the main panels
+    ResourcesOpened,
+    /// A key was applied to the resource browser
+    ResourcesInput { event: Event },
+    /// The resource browser was closed
+    ResourcesClosed,
+    /// A resource was read; `response` is the whole JSON-RPC response
+    ResourceRead {
+        server: String,
+        uri: String,
+        response: serde_json::Value,
+    },
+    /// The server accepted a subscription change
+    ResourceSubscribed {
+        server: String,
+        uri: String,
+        subscribed: bool,
+    },
+    /// The server said a resource changed
+    ResourceUpdated { server: String, uri: String },
+    /// A server listed its prompts
+    PromptsListed {
+        server: String,
+        prompts: Vec<serde_json::Value>,
+    },
+    /// The prompt browser replaced the main panels
+    PromptsOpened,
+    /// A key was applied to the prompt browser
+    PromptsInput { event: Event },
+    /// The prompt browser was closed
+    PromptsClosed,
+    /// The server suggested values for the argument being filled
+    PromptCompleted { values: Vec<String> },
+    /// A prompt was fetched; `response` is the whole JSON-RPC response
+    PromptFetched { response: serde_json::Value },
+    /// A request to a server finished after `latency_ms`
+    RequestTimed {
+        server: String,
+        method: String,
+        at: DateTime<Utc>,
+        latency_ms: f64,
+        error: bool,
+    },
+    /// The stats panel replaced the main panels
+    StatsOpened,
+    /// A key was applied to the stats panel
+    StatsInput { event: Event },
+    /// The stats panel was closed
+    StatsClosed,
+    /// The keybinding help was shown over everything else
+    HelpOpened,
+    /// The keybinding help was closed
+    HelpClosed,
+    /// The LLM router explained one of its decisions
+    DecisionTraced { trace: DecisionTrace },
+    /// Cancel the latest undoable event still in effect
+    Undo,
+}
+
+impl AppEvent {
+    /// Whether [`AppEvent::Undo`] can cancel this event
+    pub fn is_undoable(&self) -> bool {
+        matches!(self, Self::ActivitiesCleared)
+    }
+}
+
+/// An event with the time it was recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: AppEvent,
+}
+
+/// Everything the TUI renders, derived purely from events
+#[derive(Debug, Clone, Default)]
+pub struct AppState {
+    /// Connected clients (AI assistants, tools)
+    pub clients: HashMap<String, Client>,
+    /// Available servers (backend services)
+    pub servers: HashMap<String, Server>,
+    /// Activity feed items
+    pub activities: Vec<ActivityItem>,
+    /// Current query input
+    pub query_input: String,
+    /// Captured protocol traffic, exportable as a session archive
+    pub captured: Vec<ObservedMessage>,
+    /// Tools offered by each server, with call statistics
+    pub catalog: ToolCatalog,
+    /// Call form shown in place of the query input
+    pub tool_form: Option<ToolForm>,
+    /// Traffic inspector shown over the main panels
+    pub inspector: Option<TrafficInspector>,
+    /// Saved sessions browser shown over the main panels
+    pub session_browser: Option<SessionBrowser>,
+    /// Routing decisions from the LLM layer, oldest first
+    pub decision_traces: Vec<DecisionTrace>,
+    /// Log messages from connected servers, oldest first
+    pub server_logs: Vec<ServerLogLine>,
+    /// Server logs panel shown over the main panels
+    pub server_log_view: Option<ServerLogView>,
+    /// Resources of connected servers, by server in listing order
+    pub resources: Vec<ResourceEntry>,
+    /// Resource browser shown over the main panels
+    pub resource_browser: Option<ResourceBrowser>,
+    /// Prompts of connected servers, by server in listing order
+    pub prompts: Vec<PromptEntry>,
+    /// Prompt browser shown over the main panels
+    pub prompt_browser: Option<PromptBrowser>,
+    /// Request rate, latency and errors per server over a sliding window
+    pub metrics: MetricsWindow,
+    /// Captured requests awaiting their responses, to time them
+    pub request_tracker: RequestTracker,
+    /// Stats panel shown over the main panels
+    pub stats_panel: Option<StatsPanel>,
+    /// Keybinding help shown over the main panels and any other view
+    pub help_open: bool,
+}
+
+impl AppState {
+    /// List lengths the UI needs to move selections
+    pub fn navigation_context(&self) -> NavigationContext {
+        NavigationContext {
+            client_len: self.clients.len(),
+            server_len: self.servers.len(),
+            activity_len: self.activities.len(),
+        }
+    }
+
+    /// The screen drawn over the main panels, if one is open
+    pub fn overlay(&self) -> Option<Overlay<'_>> {
+        if self.help_open {
+            return Some(Overlay::Help);
+        }
+        if let Some(inspector) = &self.inspector {
+            return Some(Overlay::Inspector(inspector, &self.captured));
+        }
+        if let Some(view) = &self.server_log_view {
+            return Some(Overlay::ServerLogs(view, &self.server_logs));
+        }
+        if let Some(browser) = &self.resource_browser {
+            return Some(Overlay::Resources(browser, &self.resources));
+        }
+        if let Some(browser) = &self.prompt_browser {
+            return Some(Overlay::Prompts(browser, &self.prompts));
+        }
+        if let Some(panel) = &self.stats_panel {
+            return Some(Overlay::Stats(panel, &self.metrics, &self.servers));
+        }
+        self.session_browser.as_ref().map(Overlay::Sessions)
+    }
+
+    /// A listed resource
+    pub fn resource(&self, server: &str, uri: &str) -> Option<&ResourceEntry> {
+        self.resources
+            .iter()
+            .find(|entry| entry.server == server && entry.uri == uri)
+    }
+
+    fn resource_mut(&mut self, server: &str, uri: &str) -> Option<&mut ResourceEntry> {
+        self.resources
+            .iter_mut()
+            .find(|entry| entry.server == server && entry.uri == uri)
+    }
+
+    /// Apply an event, returning whether it changed anything.
+    ///
+    /// [`AppEvent::Undo`] is not handled here; it needs the whole log.
+    pub fn apply(&mut self, ui: &mut UI, event: &AppEvent) -> bool {
+        match event {
+            AppEvent::ClientUpdated { client } => {
+                self.clients.insert(client.id.clone(), client.clone());
+            }
+            AppEvent::ServerUpdated { server } => {
+                self.servers.insert(server.id.clone(), server.clone());
+            }
+            AppEvent::ActivityRecorded { activity } => self.activities.push(activity.clone()),
+            AppEvent::ActivitiesPruned { count } => {
+                let count = (*count).min(self.activities.len());
+                self.activities.drain(0..count);
+                ui.activity_feed.pruned(count);
+            }
+            AppEvent::ActivitiesCleared => {
+                if self.activities.is_empty() {
+                    return false;
+                }
+                ui.activity_feed.pruned(self.activities.len());
+                self.activities.clear();
+            }
+            AppEvent::MessageCaptured { message } => {
+                if let Some(completed) = self.request_tracker.observe(message) {
+                    // Named like the activity feed names the proxy
+                    let proxy = completed.proxy_id.0.to_string();
+                    self.metrics.record(
+                        &proxy[..8],
+                        completed.completed,
+                        completed.latency_ms,
+                        completed.error,
+                    );
+                }
+                self.captured.push(message.clone());
+            }
+            AppEvent::MessagesPruned { count } => {
+                let count = (*count).min(self.captured.len());
+                self.captured.drain(0..count);
+            }
+            AppEvent::QueryChanged { query } => {
+                if self.query_input == *query {
+                    return false;
+                }
+                self.query_input = query.clone();
+            }
+            AppEvent::Navigated { event } => match event {
+                Event::Tab => ui.cycle_focus(),
+                Event::FocusNext => ui.focus_next(),
+                Event::FocusPrev => ui.focus_prev(),
+                _ => return ui.handle_navigation(self.navigation_context(), event.clone()),
+            },
+            AppEvent::ToolsListed { server, tools } => {
+                self.catalog.insert_tools(server, tools.iter().cloned());
+            }
+            AppEvent::ToolCalled {
+                server,
+                tool,
+                latency_ms,
+            } => {
+                self.catalog.record_call(
+                    server,
+                    tool,
+                    std::time::Duration::from_millis(*latency_ms),
+                );
+            }
+            AppEvent::ToolFormOpened { server, tool } => {
+                self.tool_form = Some(ToolForm::new(server.clone(), tool));
+            }
+            AppEvent::ToolFormInput { event } => {
+                return self
+                    .tool_form
+                    .as_mut()
+                    .is_some_and(|form| form.handle(event));
+            }
+            AppEvent::ToolFormClosed => return self.tool_form.take().is_some(),
+            AppEvent::InspectorOpened => {
+                if self.inspector.is_some() {
+                    return false;
+                }
+                self.inspector = Some(TrafficInspector::new());
+            }
+            AppEvent::InspectorInput { event } => {
+                return self
+                    .inspector
+                    .as_mut()
+                    .is_some_and(|inspector| inspector.handle(event, &self.captured));
+            }
+            AppEvent::InspectorClosed => return self.inspector.take().is_some(),
+            AppEvent::SessionsOpened { sessions } => {
+                self.session_browser = Some(SessionBrowser::new(sessions.clone()));
+            }
+            AppEvent::SessionsInput { event } => {
+                return self
+                    .session_browser
+                    .as_mut()
+                    .is_some_and(|browser| browser.handle(event));
+            }
+            AppEvent::SessionsClosed => return self.session_browser.take().is_some(),
+            AppEvent::ServerLogged { server, entry } => {
+                self.server_logs.push((server.clone(), entry.clone()));
+            }
+            AppEvent::ServerLogsPruned { count } => {
+                let count = (*count).min(self.server_logs.len());
+                self.server_logs.drain(0..count);
+            }
+            AppEvent::ServerLogsOpened => {
+                if self.server_log_view.is_some() {
+                    return false;
+                }
+                self.server_log_view = Some(ServerLogView::new());
+            }
+            AppEvent::ServerLogsInput { event } => {
+                return self
+                    .server_log_view
+                    .as_mut()
+                    .is_some_and(|view| view.handle(event, &self.server_logs));
+            }
+            AppEvent::ServerLogsClosed => return self.server_log_view.take().is_some(),
+            AppEvent::ResourcesListed { server, resources } => {
+                let mut listed: Vec<ResourceEntry> = resources
+                    .iter()
+                    .filter_map(|resource| ResourceEntry::from_listing(server, resource))
+                    .collect();
+                // What was learned about resources still listed carries over
+                for entry in &mut listed {
+                    if let Some(known) = self.resource(server, &entry.uri) {
+                        entry.subscribed = known.subscribed;
+                        entry.updates = known.updates;
+                        entry.contents = known.contents.clone();
+                    }
+                }
+                let at = self
+                    .resources
+                    .iter()
+                    .position(|entry| entry.server == *server)
+                    .unwrap_or(self.resources.len());
+                self.resources.retain(|entry| entry.server != *server);
+                self.resources.splice(at..at, listed);
+            }
+            AppEvent::ResourcesOpened => {
+                if self.resource_browser.is_some() {
+                    return false;
+                }
+                self.resource_browser = Some(ResourceBrowser::new());
+            }
+            AppEvent::ResourcesInput { event } => {
+                return self
+                    .resource_browser
+                    .as_mut()
+                    .is_some_and(|browser| browser.handle(event, &self.resources));
+            }
+            AppEvent::ResourcesClosed => return self.resource_browser.take().is_some(),
+            AppEvent::ResourceRead {
+                server,
+                uri,
+                response,
+            } => {
+                let Some(entry) = self.resource_mut(server, uri) else {
+                    return false;
+                };
+                entry.contents = Some(response.clone());
+                entry.updates = 0;
+            }
+            AppEvent::ResourceSubscribed {
+                server,
+                uri,
+                subscribed,
+            } => {
+                let Some(entry) = self.resource_mut(server, uri) else {
+                    return false;
+                };
+                entry.subscribed = *subscribed;
+            }
+            AppEvent::ResourceUpdated { server, uri } => {
+                let Some(entry) = self.resource_mut(server, uri) else {
+                    return false;
+                };
+                entry.updates += 1;
+            }
+            AppEvent::PromptsListed { server, prompts } => {
+                let listed: Vec<PromptEntry> = prompts
+                    .iter()
+                    .filter_map(|prompt| PromptEntry::from_listing(server, prompt))
+                    .collect();
+                let at = self
+                    .prompts
+                    .iter()
+                    .position(|entry| entry.server == *server)
+                    .unwrap_or(self.prompts.len());
+                self.prompts.retain(|entry| entry.server != *server);
+                self.prompts.splice(at..at, listed);
+            }
+            AppEvent::PromptsOpened => {
+                if self.prompt_browser.is_some() {
+                    return false;
+                }
+                self.prompt_browser = Some(PromptBrowser::new());
+            }
+            AppEvent::PromptsInput { event } => {
+                return self
+                    .prompt_browser
+                    .as_mut()
+                    .is_some_and(|browser| browser.handle(event, &self.prompts));
+            }
+            AppEvent::PromptsClosed => return self.prompt_browser.take().is_some(),
+            AppEvent::PromptCompleted { values } => {
+                let Some(browser) = &mut self.prompt_browser else {
+                    return false;
+                };
+                browser.set_completions(values.clone());
+            }
+            AppEvent::PromptFetched { response } => {
+                let Some(browser) = &mut self.prompt_browser else {
+                    return false;
+                };
+                browser.set_response(response.clone());
+            }
+            AppEvent::RequestTimed {
+                server,
+                at,
+                latency_ms,
+                error,
+                ..
+            } => self.metrics.record(server, *at, *latency_ms, *error),
+            AppEvent::StatsOpened => {
+                if self.stats_panel.is_some() {
+                    return false;
+                }
+                self.stats_panel = Some(StatsPanel::new());
+            }
+            AppEvent::StatsInput { event } => {
+                return self
+                    .stats_panel
+                    .as_mut()
+                    .is_some_and(|panel| panel.handle(event, &self.metrics));
+            }
+            AppEvent::StatsClosed => return self.stats_panel.take().is_some(),
+            AppEvent::HelpOpened => return !std::mem::replace(&mut self.help_open, true),
+            AppEvent::HelpClosed => return std::mem::replace(&mut self.help_open, false),
+            AppEvent::DecisionTraced { trace } => self.decision_traces.push(trace.clone()),
+            AppEvent::Undo => return false,
+        }
+        true
+    }
+}
+
+/// Append-only record of every applied event
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    events: Vec<RecordedEvent>,
+}
+
+impl EventLog {
+    /// Create an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an event stamped with the current time
+    pub fn push(&mut self, event: AppEvent) {
+        self.events.push(RecordedEvent {
+            at: Utc::now(),
+            event,
+        });
+    }
+
+    /// Recorded events, oldest first
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Number of recorded events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Whether an [`AppEvent::Undo`] would cancel anything
+    pub fn can_undo(&self) -> bool {
+        let cancelled = self.cancelled();
+        self.events
+            .iter()
+            .enumerate()
+            .any(|(i, recorded)| recorded.event.is_undoable() && !cancelled.contains(&i))
+    }
+
+    /// Rebuild the state and UI from scratch by applying every event still in effect
+    pub fn replay(&self) -> (AppState, UI) {
+        let mut state = AppState::default();
+        let mut ui = UI::new();
+        let cancelled = self.cancelled();
+        for (i, recorded) in self.events.iter().enumerate() {
+            if !cancelled.contains(&i) {
+                state.apply(&mut ui, &recorded.event);
+            }
+        }
+        (state, ui)
+    }
+
+    /// Indices of events cancelled by a later [`AppEvent::Undo`]
+    fn cancelled(&self) -> HashSet<usize> {
+        let mut cancelled = HashSet::new();
+        for (i, recorded) in self.events.iter().enumerate() {
+            if !matches!(recorded.event, AppEvent::Undo) {
+                continue;
+            }
+            let target = self.events[..i]
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(j, earlier)| earlier.event.is_undoable() && !cancelled.contains(j));
+            if let Some((j, _)) = target {
+                cancelled.insert(j);
+            }
+        }
+        cancelled
+    }
+
+    /// Load a log written by [`EventLog::save`], one JSON event per line
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open event log {}", path.display()))?;
+
+        let mut events = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line).with_context(|| {
+                format!("Invalid event on line {} of {}", number + 1, path.display())
+            })?;
+            events.push(event);
+        }
+        Ok(Self { events })
+    }
+
+    /// Write the log as JSONL
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create event log {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        for event in &self.events {
+            serde_json::to_writer(&mut writer, event)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{ActivityStatus, FocusArea};
+
+    fn activity(action: &str) -> AppEvent {
+        AppEvent::ActivityRecorded {
+            activity: ActivityItem {
+                timestamp: Utc::now(),
+                client: "User".to_string(),
+                server: "System".to_string(),
+                action: action.to_string(),
+                status: ActivityStatus::Success,
+                detail: None,
+                direction: None,
+            },
+        }
+    }
+
+    fn record(events: Vec<AppEvent>) -> EventLog {
+        let mut log = EventLog::new();
+        for event in events {
+            log.push(event);
+        }
+        log
+    }
+
+    #[test]
+    fn test_replay_restores_state_and_focus() {
+        let log = record(vec![
+            activity("first"),
+            AppEvent::QueryChanged {
+                query: "list tools".to_string(),
+            },
+            AppEvent::Navigated { event: Event::Tab },
+            AppEvent::Navigated {
+                event: Event::Right,
+            },
+        ]);
+
+        let file = tempfile_path("replay");
+        log.save(&file).unwrap();
+        let (state, ui) = EventLog::load(&file).unwrap().replay();
+        std::fs::remove_file(&file).ok();
+
+        assert_eq!(state.activities.len(), 1);
+        assert_eq!(state.query_input, "list tools");
+        assert_eq!(ui.get_focus(), FocusArea::Activity);
+    }
+
+    #[test]
+    fn test_undo_cancels_latest_destructive_event() {
+        let mut log = record(vec![
+            activity("first"),
+            AppEvent::ActivitiesCleared,
+            activity("second"),
+            AppEvent::ActivitiesCleared,
+        ]);
+        assert_eq!(log.replay().0.activities.len(), 0);
+
+        log.push(AppEvent::Undo);
+        let actions: Vec<_> = log
+            .replay()
+            .0
+            .activities
+            .into_iter()
+            .map(|a| a.action)
+            .collect();
+        assert_eq!(actions, vec!["second"]);
+
+        log.push(AppEvent::Undo);
+        assert_eq!(log.replay().0.activities.len(), 2);
+        assert!(!log.can_undo());
+
+        // Nothing left to cancel
+        log.push(AppEvent::Undo);
+        assert_eq!(log.replay().0.activities.len(), 2);
+    }
+
+    #[test]
+    fn test_tool_form_replays() {
+        let tool = serde_json::json!({
+            "name": "echo",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"]
+            }
+        });
+        let mut events = vec![
+            AppEvent::ToolsListed {
+                server: "profile:demo".to_string(),
+                tools: vec![tool.clone()],
+            },
+            AppEvent::ToolFormOpened {
+                server: "profile:demo".to_string(),
+                tool,
+            },
+        ];
+        events.extend("hi".chars().map(|c| AppEvent::ToolFormInput {
+            event: Event::Input(c),
+        }));
+        events.push(AppEvent::ToolFormInput {
+            event: Event::Enter,
+        });
+        events.push(AppEvent::ToolCalled {
+            server: "profile:demo".to_string(),
+            tool: "echo".to_string(),
+            latency_ms: 12,
+        });
+        let (state, _) = record(events).replay();
+
+        assert_eq!(state.catalog.get("profile:demo", "echo").unwrap().calls, 1);
+        let form = state.tool_form.expect("form stays open until closed");
+        assert_eq!(
+            form.submission(),
+            Some(&serde_json::json!({ "text": "hi" }))
+        );
+    }
+
+    #[test]
+    fn test_focus_skips_hidden_panels() {
+        let mut ui = UI::new();
+        let mut config = mcp_common::MonitorConfig::default();
+        config.panels.clients = false;
+        config.panels.activity = false;
+        ui.configure(&config);
+        assert_eq!(ui.get_focus(), FocusArea::Servers);
+
+        let mut state = AppState::default();
+        state.apply(&mut ui, &AppEvent::Navigated { event: Event::Tab });
+        assert_eq!(ui.get_focus(), FocusArea::QuickAccess);
+        state.apply(&mut ui, &AppEvent::Navigated { event: Event::Tab });
+        state.apply(&mut ui, &AppEvent::Navigated { event: Event::Tab });
+        assert_eq!(ui.get_focus(), FocusArea::Servers);
+    }
+
+    #[test]
+    fn test_resource_updates_survive_relisting_until_read() {
+        let server = "profile:fs".to_string();
+        let uri = "file:///notes.md".to_string();
+        let listed = AppEvent::ResourcesListed {
+            server: server.clone(),
+            resources: vec![
+                serde_json::json!({ "uri": uri }),
+                serde_json::json!({ "uri": "file:///todo.md" }),
+            ],
+        };
+        let mut log = record(vec![
+            listed.clone(),
+            AppEvent::ResourceSubscribed {
+                server: server.clone(),
+                uri: uri.clone(),
+                subscribed: true,
+            },
+            AppEvent::ResourceUpdated {
+                server: server.clone(),
+                uri: uri.clone(),
+            },
+            AppEvent::ResourceUpdated {
+                server: server.clone(),
+                uri: uri.clone(),
+            },
+            listed,
+        ]);
+        let (state, _) = log.replay();
+        assert_eq!(state.resources.len(), 2);
+        let entry = state.resource(&server, &uri).unwrap();
+        assert!(entry.subscribed);
+        assert_eq!(entry.updates, 2);
+
+        log.push(AppEvent::ResourceRead {
+            server: server.clone(),
+            uri: uri.clone(),
+            response: serde_json::json!({ "result": { "contents": [] } }),
+        });
+        let (state, _) = log.replay();
+        let entry = state.resource(&server, &uri).unwrap();
+        assert_eq!(entry.updates, 0);
+        assert!(entry.contents.is_some());
+    }
+
+    fn tempfile_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mcp-tui-{}-{}.jsonl", name, std::process::id()))
+    }
+}
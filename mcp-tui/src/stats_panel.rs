@@ -0,0 +1,181 @@
+//! Per-server performance charts.
+//!
+//! Every request the monitor sends, and every request paired with its
+//! response in captured traffic, is counted into a [`MetricsWindow`]. This
+//! panel draws one row per server with sparklines of its request rate, p50
+//! and p95 latency and error rate across the window, newest on the right, so
+//! a backend that slows down or starts failing stands out at a glance.
+
+use std::collections::HashMap;
+
+use mcp_common::{MetricsWindow, Series};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, Paragraph, Sparkline},
+    Frame,
+};
+
+use crate::components::Server;
+use crate::events::Event;
+use crate::theme;
+
+/// Rows each server takes: its name and a bordered sparkline
+const ROW_HEIGHT: u16 = 5;
+
+/// Scrollable list of per-server charts
+#[derive(Debug, Clone, Default)]
+pub struct StatsPanel {
+    /// First server shown
+    offset: usize,
+}
+
+impl StatsPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a key, returning whether the view changed
+    pub fn handle(&mut self, event: &Event, metrics: &MetricsWindow) -> bool {
+        let last = metrics.servers().count().saturating_sub(1);
+        let offset = match event {
+            Event::Up => self.offset.saturating_sub(1),
+            Event::Down => (self.offset + 1).min(last),
+            Event::PageUp => 0,
+            Event::PageDown => last,
+            _ => return false,
+        };
+        let changed = offset != self.offset;
+        self.offset = offset;
+        changed
+    }
+
+    pub fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        metrics: &MetricsWindow,
+        servers: &HashMap<String, Server>,
+    ) {
+        let block = Block::default()
+            .title(format!(
+                "Stats · last {}s in {}s buckets (↑/↓ scroll, Esc close)",
+                metrics.span().as_secs(),
+                metrics.bucket_width().as_secs()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().accent));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let ids: Vec<&str> = metrics.servers().collect();
+        if ids.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No requests yet")
+                    .style(Style::default().fg(theme::current().muted)),
+                inner,
+            );
+            return;
+        }
+
+        let fits = usize::from((inner.height / ROW_HEIGHT).max(1));
+        let offset = self.offset.min(ids.len().saturating_sub(fits));
+        let shown = &ids[offset..ids.len().min(offset + fits)];
+        let mut constraints = vec![Constraint::Length(ROW_HEIGHT); shown.len()];
+        constraints.push(Constraint::Min(0));
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(inner);
+        for (id, row) in shown.iter().zip(rows.iter()) {
+            let name = servers.get(*id).map_or(*id, |server| server.name.as_str());
+            render_server(frame, *row, name, &metrics.series(id));
+        }
+    }
+}
+
+/// A server's name over its four charts
+fn render_server(frame: &mut Frame, area: Rect, name: &str, series: &Series) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(area);
+    frame.render_widget(
+        Paragraph::new(name.to_string()).style(Style::default().fg(theme::current().highlight)),
+        rows[0],
+    );
+
+    let colors = theme::current();
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        // Explicit widths; ratios leave rounding ties to the layout solver
+        .constraints({
+            let quarter = Constraint::Length(rows[1].width / 4);
+            [quarter, quarter, quarter, Constraint::Min(0)]
+        })
+        .split(rows[1]);
+    let error_pct: Vec<f64> = series.error_rate.iter().map(|rate| rate * 100.0).collect();
+    let charts = [
+        ("req/s", &series.requests_per_sec, "", 2, colors.accent),
+        ("p50", &series.p50_ms, "ms", 0, colors.success),
+        ("p95", &series.p95_ms, "ms", 0, colors.warning),
+        ("errors", &error_pct, "%", 0, colors.error),
+    ];
+    for ((label, values, unit, precision, color), area) in charts.into_iter().zip(columns.iter()) {
+        render_chart(frame, *area, label, values, unit, precision, color);
+    }
+}
+
+/// One sparkline, titled with its newest value
+fn render_chart(
+    frame: &mut Frame,
+    area: Rect,
+    label: &str,
+    values: &[f64],
+    unit: &str,
+    precision: usize,
+    color: ratatui::style::Color,
+) {
+    let current = values.last().copied().unwrap_or(0.0);
+    let block = Block::default()
+        .title(format!("{} {:.*}{}", label, precision, current, unit))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::current().muted));
+    // Sparklines draw from the left, so keep the newest buckets that fit;
+    // scaling keeps fractions from rounding away against the maximum
+    let width = usize::from(block.inner(area).width);
+    let data: Vec<u64> = values[values.len().saturating_sub(width)..]
+        .iter()
+        .map(|value| (value * 100.0).round() as u64)
+        .collect();
+    frame.render_widget(
+        Sparkline::default()
+            .block(block)
+            .data(&data)
+            .style(Style::default().fg(color)),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    #[test]
+    fn test_scroll_stays_within_servers() {
+        let mut metrics = MetricsWindow::new(Duration::from_secs(5), 12);
+        let at = Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, 0).unwrap();
+        metrics.record("db", at, 12.0, false);
+        metrics.record("fs", at, 30.0, true);
+
+        let mut panel = StatsPanel::new();
+        assert!(!panel.handle(&Event::Up, &metrics));
+        assert!(panel.handle(&Event::Down, &metrics));
+        assert!(!panel.handle(&Event::Down, &metrics));
+        assert_eq!(panel.offset, 1);
+        assert!(panel.handle(&Event::PageUp, &metrics));
+        assert_eq!(panel.offset, 0);
+    }
+}
@@ -0,0 +1,318 @@
+//! Colours the interface is drawn with.
+//!
+//! Every widget asks [`current`] for the colour of a role (accent, muted text,
+//! errors, ...) instead of naming one, so a [`Theme`] recolours the whole
+//! interface. Themes start from the [`Theme::DARK`] or [`Theme::LIGHT`] preset
+//! and override roles from the `[theme]` table of the monitor configuration:
+//!
+//! ```toml
+//! [theme]
+//! preset = "light"
+//! accent = "#005f87"
+//! muted = "244"
+//! ```
+//!
+//! Colours are names, `#rrggbb` or 256-colour indices. Before a theme is
+//! used, [`Theme::adapt`] fits it to what the terminal can show: `#rrggbb`
+//! becomes the nearest 256-colour or 16-colour entry, and `NO_COLOR` turns
+//! colour off.
+
+use std::sync::RwLock;
+
+use anyhow::{anyhow, bail, Result};
+use mcp_common::ThemeConfig;
+use ratatui::style::Color;
+
+/// The theme in use, read by every widget as it renders
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::DARK);
+
+/// The theme in use
+pub fn current() -> Theme {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Draw with `theme` from the next frame on
+pub fn set(theme: Theme) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = theme;
+}
+
+/// A colour for each role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Focused borders, keys and links
+    pub accent: Color,
+    /// Names and body text
+    pub text: Color,
+    /// Less important text
+    pub secondary: Color,
+    /// Timestamps, hints and placeholders
+    pub muted: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    /// Failures beyond errors, such as critical server logs
+    pub critical: Color,
+    /// Methods, keywords and other emphasis
+    pub highlight: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+impl Theme {
+    /// For dark terminal backgrounds
+    pub const DARK: Self = Self {
+        accent: Color::Cyan,
+        text: Color::White,
+        secondary: Color::Gray,
+        muted: Color::DarkGray,
+        success: Color::Green,
+        warning: Color::Yellow,
+        error: Color::Red,
+        critical: Color::LightRed,
+        highlight: Color::Magenta,
+    };
+
+    /// For light terminal backgrounds
+    pub const LIGHT: Self = Self {
+        accent: Color::Blue,
+        text: Color::Black,
+        secondary: Color::DarkGray,
+        muted: Color::Gray,
+        success: Color::Rgb(0, 135, 0),
+        warning: Color::Rgb(175, 95, 0),
+        error: Color::Red,
+        critical: Color::Rgb(175, 0, 0),
+        highlight: Color::Magenta,
+    };
+
+    /// A preset by name: `dark` or `light`
+    pub fn preset(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark" => Ok(Self::DARK),
+            "light" => Ok(Self::LIGHT),
+            other => bail!("Unknown theme preset '{}': use 'dark' or 'light'", other),
+        }
+    }
+
+    /// The configured preset with the configured roles overridden
+    pub fn from_config(config: &ThemeConfig) -> Result<Self> {
+        let mut theme = Self::preset(&config.preset)?;
+        for (role, value) in &config.colors {
+            let color: Color = value
+                .parse()
+                .map_err(|_| anyhow!("Invalid colour '{}' for theme role '{}'", value, role))?;
+            *theme.role_mut(role)? = color;
+        }
+        Ok(theme)
+    }
+
+    fn role_mut(&mut self, role: &str) -> Result<&mut Color> {
+        Ok(match role {
+            "accent" => &mut self.accent,
+            "text" => &mut self.text,
+            "secondary" => &mut self.secondary,
+            "muted" => &mut self.muted,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "critical" => &mut self.critical,
+            "highlight" => &mut self.highlight,
+            other => bail!("Unknown theme role '{}'", other),
+        })
+    }
+
+    /// Fit every colour to what the terminal can show
+    pub fn adapt(self, support: ColorSupport) -> Self {
+        let fit = |color| support.fit(color);
+        Self {
+            accent: fit(self.accent),
+            text: fit(self.text),
+            secondary: fit(self.secondary),
+            muted: fit(self.muted),
+            success: fit(self.success),
+            warning: fit(self.warning),
+            error: fit(self.error),
+            critical: fit(self.critical),
+            highlight: fit(self.highlight),
+        }
+    }
+}
+
+/// Colours a terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// `NO_COLOR` is set
+    None,
+    /// The 16 ANSI colours
+    Basic,
+    /// The xterm 256-colour palette
+    Ansi256,
+    /// Any `#rrggbb`
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// What the terminal running this process supports, from its environment
+    pub fn detect() -> Self {
+        Self::from_env(
+            std::env::var("COLORTERM").ok().as_deref(),
+            std::env::var("TERM").ok().as_deref(),
+            std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+        )
+    }
+
+    /// Support given `COLORTERM`, `TERM` and whether `NO_COLOR` is set
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>, no_color: bool) -> Self {
+        if no_color {
+            return Self::None;
+        }
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return Self::TrueColor;
+        }
+        match term {
+            Some(term) if term.contains("truecolor") || term.contains("direct") => Self::TrueColor,
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            _ => Self::Basic,
+        }
+    }
+
+    /// The closest colour to `color` this terminal can show
+    pub fn fit(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::None, _) => Color::Reset,
+            (Self::TrueColor, color) => color,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_indexed(r, g, b)),
+            (Self::Basic, Color::Rgb(r, g, b)) => nearest_basic(r, g, b),
+            (Self::Basic, Color::Indexed(index)) => {
+                let (r, g, b) = indexed_rgb(index);
+                nearest_basic(r, g, b)
+            }
+            (_, color) => color,
+        }
+    }
+}
+
+/// The 16 ANSI colours with their usual xterm values
+const BASIC: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Levels of each channel in the 6×6×6 colour cube
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE[(i / 36) as usize],
+                CUBE[(i / 6 % 6) as usize],
+                CUBE[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_indexed(r: u8, g: u8, b: u8) -> u8 {
+    (16..=255)
+        .min_by_key(|index| distance(indexed_rgb(*index), (r, g, b)))
+        .unwrap_or(16)
+}
+
+fn nearest_basic(r: u8, g: u8, b: u8) -> Color {
+    BASIC
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb, (r, g, b)))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_overrides_preset_roles() {
+        let mut config = ThemeConfig {
+            preset: "light".to_string(),
+            ..ThemeConfig::default()
+        };
+        config
+            .colors
+            .insert("accent".to_string(), "#005f87".to_string());
+        config.colors.insert("muted".to_string(), "244".to_string());
+
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.accent, Color::Rgb(0, 95, 135));
+        assert_eq!(theme.muted, Color::Indexed(244));
+        assert_eq!(theme.text, Theme::LIGHT.text);
+
+        config
+            .colors
+            .insert("border".to_string(), "red".to_string());
+        assert!(Theme::from_config(&config).is_err());
+        assert!(Theme::preset("solarized").is_err());
+    }
+
+    #[test]
+    fn test_colors_fit_the_terminal() {
+        assert_eq!(
+            ColorSupport::from_env(Some("truecolor"), Some("xterm"), false),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            ColorSupport::from_env(None, Some("screen-256color"), false),
+            ColorSupport::Ansi256
+        );
+        assert_eq!(
+            ColorSupport::from_env(None, Some("linux"), false),
+            ColorSupport::Basic
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("truecolor"), None, true),
+            ColorSupport::None
+        );
+
+        let orange = Color::Rgb(175, 95, 0);
+        assert_eq!(ColorSupport::TrueColor.fit(orange), orange);
+        assert_eq!(ColorSupport::Ansi256.fit(orange), Color::Indexed(130));
+        assert_eq!(
+            ColorSupport::Basic.fit(Color::Indexed(196)),
+            Color::LightRed
+        );
+        assert_eq!(ColorSupport::Basic.fit(Color::Cyan), Color::Cyan);
+        assert_eq!(ColorSupport::None.fit(Color::Cyan), Color::Reset);
+
+        let dark = Theme::DARK.adapt(ColorSupport::Basic);
+        assert_eq!(dark, Theme::DARK);
+    }
+}
@@ -0,0 +1,482 @@
+//! Input form for calling a tool, generated from its input schema.
+//!
+//! Fields come from [`ParameterValidator::extract_parameter_hints`]: enums
+//! are dropdowns cycled with Left/Right, booleans are toggles flipped with
+//! Space, everything else is typed text. Required fields are listed first and
+//! marked with `*`. Enter validates the arguments against the schema; when
+//! they pass, [`ToolForm::submission`] holds the `tools/call` arguments.
+
+use mcp_core::validation::{ParameterHint, ParameterValidator, ValidationError};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde_json::{Map, Value};
+
+use crate::events::Event;
+use crate::theme;
+
+/// What a field holds
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    Toggle(bool),
+    /// Index into the hint's enum values; `None` leaves the argument unset
+    Choice(Option<usize>),
+}
+
+/// One input of the form
+#[derive(Debug, Clone)]
+pub struct FormField {
+    pub hint: ParameterHint,
+    pub value: FieldValue,
+    pub error: Option<String>,
+}
+
+impl FormField {
+    fn new(hint: ParameterHint) -> Self {
+        let value = match (&hint.enum_values, hint.param_type.as_str()) {
+            (Some(options), _) => {
+                let default = hint
+                    .default_value
+                    .as_ref()
+                    .and_then(|default| options.iter().position(|option| option == default));
+                FieldValue::Choice(default.or(if hint.required && !options.is_empty() {
+                    Some(0)
+                } else {
+                    None
+                }))
+            }
+            (None, "boolean") => FieldValue::Toggle(
+                hint.default_value
+                    .as_ref()
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            ),
+            (None, _) => FieldValue::Text(match &hint.default_value {
+                Some(Value::String(text)) => text.clone(),
+                Some(Value::Null) | None => String::new(),
+                Some(other) => other.to_string(),
+            }),
+        };
+        Self {
+            hint,
+            value,
+            error: None,
+        }
+    }
+
+    /// The argument this field contributes, `None` when left unset
+    fn argument(&self) -> Result<Option<Value>, String> {
+        let options = self.hint.enum_values.as_deref().unwrap_or_default();
+        match &self.value {
+            FieldValue::Toggle(on) => Ok(Some(Value::Bool(*on))),
+            FieldValue::Choice(selected) => Ok(selected.and_then(|i| options.get(i).cloned())),
+            FieldValue::Text(text) if text.is_empty() => Ok(None),
+            FieldValue::Text(text) => match self.hint.param_type.as_str() {
+                "integer" => text
+                    .trim()
+                    .parse::<i64>()
+                    .map(|n| Some(Value::from(n)))
+                    .map_err(|_| "Expected a whole number".to_string()),
+                "number" => text
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(|n| Some(Value::Number(n)))
+                    .ok_or_else(|| "Expected a number".to_string()),
+                "array" | "object" => serde_json::from_str(text)
+                    .map(Some)
+                    .map_err(|e| format!("Invalid JSON: {}", e)),
+                _ => Ok(Some(Value::String(text.clone()))),
+            },
+        }
+    }
+
+    fn display(&self) -> String {
+        match &self.value {
+            FieldValue::Text(text) => text.clone(),
+            FieldValue::Toggle(on) => if *on { "[x]" } else { "[ ]" }.to_string(),
+            FieldValue::Choice(selected) => {
+                let options = self.hint.enum_values.as_deref().unwrap_or_default();
+                let label = match selected.and_then(|i| options.get(i)) {
+                    Some(Value::String(option)) => option.clone(),
+                    Some(option) => option.to_string(),
+                    None => "(unset)".to_string(),
+                };
+                format!("< {} >", label)
+            }
+        }
+    }
+}
+
+/// A `tools/call` form for one tool of one server
+#[derive(Debug, Clone)]
+pub struct ToolForm {
+    /// Server the tool belongs to
+    pub server: String,
+    /// Tool name
+    pub tool: String,
+    pub description: Option<String>,
+    schema: Value,
+    fields: Vec<FormField>,
+    selected: usize,
+    /// Problems not tied to a single field
+    error: Option<String>,
+    submission: Option<Value>,
+}
+
+impl ToolForm {
+    /// Build the form for a tool as listed by `tools/list`
+    pub fn new(server: impl Into<String>, tool: &Value) -> Self {
+        let schema = tool
+            .get("inputSchema")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({ "type": "object" }));
+        let mut hints: Vec<ParameterHint> = ParameterValidator::new()
+            .extract_parameter_hints(&schema)
+            .into_values()
+            .collect();
+        hints.sort_by(|a, b| b.required.cmp(&a.required).then(a.name.cmp(&b.name)));
+
+        Self {
+            server: server.into(),
+            tool: tool["name"].as_str().unwrap_or_default().to_string(),
+            description: tool["description"].as_str().map(str::to_string),
+            schema,
+            fields: hints.into_iter().map(FormField::new).collect(),
+            selected: 0,
+            error: None,
+            submission: None,
+        }
+    }
+
+    /// Whether a tool's schema has fields worth a form
+    pub fn is_structured(tool: &Value) -> bool {
+        tool.pointer("/inputSchema/properties")
+            .and_then(Value::as_object)
+            .is_some_and(|properties| !properties.is_empty())
+    }
+
+    pub fn fields(&self) -> &[FormField] {
+        &self.fields
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Validated arguments, set once Enter succeeds
+    pub fn submission(&self) -> Option<&Value> {
+        self.submission.as_ref()
+    }
+
+    /// Apply a key, returning whether the form changed
+    pub fn handle(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Up => self.select(self.selected.checked_sub(1)),
+            Event::Down | Event::Tab => self.select(Some(self.selected + 1)),
+            Event::Left => self.cycle(false),
+            Event::Right => self.cycle(true),
+            Event::Input(character) => self.input(*character),
+            Event::Backspace => match self.current_mut() {
+                Some(FormField {
+                    value: FieldValue::Text(text),
+                    ..
+                }) => text.pop().is_some(),
+                Some(FormField {
+                    value: FieldValue::Choice(selected),
+                    hint,
+                    ..
+                }) if !hint.required => selected.take().is_some(),
+                _ => false,
+            },
+            Event::Enter => {
+                self.submit();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Convert and validate the fields, storing the arguments on success
+    pub fn submit(&mut self) -> Option<&Value> {
+        self.error = None;
+        self.submission = None;
+        let mut arguments = Map::new();
+        let mut failed = false;
+        for field in &mut self.fields {
+            field.error = None;
+            match field.argument() {
+                Ok(Some(value)) => {
+                    arguments.insert(field.hint.name.clone(), value);
+                }
+                Ok(None) if field.hint.required => {
+                    field.error = Some("Required".to_string());
+                    failed = true;
+                }
+                Ok(None) => {}
+                Err(reason) => {
+                    field.error = Some(reason);
+                    failed = true;
+                }
+            }
+        }
+        if failed {
+            return None;
+        }
+
+        let result = ParameterValidator::new().validate(&self.schema, &Value::Object(arguments));
+        for error in &result.errors {
+            let (field, reason) = match error {
+                ValidationError::MissingRequired { field } => (field, "Required".to_string()),
+                ValidationError::ValidationFailed { field, reason }
+                | ValidationError::TransformationFailed { field, reason } => {
+                    (field, reason.clone())
+                }
+                other => {
+                    self.error = Some(other.to_string());
+                    continue;
+                }
+            };
+            match self.fields.iter_mut().find(|f| f.hint.name == *field) {
+                Some(f) => f.error = Some(reason),
+                None => self.error = Some(error.to_string()),
+            }
+        }
+        if result.is_valid {
+            self.submission = Some(result.validated_params);
+        }
+        self.submission.as_ref()
+    }
+
+    /// Rows needed to show every field, its description and border
+    pub fn height(&self) -> u16 {
+        let rows: usize = self
+            .fields
+            .iter()
+            .map(|field| 1 + usize::from(field.error.is_some()))
+            .sum();
+        (rows.max(1) + 3) as u16
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let title = format!(
+            "{} / {} (Enter call · Esc cancel · Space toggle · ←/→ choose)",
+            self.server, self.tool
+        );
+        let mut block = Block::default().title(title).borders(Borders::ALL);
+        if focused {
+            block = block.border_style(Style::default().fg(theme::current().accent));
+        }
+
+        let label_width = self
+            .fields
+            .iter()
+            .map(|field| field.hint.name.len() + 2)
+            .max()
+            .unwrap_or(0);
+        let mut lines = Vec::new();
+        if self.fields.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No arguments; press Enter to call",
+                Style::default().fg(theme::current().muted),
+            )));
+        }
+        for (i, field) in self.fields.iter().enumerate() {
+            let selected = i == self.selected && focused;
+            let marker = if field.hint.required { "*" } else { " " };
+            let label = format!("{}{}", field.hint.name, marker);
+            let mut value_style = Style::default();
+            if selected {
+                value_style = value_style.add_modifier(Modifier::REVERSED);
+            }
+            let mut spans = vec![
+                Span::styled(
+                    format!("{:<width$}", label, width = label_width),
+                    Style::default().fg(if field.hint.required {
+                        theme::current().warning
+                    } else {
+                        theme::current().secondary
+                    }),
+                ),
+                Span::styled(format!(" {} ", field.display()), value_style),
+            ];
+            let note = field
+                .hint
+                .description
+                .clone()
+                .unwrap_or_else(|| field.hint.param_type.clone());
+            spans.push(Span::styled(
+                format!("  {}", note),
+                Style::default().fg(theme::current().muted),
+            ));
+            lines.push(Line::from(spans));
+            if let Some(error) = &field.error {
+                lines.push(Line::from(Span::styled(
+                    format!("{:<width$} ! {}", "", error, width = label_width),
+                    Style::default().fg(theme::current().error),
+                )));
+            }
+        }
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::default().fg(theme::current().error),
+            )));
+        }
+
+        // Keep the selected field in view
+        let visible = area.height.saturating_sub(2) as usize;
+        let selected_row: usize = self.fields[..self.selected.min(self.fields.len())]
+            .iter()
+            .map(|field| 1 + usize::from(field.error.is_some()))
+            .sum();
+        let scroll = selected_row.saturating_sub(visible.saturating_sub(1));
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((scroll as u16, 0)),
+            area,
+        );
+    }
+
+    fn current_mut(&mut self) -> Option<&mut FormField> {
+        self.fields.get_mut(self.selected)
+    }
+
+    fn select(&mut self, index: Option<usize>) -> bool {
+        match index {
+            Some(index) if index < self.fields.len() && index != self.selected => {
+                self.selected = index;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn cycle(&mut self, forward: bool) -> bool {
+        let Some(field) = self.current_mut() else {
+            return false;
+        };
+        let count = field.hint.enum_values.as_ref().map_or(0, Vec::len);
+        let required = field.hint.required;
+        match &mut field.value {
+            FieldValue::Choice(selected) if count > 0 => {
+                // Optional dropdowns include an unset position after the last option
+                let positions = if required { count } else { count + 1 };
+                let current = selected.unwrap_or(count);
+                let next = if forward {
+                    (current + 1) % positions
+                } else {
+                    (current + positions - 1) % positions
+                };
+                *selected = (next < count).then_some(next);
+                true
+            }
+            FieldValue::Toggle(on) => {
+                *on = !*on;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn input(&mut self, character: char) -> bool {
+        let Some(field) = self.current_mut() else {
+            return false;
+        };
+        field.error = None;
+        match &mut field.value {
+            FieldValue::Text(text) => {
+                text.push(character);
+                true
+            }
+            FieldValue::Toggle(on) if character == ' ' => {
+                *on = !*on;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn weather_tool() -> Value {
+        json!({
+            "name": "get_weather",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string", "description": "City name" },
+                    "units": { "type": "string", "enum": ["metric", "imperial"] },
+                    "days": { "type": "integer", "default": 1 },
+                    "alerts": { "type": "boolean" }
+                },
+                "required": ["city"]
+            }
+        })
+    }
+
+    fn press(form: &mut ToolForm, events: &[Event]) {
+        for event in events {
+            form.handle(event);
+        }
+    }
+
+    #[test]
+    fn test_fields_follow_the_schema() {
+        let form = ToolForm::new("weather", &weather_tool());
+        let names: Vec<_> = form.fields().iter().map(|f| f.hint.name.as_str()).collect();
+        assert_eq!(names, vec!["city", "alerts", "days", "units"]);
+        assert!(form.fields()[0].hint.required);
+        assert_eq!(form.fields()[1].value, FieldValue::Toggle(false));
+        assert_eq!(form.fields()[2].value, FieldValue::Text("1".to_string()));
+        assert_eq!(form.fields()[3].value, FieldValue::Choice(None));
+        assert!(ToolForm::is_structured(&weather_tool()));
+        assert!(!ToolForm::is_structured(&json!({ "name": "ping" })));
+    }
+
+    #[test]
+    fn test_submit_validates_and_converts() {
+        let mut form = ToolForm::new("weather", &weather_tool());
+        press(&mut form, &[Event::Enter]);
+        assert!(form.submission().is_none());
+        assert_eq!(form.fields()[0].error.as_deref(), Some("Required"));
+
+        press(
+            &mut form,
+            &[Event::Input('P'), Event::Input('a'), Event::Down],
+        );
+        press(
+            &mut form,
+            &[Event::Input(' '), Event::Down, Event::Input('x')],
+        );
+        press(&mut form, &[Event::Enter]);
+        assert!(form.submission().is_none());
+        assert_eq!(
+            form.fields()[2].error.as_deref(),
+            Some("Expected a whole number")
+        );
+
+        press(&mut form, &[Event::Backspace, Event::Down, Event::Right]);
+        press(&mut form, &[Event::Right, Event::Left, Event::Enter]);
+        assert_eq!(
+            form.submission(),
+            Some(&json!({ "city": "Pa", "alerts": true, "days": 1, "units": "metric" }))
+        );
+
+        // Optional dropdowns can be unset again
+        press(&mut form, &[Event::Backspace, Event::Enter]);
+        assert_eq!(form.submission().unwrap().get("units"), None);
+    }
+}
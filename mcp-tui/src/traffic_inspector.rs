@@ -0,0 +1,752 @@
+//! Inspector for captured protocol traffic.
+//!
+//! The list shows every captured message matching the filter line. Typed text
+//! matches the method (responses match through their request), `dir:out` and
+//! `dir:in` pick a direction, and `server:ID` keeps one proxy's traffic by id
+//! prefix. Enter opens the selected message as a JSON tree whose objects and
+//! arrays fold with Enter. `d` switches to the diff view: the request as the
+//! client sent it against what interceptors forwarded, then the backend's
+//! response against what the client received. Esc goes back a level.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use mcp_common::{ObservedMessage, TrafficDirection};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+
+use crate::events::Event;
+use crate::theme;
+
+/// Lines moved by PgUp/PgDn in the detail views
+const PAGE: usize = 10;
+
+/// Which messages the list shows
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrafficFilter {
+    /// Substring of the method, or of the request's method for a response
+    pub method: String,
+    pub direction: Option<TrafficDirection>,
+    /// Prefix of the proxy id
+    pub server: Option<String>,
+}
+
+impl TrafficFilter {
+    /// Parse a filter line such as `tools/call dir:out server:3f2a`
+    pub fn parse(text: &str) -> Self {
+        let mut filter = Self::default();
+        let mut method = Vec::new();
+        for term in text.split_whitespace() {
+            if let Some(direction) = term.strip_prefix("dir:") {
+                filter.direction = match direction {
+                    "out" => Some(TrafficDirection::ClientToServer),
+                    "in" => Some(TrafficDirection::ServerToClient),
+                    _ => None,
+                };
+            } else if let Some(server) = term.strip_prefix("server:") {
+                filter.server = Some(server.to_string()).filter(|s| !s.is_empty());
+            } else {
+                method.push(term);
+            }
+        }
+        filter.method = method.join(" ");
+        filter
+    }
+
+    /// Whether a message passes, given the method it belongs to
+    pub fn matches(&self, message: &ObservedMessage, method: Option<&str>) -> bool {
+        if self.direction.is_some_and(|d| d != message.direction) {
+            return false;
+        }
+        if let Some(server) = &self.server {
+            if !message.proxy_id.0.to_string().starts_with(server.as_str()) {
+                return false;
+            }
+        }
+        self.method.is_empty() || method.is_some_and(|m| m.contains(&self.method))
+    }
+}
+
+/// How one leaf of a JSON document changed
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Added {
+        pointer: String,
+        value: Value,
+    },
+    Removed {
+        pointer: String,
+        value: Value,
+    },
+    Changed {
+        pointer: String,
+        before: Value,
+        after: Value,
+    },
+}
+
+/// Leaf-level differences between two JSON documents, keyed by JSON pointer
+pub fn diff_values(before: &Value, after: &Value) -> Vec<DiffLine> {
+    let mut left = BTreeMap::new();
+    let mut right = BTreeMap::new();
+    leaves(before, String::new(), &mut left);
+    leaves(after, String::new(), &mut right);
+
+    let mut lines = Vec::new();
+    for (pointer, value) in &left {
+        match right.get(pointer) {
+            None => lines.push(DiffLine::Removed {
+                pointer: pointer.clone(),
+                value: (*value).clone(),
+            }),
+            Some(other) if other != value => lines.push(DiffLine::Changed {
+                pointer: pointer.clone(),
+                before: (*value).clone(),
+                after: (*other).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (pointer, value) in right {
+        if !left.contains_key(&pointer) {
+            lines.push(DiffLine::Added {
+                pointer,
+                value: value.clone(),
+            });
+        }
+    }
+    lines
+}
+
+fn leaves<'a>(value: &'a Value, pointer: String, out: &mut BTreeMap<String, &'a Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                leaves(child, format!("{}/{}", pointer, key), out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, child) in items.iter().enumerate() {
+                leaves(child, format!("{}/{}", pointer, i), out);
+            }
+        }
+        _ => {
+            out.insert(pointer, value);
+        }
+    }
+}
+
+/// One visible row of the JSON tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRow {
+    pub pointer: String,
+    pub depth: usize,
+    pub text: String,
+    /// Some(folded) for objects and arrays
+    pub fold: Option<bool>,
+}
+
+/// Rows of a JSON document with the given containers folded
+pub fn json_rows(value: &Value, folded: &HashSet<String>) -> Vec<JsonRow> {
+    let mut rows = Vec::new();
+    push_children(value, "", 0, folded, &mut rows);
+    rows
+}
+
+fn push_children(
+    value: &Value,
+    pointer: &str,
+    depth: usize,
+    folded: &HashSet<String>,
+    rows: &mut Vec<JsonRow>,
+) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        _ => return,
+    };
+    for (key, child) in children {
+        let pointer = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+        let (text, fold) = match child {
+            Value::Object(map) => (format!("{} {{{}}}", key, map.len()), true),
+            Value::Array(items) => (format!("{} [{}]", key, items.len()), true),
+            scalar => (format!("{}: {}", key, scalar), false),
+        };
+        let is_folded = folded.contains(&pointer);
+        rows.push(JsonRow {
+            pointer: pointer.clone(),
+            depth,
+            text,
+            fold: fold.then_some(is_folded),
+        });
+        if fold && !is_folded {
+            push_children(child, &pointer, depth + 1, folded, rows);
+        }
+    }
+}
+
+/// What the inspector is showing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InspectorView {
+    List,
+    Json,
+    Diff,
+}
+
+/// Captured-traffic browser shown over the main panels
+#[derive(Debug, Clone)]
+pub struct TrafficInspector {
+    pub filter_text: String,
+    pub view: InspectorView,
+    /// Position in the filtered list
+    selected: usize,
+    /// Index into the captured messages of the message being viewed
+    opened: Option<usize>,
+    folded: HashSet<String>,
+    /// Row of the JSON tree, or first line of the diff
+    cursor: usize,
+}
+
+impl Default for TrafficInspector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrafficInspector {
+    pub fn new() -> Self {
+        Self {
+            filter_text: String::new(),
+            view: InspectorView::List,
+            selected: 0,
+            opened: None,
+            folded: HashSet::new(),
+            cursor: 0,
+        }
+    }
+
+    pub fn filter(&self) -> TrafficFilter {
+        TrafficFilter::parse(&self.filter_text)
+    }
+
+    /// Indices of the messages passing the filter, oldest first
+    pub fn visible(&self, messages: &[ObservedMessage]) -> Vec<usize> {
+        let filter = self.filter();
+        let methods = request_methods(messages);
+        messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| filter.matches(message, method_of(message, &methods)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Index of the message under the list cursor
+    pub fn selected(&self, messages: &[ObservedMessage]) -> Option<usize> {
+        let visible = self.visible(messages);
+        visible
+            .get(self.selected.min(visible.len().saturating_sub(1)))
+            .copied()
+    }
+
+    /// Apply a key, returning whether anything changed
+    pub fn handle(&mut self, event: &Event, messages: &[ObservedMessage]) -> bool {
+        match self.view {
+            InspectorView::List => match event {
+                Event::Up => self.select(self.selected.saturating_sub(1), messages),
+                Event::Down => self.select(self.selected + 1, messages),
+                Event::Input(c) => {
+                    self.filter_text.push(*c);
+                    self.selected = 0;
+                    true
+                }
+                Event::Backspace => {
+                    self.selected = 0;
+                    self.filter_text.pop().is_some()
+                }
+                Event::Enter => match self.selected(messages) {
+                    Some(index) => {
+                        self.opened = Some(index);
+                        self.folded.clear();
+                        self.cursor = 0;
+                        self.view = InspectorView::Json;
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            },
+            InspectorView::Json | InspectorView::Diff => match event {
+                Event::Quit => {
+                    self.view = InspectorView::List;
+                    self.opened = None;
+                    true
+                }
+                Event::Input('d') => {
+                    self.view = if self.view == InspectorView::Json {
+                        InspectorView::Diff
+                    } else {
+                        InspectorView::Json
+                    };
+                    self.cursor = 0;
+                    true
+                }
+                Event::Up => self.scroll_to(self.cursor.saturating_sub(1), messages),
+                Event::Down => self.scroll_to(self.cursor + 1, messages),
+                Event::PageUp => self.scroll_to(self.cursor.saturating_sub(PAGE), messages),
+                Event::PageDown => self.scroll_to(self.cursor + PAGE, messages),
+                Event::Enter if self.view == InspectorView::Json => self.toggle_fold(messages),
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether Esc should close the inspector rather than go back
+    pub fn at_list(&self) -> bool {
+        self.view == InspectorView::List
+    }
+
+    fn select(&mut self, position: usize, messages: &[ObservedMessage]) -> bool {
+        let position = position.min(self.visible(messages).len().saturating_sub(1));
+        let changed = position != self.selected;
+        self.selected = position;
+        changed
+    }
+
+    fn scroll_to(&mut self, cursor: usize, messages: &[ObservedMessage]) -> bool {
+        let len = match self.view {
+            InspectorView::Json => self.rows(messages).len(),
+            _ => self.diff_lines(messages).len(),
+        };
+        let cursor = cursor.min(len.saturating_sub(1));
+        let changed = cursor != self.cursor;
+        self.cursor = cursor;
+        changed
+    }
+
+    fn toggle_fold(&mut self, messages: &[ObservedMessage]) -> bool {
+        let Some(row) = self.rows(messages).into_iter().nth(self.cursor) else {
+            return false;
+        };
+        if row.fold.is_none() {
+            return false;
+        }
+        if !self.folded.remove(&row.pointer) {
+            self.folded.insert(row.pointer);
+        }
+        true
+    }
+
+    fn rows(&self, messages: &[ObservedMessage]) -> Vec<JsonRow> {
+        self.opened
+            .and_then(|index| messages.get(index))
+            .map(|message| json_rows(&message.payload, &self.folded))
+            .unwrap_or_default()
+    }
+
+    /// The diff view for the opened message and its counterpart
+    pub fn diff_lines(&self, messages: &[ObservedMessage]) -> Vec<Line<'static>> {
+        let Some(message) = self.opened.and_then(|index| messages.get(index)) else {
+            return Vec::new();
+        };
+        let counterpart = counterpart(messages, self.opened.unwrap_or_default());
+        let (request, response) = if message.method.is_some() {
+            (Some(message), counterpart)
+        } else {
+            (counterpart, Some(message))
+        };
+
+        let mut lines = Vec::new();
+        stage(
+            &mut lines,
+            "Request: client → interceptors → server",
+            request,
+            "no request captured",
+        );
+        stage(
+            &mut lines,
+            "Response: server → interceptors → client",
+            response,
+            "no response captured",
+        );
+        lines
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, messages: &[ObservedMessage]) {
+        let visible = self.visible(messages);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+            .split(area);
+        let filter = Paragraph::new(self.filter_text.as_str()).block(
+            Block::default()
+                .title(format!(
+                    "Traffic {}/{} · filter: method dir:in|out server:ID",
+                    visible.len(),
+                    messages.len()
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::current().accent)),
+        );
+        frame.render_widget(filter, chunks[0]);
+
+        match self.view {
+            InspectorView::List => self.render_list(frame, chunks[1], messages, &visible),
+            InspectorView::Json => {
+                let rows = self.rows(messages);
+                let lines = rows
+                    .iter()
+                    .enumerate()
+                    .map(|(i, row)| json_line(row, i == self.cursor))
+                    .collect();
+                self.render_detail(
+                    frame,
+                    chunks[1],
+                    lines,
+                    "JSON (Enter fold · d diff · Esc back)",
+                );
+            }
+            InspectorView::Diff => {
+                let lines = self.diff_lines(messages);
+                self.render_detail(frame, chunks[1], lines, "Diff (d JSON · Esc back)");
+            }
+        }
+    }
+
+    fn render_list(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        messages: &[ObservedMessage],
+        visible: &[usize],
+    ) {
+        let methods = request_methods(messages);
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|&index| {
+                let message = &messages[index];
+                let arrow = match message.direction {
+                    TrafficDirection::ClientToServer => "→",
+                    TrafficDirection::ServerToClient => "←",
+                };
+                let method = method_of(message, &methods).unwrap_or("?");
+                let id = message
+                    .id
+                    .as_ref()
+                    .map(Value::to_string)
+                    .unwrap_or_default();
+                let mut spans = vec![
+                    Span::styled(
+                        format!("[{}] ", message.timestamp.format("%H:%M:%S%.3f")),
+                        Style::default().fg(theme::current().muted),
+                    ),
+                    Span::raw(format!("{} {} ", arrow, method)),
+                    Span::styled(id, Style::default().fg(theme::current().muted)),
+                ];
+                if message.modified {
+                    spans.push(Span::styled(
+                        " [MODIFIED]",
+                        Style::default().fg(theme::current().warning),
+                    ));
+                }
+                if message.payload.get("error").is_some() {
+                    spans.push(Span::styled(
+                        " [ERROR]",
+                        Style::default().fg(theme::current().error),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select((!items.is_empty()).then(|| self.selected.min(items.len() - 1)));
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title("Messages (Enter open · Esc close)")
+                    .borders(Borders::ALL),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn render_detail(&self, frame: &mut Frame, area: Rect, lines: Vec<Line<'static>>, title: &str) {
+        let height = usize::from(area.height.saturating_sub(2));
+        // Keep the cursor on screen
+        let offset = self.cursor.saturating_sub(height.saturating_sub(1));
+        let paragraph = Paragraph::new(lines)
+            .scroll((u16::try_from(offset).unwrap_or(u16::MAX), 0))
+            .block(Block::default().title(title).borders(Borders::ALL));
+        frame.render_widget(paragraph, area);
+    }
+}
+
+/// Method of every request, keyed by proxy, request direction and id
+fn request_methods(
+    messages: &[ObservedMessage],
+) -> HashMap<(String, TrafficDirection, String), &str> {
+    messages
+        .iter()
+        .filter_map(|message| {
+            let method = message.method.as_deref()?;
+            let id = message.id.as_ref()?;
+            Some((
+                (
+                    message.proxy_id.0.to_string(),
+                    message.direction,
+                    id.to_string(),
+                ),
+                method,
+            ))
+        })
+        .collect()
+}
+
+fn method_of<'a>(
+    message: &'a ObservedMessage,
+    methods: &HashMap<(String, TrafficDirection, String), &'a str>,
+) -> Option<&'a str> {
+    if let Some(method) = message.method.as_deref() {
+        return Some(method);
+    }
+    let id = message.id.as_ref()?;
+    let request_direction = match message.direction {
+        TrafficDirection::ClientToServer => TrafficDirection::ServerToClient,
+        TrafficDirection::ServerToClient => TrafficDirection::ClientToServer,
+    };
+    methods
+        .get(&(
+            message.proxy_id.0.to_string(),
+            request_direction,
+            id.to_string(),
+        ))
+        .copied()
+}
+
+/// The response to a request or the request of a response
+fn counterpart(messages: &[ObservedMessage], index: usize) -> Option<&ObservedMessage> {
+    let message = &messages[index];
+    let id = message.id.as_ref()?;
+    let pairs = |other: &&ObservedMessage| {
+        other.proxy_id == message.proxy_id
+            && other.direction != message.direction
+            && other.id.as_ref() == Some(id)
+            && other.method.is_some() != message.method.is_some()
+    };
+    if message.method.is_some() {
+        messages[index + 1..].iter().find(pairs)
+    } else {
+        messages[..index].iter().rev().find(pairs)
+    }
+}
+
+fn stage(
+    lines: &mut Vec<Line<'static>>,
+    title: &str,
+    message: Option<&ObservedMessage>,
+    missing: &str,
+) {
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    let note = Style::default().fg(theme::current().muted);
+    if !lines.is_empty() {
+        lines.push(Line::default());
+    }
+    lines.push(Line::from(Span::styled(title.to_string(), heading)));
+    let Some(message) = message else {
+        lines.push(Line::from(Span::styled(format!("  {}", missing), note)));
+        return;
+    };
+    match &message.original {
+        Some(original) => {
+            let changes = diff_values(original, &message.payload);
+            lines.push(Line::from(Span::styled(
+                format!("  {} change(s) made by interceptors", changes.len()),
+                Style::default().fg(theme::current().warning),
+            )));
+            lines.extend(changes.iter().map(diff_line));
+        }
+        None if message.modified => lines.push(Line::from(Span::styled(
+            "  modified by interceptors; the original was not captured",
+            Style::default().fg(theme::current().warning),
+        ))),
+        None => lines.push(Line::from(Span::styled("  forwarded unchanged", note))),
+    }
+    // The payload as it finally went out
+    let mut delivered = BTreeMap::new();
+    leaves(&message.payload, String::new(), &mut delivered);
+    lines.extend(delivered.into_iter().map(|(pointer, value)| {
+        Line::from(Span::raw(format!(
+            "    {}: {}",
+            display_pointer(&pointer),
+            value
+        )))
+    }));
+}
+
+fn diff_line(line: &DiffLine) -> Line<'static> {
+    let (text, color) = match line {
+        DiffLine::Added { pointer, value } => (
+            format!("  + {}: {}", display_pointer(pointer), value),
+            theme::current().success,
+        ),
+        DiffLine::Removed { pointer, value } => (
+            format!("  - {}: {}", display_pointer(pointer), value),
+            theme::current().error,
+        ),
+        DiffLine::Changed {
+            pointer,
+            before,
+            after,
+        } => (
+            format!("  ~ {}: {} → {}", display_pointer(pointer), before, after),
+            theme::current().warning,
+        ),
+    };
+    Line::from(Span::styled(text, Style::default().fg(color)))
+}
+
+fn display_pointer(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "/"
+    } else {
+        pointer
+    }
+}
+
+fn json_line(row: &JsonRow, selected: bool) -> Line<'static> {
+    let marker = match row.fold {
+        Some(true) => "▸ ",
+        Some(false) => "▾ ",
+        None => "  ",
+    };
+    let mut style = Style::default();
+    if row.fold.is_some() {
+        style = style.fg(theme::current().accent);
+    }
+    if selected {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    Line::from(Span::styled(
+        format!("{}{}{}", "  ".repeat(row.depth), marker, row.text),
+        style,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_common::ProxyId;
+    use serde_json::json;
+
+    fn capture() -> Vec<ObservedMessage> {
+        let proxy = ProxyId::new();
+        let mut call = ObservedMessage::new(
+            proxy.clone(),
+            TrafficDirection::ClientToServer,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                   "params": {"name": "read", "arguments": {"path": "/srv/a"}}}),
+        );
+        call.modified = true;
+        call.original = Some(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": {"name": "read", "arguments": {"path": "a", "token": "x"}}}));
+        vec![
+            ObservedMessage::new(
+                proxy.clone(),
+                TrafficDirection::ClientToServer,
+                json!({"jsonrpc": "2.0", "id": 0, "method": "tools/list"}),
+            ),
+            call,
+            ObservedMessage::new(
+                proxy,
+                TrafficDirection::ServerToClient,
+                json!({"jsonrpc": "2.0", "id": 1, "result": {"content": []}}),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_filter_matches_responses_through_their_request() {
+        let messages = capture();
+        let mut inspector = TrafficInspector::new();
+        "tools/call".chars().for_each(|c| {
+            inspector.handle(&Event::Input(c), &messages);
+        });
+        assert_eq!(inspector.visible(&messages), vec![1, 2]);
+
+        inspector.filter_text = "call dir:in".to_string();
+        assert_eq!(inspector.visible(&messages), vec![2]);
+
+        inspector.filter_text = "server:nobody".to_string();
+        assert!(inspector.visible(&messages).is_empty());
+    }
+
+    #[test]
+    fn test_diff_shows_interceptor_changes() {
+        let messages = capture();
+        let original = messages[1].original.as_ref().unwrap();
+        let changes = diff_values(original, &messages[1].payload);
+        assert_eq!(
+            changes,
+            vec![
+                DiffLine::Changed {
+                    pointer: "/params/arguments/path".to_string(),
+                    before: json!("a"),
+                    after: json!("/srv/a"),
+                },
+                DiffLine::Removed {
+                    pointer: "/params/arguments/token".to_string(),
+                    value: json!("x"),
+                },
+            ]
+        );
+
+        // Opening the response pairs it with its request
+        let mut inspector = TrafficInspector::new();
+        inspector.handle(&Event::Down, &messages);
+        inspector.handle(&Event::Down, &messages);
+        inspector.handle(&Event::Enter, &messages);
+        inspector.handle(&Event::Input('d'), &messages);
+        assert_eq!(inspector.view, InspectorView::Diff);
+        let text: Vec<String> = inspector
+            .diff_lines(&messages)
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert!(text.contains(&"  2 change(s) made by interceptors".to_string()));
+        assert!(text.contains(&"  forwarded unchanged".to_string()));
+    }
+
+    #[test]
+    fn test_json_rows_fold() {
+        let messages = capture();
+        let mut inspector = TrafficInspector::new();
+        inspector.handle(&Event::Down, &messages);
+        inspector.handle(&Event::Enter, &messages);
+        let unfolded = inspector.rows(&messages).len();
+
+        // Rows are ordered by key: id, jsonrpc, method, params
+        for _ in 0..3 {
+            inspector.handle(&Event::Down, &messages);
+        }
+        assert!(inspector.handle(&Event::Enter, &messages));
+        let rows = inspector.rows(&messages);
+        assert_eq!(rows.len(), 4);
+        assert!(rows.len() < unfolded);
+        assert_eq!(rows[3].fold, Some(true));
+
+        assert!(inspector.handle(&Event::Quit, &messages));
+        assert!(inspector.at_list());
+    }
+}
@@ -1,16 +1,23 @@
 use std::collections::HashMap;
 
+use mcp_common::{MetricsWindow, MonitorConfig, ObservedMessage, PanelConfig};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
 use crate::{
     components::{
-        ActivityFeed, ActivityItem, Client, ClientsPanel, FocusArea, QueryInput, QuickAccess,
-        Server, ServersPanel,
+        ActivityFeed, ActivityItem, Client, ClientsPanel, FocusArea, PromptBrowser, PromptEntry,
+        QueryInput, QuickAccess, ResourceBrowser, ResourceEntry, Server, ServerLogLine,
+        ServerLogView, ServersPanel, SessionBrowser, StatsPanel, ToolForm, TrafficInspector,
     },
     events::Event,
+    keymap::Keymap,
+    theme,
 };
 
 const FOCUS_ORDER: [FocusArea; 5] = [
@@ -21,6 +28,22 @@ const FOCUS_ORDER: [FocusArea; 5] = [
     FocusArea::QueryInput,
 ];
 
+/// A screen drawn over the main panels
+pub enum Overlay<'a> {
+    Inspector(&'a TrafficInspector, &'a [ObservedMessage]),
+    Sessions(&'a SessionBrowser),
+    ServerLogs(&'a ServerLogView, &'a [ServerLogLine]),
+    Resources(&'a ResourceBrowser, &'a [ResourceEntry]),
+    Prompts(&'a PromptBrowser, &'a [PromptEntry]),
+    Stats(
+        &'a StatsPanel,
+        &'a MetricsWindow,
+        &'a HashMap<String, Server>,
+    ),
+    /// Every action with its keys
+    Help,
+}
+
 pub struct NavigationContext {
     pub client_len: usize,
     pub server_len: usize,
@@ -34,6 +57,10 @@ pub struct UI {
     pub activity_feed: ActivityFeed,
     pub query_input: QueryInput,
     pub quick_access: QuickAccess,
+    /// Panels shown on the main screen
+    panels: PanelConfig,
+    /// Keys listed by the help overlay
+    pub keymap: Keymap,
 }
 
 impl UI {
@@ -45,6 +72,27 @@ impl UI {
             activity_feed: ActivityFeed::new(),
             query_input: QueryInput::new(),
             quick_access: QuickAccess::new(),
+            panels: PanelConfig::default(),
+            keymap: Keymap::default(),
+        }
+    }
+
+    /// Apply the panel settings of a monitor configuration
+    pub fn configure(&mut self, config: &MonitorConfig) {
+        self.panels = config.panels.clone();
+        if !self.is_shown(self.focus) {
+            self.focus_next();
+        }
+    }
+
+    /// Whether `area` is on screen; the query input always is
+    pub fn is_shown(&self, area: FocusArea) -> bool {
+        match area {
+            FocusArea::Clients => self.panels.clients,
+            FocusArea::Servers => self.panels.servers,
+            FocusArea::Activity => self.panels.activity,
+            FocusArea::QuickAccess => self.panels.quick_access,
+            FocusArea::QueryInput => true,
         }
     }
 
@@ -56,47 +104,116 @@ impl UI {
         servers: &HashMap<String, Server>,
         activities: &[ActivityItem],
         query_input: &str,
+        tool_form: Option<&ToolForm>,
+        overlay: Option<Overlay>,
     ) {
         let area = frame.size();
 
+        // A tool form takes the query input's place and grows to fit its fields
+        let input_height = tool_form.map_or(5, |form| form.height().clamp(5, area.height / 2));
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(10), Constraint::Length(5)].as_ref())
+            .constraints([Constraint::Min(10), Constraint::Length(input_height)].as_ref())
             .split(area);
 
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
-            .split(chunks[0]);
+        // Overlays cover the panels but leave the input area alone
+        if let Some(overlay) = overlay {
+            match overlay {
+                Overlay::Inspector(inspector, messages) => {
+                    inspector.render(frame, chunks[0], messages)
+                }
+                Overlay::Sessions(browser) => browser.render(frame, chunks[0]),
+                Overlay::ServerLogs(view, logs) => view.render(frame, chunks[0], logs),
+                Overlay::Resources(browser, resources) => {
+                    browser.render(frame, chunks[0], resources)
+                }
+                Overlay::Prompts(browser, prompts) => browser.render(frame, chunks[0], prompts),
+                Overlay::Stats(panel, metrics, servers) => {
+                    panel.render(frame, chunks[0], metrics, servers)
+                }
+                Overlay::Help => self.render_help(frame, chunks[0]),
+            }
+            self.render_input(frame, chunks[1], query_input, tool_form);
+            return;
+        }
 
-        let left = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-            .split(main_chunks[0]);
+        // Hidden panels give their space to the rest of their column, and an
+        // empty column gives it to the other one
+        let columns: Vec<(Vec<(FocusArea, u32)>, u32)> = [
+            (vec![(FocusArea::Clients, 50), (FocusArea::Servers, 50)], 55),
+            (
+                vec![(FocusArea::Activity, 65), (FocusArea::QuickAccess, 35)],
+                45,
+            ),
+        ]
+        .into_iter()
+        .map(|(panels, width)| {
+            let shown = panels
+                .into_iter()
+                .filter(|(panel, _)| self.is_shown(*panel))
+                .collect::<Vec<_>>();
+            (shown, width)
+        })
+        .filter(|(panels, _)| !panels.is_empty())
+        .collect();
 
-        let right = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
-            .split(main_chunks[1]);
-
-        self.clients_panel
-            .render(frame, left[0], clients, self.focus == FocusArea::Clients);
-        self.servers_panel
-            .render(frame, left[1], servers, self.focus == FocusArea::Servers);
-        self.activity_feed.render(
-            frame,
-            right[0],
-            activities,
-            self.focus == FocusArea::Activity,
-        );
-        self.quick_access
-            .render(frame, right[1], self.focus == FocusArea::QuickAccess);
-        self.query_input.render(
-            frame,
-            chunks[1],
-            query_input,
-            self.focus == FocusArea::QueryInput,
-        );
+        let widths: Vec<u32> = columns.iter().map(|(_, width)| *width).collect();
+        let column_areas = split(Direction::Horizontal, chunks[0], &widths);
+        for ((panels, _), column) in columns.iter().zip(column_areas.iter()) {
+            let heights: Vec<u32> = panels.iter().map(|(_, height)| *height).collect();
+            let areas = split(Direction::Vertical, *column, &heights);
+            for ((panel, _), area) in panels.iter().zip(areas.iter()) {
+                let focused = self.focus == *panel;
+                match panel {
+                    FocusArea::Clients => self.clients_panel.render(frame, *area, clients, focused),
+                    FocusArea::Servers => self.servers_panel.render(frame, *area, servers, focused),
+                    FocusArea::Activity => {
+                        self.activity_feed.render(frame, *area, activities, focused)
+                    }
+                    FocusArea::QuickAccess => self.quick_access.render(frame, *area, focused),
+                    FocusArea::QueryInput => {}
+                }
+            }
+        }
+        self.render_input(frame, chunks[1], query_input, tool_form);
+    }
+
+    fn render_help(&self, frame: &mut Frame, area: Rect) {
+        let theme = theme::current();
+        let lines: Vec<Line> = self
+            .keymap
+            .help()
+            .into_iter()
+            .map(|(action, keys)| {
+                let keys: Vec<String> = keys.iter().map(ToString::to_string).collect();
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<28}", keys.join(", ")),
+                        Style::default().fg(theme.accent),
+                    ),
+                    Span::styled(action, Style::default().fg(theme.text)),
+                ])
+            })
+            .collect();
+        let block = Block::default()
+            .title("Keys (Esc to close)")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent));
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn render_input(
+        &mut self,
+        frame: &mut Frame,
+        area: ratatui::layout::Rect,
+        query_input: &str,
+        tool_form: Option<&ToolForm>,
+    ) {
+        let focused = self.focus == FocusArea::QueryInput;
+        match tool_form {
+            Some(form) => form.render(frame, area, focused),
+            None => self.query_input.render(frame, area, query_input, focused),
+        }
     }
 
     pub fn get_focus(&self) -> FocusArea {
@@ -108,19 +225,23 @@ impl UI {
     }
 
     pub fn focus_next(&mut self) {
-        let idx = focus_index(self.focus);
-        let next = FOCUS_ORDER[(idx + 1) % FOCUS_ORDER.len()];
-        self.set_focus(next);
+        self.step_focus(1);
     }
 
     pub fn focus_prev(&mut self) {
-        let idx = focus_index(self.focus);
-        let prev = if idx == 0 {
-            FOCUS_ORDER[FOCUS_ORDER.len() - 1]
-        } else {
-            FOCUS_ORDER[idx - 1]
-        };
-        self.set_focus(prev);
+        self.step_focus(FOCUS_ORDER.len() - 1);
+    }
+
+    /// Move `step` places along the focus order, skipping hidden panels
+    fn step_focus(&mut self, step: usize) {
+        let mut idx = focus_index(self.focus);
+        loop {
+            idx = (idx + step) % FOCUS_ORDER.len();
+            if self.is_shown(FOCUS_ORDER[idx]) {
+                break;
+            }
+        }
+        self.set_focus(FOCUS_ORDER[idx]);
     }
 
     pub fn handle_navigation(&mut self, ctx: NavigationContext, event: Event) -> bool {
@@ -145,6 +266,28 @@ impl UI {
                 }
                 true
             }
+            Event::TogglePause => {
+                self.activity_feed.toggle_pause(ctx.activity_len);
+                true
+            }
+            Event::Input(character) if self.focus == FocusArea::Activity => {
+                self.activity_feed.input(character);
+                true
+            }
+            Event::Backspace if self.focus == FocusArea::Activity => self.activity_feed.backspace(),
+            Event::Enter if self.focus == FocusArea::Activity => {
+                self.activity_feed.toggle_expanded();
+                true
+            }
+            Event::Quit if self.focus == FocusArea::Activity => self.activity_feed.escape(),
+            Event::PageUp | Event::PageDown if self.focus == FocusArea::Activity => {
+                if event == Event::PageDown {
+                    self.activity_feed.page_down();
+                } else {
+                    self.activity_feed.page_up();
+                }
+                true
+            }
             Event::Left => {
                 let next_focus = match self.focus {
                     FocusArea::Servers => Some(FocusArea::Clients),
@@ -153,11 +296,12 @@ impl UI {
                     FocusArea::QueryInput => Some(FocusArea::QuickAccess),
                     FocusArea::Clients => None,
                 };
-                if let Some(focus) = next_focus {
-                    self.set_focus(focus);
-                    return true;
+                match next_focus {
+                    Some(focus) if self.is_shown(focus) => self.set_focus(focus),
+                    Some(_) => self.focus_prev(),
+                    None => return false,
                 }
-                false
+                true
             }
             Event::Right => {
                 let next_focus = match self.focus {
@@ -167,11 +311,12 @@ impl UI {
                     FocusArea::QuickAccess => Some(FocusArea::QueryInput),
                     FocusArea::QueryInput => None,
                 };
-                if let Some(focus) = next_focus {
-                    self.set_focus(focus);
-                    return true;
+                match next_focus {
+                    Some(focus) if self.is_shown(focus) => self.set_focus(focus),
+                    Some(_) => self.focus_next(),
+                    None => return false,
                 }
-                false
+                true
             }
             _ => false,
         }
@@ -187,6 +332,19 @@ impl UI {
     }
 }
 
+/// Split `area` in proportion to `weights`
+fn split(direction: Direction, area: Rect, weights: &[u32]) -> std::rc::Rc<[Rect]> {
+    let total = weights.iter().sum::<u32>().max(1);
+    let constraints: Vec<Constraint> = weights
+        .iter()
+        .map(|weight| Constraint::Ratio(*weight, total))
+        .collect();
+    Layout::default()
+        .direction(direction)
+        .constraints(constraints.as_slice())
+        .split(area)
+}
+
 fn focus_index(focus: FocusArea) -> usize {
     FOCUS_ORDER
         .iter()
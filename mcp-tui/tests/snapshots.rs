@@ -0,0 +1,298 @@
+//! Snapshot tests for TUI rendering.
+//!
+//! Each test renders a screen from fixture state into a ratatui `TestBackend`
+//! and compares the text grid against the stored snapshot in `snapshots/`.
+//! After an intentional layout change, review and accept the new output with
+//! `cargo insta review` (or rerun with `INSTA_UPDATE=always`).
+
+use std::collections::HashMap;
+
+use chrono::{TimeZone, Utc};
+use mcp_common::{MetricsWindow, ObservedMessage, ProxyId, SessionArchive, TrafficDirection};
+use mcp_tui::components::{
+    ActivityFeed, ActivityItem, ActivityStatus, Client, ClientStatus, ClientsPanel, QueryInput,
+    QuickAccess, SavedSession, Server, ServerStatus, ServersPanel, SessionBrowser, StatsPanel,
+    TrafficInspector,
+};
+use mcp_tui::ui::UI;
+use ratatui::{backend::TestBackend, Frame, Terminal};
+use serde_json::json;
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+
+fn render(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> TestBackend {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(draw).unwrap();
+    terminal.backend().clone()
+}
+
+fn clients() -> HashMap<String, Client> {
+    [
+        Client::new(
+            "claude",
+            "Claude Desktop",
+            "stdio via proxy",
+            ClientStatus::Connected,
+        ),
+        Client::new("cursor", "Cursor", "http-sse", ClientStatus::Disconnected),
+        Client::new("probe", "mcp-probe", "conformance run", ClientStatus::Error),
+    ]
+    .into_iter()
+    .map(|client| (client.id.clone(), client))
+    .collect()
+}
+
+fn servers() -> HashMap<String, Server> {
+    [
+        Server::new(
+            "everything",
+            "everything",
+            "Reference server with every feature",
+            ServerStatus::Running,
+        ),
+        Server::new(
+            "files",
+            "filesystem",
+            "Read-only /srv",
+            ServerStatus::Degraded,
+        ),
+        Server::new(
+            "github",
+            "github",
+            "Waiting for token",
+            ServerStatus::Starting,
+        ),
+    ]
+    .into_iter()
+    .map(|server| (server.id.clone(), server))
+    .collect()
+}
+
+fn activities() -> Vec<ActivityItem> {
+    let at = |secs| Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, secs).unwrap();
+    vec![
+        ActivityItem {
+            timestamp: at(1),
+            client: "Claude Desktop".to_string(),
+            server: "everything".to_string(),
+            action: "initialize".to_string(),
+            status: ActivityStatus::Success,
+            detail: None,
+            direction: None,
+        },
+        ActivityItem {
+            timestamp: at(2),
+            client: "Claude Desktop".to_string(),
+            server: "everything".to_string(),
+            action: "tools/list".to_string(),
+            status: ActivityStatus::Success,
+            detail: None,
+            direction: None,
+        },
+        ActivityItem {
+            timestamp: at(5),
+            client: "Cursor".to_string(),
+            server: "filesystem".to_string(),
+            action: "resources/read file:///srv/missing".to_string(),
+            status: ActivityStatus::Failed,
+            detail: None,
+            direction: None,
+        },
+        ActivityItem {
+            timestamp: at(7),
+            client: "Claude Desktop".to_string(),
+            server: "github".to_string(),
+            action: "tools/call search_issues".to_string(),
+            status: ActivityStatus::Processing,
+            detail: None,
+            direction: None,
+        },
+    ]
+}
+
+#[test]
+fn full_layout() {
+    let mut ui = UI::new();
+    let (clients, servers, activities) = (clients(), servers(), activities());
+    let backend = render(WIDTH, HEIGHT, |frame| {
+        ui.draw(frame, &clients, &servers, &activities, "", None, None)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn full_layout_empty() {
+    let mut ui = UI::new();
+    let backend = render(WIDTH, HEIGHT, |frame| {
+        ui.draw(frame, &HashMap::new(), &HashMap::new(), &[], "", None, None)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn full_layout_narrow() {
+    let mut ui = UI::new();
+    let (clients, servers, activities) = (clients(), servers(), activities());
+    let backend = render(60, 24, |frame| {
+        ui.draw(frame, &clients, &servers, &activities, "", None, None)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn full_layout_query_focused() {
+    let mut ui = UI::new();
+    ui.focus_prev();
+    let (clients, servers, activities) = (clients(), servers(), activities());
+    let backend = render(WIDTH, HEIGHT, |frame| {
+        ui.draw(
+            frame,
+            &clients,
+            &servers,
+            &activities,
+            "list tools on everything",
+            None,
+            None,
+        )
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn activity_feed() {
+    let mut feed = ActivityFeed::new();
+    let activities = activities();
+    let backend = render(70, 8, |frame| {
+        feed.render(frame, frame.size(), &activities, true)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn clients_panel() {
+    let mut panel = ClientsPanel::new();
+    let clients = clients();
+    let backend = render(40, 8, |frame| {
+        panel.render(frame, frame.size(), &clients, false)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn servers_panel() {
+    let mut panel = ServersPanel::new();
+    let servers = servers();
+    let backend = render(50, 8, |frame| {
+        panel.render(frame, frame.size(), &servers, true)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn quick_access() {
+    let mut quick_access = QuickAccess::new();
+    let backend = render(60, 12, |frame| {
+        quick_access.render(frame, frame.size(), true)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn query_input_placeholder() {
+    let input = QueryInput::new();
+    let backend = render(50, 3, |frame| input.render(frame, frame.size(), "", false));
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn traffic_inspector() {
+    let proxy = ProxyId::new();
+    let at = |second, direction, payload| {
+        let mut message = ObservedMessage::new(proxy.clone(), direction, payload);
+        message.timestamp = Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, second).unwrap();
+        message
+    };
+    let mut call = at(
+        1,
+        TrafficDirection::ClientToServer,
+        json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": {"name": "read"}}),
+    );
+    call.modified = true;
+    let messages = vec![
+        at(
+            0,
+            TrafficDirection::ClientToServer,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        ),
+        call,
+        at(
+            2,
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": 2, "error": {"code": -32602, "message": "bad"}}),
+        ),
+    ];
+    let inspector = TrafficInspector::new();
+    let backend = render(70, 9, |frame| {
+        inspector.render(frame, frame.size(), &messages)
+    });
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn session_browser() {
+    let proxy = ProxyId::new();
+    let at = |second, direction, payload| {
+        let mut message = ObservedMessage::new(proxy.clone(), direction, payload);
+        message.timestamp = Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, second).unwrap();
+        message
+    };
+    let messages = vec![
+        at(
+            0,
+            TrafficDirection::ClientToServer,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "weather"}}),
+        ),
+        at(
+            1,
+            TrafficDirection::ServerToClient,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"content": []}}),
+        ),
+        at(
+            2,
+            TrafficDirection::ClientToServer,
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        ),
+    ];
+    let mut browser = SessionBrowser::new(vec![SavedSession {
+        path: "sessions/mcp-session-20250115-093000.json".into(),
+        modified: Utc.with_ymd_and_hms(2025, 1, 15, 9, 31, 0).unwrap(),
+        archive: SessionArchive::new("test", "1").with_observed(&messages),
+    }]);
+    browser.handle(&mcp_tui::Event::Right);
+    let backend = render(90, 16, |frame| browser.render(frame, frame.size()));
+    insta::assert_snapshot!(backend);
+}
+
+#[test]
+fn stats_panel() {
+    let mut metrics = MetricsWindow::new(std::time::Duration::from_secs(5), 12);
+    for second in 0..60 {
+        let at = Utc.with_ymd_and_hms(2025, 1, 15, 9, 30, second).unwrap();
+        // Filesystem slows down and starts failing halfway through
+        let slow = second >= 30;
+        metrics.record("everything", at, 20.0 + f64::from(second % 5), false);
+        metrics.record(
+            "files",
+            at,
+            if slow { 400.0 } else { 40.0 },
+            slow && second % 3 == 0,
+        );
+    }
+    let panel = StatsPanel::new();
+    let servers = servers();
+    let backend = render(WIDTH, 13, |frame| {
+        panel.render(frame, frame.size(), &metrics, &servers)
+    });
+    insta::assert_snapshot!(backend);
+}
@@ -116,6 +116,7 @@ async fn test_end_to_end_proxy_monitor_communication() {
                 successful_requests: (iteration + 1) as u64,
                 failed_requests: 0,
                 active_connections: 1,
+                standby_connections: 0,
                 uptime: Duration::from_secs((iteration + 1) * 10),
                 bytes_transferred: (iteration + 1) * 256,
             };
@@ -321,6 +322,7 @@ async fn test_error_handling_end_to_end() {
         successful_requests: 0,
         failed_requests: 2,
         active_connections: 1,
+        standby_connections: 0,
         uptime: Duration::from_secs(300),
         bytes_transferred: 1024,
     };
@@ -474,6 +476,7 @@ async fn test_high_throughput_end_to_end() {
                 successful_requests: (i + 1) as u64,
                 failed_requests: 0,
                 active_connections: 1,
+                standby_connections: 0,
                 uptime: Duration::from_secs((i + 1) / 10),
                 bytes_transferred: (i + 1) * 128,
             };